@@ -0,0 +1,6 @@
+-- Per-client token endpoint authentication.
+-- Existing clients keep behaving as public clients ('none') until an admin
+-- opts them into secret or private_key_jwt authentication. A stored secret
+-- does not mark a client as confidential: every client was given one.
+ALTER TABLE oidc_clients ADD COLUMN token_endpoint_auth_method TEXT NOT NULL DEFAULT 'none';
+ALTER TABLE oidc_clients ADD COLUMN jwks TEXT;
@@ -0,0 +1,21 @@
+-- Per-realm token signing keys.
+-- Exactly one key per realm is 'active' (signs new tokens); 'passive' keys are
+-- still published in the JWKS and verify tokens; 'retired' keys do neither.
+-- Private keys are stored encrypted with the instance secret key.
+CREATE TABLE signing_keys (
+    id TEXT PRIMARY KEY,
+    realm_id TEXT NOT NULL,
+    kid TEXT NOT NULL UNIQUE,
+    algorithm TEXT NOT NULL,
+    state TEXT NOT NULL,
+    private_key_pem TEXT NOT NULL,
+    public_key_pem TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    activated_at DATETIME,
+    deactivated_at DATETIME,
+    retired_at DATETIME,
+    FOREIGN KEY (realm_id) REFERENCES realms(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_signing_keys_realm_id ON signing_keys (realm_id);
+CREATE UNIQUE INDEX signing_keys_one_active_per_realm
+    ON signing_keys (realm_id) WHERE state = 'active';
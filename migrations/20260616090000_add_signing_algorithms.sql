@@ -0,0 +1,8 @@
+-- Token signing algorithm per realm, with an optional per-client override.
+-- A realm keeps one active signing key per algorithm in use.
+ALTER TABLE realms ADD COLUMN signing_algorithm TEXT NOT NULL DEFAULT 'RS256';
+ALTER TABLE oidc_clients ADD COLUMN signing_algorithm TEXT;
+
+DROP INDEX signing_keys_one_active_per_realm;
+CREATE UNIQUE INDEX signing_keys_one_active_per_algorithm
+    ON signing_keys (realm_id, algorithm) WHERE state = 'active';
@@ -0,0 +1,3 @@
+-- OIDC front-channel and back-channel logout endpoints registered per client.
+ALTER TABLE oidc_clients ADD COLUMN backchannel_logout_uri TEXT;
+ALTER TABLE oidc_clients ADD COLUMN frontchannel_logout_uri TEXT;
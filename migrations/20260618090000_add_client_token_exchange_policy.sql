@@ -0,0 +1,2 @@
+-- RFC 8693 token exchange: JSON array of {audience, scopes} rules per client.
+ALTER TABLE oidc_clients ADD COLUMN token_exchange_policy TEXT;
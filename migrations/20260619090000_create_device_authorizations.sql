@@ -0,0 +1,18 @@
+-- RFC 8628 device authorizations. A row is pending until the user approves or
+-- denies its user_code, and is deleted once the device redeems it or it expires.
+CREATE TABLE device_authorizations (
+    device_code TEXT PRIMARY KEY,
+    user_code TEXT NOT NULL UNIQUE,
+    realm_id TEXT NOT NULL,
+    client_id TEXT NOT NULL,
+    scope TEXT,
+    status TEXT NOT NULL DEFAULT 'pending',
+    user_id TEXT,
+    interval_secs INTEGER NOT NULL,
+    last_polled_at DATETIME,
+    expires_at DATETIME NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (realm_id) REFERENCES realms(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_device_authorizations_expires_at ON device_authorizations (expires_at);
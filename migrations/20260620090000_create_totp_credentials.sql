@@ -0,0 +1,17 @@
+-- Authenticator-app (RFC 6238) credentials. One per user; the shared secret is
+-- stored encrypted and last_used_step blocks replay of an accepted code.
+CREATE TABLE totp_credentials (
+    id TEXT PRIMARY KEY NOT NULL,
+    realm_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    secret_encrypted TEXT NOT NULL,
+    digits INTEGER NOT NULL DEFAULT 6,
+    period_secs INTEGER NOT NULL DEFAULT 30,
+    last_used_step INTEGER,
+    last_used_at DATETIME,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (realm_id) REFERENCES realms(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    UNIQUE (realm_id, user_id)
+);
@@ -0,0 +1,2 @@
+-- Resource Owner Password grant: runs the realm's direct grant flow. Off by default.
+ALTER TABLE oidc_clients ADD COLUMN direct_grant_enabled BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,30 @@
+-- Per-realm password rules. Realms without a row use PasswordPolicy::defaults
+-- (8-100 characters, no other checks).
+CREATE TABLE realm_password_policies (
+    realm_id TEXT PRIMARY KEY NOT NULL,
+    min_length INTEGER NOT NULL DEFAULT 8,
+    max_length INTEGER NOT NULL DEFAULT 100,
+    require_lowercase BOOLEAN NOT NULL DEFAULT 0,
+    require_uppercase BOOLEAN NOT NULL DEFAULT 0,
+    require_digit BOOLEAN NOT NULL DEFAULT 0,
+    require_symbol BOOLEAN NOT NULL DEFAULT 0,
+    disallow_username BOOLEAN NOT NULL DEFAULT 0,
+    history_count INTEGER NOT NULL DEFAULT 0,
+    max_age_days INTEGER NOT NULL DEFAULT 0,
+    check_breached BOOLEAN NOT NULL DEFAULT 0,
+    FOREIGN KEY (realm_id) REFERENCES realms(id) ON DELETE CASCADE
+);
+
+-- Every password set is recorded here. The newest row dates the current
+-- password (maximum age); older rows back the reuse check.
+CREATE TABLE password_history (
+    id TEXT PRIMARY KEY NOT NULL,
+    realm_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    hashed_password TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (realm_id) REFERENCES realms(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_password_history_user_created
+    ON password_history(user_id, created_at);
@@ -0,0 +1,15 @@
+-- RFC 9126 pushed authorization requests. A row holds the validated
+-- authorization parameters until /authorize redeems its request_uri once.
+CREATE TABLE pushed_authorization_requests (
+    request_uri TEXT PRIMARY KEY,
+    realm_id TEXT NOT NULL,
+    client_id TEXT NOT NULL,
+    parameters TEXT NOT NULL,
+    expires_at DATETIME NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (realm_id) REFERENCES realms(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_pushed_authorization_requests_expires_at ON pushed_authorization_requests (expires_at);
+
+-- Clients with this flag must start every authorization through the PAR endpoint.
+ALTER TABLE oidc_clients ADD COLUMN require_pushed_authorization_requests BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,26 @@
+-- Per-client protocol mappers: each copies one value (a public metadata path,
+-- the user's roles or group paths) into a named claim of the selected tokens.
+CREATE TABLE client_protocol_mappers (
+    id TEXT PRIMARY KEY,
+    realm_id TEXT NOT NULL,
+    client_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    mapper_type TEXT NOT NULL,
+    config TEXT NOT NULL DEFAULT '{}',
+    claim_name TEXT NOT NULL,
+    add_to_access_token BOOLEAN NOT NULL DEFAULT 0,
+    add_to_id_token BOOLEAN NOT NULL DEFAULT 0,
+    add_to_userinfo BOOLEAN NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (client_id, name),
+    FOREIGN KEY (realm_id) REFERENCES realms(id) ON DELETE CASCADE,
+    FOREIGN KEY (client_id) REFERENCES oidc_clients(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_client_protocol_mappers_client_id ON client_protocol_mappers (client_id);
+
+-- The scope and OIDC `claims` request granted at authorization time, so token
+-- refreshes and userinfo release the same claims.
+ALTER TABLE authorization_codes ADD COLUMN scope TEXT;
+ALTER TABLE authorization_codes ADD COLUMN claims TEXT;
+ALTER TABLE refresh_tokens ADD COLUMN scope TEXT;
+ALTER TABLE refresh_tokens ADD COLUMN claims TEXT;
@@ -0,0 +1,38 @@
+-- Per-realm rules for dynamic client registration (RFC 7591). Realms without
+-- a row use ClientRegistrationPolicy::defaults.
+CREATE TABLE realm_client_registration_policies (
+    realm_id TEXT PRIMARY KEY NOT NULL,
+    allowed_redirect_hosts TEXT NOT NULL DEFAULT '[]',
+    require_https BOOLEAN NOT NULL DEFAULT 1,
+    allowed_grant_types TEXT NOT NULL DEFAULT '["authorization_code","refresh_token"]',
+    FOREIGN KEY (realm_id) REFERENCES realms(id) ON DELETE CASCADE
+);
+
+-- Bearer tokens an admin issues to let automation register clients.
+CREATE TABLE client_initial_access_tokens (
+    id TEXT PRIMARY KEY NOT NULL,
+    realm_id TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    description TEXT,
+    expires_at DATETIME NOT NULL,
+    remaining_uses INTEGER NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (realm_id) REFERENCES realms(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_client_initial_access_tokens_realm
+    ON client_initial_access_tokens(realm_id);
+
+-- Clients created through dynamic registration, with the hash of their
+-- registration access token (RFC 7592).
+CREATE TABLE client_registrations (
+    client_id TEXT PRIMARY KEY NOT NULL,
+    realm_id TEXT NOT NULL,
+    registration_token_hash TEXT NOT NULL,
+    client_name TEXT,
+    grant_types TEXT NOT NULL DEFAULT '[]',
+    initial_access_token_id TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (client_id) REFERENCES oidc_clients(id) ON DELETE CASCADE,
+    FOREIGN KEY (realm_id) REFERENCES realms(id) ON DELETE CASCADE,
+    FOREIGN KEY (initial_access_token_id) REFERENCES client_initial_access_tokens(id) ON DELETE SET NULL
+);
@@ -0,0 +1,6 @@
+-- Admin impersonation. A session minted for a user on behalf of an admin
+-- names that admin; audit events recorded during it name both.
+ALTER TABLE refresh_tokens ADD COLUMN impersonator_id TEXT NULL;
+ALTER TABLE audit_events ADD COLUMN impersonator_user_id TEXT NULL;
+CREATE INDEX idx_audit_events_impersonator
+    ON audit_events (impersonator_user_id);
@@ -0,0 +1,19 @@
+-- The externalId a SCIM client assigned to a user or group. Rows go away
+-- with the resource, however it is deleted.
+CREATE TABLE scim_user_external_ids (
+    resource_id TEXT PRIMARY KEY NOT NULL,
+    realm_id TEXT NOT NULL,
+    external_id TEXT NOT NULL,
+    FOREIGN KEY (resource_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (realm_id) REFERENCES realms(id) ON DELETE CASCADE,
+    UNIQUE (realm_id, external_id)
+);
+
+CREATE TABLE scim_group_external_ids (
+    resource_id TEXT PRIMARY KEY NOT NULL,
+    realm_id TEXT NOT NULL,
+    external_id TEXT NOT NULL,
+    FOREIGN KEY (resource_id) REFERENCES groups(id) ON DELETE CASCADE,
+    FOREIGN KEY (realm_id) REFERENCES realms(id) ON DELETE CASCADE,
+    UNIQUE (realm_id, external_id)
+);
@@ -0,0 +1,16 @@
+-- Scopes a user approved for a client. One row per user and client; the
+-- consent node skips its prompt while the stored scopes cover a request.
+CREATE TABLE consent_grants (
+    id TEXT PRIMARY KEY NOT NULL,
+    realm_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    client_id TEXT NOT NULL,
+    scopes TEXT NOT NULL DEFAULT '[]',
+    granted_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (realm_id) REFERENCES realms(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (realm_id, client_id) REFERENCES oidc_clients(realm_id, client_id)
+        ON DELETE CASCADE ON UPDATE CASCADE,
+    UNIQUE (realm_id, user_id, client_id)
+);
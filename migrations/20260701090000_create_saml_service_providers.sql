@@ -0,0 +1,19 @@
+-- Service providers registered with a realm's SAML identity provider.
+CREATE TABLE saml_service_providers (
+    id TEXT PRIMARY KEY NOT NULL,
+    realm_id TEXT NOT NULL,
+    entity_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    acs_urls TEXT NOT NULL DEFAULT '[]',
+    slo_url TEXT,
+    slo_binding TEXT NOT NULL DEFAULT 'redirect',
+    name_id_format TEXT NOT NULL DEFAULT 'unspecified',
+    attribute_mappings TEXT NOT NULL DEFAULT '[]',
+    sign_response BOOLEAN NOT NULL DEFAULT FALSE,
+    allow_idp_initiated BOOLEAN NOT NULL DEFAULT FALSE,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (realm_id) REFERENCES realms(id) ON DELETE CASCADE,
+    UNIQUE (realm_id, entity_id)
+);
@@ -0,0 +1,25 @@
+-- SQLite cannot alter a CHECK constraint, and rebuilding identity_providers
+-- inside the migration transaction (where foreign keys stay on) would drag the
+-- federated identity and broker state references along. Widening the CHECK is
+-- the schema edit SQLite documents as safe through writable_schema; the
+-- ALTER TABLEs that follow bump the schema cookie so every connection reloads.
+PRAGMA writable_schema = ON;
+
+UPDATE sqlite_master
+SET sql = replace(
+    sql,
+    'CHECK (protocol IN (''oidc'', ''oauth2''))',
+    'CHECK (protocol IN (''oidc'', ''oauth2'', ''saml''))'
+)
+WHERE type = 'table' AND name = 'identity_providers';
+
+PRAGMA writable_schema = OFF;
+
+ALTER TABLE identity_providers
+    ADD COLUMN saml_metadata_url TEXT;
+
+ALTER TABLE identity_providers
+    ADD COLUMN saml_signing_certificates_json TEXT NOT NULL DEFAULT '[]';
+
+ALTER TABLE identity_providers
+    ADD COLUMN saml_name_id_format TEXT;
@@ -0,0 +1,59 @@
+-- LDAP / Active Directory user storage providers. Imported users keep a
+-- link to their directory entry; passwords are checked by binding as them.
+CREATE TABLE user_federation_providers (
+    id TEXT PRIMARY KEY NOT NULL,
+    realm_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    vendor TEXT NOT NULL CHECK (vendor IN ('ldap', 'active_directory')),
+    connection_url TEXT NOT NULL,
+    bind_dn TEXT,
+    bind_credential TEXT,
+    users_dn TEXT NOT NULL,
+    user_object_classes TEXT NOT NULL DEFAULT '[]',
+    custom_user_filter TEXT,
+    search_scope TEXT NOT NULL DEFAULT 'subtree'
+        CHECK (search_scope IN ('base', 'one_level', 'subtree')),
+    username_attribute TEXT NOT NULL,
+    uuid_attribute TEXT NOT NULL,
+    membership_attribute TEXT NOT NULL,
+    edit_mode TEXT NOT NULL DEFAULT 'read_only'
+        CHECK (edit_mode IN ('read_only', 'writable')),
+    sync_interval_secs INTEGER NOT NULL DEFAULT 0,
+    priority INTEGER NOT NULL DEFAULT 0,
+    last_sync_at DATETIME,
+    last_sync_error TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (realm_id) REFERENCES realms(id) ON DELETE CASCADE,
+    UNIQUE (realm_id, name)
+);
+
+CREATE TABLE user_federation_mappers (
+    id TEXT PRIMARY KEY NOT NULL,
+    provider_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    mapper_type TEXT NOT NULL CHECK (mapper_type IN ('attribute', 'group_role')),
+    ldap_attribute TEXT,
+    user_attribute TEXT,
+    group_dn TEXT,
+    role_id TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (provider_id) REFERENCES user_federation_providers(id) ON DELETE CASCADE,
+    FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE,
+    UNIQUE (provider_id, name)
+);
+
+CREATE TABLE user_federation_links (
+    user_id TEXT PRIMARY KEY NOT NULL,
+    provider_id TEXT NOT NULL,
+    external_id TEXT NOT NULL,
+    dn TEXT NOT NULL,
+    last_synced_at DATETIME,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (provider_id) REFERENCES user_federation_providers(id) ON DELETE CASCADE,
+    UNIQUE (provider_id, external_id)
+);
+
+CREATE INDEX idx_user_federation_mappers_provider ON user_federation_mappers(provider_id);
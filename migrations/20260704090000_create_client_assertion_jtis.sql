@@ -0,0 +1,9 @@
+-- `jti` values of accepted private_key_jwt client assertions, kept until the
+-- assertion expires so a captured assertion cannot be replayed.
+CREATE TABLE client_assertion_jtis (
+    client_id TEXT NOT NULL,
+    jti TEXT NOT NULL,
+    expires_at DATETIME NOT NULL,
+    PRIMARY KEY (client_id, jti),
+    FOREIGN KEY (client_id) REFERENCES oidc_clients(id) ON DELETE CASCADE
+);
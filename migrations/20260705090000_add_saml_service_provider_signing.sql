@@ -0,0 +1,7 @@
+-- Certificates a service provider signs its AuthnRequests and LogoutRequests
+-- with, and whether unsigned AuthnRequests are refused.
+ALTER TABLE saml_service_providers
+    ADD COLUMN signing_certificates TEXT NOT NULL DEFAULT '[]';
+
+ALTER TABLE saml_service_providers
+    ADD COLUMN require_signed_requests BOOLEAN NOT NULL DEFAULT FALSE;
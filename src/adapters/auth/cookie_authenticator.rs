@@ -135,6 +135,8 @@ mod tests {
             async fn save(&self, token: &RefreshToken) -> Result<()>;
             async fn find_by_id(&self, id: &Uuid) -> Result<Option<RefreshToken>>;
             async fn find_by_id_any(&self, id: &Uuid) -> Result<Option<RefreshToken>>;
+            async fn find_active_in_family(&self, family_id: &Uuid) -> Result<Option<RefreshToken>>;
+            async fn list_active_for_user(&self, realm_id: &Uuid, user_id: &Uuid) -> Result<Vec<RefreshToken>>;
             async fn delete_by_id(&self, id: &Uuid) -> Result<()>;
             async fn mark_replaced(&self, old_id: &Uuid, new_id: &Uuid) -> Result<()>;
             async fn revoke_family(&self, family_id: &Uuid) -> Result<()>;
@@ -201,6 +203,9 @@ mod tests {
             revoked_at: None,
             replaced_by: None,
             step_up_at: None,
+            scope: None,
+            claims: None,
+            impersonator_id: None,
         };
 
         let mut repo = MockSessionRepo::new();
@@ -238,6 +243,9 @@ mod tests {
             revoked_at: None,
             replaced_by: None,
             step_up_at: None,
+            scope: None,
+            claims: None,
+            impersonator_id: None,
         };
 
         let mut repo = MockSessionRepo::new();
@@ -13,8 +13,12 @@ pub mod password_authenticator;
 pub mod recovery_issue_node;
 pub mod registration_authenticator;
 pub mod reset_password_authenticator;
+pub mod sms_otp_issue_node;
 pub mod subflow_node;
+pub mod totp_enroll_authenticator;
+pub mod totp_verify_authenticator;
 pub mod verify_email_otp_authenticator;
+pub mod verify_sms_otp_authenticator;
 
 use crate::adapters::auth::collect_idp_choice_authenticator::CollectIdpChoiceAuthenticator;
 use crate::adapters::auth::cookie_authenticator::CookieAuthenticator;
@@ -31,16 +35,27 @@ use crate::adapters::auth::password_authenticator::PasswordAuthenticator;
 use crate::adapters::auth::recovery_issue_node::RecoveryIssueNode;
 use crate::adapters::auth::registration_authenticator::RegistrationAuthenticator;
 use crate::adapters::auth::reset_password_authenticator::ResetPasswordAuthenticator;
+use crate::adapters::auth::sms_otp_issue_node::SmsOtpIssueNode;
 use crate::adapters::auth::subflow_node::SubflowNode;
+use crate::adapters::auth::totp_enroll_authenticator::TotpEnrollAuthenticator;
+use crate::adapters::auth::totp_verify_authenticator::TotpVerifyAuthenticator;
 use crate::adapters::auth::verify_email_otp_authenticator::VerifyEmailOtpAuthenticator;
+use crate::adapters::auth::verify_sms_otp_authenticator::VerifySmsOtpAuthenticator;
 use crate::application::audit_service::AuditService;
+use crate::application::consent_service::ConsentService;
 use crate::application::idp_service::IdentityProviderService;
+use crate::application::logout_service::LogoutService;
 use crate::application::oauth_broker_service::OAuthBrokerService;
+use crate::application::password_policy_service::PasswordPolicyService;
 use crate::application::rbac_service::RbacService;
 use crate::application::runtime_registry::RuntimeRegistry;
+use crate::application::sms_otp_service::SmsOtpService;
+use crate::application::totp_service::TotpService;
+use crate::application::user_federation_service::UserFederationService;
 use crate::application::user_service::UserService;
 use crate::domain::execution::StepType;
 use crate::ports::auth_session_action_repository::AuthSessionActionRepository;
+use crate::ports::event_bus::EventPublisher;
 use crate::ports::flow_store::FlowStore;
 use crate::ports::login_attempt_repository::LoginAttemptRepository;
 use crate::ports::realm_passkey_settings_repository::RealmPasskeySettingsRepository;
@@ -60,25 +75,35 @@ pub struct BuiltinAuthContext {
     pub lockout_threshold: i64,
     pub lockout_duration_secs: i64,
     pub session_repo: Arc<dyn SessionRepository>,
+    pub logout_service: Arc<LogoutService>,
     pub flow_store: Arc<dyn FlowStore>,
     pub action_repo: Arc<dyn AuthSessionActionRepository>,
     pub recovery_attempt_repo: Arc<dyn RecoveryAttemptRepository>,
     pub audit_service: Arc<AuditService>,
+    pub event_publisher: Arc<dyn EventPublisher>,
     pub recovery_settings_repo: Arc<dyn RealmRecoverySettingsRepository>,
     pub passkey_settings_repo: Arc<dyn RealmPasskeySettingsRepository>,
     pub identity_provider_service: Arc<IdentityProviderService>,
     pub oauth_broker_service: Arc<OAuthBrokerService>,
+    pub user_federation_service: Arc<UserFederationService>,
+    pub totp_service: Arc<TotpService>,
+    pub sms_otp_service: Arc<SmsOtpService>,
+    pub password_policy_service: Arc<PasswordPolicyService>,
+    pub consent_service: Arc<ConsentService>,
 }
 
 pub fn register_builtins(registry: &mut RuntimeRegistry, ctx: BuiltinAuthContext) {
     // 1. Password Node (Worker)
     // Connects "core.auth.password" string -> PasswordAuthenticator Struct
     let pw_node = Arc::new(PasswordAuthenticator::new(
-        ctx.user_repo,
+        ctx.user_repo.clone(),
         ctx.realm_repo.clone(),
         ctx.login_attempt_repo,
         ctx.identity_provider_service.clone(),
         ctx.oauth_broker_service.clone(),
+        ctx.user_service.clone(),
+        ctx.user_federation_service.clone(),
+        ctx.event_publisher.clone(),
         ctx.lockout_threshold,
         ctx.lockout_duration_secs,
     ));
@@ -103,11 +128,31 @@ pub fn register_builtins(registry: &mut RuntimeRegistry, ctx: BuiltinAuthContext
         StepType::Authenticator,
     );
 
+    // 1.2 Authenticator App (TOTP) Nodes
+    let totp_enroll_node = Arc::new(TotpEnrollAuthenticator::new(
+        ctx.totp_service.clone(),
+        ctx.user_repo.clone(),
+        ctx.realm_repo.clone(),
+    ));
+    registry.register_node(
+        "core.auth.totp_enroll",
+        totp_enroll_node,
+        StepType::Authenticator,
+    );
+
+    let totp_verify_node = Arc::new(TotpVerifyAuthenticator::new(ctx.totp_service));
+    registry.register_node(
+        "core.auth.totp_verify",
+        totp_verify_node,
+        StepType::Authenticator,
+    );
+
     // 2. Registration Node
     let registration_node = Arc::new(RegistrationAuthenticator::new(
         ctx.user_service.clone(),
         ctx.realm_repo.clone(),
         ctx.rbac_service,
+        ctx.password_policy_service.clone(),
     ));
     registry.register_node(
         "core.auth.register",
@@ -129,10 +174,12 @@ pub fn register_builtins(registry: &mut RuntimeRegistry, ctx: BuiltinAuthContext
     // 4. Reset Password Node
     let reset_node = Arc::new(ResetPasswordAuthenticator::new(
         ctx.user_service.clone(),
-        ctx.session_repo.clone(),
+        ctx.logout_service.clone(),
         ctx.audit_service.clone(),
+        ctx.event_publisher.clone(),
         ctx.recovery_settings_repo.clone(),
         ctx.action_repo.clone(),
+        ctx.password_policy_service.clone(),
     ));
     registry.register_node(
         "core.auth.reset_password",
@@ -141,7 +188,7 @@ pub fn register_builtins(registry: &mut RuntimeRegistry, ctx: BuiltinAuthContext
     );
 
     // 5. OIDC Consent Node
-    let consent_node = Arc::new(OidcConsentAuthenticator::new());
+    let consent_node = Arc::new(OidcConsentAuthenticator::new(ctx.consent_service));
     registry.register_node("core.oidc.consent", consent_node, StepType::Authenticator);
 
     let collect_idp_choice_node = Arc::new(CollectIdpChoiceAuthenticator::new(
@@ -190,6 +237,21 @@ pub fn register_builtins(registry: &mut RuntimeRegistry, ctx: BuiltinAuthContext
         StepType::Authenticator,
     );
 
+    // 8.1 SMS OTP Issue Logic Node + Verify Authenticator
+    let sms_otp_issue_node = Arc::new(SmsOtpIssueNode::new(ctx.sms_otp_service.clone()));
+    registry.register_node(
+        "core.logic.issue_sms_otp",
+        sms_otp_issue_node,
+        StepType::Logic,
+    );
+
+    let verify_sms_otp_node = Arc::new(VerifySmsOtpAuthenticator::new(ctx.sms_otp_service));
+    registry.register_node(
+        "core.auth.verify_sms_otp",
+        verify_sms_otp_node,
+        StepType::Authenticator,
+    );
+
     // 9. Cookie Authenticator (SSO)
     let cookie_node = Arc::new(CookieAuthenticator::new(ctx.session_repo));
     registry.register_node("core.auth.cookie", cookie_node, StepType::Authenticator);
@@ -1,16 +1,38 @@
 use async_trait::async_trait;
 use serde_json::{json, Value};
-use tracing::instrument;
+use std::sync::Arc;
+use tracing::{instrument, warn};
+use uuid::Uuid;
 
+use crate::application::consent_service::ConsentService;
 use crate::domain::auth_session::AuthenticationSession;
 use crate::domain::execution::lifecycle::{LifecycleNode, NodeOutcome};
 use crate::error::{Error, Result};
 
-pub struct OidcConsentAuthenticator;
+pub struct OidcConsentAuthenticator {
+    consent_service: Arc<ConsentService>,
+}
 
 impl OidcConsentAuthenticator {
-    pub fn new() -> Self {
-        Self
+    pub fn new(consent_service: Arc<ConsentService>) -> Self {
+        Self { consent_service }
+    }
+
+    /// The signed-in user, client and requested scopes, when the session
+    /// carries all three.
+    fn consent_subject(session: &AuthenticationSession) -> Option<(Uuid, String, Vec<String>)> {
+        let user_id = session.user_id?;
+        let oidc = session.context.get("oidc")?;
+        let client_id = oidc
+            .get("client_id")
+            .and_then(|value| value.as_str())
+            .filter(|value| !value.is_empty())?;
+        let scopes = oidc
+            .get("scope")
+            .and_then(|value| value.as_str())
+            .map(parse_scopes)
+            .unwrap_or_default();
+        Some((user_id, client_id.to_string(), scopes))
     }
 
     fn build_context(session: &AuthenticationSession) -> Value {
@@ -56,6 +78,18 @@ impl LifecycleNode for OidcConsentAuthenticator {
         fields(telemetry = "span", node = "oidc_consent", phase = "execute")
     )]
     async fn execute(&self, session: &mut AuthenticationSession) -> Result<NodeOutcome> {
+        if let Some((user_id, client_id, scopes)) = Self::consent_subject(session) {
+            if self
+                .consent_service
+                .is_granted(session.realm_id, user_id, &client_id, &scopes)
+                .await?
+            {
+                return Ok(NodeOutcome::Continue {
+                    output: "allow".to_string(),
+                });
+            }
+        }
+
         Ok(NodeOutcome::SuspendForUI {
             screen: "core.oidc.consent".to_string(),
             context: Self::build_context(session),
@@ -68,7 +102,7 @@ impl LifecycleNode for OidcConsentAuthenticator {
     )]
     async fn handle_input(
         &self,
-        _session: &mut AuthenticationSession,
+        session: &mut AuthenticationSession,
         input: Value,
     ) -> Result<NodeOutcome> {
         let decision = Self::resolve_decision(&input).unwrap_or_else(|| "allow".to_string());
@@ -82,6 +116,20 @@ impl LifecycleNode for OidcConsentAuthenticator {
             }
         };
 
+        if output == "allow" {
+            if let Some((user_id, client_id, scopes)) = Self::consent_subject(session) {
+                // The user already approved; a failed write only means they
+                // are asked again next time.
+                if let Err(err) = self
+                    .consent_service
+                    .record_grant(session.realm_id, user_id, &client_id, &scopes)
+                    .await
+                {
+                    warn!("Failed to record consent for client {}: {}", client_id, err);
+                }
+            }
+        }
+
         Ok(NodeOutcome::Continue {
             output: output.to_string(),
         })
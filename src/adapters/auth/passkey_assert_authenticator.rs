@@ -176,6 +176,7 @@ mod tests {
     use super::*;
     use crate::domain::auth_session::AuthenticationSession;
     use crate::ports::realm_passkey_settings_repository::RealmPasskeySettingsRepository;
+    use crate::ports::transaction_manager::Transaction;
     use async_trait::async_trait;
     use chrono::Utc;
     use serde_json::json;
@@ -190,7 +191,11 @@ mod tests {
             Ok(self.settings.clone())
         }
 
-        async fn upsert(&self, _settings: &RealmPasskeySettings) -> Result<()> {
+        async fn upsert(
+            &self,
+            _settings: &RealmPasskeySettings,
+            _tx: Option<&mut dyn Transaction>,
+        ) -> Result<()> {
             Ok(())
         }
     }
@@ -115,6 +115,7 @@ impl LifecycleNode for PasskeyEnrollAuthenticator {
 mod tests {
     use super::*;
     use crate::ports::realm_passkey_settings_repository::RealmPasskeySettingsRepository;
+    use crate::ports::transaction_manager::Transaction;
     use async_trait::async_trait;
     use serde_json::json;
     use uuid::Uuid;
@@ -129,7 +130,11 @@ mod tests {
             Ok(self.settings.clone())
         }
 
-        async fn upsert(&self, _settings: &RealmPasskeySettings) -> Result<()> {
+        async fn upsert(
+            &self,
+            _settings: &RealmPasskeySettings,
+            _tx: Option<&mut dyn Transaction>,
+        ) -> Result<()> {
             Ok(())
         }
     }
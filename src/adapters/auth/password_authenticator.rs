@@ -1,22 +1,27 @@
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde_json::{json, Value};
 use std::sync::Arc;
 use tracing::{instrument, warn};
 
 use crate::application::idp_service::{IdentityProviderLoginOption, IdentityProviderService};
 use crate::application::oauth_broker_service::OAuthBrokerService;
+use crate::application::user_federation_service::{FederatedLogin, UserFederationService};
+use crate::application::user_service::UserService;
 use crate::domain::auth_session::AuthenticationSession;
+use crate::domain::events::{DomainEvent, UserLockedOut};
 use crate::domain::{
     crypto::HashedPassword,
     execution::lifecycle::{LifecycleNode, NodeOutcome},
     identity_provider::OAuthBrokerResult,
 };
 use crate::error::{Error, Result};
+use crate::ports::event_bus::EventPublisher;
 use crate::ports::login_attempt_repository::LoginAttemptRepository;
 use crate::ports::realm_repository::RealmRepository;
 use crate::ports::user_repository::UserRepository;
 const LOCKOUT_MESSAGE: &str = "Account temporarily locked. Try again later.";
+const DIRECTORY_UNAVAILABLE_MESSAGE: &str = "The user directory is unavailable. Try again later.";
 const LINK_ERROR_KEY: &str = "oauth_link_error";
 const FAILURE_KEY: &str = "oauth_failure";
 
@@ -28,17 +33,24 @@ pub struct PasswordAuthenticator {
     login_attempt_repo: Arc<dyn LoginAttemptRepository>,
     identity_provider_service: Arc<IdentityProviderService>,
     oauth_broker_service: Arc<OAuthBrokerService>,
+    user_service: Arc<UserService>,
+    user_federation_service: Arc<UserFederationService>,
+    event_publisher: Arc<dyn EventPublisher>,
     lockout_threshold: i64,
     lockout_duration_secs: i64,
 }
 
 impl PasswordAuthenticator {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         user_repo: Arc<dyn UserRepository>,
         realm_repo: Arc<dyn RealmRepository>,
         login_attempt_repo: Arc<dyn LoginAttemptRepository>,
         identity_provider_service: Arc<IdentityProviderService>,
         oauth_broker_service: Arc<OAuthBrokerService>,
+        user_service: Arc<UserService>,
+        user_federation_service: Arc<UserFederationService>,
+        event_publisher: Arc<dyn EventPublisher>,
         lockout_threshold: i64,
         lockout_duration_secs: i64,
     ) -> Self {
@@ -48,11 +60,33 @@ impl PasswordAuthenticator {
             login_attempt_repo,
             identity_provider_service,
             oauth_broker_service,
+            user_service,
+            user_federation_service,
+            event_publisher,
             lockout_threshold,
             lockout_duration_secs,
         }
     }
 
+    /// Announces the failure that tripped the lockout; later attempts are
+    /// rejected before they are counted, so each lockout is published once.
+    async fn publish_locked_out(
+        &self,
+        session: &AuthenticationSession,
+        user_id: Option<uuid::Uuid>,
+        username: &str,
+        locked_until: DateTime<Utc>,
+    ) {
+        self.event_publisher
+            .publish(DomainEvent::UserLockedOut(UserLockedOut {
+                realm_id: session.realm_id,
+                user_id,
+                username: username.to_string(),
+                locked_until,
+            }))
+            .await;
+    }
+
     async fn lockout_policy(&self, realm_id: &uuid::Uuid) -> Result<(i64, i64)> {
         if let Some(realm) = self.realm_repo.find_by_id(realm_id).await? {
             return Ok((realm.lockout_threshold, realm.lockout_duration_secs));
@@ -385,14 +419,28 @@ impl LifecycleNode for PasswordAuthenticator {
             }
         }
 
-        // 2. Lookup User
-        let user = match self
+        // 2. Lookup User, importing unknown ones from the realm's user directories
+        let local_user = self
             .user_repo
             .find_by_username(&_session.realm_id, username)
-            .await?
-        {
-            Some(u) => u,
+            .await?;
+        let imported = match local_user {
+            Some(_) => FederatedLogin::NotFederated,
             None => {
+                self.user_federation_service
+                    .import_on_login(_session.realm_id, username, password)
+                    .await?
+            }
+        };
+        let (mut user, password_verified) = match (local_user, imported) {
+            (Some(u), _) => (u, false),
+            (None, FederatedLogin::Authenticated(u)) => (*u, true),
+            (None, FederatedLogin::Unavailable) => {
+                return self
+                    .reject_auth(_session, username, DIRECTORY_UNAVAILABLE_MESSAGE)
+                    .await;
+            }
+            (None, _) => {
                 // Security: Fake verify to prevent timing attacks (optional)
                 warn!("Login failed: User not found '{}'", username);
                 if lockout_enabled {
@@ -407,6 +455,8 @@ impl LifecycleNode for PasswordAuthenticator {
                         .await?;
                     if let Some(locked_until) = attempt.locked_until {
                         if locked_until > Utc::now() {
+                            self.publish_locked_out(_session, None, username, locked_until)
+                                .await;
                             return self.reject_auth(_session, username, LOCKOUT_MESSAGE).await;
                         }
                     }
@@ -431,9 +481,31 @@ impl LifecycleNode for PasswordAuthenticator {
                 .await;
         }
 
-        // 3. Verify Password Hash
-        let hashed = HashedPassword::from_hash(&user.hashed_password)?;
-        if !hashed.verify(password)? {
+        // 3. Verify Password: federated users bind to their directory
+        let password_valid = if password_verified {
+            true
+        } else {
+            match self
+                .user_federation_service
+                .authenticate(&user, password)
+                .await?
+            {
+                FederatedLogin::NotFederated => {
+                    HashedPassword::from_hash(&user.hashed_password)?.verify(password)?
+                }
+                FederatedLogin::Authenticated(refreshed) => {
+                    user = *refreshed;
+                    true
+                }
+                FederatedLogin::InvalidCredentials => false,
+                FederatedLogin::Unavailable => {
+                    return self
+                        .reject_auth(_session, username, DIRECTORY_UNAVAILABLE_MESSAGE)
+                        .await;
+                }
+            }
+        };
+        if !password_valid {
             warn!("Login failed: Invalid password for '{}'", username);
             if lockout_enabled {
                 let attempt = self
@@ -447,6 +519,8 @@ impl LifecycleNode for PasswordAuthenticator {
                     .await?;
                 if let Some(locked_until) = attempt.locked_until {
                     if locked_until > Utc::now() {
+                        self.publish_locked_out(_session, Some(user.id), username, locked_until)
+                            .await;
                         return self.reject_auth(_session, username, LOCKOUT_MESSAGE).await;
                     }
                 }
@@ -462,6 +536,9 @@ impl LifecycleNode for PasswordAuthenticator {
                 .await?;
         }
 
+        // An expired password is handled like an admin-forced reset below.
+        self.user_service.expire_stale_password(&mut user).await?;
+
         // 4. Success Logic
         // A. Update Identity in Session
         _session.user_id = Some(user.id);
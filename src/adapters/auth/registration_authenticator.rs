@@ -1,8 +1,10 @@
+use crate::application::password_policy_service::PasswordPolicyService;
 use crate::application::rbac_service::RbacService;
 use crate::application::realm_policy::RealmCapabilities;
 use crate::application::user_service::UserService;
 use crate::domain::auth_session::AuthenticationSession;
 use crate::domain::execution::lifecycle::{LifecycleNode, NodeOutcome};
+use crate::domain::password_policy::summarize_violations;
 use crate::error::{Error, Result};
 use crate::ports::realm_repository::RealmRepository;
 use async_trait::async_trait;
@@ -14,6 +16,7 @@ pub struct RegistrationAuthenticator {
     user_service: Arc<UserService>,
     realm_repo: Arc<dyn RealmRepository>,
     rbac_service: Arc<RbacService>,
+    password_policy_service: Arc<PasswordPolicyService>,
 }
 
 impl RegistrationAuthenticator {
@@ -21,11 +24,13 @@ impl RegistrationAuthenticator {
         user_service: Arc<UserService>,
         realm_repo: Arc<dyn RealmRepository>,
         rbac_service: Arc<RbacService>,
+        password_policy_service: Arc<PasswordPolicyService>,
     ) -> Self {
         Self {
             user_service,
             realm_repo,
             rbac_service,
+            password_policy_service,
         }
     }
 }
@@ -44,6 +49,11 @@ impl LifecycleNode for RegistrationAuthenticator {
         let previous_error = session.context.get("error").cloned();
         let username_prefill = session.context.get("username").cloned();
         let invitation_email = session.context.get("invitation_email").cloned();
+        let previous_violations = session.context.get("password_violations").cloned();
+        let policy = self
+            .password_policy_service
+            .policy_for_realm(session.realm_id)
+            .await?;
 
         Ok(NodeOutcome::SuspendForUI {
             screen: "core.auth.register".to_string(),
@@ -51,6 +61,9 @@ impl LifecycleNode for RegistrationAuthenticator {
                 "username": username_prefill,
                 "email": invitation_email,
                 "error": previous_error,
+                "password_violations": previous_violations,
+                "min_password_length": policy.min_length,
+                "password_policy_hint": policy.hint(),
             }),
         })
     }
@@ -97,12 +110,6 @@ impl LifecycleNode for RegistrationAuthenticator {
             .and_then(|value| value.as_str())
             .ok_or(Error::Validation("Password is required".to_string()))?;
 
-        if password.len() < 8 {
-            return self
-                .reject_registration(session, username, "Password must be at least 8 characters")
-                .await;
-        }
-
         let email_input = input
             .get("email")
             .and_then(|value| value.as_str())
@@ -152,6 +159,7 @@ impl LifecycleNode for RegistrationAuthenticator {
                 session.user_id = Some(user.id);
                 if let Some(ctx) = session.context.as_object_mut() {
                     ctx.remove("error");
+                    ctx.remove("password_violations");
                     ctx.remove("password");
                     ctx.insert("username".to_string(), json!(username));
                     if let Some(email) = email_value {
@@ -188,6 +196,15 @@ impl LifecycleNode for RegistrationAuthenticator {
                 self.reject_registration(session, username, "User already exists")
                     .await
             }
+            Err(Error::PasswordPolicy(violations)) => {
+                let outcome = self
+                    .reject_registration(session, username, &summarize_violations(&violations))
+                    .await?;
+                if let Some(ctx) = session.context.as_object_mut() {
+                    ctx.insert("password_violations".to_string(), json!(violations));
+                }
+                Ok(outcome)
+            }
             Err(err) => Err(err),
         }
     }
@@ -203,6 +220,7 @@ impl LifecycleNode for RegistrationAuthenticator {
     async fn on_exit(&self, session: &mut AuthenticationSession) -> Result<()> {
         if let Some(ctx) = session.context.as_object_mut() {
             ctx.remove("password");
+            ctx.remove("password_violations");
         }
         Ok(())
     }
@@ -251,6 +269,7 @@ impl RegistrationAuthenticator {
         if let Some(ctx) = session.context.as_object_mut() {
             ctx.insert("error".to_string(), json!(reason));
             ctx.insert("username".to_string(), json!(username));
+            ctx.remove("password_violations");
         } else {
             session.context = json!({
                 "error": reason,
@@ -1,43 +1,51 @@
 use crate::application::audit_service::AuditService;
+use crate::application::logout_service::LogoutService;
+use crate::application::password_policy_service::PasswordPolicyService;
 use crate::application::user_service::UserService;
 use crate::domain::audit::NewAuditEvent;
 use crate::domain::auth_session::AuthenticationSession;
+use crate::domain::events::{DomainEvent, PasswordReset, PasswordResetSource};
 use crate::domain::execution::lifecycle::{LifecycleNode, NodeOutcome};
+use crate::domain::password_policy::{summarize_violations, PasswordPolicyViolation};
 use crate::domain::realm_recovery_settings::RealmRecoverySettings;
 use crate::error::{Error, Result};
 use crate::ports::auth_session_action_repository::AuthSessionActionRepository;
+use crate::ports::event_bus::EventPublisher;
 use crate::ports::realm_recovery_settings_repository::RealmRecoverySettingsRepository;
-use crate::ports::session_repository::SessionRepository;
 use async_trait::async_trait;
 use serde_json::{json, Value};
 use std::sync::Arc;
 use tracing::instrument;
 use uuid::Uuid;
 
-const MIN_PASSWORD_LENGTH: usize = 8;
-
 pub struct ResetPasswordAuthenticator {
     user_service: Arc<UserService>,
-    session_repo: Arc<dyn SessionRepository>,
+    logout_service: Arc<LogoutService>,
     audit_service: Arc<AuditService>,
+    event_publisher: Arc<dyn EventPublisher>,
     recovery_settings_repo: Arc<dyn RealmRecoverySettingsRepository>,
     action_repo: Arc<dyn AuthSessionActionRepository>,
+    password_policy_service: Arc<PasswordPolicyService>,
 }
 
 impl ResetPasswordAuthenticator {
     pub fn new(
         user_service: Arc<UserService>,
-        session_repo: Arc<dyn SessionRepository>,
+        logout_service: Arc<LogoutService>,
         audit_service: Arc<AuditService>,
+        event_publisher: Arc<dyn EventPublisher>,
         recovery_settings_repo: Arc<dyn RealmRecoverySettingsRepository>,
         action_repo: Arc<dyn AuthSessionActionRepository>,
+        password_policy_service: Arc<PasswordPolicyService>,
     ) -> Self {
         Self {
             user_service,
-            session_repo,
+            logout_service,
             audit_service,
+            event_publisher,
             recovery_settings_repo,
             action_repo,
+            password_policy_service,
         }
     }
 
@@ -130,6 +138,7 @@ impl ResetPasswordAuthenticator {
     ) -> Result<NodeOutcome> {
         if let Some(ctx) = session.context.as_object_mut() {
             ctx.insert("error".to_string(), json!(reason));
+            ctx.remove("password_violations");
         } else {
             session.context = json!({ "error": reason });
         }
@@ -138,6 +147,20 @@ impl ResetPasswordAuthenticator {
             error: reason.to_string(),
         })
     }
+
+    async fn reject_password(
+        &self,
+        session: &mut AuthenticationSession,
+        violations: Vec<PasswordPolicyViolation>,
+    ) -> Result<NodeOutcome> {
+        let outcome = self
+            .reject_request(session, &summarize_violations(&violations))
+            .await?;
+        if let Some(ctx) = session.context.as_object_mut() {
+            ctx.insert("password_violations".to_string(), json!(violations));
+        }
+        Ok(outcome)
+    }
 }
 
 #[async_trait]
@@ -152,11 +175,18 @@ impl LifecycleNode for ResetPasswordAuthenticator {
     )]
     async fn execute(&self, session: &mut AuthenticationSession) -> Result<NodeOutcome> {
         let previous_error = session.context.get("error").cloned();
+        let previous_violations = session.context.get("password_violations").cloned();
+        let policy = self
+            .password_policy_service
+            .policy_for_realm(session.realm_id)
+            .await?;
         Ok(NodeOutcome::SuspendForUI {
             screen: "core.auth.reset_password".to_string(),
             context: json!({
                 "error": previous_error,
-                "min_password_length": MIN_PASSWORD_LENGTH,
+                "password_violations": previous_violations,
+                "min_password_length": policy.min_length,
+                "password_policy_hint": policy.hint(),
             }),
         })
     }
@@ -179,18 +209,6 @@ impl LifecycleNode for ResetPasswordAuthenticator {
             .and_then(|value| value.as_str())
             .ok_or_else(|| Error::Validation("Password is required".to_string()))?;
 
-        if password.len() < MIN_PASSWORD_LENGTH {
-            return self
-                .reject_request(
-                    session,
-                    &format!(
-                        "Password must be at least {} characters",
-                        MIN_PASSWORD_LENGTH
-                    ),
-                )
-                .await;
-        }
-
         if let Some(confirm) = input
             .get("password_confirm")
             .or_else(|| input.get("confirm_password"))
@@ -203,6 +221,7 @@ impl LifecycleNode for ResetPasswordAuthenticator {
         }
 
         let mut user_id: Option<Uuid> = None;
+        let mut source = PasswordResetSource::Recovery;
         if let Some(payload) = self.resolve_action_payload(session).await? {
             user_id = self.resolve_user_id(session, &payload).await?;
             if user_id.is_none() {
@@ -220,6 +239,7 @@ impl LifecycleNode for ResetPasswordAuthenticator {
                 .unwrap_or(false);
             if force_reset_flow {
                 user_id = session.user_id;
+                source = PasswordResetSource::ForcedReset;
             }
         }
 
@@ -229,9 +249,17 @@ impl LifecycleNode for ResetPasswordAuthenticator {
                 .await;
         };
 
-        self.user_service
-            .update_password(session.realm_id, user_id, password)
-            .await?;
+        match self
+            .user_service
+            .update_password(session.realm_id, user_id, password, false)
+            .await
+        {
+            Ok(_) => {}
+            Err(Error::PasswordPolicy(violations)) => {
+                return self.reject_password(session, violations).await;
+            }
+            Err(err) => return Err(err),
+        }
 
         let recovery_settings = self
             .recovery_settings_repo
@@ -240,8 +268,8 @@ impl LifecycleNode for ResetPasswordAuthenticator {
             .unwrap_or_else(|| RealmRecoverySettings::defaults(session.realm_id));
 
         if recovery_settings.revoke_sessions_on_reset {
-            self.session_repo
-                .revoke_all_for_user(&session.realm_id, &user_id)
+            self.logout_service
+                .revoke_all_for_user(session.realm_id, user_id)
                 .await?;
         }
 
@@ -260,8 +288,17 @@ impl LifecycleNode for ResetPasswordAuthenticator {
             tracing::warn!("Failed to write password reset audit event: {}", err);
         }
 
+        self.event_publisher
+            .publish(DomainEvent::PasswordReset(PasswordReset {
+                realm_id: session.realm_id,
+                user_id,
+                source,
+            }))
+            .await;
+
         if let Some(ctx) = session.context.as_object_mut() {
             ctx.remove("error");
+            ctx.remove("password_violations");
         }
 
         Ok(NodeOutcome::Continue {
@@ -284,6 +321,7 @@ impl LifecycleNode for ResetPasswordAuthenticator {
             ctx.remove("confirm_password");
             ctx.remove("password_confirmation");
             ctx.remove("error");
+            ctx.remove("password_violations");
             ctx.remove("action_payload");
             ctx.remove("force_password_reset");
         }
@@ -0,0 +1,131 @@
+use crate::application::sms_otp_service::SmsOtpService;
+use crate::domain::auth_session::AuthenticationSession;
+use crate::domain::execution::lifecycle::{LifecycleNode, NodeOutcome};
+use crate::domain::sms_otp::{
+    SmsOtpPolicy, DEFAULT_SMS_MAX_RESENDS, DEFAULT_SMS_OTP_TTL_MINUTES,
+    DEFAULT_SMS_RESEND_COOLDOWN_SECS,
+};
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tracing::{instrument, warn};
+use uuid::Uuid;
+
+/// Session context key shared with `VerifySmsOtpAuthenticator`.
+pub(crate) const SMS_OTP_CONTEXT_KEY: &str = "sms_otp";
+pub(crate) const SMS_OTP_FAILED_ATTEMPTS_KEY: &str = "sms_otp_failed_attempts";
+
+pub struct SmsOtpIssueNode {
+    sms_otp_service: Arc<SmsOtpService>,
+}
+
+impl SmsOtpIssueNode {
+    pub fn new(sms_otp_service: Arc<SmsOtpService>) -> Self {
+        Self { sms_otp_service }
+    }
+
+    fn node_config(session: &AuthenticationSession) -> Value {
+        session
+            .context
+            .get("node_config")
+            .cloned()
+            .unwrap_or_else(|| json!({}))
+    }
+
+    fn resolve_i64(config: &Value, key: &str, fallback: i64) -> i64 {
+        config
+            .get(key)
+            .and_then(|value| value.as_i64())
+            .unwrap_or(fallback)
+            .max(0)
+    }
+
+    fn policy(config: &Value) -> SmsOtpPolicy {
+        SmsOtpPolicy {
+            ttl_minutes: Self::resolve_i64(
+                config,
+                "token_ttl_minutes",
+                DEFAULT_SMS_OTP_TTL_MINUTES,
+            )
+            .max(1),
+            resend_cooldown_secs: Self::resolve_i64(
+                config,
+                "resend_cooldown_secs",
+                DEFAULT_SMS_RESEND_COOLDOWN_SECS,
+            ),
+            max_resends: Self::resolve_i64(config, "max_resends", DEFAULT_SMS_MAX_RESENDS as i64)
+                as u32,
+            message_template: config
+                .get("message_template")
+                .and_then(|value| value.as_str())
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty()),
+        }
+    }
+
+    fn user_id(session: &AuthenticationSession) -> Option<Uuid> {
+        session.user_id.or_else(|| {
+            session
+                .context
+                .get("user_id")
+                .and_then(|value| value.as_str())
+                .and_then(|value| Uuid::parse_str(value).ok())
+        })
+    }
+}
+
+#[async_trait]
+impl LifecycleNode for SmsOtpIssueNode {
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", node = "sms_otp_issue_node", phase = "execute")
+    )]
+    async fn execute(&self, session: &mut AuthenticationSession) -> Result<NodeOutcome> {
+        let Some(user_id) = Self::user_id(session) else {
+            return Ok(NodeOutcome::FlowFailure {
+                reason: "User must be identified before sending an SMS code".to_string(),
+            });
+        };
+        let config = Self::node_config(session);
+        let require_verified = config
+            .get("require_verified_phone")
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false);
+
+        let Some(phone) = self
+            .sms_otp_service
+            .find_target_phone(session.realm_id, user_id, require_verified)
+            .await?
+        else {
+            return Ok(NodeOutcome::Continue {
+                output: "no_phone".to_string(),
+            });
+        };
+
+        let challenge = match self
+            .sms_otp_service
+            .issue(session.realm_id, &phone, Self::policy(&config))
+            .await
+        {
+            Ok(challenge) => challenge,
+            Err(err) => {
+                warn!("Failed to send SMS verification code: {}", err);
+                return Ok(NodeOutcome::FlowFailure {
+                    reason: "Unable to send a verification code right now.".to_string(),
+                });
+            }
+        };
+
+        let challenge = serde_json::to_value(&challenge)
+            .map_err(|err| Error::System(format!("Failed to store SMS challenge: {}", err)))?;
+        session.update_context(SMS_OTP_CONTEXT_KEY, challenge);
+        if let Some(ctx) = session.context.as_object_mut() {
+            ctx.remove(SMS_OTP_FAILED_ATTEMPTS_KEY);
+        }
+
+        Ok(NodeOutcome::Continue {
+            output: "issued".to_string(),
+        })
+    }
+}
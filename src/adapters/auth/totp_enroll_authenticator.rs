@@ -0,0 +1,251 @@
+use crate::application::totp_service::{TotpEnrollment, TotpService, DEFAULT_TOTP_DRIFT_STEPS};
+use crate::domain::auth_session::AuthenticationSession;
+use crate::domain::execution::lifecycle::{LifecycleNode, NodeOutcome};
+use crate::domain::totp_credential::{TOTP_DIGITS, TOTP_PERIOD_SECS};
+use crate::error::{Error, Result};
+use crate::ports::realm_repository::RealmRepository;
+use crate::ports::user_repository::UserRepository;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tracing::instrument;
+use uuid::Uuid;
+
+/// Session context key holding the encrypted secret offered on the enroll page.
+const PENDING_SECRET_KEY: &str = "totp_pending_secret";
+
+pub struct TotpEnrollAuthenticator {
+    totp_service: Arc<TotpService>,
+    user_repo: Arc<dyn UserRepository>,
+    realm_repo: Arc<dyn RealmRepository>,
+}
+
+impl TotpEnrollAuthenticator {
+    pub fn new(
+        totp_service: Arc<TotpService>,
+        user_repo: Arc<dyn UserRepository>,
+        realm_repo: Arc<dyn RealmRepository>,
+    ) -> Self {
+        Self {
+            totp_service,
+            user_repo,
+            realm_repo,
+        }
+    }
+
+    fn node_config(session: &AuthenticationSession) -> Value {
+        session
+            .context
+            .get("node_config")
+            .cloned()
+            .unwrap_or_else(|| json!({}))
+    }
+
+    fn allow_skip(session: &AuthenticationSession) -> bool {
+        Self::node_config(session)
+            .get("allow_skip")
+            .and_then(|value| value.as_bool())
+            .unwrap_or(true)
+    }
+
+    fn drift_steps(session: &AuthenticationSession) -> i64 {
+        Self::node_config(session)
+            .get("drift_steps")
+            .and_then(|value| value.as_i64())
+            .unwrap_or(DEFAULT_TOTP_DRIFT_STEPS)
+    }
+
+    fn user_id(session: &AuthenticationSession) -> Option<Uuid> {
+        session.user_id.or_else(|| {
+            session
+                .context
+                .get("user_id")
+                .and_then(|value| value.as_str())
+                .and_then(|value| Uuid::parse_str(value).ok())
+        })
+    }
+
+    fn pending_enrollment(&self, session: &mut AuthenticationSession) -> Result<TotpEnrollment> {
+        if let Some(sealed) = session
+            .context
+            .get(PENDING_SECRET_KEY)
+            .and_then(|value| value.as_str())
+        {
+            return self.totp_service.resume_enrollment(sealed);
+        }
+        let enrollment = self.totp_service.start_enrollment()?;
+        session.update_context(PENDING_SECRET_KEY, json!(enrollment.secret_encrypted));
+        Ok(enrollment)
+    }
+
+    async fn render(
+        &self,
+        session: &mut AuthenticationSession,
+        user_id: Uuid,
+    ) -> Result<NodeOutcome> {
+        let realm = self
+            .realm_repo
+            .find_by_id(&session.realm_id)
+            .await?
+            .ok_or_else(|| Error::NotFound("Realm not found".to_string()))?;
+        let user = self
+            .user_repo
+            .find_by_id(&user_id)
+            .await?
+            .ok_or(Error::UserNotFound)?;
+        let issuer = Self::node_config(session)
+            .get("issuer")
+            .and_then(|value| value.as_str())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+            .unwrap_or(realm.name);
+
+        let enrollment = self.pending_enrollment(session)?;
+        let previous_error = session.context.get("error").cloned();
+
+        Ok(NodeOutcome::SuspendForUI {
+            screen: "core.auth.totp_enroll".to_string(),
+            context: json!({
+                "totp_enrollment": true,
+                "otpauth_uri": enrollment.otpauth_uri(&issuer, &user.username),
+                "secret": enrollment.secret_display(),
+                "issuer": issuer,
+                "account": user.username,
+                "digits": TOTP_DIGITS,
+                "period": TOTP_PERIOD_SECS,
+                "can_skip": Self::allow_skip(session),
+                "error": previous_error,
+                "template_key": "totp_enroll"
+            }),
+        })
+    }
+
+    fn reject(session: &mut AuthenticationSession, reason: &str) -> NodeOutcome {
+        session.update_context("error", json!(reason));
+        NodeOutcome::Reject {
+            error: reason.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl LifecycleNode for TotpEnrollAuthenticator {
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            node = "totp_enroll_authenticator",
+            phase = "execute"
+        )
+    )]
+    async fn execute(&self, session: &mut AuthenticationSession) -> Result<NodeOutcome> {
+        let Some(user_id) = Self::user_id(session) else {
+            return Ok(NodeOutcome::Continue {
+                output: "failure".to_string(),
+            });
+        };
+
+        // Enrolling again would silently replace the user's existing app.
+        if self
+            .totp_service
+            .has_credential(session.realm_id, user_id)
+            .await?
+        {
+            return Ok(NodeOutcome::Continue {
+                output: "skip".to_string(),
+            });
+        }
+
+        self.render(session, user_id).await
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            node = "totp_enroll_authenticator",
+            phase = "handle_input"
+        )
+    )]
+    async fn handle_input(
+        &self,
+        session: &mut AuthenticationSession,
+        input: Value,
+    ) -> Result<NodeOutcome> {
+        let Some(user_id) = Self::user_id(session) else {
+            return Ok(NodeOutcome::Continue {
+                output: "failure".to_string(),
+            });
+        };
+
+        if input
+            .get("action")
+            .and_then(|value| value.as_str())
+            .is_some_and(|action| action == "skip")
+        {
+            if Self::allow_skip(session) {
+                return Ok(NodeOutcome::Continue {
+                    output: "skip".to_string(),
+                });
+            }
+            return Ok(Self::reject(
+                session,
+                "Setting up an authenticator app is required.",
+            ));
+        }
+
+        let Some(sealed) = session
+            .context
+            .get(PENDING_SECRET_KEY)
+            .and_then(|value| value.as_str())
+            .map(str::to_string)
+        else {
+            return Ok(Self::reject(
+                session,
+                "Authenticator setup expired. Scan the new code to continue.",
+            ));
+        };
+        let code = input
+            .get("otp")
+            .and_then(|value| value.as_str())
+            .unwrap_or_default();
+
+        let enrolled = self
+            .totp_service
+            .complete_enrollment(
+                session.realm_id,
+                user_id,
+                &sealed,
+                code,
+                Self::drift_steps(session),
+            )
+            .await?;
+        if !enrolled {
+            return Ok(Self::reject(
+                session,
+                "That code did not match. Check your authenticator app and try again.",
+            ));
+        }
+
+        Ok(NodeOutcome::Continue {
+            output: "success".to_string(),
+        })
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            node = "totp_enroll_authenticator",
+            phase = "on_exit"
+        )
+    )]
+    async fn on_exit(&self, session: &mut AuthenticationSession) -> Result<()> {
+        if let Some(ctx) = session.context.as_object_mut() {
+            ctx.remove(PENDING_SECRET_KEY);
+            ctx.remove("error");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+use crate::application::totp_service::{TotpService, TotpVerification, DEFAULT_TOTP_DRIFT_STEPS};
+use crate::domain::auth_session::AuthenticationSession;
+use crate::domain::execution::lifecycle::{LifecycleNode, NodeOutcome};
+use crate::error::Result;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tracing::instrument;
+use uuid::Uuid;
+
+const FAILED_ATTEMPTS_KEY: &str = "totp_failed_attempts";
+const DEFAULT_MAX_ATTEMPTS: i64 = 5;
+
+pub struct TotpVerifyAuthenticator {
+    totp_service: Arc<TotpService>,
+}
+
+impl TotpVerifyAuthenticator {
+    pub fn new(totp_service: Arc<TotpService>) -> Self {
+        Self { totp_service }
+    }
+
+    fn node_config(session: &AuthenticationSession) -> Value {
+        session
+            .context
+            .get("node_config")
+            .cloned()
+            .unwrap_or_else(|| json!({}))
+    }
+
+    fn drift_steps(session: &AuthenticationSession) -> i64 {
+        Self::node_config(session)
+            .get("drift_steps")
+            .and_then(|value| value.as_i64())
+            .unwrap_or(DEFAULT_TOTP_DRIFT_STEPS)
+    }
+
+    fn max_attempts(session: &AuthenticationSession) -> i64 {
+        Self::node_config(session)
+            .get("max_attempts")
+            .and_then(|value| value.as_i64())
+            .filter(|value| *value > 0)
+            .unwrap_or(DEFAULT_MAX_ATTEMPTS)
+    }
+
+    fn user_id(session: &AuthenticationSession) -> Option<Uuid> {
+        session.user_id.or_else(|| {
+            session
+                .context
+                .get("user_id")
+                .and_then(|value| value.as_str())
+                .and_then(|value| Uuid::parse_str(value).ok())
+        })
+    }
+
+    fn failed_attempts(session: &AuthenticationSession) -> i64 {
+        session
+            .context
+            .get(FAILED_ATTEMPTS_KEY)
+            .and_then(|value| value.as_i64())
+            .unwrap_or(0)
+    }
+}
+
+#[async_trait]
+impl LifecycleNode for TotpVerifyAuthenticator {
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            node = "totp_verify_authenticator",
+            phase = "execute"
+        )
+    )]
+    async fn execute(&self, session: &mut AuthenticationSession) -> Result<NodeOutcome> {
+        let Some(user_id) = Self::user_id(session) else {
+            return Ok(NodeOutcome::Continue {
+                output: "failure".to_string(),
+            });
+        };
+
+        if !self
+            .totp_service
+            .has_credential(session.realm_id, user_id)
+            .await?
+        {
+            return Ok(NodeOutcome::Continue {
+                output: "not_enrolled".to_string(),
+            });
+        }
+
+        let previous_error = session.context.get("error").cloned();
+
+        Ok(NodeOutcome::SuspendForUI {
+            screen: "core.auth.totp_verify".to_string(),
+            context: json!({
+                "totp_verification": true,
+                "error": previous_error,
+                "template_key": "mfa"
+            }),
+        })
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            node = "totp_verify_authenticator",
+            phase = "handle_input"
+        )
+    )]
+    async fn handle_input(
+        &self,
+        session: &mut AuthenticationSession,
+        input: Value,
+    ) -> Result<NodeOutcome> {
+        let Some(user_id) = Self::user_id(session) else {
+            return Ok(NodeOutcome::Continue {
+                output: "failure".to_string(),
+            });
+        };
+        let code = input
+            .get("otp")
+            .and_then(|value| value.as_str())
+            .unwrap_or_default();
+
+        match self
+            .totp_service
+            .verify_code(session.realm_id, user_id, code, Self::drift_steps(session))
+            .await?
+        {
+            TotpVerification::Valid => Ok(NodeOutcome::Continue {
+                output: "success".to_string(),
+            }),
+            TotpVerification::NotEnrolled => Ok(NodeOutcome::Continue {
+                output: "not_enrolled".to_string(),
+            }),
+            TotpVerification::Invalid => {
+                let attempts = Self::failed_attempts(session) + 1;
+                if attempts >= Self::max_attempts(session) {
+                    return Ok(NodeOutcome::Continue {
+                        output: "failure".to_string(),
+                    });
+                }
+                session.update_context(FAILED_ATTEMPTS_KEY, json!(attempts));
+                session.update_context("error", json!("Invalid authentication code."));
+                Ok(NodeOutcome::Reject {
+                    error: "Invalid authentication code.".to_string(),
+                })
+            }
+        }
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            node = "totp_verify_authenticator",
+            phase = "on_exit"
+        )
+    )]
+    async fn on_exit(&self, session: &mut AuthenticationSession) -> Result<()> {
+        if let Some(ctx) = session.context.as_object_mut() {
+            ctx.remove(FAILED_ATTEMPTS_KEY);
+            ctx.remove("error");
+        }
+        Ok(())
+    }
+}
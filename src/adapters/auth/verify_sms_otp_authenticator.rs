@@ -0,0 +1,230 @@
+use crate::adapters::auth::sms_otp_issue_node::{SMS_OTP_CONTEXT_KEY, SMS_OTP_FAILED_ATTEMPTS_KEY};
+use crate::application::sms_otp_service::{SmsOtpService, SmsResendOutcome};
+use crate::domain::auth_session::AuthenticationSession;
+use crate::domain::execution::lifecycle::{LifecycleNode, NodeOutcome};
+use crate::domain::sms_otp::{SmsOtpChallenge, SmsOtpCheck, SmsResendDecision};
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tracing::{instrument, warn};
+
+const DEFAULT_MAX_ATTEMPTS: i64 = 5;
+const RESEND_MESSAGE_KEY: &str = "sms_otp_resend_message";
+
+pub struct VerifySmsOtpAuthenticator {
+    sms_otp_service: Arc<SmsOtpService>,
+}
+
+impl VerifySmsOtpAuthenticator {
+    pub fn new(sms_otp_service: Arc<SmsOtpService>) -> Self {
+        Self { sms_otp_service }
+    }
+
+    fn max_attempts(session: &AuthenticationSession) -> i64 {
+        session
+            .context
+            .get("node_config")
+            .and_then(|config| config.get("max_attempts"))
+            .and_then(|value| value.as_i64())
+            .filter(|value| *value > 0)
+            .unwrap_or(DEFAULT_MAX_ATTEMPTS)
+    }
+
+    fn challenge(session: &AuthenticationSession) -> Option<SmsOtpChallenge> {
+        session
+            .context
+            .get(SMS_OTP_CONTEXT_KEY)
+            .cloned()
+            .and_then(|value| serde_json::from_value(value).ok())
+    }
+
+    fn store_challenge(
+        session: &mut AuthenticationSession,
+        challenge: &SmsOtpChallenge,
+    ) -> Result<()> {
+        let value = serde_json::to_value(challenge)
+            .map_err(|err| Error::System(format!("Failed to store SMS challenge: {}", err)))?;
+        session.update_context(SMS_OTP_CONTEXT_KEY, value);
+        Ok(())
+    }
+
+    fn failed_attempts(session: &AuthenticationSession) -> i64 {
+        session
+            .context
+            .get(SMS_OTP_FAILED_ATTEMPTS_KEY)
+            .and_then(|value| value.as_i64())
+            .unwrap_or(0)
+    }
+
+    fn reject(session: &mut AuthenticationSession, reason: &str) -> NodeOutcome {
+        if let Some(ctx) = session.context.as_object_mut() {
+            ctx.remove(RESEND_MESSAGE_KEY);
+        }
+        session.update_context("error", json!(reason));
+        NodeOutcome::Reject {
+            error: reason.to_string(),
+        }
+    }
+
+    async fn resend(
+        &self,
+        session: &mut AuthenticationSession,
+        challenge: &SmsOtpChallenge,
+    ) -> Result<NodeOutcome> {
+        let outcome = match self
+            .sms_otp_service
+            .resend(session.realm_id, challenge)
+            .await
+        {
+            Ok(outcome) => outcome,
+            Err(err) => {
+                warn!("Failed to resend SMS verification code: {}", err);
+                return Ok(Self::reject(
+                    session,
+                    "We could not send a new code. Try again shortly.",
+                ));
+            }
+        };
+
+        match outcome {
+            SmsResendOutcome::Sent(next) => {
+                Self::store_challenge(session, &next)?;
+                if let Some(ctx) = session.context.as_object_mut() {
+                    ctx.remove("error");
+                }
+                session.update_context(RESEND_MESSAGE_KEY, json!("A new code has been sent."));
+                Ok(NodeOutcome::Reject {
+                    error: "SMS code resent".to_string(),
+                })
+            }
+            SmsResendOutcome::CoolingDown { retry_after_secs } => Ok(Self::reject(
+                session,
+                &format!(
+                    "Please wait {} seconds before requesting a new code.",
+                    retry_after_secs
+                ),
+            )),
+            SmsResendOutcome::LimitReached => Ok(Self::reject(
+                session,
+                "No more codes can be sent for this sign-in.",
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl LifecycleNode for VerifySmsOtpAuthenticator {
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            node = "verify_sms_otp_authenticator",
+            phase = "execute"
+        )
+    )]
+    async fn execute(&self, session: &mut AuthenticationSession) -> Result<NodeOutcome> {
+        let Some(challenge) = Self::challenge(session) else {
+            return Ok(NodeOutcome::Continue {
+                output: "failure".to_string(),
+            });
+        };
+
+        let can_resend = challenge.resend_decision(Utc::now()) != SmsResendDecision::LimitReached;
+        Ok(NodeOutcome::SuspendForUI {
+            screen: "core.auth.verify_sms_otp".to_string(),
+            context: json!({
+                "phone": challenge.masked_phone,
+                "expires_at": challenge.expires_at,
+                "can_resend": can_resend,
+                "resend_message": session.context.get(RESEND_MESSAGE_KEY).cloned(),
+                "error": session.context.get("error").cloned(),
+                "template_key": "sms_otp"
+            }),
+        })
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            node = "verify_sms_otp_authenticator",
+            phase = "handle_input"
+        )
+    )]
+    async fn handle_input(
+        &self,
+        session: &mut AuthenticationSession,
+        input: Value,
+    ) -> Result<NodeOutcome> {
+        let Some(challenge) = Self::challenge(session) else {
+            return Ok(NodeOutcome::Continue {
+                output: "failure".to_string(),
+            });
+        };
+
+        if input
+            .get("action")
+            .and_then(|value| value.as_str())
+            .is_some_and(|action| action == "resend")
+        {
+            return self.resend(session, &challenge).await;
+        }
+
+        let code = input
+            .get("otp")
+            .and_then(|value| value.as_str())
+            .unwrap_or_default();
+        match challenge.check(code, Utc::now()) {
+            SmsOtpCheck::Valid => {
+                self.sms_otp_service
+                    .mark_verified(session.realm_id, &challenge)
+                    .await?;
+                session.update_context("phone_verified", json!(true));
+                Ok(NodeOutcome::Continue {
+                    output: "success".to_string(),
+                })
+            }
+            SmsOtpCheck::Expired => {
+                if challenge.resend_decision(Utc::now()) == SmsResendDecision::LimitReached {
+                    return Ok(NodeOutcome::Continue {
+                        output: "failure".to_string(),
+                    });
+                }
+                Ok(Self::reject(
+                    session,
+                    "This code has expired. Request a new one.",
+                ))
+            }
+            SmsOtpCheck::Invalid => {
+                let attempts = Self::failed_attempts(session) + 1;
+                if attempts >= Self::max_attempts(session) {
+                    return Ok(NodeOutcome::Continue {
+                        output: "failure".to_string(),
+                    });
+                }
+                session.update_context(SMS_OTP_FAILED_ATTEMPTS_KEY, json!(attempts));
+                Ok(Self::reject(session, "Invalid verification code."))
+            }
+        }
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            node = "verify_sms_otp_authenticator",
+            phase = "on_exit"
+        )
+    )]
+    async fn on_exit(&self, session: &mut AuthenticationSession) -> Result<()> {
+        if let Some(ctx) = session.context.as_object_mut() {
+            ctx.remove(SMS_OTP_CONTEXT_KEY);
+            ctx.remove(SMS_OTP_FAILED_ATTEMPTS_KEY);
+            ctx.remove(RESEND_MESSAGE_KEY);
+            ctx.remove("error");
+        }
+        Ok(())
+    }
+}
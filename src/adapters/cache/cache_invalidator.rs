@@ -194,6 +194,17 @@ impl EventHandler for CacheInvalidator {
                     self.cache.clear_user_permissions(user_id).await;
                 }
             }
+            // Security events change no cached permissions.
+            DomainEvent::LoginSucceeded(_)
+            | DomainEvent::LoginFailed(_)
+            | DomainEvent::UserLockedOut(_)
+            | DomainEvent::PasskeyEnrolled(_)
+            | DomainEvent::MfaEnrolled(_)
+            | DomainEvent::MfaRemoved(_)
+            | DomainEvent::SessionRevoked(_)
+            | DomainEvent::PasswordReset(_)
+            | DomainEvent::IdpLinked(_)
+            | DomainEvent::RefreshTokenReuseDetected(_) => {}
         }
     }
 }
@@ -1,53 +1,98 @@
-use crate::adapters::crypto::key_manager::KeyPair;
+use crate::application::signing_key_service::SigningKeyService;
 use crate::config::AuthConfig;
+use crate::domain::claims::RESERVED_CLAIMS;
+use crate::domain::signing_key::SigningAlgorithm;
 use crate::error::Error;
-use crate::ports::token_service::IdTokenClaims;
+use crate::ports::token_service::{ActorClaim, IdTokenClaims, LogoutTokenClaims};
 use crate::{
-    domain::user::User,
+    domain::{oidc::OidcClient, user::User},
     error::Result,
     ports::token_service::{AccessTokenClaims, TokenService},
 };
 use async_trait::async_trait;
-use base64::engine::general_purpose::URL_SAFE_NO_PAD;
-use base64::Engine;
 use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
-use rsa::pkcs8::DecodePublicKey;
-use rsa::traits::PublicKeyParts;
-use rsa::RsaPublicKey;
-use serde_json::json;
+use jsonwebtoken::{dangerous, decode, decode_header, encode, Header, Validation};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
 use std::collections::HashSet;
+use std::sync::Arc;
 use tracing::instrument;
 use uuid::Uuid;
 
+/// Event member identifying a Back-Channel Logout token.
+const BACKCHANNEL_LOGOUT_EVENT: &str = "http://schemas.openid.net/event/backchannel-logout";
+/// Logout tokens are consumed immediately; a short lifetime limits replay.
+const LOGOUT_TOKEN_TTL_SECS: i64 = 120;
+
 pub struct JwtService {
-    encoding_key: EncodingKey,
-    decoding_key: DecodingKey,
+    signing_keys: Arc<SigningKeyService>,
     access_token_ttl_secs: i64,
-    key_id: String,
-    pub public_key_pem: String,
     issuer: String,
 }
 
 impl JwtService {
-    pub fn new(config: AuthConfig, keys: KeyPair) -> Result<Self> {
-        // Create EncodingKey from Private PEM (for signing)
-        let encoding_key = EncodingKey::from_rsa_pem(keys.private_key_pem.as_bytes())
-            .map_err(|e| Error::Unexpected(anyhow::anyhow!("Invalid Private Key: {}", e)))?;
-
-        // Create DecodingKey from Public PEM (for verification)
-        let decoding_key = DecodingKey::from_rsa_pem(keys.public_key_pem.as_bytes())
-            .map_err(|e| Error::Unexpected(anyhow::anyhow!("Invalid Public Key: {}", e)))?;
-
-        Ok(Self {
-            encoding_key,
-            decoding_key,
+    pub fn new(config: AuthConfig, signing_keys: Arc<SigningKeyService>) -> Self {
+        Self {
+            signing_keys,
             access_token_ttl_secs: config.access_token_ttl_secs,
-            key_id: config.jwt_key_id,
-            public_key_pem: keys.public_key_pem,
             issuer: config.issuer,
-        })
+        }
     }
+
+    /// Signs claims with the realm's active key for `algorithm`, naming it in
+    /// the `kid` header.
+    async fn sign<T: Serialize>(
+        &self,
+        realm_id: Uuid,
+        algorithm: SigningAlgorithm,
+        claims: &T,
+    ) -> Result<String> {
+        let key = self.signing_keys.signing_key(realm_id, algorithm).await?;
+        let mut header = Header::new(key.algorithm);
+        header.kid = Some(key.kid.clone());
+
+        encode(&header, claims, &key.encoding_key).map_err(|e| Error::Unexpected(e.into()))
+    }
+
+    /// Verifies a token against the key of `realm_id` named by its `kid`
+    /// header. The key pins the algorithm; the token header is not trusted
+    /// for it.
+    async fn verify<T: DeserializeOwned>(
+        &self,
+        realm_id: Uuid,
+        token: &str,
+        configure: impl FnOnce(&mut Validation),
+    ) -> Result<T> {
+        let kid = decode_header(token)
+            .ok()
+            .and_then(|header| header.kid)
+            .ok_or(Error::InvalidCredentials)?;
+        let key = self
+            .signing_keys
+            .verifying_key(realm_id, &kid)
+            .await?
+            .ok_or(Error::InvalidCredentials)?;
+
+        let mut validation = Validation::new(key.algorithm);
+        configure(&mut validation);
+        let token_data = decode::<T>(token, &key.decoding_key, &validation)
+            .map_err(|_| Error::InvalidCredentials)?;
+
+        Ok(token_data.claims)
+    }
+}
+
+/// Extra claims minus those the token service sets itself, so a mapper can
+/// never emit a second `sub` or `preferred_username`.
+fn unreserved(extra_claims: &Map<String, Value>) -> Map<String, Value> {
+    extra_claims
+        .iter()
+        .filter(|(name, _)| {
+            !RESERVED_CLAIMS.contains(&name.as_str()) && name.as_str() != "preferred_username"
+        })
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect()
 }
 
 #[async_trait]
@@ -57,9 +102,13 @@ impl TokenService for JwtService {
         &self,
         user: &User,
         session_id: Uuid,
+        client_id: Option<&str>,
         permissions: &HashSet<String>,
         roles: &[String],
         groups: &[String],
+        scope: Option<&str>,
+        extra_claims: &Map<String, Value>,
+        actor: Option<&ActorClaim>,
     ) -> Result<String> {
         let now = Utc::now();
         let expiration = now
@@ -75,16 +124,18 @@ impl TokenService for JwtService {
             groups: groups.to_vec(),
             exp: expiration,
             iat: now.timestamp().max(0) as usize,
+            azp: None,
+            scope: scope.map(str::to_string),
+            aud: None,
+            act: actor.cloned(),
+            extra: unreserved(extra_claims),
         };
 
-        // Set the Key ID in the header
-        let mut header = Header::new(Algorithm::RS256);
-        header.kid = Some(self.key_id.clone());
-
-        Ok(
-            encode(&header, &claims, &self.encoding_key)
-                .map_err(|e| Error::Unexpected(e.into()))?,
-        )
+        let algorithm = self
+            .signing_keys
+            .signing_algorithm(user.realm_id, client_id)
+            .await?;
+        self.sign(user.realm_id, algorithm, &claims).await
     }
 
     #[instrument(skip_all, fields(telemetry = "span"))]
@@ -92,7 +143,9 @@ impl TokenService for JwtService {
         &self,
         user: &User,
         client_id: &str,
+        session_id: Uuid,
         groups: &[String],
+        extra_claims: &Map<String, Value>,
     ) -> Result<String> {
         let now = Utc::now();
         let expiration = (now + Duration::seconds(self.access_token_ttl_secs)).timestamp();
@@ -105,54 +158,160 @@ impl TokenService for JwtService {
             iat: now.timestamp(),
             preferred_username: user.username.clone(),
             groups: groups.to_vec(),
+            sid: Some(session_id),
+            extra: unreserved(extra_claims),
+        };
+
+        let algorithm = self
+            .signing_keys
+            .signing_algorithm(user.realm_id, Some(client_id))
+            .await?;
+        self.sign(user.realm_id, algorithm, &claims).await
+    }
+
+    #[instrument(skip_all, fields(telemetry = "span"))]
+    async fn create_client_access_token(
+        &self,
+        client: &OidcClient,
+        permissions: &HashSet<String>,
+        roles: &[String],
+        scope: Option<&str>,
+    ) -> Result<String> {
+        let now = Utc::now();
+        let expiration = (now + Duration::seconds(self.access_token_ttl_secs)).timestamp() as usize;
+
+        let claims = AccessTokenClaims {
+            sub: client.id,
+            sid: Uuid::nil(),
+            perms: permissions.clone(),
+            roles: roles.to_vec(),
+            groups: Vec::new(),
+            exp: expiration,
+            iat: now.timestamp().max(0) as usize,
+            azp: Some(client.client_id.clone()),
+            scope: scope.map(str::to_string),
+            aud: None,
+            act: None,
+            extra: Map::new(),
         };
 
-        let mut header = Header::new(Algorithm::RS256);
-        header.kid = Some(self.key_id.clone());
+        let algorithm = match client.signing_algorithm {
+            Some(algorithm) => algorithm,
+            None => {
+                self.signing_keys
+                    .signing_algorithm(client.realm_id, None)
+                    .await?
+            }
+        };
+        self.sign(client.realm_id, algorithm, &claims).await
+    }
 
-        Ok(
-            encode(&header, &claims, &self.encoding_key)
-                .map_err(|e| Error::Unexpected(e.into()))?,
-        )
+    #[instrument(skip_all, fields(telemetry = "span"))]
+    async fn create_exchanged_access_token(
+        &self,
+        realm_id: Uuid,
+        subject: &AccessTokenClaims,
+        actor: &str,
+        audience: &str,
+        scope: Option<&str>,
+    ) -> Result<String> {
+        let now = Utc::now();
+        let expiration = (now + Duration::seconds(self.access_token_ttl_secs)).timestamp() as usize;
+
+        let claims = AccessTokenClaims {
+            sub: subject.sub,
+            sid: subject.sid,
+            // Permissions govern this server's own API, which exchanged
+            // tokens are not meant for.
+            perms: HashSet::new(),
+            roles: subject.roles.clone(),
+            groups: subject.groups.clone(),
+            exp: expiration.min(subject.exp),
+            iat: now.timestamp().max(0) as usize,
+            azp: Some(actor.to_string()),
+            scope: scope.map(str::to_string),
+            aud: Some(audience.to_string()),
+            act: Some(ActorClaim {
+                sub: actor.to_string(),
+                act: subject.act.clone().map(Box::new),
+            }),
+            extra: subject.extra.clone(),
+        };
+
+        let algorithm = self
+            .signing_keys
+            .signing_algorithm(realm_id, Some(audience))
+            .await?;
+        self.sign(realm_id, algorithm, &claims).await
     }
 
     #[instrument(skip_all, fields(telemetry = "span"))]
-    async fn validate_access_token(&self, token: &str) -> Result<AccessTokenClaims> {
-        let validation = Validation::new(Algorithm::RS256);
+    async fn validate_access_token(
+        &self,
+        realm_id: Uuid,
+        token: &str,
+    ) -> Result<AccessTokenClaims> {
+        // Exchanged tokens carry `aud`; callers decide whether they accept it.
+        self.verify(realm_id, token, |validation| {
+            validation.validate_aud = false
+        })
+        .await
+    }
 
-        let token_data = decode::<AccessTokenClaims>(token, &self.decoding_key, &validation)
-            .map_err(|_| Error::InvalidCredentials)?;
+    fn unverified_session_id(&self, token: &str) -> Option<Uuid> {
+        dangerous::insecure_decode::<AccessTokenClaims>(token)
+            .ok()
+            .map(|data| data.claims.sid)
+    }
 
-        Ok(token_data.claims)
+    #[instrument(skip_all, fields(telemetry = "span"))]
+    async fn validate_id_token_hint(&self, realm_id: Uuid, token: &str) -> Result<IdTokenClaims> {
+        let issuer = self.issuer.clone();
+        self.verify(realm_id, token, |validation| {
+            validation.validate_exp = false;
+            validation.validate_aud = false;
+            validation.set_issuer(&[issuer]);
+        })
+        .await
+    }
+
+    #[instrument(skip_all, fields(telemetry = "span"))]
+    async fn create_logout_token(
+        &self,
+        realm_id: Uuid,
+        client_id: &str,
+        subject: Uuid,
+        session_id: Option<Uuid>,
+    ) -> Result<String> {
+        let now = Utc::now();
+        let claims = LogoutTokenClaims {
+            iss: self.issuer.clone(),
+            aud: client_id.to_string(),
+            iat: now.timestamp(),
+            exp: (now + Duration::seconds(LOGOUT_TOKEN_TTL_SECS)).timestamp(),
+            jti: Uuid::new_v4().to_string(),
+            sub: subject.to_string(),
+            sid: session_id,
+            events: json!({ BACKCHANNEL_LOGOUT_EVENT: {} }),
+        };
+
+        let algorithm = self
+            .signing_keys
+            .signing_algorithm(realm_id, Some(client_id))
+            .await?;
+        let key = self.signing_keys.signing_key(realm_id, algorithm).await?;
+        let mut header = Header::new(key.algorithm);
+        header.kid = Some(key.kid.clone());
+        header.typ = Some("logout+jwt".to_string());
+
+        encode(&header, &claims, &key.encoding_key).map_err(|e| Error::Unexpected(e.into()))
     }
 
-    fn get_key_id(&self) -> &str {
-        &self.key_id
+    fn access_token_ttl_secs(&self) -> i64 {
+        self.access_token_ttl_secs
     }
 
-    fn get_jwks(&self) -> Result<serde_json::Value> {
-        // 1. Parse the PEM to get the raw RSA components
-        let public_key = RsaPublicKey::from_public_key_pem(&self.public_key_pem)
-            .map_err(|e| Error::Unexpected(anyhow::anyhow!("Failed to parse public key: {}", e)))?;
-
-        // 2. Extract Modulus (n) and Exponent (e)
-        let n = public_key.n();
-        let e = public_key.e();
-
-        // 3. Convert to Base64URL (Big Endian)
-        let n_b64 = URL_SAFE_NO_PAD.encode(n.to_be_bytes());
-        let e_b64 = URL_SAFE_NO_PAD.encode(e.to_be_bytes());
-
-        // 4. Construct the JWKS JSON
-        Ok(json!({
-            "keys": [{
-                "kty": "RSA",
-                "use": "sig",
-                "kid": self.key_id,
-                "alg": "RS256",
-                "n": n_b64,
-                "e": e_b64
-            }]
-        }))
+    async fn get_jwks(&self, realm_id: &Uuid) -> Result<serde_json::Value> {
+        self.signing_keys.jwks(*realm_id).await
     }
 }
@@ -1,3 +1,2 @@
 mod argon2_hasher;
 pub mod jwt_service;
-pub mod key_manager;
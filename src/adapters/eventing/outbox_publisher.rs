@@ -6,6 +6,8 @@ use chrono::Utc;
 use tracing::error;
 use uuid::Uuid;
 
+/// Writes published events to the outbox outside of any transaction. Events
+/// that name their realm reach that realm's webhook subscriptions.
 #[derive(Clone)]
 pub struct OutboxEventPublisher {
     db: Database,
@@ -23,14 +25,15 @@ impl EventPublisher for OutboxEventPublisher {
         let event_id = Uuid::new_v4();
         let occurred_at = Utc::now();
         let event_type = event.event_type().to_string();
-        let payload_json = event.envelope_json(event_id, occurred_at, None, None);
+        let realm_id = event.realm_id();
+        let payload_json = event.envelope_json(event_id, occurred_at, realm_id, None);
 
         let result = sqlx::query(
             "INSERT INTO event_outbox (id, realm_id, event_type, event_version, occurred_at, payload_json, status, attempt_count, next_attempt_at)
              VALUES (?, ?, ?, ?, ?, ?, 'pending', 0, ?)",
         )
         .bind(event_id.to_string())
-        .bind(None::<String>)
+        .bind(realm_id.map(|id| id.to_string()))
         .bind(event_type)
         .bind(EVENT_VERSION_V1)
         .bind(occurred_at.to_rfc3339())
@@ -1,11 +1,14 @@
 use crate::adapters::observability::telemetry_store::TelemetryDatabase;
 use crate::adapters::persistence::connection::Database;
+use crate::domain::oidc::{BackchannelLogoutDelivery, BACKCHANNEL_LOGOUT_EVENT_TYPE};
+use crate::ports::token_service::TokenService;
 use anyhow::anyhow;
 use chrono::{DateTime, Duration as ChronoDuration, Utc};
 use rand::RngExt;
 use serde::Deserialize;
 use sqlx::Row;
 use std::error::Error as StdError;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tracing::{error, info, warn};
 use uuid::Uuid;
@@ -27,6 +30,7 @@ struct OutboxRow {
 pub struct OutboxWorker {
     db: Database,
     telemetry_db: TelemetryDatabase,
+    token_service: Arc<dyn TokenService>,
     http_client: reqwest::Client,
     poll_interval: Duration,
     batch_size: i64,
@@ -45,7 +49,11 @@ struct WebhookFailureLog<'a> {
 }
 
 impl OutboxWorker {
-    pub fn new(db: Database, telemetry_db: TelemetryDatabase) -> Self {
+    pub fn new(
+        db: Database,
+        telemetry_db: TelemetryDatabase,
+        token_service: Arc<dyn TokenService>,
+    ) -> Self {
         let http_client = reqwest::Client::builder()
             .timeout(Duration::from_secs(5))
             .build()
@@ -53,6 +61,7 @@ impl OutboxWorker {
         Self {
             db,
             telemetry_db,
+            token_service,
             http_client,
             poll_interval: Duration::from_millis(500),
             batch_size: 50,
@@ -150,40 +159,48 @@ impl OutboxWorker {
         let attempt = outbox.attempt_count + 1;
         let mut failures: Vec<String> = Vec::new();
 
-        let webhook_targets = self.fetch_webhook_targets(outbox).await?;
-
-        if webhook_targets.is_empty() {
-            self.log_delivery(DeliveryLogEntry {
-                outbox,
-                target_type: "none",
-                target_id: "none",
-                attempt,
-                response_status: None,
-                response_body: None,
-                error: Some("no_targets".to_string()),
-                error_chain: None,
-                latency_ms: 0,
-            })
-            .await?;
+        // Back-channel logout rows are addressed to a client, not to webhook
+        // subscriptions.
+        if outbox.event_type == BACKCHANNEL_LOGOUT_EVENT_TYPE {
+            if let Err(err) = self.dispatch_backchannel_logout(outbox, attempt).await {
+                failures.push(err.to_string());
+            }
+        } else {
+            let webhook_targets = self.fetch_webhook_targets(outbox).await?;
 
-            sqlx::query(
-                "UPDATE event_outbox
-                 SET status = 'skipped', attempt_count = ?, last_error = ?, locked_at = NULL, locked_by = NULL
-                 WHERE id = ?",
-            )
-            .bind(attempt)
-            .bind("no_targets")
-            .bind(&outbox.id)
-            .execute(&*self.db)
-            .await?;
+            if webhook_targets.is_empty() {
+                self.log_delivery(DeliveryLogEntry {
+                    outbox,
+                    target_type: "none",
+                    target_id: "none",
+                    attempt,
+                    response_status: None,
+                    response_body: None,
+                    error: Some("no_targets".to_string()),
+                    error_chain: None,
+                    latency_ms: 0,
+                })
+                .await?;
 
-            return Ok(());
-        }
+                sqlx::query(
+                    "UPDATE event_outbox
+                     SET status = 'skipped', attempt_count = ?, last_error = ?, locked_at = NULL, locked_by = NULL
+                     WHERE id = ?",
+                )
+                .bind(attempt)
+                .bind("no_targets")
+                .bind(&outbox.id)
+                .execute(&*self.db)
+                .await?;
+
+                return Ok(());
+            }
 
-        for target in webhook_targets {
-            match self.dispatch_webhook(outbox, attempt, &target).await {
-                Ok(()) => {}
-                Err(err) => failures.push(err.to_string()),
+            for target in webhook_targets {
+                match self.dispatch_webhook(outbox, attempt, &target).await {
+                    Ok(()) => {}
+                    Err(err) => failures.push(err.to_string()),
+                }
             }
         }
 
@@ -413,6 +430,93 @@ impl OutboxWorker {
         Ok(())
     }
 
+    /// Signs a logout token for the client and POSTs it as
+    /// `application/x-www-form-urlencoded` (OIDC Back-Channel Logout 1.0).
+    async fn dispatch_backchannel_logout(
+        &self,
+        outbox: &OutboxRow,
+        attempt: i64,
+    ) -> anyhow::Result<()> {
+        let envelope: serde_json::Value = serde_json::from_str(&outbox.payload_json)?;
+        let delivery: BackchannelLogoutDelivery = serde_json::from_value(envelope["data"].clone())?;
+        let realm_id: Uuid = outbox
+            .realm_id
+            .as_deref()
+            .ok_or_else(|| anyhow!("back-channel logout event has no realm"))?
+            .parse()?;
+
+        let logout_token = self
+            .token_service
+            .create_logout_token(
+                realm_id,
+                &delivery.client_id,
+                delivery.sub,
+                Some(delivery.sid),
+            )
+            .await?;
+        let body = url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("logout_token", &logout_token)
+            .finish();
+
+        let start = Instant::now();
+        let response = self
+            .http_client
+            .post(&delivery.backchannel_logout_uri)
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body(body)
+            .send()
+            .await;
+        let latency_ms = start.elapsed().as_millis() as i64;
+
+        match response {
+            Ok(resp) => {
+                let status_code = resp.status().as_u16() as i64;
+                let body = resp.text().await.unwrap_or_default();
+                let is_success = (200..300).contains(&status_code);
+                let error = (!is_success).then(|| format!("http_{}", status_code));
+
+                self.log_delivery(DeliveryLogEntry {
+                    outbox,
+                    target_type: "backchannel_logout",
+                    target_id: &delivery.client_id,
+                    attempt,
+                    response_status: Some(status_code),
+                    response_body: Some(body),
+                    error,
+                    error_chain: None,
+                    latency_ms,
+                })
+                .await?;
+
+                if !is_success {
+                    return Err(anyhow!(
+                        "back-channel logout to {} failed with status {}",
+                        delivery.client_id,
+                        status_code
+                    ));
+                }
+            }
+            Err(err) => {
+                let error_chain = collect_error_chain(&err);
+                self.log_delivery(DeliveryLogEntry {
+                    outbox,
+                    target_type: "backchannel_logout",
+                    target_id: &delivery.client_id,
+                    attempt,
+                    response_status: None,
+                    response_body: None,
+                    error: Some(err.to_string()),
+                    error_chain: serialize_error_chain(&error_chain),
+                    latency_ms,
+                })
+                .await?;
+                return Err(err.into());
+            }
+        }
+
+        Ok(())
+    }
+
     async fn record_webhook_success(&self, endpoint_id: &str) -> anyhow::Result<()> {
         sqlx::query(
             "UPDATE webhook_endpoints
@@ -0,0 +1 @@
+pub mod tcp_ldap_connector;
@@ -0,0 +1,239 @@
+use crate::domain::ldap::ber::element_length;
+use crate::domain::ldap::{
+    BindRequest, Control, LdapMessage, LdapResult, Modification, ModifyRequest, PagedResults,
+    ProtocolOp, SearchEntry, SearchRequest,
+};
+use crate::error::{Error, Result};
+use crate::ports::ldap_client::{LdapConnection, LdapConnector};
+use async_trait::async_trait;
+use rustls::pki_types::ServerName;
+use rustls::{ClientConfig, RootCertStore};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use url::Url;
+
+const DEFAULT_PORT: u16 = 389;
+const DEFAULT_TLS_PORT: u16 = 636;
+const SEARCH_PAGE_SIZE: i64 = 500;
+
+/// Speaks LDAPv3 over TCP, or TLS for `ldaps://` URLs. Servers are verified
+/// against the bundled web PKI roots.
+pub struct TcpLdapConnector {
+    timeout: Duration,
+    tls: Arc<ClientConfig>,
+}
+
+impl TcpLdapConnector {
+    pub fn new(timeout: Duration) -> Self {
+        let roots = RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+        };
+        let tls =
+            ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .expect("ring supports the default TLS versions")
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+        Self {
+            timeout,
+            tls: Arc::new(tls),
+        }
+    }
+}
+
+trait LdapStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> LdapStream for T {}
+
+#[async_trait]
+impl LdapConnector for TcpLdapConnector {
+    async fn connect(&self, url: &str) -> Result<Box<dyn LdapConnection>> {
+        let parsed = Url::parse(url)
+            .map_err(|_| Error::Validation(format!("Invalid LDAP URL '{}'", url)))?;
+        let secure = match parsed.scheme() {
+            "ldap" => false,
+            "ldaps" => true,
+            other => {
+                return Err(Error::Validation(format!(
+                    "Unsupported LDAP URL scheme '{}'",
+                    other
+                )))
+            }
+        };
+        let host = parsed
+            .host_str()
+            .ok_or_else(|| Error::Validation(format!("LDAP URL '{}' has no host", url)))?
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string();
+        let port = parsed.port().unwrap_or(if secure {
+            DEFAULT_TLS_PORT
+        } else {
+            DEFAULT_PORT
+        });
+
+        let tcp = tokio::time::timeout(self.timeout, TcpStream::connect((host.as_str(), port)))
+            .await
+            .map_err(|_| unavailable(format!("connecting to {}:{} timed out", host, port)))?
+            .map_err(|err| unavailable(format!("cannot connect to {}:{}: {}", host, port, err)))?;
+        tcp.set_nodelay(true).ok();
+
+        let stream: Box<dyn LdapStream> = if secure {
+            let server_name = ServerName::try_from(host.clone())
+                .map_err(|_| Error::Validation(format!("Invalid LDAP host '{}'", host)))?;
+            let tls = tokio::time::timeout(
+                self.timeout,
+                TlsConnector::from(self.tls.clone()).connect(server_name, tcp),
+            )
+            .await
+            .map_err(|_| unavailable(format!("TLS handshake with {} timed out", host)))?
+            .map_err(|err| unavailable(format!("TLS handshake with {} failed: {}", host, err)))?;
+            Box::new(tls)
+        } else {
+            Box::new(tcp)
+        };
+
+        Ok(Box::new(TcpLdapConnection {
+            stream,
+            buffer: Vec::new(),
+            next_message_id: 1,
+            timeout: self.timeout,
+        }))
+    }
+}
+
+fn unavailable(detail: String) -> Error {
+    Error::System(format!("LDAP server unavailable: {}", detail))
+}
+
+struct TcpLdapConnection {
+    stream: Box<dyn LdapStream>,
+    buffer: Vec<u8>,
+    next_message_id: i64,
+    timeout: Duration,
+}
+
+impl TcpLdapConnection {
+    async fn send(&mut self, op: ProtocolOp, controls: Vec<Control>) -> Result<i64> {
+        let message_id = self.next_message_id;
+        self.next_message_id += 1;
+        let mut message = LdapMessage::new(message_id, op);
+        message.controls = controls;
+        tokio::time::timeout(self.timeout, self.stream.write_all(&message.encode()))
+            .await
+            .map_err(|_| unavailable("write timed out".to_string()))?
+            .map_err(|err| unavailable(format!("write failed: {}", err)))?;
+        Ok(message_id)
+    }
+
+    /// Reads the next message for `message_id`, skipping stray ones.
+    async fn receive(&mut self, message_id: i64) -> Result<LdapMessage> {
+        loop {
+            if let Some(len) = element_length(&self.buffer)? {
+                let message = LdapMessage::decode(&self.buffer[..len])?;
+                self.buffer.drain(..len);
+                if message.message_id == message_id {
+                    return Ok(message);
+                }
+                if message.message_id == 0 {
+                    return Err(unavailable("the server ended the connection".to_string()));
+                }
+                continue;
+            }
+
+            let mut chunk = [0u8; 8192];
+            let read = tokio::time::timeout(self.timeout, self.stream.read(&mut chunk))
+                .await
+                .map_err(|_| unavailable("response timed out".to_string()))?
+                .map_err(|err| unavailable(format!("read failed: {}", err)))?;
+            if read == 0 {
+                return Err(unavailable("the server closed the connection".to_string()));
+            }
+            self.buffer.extend_from_slice(&chunk[..read]);
+        }
+    }
+}
+
+fn unexpected(op: &ProtocolOp) -> Error {
+    Error::System(format!("Unexpected LDAP response: {:?}", op))
+}
+
+#[async_trait]
+impl LdapConnection for TcpLdapConnection {
+    async fn simple_bind(&mut self, dn: &str, password: &str) -> Result<LdapResult> {
+        let message_id = self
+            .send(
+                ProtocolOp::BindRequest(BindRequest {
+                    name: dn.to_string(),
+                    password: password.to_string(),
+                }),
+                Vec::new(),
+            )
+            .await?;
+        match self.receive(message_id).await?.op {
+            ProtocolOp::BindResponse(result) => Ok(result),
+            other => Err(unexpected(&other)),
+        }
+    }
+
+    async fn search(&mut self, request: SearchRequest) -> Result<Vec<SearchEntry>> {
+        let mut entries = Vec::new();
+        let mut cookie = Vec::new();
+        loop {
+            let paging = PagedResults {
+                size: SEARCH_PAGE_SIZE,
+                cookie,
+            };
+            let message_id = self
+                .send(
+                    ProtocolOp::SearchRequest(request.clone()),
+                    vec![paging.to_control()],
+                )
+                .await?;
+            let done = loop {
+                let message = self.receive(message_id).await?;
+                match message.op {
+                    ProtocolOp::SearchResultEntry(entry) => entries.push(entry),
+                    ProtocolOp::SearchResultReference(_) => {}
+                    ProtocolOp::SearchResultDone(result) => break (result, message.controls),
+                    other => return Err(unexpected(&other)),
+                }
+            };
+            let (result, controls) = done;
+            if !result.is_success() {
+                return Err(Error::System(format!("LDAP search failed: {}", result)));
+            }
+            // Servers without paging support ignore the (non-critical)
+            // control and return everything at once.
+            match PagedResults::from_controls(&controls)? {
+                Some(page) if !page.cookie.is_empty() => cookie = page.cookie,
+                _ => return Ok(entries),
+            }
+        }
+    }
+
+    async fn modify(&mut self, dn: &str, changes: Vec<Modification>) -> Result<LdapResult> {
+        let message_id = self
+            .send(
+                ProtocolOp::ModifyRequest(ModifyRequest {
+                    dn: dn.to_string(),
+                    changes,
+                }),
+                Vec::new(),
+            )
+            .await?;
+        match self.receive(message_id).await?.op {
+            ProtocolOp::ModifyResponse(result) => Ok(result),
+            other => Err(unexpected(&other)),
+        }
+    }
+
+    async fn unbind(&mut self) -> Result<()> {
+        self.send(ProtocolOp::UnbindRequest, Vec::new()).await?;
+        self.stream.shutdown().await.ok();
+        Ok(())
+    }
+}
@@ -2,9 +2,11 @@ pub(crate) mod auth;
 pub mod cache;
 pub mod crypto;
 pub mod eventing;
+pub mod ldap;
 pub mod logging;
 pub mod observability;
 pub mod persistence;
+pub mod sms;
 pub mod web;
 
 pub use persistence::{
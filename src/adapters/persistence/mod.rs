@@ -3,6 +3,9 @@ pub mod migrate;
 pub mod sqlite_audit_repository;
 pub mod sqlite_auth_session_action_repository;
 pub mod sqlite_auth_session_repository;
+pub mod sqlite_client_registration_repository;
+pub mod sqlite_consent_repository;
+pub mod sqlite_device_authorization_repository;
 pub mod sqlite_federated_identity_repository;
 pub mod sqlite_flow_repository;
 pub mod sqlite_flow_store;
@@ -17,6 +20,10 @@ pub mod sqlite_oidc_repository;
 pub mod sqlite_outbox_repository;
 pub mod sqlite_passkey_challenge_repository;
 pub mod sqlite_passkey_credential_repository;
+pub mod sqlite_password_history_repository;
+pub mod sqlite_password_policy_repository;
+pub mod sqlite_protocol_mapper_repository;
+pub mod sqlite_pushed_authorization_request_repository;
 pub mod sqlite_rbac_repository;
 pub mod sqlite_realm_email_settings_repository;
 pub mod sqlite_realm_idp_settings_repository;
@@ -25,9 +32,14 @@ pub mod sqlite_realm_recovery_settings_repository;
 pub mod sqlite_realm_repository;
 pub mod sqlite_realm_security_headers_repository;
 pub mod sqlite_recovery_attempt_repository;
+pub mod sqlite_saml_service_provider_repository;
+pub mod sqlite_scim_repository;
 pub mod sqlite_session_repository;
+pub mod sqlite_signing_key_repository;
 pub mod sqlite_theme_repository;
+pub mod sqlite_totp_credential_repository;
 pub mod sqlite_user_email_repository;
+pub mod sqlite_user_federation_repository;
 pub mod sqlite_user_phone_number_repository;
 pub mod sqlite_user_repository;
 pub mod sqlite_webhook_repository;
@@ -24,6 +24,7 @@ struct AuditEventRow {
     id: String,
     realm_id: String,
     actor_user_id: Option<String>,
+    impersonator_user_id: Option<String>,
     action: String,
     target_type: String,
     target_id: Option<String>,
@@ -45,6 +46,9 @@ impl AuditEventRow {
             actor_user_id: self
                 .actor_user_id
                 .and_then(|value| Uuid::parse_str(&value).ok()),
+            impersonator_user_id: self
+                .impersonator_user_id
+                .and_then(|value| Uuid::parse_str(&value).ok()),
             action: self.action,
             target_type: self.target_type,
             target_id: self.target_id,
@@ -64,12 +68,13 @@ impl AuditRepository for SqliteAuditRepository {
         let metadata = serde_json::to_string(&event.metadata).unwrap_or_else(|_| "{}".to_string());
 
         sqlx::query(
-            "INSERT INTO audit_events (id, realm_id, actor_user_id, action, target_type, target_id, metadata, created_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            "INSERT INTO audit_events (id, realm_id, actor_user_id, impersonator_user_id, action, target_type, target_id, metadata, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
         .bind(event.id.to_string())
         .bind(event.realm_id.to_string())
         .bind(event.actor_user_id.map(|id| id.to_string()))
+        .bind(event.impersonator_user_id.map(|id| id.to_string()))
         .bind(&event.action)
         .bind(&event.target_type)
         .bind(&event.target_id)
@@ -88,7 +93,7 @@ impl AuditRepository for SqliteAuditRepository {
     )]
     async fn list_recent(&self, realm_id: &Uuid, limit: usize) -> Result<Vec<AuditEvent>> {
         let rows: Vec<AuditEventRow> = sqlx::query_as(
-            "SELECT id, realm_id, actor_user_id, action, target_type, target_id, metadata, created_at
+            "SELECT id, realm_id, actor_user_id, impersonator_user_id, action, target_type, target_id, metadata, created_at
              FROM audit_events
              WHERE realm_id = ?
              ORDER BY created_at DESC
@@ -222,7 +227,7 @@ impl AuditRepository for SqliteAuditRepository {
         }
 
         let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(
-            "SELECT id, realm_id, actor_user_id, action, target_type, target_id, metadata, created_at
+            "SELECT id, realm_id, actor_user_id, impersonator_user_id, action, target_type, target_id, metadata, created_at
              FROM audit_events
              WHERE realm_id = ",
         );
@@ -263,7 +268,7 @@ impl AuditRepository for SqliteAuditRepository {
         }
 
         let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(
-            "SELECT id, realm_id, actor_user_id, action, target_type, target_id, metadata, created_at
+            "SELECT id, realm_id, actor_user_id, impersonator_user_id, action, target_type, target_id, metadata, created_at
              FROM audit_events
              WHERE realm_id = ",
         );
@@ -0,0 +1,347 @@
+use crate::adapters::persistence::connection::Database;
+use crate::domain::client_registration::{
+    ClientRegistration, ClientRegistrationPolicy, InitialAccessToken,
+};
+use crate::error::{Error, Result};
+use crate::ports::client_registration_repository::ClientRegistrationRepository;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tracing::instrument;
+use uuid::Uuid;
+
+pub struct SqliteClientRegistrationRepository {
+    pool: Database,
+}
+
+impl SqliteClientRegistrationRepository {
+    pub fn new(pool: Database) -> Self {
+        Self { pool }
+    }
+}
+
+fn parse_uuid(value: &str, what: &str) -> Result<Uuid> {
+    Uuid::parse_str(value).map_err(|_| Error::System(format!("Invalid {} id", what)))
+}
+
+fn parse_list(value: &str, what: &str) -> Result<Vec<String>> {
+    serde_json::from_str(value).map_err(|err| Error::System(format!("Invalid {}: {}", what, err)))
+}
+
+fn list_json(values: &[String]) -> Result<String> {
+    serde_json::to_string(values).map_err(|e| Error::Unexpected(e.into()))
+}
+
+#[derive(sqlx::FromRow)]
+struct PolicyRow {
+    realm_id: String,
+    allowed_redirect_hosts: String,
+    require_https: bool,
+    allowed_grant_types: String,
+}
+
+impl TryFrom<PolicyRow> for ClientRegistrationPolicy {
+    type Error = Error;
+
+    fn try_from(row: PolicyRow) -> Result<Self> {
+        Ok(Self {
+            realm_id: parse_uuid(&row.realm_id, "client registration policy realm")?,
+            allowed_redirect_hosts: parse_list(&row.allowed_redirect_hosts, "redirect hosts")?,
+            require_https: row.require_https,
+            allowed_grant_types: parse_list(&row.allowed_grant_types, "grant types")?,
+        })
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct InitialAccessTokenRow {
+    id: String,
+    realm_id: String,
+    token_hash: String,
+    description: Option<String>,
+    expires_at: DateTime<Utc>,
+    remaining_uses: i64,
+    created_at: DateTime<Utc>,
+}
+
+impl TryFrom<InitialAccessTokenRow> for InitialAccessToken {
+    type Error = Error;
+
+    fn try_from(row: InitialAccessTokenRow) -> Result<Self> {
+        Ok(Self {
+            id: parse_uuid(&row.id, "initial access token")?,
+            realm_id: parse_uuid(&row.realm_id, "initial access token realm")?,
+            token_hash: row.token_hash,
+            description: row.description,
+            expires_at: row.expires_at,
+            remaining_uses: row.remaining_uses,
+            created_at: row.created_at,
+        })
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct RegistrationRow {
+    client_id: String,
+    realm_id: String,
+    registration_token_hash: String,
+    client_name: Option<String>,
+    grant_types: String,
+    initial_access_token_id: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+impl TryFrom<RegistrationRow> for ClientRegistration {
+    type Error = Error;
+
+    fn try_from(row: RegistrationRow) -> Result<Self> {
+        Ok(Self {
+            client_id: parse_uuid(&row.client_id, "registered client")?,
+            realm_id: parse_uuid(&row.realm_id, "client registration realm")?,
+            registration_token_hash: row.registration_token_hash,
+            client_name: row.client_name,
+            grant_types: parse_list(&row.grant_types, "grant types")?,
+            initial_access_token_id: row
+                .initial_access_token_id
+                .as_deref()
+                .map(|id| parse_uuid(id, "initial access token"))
+                .transpose()?,
+            created_at: row.created_at,
+        })
+    }
+}
+
+#[async_trait]
+impl ClientRegistrationRepository for SqliteClientRegistrationRepository {
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "realm_client_registration_policies",
+            db_op = "select"
+        )
+    )]
+    async fn find_policy(&self, realm_id: &Uuid) -> Result<Option<ClientRegistrationPolicy>> {
+        let row: Option<PolicyRow> =
+            sqlx::query_as("SELECT * FROM realm_client_registration_policies WHERE realm_id = ?")
+                .bind(realm_id.to_string())
+                .fetch_optional(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        row.map(TryInto::try_into).transpose()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "realm_client_registration_policies",
+            db_op = "upsert"
+        )
+    )]
+    async fn upsert_policy(&self, policy: &ClientRegistrationPolicy) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO realm_client_registration_policies (
+                realm_id, allowed_redirect_hosts, require_https, allowed_grant_types
+            ) VALUES (?, ?, ?, ?)
+            ON CONFLICT(realm_id) DO UPDATE SET
+                allowed_redirect_hosts = excluded.allowed_redirect_hosts,
+                require_https = excluded.require_https,
+                allowed_grant_types = excluded.allowed_grant_types",
+        )
+        .bind(policy.realm_id.to_string())
+        .bind(list_json(&policy.allowed_redirect_hosts)?)
+        .bind(policy.require_https)
+        .bind(list_json(&policy.allowed_grant_types)?)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "client_initial_access_tokens",
+            db_op = "insert"
+        )
+    )]
+    async fn create_initial_access_token(&self, token: &InitialAccessToken) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO client_initial_access_tokens (
+                id, realm_id, token_hash, description, expires_at, remaining_uses, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(token.id.to_string())
+        .bind(token.realm_id.to_string())
+        .bind(&token.token_hash)
+        .bind(&token.description)
+        .bind(token.expires_at)
+        .bind(token.remaining_uses)
+        .bind(token.created_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "client_initial_access_tokens",
+            db_op = "select"
+        )
+    )]
+    async fn list_initial_access_tokens(&self, realm_id: &Uuid) -> Result<Vec<InitialAccessToken>> {
+        let rows: Vec<InitialAccessTokenRow> = sqlx::query_as(
+            "SELECT * FROM client_initial_access_tokens
+             WHERE realm_id = ?
+             ORDER BY created_at DESC",
+        )
+        .bind(realm_id.to_string())
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        rows.into_iter().map(TryInto::try_into).collect()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "client_initial_access_tokens",
+            db_op = "select"
+        )
+    )]
+    async fn find_initial_access_token(
+        &self,
+        realm_id: &Uuid,
+        token_hash: &str,
+    ) -> Result<Option<InitialAccessToken>> {
+        let row: Option<InitialAccessTokenRow> = sqlx::query_as(
+            "SELECT * FROM client_initial_access_tokens WHERE realm_id = ? AND token_hash = ?",
+        )
+        .bind(realm_id.to_string())
+        .bind(token_hash)
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        row.map(TryInto::try_into).transpose()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "client_initial_access_tokens",
+            db_op = "update"
+        )
+    )]
+    async fn consume_initial_access_token(&self, id: &Uuid, now: DateTime<Utc>) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE client_initial_access_tokens
+             SET remaining_uses = remaining_uses - 1
+             WHERE id = ? AND remaining_uses > 0 AND expires_at > ?",
+        )
+        .bind(id.to_string())
+        .bind(now)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "client_initial_access_tokens",
+            db_op = "delete"
+        )
+    )]
+    async fn delete_initial_access_token(&self, realm_id: &Uuid, id: &Uuid) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM client_initial_access_tokens WHERE realm_id = ? AND id = ?")
+                .bind(realm_id.to_string())
+                .bind(id.to_string())
+                .execute(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "client_registrations",
+            db_op = "insert"
+        )
+    )]
+    async fn create_registration(&self, registration: &ClientRegistration) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO client_registrations (
+                client_id, realm_id, registration_token_hash, client_name, grant_types,
+                initial_access_token_id, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(registration.client_id.to_string())
+        .bind(registration.realm_id.to_string())
+        .bind(&registration.registration_token_hash)
+        .bind(&registration.client_name)
+        .bind(list_json(&registration.grant_types)?)
+        .bind(
+            registration
+                .initial_access_token_id
+                .map(|id| id.to_string()),
+        )
+        .bind(registration.created_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "client_registrations",
+            db_op = "select"
+        )
+    )]
+    async fn find_registration(&self, client_id: &Uuid) -> Result<Option<ClientRegistration>> {
+        let row: Option<RegistrationRow> =
+            sqlx::query_as("SELECT * FROM client_registrations WHERE client_id = ?")
+                .bind(client_id.to_string())
+                .fetch_optional(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        row.map(TryInto::try_into).transpose()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "client_registrations",
+            db_op = "update"
+        )
+    )]
+    async fn update_registration(&self, registration: &ClientRegistration) -> Result<()> {
+        sqlx::query(
+            "UPDATE client_registrations
+             SET registration_token_hash = ?, client_name = ?, grant_types = ?
+             WHERE client_id = ?",
+        )
+        .bind(&registration.registration_token_hash)
+        .bind(&registration.client_name)
+        .bind(list_json(&registration.grant_types)?)
+        .bind(registration.client_id.to_string())
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+use crate::adapters::persistence::connection::Database;
+use crate::domain::consent_grant::ConsentGrant;
+use crate::error::{Error, Result};
+use crate::ports::consent_repository::ConsentRepository;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tracing::instrument;
+use uuid::Uuid;
+
+pub struct SqliteConsentRepository {
+    pool: Database,
+}
+
+impl SqliteConsentRepository {
+    pub fn new(pool: Database) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ConsentGrantRecord {
+    id: String,
+    realm_id: String,
+    user_id: String,
+    client_id: String,
+    scopes: String,
+    granted_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl ConsentGrantRecord {
+    fn into_domain(self) -> Result<ConsentGrant> {
+        Ok(ConsentGrant {
+            id: Uuid::parse_str(&self.id)
+                .map_err(|_| Error::System("Invalid consent grant id".to_string()))?,
+            realm_id: Uuid::parse_str(&self.realm_id)
+                .map_err(|_| Error::System("Invalid consent realm id".to_string()))?,
+            user_id: Uuid::parse_str(&self.user_id)
+                .map_err(|_| Error::System("Invalid consent user id".to_string()))?,
+            client_id: self.client_id,
+            scopes: serde_json::from_str(&self.scopes)
+                .map_err(|_| Error::System("Invalid consent scopes".to_string()))?,
+            granted_at: self.granted_at,
+            updated_at: self.updated_at,
+        })
+    }
+}
+
+#[async_trait]
+impl ConsentRepository for SqliteConsentRepository {
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "consent_grants", db_op = "select")
+    )]
+    async fn find(
+        &self,
+        realm_id: &Uuid,
+        user_id: &Uuid,
+        client_id: &str,
+    ) -> Result<Option<ConsentGrant>> {
+        let record: Option<ConsentGrantRecord> = sqlx::query_as(
+            "SELECT * FROM consent_grants WHERE realm_id = ? AND user_id = ? AND client_id = ?",
+        )
+        .bind(realm_id.to_string())
+        .bind(user_id.to_string())
+        .bind(client_id)
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+
+        record.map(ConsentGrantRecord::into_domain).transpose()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "consent_grants", db_op = "select")
+    )]
+    async fn list_for_user(&self, realm_id: &Uuid, user_id: &Uuid) -> Result<Vec<ConsentGrant>> {
+        let records: Vec<ConsentGrantRecord> = sqlx::query_as(
+            "SELECT * FROM consent_grants WHERE realm_id = ? AND user_id = ? ORDER BY client_id",
+        )
+        .bind(realm_id.to_string())
+        .bind(user_id.to_string())
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+
+        records
+            .into_iter()
+            .map(ConsentGrantRecord::into_domain)
+            .collect()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "consent_grants", db_op = "upsert")
+    )]
+    async fn save(&self, grant: &ConsentGrant) -> Result<()> {
+        let scopes =
+            serde_json::to_string(&grant.scopes).map_err(|e| Error::Unexpected(e.into()))?;
+        sqlx::query(
+            "INSERT INTO consent_grants (
+                id, realm_id, user_id, client_id, scopes, granted_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(realm_id, user_id, client_id) DO UPDATE SET
+                scopes = excluded.scopes,
+                updated_at = excluded.updated_at",
+        )
+        .bind(grant.id.to_string())
+        .bind(grant.realm_id.to_string())
+        .bind(grant.user_id.to_string())
+        .bind(&grant.client_id)
+        .bind(scopes)
+        .bind(grant.granted_at)
+        .bind(grant.updated_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "consent_grants", db_op = "delete")
+    )]
+    async fn delete(&self, realm_id: &Uuid, user_id: &Uuid, client_id: &str) -> Result<bool> {
+        let result = sqlx::query(
+            "DELETE FROM consent_grants WHERE realm_id = ? AND user_id = ? AND client_id = ?",
+        )
+        .bind(realm_id.to_string())
+        .bind(user_id.to_string())
+        .bind(client_id)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+
+        Ok(result.rows_affected() == 1)
+    }
+}
@@ -0,0 +1,239 @@
+use crate::adapters::persistence::connection::Database;
+use crate::domain::oidc::{DeviceAuthorization, DeviceAuthorizationStatus};
+use crate::error::{Error, Result};
+use crate::ports::device_authorization_repository::DeviceAuthorizationRepository;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tracing::instrument;
+use uuid::Uuid;
+
+pub struct SqliteDeviceAuthorizationRepository {
+    pool: Database,
+}
+
+impl SqliteDeviceAuthorizationRepository {
+    pub fn new(pool: Database) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct DeviceAuthorizationRow {
+    device_code: String,
+    user_code: String,
+    realm_id: String,
+    client_id: String,
+    scope: Option<String>,
+    status: String,
+    user_id: Option<String>,
+    interval_secs: i64,
+    last_polled_at: Option<DateTime<Utc>>,
+    expires_at: DateTime<Utc>,
+    created_at: DateTime<Utc>,
+}
+
+impl TryFrom<DeviceAuthorizationRow> for DeviceAuthorization {
+    type Error = Error;
+
+    fn try_from(row: DeviceAuthorizationRow) -> Result<Self> {
+        Ok(Self {
+            device_code: row.device_code,
+            user_code: row.user_code,
+            realm_id: Uuid::parse_str(&row.realm_id)
+                .map_err(|_| Error::System("Invalid device authorization realm id".into()))?,
+            client_id: row.client_id,
+            scope: row.scope,
+            status: DeviceAuthorizationStatus::try_from(row.status).map_err(Error::System)?,
+            user_id: row
+                .user_id
+                .map(|id| Uuid::parse_str(&id))
+                .transpose()
+                .map_err(|_| Error::System("Invalid device authorization user id".into()))?,
+            interval_secs: row.interval_secs,
+            last_polled_at: row.last_polled_at,
+            expires_at: row.expires_at,
+            created_at: row.created_at,
+        })
+    }
+}
+
+#[async_trait]
+impl DeviceAuthorizationRepository for SqliteDeviceAuthorizationRepository {
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "device_authorizations",
+            db_op = "insert"
+        )
+    )]
+    async fn create(&self, authorization: &DeviceAuthorization) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO device_authorizations (
+                device_code, user_code, realm_id, client_id, scope, status, user_id,
+                interval_secs, last_polled_at, expires_at, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&authorization.device_code)
+        .bind(&authorization.user_code)
+        .bind(authorization.realm_id.to_string())
+        .bind(&authorization.client_id)
+        .bind(&authorization.scope)
+        .bind(authorization.status.as_str())
+        .bind(authorization.user_id.map(|id| id.to_string()))
+        .bind(authorization.interval_secs)
+        .bind(authorization.last_polled_at)
+        .bind(authorization.expires_at)
+        .bind(authorization.created_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "device_authorizations",
+            db_op = "select"
+        )
+    )]
+    async fn find_by_device_code(&self, device_code: &str) -> Result<Option<DeviceAuthorization>> {
+        let row: Option<DeviceAuthorizationRow> =
+            sqlx::query_as("SELECT * FROM device_authorizations WHERE device_code = ?")
+                .bind(device_code)
+                .fetch_optional(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        row.map(TryInto::try_into).transpose()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "device_authorizations",
+            db_op = "select"
+        )
+    )]
+    async fn find_by_user_code(
+        &self,
+        realm_id: &Uuid,
+        user_code: &str,
+    ) -> Result<Option<DeviceAuthorization>> {
+        let row: Option<DeviceAuthorizationRow> = sqlx::query_as(
+            "SELECT * FROM device_authorizations WHERE realm_id = ? AND user_code = ?",
+        )
+        .bind(realm_id.to_string())
+        .bind(user_code)
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        row.map(TryInto::try_into).transpose()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "device_authorizations",
+            db_op = "update"
+        )
+    )]
+    async fn resolve(
+        &self,
+        device_code: &str,
+        status: DeviceAuthorizationStatus,
+        user_id: Option<Uuid>,
+        now: DateTime<Utc>,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE device_authorizations
+             SET status = ?, user_id = ?
+             WHERE device_code = ? AND status = 'pending' AND expires_at > ?",
+        )
+        .bind(status.as_str())
+        .bind(user_id.map(|id| id.to_string()))
+        .bind(device_code)
+        .bind(now)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "device_authorizations",
+            db_op = "update"
+        )
+    )]
+    async fn record_poll(
+        &self,
+        device_code: &str,
+        polled_at: DateTime<Utc>,
+        interval_secs: i64,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE device_authorizations
+             SET last_polled_at = ?, interval_secs = ?
+             WHERE device_code = ?",
+        )
+        .bind(polled_at)
+        .bind(interval_secs)
+        .bind(device_code)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "device_authorizations",
+            db_op = "delete"
+        )
+    )]
+    async fn consume(&self, device_code: &str) -> Result<bool> {
+        let result = sqlx::query(
+            "DELETE FROM device_authorizations WHERE device_code = ? AND status = 'approved'",
+        )
+        .bind(device_code)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "device_authorizations",
+            db_op = "delete"
+        )
+    )]
+    async fn delete_expired_before(&self, cutoff: DateTime<Utc>, batch_size: i64) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM device_authorizations
+             WHERE device_code IN (
+                 SELECT device_code
+                 FROM device_authorizations
+                 WHERE expires_at < ?
+                 ORDER BY created_at ASC
+                 LIMIT ?
+             )",
+        )
+        .bind(cutoff)
+        .bind(batch_size)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(result.rows_affected())
+    }
+}
@@ -1,7 +1,10 @@
 use crate::adapters::persistence::connection::Database;
+use crate::adapters::persistence::transaction::SqliteTransaction;
 use crate::domain::identity_provider::{IdentityProvider, IdentityProviderProtocol};
+use crate::domain::saml::NameIdFormat;
 use crate::error::{Error, Result};
 use crate::ports::identity_provider_repository::IdentityProviderRepository;
+use crate::ports::transaction_manager::Transaction;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use tracing::instrument;
@@ -48,6 +51,9 @@ struct IdentityProviderRow {
     metadata_cache_json: Option<String>,
     jwks_cached_at: Option<DateTime<Utc>>,
     jwks_cache_json: Option<String>,
+    saml_metadata_url: Option<String>,
+    saml_signing_certificates_json: String,
+    saml_name_id_format: Option<String>,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
 }
@@ -88,6 +94,13 @@ impl TryFrom<IdentityProviderRow> for IdentityProvider {
             metadata_cache_json: row.metadata_cache_json,
             jwks_cached_at: row.jwks_cached_at,
             jwks_cache_json: row.jwks_cache_json,
+            saml_metadata_url: row.saml_metadata_url,
+            saml_signing_certificates_json: row.saml_signing_certificates_json,
+            saml_name_id_format: row
+                .saml_name_id_format
+                .map(NameIdFormat::try_from)
+                .transpose()
+                .map_err(Error::System)?,
             created_at: row.created_at,
             updated_at: row.updated_at,
         })
@@ -100,16 +113,21 @@ impl IdentityProviderRepository for SqliteIdentityProviderRepository {
         skip_all,
         fields(telemetry = "span", db_table = "identity_providers", db_op = "insert")
     )]
-    async fn create(&self, provider: &IdentityProvider) -> Result<()> {
-        sqlx::query(
+    async fn create(
+        &self,
+        provider: &IdentityProvider,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        let query = sqlx::query(
             "INSERT INTO identity_providers (
                 id, realm_id, alias, display_name, protocol, preset_key, enabled, client_id,
                 client_secret, issuer, authorization_endpoint, token_endpoint, userinfo_endpoint,
                 jwks_uri, scopes_json, claim_mapping_json, pkce_required, allow_login, allow_link,
                 allow_jit_provisioning, allow_email_auto_link, require_verified_email, icon_ref,
                 button_color, sort_order, metadata_cached_at, metadata_cache_json, jwks_cached_at,
-                jwks_cache_json, created_at, updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                jwks_cache_json, saml_metadata_url, saml_signing_certificates_json,
+                saml_name_id_format, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
         .bind(provider.id.to_string())
         .bind(provider.realm_id.to_string())
@@ -140,11 +158,24 @@ impl IdentityProviderRepository for SqliteIdentityProviderRepository {
         .bind(&provider.metadata_cache_json)
         .bind(provider.jwks_cached_at)
         .bind(&provider.jwks_cache_json)
+        .bind(&provider.saml_metadata_url)
+        .bind(&provider.saml_signing_certificates_json)
+        .bind(provider.saml_name_id_format.map(|format| format.to_string()))
         .bind(provider.created_at)
-        .bind(provider.updated_at)
-        .execute(&*self.pool)
-        .await
-        .map_err(|e| Error::Unexpected(e.into()))?;
+        .bind(provider.updated_at);
+
+        if let Some(tx) = tx {
+            let sql_tx = SqliteTransaction::from_trait(tx).expect("Invalid TX type");
+            query
+                .execute(&mut **sql_tx)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        } else {
+            query
+                .execute(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        }
         Ok(())
     }
 
@@ -152,8 +183,12 @@ impl IdentityProviderRepository for SqliteIdentityProviderRepository {
         skip_all,
         fields(telemetry = "span", db_table = "identity_providers", db_op = "update")
     )]
-    async fn update(&self, provider: &IdentityProvider) -> Result<()> {
-        sqlx::query(
+    async fn update(
+        &self,
+        provider: &IdentityProvider,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        let query = sqlx::query(
             "UPDATE identity_providers SET
                 alias = ?, display_name = ?, protocol = ?, preset_key = ?, enabled = ?, client_id = ?,
                 client_secret = ?, issuer = ?, authorization_endpoint = ?, token_endpoint = ?,
@@ -161,7 +196,8 @@ impl IdentityProviderRepository for SqliteIdentityProviderRepository {
                 pkce_required = ?, allow_login = ?, allow_link = ?, allow_jit_provisioning = ?,
                 allow_email_auto_link = ?, require_verified_email = ?, icon_ref = ?, button_color = ?,
                 sort_order = ?, metadata_cached_at = ?, metadata_cache_json = ?, jwks_cached_at = ?,
-                jwks_cache_json = ?, updated_at = ?
+                jwks_cache_json = ?, saml_metadata_url = ?, saml_signing_certificates_json = ?,
+                saml_name_id_format = ?, updated_at = ?
              WHERE id = ?",
         )
         .bind(&provider.alias)
@@ -191,11 +227,24 @@ impl IdentityProviderRepository for SqliteIdentityProviderRepository {
         .bind(&provider.metadata_cache_json)
         .bind(provider.jwks_cached_at)
         .bind(&provider.jwks_cache_json)
+        .bind(&provider.saml_metadata_url)
+        .bind(&provider.saml_signing_certificates_json)
+        .bind(provider.saml_name_id_format.map(|format| format.to_string()))
         .bind(provider.updated_at)
-        .bind(provider.id.to_string())
-        .execute(&*self.pool)
-        .await
-        .map_err(|e| Error::Unexpected(e.into()))?;
+        .bind(provider.id.to_string());
+
+        if let Some(tx) = tx {
+            let sql_tx = SqliteTransaction::from_trait(tx).expect("Invalid TX type");
+            query
+                .execute(&mut **sql_tx)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        } else {
+            query
+                .execute(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        }
         Ok(())
     }
 
@@ -251,12 +300,17 @@ impl IdentityProviderRepository for SqliteIdentityProviderRepository {
         skip_all,
         fields(telemetry = "span", db_table = "identity_providers", db_op = "delete")
     )]
-    async fn delete(&self, id: &Uuid) -> Result<()> {
-        sqlx::query("DELETE FROM identity_providers WHERE id = ?")
-            .bind(id.to_string())
-            .execute(&*self.pool)
-            .await
-            .map_err(|e| Error::Unexpected(e.into()))?;
+    async fn delete(&self, id: &Uuid, tx: Option<&mut dyn Transaction>) -> Result<()> {
+        let query = sqlx::query("DELETE FROM identity_providers WHERE id = ?").bind(id.to_string());
+
+        if let Some(tx) = tx {
+            let sql_tx = SqliteTransaction::from_trait(tx).expect("Invalid TX");
+            query.execute(&mut **sql_tx).await
+        } else {
+            query.execute(&*self.pool).await
+        }
+        .map_err(|e| Error::Unexpected(e.into()))?;
+
         Ok(())
     }
 }
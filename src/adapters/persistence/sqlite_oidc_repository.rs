@@ -8,7 +8,7 @@ use crate::{
     ports::transaction_manager::Transaction,
 };
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use sqlx::{QueryBuilder, Sqlite};
 use tracing::instrument;
 use uuid::Uuid;
@@ -67,14 +67,40 @@ impl OidcRepository for SqliteOidcRepository {
         Ok(client)
     }
 
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "oidc_clients", db_op = "select")
+    )]
+    async fn find_client_by_id_with_tx(
+        &self,
+        realm_id: &Uuid,
+        client_id: &str,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<Option<OidcClient>> {
+        let query =
+            sqlx::query_as("SELECT * FROM oidc_clients WHERE realm_id = ? AND client_id = ?")
+                .bind(realm_id.to_string())
+                .bind(client_id);
+
+        let client = if let Some(tx) = tx {
+            let sql_tx = SqliteTransaction::from_trait(tx).expect("Invalid TX");
+            query.fetch_optional(&mut **sql_tx).await
+        } else {
+            query.fetch_optional(&*self.pool).await
+        }
+        .map_err(|e| Error::Unexpected(e.into()))?;
+
+        Ok(client)
+    }
+
     #[instrument(
         skip_all,
         fields(telemetry = "span", db_table = "oidc_clients", db_op = "insert")
     )]
     async fn create_client(&self, client: &OidcClient) -> Result<()> {
         sqlx::query(
-            "INSERT INTO oidc_clients (id, realm_id, client_id, client_secret, redirect_uris, scopes, web_origins, managed_by_config)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            "INSERT INTO oidc_clients (id, realm_id, client_id, client_secret, redirect_uris, scopes, web_origins, managed_by_config, token_endpoint_auth_method, jwks, signing_algorithm, backchannel_logout_uri, frontchannel_logout_uri, token_exchange_policy, direct_grant_enabled, require_pushed_authorization_requests)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
             .bind(client.id.to_string())
             .bind(client.realm_id.to_string())
@@ -84,6 +110,14 @@ impl OidcRepository for SqliteOidcRepository {
             .bind(&client.scopes)
             .bind(&client.web_origins)
             .bind(client.managed_by_config)
+            .bind(client.token_endpoint_auth_method.as_str())
+            .bind(&client.jwks)
+            .bind(client.signing_algorithm)
+            .bind(&client.backchannel_logout_uri)
+            .bind(&client.frontchannel_logout_uri)
+        .bind(&client.token_exchange_policy)
+        .bind(client.direct_grant_enabled)
+        .bind(client.require_pushed_authorization_requests)
             .execute(&*self.pool)
             .await
             .map_err(|e| Error::Unexpected(e.into()))?;
@@ -100,8 +134,8 @@ impl OidcRepository for SqliteOidcRepository {
         tx: Option<&mut dyn Transaction>,
     ) -> Result<()> {
         let query = sqlx::query(
-            "INSERT INTO oidc_clients (id, realm_id, client_id, client_secret, redirect_uris, scopes, web_origins, managed_by_config)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            "INSERT INTO oidc_clients (id, realm_id, client_id, client_secret, redirect_uris, scopes, web_origins, managed_by_config, token_endpoint_auth_method, jwks, signing_algorithm, backchannel_logout_uri, frontchannel_logout_uri, token_exchange_policy, direct_grant_enabled, require_pushed_authorization_requests)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
         .bind(client.id.to_string())
         .bind(client.realm_id.to_string())
@@ -110,7 +144,15 @@ impl OidcRepository for SqliteOidcRepository {
         .bind(&client.redirect_uris)
         .bind(&client.scopes)
         .bind(&client.web_origins)
-        .bind(client.managed_by_config);
+        .bind(client.managed_by_config)
+        .bind(client.token_endpoint_auth_method.as_str())
+        .bind(&client.jwks)
+        .bind(client.signing_algorithm)
+        .bind(&client.backchannel_logout_uri)
+        .bind(&client.frontchannel_logout_uri)
+        .bind(&client.token_exchange_policy)
+        .bind(client.direct_grant_enabled)
+        .bind(client.require_pushed_authorization_requests);
 
         if let Some(tx) = tx {
             let sql_tx = SqliteTransaction::from_trait(tx).expect("Invalid TX");
@@ -259,6 +301,28 @@ impl OidcRepository for SqliteOidcRepository {
         Ok(())
     }
 
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "oidc_clients", db_op = "delete")
+    )]
+    async fn delete_client_with_tx(
+        &self,
+        id: &Uuid,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        let query = sqlx::query("DELETE FROM oidc_clients WHERE id = ?").bind(id.to_string());
+
+        if let Some(tx) = tx {
+            let sql_tx = SqliteTransaction::from_trait(tx).expect("Invalid TX");
+            query.execute(&mut **sql_tx).await
+        } else {
+            query.execute(&*self.pool).await
+        }
+        .map_err(|e| Error::Unexpected(e.into()))?;
+
+        Ok(())
+    }
+
     #[instrument(
         skip_all,
         fields(telemetry = "span", db_table = "oidc_clients", db_op = "select")
@@ -278,7 +342,7 @@ impl OidcRepository for SqliteOidcRepository {
     )]
     async fn update_client(&self, client: &OidcClient) -> Result<()> {
         sqlx::query(
-            "UPDATE oidc_clients SET client_id = ?, client_secret = ?, redirect_uris = ?, scopes = ?, web_origins = ?, managed_by_config = ? WHERE id = ?",
+            "UPDATE oidc_clients SET client_id = ?, client_secret = ?, redirect_uris = ?, scopes = ?, web_origins = ?, managed_by_config = ?, token_endpoint_auth_method = ?, jwks = ?, signing_algorithm = ?, backchannel_logout_uri = ?, frontchannel_logout_uri = ?, token_exchange_policy = ?, direct_grant_enabled = ?, require_pushed_authorization_requests = ? WHERE id = ?",
         )
         .bind(&client.client_id)
         .bind(&client.client_secret)
@@ -286,6 +350,14 @@ impl OidcRepository for SqliteOidcRepository {
         .bind(&client.scopes)
         .bind(&client.web_origins)
         .bind(client.managed_by_config)
+        .bind(client.token_endpoint_auth_method.as_str())
+        .bind(&client.jwks)
+        .bind(client.signing_algorithm)
+        .bind(&client.backchannel_logout_uri)
+        .bind(&client.frontchannel_logout_uri)
+        .bind(&client.token_exchange_policy)
+        .bind(client.direct_grant_enabled)
+        .bind(client.require_pushed_authorization_requests)
         .bind(client.id.to_string())
         .execute(&*self.pool)
         .await
@@ -303,7 +375,7 @@ impl OidcRepository for SqliteOidcRepository {
         tx: Option<&mut dyn Transaction>,
     ) -> Result<()> {
         let query = sqlx::query(
-            "UPDATE oidc_clients SET client_id = ?, client_secret = ?, redirect_uris = ?, scopes = ?, web_origins = ?, managed_by_config = ? WHERE id = ?",
+            "UPDATE oidc_clients SET client_id = ?, client_secret = ?, redirect_uris = ?, scopes = ?, web_origins = ?, managed_by_config = ?, token_endpoint_auth_method = ?, jwks = ?, signing_algorithm = ?, backchannel_logout_uri = ?, frontchannel_logout_uri = ?, token_exchange_policy = ?, direct_grant_enabled = ?, require_pushed_authorization_requests = ? WHERE id = ?",
         )
         .bind(&client.client_id)
         .bind(&client.client_secret)
@@ -311,6 +383,14 @@ impl OidcRepository for SqliteOidcRepository {
         .bind(&client.scopes)
         .bind(&client.web_origins)
         .bind(client.managed_by_config)
+        .bind(client.token_endpoint_auth_method.as_str())
+        .bind(&client.jwks)
+        .bind(client.signing_algorithm)
+        .bind(&client.backchannel_logout_uri)
+        .bind(&client.frontchannel_logout_uri)
+        .bind(&client.token_exchange_policy)
+        .bind(client.direct_grant_enabled)
+        .bind(client.require_pushed_authorization_requests)
         .bind(client.id.to_string());
 
         if let Some(tx) = tx {
@@ -331,8 +411,8 @@ impl OidcRepository for SqliteOidcRepository {
     )]
     async fn save_auth_code(&self, code: &AuthCode) -> Result<()> {
         sqlx::query(
-            "INSERT INTO authorization_codes (code, user_id, client_id, redirect_uri, nonce, code_challenge, code_challenge_method, expires_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            "INSERT INTO authorization_codes (code, user_id, client_id, redirect_uri, nonce, code_challenge, code_challenge_method, expires_at, scope, claims)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
             .bind(&code.code)
             .bind(code.user_id.to_string())
@@ -342,6 +422,8 @@ impl OidcRepository for SqliteOidcRepository {
             .bind(&code.code_challenge)
             .bind(&code.code_challenge_method)
             .bind(code.expires_at)
+            .bind(&code.scope)
+            .bind(&code.claims)
             .execute(&*self.pool)
             .await
             .map_err(|e| Error::Unexpected(e.into()))?;
@@ -401,4 +483,39 @@ impl OidcRepository for SqliteOidcRepository {
 
         Ok(result.is_some())
     }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "client_assertion_jtis",
+            db_op = "insert"
+        )
+    )]
+    async fn record_client_assertion_jti(
+        &self,
+        client_id: &Uuid,
+        jti: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool> {
+        // Expired entries can no longer match a valid assertion.
+        sqlx::query("DELETE FROM client_assertion_jtis WHERE client_id = ? AND expires_at <= ?")
+            .bind(client_id.to_string())
+            .bind(Utc::now())
+            .execute(&*self.pool)
+            .await
+            .map_err(|e| Error::Unexpected(e.into()))?;
+
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO client_assertion_jtis (client_id, jti, expires_at) VALUES (?, ?, ?)",
+        )
+        .bind(client_id.to_string())
+        .bind(jti)
+        .bind(expires_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+
+        Ok(result.rows_affected() == 1)
+    }
 }
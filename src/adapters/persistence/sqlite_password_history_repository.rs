@@ -0,0 +1,143 @@
+use crate::adapters::persistence::connection::Database;
+use crate::adapters::persistence::transaction::SqliteTransaction;
+use crate::domain::password_policy::PasswordHistoryEntry;
+use crate::error::{Error, Result};
+use crate::ports::password_history_repository::PasswordHistoryRepository;
+use crate::ports::transaction_manager::Transaction;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tracing::instrument;
+use uuid::Uuid;
+
+pub struct SqlitePasswordHistoryRepository {
+    pool: Database,
+}
+
+impl SqlitePasswordHistoryRepository {
+    pub fn new(pool: Database) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct PasswordHistoryRecord {
+    id: String,
+    realm_id: String,
+    user_id: String,
+    hashed_password: String,
+    created_at: DateTime<Utc>,
+}
+
+impl PasswordHistoryRecord {
+    fn into_domain(self) -> Result<PasswordHistoryEntry> {
+        Ok(PasswordHistoryEntry {
+            id: Uuid::parse_str(&self.id)
+                .map_err(|_| Error::System("Invalid password history id".to_string()))?,
+            realm_id: Uuid::parse_str(&self.realm_id)
+                .map_err(|_| Error::System("Invalid password history realm id".to_string()))?,
+            user_id: Uuid::parse_str(&self.user_id)
+                .map_err(|_| Error::System("Invalid password history user id".to_string()))?,
+            hashed_password: self.hashed_password,
+            created_at: self.created_at,
+        })
+    }
+}
+
+#[async_trait]
+impl PasswordHistoryRepository for SqlitePasswordHistoryRepository {
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "password_history", db_op = "select")
+    )]
+    async fn list_recent(&self, user_id: &Uuid, limit: i64) -> Result<Vec<PasswordHistoryEntry>> {
+        let records: Vec<PasswordHistoryRecord> = sqlx::query_as(
+            "SELECT * FROM password_history WHERE user_id = ?
+             ORDER BY created_at DESC, rowid DESC LIMIT ?",
+        )
+        .bind(user_id.to_string())
+        .bind(limit)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+
+        records
+            .into_iter()
+            .map(PasswordHistoryRecord::into_domain)
+            .collect()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "password_history", db_op = "insert")
+    )]
+    async fn insert(
+        &self,
+        entry: &PasswordHistoryEntry,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        let query = sqlx::query(
+            "INSERT INTO password_history (id, realm_id, user_id, hashed_password, created_at)
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(entry.id.to_string())
+        .bind(entry.realm_id.to_string())
+        .bind(entry.user_id.to_string())
+        .bind(&entry.hashed_password)
+        .bind(entry.created_at);
+
+        match tx {
+            Some(tx) => {
+                let sql_tx = SqliteTransaction::from_trait(tx).expect("Invalid TX type");
+                query
+                    .execute(&mut **sql_tx)
+                    .await
+                    .map_err(|e| Error::Unexpected(e.into()))?;
+            }
+            None => {
+                query
+                    .execute(&*self.pool)
+                    .await
+                    .map_err(|e| Error::Unexpected(e.into()))?;
+            }
+        }
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "password_history", db_op = "delete")
+    )]
+    async fn prune(
+        &self,
+        user_id: &Uuid,
+        keep: i64,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        let query = sqlx::query(
+            "DELETE FROM password_history WHERE user_id = ? AND id NOT IN (
+                SELECT id FROM password_history WHERE user_id = ?
+                ORDER BY created_at DESC, rowid DESC LIMIT ?
+            )",
+        )
+        .bind(user_id.to_string())
+        .bind(user_id.to_string())
+        .bind(keep);
+
+        match tx {
+            Some(tx) => {
+                let sql_tx = SqliteTransaction::from_trait(tx).expect("Invalid TX type");
+                query
+                    .execute(&mut **sql_tx)
+                    .await
+                    .map_err(|e| Error::Unexpected(e.into()))?;
+            }
+            None => {
+                query
+                    .execute(&*self.pool)
+                    .await
+                    .map_err(|e| Error::Unexpected(e.into()))?;
+            }
+        }
+        Ok(())
+    }
+}
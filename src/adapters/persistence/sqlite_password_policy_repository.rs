@@ -0,0 +1,134 @@
+use crate::adapters::persistence::connection::Database;
+use crate::adapters::persistence::transaction::SqliteTransaction;
+use crate::domain::password_policy::PasswordPolicy;
+use crate::error::{Error, Result};
+use crate::ports::password_policy_repository::PasswordPolicyRepository;
+use crate::ports::transaction_manager::Transaction;
+use async_trait::async_trait;
+use tracing::instrument;
+use uuid::Uuid;
+
+pub struct SqlitePasswordPolicyRepository {
+    pool: Database,
+}
+
+impl SqlitePasswordPolicyRepository {
+    pub fn new(pool: Database) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct PasswordPolicyRecord {
+    realm_id: String,
+    min_length: i64,
+    max_length: i64,
+    require_lowercase: bool,
+    require_uppercase: bool,
+    require_digit: bool,
+    require_symbol: bool,
+    disallow_username: bool,
+    history_count: i64,
+    max_age_days: i64,
+    check_breached: bool,
+}
+
+impl PasswordPolicyRecord {
+    fn into_policy(self) -> Result<PasswordPolicy> {
+        let realm_id = Uuid::parse_str(&self.realm_id)
+            .map_err(|_| Error::System("Invalid realm id in password policy".to_string()))?;
+        Ok(PasswordPolicy {
+            realm_id,
+            min_length: self.min_length,
+            max_length: self.max_length,
+            require_lowercase: self.require_lowercase,
+            require_uppercase: self.require_uppercase,
+            require_digit: self.require_digit,
+            require_symbol: self.require_symbol,
+            disallow_username: self.disallow_username,
+            history_count: self.history_count,
+            max_age_days: self.max_age_days,
+            check_breached: self.check_breached,
+        })
+    }
+}
+
+#[async_trait]
+impl PasswordPolicyRepository for SqlitePasswordPolicyRepository {
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "realm_password_policies",
+            db_op = "select"
+        )
+    )]
+    async fn find_by_realm_id(&self, realm_id: &Uuid) -> Result<Option<PasswordPolicy>> {
+        let record: Option<PasswordPolicyRecord> =
+            sqlx::query_as("SELECT * FROM realm_password_policies WHERE realm_id = ?")
+                .bind(realm_id.to_string())
+                .fetch_optional(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        record.map(PasswordPolicyRecord::into_policy).transpose()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "realm_password_policies",
+            db_op = "upsert"
+        )
+    )]
+    async fn upsert(
+        &self,
+        policy: &PasswordPolicy,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        let query = sqlx::query(
+            "INSERT INTO realm_password_policies (
+                realm_id, min_length, max_length, require_lowercase, require_uppercase,
+                require_digit, require_symbol, disallow_username, history_count,
+                max_age_days, check_breached
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(realm_id) DO UPDATE SET
+                min_length = excluded.min_length,
+                max_length = excluded.max_length,
+                require_lowercase = excluded.require_lowercase,
+                require_uppercase = excluded.require_uppercase,
+                require_digit = excluded.require_digit,
+                require_symbol = excluded.require_symbol,
+                disallow_username = excluded.disallow_username,
+                history_count = excluded.history_count,
+                max_age_days = excluded.max_age_days,
+                check_breached = excluded.check_breached",
+        )
+        .bind(policy.realm_id.to_string())
+        .bind(policy.min_length)
+        .bind(policy.max_length)
+        .bind(policy.require_lowercase)
+        .bind(policy.require_uppercase)
+        .bind(policy.require_digit)
+        .bind(policy.require_symbol)
+        .bind(policy.disallow_username)
+        .bind(policy.history_count)
+        .bind(policy.max_age_days)
+        .bind(policy.check_breached);
+
+        if let Some(tx) = tx {
+            let sql_tx = SqliteTransaction::from_trait(tx).expect("Invalid TX type");
+            query
+                .execute(&mut **sql_tx)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        } else {
+            query
+                .execute(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,195 @@
+use crate::adapters::persistence::connection::Database;
+use crate::domain::claims::{ProtocolMapper, ProtocolMapperKind};
+use crate::error::{Error, Result};
+use crate::ports::protocol_mapper_repository::ProtocolMapperRepository;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use tracing::instrument;
+use uuid::Uuid;
+
+pub struct SqliteProtocolMapperRepository {
+    pool: Database,
+}
+
+impl SqliteProtocolMapperRepository {
+    pub fn new(pool: Database) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ProtocolMapperRow {
+    id: String,
+    realm_id: String,
+    client_id: String,
+    name: String,
+    mapper_type: String,
+    config: String,
+    claim_name: String,
+    add_to_access_token: bool,
+    add_to_id_token: bool,
+    add_to_userinfo: bool,
+    created_at: DateTime<Utc>,
+}
+
+impl TryFrom<ProtocolMapperRow> for ProtocolMapper {
+    type Error = Error;
+
+    fn try_from(row: ProtocolMapperRow) -> Result<Self> {
+        let parse_uuid = |value: &str| {
+            Uuid::parse_str(value)
+                .map_err(|_| Error::System("Invalid protocol mapper id".to_string()))
+        };
+        // The kind is stored as its type plus a config object; together they
+        // form the tagged representation.
+        let mut kind: Value = serde_json::from_str(&row.config)
+            .map_err(|err| Error::System(format!("Invalid protocol mapper config: {}", err)))?;
+        if !kind.is_object() {
+            kind = json!({});
+        }
+        kind["type"] = json!(row.mapper_type);
+        let kind: ProtocolMapperKind = serde_json::from_value(kind)
+            .map_err(|err| Error::System(format!("Invalid protocol mapper config: {}", err)))?;
+
+        Ok(Self {
+            id: parse_uuid(&row.id)?,
+            realm_id: parse_uuid(&row.realm_id)?,
+            client_id: parse_uuid(&row.client_id)?,
+            name: row.name,
+            kind,
+            claim_name: row.claim_name,
+            add_to_access_token: row.add_to_access_token,
+            add_to_id_token: row.add_to_id_token,
+            add_to_userinfo: row.add_to_userinfo,
+            created_at: row.created_at,
+        })
+    }
+}
+
+/// The kind's fields without the `type` tag, as stored in `config`.
+fn mapper_config(kind: &ProtocolMapperKind) -> Result<String> {
+    let mut config = serde_json::to_value(kind).map_err(|e| Error::Unexpected(e.into()))?;
+    if let Some(object) = config.as_object_mut() {
+        object.remove("type");
+    }
+    Ok(config.to_string())
+}
+
+#[async_trait]
+impl ProtocolMapperRepository for SqliteProtocolMapperRepository {
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "client_protocol_mappers",
+            db_op = "select"
+        )
+    )]
+    async fn list_for_client(&self, client_id: &Uuid) -> Result<Vec<ProtocolMapper>> {
+        let rows: Vec<ProtocolMapperRow> = sqlx::query_as(
+            "SELECT * FROM client_protocol_mappers WHERE client_id = ? ORDER BY created_at, name",
+        )
+        .bind(client_id.to_string())
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        rows.into_iter().map(TryInto::try_into).collect()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "client_protocol_mappers",
+            db_op = "select"
+        )
+    )]
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<ProtocolMapper>> {
+        let row: Option<ProtocolMapperRow> =
+            sqlx::query_as("SELECT * FROM client_protocol_mappers WHERE id = ?")
+                .bind(id.to_string())
+                .fetch_optional(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        row.map(TryInto::try_into).transpose()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "client_protocol_mappers",
+            db_op = "insert"
+        )
+    )]
+    async fn create(&self, mapper: &ProtocolMapper) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO client_protocol_mappers (
+                id, realm_id, client_id, name, mapper_type, config, claim_name,
+                add_to_access_token, add_to_id_token, add_to_userinfo, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(mapper.id.to_string())
+        .bind(mapper.realm_id.to_string())
+        .bind(mapper.client_id.to_string())
+        .bind(&mapper.name)
+        .bind(mapper.kind.as_str())
+        .bind(mapper_config(&mapper.kind)?)
+        .bind(&mapper.claim_name)
+        .bind(mapper.add_to_access_token)
+        .bind(mapper.add_to_id_token)
+        .bind(mapper.add_to_userinfo)
+        .bind(mapper.created_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "client_protocol_mappers",
+            db_op = "update"
+        )
+    )]
+    async fn update(&self, mapper: &ProtocolMapper) -> Result<()> {
+        sqlx::query(
+            "UPDATE client_protocol_mappers
+             SET name = ?, mapper_type = ?, config = ?, claim_name = ?,
+                 add_to_access_token = ?, add_to_id_token = ?, add_to_userinfo = ?
+             WHERE id = ?",
+        )
+        .bind(&mapper.name)
+        .bind(mapper.kind.as_str())
+        .bind(mapper_config(&mapper.kind)?)
+        .bind(&mapper.claim_name)
+        .bind(mapper.add_to_access_token)
+        .bind(mapper.add_to_id_token)
+        .bind(mapper.add_to_userinfo)
+        .bind(mapper.id.to_string())
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "client_protocol_mappers",
+            db_op = "delete"
+        )
+    )]
+    async fn delete(&self, id: &Uuid) -> Result<()> {
+        sqlx::query("DELETE FROM client_protocol_mappers WHERE id = ?")
+            .bind(id.to_string())
+            .execute(&*self.pool)
+            .await
+            .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+use crate::adapters::persistence::connection::Database;
+use crate::domain::oidc::PushedAuthorizationRequest;
+use crate::error::{Error, Result};
+use crate::ports::pushed_authorization_request_repository::PushedAuthorizationRequestRepository;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tracing::instrument;
+use uuid::Uuid;
+
+pub struct SqlitePushedAuthorizationRequestRepository {
+    pool: Database,
+}
+
+impl SqlitePushedAuthorizationRequestRepository {
+    pub fn new(pool: Database) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct PushedAuthorizationRequestRow {
+    request_uri: String,
+    realm_id: String,
+    client_id: String,
+    parameters: String,
+    expires_at: DateTime<Utc>,
+    created_at: DateTime<Utc>,
+}
+
+impl TryFrom<PushedAuthorizationRequestRow> for PushedAuthorizationRequest {
+    type Error = Error;
+
+    fn try_from(row: PushedAuthorizationRequestRow) -> Result<Self> {
+        Ok(Self {
+            request_uri: row.request_uri,
+            realm_id: Uuid::parse_str(&row.realm_id)
+                .map_err(|_| Error::System("Invalid pushed authorization realm id".into()))?,
+            client_id: row.client_id,
+            request: serde_json::from_str(&row.parameters).map_err(|err| {
+                Error::System(format!("Invalid pushed authorization parameters: {}", err))
+            })?,
+            expires_at: row.expires_at,
+            created_at: row.created_at,
+        })
+    }
+}
+
+#[async_trait]
+impl PushedAuthorizationRequestRepository for SqlitePushedAuthorizationRequestRepository {
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "pushed_authorization_requests",
+            db_op = "insert"
+        )
+    )]
+    async fn create(&self, request: &PushedAuthorizationRequest) -> Result<()> {
+        let parameters =
+            serde_json::to_string(&request.request).map_err(|e| Error::Unexpected(e.into()))?;
+        sqlx::query(
+            "INSERT INTO pushed_authorization_requests (
+                request_uri, realm_id, client_id, parameters, expires_at, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&request.request_uri)
+        .bind(request.realm_id.to_string())
+        .bind(&request.client_id)
+        .bind(parameters)
+        .bind(request.expires_at)
+        .bind(request.created_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "pushed_authorization_requests",
+            db_op = "delete"
+        )
+    )]
+    async fn consume(
+        &self,
+        realm_id: &Uuid,
+        client_id: &str,
+        request_uri: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<PushedAuthorizationRequest>> {
+        let row: Option<PushedAuthorizationRequestRow> = sqlx::query_as(
+            "DELETE FROM pushed_authorization_requests
+             WHERE realm_id = ? AND client_id = ? AND request_uri = ?
+             RETURNING *",
+        )
+        .bind(realm_id.to_string())
+        .bind(client_id)
+        .bind(request_uri)
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        row.filter(|row| row.expires_at > now)
+            .map(TryInto::try_into)
+            .transpose()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "pushed_authorization_requests",
+            db_op = "delete"
+        )
+    )]
+    async fn delete_expired_before(&self, cutoff: DateTime<Utc>, batch_size: i64) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM pushed_authorization_requests
+             WHERE request_uri IN (
+                 SELECT request_uri
+                 FROM pushed_authorization_requests
+                 WHERE expires_at < ?
+                 ORDER BY created_at ASC
+                 LIMIT ?
+             )",
+        )
+        .bind(cutoff)
+        .bind(batch_size)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(result.rows_affected())
+    }
+}
@@ -610,6 +610,29 @@ impl RbacRepository for SqliteRbacRepository {
         Ok(role)
     }
 
+    async fn find_role_by_name_with_tx(
+        &self,
+        realm_id: &Uuid,
+        name: &str,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<Option<Role>> {
+        let query = sqlx::query_as(
+            "SELECT * FROM roles WHERE realm_id = ? AND name = ? AND client_id IS NULL",
+        )
+        .bind(realm_id.to_string())
+        .bind(name);
+
+        let role = if let Some(tx) = tx {
+            let sql_tx = SqliteTransaction::from_trait(tx).expect("Invalid TX");
+            query.fetch_optional(&mut **sql_tx).await
+        } else {
+            query.fetch_optional(&*self.pool).await
+        }
+        .map_err(|e| Error::Unexpected(e.into()))?;
+
+        Ok(role)
+    }
+
     async fn find_group_by_name(&self, realm_id: &Uuid, name: &str) -> Result<Option<Group>> {
         let group = sqlx::query_as("SELECT * FROM groups WHERE realm_id = ? AND name = ?")
             .bind(realm_id.to_string())
@@ -629,6 +652,26 @@ impl RbacRepository for SqliteRbacRepository {
         Ok(group)
     }
 
+    async fn find_client_realm_id(&self, client_id: &Uuid) -> Result<Option<Uuid>> {
+        let realm_id: Option<String> =
+            sqlx::query_scalar("SELECT realm_id FROM oidc_clients WHERE id = ?")
+                .bind(client_id.to_string())
+                .fetch_optional(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(realm_id.and_then(|id| Uuid::parse_str(&id).ok()))
+    }
+
+    async fn is_system_realm(&self, realm_id: &Uuid) -> Result<bool> {
+        let is_system: Option<bool> =
+            sqlx::query_scalar("SELECT is_system FROM realms WHERE id = ?")
+                .bind(realm_id.to_string())
+                .fetch_optional(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(is_system.unwrap_or(false))
+    }
+
     async fn count_role_stats(&self, realm_id: &Uuid) -> Result<RoleStats> {
         let realm = realm_id.to_string();
 
@@ -778,6 +821,41 @@ impl RbacRepository for SqliteRbacRepository {
         Ok(PageResponse::new(roles, total, req.page, limit))
     }
 
+    async fn find_roles_for_client(&self, realm_id: &Uuid, client_id: &Uuid) -> Result<Vec<Role>> {
+        let roles = sqlx::query_as(
+            "SELECT * FROM roles WHERE realm_id = ? AND client_id = ? ORDER BY name ASC",
+        )
+        .bind(realm_id.to_string())
+        .bind(client_id.to_string())
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(roles)
+    }
+
+    async fn find_roles_for_client_with_tx(
+        &self,
+        realm_id: &Uuid,
+        client_id: &Uuid,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<Vec<Role>> {
+        let query = sqlx::query_as(
+            "SELECT * FROM roles WHERE realm_id = ? AND client_id = ? ORDER BY name ASC",
+        )
+        .bind(realm_id.to_string())
+        .bind(client_id.to_string());
+
+        let roles = if let Some(tx) = tx {
+            let sql_tx = SqliteTransaction::from_trait(tx).expect("Invalid TX");
+            query.fetch_all(&mut **sql_tx).await
+        } else {
+            query.fetch_all(&*self.pool).await
+        }
+        .map_err(|e| Error::Unexpected(e.into()))?;
+
+        Ok(roles)
+    }
+
     async fn find_role_by_id(&self, role_id: &Uuid) -> Result<Option<Role>> {
         let role = sqlx::query_as("SELECT * FROM roles WHERE id = ?")
             .bind(role_id.to_string())
@@ -1703,6 +1781,37 @@ impl RbacRepository for SqliteRbacRepository {
         Ok(row.0 > 0)
     }
 
+    async fn is_user_in_group_subtree(
+        &self,
+        realm_id: &Uuid,
+        root_id: &Uuid,
+        user_id: &Uuid,
+    ) -> Result<bool> {
+        let row: (i64,) = sqlx::query_as(
+            r#"
+            WITH RECURSIVE subtree(id) AS (
+                SELECT id FROM groups WHERE id = ? AND realm_id = ?
+                UNION ALL
+                SELECT g.id FROM groups g
+                JOIN subtree s ON g.parent_id = s.id
+                WHERE g.realm_id = ?
+            )
+            SELECT COUNT(1) FROM user_groups ug
+            JOIN subtree s ON ug.group_id = s.id
+            WHERE ug.user_id = ?
+            "#,
+        )
+        .bind(root_id.to_string())
+        .bind(realm_id.to_string())
+        .bind(realm_id.to_string())
+        .bind(user_id.to_string())
+        .fetch_one(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+
+        Ok(row.0 > 0)
+    }
+
     async fn get_next_group_sort_order(
         &self,
         realm_id: &Uuid,
@@ -2163,6 +2272,37 @@ impl RbacRepository for SqliteRbacRepository {
         Ok(rows.into_iter().map(|(n,)| n).collect())
     }
 
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "rbac", db_op = "select")
+    )]
+    async fn find_group_paths_for_user(&self, user_id: &Uuid) -> Result<Vec<String>> {
+        let rows: Vec<(String,)> = sqlx::query_as(
+            r#"
+            WITH RECURSIVE group_path(member_id, parent_id, path, depth) AS (
+                SELECT g.id, g.parent_id, '/' || g.name, 0
+                FROM groups g
+                JOIN user_groups ug ON g.id = ug.group_id
+                WHERE ug.user_id = ?
+                UNION ALL
+                SELECT gp.member_id, g.parent_id, '/' || g.name || gp.path, gp.depth + 1
+                FROM groups g
+                JOIN group_path gp ON g.id = gp.parent_id
+                WHERE gp.depth < 64
+            )
+            SELECT path FROM group_path
+            WHERE parent_id IS NULL
+            ORDER BY path
+        "#,
+        )
+        .bind(user_id.to_string())
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+
+        Ok(rows.into_iter().map(|(path,)| path).collect())
+    }
+
     async fn delete_role(&self, role_id: &Uuid, tx: Option<&mut dyn Transaction>) -> Result<()> {
         // Simple delete. The database FK constraints (ON DELETE CASCADE)
         // will automatically clean up role_permissions, user_roles, etc.
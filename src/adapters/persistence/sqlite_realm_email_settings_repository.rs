@@ -1,7 +1,9 @@
 use crate::adapters::persistence::connection::Database;
+use crate::adapters::persistence::transaction::SqliteTransaction;
 use crate::domain::realm_email_settings::RealmEmailSettings;
 use crate::error::{Error, Result};
 use crate::ports::realm_email_settings_repository::RealmEmailSettingsRepository;
+use crate::ports::transaction_manager::Transaction;
 use async_trait::async_trait;
 use tracing::instrument;
 use uuid::Uuid;
@@ -79,8 +81,12 @@ impl RealmEmailSettingsRepository for SqliteRealmEmailSettingsRepository {
             db_op = "upsert"
         )
     )]
-    async fn upsert(&self, settings: &RealmEmailSettings) -> Result<()> {
-        sqlx::query(
+    async fn upsert(
+        &self,
+        settings: &RealmEmailSettings,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        let query = sqlx::query(
             "INSERT INTO realm_email_settings (
                 realm_id, enabled, from_address, from_name, reply_to_address,
                 smtp_host, smtp_port, smtp_username, smtp_password, smtp_security
@@ -105,10 +111,20 @@ impl RealmEmailSettingsRepository for SqliteRealmEmailSettingsRepository {
         .bind(settings.smtp_port)
         .bind(&settings.smtp_username)
         .bind(&settings.smtp_password)
-        .bind(&settings.smtp_security)
-        .execute(&*self.pool)
-        .await
-        .map_err(|e| Error::Unexpected(e.into()))?;
+        .bind(&settings.smtp_security);
+
+        if let Some(tx) = tx {
+            let sql_tx = SqliteTransaction::from_trait(tx).expect("Invalid TX type");
+            query
+                .execute(&mut **sql_tx)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        } else {
+            query
+                .execute(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        }
 
         Ok(())
     }
@@ -1,7 +1,9 @@
 use crate::adapters::persistence::connection::Database;
+use crate::adapters::persistence::transaction::SqliteTransaction;
 use crate::domain::realm_idp_settings::RealmIdpSettings;
 use crate::error::{Error, Result};
 use crate::ports::realm_idp_settings_repository::RealmIdpSettingsRepository;
+use crate::ports::transaction_manager::Transaction;
 use async_trait::async_trait;
 use tracing::instrument;
 use uuid::Uuid;
@@ -58,8 +60,12 @@ impl RealmIdpSettingsRepository for SqliteRealmIdpSettingsRepository {
         skip_all,
         fields(telemetry = "span", db_table = "realm_idp_settings", db_op = "upsert")
     )]
-    async fn upsert(&self, settings: &RealmIdpSettings) -> Result<()> {
-        sqlx::query(
+    async fn upsert(
+        &self,
+        settings: &RealmIdpSettings,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        let query = sqlx::query(
             "INSERT INTO realm_idp_settings (
                 realm_id, oauth_start_rate_limit_max, oauth_start_rate_limit_window_minutes
             ) VALUES (?, ?, ?)
@@ -69,10 +75,20 @@ impl RealmIdpSettingsRepository for SqliteRealmIdpSettingsRepository {
         )
         .bind(settings.realm_id.to_string())
         .bind(settings.oauth_start_rate_limit_max)
-        .bind(settings.oauth_start_rate_limit_window_minutes)
-        .execute(&*self.pool)
-        .await
-        .map_err(|e| Error::Unexpected(e.into()))?;
+        .bind(settings.oauth_start_rate_limit_window_minutes);
+
+        if let Some(tx) = tx {
+            let sql_tx = SqliteTransaction::from_trait(tx).expect("Invalid TX type");
+            query
+                .execute(&mut **sql_tx)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        } else {
+            query
+                .execute(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        }
 
         Ok(())
     }
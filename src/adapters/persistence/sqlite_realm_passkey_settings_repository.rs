@@ -1,7 +1,9 @@
 use crate::adapters::persistence::connection::Database;
+use crate::adapters::persistence::transaction::SqliteTransaction;
 use crate::domain::realm_passkey_settings::RealmPasskeySettings;
 use crate::error::{Error, Result};
 use crate::ports::realm_passkey_settings_repository::RealmPasskeySettingsRepository;
+use crate::ports::transaction_manager::Transaction;
 use async_trait::async_trait;
 use tracing::instrument;
 use uuid::Uuid;
@@ -72,8 +74,12 @@ impl RealmPasskeySettingsRepository for SqliteRealmPasskeySettingsRepository {
             db_op = "upsert"
         )
     )]
-    async fn upsert(&self, settings: &RealmPasskeySettings) -> Result<()> {
-        sqlx::query(
+    async fn upsert(
+        &self,
+        settings: &RealmPasskeySettings,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        let query = sqlx::query(
             "INSERT INTO realm_passkey_settings (
                 realm_id, enabled, allow_password_fallback, discoverable_preferred,
                 challenge_ttl_secs, reauth_max_age_secs
@@ -91,10 +97,20 @@ impl RealmPasskeySettingsRepository for SqliteRealmPasskeySettingsRepository {
         .bind(settings.allow_password_fallback)
         .bind(settings.discoverable_preferred)
         .bind(settings.challenge_ttl_secs)
-        .bind(settings.reauth_max_age_secs)
-        .execute(&*self.pool)
-        .await
-        .map_err(|e| Error::Unexpected(e.into()))?;
+        .bind(settings.reauth_max_age_secs);
+
+        if let Some(tx) = tx {
+            let sql_tx = SqliteTransaction::from_trait(tx).expect("Invalid TX type");
+            query
+                .execute(&mut **sql_tx)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        } else {
+            query
+                .execute(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        }
 
         Ok(())
     }
@@ -1,7 +1,9 @@
 use crate::adapters::persistence::connection::Database;
+use crate::adapters::persistence::transaction::SqliteTransaction;
 use crate::domain::realm_recovery_settings::RealmRecoverySettings;
 use crate::error::{Error, Result};
 use crate::ports::realm_recovery_settings_repository::RealmRecoverySettingsRepository;
+use crate::ports::transaction_manager::Transaction;
 use async_trait::async_trait;
 use tracing::instrument;
 use uuid::Uuid;
@@ -73,8 +75,12 @@ impl RealmRecoverySettingsRepository for SqliteRealmRecoverySettingsRepository {
             db_op = "upsert"
         )
     )]
-    async fn upsert(&self, settings: &RealmRecoverySettings) -> Result<()> {
-        sqlx::query(
+    async fn upsert(
+        &self,
+        settings: &RealmRecoverySettings,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        let query = sqlx::query(
             "INSERT INTO realm_recovery_settings (
                 realm_id, token_ttl_minutes, rate_limit_max, rate_limit_window_minutes,
                 revoke_sessions_on_reset, email_subject, email_body
@@ -93,10 +99,20 @@ impl RealmRecoverySettingsRepository for SqliteRealmRecoverySettingsRepository {
         .bind(settings.rate_limit_window_minutes)
         .bind(settings.revoke_sessions_on_reset)
         .bind(&settings.email_subject)
-        .bind(&settings.email_body)
-        .execute(&*self.pool)
-        .await
-        .map_err(|e| Error::Unexpected(e.into()))?;
+        .bind(&settings.email_body);
+
+        if let Some(tx) = tx {
+            let sql_tx = SqliteTransaction::from_trait(tx).expect("Invalid TX type");
+            query
+                .execute(&mut **sql_tx)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        } else {
+            query
+                .execute(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        }
 
         Ok(())
     }
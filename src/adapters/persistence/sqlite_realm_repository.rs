@@ -2,6 +2,7 @@ use crate::adapters::persistence::connection::Database;
 use crate::adapters::persistence::transaction::SqliteTransaction;
 use crate::domain::auth_flow::AuthFlow;
 use crate::domain::realm::{RealmIdpDefaultEmailLinkPolicy, RealmIdpDefaultJitPolicy};
+use crate::domain::signing_key::SigningAlgorithm;
 use crate::ports::transaction_manager::Transaction;
 use crate::{
     domain::realm::Realm,
@@ -29,6 +30,7 @@ struct RealmRecord {
     access_token_ttl_secs: i64,
     refresh_token_ttl_secs: i64,
     pkce_required_public_clients: bool,
+    signing_algorithm: SigningAlgorithm,
     lockout_threshold: i64,
     lockout_duration_secs: i64,
     is_system: bool,
@@ -57,6 +59,7 @@ impl RealmRecord {
             access_token_ttl_secs: self.access_token_ttl_secs,
             refresh_token_ttl_secs: self.refresh_token_ttl_secs,
             pkce_required_public_clients: self.pkce_required_public_clients,
+            signing_algorithm: self.signing_algorithm,
             lockout_threshold: self.lockout_threshold,
             lockout_duration_secs: self.lockout_duration_secs,
             is_system: self.is_system,
@@ -119,18 +122,19 @@ impl RealmRepository for SqliteRealmRepository {
         let query = sqlx::query(
             "INSERT INTO realms (
                 id, name, access_token_ttl_secs, refresh_token_ttl_secs,
-                pkce_required_public_clients, lockout_threshold, lockout_duration_secs,
+                pkce_required_public_clients, signing_algorithm, lockout_threshold, lockout_duration_secs,
                 is_system, registration_enabled, default_registration_role_ids, invitation_resend_limit,
                 idp_broker_enabled, idp_default_jit_policy, idp_default_email_link_policy,
                 idp_minimum_remaining_factor,
                 browser_flow_id, registration_flow_id, direct_grant_flow_id, reset_credentials_flow_id, invitation_flow_id
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
         )
             .bind(realm.id.to_string())
             .bind(&realm.name)
             .bind(realm.access_token_ttl_secs)
             .bind(realm.refresh_token_ttl_secs)
             .bind(realm.pkce_required_public_clients)
+            .bind(realm.signing_algorithm)
             .bind(realm.lockout_threshold)
             .bind(realm.lockout_duration_secs)
             .bind(realm.is_system)
@@ -210,6 +214,7 @@ impl RealmRepository for SqliteRealmRepository {
                 access_token_ttl_secs = ?,
                 refresh_token_ttl_secs = ?,
                 pkce_required_public_clients = ?,
+                signing_algorithm = ?,
                 lockout_threshold = ?,
                 lockout_duration_secs = ?,
                 is_system = ?,
@@ -231,6 +236,7 @@ impl RealmRepository for SqliteRealmRepository {
         .bind(realm.access_token_ttl_secs)
         .bind(realm.refresh_token_ttl_secs)
         .bind(realm.pkce_required_public_clients)
+        .bind(realm.signing_algorithm)
         .bind(realm.lockout_threshold)
         .bind(realm.lockout_duration_secs)
         .bind(realm.is_system)
@@ -1,7 +1,9 @@
 use crate::adapters::persistence::connection::Database;
+use crate::adapters::persistence::transaction::SqliteTransaction;
 use crate::domain::realm_security_headers::RealmSecurityHeaders;
 use crate::error::{Error, Result};
 use crate::ports::realm_security_headers_repository::RealmSecurityHeadersRepository;
+use crate::ports::transaction_manager::Transaction;
 use async_trait::async_trait;
 use tracing::instrument;
 use uuid::Uuid;
@@ -71,8 +73,12 @@ impl RealmSecurityHeadersRepository for SqliteRealmSecurityHeadersRepository {
             db_op = "upsert"
         )
     )]
-    async fn upsert(&self, settings: &RealmSecurityHeaders) -> Result<()> {
-        sqlx::query(
+    async fn upsert(
+        &self,
+        settings: &RealmSecurityHeaders,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        let query = sqlx::query(
             "INSERT INTO realm_security_headers (
                 realm_id, x_frame_options, content_security_policy,
                 x_content_type_options, referrer_policy, strict_transport_security
@@ -89,10 +95,20 @@ impl RealmSecurityHeadersRepository for SqliteRealmSecurityHeadersRepository {
         .bind(&settings.content_security_policy)
         .bind(&settings.x_content_type_options)
         .bind(&settings.referrer_policy)
-        .bind(&settings.strict_transport_security)
-        .execute(&*self.pool)
-        .await
-        .map_err(|e| Error::Unexpected(e.into()))?;
+        .bind(&settings.strict_transport_security);
+
+        if let Some(tx) = tx {
+            let sql_tx = SqliteTransaction::from_trait(tx).expect("Invalid TX type");
+            query
+                .execute(&mut **sql_tx)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        } else {
+            query
+                .execute(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        }
 
         Ok(())
     }
@@ -0,0 +1,249 @@
+use crate::adapters::persistence::connection::Database;
+use crate::domain::saml::{NameIdFormat, SamlBinding, SamlServiceProvider};
+use crate::error::{Error, Result};
+use crate::ports::saml_service_provider_repository::SamlServiceProviderRepository;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tracing::instrument;
+use uuid::Uuid;
+
+pub struct SqliteSamlServiceProviderRepository {
+    pool: Database,
+}
+
+impl SqliteSamlServiceProviderRepository {
+    pub fn new(pool: Database) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct SamlServiceProviderRecord {
+    id: String,
+    realm_id: String,
+    entity_id: String,
+    name: String,
+    acs_urls: String,
+    slo_url: Option<String>,
+    slo_binding: String,
+    name_id_format: String,
+    attribute_mappings: String,
+    sign_response: bool,
+    signing_certificates: String,
+    require_signed_requests: bool,
+    allow_idp_initiated: bool,
+    enabled: bool,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl SamlServiceProviderRecord {
+    fn into_domain(self) -> Result<SamlServiceProvider> {
+        Ok(SamlServiceProvider {
+            id: Uuid::parse_str(&self.id)
+                .map_err(|_| Error::System("Invalid SAML service provider id".to_string()))?,
+            realm_id: Uuid::parse_str(&self.realm_id)
+                .map_err(|_| Error::System("Invalid SAML service provider realm id".to_string()))?,
+            entity_id: self.entity_id,
+            name: self.name,
+            acs_urls: serde_json::from_str(&self.acs_urls)
+                .map_err(|_| Error::System("Invalid SAML ACS URLs".to_string()))?,
+            slo_url: self.slo_url,
+            slo_binding: SamlBinding::try_from(self.slo_binding).map_err(Error::System)?,
+            name_id_format: NameIdFormat::try_from(self.name_id_format).map_err(Error::System)?,
+            attribute_mappings: serde_json::from_str(&self.attribute_mappings)
+                .map_err(|_| Error::System("Invalid SAML attribute mappings".to_string()))?,
+            sign_response: self.sign_response,
+            signing_certificates: serde_json::from_str(&self.signing_certificates)
+                .map_err(|_| Error::System("Invalid SAML signing certificates".to_string()))?,
+            require_signed_requests: self.require_signed_requests,
+            allow_idp_initiated: self.allow_idp_initiated,
+            enabled: self.enabled,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        })
+    }
+}
+
+fn encode_lists(provider: &SamlServiceProvider) -> Result<(String, String, String)> {
+    let acs_urls =
+        serde_json::to_string(&provider.acs_urls).map_err(|e| Error::Unexpected(e.into()))?;
+    let attribute_mappings = serde_json::to_string(&provider.attribute_mappings)
+        .map_err(|e| Error::Unexpected(e.into()))?;
+    let signing_certificates = serde_json::to_string(&provider.signing_certificates)
+        .map_err(|e| Error::Unexpected(e.into()))?;
+    Ok((acs_urls, attribute_mappings, signing_certificates))
+}
+
+#[async_trait]
+impl SamlServiceProviderRepository for SqliteSamlServiceProviderRepository {
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "saml_service_providers",
+            db_op = "insert"
+        )
+    )]
+    async fn create(&self, provider: &SamlServiceProvider) -> Result<()> {
+        let (acs_urls, attribute_mappings, signing_certificates) = encode_lists(provider)?;
+        sqlx::query(
+            "INSERT INTO saml_service_providers (
+                id, realm_id, entity_id, name, acs_urls, slo_url, slo_binding,
+                name_id_format, attribute_mappings, sign_response, signing_certificates,
+                require_signed_requests, allow_idp_initiated, enabled, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(provider.id.to_string())
+        .bind(provider.realm_id.to_string())
+        .bind(&provider.entity_id)
+        .bind(&provider.name)
+        .bind(acs_urls)
+        .bind(&provider.slo_url)
+        .bind(provider.slo_binding.to_string())
+        .bind(provider.name_id_format.to_string())
+        .bind(attribute_mappings)
+        .bind(provider.sign_response)
+        .bind(signing_certificates)
+        .bind(provider.require_signed_requests)
+        .bind(provider.allow_idp_initiated)
+        .bind(provider.enabled)
+        .bind(provider.created_at)
+        .bind(provider.updated_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "saml_service_providers",
+            db_op = "update"
+        )
+    )]
+    async fn update(&self, provider: &SamlServiceProvider) -> Result<()> {
+        let (acs_urls, attribute_mappings, signing_certificates) = encode_lists(provider)?;
+        sqlx::query(
+            "UPDATE saml_service_providers SET
+                entity_id = ?, name = ?, acs_urls = ?, slo_url = ?, slo_binding = ?,
+                name_id_format = ?, attribute_mappings = ?, sign_response = ?,
+                signing_certificates = ?, require_signed_requests = ?,
+                allow_idp_initiated = ?, enabled = ?, updated_at = ?
+            WHERE realm_id = ? AND id = ?",
+        )
+        .bind(&provider.entity_id)
+        .bind(&provider.name)
+        .bind(acs_urls)
+        .bind(&provider.slo_url)
+        .bind(provider.slo_binding.to_string())
+        .bind(provider.name_id_format.to_string())
+        .bind(attribute_mappings)
+        .bind(provider.sign_response)
+        .bind(signing_certificates)
+        .bind(provider.require_signed_requests)
+        .bind(provider.allow_idp_initiated)
+        .bind(provider.enabled)
+        .bind(provider.updated_at)
+        .bind(provider.realm_id.to_string())
+        .bind(provider.id.to_string())
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "saml_service_providers",
+            db_op = "select"
+        )
+    )]
+    async fn find_by_id(&self, realm_id: &Uuid, id: &Uuid) -> Result<Option<SamlServiceProvider>> {
+        let record: Option<SamlServiceProviderRecord> =
+            sqlx::query_as("SELECT * FROM saml_service_providers WHERE realm_id = ? AND id = ?")
+                .bind(realm_id.to_string())
+                .bind(id.to_string())
+                .fetch_optional(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+
+        record
+            .map(SamlServiceProviderRecord::into_domain)
+            .transpose()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "saml_service_providers",
+            db_op = "select"
+        )
+    )]
+    async fn find_by_entity_id(
+        &self,
+        realm_id: &Uuid,
+        entity_id: &str,
+    ) -> Result<Option<SamlServiceProvider>> {
+        let record: Option<SamlServiceProviderRecord> = sqlx::query_as(
+            "SELECT * FROM saml_service_providers WHERE realm_id = ? AND entity_id = ?",
+        )
+        .bind(realm_id.to_string())
+        .bind(entity_id)
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+
+        record
+            .map(SamlServiceProviderRecord::into_domain)
+            .transpose()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "saml_service_providers",
+            db_op = "select"
+        )
+    )]
+    async fn list_by_realm(&self, realm_id: &Uuid) -> Result<Vec<SamlServiceProvider>> {
+        let records: Vec<SamlServiceProviderRecord> = sqlx::query_as(
+            "SELECT * FROM saml_service_providers WHERE realm_id = ? ORDER BY entity_id",
+        )
+        .bind(realm_id.to_string())
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+
+        records
+            .into_iter()
+            .map(SamlServiceProviderRecord::into_domain)
+            .collect()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "saml_service_providers",
+            db_op = "delete"
+        )
+    )]
+    async fn delete(&self, realm_id: &Uuid, id: &Uuid) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM saml_service_providers WHERE realm_id = ? AND id = ?")
+                .bind(realm_id.to_string())
+                .bind(id.to_string())
+                .execute(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+
+        Ok(result.rows_affected() == 1)
+    }
+}
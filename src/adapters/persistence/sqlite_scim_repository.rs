@@ -0,0 +1,111 @@
+use crate::adapters::persistence::connection::Database;
+use crate::domain::scim::ScimResourceType;
+use crate::error::{Error, Result};
+use crate::ports::scim_repository::ScimRepository;
+use async_trait::async_trait;
+use tracing::instrument;
+use uuid::Uuid;
+
+pub struct SqliteScimRepository {
+    pool: Database,
+}
+
+impl SqliteScimRepository {
+    pub fn new(pool: Database) -> Self {
+        Self { pool }
+    }
+}
+
+fn table(resource_type: ScimResourceType) -> &'static str {
+    match resource_type {
+        ScimResourceType::User => "scim_user_external_ids",
+        ScimResourceType::Group => "scim_group_external_ids",
+    }
+}
+
+#[async_trait]
+impl ScimRepository for SqliteScimRepository {
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "scim_external_ids", db_op = "select")
+    )]
+    async fn find_external_id(
+        &self,
+        resource_type: ScimResourceType,
+        resource_id: &Uuid,
+    ) -> Result<Option<String>> {
+        let query = format!(
+            "SELECT external_id FROM {} WHERE resource_id = ?",
+            table(resource_type)
+        );
+        sqlx::query_scalar(&query)
+            .bind(resource_id.to_string())
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(|e| Error::Unexpected(e.into()))
+    }
+
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "scim_external_ids", db_op = "select")
+    )]
+    async fn find_by_external_id(
+        &self,
+        resource_type: ScimResourceType,
+        realm_id: &Uuid,
+        external_id: &str,
+    ) -> Result<Option<Uuid>> {
+        let query = format!(
+            "SELECT resource_id FROM {} WHERE realm_id = ? AND external_id = ?",
+            table(resource_type)
+        );
+        let resource_id: Option<String> = sqlx::query_scalar(&query)
+            .bind(realm_id.to_string())
+            .bind(external_id)
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(|e| Error::Unexpected(e.into()))?;
+        resource_id
+            .map(|id| {
+                Uuid::parse_str(&id).map_err(|_| Error::System("Invalid SCIM resource id".into()))
+            })
+            .transpose()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "scim_external_ids", db_op = "upsert")
+    )]
+    async fn set_external_id(
+        &self,
+        resource_type: ScimResourceType,
+        realm_id: &Uuid,
+        resource_id: &Uuid,
+        external_id: Option<&str>,
+    ) -> Result<()> {
+        match external_id {
+            Some(external_id) => {
+                let query = format!(
+                    "INSERT INTO {} (resource_id, realm_id, external_id) VALUES (?, ?, ?)
+                     ON CONFLICT(resource_id) DO UPDATE SET external_id = excluded.external_id",
+                    table(resource_type)
+                );
+                sqlx::query(&query)
+                    .bind(resource_id.to_string())
+                    .bind(realm_id.to_string())
+                    .bind(external_id)
+                    .execute(&*self.pool)
+                    .await
+            }
+            None => {
+                let query = format!("DELETE FROM {} WHERE resource_id = ?", table(resource_type));
+                sqlx::query(&query)
+                    .bind(resource_id.to_string())
+                    .execute(&*self.pool)
+                    .await
+            }
+        }
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(())
+    }
+}
@@ -28,8 +28,8 @@ impl SessionRepository for SqliteSessionRepository {
     async fn save(&self, token: &RefreshToken) -> Result<()> {
         sqlx::query(
             "INSERT INTO refresh_tokens
-            (id, family_id, user_id, realm_id, client_id, expires_at, ip_address, user_agent, created_at, last_used_at, revoked_at, replaced_by)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            (id, family_id, user_id, realm_id, client_id, expires_at, ip_address, user_agent, created_at, last_used_at, revoked_at, replaced_by, scope, claims, impersonator_id)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
             .bind(token.id.to_string())
             .bind(token.family_id.to_string())
@@ -43,6 +43,9 @@ impl SessionRepository for SqliteSessionRepository {
             .bind(token.last_used_at)
             .bind(token.revoked_at)
             .bind(token.replaced_by.map(|id| id.to_string()))
+            .bind(&token.scope)
+            .bind(&token.claims)
+            .bind(token.impersonator_id.map(|id| id.to_string()))
             .execute(&*self.pool)
             .await
             .map_err(|e| Error::Unexpected(e.into()))?;
@@ -78,6 +81,41 @@ impl SessionRepository for SqliteSessionRepository {
             .map_err(|e| Error::Unexpected(e.into()))?)
     }
 
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "refresh_tokens", db_op = "select")
+    )]
+    async fn find_active_in_family(&self, family_id: &Uuid) -> Result<Option<RefreshToken>> {
+        Ok(sqlx::query_as(
+            "SELECT * FROM refresh_tokens WHERE family_id = ? AND expires_at > ? AND revoked_at IS NULL AND replaced_by IS NULL ORDER BY created_at DESC LIMIT 1",
+        )
+        .bind(family_id.to_string())
+        .bind(Utc::now())
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "refresh_tokens", db_op = "select")
+    )]
+    async fn list_active_for_user(
+        &self,
+        realm_id: &Uuid,
+        user_id: &Uuid,
+    ) -> Result<Vec<RefreshToken>> {
+        Ok(sqlx::query_as(
+            "SELECT * FROM refresh_tokens WHERE realm_id = ? AND user_id = ? AND expires_at > ? AND revoked_at IS NULL AND replaced_by IS NULL",
+        )
+        .bind(realm_id.to_string())
+        .bind(user_id.to_string())
+        .bind(Utc::now())
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?)
+    }
+
     #[instrument(
         skip_all,
         fields(telemetry = "span", db_table = "refresh_tokens", db_op = "delete")
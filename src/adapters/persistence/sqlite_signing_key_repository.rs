@@ -0,0 +1,205 @@
+use crate::adapters::persistence::connection::Database;
+use crate::domain::signing_key::{SigningAlgorithm, SigningKey, SigningKeyState};
+use crate::error::{Error, Result};
+use crate::ports::signing_key_repository::SigningKeyRepository;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tracing::instrument;
+use uuid::Uuid;
+
+pub struct SqliteSigningKeyRepository {
+    pool: Database,
+}
+
+impl SqliteSigningKeyRepository {
+    pub fn new(pool: Database) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SigningKeyRepository for SqliteSigningKeyRepository {
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "signing_keys", db_op = "insert")
+    )]
+    async fn create(&self, key: &SigningKey) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO signing_keys
+            (id, realm_id, kid, algorithm, state, private_key_pem, public_key_pem, created_at, activated_at, deactivated_at, retired_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(key.id.to_string())
+        .bind(key.realm_id.to_string())
+        .bind(&key.kid)
+        .bind(key.algorithm)
+        .bind(key.state.as_str())
+        .bind(&key.private_key_pem)
+        .bind(&key.public_key_pem)
+        .bind(key.created_at)
+        .bind(key.activated_at)
+        .bind(key.deactivated_at)
+        .bind(key.retired_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "signing_keys", db_op = "select")
+    )]
+    async fn find_by_id(&self, realm_id: &Uuid, id: &Uuid) -> Result<Option<SigningKey>> {
+        sqlx::query_as("SELECT * FROM signing_keys WHERE realm_id = ? AND id = ?")
+            .bind(realm_id.to_string())
+            .bind(id.to_string())
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(|e| Error::Unexpected(e.into()))
+    }
+
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "signing_keys", db_op = "select")
+    )]
+    async fn find_by_kid(&self, kid: &str) -> Result<Option<SigningKey>> {
+        sqlx::query_as("SELECT * FROM signing_keys WHERE kid = ?")
+            .bind(kid)
+            .fetch_optional(&*self.pool)
+            .await
+            .map_err(|e| Error::Unexpected(e.into()))
+    }
+
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "signing_keys", db_op = "select")
+    )]
+    async fn find_active(
+        &self,
+        realm_id: &Uuid,
+        algorithm: SigningAlgorithm,
+    ) -> Result<Option<SigningKey>> {
+        sqlx::query_as(
+            "SELECT * FROM signing_keys WHERE realm_id = ? AND algorithm = ? AND state = ?",
+        )
+        .bind(realm_id.to_string())
+        .bind(algorithm)
+        .bind(SigningKeyState::Active.as_str())
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))
+    }
+
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "signing_keys", db_op = "select")
+    )]
+    async fn list_by_realm(&self, realm_id: &Uuid) -> Result<Vec<SigningKey>> {
+        sqlx::query_as("SELECT * FROM signing_keys WHERE realm_id = ? ORDER BY created_at DESC")
+            .bind(realm_id.to_string())
+            .fetch_all(&*self.pool)
+            .await
+            .map_err(|e| Error::Unexpected(e.into()))
+    }
+
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "signing_keys", db_op = "select")
+    )]
+    async fn list_active(&self) -> Result<Vec<SigningKey>> {
+        sqlx::query_as("SELECT * FROM signing_keys WHERE state = ?")
+            .bind(SigningKeyState::Active.as_str())
+            .fetch_all(&*self.pool)
+            .await
+            .map_err(|e| Error::Unexpected(e.into()))
+    }
+
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "signing_keys", db_op = "update")
+    )]
+    async fn activate(&self, realm_id: &Uuid, id: &Uuid, at: DateTime<Utc>) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| Error::Unexpected(e.into()))?;
+
+        sqlx::query(
+            "UPDATE signing_keys SET state = ?, deactivated_at = ?
+             WHERE realm_id = ? AND state = ?
+               AND algorithm = (SELECT algorithm FROM signing_keys WHERE realm_id = ? AND id = ?)",
+        )
+        .bind(SigningKeyState::Passive.as_str())
+        .bind(at)
+        .bind(realm_id.to_string())
+        .bind(SigningKeyState::Active.as_str())
+        .bind(realm_id.to_string())
+        .bind(id.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+
+        let result = sqlx::query(
+            "UPDATE signing_keys SET state = ?, activated_at = ?, deactivated_at = NULL
+             WHERE realm_id = ? AND id = ? AND state = ?",
+        )
+        .bind(SigningKeyState::Active.as_str())
+        .bind(at)
+        .bind(realm_id.to_string())
+        .bind(id.to_string())
+        .bind(SigningKeyState::Passive.as_str())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound("Signing key not found".to_string()));
+        }
+
+        tx.commit().await.map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "signing_keys", db_op = "update")
+    )]
+    async fn retire(&self, realm_id: &Uuid, id: &Uuid, at: DateTime<Utc>) -> Result<()> {
+        let result = sqlx::query(
+            "UPDATE signing_keys SET state = ?, retired_at = ? WHERE realm_id = ? AND id = ? AND state = ?",
+        )
+        .bind(SigningKeyState::Retired.as_str())
+        .bind(at)
+        .bind(realm_id.to_string())
+        .bind(id.to_string())
+        .bind(SigningKeyState::Passive.as_str())
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound("Signing key not found".to_string()));
+        }
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "signing_keys", db_op = "update")
+    )]
+    async fn retire_passive_before(&self, cutoff: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query(
+            "UPDATE signing_keys SET state = ?, retired_at = ? WHERE state = ? AND deactivated_at < ?",
+        )
+        .bind(SigningKeyState::Retired.as_str())
+        .bind(Utc::now())
+        .bind(SigningKeyState::Passive.as_str())
+        .bind(cutoff)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(result.rows_affected())
+    }
+}
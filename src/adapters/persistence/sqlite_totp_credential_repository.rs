@@ -0,0 +1,144 @@
+use crate::adapters::persistence::connection::Database;
+use crate::domain::totp_credential::TotpCredential;
+use crate::error::{Error, Result};
+use crate::ports::totp_credential_repository::TotpCredentialRepository;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tracing::instrument;
+use uuid::Uuid;
+
+pub struct SqliteTotpCredentialRepository {
+    pool: Database,
+}
+
+impl SqliteTotpCredentialRepository {
+    pub fn new(pool: Database) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct TotpCredentialRecord {
+    id: String,
+    realm_id: String,
+    user_id: String,
+    secret_encrypted: String,
+    digits: i64,
+    period_secs: i64,
+    last_used_step: Option<i64>,
+    last_used_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl TotpCredentialRecord {
+    fn into_domain(self) -> Result<TotpCredential> {
+        Ok(TotpCredential {
+            id: Uuid::parse_str(&self.id)
+                .map_err(|_| Error::System("Invalid TOTP credential id".to_string()))?,
+            realm_id: Uuid::parse_str(&self.realm_id)
+                .map_err(|_| Error::System("Invalid TOTP realm id".to_string()))?,
+            user_id: Uuid::parse_str(&self.user_id)
+                .map_err(|_| Error::System("Invalid TOTP user id".to_string()))?,
+            secret_encrypted: self.secret_encrypted,
+            digits: self.digits,
+            period_secs: self.period_secs,
+            last_used_step: self.last_used_step,
+            last_used_at: self.last_used_at,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        })
+    }
+}
+
+#[async_trait]
+impl TotpCredentialRepository for SqliteTotpCredentialRepository {
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "totp_credentials", db_op = "insert")
+    )]
+    async fn create(&self, credential: &TotpCredential) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO totp_credentials (
+                id, realm_id, user_id, secret_encrypted, digits, period_secs,
+                last_used_step, last_used_at, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(credential.id.to_string())
+        .bind(credential.realm_id.to_string())
+        .bind(credential.user_id.to_string())
+        .bind(&credential.secret_encrypted)
+        .bind(credential.digits)
+        .bind(credential.period_secs)
+        .bind(credential.last_used_step)
+        .bind(credential.last_used_at)
+        .bind(credential.created_at)
+        .bind(credential.updated_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "totp_credentials", db_op = "select")
+    )]
+    async fn find_by_user(
+        &self,
+        realm_id: &Uuid,
+        user_id: &Uuid,
+    ) -> Result<Option<TotpCredential>> {
+        let record: Option<TotpCredentialRecord> =
+            sqlx::query_as("SELECT * FROM totp_credentials WHERE realm_id = ? AND user_id = ?")
+                .bind(realm_id.to_string())
+                .bind(user_id.to_string())
+                .fetch_optional(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+
+        record.map(TotpCredentialRecord::into_domain).transpose()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "totp_credentials", db_op = "update")
+    )]
+    async fn record_use(
+        &self,
+        credential_id: &Uuid,
+        step: i64,
+        used_at: DateTime<Utc>,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE totp_credentials
+             SET last_used_step = ?, last_used_at = ?, updated_at = ?
+             WHERE id = ? AND (last_used_step IS NULL OR last_used_step < ?)",
+        )
+        .bind(step)
+        .bind(used_at)
+        .bind(Utc::now())
+        .bind(credential_id.to_string())
+        .bind(step)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "totp_credentials", db_op = "delete")
+    )]
+    async fn delete_by_user(&self, realm_id: &Uuid, user_id: &Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM totp_credentials WHERE realm_id = ? AND user_id = ?")
+            .bind(realm_id.to_string())
+            .bind(user_id.to_string())
+            .execute(&*self.pool)
+            .await
+            .map_err(|e| Error::Unexpected(e.into()))?;
+
+        Ok(result.rows_affected() == 1)
+    }
+}
@@ -0,0 +1,542 @@
+use crate::adapters::persistence::connection::Database;
+use crate::domain::ldap::SearchScope;
+use crate::domain::user_federation::{
+    FederationEditMode, FederationMapperKind, FederationVendor, UserAttribute, UserFederationLink,
+    UserFederationMapper, UserFederationProvider,
+};
+use crate::error::{Error, Result};
+use crate::ports::user_federation_repository::UserFederationRepository;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tracing::instrument;
+use uuid::Uuid;
+
+pub struct SqliteUserFederationRepository {
+    pool: Database,
+}
+
+impl SqliteUserFederationRepository {
+    pub fn new(pool: Database) -> Self {
+        Self { pool }
+    }
+}
+
+fn parse_id(value: &str, what: &str) -> Result<Uuid> {
+    Uuid::parse_str(value).map_err(|_| Error::System(format!("Invalid {}", what)))
+}
+
+#[derive(sqlx::FromRow)]
+struct ProviderRecord {
+    id: String,
+    realm_id: String,
+    name: String,
+    enabled: bool,
+    vendor: String,
+    connection_url: String,
+    bind_dn: Option<String>,
+    bind_credential: Option<String>,
+    users_dn: String,
+    user_object_classes: String,
+    custom_user_filter: Option<String>,
+    search_scope: String,
+    username_attribute: String,
+    uuid_attribute: String,
+    membership_attribute: String,
+    edit_mode: String,
+    sync_interval_secs: i64,
+    priority: i64,
+    last_sync_at: Option<DateTime<Utc>>,
+    last_sync_error: Option<String>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl ProviderRecord {
+    fn into_domain(self) -> Result<UserFederationProvider> {
+        Ok(UserFederationProvider {
+            id: parse_id(&self.id, "user federation provider id")?,
+            realm_id: parse_id(&self.realm_id, "user federation provider realm id")?,
+            name: self.name,
+            enabled: self.enabled,
+            vendor: FederationVendor::try_from(self.vendor).map_err(Error::System)?,
+            connection_url: self.connection_url,
+            bind_dn: self.bind_dn,
+            bind_credential: self.bind_credential,
+            users_dn: self.users_dn,
+            user_object_classes: serde_json::from_str(&self.user_object_classes)
+                .map_err(|_| Error::System("Invalid user object classes".to_string()))?,
+            custom_user_filter: self.custom_user_filter,
+            search_scope: SearchScope::try_from(self.search_scope).map_err(Error::System)?,
+            username_attribute: self.username_attribute,
+            uuid_attribute: self.uuid_attribute,
+            membership_attribute: self.membership_attribute,
+            edit_mode: FederationEditMode::try_from(self.edit_mode).map_err(Error::System)?,
+            sync_interval_secs: self.sync_interval_secs,
+            priority: self.priority,
+            last_sync_at: self.last_sync_at,
+            last_sync_error: self.last_sync_error,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        })
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct MapperRecord {
+    id: String,
+    provider_id: String,
+    name: String,
+    mapper_type: String,
+    ldap_attribute: Option<String>,
+    user_attribute: Option<String>,
+    group_dn: Option<String>,
+    role_id: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+impl MapperRecord {
+    fn into_domain(self) -> Result<UserFederationMapper> {
+        let missing =
+            |column: &str| Error::System(format!("User federation mapper is missing {}", column));
+        let kind = match self.mapper_type.as_str() {
+            "attribute" => FederationMapperKind::Attribute {
+                ldap_attribute: self
+                    .ldap_attribute
+                    .ok_or_else(|| missing("ldap_attribute"))?,
+                user_attribute: UserAttribute::try_from(
+                    self.user_attribute
+                        .ok_or_else(|| missing("user_attribute"))?,
+                )
+                .map_err(Error::System)?,
+            },
+            "group_role" => FederationMapperKind::GroupRole {
+                group_dn: self.group_dn.ok_or_else(|| missing("group_dn"))?,
+                role_id: parse_id(
+                    &self.role_id.ok_or_else(|| missing("role_id"))?,
+                    "user federation mapper role id",
+                )?,
+            },
+            other => {
+                return Err(Error::System(format!(
+                    "Unknown user federation mapper type '{}'",
+                    other
+                )))
+            }
+        };
+        Ok(UserFederationMapper {
+            id: parse_id(&self.id, "user federation mapper id")?,
+            provider_id: parse_id(&self.provider_id, "user federation mapper provider id")?,
+            name: self.name,
+            kind,
+            created_at: self.created_at,
+        })
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct LinkRecord {
+    user_id: String,
+    provider_id: String,
+    external_id: String,
+    dn: String,
+    last_synced_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+}
+
+impl LinkRecord {
+    fn into_domain(self) -> Result<UserFederationLink> {
+        Ok(UserFederationLink {
+            user_id: parse_id(&self.user_id, "user federation link user id")?,
+            provider_id: parse_id(&self.provider_id, "user federation link provider id")?,
+            external_id: self.external_id,
+            dn: self.dn,
+            last_synced_at: self.last_synced_at,
+            created_at: self.created_at,
+        })
+    }
+}
+
+#[async_trait]
+impl UserFederationRepository for SqliteUserFederationRepository {
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "user_federation_providers",
+            db_op = "insert"
+        )
+    )]
+    async fn create_provider(&self, provider: &UserFederationProvider) -> Result<()> {
+        let object_classes = serde_json::to_string(&provider.user_object_classes)
+            .map_err(|e| Error::Unexpected(e.into()))?;
+        sqlx::query(
+            "INSERT INTO user_federation_providers (
+                id, realm_id, name, enabled, vendor, connection_url, bind_dn, bind_credential,
+                users_dn, user_object_classes, custom_user_filter, search_scope,
+                username_attribute, uuid_attribute, membership_attribute, edit_mode,
+                sync_interval_secs, priority, last_sync_at, last_sync_error, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(provider.id.to_string())
+        .bind(provider.realm_id.to_string())
+        .bind(&provider.name)
+        .bind(provider.enabled)
+        .bind(provider.vendor.to_string())
+        .bind(&provider.connection_url)
+        .bind(&provider.bind_dn)
+        .bind(&provider.bind_credential)
+        .bind(&provider.users_dn)
+        .bind(object_classes)
+        .bind(&provider.custom_user_filter)
+        .bind(provider.search_scope.to_string())
+        .bind(&provider.username_attribute)
+        .bind(&provider.uuid_attribute)
+        .bind(&provider.membership_attribute)
+        .bind(provider.edit_mode.to_string())
+        .bind(provider.sync_interval_secs)
+        .bind(provider.priority)
+        .bind(provider.last_sync_at)
+        .bind(&provider.last_sync_error)
+        .bind(provider.created_at)
+        .bind(provider.updated_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "user_federation_providers",
+            db_op = "update"
+        )
+    )]
+    async fn update_provider(&self, provider: &UserFederationProvider) -> Result<()> {
+        let object_classes = serde_json::to_string(&provider.user_object_classes)
+            .map_err(|e| Error::Unexpected(e.into()))?;
+        sqlx::query(
+            "UPDATE user_federation_providers SET
+                name = ?, enabled = ?, vendor = ?, connection_url = ?, bind_dn = ?,
+                bind_credential = ?, users_dn = ?, user_object_classes = ?,
+                custom_user_filter = ?, search_scope = ?, username_attribute = ?,
+                uuid_attribute = ?, membership_attribute = ?, edit_mode = ?,
+                sync_interval_secs = ?, priority = ?, last_sync_at = ?, last_sync_error = ?,
+                updated_at = ?
+            WHERE realm_id = ? AND id = ?",
+        )
+        .bind(&provider.name)
+        .bind(provider.enabled)
+        .bind(provider.vendor.to_string())
+        .bind(&provider.connection_url)
+        .bind(&provider.bind_dn)
+        .bind(&provider.bind_credential)
+        .bind(&provider.users_dn)
+        .bind(object_classes)
+        .bind(&provider.custom_user_filter)
+        .bind(provider.search_scope.to_string())
+        .bind(&provider.username_attribute)
+        .bind(&provider.uuid_attribute)
+        .bind(&provider.membership_attribute)
+        .bind(provider.edit_mode.to_string())
+        .bind(provider.sync_interval_secs)
+        .bind(provider.priority)
+        .bind(provider.last_sync_at)
+        .bind(&provider.last_sync_error)
+        .bind(provider.updated_at)
+        .bind(provider.realm_id.to_string())
+        .bind(provider.id.to_string())
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "user_federation_providers",
+            db_op = "select"
+        )
+    )]
+    async fn find_provider(
+        &self,
+        realm_id: &Uuid,
+        id: &Uuid,
+    ) -> Result<Option<UserFederationProvider>> {
+        let record: Option<ProviderRecord> =
+            sqlx::query_as("SELECT * FROM user_federation_providers WHERE realm_id = ? AND id = ?")
+                .bind(realm_id.to_string())
+                .bind(id.to_string())
+                .fetch_optional(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        record.map(ProviderRecord::into_domain).transpose()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "user_federation_providers",
+            db_op = "select"
+        )
+    )]
+    async fn find_provider_by_name(
+        &self,
+        realm_id: &Uuid,
+        name: &str,
+    ) -> Result<Option<UserFederationProvider>> {
+        let record: Option<ProviderRecord> = sqlx::query_as(
+            "SELECT * FROM user_federation_providers WHERE realm_id = ? AND name = ?",
+        )
+        .bind(realm_id.to_string())
+        .bind(name)
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        record.map(ProviderRecord::into_domain).transpose()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "user_federation_providers",
+            db_op = "select"
+        )
+    )]
+    async fn list_providers(&self, realm_id: &Uuid) -> Result<Vec<UserFederationProvider>> {
+        let records: Vec<ProviderRecord> = sqlx::query_as(
+            "SELECT * FROM user_federation_providers WHERE realm_id = ? ORDER BY priority, name",
+        )
+        .bind(realm_id.to_string())
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        records
+            .into_iter()
+            .map(ProviderRecord::into_domain)
+            .collect()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "user_federation_providers",
+            db_op = "select"
+        )
+    )]
+    async fn list_enabled_providers(&self) -> Result<Vec<UserFederationProvider>> {
+        let records: Vec<ProviderRecord> = sqlx::query_as(
+            "SELECT * FROM user_federation_providers WHERE enabled = TRUE ORDER BY realm_id, priority, name",
+        )
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        records
+            .into_iter()
+            .map(ProviderRecord::into_domain)
+            .collect()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "user_federation_providers",
+            db_op = "delete"
+        )
+    )]
+    async fn delete_provider(&self, realm_id: &Uuid, id: &Uuid) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM user_federation_providers WHERE realm_id = ? AND id = ?")
+                .bind(realm_id.to_string())
+                .bind(id.to_string())
+                .execute(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "user_federation_mappers",
+            db_op = "insert"
+        )
+    )]
+    async fn create_mapper(&self, mapper: &UserFederationMapper) -> Result<()> {
+        let (mapper_type, ldap_attribute, user_attribute, group_dn, role_id) = match &mapper.kind {
+            FederationMapperKind::Attribute {
+                ldap_attribute,
+                user_attribute,
+            } => (
+                "attribute",
+                Some(ldap_attribute.clone()),
+                Some(user_attribute.to_string()),
+                None,
+                None,
+            ),
+            FederationMapperKind::GroupRole { group_dn, role_id } => (
+                "group_role",
+                None,
+                None,
+                Some(group_dn.clone()),
+                Some(role_id.to_string()),
+            ),
+        };
+        sqlx::query(
+            "INSERT INTO user_federation_mappers (
+                id, provider_id, name, mapper_type, ldap_attribute, user_attribute,
+                group_dn, role_id, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(mapper.id.to_string())
+        .bind(mapper.provider_id.to_string())
+        .bind(&mapper.name)
+        .bind(mapper_type)
+        .bind(ldap_attribute)
+        .bind(user_attribute)
+        .bind(group_dn)
+        .bind(role_id)
+        .bind(mapper.created_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "user_federation_mappers",
+            db_op = "select"
+        )
+    )]
+    async fn list_mappers(&self, provider_id: &Uuid) -> Result<Vec<UserFederationMapper>> {
+        let records: Vec<MapperRecord> = sqlx::query_as(
+            "SELECT * FROM user_federation_mappers WHERE provider_id = ? ORDER BY created_at, name",
+        )
+        .bind(provider_id.to_string())
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        records.into_iter().map(MapperRecord::into_domain).collect()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "user_federation_mappers",
+            db_op = "delete"
+        )
+    )]
+    async fn delete_mapper(&self, provider_id: &Uuid, id: &Uuid) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM user_federation_mappers WHERE provider_id = ? AND id = ?")
+                .bind(provider_id.to_string())
+                .bind(id.to_string())
+                .execute(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "user_federation_links",
+            db_op = "upsert"
+        )
+    )]
+    async fn save_link(&self, link: &UserFederationLink) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO user_federation_links (
+                user_id, provider_id, external_id, dn, last_synced_at, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT(user_id) DO UPDATE SET
+                provider_id = excluded.provider_id,
+                external_id = excluded.external_id,
+                dn = excluded.dn,
+                last_synced_at = excluded.last_synced_at",
+        )
+        .bind(link.user_id.to_string())
+        .bind(link.provider_id.to_string())
+        .bind(&link.external_id)
+        .bind(&link.dn)
+        .bind(link.last_synced_at)
+        .bind(link.created_at)
+        .execute(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "user_federation_links",
+            db_op = "select"
+        )
+    )]
+    async fn find_link_by_user(&self, user_id: &Uuid) -> Result<Option<UserFederationLink>> {
+        let record: Option<LinkRecord> =
+            sqlx::query_as("SELECT * FROM user_federation_links WHERE user_id = ?")
+                .bind(user_id.to_string())
+                .fetch_optional(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        record.map(LinkRecord::into_domain).transpose()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "user_federation_links",
+            db_op = "select"
+        )
+    )]
+    async fn find_link_by_external_id(
+        &self,
+        provider_id: &Uuid,
+        external_id: &str,
+    ) -> Result<Option<UserFederationLink>> {
+        let record: Option<LinkRecord> = sqlx::query_as(
+            "SELECT * FROM user_federation_links WHERE provider_id = ? AND external_id = ?",
+        )
+        .bind(provider_id.to_string())
+        .bind(external_id)
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+        record.map(LinkRecord::into_domain).transpose()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            telemetry = "span",
+            db_table = "user_federation_links",
+            db_op = "count"
+        )
+    )]
+    async fn count_links(&self, provider_id: &Uuid) -> Result<u64> {
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM user_federation_links WHERE provider_id = ?")
+                .bind(provider_id.to_string())
+                .fetch_one(&*self.pool)
+                .await
+                .map_err(|e| Error::Unexpected(e.into()))?;
+        Ok(count as u64)
+    }
+}
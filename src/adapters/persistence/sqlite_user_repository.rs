@@ -99,6 +99,31 @@ impl UserRepository for SqliteUserRepository {
         Ok(user)
     }
 
+    #[instrument(
+        skip_all,
+        fields(telemetry = "span", db_table = "users", db_op = "select")
+    )]
+    async fn find_by_username_with_tx(
+        &self,
+        realm_id: &Uuid,
+        username: &str,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<Option<User>> {
+        let query = sqlx::query_as("SELECT * FROM users WHERE realm_id = ? AND username = ?")
+            .bind(realm_id.to_string())
+            .bind(username);
+
+        let user = if let Some(tx) = tx {
+            let sql_tx = SqliteTransaction::from_trait(tx).expect("Invalid TX");
+            query.fetch_optional(&mut **sql_tx).await
+        } else {
+            query.fetch_optional(&*self.pool).await
+        }
+        .map_err(|e| Error::Unexpected(e.into()))?;
+
+        Ok(user)
+    }
+
     #[instrument(
         skip_all,
         fields(telemetry = "span", db_table = "users", db_op = "select")
@@ -0,0 +1,164 @@
+use crate::config::SmsConfig;
+use crate::error::{Error, Result};
+use crate::ports::http_client::{HttpDeliveryClient, HttpDeliveryRequest};
+use crate::ports::sms_sender::{SmsMessage, SmsSender};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Posts messages to a provider-agnostic HTTP gateway. The request body is
+/// rendered from `gateway_body_template` so most SMS APIs can be targeted
+/// without code changes.
+pub struct HttpSmsSender {
+    client: Arc<dyn HttpDeliveryClient>,
+    url: String,
+    method: String,
+    authorization: Option<String>,
+    content_type: String,
+    body_template: String,
+}
+
+impl HttpSmsSender {
+    pub fn new(client: Arc<dyn HttpDeliveryClient>, config: &SmsConfig) -> Self {
+        let authorization =
+            Some(config.gateway_authorization.trim().to_string()).filter(|value| !value.is_empty());
+        Self {
+            client,
+            url: config.gateway_url.trim().to_string(),
+            method: config.gateway_method.trim().to_ascii_uppercase(),
+            authorization,
+            content_type: config.gateway_content_type.trim().to_string(),
+            body_template: config.gateway_body_template.clone(),
+        }
+    }
+
+    fn render_body(&self, message: &SmsMessage) -> String {
+        self.body_template
+            .replace("{to}", &json_escape(&message.to))
+            .replace("{message}", &json_escape(&message.body))
+            .replace("{realm}", &json_escape(&message.realm_name))
+    }
+}
+
+fn json_escape(value: &str) -> String {
+    let quoted = serde_json::Value::String(value.to_string()).to_string();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+#[async_trait]
+impl SmsSender for HttpSmsSender {
+    async fn send(&self, message: &SmsMessage) -> Result<()> {
+        let mut headers = HashMap::new();
+        if !self.content_type.is_empty() {
+            headers.insert("Content-Type".to_string(), self.content_type.clone());
+        }
+        if let Some(authorization) = &self.authorization {
+            headers.insert("Authorization".to_string(), authorization.clone());
+        }
+
+        let response = self
+            .client
+            .send(HttpDeliveryRequest {
+                method: self.method.clone(),
+                url: self.url.clone(),
+                headers,
+                body: self.render_body(message),
+            })
+            .await
+            .map_err(|err| Error::System(format!("SMS gateway request failed: {}", err)))?;
+
+        if !(200..300).contains(&response.status_code) {
+            warn!(
+                status = response.status_code,
+                "SMS gateway rejected message for realm {}", message.realm_name
+            );
+            return Err(Error::System(format!(
+                "SMS gateway returned status {}",
+                response.status_code
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ports::http_client::{HttpDeliveryError, HttpDeliveryResponse};
+    use std::sync::Mutex;
+    use uuid::Uuid;
+
+    struct RecordingClient {
+        status_code: u16,
+        requests: Mutex<Vec<HttpDeliveryRequest>>,
+    }
+
+    #[async_trait]
+    impl HttpDeliveryClient for RecordingClient {
+        async fn send(
+            &self,
+            request: HttpDeliveryRequest,
+        ) -> std::result::Result<HttpDeliveryResponse, HttpDeliveryError> {
+            self.requests.lock().unwrap().push(request);
+            Ok(HttpDeliveryResponse {
+                status_code: self.status_code,
+                body: String::new(),
+            })
+        }
+    }
+
+    fn message() -> SmsMessage {
+        SmsMessage {
+            realm_id: Uuid::new_v4(),
+            realm_name: "master".to_string(),
+            to: "+1 555 0100".to_string(),
+            body: "Your code is \"123456\"".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn renders_template_with_escaped_values_and_auth_header() {
+        let client = Arc::new(RecordingClient {
+            status_code: 202,
+            requests: Mutex::new(Vec::new()),
+        });
+        let config = SmsConfig {
+            provider: "http".to_string(),
+            gateway_url: "https://sms.example.test/send".to_string(),
+            gateway_authorization: "Bearer gateway-key".to_string(),
+            ..SmsConfig::default()
+        };
+        let sender = HttpSmsSender::new(client.clone(), &config);
+
+        sender.send(&message()).await.expect("send");
+
+        let requests = client.requests.lock().unwrap();
+        let request = &requests[0];
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.url, "https://sms.example.test/send");
+        assert_eq!(
+            request.headers.get("Authorization").map(String::as_str),
+            Some("Bearer gateway-key")
+        );
+        let body: serde_json::Value = serde_json::from_str(&request.body).expect("json body");
+        assert_eq!(body["to"], "+1 555 0100");
+        assert_eq!(body["message"], "Your code is \"123456\"");
+    }
+
+    #[tokio::test]
+    async fn non_success_status_is_an_error() {
+        let client = Arc::new(RecordingClient {
+            status_code: 500,
+            requests: Mutex::new(Vec::new()),
+        });
+        let config = SmsConfig {
+            provider: "http".to_string(),
+            gateway_url: "https://sms.example.test/send".to_string(),
+            ..SmsConfig::default()
+        };
+        let sender = HttpSmsSender::new(client, &config);
+
+        assert!(sender.send(&message()).await.is_err());
+    }
+}
@@ -0,0 +1,95 @@
+use crate::error::{Error, Result};
+use crate::ports::sms_sender::{SmsMessage, SmsSender};
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::json;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tracing::info;
+
+/// Development sender: appends each message as a JSON line to a local file
+/// instead of contacting a gateway.
+pub struct LogSmsSender {
+    path: PathBuf,
+    write_lock: Mutex<()>,
+}
+
+impl LogSmsSender {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    fn append(&self, line: &str) -> std::io::Result<()> {
+        let _guard = self
+            .write_lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", line)
+    }
+}
+
+#[async_trait]
+impl SmsSender for LogSmsSender {
+    async fn send(&self, message: &SmsMessage) -> Result<()> {
+        let line = json!({
+            "sent_at": Utc::now(),
+            "realm": message.realm_name,
+            "to": message.to,
+            "message": message.body,
+        })
+        .to_string();
+        self.append(&line)
+            .map_err(|err| Error::System(format!("Failed to write SMS log: {}", err)))?;
+        info!(
+            realm = %message.realm_name,
+            path = %self.path.display(),
+            "SMS written to development outbox"
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn appends_one_json_line_per_message() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let path = dir.path().join("nested").join("sms.log");
+        let sender = LogSmsSender::new(&path);
+        for body in ["first", "second"] {
+            sender
+                .send(&SmsMessage {
+                    realm_id: Uuid::new_v4(),
+                    realm_name: "master".to_string(),
+                    to: "+15550100".to_string(),
+                    body: body.to_string(),
+                })
+                .await
+                .expect("send");
+        }
+
+        let contents = std::fs::read_to_string(&path).expect("read log");
+        let lines: Vec<serde_json::Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("json line"))
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["to"], "+15550100");
+        assert_eq!(lines[1]["message"], "second");
+    }
+}
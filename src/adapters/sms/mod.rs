@@ -0,0 +1,2 @@
+pub mod http_sms_sender;
+pub mod log_sms_sender;
@@ -3,7 +3,9 @@ use crate::application::passkey_assertion_service::{
     BeginAssertionRequest, BeginEnrollmentRequest, VerifyAssertionRequest, VerifyEnrollmentRequest,
 };
 use crate::application::realm_policy::RealmCapabilities;
-use crate::domain::oidc::OidcContext;
+use crate::domain::claims::ClaimsGrant;
+use crate::domain::oidc::{DeviceContext, OidcContext};
+use crate::domain::saml::SAML_CONTEXT_KEY;
 use crate::{
     constants::{LOGIN_SESSION_COOKIE, REFRESH_TOKEN_COOKIE},
     domain::{
@@ -117,6 +119,28 @@ pub(crate) fn create_login_cookie(session_id: Uuid) -> Cookie<'static> {
         .into()
 }
 
+/// Creates a "Root" session (client_id = None) for global SSO and sets its
+/// refresh cookie.
+async fn append_root_session_cookie(
+    state: &AppState,
+    user_id: Uuid,
+    ip_address: String,
+    headers: &mut HeaderMap,
+) -> Result<RefreshToken> {
+    let user = state.user_service.get_user(user_id).await?;
+    let (_, refresh_token) = state
+        .auth_service
+        .create_session(&user, None, Some(ip_address), None)
+        .await?;
+
+    let refresh_cookie = create_refresh_cookie(&refresh_token);
+    headers.append(
+        header::SET_COOKIE,
+        HeaderValue::from_str(&refresh_cookie.to_string())?,
+    );
+    Ok(refresh_token)
+}
+
 // GET /api/auth/login
 // This handles generating OIDC codes OR Dashboard Cookies upon flow completion
 pub(crate) async fn handle_flow_success(
@@ -143,7 +167,88 @@ pub(crate) async fn handle_flow_success(
         .user_id
         .ok_or(Error::System("Authenticated user not found".into()))?;
 
-    // 3. PRIORITY 1: OIDC (Dummy App / External Clients)
+    // 3. PRIORITY 1: Device verification (RFC 8628). The device gets its
+    // tokens by polling; the browser only needs a confirmation page.
+    if let Some(device_value) = final_session.context.get("device") {
+        if let Ok(device_ctx) = serde_json::from_value::<DeviceContext>(device_value.clone()) {
+            state
+                .oidc_service
+                .approve_device_authorization(
+                    final_session.realm_id,
+                    &device_ctx.device_code,
+                    user_id,
+                )
+                .await?;
+
+            if final_session.context.get("sso_token_id").is_none() {
+                append_root_session_cookie(state, user_id, ip_address, &mut headers).await?;
+            }
+
+            let realm = state
+                .realm_service
+                .find_by_id(final_session.realm_id)
+                .await?
+                .ok_or(Error::InvalidLoginSession)?;
+            let url = format!("/api/realms/{}/oidc/device/complete", realm.name);
+
+            return Ok((
+                StatusCode::OK,
+                headers,
+                Json(serde_json::json!({
+                   "status": "redirect", "url": url
+                })),
+            )
+                .into_response());
+        }
+    }
+
+    // 4. PRIORITY 2: SAML. The assertion's SessionIndex is the family of
+    // the browser's SSO session, so SP-initiated logout can end it.
+    if final_session.context.get(SAML_CONTEXT_KEY).is_some() {
+        let sso_token = match final_session
+            .context
+            .get("sso_token_id")
+            .and_then(|v| v.as_str())
+            .and_then(|v| Uuid::parse_str(v).ok())
+        {
+            Some(token_id) => state.session_repo.find_by_id(&token_id).await?,
+            None => None,
+        };
+        let family_id = match sso_token {
+            Some(token) => token.family_id,
+            None => {
+                append_root_session_cookie(state, user_id, ip_address, &mut headers)
+                    .await?
+                    .family_id
+            }
+        };
+
+        state
+            .saml_service
+            .complete_login(final_session.id, user_id, family_id)
+            .await?;
+
+        let realm = state
+            .realm_service
+            .find_by_id(final_session.realm_id)
+            .await?
+            .ok_or(Error::InvalidLoginSession)?;
+        let url = format!(
+            "/api/realms/{}/saml/sso/complete?session={}",
+            realm.name, final_session.id
+        );
+
+        return Ok((
+            StatusCode::OK,
+            headers,
+            Json(serde_json::json!({
+               "status": "redirect", "url": url
+            })),
+        )
+            .into_response());
+    }
+
+    // 5. PRIORITY 3: OIDC (Dummy App / External Clients)
     if let Some(oidc_value) = final_session.context.get("oidc") {
         if let Ok(oidc_ctx) = serde_json::from_value::<OidcContext>(oidc_value.clone()) {
             // [OPTIMIZATION] Root Session Management
@@ -153,18 +258,7 @@ pub(crate) async fn handle_flow_success(
             let sso_cookie_update_needed = final_session.context.get("sso_token_id").is_none();
 
             if sso_cookie_update_needed {
-                let user = state.user_service.get_user(user_id).await?;
-                // Create a "Root" session (client_id = None) for global SSO
-                let (_, refresh_token) = state
-                    .auth_service
-                    .create_session(&user, None, Some(ip_address), None)
-                    .await?;
-
-                let refresh_cookie = create_refresh_cookie(&refresh_token);
-                headers.append(
-                    header::SET_COOKIE,
-                    HeaderValue::from_str(&refresh_cookie.to_string())?,
-                );
+                append_root_session_cookie(state, user_id, ip_address, &mut headers).await?;
             }
 
             // Generate Authorization Code for the specific App
@@ -180,6 +274,7 @@ pub(crate) async fn handle_flow_success(
                     oidc_ctx
                         .code_challenge_method
                         .unwrap_or_else(|| "S256".to_string()),
+                    ClaimsGrant::from_stored(oidc_ctx.scope, oidc_ctx.claims.as_deref()),
                 )
                 .await?;
 
@@ -202,20 +297,10 @@ pub(crate) async fn handle_flow_success(
         }
     }
 
-    // 4. PRIORITY 2: Dashboard (Direct Login)
+    // 6. PRIORITY 4: Dashboard (Direct Login)
     if redirect_url == "/" {
         // Dashboard login always refreshes the Root Session
-        let user = state.user_service.get_user(user_id).await?;
-        let (_login_resp, refresh_token) = state
-            .auth_service
-            .create_session(&user, None, Some(ip_address), None)
-            .await?;
-
-        let refresh_cookie = create_refresh_cookie(&refresh_token);
-        headers.append(
-            header::SET_COOKIE,
-            HeaderValue::from_str(&refresh_cookie.to_string())?,
-        );
+        append_root_session_cookie(state, user_id, ip_address, &mut headers).await?;
 
         return Ok((
             StatusCode::OK,
@@ -227,7 +312,7 @@ pub(crate) async fn handle_flow_success(
             .into_response());
     }
 
-    // 5. Generic Redirect (Fallback)
+    // 7. Generic Redirect (Fallback)
     Ok((
         StatusCode::OK,
         headers,
@@ -1,10 +1,14 @@
 use crate::adapters::web::middleware::request_logging::RequestContext;
+use crate::application::audit_service;
 use crate::constants::ACCESS_TOKEN_COOKIE;
-use crate::{domain::user::User, AppState};
+use crate::{
+    domain::{audit::NewAuditEvent, session::RefreshToken, user::User},
+    AppState,
+};
 use axum::{
     body::Body,
     extract::State,
-    http::{header, Request, StatusCode},
+    http::{header, Method, Request, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
     Json,
@@ -22,6 +26,11 @@ pub struct AuthUser(pub User);
 #[derive(Clone, Copy)]
 pub struct CurrentSessionId(pub uuid::Uuid);
 
+/// The admin impersonating the authenticated user, attached to requests made
+/// with an impersonation session.
+#[derive(Clone, Copy)]
+pub struct Impersonator(pub uuid::Uuid);
+
 #[instrument(skip_all, fields(telemetry = "span"))]
 pub async fn auth_guard(
     State(state): State<AppState>,
@@ -63,7 +72,7 @@ pub async fn auth_guard(
         .validate_token_and_get_session(&token)
         .await
     {
-        Ok((user, session_id)) => {
+        Ok((user, session)) => {
             let user_id = user.id;
             // [CRITICAL] Insert ONLY the UUID if your permission_guard expects Uuid
             req.extensions_mut().insert(user_id);
@@ -72,7 +81,7 @@ pub async fn auth_guard(
             req.extensions_mut().insert(AuthUser(user));
 
             // The caller's current session id, for caller-scoped session actions.
-            req.extensions_mut().insert(CurrentSessionId(session_id));
+            req.extensions_mut().insert(CurrentSessionId(session.id));
 
             if let Some(context) = req.extensions().get::<RequestContext>() {
                 context.set_user_id(user_id);
@@ -80,7 +89,30 @@ pub async fn auth_guard(
 
             Span::current().record("user_id", field::display(user_id));
 
-            next.run(req).await
+            match session.impersonator_id {
+                Some(impersonator_id) => {
+                    req.extensions_mut().insert(Impersonator(impersonator_id));
+                    let method = req.method().clone();
+                    let path = req.uri().path().to_string();
+                    let response = audit_service::with_impersonator(impersonator_id, async {
+                        let response = next.run(req).await;
+                        if method != Method::GET && method != Method::HEAD {
+                            record_impersonated_request(
+                                &state,
+                                &session,
+                                &method,
+                                &path,
+                                response.status(),
+                            )
+                            .await;
+                        }
+                        response
+                    })
+                    .await;
+                    response
+                }
+                None => next.run(req).await,
+            }
         }
         Err(e) => {
             // Convert your domain error into an Axum response
@@ -88,3 +120,29 @@ pub async fn auth_guard(
         }
     }
 }
+
+/// Every change made while impersonating is audited, even where the handler
+/// records nothing itself.
+async fn record_impersonated_request(
+    state: &AppState,
+    session: &RefreshToken,
+    method: &Method,
+    path: &str,
+    status: StatusCode,
+) {
+    let event = NewAuditEvent {
+        realm_id: session.realm_id,
+        actor_user_id: Some(session.user_id),
+        action: "impersonation.request".to_string(),
+        target_type: "session".to_string(),
+        target_id: Some(session.id.to_string()),
+        metadata: json!({
+            "method": method.as_str(),
+            "path": path,
+            "status": status.as_u16(),
+        }),
+    };
+    if let Err(err) = state.audit_service.record(event).await {
+        tracing::error!("Failed to write impersonation audit event: {:?}", err);
+    }
+}
@@ -6,6 +6,7 @@ use axum::{
 use serde_json::json;
 
 // Import the application's crate error type
+use crate::domain::password_policy::summarize_violations;
 use crate::error::Error;
 
 /// This is the adapter's translation layer.
@@ -17,10 +18,13 @@ impl IntoResponse for Error {
             | Error::SessionRevoked
             | Error::ReauthRequired
             | Error::InvalidRefreshToken
+            | Error::OidcInvalidClient(_)
             | Error::OidcInvalidCode => (StatusCode::UNAUTHORIZED, self.to_string(), None),
 
             // 403 Forbidden
-            Error::SecurityViolation(_) => (StatusCode::FORBIDDEN, self.to_string(), None),
+            Error::SecurityViolation(_) | Error::InsufficientPermissions => {
+                (StatusCode::FORBIDDEN, self.to_string(), None)
+            }
 
             // 409 Conflict
             Error::UserAlreadyExists
@@ -60,6 +64,15 @@ impl IntoResponse for Error {
                 Some(json!({ "fields": fields })),
             ),
 
+            Error::PasswordPolicy(ref violations) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                self.to_string(),
+                Some(json!({
+                    "fields": { "password": summarize_violations(violations) },
+                    "violations": violations,
+                })),
+            ),
+
             Error::FlowPublishValidation(ref details) => (
                 StatusCode::BAD_REQUEST,
                 details.message.clone(),
@@ -85,7 +98,20 @@ impl IntoResponse for Error {
 
             Error::OidcClientNotFound(_)
             | Error::OidcInvalidRedirect(_)
-            | Error::OidcInvalidRequest(_) => (StatusCode::BAD_REQUEST, self.to_string(), None),
+            | Error::OidcInvalidRequest(_)
+            | Error::OidcUnauthorizedClient(_)
+            | Error::OidcInvalidGrant(_)
+            | Error::OidcInvalidScope(_)
+            | Error::OidcInvalidTarget(_)
+            | Error::OidcAuthorizationPending
+            | Error::OidcSlowDown
+            | Error::OidcExpiredToken
+            | Error::OidcAccessDenied(_)
+            | Error::OidcInvalidRequestUri(_)
+            | Error::OidcInvalidRequestObject(_)
+            | Error::OidcInvalidClientMetadata(_)
+            | Error::SamlInvalidRequest(_) => (StatusCode::BAD_REQUEST, self.to_string(), None),
+            Error::OidcInvalidToken(_) => (StatusCode::UNAUTHORIZED, self.to_string(), None),
 
             Error::Jwt(_) => (
                 StatusCode::UNAUTHORIZED,
@@ -120,6 +146,7 @@ fn error_code(error: &Error) -> &'static str {
         Error::InvalidActionToken => "auth.invalid_action_token",
         Error::OidcInvalidCode => "oidc.invalid_code",
         Error::SecurityViolation(_) => "security.violation",
+        Error::InsufficientPermissions => "rbac.insufficient_permissions",
         Error::UserAlreadyExists => "user.already_exists",
         Error::UsernameAlreadyExists => "user.username_already_exists",
         Error::EmailAlreadyExists => "user.email_already_exists",
@@ -139,10 +166,25 @@ fn error_code(error: &Error) -> &'static str {
         Error::Conflict(_) => "request.conflict",
         Error::FieldsValidation { .. } => "validation.failed",
         Error::FlowPublishValidation(_) => "validation.failed",
+        Error::PasswordPolicy(_) => "password.policy_violation",
         Error::NotFound(_) => "resource.not_found",
         Error::OidcClientNotFound(_) => "oidc.client_not_found",
         Error::OidcInvalidRedirect(_) => "oidc.invalid_redirect",
         Error::OidcInvalidRequest(_) => "oidc.invalid_request",
+        Error::OidcInvalidClient(_) => "oidc.invalid_client",
+        Error::OidcUnauthorizedClient(_) => "oidc.unauthorized_client",
+        Error::OidcInvalidGrant(_) => "oidc.invalid_grant",
+        Error::OidcInvalidScope(_) => "oidc.invalid_scope",
+        Error::OidcInvalidTarget(_) => "oidc.invalid_target",
+        Error::OidcAuthorizationPending => "oidc.authorization_pending",
+        Error::OidcSlowDown => "oidc.slow_down",
+        Error::OidcExpiredToken => "oidc.expired_token",
+        Error::OidcAccessDenied(_) => "oidc.access_denied",
+        Error::OidcInvalidRequestUri(_) => "oidc.invalid_request_uri",
+        Error::OidcInvalidRequestObject(_) => "oidc.invalid_request_object",
+        Error::OidcInvalidClientMetadata(_) => "oidc.invalid_client_metadata",
+        Error::OidcInvalidToken(_) => "oidc.invalid_token",
+        Error::SamlInvalidRequest(_) => "saml.invalid_request",
         Error::Jwt(_) => "auth.invalid_token",
         Error::InvalidHeader(_) => "request.invalid_header",
         Error::Config(_) => "config.error",
@@ -11,6 +11,7 @@ use uuid::Uuid;
 use crate::bootstrap::app_state::AppState;
 use crate::constants::{LOGIN_SESSION_COOKIE, REFRESH_TOKEN_COOKIE};
 use crate::domain::auth_session::AuthenticationSession;
+use crate::domain::claims::ClaimsGrant;
 use crate::domain::execution::{ExecutionPlan, ExecutionResult};
 use crate::domain::oidc::OidcContext;
 use crate::domain::session::RefreshToken;
@@ -226,6 +227,7 @@ async fn resolve_redirect_target(
                     oidc_ctx
                         .code_challenge_method
                         .unwrap_or_else(|| "S256".to_string()),
+                    ClaimsGrant::from_stored(oidc_ctx.scope, oidc_ctx.claims.as_deref()),
                 )
                 .await?;
 
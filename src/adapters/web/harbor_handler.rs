@@ -10,7 +10,7 @@ use uuid::Uuid;
 use crate::application::harbor::{
     bootstrap_import_bundle, read_bundle_from_path, resolve_bootstrap_realm_name,
     write_bundle_to_path, ConflictPolicy, ExportPolicy, HarborBundle, HarborImportResult,
-    HarborScope,
+    HarborReconcileResult, HarborScope,
 };
 use crate::domain::harbor_job::HarborJob;
 use crate::domain::permissions;
@@ -40,6 +40,15 @@ pub struct HarborImportRequest {
     pub dry_run: Option<bool>,
 }
 
+#[derive(Deserialize)]
+pub struct HarborReconcileRequest {
+    pub bundle: HarborBundle,
+    #[serde(default)]
+    pub prune: bool,
+    #[serde(default)]
+    pub apply: bool,
+}
+
 #[derive(Deserialize)]
 pub struct HarborBootstrapImportRequest {
     pub realm_name: Option<String>,
@@ -337,6 +346,39 @@ pub async fn import_harbor_bundle_handler(
     Ok((StatusCode::OK, Json(result)).into_response())
 }
 
+pub async fn reconcile_harbor_bundle_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+    Json(payload): Json<HarborReconcileRequest>,
+) -> Result<impl IntoResponse> {
+    let realm = state
+        .realm_service
+        .find_by_name(&realm_name)
+        .await?
+        .ok_or(Error::RealmNotFound(realm_name))?;
+
+    if !payload.apply {
+        let plan = state
+            .harbor_service
+            .plan_reconcile(realm.id, payload.bundle, payload.prune)
+            .await?;
+        return Ok((
+            StatusCode::OK,
+            Json(HarborReconcileResult {
+                applied: false,
+                plan,
+                import: None,
+            }),
+        ));
+    }
+
+    let result = state
+        .harbor_service
+        .apply_reconcile(realm.id, payload.bundle, payload.prune)
+        .await?;
+    Ok((StatusCode::OK, Json(result)))
+}
+
 pub async fn bootstrap_import_harbor_bundle_handler(
     State(state): State<AppState>,
     Query(query): Query<HarborImportQuery>,
@@ -1023,6 +1065,31 @@ fn parse_scope_fields(scope: Option<&str>, id: Option<String>) -> Result<HarborS
                 .map_err(|_| Error::Validation("Invalid role id".to_string()))?;
             Ok(HarborScope::Role { role_id })
         }
+        "identity_provider" => {
+            let id = id.ok_or_else(|| {
+                Error::Validation("Identity provider scope requires id".to_string())
+            })?;
+            let alias = id.trim().to_string();
+            if alias.is_empty() {
+                return Err(Error::Validation(
+                    "Identity provider alias is required".to_string(),
+                ));
+            }
+            Ok(HarborScope::IdentityProvider { alias })
+        }
+        "webhook" => {
+            let id =
+                id.ok_or_else(|| Error::Validation("Webhook scope requires id".to_string()))?;
+            let endpoint_id = Uuid::parse_str(&id)
+                .map_err(|_| Error::Validation("Invalid webhook id".to_string()))?;
+            Ok(HarborScope::Webhook { endpoint_id })
+        }
+        "group" => {
+            let id = id.ok_or_else(|| Error::Validation("Group scope requires id".to_string()))?;
+            let group_id = Uuid::parse_str(&id)
+                .map_err(|_| Error::Validation("Invalid group id".to_string()))?;
+            Ok(HarborScope::Group { group_id })
+        }
         "full_realm" => Ok(HarborScope::FullRealm),
         _ => Err(Error::Validation("Unsupported harbor scope".to_string())),
     }
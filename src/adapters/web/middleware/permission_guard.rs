@@ -1,7 +1,9 @@
+use crate::domain::permissions::AdminResource;
+use crate::error::{Error, Result};
 use crate::AppState;
 use axum::{
     body::Body,
-    extract::State,
+    extract::{OriginalUri, State},
     http::{Request, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
@@ -20,34 +22,137 @@ pub async fn require_permission(
     required_permission: &str,
 ) -> Response {
     // 1. Extract User ID from the request extensions (set by AuthMiddleware)
-    let user_id = match req.extensions().get::<Uuid>() {
-        Some(id) => *id,
+    let Some(user_id) = req.extensions().get::<Uuid>().copied() else {
+        return missing_token();
+    };
+
+    // 2. Check Permission via Service (which uses Cache). Realm routes also
+    // accept grants scoped to that realm.
+    let path = request_path(&req);
+    let allowed = match realm_id_from_path(&state, &path).await {
+        Some(realm_id) => {
+            state
+                .rbac_service
+                .user_has_permission_for(
+                    &user_id,
+                    required_permission,
+                    &AdminResource::Realm(realm_id),
+                )
+                .await
+        }
         None => {
-            return (
-                StatusCode::UNAUTHORIZED,
-                Json(json!({
-                    "error": "Missing Authentication Token",
-                    "code": "auth.missing_token"
-                })),
-            )
-                .into_response()
+            state
+                .rbac_service
+                .user_has_permission(&user_id, required_permission)
+                .await
         }
     };
 
-    // 2. Check Permission via Service (which uses Cache)
-    match state
+    match allowed {
+        Ok(true) => next.run(req).await,
+        _ => insufficient_permissions(),
+    }
+}
+
+/// Like `require_permission`, but also lets through admins whose grant is
+/// scoped to a client or group. Every handler behind this guard must check
+/// the resource it touches with [`authorize`].
+#[instrument(skip_all, fields(telemetry = "span"))]
+pub async fn require_scoped_permission(
+    State(state): State<AppState>,
+    req: Request<Body>,
+    next: Next,
+    required_permission: &str,
+) -> Response {
+    let Some(user_id) = req.extensions().get::<Uuid>().copied() else {
+        return missing_token();
+    };
+
+    let path = request_path(&req);
+    let allowed = match realm_id_from_path(&state, &path).await {
+        Some(realm_id) => {
+            state
+                .rbac_service
+                .user_has_permission_in_realm(&user_id, required_permission, &realm_id)
+                .await
+        }
+        None => {
+            state
+                .rbac_service
+                .user_has_permission(&user_id, required_permission)
+                .await
+        }
+    };
+
+    match allowed {
+        Ok(true) => next.run(req).await,
+        _ => insufficient_permissions(),
+    }
+}
+
+/// The resource check for handlers behind [`require_scoped_permission`].
+pub async fn authorize(
+    state: &AppState,
+    user_id: &Uuid,
+    permission: &str,
+    resource: AdminResource,
+) -> Result<()> {
+    if state
         .rbac_service
-        .user_has_permission(&user_id, required_permission)
-        .await
+        .user_has_permission_for(user_id, permission, &resource)
+        .await?
     {
-        Ok(true) => next.run(req).await,
-        _ => (
-            StatusCode::FORBIDDEN,
-            Json(json!({
-                "error": "Insufficient Permissions",
-                "code": "rbac.insufficient_permissions"
-            })),
-        )
-            .into_response(),
+        Ok(())
+    } else {
+        Err(Error::InsufficientPermissions)
+    }
+}
+
+// Nested routers see a stripped URI; the original one has the full path.
+fn request_path(req: &Request<Body>) -> String {
+    req.extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.path().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string())
+}
+
+/// The realm a `/api/realms/{realm}/...` request targets, by name or id.
+async fn realm_id_from_path(state: &AppState, path: &str) -> Option<Uuid> {
+    let mut segments = path.split('/').filter(|segment| !segment.is_empty());
+    if segments.next()? != "api" || segments.next()? != "realms" {
+        return None;
     }
+    let realm_segment = segments.next()?;
+    if let Ok(id) = Uuid::parse_str(realm_segment) {
+        return Some(id);
+    }
+    state
+        .realm_service
+        .find_by_name(realm_segment)
+        .await
+        .ok()
+        .flatten()
+        .map(|realm| realm.id)
+}
+
+fn missing_token() -> Response {
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(json!({
+            "error": "Missing Authentication Token",
+            "code": "auth.missing_token"
+        })),
+    )
+        .into_response()
+}
+
+fn insufficient_permissions() -> Response {
+    (
+        StatusCode::FORBIDDEN,
+        Json(json!({
+            "error": "Insufficient Permissions",
+            "code": "rbac.insufficient_permissions"
+        })),
+    )
+        .into_response()
 }
@@ -15,18 +15,24 @@ pub mod observability_handler;
 pub mod oidc_handler;
 pub mod outbound_http_client;
 pub mod rbac_handler;
+pub mod realm_client_registration_handler;
 pub mod realm_email_handler;
 mod realm_handler;
 pub mod realm_idp_settings_handler;
 pub mod realm_passkey_handler;
+pub mod realm_password_policy_handler;
 pub mod realm_recovery_handler;
 pub mod realm_security_headers_handler;
 pub mod router;
+pub mod saml_handler;
+pub mod scim_handler;
 pub mod search_handler;
 pub mod server;
 mod session_handler;
 pub mod setup_handler;
+pub mod signing_key_handler;
 pub mod theme_handler;
+pub mod user_federation_handler;
 pub mod user_handler;
 pub mod validation;
 pub mod webhook_handler;
@@ -3,16 +3,19 @@ use crate::adapters::web::auth_handler::{
 };
 use crate::constants::LOGIN_SESSION_COOKIE;
 use crate::domain::auth_session::SessionStatus;
+use crate::domain::claims::ClaimsGrant;
 use crate::domain::execution::ExecutionResult;
+use crate::domain::identity_provider::OAuthBrokerResult;
 use crate::domain::oidc::OidcContext;
+use crate::domain::saml::SAML_CONTEXT_KEY;
 use crate::error::{Error, Result};
 use crate::AppState;
-use axum::extract::{Path, Query, Request, State};
+use axum::extract::{Form, Path, Query, Request, State};
 use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::Json;
 use axum_extra::extract::cookie::CookieJar;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use url::Url;
 use uuid::Uuid;
@@ -22,6 +25,10 @@ const CALLBACK_INVALID_REQUEST_ACTION: &str = "idp_callback_invalid_request";
 const CALLBACK_UPSTREAM_ERROR_ACTION: &str = "idp_callback_upstream_error";
 const CALLBACK_SESSION_MISMATCH_ACTION: &str = "idp_callback_session_mismatch";
 
+/// Where the ACS parks a validated SAML login until the browser comes back
+/// with its login cookie, which a cross-site POST does not carry.
+const SAML_BROKER_RESULT_KEY: &str = "saml_broker_result";
+
 struct FailureRedirectContext {
     realm_id: Uuid,
     provider_id: Option<Uuid>,
@@ -40,6 +47,20 @@ pub struct OAuthCallbackQuery {
     pub error: Option<String>,
 }
 
+#[derive(Deserialize)]
+pub struct SamlAcsForm {
+    #[serde(rename = "SAMLResponse")]
+    pub saml_response: Option<String>,
+    #[serde(rename = "RelayState")]
+    pub relay_state: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PendingSamlResult {
+    provider_alias: String,
+    result: OAuthBrokerResult,
+}
+
 pub async fn oauth_start_handler(
     State(state): State<AppState>,
     Path((realm_name, alias)): Path<(String, String)>,
@@ -248,31 +269,249 @@ pub async fn oauth_callback_handler(
     );
     state.auth_session_repo.update(&session).await?;
 
+    continue_broker_flow(
+        &state,
+        realm.id,
+        &realm_name,
+        provider.as_ref().map(|value| value.id),
+        &alias,
+        callback.auth_session_id,
+    )
+    .await
+}
+
+/// POST /api/realms/{realm}/auth/saml/{alias}/acs
+///
+/// The identity provider posts cross-site, so the login cookie is absent. A
+/// valid response is parked on the auth session named by the RelayState and
+/// the browser is sent on to `continue`, which checks the cookie.
+pub async fn saml_acs_handler(
+    State(state): State<AppState>,
+    Path((realm_name, alias)): Path<(String, String)>,
+    Form(form): Form<SamlAcsForm>,
+) -> Result<Response> {
+    let realm = state
+        .realm_service
+        .find_by_name(&realm_name)
+        .await?
+        .ok_or(Error::RealmNotFound(realm_name.clone()))?;
+    let provider = state
+        .identity_provider_service
+        .get_domain_by_alias(realm.id, &alias)
+        .await
+        .ok();
+
+    state
+        .audit_service
+        .record(crate::domain::audit::NewAuditEvent {
+            realm_id: realm.id,
+            actor_user_id: None,
+            action: "idp_callback_received".to_string(),
+            target_type: "identity_provider".to_string(),
+            target_id: provider.as_ref().map(|value| value.id.to_string()),
+            metadata: json!({
+                "provider_alias": alias,
+                "state": form.relay_state,
+                "has_saml_response": form.saml_response.is_some(),
+            }),
+        })
+        .await?;
+
+    let (saml_response, relay_state) = match (form.saml_response, form.relay_state) {
+        (Some(saml_response), Some(relay_state)) => (saml_response, relay_state),
+        (saml_response, _) => {
+            let reason = if saml_response.is_none() {
+                "missing_saml_response"
+            } else {
+                "missing_relay_state"
+            };
+            return failure_redirect(
+                &state,
+                FailureRedirectContext {
+                    realm_id: realm.id,
+                    provider_id: provider.as_ref().map(|value| value.id),
+                    provider_alias: alias.clone(),
+                    realm_name: realm_name.clone(),
+                    message: "SAML response did not include SAMLResponse and RelayState"
+                        .to_string(),
+                    session_id: None,
+                    action: CALLBACK_INVALID_REQUEST_ACTION,
+                    extra_metadata: json!({ "reason": reason }),
+                },
+            )
+            .await;
+        }
+    };
+
+    let callback = match state
+        .oauth_broker_service
+        .handle_saml_response(realm.id, &alias, &saml_response, &relay_state)
+        .await
+    {
+        Ok(value) => value,
+        Err(err) => {
+            return failure_redirect(
+                &state,
+                FailureRedirectContext {
+                    realm_id: realm.id,
+                    provider_id: provider.as_ref().map(|value| value.id),
+                    provider_alias: alias.clone(),
+                    realm_name: realm_name.clone(),
+                    message: err.to_string(),
+                    session_id: None,
+                    action: CALLBACK_FAILURE_ACTION,
+                    extra_metadata: json!({}),
+                },
+            )
+            .await;
+        }
+    };
+
+    let mut session = state
+        .auth_session_repo
+        .find_by_id(&callback.auth_session_id)
+        .await?
+        .ok_or(Error::InvalidLoginSession)?;
+    session.update_context(
+        SAML_BROKER_RESULT_KEY,
+        serde_json::to_value(PendingSamlResult {
+            provider_alias: alias.clone(),
+            result: callback.broker_result,
+        })
+        .map_err(|err| Error::System(format!("Failed to serialize broker result: {}", err)))?,
+    );
+    state.auth_session_repo.update(&session).await?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::LOCATION,
+        HeaderValue::from_str(&format!(
+            "/api/realms/{}/auth/saml/{}/continue",
+            realm_name, alias
+        ))?,
+    );
+    Ok((StatusCode::SEE_OTHER, headers).into_response())
+}
+
+/// GET /api/realms/{realm}/auth/saml/{alias}/continue
+pub async fn saml_acs_continue_handler(
+    State(state): State<AppState>,
+    Path((realm_name, alias)): Path<(String, String)>,
+    jar: CookieJar,
+) -> Result<Response> {
+    let realm = state
+        .realm_service
+        .find_by_name(&realm_name)
+        .await?
+        .ok_or(Error::RealmNotFound(realm_name.clone()))?;
+    let provider = state
+        .identity_provider_service
+        .get_domain_by_alias(realm.id, &alias)
+        .await
+        .ok();
+
+    let session = match resolve_cookie_session_id(&jar)? {
+        Some(session_id) => state.auth_session_repo.find_by_id(&session_id).await?,
+        None => None,
+    };
+    let pending = session.and_then(|mut session| {
+        let value = session
+            .context
+            .as_object_mut()?
+            .remove(SAML_BROKER_RESULT_KEY)?;
+        let pending = serde_json::from_value::<PendingSamlResult>(value).ok()?;
+        (session.realm_id == realm.id && pending.provider_alias == alias)
+            .then_some((session, pending))
+    });
+    let Some((mut session, pending)) = pending else {
+        return failure_redirect(
+            &state,
+            FailureRedirectContext {
+                realm_id: realm.id,
+                provider_id: provider.as_ref().map(|value| value.id),
+                provider_alias: alias.clone(),
+                realm_name: realm_name.clone(),
+                message: "The SAML login session was lost. Start the sign-in flow again."
+                    .to_string(),
+                session_id: None,
+                action: CALLBACK_SESSION_MISMATCH_ACTION,
+                extra_metadata: json!({}),
+            },
+        )
+        .await;
+    };
+
+    session.update_context(
+        "oauth_broker_result",
+        serde_json::to_value(&pending.result)
+            .map_err(|err| Error::System(format!("Failed to serialize broker result: {}", err)))?,
+    );
+    state.auth_session_repo.update(&session).await?;
+
+    continue_broker_flow(
+        &state,
+        realm.id,
+        &realm_name,
+        provider.as_ref().map(|value| value.id),
+        &alias,
+        session.id,
+    )
+    .await
+}
+
+/// GET /api/realms/{realm}/auth/saml/{alias}/metadata
+pub async fn saml_sp_metadata_handler(
+    State(state): State<AppState>,
+    Path((realm_name, alias)): Path<(String, String)>,
+) -> Result<Response> {
+    let realm = state
+        .realm_service
+        .find_by_name(&realm_name)
+        .await?
+        .ok_or(Error::RealmNotFound(realm_name.clone()))?;
+    let metadata = state
+        .oauth_broker_service
+        .saml_sp_metadata(realm.id, &realm_name, &alias)
+        .await?;
+    Ok((
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "application/samlmetadata+xml")],
+        metadata,
+    )
+        .into_response())
+}
+
+/// Resumes the login flow once the broker result is on the auth session.
+async fn continue_broker_flow(
+    state: &AppState,
+    realm_id: Uuid,
+    realm_name: &str,
+    provider_id: Option<Uuid>,
+    alias: &str,
+    session_id: Uuid,
+) -> Result<Response> {
     let execution = state
         .flow_executor
-        .execute(
-            callback.auth_session_id,
-            Some(json!({ "oauth_callback": true })),
-        )
+        .execute(session_id, Some(json!({ "oauth_callback": true })))
         .await?;
 
     match execution {
         ExecutionResult::Success { redirect_url } => {
-            finish_browser_flow_redirect(&state, callback.auth_session_id, redirect_url).await
+            finish_browser_flow_redirect(state, session_id, redirect_url).await
         }
         ExecutionResult::Challenge { .. } | ExecutionResult::AwaitingAction { .. } => {
-            redirect_back_to_login(&realm_name, Some(callback.auth_session_id), None)
+            redirect_back_to_login(realm_name, Some(session_id), None)
         }
         ExecutionResult::Failure { reason } => {
             failure_redirect(
-                &state,
+                state,
                 FailureRedirectContext {
-                    realm_id: realm.id,
-                    provider_id: provider.as_ref().map(|value| value.id),
-                    provider_alias: alias.clone(),
-                    realm_name: realm_name.clone(),
+                    realm_id,
+                    provider_id,
+                    provider_alias: alias.to_string(),
+                    realm_name: realm_name.to_string(),
                     message: reason.clone(),
-                    session_id: Some(callback.auth_session_id),
+                    session_id: Some(session_id),
                     action: CALLBACK_FAILURE_ACTION,
                     extra_metadata: json!({}),
                 },
@@ -326,7 +565,46 @@ async fn finish_browser_flow_redirect(
         HeaderValue::from_str(&create_clear_login_cookie().to_string())?,
     );
 
-    let target_url = if let Some(oidc_value) = final_session.context.get("oidc") {
+    let target_url = if final_session.context.get(SAML_CONTEXT_KEY).is_some() {
+        let sso_token = match final_session
+            .context
+            .get("sso_token_id")
+            .and_then(|v| v.as_str())
+            .and_then(|v| Uuid::parse_str(v).ok())
+        {
+            Some(token_id) => state.session_repo.find_by_id(&token_id).await?,
+            None => None,
+        };
+        let family_id = match sso_token {
+            Some(token) => token.family_id,
+            None => {
+                let user = state.user_service.get_user(user_id).await?;
+                let (_, refresh_token) = state
+                    .auth_service
+                    .create_session(&user, None, None, None)
+                    .await?;
+                response_headers.append(
+                    header::SET_COOKIE,
+                    HeaderValue::from_str(&create_refresh_cookie(&refresh_token).to_string())?,
+                );
+                refresh_token.family_id
+            }
+        };
+        state
+            .saml_service
+            .complete_login(final_session.id, user_id, family_id)
+            .await?;
+
+        let realm = state
+            .realm_service
+            .find_by_id(final_session.realm_id)
+            .await?
+            .ok_or(Error::InvalidLoginSession)?;
+        format!(
+            "/api/realms/{}/saml/sso/complete?session={}",
+            realm.name, final_session.id
+        )
+    } else if let Some(oidc_value) = final_session.context.get("oidc") {
         if let Ok(oidc_ctx) = serde_json::from_value::<OidcContext>(oidc_value.clone()) {
             if final_session.context.get("sso_token_id").is_none() {
                 let user = state.user_service.get_user(user_id).await?;
@@ -352,6 +630,7 @@ async fn finish_browser_flow_redirect(
                     oidc_ctx
                         .code_challenge_method
                         .unwrap_or_else(|| "S256".to_string()),
+                    ClaimsGrant::from_stored(oidc_ctx.scope, oidc_ctx.claims.as_deref()),
                 )
                 .await?;
 
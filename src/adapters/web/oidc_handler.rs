@@ -1,19 +1,39 @@
+use crate::adapters::web::auth_handler::{create_clear_cookie, create_clear_login_cookie};
+use crate::adapters::web::middleware::permission_guard;
+use crate::application::claims_service::ProtocolMapperPayload;
+use crate::application::client_registration_service::{ClientMetadata, RegisteredClient};
+use crate::application::oidc_service::{
+    token_exchange_policy_json, PasswordGrantRequest, TokenExchangeRequest, TokenResponse,
+    CLIENT_ASSERTION_TYPE_JWT_BEARER,
+};
 use crate::constants::{LOGIN_SESSION_COOKIE, REFRESH_TOKEN_COOKIE};
-use crate::domain::oidc::{OidcClient, OidcRequest}; // Use OidcRequest from domain
+use crate::domain::auth_session::AuthenticationSession;
+use crate::domain::claims::supported_claims;
+use crate::domain::oidc::{
+    format_user_code, AuthorizationRequestParams, ClientAuthentication, EndSessionRequest,
+    OidcClient, OidcRequest, TokenEndpointAuthMethod, TokenExchangeRule, DEVICE_CODE_GRANT_TYPE,
+    TOKEN_EXCHANGE_GRANT_TYPE,
+}; // Use OidcRequest from domain
 use crate::domain::pagination::{PageRequest, PageResponse};
+use crate::domain::permissions::{self, AdminResource};
 use crate::domain::session::RefreshToken;
+use crate::domain::signing_key::SigningAlgorithm;
 use crate::{
     error::{Error, Result},
     AppState,
 };
+use axum::extract::rejection::JsonRejection;
 use axum::extract::{ConnectInfo, FromRequest, OriginalUri, Path, Request};
 use axum::{
     extract::{Query, State},
     http::StatusCode,
-    response::{IntoResponse, Redirect, Response}, // Redirect is needed for authorize
+    response::{Html, IntoResponse, Redirect, Response}, // Redirect is needed for authorize
+    Extension,
     Json,
 };
-use axum_extra::extract::cookie::{Cookie, SameSite}; // Use axum_extra cookie types
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite}; // Use axum_extra cookie types
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
 use cookie::CookieBuilder;
 use http::{header, HeaderMap, HeaderValue};
 use serde::de::DeserializeOwned;
@@ -22,15 +42,100 @@ use std::net::SocketAddr;
 use url::Url;
 use uuid::Uuid;
 
+/// How long the end-session page gives front-channel logout iframes to load
+/// before following the post-logout redirect.
+const FRONTCHANNEL_LOGOUT_DELAY_SECS: u32 = 2;
+
 // Note: AuthorizeParams is replaced by domain::oidc::OidcRequest to match service signature
 
+/// Client credentials accepted in the form body of token, introspection and
+/// revocation requests (RFC 6749, Section 2.3 and RFC 7523).
+#[derive(Deserialize)]
+pub struct ClientCredentialParams {
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    pub client_assertion_type: Option<String>,
+    pub client_assertion: Option<String>,
+}
+
 #[derive(Deserialize)]
 pub struct TokenParams {
     pub grant_type: Option<String>,
     pub code: Option<String>,
     pub redirect_uri: Option<String>,
-    pub client_id: Option<String>,
     pub code_verifier: Option<String>,
+    pub refresh_token: Option<String>,
+    pub scope: Option<String>,
+    pub subject_token: Option<String>,
+    pub subject_token_type: Option<String>,
+    pub audience: Option<String>,
+    pub requested_token_type: Option<String>,
+    pub actor_token: Option<String>,
+    pub device_code: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub otp: Option<String>,
+    #[serde(flatten)]
+    pub credentials: ClientCredentialParams,
+}
+
+/// Form body of the PAR endpoint (RFC 9126, Section 2.1). `client_id` is read
+/// from the client credentials.
+#[derive(Deserialize)]
+pub struct PushedAuthorizationParams {
+    pub redirect_uri: Option<String>,
+    pub response_type: Option<String>,
+    pub scope: Option<String>,
+    pub state: Option<String>,
+    pub nonce: Option<String>,
+    pub code_challenge: Option<String>,
+    pub code_challenge_method: Option<String>,
+    pub claims: Option<String>,
+    pub request: Option<String>,
+    pub request_uri: Option<String>,
+    #[serde(flatten)]
+    pub credentials: ClientCredentialParams,
+}
+
+#[derive(Serialize)]
+pub struct PushedAuthorizationResponse {
+    pub request_uri: String,
+    pub expires_in: i64,
+}
+
+/// Form body of the device authorization endpoint (RFC 8628, Section 3.1).
+#[derive(Deserialize)]
+pub struct DeviceAuthorizationParams {
+    pub scope: Option<String>,
+    #[serde(flatten)]
+    pub credentials: ClientCredentialParams,
+}
+
+#[derive(Serialize)]
+pub struct DeviceAuthorizationResponse {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    pub verification_uri_complete: String,
+    pub expires_in: i64,
+    pub interval: i64,
+}
+
+/// Query and form fields of the device verification page. `action=deny`
+/// declines the request instead of starting the login flow.
+#[derive(Deserialize, Default)]
+pub struct DeviceVerificationParams {
+    pub user_code: Option<String>,
+    pub action: Option<String>,
+}
+
+/// Form body of the introspection (RFC 7662) and revocation (RFC 7009)
+/// endpoints. `token_type_hint` is accepted but not needed.
+#[derive(Deserialize)]
+pub struct TokenLookupParams {
+    pub token: Option<String>,
+    #[serde(flatten)]
+    pub credentials: ClientCredentialParams,
 }
 
 pub struct JsonForm<T>(pub T);
@@ -91,7 +196,7 @@ fn oidc_bearer_error(status: StatusCode, error: &str, description: &str) -> Resp
 fn normalize_authorize_error(error: &Error) -> (&'static str, StatusCode, String) {
     match error {
         Error::OidcInvalidRequest(message) => {
-            let error_code = if message.contains("response_type") {
+            let error_code = if message.starts_with("Unsupported response_type") {
                 "unsupported_response_type"
             } else {
                 "invalid_request"
@@ -106,6 +211,16 @@ fn normalize_authorize_error(error: &Error) -> (&'static str, StatusCode, String
             StatusCode::BAD_REQUEST,
             message.clone(),
         ),
+        Error::OidcInvalidRequestUri(message) => (
+            "invalid_request_uri",
+            StatusCode::BAD_REQUEST,
+            message.clone(),
+        ),
+        Error::OidcInvalidRequestObject(message) => (
+            "invalid_request_object",
+            StatusCode::BAD_REQUEST,
+            message.clone(),
+        ),
         Error::Validation(message) => ("invalid_request", StatusCode::BAD_REQUEST, message.clone()),
         Error::SecurityViolation(message) => {
             ("access_denied", StatusCode::FORBIDDEN, message.clone())
@@ -136,9 +251,38 @@ fn normalize_token_error(error: &Error) -> (&'static str, StatusCode, String) {
             };
             (error_code, StatusCode::BAD_REQUEST, message.clone())
         }
-        Error::OidcClientNotFound(message) => {
+        Error::OidcClientNotFound(message) | Error::OidcInvalidClient(message) => {
             ("invalid_client", StatusCode::UNAUTHORIZED, message.clone())
         }
+        Error::OidcUnauthorizedClient(message) => (
+            "unauthorized_client",
+            StatusCode::BAD_REQUEST,
+            message.clone(),
+        ),
+        Error::OidcInvalidGrant(message) => {
+            ("invalid_grant", StatusCode::BAD_REQUEST, message.clone())
+        }
+        Error::OidcInvalidScope(message) => {
+            ("invalid_scope", StatusCode::BAD_REQUEST, message.clone())
+        }
+        Error::OidcInvalidTarget(message) => {
+            ("invalid_target", StatusCode::BAD_REQUEST, message.clone())
+        }
+        Error::OidcAuthorizationPending => (
+            "authorization_pending",
+            StatusCode::BAD_REQUEST,
+            error.to_string(),
+        ),
+        Error::OidcSlowDown => ("slow_down", StatusCode::BAD_REQUEST, error.to_string()),
+        Error::OidcExpiredToken => ("expired_token", StatusCode::BAD_REQUEST, error.to_string()),
+        Error::OidcAccessDenied(message) => {
+            ("access_denied", StatusCode::BAD_REQUEST, message.clone())
+        }
+        Error::OidcInvalidRequestObject(message) => (
+            "invalid_request_object",
+            StatusCode::BAD_REQUEST,
+            message.clone(),
+        ),
         Error::Validation(message) => ("invalid_request", StatusCode::BAD_REQUEST, message.clone()),
         _ => (
             "server_error",
@@ -148,6 +292,17 @@ fn normalize_token_error(error: &Error) -> (&'static str, StatusCode, String) {
     }
 }
 
+/// PAR errors use the token endpoint's format (RFC 9126, Section 2.3), but a
+/// bad redirect URI is a malformed request rather than a bad grant.
+fn normalize_par_error(error: &Error) -> (&'static str, StatusCode, String) {
+    match error {
+        Error::OidcInvalidRedirect(message) => {
+            ("invalid_request", StatusCode::BAD_REQUEST, message.clone())
+        }
+        _ => normalize_token_error(error),
+    }
+}
+
 fn normalize_userinfo_error(error: &Error) -> (&'static str, StatusCode, String) {
     match error {
         Error::Jwt(_) => (
@@ -201,17 +356,34 @@ fn create_refresh_cookie(token: &RefreshToken) -> Cookie<'static> {
         .into()
 }
 
+/// Login session cookie for a flow started by an OIDC endpoint.
+pub(crate) fn login_session_cookie(session: &AuthenticationSession) -> CookieBuilder<'static> {
+    let expires_time = time::OffsetDateTime::from_unix_timestamp(session.expires_at.timestamp())
+        .unwrap_or(time::OffsetDateTime::UNIX_EPOCH);
+
+    // Force insecure for localhost dev
+    let is_production = false;
+
+    Cookie::build((LOGIN_SESSION_COOKIE, session.id.to_string()))
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .secure(is_production)
+        .expires(expires_time)
+}
+
 /// GET /api/realms/{realm}/protocol/openid-connect/authorize
 /// Starts the OIDC flow.
 ///
-/// 1. Validates Client.
+/// 1. Resolves the request from the query, a signed `request` object or a
+///    pushed `request_uri`, and validates the client.
 /// 2. Creates an AuthenticationSession in DB with OIDC context preserved.
 /// 3. Sets a cookie.
 /// 4. Redirects browser to Frontend Login UI.
 pub async fn authorize_handler(
     State(state): State<AppState>,
     Path(realm_name): Path<String>,
-    Query(params): Query<OidcRequest>,
+    Query(params): Query<AuthorizationRequestParams>,
     uri: OriginalUri,
 ) -> Result<Response> {
     // 1. Resolve Realm
@@ -221,57 +393,55 @@ pub async fn authorize_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name.clone()))?;
 
-    // 2. Initiate the Graph Session via OidcService
+    // 2. Resolve the request, then initiate the Graph Session via OidcService
     // This handles client validation, flow lookup, and unified session creation.
-    let session = match state
+    let audiences = request_object_audiences(&state, &realm_name).await;
+    let request = match state
         .oidc_service
-        .initiate_browser_login(realm.id, params.clone())
+        .resolve_authorization_request(realm.id, params.clone(), &audiences)
         .await
     {
-        Ok(session) => session,
+        Ok(request) => request,
         Err(err) => {
-            let (error_code, status, description) = normalize_authorize_error(&err);
-
-            let redirect_ok = state
-                .oidc_service
-                .validate_client(&realm.id, &params.client_id, &params.redirect_uri)
-                .await
-                .is_ok();
-
-            if redirect_ok {
-                if let Some(redirect) = build_authorize_redirect(
-                    &params.redirect_uri,
-                    error_code,
-                    &description,
-                    params.state.as_deref(),
-                ) {
-                    return Ok(redirect.into_response());
+            // Parameters we could not verify are only trusted for the error
+            // redirect when they came straight from the query.
+            let request = match (&params.client_id, &params.redirect_uri) {
+                (Some(client_id), Some(redirect_uri)) if !params.is_by_reference() => {
+                    Some(OidcRequest {
+                        client_id: client_id.clone(),
+                        redirect_uri: redirect_uri.clone(),
+                        response_type: params.response_type.clone().unwrap_or_default(),
+                        scope: None,
+                        state: params.state.clone(),
+                        nonce: None,
+                        code_challenge: None,
+                        code_challenge_method: None,
+                        claims: None,
+                    })
                 }
-            }
+                _ => None,
+            };
+            return authorize_error_response(&state, realm.id, request.as_ref(), &err).await;
+        }
+    };
 
-            return Ok(oidc_error_response(status, error_code, Some(&description)));
+    let session = match state
+        .oidc_service
+        .initiate_browser_login(realm.id, request.clone())
+        .await
+    {
+        Ok(session) => session,
+        Err(err) => {
+            return authorize_error_response(&state, realm.id, Some(&request), &err).await;
         }
     };
 
     // 3. Set the Session Cookie
     let mut headers = HeaderMap::new();
-
-    let expires_time = time::OffsetDateTime::from_unix_timestamp(session.expires_at.timestamp())
-        .unwrap_or(time::OffsetDateTime::UNIX_EPOCH);
-
-    // Force insecure for localhost dev
-    let is_production = false;
-
-    let cookie: CookieBuilder = Cookie::build((LOGIN_SESSION_COOKIE, session.id.to_string()))
-        .path("/")
-        .http_only(true)
-        .same_site(SameSite::Lax)
-        .secure(is_production)
-        .expires(expires_time);
-
     headers.insert(
         header::SET_COOKIE,
-        HeaderValue::from_str(&cookie.to_string()).map_err(|e| Error::Unexpected(e.into()))?,
+        HeaderValue::from_str(&login_session_cookie(&session).to_string())
+            .map_err(|e| Error::Unexpected(e.into()))?,
     );
 
     // 4. Redirect to Frontend Login WITH PARAMS
@@ -284,11 +454,112 @@ pub async fn authorize_handler(
     Ok((headers, Redirect::to(&frontend_login_url)).into_response())
 }
 
-/// POST /api/oidc/token
-/// Exchanges an Authorization Code for an Access Token.
+/// Sends an `/authorize` failure back to the client's redirect URI when it is
+/// registered, and as a JSON error otherwise.
+async fn authorize_error_response(
+    state: &AppState,
+    realm_id: Uuid,
+    request: Option<&OidcRequest>,
+    err: &Error,
+) -> Result<Response> {
+    let (error_code, status, description) = normalize_authorize_error(err);
+
+    if let Some(request) = request {
+        let redirect_ok = state
+            .oidc_service
+            .validate_client(&realm_id, &request.client_id, &request.redirect_uri)
+            .await
+            .is_ok();
+
+        if redirect_ok {
+            if let Some(redirect) = build_authorize_redirect(
+                &request.redirect_uri,
+                error_code,
+                &description,
+                request.state.as_deref(),
+            ) {
+                return Ok(redirect.into_response());
+            }
+        }
+    }
+
+    Ok(oidc_error_response(status, error_code, Some(&description)))
+}
+
+/// Audiences a request object may name: the issuer, or the authorization or
+/// PAR endpoint (RFC 9101, Section 6.1).
+async fn request_object_audiences(state: &AppState, realm_name: &str) -> Vec<String> {
+    let settings = state.settings.read().await;
+    let base = settings.server.public_url.trim_end_matches('/').to_string();
+    vec![
+        settings.auth.issuer.clone(),
+        format!("{}/api/realms/{}/oidc/authorize", base, realm_name),
+        format!("{}/api/realms/{}/oidc/par", base, realm_name),
+    ]
+}
+
+/// POST /api/realms/{realm}/oidc/par (RFC 9126)
+/// Stores an authorization request from an authenticated client and returns
+/// the `request_uri` to send to `/authorize` instead.
+pub async fn pushed_authorization_request_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+    headers: HeaderMap,
+    JsonForm(params): JsonForm<PushedAuthorizationParams>,
+) -> Result<Response> {
+    let client = match authenticate_endpoint_client(
+        &state,
+        &realm_name,
+        "par",
+        &headers,
+        &params.credentials,
+    )
+    .await?
+    {
+        Ok(client) => client,
+        Err(response) => return Ok(response),
+    };
+
+    let audiences = request_object_audiences(&state, &realm_name).await;
+    let request = AuthorizationRequestParams {
+        client_id: params.credentials.client_id.clone(),
+        redirect_uri: params.redirect_uri,
+        response_type: params.response_type,
+        scope: params.scope,
+        state: params.state,
+        nonce: params.nonce,
+        code_challenge: params.code_challenge,
+        code_challenge_method: params.code_challenge_method,
+        claims: params.claims,
+        request: params.request,
+        request_uri: params.request_uri,
+    };
+    let pushed = match state
+        .oidc_service
+        .push_authorization_request(&client, request, &audiences)
+        .await
+    {
+        Ok(pushed) => pushed,
+        Err(err) => {
+            let (error_code, status, description) = normalize_par_error(&err);
+            return Ok(oidc_error_response(status, error_code, Some(&description)));
+        }
+    };
+
+    let response = PushedAuthorizationResponse {
+        expires_in: (pushed.expires_at - pushed.created_at).num_seconds(),
+        request_uri: pushed.request_uri,
+    };
+    Ok((StatusCode::CREATED, Json(response)).into_response())
+}
+
+/// POST /api/realms/{realm}/oidc/token
+/// Dispatches on `grant_type`: authorization_code, client_credentials,
+/// refresh_token, password (direct grant flow), token exchange (RFC 8693) and
+/// device_code (RFC 8628) are supported.
 pub async fn token_handler(
     State(state): State<AppState>,
-    Path(_realm_name): Path<String>,
+    Path(realm_name): Path<String>,
     headers: HeaderMap,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     JsonForm(params): JsonForm<TokenParams>,
@@ -301,7 +572,15 @@ pub async fn token_handler(
             Some("grant_type is required"),
         ));
     }
-    if grant_type != "authorization_code" {
+    if !matches!(
+        grant_type,
+        "authorization_code"
+            | "client_credentials"
+            | "refresh_token"
+            | "password"
+            | TOKEN_EXCHANGE_GRANT_TYPE
+            | DEVICE_CODE_GRANT_TYPE
+    ) {
         return Ok(oidc_error_response(
             StatusCode::BAD_REQUEST,
             "unsupported_grant_type",
@@ -309,30 +588,31 @@ pub async fn token_handler(
         ));
     }
 
-    let code = params.code.as_deref().unwrap_or_default().trim();
-    if code.is_empty() {
-        return Ok(oidc_error_response(
-            StatusCode::UNPROCESSABLE_ENTITY,
-            "invalid_request",
-            Some("code is required"),
-        ));
-    }
-
-    let client_id = params.client_id.as_deref().unwrap_or_default().trim();
-    if client_id.is_empty() {
-        return Ok(oidc_error_response(
-            StatusCode::UNPROCESSABLE_ENTITY,
-            "invalid_request",
-            Some("client_id is required"),
-        ));
-    }
-
-    let redirect_uri = params.redirect_uri.as_deref().unwrap_or_default().trim();
-    if redirect_uri.is_empty() {
+    // Malformed requests are rejected before any client credentials are checked.
+    let is_blank = |value: &Option<String>| value.as_deref().unwrap_or_default().trim().is_empty();
+    let missing = match grant_type {
+        "authorization_code" if is_blank(&params.code) => Some("code is required"),
+        "authorization_code" if is_blank(&params.redirect_uri) => Some("redirect_uri is required"),
+        "refresh_token" if is_blank(&params.refresh_token) => Some("refresh_token is required"),
+        "password" if is_blank(&params.username) => Some("username is required"),
+        "password" if params.password.as_deref().unwrap_or_default().is_empty() => {
+            Some("password is required")
+        }
+        TOKEN_EXCHANGE_GRANT_TYPE if is_blank(&params.subject_token) => {
+            Some("subject_token is required")
+        }
+        TOKEN_EXCHANGE_GRANT_TYPE if is_blank(&params.subject_token_type) => {
+            Some("subject_token_type is required")
+        }
+        TOKEN_EXCHANGE_GRANT_TYPE if is_blank(&params.audience) => Some("audience is required"),
+        DEVICE_CODE_GRANT_TYPE if is_blank(&params.device_code) => Some("device_code is required"),
+        _ => None,
+    };
+    if let Some(description) = missing {
         return Ok(oidc_error_response(
             StatusCode::UNPROCESSABLE_ENTITY,
             "invalid_request",
-            Some("redirect_uri is required"),
+            Some(description),
         ));
     }
 
@@ -348,65 +628,755 @@ pub async fn token_handler(
         .map(|s| s.split(',').next().unwrap_or(s).trim().to_string())
         .unwrap_or_else(|| addr.ip().to_string());
 
-    // Call the service
-    let (token_response, refresh_token) = match state
+    let client = match authenticate_endpoint_client(
+        &state,
+        &realm_name,
+        "token",
+        &headers,
+        &params.credentials,
+    )
+    .await?
+    {
+        Ok(client) => client,
+        Err(response) => return Ok(response),
+    };
+
+    if let Err(err) = state
+        .client_registration_service
+        .ensure_grant_type_allowed(&client, grant_type)
+        .await
+    {
+        let (error_code, status, description) = normalize_token_error(&err);
+        return Ok(oidc_error_response(status, error_code, Some(&description)));
+    }
+
+    match grant_type {
+        "client_credentials" => {
+            match state
+                .oidc_service
+                .client_credentials_grant(&client, params.scope.as_deref())
+                .await
+            {
+                Ok(token_response) => Ok((StatusCode::OK, Json(token_response)).into_response()),
+                Err(err) => {
+                    let (error_code, status, description) = normalize_token_error(&err);
+                    Ok(oidc_error_response(status, error_code, Some(&description)))
+                }
+            }
+        }
+        TOKEN_EXCHANGE_GRANT_TYPE => {
+            let request = TokenExchangeRequest {
+                subject_token: params.subject_token.unwrap_or_default().trim().to_string(),
+                subject_token_type: params
+                    .subject_token_type
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string(),
+                audience: params.audience.unwrap_or_default().trim().to_string(),
+                scope: params.scope,
+                requested_token_type: params.requested_token_type,
+                actor_token: params.actor_token,
+            };
+            match state
+                .oidc_service
+                .token_exchange_grant(&client, &request)
+                .await
+            {
+                Ok(token_response) => Ok((StatusCode::OK, Json(token_response)).into_response()),
+                Err(err) => {
+                    let (error_code, status, description) = normalize_token_error(&err);
+                    Ok(oidc_error_response(status, error_code, Some(&description)))
+                }
+            }
+        }
+        "password" => {
+            let request = PasswordGrantRequest {
+                username: params.username.unwrap_or_default().trim().to_string(),
+                password: params.password.unwrap_or_default(),
+                scope: params.scope,
+                otp: params.otp,
+            };
+            // Headless clients get the refresh token in the body only.
+            match state
+                .oidc_service
+                .password_grant(&client, &request, Some(ip_address), user_agent)
+                .await
+            {
+                Ok((token_response, _)) => {
+                    Ok((StatusCode::OK, Json(token_response)).into_response())
+                }
+                Err(err) => {
+                    let (error_code, status, description) = normalize_token_error(&err);
+                    Ok(oidc_error_response(status, error_code, Some(&description)))
+                }
+            }
+        }
+        DEVICE_CODE_GRANT_TYPE => {
+            let device_code = params.device_code.as_deref().unwrap_or_default().trim();
+            // The device is not a browser, so no refresh cookie is set.
+            match state
+                .oidc_service
+                .device_code_grant(&client, device_code, Some(ip_address), user_agent)
+                .await
+            {
+                Ok((token_response, _)) => {
+                    Ok((StatusCode::OK, Json(token_response)).into_response())
+                }
+                Err(err) => {
+                    let (error_code, status, description) = normalize_token_error(&err);
+                    Ok(oidc_error_response(status, error_code, Some(&description)))
+                }
+            }
+        }
+        "refresh_token" => {
+            let refresh_token = params.refresh_token.as_deref().unwrap_or_default().trim();
+
+            let (token_response, refresh_token) = match state
+                .oidc_service
+                .refresh_token_grant(&client, refresh_token)
+                .await
+            {
+                Ok(values) => values,
+                Err(err) => {
+                    let (error_code, status, description) = normalize_token_error(&err);
+                    return Ok(oidc_error_response(status, error_code, Some(&description)));
+                }
+            };
+
+            token_response_with_cookie(token_response, &refresh_token)
+        }
+        _ => {
+            let code = params.code.as_deref().unwrap_or_default().trim();
+            let redirect_uri = params.redirect_uri.as_deref().unwrap_or_default().trim();
+
+            // Call the service
+            let (token_response, refresh_token) = match state
+                .oidc_service
+                .exchange_code_for_token(
+                    &client,
+                    code,
+                    redirect_uri,
+                    params.code_verifier.as_deref().unwrap_or(""),
+                    Some(ip_address),
+                    user_agent,
+                )
+                .await
+            {
+                Ok(values) => values,
+                Err(err) => {
+                    let (error_code, status, description) = normalize_token_error(&err);
+                    return Ok(oidc_error_response(status, error_code, Some(&description)));
+                }
+            };
+
+            token_response_with_cookie(token_response, &refresh_token)
+        }
+    }
+}
+
+/// POST /api/realms/{realm}/oidc/device_authorization (RFC 8628)
+/// Issues a device code and the user code to show alongside the
+/// verification URI. Public clients are allowed.
+pub async fn device_authorization_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+    headers: HeaderMap,
+    JsonForm(params): JsonForm<DeviceAuthorizationParams>,
+) -> Result<Response> {
+    let client = match authenticate_endpoint_client(
+        &state,
+        &realm_name,
+        "device_authorization",
+        &headers,
+        &params.credentials,
+    )
+    .await?
+    {
+        Ok(client) => client,
+        Err(response) => return Ok(response),
+    };
+
+    let authorization = match state
         .oidc_service
-        .exchange_code_for_token(
-            code,
-            redirect_uri,
-            params.code_verifier.as_deref().unwrap_or(""),
-            Some(ip_address),
-            user_agent,
-        )
+        .start_device_authorization(&client, params.scope.as_deref())
         .await
     {
-        Ok(values) => values,
+        Ok(authorization) => authorization,
         Err(err) => {
             let (error_code, status, description) = normalize_token_error(&err);
             return Ok(oidc_error_response(status, error_code, Some(&description)));
         }
     };
 
-    // Create the HttpOnly Cookie
-    let cookie = create_refresh_cookie(&refresh_token);
+    let verification_uri = {
+        let settings = state.settings.read().await;
+        format!(
+            "{}/api/realms/{}/oidc/device",
+            settings.server.public_url.trim_end_matches('/'),
+            realm_name
+        )
+    };
+    let user_code = format_user_code(&authorization.user_code);
+    let mut verification_uri_complete =
+        Url::parse(&verification_uri).map_err(|e| Error::Unexpected(e.into()))?;
+    verification_uri_complete
+        .query_pairs_mut()
+        .append_pair("user_code", &user_code);
+
+    let response = DeviceAuthorizationResponse {
+        device_code: authorization.device_code,
+        user_code,
+        verification_uri,
+        verification_uri_complete: verification_uri_complete.to_string(),
+        expires_in: (authorization.expires_at - authorization.created_at).num_seconds(),
+        interval: authorization.interval_secs,
+    };
+    Ok((StatusCode::OK, Json(response)).into_response())
+}
+
+/// GET /api/realms/{realm}/oidc/device
+/// Asks the user to confirm the code shown on their device. A prefilled
+/// `user_code` is never approved without this confirmation.
+pub async fn device_verification_page_handler(
+    Path(realm_name): Path<String>,
+    Query(params): Query<DeviceVerificationParams>,
+) -> Result<Response> {
+    let page = render_device_page(&realm_name, params.user_code.as_deref(), None);
+    Ok((StatusCode::OK, Html(page)).into_response())
+}
+
+/// POST /api/realms/{realm}/oidc/device
+/// Starts the realm's browser flow for the confirmed user code, or denies it.
+pub async fn device_verification_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+    JsonForm(params): JsonForm<DeviceVerificationParams>,
+) -> Result<Response> {
+    let realm = state
+        .realm_service
+        .find_by_name(&realm_name)
+        .await?
+        .ok_or_else(|| Error::RealmNotFound(realm_name.clone()))?;
+    let user_code = params.user_code.as_deref().unwrap_or_default().trim();
+
+    if params.action.as_deref() == Some("deny") {
+        return match state
+            .oidc_service
+            .deny_device_authorization(realm.id, user_code)
+            .await
+        {
+            Ok(()) => Ok((
+                StatusCode::OK,
+                Html(render_device_message(
+                    "Request denied",
+                    "The device was not signed in. You can close this window.",
+                )),
+            )
+                .into_response()),
+            Err(err) => Ok(device_page_error(&realm_name, user_code, &err)),
+        };
+    }
+
+    let session = match state
+        .oidc_service
+        .initiate_device_verification(realm.id, user_code)
+        .await
+    {
+        Ok(session) => session,
+        Err(err) => return Ok(device_page_error(&realm_name, user_code, &err)),
+    };
 
-    // Set the header
     let mut headers = HeaderMap::new();
     headers.insert(
         header::SET_COOKIE,
-        HeaderValue::from_str(&cookie.to_string()).map_err(|e| Error::Unexpected(e.into()))?,
+        HeaderValue::from_str(&login_session_cookie(&session).to_string())
+            .map_err(|e| Error::Unexpected(e.into()))?,
     );
+    let frontend_login_url = format!("/#/login?realm={}", realm_name);
+    Ok((headers, Redirect::to(&frontend_login_url)).into_response())
+}
 
-    // Return the tuple (Status, Headers, JSON Body)
-    Ok((StatusCode::OK, headers, Json(token_response)).into_response())
+/// GET /api/realms/{realm}/oidc/device/complete
+/// Shown once the login flow has approved the device.
+pub async fn device_verification_complete_handler() -> Result<Response> {
+    let page = render_device_message(
+        "Device connected",
+        "You are signed in on your device. You can close this window.",
+    );
+    Ok((StatusCode::OK, Html(page)).into_response())
 }
 
-/// Get /.well-known/jwks.json
-pub async fn jwks_handler(State(state): State<AppState>) -> Result<impl IntoResponse> {
-    let jwks = state.oidc_service.get_jwks()?;
-    Ok((StatusCode::OK, Json(jwks)))
+fn device_page_error(realm_name: &str, user_code: &str, error: &Error) -> Response {
+    let (_, status, description) = normalize_token_error(error);
+    let page = render_device_page(realm_name, Some(user_code), Some(&description));
+    (status, Html(page)).into_response()
 }
 
-/// Get /.well-known/openid-configuration
-pub async fn discovery_handler(
+fn render_device_page(realm_name: &str, user_code: Option<&str>, error: Option<&str>) -> String {
+    let error = error
+        .map(|message| format!(r#"<p role="alert">{}</p>"#, escape_html(message)))
+        .unwrap_or_default();
+    format!(
+        r#"<!DOCTYPE html><html><head><meta charset="utf-8"><title>Connect a device</title></head><body><h1>Connect a device</h1><p>Enter the code shown on your device. Only continue if you started signing in on that device yourself.</p>{}<form method="post" action="/api/realms/{}/oidc/device"><input name="user_code" value="{}" autocomplete="off" autocapitalize="characters" required><button type="submit" name="action" value="approve">Continue</button><button type="submit" name="action" value="deny" formnovalidate>Deny</button></form></body></html>"#,
+        error,
+        escape_html(realm_name),
+        escape_html(user_code.unwrap_or_default())
+    )
+}
+
+fn render_device_message(title: &str, message: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html><html><head><meta charset="utf-8"><title>{0}</title></head><body><h1>{0}</h1><p>{1}</p></body></html>"#,
+        escape_html(title),
+        escape_html(message)
+    )
+}
+
+/// POST /api/realms/{realm}/oidc/introspect (RFC 7662)
+pub async fn introspect_handler(
     State(state): State<AppState>,
     Path(realm_name): Path<String>,
-) -> Result<impl IntoResponse> {
-    let settings = state.settings.read().await;
-    let base = settings.server.public_url.trim_end_matches('/').to_string();
+    headers: HeaderMap,
+    JsonForm(params): JsonForm<TokenLookupParams>,
+) -> Result<Response> {
+    let client = match authenticate_endpoint_client(
+        &state,
+        &realm_name,
+        "introspect",
+        &headers,
+        &params.credentials,
+    )
+    .await?
+    {
+        Ok(client) => client,
+        Err(response) => return Ok(response),
+    };
 
-    let response = serde_json::json!({
-        "issuer": settings.auth.issuer,
-        "authorization_endpoint": format!("{}/api/realms/{}/oidc/authorize", base, realm_name),
-        "token_endpoint": format!("{}/api/realms/{}/oidc/token", base, realm_name),
-        "userinfo_endpoint": format!("{}/api/realms/{}/oidc/userinfo", base, realm_name),
+    let token = params.token.as_deref().unwrap_or_default().trim();
+    if token.is_empty() {
+        return Ok(oidc_error_response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "invalid_request",
+            Some("token is required"),
+        ));
+    }
+
+    match state.oidc_service.introspect_token(&client, token).await {
+        Ok(introspection) => Ok((StatusCode::OK, Json(introspection)).into_response()),
+        Err(err) => {
+            let (error_code, status, description) = normalize_token_error(&err);
+            Ok(oidc_error_response(status, error_code, Some(&description)))
+        }
+    }
+}
+
+/// POST /api/realms/{realm}/oidc/revoke (RFC 7009)
+/// Answers 200 for unknown tokens so callers cannot probe for valid ones.
+pub async fn revoke_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+    headers: HeaderMap,
+    JsonForm(params): JsonForm<TokenLookupParams>,
+) -> Result<Response> {
+    let client = match authenticate_endpoint_client(
+        &state,
+        &realm_name,
+        "revoke",
+        &headers,
+        &params.credentials,
+    )
+    .await?
+    {
+        Ok(client) => client,
+        Err(response) => return Ok(response),
+    };
+
+    let token = params.token.as_deref().unwrap_or_default().trim();
+    if token.is_empty() {
+        return Ok(oidc_error_response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "invalid_request",
+            Some("token is required"),
+        ));
+    }
+
+    match state.oidc_service.revoke_token(&client, token).await {
+        Ok(()) => Ok(StatusCode::OK.into_response()),
+        Err(err) => {
+            let (error_code, status, description) = normalize_token_error(&err);
+            Ok(oidc_error_response(status, error_code, Some(&description)))
+        }
+    }
+}
+
+/// GET /api/realms/{realm}/oidc/logout (OIDC RP-Initiated Logout 1.0)
+pub async fn end_session_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+    jar: CookieJar,
+    Query(params): Query<EndSessionRequest>,
+) -> Result<Response> {
+    end_session(&state, &realm_name, &jar, params).await
+}
+
+/// POST /api/realms/{realm}/oidc/logout, with the same parameters as a form.
+pub async fn end_session_form_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+    jar: CookieJar,
+    JsonForm(params): JsonForm<EndSessionRequest>,
+) -> Result<Response> {
+    end_session(&state, &realm_name, &jar, params).await
+}
+
+/// Ends the browser's SSO session and the hinted client session, clears the
+/// session cookies, then either renders a page that loads each front-channel
+/// logout URL before moving on, or redirects straight away.
+async fn end_session(
+    state: &AppState,
+    realm_name: &str,
+    jar: &CookieJar,
+    params: EndSessionRequest,
+) -> Result<Response> {
+    let realm = state
+        .realm_service
+        .find_by_name(realm_name)
+        .await?
+        .ok_or_else(|| Error::RealmNotFound(realm_name.to_string()))?;
+
+    let sso_token_id = jar
+        .get(REFRESH_TOKEN_COOKIE)
+        .and_then(|cookie| Uuid::parse_str(cookie.value()).ok());
+    let post_logout_state = params.state.clone();
+    let outcome = match state
+        .oidc_service
+        .end_session(realm.id, params, sso_token_id)
+        .await
+    {
+        Ok(outcome) => outcome,
+        Err(err) => {
+            let (error_code, status, description) = normalize_authorize_error(&err);
+            return Ok(oidc_error_response(status, error_code, Some(&description)));
+        }
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.append(
+        header::SET_COOKIE,
+        HeaderValue::from_str(&create_clear_cookie().to_string())?,
+    );
+    headers.append(
+        header::SET_COOKIE,
+        HeaderValue::from_str(&create_clear_login_cookie().to_string())?,
+    );
+
+    let redirect = outcome.post_logout_redirect_uri.and_then(|uri| {
+        let mut url = Url::parse(&uri).ok()?;
+        if let Some(state) = post_logout_state.as_deref() {
+            url.query_pairs_mut().append_pair("state", state);
+        }
+        Some(url.to_string())
+    });
+
+    if outcome.frontchannel_logout_urls.is_empty() {
+        if let Some(redirect) = redirect {
+            return Ok((headers, Redirect::to(&redirect)).into_response());
+        }
+    }
+
+    let page = render_logout_page(&outcome.frontchannel_logout_urls, redirect.as_deref());
+    Ok((StatusCode::OK, headers, Html(page)).into_response())
+}
+
+/// Front-channel logout URLs load in hidden iframes; the page then follows
+/// the post-logout redirect after a short delay, without relying on script.
+fn render_logout_page(frontchannel_logout_urls: &[String], redirect: Option<&str>) -> String {
+    let refresh = redirect
+        .map(|url| {
+            format!(
+                r#"<meta http-equiv="refresh" content="{};url={}">"#,
+                FRONTCHANNEL_LOGOUT_DELAY_SECS,
+                escape_html(url)
+            )
+        })
+        .unwrap_or_default();
+    let frames: String = frontchannel_logout_urls
+        .iter()
+        .map(|url| {
+            format!(
+                r#"<iframe src="{}" style="display:none" title="logout"></iframe>"#,
+                escape_html(url)
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<!DOCTYPE html><html><head><meta charset="utf-8"><title>Signed out</title>{}</head><body><p>You have been signed out.</p>{}</body></html>"#,
+        refresh, frames
+    )
+}
+
+pub(crate) fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Resolves the realm and authenticates the client calling one of the token,
+/// introspection or revocation endpoints. The inner `Err` is the OAuth error
+/// response to send back as-is.
+async fn authenticate_endpoint_client(
+    state: &AppState,
+    realm_name: &str,
+    endpoint: &str,
+    headers: &HeaderMap,
+    credentials: &ClientCredentialParams,
+) -> Result<std::result::Result<OidcClient, Response>> {
+    // A private_key_jwt assertion may name this endpoint, the token endpoint
+    // or the issuer as its audience.
+    let audiences = {
+        let settings = state.settings.read().await;
+        let base = settings.server.public_url.trim_end_matches('/').to_string();
+        let mut audiences = vec![
+            format!("{}/api/realms/{}/oidc/{}", base, realm_name, endpoint),
+            format!("{}/api/realms/{}/oidc/token", base, realm_name),
+            settings.auth.issuer.clone(),
+        ];
+        audiences.dedup();
+        audiences
+    };
+    let client_auth = match client_authentication(headers, credentials, audiences) {
+        Ok(client_auth) => client_auth,
+        Err((status, description)) => {
+            return Ok(Err(oidc_error_response(
+                status,
+                "invalid_request",
+                Some(description),
+            )));
+        }
+    };
+
+    let realm = state
+        .realm_service
+        .find_by_name(realm_name)
+        .await?
+        .ok_or_else(|| Error::RealmNotFound(realm_name.to_string()))?;
+
+    match state
+        .oidc_service
+        .authenticate_client(&realm.id, &client_auth)
+        .await
+    {
+        Ok(client) => Ok(Ok(client)),
+        Err(err) => {
+            let (error_code, status, description) = normalize_token_error(&err);
+            let mut response = oidc_error_response(status, error_code, Some(&description));
+            if matches!(client_auth, ClientAuthentication::SecretBasic { .. })
+                && status == StatusCode::UNAUTHORIZED
+            {
+                response.headers_mut().insert(
+                    header::WWW_AUTHENTICATE,
+                    HeaderValue::from_static("Basic realm=\"token\""),
+                );
+            }
+            Ok(Err(response))
+        }
+    }
+}
+
+/// Works out which client authentication method a request uses
+/// (RFC 6749, Section 2.3). Presenting more than one method is rejected.
+fn client_authentication(
+    headers: &HeaderMap,
+    params: &ClientCredentialParams,
+    audiences: Vec<String>,
+) -> std::result::Result<ClientAuthentication, (StatusCode, &'static str)> {
+    let form_client_id = params
+        .client_id
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+    // Other schemes (e.g. a stray Bearer token) are not client credentials.
+    let basic = match headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split_once(' '))
+        .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("basic"))
+    {
+        Some((_, encoded)) => parse_basic_credentials(encoded).map(Some).ok_or((
+            StatusCode::BAD_REQUEST,
+            "Malformed Basic authorization header",
+        ))?,
+        None => None,
+    };
+    let form_secret = params
+        .client_secret
+        .as_deref()
+        .filter(|value| !value.is_empty());
+    let assertion = params
+        .client_assertion
+        .as_deref()
+        .filter(|value| !value.is_empty());
+
+    let presented = [basic.is_some(), form_secret.is_some(), assertion.is_some()];
+    if presented.iter().filter(|present| **present).count() > 1 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Only one client authentication method may be used",
+        ));
+    }
+
+    if let Some((client_id, client_secret)) = basic {
+        if form_client_id.is_some_and(|form_id| form_id != client_id) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "client_id does not match the Authorization header",
+            ));
+        }
+        return Ok(ClientAuthentication::SecretBasic {
+            client_id,
+            client_secret,
+        });
+    }
+
+    if let Some(assertion) = assertion {
+        if params.client_assertion_type.as_deref() != Some(CLIENT_ASSERTION_TYPE_JWT_BEARER) {
+            return Err((StatusCode::BAD_REQUEST, "Unsupported client_assertion_type"));
+        }
+        return Ok(ClientAuthentication::PrivateKeyJwt {
+            client_id: form_client_id.map(str::to_string),
+            assertion: assertion.to_string(),
+            audiences,
+        });
+    }
+
+    let Some(client_id) = form_client_id else {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, "client_id is required"));
+    };
+    Ok(match form_secret {
+        Some(client_secret) => ClientAuthentication::SecretPost {
+            client_id: client_id.to_string(),
+            client_secret: client_secret.to_string(),
+        },
+        None => ClientAuthentication::None {
+            client_id: client_id.to_string(),
+        },
+    })
+}
+
+/// Decodes the `base64(urlencode(client_id):urlencode(secret))` part of a
+/// Basic authorization header.
+fn parse_basic_credentials(encoded: &str) -> Option<(String, String)> {
+    let decoded = STANDARD.decode(encoded.trim()).ok()?;
+    let decoded = String::from_utf8(decoded).ok()?;
+    let (client_id, client_secret) = decoded.split_once(':')?;
+    let client_id = urlencoding::decode(client_id).ok()?.into_owned();
+    let client_secret = urlencoding::decode(client_secret).ok()?.into_owned();
+    if client_id.is_empty() {
+        return None;
+    }
+    Some((client_id, client_secret))
+}
+
+/// Returns the token JSON and mirrors the refresh token into the HttpOnly
+/// cookie used by the browser-side `/auth/refresh` flow.
+fn token_response_with_cookie(
+    token_response: TokenResponse,
+    refresh_token: &RefreshToken,
+) -> Result<Response> {
+    let cookie = create_refresh_cookie(refresh_token);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::SET_COOKIE,
+        HeaderValue::from_str(&cookie.to_string()).map_err(|e| Error::Unexpected(e.into()))?,
+    );
+
+    // Return the tuple (Status, Headers, JSON Body)
+    Ok((StatusCode::OK, headers, Json(token_response)).into_response())
+}
+
+/// Get /.well-known/jwks.json
+pub async fn jwks_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+) -> Result<impl IntoResponse> {
+    let realm = state
+        .realm_service
+        .find_by_name(&realm_name)
+        .await?
+        .ok_or(Error::RealmNotFound(realm_name))?;
+    let jwks = state.oidc_service.get_jwks(&realm.id).await?;
+    Ok((StatusCode::OK, Json(jwks)))
+}
+
+/// Get /.well-known/openid-configuration
+pub async fn discovery_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+) -> Result<impl IntoResponse> {
+    let settings = state.settings.read().await;
+    let base = settings.server.public_url.trim_end_matches('/').to_string();
+    let confidential_auth_methods = TokenEndpointAuthMethod::ALL
+        .iter()
+        .filter(|method| **method != TokenEndpointAuthMethod::None)
+        .map(TokenEndpointAuthMethod::as_str)
+        .collect::<Vec<_>>();
+    let signing_algorithms = SigningAlgorithm::ALL
+        .iter()
+        .map(SigningAlgorithm::as_str)
+        .collect::<Vec<_>>();
+    // Client assertions and request objects are verified against client JWKS,
+    // so only asymmetric algorithms apply.
+    let asymmetric_algorithms = [
+        "RS256", "RS384", "RS512", "PS256", "PS384", "PS512", "ES256", "ES384", "EdDSA",
+    ];
+
+    let response = serde_json::json!({
+        "issuer": settings.auth.issuer,
+        "authorization_endpoint": format!("{}/api/realms/{}/oidc/authorize", base, realm_name),
+        "token_endpoint": format!("{}/api/realms/{}/oidc/token", base, realm_name),
+        "userinfo_endpoint": format!("{}/api/realms/{}/oidc/userinfo", base, realm_name),
+        "introspection_endpoint": format!("{}/api/realms/{}/oidc/introspect", base, realm_name),
+        "revocation_endpoint": format!("{}/api/realms/{}/oidc/revoke", base, realm_name),
+        "end_session_endpoint": format!("{}/api/realms/{}/oidc/logout", base, realm_name),
+        "device_authorization_endpoint": format!("{}/api/realms/{}/oidc/device_authorization", base, realm_name),
+        "pushed_authorization_request_endpoint": format!("{}/api/realms/{}/oidc/par", base, realm_name),
+        "registration_endpoint": format!("{}/api/realms/{}/oidc/register", base, realm_name),
+        "require_pushed_authorization_requests": false,
         "jwks_uri": format!("{}/api/realms/{}/oidc/.well-known/jwks.json", base, realm_name),
         "response_types_supported": ["code"],
+        "grant_types_supported": [
+            "authorization_code",
+            "client_credentials",
+            "refresh_token",
+            "password",
+            TOKEN_EXCHANGE_GRANT_TYPE,
+            DEVICE_CODE_GRANT_TYPE
+        ],
         "subject_types_supported": ["public"],
-        "id_token_signing_alg_values_supported": ["RS256"],
+        "id_token_signing_alg_values_supported": signing_algorithms,
         "code_challenge_methods_supported": ["S256"],
-        "token_endpoint_auth_methods_supported": ["none"],
-        "scopes_supported": ["openid", "profile"]
+        "token_endpoint_auth_methods_supported": TokenEndpointAuthMethod::ALL
+            .iter()
+            .map(TokenEndpointAuthMethod::as_str)
+            .collect::<Vec<_>>(),
+        "token_endpoint_auth_signing_alg_values_supported": asymmetric_algorithms,
+        "request_parameter_supported": true,
+        "request_uri_parameter_supported": false,
+        "request_object_signing_alg_values_supported": asymmetric_algorithms,
+        "introspection_endpoint_auth_methods_supported": confidential_auth_methods,
+        "revocation_endpoint_auth_methods_supported": confidential_auth_methods,
+        "frontchannel_logout_supported": true,
+        "frontchannel_logout_session_supported": true,
+        "backchannel_logout_supported": true,
+        "backchannel_logout_session_supported": true,
+        "scopes_supported": ["openid", "profile", "email", "phone", "address"],
+        "claims_supported": supported_claims(),
+        "claims_parameter_supported": true
     });
 
     Ok((StatusCode::OK, Json(response)))
@@ -415,8 +1385,15 @@ pub async fn discovery_handler(
 /// Get /userinfo
 pub async fn userinfo_handler(
     State(state): State<AppState>,
+    Path(realm_name): Path<String>,
     headers: HeaderMap,
 ) -> Result<Response> {
+    let realm = state
+        .realm_service
+        .find_by_name(&realm_name)
+        .await?
+        .ok_or(Error::RealmNotFound(realm_name))?;
+
     let Some(auth_header) = headers
         .get(header::AUTHORIZATION)
         .and_then(|value| value.to_str().ok())
@@ -436,7 +1413,7 @@ pub async fn userinfo_handler(
         ));
     };
 
-    match state.oidc_service.userinfo(token).await {
+    match state.oidc_service.userinfo(realm.id, token).await {
         Ok(response) => Ok((StatusCode::OK, Json(response)).into_response()),
         Err(err) => {
             let (error_code, status, description) = normalize_userinfo_error(&err);
@@ -449,6 +1426,240 @@ pub async fn userinfo_handler(
     }
 }
 
+fn normalize_registration_error(error: &Error) -> (&'static str, StatusCode, String) {
+    match error {
+        Error::OidcInvalidToken(message) => {
+            ("invalid_token", StatusCode::UNAUTHORIZED, message.clone())
+        }
+        Error::OidcInvalidRedirect(message) => (
+            "invalid_redirect_uri",
+            StatusCode::BAD_REQUEST,
+            message.clone(),
+        ),
+        Error::OidcInvalidClientMetadata(message) | Error::Validation(message) => (
+            "invalid_client_metadata",
+            StatusCode::BAD_REQUEST,
+            message.clone(),
+        ),
+        _ => (
+            "server_error",
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Unexpected server error".to_string(),
+        ),
+    }
+}
+
+fn registration_error_response(error: &Error) -> Response {
+    let (error_code, status, description) = normalize_registration_error(error);
+    if status == StatusCode::UNAUTHORIZED {
+        oidc_bearer_error(status, error_code, &description)
+    } else {
+        oidc_error_response(status, error_code, Some(&description))
+    }
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+/// Client information response (RFC 7591, Section 3.2.1 and RFC 7592,
+/// Section 3).
+async fn client_registration_response(
+    state: &AppState,
+    realm_name: &str,
+    registered: RegisteredClient,
+) -> serde_json::Value {
+    let base = state
+        .settings
+        .read()
+        .await
+        .server
+        .public_url
+        .trim_end_matches('/')
+        .to_string();
+    let client = &registered.client;
+    let registration = &registered.registration;
+    let uses_code = registration
+        .grant_types
+        .iter()
+        .any(|grant_type| grant_type == "authorization_code");
+
+    let mut response = serde_json::json!({
+        "client_id": client.client_id,
+        "client_id_issued_at": registration.created_at.timestamp(),
+        "registration_client_uri": format!(
+            "{}/api/realms/{}/oidc/register/{}",
+            base, realm_name, client.client_id
+        ),
+        "redirect_uris": serde_json::from_str::<Vec<String>>(&client.redirect_uris)
+            .unwrap_or_default(),
+        "grant_types": registration.grant_types,
+        "response_types": if uses_code { vec!["code"] } else { Vec::new() },
+        "token_endpoint_auth_method": client.token_endpoint_auth_method.as_str(),
+        "scope": serde_json::from_str::<Vec<String>>(&client.scopes)
+            .unwrap_or_default()
+            .join(" "),
+    });
+    let object = response.as_object_mut().expect("json object");
+    if let Some(secret) = registered.client_secret {
+        object.insert("client_secret".to_string(), secret.into());
+        object.insert("client_secret_expires_at".to_string(), 0.into());
+    }
+    if let Some(token) = registered.registration_access_token {
+        object.insert("registration_access_token".to_string(), token.into());
+    }
+    if let Some(name) = &registration.client_name {
+        object.insert("client_name".to_string(), name.clone().into());
+    }
+    if let Some(jwks) = client
+        .jwks
+        .as_deref()
+        .and_then(|raw| serde_json::from_str::<serde_json::Value>(raw).ok())
+    {
+        object.insert("jwks".to_string(), jwks);
+    }
+    if let Some(uri) = &client.backchannel_logout_uri {
+        object.insert("backchannel_logout_uri".to_string(), uri.clone().into());
+    }
+    if let Some(uri) = &client.frontchannel_logout_uri {
+        object.insert("frontchannel_logout_uri".to_string(), uri.clone().into());
+    }
+    response
+}
+
+/// Malformed metadata is an `invalid_client_metadata` error rather than the
+/// generic JSON rejection.
+fn parse_client_metadata(
+    body: std::result::Result<Json<ClientMetadata>, JsonRejection>,
+) -> Result<ClientMetadata> {
+    body.map(|Json(metadata)| metadata)
+        .map_err(|rejection| Error::OidcInvalidClientMetadata(rejection.body_text()))
+}
+
+/// POST /api/realms/{realm}/oidc/register
+/// Dynamic client registration (RFC 7591), authorized by an initial access
+/// token issued by a realm admin.
+pub async fn register_client_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+    headers: HeaderMap,
+    body: std::result::Result<Json<ClientMetadata>, JsonRejection>,
+) -> Result<Response> {
+    let realm = state
+        .realm_service
+        .find_by_name(&realm_name)
+        .await?
+        .ok_or(Error::RealmNotFound(realm_name.clone()))?;
+    let Some(token) = bearer_token(&headers) else {
+        return Ok(oidc_bearer_error(
+            StatusCode::UNAUTHORIZED,
+            "invalid_token",
+            "An initial access token is required",
+        ));
+    };
+    let metadata = match parse_client_metadata(body) {
+        Ok(metadata) => metadata,
+        Err(err) => return Ok(registration_error_response(&err)),
+    };
+
+    match state
+        .client_registration_service
+        .register(realm.id, token, metadata)
+        .await
+    {
+        Ok(registered) => {
+            let response = client_registration_response(&state, &realm_name, registered).await;
+            Ok((StatusCode::CREATED, Json(response)).into_response())
+        }
+        Err(err) => Ok(registration_error_response(&err)),
+    }
+}
+
+/// GET /api/realms/{realm}/oidc/register/{client_id} (RFC 7592, Section 2.1)
+pub async fn read_client_registration_handler(
+    State(state): State<AppState>,
+    Path((realm_name, client_id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let realm = state
+        .realm_service
+        .find_by_name(&realm_name)
+        .await?
+        .ok_or(Error::RealmNotFound(realm_name.clone()))?;
+    let token = bearer_token(&headers).unwrap_or_default();
+
+    match state
+        .client_registration_service
+        .read(realm.id, &client_id, token)
+        .await
+    {
+        Ok(registered) => {
+            let response = client_registration_response(&state, &realm_name, registered).await;
+            Ok((StatusCode::OK, Json(response)).into_response())
+        }
+        Err(err) => Ok(registration_error_response(&err)),
+    }
+}
+
+/// PUT /api/realms/{realm}/oidc/register/{client_id} (RFC 7592, Section 2.2)
+pub async fn update_client_registration_handler(
+    State(state): State<AppState>,
+    Path((realm_name, client_id)): Path<(String, String)>,
+    headers: HeaderMap,
+    body: std::result::Result<Json<ClientMetadata>, JsonRejection>,
+) -> Result<Response> {
+    let realm = state
+        .realm_service
+        .find_by_name(&realm_name)
+        .await?
+        .ok_or(Error::RealmNotFound(realm_name.clone()))?;
+    let token = bearer_token(&headers).unwrap_or_default();
+    let metadata = match parse_client_metadata(body) {
+        Ok(metadata) => metadata,
+        Err(err) => return Ok(registration_error_response(&err)),
+    };
+
+    match state
+        .client_registration_service
+        .update(realm.id, &client_id, token, metadata)
+        .await
+    {
+        Ok(registered) => {
+            let response = client_registration_response(&state, &realm_name, registered).await;
+            Ok((StatusCode::OK, Json(response)).into_response())
+        }
+        Err(err) => Ok(registration_error_response(&err)),
+    }
+}
+
+/// DELETE /api/realms/{realm}/oidc/register/{client_id} (RFC 7592, Section 2.3)
+pub async fn delete_client_registration_handler(
+    State(state): State<AppState>,
+    Path((realm_name, client_id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let realm = state
+        .realm_service
+        .find_by_name(&realm_name)
+        .await?
+        .ok_or(Error::RealmNotFound(realm_name))?;
+    let token = bearer_token(&headers).unwrap_or_default();
+
+    match state
+        .client_registration_service
+        .delete(realm.id, &client_id, token)
+        .await
+    {
+        Ok(()) => Ok(StatusCode::NO_CONTENT.into_response()),
+        Err(err) => Ok(registration_error_response(&err)),
+    }
+}
+
 pub async fn list_clients_handler(
     State(state): State<AppState>,
     Path(realm_name): Path<String>,
@@ -496,6 +1707,14 @@ pub struct CreateClientRequest {
     pub redirect_uris: Vec<String>,
     pub web_origins: Option<Vec<String>>,
     pub scopes: Option<Vec<String>>,
+    pub token_endpoint_auth_method: Option<TokenEndpointAuthMethod>,
+    pub jwks: Option<serde_json::Value>,
+    pub signing_algorithm: Option<SigningAlgorithm>,
+    pub backchannel_logout_uri: Option<String>,
+    pub frontchannel_logout_uri: Option<String>,
+    pub token_exchange_policy: Option<Vec<TokenExchangeRule>>,
+    pub direct_grant_enabled: Option<bool>,
+    pub require_pushed_authorization_requests: Option<bool>,
 }
 
 #[derive(Serialize)]
@@ -509,6 +1728,14 @@ pub struct OidcClientResponse {
     pub web_origins: String,
     pub managed_by_config: bool,
     pub confidential: bool,
+    pub token_endpoint_auth_method: TokenEndpointAuthMethod,
+    pub jwks: Option<serde_json::Value>,
+    pub signing_algorithm: Option<SigningAlgorithm>,
+    pub backchannel_logout_uri: Option<String>,
+    pub frontchannel_logout_uri: Option<String>,
+    pub token_exchange_policy: Vec<TokenExchangeRule>,
+    pub direct_grant_enabled: bool,
+    pub require_pushed_authorization_requests: bool,
 }
 
 fn to_client_response(client: &OidcClient, secret: Option<String>) -> OidcClientResponse {
@@ -522,6 +1749,17 @@ fn to_client_response(client: &OidcClient, secret: Option<String>) -> OidcClient
         web_origins: client.web_origins.clone(),
         managed_by_config: client.managed_by_config,
         confidential: client.client_secret.is_some(),
+        token_endpoint_auth_method: client.token_endpoint_auth_method,
+        jwks: client
+            .jwks
+            .as_deref()
+            .and_then(|raw| serde_json::from_str(raw).ok()),
+        signing_algorithm: client.signing_algorithm,
+        backchannel_logout_uri: client.backchannel_logout_uri.clone(),
+        frontchannel_logout_uri: client.frontchannel_logout_uri.clone(),
+        token_exchange_policy: client.token_exchange_rules().unwrap_or_default(),
+        direct_grant_enabled: client.direct_grant_enabled,
+        require_pushed_authorization_requests: client.require_pushed_authorization_requests,
     }
 }
 
@@ -562,6 +1800,18 @@ pub async fn create_client_handler(
         web_origins: web_origins_json,
         scopes: scopes_json,
         managed_by_config: false,
+        token_endpoint_auth_method: payload.token_endpoint_auth_method.unwrap_or_default(),
+        jwks: payload.jwks.map(|jwks| jwks.to_string()),
+        signing_algorithm: payload.signing_algorithm,
+        backchannel_logout_uri: payload.backchannel_logout_uri,
+        frontchannel_logout_uri: payload.frontchannel_logout_uri,
+        token_exchange_policy: token_exchange_policy_json(
+            &payload.token_exchange_policy.unwrap_or_default(),
+        )?,
+        direct_grant_enabled: payload.direct_grant_enabled.unwrap_or(false),
+        require_pushed_authorization_requests: payload
+            .require_pushed_authorization_requests
+            .unwrap_or(false),
     };
 
     let secret = state.oidc_service.register_client(&mut client).await?;
@@ -573,38 +1823,30 @@ pub async fn create_client_handler(
 
 pub async fn get_client_handler(
     State(state): State<AppState>,
-    Path((_realm, id)): Path<(String, Uuid)>,
+    Extension(actor_id): Extension<Uuid>,
+    Path((realm_name, id)): Path<(String, Uuid)>,
 ) -> Result<impl IntoResponse> {
-    let client = state.oidc_service.get_client(id).await?;
+    let client = realm_client(&state, actor_id, realm_name, id, permissions::CLIENT_READ).await?;
     Ok((StatusCode::OK, Json(to_client_response(&client, None))))
 }
 
 pub async fn update_client_handler(
     State(state): State<AppState>,
-    Path((_realm, id)): Path<(String, Uuid)>,
+    Extension(actor_id): Extension<Uuid>,
+    Path((realm_name, id)): Path<(String, Uuid)>,
     Json(payload): Json<crate::application::oidc_service::UpdateClientRequest>,
 ) -> Result<impl IntoResponse> {
+    realm_client(&state, actor_id, realm_name, id, permissions::CLIENT_UPDATE).await?;
     let client = state.oidc_service.update_client(id, payload).await?;
     Ok((StatusCode::OK, Json(to_client_response(&client, None))))
 }
 
 pub async fn rotate_client_secret_handler(
     State(state): State<AppState>,
+    Extension(actor_id): Extension<Uuid>,
     Path((realm_name, id)): Path<(String, Uuid)>,
 ) -> Result<impl IntoResponse> {
-    let realm = state
-        .realm_service
-        .find_by_name(&realm_name)
-        .await?
-        .ok_or(Error::RealmNotFound(realm_name))?;
-
-    let client = state.oidc_service.get_client(id).await?;
-    if client.realm_id != realm.id {
-        return Err(Error::SecurityViolation(
-            "Client does not belong to this realm".to_string(),
-        ));
-    }
-
+    realm_client(&state, actor_id, realm_name, id, permissions::CLIENT_UPDATE).await?;
     let (client, secret) = state.oidc_service.rotate_client_secret(id).await?;
     Ok((
         StatusCode::OK,
@@ -614,29 +1856,33 @@ pub async fn rotate_client_secret_handler(
 
 pub async fn get_client_delete_summary_handler(
     State(state): State<AppState>,
+    Extension(actor_id): Extension<Uuid>,
     Path((realm_name, id)): Path<(String, Uuid)>,
 ) -> Result<impl IntoResponse> {
-    let realm = state
-        .realm_service
-        .find_by_name(&realm_name)
-        .await?
-        .ok_or(Error::RealmNotFound(realm_name))?;
-
-    let client = state.oidc_service.get_client(id).await?;
-    if client.realm_id != realm.id {
-        return Err(Error::SecurityViolation(
-            "Client does not belong to this realm".to_string(),
-        ));
-    }
-
+    realm_client(&state, actor_id, realm_name, id, permissions::CLIENT_READ).await?;
     let summary = state.oidc_service.get_client_delete_summary(id).await?;
     Ok((StatusCode::OK, Json(summary)))
 }
 
 pub async fn delete_client_handler(
     State(state): State<AppState>,
+    Extension(actor_id): Extension<Uuid>,
     Path((realm_name, id)): Path<(String, Uuid)>,
 ) -> Result<impl IntoResponse> {
+    realm_client(&state, actor_id, realm_name, id, permissions::CLIENT_DELETE).await?;
+    state.oidc_service.delete_client(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Loads a client for the admin API, refusing clients of another realm and
+/// callers whose `permission` grant does not cover the client.
+async fn realm_client(
+    state: &AppState,
+    actor_id: Uuid,
+    realm_name: String,
+    id: Uuid,
+    permission: &str,
+) -> Result<OidcClient> {
     let realm = state
         .realm_service
         .find_by_name(&realm_name)
@@ -649,7 +1895,58 @@ pub async fn delete_client_handler(
             "Client does not belong to this realm".to_string(),
         ));
     }
+    let resource = AdminResource::Client {
+        realm_id: realm.id,
+        client_id: client.id,
+    };
+    permission_guard::authorize(state, &actor_id, permission, resource).await?;
+    Ok(client)
+}
 
-    state.oidc_service.delete_client(id).await?;
+pub async fn list_protocol_mappers_handler(
+    State(state): State<AppState>,
+    Extension(actor_id): Extension<Uuid>,
+    Path((realm_name, id)): Path<(String, Uuid)>,
+) -> Result<impl IntoResponse> {
+    let client = realm_client(&state, actor_id, realm_name, id, permissions::CLIENT_READ).await?;
+    let mappers = state.claims_service.list_mappers(&client).await?;
+    Ok((StatusCode::OK, Json(mappers)))
+}
+
+pub async fn create_protocol_mapper_handler(
+    State(state): State<AppState>,
+    Extension(actor_id): Extension<Uuid>,
+    Path((realm_name, id)): Path<(String, Uuid)>,
+    Json(payload): Json<ProtocolMapperPayload>,
+) -> Result<impl IntoResponse> {
+    let client = realm_client(&state, actor_id, realm_name, id, permissions::CLIENT_UPDATE).await?;
+    let mapper = state.claims_service.create_mapper(&client, payload).await?;
+    Ok((StatusCode::CREATED, Json(mapper)))
+}
+
+pub async fn update_protocol_mapper_handler(
+    State(state): State<AppState>,
+    Extension(actor_id): Extension<Uuid>,
+    Path((realm_name, id, mapper_id)): Path<(String, Uuid, Uuid)>,
+    Json(payload): Json<ProtocolMapperPayload>,
+) -> Result<impl IntoResponse> {
+    let client = realm_client(&state, actor_id, realm_name, id, permissions::CLIENT_UPDATE).await?;
+    let mapper = state
+        .claims_service
+        .update_mapper(&client, mapper_id, payload)
+        .await?;
+    Ok((StatusCode::OK, Json(mapper)))
+}
+
+pub async fn delete_protocol_mapper_handler(
+    State(state): State<AppState>,
+    Extension(actor_id): Extension<Uuid>,
+    Path((realm_name, id, mapper_id)): Path<(String, Uuid, Uuid)>,
+) -> Result<impl IntoResponse> {
+    let client = realm_client(&state, actor_id, realm_name, id, permissions::CLIENT_UPDATE).await?;
+    state
+        .claims_service
+        .delete_mapper(&client, mapper_id)
+        .await?;
     Ok(StatusCode::NO_CONTENT)
 }
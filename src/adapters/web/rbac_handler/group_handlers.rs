@@ -1,7 +1,8 @@
-use super::record_audit;
 use super::*;
+use super::{authorize, record_audit};
 use crate::adapters::web::auth_middleware::AuthUser;
 use crate::application::rbac_service::CreateGroupPayload;
+use crate::domain::permissions::{self, AdminResource};
 use crate::domain::rbac::{GroupMemberFilter, GroupRoleFilter};
 use crate::error::{Error, Result};
 use crate::AppState;
@@ -27,6 +28,15 @@ pub async fn create_group_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    let parent = match payload.parent_id {
+        Some(group_id) => AdminResource::Group {
+            realm_id: realm.id,
+            group_id,
+        },
+        None => AdminResource::Realm(realm.id),
+    };
+    authorize(&state, actor.id, permissions::RBAC_WRITE, parent).await?;
+
     // 2. Pass realm ID to service
     let group = state.rbac_service.create_group(realm.id, payload).await?;
 
@@ -46,6 +56,7 @@ pub async fn create_group_handler(
 }
 pub async fn list_groups_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path(realm_name): Path<String>,
     Query(req): Query<GroupListQuery>,
 ) -> Result<impl IntoResponse> {
@@ -55,11 +66,20 @@ pub async fn list_groups_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize(
+        &state,
+        actor.id,
+        permissions::RBAC_WRITE,
+        AdminResource::Realm(realm.id),
+    )
+    .await?;
+
     let response = state.rbac_service.list_groups(realm.id, req.page).await?;
     Ok((StatusCode::OK, Json(response)))
 }
 pub async fn list_group_roots_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path(realm_name): Path<String>,
     Query(req): Query<GroupTreeQuery>,
 ) -> Result<impl IntoResponse> {
@@ -69,6 +89,14 @@ pub async fn list_group_roots_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize(
+        &state,
+        actor.id,
+        permissions::RBAC_WRITE,
+        AdminResource::Realm(realm.id),
+    )
+    .await?;
+
     let response = state
         .rbac_service
         .list_group_roots(realm.id, req.page)
@@ -77,6 +105,7 @@ pub async fn list_group_roots_handler(
 }
 pub async fn list_group_children_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, group_id)): Path<(String, Uuid)>,
     Query(req): Query<GroupTreeQuery>,
 ) -> Result<impl IntoResponse> {
@@ -86,6 +115,17 @@ pub async fn list_group_children_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize(
+        &state,
+        actor.id,
+        permissions::RBAC_WRITE,
+        AdminResource::Group {
+            realm_id: realm.id,
+            group_id,
+        },
+    )
+    .await?;
+
     let response = state
         .rbac_service
         .list_group_children(realm.id, group_id, req.page)
@@ -95,6 +135,7 @@ pub async fn list_group_children_handler(
 
 pub async fn get_group_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, group_id)): Path<(String, Uuid)>,
 ) -> Result<impl IntoResponse> {
     let realm = state
@@ -103,11 +144,23 @@ pub async fn get_group_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize(
+        &state,
+        actor.id,
+        permissions::RBAC_WRITE,
+        AdminResource::Group {
+            realm_id: realm.id,
+            group_id,
+        },
+    )
+    .await?;
+
     let group = state.rbac_service.get_group(realm.id, group_id).await?;
     Ok((StatusCode::OK, Json(group)))
 }
 pub async fn get_group_delete_summary_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, group_id)): Path<(String, Uuid)>,
 ) -> Result<impl IntoResponse> {
     let realm = state
@@ -116,6 +169,17 @@ pub async fn get_group_delete_summary_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize(
+        &state,
+        actor.id,
+        permissions::RBAC_WRITE,
+        AdminResource::Group {
+            realm_id: realm.id,
+            group_id,
+        },
+    )
+    .await?;
+
     let summary = state
         .rbac_service
         .get_group_delete_summary(realm.id, group_id)
@@ -135,6 +199,17 @@ pub async fn update_group_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize(
+        &state,
+        actor.id,
+        permissions::RBAC_WRITE,
+        AdminResource::Group {
+            realm_id: realm.id,
+            group_id,
+        },
+    )
+    .await?;
+
     let updated_group = state
         .rbac_service
         .update_group(realm.id, group_id, payload)
@@ -167,6 +242,17 @@ pub async fn delete_group_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize(
+        &state,
+        actor.id,
+        permissions::RBAC_WRITE,
+        AdminResource::Group {
+            realm_id: realm.id,
+            group_id,
+        },
+    )
+    .await?;
+
     let cascade = query.cascade.unwrap_or(false);
     state
         .rbac_service
@@ -198,6 +284,25 @@ pub async fn move_group_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize(
+        &state,
+        actor.id,
+        permissions::RBAC_WRITE,
+        AdminResource::Group {
+            realm_id: realm.id,
+            group_id,
+        },
+    )
+    .await?;
+    let destination = match payload.parent_id {
+        Some(group_id) => AdminResource::Group {
+            realm_id: realm.id,
+            group_id,
+        },
+        None => AdminResource::Realm(realm.id),
+    };
+    authorize(&state, actor.id, permissions::RBAC_WRITE, destination).await?;
+
     state
         .rbac_service
         .move_group(
@@ -238,6 +343,23 @@ pub async fn assign_user_to_group_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize(
+        &state,
+        actor.id,
+        permissions::RBAC_WRITE,
+        AdminResource::Group {
+            realm_id: realm.id,
+            group_id,
+        },
+    )
+    .await?;
+    // Scoped admins can only move users they already manage.
+    let member = AdminResource::User {
+        realm_id: realm.id,
+        user_id: payload.user_id,
+    };
+    authorize(&state, actor.id, permissions::RBAC_WRITE, member).await?;
+
     state
         .rbac_service
         .assign_user_to_group(realm.id, payload.user_id, group_id)
@@ -268,6 +390,17 @@ pub async fn remove_user_from_group_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize(
+        &state,
+        actor.id,
+        permissions::RBAC_WRITE,
+        AdminResource::Group {
+            realm_id: realm.id,
+            group_id,
+        },
+    )
+    .await?;
+
     state
         .rbac_service
         .remove_user_from_group(realm.id, user_id, group_id)
@@ -288,6 +421,7 @@ pub async fn remove_user_from_group_handler(
 }
 pub async fn list_group_members_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, group_id)): Path<(String, Uuid)>,
 ) -> Result<impl IntoResponse> {
     let realm = state
@@ -296,6 +430,17 @@ pub async fn list_group_members_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize(
+        &state,
+        actor.id,
+        permissions::RBAC_WRITE,
+        AdminResource::Group {
+            realm_id: realm.id,
+            group_id,
+        },
+    )
+    .await?;
+
     let users = state
         .rbac_service
         .get_group_member_ids(realm.id, group_id)
@@ -305,6 +450,7 @@ pub async fn list_group_members_handler(
 }
 pub async fn list_group_members_page_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, group_id)): Path<(String, Uuid)>,
     Query(query): Query<GroupMembersListQuery>,
 ) -> Result<impl IntoResponse> {
@@ -314,6 +460,17 @@ pub async fn list_group_members_page_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize(
+        &state,
+        actor.id,
+        permissions::RBAC_WRITE,
+        AdminResource::Group {
+            realm_id: realm.id,
+            group_id,
+        },
+    )
+    .await?;
+
     let filter = match query.filter.as_deref().unwrap_or("all") {
         "all" => GroupMemberFilter::All,
         "members" => GroupMemberFilter::Members,
@@ -340,9 +497,17 @@ pub async fn assign_role_to_group_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize(
+        &state,
+        actor.id,
+        permissions::RBAC_WRITE,
+        AdminResource::Realm(realm.id),
+    )
+    .await?;
+
     state
         .rbac_service
-        .assign_role_to_group(realm.id, payload.role_id, group_id)
+        .assign_role_to_group_as(actor.id, realm.id, payload.role_id, group_id)
         .await?;
 
     record_audit(
@@ -370,6 +535,14 @@ pub async fn remove_role_from_group_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize(
+        &state,
+        actor.id,
+        permissions::RBAC_WRITE,
+        AdminResource::Realm(realm.id),
+    )
+    .await?;
+
     state
         .rbac_service
         .remove_role_from_group(realm.id, role_id, group_id)
@@ -402,6 +575,26 @@ pub async fn bulk_group_members_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize(
+        &state,
+        actor.id,
+        permissions::RBAC_WRITE,
+        AdminResource::Group {
+            realm_id: realm.id,
+            group_id,
+        },
+    )
+    .await?;
+    if payload.action == "add" {
+        for user_id in &payload.user_ids {
+            let member = AdminResource::User {
+                realm_id: realm.id,
+                user_id: *user_id,
+            };
+            authorize(&state, actor.id, permissions::RBAC_WRITE, member).await?;
+        }
+    }
+
     let action = payload.action.clone();
     let count = payload.user_ids.len();
     state
@@ -439,11 +632,25 @@ pub async fn bulk_group_roles_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize(
+        &state,
+        actor.id,
+        permissions::RBAC_WRITE,
+        AdminResource::Realm(realm.id),
+    )
+    .await?;
+
     let action = payload.action.clone();
     let count = payload.role_ids.len();
     state
         .rbac_service
-        .bulk_update_group_roles(realm.id, group_id, payload.role_ids, payload.action)
+        .bulk_update_group_roles_as(
+            actor.id,
+            realm.id,
+            group_id,
+            payload.role_ids,
+            payload.action,
+        )
         .await?;
 
     record_audit(
@@ -464,6 +671,7 @@ pub async fn bulk_group_roles_handler(
 }
 pub async fn list_group_roles_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, group_id)): Path<(String, Uuid)>,
     Query(query): Query<GroupRolesScopeQuery>,
 ) -> Result<impl IntoResponse> {
@@ -473,6 +681,17 @@ pub async fn list_group_roles_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize(
+        &state,
+        actor.id,
+        permissions::RBAC_WRITE,
+        AdminResource::Group {
+            realm_id: realm.id,
+            group_id,
+        },
+    )
+    .await?;
+
     let scope = query.scope.unwrap_or_else(|| "direct".to_string());
 
     let roles = match scope.as_str() {
@@ -499,6 +718,7 @@ pub async fn list_group_roles_handler(
 }
 pub async fn list_group_roles_page_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, group_id)): Path<(String, Uuid)>,
     Query(query): Query<GroupRolesListQuery>,
 ) -> Result<impl IntoResponse> {
@@ -508,6 +728,17 @@ pub async fn list_group_roles_page_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize(
+        &state,
+        actor.id,
+        permissions::RBAC_WRITE,
+        AdminResource::Group {
+            realm_id: realm.id,
+            group_id,
+        },
+    )
+    .await?;
+
     let filter = match query.filter.as_deref().unwrap_or("all") {
         "all" => GroupRoleFilter::All,
         "assigned" => GroupRoleFilter::Direct,
@@ -1,5 +1,8 @@
+use crate::adapters::web::middleware::permission_guard;
 use crate::domain::audit::NewAuditEvent;
 use crate::domain::pagination::PageRequest;
+use crate::domain::permissions::AdminResource;
+use crate::error::Result;
 use crate::AppState;
 use serde::Deserialize;
 use tracing::error;
@@ -28,6 +31,17 @@ pub(super) async fn record_audit(
     }
 }
 
+/// Checks the caller's grant against the resource a handler acts on, for
+/// routes behind `require_scoped_permission`.
+pub(super) async fn authorize(
+    state: &AppState,
+    actor_id: Uuid,
+    permission: &str,
+    resource: AdminResource,
+) -> Result<()> {
+    permission_guard::authorize(state, &actor_id, permission, resource).await
+}
+
 pub mod group_handlers;
 pub mod role_handlers;
 
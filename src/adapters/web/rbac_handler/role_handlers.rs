@@ -1,11 +1,11 @@
-use super::record_audit;
 use super::*;
+use super::{authorize, record_audit};
 use crate::adapters::web::auth_middleware::AuthUser;
 use crate::application::rbac_service::{
     CreateCustomPermissionPayload, CreateRolePayload, UpdateCustomPermissionPayload,
 };
 use crate::domain::pagination::PageRequest;
-use crate::domain::permissions::{self, PermissionDef, ResourceGroup};
+use crate::domain::permissions::{self, AdminResource, PermissionDef, ResourceGroup};
 use crate::domain::rbac::{RoleCompositeFilter, RoleMemberFilter, UserRoleFilter};
 use crate::error::{Error, Result};
 use crate::AppState;
@@ -114,7 +114,7 @@ pub async fn assign_permission_handler(
     let permission = payload.permission.clone();
     state
         .rbac_service
-        .assign_permission_to_role(realm.id, role_id, payload.permission)
+        .assign_permission_to_role_as(actor.id, realm.id, role_id, payload.permission)
         .await?;
 
     record_audit(
@@ -142,9 +142,17 @@ pub async fn assign_user_role_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize(
+        &state,
+        actor.id,
+        permissions::USER_WRITE,
+        AdminResource::Realm(realm.id),
+    )
+    .await?;
+
     state
         .rbac_service
-        .assign_role_to_user(realm.id, user_id, payload.role_id)
+        .assign_role_to_user_as(actor.id, realm.id, user_id, payload.role_id)
         .await?;
 
     record_audit(
@@ -162,6 +170,7 @@ pub async fn assign_user_role_handler(
 }
 pub async fn list_user_roles_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, user_id)): Path<(String, Uuid)>,
     Query(query): Query<UserRolesScopeQuery>,
 ) -> Result<impl IntoResponse> {
@@ -171,6 +180,17 @@ pub async fn list_user_roles_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize(
+        &state,
+        actor.id,
+        permissions::USER_WRITE,
+        AdminResource::User {
+            realm_id: realm.id,
+            user_id,
+        },
+    )
+    .await?;
+
     let scope = query.scope.unwrap_or_else(|| "direct".to_string());
 
     let roles = match scope.as_str() {
@@ -197,6 +217,7 @@ pub async fn list_user_roles_handler(
 }
 pub async fn list_user_roles_page_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, user_id)): Path<(String, Uuid)>,
     Query(query): Query<UserRolesListQuery>,
 ) -> Result<impl IntoResponse> {
@@ -206,6 +227,17 @@ pub async fn list_user_roles_page_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize(
+        &state,
+        actor.id,
+        permissions::USER_WRITE,
+        AdminResource::User {
+            realm_id: realm.id,
+            user_id,
+        },
+    )
+    .await?;
+
     let filter = match query.filter.as_deref().unwrap_or("all") {
         "all" => UserRoleFilter::All,
         "direct" => UserRoleFilter::Direct,
@@ -354,6 +386,14 @@ pub async fn remove_user_role_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize(
+        &state,
+        actor.id,
+        permissions::USER_WRITE,
+        AdminResource::Realm(realm.id),
+    )
+    .await?;
+
     state
         .rbac_service
         .remove_role_from_user(realm.id, user_id, role_id)
@@ -776,7 +816,13 @@ pub async fn bulk_permissions_handler(
     let count = payload.permissions.len();
     state
         .rbac_service
-        .bulk_update_permissions(realm.id, role_id, payload.permissions, payload.action)
+        .bulk_update_permissions_as(
+            actor.id,
+            realm.id,
+            role_id,
+            payload.permissions,
+            payload.action,
+        )
         .await?;
 
     record_audit(
@@ -0,0 +1,78 @@
+use crate::application::client_registration_service::{
+    CreateInitialAccessTokenPayload, UpdateClientRegistrationPolicyPayload,
+};
+use crate::domain::client_registration::InitialAccessToken;
+use crate::{error::Result, AppState};
+use axum::extract::{Path, State};
+use axum::{http::StatusCode, response::IntoResponse, Json};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// An initial access token together with its plaintext, which is only shown
+/// once.
+#[derive(Serialize)]
+pub struct CreatedInitialAccessTokenResponse {
+    #[serde(flatten)]
+    pub token: InitialAccessToken,
+    pub access_token: String,
+}
+
+pub async fn get_client_registration_policy_handler(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    let policy = state.client_registration_service.get_policy(id).await?;
+    Ok((StatusCode::OK, Json(policy)))
+}
+
+pub async fn update_client_registration_policy_handler(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateClientRegistrationPolicyPayload>,
+) -> Result<impl IntoResponse> {
+    let policy = state
+        .client_registration_service
+        .update_policy(id, payload)
+        .await?;
+    Ok(Json(policy))
+}
+
+pub async fn list_initial_access_tokens_handler(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    let tokens = state
+        .client_registration_service
+        .list_initial_access_tokens(id)
+        .await?;
+    Ok((StatusCode::OK, Json(tokens)))
+}
+
+pub async fn create_initial_access_token_handler(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CreateInitialAccessTokenPayload>,
+) -> Result<impl IntoResponse> {
+    let (token, access_token) = state
+        .client_registration_service
+        .create_initial_access_token(id, payload)
+        .await?;
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedInitialAccessTokenResponse {
+            token,
+            access_token,
+        }),
+    ))
+}
+
+pub async fn delete_initial_access_token_handler(
+    State(state): State<AppState>,
+    Path((id, token_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse> {
+    state
+        .client_registration_service
+        .delete_initial_access_token(id, token_id)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,63 @@
+use crate::application::password_policy_service::UpdatePasswordPolicyPayload;
+use crate::domain::password_policy::PasswordPolicy;
+use crate::{error::Result, AppState};
+use axum::extract::{Path, State};
+use axum::{http::StatusCode, response::IntoResponse, Json};
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Serialize)]
+pub struct RealmPasswordPolicyResponse {
+    pub realm_id: Uuid,
+    pub min_length: i64,
+    pub max_length: i64,
+    pub require_lowercase: bool,
+    pub require_uppercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    pub disallow_username: bool,
+    pub history_count: i64,
+    pub max_age_days: i64,
+    pub check_breached: bool,
+}
+
+impl From<PasswordPolicy> for RealmPasswordPolicyResponse {
+    fn from(policy: PasswordPolicy) -> Self {
+        Self {
+            realm_id: policy.realm_id,
+            min_length: policy.min_length,
+            max_length: policy.max_length,
+            require_lowercase: policy.require_lowercase,
+            require_uppercase: policy.require_uppercase,
+            require_digit: policy.require_digit,
+            require_symbol: policy.require_symbol,
+            disallow_username: policy.disallow_username,
+            history_count: policy.history_count,
+            max_age_days: policy.max_age_days,
+            check_breached: policy.check_breached,
+        }
+    }
+}
+
+pub async fn get_realm_password_policy_handler(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    let policy = state.password_policy_service.get_policy(id).await?;
+    Ok((
+        StatusCode::OK,
+        Json(RealmPasswordPolicyResponse::from(policy)),
+    ))
+}
+
+pub async fn update_realm_password_policy_handler(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdatePasswordPolicyPayload>,
+) -> Result<impl IntoResponse> {
+    let policy = state
+        .password_policy_service
+        .update_policy(id, payload)
+        .await?;
+    Ok(Json(RealmPasswordPolicyResponse::from(policy)))
+}
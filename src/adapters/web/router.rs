@@ -1,10 +1,12 @@
 use super::{
     audit_handler, auth_handler, auth_middleware, config_handler, execution_handler, flow_handler,
     harbor_handler, idp_admin_handler, invitation_handler, log_stream_handler,
-    oauth_broker_handler, observability_handler, oidc_handler, rbac_handler, realm_email_handler,
-    realm_handler, realm_idp_settings_handler, realm_passkey_handler, realm_recovery_handler,
-    realm_security_headers_handler, search_handler, server::ui_handler, session_handler,
-    setup_handler, theme_handler, user_handler, webhook_handler,
+    oauth_broker_handler, observability_handler, oidc_handler, rbac_handler,
+    realm_client_registration_handler, realm_email_handler, realm_handler,
+    realm_idp_settings_handler, realm_passkey_handler, realm_password_policy_handler,
+    realm_recovery_handler, realm_security_headers_handler, saml_handler, scim_handler,
+    search_handler, server::ui_handler, session_handler, setup_handler, signing_key_handler,
+    theme_handler, user_federation_handler, user_handler, webhook_handler,
 };
 use crate::adapters::web::middleware::{
     cors_middleware, permission_guard, request_logging, security_headers,
@@ -27,9 +29,11 @@ pub fn create_router(app_state: AppState) -> Router {
         .nest("/execution", execution_routes())
         .nest("/realms/{realm}/auth", auth_routes())
         .nest("/realms/{realm}/oidc", oidc_routes())
+        .nest("/realms/{realm}/saml", saml_routes())
         .nest("/realms/{realm}/theme", theme_routes())
         .nest("/realms/{realm}/invitations", public_invitation_routes())
-        .nest("/realms/{realm}/users", public_user_routes());
+        .nest("/realms/{realm}/users", public_user_routes())
+        .nest("/realms/{realm}/scim/v2", scim_routes(app_state.clone()));
 
     // 2. Protected Routes (Require Login)
     // We construct these using the corrected helper functions
@@ -37,10 +41,18 @@ pub fn create_router(app_state: AppState) -> Router {
         .merge(config_routes(app_state.clone()))
         .nest("/realms", realm_routes(app_state.clone()))
         .nest("/realms/{realm}/clients", client_routes(app_state.clone()))
+        .nest(
+            "/realms/{realm}/saml/service-providers",
+            saml_service_provider_routes(app_state.clone()),
+        )
         .nest(
             "/realms/{realm}/identity-providers",
             identity_provider_routes(app_state.clone()),
         )
+        .nest(
+            "/realms/{realm}/user-federation",
+            user_federation_routes(app_state.clone()),
+        )
         .nest("/realms/{realm}/rbac", rbac_routes(app_state.clone()))
         .nest("/realms/{realm}/audits", audit_routes(app_state.clone()))
         .nest(
@@ -165,6 +177,18 @@ fn auth_routes() -> Router<AppState> {
             "/oauth/{alias}/callback",
             get(oauth_broker_handler::oauth_callback_handler),
         )
+        .route(
+            "/saml/{alias}/acs",
+            post(oauth_broker_handler::saml_acs_handler),
+        )
+        .route(
+            "/saml/{alias}/continue",
+            get(oauth_broker_handler::saml_acs_continue_handler),
+        )
+        .route(
+            "/saml/{alias}/metadata",
+            get(oauth_broker_handler::saml_sp_metadata_handler),
+        )
         .route("/refresh", post(auth_handler::refresh_handler))
         .route("/logout", post(auth_handler::logout_handler))
 }
@@ -210,6 +234,54 @@ fn identity_provider_routes(state: AppState) -> Router<AppState> {
         ))
 }
 
+fn user_federation_routes(state: AppState) -> Router<AppState> {
+    let read_routes = Router::new()
+        .route("/", get(user_federation_handler::list_providers_handler))
+        .route("/{id}", get(user_federation_handler::get_provider_handler))
+        .route(
+            "/{id}/mappers",
+            get(user_federation_handler::list_mappers_handler),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            move |state, req, next| {
+                permission_guard::require_permission(state, req, next, permissions::REALM_READ)
+            },
+        ));
+
+    let write_routes = Router::new()
+        .route("/", post(user_federation_handler::create_provider_handler))
+        .route(
+            "/{id}",
+            put(user_federation_handler::update_provider_handler)
+                .delete(user_federation_handler::delete_provider_handler),
+        )
+        .route(
+            "/{id}/mappers",
+            post(user_federation_handler::create_mapper_handler),
+        )
+        .route(
+            "/{id}/mappers/{mapper_id}",
+            delete(user_federation_handler::delete_mapper_handler),
+        )
+        .route(
+            "/{id}/test-connection",
+            post(user_federation_handler::test_connection_handler),
+        )
+        .route(
+            "/{id}/sync",
+            post(user_federation_handler::sync_provider_handler),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            state,
+            move |state, req, next| {
+                permission_guard::require_permission(state, req, next, permissions::REALM_WRITE)
+            },
+        ));
+
+    read_routes.merge(write_routes)
+}
+
 fn config_routes(state: AppState) -> Router<AppState> {
     Router::new()
         .route(
@@ -228,6 +300,49 @@ fn public_user_routes() -> Router<AppState> {
     Router::new().route("/", post(user_handler::create_user_handler))
 }
 
+// SCIM clients authenticate with a client_credentials token; each handler
+// checks the token's scim:* scopes.
+fn scim_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route(
+            "/ServiceProviderConfig",
+            get(scim_handler::service_provider_config_handler),
+        )
+        .route("/ResourceTypes", get(scim_handler::resource_types_handler))
+        .route(
+            "/ResourceTypes/{name}",
+            get(scim_handler::resource_type_handler),
+        )
+        .route("/Schemas", get(scim_handler::schemas_handler))
+        .route("/Schemas/{id}", get(scim_handler::schema_handler))
+        .route(
+            "/Users",
+            get(scim_handler::list_users_handler).post(scim_handler::create_user_handler),
+        )
+        .route(
+            "/Users/{id}",
+            get(scim_handler::get_user_handler)
+                .put(scim_handler::replace_user_handler)
+                .patch(scim_handler::patch_user_handler)
+                .delete(scim_handler::delete_user_handler),
+        )
+        .route(
+            "/Groups",
+            get(scim_handler::list_groups_handler).post(scim_handler::create_group_handler),
+        )
+        .route(
+            "/Groups/{id}",
+            get(scim_handler::get_group_handler)
+                .put(scim_handler::replace_group_handler)
+                .patch(scim_handler::patch_group_handler)
+                .delete(scim_handler::delete_group_handler),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            state,
+            scim_handler::scim_auth_guard,
+        ))
+}
+
 fn public_invitation_routes() -> Router<AppState> {
     Router::new().route(
         "/accept",
@@ -244,20 +359,44 @@ fn protected_user_routes(state: AppState) -> Router<AppState> {
         .route(
             "/me/metadata/unsafe",
             put(user_handler::update_me_unsafe_metadata_handler),
+        )
+        .route("/me/consents", get(user_handler::list_me_consents_handler))
+        .route(
+            "/me/consents/{client_id}",
+            delete(user_handler::revoke_me_consent_handler),
         );
 
     // 2. Read Permission
     let read_routes = Router::new()
         .route("/", get(user_handler::list_users_handler))
         .route("/stats", get(user_handler::get_user_stats_handler))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            move |state, req, next| {
+                permission_guard::require_permission(state, req, next, permissions::USER_READ)
+            },
+        ));
+
+    // Per-user routes also admit grants scoped to a group subtree; the
+    // handlers check the user they act on.
+    let user_read_routes = Router::new()
         .route(
             "/{id}/metadata",
             get(user_handler::get_user_metadata_handler),
         )
+        .route(
+            "/{id}/consents",
+            get(user_handler::list_user_consents_handler),
+        )
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
             move |state, req, next| {
-                permission_guard::require_permission(state, req, next, permissions::USER_READ)
+                permission_guard::require_scoped_permission(
+                    state,
+                    req,
+                    next,
+                    permissions::USER_READ,
+                )
             },
         ));
 
@@ -267,7 +406,12 @@ fn protected_user_routes(state: AppState) -> Router<AppState> {
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
             move |state, req, next| {
-                permission_guard::require_permission(state, req, next, permissions::USER_DELETE)
+                permission_guard::require_scoped_permission(
+                    state,
+                    req,
+                    next,
+                    permissions::USER_DELETE,
+                )
             },
         ));
 
@@ -276,7 +420,12 @@ fn protected_user_routes(state: AppState) -> Router<AppState> {
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
             move |state, req, next| {
-                permission_guard::require_permission(state, req, next, permissions::USER_LOCK)
+                permission_guard::require_scoped_permission(
+                    state,
+                    req,
+                    next,
+                    permissions::USER_LOCK,
+                )
             },
         ));
 
@@ -285,7 +434,24 @@ fn protected_user_routes(state: AppState) -> Router<AppState> {
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
             move |state, req, next| {
-                permission_guard::require_permission(state, req, next, permissions::USER_BAN)
+                permission_guard::require_scoped_permission(state, req, next, permissions::USER_BAN)
+            },
+        ));
+
+    let impersonate_routes = Router::new()
+        .route(
+            "/{id}/impersonate",
+            post(user_handler::impersonate_user_handler),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            move |state, req, next| {
+                permission_guard::require_scoped_permission(
+                    state,
+                    req,
+                    next,
+                    permissions::USER_IMPERSONATE,
+                )
             },
         ));
 
@@ -309,6 +475,14 @@ fn protected_user_routes(state: AppState) -> Router<AppState> {
             "/{id}/credentials/passkeys/{credential_id}",
             put(user_handler::update_user_passkey_metadata_handler),
         )
+        .route(
+            "/{id}/credentials/totp",
+            delete(user_handler::remove_user_totp_handler),
+        )
+        .route(
+            "/{id}/consents/{client_id}",
+            delete(user_handler::revoke_user_consent_handler),
+        )
         .route(
             "/{id}/credentials/federated/{federated_identity_id}",
             delete(user_handler::unlink_user_federated_identity_handler),
@@ -380,16 +554,23 @@ fn protected_user_routes(state: AppState) -> Router<AppState> {
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
             move |state, req, next| {
-                permission_guard::require_permission(state, req, next, permissions::USER_WRITE)
+                permission_guard::require_scoped_permission(
+                    state,
+                    req,
+                    next,
+                    permissions::USER_WRITE,
+                )
             },
         ));
 
     // Merge them all
     base_routes
         .merge(read_routes)
+        .merge(user_read_routes)
         .merge(delete_routes)
         .merge(lock_routes)
         .merge(ban_routes)
+        .merge(impersonate_routes)
         .merge(write_routes)
 }
 
@@ -439,6 +620,14 @@ fn realm_routes(state: AppState) -> Router<AppState> {
             "/{id}/recovery-settings",
             get(realm_recovery_handler::get_realm_recovery_settings_handler),
         )
+        .route(
+            "/{id}/password-policy",
+            get(realm_password_policy_handler::get_realm_password_policy_handler),
+        )
+        .route(
+            "/{id}/client-registration-policy",
+            get(realm_client_registration_handler::get_client_registration_policy_handler),
+        )
         .route(
             "/{id}/idp-settings",
             get(realm_idp_settings_handler::get_realm_idp_settings_handler),
@@ -455,6 +644,10 @@ fn realm_routes(state: AppState) -> Router<AppState> {
             "/{id}/security-headers",
             get(realm_security_headers_handler::get_realm_security_headers_handler),
         )
+        .route(
+            "/{id}/keys",
+            get(signing_key_handler::list_signing_keys_handler),
+        )
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
             move |state, req, next| {
@@ -485,6 +678,14 @@ fn realm_routes(state: AppState) -> Router<AppState> {
             "/{id}/recovery-settings",
             put(realm_recovery_handler::update_realm_recovery_settings_handler),
         )
+        .route(
+            "/{id}/password-policy",
+            put(realm_password_policy_handler::update_realm_password_policy_handler),
+        )
+        .route(
+            "/{id}/client-registration-policy",
+            put(realm_client_registration_handler::update_client_registration_policy_handler),
+        )
         .route(
             "/{id}/idp-settings",
             put(realm_idp_settings_handler::update_realm_idp_settings_handler),
@@ -505,6 +706,18 @@ fn realm_routes(state: AppState) -> Router<AppState> {
             "/{id}/security-headers",
             put(realm_security_headers_handler::update_realm_security_headers_handler),
         )
+        .route(
+            "/{id}/keys",
+            post(signing_key_handler::generate_signing_key_handler),
+        )
+        .route(
+            "/{id}/keys/rotate",
+            post(signing_key_handler::rotate_signing_key_handler),
+        )
+        .route(
+            "/{id}/keys/{key_id}/retire",
+            post(signing_key_handler::retire_signing_key_handler),
+        )
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
             move |state, req, next| {
@@ -551,16 +764,36 @@ fn realm_routes(state: AppState) -> Router<AppState> {
             },
         ));
 
+    // Initial access tokens let their holder register clients, so managing
+    // them requires client:create.
+    let client_registration_routes = Router::new()
+        .route(
+            "/{id}/initial-access-tokens",
+            get(realm_client_registration_handler::list_initial_access_tokens_handler)
+                .post(realm_client_registration_handler::create_initial_access_token_handler),
+        )
+        .route(
+            "/{id}/initial-access-tokens/{token_id}",
+            delete(realm_client_registration_handler::delete_initial_access_token_handler),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            move |state, req, next| {
+                permission_guard::require_permission(state, req, next, permissions::CLIENT_CREATE)
+            },
+        ));
+
     read_routes
         .merge(write_routes)
         .merge(session_read_routes)
         .merge(session_write_routes)
+        .merge(client_registration_routes)
 }
 
 fn rbac_routes(state: AppState) -> Router<AppState> {
     // All these require RBAC_WRITE, so we can keep them in one block
     // unless you want to separate Read (List Roles) from Write.
-    Router::new()
+    let role_routes = Router::new()
         .route("/roles", post(rbac_handler::create_role_handler))
         .route("/roles", get(rbac_handler::list_roles_handler))
         .route("/roles/stats", get(rbac_handler::get_role_stats_handler))
@@ -611,6 +844,36 @@ fn rbac_routes(state: AppState) -> Router<AppState> {
             "/roles/{id}/delete-summary",
             get(rbac_handler::get_role_delete_summary_handler),
         )
+        .route(
+            "/roles/{id}",
+            delete(rbac_handler::delete_role_handler)
+                .get(rbac_handler::get_role_handler)
+                .put(rbac_handler::update_role_handler),
+        )
+        .route("/permissions", get(rbac_handler::list_permissions_handler))
+        .route(
+            "/permissions/custom",
+            post(rbac_handler::create_custom_permission_handler),
+        )
+        .route(
+            "/permissions/custom/{id}/delete-summary",
+            get(rbac_handler::get_custom_permission_delete_summary_handler),
+        )
+        .route(
+            "/permissions/custom/{id}",
+            put(rbac_handler::update_custom_permission_handler)
+                .delete(rbac_handler::delete_custom_permission_handler),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            move |state, req, next| {
+                permission_guard::require_permission(state, req, next, permissions::RBAC_WRITE)
+            },
+        ));
+
+    // Group routes also admit grants scoped to a group subtree; the handlers
+    // check the groups and users they act on.
+    let group_routes = Router::new()
         .route(
             "/groups",
             post(rbac_handler::create_group_handler).get(rbac_handler::list_groups_handler),
@@ -665,32 +928,19 @@ fn rbac_routes(state: AppState) -> Router<AppState> {
             "/groups/{id}/roles/{role_id}",
             delete(rbac_handler::remove_role_from_group_handler),
         )
-        .route(
-            "/roles/{id}",
-            delete(rbac_handler::delete_role_handler)
-                .get(rbac_handler::get_role_handler)
-                .put(rbac_handler::update_role_handler),
-        )
-        .route("/permissions", get(rbac_handler::list_permissions_handler))
-        .route(
-            "/permissions/custom",
-            post(rbac_handler::create_custom_permission_handler),
-        )
-        .route(
-            "/permissions/custom/{id}/delete-summary",
-            get(rbac_handler::get_custom_permission_delete_summary_handler),
-        )
-        .route(
-            "/permissions/custom/{id}",
-            put(rbac_handler::update_custom_permission_handler)
-                .delete(rbac_handler::delete_custom_permission_handler),
-        )
         .route_layer(middleware::from_fn_with_state(
             state,
             move |state, req, next| {
-                permission_guard::require_permission(state, req, next, permissions::RBAC_WRITE)
+                permission_guard::require_scoped_permission(
+                    state,
+                    req,
+                    next,
+                    permissions::RBAC_WRITE,
+                )
             },
-        ))
+        ));
+
+    role_routes.merge(group_routes)
 }
 
 fn webhook_routes(state: AppState) -> Router<AppState> {
@@ -888,15 +1138,34 @@ fn client_routes(state: AppState) -> Router<AppState> {
     let read_routes = Router::new()
         .route("/", get(oidc_handler::list_clients_handler))
         .route("/stats", get(oidc_handler::get_client_stats_handler))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            move |state, req, next| {
+                permission_guard::require_permission(state, req, next, permissions::CLIENT_READ)
+            },
+        ));
+
+    // Per-client routes also admit grants scoped to one client; the handlers
+    // check the client they act on.
+    let client_read_routes = Router::new()
         .route("/{id}", get(oidc_handler::get_client_handler))
         .route(
             "/{id}/delete-summary",
             get(oidc_handler::get_client_delete_summary_handler),
         )
+        .route(
+            "/{id}/mappers",
+            get(oidc_handler::list_protocol_mappers_handler),
+        )
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
             move |state, req, next| {
-                permission_guard::require_permission(state, req, next, permissions::CLIENT_READ)
+                permission_guard::require_scoped_permission(
+                    state,
+                    req,
+                    next,
+                    permissions::CLIENT_READ,
+                )
             },
         ));
 
@@ -915,15 +1184,82 @@ fn client_routes(state: AppState) -> Router<AppState> {
             "/{id}/rotate-secret",
             post(oidc_handler::rotate_client_secret_handler),
         )
+        .route(
+            "/{id}/mappers",
+            post(oidc_handler::create_protocol_mapper_handler),
+        )
+        .route(
+            "/{id}/mappers/{mapper_id}",
+            put(oidc_handler::update_protocol_mapper_handler)
+                .delete(oidc_handler::delete_protocol_mapper_handler),
+        )
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
             move |state, req, next| {
-                permission_guard::require_permission(state, req, next, permissions::CLIENT_UPDATE)
+                permission_guard::require_scoped_permission(
+                    state,
+                    req,
+                    next,
+                    permissions::CLIENT_UPDATE,
+                )
             },
         ));
 
     let delete_routes = Router::new()
         .route("/{id}", delete(oidc_handler::delete_client_handler))
+        .route_layer(middleware::from_fn_with_state(
+            state,
+            move |state, req, next| {
+                permission_guard::require_scoped_permission(
+                    state,
+                    req,
+                    next,
+                    permissions::CLIENT_DELETE,
+                )
+            },
+        ));
+
+    read_routes
+        .merge(client_read_routes)
+        .merge(create_routes)
+        .merge(update_routes)
+        .merge(delete_routes)
+}
+
+fn saml_service_provider_routes(state: AppState) -> Router<AppState> {
+    let read_routes = Router::new()
+        .route("/", get(saml_handler::list_service_providers_handler))
+        .route("/{id}", get(saml_handler::get_service_provider_handler))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            move |state, req, next| {
+                permission_guard::require_permission(state, req, next, permissions::CLIENT_READ)
+            },
+        ));
+
+    let create_routes = Router::new()
+        .route("/", post(saml_handler::create_service_provider_handler))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            move |state, req, next| {
+                permission_guard::require_permission(state, req, next, permissions::CLIENT_CREATE)
+            },
+        ));
+
+    let update_routes = Router::new()
+        .route("/{id}", put(saml_handler::update_service_provider_handler))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            move |state, req, next| {
+                permission_guard::require_permission(state, req, next, permissions::CLIENT_UPDATE)
+            },
+        ));
+
+    let delete_routes = Router::new()
+        .route(
+            "/{id}",
+            delete(saml_handler::delete_service_provider_handler),
+        )
         .route_layer(middleware::from_fn_with_state(
             state,
             move |state, req, next| {
@@ -980,6 +1316,10 @@ fn harbor_routes(state: AppState) -> Router<AppState> {
             "/import/archive",
             post(harbor_handler::import_harbor_archive_handler),
         )
+        .route(
+            "/reconcile",
+            post(harbor_handler::reconcile_harbor_bundle_handler),
+        )
         .route("/jobs", get(harbor_handler::list_harbor_jobs_handler))
         .route(
             "/jobs/{job_id}/conflicts",
@@ -1016,7 +1356,52 @@ fn oidc_routes() -> Router<AppState> {
             get(oidc_handler::discovery_handler),
         )
         .route("/authorize", get(oidc_handler::authorize_handler))
+        .route(
+            "/par",
+            post(oidc_handler::pushed_authorization_request_handler),
+        )
         .route("/token", post(oidc_handler::token_handler))
+        .route("/register", post(oidc_handler::register_client_handler))
+        .route(
+            "/register/{client_id}",
+            get(oidc_handler::read_client_registration_handler)
+                .put(oidc_handler::update_client_registration_handler)
+                .delete(oidc_handler::delete_client_registration_handler),
+        )
+        .route(
+            "/device_authorization",
+            post(oidc_handler::device_authorization_handler),
+        )
+        .route(
+            "/device",
+            get(oidc_handler::device_verification_page_handler)
+                .post(oidc_handler::device_verification_handler),
+        )
+        .route(
+            "/device/complete",
+            get(oidc_handler::device_verification_complete_handler),
+        )
+        .route("/introspect", post(oidc_handler::introspect_handler))
+        .route("/revoke", post(oidc_handler::revoke_handler))
+        .route(
+            "/logout",
+            get(oidc_handler::end_session_handler).post(oidc_handler::end_session_form_handler),
+        )
         .route("/userinfo", get(oidc_handler::userinfo_handler))
         .route("/.well-known/jwks.json", get(oidc_handler::jwks_handler))
 }
+
+fn saml_routes() -> Router<AppState> {
+    Router::new()
+        .route("/metadata", get(saml_handler::metadata_handler))
+        .route(
+            "/sso",
+            get(saml_handler::sso_redirect_handler).post(saml_handler::sso_post_handler),
+        )
+        .route("/sso/init", get(saml_handler::idp_initiated_handler))
+        .route("/sso/complete", get(saml_handler::sso_complete_handler))
+        .route(
+            "/slo",
+            get(saml_handler::slo_redirect_handler).post(saml_handler::slo_post_handler),
+        )
+}
@@ -0,0 +1,306 @@
+use crate::adapters::web::auth_handler::{create_clear_cookie, create_clear_login_cookie};
+use crate::adapters::web::oidc_handler::{escape_html, login_session_cookie};
+use crate::application::saml_service::{
+    CreateSamlServiceProviderRequest, SamlLogoutOutcome, SamlPostForm,
+    UpdateSamlServiceProviderRequest,
+};
+use crate::constants::REFRESH_TOKEN_COOKIE;
+use crate::domain::auth_session::AuthenticationSession;
+use crate::domain::realm::Realm;
+use crate::domain::saml::SamlBinding;
+use crate::error::{Error, Result};
+use crate::AppState;
+use axum::extract::{Form, Path, Query, RawQuery, State};
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Redirect, Response};
+use axum::Json;
+use axum_extra::extract::cookie::CookieJar;
+use http::{header, HeaderMap, HeaderValue};
+use serde::Deserialize;
+use uuid::Uuid;
+
+/// `SAMLRequest` and `RelayState`, from the query (HTTP-Redirect) or the
+/// form body (HTTP-POST).
+#[derive(Debug, Deserialize)]
+pub struct SamlRequestParams {
+    #[serde(rename = "SAMLRequest")]
+    pub saml_request: Option<String>,
+    #[serde(rename = "RelayState")]
+    pub relay_state: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdpInitiatedParams {
+    /// Entity ID of the service provider to sign in to.
+    pub sp: String,
+    #[serde(rename = "RelayState")]
+    pub relay_state: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompleteParams {
+    pub session: Uuid,
+}
+
+async fn find_realm(state: &AppState, realm_name: &str) -> Result<Realm> {
+    state
+        .realm_service
+        .find_by_name(realm_name)
+        .await?
+        .ok_or_else(|| Error::RealmNotFound(realm_name.to_string()))
+}
+
+fn saml_request(params: &SamlRequestParams) -> Result<&str> {
+    params
+        .saml_request
+        .as_deref()
+        .ok_or_else(|| Error::SamlInvalidRequest("SAMLRequest is required".to_string()))
+}
+
+/// GET /api/realms/{realm}/saml/metadata
+pub async fn metadata_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+) -> Result<Response> {
+    let realm = find_realm(&state, &realm_name).await?;
+    let metadata = state.saml_service.metadata(&realm).await?;
+    Ok((
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "application/samlmetadata+xml")],
+        metadata,
+    )
+        .into_response())
+}
+
+/// GET /api/realms/{realm}/saml/sso (HTTP-Redirect binding)
+pub async fn sso_redirect_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+    RawQuery(raw_query): RawQuery,
+    Query(params): Query<SamlRequestParams>,
+) -> Result<Response> {
+    start_sp_initiated(
+        &state,
+        &realm_name,
+        SamlBinding::Redirect,
+        params,
+        raw_query.as_deref(),
+    )
+    .await
+}
+
+/// POST /api/realms/{realm}/saml/sso (HTTP-POST binding)
+pub async fn sso_post_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+    Form(params): Form<SamlRequestParams>,
+) -> Result<Response> {
+    start_sp_initiated(&state, &realm_name, SamlBinding::Post, params, None).await
+}
+
+async fn start_sp_initiated(
+    state: &AppState,
+    realm_name: &str,
+    binding: SamlBinding,
+    params: SamlRequestParams,
+    raw_query: Option<&str>,
+) -> Result<Response> {
+    let realm = find_realm(state, realm_name).await?;
+    let session = state
+        .saml_service
+        .start_sp_initiated_login(
+            &realm,
+            binding,
+            saml_request(&params)?,
+            params.relay_state.clone(),
+            raw_query,
+        )
+        .await?;
+    redirect_to_login(realm_name, &session)
+}
+
+/// GET /api/realms/{realm}/saml/sso/init?sp={entity_id}
+/// Starts an IdP-initiated login to a service provider that allows it.
+pub async fn idp_initiated_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+    Query(params): Query<IdpInitiatedParams>,
+) -> Result<Response> {
+    let realm = find_realm(&state, &realm_name).await?;
+    let session = state
+        .saml_service
+        .start_idp_initiated_login(&realm, &params.sp, params.relay_state)
+        .await?;
+    redirect_to_login(&realm_name, &session)
+}
+
+/// Sends the browser to the login UI with the flow's session cookie, as
+/// `/authorize` does.
+fn redirect_to_login(realm_name: &str, session: &AuthenticationSession) -> Result<Response> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::SET_COOKIE,
+        HeaderValue::from_str(&login_session_cookie(session).to_string())
+            .map_err(|e| Error::Unexpected(e.into()))?,
+    );
+    let frontend_login_url = format!("/#/login?realm={}", realm_name);
+    Ok((headers, Redirect::to(&frontend_login_url)).into_response())
+}
+
+/// GET /api/realms/{realm}/saml/sso/complete?session={id}
+/// Where a finished SAML login lands: posts the signed response to the
+/// service provider.
+pub async fn sso_complete_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+    Query(params): Query<CompleteParams>,
+) -> Result<Response> {
+    let realm = find_realm(&state, &realm_name).await?;
+    let form = state
+        .saml_service
+        .take_response(realm.id, params.session)
+        .await?;
+    Ok((StatusCode::OK, Html(render_post_form(&form))).into_response())
+}
+
+/// GET /api/realms/{realm}/saml/slo (HTTP-Redirect binding)
+pub async fn slo_redirect_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+    jar: CookieJar,
+    RawQuery(raw_query): RawQuery,
+    Query(params): Query<SamlRequestParams>,
+) -> Result<Response> {
+    single_logout(
+        &state,
+        &realm_name,
+        &jar,
+        SamlBinding::Redirect,
+        params,
+        raw_query.as_deref(),
+    )
+    .await
+}
+
+/// POST /api/realms/{realm}/saml/slo (HTTP-POST binding)
+pub async fn slo_post_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+    jar: CookieJar,
+    Form(params): Form<SamlRequestParams>,
+) -> Result<Response> {
+    single_logout(&state, &realm_name, &jar, SamlBinding::Post, params, None).await
+}
+
+/// Ends the sessions the service provider names, clears the browser's
+/// session cookies and answers on the provider's SLO endpoint.
+async fn single_logout(
+    state: &AppState,
+    realm_name: &str,
+    jar: &CookieJar,
+    binding: SamlBinding,
+    params: SamlRequestParams,
+    raw_query: Option<&str>,
+) -> Result<Response> {
+    let realm = find_realm(state, realm_name).await?;
+    let browser_session = jar
+        .get(REFRESH_TOKEN_COOKIE)
+        .and_then(|cookie| Uuid::parse_str(cookie.value()).ok());
+    let outcome = state
+        .saml_service
+        .logout(
+            &realm,
+            binding,
+            saml_request(&params)?,
+            params.relay_state.clone(),
+            raw_query,
+            browser_session,
+        )
+        .await?;
+
+    let mut headers = HeaderMap::new();
+    headers.append(
+        header::SET_COOKIE,
+        HeaderValue::from_str(&create_clear_cookie().to_string())?,
+    );
+    headers.append(
+        header::SET_COOKIE,
+        HeaderValue::from_str(&create_clear_login_cookie().to_string())?,
+    );
+    match outcome {
+        SamlLogoutOutcome::Redirect(url) => Ok((headers, Redirect::to(&url)).into_response()),
+        SamlLogoutOutcome::Post(form) => {
+            Ok((StatusCode::OK, headers, Html(render_post_form(&form))).into_response())
+        }
+    }
+}
+
+/// The HTTP-POST binding: a form that submits itself, with a button for
+/// browsers that do not run script.
+fn render_post_form(form: &SamlPostForm) -> String {
+    let relay_state = form
+        .relay_state
+        .as_deref()
+        .map(|value| {
+            format!(
+                r#"<input type="hidden" name="RelayState" value="{}">"#,
+                escape_html(value)
+            )
+        })
+        .unwrap_or_default();
+    format!(
+        r#"<!DOCTYPE html><html><head><meta charset="utf-8"><title>Signing in</title></head><body onload="document.forms[0].submit()"><form method="post" action="{}"><input type="hidden" name="SAMLResponse" value="{}">{}<noscript><p>Your browser does not run scripts. Select Continue to proceed.</p></noscript><button type="submit">Continue</button></form></body></html>"#,
+        escape_html(&form.action),
+        escape_html(&form.saml_response),
+        relay_state
+    )
+}
+
+// --- Admin: service provider registrations ---
+
+pub async fn list_service_providers_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+) -> Result<impl IntoResponse> {
+    let realm = find_realm(&state, &realm_name).await?;
+    let providers = state.saml_service.list(realm.id).await?;
+    Ok((StatusCode::OK, Json(providers)))
+}
+
+pub async fn create_service_provider_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+    Json(payload): Json<CreateSamlServiceProviderRequest>,
+) -> Result<impl IntoResponse> {
+    let realm = find_realm(&state, &realm_name).await?;
+    let provider = state.saml_service.create(realm.id, payload).await?;
+    Ok((StatusCode::CREATED, Json(provider)))
+}
+
+pub async fn get_service_provider_handler(
+    State(state): State<AppState>,
+    Path((realm_name, id)): Path<(String, Uuid)>,
+) -> Result<impl IntoResponse> {
+    let realm = find_realm(&state, &realm_name).await?;
+    let provider = state.saml_service.get(realm.id, id).await?;
+    Ok((StatusCode::OK, Json(provider)))
+}
+
+pub async fn update_service_provider_handler(
+    State(state): State<AppState>,
+    Path((realm_name, id)): Path<(String, Uuid)>,
+    Json(payload): Json<UpdateSamlServiceProviderRequest>,
+) -> Result<impl IntoResponse> {
+    let realm = find_realm(&state, &realm_name).await?;
+    let provider = state.saml_service.update(realm.id, id, payload).await?;
+    Ok((StatusCode::OK, Json(provider)))
+}
+
+pub async fn delete_service_provider_handler(
+    State(state): State<AppState>,
+    Path((realm_name, id)): Path<(String, Uuid)>,
+) -> Result<impl IntoResponse> {
+    let realm = find_realm(&state, &realm_name).await?;
+    state.saml_service.delete(realm.id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
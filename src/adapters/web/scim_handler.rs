@@ -0,0 +1,425 @@
+use std::collections::HashMap;
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{Path, Query, State},
+    http::{header, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::application::scim_service::{ScimCaller, ScimPage};
+use crate::domain::scim::{self, PatchRequest, ScimFilter};
+use crate::error::Error;
+use crate::AppState;
+
+pub const SCIM_CONTENT_TYPE: &str = "application/scim+json";
+
+/// An error in the SCIM error format (RFC 7644, Section 3.12).
+pub struct ScimError {
+    error: Error,
+    scim_type: Option<&'static str>,
+}
+
+impl ScimError {
+    fn typed(error: Error, scim_type: &'static str) -> Self {
+        Self {
+            error,
+            scim_type: Some(scim_type),
+        }
+    }
+}
+
+impl From<Error> for ScimError {
+    fn from(error: Error) -> Self {
+        Self {
+            error,
+            scim_type: None,
+        }
+    }
+}
+
+impl IntoResponse for ScimError {
+    fn into_response(self) -> Response {
+        let detail = self.error.to_string();
+        let status = self.error.into_response().status();
+        let detail = if status.is_server_error() {
+            "Internal server error".to_string()
+        } else {
+            detail
+        };
+        let scim_type = self.scim_type.or(match status {
+            StatusCode::CONFLICT => Some("uniqueness"),
+            StatusCode::BAD_REQUEST => Some("invalidValue"),
+            _ => None,
+        });
+        let mut response = scim_json(
+            status,
+            scim::error_body(status.as_u16(), scim_type, &detail),
+        );
+        if status == StatusCode::UNAUTHORIZED {
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+        }
+        response
+    }
+}
+
+type ScimResult = std::result::Result<Response, ScimError>;
+
+/// Authenticates SCIM requests with a client_credentials access token issued
+/// in the realm named by the path.
+pub async fn scim_auth_guard(
+    State(state): State<AppState>,
+    Path(params): Path<HashMap<String, String>>,
+    mut req: Request<Body>,
+    next: Next,
+) -> Response {
+    let realm_name = params.get("realm").cloned().unwrap_or_default();
+    let realm = match state.realm_service.find_by_name(&realm_name).await {
+        Ok(Some(realm)) => realm,
+        Ok(None) => return ScimError::from(Error::RealmNotFound(realm_name)).into_response(),
+        Err(err) => return ScimError::from(err).into_response(),
+    };
+    let token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .map(str::to_string);
+    let Some(token) = token else {
+        return ScimError::from(Error::OidcInvalidToken(
+            "An access token is required".to_string(),
+        ))
+        .into_response();
+    };
+
+    match state.scim_service.authenticate(realm.id, &token).await {
+        Ok(caller) => {
+            req.extensions_mut().insert(caller);
+            next.run(req).await
+        }
+        Err(err) => ScimError::from(err).into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimListQuery {
+    pub filter: Option<String>,
+    pub start_index: Option<i64>,
+    pub count: Option<i64>,
+}
+
+impl ScimListQuery {
+    fn filter(&self) -> std::result::Result<Option<ScimFilter>, ScimError> {
+        self.filter
+            .as_deref()
+            .filter(|filter| !filter.trim().is_empty())
+            .map(ScimFilter::parse)
+            .transpose()
+            .map_err(|err| ScimError::typed(err, "invalidFilter"))
+    }
+
+    fn page(&self) -> ScimPage {
+        ScimPage::new(self.start_index, self.count)
+    }
+}
+
+fn scim_json(status: StatusCode, body: Value) -> Response {
+    (
+        status,
+        [(header::CONTENT_TYPE, SCIM_CONTENT_TYPE)],
+        body.to_string(),
+    )
+        .into_response()
+}
+
+fn created(body: Value) -> Response {
+    let location = body["meta"]["location"].as_str().map(str::to_string);
+    let mut response = scim_json(StatusCode::CREATED, body);
+    if let Some(location) = location.and_then(|location| HeaderValue::from_str(&location).ok()) {
+        response.headers_mut().insert(header::LOCATION, location);
+    }
+    response
+}
+
+/// SCIM clients send `application/scim+json`, which the `Json` extractor
+/// refuses.
+fn parse_body<T: DeserializeOwned>(body: &Bytes) -> std::result::Result<T, ScimError> {
+    serde_json::from_slice(body).map_err(|err| {
+        ScimError::typed(
+            Error::Validation(format!("Invalid request body: {}", err)),
+            "invalidSyntax",
+        )
+    })
+}
+
+fn resource_id(raw: &str) -> std::result::Result<Uuid, ScimError> {
+    Uuid::parse_str(raw).map_err(|_| Error::NotFound("Resource not found".to_string()).into())
+}
+
+async fn base_url(state: &AppState, realm_name: &str) -> String {
+    let public_url = state.settings.read().await.server.public_url.clone();
+    format!(
+        "{}/api/realms/{}/scim/v2",
+        public_url.trim_end_matches('/'),
+        realm_name
+    )
+}
+
+// --- Discovery ---
+
+pub async fn service_provider_config_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+) -> Response {
+    let base = base_url(&state, &realm_name).await;
+    scim_json(StatusCode::OK, scim::service_provider_config(&base))
+}
+
+pub async fn resource_types_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+) -> Response {
+    let base = base_url(&state, &realm_name).await;
+    let resource_types = scim::resource_types(&base);
+    scim_json(
+        StatusCode::OK,
+        scim::list_response(resource_types.len(), 1, resource_types),
+    )
+}
+
+pub async fn resource_type_handler(
+    State(state): State<AppState>,
+    Path((realm_name, name)): Path<(String, String)>,
+) -> ScimResult {
+    let base = base_url(&state, &realm_name).await;
+    let resource_type = scim::resource_types(&base)
+        .into_iter()
+        .find(|resource_type| resource_type["id"] == name.as_str())
+        .ok_or_else(|| Error::NotFound(format!("Resource type '{}' not found", name)))?;
+    Ok(scim_json(StatusCode::OK, resource_type))
+}
+
+pub async fn schemas_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+) -> Response {
+    let base = base_url(&state, &realm_name).await;
+    let schemas = scim::schemas(&base);
+    scim_json(
+        StatusCode::OK,
+        scim::list_response(schemas.len(), 1, schemas),
+    )
+}
+
+pub async fn schema_handler(
+    State(state): State<AppState>,
+    Path((realm_name, id)): Path<(String, String)>,
+) -> ScimResult {
+    let base = base_url(&state, &realm_name).await;
+    let schema = scim::schemas(&base)
+        .into_iter()
+        .find(|schema| schema["id"] == id.as_str())
+        .ok_or_else(|| Error::NotFound(format!("Schema '{}' not found", id)))?;
+    Ok(scim_json(StatusCode::OK, schema))
+}
+
+// --- Users ---
+
+pub async fn list_users_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+    Extension(caller): Extension<ScimCaller>,
+    Query(query): Query<ScimListQuery>,
+) -> ScimResult {
+    caller.require(scim::SCOPE_USERS_READ)?;
+    let filter = query.filter()?;
+    let base = base_url(&state, &realm_name).await;
+    let users = state
+        .scim_service
+        .list_users(caller.realm_id, filter.as_ref(), query.page(), &base)
+        .await?;
+    Ok(scim_json(StatusCode::OK, users))
+}
+
+pub async fn get_user_handler(
+    State(state): State<AppState>,
+    Path((realm_name, id)): Path<(String, String)>,
+    Extension(caller): Extension<ScimCaller>,
+) -> ScimResult {
+    caller.require(scim::SCOPE_USERS_READ)?;
+    let base = base_url(&state, &realm_name).await;
+    let user = state
+        .scim_service
+        .get_user(caller.realm_id, resource_id(&id)?, &base)
+        .await?;
+    Ok(scim_json(StatusCode::OK, user))
+}
+
+pub async fn create_user_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+    Extension(caller): Extension<ScimCaller>,
+    body: Bytes,
+) -> ScimResult {
+    caller.require(scim::SCOPE_USERS_WRITE)?;
+    let resource: Value = parse_body(&body)?;
+    let base = base_url(&state, &realm_name).await;
+    let user = state
+        .scim_service
+        .create_user(caller.realm_id, &resource, &base)
+        .await?;
+    Ok(created(user))
+}
+
+pub async fn replace_user_handler(
+    State(state): State<AppState>,
+    Path((realm_name, id)): Path<(String, String)>,
+    Extension(caller): Extension<ScimCaller>,
+    body: Bytes,
+) -> ScimResult {
+    caller.require(scim::SCOPE_USERS_WRITE)?;
+    let user_id = resource_id(&id)?;
+    let resource: Value = parse_body(&body)?;
+    let base = base_url(&state, &realm_name).await;
+    let user = state
+        .scim_service
+        .replace_user(caller.realm_id, user_id, &resource, &base)
+        .await?;
+    Ok(scim_json(StatusCode::OK, user))
+}
+
+pub async fn patch_user_handler(
+    State(state): State<AppState>,
+    Path((realm_name, id)): Path<(String, String)>,
+    Extension(caller): Extension<ScimCaller>,
+    body: Bytes,
+) -> ScimResult {
+    caller.require(scim::SCOPE_USERS_WRITE)?;
+    let user_id = resource_id(&id)?;
+    let patch: PatchRequest = parse_body(&body)?;
+    let base = base_url(&state, &realm_name).await;
+    let user = state
+        .scim_service
+        .patch_user(caller.realm_id, user_id, &patch, &base)
+        .await?;
+    Ok(scim_json(StatusCode::OK, user))
+}
+
+pub async fn delete_user_handler(
+    State(state): State<AppState>,
+    Path((_realm_name, id)): Path<(String, String)>,
+    Extension(caller): Extension<ScimCaller>,
+) -> ScimResult {
+    caller.require(scim::SCOPE_USERS_WRITE)?;
+    state
+        .scim_service
+        .delete_user(caller.realm_id, resource_id(&id)?)
+        .await?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+// --- Groups ---
+
+pub async fn list_groups_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+    Extension(caller): Extension<ScimCaller>,
+    Query(query): Query<ScimListQuery>,
+) -> ScimResult {
+    caller.require(scim::SCOPE_GROUPS_READ)?;
+    let filter = query.filter()?;
+    let base = base_url(&state, &realm_name).await;
+    let groups = state
+        .scim_service
+        .list_groups(caller.realm_id, filter.as_ref(), query.page(), &base)
+        .await?;
+    Ok(scim_json(StatusCode::OK, groups))
+}
+
+pub async fn get_group_handler(
+    State(state): State<AppState>,
+    Path((realm_name, id)): Path<(String, String)>,
+    Extension(caller): Extension<ScimCaller>,
+) -> ScimResult {
+    caller.require(scim::SCOPE_GROUPS_READ)?;
+    let base = base_url(&state, &realm_name).await;
+    let group = state
+        .scim_service
+        .get_group(caller.realm_id, resource_id(&id)?, &base)
+        .await?;
+    Ok(scim_json(StatusCode::OK, group))
+}
+
+pub async fn create_group_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+    Extension(caller): Extension<ScimCaller>,
+    body: Bytes,
+) -> ScimResult {
+    caller.require(scim::SCOPE_GROUPS_WRITE)?;
+    let resource: Value = parse_body(&body)?;
+    let base = base_url(&state, &realm_name).await;
+    let group = state
+        .scim_service
+        .create_group(caller.realm_id, &resource, &base)
+        .await?;
+    Ok(created(group))
+}
+
+pub async fn replace_group_handler(
+    State(state): State<AppState>,
+    Path((realm_name, id)): Path<(String, String)>,
+    Extension(caller): Extension<ScimCaller>,
+    body: Bytes,
+) -> ScimResult {
+    caller.require(scim::SCOPE_GROUPS_WRITE)?;
+    let group_id = resource_id(&id)?;
+    let resource: Value = parse_body(&body)?;
+    let base = base_url(&state, &realm_name).await;
+    let group = state
+        .scim_service
+        .replace_group(caller.realm_id, group_id, &resource, &base)
+        .await?;
+    Ok(scim_json(StatusCode::OK, group))
+}
+
+pub async fn patch_group_handler(
+    State(state): State<AppState>,
+    Path((realm_name, id)): Path<(String, String)>,
+    Extension(caller): Extension<ScimCaller>,
+    body: Bytes,
+) -> ScimResult {
+    caller.require(scim::SCOPE_GROUPS_WRITE)?;
+    let group_id = resource_id(&id)?;
+    let patch: PatchRequest = parse_body(&body)?;
+    let base = base_url(&state, &realm_name).await;
+    let group = state
+        .scim_service
+        .patch_group(caller.realm_id, group_id, &patch, &base)
+        .await?;
+    Ok(scim_json(StatusCode::OK, group))
+}
+
+pub async fn delete_group_handler(
+    State(state): State<AppState>,
+    Path((_realm_name, id)): Path<(String, String)>,
+    Extension(caller): Extension<ScimCaller>,
+) -> ScimResult {
+    caller.require(scim::SCOPE_GROUPS_WRITE)?;
+    state
+        .scim_service
+        .delete_group(caller.realm_id, resource_id(&id)?)
+        .await?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
@@ -0,0 +1,112 @@
+use crate::domain::signing_key::{SigningAlgorithm, SigningKey, SigningKeyState};
+use crate::error::{Error, Result};
+use crate::AppState;
+use axum::extract::{Path, State};
+use axum::{http::StatusCode, response::IntoResponse, Json};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A realm signing key as shown to admins. The private key never leaves the
+/// server.
+#[derive(Serialize)]
+pub struct SigningKeyResponse {
+    pub id: Uuid,
+    pub realm_id: Uuid,
+    pub kid: String,
+    pub algorithm: SigningAlgorithm,
+    pub state: SigningKeyState,
+    pub public_key_pem: String,
+    pub created_at: DateTime<Utc>,
+    pub activated_at: Option<DateTime<Utc>>,
+    pub deactivated_at: Option<DateTime<Utc>>,
+    pub retired_at: Option<DateTime<Utc>>,
+}
+
+impl From<SigningKey> for SigningKeyResponse {
+    fn from(key: SigningKey) -> Self {
+        Self {
+            id: key.id,
+            realm_id: key.realm_id,
+            kid: key.kid,
+            algorithm: key.algorithm,
+            state: key.state,
+            public_key_pem: key.public_key_pem,
+            created_at: key.created_at,
+            activated_at: key.activated_at,
+            deactivated_at: key.deactivated_at,
+            retired_at: key.retired_at,
+        }
+    }
+}
+
+/// Selects the key family to act on; defaults to the realm's algorithm.
+#[derive(Deserialize)]
+pub struct SigningKeyRequest {
+    pub algorithm: Option<SigningAlgorithm>,
+}
+
+async fn ensure_realm(state: &AppState, id: Uuid) -> Result<()> {
+    state
+        .realm_service
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| Error::RealmNotFound(id.to_string()))?;
+    Ok(())
+}
+
+async fn requested_algorithm(
+    state: &AppState,
+    id: Uuid,
+    payload: Option<Json<SigningKeyRequest>>,
+) -> Result<SigningAlgorithm> {
+    match payload.and_then(|Json(payload)| payload.algorithm) {
+        Some(algorithm) => Ok(algorithm),
+        None => state.signing_key_service.signing_algorithm(id, None).await,
+    }
+}
+
+pub async fn list_signing_keys_handler(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    ensure_realm(&state, id).await?;
+    let keys = state.signing_key_service.list_keys(id).await?;
+    let response: Vec<SigningKeyResponse> = keys.into_iter().map(Into::into).collect();
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Generates a passive key that is published in the JWKS until the next
+/// rotation makes it active.
+pub async fn generate_signing_key_handler(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    payload: Option<Json<SigningKeyRequest>>,
+) -> Result<impl IntoResponse> {
+    ensure_realm(&state, id).await?;
+    let algorithm = requested_algorithm(&state, id, payload).await?;
+    let key = state
+        .signing_key_service
+        .generate_key(id, algorithm)
+        .await?;
+    Ok((StatusCode::CREATED, Json(SigningKeyResponse::from(key))))
+}
+
+pub async fn rotate_signing_key_handler(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    payload: Option<Json<SigningKeyRequest>>,
+) -> Result<impl IntoResponse> {
+    ensure_realm(&state, id).await?;
+    let algorithm = requested_algorithm(&state, id, payload).await?;
+    let key = state.signing_key_service.rotate(id, algorithm).await?;
+    Ok((StatusCode::OK, Json(SigningKeyResponse::from(key))))
+}
+
+pub async fn retire_signing_key_handler(
+    State(state): State<AppState>,
+    Path((id, key_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse> {
+    let key = state.signing_key_service.retire_key(id, key_id).await?;
+    Ok((StatusCode::OK, Json(SigningKeyResponse::from(key))))
+}
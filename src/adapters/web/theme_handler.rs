@@ -998,11 +998,14 @@ fn default_template_key(node_type: &str) -> Option<&'static str> {
         "core.auth.password" => Some("login"),
         "core.auth.passkey_assert" => Some("passkey_assert"),
         "core.auth.passkey_enroll" => Some("passkey_enroll"),
+        "core.auth.totp_enroll" => Some("totp_enroll"),
+        "core.auth.totp_verify" => Some("mfa"),
         "core.auth.register" => Some("register"),
         "core.auth.forgot_credentials" => Some("forgot_credentials"),
         "core.auth.reset_password" => Some("reset_password"),
         "core.logic.recovery_issue" => Some("awaiting_action"),
         "core.logic.issue_email_otp" => Some("awaiting_action"),
+        "core.auth.verify_sms_otp" => Some("sms_otp"),
         "core.oidc.consent" => Some("consent"),
         _ => None,
     }
@@ -0,0 +1,131 @@
+use crate::application::user_federation_service::{
+    CreateUserFederationMapperRequest, CreateUserFederationProviderRequest,
+    UpdateUserFederationProviderRequest,
+};
+use crate::domain::realm::Realm;
+use crate::error::{Error, Result};
+use crate::AppState;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use uuid::Uuid;
+
+async fn find_realm(state: &AppState, realm_name: &str) -> Result<Realm> {
+    state
+        .realm_service
+        .find_by_name(realm_name)
+        .await?
+        .ok_or_else(|| Error::RealmNotFound(realm_name.to_string()))
+}
+
+pub async fn list_providers_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+) -> Result<impl IntoResponse> {
+    let realm = find_realm(&state, &realm_name).await?;
+    let providers = state.user_federation_service.list(realm.id).await?;
+    Ok((StatusCode::OK, Json(providers)))
+}
+
+pub async fn create_provider_handler(
+    State(state): State<AppState>,
+    Path(realm_name): Path<String>,
+    Json(payload): Json<CreateUserFederationProviderRequest>,
+) -> Result<impl IntoResponse> {
+    let realm = find_realm(&state, &realm_name).await?;
+    let provider = state
+        .user_federation_service
+        .create(realm.id, payload)
+        .await?;
+    Ok((StatusCode::CREATED, Json(provider)))
+}
+
+pub async fn get_provider_handler(
+    State(state): State<AppState>,
+    Path((realm_name, id)): Path<(String, Uuid)>,
+) -> Result<impl IntoResponse> {
+    let realm = find_realm(&state, &realm_name).await?;
+    let provider = state.user_federation_service.get(realm.id, id).await?;
+    Ok((StatusCode::OK, Json(provider)))
+}
+
+pub async fn update_provider_handler(
+    State(state): State<AppState>,
+    Path((realm_name, id)): Path<(String, Uuid)>,
+    Json(payload): Json<UpdateUserFederationProviderRequest>,
+) -> Result<impl IntoResponse> {
+    let realm = find_realm(&state, &realm_name).await?;
+    let provider = state
+        .user_federation_service
+        .update(realm.id, id, payload)
+        .await?;
+    Ok((StatusCode::OK, Json(provider)))
+}
+
+pub async fn delete_provider_handler(
+    State(state): State<AppState>,
+    Path((realm_name, id)): Path<(String, Uuid)>,
+) -> Result<impl IntoResponse> {
+    let realm = find_realm(&state, &realm_name).await?;
+    state.user_federation_service.delete(realm.id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn test_connection_handler(
+    State(state): State<AppState>,
+    Path((realm_name, id)): Path<(String, Uuid)>,
+) -> Result<impl IntoResponse> {
+    let realm = find_realm(&state, &realm_name).await?;
+    let result = state
+        .user_federation_service
+        .test_connection(realm.id, id)
+        .await?;
+    Ok((StatusCode::OK, Json(result)))
+}
+
+pub async fn sync_provider_handler(
+    State(state): State<AppState>,
+    Path((realm_name, id)): Path<(String, Uuid)>,
+) -> Result<impl IntoResponse> {
+    let realm = find_realm(&state, &realm_name).await?;
+    let result = state.user_federation_service.sync(realm.id, id).await?;
+    Ok((StatusCode::OK, Json(result)))
+}
+
+pub async fn list_mappers_handler(
+    State(state): State<AppState>,
+    Path((realm_name, id)): Path<(String, Uuid)>,
+) -> Result<impl IntoResponse> {
+    let realm = find_realm(&state, &realm_name).await?;
+    let mappers = state
+        .user_federation_service
+        .list_mappers(realm.id, id)
+        .await?;
+    Ok((StatusCode::OK, Json(mappers)))
+}
+
+pub async fn create_mapper_handler(
+    State(state): State<AppState>,
+    Path((realm_name, id)): Path<(String, Uuid)>,
+    Json(payload): Json<CreateUserFederationMapperRequest>,
+) -> Result<impl IntoResponse> {
+    let realm = find_realm(&state, &realm_name).await?;
+    let mapper = state
+        .user_federation_service
+        .create_mapper(realm.id, id, payload)
+        .await?;
+    Ok((StatusCode::CREATED, Json(mapper)))
+}
+
+pub async fn delete_mapper_handler(
+    State(state): State<AppState>,
+    Path((realm_name, id, mapper_id)): Path<(String, Uuid, Uuid)>,
+) -> Result<impl IntoResponse> {
+    let realm = find_realm(&state, &realm_name).await?;
+    state
+        .user_federation_service
+        .delete_mapper(realm.id, id, mapper_id)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
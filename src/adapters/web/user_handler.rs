@@ -1,21 +1,26 @@
-use crate::adapters::web::auth_middleware::AuthUser;
+use crate::adapters::web::auth_middleware::{AuthUser, Impersonator};
+use crate::adapters::web::middleware::permission_guard;
 use crate::adapters::web::validation::ValidatedJson;
 use crate::application::user_credentials_service::UserCredentialsSummary;
 use crate::application::user_service::{
     admin_metadata_response, UserMetadataVisibility, UserStats,
 };
+use crate::domain::audit::NewAuditEvent;
 use crate::domain::pagination::PageRequest;
+use crate::domain::password_policy::PASSWORD_LENGTH_LIMIT;
+use crate::domain::permissions::{self, AdminResource};
 use crate::domain::user::{User, UserDateTimeRangeFilter, UserListFilters};
 use crate::domain::user_email::UserEmail;
 use crate::domain::user_phone_number::UserPhoneNumber;
 use crate::error::{Error, Result};
 use crate::AppState;
 use axum::extract::{Path, Query};
+use axum::http::{header, HeaderMap};
 use axum::{extract::State, http::StatusCode, response::IntoResponse, Extension, Json};
 use chrono::{DateTime, Days, NaiveDate, TimeZone, Utc};
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
-use tracing::warn;
+use tracing::{error, warn};
 use uuid::Uuid;
 use validator::Validate;
 
@@ -117,6 +122,26 @@ async fn user_response_from_user(state: &AppState, user: User) -> Result<UserRes
     Ok(UserResponse::new_admin(user, emails, phone_numbers))
 }
 
+/// Bounds applied when an admin bypasses the realm's password policy.
+fn check_unpoliced_password(password: &str) -> Result<()> {
+    let length = password.chars().count() as i64;
+    if length > 0 && length <= PASSWORD_LENGTH_LIMIT {
+        return Ok(());
+    }
+    let mut fields = std::collections::HashMap::new();
+    fields.insert(
+        "password".to_string(),
+        format!(
+            "Password is required and must be no more than {} characters",
+            PASSWORD_LENGTH_LIMIT
+        ),
+    );
+    Err(Error::FieldsValidation {
+        message: "Validation failed".to_string(),
+        fields,
+    })
+}
+
 // ---------------------------------------------------------------------------
 // Create user
 // ---------------------------------------------------------------------------
@@ -127,7 +152,6 @@ pub struct CreateUserPayload {
     username: String,
     #[validate(email(message = "Email address is invalid"))]
     email: Option<String>,
-    #[validate(length(max = 100, message = "Password must be between 8 and 100 characters"))]
     password: String,
     ignore_password_policies: Option<bool>,
 }
@@ -144,16 +168,8 @@ pub async fn create_user_handler(
     }
 
     let ignore_policies = payload.ignore_password_policies.unwrap_or(false);
-    if !ignore_policies && payload.password.len() < 8 {
-        let mut fields = std::collections::HashMap::new();
-        fields.insert(
-            "password".to_string(),
-            "Password must be between 8 and 100 characters".to_string(),
-        );
-        return Err(Error::FieldsValidation {
-            message: "Validation failed".to_string(),
-            fields,
-        });
+    if ignore_policies {
+        check_unpoliced_password(&payload.password)?;
     }
 
     let realm = state
@@ -298,6 +314,32 @@ pub async fn update_me_unsafe_metadata_handler(
     Ok((StatusCode::OK, Json(metadata)))
 }
 
+pub async fn list_me_consents_handler(
+    Extension(AuthUser(user)): Extension<AuthUser>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse> {
+    let grants = state
+        .consent_service
+        .list_grants(user.realm_id, user.id)
+        .await?;
+    Ok((StatusCode::OK, Json(grants)))
+}
+
+pub async fn revoke_me_consent_handler(
+    Extension(AuthUser(user)): Extension<AuthUser>,
+    State(state): State<AppState>,
+    Path((_realm_name, client_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse> {
+    state
+        .consent_service
+        .revoke_grant(user.realm_id, Some(user.id), user.id, &client_id)
+        .await?;
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({ "status": "revoked" })),
+    ))
+}
+
 // ---------------------------------------------------------------------------
 // List users
 // ---------------------------------------------------------------------------
@@ -433,12 +475,31 @@ fn parse_rfc3339(value: &str) -> Option<DateTime<Utc>> {
         .map(|datetime| datetime.with_timezone(&Utc))
 }
 
+/// Checks the caller may use `permission` on this user. Admins whose grant is
+/// scoped to a group subtree may only manage that subtree's members.
+async fn authorize_user(
+    state: &AppState,
+    actor: &User,
+    realm_id: Uuid,
+    user_id: Uuid,
+    permission: &str,
+) -> Result<()> {
+    permission_guard::authorize(
+        state,
+        &actor.id,
+        permission,
+        AdminResource::User { realm_id, user_id },
+    )
+    .await
+}
+
 // ---------------------------------------------------------------------------
 // Get single user
 // ---------------------------------------------------------------------------
 
 pub async fn get_user_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, id)): Path<(String, Uuid)>,
 ) -> Result<impl IntoResponse> {
     let realm = state
@@ -447,6 +508,8 @@ pub async fn get_user_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize_user(&state, &actor, realm.id, id, permissions::USER_WRITE).await?;
+
     let user = state.user_service.get_user_in_realm(realm.id, id).await?;
     Ok((
         StatusCode::OK,
@@ -456,6 +519,7 @@ pub async fn get_user_handler(
 
 pub async fn get_user_metadata_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, id)): Path<(String, Uuid)>,
 ) -> Result<impl IntoResponse> {
     let realm = state
@@ -464,6 +528,8 @@ pub async fn get_user_metadata_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize_user(&state, &actor, realm.id, id, permissions::USER_READ).await?;
+
     let metadata = state
         .user_service
         .get_admin_metadata(realm.id, id, true)
@@ -473,11 +539,13 @@ pub async fn get_user_metadata_handler(
 
 pub async fn update_user_public_metadata_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, id)): Path<(String, Uuid)>,
     Json(payload): Json<UpdateUserMetadataPayload>,
 ) -> Result<impl IntoResponse> {
     update_user_metadata(
         state,
+        actor,
         realm_name,
         id,
         UserMetadataVisibility::Public,
@@ -488,11 +556,13 @@ pub async fn update_user_public_metadata_handler(
 
 pub async fn update_user_private_metadata_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, id)): Path<(String, Uuid)>,
     Json(payload): Json<UpdateUserMetadataPayload>,
 ) -> Result<impl IntoResponse> {
     update_user_metadata(
         state,
+        actor,
         realm_name,
         id,
         UserMetadataVisibility::Private,
@@ -503,11 +573,13 @@ pub async fn update_user_private_metadata_handler(
 
 pub async fn update_user_unsafe_metadata_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, id)): Path<(String, Uuid)>,
     Json(payload): Json<UpdateUserMetadataPayload>,
 ) -> Result<impl IntoResponse> {
     update_user_metadata(
         state,
+        actor,
         realm_name,
         id,
         UserMetadataVisibility::Unsafe,
@@ -518,6 +590,7 @@ pub async fn update_user_unsafe_metadata_handler(
 
 async fn update_user_metadata(
     state: AppState,
+    actor: User,
     realm_name: String,
     user_id: Uuid,
     visibility: UserMetadataVisibility,
@@ -528,6 +601,7 @@ async fn update_user_metadata(
         .find_by_name(&realm_name)
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
+    authorize_user(&state, &actor, realm.id, user_id, permissions::USER_WRITE).await?;
 
     let metadata = state
         .user_service
@@ -562,6 +636,16 @@ pub async fn delete_users_handler(
             "You cannot delete your own account.".to_string(),
         ));
     }
+    for user_id in &payload.user_ids {
+        authorize_user(
+            &state,
+            &current_user,
+            realm.id,
+            *user_id,
+            permissions::USER_DELETE,
+        )
+        .await?;
+    }
 
     let count = state
         .user_service
@@ -591,13 +675,15 @@ pub async fn lock_user_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize_user(&state, &current_user, realm.id, id, permissions::USER_LOCK).await?;
+
     let user = state
         .user_service
         .lock_user(realm.id, id, realm.lockout_duration_secs)
         .await?;
     state
-        .session_repo
-        .revoke_all_for_user(&realm.id, &id)
+        .logout_service
+        .revoke_all_for_user(realm.id, id)
         .await?;
 
     Ok((
@@ -623,10 +709,12 @@ pub async fn ban_user_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize_user(&state, &current_user, realm.id, id, permissions::USER_BAN).await?;
+
     let user = state.user_service.ban_user(realm.id, id).await?;
     state
-        .session_repo
-        .revoke_all_for_user(&realm.id, &id)
+        .logout_service
+        .revoke_all_for_user(realm.id, id)
         .await?;
 
     Ok((
@@ -635,6 +723,80 @@ pub async fn ban_user_handler(
     ))
 }
 
+// ---------------------------------------------------------------------------
+// Impersonation
+// ---------------------------------------------------------------------------
+
+#[derive(Serialize)]
+pub struct ImpersonationResponse {
+    pub access_token: String,
+    pub session_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub async fn impersonate_user_handler(
+    State(state): State<AppState>,
+    Extension(AuthUser(current_user)): Extension<AuthUser>,
+    impersonator: Option<Extension<Impersonator>>,
+    headers: HeaderMap,
+    Path((realm_name, id)): Path<(String, Uuid)>,
+) -> Result<impl IntoResponse> {
+    if impersonator.is_some() {
+        return Err(Error::Validation(
+            "You cannot start an impersonation from an impersonation session.".to_string(),
+        ));
+    }
+
+    let realm = state
+        .realm_service
+        .find_by_name(&realm_name)
+        .await?
+        .ok_or(Error::RealmNotFound(realm_name))?;
+
+    authorize_user(
+        &state,
+        &current_user,
+        realm.id,
+        id,
+        permissions::USER_IMPERSONATE,
+    )
+    .await?;
+
+    let target = state.user_service.get_user_in_realm(realm.id, id).await?;
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let (login, session) = state
+        .auth_service
+        .create_impersonation_session(&current_user, &target, None, user_agent)
+        .await?;
+
+    let event = NewAuditEvent {
+        realm_id: realm.id,
+        actor_user_id: Some(current_user.id),
+        action: "user.impersonate".to_string(),
+        target_type: "user".to_string(),
+        target_id: Some(target.id.to_string()),
+        metadata: serde_json::json!({
+            "session_id": session.id,
+            "expires_at": session.expires_at,
+        }),
+    };
+    if let Err(err) = state.audit_service.record(event).await {
+        error!("Failed to write impersonation audit event: {:?}", err);
+    }
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ImpersonationResponse {
+            access_token: login.access_token,
+            session_id: session.id,
+            expires_at: session.expires_at,
+        }),
+    ))
+}
+
 // ---------------------------------------------------------------------------
 // Update user profile (username only; emails go through /emails sub-resource)
 // ---------------------------------------------------------------------------
@@ -651,6 +813,7 @@ pub struct UpdateUserRequest {
 
 pub async fn update_user_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, id)): Path<(String, Uuid)>,
     ValidatedJson(payload): ValidatedJson<UpdateUserRequest>,
 ) -> Result<impl IntoResponse> {
@@ -660,6 +823,8 @@ pub async fn update_user_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize_user(&state, &actor, realm.id, id, permissions::USER_WRITE).await?;
+
     let username = payload.username.map(|value| value.trim().to_string());
     if username.as_deref().is_some_and(|value| value.is_empty()) {
         return Err(Error::Validation("Username cannot be empty".to_string()));
@@ -692,6 +857,7 @@ pub async fn update_user_handler(
 
 pub async fn list_user_emails_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, id)): Path<(String, Uuid)>,
 ) -> Result<impl IntoResponse> {
     let realm = state
@@ -700,6 +866,8 @@ pub async fn list_user_emails_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize_user(&state, &actor, realm.id, id, permissions::USER_WRITE).await?;
+
     // Confirm the user belongs to this realm
     state.user_service.get_user_in_realm(realm.id, id).await?;
 
@@ -723,6 +891,7 @@ pub struct AddUserEmailPayload {
 
 pub async fn add_user_email_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, id)): Path<(String, Uuid)>,
     ValidatedJson(payload): ValidatedJson<AddUserEmailPayload>,
 ) -> Result<impl IntoResponse> {
@@ -732,6 +901,8 @@ pub async fn add_user_email_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize_user(&state, &actor, realm.id, id, permissions::USER_WRITE).await?;
+
     state.user_service.get_user_in_realm(realm.id, id).await?;
 
     let email = match state
@@ -769,6 +940,7 @@ pub async fn add_user_email_handler(
 
 pub async fn remove_user_email_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, id, email_id)): Path<(String, Uuid, Uuid)>,
 ) -> Result<impl IntoResponse> {
     let realm = state
@@ -777,6 +949,8 @@ pub async fn remove_user_email_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize_user(&state, &actor, realm.id, id, permissions::USER_WRITE).await?;
+
     state.user_service.get_user_in_realm(realm.id, id).await?;
     state.user_email_service.remove_email(id, email_id).await?;
 
@@ -792,6 +966,7 @@ pub async fn remove_user_email_handler(
 
 pub async fn set_primary_email_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, id, email_id)): Path<(String, Uuid, Uuid)>,
 ) -> Result<impl IntoResponse> {
     let realm = state
@@ -800,6 +975,8 @@ pub async fn set_primary_email_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize_user(&state, &actor, realm.id, id, permissions::USER_WRITE).await?;
+
     state.user_service.get_user_in_realm(realm.id, id).await?;
     state.user_email_service.set_primary(id, email_id).await?;
 
@@ -820,6 +997,7 @@ pub struct SetVerifiedPayload {
 
 pub async fn set_email_verified_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, id, email_id)): Path<(String, Uuid, Uuid)>,
     Json(payload): Json<SetVerifiedPayload>,
 ) -> Result<impl IntoResponse> {
@@ -829,6 +1007,8 @@ pub async fn set_email_verified_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize_user(&state, &actor, realm.id, id, permissions::USER_WRITE).await?;
+
     state.user_service.get_user_in_realm(realm.id, id).await?;
     state
         .user_email_service
@@ -847,6 +1027,7 @@ pub async fn set_email_verified_handler(
 
 pub async fn list_user_phone_numbers_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, id)): Path<(String, Uuid)>,
 ) -> Result<impl IntoResponse> {
     let realm = state
@@ -855,6 +1036,8 @@ pub async fn list_user_phone_numbers_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize_user(&state, &actor, realm.id, id, permissions::USER_WRITE).await?;
+
     state.user_service.get_user_in_realm(realm.id, id).await?;
 
     let phone_numbers = state
@@ -880,6 +1063,7 @@ pub struct AddUserPhoneNumberPayload {
 
 pub async fn add_user_phone_number_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, id)): Path<(String, Uuid)>,
     ValidatedJson(payload): ValidatedJson<AddUserPhoneNumberPayload>,
 ) -> Result<impl IntoResponse> {
@@ -889,6 +1073,8 @@ pub async fn add_user_phone_number_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize_user(&state, &actor, realm.id, id, permissions::USER_WRITE).await?;
+
     state.user_service.get_user_in_realm(realm.id, id).await?;
 
     let phone_number = match state
@@ -926,6 +1112,7 @@ pub async fn add_user_phone_number_handler(
 
 pub async fn remove_user_phone_number_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, id, phone_number_id)): Path<(String, Uuid, Uuid)>,
 ) -> Result<impl IntoResponse> {
     let realm = state
@@ -934,6 +1121,8 @@ pub async fn remove_user_phone_number_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize_user(&state, &actor, realm.id, id, permissions::USER_WRITE).await?;
+
     state.user_service.get_user_in_realm(realm.id, id).await?;
     state
         .user_phone_number_service
@@ -952,6 +1141,7 @@ pub async fn remove_user_phone_number_handler(
 
 pub async fn set_primary_phone_number_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, id, phone_number_id)): Path<(String, Uuid, Uuid)>,
 ) -> Result<impl IntoResponse> {
     let realm = state
@@ -960,6 +1150,8 @@ pub async fn set_primary_phone_number_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize_user(&state, &actor, realm.id, id, permissions::USER_WRITE).await?;
+
     state.user_service.get_user_in_realm(realm.id, id).await?;
     state
         .user_phone_number_service
@@ -978,6 +1170,7 @@ pub async fn set_primary_phone_number_handler(
 
 pub async fn set_phone_number_verified_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, id, phone_number_id)): Path<(String, Uuid, Uuid)>,
     Json(payload): Json<SetVerifiedPayload>,
 ) -> Result<impl IntoResponse> {
@@ -987,6 +1180,8 @@ pub async fn set_phone_number_verified_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize_user(&state, &actor, realm.id, id, permissions::USER_WRITE).await?;
+
     state.user_service.get_user_in_realm(realm.id, id).await?;
     state
         .user_phone_number_service
@@ -1023,6 +1218,7 @@ pub struct UpdatePasswordPolicyRequest {
 
 pub async fn list_user_credentials_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, id)): Path<(String, Uuid)>,
 ) -> Result<impl IntoResponse> {
     let realm = state
@@ -1031,6 +1227,8 @@ pub async fn list_user_credentials_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize_user(&state, &actor, realm.id, id, permissions::USER_WRITE).await?;
+
     let credentials: UserCredentialsSummary = state
         .user_credentials_service
         .list_credentials(realm.id, id)
@@ -1040,30 +1238,15 @@ pub async fn list_user_credentials_handler(
 
 pub async fn update_user_password_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, id)): Path<(String, Uuid)>,
     Json(payload): Json<UpdateUserPasswordRequest>,
 ) -> Result<impl IntoResponse> {
+    // Without the skip flag the realm's password policy decides; see
+    // `UserService::update_password`.
     let skip_password_checks = payload.skip_password_checks.unwrap_or(false);
-    let invalid_password = if skip_password_checks {
-        payload.password.is_empty() || payload.password.len() > 100
-    } else {
-        payload.password.len() < 8 || payload.password.len() > 100
-    };
-
-    if invalid_password {
-        let mut fields = std::collections::HashMap::new();
-        fields.insert(
-            "password".to_string(),
-            if skip_password_checks {
-                "Password is required and must be no more than 100 characters".to_string()
-            } else {
-                "Password must be between 8 and 100 characters".to_string()
-            },
-        );
-        return Err(Error::FieldsValidation {
-            message: "Validation failed".to_string(),
-            fields,
-        });
+    if skip_password_checks {
+        check_unpoliced_password(&payload.password)?;
     }
 
     let realm = state
@@ -1072,6 +1255,8 @@ pub async fn update_user_password_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize_user(&state, &actor, realm.id, id, permissions::USER_WRITE).await?;
+
     state
         .user_credentials_service
         .update_password(
@@ -1079,6 +1264,7 @@ pub async fn update_user_password_handler(
             id,
             &payload.password,
             payload.sign_out_all_sessions.unwrap_or(false),
+            skip_password_checks,
         )
         .await?;
     Ok((
@@ -1089,6 +1275,7 @@ pub async fn update_user_password_handler(
 
 pub async fn revoke_user_passkey_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, id, credential_id)): Path<(String, Uuid, Uuid)>,
 ) -> Result<impl IntoResponse> {
     let realm = state
@@ -1097,6 +1284,8 @@ pub async fn revoke_user_passkey_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize_user(&state, &actor, realm.id, id, permissions::USER_WRITE).await?;
+
     state
         .user_credentials_service
         .revoke_passkey(realm.id, id, credential_id)
@@ -1107,6 +1296,29 @@ pub async fn revoke_user_passkey_handler(
     ))
 }
 
+pub async fn remove_user_totp_handler(
+    State(state): State<AppState>,
+    Extension(AuthUser(current_user)): Extension<AuthUser>,
+    Path((realm_name, id)): Path<(String, Uuid)>,
+) -> Result<impl IntoResponse> {
+    let realm = state
+        .realm_service
+        .find_by_name(&realm_name)
+        .await?
+        .ok_or(Error::RealmNotFound(realm_name))?;
+
+    authorize_user(&state, &current_user, realm.id, id, permissions::USER_WRITE).await?;
+
+    state
+        .user_credentials_service
+        .remove_totp(realm.id, Some(current_user.id), id)
+        .await?;
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({ "status": "removed" })),
+    ))
+}
+
 pub async fn unlink_user_federated_identity_handler(
     State(state): State<AppState>,
     Extension(AuthUser(current_user)): Extension<AuthUser>,
@@ -1118,6 +1330,8 @@ pub async fn unlink_user_federated_identity_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize_user(&state, &current_user, realm.id, id, permissions::USER_WRITE).await?;
+
     state
         .user_credentials_service
         .unlink_federated_identity(realm.id, Some(current_user.id), id, federated_identity_id)
@@ -1128,8 +1342,49 @@ pub async fn unlink_user_federated_identity_handler(
     ))
 }
 
+pub async fn list_user_consents_handler(
+    State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
+    Path((realm_name, id)): Path<(String, Uuid)>,
+) -> Result<impl IntoResponse> {
+    let realm = state
+        .realm_service
+        .find_by_name(&realm_name)
+        .await?
+        .ok_or(Error::RealmNotFound(realm_name))?;
+
+    authorize_user(&state, &actor, realm.id, id, permissions::USER_READ).await?;
+
+    let grants = state.consent_service.list_grants(realm.id, id).await?;
+    Ok((StatusCode::OK, Json(grants)))
+}
+
+pub async fn revoke_user_consent_handler(
+    State(state): State<AppState>,
+    Extension(AuthUser(current_user)): Extension<AuthUser>,
+    Path((realm_name, id, client_id)): Path<(String, Uuid, String)>,
+) -> Result<impl IntoResponse> {
+    let realm = state
+        .realm_service
+        .find_by_name(&realm_name)
+        .await?
+        .ok_or(Error::RealmNotFound(realm_name))?;
+
+    authorize_user(&state, &current_user, realm.id, id, permissions::USER_WRITE).await?;
+
+    state
+        .consent_service
+        .revoke_grant(realm.id, Some(current_user.id), id, &client_id)
+        .await?;
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({ "status": "revoked" })),
+    ))
+}
+
 pub async fn update_user_passkey_metadata_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, id, credential_id)): Path<(String, Uuid, Uuid)>,
     Json(payload): Json<UpdatePasskeyMetadataRequest>,
 ) -> Result<impl IntoResponse> {
@@ -1139,6 +1394,8 @@ pub async fn update_user_passkey_metadata_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize_user(&state, &actor, realm.id, id, permissions::USER_WRITE).await?;
+
     let friendly_name = payload
         .friendly_name
         .map(|value| value.trim().to_string())
@@ -1156,6 +1413,7 @@ pub async fn update_user_passkey_metadata_handler(
 
 pub async fn update_user_password_policy_handler(
     State(state): State<AppState>,
+    Extension(AuthUser(actor)): Extension<AuthUser>,
     Path((realm_name, id)): Path<(String, Uuid)>,
     Json(payload): Json<UpdatePasswordPolicyRequest>,
 ) -> Result<impl IntoResponse> {
@@ -1165,6 +1423,8 @@ pub async fn update_user_password_policy_handler(
         .await?
         .ok_or(Error::RealmNotFound(realm_name))?;
 
+    authorize_user(&state, &actor, realm.id, id, permissions::USER_WRITE).await?;
+
     state
         .user_credentials_service
         .update_password_policy(
@@ -2,9 +2,20 @@ use crate::domain::audit::{AuditActionCount, AuditEvent, NewAuditEvent};
 use crate::error::Result;
 use crate::ports::audit_repository::AuditRepository;
 use chrono::{DateTime, Utc};
+use std::future::Future;
 use std::sync::Arc;
 use uuid::Uuid;
 
+tokio::task_local! {
+    static IMPERSONATOR: Uuid;
+}
+
+/// Runs `future` on behalf of an impersonating admin: every audit event it
+/// records names `impersonator_id` next to its actor.
+pub async fn with_impersonator<F: Future>(impersonator_id: Uuid, future: F) -> F::Output {
+    IMPERSONATOR.scope(impersonator_id, future).await
+}
+
 pub struct AuditService {
     repo: Arc<dyn AuditRepository>,
 }
@@ -19,6 +30,7 @@ impl AuditService {
             id: Uuid::new_v4(),
             realm_id: event.realm_id,
             actor_user_id: event.actor_user_id,
+            impersonator_user_id: IMPERSONATOR.try_with(|id| *id).ok(),
             action: event.action,
             target_type: event.target_type,
             target_id: event.target_id,
@@ -1,10 +1,16 @@
+use crate::application::claims_service::ClaimsService;
+use crate::application::logout_service::LogoutService;
 use crate::application::rbac_service::RbacService;
+use crate::domain::claims::ClaimsGrant;
+use crate::domain::events::{DomainEvent, RefreshTokenReuseDetected, SessionRevocationReason};
 use crate::domain::pagination::{PageRequest, PageResponse};
+use crate::domain::permissions::{self, PermissionGrant};
 use crate::domain::session::{RefreshToken, SessionListFilter, SessionStats};
 use crate::domain::user::User;
+use crate::ports::event_bus::EventPublisher;
 use crate::ports::realm_repository::RealmRepository;
 use crate::ports::session_repository::SessionRepository;
-use crate::ports::token_service::{AccessTokenClaims, TokenService};
+use crate::ports::token_service::{AccessTokenClaims, ActorClaim, TokenService};
 use crate::{
     error::{Error, Result},
     ports::user_repository::UserRepository,
@@ -36,6 +42,8 @@ pub struct SessionView {
     #[serde(flatten)]
     pub token: RefreshToken,
     pub username: Option<String>,
+    /// Set on impersonation sessions.
+    pub impersonator_username: Option<String>,
 }
 
 pub struct AuthService {
@@ -46,9 +54,13 @@ pub struct AuthService {
     rbac_service: Arc<RbacService>,
     settings: crate::config::AuthConfig,
     security: crate::config::SecurityConfig,
+    logout_service: Arc<LogoutService>,
+    claims_service: Arc<ClaimsService>,
+    event_publisher: Arc<dyn EventPublisher>,
 }
 
 impl AuthService {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         user_repo: Arc<dyn UserRepository>,
         realm_repo: Arc<dyn RealmRepository>,
@@ -57,6 +69,9 @@ impl AuthService {
         rbac_service: Arc<RbacService>,
         settings: crate::config::AuthConfig,
         security: crate::config::SecurityConfig,
+        logout_service: Arc<LogoutService>,
+        claims_service: Arc<ClaimsService>,
+        event_publisher: Arc<dyn EventPublisher>,
     ) -> Self {
         Self {
             user_repo,
@@ -66,6 +81,9 @@ impl AuthService {
             rbac_service,
             settings,
             security,
+            logout_service,
+            claims_service,
+            event_publisher,
         }
     }
 
@@ -76,6 +94,98 @@ impl AuthService {
         client_id: Option<String>,
         ip_address: Option<String>,
         user_agent: Option<String>,
+    ) -> Result<(LoginResponse, RefreshToken)> {
+        self.create_session_with_grant(
+            user,
+            client_id,
+            ClaimsGrant::default(),
+            ip_address,
+            user_agent,
+        )
+        .await
+    }
+
+    /// Creates a session whose tokens carry the claims `grant` releases to
+    /// the client. The grant is kept on the session for refresh and userinfo.
+    #[instrument(skip_all, fields(telemetry = "span"))]
+    pub async fn create_session_with_grant(
+        &self,
+        user: &User,
+        client_id: Option<String>,
+        grant: ClaimsGrant,
+        ip_address: Option<String>,
+        user_agent: Option<String>,
+    ) -> Result<(LoginResponse, RefreshToken)> {
+        self.start_session(user, client_id, grant, ip_address, user_agent, None)
+            .await
+    }
+
+    /// Starts a session as `target` on behalf of `impersonator`. It lasts
+    /// `impersonation_session_ttl_secs`, its access tokens name the
+    /// impersonator in `act`, and it leaves the target's own sessions and
+    /// sign-in time alone.
+    #[instrument(skip_all, fields(telemetry = "span"))]
+    pub async fn create_impersonation_session(
+        &self,
+        impersonator: &User,
+        target: &User,
+        ip_address: Option<String>,
+        user_agent: Option<String>,
+    ) -> Result<(LoginResponse, RefreshToken)> {
+        if impersonator.id == target.id {
+            return Err(Error::Validation(
+                "Admins cannot impersonate themselves.".to_string(),
+            ));
+        }
+        // Master-realm admins may impersonate users of the realms they manage.
+        if !self
+            .rbac_service
+            .user_has_permission_in_realm(
+                &impersonator.id,
+                permissions::USER_IMPERSONATE,
+                &target.realm_id,
+            )
+            .await?
+        {
+            return Err(Error::InsufficientPermissions);
+        }
+        // Impersonating someone must not grant the admin anything they do not
+        // already hold, at the same or a broader scope.
+        let target_permissions = self
+            .rbac_service
+            .get_effective_permissions(&target.id)
+            .await?;
+        for permission in &target_permissions {
+            let Some(grant) = PermissionGrant::parse(permission) else {
+                continue;
+            };
+            if !self
+                .rbac_service
+                .user_covers_grant(&impersonator.id, &grant, target.realm_id)
+                .await?
+            {
+                return Err(Error::InsufficientPermissions);
+            }
+        }
+        self.start_session(
+            target,
+            None,
+            ClaimsGrant::default(),
+            ip_address,
+            user_agent,
+            Some(impersonator.id),
+        )
+        .await
+    }
+
+    async fn start_session(
+        &self,
+        user: &User,
+        client_id: Option<String>,
+        grant: ClaimsGrant,
+        ip_address: Option<String>,
+        user_agent: Option<String>,
+        impersonator_id: Option<Uuid>,
     ) -> Result<(LoginResponse, RefreshToken)> {
         // 1. Get realm from user. For now, use the default.
         let realm = self
@@ -84,38 +194,46 @@ impl AuthService {
             .await?
             .ok_or_else(|| Error::RealmNotFound(user.realm_id.to_string()))?;
 
-        // 1.5 Update last sign in
-        let mut updated_user = user.clone();
-        updated_user.last_sign_in_at = Some(Utc::now());
-        if let Err(e) = self.user_repo.update(&updated_user, None).await {
-            tracing::error!(
-                "Failed to update last_sign_in_at for user {}: {}",
-                user.id,
-                e
-            );
-        }
+        // Impersonation is not the user signing in, so the user's last sign-in
+        // and their other sessions are left alone.
+        if impersonator_id.is_none() {
+            // 1.5 Update last sign in
+            let mut updated_user = user.clone();
+            updated_user.last_sign_in_at = Some(Utc::now());
+            if let Err(e) = self.user_repo.update(&updated_user, None).await {
+                tracing::error!(
+                    "Failed to update last_sign_in_at for user {}: {}",
+                    user.id,
+                    e
+                );
+            }
 
-        // 1.6 Optionally revoke existing active tokens for the same user+client
-        // combo. Enforces a single active session per (user, client) when
-        // `single_session_per_client` is enabled; otherwise concurrent sessions
-        // (e.g. multiple browsers) are allowed and stale tokens expire via TTL.
-        if self.settings.single_session_per_client {
-            if let Some(ref cid) = client_id {
-                let _ = self
-                    .session_repo
-                    .revoke_by_user_and_client(&user.realm_id, &user.id, cid)
-                    .await;
-            } else {
-                // Root SSO token — revoke previous root tokens (client_id IS NULL)
-                let _ = self
-                    .session_repo
-                    .revoke_root_tokens_for_user(&user.realm_id, &user.id)
-                    .await;
+            // 1.6 Optionally revoke existing active tokens for the same user+client
+            // combo. Enforces a single active session per (user, client) when
+            // `single_session_per_client` is enabled; otherwise concurrent sessions
+            // (e.g. multiple browsers) are allowed and stale tokens expire via TTL.
+            if self.settings.single_session_per_client {
+                if let Some(ref cid) = client_id {
+                    let _ = self
+                        .session_repo
+                        .revoke_by_user_and_client(&user.realm_id, &user.id, cid)
+                        .await;
+                } else {
+                    // Root SSO token — revoke previous root tokens (client_id IS NULL)
+                    let _ = self
+                        .session_repo
+                        .revoke_root_tokens_for_user(&user.realm_id, &user.id)
+                        .await;
+                }
             }
         }
 
         // 2. Create the Stateful Refresh Token
-        let expires_at = Utc::now() + Duration::seconds(self.settings.refresh_token_ttl_secs);
+        let ttl_secs = match impersonator_id {
+            Some(_) => self.settings.impersonation_session_ttl_secs,
+            None => self.settings.refresh_token_ttl_secs,
+        };
+        let expires_at = Utc::now() + Duration::seconds(ttl_secs);
         let now = Utc::now();
         let refresh_token = RefreshToken {
             id: Uuid::new_v4(),
@@ -131,6 +249,9 @@ impl AuthService {
             revoked_at: None,
             replaced_by: None,
             step_up_at: None,
+            scope: grant.scope.clone(),
+            claims: grant.claims_json(),
+            impersonator_id,
         };
         self.session_repo.save(&refresh_token).await?;
 
@@ -144,17 +265,38 @@ impl AuthService {
             .get_user_roles_and_groups(&user.id)
             .await?;
 
+        let claims = self
+            .claims_service
+            .issue(user, client_id.as_deref(), &grant, &roles)
+            .await?;
+
         // 4. Create the Stateless Access Token (JWT)
         let access_token = self
             .token_service
-            .create_access_token(user, refresh_token.id, &permissions, &roles, &groups)
+            .create_access_token(
+                user,
+                refresh_token.id,
+                client_id.as_deref(),
+                &permissions,
+                &roles,
+                &groups,
+                grant.scope.as_deref(),
+                &claims.access_token,
+                actor_claim(&refresh_token).as_ref(),
+            )
             .await?;
 
         let mut id_token = None;
         if let Some(cid) = client_id {
             id_token = Some(
                 self.token_service
-                    .create_id_token(user, &cid, &groups)
+                    .create_id_token(
+                        user,
+                        &cid,
+                        refresh_token.family_id,
+                        &groups,
+                        &claims.id_token,
+                    )
                     .await?,
             );
         }
@@ -176,19 +318,31 @@ impl AuthService {
     }
 
     /// Validates an access token and returns the User together with the current
-    /// session id (the `sid` claim / live refresh-token id). The session id is
-    /// the trusted source for caller-scoped actions like "revoke other sessions".
+    /// session (the `sid` claim / live refresh token). The session id is the
+    /// trusted source for caller-scoped actions like "revoke other sessions".
     #[instrument(skip_all, fields(telemetry = "span"))]
-    pub async fn validate_token_and_get_session(&self, token: &str) -> Result<(User, Uuid)> {
-        // 1. Validate the JWT
-        let claims: AccessTokenClaims = self.token_service.validate_access_token(token).await?;
-
-        // 2. Check if the session is still valid in the DB
-        let session = self.session_repo.find_by_id(&claims.sid).await?;
-        let session = match session {
+    pub async fn validate_token_and_get_session(
+        &self,
+        token: &str,
+    ) -> Result<(User, RefreshToken)> {
+        // 1. Find the session the token claims, then validate the JWT with
+        // the keys of that session's realm.
+        let sid = self
+            .token_service
+            .unverified_session_id(token)
+            .ok_or(Error::InvalidCredentials)?;
+        let session = match self.session_repo.find_by_id(&sid).await? {
             Some(session) => session,
             None => return Err(Error::SessionRevoked),
         };
+        let claims: AccessTokenClaims = self
+            .token_service
+            .validate_access_token(session.realm_id, token)
+            .await?;
+        // Exchanged tokens are aimed at another service, not at this API.
+        if claims.aud.is_some() {
+            return Err(Error::InvalidCredentials);
+        }
 
         // 2b. Forced re-authentication (step-up). When immediate invalidation is
         // enabled, reject any access token issued before the step-up timestamp so
@@ -208,7 +362,7 @@ impl AuthService {
             .await?
             .ok_or(Error::UserNotFound)?;
 
-        Ok((user, claims.sid))
+        Ok((user, session))
     }
 
     #[instrument(skip_all, fields(telemetry = "span"))]
@@ -230,9 +384,28 @@ impl AuthService {
 
         // Reuse detection: if this token was already rotated or revoked, kill the family.
         if old_token.revoked_at.is_some() || old_token.replaced_by.is_some() {
+            let ended = self
+                .session_repo
+                .find_active_in_family(&old_token.family_id)
+                .await?;
             self.session_repo
                 .revoke_family(&old_token.family_id)
                 .await?;
+            self.event_publisher
+                .publish(DomainEvent::RefreshTokenReuseDetected(
+                    RefreshTokenReuseDetected {
+                        realm_id: old_token.realm_id,
+                        user_id: old_token.user_id,
+                        family_id: old_token.family_id,
+                        client_id: old_token.client_id.clone(),
+                    },
+                ))
+                .await;
+            if let Some(ended) = ended {
+                self.logout_service
+                    .sessions_ended(&[ended], SessionRevocationReason::Revoked)
+                    .await;
+            }
             return Err(Error::SecurityViolation(
                 "Refresh token reuse detected".to_string(),
             ));
@@ -258,8 +431,12 @@ impl AuthService {
             .await?
             .ok_or(Error::RealmNotFound("".to_string()))?; // The realm was deleted
 
-        // 3. Create a NEW Refresh Token
-        let expires_at = Utc::now() + Duration::seconds(self.settings.refresh_token_ttl_secs);
+        // 3. Create a NEW Refresh Token. Impersonation sessions keep their
+        // original expiry.
+        let expires_at = match old_token.impersonator_id {
+            Some(_) => old_token.expires_at,
+            None => Utc::now() + Duration::seconds(self.settings.refresh_token_ttl_secs),
+        };
         let now = Utc::now();
         let new_refresh_token = RefreshToken {
             id: Uuid::new_v4(), // New ID
@@ -275,6 +452,9 @@ impl AuthService {
             revoked_at: None,
             replaced_by: None,
             step_up_at: None,
+            scope: old_token.scope.clone(),
+            claims: old_token.claims.clone(),
+            impersonator_id: old_token.impersonator_id,
         };
         // Mark the old token as replaced (rotation).
         self.session_repo
@@ -292,17 +472,47 @@ impl AuthService {
             .get_user_roles_and_groups(&user.id)
             .await?;
 
+        let grant = ClaimsGrant::from_stored(
+            new_refresh_token.scope.clone(),
+            new_refresh_token.claims.as_deref(),
+        );
+        let claims = self
+            .claims_service
+            .issue(
+                &user,
+                new_refresh_token.client_id.as_deref(),
+                &grant,
+                &roles,
+            )
+            .await?;
+
         // 5. Create a new Access Token (JWT) linked to the *new* session
         let access_token = self
             .token_service
-            .create_access_token(&user, new_refresh_token.id, &permissions, &roles, &groups)
+            .create_access_token(
+                &user,
+                new_refresh_token.id,
+                new_refresh_token.client_id.as_deref(),
+                &permissions,
+                &roles,
+                &groups,
+                grant.scope.as_deref(),
+                &claims.access_token,
+                actor_claim(&new_refresh_token).as_ref(),
+            )
             .await?;
 
         let mut id_token = None;
         if let Some(cid) = &new_refresh_token.client_id {
             id_token = Some(
                 self.token_service
-                    .create_id_token(&user, cid, &groups)
+                    .create_id_token(
+                        &user,
+                        cid,
+                        new_refresh_token.family_id,
+                        &groups,
+                        &claims.id_token,
+                    )
                     .await?,
             );
         }
@@ -316,26 +526,107 @@ impl AuthService {
         ))
     }
 
+    /// Refresh-token grant at the OIDC token endpoint. The token must belong to
+    /// the realm and to the client presenting it; a mismatch is rejected before
+    /// rotation so another client cannot burn (or trip reuse detection on) a
+    /// family it does not own.
+    #[instrument(skip_all, fields(telemetry = "span"))]
+    pub async fn refresh_session_for_client(
+        &self,
+        realm_id: Uuid,
+        client_id: &str,
+        refresh_token_id: Uuid,
+    ) -> Result<(LoginResponse, RefreshToken)> {
+        let token = self
+            .session_repo
+            .find_by_id_any(&refresh_token_id)
+            .await?
+            .ok_or(Error::InvalidRefreshToken)?;
+
+        if token.realm_id != realm_id || token.client_id.as_deref() != Some(client_id) {
+            return Err(Error::InvalidRefreshToken);
+        }
+
+        self.refresh_session(refresh_token_id).await
+    }
+
     /// Logs out a user by deleting their specific refresh token session.
     pub async fn logout(&self, refresh_token_id: Uuid) -> Result<()> {
-        self.session_repo.delete_by_id(&refresh_token_id).await
+        let session = self.session_repo.find_by_id(&refresh_token_id).await?;
+        self.session_repo.delete_by_id(&refresh_token_id).await?;
+        if let Some(session) = session {
+            self.logout_service
+                .sessions_ended(&[session], SessionRevocationReason::Logout)
+                .await;
+        }
+        Ok(())
     }
 
     /// Logs out by revoking the cookie's token, then also revokes any tokens
     /// for the specified client so cross-origin OIDC sessions are cleaned up.
     pub async fn logout_with_client(&self, refresh_token_id: Uuid, client_id: &str) -> Result<()> {
         if let Ok(Some(token)) = self.session_repo.find_by_id_any(&refresh_token_id).await {
+            let ended = self
+                .active_sessions(token.realm_id, token.user_id, |session| {
+                    session.id == refresh_token_id
+                        || session.client_id.as_deref() == Some(client_id)
+                })
+                .await;
             let _ = self.session_repo.delete_by_id(&refresh_token_id).await;
             let _ = self
                 .session_repo
                 .revoke_by_user_and_client(&token.realm_id, &token.user_id, client_id)
                 .await;
+            self.logout_service
+                .sessions_ended(&ended, SessionRevocationReason::Logout)
+                .await;
         } else {
             let _ = self.session_repo.delete_by_id(&refresh_token_id).await;
         }
         Ok(())
     }
 
+    /// Ends the SSO session behind the browser cookie and the client session
+    /// identified by an ID token's `sid` (or, for tokens without one, all of
+    /// the user's sessions with that client). Returns the front-channel logout
+    /// URLs of the clients whose sessions ended.
+    pub async fn end_session(
+        &self,
+        realm_id: Uuid,
+        sso_token_id: Option<Uuid>,
+        user_id: Option<Uuid>,
+        client_id: Option<&str>,
+        sid: Option<Uuid>,
+    ) -> Result<Vec<String>> {
+        let mut ended = Vec::new();
+        if let Some(id) = sso_token_id {
+            if let Some(session) = self.session_repo.find_by_id(&id).await? {
+                if session.realm_id == realm_id {
+                    self.session_repo.delete_by_id(&id).await?;
+                    ended.push(session);
+                }
+            }
+        }
+
+        if let Some(user_id) = user_id {
+            let hinted = self
+                .active_sessions(realm_id, user_id, |session| match (sid, client_id) {
+                    (Some(sid), _) => session.family_id == sid,
+                    (None, Some(client_id)) => session.client_id.as_deref() == Some(client_id),
+                    (None, None) => false,
+                })
+                .await;
+            let ids: Vec<Uuid> = hinted.iter().map(|session| session.id).collect();
+            self.session_repo.revoke_many(&realm_id, &ids).await?;
+            ended.extend(hinted);
+        }
+
+        self.logout_service
+            .sessions_ended(&ended, SessionRevocationReason::Logout)
+            .await;
+        Ok(self.logout_service.frontchannel_logout_urls(&ended).await)
+    }
+
     pub async fn get_session_stats(&self, realm_id: Uuid) -> Result<SessionStats> {
         self.session_repo.get_stats(&realm_id).await
     }
@@ -353,9 +644,13 @@ impl AuthService {
         // each distinct user at most once.
         use std::collections::hash_map::Entry;
         let mut users: std::collections::HashMap<Uuid, User> = std::collections::HashMap::new();
-        for token in &page.data {
-            if let Entry::Vacant(slot) = users.entry(token.user_id) {
-                if let Some(user) = self.user_repo.find_by_id(&token.user_id).await? {
+        for user_id in page
+            .data
+            .iter()
+            .flat_map(|token| std::iter::once(token.user_id).chain(token.impersonator_id))
+        {
+            if let Entry::Vacant(slot) = users.entry(user_id) {
+                if let Some(user) = self.user_repo.find_by_id(&user_id).await? {
                     slot.insert(user);
                 }
             }
@@ -365,9 +660,10 @@ impl AuthService {
             .data
             .into_iter()
             .map(|token| {
-                let user = users.get(&token.user_id);
+                let username = |id: &Uuid| users.get(id).map(|u| u.username.clone());
                 SessionView {
-                    username: user.map(|u| u.username.clone()),
+                    username: username(&token.user_id),
+                    impersonator_username: token.impersonator_id.as_ref().and_then(username),
                     token,
                 }
             })
@@ -396,7 +692,19 @@ impl AuthService {
         if targets.is_empty() {
             return Ok(0);
         }
-        self.session_repo.revoke_many(&realm_id, &targets).await
+        let mut ended = Vec::new();
+        for id in &targets {
+            if let Some(session) = self.session_repo.find_by_id(id).await? {
+                if session.realm_id == realm_id {
+                    ended.push(session);
+                }
+            }
+        }
+        let revoked = self.session_repo.revoke_many(&realm_id, &targets).await?;
+        self.logout_service
+            .sessions_ended(&ended, SessionRevocationReason::Revoked)
+            .await;
+        Ok(revoked)
     }
 
     /// Revoke all of a user's active sessions except their current one.
@@ -406,16 +714,30 @@ impl AuthService {
         user_id: Uuid,
         current_sid: Uuid,
     ) -> Result<u64> {
-        self.session_repo
+        let ended = self
+            .active_sessions(realm_id, user_id, |session| session.id != current_sid)
+            .await;
+        let revoked = self
+            .session_repo
             .revoke_others_for_user(&realm_id, &user_id, &current_sid)
-            .await
+            .await?;
+        self.logout_service
+            .sessions_ended(&ended, SessionRevocationReason::Revoked)
+            .await;
+        Ok(revoked)
     }
 
     /// Revoke every active session for a user in a realm (admin-wide eviction).
     pub async fn revoke_user_sessions(&self, realm_id: Uuid, user_id: Uuid) -> Result<u64> {
-        self.session_repo
+        let ended = self.active_sessions(realm_id, user_id, |_| true).await;
+        let revoked = self
+            .session_repo
             .revoke_user_sessions(&realm_id, &user_id)
-            .await
+            .await?;
+        self.logout_service
+            .sessions_ended(&ended, SessionRevocationReason::Revoked)
+            .await;
+        Ok(revoked)
     }
 
     /// Mark a session for forced re-authentication. Returns true if a matching
@@ -423,6 +745,36 @@ impl AuthService {
     pub async fn request_step_up(&self, realm_id: Uuid, id: Uuid) -> Result<bool> {
         self.session_repo.request_step_up(&realm_id, &id).await
     }
+
+    /// A user's live sessions matching `filter`, captured before a revocation
+    /// so their clients can be notified. A lookup failure only skips the
+    /// notification; it never blocks the revocation itself.
+    async fn active_sessions(
+        &self,
+        realm_id: Uuid,
+        user_id: Uuid,
+        filter: impl Fn(&RefreshToken) -> bool,
+    ) -> Vec<RefreshToken> {
+        match self
+            .session_repo
+            .list_active_for_user(&realm_id, &user_id)
+            .await
+        {
+            Ok(sessions) => sessions.into_iter().filter(|s| filter(s)).collect(),
+            Err(err) => {
+                tracing::warn!("Failed to list sessions for logout notification: {}", err);
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// The `act` claim of an impersonation session's access tokens.
+fn actor_claim(session: &RefreshToken) -> Option<ActorClaim> {
+    session.impersonator_id.map(|id| ActorClaim {
+        sub: id.to_string(),
+        act: None,
+    })
 }
 
 #[cfg(test)]
@@ -1,11 +1,19 @@
 use super::AuthService;
+use crate::application::claims_service::ClaimsService;
+use crate::application::logout_service::LogoutService;
 use crate::application::rbac_service::RbacService;
 use crate::config::AuthConfig;
 use crate::constants::DEFAULT_REALM_NAME;
 use crate::domain::auth_flow::AuthFlow;
-use crate::domain::events::EventEnvelope;
+use crate::domain::claims::ProtocolMapper;
+use crate::domain::events::{DomainEvent, EventEnvelope, SessionRevocationReason};
 use crate::domain::group::Group;
+use crate::domain::oidc::{
+    AuthCode, ClientDeleteSummary, ClientStats, OidcClient, TokenEndpointAuthMethod,
+    BACKCHANNEL_LOGOUT_EVENT_TYPE,
+};
 use crate::domain::pagination::{PageRequest, PageResponse};
+use crate::domain::permissions;
 use crate::domain::rbac::{
     CustomPermission, CustomPermissionRoleImpact, GroupMemberFilter, GroupMemberRow,
     GroupRoleFilter, GroupRoleRow, GroupTreeRow, RoleCompositeFilter, RoleCompositeRow,
@@ -14,17 +22,24 @@ use crate::domain::rbac::{
 use crate::domain::role::{Permission, Role};
 use crate::domain::session::RefreshToken;
 use crate::domain::user::User;
+use crate::domain::user_email::UserEmail;
+use crate::domain::user_phone_number::UserPhoneNumber;
 use crate::error::{Error, Result};
+use crate::ports::event_bus::EventPublisher;
+use crate::ports::oidc_repository::OidcRepository;
 use crate::ports::outbox_repository::OutboxRepository;
+use crate::ports::protocol_mapper_repository::ProtocolMapperRepository;
 use crate::ports::rbac_repository::RbacRepository;
 use crate::ports::realm_repository::RealmRepository;
 use crate::ports::session_repository::SessionRepository;
-use crate::ports::token_service::{AccessTokenClaims, TokenService};
+use crate::ports::token_service::{AccessTokenClaims, ActorClaim, IdTokenClaims, TokenService};
 use crate::ports::transaction_manager::{Transaction, TransactionManager};
+use crate::ports::user_email_repository::UserEmailRepository;
+use crate::ports::user_phone_number_repository::UserPhoneNumberRepository;
 use crate::ports::user_repository::UserRepository;
 use async_trait::async_trait;
 use chrono::{DateTime, Duration, Utc};
-use serde_json::json;
+use serde_json::{json, Map, Value};
 use std::any::Any;
 use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
@@ -34,7 +49,10 @@ fn empty_page<T>() -> PageResponse<T> {
     PageResponse::new(Vec::new(), 0, 1, 10)
 }
 
-struct TestRbacRepo;
+#[derive(Default)]
+struct TestRbacRepo {
+    effective_permissions: HashMap<Uuid, HashSet<String>>,
+}
 
 #[allow(clippy::unused_async)]
 #[async_trait]
@@ -129,6 +147,14 @@ impl RbacRepository for TestRbacRepo {
         Ok(None)
     }
 
+    async fn find_client_realm_id(&self, _client_id: &Uuid) -> Result<Option<Uuid>> {
+        Ok(None)
+    }
+
+    async fn is_system_realm(&self, _realm_id: &Uuid) -> Result<bool> {
+        Ok(false)
+    }
+
     async fn list_roles(&self, _realm_id: &Uuid, _req: &PageRequest) -> Result<PageResponse<Role>> {
         Ok(empty_page())
     }
@@ -142,6 +168,14 @@ impl RbacRepository for TestRbacRepo {
         Ok(empty_page())
     }
 
+    async fn find_roles_for_client(
+        &self,
+        _realm_id: &Uuid,
+        _client_id: &Uuid,
+    ) -> Result<Vec<Role>> {
+        Ok(Vec::new())
+    }
+
     async fn find_role_by_id(&self, _role_id: &Uuid) -> Result<Option<Role>> {
         Ok(None)
     }
@@ -253,6 +287,15 @@ impl RbacRepository for TestRbacRepo {
         Ok(false)
     }
 
+    async fn is_user_in_group_subtree(
+        &self,
+        _realm_id: &Uuid,
+        _root_id: &Uuid,
+        _user_id: &Uuid,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+
     async fn get_next_group_sort_order(
         &self,
         _realm_id: &Uuid,
@@ -325,8 +368,12 @@ impl RbacRepository for TestRbacRepo {
         Ok(Vec::new())
     }
 
-    async fn get_effective_permissions_for_user(&self, _user_id: &Uuid) -> Result<HashSet<String>> {
-        Ok(HashSet::new())
+    async fn get_effective_permissions_for_user(&self, user_id: &Uuid) -> Result<HashSet<String>> {
+        Ok(self
+            .effective_permissions
+            .get(user_id)
+            .cloned()
+            .unwrap_or_default())
     }
 
     async fn find_role_names_for_user(&self, _user_id: &Uuid) -> Result<Vec<String>> {
@@ -337,6 +384,10 @@ impl RbacRepository for TestRbacRepo {
         Ok(Vec::new())
     }
 
+    async fn find_group_paths_for_user(&self, _user_id: &Uuid) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
     async fn delete_role(&self, _role_id: &Uuid, _tx: Option<&mut dyn Transaction>) -> Result<()> {
         Ok(())
     }
@@ -469,17 +520,140 @@ impl RbacRepository for TestRbacRepo {
 }
 
 #[derive(Default)]
-struct TestOutboxRepo;
+struct TestOutboxRepo {
+    inserted: Mutex<Vec<EventEnvelope>>,
+}
+
+impl TestOutboxRepo {
+    fn inserted(&self) -> Vec<EventEnvelope> {
+        self.inserted.lock().unwrap().clone()
+    }
+}
 
 #[async_trait]
 impl OutboxRepository for TestOutboxRepo {
     async fn insert(
         &self,
-        _envelope: &EventEnvelope,
+        envelope: &EventEnvelope,
         _tx: Option<&mut dyn Transaction>,
     ) -> Result<()> {
+        self.inserted.lock().unwrap().push(envelope.clone());
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct TestEventPublisher {
+    published: Mutex<Vec<DomainEvent>>,
+}
+
+impl TestEventPublisher {
+    fn published(&self) -> Vec<DomainEvent> {
+        self.published.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl EventPublisher for TestEventPublisher {
+    async fn publish(&self, event: DomainEvent) {
+        self.published.lock().unwrap().push(event);
+    }
+}
+
+#[derive(Default)]
+struct TestOidcRepo {
+    clients: Mutex<Vec<OidcClient>>,
+}
+
+impl TestOidcRepo {
+    fn insert(&self, client: OidcClient) {
+        self.clients.lock().unwrap().push(client);
+    }
+}
+
+#[allow(clippy::unused_async)]
+#[async_trait]
+impl OidcRepository for TestOidcRepo {
+    async fn find_client_by_id(
+        &self,
+        realm_id: &Uuid,
+        client_id: &str,
+    ) -> Result<Option<OidcClient>> {
+        Ok(self
+            .clients
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|client| &client.realm_id == realm_id && client.client_id == client_id)
+            .cloned())
+    }
+
+    async fn create_client(&self, client: &OidcClient) -> Result<()> {
+        self.insert(client.clone());
+        Ok(())
+    }
+
+    async fn find_clients_by_realm(
+        &self,
+        _realm_id: &Uuid,
+        _req: &PageRequest,
+    ) -> Result<PageResponse<OidcClient>> {
+        Ok(empty_page())
+    }
+
+    async fn count_client_stats(&self, _realm_id: &Uuid) -> Result<ClientStats> {
+        Ok(ClientStats {
+            total: 0,
+            confidential: 0,
+            public: 0,
+        })
+    }
+
+    async fn count_client_delete_summary(&self, id: &Uuid) -> Result<ClientDeleteSummary> {
+        Ok(ClientDeleteSummary {
+            client_id: *id,
+            name: String::new(),
+            role_count: 0,
+            permission_count: 0,
+        })
+    }
+
+    async fn delete_client(&self, _id: &Uuid) -> Result<()> {
         Ok(())
     }
+
+    async fn find_client_by_uuid(&self, _id: &Uuid) -> Result<Option<OidcClient>> {
+        Ok(None)
+    }
+
+    async fn update_client(&self, _client: &OidcClient) -> Result<()> {
+        Ok(())
+    }
+
+    async fn save_auth_code(&self, _code: &AuthCode) -> Result<()> {
+        Ok(())
+    }
+
+    async fn find_auth_code_by_code(&self, _code: &str) -> Result<Option<AuthCode>> {
+        Ok(None)
+    }
+
+    async fn delete_auth_code(&self, _code: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn is_origin_allowed(&self, _origin: &str) -> Result<bool> {
+        Ok(false)
+    }
+
+    async fn record_client_assertion_jti(
+        &self,
+        _client_id: &Uuid,
+        _jti: &str,
+        _expires_at: DateTime<Utc>,
+    ) -> Result<bool> {
+        Ok(true)
+    }
 }
 
 struct TestTx;
@@ -702,6 +876,40 @@ impl SessionRepository for TestSessionRepo {
         Ok(self.stored.lock().unwrap().get(id).cloned())
     }
 
+    async fn find_active_in_family(&self, family_id: &Uuid) -> Result<Option<RefreshToken>> {
+        Ok(self
+            .stored
+            .lock()
+            .unwrap()
+            .values()
+            .find(|token| {
+                &token.family_id == family_id
+                    && token.revoked_at.is_none()
+                    && token.replaced_by.is_none()
+            })
+            .cloned())
+    }
+
+    async fn list_active_for_user(
+        &self,
+        realm_id: &Uuid,
+        user_id: &Uuid,
+    ) -> Result<Vec<RefreshToken>> {
+        Ok(self
+            .stored
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|token| {
+                &token.realm_id == realm_id
+                    && &token.user_id == user_id
+                    && token.revoked_at.is_none()
+                    && token.replaced_by.is_none()
+            })
+            .cloned()
+            .collect())
+    }
+
     async fn delete_by_id(&self, id: &Uuid) -> Result<()> {
         let mut stored = self.stored.lock().unwrap();
         if let Some(token) = stored.get_mut(id) {
@@ -831,10 +1039,159 @@ impl SessionRepository for TestSessionRepo {
     }
 }
 
+#[derive(Default)]
+struct TestProtocolMapperRepo;
+
+#[allow(clippy::unused_async)]
+#[async_trait]
+impl ProtocolMapperRepository for TestProtocolMapperRepo {
+    async fn list_for_client(&self, _client_id: &Uuid) -> Result<Vec<ProtocolMapper>> {
+        Ok(Vec::new())
+    }
+
+    async fn find_by_id(&self, _id: &Uuid) -> Result<Option<ProtocolMapper>> {
+        Ok(None)
+    }
+
+    async fn create(&self, _mapper: &ProtocolMapper) -> Result<()> {
+        Ok(())
+    }
+
+    async fn update(&self, _mapper: &ProtocolMapper) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete(&self, _id: &Uuid) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct TestUserEmailRepo {
+    emails: Mutex<Vec<UserEmail>>,
+}
+
+#[allow(clippy::unused_async)]
+#[async_trait]
+impl UserEmailRepository for TestUserEmailRepo {
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<UserEmail>> {
+        Ok(self
+            .emails
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|email| email.user_id == *user_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn find_by_email(
+        &self,
+        _realm_id: &Uuid,
+        _email_normalized: &str,
+    ) -> Result<Option<UserEmail>> {
+        Ok(None)
+    }
+
+    async fn find_primary(&self, user_id: &Uuid) -> Result<Option<UserEmail>> {
+        Ok(self
+            .emails
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|email| email.user_id == *user_id && email.is_primary)
+            .cloned())
+    }
+
+    async fn save(&self, email: &UserEmail, _tx: Option<&mut dyn Transaction>) -> Result<()> {
+        self.emails.lock().unwrap().push(email.clone());
+        Ok(())
+    }
+
+    async fn set_primary(
+        &self,
+        _user_id: &Uuid,
+        _email_id: &Uuid,
+        _tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn set_verified(
+        &self,
+        _email_id: &Uuid,
+        _is_verified: bool,
+        _tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete(&self, _email_id: &Uuid, _tx: Option<&mut dyn Transaction>) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct TestUserPhoneNumberRepo;
+
+#[allow(clippy::unused_async)]
+#[async_trait]
+impl UserPhoneNumberRepository for TestUserPhoneNumberRepo {
+    async fn find_by_user_id(&self, _user_id: &Uuid) -> Result<Vec<UserPhoneNumber>> {
+        Ok(Vec::new())
+    }
+
+    async fn find_by_phone_number(
+        &self,
+        _realm_id: &Uuid,
+        _phone_number_normalized: &str,
+    ) -> Result<Option<UserPhoneNumber>> {
+        Ok(None)
+    }
+
+    async fn find_primary(&self, _user_id: &Uuid) -> Result<Option<UserPhoneNumber>> {
+        Ok(None)
+    }
+
+    async fn save(
+        &self,
+        _phone_number: &UserPhoneNumber,
+        _tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn set_primary(
+        &self,
+        _user_id: &Uuid,
+        _phone_number_id: &Uuid,
+        _tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn set_verified(
+        &self,
+        _phone_number_id: &Uuid,
+        _is_verified: bool,
+        _tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete(
+        &self,
+        _phone_number_id: &Uuid,
+        _tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
 #[derive(Default)]
 struct TestTokenService {
     claims: Mutex<Option<AccessTokenClaims>>,
     access_tokens: Mutex<Vec<Uuid>>,
+    actors: Mutex<Vec<Option<ActorClaim>>>,
     id_tokens: Mutex<Vec<String>>,
 }
 
@@ -855,11 +1212,16 @@ impl TokenService for TestTokenService {
         &self,
         _user: &User,
         session_id: Uuid,
+        _client_id: Option<&str>,
         _permissions: &HashSet<String>,
         _roles: &[String],
         _groups: &[String],
+        _scope: Option<&str>,
+        _extra_claims: &Map<String, Value>,
+        actor: Option<&ActorClaim>,
     ) -> Result<String> {
         self.access_tokens.lock().unwrap().push(session_id);
+        self.actors.lock().unwrap().push(actor.cloned());
         Ok("access-token".to_string())
     }
 
@@ -867,13 +1229,54 @@ impl TokenService for TestTokenService {
         &self,
         _user: &User,
         client_id: &str,
+        _session_id: Uuid,
         _groups: &[String],
+        _extra_claims: &Map<String, Value>,
     ) -> Result<String> {
         self.id_tokens.lock().unwrap().push(client_id.to_string());
         Ok("id-token".to_string())
     }
 
-    async fn validate_access_token(&self, _token: &str) -> Result<AccessTokenClaims> {
+    async fn validate_id_token_hint(&self, _realm_id: Uuid, _token: &str) -> Result<IdTokenClaims> {
+        Err(Error::InvalidCredentials)
+    }
+
+    async fn create_logout_token(
+        &self,
+        _realm_id: Uuid,
+        _client_id: &str,
+        _subject: Uuid,
+        _session_id: Option<Uuid>,
+    ) -> Result<String> {
+        Ok("logout-token".to_string())
+    }
+
+    async fn create_client_access_token(
+        &self,
+        _client: &crate::domain::oidc::OidcClient,
+        _permissions: &HashSet<String>,
+        _roles: &[String],
+        _scope: Option<&str>,
+    ) -> Result<String> {
+        Ok("client-access-token".to_string())
+    }
+
+    async fn create_exchanged_access_token(
+        &self,
+        _realm_id: Uuid,
+        _subject: &AccessTokenClaims,
+        _actor: &str,
+        _audience: &str,
+        _scope: Option<&str>,
+    ) -> Result<String> {
+        Ok("exchanged-token".to_string())
+    }
+
+    async fn validate_access_token(
+        &self,
+        _realm_id: Uuid,
+        _token: &str,
+    ) -> Result<AccessTokenClaims> {
         if let Some(claims) = self.claims.lock().unwrap().as_ref() {
             Ok(AccessTokenClaims {
                 sub: claims.sub,
@@ -883,17 +1286,30 @@ impl TokenService for TestTokenService {
                 groups: claims.groups.clone(),
                 exp: claims.exp,
                 iat: claims.iat,
+                azp: claims.azp.clone(),
+                scope: claims.scope.clone(),
+                aud: None,
+                act: None,
+                extra: Map::new(),
             })
         } else {
             Err(Error::InvalidCredentials)
         }
     }
 
-    fn get_key_id(&self) -> &str {
-        "kid"
+    fn unverified_session_id(&self, _token: &str) -> Option<Uuid> {
+        self.claims
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|claims| claims.sid)
+    }
+
+    fn access_token_ttl_secs(&self) -> i64 {
+        900
     }
 
-    fn get_jwks(&self) -> Result<serde_json::Value> {
+    async fn get_jwks(&self, _realm_id: &Uuid) -> Result<serde_json::Value> {
         Ok(json!({}))
     }
 }
@@ -904,21 +1320,62 @@ fn build_service(
     session_repo: Arc<TestSessionRepo>,
     token_service: Arc<TestTokenService>,
 ) -> AuthService {
-    let rbac_repo = Arc::new(TestRbacRepo);
+    build_service_with_logout(
+        user_repo,
+        realm_repo,
+        session_repo,
+        token_service,
+        Arc::new(TestOidcRepo::default()),
+        Arc::new(TestOutboxRepo::default()),
+        Arc::new(TestEventPublisher::default()),
+    )
+}
+
+fn build_service_with_logout(
+    user_repo: Arc<TestUserRepo>,
+    realm_repo: Arc<TestRealmRepo>,
+    session_repo: Arc<TestSessionRepo>,
+    token_service: Arc<TestTokenService>,
+    oidc_repo: Arc<TestOidcRepo>,
+    outbox_repo: Arc<TestOutboxRepo>,
+    events: Arc<TestEventPublisher>,
+) -> AuthService {
+    build_service_with_rbac(
+        user_repo,
+        realm_repo,
+        session_repo,
+        token_service,
+        oidc_repo,
+        outbox_repo,
+        events,
+        TestRbacRepo::default(),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_service_with_rbac(
+    user_repo: Arc<TestUserRepo>,
+    realm_repo: Arc<TestRealmRepo>,
+    session_repo: Arc<TestSessionRepo>,
+    token_service: Arc<TestTokenService>,
+    oidc_repo: Arc<TestOidcRepo>,
+    outbox_repo: Arc<TestOutboxRepo>,
+    events: Arc<TestEventPublisher>,
+    rbac_repo: TestRbacRepo,
+) -> AuthService {
+    let rbac_repo = Arc::new(rbac_repo);
     let cache = Arc::new(crate::adapters::cache::moka_cache::MokaCacheService::default());
     let event_bus = Arc::new(crate::adapters::eventing::in_memory_bus::InMemoryEventBus::default());
-    let outbox_repo = Arc::new(TestOutboxRepo);
     let tx_manager = Arc::new(TestTxManager);
     let rbac_service = Arc::new(RbacService::new(
         rbac_repo,
         cache,
         event_bus,
-        outbox_repo,
+        Arc::new(TestOutboxRepo::default()),
         tx_manager,
     ));
     let settings = AuthConfig {
         jwt_secret: "secret".to_string(),
-        jwt_key_id: "kid".to_string(),
         issuer: "http://issuer".to_string(),
         access_token_ttl_secs: 60,
         refresh_token_ttl_secs: 120,
@@ -931,9 +1388,33 @@ fn build_service(
         passkey_challenge_cleanup_batch_size: 500,
         oauth_broker_state_cleanup_interval_secs: 300,
         oauth_broker_state_cleanup_batch_size: 500,
+        device_code_cleanup_interval_secs: 300,
+        device_code_cleanup_batch_size: 500,
+        par_request_cleanup_interval_secs: 300,
+        par_request_cleanup_batch_size: 500,
+        signing_key_rotation_interval_secs: 0,
+        signing_key_retention_secs: 0,
+        signing_key_rotation_check_interval_secs: 0,
+        user_federation_sync_check_interval_secs: 0,
         single_session_per_client: false,
+        impersonation_session_ttl_secs: 900,
     };
 
+    let claims_service = Arc::new(ClaimsService::new(
+        oidc_repo.clone(),
+        Arc::new(TestProtocolMapperRepo),
+        Arc::new(TestUserEmailRepo::default()),
+        Arc::new(TestUserPhoneNumberRepo),
+        Arc::new(TestRbacRepo::default()),
+    ));
+    let logout_service = Arc::new(LogoutService::new(
+        session_repo.clone(),
+        oidc_repo,
+        outbox_repo,
+        events.clone(),
+        settings.issuer.clone(),
+    ));
+
     AuthService::new(
         user_repo,
         realm_repo,
@@ -942,6 +1423,9 @@ fn build_service(
         rbac_service,
         settings,
         crate::config::SecurityConfig::default(),
+        logout_service,
+        claims_service,
+        events,
     )
 }
 
@@ -952,6 +1436,7 @@ fn base_realm() -> crate::domain::realm::Realm {
         access_token_ttl_secs: 300,
         refresh_token_ttl_secs: 900,
         pkce_required_public_clients: true,
+        signing_algorithm: crate::domain::signing_key::SigningAlgorithm::default(),
         lockout_threshold: 5,
         lockout_duration_secs: 900,
         is_system: false,
@@ -1120,6 +1605,11 @@ async fn validate_token_and_get_user_rejects_revoked_sessions() {
         groups: Vec::new(),
         exp: 0,
         iat: 0,
+        azp: None,
+        scope: None,
+        aud: None,
+        act: None,
+        extra: Map::new(),
     });
 
     let service = build_service(user_repo, realm_repo, session_repo, token_service);
@@ -1177,6 +1667,9 @@ async fn validate_token_and_get_user_returns_user() {
         revoked_at: None,
         replaced_by: None,
         step_up_at: None,
+        scope: None,
+        claims: None,
+        impersonator_id: None,
     });
 
     token_service.set_claims(AccessTokenClaims {
@@ -1187,6 +1680,11 @@ async fn validate_token_and_get_user_returns_user() {
         groups: Vec::new(),
         exp: 0,
         iat: 0,
+        azp: None,
+        scope: None,
+        aud: None,
+        act: None,
+        extra: Map::new(),
     });
 
     let service = build_service(user_repo, realm_repo, session_repo, token_service);
@@ -1214,6 +1712,50 @@ async fn refresh_session_errors_when_token_missing() {
     }
 }
 
+#[tokio::test]
+async fn refresh_session_reuse_revokes_family_and_publishes_events() {
+    let realm_id = Uuid::new_v4();
+    let session_repo = Arc::new(TestSessionRepo::default());
+    let mut rotated = client_session(realm_id, Uuid::new_v4(), Some("app"));
+    let mut live = client_session(realm_id, rotated.user_id, Some("app"));
+    live.family_id = rotated.family_id;
+    rotated.replaced_by = Some(live.id);
+    session_repo.insert(rotated.clone());
+    session_repo.insert(live.clone());
+    let events = Arc::new(TestEventPublisher::default());
+
+    let service = build_service_with_logout(
+        Arc::new(TestUserRepo::default()),
+        Arc::new(TestRealmRepo::default()),
+        session_repo.clone(),
+        Arc::new(TestTokenService::default()),
+        Arc::new(TestOidcRepo::default()),
+        Arc::new(TestOutboxRepo::default()),
+        events.clone(),
+    );
+
+    match service.refresh_session(rotated.id).await {
+        Err(Error::SecurityViolation(_)) => {}
+        Err(other) => panic!("unexpected error: {:?}", other),
+        Ok(_) => panic!("expected error"),
+    }
+    assert_eq!(
+        session_repo.revoked_families.lock().unwrap().clone(),
+        vec![rotated.family_id]
+    );
+    match events.published().as_slice() {
+        [DomainEvent::RefreshTokenReuseDetected(detected), DomainEvent::SessionRevoked(revoked)] => {
+            assert_eq!(detected.realm_id, realm_id);
+            assert_eq!(detected.user_id, rotated.user_id);
+            assert_eq!(detected.family_id, rotated.family_id);
+            assert_eq!(detected.client_id.as_deref(), Some("app"));
+            assert_eq!(revoked.session_id, live.id);
+            assert_eq!(revoked.reason, SessionRevocationReason::Revoked);
+        }
+        other => panic!("unexpected events: {:?}", other),
+    }
+}
+
 #[tokio::test]
 async fn refresh_session_errors_when_user_missing() {
     let session_repo = Arc::new(TestSessionRepo::default());
@@ -1232,6 +1774,9 @@ async fn refresh_session_errors_when_user_missing() {
         revoked_at: None,
         replaced_by: None,
         step_up_at: None,
+        scope: None,
+        claims: None,
+        impersonator_id: None,
     });
 
     let service = build_service(
@@ -1287,6 +1832,9 @@ async fn refresh_session_errors_when_realm_missing() {
         revoked_at: None,
         replaced_by: None,
         step_up_at: None,
+        scope: None,
+        claims: None,
+        impersonator_id: None,
     });
 
     let service = build_service(
@@ -1347,6 +1895,9 @@ async fn refresh_session_rotates_tokens_and_issues_id_token() {
         revoked_at: None,
         replaced_by: None,
         step_up_at: None,
+        scope: None,
+        claims: None,
+        impersonator_id: None,
     });
 
     let token_service = Arc::new(TestTokenService::default());
@@ -1373,3 +1924,336 @@ async fn refresh_session_rotates_tokens_and_issues_id_token() {
     assert_eq!(session_repo.saved_tokens().len(), 1);
     assert_eq!(token_service.id_token_calls(), 1);
 }
+
+fn realm_user(realm_id: Uuid, username: &str) -> User {
+    User {
+        id: Uuid::new_v4(),
+        realm_id,
+        username: username.to_string(),
+        first_name: None,
+        last_name: None,
+        hashed_password: "hash".to_string(),
+        public_metadata_json: crate::domain::user::EMPTY_METADATA_JSON.to_string(),
+        private_metadata_json: crate::domain::user::EMPTY_METADATA_JSON.to_string(),
+        unsafe_metadata_json: crate::domain::user::EMPTY_METADATA_JSON.to_string(),
+        force_password_reset: false,
+        password_login_disabled: false,
+        created_at: Some(Utc::now()),
+        updated_at: None,
+        last_sign_in_at: None,
+        locked_until: None,
+        banned_at: None,
+    }
+}
+
+#[tokio::test]
+async fn impersonation_session_is_time_boxed_and_names_the_admin() {
+    let realm_repo = Arc::new(TestRealmRepo::default());
+    let realm = base_realm();
+    realm_repo.insert(realm.clone());
+    // The admin lives in another (master) realm and impersonates across realms.
+    let admin = realm_user(Uuid::new_v4(), "support");
+    let customer = realm_user(realm.id, "customer");
+    let user_repo = Arc::new(TestUserRepo::default());
+    user_repo.insert(admin.clone());
+    user_repo.insert(customer.clone());
+    let session_repo = Arc::new(TestSessionRepo::default());
+    let token_service = Arc::new(TestTokenService::default());
+    let rbac_repo = TestRbacRepo {
+        effective_permissions: HashMap::from([
+            (
+                admin.id,
+                HashSet::from([
+                    format!("{}@realm:{}", permissions::USER_IMPERSONATE, realm.id),
+                    "user:*".to_string(),
+                ]),
+            ),
+            (
+                customer.id,
+                HashSet::from([format!("{}@realm:{}", permissions::USER_READ, realm.id)]),
+            ),
+        ]),
+    };
+    let service = build_service_with_rbac(
+        user_repo.clone(),
+        realm_repo,
+        session_repo.clone(),
+        token_service.clone(),
+        Arc::new(TestOidcRepo::default()),
+        Arc::new(TestOutboxRepo::default()),
+        Arc::new(TestEventPublisher::default()),
+        rbac_repo,
+    );
+
+    let self_impersonation = service
+        .create_impersonation_session(&admin, &admin, None, None)
+        .await;
+    assert!(matches!(self_impersonation, Err(Error::Validation(_))));
+
+    let (_, session) = service
+        .create_impersonation_session(&admin, &customer, None, Some("agent".to_string()))
+        .await
+        .expect("impersonate");
+    assert_eq!(session.user_id, customer.id);
+    assert_eq!(session.impersonator_id, Some(admin.id));
+    assert!(session.client_id.is_none());
+    assert!(session.expires_at <= Utc::now() + Duration::seconds(900));
+    assert!(session.expires_at > Utc::now() + Duration::seconds(800));
+    let actor = ActorClaim {
+        sub: admin.id.to_string(),
+        act: None,
+    };
+    assert_eq!(
+        token_service.actors.lock().unwrap().clone(),
+        vec![Some(actor.clone())]
+    );
+    // The customer did not sign in.
+    let stored = user_repo.find_by_id(&customer.id).await.unwrap().unwrap();
+    assert!(stored.last_sign_in_at.is_none());
+
+    session_repo.insert(session.clone());
+    let (_, refreshed) = service
+        .refresh_session(session.id)
+        .await
+        .expect("refresh impersonation");
+    assert_eq!(refreshed.impersonator_id, Some(admin.id));
+    assert_eq!(refreshed.expires_at, session.expires_at);
+    assert_eq!(
+        token_service.actors.lock().unwrap().last().cloned(),
+        Some(Some(actor))
+    );
+}
+
+#[tokio::test]
+async fn impersonation_requires_the_admin_to_cover_the_targets_grants() {
+    let realm_repo = Arc::new(TestRealmRepo::default());
+    let realm = base_realm();
+    realm_repo.insert(realm.clone());
+    let admin = realm_user(realm.id, "support");
+    let customer = realm_user(realm.id, "customer");
+    let outsider = realm_user(realm.id, "outsider");
+    let rbac_repo = TestRbacRepo {
+        effective_permissions: HashMap::from([
+            (
+                admin.id,
+                HashSet::from([
+                    format!("{}@realm:{}", permissions::USER_IMPERSONATE, realm.id),
+                    format!("{}@realm:{}", permissions::USER_WRITE, realm.id),
+                ]),
+            ),
+            (
+                outsider.id,
+                HashSet::from([permissions::USER_IMPERSONATE.to_string()]),
+            ),
+            (
+                customer.id,
+                HashSet::from([permissions::USER_WRITE.to_string()]),
+            ),
+        ]),
+    };
+    let service = build_service_with_rbac(
+        Arc::new(TestUserRepo::default()),
+        realm_repo,
+        Arc::new(TestSessionRepo::default()),
+        Arc::new(TestTokenService::default()),
+        Arc::new(TestOidcRepo::default()),
+        Arc::new(TestOutboxRepo::default()),
+        Arc::new(TestEventPublisher::default()),
+        rbac_repo,
+    );
+
+    // A realm-scoped grant does not cover the customer's global one.
+    let broader_target = service
+        .create_impersonation_session(&admin, &customer, None, None)
+        .await;
+    assert!(matches!(
+        broader_target,
+        Err(Error::InsufficientPermissions)
+    ));
+
+    // Without user:impersonate for the realm nothing is checked further.
+    let unauthorized = service
+        .create_impersonation_session(&customer, &admin, None, None)
+        .await;
+    assert!(matches!(unauthorized, Err(Error::InsufficientPermissions)));
+
+    // The outsider holds user:impersonate but not the admin's user:write grant.
+    let uncovered = service
+        .create_impersonation_session(&outsider, &admin, None, None)
+        .await;
+    assert!(matches!(uncovered, Err(Error::InsufficientPermissions)));
+}
+
+fn client_session(realm_id: Uuid, user_id: Uuid, client_id: Option<&str>) -> RefreshToken {
+    RefreshToken {
+        id: Uuid::new_v4(),
+        family_id: Uuid::new_v4(),
+        user_id,
+        realm_id,
+        client_id: client_id.map(str::to_string),
+        expires_at: Utc::now() + Duration::seconds(60),
+        ip_address: None,
+        user_agent: None,
+        created_at: Utc::now(),
+        last_used_at: Utc::now(),
+        revoked_at: None,
+        replaced_by: None,
+        step_up_at: None,
+        scope: None,
+        claims: None,
+        impersonator_id: None,
+    }
+}
+
+fn logout_client(realm_id: Uuid, client_id: &str) -> OidcClient {
+    OidcClient {
+        id: Uuid::new_v4(),
+        realm_id,
+        client_id: client_id.to_string(),
+        client_secret: None,
+        redirect_uris: "[]".to_string(),
+        scopes: "[]".to_string(),
+        web_origins: "[]".to_string(),
+        managed_by_config: false,
+        token_endpoint_auth_method: TokenEndpointAuthMethod::None,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: Some(format!("https://{}.example.com/bc", client_id)),
+        frontchannel_logout_uri: Some(format!("https://{}.example.com/fc", client_id)),
+        token_exchange_policy: None,
+        direct_grant_enabled: false,
+        require_pushed_authorization_requests: false,
+    }
+}
+
+#[tokio::test]
+async fn logout_queues_backchannel_logout_for_client_session() {
+    let realm_id = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+    let session_repo = Arc::new(TestSessionRepo::default());
+    let session = client_session(realm_id, user_id, Some("app"));
+    session_repo.insert(session.clone());
+    let oidc_repo = Arc::new(TestOidcRepo::default());
+    oidc_repo.insert(logout_client(realm_id, "app"));
+    let outbox_repo = Arc::new(TestOutboxRepo::default());
+    let events = Arc::new(TestEventPublisher::default());
+
+    let service = build_service_with_logout(
+        Arc::new(TestUserRepo::default()),
+        Arc::new(TestRealmRepo::default()),
+        session_repo,
+        Arc::new(TestTokenService::default()),
+        oidc_repo,
+        outbox_repo.clone(),
+        events.clone(),
+    );
+
+    service.logout(session.id).await.expect("logout");
+
+    match events.published().as_slice() {
+        [DomainEvent::SessionRevoked(revoked)] => {
+            assert_eq!(revoked.realm_id, realm_id);
+            assert_eq!(revoked.session_id, session.id);
+            assert_eq!(revoked.client_id.as_deref(), Some("app"));
+            assert_eq!(revoked.reason, SessionRevocationReason::Logout);
+        }
+        other => panic!("unexpected events: {:?}", other),
+    }
+
+    let inserted = outbox_repo.inserted();
+    assert_eq!(inserted.len(), 1);
+    assert_eq!(inserted[0].event_type, BACKCHANNEL_LOGOUT_EVENT_TYPE);
+    assert_eq!(inserted[0].realm_id, Some(realm_id));
+    assert_eq!(inserted[0].data["client_id"], "app");
+    assert_eq!(
+        inserted[0].data["backchannel_logout_uri"],
+        "https://app.example.com/bc"
+    );
+    assert_eq!(inserted[0].data["sub"], json!(user_id));
+    assert_eq!(inserted[0].data["sid"], json!(session.family_id));
+}
+
+#[tokio::test]
+async fn revoke_user_sessions_notifies_only_clients_with_backchannel_uri() {
+    let realm_id = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+    let session_repo = Arc::new(TestSessionRepo::default());
+    session_repo.insert(client_session(realm_id, user_id, None));
+    session_repo.insert(client_session(realm_id, user_id, Some("app")));
+    session_repo.insert(client_session(realm_id, user_id, Some("legacy")));
+    let oidc_repo = Arc::new(TestOidcRepo::default());
+    oidc_repo.insert(logout_client(realm_id, "app"));
+    let mut legacy = logout_client(realm_id, "legacy");
+    legacy.backchannel_logout_uri = None;
+    oidc_repo.insert(legacy);
+    let outbox_repo = Arc::new(TestOutboxRepo::default());
+
+    let service = build_service_with_logout(
+        Arc::new(TestUserRepo::default()),
+        Arc::new(TestRealmRepo::default()),
+        session_repo,
+        Arc::new(TestTokenService::default()),
+        oidc_repo,
+        outbox_repo.clone(),
+        Arc::new(TestEventPublisher::default()),
+    );
+
+    let revoked = service
+        .revoke_user_sessions(realm_id, user_id)
+        .await
+        .expect("revoke");
+
+    assert_eq!(revoked, 3);
+    let inserted = outbox_repo.inserted();
+    assert_eq!(inserted.len(), 1);
+    assert_eq!(inserted[0].data["client_id"], "app");
+}
+
+#[tokio::test]
+async fn end_session_revokes_hinted_family_and_returns_frontchannel_urls() {
+    let realm_id = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+    let session_repo = Arc::new(TestSessionRepo::default());
+    let sso = client_session(realm_id, user_id, None);
+    let hinted = client_session(realm_id, user_id, Some("app"));
+    let other = client_session(realm_id, user_id, Some("app"));
+    for session in [&sso, &hinted, &other] {
+        session_repo.insert(session.clone());
+    }
+    let oidc_repo = Arc::new(TestOidcRepo::default());
+    oidc_repo.insert(logout_client(realm_id, "app"));
+    let outbox_repo = Arc::new(TestOutboxRepo::default());
+
+    let service = build_service_with_logout(
+        Arc::new(TestUserRepo::default()),
+        Arc::new(TestRealmRepo::default()),
+        session_repo.clone(),
+        Arc::new(TestTokenService::default()),
+        oidc_repo,
+        outbox_repo.clone(),
+        Arc::new(TestEventPublisher::default()),
+    );
+
+    let urls = service
+        .end_session(
+            realm_id,
+            Some(sso.id),
+            Some(user_id),
+            Some("app"),
+            Some(hinted.family_id),
+        )
+        .await
+        .expect("end_session");
+
+    assert_eq!(
+        urls,
+        vec![format!(
+            "https://app.example.com/fc?iss=http%3A%2F%2Fissuer&sid={}",
+            hinted.family_id
+        )]
+    );
+    assert!(session_repo.find_by_id(&sso.id).await.unwrap().is_none());
+    assert!(session_repo.find_by_id(&hinted.id).await.unwrap().is_none());
+    assert!(session_repo.find_by_id(&other.id).await.unwrap().is_some());
+    assert_eq!(outbox_repo.inserted().len(), 1);
+}
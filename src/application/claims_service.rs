@@ -0,0 +1,359 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use crate::domain::claims::{
+    json_path, scope_claims, ClaimsGrant, IssuedClaims, ProtocolMapper, ProtocolMapperKind,
+    RESERVED_CLAIMS,
+};
+use crate::domain::oidc::OidcClient;
+use crate::domain::user::User;
+use crate::error::{Error, Result};
+use crate::ports::oidc_repository::OidcRepository;
+use crate::ports::protocol_mapper_repository::ProtocolMapperRepository;
+use crate::ports::rbac_repository::RbacRepository;
+use crate::ports::user_email_repository::UserEmailRepository;
+use crate::ports::user_phone_number_repository::UserPhoneNumberRepository;
+
+/// Profile claims read as-is from the user's public metadata.
+const METADATA_PROFILE_CLAIMS: &[&str] = &[
+    "middle_name",
+    "nickname",
+    "profile",
+    "picture",
+    "website",
+    "gender",
+    "birthdate",
+    "zoneinfo",
+    "locale",
+    "address",
+];
+
+#[derive(Debug, Deserialize)]
+pub struct ProtocolMapperPayload {
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: ProtocolMapperKind,
+    pub claim_name: String,
+    #[serde(default)]
+    pub add_to_access_token: bool,
+    #[serde(default)]
+    pub add_to_id_token: bool,
+    #[serde(default)]
+    pub add_to_userinfo: bool,
+}
+
+/// Decides which claims a client receives: the standard claims of the scopes
+/// it was granted, the members of the OIDC `claims` parameter, and whatever
+/// its protocol mappers add.
+pub struct ClaimsService {
+    oidc_repo: Arc<dyn OidcRepository>,
+    mapper_repo: Arc<dyn ProtocolMapperRepository>,
+    user_email_repo: Arc<dyn UserEmailRepository>,
+    user_phone_number_repo: Arc<dyn UserPhoneNumberRepository>,
+    rbac_repo: Arc<dyn RbacRepository>,
+}
+
+impl ClaimsService {
+    pub fn new(
+        oidc_repo: Arc<dyn OidcRepository>,
+        mapper_repo: Arc<dyn ProtocolMapperRepository>,
+        user_email_repo: Arc<dyn UserEmailRepository>,
+        user_phone_number_repo: Arc<dyn UserPhoneNumberRepository>,
+        rbac_repo: Arc<dyn RbacRepository>,
+    ) -> Self {
+        Self {
+            oidc_repo,
+            mapper_repo,
+            user_email_repo,
+            user_phone_number_repo,
+            rbac_repo,
+        }
+    }
+
+    /// Claims to add to the tokens and userinfo response of `client_id` for
+    /// `user`. Scope claims only flow for scopes registered on the client,
+    /// and a `claims` member only if one of those scopes could release it.
+    pub async fn issue(
+        &self,
+        user: &User,
+        client_id: Option<&str>,
+        grant: &ClaimsGrant,
+        roles: &[String],
+    ) -> Result<IssuedClaims> {
+        let Some(client_id) = client_id else {
+            return Ok(IssuedClaims::default());
+        };
+        let Some(client) = self
+            .oidc_repo
+            .find_client_by_id(&user.realm_id, client_id)
+            .await?
+        else {
+            return Ok(IssuedClaims::default());
+        };
+
+        let registered = client_scopes(&client)?;
+        let releasable: BTreeSet<&str> = registered
+            .iter()
+            .flat_map(|scope| scope_claims(scope).iter().copied())
+            .collect();
+
+        let mut id_token_claims: BTreeSet<&str> = grant
+            .scopes()
+            .filter(|scope| registered.iter().any(|registered| registered == scope))
+            .flat_map(|scope| scope_claims(scope).iter().copied())
+            .collect();
+        let mut userinfo_claims = id_token_claims.clone();
+        if let Some(requested) = &grant.claims {
+            id_token_claims.extend(
+                requested
+                    .id_token
+                    .keys()
+                    .filter_map(|name| releasable.get(name.as_str()).copied()),
+            );
+            userinfo_claims.extend(
+                requested
+                    .userinfo
+                    .keys()
+                    .filter_map(|name| releasable.get(name.as_str()).copied()),
+            );
+        }
+
+        let wanted: BTreeSet<&str> = id_token_claims.union(&userinfo_claims).copied().collect();
+        let values = self.standard_claims(user, &wanted).await?;
+
+        let mut issued = IssuedClaims::default();
+        for (name, value) in &values {
+            if id_token_claims.contains(name.as_str()) {
+                issued.id_token.insert(name.clone(), value.clone());
+            }
+            if userinfo_claims.contains(name.as_str()) {
+                issued.userinfo.insert(name.clone(), value.clone());
+            }
+        }
+
+        let metadata = public_metadata(user);
+        let mut group_paths: Option<Vec<String>> = None;
+        for mapper in self.mapper_repo.list_for_client(&client.id).await? {
+            if RESERVED_CLAIMS.contains(&mapper.claim_name.as_str()) {
+                continue;
+            }
+            let value = match &mapper.kind {
+                ProtocolMapperKind::UserMetadata { path } => json_path(&metadata, path).cloned(),
+                ProtocolMapperKind::RoleList { prefix } => Some(json!(roles
+                    .iter()
+                    .filter(|role| prefix
+                        .as_deref()
+                        .is_none_or(|prefix| role.starts_with(prefix)))
+                    .collect::<Vec<_>>())),
+                ProtocolMapperKind::GroupPath => {
+                    if group_paths.is_none() {
+                        group_paths =
+                            Some(self.rbac_repo.find_group_paths_for_user(&user.id).await?);
+                    }
+                    group_paths.as_ref().map(|paths| json!(paths))
+                }
+            };
+            let Some(value) = value else {
+                continue;
+            };
+            if mapper.add_to_access_token {
+                issued
+                    .access_token
+                    .insert(mapper.claim_name.clone(), value.clone());
+            }
+            if mapper.add_to_id_token {
+                issued
+                    .id_token
+                    .insert(mapper.claim_name.clone(), value.clone());
+            }
+            if mapper.add_to_userinfo {
+                issued.userinfo.insert(mapper.claim_name.clone(), value);
+            }
+        }
+
+        Ok(issued)
+    }
+
+    /// Values of the `wanted` standard claims the user has.
+    async fn standard_claims(
+        &self,
+        user: &User,
+        wanted: &BTreeSet<&str>,
+    ) -> Result<Map<String, Value>> {
+        let mut claims = Map::new();
+        if wanted.is_empty() {
+            return Ok(claims);
+        }
+
+        let name = [user.first_name.as_deref(), user.last_name.as_deref()]
+            .into_iter()
+            .flatten()
+            .filter(|part| !part.trim().is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !name.is_empty() {
+            claims.insert("name".to_string(), json!(name));
+        }
+        if let Some(given_name) = &user.first_name {
+            claims.insert("given_name".to_string(), json!(given_name));
+        }
+        if let Some(family_name) = &user.last_name {
+            claims.insert("family_name".to_string(), json!(family_name));
+        }
+        claims.insert("preferred_username".to_string(), json!(user.username));
+        if let Some(updated_at) = user.updated_at {
+            claims.insert("updated_at".to_string(), json!(updated_at.timestamp()));
+        }
+
+        let metadata = public_metadata(user);
+        for name in METADATA_PROFILE_CLAIMS {
+            if let Some(value) = metadata.get(*name).filter(|value| !value.is_null()) {
+                claims.insert(name.to_string(), value.clone());
+            }
+        }
+
+        if wanted.contains("email") || wanted.contains("email_verified") {
+            if let Some(email) = self.user_email_repo.find_primary(&user.id).await? {
+                claims.insert("email".to_string(), json!(email.email));
+                claims.insert("email_verified".to_string(), json!(email.is_verified));
+            }
+        }
+        if wanted.contains("phone_number") || wanted.contains("phone_number_verified") {
+            if let Some(phone) = self.user_phone_number_repo.find_primary(&user.id).await? {
+                claims.insert("phone_number".to_string(), json!(phone.phone_number));
+                claims.insert(
+                    "phone_number_verified".to_string(),
+                    json!(phone.is_verified),
+                );
+            }
+        }
+
+        claims.retain(|name, _| wanted.contains(name.as_str()));
+        Ok(claims)
+    }
+
+    pub async fn list_mappers(&self, client: &OidcClient) -> Result<Vec<ProtocolMapper>> {
+        self.mapper_repo.list_for_client(&client.id).await
+    }
+
+    pub async fn create_mapper(
+        &self,
+        client: &OidcClient,
+        payload: ProtocolMapperPayload,
+    ) -> Result<ProtocolMapper> {
+        let payload = validate_mapper_payload(payload)?;
+        self.ensure_unique_name(client, &payload.name, None).await?;
+
+        let mapper = ProtocolMapper {
+            id: Uuid::new_v4(),
+            realm_id: client.realm_id,
+            client_id: client.id,
+            name: payload.name,
+            kind: payload.kind,
+            claim_name: payload.claim_name,
+            add_to_access_token: payload.add_to_access_token,
+            add_to_id_token: payload.add_to_id_token,
+            add_to_userinfo: payload.add_to_userinfo,
+            created_at: Utc::now(),
+        };
+        self.mapper_repo.create(&mapper).await?;
+        Ok(mapper)
+    }
+
+    pub async fn update_mapper(
+        &self,
+        client: &OidcClient,
+        mapper_id: Uuid,
+        payload: ProtocolMapperPayload,
+    ) -> Result<ProtocolMapper> {
+        let mut mapper = self.find_mapper(client, mapper_id).await?;
+        let payload = validate_mapper_payload(payload)?;
+        self.ensure_unique_name(client, &payload.name, Some(mapper.id))
+            .await?;
+
+        mapper.name = payload.name;
+        mapper.kind = payload.kind;
+        mapper.claim_name = payload.claim_name;
+        mapper.add_to_access_token = payload.add_to_access_token;
+        mapper.add_to_id_token = payload.add_to_id_token;
+        mapper.add_to_userinfo = payload.add_to_userinfo;
+        self.mapper_repo.update(&mapper).await?;
+        Ok(mapper)
+    }
+
+    pub async fn delete_mapper(&self, client: &OidcClient, mapper_id: Uuid) -> Result<()> {
+        let mapper = self.find_mapper(client, mapper_id).await?;
+        self.mapper_repo.delete(&mapper.id).await
+    }
+
+    async fn find_mapper(&self, client: &OidcClient, mapper_id: Uuid) -> Result<ProtocolMapper> {
+        self.mapper_repo
+            .find_by_id(&mapper_id)
+            .await?
+            .filter(|mapper| mapper.client_id == client.id)
+            .ok_or_else(|| Error::NotFound("Protocol mapper not found".to_string()))
+    }
+
+    async fn ensure_unique_name(
+        &self,
+        client: &OidcClient,
+        name: &str,
+        except: Option<Uuid>,
+    ) -> Result<()> {
+        let taken = self
+            .mapper_repo
+            .list_for_client(&client.id)
+            .await?
+            .iter()
+            .any(|mapper| mapper.name == name && Some(mapper.id) != except);
+        if taken {
+            return Err(Error::Conflict(format!(
+                "Protocol mapper '{}' already exists",
+                name
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn validate_mapper_payload(mut payload: ProtocolMapperPayload) -> Result<ProtocolMapperPayload> {
+    payload.name = payload.name.trim().to_string();
+    payload.claim_name = payload.claim_name.trim().to_string();
+    if payload.name.is_empty() {
+        return Err(Error::Validation("Mapper name is required".to_string()));
+    }
+    if payload.claim_name.is_empty() {
+        return Err(Error::Validation("claim_name is required".to_string()));
+    }
+    if RESERVED_CLAIMS.contains(&payload.claim_name.as_str()) {
+        return Err(Error::Validation(format!(
+            "Claim '{}' is reserved",
+            payload.claim_name
+        )));
+    }
+    if let ProtocolMapperKind::UserMetadata { path } = &payload.kind {
+        if path.trim().is_empty() {
+            return Err(Error::Validation("Metadata path is required".to_string()));
+        }
+    }
+    if !(payload.add_to_access_token || payload.add_to_id_token || payload.add_to_userinfo) {
+        return Err(Error::Validation(
+            "Mapper must add its claim to at least one token".to_string(),
+        ));
+    }
+    Ok(payload)
+}
+
+fn client_scopes(client: &OidcClient) -> Result<Vec<String>> {
+    serde_json::from_str(&client.scopes)
+        .map_err(|_| Error::Unexpected(anyhow::anyhow!("Invalid scopes format in DB")))
+}
+
+fn public_metadata(user: &User) -> Value {
+    serde_json::from_str(&user.public_metadata_json).unwrap_or_else(|_| json!({}))
+}
@@ -0,0 +1,631 @@
+use std::sync::Arc;
+
+use base64::Engine;
+use chrono::{Duration, Utc};
+use rand::distr::{Alphanumeric, SampleString};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::application::audit_service::AuditService;
+use crate::application::oidc_service::{OidcService, UpdateClientRequest};
+use crate::domain::audit::NewAuditEvent;
+use crate::domain::client_registration::{
+    ClientRegistration, ClientRegistrationPolicy, InitialAccessToken, REGISTRABLE_GRANT_TYPES,
+};
+use crate::domain::oidc::{OidcClient, TokenEndpointAuthMethod, TOKEN_EXCHANGE_GRANT_TYPE};
+use crate::error::{Error, Result};
+use crate::ports::client_registration_repository::ClientRegistrationRepository;
+use crate::ports::realm_repository::RealmRepository;
+
+const DEFAULT_INITIAL_TOKEN_TTL_SECS: i64 = 86_400;
+const MAX_INITIAL_TOKEN_TTL_SECS: i64 = 30 * 86_400;
+const MAX_INITIAL_TOKEN_USES: i64 = 1000;
+/// Grants that need the client to authenticate, so public clients cannot
+/// register them.
+const CONFIDENTIAL_GRANT_TYPES: &[&str] = &["client_credentials", TOKEN_EXCHANGE_GRANT_TYPE];
+
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateClientRegistrationPolicyPayload {
+    pub allowed_redirect_hosts: Option<Vec<String>>,
+    pub require_https: Option<bool>,
+    pub allowed_grant_types: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateInitialAccessTokenPayload {
+    pub description: Option<String>,
+    /// Lifetime in seconds; one day when omitted.
+    pub expires_in: Option<i64>,
+    /// Number of clients the token may register; one when omitted.
+    pub count: Option<i64>,
+}
+
+/// Client metadata of a registration or update request (RFC 7591, Section 2).
+/// Members ReAuth does not support are ignored.
+#[derive(Debug, Default, Deserialize)]
+pub struct ClientMetadata {
+    /// Only sent on updates, where it must name the client being updated.
+    pub client_id: Option<String>,
+    #[serde(default)]
+    pub redirect_uris: Vec<String>,
+    pub token_endpoint_auth_method: Option<String>,
+    pub grant_types: Option<Vec<String>>,
+    pub response_types: Option<Vec<String>>,
+    pub client_name: Option<String>,
+    pub scope: Option<String>,
+    pub jwks: Option<Value>,
+    pub backchannel_logout_uri: Option<String>,
+    pub frontchannel_logout_uri: Option<String>,
+}
+
+/// A dynamically registered client as returned to the registrant. The secret
+/// is only set for secret-based authentication methods, and the registration
+/// access token only when it was just issued.
+#[derive(Debug)]
+pub struct RegisteredClient {
+    pub client: OidcClient,
+    pub registration: ClientRegistration,
+    pub client_secret: Option<String>,
+    pub registration_access_token: Option<String>,
+}
+
+/// Metadata checked against the realm policy and ready to apply.
+struct ResolvedMetadata {
+    redirect_uris: Vec<String>,
+    auth_method: TokenEndpointAuthMethod,
+    grant_types: Vec<String>,
+    scopes: Vec<String>,
+    client_name: Option<String>,
+    jwks: Option<Value>,
+    backchannel_logout_uri: Option<String>,
+    frontchannel_logout_uri: Option<String>,
+}
+
+/// Dynamic client registration (RFC 7591) behind realm-issued initial access
+/// tokens, and management of the registered clients (RFC 7592).
+pub struct ClientRegistrationService {
+    realm_repo: Arc<dyn RealmRepository>,
+    registration_repo: Arc<dyn ClientRegistrationRepository>,
+    oidc_service: Arc<OidcService>,
+    audit_service: Arc<AuditService>,
+}
+
+impl ClientRegistrationService {
+    pub fn new(
+        realm_repo: Arc<dyn RealmRepository>,
+        registration_repo: Arc<dyn ClientRegistrationRepository>,
+        oidc_service: Arc<OidcService>,
+        audit_service: Arc<AuditService>,
+    ) -> Self {
+        Self {
+            realm_repo,
+            registration_repo,
+            oidc_service,
+            audit_service,
+        }
+    }
+
+    pub async fn get_policy(&self, realm_id: Uuid) -> Result<ClientRegistrationPolicy> {
+        self.ensure_realm_exists(&realm_id).await?;
+        self.policy_for_realm(realm_id).await
+    }
+
+    pub async fn update_policy(
+        &self,
+        realm_id: Uuid,
+        payload: UpdateClientRegistrationPolicyPayload,
+    ) -> Result<ClientRegistrationPolicy> {
+        self.ensure_realm_exists(&realm_id).await?;
+        let mut policy = self.policy_for_realm(realm_id).await?;
+
+        if let Some(hosts) = payload.allowed_redirect_hosts {
+            policy.allowed_redirect_hosts = normalize_host_patterns(hosts)?;
+        }
+        if let Some(value) = payload.require_https {
+            policy.require_https = value;
+        }
+        if let Some(grant_types) = payload.allowed_grant_types {
+            if let Some(unknown) = grant_types
+                .iter()
+                .find(|grant_type| !REGISTRABLE_GRANT_TYPES.contains(&grant_type.as_str()))
+            {
+                return Err(Error::Validation(format!(
+                    "Unsupported grant type '{}'",
+                    unknown
+                )));
+            }
+            policy.allowed_grant_types = dedupe(grant_types);
+        }
+
+        self.registration_repo.upsert_policy(&policy).await?;
+        Ok(policy)
+    }
+
+    /// The realm's stored policy, or the defaults when none was saved.
+    pub async fn policy_for_realm(&self, realm_id: Uuid) -> Result<ClientRegistrationPolicy> {
+        Ok(self
+            .registration_repo
+            .find_policy(&realm_id)
+            .await?
+            .unwrap_or_else(|| ClientRegistrationPolicy::defaults(realm_id)))
+    }
+
+    /// Refuses a token request for a grant a dynamically registered client
+    /// did not register, or that the realm policy no longer allows. Clients
+    /// created by admins have no registration and are not limited here.
+    pub async fn ensure_grant_type_allowed(
+        &self,
+        client: &OidcClient,
+        grant_type: &str,
+    ) -> Result<()> {
+        let Some(registration) = self.registration_repo.find_registration(&client.id).await? else {
+            return Ok(());
+        };
+        if !registration.grant_types.iter().any(|g| g == grant_type) {
+            return Err(Error::OidcUnauthorizedClient(format!(
+                "Client is not registered for grant type '{}'",
+                grant_type
+            )));
+        }
+        if !self
+            .policy_for_realm(client.realm_id)
+            .await?
+            .allows_grant_type(grant_type)
+        {
+            return Err(Error::OidcUnauthorizedClient(format!(
+                "Grant type '{}' is not allowed in this realm",
+                grant_type
+            )));
+        }
+        Ok(())
+    }
+
+    pub async fn list_initial_access_tokens(
+        &self,
+        realm_id: Uuid,
+    ) -> Result<Vec<InitialAccessToken>> {
+        self.ensure_realm_exists(&realm_id).await?;
+        self.registration_repo
+            .list_initial_access_tokens(&realm_id)
+            .await
+    }
+
+    /// Issues an initial access token. The plaintext is only returned here.
+    pub async fn create_initial_access_token(
+        &self,
+        realm_id: Uuid,
+        payload: CreateInitialAccessTokenPayload,
+    ) -> Result<(InitialAccessToken, String)> {
+        self.ensure_realm_exists(&realm_id).await?;
+        let expires_in = payload.expires_in.unwrap_or(DEFAULT_INITIAL_TOKEN_TTL_SECS);
+        if !(1..=MAX_INITIAL_TOKEN_TTL_SECS).contains(&expires_in) {
+            return Err(Error::Validation(format!(
+                "expires_in must be between 1 and {} seconds",
+                MAX_INITIAL_TOKEN_TTL_SECS
+            )));
+        }
+        let count = payload.count.unwrap_or(1);
+        if !(1..=MAX_INITIAL_TOKEN_USES).contains(&count) {
+            return Err(Error::Validation(format!(
+                "count must be between 1 and {}",
+                MAX_INITIAL_TOKEN_USES
+            )));
+        }
+
+        let plaintext = generate_token();
+        let now = Utc::now();
+        let token = InitialAccessToken {
+            id: Uuid::new_v4(),
+            realm_id,
+            token_hash: hash_token(&plaintext),
+            description: payload
+                .description
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty()),
+            expires_at: now + Duration::seconds(expires_in),
+            remaining_uses: count,
+            created_at: now,
+        };
+        self.registration_repo
+            .create_initial_access_token(&token)
+            .await?;
+        Ok((token, plaintext))
+    }
+
+    pub async fn delete_initial_access_token(&self, realm_id: Uuid, id: Uuid) -> Result<()> {
+        if !self
+            .registration_repo
+            .delete_initial_access_token(&realm_id, &id)
+            .await?
+        {
+            return Err(Error::NotFound(
+                "Initial access token not found".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// RFC 7591 registration. The metadata is checked against the realm
+    /// policy before the initial access token loses a use.
+    pub async fn register(
+        &self,
+        realm_id: Uuid,
+        initial_access_token: &str,
+        metadata: ClientMetadata,
+    ) -> Result<RegisteredClient> {
+        let token = self
+            .registration_repo
+            .find_initial_access_token(&realm_id, &hash_token(initial_access_token))
+            .await?
+            .filter(|token| token.remaining_uses > 0 && token.expires_at > Utc::now())
+            .ok_or_else(invalid_initial_access_token)?;
+
+        let policy = self.policy_for_realm(realm_id).await?;
+        let resolved = resolve_metadata(&policy, metadata)?;
+
+        if !self
+            .registration_repo
+            .consume_initial_access_token(&token.id, Utc::now())
+            .await?
+        {
+            return Err(invalid_initial_access_token());
+        }
+
+        let mut client = OidcClient {
+            id: Uuid::new_v4(),
+            realm_id,
+            client_id: Uuid::new_v4().to_string(),
+            client_secret: None,
+            redirect_uris: to_json(&resolved.redirect_uris)?,
+            scopes: to_json(&resolved.scopes)?,
+            web_origins: "[]".to_string(),
+            managed_by_config: false,
+            token_endpoint_auth_method: resolved.auth_method,
+            jwks: resolved.jwks.map(|jwks| jwks.to_string()),
+            signing_algorithm: None,
+            backchannel_logout_uri: resolved.backchannel_logout_uri,
+            frontchannel_logout_uri: resolved.frontchannel_logout_uri,
+            token_exchange_policy: None,
+            direct_grant_enabled: resolved.grant_types.iter().any(|g| g == "password"),
+            require_pushed_authorization_requests: false,
+        };
+        let secret = self.oidc_service.register_client(&mut client).await?;
+
+        let registration_access_token = generate_token();
+        let registration = ClientRegistration {
+            client_id: client.id,
+            realm_id,
+            registration_token_hash: hash_token(&registration_access_token),
+            client_name: resolved.client_name,
+            grant_types: resolved.grant_types,
+            initial_access_token_id: Some(token.id),
+            created_at: Utc::now(),
+        };
+        self.registration_repo
+            .create_registration(&registration)
+            .await?;
+        self.record(&client, "client_registered", Some(&token))
+            .await;
+
+        Ok(RegisteredClient {
+            client_secret: secret.filter(|_| uses_secret(client.token_endpoint_auth_method)),
+            client,
+            registration,
+            registration_access_token: Some(registration_access_token),
+        })
+    }
+
+    /// RFC 7592 read.
+    pub async fn read(
+        &self,
+        realm_id: Uuid,
+        client_id: &str,
+        registration_access_token: &str,
+    ) -> Result<RegisteredClient> {
+        let (client, registration) = self
+            .authorize_management(realm_id, client_id, registration_access_token)
+            .await?;
+        Ok(registered_client(client, registration))
+    }
+
+    /// RFC 7592 update: the metadata replaces what was registered.
+    pub async fn update(
+        &self,
+        realm_id: Uuid,
+        client_id: &str,
+        registration_access_token: &str,
+        metadata: ClientMetadata,
+    ) -> Result<RegisteredClient> {
+        let (client, mut registration) = self
+            .authorize_management(realm_id, client_id, registration_access_token)
+            .await?;
+        if metadata.client_id.as_deref() != Some(client_id) {
+            return Err(Error::OidcInvalidClientMetadata(
+                "client_id must match the registered client".to_string(),
+            ));
+        }
+
+        let policy = self.policy_for_realm(realm_id).await?;
+        let resolved = resolve_metadata(&policy, metadata)?;
+        let updated = self
+            .oidc_service
+            .update_client(
+                client.id,
+                UpdateClientRequest {
+                    redirect_uris: Some(resolved.redirect_uris),
+                    scopes: Some(resolved.scopes),
+                    token_endpoint_auth_method: Some(resolved.auth_method),
+                    jwks: resolved.jwks,
+                    backchannel_logout_uri: Some(
+                        resolved.backchannel_logout_uri.unwrap_or_default(),
+                    ),
+                    frontchannel_logout_uri: Some(
+                        resolved.frontchannel_logout_uri.unwrap_or_default(),
+                    ),
+                    direct_grant_enabled: Some(
+                        resolved.grant_types.iter().any(|g| g == "password"),
+                    ),
+                    ..Default::default()
+                },
+            )
+            .await?;
+
+        registration.client_name = resolved.client_name;
+        registration.grant_types = resolved.grant_types;
+        self.registration_repo
+            .update_registration(&registration)
+            .await?;
+        self.record(&updated, "client_registration_updated", None)
+            .await;
+
+        // Reload to return the decrypted secret.
+        let (client, registration) = self
+            .authorize_management(realm_id, client_id, registration_access_token)
+            .await?;
+        Ok(registered_client(client, registration))
+    }
+
+    /// RFC 7592 delete. The registration goes with the client.
+    pub async fn delete(
+        &self,
+        realm_id: Uuid,
+        client_id: &str,
+        registration_access_token: &str,
+    ) -> Result<()> {
+        let (client, _) = self
+            .authorize_management(realm_id, client_id, registration_access_token)
+            .await?;
+        self.oidc_service.delete_client(client.id).await?;
+        self.record(&client, "client_registration_deleted", None)
+            .await;
+        Ok(())
+    }
+
+    /// Loads a registered client for its registration access token. Unknown
+    /// clients, clients created another way and wrong tokens all fail the
+    /// same way, so the endpoint does not reveal which clients exist.
+    async fn authorize_management(
+        &self,
+        realm_id: Uuid,
+        client_id: &str,
+        registration_access_token: &str,
+    ) -> Result<(OidcClient, ClientRegistration)> {
+        let invalid =
+            || Error::OidcInvalidToken("Registration access token is invalid".to_string());
+        let client = self
+            .oidc_service
+            .find_client_by_client_id_with_secret(&realm_id, client_id)
+            .await?
+            .ok_or_else(invalid)?;
+        let registration = self
+            .registration_repo
+            .find_registration(&client.id)
+            .await?
+            .filter(|registration| {
+                registration.registration_token_hash == hash_token(registration_access_token)
+            })
+            .ok_or_else(invalid)?;
+        Ok((client, registration))
+    }
+
+    async fn record(
+        &self,
+        client: &OidcClient,
+        action: &str,
+        initial_access_token: Option<&InitialAccessToken>,
+    ) {
+        let event = NewAuditEvent {
+            realm_id: client.realm_id,
+            actor_user_id: None,
+            action: action.to_string(),
+            target_type: "oidc_client".to_string(),
+            target_id: Some(client.id.to_string()),
+            metadata: json!({
+                "client_id": client.client_id,
+                "initial_access_token_id": initial_access_token.map(|token| token.id),
+            }),
+        };
+        if let Err(err) = self.audit_service.record(event).await {
+            error!("Failed to write client registration audit event: {:?}", err);
+        }
+    }
+
+    async fn ensure_realm_exists(&self, realm_id: &Uuid) -> Result<()> {
+        if self.realm_repo.find_by_id(realm_id).await?.is_none() {
+            return Err(Error::RealmNotFound(realm_id.to_string()));
+        }
+        Ok(())
+    }
+}
+
+fn registered_client(client: OidcClient, registration: ClientRegistration) -> RegisteredClient {
+    let client_secret = client
+        .client_secret
+        .clone()
+        .filter(|_| uses_secret(client.token_endpoint_auth_method));
+    RegisteredClient {
+        client,
+        registration,
+        client_secret,
+        registration_access_token: None,
+    }
+}
+
+/// Applies the RFC 7591 defaults and checks the metadata against `policy`.
+fn resolve_metadata(
+    policy: &ClientRegistrationPolicy,
+    metadata: ClientMetadata,
+) -> Result<ResolvedMetadata> {
+    let auth_method = match metadata.token_endpoint_auth_method.as_deref() {
+        None => TokenEndpointAuthMethod::ClientSecretBasic,
+        Some(method) => TokenEndpointAuthMethod::ALL
+            .into_iter()
+            .find(|candidate| candidate.as_str() == method)
+            .ok_or_else(|| {
+                Error::OidcInvalidClientMetadata(format!(
+                    "Unsupported token_endpoint_auth_method '{}'",
+                    method
+                ))
+            })?,
+    };
+
+    let grant_types = dedupe(
+        metadata
+            .grant_types
+            .unwrap_or_else(|| vec!["authorization_code".to_string()]),
+    );
+    for grant_type in &grant_types {
+        if !REGISTRABLE_GRANT_TYPES.contains(&grant_type.as_str()) {
+            return Err(Error::OidcInvalidClientMetadata(format!(
+                "Unsupported grant type '{}'",
+                grant_type
+            )));
+        }
+        if !policy.allows_grant_type(grant_type) {
+            return Err(Error::OidcInvalidClientMetadata(format!(
+                "Grant type '{}' is not allowed in this realm",
+                grant_type
+            )));
+        }
+        if auth_method == TokenEndpointAuthMethod::None
+            && CONFIDENTIAL_GRANT_TYPES.contains(&grant_type.as_str())
+        {
+            return Err(Error::OidcInvalidClientMetadata(format!(
+                "Grant type '{}' requires client authentication",
+                grant_type
+            )));
+        }
+    }
+
+    let uses_code = grant_types.iter().any(|g| g == "authorization_code");
+    let response_types = metadata.response_types.unwrap_or_else(|| {
+        if uses_code {
+            vec!["code".to_string()]
+        } else {
+            Vec::new()
+        }
+    });
+    if response_types
+        .iter()
+        .any(|response_type| response_type != "code")
+    {
+        return Err(Error::OidcInvalidClientMetadata(
+            "Only the 'code' response type is supported".to_string(),
+        ));
+    }
+    if uses_code == response_types.is_empty() {
+        return Err(Error::OidcInvalidClientMetadata(
+            "The 'code' response type and 'authorization_code' grant go together".to_string(),
+        ));
+    }
+
+    if uses_code && metadata.redirect_uris.is_empty() {
+        return Err(Error::OidcInvalidRedirect(
+            "redirect_uris is required for the authorization_code grant".to_string(),
+        ));
+    }
+    for uri in &metadata.redirect_uris {
+        if let Some(reason) = policy.check_redirect_uri(uri) {
+            return Err(Error::OidcInvalidRedirect(reason));
+        }
+    }
+
+    let scopes = metadata
+        .scope
+        .as_deref()
+        .unwrap_or("openid")
+        .split_whitespace()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+
+    Ok(ResolvedMetadata {
+        redirect_uris: dedupe(metadata.redirect_uris),
+        auth_method,
+        grant_types,
+        scopes: dedupe(scopes),
+        client_name: metadata
+            .client_name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty()),
+        jwks: metadata.jwks,
+        backchannel_logout_uri: metadata.backchannel_logout_uri,
+        frontchannel_logout_uri: metadata.frontchannel_logout_uri,
+    })
+}
+
+/// Host patterns are bare host names, optionally with a leading `*.`.
+fn normalize_host_patterns(hosts: Vec<String>) -> Result<Vec<String>> {
+    let hosts = hosts
+        .into_iter()
+        .map(|host| host.trim().to_ascii_lowercase())
+        .filter(|host| !host.is_empty())
+        .collect::<Vec<_>>();
+    for host in &hosts {
+        let name = host.strip_prefix("*.").unwrap_or(host);
+        if name.is_empty() || name.contains(['/', ':', '*', '?', '#', '@']) {
+            return Err(Error::Validation(format!(
+                "'{}' is not a host name or *.domain pattern",
+                host
+            )));
+        }
+    }
+    Ok(dedupe(hosts))
+}
+
+fn uses_secret(method: TokenEndpointAuthMethod) -> bool {
+    matches!(
+        method,
+        TokenEndpointAuthMethod::ClientSecretBasic | TokenEndpointAuthMethod::ClientSecretPost
+    )
+}
+
+fn invalid_initial_access_token() -> Error {
+    Error::OidcInvalidToken("Initial access token is invalid or expired".to_string())
+}
+
+fn dedupe(values: Vec<String>) -> Vec<String> {
+    let mut unique = Vec::with_capacity(values.len());
+    for value in values {
+        if !unique.contains(&value) {
+            unique.push(value);
+        }
+    }
+    unique
+}
+
+fn to_json(values: &[String]) -> Result<String> {
+    serde_json::to_string(values).map_err(|e| Error::Unexpected(e.into()))
+}
+
+fn generate_token() -> String {
+    Alphanumeric.sample_string(&mut rand::rng(), 48)
+}
+
+fn hash_token(token: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(token.as_bytes());
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(hasher.finalize())
+}
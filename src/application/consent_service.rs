@@ -0,0 +1,108 @@
+use crate::application::audit_service::AuditService;
+use crate::application::logout_service::LogoutService;
+use crate::domain::audit::NewAuditEvent;
+use crate::domain::consent_grant::ConsentGrant;
+use crate::error::{Error, Result};
+use crate::ports::consent_repository::ConsentRepository;
+use serde_json::json;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// The consent ledger. The consent node records what a user approved for a
+/// client; the account and admin APIs list and revoke those grants.
+pub struct ConsentService {
+    repo: Arc<dyn ConsentRepository>,
+    logout_service: Arc<LogoutService>,
+    audit_service: Arc<AuditService>,
+}
+
+impl ConsentService {
+    pub fn new(
+        repo: Arc<dyn ConsentRepository>,
+        logout_service: Arc<LogoutService>,
+        audit_service: Arc<AuditService>,
+    ) -> Self {
+        Self {
+            repo,
+            logout_service,
+            audit_service,
+        }
+    }
+
+    /// True when the user already approved every requested scope.
+    pub async fn is_granted(
+        &self,
+        realm_id: Uuid,
+        user_id: Uuid,
+        client_id: &str,
+        scopes: &[String],
+    ) -> Result<bool> {
+        Ok(self
+            .repo
+            .find(&realm_id, &user_id, client_id)
+            .await?
+            .is_some_and(|grant| grant.covers(scopes)))
+    }
+
+    /// Adds `scopes` to the user's grant for the client, creating it if
+    /// needed.
+    pub async fn record_grant(
+        &self,
+        realm_id: Uuid,
+        user_id: Uuid,
+        client_id: &str,
+        scopes: &[String],
+    ) -> Result<ConsentGrant> {
+        let grant = match self.repo.find(&realm_id, &user_id, client_id).await? {
+            Some(mut grant) => {
+                grant.extend(scopes);
+                grant
+            }
+            None => ConsentGrant::new(realm_id, user_id, client_id.to_string(), scopes),
+        };
+        self.repo.save(&grant).await?;
+        Ok(grant)
+    }
+
+    pub async fn list_grants(&self, realm_id: Uuid, user_id: Uuid) -> Result<Vec<ConsentGrant>> {
+        self.repo.list_for_user(&realm_id, &user_id).await
+    }
+
+    /// Revokes the client's refresh-token families for the user, then deletes
+    /// the grant, so the next sign-in asks for consent again. The grant goes
+    /// last: if revocation fails it is still there for a retry.
+    pub async fn revoke_grant(
+        &self,
+        realm_id: Uuid,
+        actor_user_id: Option<Uuid>,
+        user_id: Uuid,
+        client_id: &str,
+    ) -> Result<()> {
+        if self
+            .repo
+            .find(&realm_id, &user_id, client_id)
+            .await?
+            .is_none()
+        {
+            return Err(Error::NotFound("Consent grant not found".to_string()));
+        }
+        self.logout_service
+            .revoke_for_client(realm_id, user_id, client_id)
+            .await?;
+        if !self.repo.delete(&realm_id, &user_id, client_id).await? {
+            return Err(Error::NotFound("Consent grant not found".to_string()));
+        }
+
+        self.audit_service
+            .record(NewAuditEvent {
+                realm_id,
+                actor_user_id,
+                action: "consent_revoked".to_string(),
+                target_type: "user".to_string(),
+                target_id: Some(user_id.to_string()),
+                metadata: json!({ "client_id": client_id }),
+            })
+            .await?;
+        Ok(())
+    }
+}
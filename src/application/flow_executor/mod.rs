@@ -11,12 +11,15 @@ use crate::application::runtime_registry::RuntimeRegistry;
 use crate::domain::audit::NewAuditEvent;
 use crate::domain::auth_session::{AuthenticationSession, SessionStatus};
 use crate::domain::auth_session_action::AuthSessionAction;
+use crate::domain::events::{DomainEvent, LoginFailed, LoginSucceeded};
 use crate::domain::execution::lifecycle::NodeOutcome;
 use crate::domain::execution::{ExecutionNode, ExecutionPlan, ExecutionResult, StepType};
 use crate::domain::flow::signal::FlowSignal;
 use crate::error::{Error, Result};
 use crate::ports::auth_session_action_repository::AuthSessionActionRepository;
 use crate::ports::auth_session_repository::AuthSessionRepository;
+use crate::ports::event_bus::EventPublisher;
+use crate::ports::flow_repository::FlowRepository;
 use crate::ports::flow_store::FlowStore;
 use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use base64::Engine;
@@ -27,10 +30,12 @@ use tracing::{info_span, instrument, Instrument};
 pub struct FlowExecutor {
     session_repo: Arc<dyn AuthSessionRepository>,
     flow_store: Arc<dyn FlowStore>,
+    flow_repo: Arc<dyn FlowRepository>,
     registry: Arc<RuntimeRegistry>,
     action_repo: Arc<dyn AuthSessionActionRepository>,
     email_delivery: Option<Arc<EmailDeliveryService>>,
     audit_service: Option<Arc<AuditService>>,
+    event_publisher: Option<Arc<dyn EventPublisher>>,
 }
 
 #[derive(Debug, Clone, Copy, Serialize)]
@@ -45,6 +50,17 @@ const SUBFLOW_STACK_KEY: &str = "subflow_stack";
 const SUBFLOW_RESULT_KEY: &str = "subflow_result";
 const MAX_SUBFLOW_DEPTH: usize = 8;
 
+/// Authenticators whose rejections are failed sign-in attempts. Other nodes
+/// reject form input (registration, password reset, consent) and are not
+/// reported as `login.failed`.
+const CREDENTIAL_AUTHENTICATORS: &[&str] = &[
+    "core.auth.password",
+    "core.auth.passkey_assert",
+    "core.auth.totp_verify",
+    "core.auth.verify_email_otp",
+    "core.auth.verify_sms_otp",
+];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct SubflowFrame {
     parent_flow_version_id: Uuid,
@@ -57,21 +73,26 @@ enum SignalDispatchTarget {
 }
 
 impl FlowExecutor {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         session_repo: Arc<dyn AuthSessionRepository>,
         flow_store: Arc<dyn FlowStore>,
+        flow_repo: Arc<dyn FlowRepository>,
         registry: Arc<RuntimeRegistry>,
         action_repo: Arc<dyn AuthSessionActionRepository>,
         email_delivery: Option<Arc<EmailDeliveryService>>,
         audit_service: Option<Arc<AuditService>>,
+        event_publisher: Option<Arc<dyn EventPublisher>>,
     ) -> Self {
         Self {
             session_repo,
             flow_store,
+            flow_repo,
             registry,
             action_repo,
             email_delivery,
             audit_service,
+            event_publisher,
         }
     }
 
@@ -228,7 +249,11 @@ impl FlowExecutor {
                         restore_signal_context(&mut session, previous_signal);
                         session.current_node_id = next_id.clone();
                     }
-                    NodeOutcome::Reject { .. } => {
+                    NodeOutcome::Reject { error } => {
+                        if CREDENTIAL_AUTHENTICATORS.contains(&worker_key) {
+                            self.publish_login_failed(&session, Some(worker_key), &error)
+                                .await;
+                        }
                         let exec_span = info_span!(
                             "flow.node.execute",
                             telemetry = "span",
@@ -425,6 +450,7 @@ impl FlowExecutor {
                         }
                         session.status = SessionStatus::Completed;
                         self.session_repo.update(&session).await?;
+                        self.publish_login_succeeded(&session).await;
                         return Ok(ExecutionResult::Success {
                             redirect_url: "/".to_string(),
                         });
@@ -436,6 +462,7 @@ impl FlowExecutor {
                         }
                         session.status = SessionStatus::Failed;
                         self.session_repo.update(&session).await?;
+                        self.publish_login_failed(&session, None, &reason).await;
                         return Ok(ExecutionResult::Failure { reason });
                     }
                     _ => {
@@ -496,12 +523,13 @@ impl FlowExecutor {
                         return if is_failure {
                             session.status = SessionStatus::Failed;
                             self.session_repo.update(&session).await?;
-                            Ok(ExecutionResult::Failure {
-                                reason: "Access Denied".into(),
-                            })
+                            let reason = "Access Denied".to_string();
+                            self.publish_login_failed(&session, None, &reason).await;
+                            Ok(ExecutionResult::Failure { reason })
                         } else {
                             session.status = SessionStatus::Completed;
                             self.session_repo.update(&session).await?;
+                            self.publish_login_succeeded(&session).await;
                             Ok(ExecutionResult::Success {
                                 redirect_url: "/".into(),
                             })
@@ -513,6 +541,74 @@ impl FlowExecutor {
         }
     }
 
+    /// Publishes `login.succeeded` for a browser or direct-grant flow that
+    /// completed with a user. Registration, reset-credentials and device
+    /// verification sessions also finish with a user but are not logins.
+    async fn publish_login_succeeded(&self, session: &AuthenticationSession) {
+        let (Some(publisher), Some(user_id)) = (&self.event_publisher, session.user_id) else {
+            return;
+        };
+        if session.context.get("device").is_some() || !self.is_login_flow(session).await {
+            return;
+        }
+        publisher
+            .publish(DomainEvent::LoginSucceeded(LoginSucceeded {
+                realm_id: session.realm_id,
+                user_id,
+                username: context_username(session),
+                auth_session_id: session.id,
+            }))
+            .await;
+    }
+
+    /// Resolves the session's flow type from the runtime flow its version
+    /// belongs to.
+    async fn is_login_flow(&self, session: &AuthenticationSession) -> bool {
+        let flow_type = async {
+            let version = self
+                .flow_store
+                .get_version(&session.flow_version_id)
+                .await?;
+            let Some(flow_id) = version.and_then(|v| Uuid::parse_str(&v.flow_id).ok()) else {
+                return Ok(None);
+            };
+            let flow = self.flow_repo.find_flow_by_id(&flow_id).await?;
+            Ok::<_, Error>(flow.map(|f| f.r#type))
+        }
+        .await;
+        match flow_type {
+            Ok(Some(flow_type)) => matches!(flow_type.as_str(), "browser" | "direct"),
+            Ok(None) => false,
+            Err(err) => {
+                tracing::warn!("Failed to resolve flow type for login event: {}", err);
+                false
+            }
+        }
+    }
+
+    /// Publishes `login.failed` for a rejected credential (`authenticator`
+    /// names the node's worker) or a flow that denied access.
+    async fn publish_login_failed(
+        &self,
+        session: &AuthenticationSession,
+        authenticator: Option<&str>,
+        reason: &str,
+    ) {
+        let Some(publisher) = &self.event_publisher else {
+            return;
+        };
+        publisher
+            .publish(DomainEvent::LoginFailed(LoginFailed {
+                realm_id: session.realm_id,
+                user_id: session.user_id,
+                username: context_username(session),
+                auth_session_id: session.id,
+                authenticator: authenticator.map(str::to_string),
+                reason: reason.to_string(),
+            }))
+            .await;
+    }
+
     pub async fn resume_action(
         &self,
         realm_id: Uuid,
@@ -1261,10 +1357,13 @@ fn resolve_template_key(config: &Value) -> Option<String> {
         Some("core.auth.password") => Some("login".to_string()),
         Some("core.auth.passkey_assert") => Some("passkey_assert".to_string()),
         Some("core.auth.passkey_enroll") => Some("passkey_enroll".to_string()),
+        Some("core.auth.totp_enroll") => Some("totp_enroll".to_string()),
+        Some("core.auth.totp_verify") => Some("mfa".to_string()),
         Some("core.auth.register") => Some("register".to_string()),
         Some("core.auth.forgot_credentials") => Some("forgot_credentials".to_string()),
         Some("core.auth.reset_password") => Some("reset_password".to_string()),
         Some("core.auth.verify_email_otp") => Some("verify_email".to_string()),
+        Some("core.auth.verify_sms_otp") => Some("sms_otp".to_string()),
         Some("core.auth.collect_idp_choice") => Some("oauth_select".to_string()),
         Some("core.auth.oauth_idp") => Some("oauth_redirecting".to_string()),
         Some("core.auth.otp") => Some("mfa".to_string()),
@@ -1287,6 +1386,14 @@ fn hash_identifier(identifier: &str) -> String {
     URL_SAFE_NO_PAD.encode(result)
 }
 
+fn context_username(session: &AuthenticationSession) -> Option<String> {
+    session
+        .context
+        .get("username")
+        .and_then(|value| value.as_str())
+        .map(str::to_string)
+}
+
 fn clear_pending_action(session: &mut AuthenticationSession) {
     if let serde_json::Value::Object(ref mut map) = session.context {
         map.remove("pending_action_id");
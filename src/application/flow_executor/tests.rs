@@ -1,8 +1,10 @@
 use super::FlowExecutor;
 use crate::adapters::auth::subflow_node::SubflowNode;
 use crate::application::runtime_registry::RuntimeRegistry;
+use crate::domain::auth_flow::AuthFlow;
 use crate::domain::auth_session::{AuthenticationSession, SessionStatus};
 use crate::domain::auth_session_action::AuthSessionAction;
+use crate::domain::events::DomainEvent;
 use crate::domain::execution::lifecycle::{LifecycleNode, NodeOutcome};
 use crate::domain::execution::{ExecutionNode, ExecutionPlan, ExecutionResult, StepType};
 use crate::domain::flow::models::{FlowDeployment, FlowDraft, FlowVersion};
@@ -10,6 +12,8 @@ use crate::domain::pagination::{PageRequest, PageResponse};
 use crate::error::{Error, Result};
 use crate::ports::auth_session_action_repository::AuthSessionActionRepository;
 use crate::ports::auth_session_repository::AuthSessionRepository;
+use crate::ports::event_bus::EventPublisher;
+use crate::ports::flow_repository::FlowRepository;
 use crate::ports::flow_store::FlowStore;
 use crate::ports::transaction_manager::Transaction;
 use async_trait::async_trait;
@@ -116,6 +120,58 @@ impl AuthSessionActionRepository for TestAuthSessionActionRepo {
     }
 }
 
+#[derive(Default)]
+struct TestEventPublisher {
+    published: Mutex<Vec<DomainEvent>>,
+}
+
+impl TestEventPublisher {
+    fn published(&self) -> Vec<DomainEvent> {
+        self.published.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl EventPublisher for TestEventPublisher {
+    async fn publish(&self, event: DomainEvent) {
+        self.published.lock().unwrap().push(event);
+    }
+}
+
+#[derive(Default)]
+struct TestFlowRepo {
+    flows: Mutex<HashMap<Uuid, AuthFlow>>,
+}
+
+impl TestFlowRepo {
+    fn insert_flow(&self, flow: AuthFlow) {
+        self.flows.lock().unwrap().insert(flow.id, flow);
+    }
+}
+
+#[async_trait]
+impl FlowRepository for TestFlowRepo {
+    async fn find_flow_by_name(&self, _realm_id: &Uuid, _name: &str) -> Result<Option<AuthFlow>> {
+        Ok(None)
+    }
+
+    async fn find_flow_by_id(&self, flow_id: &Uuid) -> Result<Option<AuthFlow>> {
+        Ok(self.flows.lock().unwrap().get(flow_id).cloned())
+    }
+
+    async fn create_flow<'a>(
+        &self,
+        _flow: &AuthFlow,
+        _tx: Option<&'a mut dyn Transaction>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn list_flows_by_realm(&self, _realm_id: &Uuid) -> Result<Vec<AuthFlow>> {
+        Ok(Vec::new())
+    }
+}
+
 #[derive(Default)]
 struct TestFlowStore {
     versions: Mutex<HashMap<Uuid, FlowVersion>>,
@@ -343,7 +399,16 @@ fn new_executor(
     registry: Arc<RuntimeRegistry>,
 ) -> FlowExecutor {
     let action_repo = Arc::new(TestAuthSessionActionRepo::default());
-    FlowExecutor::new(repo, flow_store, registry, action_repo, None, None)
+    FlowExecutor::new(
+        repo,
+        flow_store,
+        Arc::new(TestFlowRepo::default()),
+        registry,
+        action_repo,
+        None,
+        None,
+        None,
+    )
 }
 
 fn hash_action_token(token: &str) -> String {
@@ -923,6 +988,76 @@ async fn execute_terminal_success_sets_status() {
     assert_eq!(updates.last().unwrap().status, SessionStatus::Completed);
 }
 
+#[tokio::test]
+async fn execute_publishes_login_succeeded_only_for_login_flows() {
+    let cases = [
+        ("browser", json!({}), true),
+        ("direct", json!({}), true),
+        ("registration", json!({}), false),
+        ("reset", json!({}), false),
+        (
+            "browser",
+            json!({ "device": { "user_code": "ABCD-EFGH" } }),
+            false,
+        ),
+    ];
+
+    for (flow_type, context, expected) in cases {
+        let realm_id = Uuid::new_v4();
+        let version_id = Uuid::new_v4();
+        let plan = build_plan(
+            "terminal",
+            vec![ExecutionNode {
+                id: "terminal".to_string(),
+                step_type: StepType::Terminal,
+                next: HashMap::new(),
+                config: json!({}),
+            }],
+        );
+        let version = build_version(version_id, &plan);
+
+        let flow_repo = Arc::new(TestFlowRepo::default());
+        flow_repo.insert_flow(AuthFlow {
+            id: Uuid::parse_str(&version.flow_id).unwrap(),
+            realm_id,
+            name: flow_type.to_string(),
+            alias: flow_type.to_string(),
+            description: None,
+            r#type: flow_type.to_string(),
+            built_in: true,
+        });
+        let flow_store = Arc::new(TestFlowStore::default());
+        flow_store.insert_version(version_id, version);
+
+        let mut session = AuthenticationSession::new(realm_id, version_id, "terminal".to_string());
+        session.user_id = Some(Uuid::new_v4());
+        session.context = context;
+        let session_id = session.id;
+        let repo = Arc::new(TestAuthSessionRepo::default());
+        repo.insert(session);
+
+        let publisher = Arc::new(TestEventPublisher::default());
+        let executor = FlowExecutor::new(
+            repo,
+            flow_store,
+            flow_repo,
+            Arc::new(RuntimeRegistry::new()),
+            Arc::new(TestAuthSessionActionRepo::default()),
+            None,
+            None,
+            Some(publisher.clone()),
+        );
+        let result = executor.execute(session_id, None).await.unwrap();
+        assert!(matches!(result, ExecutionResult::Success { .. }));
+
+        let published = publisher
+            .published()
+            .iter()
+            .any(|event| matches!(event, DomainEvent::LoginSucceeded(_)));
+        assert_eq!(published, expected, "flow type {}", flow_type);
+    }
+}
+
 #[tokio::test]
 async fn execute_errors_when_worker_missing_for_input() {
     let realm_id = Uuid::new_v4();
@@ -1060,10 +1195,12 @@ async fn execute_returns_awaiting_action_and_stores_action() {
     let executor = FlowExecutor::new(
         repo.clone(),
         flow_store,
+        Arc::new(TestFlowRepo::default()),
         Arc::new(registry),
         action_repo.clone(),
         None,
         None,
+        None,
     );
 
     let result = executor
@@ -1018,18 +1018,30 @@ mod tests {
                 .filter(|settings| &settings.realm_id == realm_id))
         }
 
-        async fn upsert(&self, _settings: &RealmPasskeySettings) -> Result<()> {
+        async fn upsert(
+            &self,
+            _settings: &RealmPasskeySettings,
+            _tx: Option<&mut dyn Transaction>,
+        ) -> Result<()> {
             Ok(())
         }
     }
 
     #[async_trait]
     impl IdentityProviderRepository for TestIdentityProviderRepo {
-        async fn create(&self, _provider: &IdentityProvider) -> Result<()> {
+        async fn create(
+            &self,
+            _provider: &IdentityProvider,
+            _tx: Option<&mut dyn Transaction>,
+        ) -> Result<()> {
             Ok(())
         }
 
-        async fn update(&self, _provider: &IdentityProvider) -> Result<()> {
+        async fn update(
+            &self,
+            _provider: &IdentityProvider,
+            _tx: Option<&mut dyn Transaction>,
+        ) -> Result<()> {
             Ok(())
         }
 
@@ -1058,7 +1070,7 @@ mod tests {
                 .collect())
         }
 
-        async fn delete(&self, _id: &Uuid) -> Result<()> {
+        async fn delete(&self, _id: &Uuid, _tx: Option<&mut dyn Transaction>) -> Result<()> {
             Ok(())
         }
     }
@@ -1245,6 +1257,9 @@ mod tests {
             metadata_cache_json: None,
             jwks_cached_at: None,
             jwks_cache_json: None,
+            saml_metadata_url: None,
+            saml_signing_certificates_json: "[]".to_string(),
+            saml_name_id_format: None,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
         }
@@ -3,7 +3,7 @@ use crate::application::harbor::types::{
     ConflictPolicy, ExportPolicy, HarborImportResourceResult, HarborResourceBundle, HarborScope,
 };
 use crate::application::oidc_service::OidcService;
-use crate::domain::oidc::OidcClient;
+use crate::domain::oidc::{OidcClient, TokenEndpointAuthMethod};
 use crate::error::{Error, Result};
 use crate::ports::transaction_manager::Transaction;
 use async_trait::async_trait;
@@ -239,6 +239,36 @@ impl HarborProvider for ClientHarborProvider {
         )
         .await
     }
+
+    async fn delete(
+        &self,
+        realm_id: Uuid,
+        scope: &HarborScope,
+        mut tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        let client_id = match scope {
+            HarborScope::Client { client_id } => client_id,
+            _ => {
+                return Err(Error::Validation(
+                    "Client delete requires client scope".to_string(),
+                ))
+            }
+        };
+
+        let client = self
+            .oidc_service
+            .find_client_by_client_id_with_tx(&realm_id, client_id, tx.as_deref_mut())
+            .await?
+            .ok_or_else(|| Error::OidcClientNotFound(client_id.clone()))?;
+        if client.managed_by_config {
+            return Err(Error::Validation(format!(
+                "Client '{}' is managed by configuration and cannot be deleted",
+                client_id
+            )));
+        }
+
+        self.oidc_service.delete_client_with_tx(client.id, tx).await
+    }
 }
 
 fn apply_client_payload(
@@ -317,6 +347,14 @@ async fn import_new_client(
         scopes: "[]".to_string(),
         web_origins: "[]".to_string(),
         managed_by_config: false,
+        token_endpoint_auth_method: TokenEndpointAuthMethod::None,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: None,
+        frontchannel_logout_uri: None,
+        token_exchange_policy: None,
+        direct_grant_enabled: false,
+        require_pushed_authorization_requests: false,
     };
 
     apply_client_payload(&mut client, &payload, false)?;
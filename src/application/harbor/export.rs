@@ -6,6 +6,7 @@ use crate::domain::flow::models::FlowDraft;
 use crate::domain::harbor_job::HarborJob;
 use crate::domain::harbor_job_conflict::HarborJobConflict;
 use crate::domain::pagination::PageRequest;
+use crate::domain::rbac::GroupTreeRow;
 use crate::domain::role::Role;
 use crate::domain::user::User;
 use crate::error::{Error, Result};
@@ -140,6 +141,38 @@ impl HarborService {
         job_id: Option<Uuid>,
     ) -> Result<HarborBundle> {
         let selection = normalize_export_selection(selection)?;
+        let resources = self
+            .export_full_resources(realm_id, policy, &selection, job_id)
+            .await?;
+
+        if resources.is_empty() {
+            return Err(Error::Validation(
+                "Full realm export selection produced no resources".to_string(),
+            ));
+        }
+
+        Ok(HarborBundle {
+            manifest: HarborManifest {
+                version: super::service::HARBOR_BUNDLE_VERSION.to_string(),
+                schema_version: super::service::HARBOR_SCHEMA_VERSION,
+                exported_at: Utc::now().to_rfc3339(),
+                source_realm: source_realm.to_string(),
+                export_type: HarborScope::FullRealm.export_type(),
+                selection: Some(selection),
+            },
+            resources,
+        })
+    }
+
+    /// Exports every resource of the selected kinds. An empty result is not an
+    /// error here; reconcile diffs against realms that have nothing yet.
+    pub(crate) async fn export_full_resources(
+        &self,
+        realm_id: Uuid,
+        policy: ExportPolicy,
+        selection: &[String],
+        job_id: Option<Uuid>,
+    ) -> Result<Vec<HarborResourceBundle>> {
         let mut resources = Vec::new();
         let mut total = 0usize;
 
@@ -173,10 +206,35 @@ impl HarborService {
             total += flow_ids.len();
         }
 
+        let mut groups = Vec::new();
+        if selection.iter().any(|key| key == "group") {
+            groups = self.list_all_group_roots(realm_id).await?;
+            total += groups.len();
+        }
+
+        let mut identity_providers = Vec::new();
+        if selection.iter().any(|key| key == "identity_provider") {
+            identity_providers = self
+                .identity_provider_service
+                .list_by_realm(realm_id)
+                .await?;
+            total += identity_providers.len();
+        }
+
+        let mut webhooks = Vec::new();
+        if selection.iter().any(|key| key == "webhook") {
+            webhooks = self.webhook_service.list_endpoints(realm_id).await?;
+            total += webhooks.len();
+        }
+
         if selection.iter().any(|key| key == "realm") {
             total += 1;
         }
 
+        if selection.iter().any(|key| key == "realm_settings") {
+            total += 1;
+        }
+
         if let Some(job_id) = job_id {
             self.try_update_job_total(job_id, total as i64).await;
         }
@@ -265,6 +323,57 @@ impl HarborService {
             }
         }
 
+        if selection.iter().any(|key| key == "group") {
+            let provider = self
+                .registry
+                .get("group")
+                .ok_or_else(|| Error::Validation("Group provider not registered".to_string()))?;
+            for group in groups {
+                let scope = HarborScope::Group { group_id: group.id };
+                let resource = provider.export(realm_id, &scope, policy).await?;
+                resources.push(resource);
+                processed += 1;
+                if let Some(job_id) = job_id {
+                    self.try_update_job_progress(job_id, processed, 0, 0).await;
+                }
+            }
+        }
+
+        if selection.iter().any(|key| key == "identity_provider") {
+            let provider = self.registry.get("identity_provider").ok_or_else(|| {
+                Error::Validation("Identity provider provider not registered".to_string())
+            })?;
+            for identity_provider in identity_providers {
+                let scope = HarborScope::IdentityProvider {
+                    alias: identity_provider.alias,
+                };
+                let resource = provider.export(realm_id, &scope, policy).await?;
+                resources.push(resource);
+                processed += 1;
+                if let Some(job_id) = job_id {
+                    self.try_update_job_progress(job_id, processed, 0, 0).await;
+                }
+            }
+        }
+
+        if selection.iter().any(|key| key == "webhook") {
+            let provider = self
+                .registry
+                .get("webhook")
+                .ok_or_else(|| Error::Validation("Webhook provider not registered".to_string()))?;
+            for webhook in webhooks {
+                let scope = HarborScope::Webhook {
+                    endpoint_id: webhook.endpoint.id,
+                };
+                let resource = provider.export(realm_id, &scope, policy).await?;
+                resources.push(resource);
+                processed += 1;
+                if let Some(job_id) = job_id {
+                    self.try_update_job_progress(job_id, processed, 0, 0).await;
+                }
+            }
+        }
+
         if selection.iter().any(|key| key == "realm") {
             let provider = self
                 .registry
@@ -280,23 +389,21 @@ impl HarborService {
             }
         }
 
-        if resources.is_empty() {
-            return Err(Error::Validation(
-                "Full realm export selection produced no resources".to_string(),
-            ));
+        if selection.iter().any(|key| key == "realm_settings") {
+            let provider = self.registry.get("realm_settings").ok_or_else(|| {
+                Error::Validation("Realm settings provider not registered".to_string())
+            })?;
+            let resource = provider
+                .export(realm_id, &HarborScope::FullRealm, policy)
+                .await?;
+            resources.push(resource);
+            processed += 1;
+            if let Some(job_id) = job_id {
+                self.try_update_job_progress(job_id, processed, 0, 0).await;
+            }
         }
 
-        Ok(HarborBundle {
-            manifest: HarborManifest {
-                version: super::service::HARBOR_BUNDLE_VERSION.to_string(),
-                schema_version: super::service::HARBOR_SCHEMA_VERSION,
-                exported_at: Utc::now().to_rfc3339(),
-                source_realm: source_realm.to_string(),
-                export_type: HarborScope::FullRealm.export_type(),
-                selection: Some(selection),
-            },
-            resources,
-        })
+        Ok(resources)
     }
 
     pub(crate) async fn list_all_clients(
@@ -417,6 +524,30 @@ impl HarborService {
         Ok(roles)
     }
 
+    pub(crate) async fn list_all_group_roots(&self, realm_id: Uuid) -> Result<Vec<GroupTreeRow>> {
+        let mut groups = Vec::new();
+        let mut page = 1;
+        loop {
+            let response = self
+                .rbac_service
+                .list_group_roots(
+                    realm_id,
+                    PageRequest {
+                        page,
+                        per_page: 200,
+                        ..PageRequest::default()
+                    },
+                )
+                .await?;
+            groups.extend(response.data);
+            if response.meta.page >= response.meta.total_pages {
+                break;
+            }
+            page += 1;
+        }
+        Ok(groups)
+    }
+
     pub(crate) async fn list_all_users(&self, realm_id: Uuid) -> Result<Vec<User>> {
         let mut users = Vec::new();
         let mut page = 1;
@@ -469,9 +600,25 @@ impl HarborService {
                 if selection.iter().any(|key| key == "flow") {
                     total += self.list_all_flow_ids_for_export(realm_id).await?.len() as i64;
                 }
+                if selection.iter().any(|key| key == "group") {
+                    total += self.list_all_group_roots(realm_id).await?.len() as i64;
+                }
+                if selection.iter().any(|key| key == "identity_provider") {
+                    total += self
+                        .identity_provider_service
+                        .list_by_realm(realm_id)
+                        .await?
+                        .len() as i64;
+                }
+                if selection.iter().any(|key| key == "webhook") {
+                    total += self.webhook_service.list_endpoints(realm_id).await?.len() as i64;
+                }
                 if selection.iter().any(|key| key == "realm") {
                     total += 1;
                 }
+                if selection.iter().any(|key| key == "realm_settings") {
+                    total += 1;
+                }
                 Ok(total)
             }
             _ => Ok(1),
@@ -622,6 +769,38 @@ impl HarborService {
             }
         };
 
+        let group_provider = match self.registry.get("group") {
+            Some(provider) => Some(provider),
+            None => {
+                warnings.push("Group provider not registered".to_string());
+                None
+            }
+        };
+
+        let identity_provider_provider = match self.registry.get("identity_provider") {
+            Some(provider) => Some(provider),
+            None => {
+                warnings.push("Identity provider provider not registered".to_string());
+                None
+            }
+        };
+
+        let webhook_provider = match self.registry.get("webhook") {
+            Some(provider) => Some(provider),
+            None => {
+                warnings.push("Webhook provider not registered".to_string());
+                None
+            }
+        };
+
+        let realm_settings_provider = match self.registry.get("realm_settings") {
+            Some(provider) => Some(provider),
+            None => {
+                warnings.push("Realm settings provider not registered".to_string());
+                None
+            }
+        };
+
         let mut theme_ids_by_name = HashMap::new();
         let mut theme_cache_by_name = HashMap::new();
         if theme_provider.is_some() {
@@ -778,6 +957,93 @@ impl HarborService {
             results.push(result);
         }
 
+        for resource in bundle.resources.iter().filter(|r| r.key == "group") {
+            let Some(provider) = group_provider.as_ref() else {
+                continue;
+            };
+
+            let mut resource = resource.clone();
+            if !client_id_map.is_empty() {
+                rewrite_group_role_client_ids(&mut resource.data, &client_id_map);
+            }
+            if !role_ref_map.is_empty() {
+                rewrite_group_role_refs(&mut resource.data, &role_ref_map);
+            }
+
+            let group_id = resource
+                .data
+                .get("group_id")
+                .and_then(|value| value.as_str())
+                .and_then(|value| Uuid::parse_str(value).ok())
+                .unwrap_or_else(Uuid::new_v4);
+
+            let scope = HarborScope::Group { group_id };
+
+            let result = provider
+                .import(
+                    realm_id,
+                    &scope,
+                    &resource,
+                    conflict_policy,
+                    false,
+                    tx.as_deref_mut(),
+                )
+                .await?;
+
+            self.record_import_progress(
+                job_id,
+                persist_job_updates,
+                &mut progress,
+                &result,
+                conflict_policy,
+            )
+            .await;
+            results.push(result);
+        }
+
+        for resource in bundle
+            .resources
+            .iter()
+            .filter(|r| r.key == "identity_provider")
+        {
+            let Some(provider) = identity_provider_provider.as_ref() else {
+                continue;
+            };
+
+            let alias = resource
+                .data
+                .get("alias")
+                .and_then(|value| value.as_str())
+                .ok_or_else(|| {
+                    Error::Validation("Identity provider bundle missing alias".to_string())
+                })?;
+
+            let scope = HarborScope::IdentityProvider {
+                alias: alias.to_string(),
+            };
+
+            let result = provider
+                .import(
+                    realm_id,
+                    &scope,
+                    resource,
+                    conflict_policy,
+                    false,
+                    tx.as_deref_mut(),
+                )
+                .await?;
+
+            self.record_import_progress(
+                job_id,
+                persist_job_updates,
+                &mut progress,
+                &result,
+                conflict_policy,
+            )
+            .await;
+            results.push(result);
+        }
+
         for resource in bundle.resources.iter().filter(|r| r.key == "flow") {
             let Some(provider) = flow_provider.as_ref() else {
                 continue;
@@ -857,6 +1123,75 @@ impl HarborService {
             results.push(result);
         }
 
+        for resource in bundle
+            .resources
+            .iter()
+            .filter(|r| r.key == "realm_settings")
+        {
+            let Some(provider) = realm_settings_provider.as_ref() else {
+                continue;
+            };
+
+            let scope = HarborScope::FullRealm;
+
+            let result = provider
+                .import(
+                    realm_id,
+                    &scope,
+                    resource,
+                    conflict_policy,
+                    false,
+                    tx.as_deref_mut(),
+                )
+                .await?;
+
+            self.record_import_progress(
+                job_id,
+                persist_job_updates,
+                &mut progress,
+                &result,
+                conflict_policy,
+            )
+            .await;
+            results.push(result);
+        }
+
+        for resource in bundle.resources.iter().filter(|r| r.key == "webhook") {
+            let Some(provider) = webhook_provider.as_ref() else {
+                continue;
+            };
+
+            let endpoint_id = resource
+                .data
+                .get("endpoint_id")
+                .and_then(|value| value.as_str())
+                .and_then(|value| Uuid::parse_str(value).ok())
+                .unwrap_or_else(Uuid::new_v4);
+
+            let scope = HarborScope::Webhook { endpoint_id };
+
+            let result = provider
+                .import(
+                    realm_id,
+                    &scope,
+                    resource,
+                    conflict_policy,
+                    false,
+                    tx.as_deref_mut(),
+                )
+                .await?;
+
+            self.record_import_progress(
+                job_id,
+                persist_job_updates,
+                &mut progress,
+                &result,
+                conflict_policy,
+            )
+            .await;
+            results.push(result);
+        }
+
         for resource in bundle.resources.iter().filter(|r| r.key == "theme") {
             let Some(provider) = theme_provider.as_ref() else {
                 continue;
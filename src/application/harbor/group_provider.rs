@@ -0,0 +1,530 @@
+use crate::application::harbor::provider::HarborProvider;
+use crate::application::harbor::types::{
+    ConflictPolicy, ExportPolicy, HarborImportResourceResult, HarborResourceBundle, HarborScope,
+};
+use crate::application::harbor::user_provider::{
+    describe_role_refs, resolve_role_refs, HarborRoleRef,
+};
+use crate::application::oidc_service::OidcService;
+use crate::domain::group::Group;
+use crate::error::{Error, Result};
+use crate::ports::rbac_repository::RbacRepository;
+use crate::ports::transaction_manager::Transaction;
+use crate::ports::user_repository::UserRepository;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::to_value;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HarborGroupNode {
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    roles: Vec<HarborRoleRef>,
+    #[serde(default)]
+    members: Vec<String>,
+    #[serde(default)]
+    children: Vec<HarborGroupNode>,
+}
+
+/// One group tree; `parent` names the group the root hangs under, if any.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HarborGroupPayload {
+    #[serde(default)]
+    group_id: Option<String>,
+    #[serde(default)]
+    parent: Option<String>,
+    #[serde(flatten)]
+    root: HarborGroupNode,
+}
+
+struct PendingGroup<'a> {
+    node: &'a HarborGroupNode,
+    parent_id: Option<Uuid>,
+    /// Set when the parent was created by this import, so sibling order
+    /// comes from the bundle.
+    sort_order: Option<i64>,
+}
+
+#[derive(Default)]
+struct GroupImportTally {
+    created: u32,
+    updated: u32,
+    errors: Vec<String>,
+    renamed_root: Option<String>,
+}
+
+pub struct GroupHarborProvider {
+    rbac_repo: Arc<dyn RbacRepository>,
+    user_repo: Arc<dyn UserRepository>,
+    oidc_service: Arc<OidcService>,
+}
+
+impl GroupHarborProvider {
+    pub fn new(
+        rbac_repo: Arc<dyn RbacRepository>,
+        user_repo: Arc<dyn UserRepository>,
+        oidc_service: Arc<OidcService>,
+    ) -> Self {
+        Self {
+            rbac_repo,
+            user_repo,
+            oidc_service,
+        }
+    }
+
+    async fn resolve_available_name(&self, realm_id: Uuid, base: &str) -> Result<String> {
+        for idx in 1..=1000 {
+            let candidate = format!("{}-{}", base, idx);
+            if self
+                .rbac_repo
+                .find_group_by_name(&realm_id, &candidate)
+                .await?
+                .is_none()
+            {
+                return Ok(candidate);
+            }
+        }
+
+        Err(Error::Validation(
+            "Unable to generate unique group name".to_string(),
+        ))
+    }
+
+    async fn resolve_member_ids(
+        &self,
+        realm_id: Uuid,
+        node: &HarborGroupNode,
+        errors: &mut Vec<String>,
+        mut tx: Option<&mut dyn Transaction>,
+    ) -> Result<Vec<Uuid>> {
+        let mut ids = Vec::new();
+        for username in &node.members {
+            match self
+                .user_repo
+                .find_by_username_with_tx(&realm_id, username, tx.as_deref_mut())
+                .await?
+            {
+                Some(user) => ids.push(user.id),
+                None => errors.push(format!(
+                    "Group '{}' member '{}' not found; membership skipped",
+                    node.name, username
+                )),
+            }
+        }
+        Ok(ids)
+    }
+
+    async fn export_node(
+        &self,
+        group: &Group,
+        children_by_parent: &HashMap<Uuid, Vec<Group>>,
+    ) -> Result<HarborGroupNode> {
+        let mut nodes = HashMap::new();
+        // Children are described before their parents so each node can be
+        // assembled from already-built subtrees.
+        let mut order = vec![group.clone()];
+        let mut idx = 0;
+        while idx < order.len() {
+            if let Some(children) = children_by_parent.get(&order[idx].id) {
+                order.extend(children.iter().cloned());
+            }
+            idx += 1;
+        }
+
+        for current in order.iter().rev() {
+            let role_ids = self.rbac_repo.find_role_ids_for_group(&current.id).await?;
+            let roles = describe_role_refs(&*self.rbac_repo, &self.oidc_service, &role_ids).await?;
+
+            let mut members = Vec::new();
+            for user_id in self.rbac_repo.find_user_ids_in_group(&current.id).await? {
+                if let Some(user) = self.user_repo.find_by_id(&user_id).await? {
+                    members.push(user.username);
+                }
+            }
+            members.sort();
+
+            let children = children_by_parent
+                .get(&current.id)
+                .map(|children| {
+                    children
+                        .iter()
+                        .filter_map(|child| nodes.remove(&child.id))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+
+            nodes.insert(
+                current.id,
+                HarborGroupNode {
+                    name: current.name.clone(),
+                    description: current.description.clone(),
+                    roles,
+                    members,
+                    children,
+                },
+            );
+        }
+
+        nodes
+            .remove(&group.id)
+            .ok_or_else(|| Error::System("Failed to assemble group tree".to_string()))
+    }
+
+    async fn import_tree(
+        &self,
+        realm_id: Uuid,
+        payload: &HarborGroupPayload,
+        conflict_policy: ConflictPolicy,
+        dry_run: bool,
+        mut tx: Option<&mut dyn Transaction>,
+    ) -> Result<GroupImportTally> {
+        let mut tally = GroupImportTally::default();
+
+        let root_parent_id = match payload.parent.as_deref() {
+            Some(parent_name) => {
+                match self
+                    .rbac_repo
+                    .find_group_by_name(&realm_id, parent_name)
+                    .await?
+                {
+                    Some(parent) => Some(parent.id),
+                    None => {
+                        tally.errors.push(format!(
+                            "Parent group '{}' not found; '{}' was imported as a root group",
+                            parent_name, payload.root.name
+                        ));
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let mut stack = vec![PendingGroup {
+            node: &payload.root,
+            parent_id: root_parent_id,
+            sort_order: None,
+        }];
+
+        while let Some(pending) = stack.pop() {
+            let node = pending.node;
+            let role_ids = resolve_role_refs(
+                &*self.rbac_repo,
+                &self.oidc_service,
+                realm_id,
+                &node.roles,
+                tx.as_deref_mut(),
+            )
+            .await?;
+            let existing = self
+                .rbac_repo
+                .find_group_by_name(&realm_id, &node.name)
+                .await?;
+
+            let (group_id, created) = match (existing, conflict_policy) {
+                (Some(existing), ConflictPolicy::Skip) => (existing.id, false),
+                (Some(mut existing), ConflictPolicy::Overwrite) => {
+                    tally.updated += 1;
+                    if !dry_run {
+                        let member_ids = self
+                            .resolve_member_ids(
+                                realm_id,
+                                node,
+                                &mut tally.errors,
+                                tx.as_deref_mut(),
+                            )
+                            .await?;
+                        existing.description = node.description.clone();
+                        self.rbac_repo
+                            .update_group(&existing, tx.as_deref_mut())
+                            .await?;
+                        let current_roles = self
+                            .rbac_repo
+                            .find_role_ids_for_group(&existing.id)
+                            .await?
+                            .into_iter()
+                            .collect::<HashSet<_>>();
+                        let desired_roles = role_ids.iter().copied().collect::<HashSet<_>>();
+                        for role_id in current_roles.difference(&desired_roles) {
+                            self.rbac_repo
+                                .remove_role_from_group(role_id, &existing.id, tx.as_deref_mut())
+                                .await?;
+                        }
+                        for role_id in desired_roles.difference(&current_roles) {
+                            self.rbac_repo
+                                .assign_role_to_group(role_id, &existing.id, tx.as_deref_mut())
+                                .await?;
+                        }
+                        // Membership is additive so an import never evicts users.
+                        for user_id in member_ids {
+                            self.rbac_repo
+                                .assign_user_to_group(&user_id, &existing.id, tx.as_deref_mut())
+                                .await?;
+                        }
+                    }
+                    (existing.id, false)
+                }
+                (existing, _) => {
+                    let name = if existing.is_some() {
+                        let renamed = self.resolve_available_name(realm_id, &node.name).await?;
+                        if std::ptr::eq(node, &payload.root) {
+                            tally.renamed_root = Some(renamed.clone());
+                        }
+                        renamed
+                    } else {
+                        node.name.clone()
+                    };
+
+                    tally.created += 1;
+                    let group_id = Uuid::new_v4();
+                    if !dry_run {
+                        let member_ids = self
+                            .resolve_member_ids(
+                                realm_id,
+                                node,
+                                &mut tally.errors,
+                                tx.as_deref_mut(),
+                            )
+                            .await?;
+                        let sort_order = match pending.sort_order {
+                            Some(sort_order) => sort_order,
+                            None => {
+                                self.rbac_repo
+                                    .get_next_group_sort_order(
+                                        &realm_id,
+                                        pending.parent_id.as_ref(),
+                                    )
+                                    .await?
+                            }
+                        };
+                        let group = Group {
+                            id: group_id,
+                            realm_id,
+                            parent_id: pending.parent_id,
+                            name,
+                            description: node.description.clone(),
+                            sort_order,
+                        };
+                        self.rbac_repo
+                            .create_group(&group, tx.as_deref_mut())
+                            .await?;
+                        for role_id in &role_ids {
+                            self.rbac_repo
+                                .assign_role_to_group(role_id, &group_id, tx.as_deref_mut())
+                                .await?;
+                        }
+                        for user_id in member_ids {
+                            self.rbac_repo
+                                .assign_user_to_group(&user_id, &group_id, tx.as_deref_mut())
+                                .await?;
+                        }
+                    }
+                    (group_id, true)
+                }
+            };
+
+            for (idx, child) in node.children.iter().enumerate().rev() {
+                stack.push(PendingGroup {
+                    node: child,
+                    parent_id: Some(group_id),
+                    sort_order: created.then_some(idx as i64),
+                });
+            }
+        }
+
+        Ok(tally)
+    }
+}
+
+#[async_trait]
+impl HarborProvider for GroupHarborProvider {
+    fn key(&self) -> &'static str {
+        "group"
+    }
+
+    fn validate(&self, resource: &HarborResourceBundle) -> Result<()> {
+        if !resource.assets.is_empty() {
+            return Err(Error::Validation(
+                "Group bundles must not include assets".to_string(),
+            ));
+        }
+
+        let payload: HarborGroupPayload = serde_json::from_value(resource.data.clone())
+            .map_err(|err| Error::Validation(format!("Invalid group bundle payload: {}", err)))?;
+
+        // Group names are unique per realm, so they must be unique within a tree too.
+        let mut names = HashSet::new();
+        let mut stack = vec![&payload.root];
+        while let Some(node) = stack.pop() {
+            if node.name.trim().is_empty() {
+                return Err(Error::Validation("Group name is required".to_string()));
+            }
+            if !names.insert(node.name.as_str()) {
+                return Err(Error::Validation(format!(
+                    "Group '{}' appears more than once in the bundle",
+                    node.name
+                )));
+            }
+            for role in &node.roles {
+                if role.name.trim().is_empty() {
+                    return Err(Error::Validation("Group role name is required".to_string()));
+                }
+            }
+            stack.extend(node.children.iter());
+        }
+
+        Ok(())
+    }
+
+    async fn export(
+        &self,
+        realm_id: Uuid,
+        scope: &HarborScope,
+        _policy: ExportPolicy,
+    ) -> Result<HarborResourceBundle> {
+        let group_id = match scope {
+            HarborScope::Group { group_id } => *group_id,
+            _ => {
+                return Err(Error::Validation(
+                    "Group export requires group scope".to_string(),
+                ))
+            }
+        };
+
+        let group = self
+            .rbac_repo
+            .find_group_by_id(&group_id)
+            .await?
+            .ok_or_else(|| Error::NotFound("Group not found".to_string()))?;
+        if group.realm_id != realm_id {
+            return Err(Error::SecurityViolation(
+                "Group belongs to different realm".to_string(),
+            ));
+        }
+
+        let parent = match group.parent_id {
+            Some(parent_id) => self
+                .rbac_repo
+                .find_group_by_id(&parent_id)
+                .await?
+                .map(|parent| parent.name),
+            None => None,
+        };
+
+        let mut children_by_parent: HashMap<Uuid, Vec<Group>> = HashMap::new();
+        for id in self
+            .rbac_repo
+            .list_group_subtree_ids(&realm_id, &group.id)
+            .await?
+        {
+            if id == group.id {
+                continue;
+            }
+            let Some(child) = self.rbac_repo.find_group_by_id(&id).await? else {
+                continue;
+            };
+            if let Some(parent_id) = child.parent_id {
+                children_by_parent.entry(parent_id).or_default().push(child);
+            }
+        }
+        for children in children_by_parent.values_mut() {
+            children.sort_by(|a, b| {
+                a.sort_order
+                    .cmp(&b.sort_order)
+                    .then_with(|| a.name.cmp(&b.name))
+            });
+        }
+
+        let root = self.export_node(&group, &children_by_parent).await?;
+        let payload = HarborGroupPayload {
+            group_id: Some(group.id.to_string()),
+            parent,
+            root,
+        };
+
+        Ok(HarborResourceBundle {
+            key: self.key().to_string(),
+            data: to_value(payload)
+                .map_err(|err| Error::System(format!("Failed to serialize group: {}", err)))?,
+            assets: Vec::new(),
+            meta: None,
+        })
+    }
+
+    async fn import(
+        &self,
+        realm_id: Uuid,
+        scope: &HarborScope,
+        resource: &HarborResourceBundle,
+        conflict_policy: ConflictPolicy,
+        dry_run: bool,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<HarborImportResourceResult> {
+        if !matches!(scope, HarborScope::Group { .. }) {
+            return Err(Error::Validation(
+                "Group import requires group scope".to_string(),
+            ));
+        }
+
+        let payload: HarborGroupPayload = serde_json::from_value(resource.data.clone())
+            .map_err(|err| Error::Validation(format!("Invalid group bundle payload: {}", err)))?;
+
+        let tally = self
+            .import_tree(realm_id, &payload, conflict_policy, dry_run, tx)
+            .await?;
+
+        let status = if dry_run {
+            "validated"
+        } else if tally.created == 0 && tally.updated == 0 {
+            "skipped"
+        } else if tally.created == 0 {
+            "updated"
+        } else {
+            "created"
+        };
+
+        Ok(HarborImportResourceResult {
+            key: self.key().to_string(),
+            status: status.to_string(),
+            created: tally.created,
+            updated: tally.updated,
+            errors: tally.errors,
+            original_id: Some(payload.root.name),
+            renamed_to: tally.renamed_root,
+        })
+    }
+
+    async fn delete(
+        &self,
+        realm_id: Uuid,
+        scope: &HarborScope,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        let group_id = match scope {
+            HarborScope::Group { group_id } => *group_id,
+            _ => {
+                return Err(Error::Validation(
+                    "Group delete requires group scope".to_string(),
+                ))
+            }
+        };
+
+        self.rbac_repo
+            .find_group_by_id(&group_id)
+            .await?
+            .filter(|group| group.realm_id == realm_id)
+            .ok_or_else(|| Error::NotFound("Group not found".to_string()))?;
+
+        // A group resource is a whole subtree, so its descendants go with it.
+        let group_ids = self
+            .rbac_repo
+            .list_group_subtree_ids(&realm_id, &group_id)
+            .await?;
+        self.rbac_repo.delete_groups(&group_ids, tx).await
+    }
+}
@@ -0,0 +1,493 @@
+use crate::application::harbor::provider::HarborProvider;
+use crate::application::harbor::types::{
+    ConflictPolicy, ExportPolicy, HarborImportResourceResult, HarborResourceBundle, HarborScope,
+};
+use crate::application::idp_service::validate_alias;
+use crate::application::secret_service::SecretService;
+use crate::domain::identity_provider::{IdentityProvider, IdentityProviderProtocol};
+use crate::domain::saml::broker::normalize_certificate;
+use crate::domain::saml::NameIdFormat;
+use crate::error::{Error, Result};
+use crate::ports::federated_identity_repository::FederatedIdentityRepository;
+use crate::ports::identity_provider_repository::IdentityProviderRepository;
+use crate::ports::transaction_manager::Transaction;
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{to_value, Value};
+use std::sync::Arc;
+use uuid::Uuid;
+
+const REDACTED_SECRET: &str = "${REDACTED}";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HarborIdentityProviderPayload {
+    pub alias: String,
+    pub display_name: String,
+    pub protocol: IdentityProviderProtocol,
+    #[serde(default)]
+    pub preset_key: Option<String>,
+    #[serde(default)]
+    pub enabled: bool,
+    pub client_id: String,
+    #[serde(default)]
+    pub client_secret: Option<String>,
+    #[serde(default)]
+    pub issuer: Option<String>,
+    #[serde(default)]
+    pub authorization_endpoint: Option<String>,
+    #[serde(default)]
+    pub token_endpoint: Option<String>,
+    #[serde(default)]
+    pub userinfo_endpoint: Option<String>,
+    #[serde(default)]
+    pub jwks_uri: Option<String>,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    #[serde(default)]
+    pub claim_mapping: Value,
+    #[serde(default = "default_true")]
+    pub pkce_required: bool,
+    #[serde(default = "default_true")]
+    pub allow_login: bool,
+    #[serde(default = "default_true")]
+    pub allow_link: bool,
+    #[serde(default)]
+    pub allow_jit_provisioning: bool,
+    #[serde(default)]
+    pub allow_email_auto_link: bool,
+    #[serde(default = "default_true")]
+    pub require_verified_email: bool,
+    #[serde(default)]
+    pub icon_ref: Option<String>,
+    #[serde(default)]
+    pub button_color: Option<String>,
+    #[serde(default)]
+    pub sort_order: i64,
+    #[serde(default)]
+    pub saml_metadata_url: Option<String>,
+    #[serde(default)]
+    pub saml_signing_certificates: Vec<String>,
+    #[serde(default)]
+    pub saml_name_id_format: Option<NameIdFormat>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+pub struct IdentityProviderHarborProvider {
+    repo: Arc<dyn IdentityProviderRepository>,
+    federated_identity_repo: Arc<dyn FederatedIdentityRepository>,
+    secret_service: Arc<SecretService>,
+}
+
+impl IdentityProviderHarborProvider {
+    pub fn new(
+        repo: Arc<dyn IdentityProviderRepository>,
+        federated_identity_repo: Arc<dyn FederatedIdentityRepository>,
+        secret_service: Arc<SecretService>,
+    ) -> Self {
+        Self {
+            repo,
+            federated_identity_repo,
+            secret_service,
+        }
+    }
+
+    /// Encrypts the bundle secret. A redacted secret keeps `existing`, which
+    /// is `None` for providers that do not exist yet.
+    fn resolve_secret(
+        &self,
+        secret: Option<&str>,
+        existing: Option<&IdentityProvider>,
+    ) -> Result<Option<String>> {
+        match secret.map(str::trim) {
+            None | Some("") => Ok(None),
+            Some(REDACTED_SECRET) => {
+                Ok(existing.and_then(|provider| provider.client_secret.clone()))
+            }
+            Some(secret) => Ok(Some(self.secret_service.encrypt(secret)?)),
+        }
+    }
+
+    async fn resolve_available_alias(&self, realm_id: Uuid, base: &str) -> Result<String> {
+        for idx in 1..=1000 {
+            let candidate = format!("{}-{}", base, idx);
+            if self
+                .repo
+                .find_by_alias(&realm_id, &candidate)
+                .await?
+                .is_none()
+            {
+                return Ok(candidate);
+            }
+        }
+
+        Err(Error::Validation(
+            "Unable to generate unique identity provider alias".to_string(),
+        ))
+    }
+
+    async fn create_provider(
+        &self,
+        realm_id: Uuid,
+        payload: HarborIdentityProviderPayload,
+        dry_run: bool,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<HarborImportResourceResult> {
+        if dry_run {
+            return Ok(HarborImportResourceResult {
+                key: self.key().to_string(),
+                status: "validated".to_string(),
+                created: 1,
+                updated: 0,
+                errors: Vec::new(),
+                original_id: Some(payload.alias),
+                renamed_to: None,
+            });
+        }
+
+        let client_secret = self.resolve_secret(payload.client_secret.as_deref(), None)?;
+        let now = Utc::now();
+        let mut provider = IdentityProvider {
+            id: Uuid::new_v4(),
+            realm_id,
+            alias: payload.alias.clone(),
+            display_name: String::new(),
+            protocol: payload.protocol,
+            preset_key: None,
+            enabled: false,
+            client_id: String::new(),
+            client_secret,
+            issuer: None,
+            authorization_endpoint: None,
+            token_endpoint: None,
+            userinfo_endpoint: None,
+            jwks_uri: None,
+            scopes_json: "[]".to_string(),
+            claim_mapping_json: "{}".to_string(),
+            pkce_required: true,
+            allow_login: true,
+            allow_link: true,
+            allow_jit_provisioning: false,
+            allow_email_auto_link: false,
+            require_verified_email: true,
+            icon_ref: None,
+            button_color: None,
+            sort_order: 0,
+            metadata_cached_at: None,
+            metadata_cache_json: None,
+            jwks_cached_at: None,
+            jwks_cache_json: None,
+            saml_metadata_url: None,
+            saml_signing_certificates_json: "[]".to_string(),
+            saml_name_id_format: None,
+            created_at: now,
+            updated_at: now,
+        };
+        apply_payload(&mut provider, &payload)?;
+        let mut errors = Vec::new();
+        if secret_redacted(payload.client_secret.as_deref()) && provider.enabled {
+            provider.enabled = false;
+            errors.push(format!(
+                "Identity provider '{}' was imported disabled because its client secret is redacted",
+                provider.alias
+            ));
+        }
+
+        self.repo.create(&provider, tx).await?;
+
+        Ok(HarborImportResourceResult {
+            key: self.key().to_string(),
+            status: "created".to_string(),
+            created: 1,
+            updated: 0,
+            errors,
+            original_id: Some(payload.alias),
+            renamed_to: None,
+        })
+    }
+}
+
+#[async_trait]
+impl HarborProvider for IdentityProviderHarborProvider {
+    fn key(&self) -> &'static str {
+        "identity_provider"
+    }
+
+    fn validate(&self, resource: &HarborResourceBundle) -> Result<()> {
+        if !resource.assets.is_empty() {
+            return Err(Error::Validation(
+                "Identity provider bundles must not include assets".to_string(),
+            ));
+        }
+
+        let payload: HarborIdentityProviderPayload = serde_json::from_value(resource.data.clone())
+            .map_err(|err| {
+                Error::Validation(format!("Invalid identity provider bundle payload: {}", err))
+            })?;
+
+        validate_alias(&payload.alias)?;
+        if payload.client_id.trim().is_empty() {
+            return Err(Error::Validation(
+                "Identity provider client_id is required".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn export(
+        &self,
+        realm_id: Uuid,
+        scope: &HarborScope,
+        policy: ExportPolicy,
+    ) -> Result<HarborResourceBundle> {
+        let alias = match scope {
+            HarborScope::IdentityProvider { alias } => alias,
+            _ => {
+                return Err(Error::Validation(
+                    "Identity provider export requires identity provider scope".to_string(),
+                ))
+            }
+        };
+
+        let provider = self
+            .repo
+            .find_by_alias(&realm_id, alias)
+            .await?
+            .ok_or_else(|| Error::NotFound("Identity provider not found".to_string()))?;
+
+        let client_secret = match (policy, provider.client_secret.as_deref()) {
+            (_, None) => None,
+            (ExportPolicy::IncludeSecrets, Some(secret)) => {
+                Some(self.secret_service.decrypt(secret)?)
+            }
+            (ExportPolicy::Redact, Some(_)) => Some(REDACTED_SECRET.to_string()),
+        };
+
+        let payload = HarborIdentityProviderPayload {
+            alias: provider.alias,
+            display_name: provider.display_name,
+            protocol: provider.protocol,
+            preset_key: provider.preset_key,
+            enabled: provider.enabled,
+            client_id: provider.client_id,
+            client_secret,
+            issuer: provider.issuer,
+            authorization_endpoint: provider.authorization_endpoint,
+            token_endpoint: provider.token_endpoint,
+            userinfo_endpoint: provider.userinfo_endpoint,
+            jwks_uri: provider.jwks_uri,
+            scopes: serde_json::from_str(&provider.scopes_json).unwrap_or_default(),
+            claim_mapping: serde_json::from_str(&provider.claim_mapping_json)
+                .unwrap_or_else(|_| Value::Object(Default::default())),
+            pkce_required: provider.pkce_required,
+            allow_login: provider.allow_login,
+            allow_link: provider.allow_link,
+            allow_jit_provisioning: provider.allow_jit_provisioning,
+            allow_email_auto_link: provider.allow_email_auto_link,
+            require_verified_email: provider.require_verified_email,
+            icon_ref: provider.icon_ref,
+            button_color: provider.button_color,
+            sort_order: provider.sort_order,
+            saml_metadata_url: provider.saml_metadata_url,
+            saml_signing_certificates: serde_json::from_str(
+                &provider.saml_signing_certificates_json,
+            )
+            .unwrap_or_default(),
+            saml_name_id_format: provider.saml_name_id_format,
+        };
+
+        Ok(HarborResourceBundle {
+            key: self.key().to_string(),
+            data: to_value(payload).map_err(|err| {
+                Error::System(format!("Failed to serialize identity provider: {}", err))
+            })?,
+            assets: Vec::new(),
+            meta: None,
+        })
+    }
+
+    async fn import(
+        &self,
+        realm_id: Uuid,
+        scope: &HarborScope,
+        resource: &HarborResourceBundle,
+        conflict_policy: ConflictPolicy,
+        dry_run: bool,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<HarborImportResourceResult> {
+        let scoped_alias = match scope {
+            HarborScope::IdentityProvider { alias } => alias,
+            _ => {
+                return Err(Error::Validation(
+                    "Identity provider import requires identity provider scope".to_string(),
+                ))
+            }
+        };
+
+        let mut payload: HarborIdentityProviderPayload =
+            serde_json::from_value(resource.data.clone()).map_err(|err| {
+                Error::Validation(format!("Invalid identity provider bundle payload: {}", err))
+            })?;
+        if &payload.alias != scoped_alias {
+            return Err(Error::Validation(
+                "Identity provider bundle alias does not match import scope".to_string(),
+            ));
+        }
+
+        let Some(existing) = self.repo.find_by_alias(&realm_id, &payload.alias).await? else {
+            return self.create_provider(realm_id, payload, dry_run, tx).await;
+        };
+
+        match conflict_policy {
+            ConflictPolicy::Skip => Ok(HarborImportResourceResult {
+                key: self.key().to_string(),
+                status: "skipped".to_string(),
+                created: 0,
+                updated: 0,
+                errors: Vec::new(),
+                original_id: Some(payload.alias),
+                renamed_to: None,
+            }),
+            ConflictPolicy::Rename => {
+                let original_alias = payload.alias.clone();
+                let renamed = self
+                    .resolve_available_alias(realm_id, &payload.alias)
+                    .await?;
+                payload.alias = renamed.clone();
+                let result = self.create_provider(realm_id, payload, dry_run, tx).await?;
+                Ok(HarborImportResourceResult {
+                    original_id: Some(original_alias),
+                    renamed_to: Some(renamed),
+                    ..result
+                })
+            }
+            ConflictPolicy::Overwrite => {
+                if dry_run {
+                    return Ok(HarborImportResourceResult {
+                        key: self.key().to_string(),
+                        status: "validated".to_string(),
+                        created: 0,
+                        updated: 1,
+                        errors: Vec::new(),
+                        original_id: Some(payload.alias),
+                        renamed_to: None,
+                    });
+                }
+
+                let mut provider = existing.clone();
+                provider.client_secret =
+                    self.resolve_secret(payload.client_secret.as_deref(), Some(&existing))?;
+                apply_payload(&mut provider, &payload)?;
+                if provider.issuer != existing.issuer || provider.jwks_uri != existing.jwks_uri {
+                    provider.metadata_cached_at = None;
+                    provider.metadata_cache_json = None;
+                    provider.jwks_cached_at = None;
+                    provider.jwks_cache_json = None;
+                }
+                provider.updated_at = Utc::now();
+                self.repo.update(&provider, tx).await?;
+
+                Ok(HarborImportResourceResult {
+                    key: self.key().to_string(),
+                    status: "updated".to_string(),
+                    created: 0,
+                    updated: 1,
+                    errors: Vec::new(),
+                    original_id: Some(payload.alias),
+                    renamed_to: None,
+                })
+            }
+        }
+    }
+
+    /// Providers that still have linked accounts are disabled rather than
+    /// removed, matching the admin API's default soft delete.
+    async fn delete(
+        &self,
+        realm_id: Uuid,
+        scope: &HarborScope,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        let alias = match scope {
+            HarborScope::IdentityProvider { alias } => alias,
+            _ => {
+                return Err(Error::Validation(
+                    "Identity provider delete requires identity provider scope".to_string(),
+                ))
+            }
+        };
+
+        let mut provider = self
+            .repo
+            .find_by_alias(&realm_id, alias)
+            .await?
+            .ok_or_else(|| Error::NotFound("Identity provider not found".to_string()))?;
+        let linked_identity_count = self
+            .federated_identity_repo
+            .count_by_provider(&realm_id, &provider.id)
+            .await?;
+
+        if linked_identity_count > 0 {
+            provider.enabled = false;
+            provider.allow_login = false;
+            provider.allow_link = false;
+            provider.updated_at = Utc::now();
+            return self.repo.update(&provider, tx).await;
+        }
+
+        self.repo.delete(&provider.id, tx).await
+    }
+}
+
+/// Copies every bundle field except the alias and client secret.
+fn apply_payload(
+    provider: &mut IdentityProvider,
+    payload: &HarborIdentityProviderPayload,
+) -> Result<()> {
+    provider.display_name = payload.display_name.clone();
+    provider.protocol = payload.protocol;
+    provider.preset_key = payload.preset_key.clone();
+    provider.enabled = payload.enabled;
+    provider.client_id = payload.client_id.clone();
+    provider.issuer = payload.issuer.clone();
+    provider.authorization_endpoint = payload.authorization_endpoint.clone();
+    provider.token_endpoint = payload.token_endpoint.clone();
+    provider.userinfo_endpoint = payload.userinfo_endpoint.clone();
+    provider.jwks_uri = payload.jwks_uri.clone();
+    provider.scopes_json = serde_json::to_string(&payload.scopes)
+        .map_err(|e| Error::System(format!("Failed to serialize provider scopes: {}", e)))?;
+    let claim_mapping = if payload.claim_mapping.is_null() {
+        Value::Object(Default::default())
+    } else {
+        payload.claim_mapping.clone()
+    };
+    provider.claim_mapping_json = serde_json::to_string(&claim_mapping)
+        .map_err(|e| Error::System(format!("Failed to serialize claim mapping: {}", e)))?;
+    provider.pkce_required = payload.pkce_required;
+    provider.allow_login = payload.allow_login;
+    provider.allow_link = payload.allow_link;
+    provider.allow_jit_provisioning = payload.allow_jit_provisioning;
+    provider.allow_email_auto_link = payload.allow_email_auto_link;
+    provider.require_verified_email = payload.require_verified_email;
+    provider.icon_ref = payload.icon_ref.clone();
+    provider.button_color = payload.button_color.clone();
+    provider.sort_order = payload.sort_order;
+    provider.saml_metadata_url = payload.saml_metadata_url.clone();
+    let certificates = payload
+        .saml_signing_certificates
+        .iter()
+        .map(|certificate| normalize_certificate(certificate))
+        .collect::<Result<Vec<_>>>()?;
+    provider.saml_signing_certificates_json = serde_json::to_string(&certificates)
+        .map_err(|e| Error::System(format!("Failed to serialize SAML certificates: {}", e)))?;
+    provider.saml_name_id_format = payload.saml_name_id_format;
+    Ok(())
+}
+
+fn secret_redacted(value: Option<&str>) -> bool {
+    matches!(value.map(str::trim), Some(REDACTED_SECRET))
+}
@@ -2,8 +2,12 @@ pub mod archive;
 pub mod bootstrap;
 pub mod client_provider;
 pub mod flow_provider;
+pub mod group_provider;
+pub mod identity_provider_provider;
 pub mod provider;
 pub mod realm_provider;
+pub mod realm_settings_provider;
+pub mod reconcile;
 pub mod role_provider;
 pub mod runner;
 pub mod schema;
@@ -11,15 +15,18 @@ pub mod service;
 pub mod theme_provider;
 pub mod types;
 pub mod user_provider;
+pub mod webhook_provider;
 
 pub use archive::{read_bundle_from_path, write_bundle_to_path};
 pub use bootstrap::{bootstrap_import_bundle, resolve_bootstrap_realm_name};
 pub use provider::{HarborProvider, HarborRegistry};
+pub use reconcile::read_bundle_dir;
 pub use runner::{HarborJobRunner, TokioHarborJobRunner};
 pub use service::HarborService;
 pub use types::{
     ConflictPolicy, ExportPolicy, HarborAsset, HarborBundle, HarborExportType, HarborImportResult,
-    HarborManifest, HarborResourceBundle, HarborScope,
+    HarborManifest, HarborPlanAction, HarborPlanEntry, HarborPlanSummary, HarborReconcilePlan,
+    HarborReconcileResult, HarborResourceBundle, HarborScope,
 };
 pub mod export;
 pub mod import;
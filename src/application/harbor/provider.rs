@@ -1,7 +1,7 @@
 use crate::application::harbor::types::{
     ConflictPolicy, ExportPolicy, HarborImportResourceResult, HarborResourceBundle, HarborScope,
 };
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::ports::transaction_manager::Transaction;
 use async_trait::async_trait;
 use std::collections::HashMap;
@@ -32,6 +32,20 @@ pub trait HarborProvider: Send + Sync {
         dry_run: bool,
         tx: Option<&mut dyn Transaction>,
     ) -> Result<HarborImportResourceResult>;
+
+    /// Removes the resource addressed by `scope`. Only reconcile pruning calls
+    /// this; resource kinds that are never pruned keep the default.
+    async fn delete(
+        &self,
+        _realm_id: Uuid,
+        _scope: &HarborScope,
+        _tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        Err(Error::Validation(format!(
+            "Harbor provider '{}' does not support deletes",
+            self.key()
+        )))
+    }
 }
 
 pub struct HarborRegistry {
@@ -4,6 +4,8 @@ use crate::application::harbor::types::{
     ConflictPolicy, ExportPolicy, HarborImportResourceResult, HarborResourceBundle, HarborScope,
 };
 use crate::application::realm_service::{RealmService, UpdateRealmPayload};
+use crate::domain::realm::{RealmIdpDefaultEmailLinkPolicy, RealmIdpDefaultJitPolicy};
+use crate::domain::signing_key::SigningAlgorithm;
 use crate::error::{Error, Result};
 use crate::ports::transaction_manager::Transaction;
 use async_trait::async_trait;
@@ -40,12 +42,22 @@ struct HarborRealmPayload {
     pub lockout_threshold: i64,
     pub lockout_duration_secs: i64,
     #[serde(default)]
+    pub signing_algorithm: Option<SigningAlgorithm>,
+    #[serde(default)]
     pub invitation_resend_limit: Option<i64>,
     #[serde(default)]
     pub registration_enabled: Option<bool>,
     #[serde(default)]
     pub default_registration_role_ids: Option<Vec<String>>,
     #[serde(default)]
+    pub idp_broker_enabled: Option<bool>,
+    #[serde(default)]
+    pub idp_default_jit_policy: Option<RealmIdpDefaultJitPolicy>,
+    #[serde(default)]
+    pub idp_default_email_link_policy: Option<RealmIdpDefaultEmailLinkPolicy>,
+    #[serde(default)]
+    pub idp_minimum_remaining_factor: Option<bool>,
+    #[serde(default)]
     pub flow_bindings: HarborRealmFlowBindings,
 }
 
@@ -123,6 +135,7 @@ impl HarborProvider for RealmHarborProvider {
             pkce_required_public_clients: realm.pkce_required_public_clients,
             lockout_threshold: realm.lockout_threshold,
             lockout_duration_secs: realm.lockout_duration_secs,
+            signing_algorithm: Some(realm.signing_algorithm),
             invitation_resend_limit: Some(realm.invitation_resend_limit),
             registration_enabled: Some(realm.registration_enabled),
             default_registration_role_ids: Some(
@@ -132,6 +145,10 @@ impl HarborProvider for RealmHarborProvider {
                     .map(|id| id.to_string())
                     .collect(),
             ),
+            idp_broker_enabled: Some(realm.idp_broker_enabled),
+            idp_default_jit_policy: Some(realm.idp_default_jit_policy),
+            idp_default_email_link_policy: Some(realm.idp_default_email_link_policy),
+            idp_minimum_remaining_factor: Some(realm.idp_minimum_remaining_factor),
             flow_bindings: HarborRealmFlowBindings {
                 browser_flow_id: realm.browser_flow_id,
                 registration_flow_id: realm.registration_flow_id,
@@ -186,9 +203,14 @@ impl HarborProvider for RealmHarborProvider {
             pkce_required_public_clients,
             lockout_threshold,
             lockout_duration_secs,
+            signing_algorithm,
             invitation_resend_limit,
             registration_enabled,
             default_registration_role_ids,
+            idp_broker_enabled,
+            idp_default_jit_policy,
+            idp_default_email_link_policy,
+            idp_minimum_remaining_factor,
             flow_bindings,
         } = payload;
 
@@ -199,16 +221,17 @@ impl HarborProvider for RealmHarborProvider {
             pkce_required_public_clients: Some(pkce_required_public_clients),
             lockout_threshold: Some(lockout_threshold),
             lockout_duration_secs: Some(lockout_duration_secs),
+            signing_algorithm,
             invitation_resend_limit,
             registration_enabled,
             default_registration_role_ids: match default_registration_role_ids {
                 Some(role_ids) => Some(parse_role_ids(&role_ids)?),
                 None => None,
             },
-            idp_broker_enabled: None,
-            idp_default_jit_policy: None,
-            idp_default_email_link_policy: None,
-            idp_minimum_remaining_factor: None,
+            idp_broker_enabled,
+            idp_default_jit_policy,
+            idp_default_email_link_policy,
+            idp_minimum_remaining_factor,
             browser_flow_id: Some(parse_optional_uuid(flow_bindings.browser_flow_id.clone())?),
             registration_flow_id: Some(parse_optional_uuid(
                 flow_bindings.registration_flow_id.clone(),
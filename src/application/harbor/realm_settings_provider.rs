@@ -0,0 +1,344 @@
+use crate::application::harbor::provider::HarborProvider;
+use crate::application::harbor::types::{
+    ConflictPolicy, ExportPolicy, HarborImportResourceResult, HarborResourceBundle, HarborScope,
+};
+use crate::application::password_policy_service::PasswordPolicyService;
+use crate::application::realm_email_settings_service::RealmEmailSettingsService;
+use crate::application::realm_idp_settings_service::RealmIdpSettingsService;
+use crate::application::realm_passkey_settings_service::RealmPasskeySettingsService;
+use crate::application::realm_recovery_settings_service::RealmRecoverySettingsService;
+use crate::application::realm_security_headers_service::RealmSecurityHeadersService;
+use crate::domain::realm_email_settings::RealmEmailSettings;
+use crate::error::{Error, Result};
+use crate::ports::transaction_manager::Transaction;
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{to_value, Value};
+use std::sync::Arc;
+use uuid::Uuid;
+
+const REDACTED_SECRET: &str = "${REDACTED}";
+
+/// Per-realm settings sections. Each is the stored record without its
+/// `realm_id`; on import a section is laid over the target's current values,
+/// so sections and fields missing from the bundle are left as they are.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct HarborRealmSettingsPayload {
+    #[serde(default)]
+    email: Option<Value>,
+    #[serde(default)]
+    passkey: Option<Value>,
+    #[serde(default)]
+    recovery: Option<Value>,
+    #[serde(default)]
+    security_headers: Option<Value>,
+    #[serde(default)]
+    identity_providers: Option<Value>,
+    #[serde(default)]
+    password_policy: Option<Value>,
+}
+
+pub struct RealmSettingsHarborProvider {
+    email_settings_service: Arc<RealmEmailSettingsService>,
+    passkey_settings_service: Arc<RealmPasskeySettingsService>,
+    recovery_settings_service: Arc<RealmRecoverySettingsService>,
+    security_headers_service: Arc<RealmSecurityHeadersService>,
+    idp_settings_service: Arc<RealmIdpSettingsService>,
+    password_policy_service: Arc<PasswordPolicyService>,
+}
+
+impl RealmSettingsHarborProvider {
+    pub fn new(
+        email_settings_service: Arc<RealmEmailSettingsService>,
+        passkey_settings_service: Arc<RealmPasskeySettingsService>,
+        recovery_settings_service: Arc<RealmRecoverySettingsService>,
+        security_headers_service: Arc<RealmSecurityHeadersService>,
+        idp_settings_service: Arc<RealmIdpSettingsService>,
+        password_policy_service: Arc<PasswordPolicyService>,
+    ) -> Self {
+        Self {
+            email_settings_service,
+            passkey_settings_service,
+            recovery_settings_service,
+            security_headers_service,
+            idp_settings_service,
+            password_policy_service,
+        }
+    }
+}
+
+#[async_trait]
+impl HarborProvider for RealmSettingsHarborProvider {
+    fn key(&self) -> &'static str {
+        "realm_settings"
+    }
+
+    fn validate(&self, resource: &HarborResourceBundle) -> Result<()> {
+        if !resource.assets.is_empty() {
+            return Err(Error::Validation(
+                "Realm settings bundles must not include assets".to_string(),
+            ));
+        }
+
+        let payload: HarborRealmSettingsPayload = serde_json::from_value(resource.data.clone())
+            .map_err(|err| {
+                Error::Validation(format!("Invalid realm settings bundle payload: {}", err))
+            })?;
+
+        for (name, section) in sections(&payload) {
+            if let Some(section) = section {
+                if !section.is_object() {
+                    return Err(Error::Validation(format!(
+                        "Realm settings section '{}' must be an object",
+                        name
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn export(
+        &self,
+        realm_id: Uuid,
+        scope: &HarborScope,
+        policy: ExportPolicy,
+    ) -> Result<HarborResourceBundle> {
+        if !matches!(scope, HarborScope::FullRealm) {
+            return Err(Error::Validation(
+                "Realm settings export requires full realm scope".to_string(),
+            ));
+        }
+
+        let mut email = self.email_settings_service.get_settings(realm_id).await?;
+        if matches!(policy, ExportPolicy::Redact) && email.smtp_password.is_some() {
+            email.smtp_password = Some(REDACTED_SECRET.to_string());
+        }
+
+        let payload = HarborRealmSettingsPayload {
+            email: Some(export_section(&email)?),
+            passkey: Some(export_section(
+                &self.passkey_settings_service.get_settings(realm_id).await?,
+            )?),
+            recovery: Some(export_section(
+                &self
+                    .recovery_settings_service
+                    .get_settings(realm_id)
+                    .await?,
+            )?),
+            security_headers: Some(export_section(
+                &self.security_headers_service.get_settings(realm_id).await?,
+            )?),
+            identity_providers: Some(export_section(
+                &self.idp_settings_service.get_settings(realm_id).await?,
+            )?),
+            password_policy: Some(export_section(
+                &self.password_policy_service.get_policy(realm_id).await?,
+            )?),
+        };
+
+        Ok(HarborResourceBundle {
+            key: self.key().to_string(),
+            data: to_value(payload).map_err(|err| {
+                Error::System(format!("Failed to serialize realm settings: {}", err))
+            })?,
+            assets: Vec::new(),
+            meta: None,
+        })
+    }
+
+    async fn import(
+        &self,
+        realm_id: Uuid,
+        scope: &HarborScope,
+        resource: &HarborResourceBundle,
+        _conflict_policy: ConflictPolicy,
+        dry_run: bool,
+        mut tx: Option<&mut dyn Transaction>,
+    ) -> Result<HarborImportResourceResult> {
+        if !matches!(scope, HarborScope::FullRealm) {
+            return Err(Error::Validation(
+                "Realm settings import requires full realm scope".to_string(),
+            ));
+        }
+
+        let payload: HarborRealmSettingsPayload = serde_json::from_value(resource.data.clone())
+            .map_err(|err| {
+                Error::Validation(format!("Invalid realm settings bundle payload: {}", err))
+            })?;
+
+        let mut errors = Vec::new();
+
+        let email = match payload.email.as_ref() {
+            Some(section) => {
+                let current = self.email_settings_service.get_settings(realm_id).await?;
+                let mut email: RealmEmailSettings =
+                    merge_section(realm_id, &current, section, "email")?;
+                if email.smtp_password.as_deref() == Some(REDACTED_SECRET) {
+                    email.smtp_password = current.smtp_password.clone();
+                    if email.smtp_password.is_none() {
+                        errors.push(
+                            "SMTP password was redacted; set it before enabling email delivery"
+                                .to_string(),
+                        );
+                        email.enabled = false;
+                    }
+                }
+                Some(email)
+            }
+            None => None,
+        };
+        let passkey = match payload.passkey.as_ref() {
+            Some(section) => Some(merge_section(
+                realm_id,
+                &self.passkey_settings_service.get_settings(realm_id).await?,
+                section,
+                "passkey",
+            )?),
+            None => None,
+        };
+        let recovery = match payload.recovery.as_ref() {
+            Some(section) => Some(merge_section(
+                realm_id,
+                &self
+                    .recovery_settings_service
+                    .get_settings(realm_id)
+                    .await?,
+                section,
+                "recovery",
+            )?),
+            None => None,
+        };
+        let security_headers = match payload.security_headers.as_ref() {
+            Some(section) => Some(merge_section(
+                realm_id,
+                &self.security_headers_service.get_settings(realm_id).await?,
+                section,
+                "security_headers",
+            )?),
+            None => None,
+        };
+        let identity_providers = match payload.identity_providers.as_ref() {
+            Some(section) => Some(merge_section(
+                realm_id,
+                &self.idp_settings_service.get_settings(realm_id).await?,
+                section,
+                "identity_providers",
+            )?),
+            None => None,
+        };
+        let password_policy = match payload.password_policy.as_ref() {
+            Some(section) => Some(merge_section(
+                realm_id,
+                &self.password_policy_service.get_policy(realm_id).await?,
+                section,
+                "password_policy",
+            )?),
+            None => None,
+        };
+
+        let updated = sections(&payload)
+            .iter()
+            .filter(|(_, section)| section.is_some())
+            .count() as u32;
+
+        if dry_run {
+            return Ok(HarborImportResourceResult {
+                key: self.key().to_string(),
+                status: "validated".to_string(),
+                created: 0,
+                updated,
+                errors,
+                original_id: None,
+                renamed_to: None,
+            });
+        }
+
+        // Each section is a complete settings record; the services validate it
+        // and write it inside the import transaction.
+        if let Some(email) = email {
+            self.email_settings_service
+                .save_settings_with_tx(email, tx.as_deref_mut())
+                .await?;
+        }
+        if let Some(passkey) = passkey {
+            self.passkey_settings_service
+                .save_settings_with_tx(passkey, tx.as_deref_mut())
+                .await?;
+        }
+        if let Some(recovery) = recovery {
+            self.recovery_settings_service
+                .save_settings_with_tx(recovery, tx.as_deref_mut())
+                .await?;
+        }
+        if let Some(security_headers) = security_headers {
+            self.security_headers_service
+                .save_settings_with_tx(security_headers, tx.as_deref_mut())
+                .await?;
+        }
+        if let Some(identity_providers) = identity_providers {
+            self.idp_settings_service
+                .save_settings_with_tx(identity_providers, tx.as_deref_mut())
+                .await?;
+        }
+        if let Some(password_policy) = password_policy {
+            self.password_policy_service
+                .save_policy_with_tx(password_policy, tx)
+                .await?;
+        }
+
+        Ok(HarborImportResourceResult {
+            key: self.key().to_string(),
+            status: "updated".to_string(),
+            created: 0,
+            updated,
+            errors,
+            original_id: None,
+            renamed_to: None,
+        })
+    }
+}
+
+fn sections(payload: &HarborRealmSettingsPayload) -> [(&'static str, Option<&Value>); 6] {
+    [
+        ("email", payload.email.as_ref()),
+        ("passkey", payload.passkey.as_ref()),
+        ("recovery", payload.recovery.as_ref()),
+        ("security_headers", payload.security_headers.as_ref()),
+        ("identity_providers", payload.identity_providers.as_ref()),
+        ("password_policy", payload.password_policy.as_ref()),
+    ]
+}
+
+fn export_section<T: Serialize>(settings: &T) -> Result<Value> {
+    let mut value = to_value(settings)
+        .map_err(|err| Error::System(format!("Failed to serialize realm settings: {}", err)))?;
+    if let Some(obj) = value.as_object_mut() {
+        obj.remove("realm_id");
+    }
+    Ok(value)
+}
+
+fn merge_section<T: Serialize + DeserializeOwned>(
+    realm_id: Uuid,
+    current: &T,
+    section: &Value,
+    name: &str,
+) -> Result<T> {
+    let mut merged = to_value(current)
+        .map_err(|err| Error::System(format!("Failed to serialize realm settings: {}", err)))?;
+    if let (Some(target), Some(source)) = (merged.as_object_mut(), section.as_object()) {
+        for (field, value) in source {
+            target.insert(field.clone(), value.clone());
+        }
+        target.insert("realm_id".to_string(), Value::String(realm_id.to_string()));
+    }
+    serde_json::from_value(merged).map_err(|err| {
+        Error::Validation(format!(
+            "Invalid realm settings section '{}': {}",
+            name, err
+        ))
+    })
+}
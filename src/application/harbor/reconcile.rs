@@ -0,0 +1,673 @@
+use super::service::HarborService;
+use crate::application::harbor::archive::read_bundle_from_path;
+use crate::application::harbor::schema::validate_bundle_schema;
+use crate::application::harbor::types::*;
+use crate::error::{Error, Result};
+use crate::ports::transaction_manager::Transaction;
+use chrono::Utc;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use super::utils::*;
+
+const REDACTED: &str = "${REDACTED}";
+
+/// Plan entries for declared resources follow `import_full_bundle`'s order.
+const APPLY_ORDER: [&str; 10] = [
+    "client",
+    "role",
+    "user",
+    "group",
+    "identity_provider",
+    "flow",
+    "realm",
+    "realm_settings",
+    "webhook",
+    "theme",
+];
+
+/// Resource kinds pruning may delete, in the order deletes run. Users, flows
+/// and the realm singletons are never removed by reconcile.
+const PRUNE_ORDER: [&str; 6] = [
+    "theme",
+    "webhook",
+    "identity_provider",
+    "group",
+    "role",
+    "client",
+];
+
+/// Database ids carried in payloads. They differ between realms and between
+/// a bundle and the resources it created, so they never count as drift.
+const VOLATILE_FIELDS: [&str; 4] = ["endpoint_id", "group_id", "role_id", "user_id"];
+
+/// Reads every bundle under `path` and merges them into one full realm
+/// bundle. `*.json` files and `.zip`/`.tar`/`.tar.gz` archives are loaded;
+/// hidden entries (such as `.git`) and unpacked `manifest.json` files are
+/// ignored. A single file path is read on its own.
+pub fn read_bundle_dir(path: &Path) -> Result<HarborBundle> {
+    let mut files = Vec::new();
+    if path.is_dir() {
+        collect_bundle_files(path, &mut files)?;
+    } else {
+        files.push(path.to_path_buf());
+    }
+    files.sort();
+
+    let mut bundles = Vec::new();
+    for file in files {
+        if let Some(bundle) = read_bundle_file(&file)? {
+            bundles.push(bundle);
+        }
+    }
+
+    if bundles.is_empty() {
+        return Err(Error::Validation(format!(
+            "No Harbor bundles found in {}",
+            path.display()
+        )));
+    }
+
+    merge_bundles(bundles)
+}
+
+fn collect_bundle_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let entries = fs::read_dir(dir).map_err(|e| Error::Unexpected(e.into()))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| Error::Unexpected(e.into()))?;
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            collect_bundle_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn read_bundle_file(path: &Path) -> Result<Option<HarborBundle>> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    if name == "manifest.json" {
+        return Ok(None);
+    }
+
+    if name.ends_with(".json") {
+        let raw = fs::read_to_string(path).map_err(|e| Error::Unexpected(e.into()))?;
+        let value: Value = serde_json::from_str(&raw).map_err(|err| {
+            Error::Validation(format!("{}: invalid JSON: {}", path.display(), err))
+        })?;
+        validate_bundle_schema(&value).map_err(|err| with_path(path, err))?;
+        let bundle = serde_json::from_value(value).map_err(|err| {
+            Error::Validation(format!("{}: invalid bundle: {}", path.display(), err))
+        })?;
+        return Ok(Some(bundle));
+    }
+
+    if [".zip", ".tar", ".tar.gz", ".tgz"]
+        .iter()
+        .any(|extension| name.ends_with(extension))
+    {
+        return read_bundle_from_path(path)
+            .map(Some)
+            .map_err(|err| with_path(path, err));
+    }
+
+    Ok(None)
+}
+
+fn with_path(path: &Path, err: Error) -> Error {
+    match err {
+        Error::Validation(message) => Error::Validation(format!("{}: {}", path.display(), message)),
+        other => other,
+    }
+}
+
+/// Combines bundles of any scope into one full realm bundle. The managed
+/// selection is the union of the inputs' selections and resource keys.
+pub(crate) fn merge_bundles(bundles: Vec<HarborBundle>) -> Result<HarborBundle> {
+    let mut source_realm = None;
+    let mut selection = BTreeSet::new();
+    let mut resources = Vec::new();
+
+    for bundle in bundles {
+        let bundle = upgrade_bundle(bundle)?;
+        if source_realm.is_none() {
+            source_realm = Some(bundle.manifest.source_realm.clone());
+        }
+        if let Some(keys) = bundle.manifest.selection {
+            selection.extend(keys);
+        }
+        for resource in bundle.resources {
+            selection.insert(resource.key.clone());
+            resources.push(resource);
+        }
+    }
+
+    let source_realm = source_realm
+        .ok_or_else(|| Error::Validation("Bundle contains no resources".to_string()))?;
+
+    Ok(HarborBundle {
+        manifest: HarborManifest {
+            version: super::service::HARBOR_BUNDLE_VERSION.to_string(),
+            schema_version: super::service::HARBOR_SCHEMA_VERSION,
+            exported_at: Utc::now().to_rfc3339(),
+            source_realm,
+            export_type: HarborExportType::FullRealm,
+            selection: Some(selection.into_iter().collect()),
+        },
+        resources,
+    })
+}
+
+impl HarborService {
+    /// Diffs `bundle` against the realm without changing anything.
+    pub async fn plan_reconcile(
+        &self,
+        realm_id: Uuid,
+        bundle: HarborBundle,
+        prune: bool,
+    ) -> Result<HarborReconcilePlan> {
+        let bundle = self.prepare_reconcile_bundle(bundle)?;
+        let (plan, _) = self.build_reconcile_plan(realm_id, &bundle, prune).await?;
+        Ok(plan)
+    }
+
+    /// Plans and applies `bundle` in one transaction: declared resources that
+    /// are new or have drifted are imported with overwrite, then pruned
+    /// resources are deleted. Nothing is written if any step fails.
+    pub async fn apply_reconcile(
+        &self,
+        realm_id: Uuid,
+        bundle: HarborBundle,
+        prune: bool,
+    ) -> Result<HarborReconcileResult> {
+        let bundle = self.prepare_reconcile_bundle(bundle)?;
+        let (plan, pending) = self.build_reconcile_plan(realm_id, &bundle, prune).await?;
+
+        if !plan.has_changes() {
+            return Ok(HarborReconcileResult {
+                applied: false,
+                plan,
+                import: None,
+            });
+        }
+
+        let resources = pending
+            .into_iter()
+            .map(|idx| bundle.resources[idx].clone())
+            .collect::<Vec<_>>();
+        let deletes = plan
+            .entries
+            .iter()
+            .filter(|entry| entry.action == HarborPlanAction::Delete)
+            .filter_map(|entry| entry.scope.clone().map(|scope| (entry.key.clone(), scope)))
+            .collect::<Vec<_>>();
+
+        let total = plan.summary.create + plan.summary.update + plan.summary.delete;
+        let job_id = self
+            .start_job(
+                realm_id,
+                super::service::HARBOR_JOB_TYPE_RECONCILE,
+                &HarborScope::FullRealm,
+                total as i64,
+                false,
+                Some(ConflictPolicy::Overwrite),
+            )
+            .await;
+
+        let mut tx = self.tx_manager.begin().await?;
+        let outcome = self
+            .apply_reconcile_with_tx(realm_id, &bundle.manifest, resources, &deletes, &mut *tx)
+            .await;
+        let result = match outcome {
+            Ok(import) => self.tx_manager.commit(tx).await.map(|_| import),
+            Err(err) => {
+                self.tx_manager.rollback(tx).await?;
+                Err(err)
+            }
+        };
+
+        match result {
+            Ok(import) => {
+                if let Some(job_id) = job_id {
+                    let (created, updated) = import
+                        .as_ref()
+                        .map(summarize_import_counts)
+                        .unwrap_or_default();
+                    self.try_mark_completed(job_id, total as i64, created, updated)
+                        .await;
+                }
+                Ok(HarborReconcileResult {
+                    applied: true,
+                    plan,
+                    import,
+                })
+            }
+            Err(err) => {
+                if let Some(job_id) = job_id {
+                    self.try_mark_failed(job_id, &err).await;
+                }
+                Err(err)
+            }
+        }
+    }
+
+    fn prepare_reconcile_bundle(&self, bundle: HarborBundle) -> Result<HarborBundle> {
+        let bundle = merge_bundles(vec![bundle])?;
+        self.validate_bundle(&bundle, &HarborScope::FullRealm)?;
+
+        let mut declared = HashSet::new();
+        for resource in &bundle.resources {
+            let identity = resource_identity(resource)?;
+            if !declared.insert((resource.key.clone(), identity.clone())) {
+                return Err(Error::Validation(format!(
+                    "Bundle declares {} '{}' more than once",
+                    resource.key,
+                    identity_label(&resource.key, &identity)
+                )));
+            }
+        }
+
+        Ok(bundle)
+    }
+
+    /// Returns the plan and the indexes of the bundle resources it imports.
+    async fn build_reconcile_plan(
+        &self,
+        realm_id: Uuid,
+        bundle: &HarborBundle,
+        prune: bool,
+    ) -> Result<(HarborReconcilePlan, Vec<usize>)> {
+        let managed_keys = normalize_export_selection(bundle.manifest.selection.clone())?;
+        let live = self
+            .export_full_resources(realm_id, ExportPolicy::Redact, &managed_keys, None)
+            .await?;
+
+        let mut live_by_identity = HashMap::new();
+        for resource in &live {
+            live_by_identity.insert(
+                (resource.key.clone(), resource_identity(resource)?),
+                resource,
+            );
+        }
+
+        let mut entries = Vec::new();
+        let mut pending = Vec::new();
+        let mut declared = HashSet::new();
+        for (idx, resource) in bundle.resources.iter().enumerate() {
+            let identity = resource_identity(resource)?;
+            let (action, changed_fields) =
+                match live_by_identity.get(&(resource.key.clone(), identity.clone())) {
+                    None => (HarborPlanAction::Create, Vec::new()),
+                    Some(current) => {
+                        let changed = diff_resource(resource, current);
+                        if changed.is_empty() {
+                            (HarborPlanAction::Unchanged, changed)
+                        } else {
+                            (HarborPlanAction::Update, changed)
+                        }
+                    }
+                };
+            if action != HarborPlanAction::Unchanged {
+                pending.push(idx);
+            }
+            entries.push(HarborPlanEntry {
+                key: resource.key.clone(),
+                name: identity_label(&resource.key, &identity),
+                action,
+                changed_fields,
+                scope: None,
+            });
+            declared.insert((resource.key.clone(), identity));
+        }
+
+        let mut warnings = Vec::new();
+        let undeclared = live
+            .iter()
+            .map(|resource| Ok((resource, resource_identity(resource)?)))
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .filter(|(resource, identity)| {
+                !declared.contains(&(resource.key.clone(), identity.clone()))
+            })
+            .collect::<Vec<_>>();
+
+        let (prunable, kept): (Vec<_>, Vec<_>) = undeclared
+            .into_iter()
+            .partition(|(resource, _)| PRUNE_ORDER.contains(&resource.key.as_str()));
+
+        if !kept.is_empty() {
+            warnings.push(format!(
+                "{} undeclared users, flows or realm settings were left in place; reconcile never deletes them",
+                kept.len()
+            ));
+        }
+
+        if prune {
+            let prune_context = self.load_prune_context(realm_id, &managed_keys).await?;
+            for (resource, identity) in prunable {
+                let Some(scope) = prune_scope(resource, &identity, &prune_context, &mut warnings)?
+                else {
+                    continue;
+                };
+                entries.push(HarborPlanEntry {
+                    key: resource.key.clone(),
+                    name: identity_label(&resource.key, &identity),
+                    action: HarborPlanAction::Delete,
+                    changed_fields: Vec::new(),
+                    scope: Some(scope),
+                });
+            }
+        } else if !prunable.is_empty() {
+            warnings.push(format!(
+                "{} live resources are not declared in the bundle; reconcile with prune to delete them",
+                prunable.len()
+            ));
+        }
+
+        entries.sort_by_key(|entry| {
+            let order = if entry.action == HarborPlanAction::Delete {
+                PRUNE_ORDER.iter().position(|key| *key == entry.key)
+            } else {
+                APPLY_ORDER.iter().position(|key| *key == entry.key)
+            };
+            (
+                entry.action == HarborPlanAction::Delete,
+                order.unwrap_or(usize::MAX),
+                entry.name.clone(),
+            )
+        });
+
+        let mut summary = HarborPlanSummary::default();
+        for entry in &entries {
+            match entry.action {
+                HarborPlanAction::Create => summary.create += 1,
+                HarborPlanAction::Update => summary.update += 1,
+                HarborPlanAction::Delete => summary.delete += 1,
+                HarborPlanAction::Unchanged => summary.unchanged += 1,
+            }
+        }
+
+        Ok((
+            HarborReconcilePlan {
+                managed_keys,
+                prune,
+                summary,
+                entries,
+                warnings,
+            },
+            pending,
+        ))
+    }
+
+    async fn load_prune_context(
+        &self,
+        realm_id: Uuid,
+        managed_keys: &[String],
+    ) -> Result<PruneContext> {
+        let mut context = PruneContext::default();
+        if managed_keys.iter().any(|key| key == "client") {
+            context.config_clients = self
+                .list_all_clients(realm_id)
+                .await?
+                .into_iter()
+                .filter(|client| client.managed_by_config)
+                .map(|client| client.client_id)
+                .collect();
+        }
+        if managed_keys.iter().any(|key| key == "theme") {
+            context.theme_ids = self
+                .theme_service
+                .list_themes(realm_id)
+                .await?
+                .into_iter()
+                .map(|theme| (theme.name, theme.id))
+                .collect();
+        }
+        Ok(context)
+    }
+
+    async fn apply_reconcile_with_tx(
+        &self,
+        realm_id: Uuid,
+        manifest: &HarborManifest,
+        resources: Vec<HarborResourceBundle>,
+        deletes: &[(String, HarborScope)],
+        tx: &mut dyn Transaction,
+    ) -> Result<Option<HarborImportResult>> {
+        let import = if resources.is_empty() {
+            None
+        } else {
+            let bundle = HarborBundle {
+                manifest: manifest.clone(),
+                resources,
+            };
+            Some(
+                self.import_full_bundle(
+                    realm_id,
+                    bundle,
+                    ConflictPolicy::Overwrite,
+                    None,
+                    false,
+                    Some(&mut *tx),
+                )
+                .await?,
+            )
+        };
+
+        for (key, scope) in deletes {
+            let provider = self.registry.get(key).ok_or_else(|| {
+                Error::System(format!("Harbor provider not registered for {}", key))
+            })?;
+            provider.delete(realm_id, scope, Some(&mut *tx)).await?;
+        }
+
+        Ok(import)
+    }
+}
+
+#[derive(Default)]
+struct PruneContext {
+    config_clients: HashSet<String>,
+    theme_ids: HashMap<String, Uuid>,
+}
+
+/// The name a resource is matched on between the bundle and the realm.
+fn resource_identity(resource: &HarborResourceBundle) -> Result<String> {
+    let data = &resource.data;
+    let identity = match resource.key.as_str() {
+        "client" => get_string_field(data, "client_id"),
+        "role" => get_string_field(data, "name")
+            .map(|name| encode_role_ref(&name, get_string_field(data, "client_id").as_deref())),
+        "user" => get_string_field(data, "username"),
+        "group" | "webhook" => get_string_field(data, "name"),
+        "identity_provider" => get_string_field(data, "alias"),
+        "flow" => get_string_field(data, "flow_id").or_else(|| get_string_field(data, "name")),
+        "theme" => parse_theme_meta(resource)?.theme.map(|theme| theme.name),
+        "realm" | "realm_settings" => Some(String::new()),
+        other => {
+            return Err(Error::Validation(format!(
+                "Reconcile does not support {} resources",
+                other
+            )))
+        }
+    };
+
+    identity.ok_or_else(|| {
+        Error::Validation(format!(
+            "{} resource is missing the field that identifies it",
+            resource.key
+        ))
+    })
+}
+
+fn identity_label(key: &str, identity: &str) -> String {
+    match key {
+        "role" => match decode_role_ref(identity) {
+            (name, Some(client_id)) => format!("{}/{}", client_id, name),
+            (name, None) => name.to_string(),
+        },
+        "realm" | "realm_settings" => key.to_string(),
+        _ => identity.to_string(),
+    }
+}
+
+fn prune_scope(
+    resource: &HarborResourceBundle,
+    identity: &str,
+    context: &PruneContext,
+    warnings: &mut Vec<String>,
+) -> Result<Option<HarborScope>> {
+    let scope = match resource.key.as_str() {
+        "client" => {
+            if context.config_clients.contains(identity) {
+                warnings.push(format!(
+                    "Client '{}' is managed by configuration and was not pruned",
+                    identity
+                ));
+                return Ok(None);
+            }
+            HarborScope::Client {
+                client_id: identity.to_string(),
+            }
+        }
+        "role" => HarborScope::Role {
+            role_id: live_id(resource, "role_id")?,
+        },
+        "group" => HarborScope::Group {
+            group_id: live_id(resource, "group_id")?,
+        },
+        "webhook" => HarborScope::Webhook {
+            endpoint_id: live_id(resource, "endpoint_id")?,
+        },
+        "identity_provider" => HarborScope::IdentityProvider {
+            alias: identity.to_string(),
+        },
+        "theme" => {
+            let meta = parse_theme_meta(resource)?;
+            let bindings = meta.bindings.unwrap_or_default();
+            let blocker = if meta.theme.as_ref().is_some_and(|theme| theme.is_system) {
+                Some("is the system theme")
+            } else if bindings.default {
+                Some("is the realm's active theme")
+            } else if !bindings.clients.is_empty() {
+                Some("is assigned to clients")
+            } else {
+                None
+            };
+            if let Some(blocker) = blocker {
+                warnings.push(format!(
+                    "Theme '{}' {} and was not pruned",
+                    identity, blocker
+                ));
+                return Ok(None);
+            }
+            let theme_id = context
+                .theme_ids
+                .get(identity)
+                .copied()
+                .ok_or_else(|| Error::NotFound(format!("Theme '{}' not found", identity)))?;
+            HarborScope::Theme { theme_id }
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(scope))
+}
+
+fn live_id(resource: &HarborResourceBundle, field: &str) -> Result<Uuid> {
+    get_string_field(&resource.data, field)
+        .and_then(|value| Uuid::parse_str(&value).ok())
+        .ok_or_else(|| {
+            Error::System(format!(
+                "Exported {} resource is missing {}",
+                resource.key, field
+            ))
+        })
+}
+
+/// Top-level fields of `desired` that differ from `current`. Fields the
+/// bundle leaves out are not compared, since imports keep or default them.
+fn diff_resource(desired: &HarborResourceBundle, current: &HarborResourceBundle) -> Vec<String> {
+    let mut changed = Vec::new();
+
+    match (desired.data.as_object(), current.data.as_object()) {
+        (Some(desired_fields), Some(current_fields)) => {
+            for (field, value) in desired_fields {
+                if VOLATILE_FIELDS.contains(&field.as_str()) {
+                    continue;
+                }
+                if !values_match(value, current_fields.get(field).unwrap_or(&Value::Null)) {
+                    changed.push(field.clone());
+                }
+            }
+        }
+        _ => {
+            if !values_match(&desired.data, &current.data) {
+                changed.push("data".to_string());
+            }
+        }
+    }
+
+    if let Some(desired_meta) = desired.meta.as_ref().and_then(Value::as_object) {
+        let current_meta = current.meta.as_ref().and_then(Value::as_object);
+        for (field, value) in desired_meta {
+            let current_value = current_meta
+                .and_then(|meta| meta.get(field))
+                .unwrap_or(&Value::Null);
+            if !values_match(value, current_value) {
+                changed.push(format!("meta.{}", field));
+            }
+        }
+    }
+
+    if asset_fingerprint(&desired.assets) != asset_fingerprint(&current.assets) {
+        changed.push("assets".to_string());
+    }
+
+    changed
+}
+
+/// Redacted secrets on either side match anything: the live side is always
+/// exported redacted, so a declared secret cannot be compared.
+fn values_match(desired: &Value, current: &Value) -> bool {
+    match (desired, current) {
+        (Value::String(value), _) | (_, Value::String(value)) if value == REDACTED => true,
+        (Value::Object(desired), Value::Object(current)) => desired
+            .iter()
+            .all(|(field, value)| values_match(value, current.get(field).unwrap_or(&Value::Null))),
+        (Value::Array(desired), Value::Array(current)) => {
+            desired.len() == current.len()
+                && desired
+                    .iter()
+                    .zip(current)
+                    .all(|(desired, current)| values_match(desired, current))
+        }
+        _ => desired == current,
+    }
+}
+
+fn asset_fingerprint(assets: &[HarborAsset]) -> Vec<(&str, &str, Option<&str>, &str)> {
+    let mut fingerprint = assets
+        .iter()
+        .map(|asset| {
+            (
+                asset.filename.as_str(),
+                asset.mime_type.as_str(),
+                asset.asset_type.as_deref(),
+                asset.data_base64.as_str(),
+            )
+        })
+        .collect::<Vec<_>>();
+    fingerprint.sort();
+    fingerprint
+}
@@ -124,7 +124,7 @@ impl HarborProvider for RoleHarborProvider {
         resource: &HarborResourceBundle,
         conflict_policy: ConflictPolicy,
         dry_run: bool,
-        tx: Option<&mut dyn Transaction>,
+        mut tx: Option<&mut dyn Transaction>,
     ) -> Result<HarborImportResourceResult> {
         let fallback_role_id = match scope {
             HarborScope::Role { role_id } => *role_id,
@@ -138,8 +138,13 @@ impl HarborProvider for RoleHarborProvider {
         let mut payload: HarborRolePayload = serde_json::from_value(resource.data.clone())
             .map_err(|err| Error::Validation(format!("Invalid role bundle payload: {}", err)))?;
 
-        let namespace_client_id =
-            resolve_client_uuid(&self.oidc_service, realm_id, payload.client_id.as_deref()).await?;
+        let namespace_client_id = resolve_client_uuid(
+            &self.oidc_service,
+            realm_id,
+            payload.client_id.as_deref(),
+            tx.as_deref_mut(),
+        )
+        .await?;
         let existing = find_role_in_namespace(
             &*self.rbac_repo,
             realm_id,
@@ -246,19 +251,45 @@ impl HarborProvider for RoleHarborProvider {
         )
         .await
     }
+
+    async fn delete(
+        &self,
+        realm_id: Uuid,
+        scope: &HarborScope,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        let role_id = match scope {
+            HarborScope::Role { role_id } => *role_id,
+            _ => {
+                return Err(Error::Validation(
+                    "Role delete requires role scope".to_string(),
+                ))
+            }
+        };
+
+        let role = self
+            .rbac_repo
+            .find_role_by_id(&role_id)
+            .await?
+            .filter(|role| role.realm_id == realm_id)
+            .ok_or_else(|| Error::NotFound("Role not found".to_string()))?;
+
+        self.rbac_repo.delete_role(&role.id, tx).await
+    }
 }
 
 async fn resolve_client_uuid(
     oidc_service: &OidcService,
     realm_id: Uuid,
     client_id: Option<&str>,
+    tx: Option<&mut dyn Transaction>,
 ) -> Result<Option<Uuid>> {
     let Some(client_id) = client_id.map(str::trim).filter(|value| !value.is_empty()) else {
         return Ok(None);
     };
 
     let client = oidc_service
-        .find_client_by_client_id(&realm_id, client_id)
+        .find_client_by_client_id_with_tx(&realm_id, client_id, tx)
         .await?
         .ok_or_else(|| {
             Error::Validation(format!("Role references unknown client_id '{}'", client_id))
@@ -59,6 +59,38 @@ static REALM_RESOURCE_SCHEMA: Lazy<Validator> = Lazy::new(|| {
     Validator::new(&schema).expect("compile realm schema")
 });
 
+static IDENTITY_PROVIDER_RESOURCE_SCHEMA: Lazy<Validator> = Lazy::new(|| {
+    let schema: Value = serde_json::from_str(include_str!(
+        "../../../docs/schemas/harbor/resource-identity-provider.schema.json"
+    ))
+    .expect("identity provider schema");
+    Validator::new(&schema).expect("compile identity provider schema")
+});
+
+static WEBHOOK_RESOURCE_SCHEMA: Lazy<Validator> = Lazy::new(|| {
+    let schema: Value = serde_json::from_str(include_str!(
+        "../../../docs/schemas/harbor/resource-webhook.schema.json"
+    ))
+    .expect("webhook schema");
+    Validator::new(&schema).expect("compile webhook schema")
+});
+
+static GROUP_RESOURCE_SCHEMA: Lazy<Validator> = Lazy::new(|| {
+    let schema: Value = serde_json::from_str(include_str!(
+        "../../../docs/schemas/harbor/resource-group.schema.json"
+    ))
+    .expect("group schema");
+    Validator::new(&schema).expect("compile group schema")
+});
+
+static REALM_SETTINGS_RESOURCE_SCHEMA: Lazy<Validator> = Lazy::new(|| {
+    let schema: Value = serde_json::from_str(include_str!(
+        "../../../docs/schemas/harbor/resource-realm-settings.schema.json"
+    ))
+    .expect("realm settings schema");
+    Validator::new(&schema).expect("compile realm settings schema")
+});
+
 pub fn validate_bundle_schema(value: &Value) -> Result<()> {
     validate_with_schema(&BUNDLE_SCHEMA, value, "bundle")
 }
@@ -71,6 +103,18 @@ pub fn validate_resource_schema(key: &str, value: &Value) -> Result<()> {
         "user" => validate_with_schema(&USER_RESOURCE_SCHEMA, value, "user resource"),
         "role" => validate_with_schema(&ROLE_RESOURCE_SCHEMA, value, "role resource"),
         "realm" => validate_with_schema(&REALM_RESOURCE_SCHEMA, value, "realm resource"),
+        "identity_provider" => validate_with_schema(
+            &IDENTITY_PROVIDER_RESOURCE_SCHEMA,
+            value,
+            "identity provider resource",
+        ),
+        "webhook" => validate_with_schema(&WEBHOOK_RESOURCE_SCHEMA, value, "webhook resource"),
+        "group" => validate_with_schema(&GROUP_RESOURCE_SCHEMA, value, "group resource"),
+        "realm_settings" => validate_with_schema(
+            &REALM_SETTINGS_RESOURCE_SCHEMA,
+            value,
+            "realm settings resource",
+        ),
         _ => Ok(()),
     }
 }
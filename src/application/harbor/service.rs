@@ -2,19 +2,22 @@ use crate::application::flow_manager::FlowManager;
 use crate::application::flow_service::FlowService;
 use crate::application::harbor::provider::HarborRegistry;
 use crate::application::harbor::runner::HarborJobRunner;
+use crate::application::idp_service::IdentityProviderService;
 use crate::application::oidc_service::OidcService;
 use crate::application::rbac_service::RbacService;
 use crate::application::theme_service::ThemeResolverService;
 use crate::application::user_service::UserService;
+use crate::application::webhook_service::WebhookService;
 use crate::ports::harbor_job_conflict_repository::HarborJobConflictRepository;
 use crate::ports::harbor_job_repository::HarborJobRepository;
 use crate::ports::transaction_manager::TransactionManager;
 use std::sync::Arc;
 
 pub(crate) const HARBOR_BUNDLE_VERSION: &str = "1.0";
-pub(crate) const HARBOR_SCHEMA_VERSION: u32 = 1;
+pub(crate) const HARBOR_SCHEMA_VERSION: u32 = 2;
 pub(crate) const HARBOR_JOB_TYPE_IMPORT: &str = "import";
 pub(crate) const HARBOR_JOB_TYPE_EXPORT: &str = "export";
+pub(crate) const HARBOR_JOB_TYPE_RECONCILE: &str = "reconcile";
 pub(crate) const HARBOR_JOB_STATUS_IN_PROGRESS: &str = "in_progress";
 
 pub(crate) struct ImportProgress {
@@ -31,6 +34,8 @@ pub struct HarborService {
     pub(crate) flow_manager: Arc<FlowManager>,
     pub(crate) rbac_service: Arc<RbacService>,
     pub(crate) user_service: Arc<UserService>,
+    pub(crate) identity_provider_service: Arc<IdentityProviderService>,
+    pub(crate) webhook_service: Arc<WebhookService>,
     pub(crate) tx_manager: Arc<dyn TransactionManager>,
     pub(crate) job_repo: Arc<dyn HarborJobRepository>,
     pub(crate) conflict_repo: Arc<dyn HarborJobConflictRepository>,
@@ -47,6 +52,8 @@ impl HarborService {
         flow_manager: Arc<FlowManager>,
         rbac_service: Arc<RbacService>,
         user_service: Arc<UserService>,
+        identity_provider_service: Arc<IdentityProviderService>,
+        webhook_service: Arc<WebhookService>,
         tx_manager: Arc<dyn TransactionManager>,
         job_repo: Arc<dyn HarborJobRepository>,
         conflict_repo: Arc<dyn HarborJobConflictRepository>,
@@ -60,6 +67,8 @@ impl HarborService {
             flow_manager,
             rbac_service,
             user_service,
+            identity_provider_service,
+            webhook_service,
             tx_manager,
             job_repo,
             conflict_repo,
@@ -271,6 +271,26 @@ impl HarborProvider for ThemeHarborProvider {
             renamed_to: None,
         })
     }
+
+    async fn delete(
+        &self,
+        realm_id: Uuid,
+        scope: &HarborScope,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        let theme_id = match scope {
+            HarborScope::Theme { theme_id } => *theme_id,
+            _ => {
+                return Err(Error::Validation(
+                    "Theme delete requires theme scope".to_string(),
+                ))
+            }
+        };
+
+        self.theme_service
+            .delete_theme_with_tx(realm_id, theme_id, tx)
+            .await
+    }
 }
 
 fn rewrite_blueprint_assets(value: &mut Value, id_map: &std::collections::HashMap<String, String>) {
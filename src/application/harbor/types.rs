@@ -17,6 +17,9 @@ pub enum HarborExportType {
     Flow,
     User,
     Role,
+    IdentityProvider,
+    Webhook,
+    Group,
     FullRealm,
 }
 
@@ -36,6 +39,9 @@ pub enum HarborScope {
     Flow { flow_id: Uuid },
     User { user_id: Uuid },
     Role { role_id: Uuid },
+    IdentityProvider { alias: String },
+    Webhook { endpoint_id: Uuid },
+    Group { group_id: Uuid },
     FullRealm,
 }
 
@@ -47,6 +53,9 @@ impl HarborScope {
             HarborScope::Flow { .. } => HarborExportType::Flow,
             HarborScope::User { .. } => HarborExportType::User,
             HarborScope::Role { .. } => HarborExportType::Role,
+            HarborScope::IdentityProvider { .. } => HarborExportType::IdentityProvider,
+            HarborScope::Webhook { .. } => HarborExportType::Webhook,
+            HarborScope::Group { .. } => HarborExportType::Group,
             HarborScope::FullRealm => HarborExportType::FullRealm,
         }
     }
@@ -58,6 +67,9 @@ impl HarborScope {
             HarborScope::Flow { .. } => Some("flow"),
             HarborScope::User { .. } => Some("user"),
             HarborScope::Role { .. } => Some("role"),
+            HarborScope::IdentityProvider { .. } => Some("identity_provider"),
+            HarborScope::Webhook { .. } => Some("webhook"),
+            HarborScope::Group { .. } => Some("group"),
             HarborScope::FullRealm => None,
         }
     }
@@ -123,6 +135,59 @@ pub struct HarborImportResult {
     pub warnings: Vec<String>,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HarborPlanAction {
+    Create,
+    Update,
+    Delete,
+    Unchanged,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarborPlanEntry {
+    pub key: String,
+    pub name: String,
+    pub action: HarborPlanAction,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changed_fields: Vec<String>,
+    /// Live resource a delete applies to.
+    #[serde(skip)]
+    pub(crate) scope: Option<HarborScope>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HarborPlanSummary {
+    pub create: u32,
+    pub update: u32,
+    pub delete: u32,
+    pub unchanged: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarborReconcilePlan {
+    pub managed_keys: Vec<String>,
+    pub prune: bool,
+    pub summary: HarborPlanSummary,
+    pub entries: Vec<HarborPlanEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+impl HarborReconcilePlan {
+    pub fn has_changes(&self) -> bool {
+        self.summary.create + self.summary.update + self.summary.delete > 0
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarborReconcileResult {
+    pub applied: bool,
+    pub plan: HarborReconcilePlan,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub import: Option<HarborImportResult>,
+}
+
 fn default_true() -> bool {
     true
 }
@@ -3,7 +3,6 @@ use crate::application::harbor::types::{
     ConflictPolicy, ExportPolicy, HarborImportResourceResult, HarborResourceBundle, HarborScope,
 };
 use crate::application::oidc_service::OidcService;
-use crate::domain::pagination::PageRequest;
 use crate::domain::role::Role;
 use crate::domain::user::User;
 use crate::error::{Error, Result};
@@ -19,11 +18,13 @@ use uuid::Uuid;
 
 const REDACTED_CREDENTIAL: &str = "${REDACTED}";
 
+/// A realm role (`client_id` unset) or client role referenced by name, so
+/// bundles stay portable across realms.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
-struct HarborUserRoleRef {
-    name: String,
+pub(crate) struct HarborRoleRef {
+    pub(crate) name: String,
     #[serde(default)]
-    client_id: Option<String>,
+    pub(crate) client_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,7 +35,7 @@ struct HarborUserPayload {
     #[serde(default)]
     hashed_password: Option<String>,
     #[serde(default)]
-    direct_roles: Vec<HarborUserRoleRef>,
+    direct_roles: Vec<HarborRoleRef>,
 }
 
 struct UserImportContext<'a> {
@@ -124,29 +125,8 @@ impl HarborProvider for UserHarborProvider {
             .rbac_repo
             .find_direct_role_ids_for_user(&user.id)
             .await?;
-        let mut direct_roles = Vec::new();
-        for role_id in direct_role_ids {
-            let role = self
-                .rbac_repo
-                .find_role_by_id(&role_id)
-                .await?
-                .ok_or_else(|| Error::NotFound("Role not found".to_string()))?;
-            let client_id = match role.client_id {
-                Some(client_uuid) => {
-                    Some(self.oidc_service.get_client(client_uuid).await?.client_id)
-                }
-                None => None,
-            };
-            direct_roles.push(HarborUserRoleRef {
-                name: role.name,
-                client_id,
-            });
-        }
-        direct_roles.sort_by(|a, b| {
-            a.client_id
-                .cmp(&b.client_id)
-                .then_with(|| a.name.cmp(&b.name))
-        });
+        let direct_roles =
+            describe_role_refs(&*self.rbac_repo, &self.oidc_service, &direct_role_ids).await?;
 
         let hashed_password = match policy {
             ExportPolicy::IncludeSecrets => Some(user.hashed_password.clone()),
@@ -176,7 +156,7 @@ impl HarborProvider for UserHarborProvider {
         resource: &HarborResourceBundle,
         conflict_policy: ConflictPolicy,
         dry_run: bool,
-        tx: Option<&mut dyn Transaction>,
+        mut tx: Option<&mut dyn Transaction>,
     ) -> Result<HarborImportResourceResult> {
         let scoped_user_id = match scope {
             HarborScope::User { user_id } => *user_id,
@@ -207,6 +187,7 @@ impl HarborProvider for UserHarborProvider {
             &self.oidc_service,
             realm_id,
             &payload.direct_roles,
+            tx.as_deref_mut(),
         )
         .await?;
 
@@ -345,15 +326,45 @@ async fn resolve_available_username(
     ))
 }
 
-async fn resolve_role_refs(
+/// Describes roles by name and owning client_id, sorted for stable exports.
+pub(crate) async fn describe_role_refs(
+    repo: &dyn RbacRepository,
+    oidc_service: &OidcService,
+    role_ids: &[Uuid],
+) -> Result<Vec<HarborRoleRef>> {
+    let mut refs = Vec::new();
+    for role_id in role_ids {
+        let role = repo
+            .find_role_by_id(role_id)
+            .await?
+            .ok_or_else(|| Error::NotFound("Role not found".to_string()))?;
+        let client_id = match role.client_id {
+            Some(client_uuid) => Some(oidc_service.get_client(client_uuid).await?.client_id),
+            None => None,
+        };
+        refs.push(HarborRoleRef {
+            name: role.name,
+            client_id,
+        });
+    }
+    refs.sort_by(|a, b| {
+        a.client_id
+            .cmp(&b.client_id)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    Ok(refs)
+}
+
+pub(crate) async fn resolve_role_refs(
     repo: &dyn RbacRepository,
     oidc_service: &OidcService,
     realm_id: Uuid,
-    refs: &[HarborUserRoleRef],
+    refs: &[HarborRoleRef],
+    mut tx: Option<&mut dyn Transaction>,
 ) -> Result<Vec<Uuid>> {
     let mut ids = Vec::new();
     for role_ref in refs {
-        let role = find_role_ref(repo, oidc_service, realm_id, role_ref).await?;
+        let role = find_role_ref(repo, oidc_service, realm_id, role_ref, tx.as_deref_mut()).await?;
         ids.push(role.id);
     }
     ids.sort();
@@ -365,57 +376,33 @@ async fn find_role_ref(
     repo: &dyn RbacRepository,
     oidc_service: &OidcService,
     realm_id: Uuid,
-    role_ref: &HarborUserRoleRef,
+    role_ref: &HarborRoleRef,
+    mut tx: Option<&mut dyn Transaction>,
 ) -> Result<Role> {
     if let Some(client_id) = role_ref.client_id.as_deref() {
         let client = oidc_service
-            .find_client_by_client_id(&realm_id, client_id)
+            .find_client_by_client_id_with_tx(&realm_id, client_id, tx.as_deref_mut())
             .await?
             .ok_or_else(|| {
-                Error::Validation(format!(
-                    "User role references unknown client_id '{}'",
-                    client_id
-                ))
+                Error::Validation(format!("Role references unknown client_id '{}'", client_id))
             })?;
 
-        let mut page = 1;
-        loop {
-            let response = repo
-                .list_client_roles(
-                    &realm_id,
-                    &client.id,
-                    &PageRequest {
-                        page,
-                        per_page: 200,
-                        q: Some(role_ref.name.clone()),
-                        ..PageRequest::default()
-                    },
-                )
-                .await?;
-
-            if let Some(role) = response
-                .data
-                .into_iter()
-                .find(|role| role.name == role_ref.name)
-            {
-                return Ok(role);
-            }
-
-            if response.meta.page >= response.meta.total_pages {
-                break;
-            }
-            page += 1;
-        }
-
-        return Err(Error::Validation(format!(
-            "User role '{}' not found for client '{}'",
-            role_ref.name, client_id
-        )));
+        return repo
+            .find_roles_for_client_with_tx(&realm_id, &client.id, tx)
+            .await?
+            .into_iter()
+            .find(|role| role.name == role_ref.name)
+            .ok_or_else(|| {
+                Error::Validation(format!(
+                    "Role '{}' not found for client '{}'",
+                    role_ref.name, client_id
+                ))
+            });
     }
 
-    repo.find_role_by_name(&realm_id, &role_ref.name)
+    repo.find_role_by_name_with_tx(&realm_id, &role_ref.name, tx)
         .await?
-        .ok_or_else(|| Error::Validation(format!("User role '{}' not found", role_ref.name)))
+        .ok_or_else(|| Error::Validation(format!("Role '{}' not found", role_ref.name)))
 }
 
 async fn sync_direct_roles(
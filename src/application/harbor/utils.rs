@@ -16,20 +16,42 @@ pub(crate) fn upgrade_bundle(mut bundle: HarborBundle) -> Result<HarborBundle> {
         )));
     }
 
-    match bundle.manifest.schema_version {
-        0 => {
-            bundle = upgrade_v0_to_v1(bundle);
-            Ok(bundle)
-        }
-        _ => Err(Error::Validation(format!(
-            "Unsupported schema version: {}",
-            bundle.manifest.schema_version
-        ))),
+    while bundle.manifest.schema_version < super::service::HARBOR_SCHEMA_VERSION {
+        bundle = match bundle.manifest.schema_version {
+            0 => upgrade_v0_to_v1(bundle),
+            1 => upgrade_v1_to_v2(bundle),
+            version => {
+                return Err(Error::Validation(format!(
+                    "Unsupported schema version: {}",
+                    version
+                )))
+            }
+        };
     }
+
+    Ok(bundle)
 }
 
 pub(crate) fn upgrade_v0_to_v1(mut bundle: HarborBundle) -> HarborBundle {
-    bundle.manifest.schema_version = super::service::HARBOR_SCHEMA_VERSION;
+    bundle.manifest.schema_version = 1;
+    bundle
+}
+
+/// Version 2 added identity providers, webhooks, groups and realm settings
+/// to full realm bundles. A v1 manifest without a selection exported the v1
+/// defaults, so record them; otherwise the resource shapes are unchanged.
+pub(crate) fn upgrade_v1_to_v2(mut bundle: HarborBundle) -> HarborBundle {
+    if bundle.manifest.export_type == HarborExportType::FullRealm
+        && bundle.manifest.selection.is_none()
+    {
+        bundle.manifest.selection = Some(
+            ["client", "flow", "realm", "role", "theme"]
+                .into_iter()
+                .map(ToString::to_string)
+                .collect(),
+        );
+    }
+    bundle.manifest.schema_version = 2;
     bundle
 }
 
@@ -89,14 +111,73 @@ pub(crate) fn rewrite_user_role_client_ids(
     value: &mut serde_json::Value,
     map: &std::collections::HashMap<String, String>,
 ) {
-    let Some(direct_roles) = value
+    if let Some(direct_roles) = value
         .get_mut("direct_roles")
         .and_then(|entry| entry.as_array_mut())
-    else {
-        return;
-    };
+    {
+        rewrite_role_client_ids(direct_roles, map);
+    }
+}
+
+pub(crate) fn rewrite_user_role_refs(
+    value: &mut serde_json::Value,
+    map: &std::collections::HashMap<String, String>,
+) {
+    if let Some(direct_roles) = value
+        .get_mut("direct_roles")
+        .and_then(|entry| entry.as_array_mut())
+    {
+        rewrite_role_refs(direct_roles, map);
+    }
+}
 
-    for role in direct_roles {
+/// Applies client renames to the `roles` of every node in a group tree.
+pub(crate) fn rewrite_group_role_client_ids(
+    value: &mut serde_json::Value,
+    map: &std::collections::HashMap<String, String>,
+) {
+    if let Some(roles) = value
+        .get_mut("roles")
+        .and_then(|entry| entry.as_array_mut())
+    {
+        rewrite_role_client_ids(roles, map);
+    }
+    if let Some(children) = value
+        .get_mut("children")
+        .and_then(|entry| entry.as_array_mut())
+    {
+        for child in children {
+            rewrite_group_role_client_ids(child, map);
+        }
+    }
+}
+
+/// Applies role renames to the `roles` of every node in a group tree.
+pub(crate) fn rewrite_group_role_refs(
+    value: &mut serde_json::Value,
+    map: &std::collections::HashMap<String, String>,
+) {
+    if let Some(roles) = value
+        .get_mut("roles")
+        .and_then(|entry| entry.as_array_mut())
+    {
+        rewrite_role_refs(roles, map);
+    }
+    if let Some(children) = value
+        .get_mut("children")
+        .and_then(|entry| entry.as_array_mut())
+    {
+        for child in children {
+            rewrite_group_role_refs(child, map);
+        }
+    }
+}
+
+fn rewrite_role_client_ids(
+    roles: &mut [serde_json::Value],
+    map: &std::collections::HashMap<String, String>,
+) {
+    for role in roles {
         let Some(obj) = role.as_object_mut() else {
             continue;
         };
@@ -112,18 +193,11 @@ pub(crate) fn rewrite_user_role_client_ids(
     }
 }
 
-pub(crate) fn rewrite_user_role_refs(
-    value: &mut serde_json::Value,
+fn rewrite_role_refs(
+    roles: &mut [serde_json::Value],
     map: &std::collections::HashMap<String, String>,
 ) {
-    let Some(direct_roles) = value
-        .get_mut("direct_roles")
-        .and_then(|entry| entry.as_array_mut())
-    else {
-        return;
-    };
-
-    for role in direct_roles {
+    for role in roles {
         let Some(obj) = role.as_object_mut() else {
             continue;
         };
@@ -210,9 +284,13 @@ pub(crate) fn normalize_export_selection(selection: Option<Vec<String>>) -> Resu
         vec![
             "client".to_string(),
             "flow".to_string(),
+            "group".to_string(),
+            "identity_provider".to_string(),
             "realm".to_string(),
+            "realm_settings".to_string(),
             "role".to_string(),
             "theme".to_string(),
+            "webhook".to_string(),
         ]
     });
 
@@ -221,10 +299,14 @@ pub(crate) fn normalize_export_selection(selection: Option<Vec<String>>) -> Resu
         let key = match key.as_str() {
             "client" | "clients" => "client",
             "flow" | "flows" => "flow",
+            "group" | "groups" => "group",
+            "identity_provider" | "identity_providers" | "idp" | "idps" => "identity_provider",
             "realm" | "realms" | "settings" => "realm",
+            "realm_settings" => "realm_settings",
             "role" | "roles" | "rbac" => "role",
             "theme" | "themes" => "theme",
             "user" | "users" => "user",
+            "webhook" | "webhooks" => "webhook",
             _ => {
                 return Err(Error::Validation(format!(
                     "Unsupported export selection: {}",
@@ -293,6 +375,9 @@ pub(crate) fn canonicalize_value(value: &mut Value) {
 pub(crate) fn resource_sort_key(resource: &HarborResourceBundle) -> (String, String) {
     let secondary = match resource.key.as_str() {
         "client" => get_string_field(&resource.data, "client_id"),
+        "group" => get_string_field(&resource.data, "name"),
+        "identity_provider" => get_string_field(&resource.data, "alias"),
+        "webhook" => get_string_field(&resource.data, "name"),
         "flow" => get_string_field(&resource.data, "flow_id")
             .or_else(|| get_string_field(&resource.data, "name")),
         "theme" => resource
@@ -361,6 +446,9 @@ pub(crate) fn scope_label(scope: &HarborScope) -> &'static str {
         HarborScope::Flow { .. } => "flow",
         HarborScope::User { .. } => "user",
         HarborScope::Role { .. } => "role",
+        HarborScope::IdentityProvider { .. } => "identity_provider",
+        HarborScope::Webhook { .. } => "webhook",
+        HarborScope::Group { .. } => "group",
         HarborScope::FullRealm => "full_realm",
     }
 }
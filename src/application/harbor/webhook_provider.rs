@@ -0,0 +1,451 @@
+use crate::application::harbor::provider::HarborProvider;
+use crate::application::harbor::types::{
+    ConflictPolicy, ExportPolicy, HarborImportResourceResult, HarborResourceBundle, HarborScope,
+};
+use crate::application::webhook_service::{
+    normalize_http_method, validate_subscription_event_types, WEBHOOK_STATUS_ACTIVE,
+    WEBHOOK_STATUS_DISABLED_SYSTEM, WEBHOOK_STATUS_DISABLED_USER,
+};
+use crate::domain::webhook::WebhookEndpoint;
+use crate::error::{Error, Result};
+use crate::ports::transaction_manager::Transaction;
+use crate::ports::webhook_repository::WebhookRepository;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::to_value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use uuid::Uuid;
+
+const REDACTED_SECRET: &str = "${REDACTED}";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HarborWebhookSubscription {
+    event_type: String,
+    enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HarborWebhookPayload {
+    #[serde(default)]
+    endpoint_id: Option<String>,
+    name: String,
+    url: String,
+    #[serde(default)]
+    http_method: Option<String>,
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    signing_secret: Option<String>,
+    #[serde(default)]
+    custom_headers: BTreeMap<String, String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    subscriptions: Vec<HarborWebhookSubscription>,
+}
+
+pub struct WebhookHarborProvider {
+    repo: Arc<dyn WebhookRepository>,
+}
+
+impl WebhookHarborProvider {
+    pub fn new(repo: Arc<dyn WebhookRepository>) -> Self {
+        Self { repo }
+    }
+
+    async fn find_by_name(&self, realm_id: Uuid, name: &str) -> Result<Option<WebhookEndpoint>> {
+        Ok(self
+            .repo
+            .list_endpoints(&realm_id)
+            .await?
+            .into_iter()
+            .find(|endpoint| endpoint.name == name))
+    }
+
+    async fn resolve_available_name(&self, realm_id: Uuid, base: &str) -> Result<String> {
+        let names = self
+            .repo
+            .list_endpoints(&realm_id)
+            .await?
+            .into_iter()
+            .map(|endpoint| endpoint.name)
+            .collect::<HashSet<_>>();
+        for idx in 1..=1000 {
+            let candidate = format!("{}-{}", base, idx);
+            if !names.contains(&candidate) {
+                return Ok(candidate);
+            }
+        }
+
+        Err(Error::Validation(
+            "Unable to generate unique webhook name".to_string(),
+        ))
+    }
+
+    async fn write_endpoint(
+        &self,
+        realm_id: Uuid,
+        payload: HarborWebhookPayload,
+        existing: Option<WebhookEndpoint>,
+        dry_run: bool,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<HarborImportResourceResult> {
+        let (created, updated) = if existing.is_some() { (0, 1) } else { (1, 0) };
+        if dry_run {
+            return Ok(HarborImportResourceResult {
+                key: self.key().to_string(),
+                status: "validated".to_string(),
+                created,
+                updated,
+                errors: Vec::new(),
+                original_id: Some(payload.name),
+                renamed_to: None,
+            });
+        }
+
+        let mut errors = Vec::new();
+        let signing_secret = match payload.signing_secret.as_deref().map(str::trim) {
+            None | Some("") | Some(REDACTED_SECRET) => existing
+                .as_ref()
+                .map(|endpoint| endpoint.signing_secret.clone())
+                .unwrap_or_else(|| Uuid::new_v4().to_string()),
+            Some(secret) => secret.to_string(),
+        };
+        if existing.is_none() && signing_secret_redacted(payload.signing_secret.as_deref()) {
+            errors.push(format!(
+                "Webhook '{}' signing secret was redacted; a new secret was generated",
+                payload.name
+            ));
+        }
+
+        let mut custom_headers = HashMap::new();
+        for (name, value) in &payload.custom_headers {
+            if value != REDACTED_SECRET {
+                custom_headers.insert(name.clone(), value.clone());
+                continue;
+            }
+            match existing
+                .as_ref()
+                .and_then(|endpoint| endpoint.custom_headers.get(name))
+            {
+                Some(current) => {
+                    custom_headers.insert(name.clone(), current.clone());
+                }
+                None => errors.push(format!(
+                    "Webhook '{}' header '{}' was redacted and has been dropped",
+                    payload.name, name
+                )),
+            }
+        }
+
+        let mut endpoint = existing.clone().unwrap_or_else(|| WebhookEndpoint {
+            id: Uuid::new_v4(),
+            realm_id,
+            name: String::new(),
+            url: String::new(),
+            http_method: String::new(),
+            status: WEBHOOK_STATUS_ACTIVE.to_string(),
+            signing_secret: String::new(),
+            custom_headers: HashMap::new(),
+            description: None,
+            consecutive_failures: 0,
+            last_fired_at: None,
+            last_failure_at: None,
+            disabled_at: None,
+            disabled_reason: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        });
+        endpoint.name = payload.name.clone();
+        endpoint.url = payload.url.clone();
+        endpoint.http_method = normalize_http_method(payload.http_method.as_deref())?;
+        endpoint.status = match payload.status.as_deref() {
+            // Automatic disabling is tied to the source's delivery history.
+            None | Some(WEBHOOK_STATUS_DISABLED_SYSTEM) => WEBHOOK_STATUS_ACTIVE.to_string(),
+            Some(status) => status.to_string(),
+        };
+        endpoint.signing_secret = signing_secret;
+        endpoint.custom_headers = custom_headers;
+        endpoint.description = payload.description.clone();
+
+        let current_subscriptions = match existing.as_ref() {
+            Some(existing) => self.repo.list_subscriptions(&existing.id).await?,
+            None => Vec::new(),
+        };
+        let desired = payload
+            .subscriptions
+            .iter()
+            .map(|subscription| subscription.event_type.as_str())
+            .collect::<HashSet<_>>();
+
+        if let Some(tx) = tx {
+            if existing.is_some() {
+                self.repo.update_endpoint(&endpoint, Some(&mut *tx)).await?;
+            } else {
+                self.repo.create_endpoint(&endpoint, Some(&mut *tx)).await?;
+            }
+            for subscription in &payload.subscriptions {
+                self.repo
+                    .set_subscription_enabled(
+                        &endpoint.id,
+                        &subscription.event_type,
+                        subscription.enabled,
+                        Some(&mut *tx),
+                    )
+                    .await?;
+            }
+            for subscription in &current_subscriptions {
+                if !desired.contains(subscription.event_type.as_str()) {
+                    self.repo
+                        .set_subscription_enabled(
+                            &endpoint.id,
+                            &subscription.event_type,
+                            false,
+                            Some(&mut *tx),
+                        )
+                        .await?;
+                }
+            }
+        } else {
+            if existing.is_some() {
+                self.repo.update_endpoint(&endpoint, None).await?;
+            } else {
+                self.repo.create_endpoint(&endpoint, None).await?;
+            }
+            for subscription in &payload.subscriptions {
+                self.repo
+                    .set_subscription_enabled(
+                        &endpoint.id,
+                        &subscription.event_type,
+                        subscription.enabled,
+                        None,
+                    )
+                    .await?;
+            }
+            for subscription in &current_subscriptions {
+                if !desired.contains(subscription.event_type.as_str()) {
+                    self.repo
+                        .set_subscription_enabled(
+                            &endpoint.id,
+                            &subscription.event_type,
+                            false,
+                            None,
+                        )
+                        .await?;
+                }
+            }
+        }
+
+        Ok(HarborImportResourceResult {
+            key: self.key().to_string(),
+            status: if existing.is_some() {
+                "updated".to_string()
+            } else {
+                "created".to_string()
+            },
+            created,
+            updated,
+            errors,
+            original_id: Some(payload.name),
+            renamed_to: None,
+        })
+    }
+}
+
+#[async_trait]
+impl HarborProvider for WebhookHarborProvider {
+    fn key(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn validate(&self, resource: &HarborResourceBundle) -> Result<()> {
+        if !resource.assets.is_empty() {
+            return Err(Error::Validation(
+                "Webhook bundles must not include assets".to_string(),
+            ));
+        }
+
+        let payload: HarborWebhookPayload = serde_json::from_value(resource.data.clone())
+            .map_err(|err| Error::Validation(format!("Invalid webhook bundle payload: {}", err)))?;
+
+        if payload.name.trim().is_empty() {
+            return Err(Error::Validation("Webhook name is required".to_string()));
+        }
+        if payload.url.trim().is_empty() {
+            return Err(Error::Validation("Webhook URL is required".to_string()));
+        }
+        normalize_http_method(payload.http_method.as_deref())?;
+        if let Some(status) = payload.status.as_deref() {
+            if ![
+                WEBHOOK_STATUS_ACTIVE,
+                WEBHOOK_STATUS_DISABLED_SYSTEM,
+                WEBHOOK_STATUS_DISABLED_USER,
+            ]
+            .contains(&status)
+            {
+                return Err(Error::Validation(format!(
+                    "Unsupported webhook status: {}",
+                    status
+                )));
+            }
+        }
+        let event_types = payload
+            .subscriptions
+            .iter()
+            .map(|subscription| subscription.event_type.clone())
+            .collect::<Vec<_>>();
+        validate_subscription_event_types(&event_types)?;
+
+        Ok(())
+    }
+
+    async fn export(
+        &self,
+        realm_id: Uuid,
+        scope: &HarborScope,
+        policy: ExportPolicy,
+    ) -> Result<HarborResourceBundle> {
+        let endpoint_id = match scope {
+            HarborScope::Webhook { endpoint_id } => *endpoint_id,
+            _ => {
+                return Err(Error::Validation(
+                    "Webhook export requires webhook scope".to_string(),
+                ))
+            }
+        };
+
+        let endpoint = self
+            .repo
+            .find_endpoint(&realm_id, &endpoint_id)
+            .await?
+            .ok_or_else(|| Error::NotFound("Webhook endpoint not found".to_string()))?;
+
+        let mut subscriptions = self
+            .repo
+            .list_subscriptions(&endpoint.id)
+            .await?
+            .into_iter()
+            .map(|subscription| HarborWebhookSubscription {
+                event_type: subscription.event_type,
+                enabled: subscription.enabled,
+            })
+            .collect::<Vec<_>>();
+        subscriptions.sort_by(|a, b| a.event_type.cmp(&b.event_type));
+
+        // Header values often carry credentials, so they follow the secret policy.
+        let (signing_secret, custom_headers) = match policy {
+            ExportPolicy::IncludeSecrets => (
+                endpoint.signing_secret,
+                endpoint.custom_headers.into_iter().collect(),
+            ),
+            ExportPolicy::Redact => (
+                REDACTED_SECRET.to_string(),
+                endpoint
+                    .custom_headers
+                    .into_keys()
+                    .map(|name| (name, REDACTED_SECRET.to_string()))
+                    .collect(),
+            ),
+        };
+
+        let payload = HarborWebhookPayload {
+            endpoint_id: Some(endpoint.id.to_string()),
+            name: endpoint.name,
+            url: endpoint.url,
+            http_method: Some(endpoint.http_method),
+            status: Some(endpoint.status),
+            signing_secret: Some(signing_secret),
+            custom_headers,
+            description: endpoint.description,
+            subscriptions,
+        };
+
+        Ok(HarborResourceBundle {
+            key: self.key().to_string(),
+            data: to_value(payload)
+                .map_err(|err| Error::System(format!("Failed to serialize webhook: {}", err)))?,
+            assets: Vec::new(),
+            meta: None,
+        })
+    }
+
+    async fn import(
+        &self,
+        realm_id: Uuid,
+        scope: &HarborScope,
+        resource: &HarborResourceBundle,
+        conflict_policy: ConflictPolicy,
+        dry_run: bool,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<HarborImportResourceResult> {
+        if !matches!(scope, HarborScope::Webhook { .. }) {
+            return Err(Error::Validation(
+                "Webhook import requires webhook scope".to_string(),
+            ));
+        }
+
+        let mut payload: HarborWebhookPayload = serde_json::from_value(resource.data.clone())
+            .map_err(|err| Error::Validation(format!("Invalid webhook bundle payload: {}", err)))?;
+
+        let Some(existing) = self.find_by_name(realm_id, &payload.name).await? else {
+            return self
+                .write_endpoint(realm_id, payload, None, dry_run, tx)
+                .await;
+        };
+
+        match conflict_policy {
+            ConflictPolicy::Skip => Ok(HarborImportResourceResult {
+                key: self.key().to_string(),
+                status: "skipped".to_string(),
+                created: 0,
+                updated: 0,
+                errors: Vec::new(),
+                original_id: Some(payload.name),
+                renamed_to: None,
+            }),
+            ConflictPolicy::Rename => {
+                let original_name = payload.name.clone();
+                let renamed = self.resolve_available_name(realm_id, &payload.name).await?;
+                payload.name = renamed.clone();
+                let result = self
+                    .write_endpoint(realm_id, payload, None, dry_run, tx)
+                    .await?;
+                Ok(HarborImportResourceResult {
+                    original_id: Some(original_name),
+                    renamed_to: Some(renamed),
+                    ..result
+                })
+            }
+            ConflictPolicy::Overwrite => {
+                self.write_endpoint(realm_id, payload, Some(existing), dry_run, tx)
+                    .await
+            }
+        }
+    }
+
+    async fn delete(
+        &self,
+        realm_id: Uuid,
+        scope: &HarborScope,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        let endpoint_id = match scope {
+            HarborScope::Webhook { endpoint_id } => *endpoint_id,
+            _ => {
+                return Err(Error::Validation(
+                    "Webhook delete requires webhook scope".to_string(),
+                ))
+            }
+        };
+
+        self.repo.delete_endpoint(&realm_id, &endpoint_id, tx).await
+    }
+}
+
+fn signing_secret_redacted(value: Option<&str>) -> bool {
+    matches!(
+        value.map(str::trim),
+        None | Some("") | Some(REDACTED_SECRET)
+    )
+}
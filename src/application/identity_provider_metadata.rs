@@ -1,4 +1,5 @@
 use crate::domain::identity_provider::{IdentityProvider, IdentityProviderProtocol};
+use crate::domain::saml::broker::parse_idp_metadata;
 use crate::error::{Error, Result};
 use crate::ports::http_client::{HttpDeliveryClient, HttpDeliveryRequest};
 use chrono::{Duration, Utc};
@@ -116,6 +117,56 @@ pub async fn load_jwks_with_refresh(
     Ok((jwks, true))
 }
 
+pub async fn maybe_refresh_saml_metadata(
+    http_client: Arc<dyn HttpDeliveryClient>,
+    provider: &mut IdentityProvider,
+) -> Result<bool> {
+    if provider.protocol != IdentityProviderProtocol::Saml
+        || provider.saml_metadata_url.is_none()
+        || !(missing_required_saml_metadata(provider) || discovery_cache_stale(provider))
+    {
+        return Ok(false);
+    }
+
+    match refresh_saml_metadata(http_client, provider).await {
+        Ok(()) => Ok(true),
+        Err(err) if !missing_required_saml_metadata(provider) => {
+            warn!(
+                provider_id = %provider.id,
+                provider_alias = %provider.alias,
+                error = %err,
+                "SAML metadata refresh failed; serving cached provider metadata."
+            );
+            Ok(false)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+pub async fn force_refresh_saml_metadata(
+    http_client: Arc<dyn HttpDeliveryClient>,
+    provider: &mut IdentityProvider,
+) -> Result<bool> {
+    if provider.protocol != IdentityProviderProtocol::Saml {
+        return Ok(false);
+    }
+    refresh_saml_metadata(http_client, provider).await?;
+    Ok(true)
+}
+
+/// Takes the entity ID, SSO location and signing certificates from an
+/// identity provider's SAML metadata document.
+pub fn apply_saml_metadata(provider: &mut IdentityProvider, xml: &str) -> Result<()> {
+    let metadata = parse_idp_metadata(xml)?;
+    provider.issuer = Some(metadata.entity_id);
+    provider.authorization_endpoint = Some(metadata.sso_url);
+    provider.saml_signing_certificates_json = serde_json::to_string(&metadata.signing_certificates)
+        .map_err(|e| Error::System(format!("Failed to serialize SAML certificates: {}", e)))?;
+    provider.metadata_cached_at = Some(Utc::now());
+    provider.metadata_cache_json = Some(xml.to_string());
+    Ok(())
+}
+
 fn should_attempt_discovery_refresh(provider: &IdentityProvider) -> bool {
     provider.protocol == IdentityProviderProtocol::Oidc
         && provider.issuer.is_some()
@@ -134,6 +185,13 @@ fn has_usable_oidc_metadata(provider: &IdentityProvider) -> bool {
         && provider.jwks_uri.is_some()
 }
 
+fn missing_required_saml_metadata(provider: &IdentityProvider) -> bool {
+    provider.issuer.is_none()
+        || provider.authorization_endpoint.is_none()
+        || serde_json::from_str::<Vec<String>>(&provider.saml_signing_certificates_json)
+            .map_or(true, |certificates| certificates.is_empty())
+}
+
 fn discovery_cache_stale(provider: &IdentityProvider) -> bool {
     if provider.metadata_cache_json.is_none() {
         return false;
@@ -196,6 +254,34 @@ async fn refresh_oidc_discovery(
     Ok(())
 }
 
+async fn refresh_saml_metadata(
+    http_client: Arc<dyn HttpDeliveryClient>,
+    provider: &mut IdentityProvider,
+) -> Result<()> {
+    let url = provider.saml_metadata_url.clone().ok_or_else(|| {
+        Error::Validation("SAML metadata import requires a metadata URL".to_string())
+    })?;
+    let response = http_client
+        .send(HttpDeliveryRequest {
+            method: "GET".to_string(),
+            url,
+            headers: HashMap::from([(
+                "accept".to_string(),
+                "application/samlmetadata+xml, application/xml".to_string(),
+            )]),
+            body: String::new(),
+        })
+        .await
+        .map_err(|err| Error::System(format!("SAML metadata request failed: {}", err.message)))?;
+    if response.status_code >= 400 {
+        return Err(Error::System(format!(
+            "SAML metadata request failed with status {}",
+            response.status_code
+        )));
+    }
+    apply_saml_metadata(provider, &response.body)
+}
+
 fn parse_cached_jwks(
     provider: &IdentityProvider,
     required_kid: Option<&str>,
@@ -340,11 +426,47 @@ mod tests {
             metadata_cache_json: Some("{}".to_string()),
             jwks_cached_at: Some(now),
             jwks_cache_json: None,
+            saml_metadata_url: None,
+            saml_signing_certificates_json: "[]".to_string(),
+            saml_name_id_format: None,
             created_at: now,
             updated_at: now,
         }
     }
 
+    fn saml_provider() -> IdentityProvider {
+        let mut provider = oidc_provider();
+        provider.protocol = IdentityProviderProtocol::Saml;
+        provider.issuer = None;
+        provider.authorization_endpoint = None;
+        provider.token_endpoint = None;
+        provider.userinfo_endpoint = None;
+        provider.jwks_uri = None;
+        provider.metadata_cached_at = None;
+        provider.metadata_cache_json = None;
+        provider.saml_metadata_url = Some("https://idp.example.com/metadata".to_string());
+        provider
+    }
+
+    fn saml_metadata_doc() -> String {
+        let key = rsa::RsaPrivateKey::new(&mut rand::rng(), 1024).expect("rsa key");
+        let certificate = crate::domain::saml::self_signed_certificate(
+            &key,
+            "idp.example.com",
+            &[1],
+            Utc::now(),
+            Utc::now() + Duration::days(1),
+        )
+        .expect("certificate");
+        crate::domain::saml::build_idp_metadata(&crate::domain::saml::IdpMetadata {
+            entity_id: "https://idp.example.com",
+            sso_url: "https://idp.example.com/sso",
+            slo_url: "https://idp.example.com/slo",
+            certificates: &[certificate],
+            name_id_formats: &[],
+        })
+    }
+
     fn discovery_doc() -> serde_json::Value {
         json!({
             "authorization_endpoint": "https://issuer.example.com/authorize-new",
@@ -446,4 +568,48 @@ mod tests {
         assert_eq!(http.calls(), 1);
         assert!(jwks.find("kid-1").is_some());
     }
+
+    #[tokio::test]
+    async fn maybe_refresh_saml_metadata_imports_missing_metadata() {
+        let http = Arc::new(FakeHttpClient {
+            responses: Arc::new(std::sync::Mutex::new(vec![Ok(HttpDeliveryResponse {
+                status_code: 200,
+                body: saml_metadata_doc(),
+            })])),
+            calls: Arc::new(AtomicUsize::new(0)),
+        });
+        let mut provider = saml_provider();
+
+        let refreshed = maybe_refresh_saml_metadata(http.clone(), &mut provider)
+            .await
+            .expect("refresh result");
+
+        assert!(refreshed);
+        assert_eq!(provider.issuer.as_deref(), Some("https://idp.example.com"));
+        assert_eq!(
+            provider.authorization_endpoint.as_deref(),
+            Some("https://idp.example.com/sso")
+        );
+        let certificates: Vec<String> =
+            serde_json::from_str(&provider.saml_signing_certificates_json).expect("certificates");
+        assert_eq!(certificates.len(), 1);
+        assert!(provider.metadata_cached_at.is_some());
+
+        // Fresh metadata is not fetched again.
+        let refreshed = maybe_refresh_saml_metadata(http.clone(), &mut provider)
+            .await
+            .expect("refresh result");
+        assert!(!refreshed);
+        assert_eq!(http.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn maybe_refresh_saml_metadata_fails_without_usable_metadata() {
+        let http = Arc::new(FakeHttpClient::error("metadata unavailable"));
+        let mut provider = saml_provider();
+
+        let result = maybe_refresh_saml_metadata(http, &mut provider).await;
+
+        assert!(result.is_err());
+    }
 }
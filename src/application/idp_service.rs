@@ -1,6 +1,7 @@
 use crate::application::audit_service::AuditService;
 use crate::application::identity_provider_metadata::{
-    force_refresh_jwks, force_refresh_oidc_discovery, maybe_refresh_oidc_discovery,
+    apply_saml_metadata, force_refresh_jwks, force_refresh_oidc_discovery,
+    force_refresh_saml_metadata, maybe_refresh_oidc_discovery, maybe_refresh_saml_metadata,
 };
 use crate::application::secret_service::SecretService;
 use crate::domain::audit::AuditActionCount;
@@ -8,6 +9,8 @@ use crate::domain::identity_provider::{
     IdentityProvider, IdentityProviderPreset, IdentityProviderProtocol,
 };
 use crate::domain::realm::{RealmIdpDefaultEmailLinkPolicy, RealmIdpDefaultJitPolicy};
+use crate::domain::saml::broker::normalize_certificate;
+use crate::domain::saml::NameIdFormat;
 use crate::error::{Error, Result};
 use crate::ports::federated_identity_repository::FederatedIdentityRepository;
 use crate::ports::http_client::{HttpDeliveryClient, HttpDeliveryRequest};
@@ -47,6 +50,11 @@ pub struct CreateIdentityProviderRequest {
     pub button_color: Option<String>,
     pub sort_order: Option<i64>,
     pub enabled: Option<bool>,
+    pub saml_metadata_url: Option<String>,
+    /// Inline SAML metadata to import instead of fetching it.
+    pub saml_metadata_xml: Option<String>,
+    pub saml_signing_certificates: Option<Vec<String>>,
+    pub saml_name_id_format: Option<NameIdFormat>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -72,6 +80,11 @@ pub struct UpdateIdentityProviderRequest {
     pub button_color: Option<String>,
     pub sort_order: Option<i64>,
     pub enabled: Option<bool>,
+    pub saml_metadata_url: Option<String>,
+    /// Inline SAML metadata to import instead of fetching it.
+    pub saml_metadata_xml: Option<String>,
+    pub saml_signing_certificates: Option<Vec<String>>,
+    pub saml_name_id_format: Option<NameIdFormat>,
 }
 
 #[derive(Debug, Serialize)]
@@ -104,6 +117,9 @@ pub struct IdentityProviderResponse {
     pub jwks_cached_at: Option<chrono::DateTime<chrono::Utc>>,
     pub client_secret_set: bool,
     pub client_secret_mask: Option<String>,
+    pub saml_metadata_url: Option<String>,
+    pub saml_signing_certificates: Vec<String>,
+    pub saml_name_id_format: Option<NameIdFormat>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -436,11 +452,14 @@ impl IdentityProviderService {
                     .collect::<Vec<_>>(),
             )
             .map_err(|e| Error::System(format!("Failed to serialize provider scopes: {}", e)))?,
-            claim_mapping_json: serde_json::to_string(
-                &request
-                    .claim_mapping
-                    .unwrap_or_else(|| preset_claim_mapping(&preset)),
-            )
+            claim_mapping_json: serde_json::to_string(&request.claim_mapping.unwrap_or_else(
+                || match request.protocol {
+                    // SAML attributes fall back to common names; see
+                    // `ValidatedAssertion::upstream_identity`.
+                    IdentityProviderProtocol::Saml => json!({}),
+                    _ => preset_claim_mapping(&preset),
+                },
+            ))
             .map_err(|e| Error::System(format!("Failed to serialize claim mapping: {}", e)))?,
             pkce_required: request.pkce_required.unwrap_or(true),
             allow_login: request.allow_login.unwrap_or(true),
@@ -463,13 +482,20 @@ impl IdentityProviderService {
             metadata_cache_json: None,
             jwks_cached_at: None,
             jwks_cache_json: None,
+            saml_metadata_url: request.saml_metadata_url,
+            saml_signing_certificates_json: serialize_saml_certificates(
+                request.saml_signing_certificates.unwrap_or_default(),
+            )?,
+            saml_name_id_format: request.saml_name_id_format,
             created_at: now,
             updated_at: now,
         };
 
         let mut provider = provider;
         maybe_refresh_oidc_discovery(self.http_client.clone(), &mut provider).await?;
-        self.repo.create(&provider).await?;
+        self.import_saml_metadata(&mut provider, request.saml_metadata_xml.as_deref())
+            .await?;
+        self.repo.create(&provider, None).await?;
         Self::to_response(provider)
     }
 
@@ -551,9 +577,20 @@ impl IdentityProviderService {
         if let Some(value) = request.enabled {
             provider.enabled = value;
         }
+        if let Some(value) = request.saml_metadata_url {
+            provider.saml_metadata_url = Some(value);
+        }
+        if let Some(value) = request.saml_signing_certificates {
+            provider.saml_signing_certificates_json = serialize_saml_certificates(value)?;
+        }
+        if let Some(value) = request.saml_name_id_format {
+            provider.saml_name_id_format = Some(value);
+        }
         provider.updated_at = Utc::now();
         maybe_refresh_oidc_discovery(self.http_client.clone(), &mut provider).await?;
-        self.repo.update(&provider).await?;
+        self.import_saml_metadata(&mut provider, request.saml_metadata_xml.as_deref())
+            .await?;
+        self.repo.update(&provider, None).await?;
         Self::to_response(provider)
     }
 
@@ -577,7 +614,7 @@ impl IdentityProviderService {
             provider.allow_login = false;
             provider.allow_link = false;
             provider.updated_at = Utc::now();
-            self.repo.update(&provider).await?;
+            self.repo.update(&provider, None).await?;
             return Ok(DeleteIdentityProviderResult {
                 provider_id: provider.id,
                 provider_alias: provider.alias,
@@ -591,7 +628,7 @@ impl IdentityProviderService {
                 .delete_by_provider(&provider.realm_id, &provider.id)
                 .await?;
         }
-        self.repo.delete(&id).await?;
+        self.repo.delete(&id, None).await?;
         Ok(DeleteIdentityProviderResult {
             provider_id: provider.id,
             provider_alias: provider.alias,
@@ -607,8 +644,11 @@ impl IdentityProviderService {
             .await?
             .ok_or_else(|| Error::NotFound("Identity provider not found".to_string()))?;
         force_refresh_oidc_discovery(self.http_client.clone(), &mut provider).await?;
+        if provider.saml_metadata_url.is_some() {
+            force_refresh_saml_metadata(self.http_client.clone(), &mut provider).await?;
+        }
         provider.updated_at = Utc::now();
-        self.repo.update(&provider).await?;
+        self.repo.update(&provider, None).await?;
         Self::to_response(provider)
     }
 
@@ -620,7 +660,34 @@ impl IdentityProviderService {
             .ok_or_else(|| Error::NotFound("Identity provider not found".to_string()))?;
         let mut provider_changed = false;
 
-        let discovery = if provider.protocol == IdentityProviderProtocol::Oidc {
+        let discovery = if provider.protocol == IdentityProviderProtocol::Saml {
+            if provider.saml_metadata_url.is_some() {
+                match force_refresh_saml_metadata(self.http_client.clone(), &mut provider).await {
+                    Ok(_) => {
+                        provider_changed = true;
+                        IdentityProviderConnectionCheck {
+                            attempted: true,
+                            ok: true,
+                            status_code: Some(200),
+                            detail: "SAML metadata refreshed".to_string(),
+                        }
+                    }
+                    Err(err) => IdentityProviderConnectionCheck {
+                        attempted: true,
+                        ok: false,
+                        status_code: None,
+                        detail: err.to_string(),
+                    },
+                }
+            } else {
+                IdentityProviderConnectionCheck {
+                    attempted: false,
+                    ok: true,
+                    status_code: None,
+                    detail: "SAML metadata URL is not configured".to_string(),
+                }
+            }
+        } else if provider.protocol == IdentityProviderProtocol::Oidc {
             if provider.issuer.is_some() {
                 match force_refresh_oidc_discovery(self.http_client.clone(), &mut provider).await {
                     Ok(_) => {
@@ -736,13 +803,16 @@ impl IdentityProviderService {
                 attempted: false,
                 ok: true,
                 status_code: None,
-                detail: "Not applicable for OAuth2 providers".to_string(),
+                detail: format!(
+                    "Not applicable for {} providers",
+                    protocol_label(provider.protocol)
+                ),
             }
         };
 
         if provider_changed {
             provider.updated_at = Utc::now();
-            self.repo.update(&provider).await?;
+            self.repo.update(&provider, None).await?;
         }
 
         let ok = [
@@ -775,6 +845,24 @@ impl IdentityProviderService {
         })
     }
 
+    /// Imports inline SAML metadata, or fetches it from the metadata URL
+    /// when the provider has none yet.
+    async fn import_saml_metadata(
+        &self,
+        provider: &mut IdentityProvider,
+        metadata_xml: Option<&str>,
+    ) -> Result<()> {
+        if provider.protocol != IdentityProviderProtocol::Saml {
+            return Ok(());
+        }
+        match metadata_xml {
+            Some(xml) => apply_saml_metadata(provider, xml),
+            None => maybe_refresh_saml_metadata(self.http_client.clone(), provider)
+                .await
+                .map(|_| ()),
+        }
+    }
+
     fn to_response(provider: IdentityProvider) -> Result<IdentityProviderResponse> {
         let scopes = serde_json::from_str(&provider.scopes_json)
             .map_err(|e| Error::System(format!("Invalid provider scopes: {}", e)))?;
@@ -810,11 +898,17 @@ impl IdentityProviderService {
             jwks_cached_at: provider.jwks_cached_at,
             client_secret_set: provider.client_secret.is_some(),
             client_secret_mask,
+            saml_metadata_url: provider.saml_metadata_url,
+            saml_signing_certificates: serde_json::from_str(
+                &provider.saml_signing_certificates_json,
+            )
+            .map_err(|e| Error::System(format!("Invalid SAML certificates: {}", e)))?,
+            saml_name_id_format: provider.saml_name_id_format,
         })
     }
 }
 
-fn validate_alias(alias: &str) -> Result<()> {
+pub(crate) fn validate_alias(alias: &str) -> Result<()> {
     let trimmed = alias.trim();
     if trimmed.is_empty() {
         return Err(Error::Validation("Provider alias is required".to_string()));
@@ -830,6 +924,23 @@ fn validate_alias(alias: &str) -> Result<()> {
     Ok(())
 }
 
+fn protocol_label(protocol: IdentityProviderProtocol) -> &'static str {
+    match protocol {
+        IdentityProviderProtocol::Oidc => "OIDC",
+        IdentityProviderProtocol::Oauth2 => "OAuth2",
+        IdentityProviderProtocol::Saml => "SAML",
+    }
+}
+
+fn serialize_saml_certificates(certificates: Vec<String>) -> Result<String> {
+    let certificates = certificates
+        .iter()
+        .map(|certificate| normalize_certificate(certificate))
+        .collect::<Result<Vec<_>>>()?;
+    serde_json::to_string(&certificates)
+        .map_err(|e| Error::System(format!("Failed to serialize SAML certificates: {}", e)))
+}
+
 fn mask_secret_tail(value: &str) -> String {
     let tail: String = value
         .chars()
@@ -926,6 +1037,7 @@ fn idp_audit_actions() -> &'static [&'static str] {
         "idp_pkce_failure",
         "idp_token_exchange_failure",
         "idp_userinfo_failure",
+        "idp_saml_assertion_failure",
     ]
 }
 
@@ -951,7 +1063,8 @@ fn summarize_activity_counts(counts: &[AuditActionCount]) -> IdentityProviderAct
             | "idp_start_rate_limited"
             | "idp_pkce_failure"
             | "idp_token_exchange_failure"
-            | "idp_userinfo_failure" => failures_last_24h += count.count,
+            | "idp_userinfo_failure"
+            | "idp_saml_assertion_failure" => failures_last_24h += count.count,
             _ => {}
         }
     }
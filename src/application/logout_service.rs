@@ -0,0 +1,175 @@
+use crate::domain::events::{
+    DomainEvent, EventEnvelope, SessionRevocationReason, SessionRevoked, EVENT_VERSION_V1,
+};
+use crate::domain::oidc::{
+    frontchannel_logout_url, BackchannelLogoutDelivery, OidcClient, BACKCHANNEL_LOGOUT_EVENT_TYPE,
+};
+use crate::domain::session::RefreshToken;
+use crate::error::Result;
+use crate::ports::event_bus::EventPublisher;
+use crate::ports::oidc_repository::OidcRepository;
+use crate::ports::outbox_repository::OutboxRepository;
+use crate::ports::session_repository::SessionRepository;
+use chrono::Utc;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tracing::warn;
+use uuid::Uuid;
+
+/// Tells relying parties and webhook subscribers that sessions ended.
+/// Back-channel requests go through the outbox; front-channel URLs are
+/// returned for the end-session page to load.
+pub struct LogoutService {
+    session_repo: Arc<dyn SessionRepository>,
+    oidc_repo: Arc<dyn OidcRepository>,
+    outbox_repo: Arc<dyn OutboxRepository>,
+    event_publisher: Arc<dyn EventPublisher>,
+    issuer: String,
+}
+
+impl LogoutService {
+    pub fn new(
+        session_repo: Arc<dyn SessionRepository>,
+        oidc_repo: Arc<dyn OidcRepository>,
+        outbox_repo: Arc<dyn OutboxRepository>,
+        event_publisher: Arc<dyn EventPublisher>,
+        issuer: String,
+    ) -> Self {
+        Self {
+            session_repo,
+            oidc_repo,
+            outbox_repo,
+            event_publisher,
+            issuer,
+        }
+    }
+
+    /// Revokes every session a user holds in a realm and notifies the clients
+    /// those sessions belonged to.
+    pub async fn revoke_all_for_user(&self, realm_id: Uuid, user_id: Uuid) -> Result<()> {
+        let sessions = self
+            .session_repo
+            .list_active_for_user(&realm_id, &user_id)
+            .await?;
+        self.session_repo
+            .revoke_all_for_user(&realm_id, &user_id)
+            .await?;
+        self.sessions_ended(&sessions, SessionRevocationReason::AccountChange)
+            .await;
+        Ok(())
+    }
+
+    /// Revokes every refresh-token family a user holds for one client and
+    /// notifies that client.
+    pub async fn revoke_for_client(
+        &self,
+        realm_id: Uuid,
+        user_id: Uuid,
+        client_id: &str,
+    ) -> Result<()> {
+        let sessions = self
+            .session_repo
+            .list_active_for_user(&realm_id, &user_id)
+            .await?
+            .into_iter()
+            .filter(|session| session.client_id.as_deref() == Some(client_id))
+            .collect::<Vec<_>>();
+        self.session_repo
+            .revoke_by_user_and_client(&realm_id, &user_id, client_id)
+            .await?;
+        self.sessions_ended(&sessions, SessionRevocationReason::ConsentWithdrawn)
+            .await;
+        Ok(())
+    }
+
+    /// Publishes `session.revoked` for each ended session and queues a
+    /// Back-Channel Logout request for each one whose client registered a
+    /// `backchannel_logout_uri`. The sessions are already revoked, so failures
+    /// are logged rather than returned.
+    pub async fn sessions_ended(&self, sessions: &[RefreshToken], reason: SessionRevocationReason) {
+        for session in sessions {
+            self.event_publisher
+                .publish(DomainEvent::SessionRevoked(SessionRevoked {
+                    realm_id: session.realm_id,
+                    user_id: session.user_id,
+                    session_id: session.id,
+                    client_id: session.client_id.clone(),
+                    reason,
+                }))
+                .await;
+        }
+        for (client, session) in self.session_clients(sessions).await {
+            let Some(uri) = client.backchannel_logout_uri else {
+                continue;
+            };
+            let delivery = BackchannelLogoutDelivery {
+                client_id: client.client_id,
+                backchannel_logout_uri: uri,
+                sub: session.user_id,
+                sid: session.family_id,
+            };
+            let data = match serde_json::to_value(&delivery) {
+                Ok(data) => data,
+                Err(err) => {
+                    warn!("Failed to encode back-channel logout: {}", err);
+                    continue;
+                }
+            };
+            let envelope = EventEnvelope {
+                event_id: Uuid::new_v4().to_string(),
+                event_type: BACKCHANNEL_LOGOUT_EVENT_TYPE.to_string(),
+                event_version: EVENT_VERSION_V1.to_string(),
+                occurred_at: Utc::now().to_rfc3339(),
+                realm_id: Some(session.realm_id),
+                actor: None,
+                data,
+            };
+            if let Err(err) = self.outbox_repo.insert(&envelope, None).await {
+                warn!(
+                    "Failed to queue back-channel logout for client {}: {}",
+                    delivery.client_id, err
+                );
+            }
+        }
+    }
+
+    /// Front-channel logout URLs of the clients whose sessions ended.
+    pub async fn frontchannel_logout_urls(&self, sessions: &[RefreshToken]) -> Vec<String> {
+        self.session_clients(sessions)
+            .await
+            .into_iter()
+            .filter_map(|(client, session)| {
+                let uri = client.frontchannel_logout_uri?;
+                frontchannel_logout_url(&uri, &self.issuer, session.family_id)
+            })
+            .collect()
+    }
+
+    /// Resolves the client of each client-bound session, once per session
+    /// family. SSO sessions (no client) are skipped.
+    async fn session_clients<'a>(
+        &self,
+        sessions: &'a [RefreshToken],
+    ) -> Vec<(OidcClient, &'a RefreshToken)> {
+        let mut seen = HashSet::new();
+        let mut resolved = Vec::new();
+        for session in sessions {
+            let Some(client_id) = session.client_id.as_deref() else {
+                continue;
+            };
+            if !seen.insert((client_id, session.family_id)) {
+                continue;
+            }
+            match self
+                .oidc_repo
+                .find_client_by_id(&session.realm_id, client_id)
+                .await
+            {
+                Ok(Some(client)) => resolved.push((client, session)),
+                Ok(None) => {}
+                Err(err) => warn!("Failed to load client {} for logout: {}", client_id, err),
+            }
+        }
+        resolved
+    }
+}
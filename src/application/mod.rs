@@ -1,5 +1,8 @@
 pub mod audit_service;
 pub mod auth_service;
+pub mod claims_service;
+pub mod client_registration_service;
+pub mod consent_service;
 pub mod delivery_replay_service;
 pub mod email_delivery_service;
 pub mod flow_engine;
@@ -11,12 +14,14 @@ pub mod harbor;
 pub mod identity_provider_metadata;
 pub mod idp_service;
 pub mod invitation_service;
+pub mod logout_service;
 pub mod metrics_service;
 pub mod node_registry;
 pub mod oauth_broker_service;
 pub mod oidc_service;
 pub mod passkey_analytics_service;
 pub mod passkey_assertion_service;
+pub mod password_policy_service;
 pub mod rbac_service;
 pub mod realm_email_settings_service;
 pub mod realm_idp_settings_service;
@@ -26,11 +31,17 @@ pub mod realm_recovery_settings_service;
 pub mod realm_security_headers_service;
 pub mod realm_service;
 pub mod runtime_registry;
+pub mod saml_service;
+pub mod scim_service;
 pub mod secret_service;
+pub mod signing_key_service;
+pub mod sms_otp_service;
 pub mod telemetry_service;
 pub mod theme_service;
+pub mod totp_service;
 pub mod user_credentials_service;
 pub mod user_email_service;
+pub mod user_federation_service;
 pub mod user_phone_number_service;
 pub mod user_service;
 pub mod webhook_service;
@@ -17,10 +17,14 @@ use crate::domain::flow::nodes::password_node::PasswordNodeProvider;
 use crate::domain::flow::nodes::recovery_issue_node::RecoveryIssueNodeProvider;
 use crate::domain::flow::nodes::registration_node::RegistrationNodeProvider;
 use crate::domain::flow::nodes::reset_password_node::ResetPasswordNodeProvider;
+use crate::domain::flow::nodes::sms_otp_issue_node::SmsOtpIssueNodeProvider;
 use crate::domain::flow::nodes::start_node::StartNode;
 use crate::domain::flow::nodes::subflow_node::SubflowNodeProvider;
 use crate::domain::flow::nodes::terminal_node::{AllowNode, DenyNode};
+use crate::domain::flow::nodes::totp_enroll_node::TotpEnrollNodeProvider;
+use crate::domain::flow::nodes::totp_verify_node::TotpVerifyNodeProvider;
 use crate::domain::flow::nodes::verify_email_otp_node::VerifyEmailOtpNodeProvider;
+use crate::domain::flow::nodes::verify_sms_otp_node::VerifySmsOtpNodeProvider;
 use crate::domain::flow::provider::NodeProvider;
 use std::sync::Arc;
 
@@ -37,9 +41,12 @@ impl NodeRegistryService {
                 Box::new(ConditionNodeProvider),
                 Box::new(RecoveryIssueNodeProvider),
                 Box::new(EmailOtpIssueNodeProvider),
+                Box::new(SmsOtpIssueNodeProvider),
                 Box::new(CookieNodeProvider),
                 Box::new(PasskeyAssertNodeProvider),
                 Box::new(PasskeyEnrollNodeProvider),
+                Box::new(TotpEnrollNodeProvider),
+                Box::new(TotpVerifyNodeProvider),
                 Box::new(PasswordNodeProvider),
                 Box::new(CollectIdpChoiceNodeProvider),
                 Box::new(ForgotCredentialsNodeProvider),
@@ -51,6 +58,7 @@ impl NodeRegistryService {
                 Box::new(RegistrationNodeProvider),
                 Box::new(ResetPasswordNodeProvider),
                 Box::new(VerifyEmailOtpNodeProvider),
+                Box::new(VerifySmsOtpNodeProvider),
                 Box::new(SubflowNodeProvider),
                 Box::new(AllowNode),
                 Box::new(DenyNode),
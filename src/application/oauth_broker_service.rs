@@ -1,16 +1,26 @@
 use crate::application::audit_service::AuditService;
 use crate::application::identity_provider_metadata::{
-    load_jwks_with_refresh, maybe_refresh_oidc_discovery,
+    load_jwks_with_refresh, maybe_refresh_oidc_discovery, maybe_refresh_saml_metadata,
 };
 use crate::application::secret_service::SecretService;
+use crate::application::signing_key_service::SigningKeyService;
+use crate::domain::auth_session::AuthenticationSession;
 use crate::domain::crypto::HashedPassword;
+use crate::domain::events::{DomainEvent, IdpLinked};
 use crate::domain::identity_provider::{
-    FederatedIdentity, IdentityProvider, OAuthBrokerResult, OAuthBrokerState, OAuthUpstreamIdentity,
+    FederatedIdentity, IdentityProvider, IdentityProviderProtocol, OAuthBrokerResult,
+    OAuthBrokerState, OAuthUpstreamIdentity,
+};
+use crate::domain::saml;
+use crate::domain::saml::broker::{
+    build_authn_request, build_sp_metadata, certificate_keys, validate_response,
+    AuthnRequestParams, ResponseExpectations,
 };
 use crate::domain::user::User;
 use crate::domain::user_email::UserEmail;
 use crate::error::{Error, Result};
 use crate::ports::auth_session_repository::AuthSessionRepository;
+use crate::ports::event_bus::EventPublisher;
 use crate::ports::federated_identity_repository::FederatedIdentityRepository;
 use crate::ports::http_client::{HttpDeliveryClient, HttpDeliveryRequest};
 use crate::ports::oauth_broker_state_repository::OAuthBrokerStateRepository;
@@ -39,7 +49,9 @@ pub struct OAuthBrokerService {
     user_repo: Arc<dyn UserRepository>,
     user_email_repo: Arc<dyn UserEmailRepository>,
     audit_service: Arc<AuditService>,
+    event_publisher: Arc<dyn EventPublisher>,
     secret_service: Arc<SecretService>,
+    signing_key_service: Arc<SigningKeyService>,
     http_client: Arc<dyn HttpDeliveryClient>,
     public_url: String,
 }
@@ -56,6 +68,15 @@ pub struct OAuthCallbackResult {
     pub broker_result: OAuthBrokerResult,
 }
 
+/// A consumed broker state whose browser binding has been verified, waiting
+/// for the upstream identity.
+struct PendingCallback {
+    broker_state: OAuthBrokerState,
+    provider: IdentityProvider,
+    session: AuthenticationSession,
+    verifier: String,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum ProviderClaimFetchStrategy {
     Default,
@@ -75,7 +96,9 @@ impl OAuthBrokerService {
         user_repo: Arc<dyn UserRepository>,
         user_email_repo: Arc<dyn UserEmailRepository>,
         audit_service: Arc<AuditService>,
+        event_publisher: Arc<dyn EventPublisher>,
         secret_service: Arc<SecretService>,
+        signing_key_service: Arc<SigningKeyService>,
         http_client: Arc<dyn HttpDeliveryClient>,
         public_url: String,
     ) -> Self {
@@ -88,7 +111,9 @@ impl OAuthBrokerService {
             user_repo,
             user_email_repo,
             audit_service,
+            event_publisher,
             secret_service,
+            signing_key_service,
             http_client,
             public_url,
         }
@@ -123,15 +148,23 @@ impl OAuthBrokerService {
                 "Identity provider is disabled".to_string(),
             ));
         }
-        if maybe_refresh_oidc_discovery(self.http_client.clone(), &mut provider).await? {
-            provider.updated_at = Utc::now();
-            self.provider_repo.update(&provider).await?;
-        }
-
-        let redirect_uri = self.callback_url(realm_path, provider_alias)?;
+        self.refresh_provider_metadata(&mut provider).await?;
+
+        // A SAML login reuses the broker state: the response comes back to
+        // the ACS, and the nonce carries the AuthnRequest ID it must answer.
+        let is_saml = provider.protocol == IdentityProviderProtocol::Saml;
+        let redirect_uri = if is_saml {
+            self.acs_url(realm_path, provider_alias)?
+        } else {
+            self.callback_url(realm_path, provider_alias)?
+        };
         let raw_verifier = Alphanumeric.sample_string(&mut rand::rng(), 64);
         let pkce_hash = sha256_text(&raw_verifier);
-        let nonce = Some(Alphanumeric.sample_string(&mut rand::rng(), 32));
+        let nonce = Some(if is_saml {
+            saml::new_message_id()
+        } else {
+            Alphanumeric.sample_string(&mut rand::rng(), 32)
+        });
         let state_id = Uuid::new_v4();
         let now = Utc::now();
         let state = OAuthBrokerState {
@@ -161,15 +194,25 @@ impl OAuthBrokerService {
         );
         self.auth_session_repo.update(&session).await?;
 
-        let redirect_url = build_authorization_url(
-            &provider,
-            &redirect_uri,
-            &state.id.to_string(),
-            nonce,
-            provider
-                .pkce_required
-                .then(|| pkce_challenge(&raw_verifier)),
-        )?;
+        let redirect_url = if is_saml {
+            self.build_saml_redirect(
+                &provider,
+                &redirect_uri,
+                nonce.as_deref().unwrap_or_default(),
+                &state.id.to_string(),
+            )
+            .await?
+        } else {
+            build_authorization_url(
+                &provider,
+                &redirect_uri,
+                &state.id.to_string(),
+                nonce,
+                provider
+                    .pkce_required
+                    .then(|| pkce_challenge(&raw_verifier)),
+            )?
+        };
 
         self.audit_service
             .record(crate::domain::audit::NewAuditEvent {
@@ -201,6 +244,101 @@ impl OAuthBrokerService {
         code: &str,
         state: &str,
     ) -> Result<OAuthCallbackResult> {
+        let mut pending = self.begin_callback(realm_id, provider_alias, state).await?;
+        if pending.provider.protocol == IdentityProviderProtocol::Saml {
+            return Err(Error::Validation(
+                "SAML identity providers respond at the assertion consumer service".to_string(),
+            ));
+        }
+
+        let upstream = self
+            .exchange_and_fetch_profile(
+                &mut pending.provider,
+                code,
+                &pending.broker_state.redirect_uri,
+                &pending.verifier,
+                pending.broker_state.nonce.as_deref(),
+                pending.broker_state.auth_session_id,
+            )
+            .await?;
+        self.finish_callback(pending, upstream).await
+    }
+
+    /// Handles a `SAMLResponse` posted to the ACS. `relay_state` is the broker
+    /// state issued with the AuthnRequest; consuming it once is what stops a
+    /// response from being replayed.
+    pub async fn handle_saml_response(
+        &self,
+        realm_id: Uuid,
+        provider_alias: &str,
+        saml_response: &str,
+        relay_state: &str,
+    ) -> Result<OAuthCallbackResult> {
+        let pending = self
+            .begin_callback(realm_id, provider_alias, relay_state)
+            .await?;
+        if pending.provider.protocol != IdentityProviderProtocol::Saml {
+            return Err(Error::Validation(
+                "Identity provider is not a SAML provider".to_string(),
+            ));
+        }
+
+        let upstream = match self.validate_saml_response(&pending, saml_response) {
+            Ok(upstream) => upstream,
+            Err(err) => {
+                self.record_provider_event(
+                    realm_id,
+                    &pending.provider,
+                    Some(pending.broker_state.auth_session_id),
+                    None,
+                    "idp_saml_assertion_failure",
+                    json!({
+                        "provider_alias": pending.provider.alias,
+                        "state_id": pending.broker_state.id,
+                        "message": err.to_string()
+                    }),
+                )
+                .await?;
+                return Err(err);
+            }
+        };
+        self.finish_callback(pending, upstream).await
+    }
+
+    /// Service provider metadata to register with a SAML identity provider.
+    pub async fn saml_sp_metadata(
+        &self,
+        realm_id: Uuid,
+        realm_path: &str,
+        provider_alias: &str,
+    ) -> Result<String> {
+        let provider = self
+            .provider_repo
+            .find_by_alias(&realm_id, provider_alias)
+            .await?
+            .filter(|provider| provider.protocol == IdentityProviderProtocol::Saml)
+            .ok_or_else(|| {
+                Error::NotFound(format!(
+                    "SAML identity provider '{}' not found",
+                    provider_alias
+                ))
+            })?;
+        let certificates = self.signing_key_service.saml_certificates(realm_id).await?;
+        Ok(build_sp_metadata(
+            &provider.client_id,
+            &self.acs_url(realm_path, provider_alias)?,
+            &certificates,
+        ))
+    }
+
+    /// Consumes the broker state and checks the verifier bound to the auth
+    /// session, the part of a callback every protocol shares.
+    async fn begin_callback(
+        &self,
+        realm_id: Uuid,
+        provider_alias: &str,
+        state: &str,
+    ) -> Result<PendingCallback> {
         let realm = self
             .realm_repo
             .find_by_id(&realm_id)
@@ -256,10 +394,7 @@ impl OAuthBrokerService {
                 "Identity provider is unavailable".to_string(),
             ));
         }
-        if maybe_refresh_oidc_discovery(self.http_client.clone(), &mut provider).await? {
-            provider.updated_at = Utc::now();
-            self.provider_repo.update(&provider).await?;
-        }
+        self.refresh_provider_metadata(&mut provider).await?;
 
         let mut session = self
             .auth_session_repo
@@ -306,23 +441,34 @@ impl OAuthBrokerService {
             ));
         }
 
-        let upstream = self
-            .exchange_and_fetch_profile(
-                &mut provider,
-                code,
-                &broker_state.redirect_uri,
-                &verifier,
-                broker_state.nonce.as_deref(),
-                broker_state.auth_session_id,
-            )
-            .await?;
+        Ok(PendingCallback {
+            broker_state,
+            provider,
+            session,
+            verifier,
+        })
+    }
+
+    /// Links the upstream identity under the provider's policies and records
+    /// the successful callback.
+    async fn finish_callback(
+        &self,
+        pending: PendingCallback,
+        upstream: OAuthUpstreamIdentity,
+    ) -> Result<OAuthCallbackResult> {
+        let PendingCallback {
+            broker_state,
+            provider,
+            session,
+            ..
+        } = pending;
         self.auth_session_repo.update(&session).await?;
 
         let broker_result = self.resolve_user_link(provider.clone(), upstream).await?;
 
         self.audit_service
             .record(crate::domain::audit::NewAuditEvent {
-                realm_id,
+                realm_id: provider.realm_id,
                 actor_user_id: broker_result.user_id,
                 action: "idp_callback_success".to_string(),
                 target_type: "identity_provider".to_string(),
@@ -343,6 +489,107 @@ impl OAuthBrokerService {
         })
     }
 
+    fn validate_saml_response(
+        &self,
+        pending: &PendingCallback,
+        saml_response: &str,
+    ) -> Result<OAuthUpstreamIdentity> {
+        let provider = &pending.provider;
+        let idp_entity_id = provider.issuer.as_deref().ok_or_else(|| {
+            Error::Validation("SAML identity provider entity ID is missing".to_string())
+        })?;
+        let certificates: Vec<String> =
+            serde_json::from_str(&provider.saml_signing_certificates_json)
+                .map_err(|e| Error::System(format!("Invalid SAML certificates: {}", e)))?;
+        let keys = certificate_keys(&certificates)?;
+        if keys.is_empty() {
+            return Err(Error::Validation(
+                "SAML identity provider has no signing certificate".to_string(),
+            ));
+        }
+        let request_id = pending.broker_state.nonce.as_deref().ok_or_else(|| {
+            Error::Validation("SAML broker state has no AuthnRequest ID".to_string())
+        })?;
+
+        let xml = saml::decode_post_message(saml_response)?;
+        let assertion = validate_response(
+            &xml,
+            &ResponseExpectations {
+                idp_entity_id,
+                sp_entity_id: &provider.client_id,
+                acs_url: &pending.broker_state.redirect_uri,
+                request_id,
+                keys: &keys,
+                now: Utc::now(),
+            },
+        )?;
+        let mapping: Value = serde_json::from_str(&provider.claim_mapping_json)
+            .map_err(|e| Error::System(format!("Invalid provider claim mapping: {}", e)))?;
+        assertion.upstream_identity(&mapping)
+    }
+
+    /// A signed HTTP-Redirect AuthnRequest to the identity provider's SSO
+    /// service, carrying the broker state as RelayState.
+    async fn build_saml_redirect(
+        &self,
+        provider: &IdentityProvider,
+        acs_url: &str,
+        request_id: &str,
+        relay_state: &str,
+    ) -> Result<String> {
+        let sso_url = provider.authorization_endpoint.as_deref().ok_or_else(|| {
+            Error::Validation("SAML identity provider SSO URL is missing".to_string())
+        })?;
+        if provider.client_id.trim().is_empty() {
+            return Err(Error::Validation(
+                "SAML identity provider needs a service provider entity ID".to_string(),
+            ));
+        }
+        let request = build_authn_request(
+            request_id,
+            &AuthnRequestParams {
+                issuer: &provider.client_id,
+                destination: sso_url,
+                acs_url,
+                name_id_format: provider.saml_name_id_format,
+                issued_at: Utc::now(),
+            },
+        );
+
+        let key = self
+            .signing_key_service
+            .saml_signing_key(provider.realm_id)
+            .await?;
+        let mut query = format!(
+            "SAMLRequest={}",
+            urlencoding::encode(&saml::encode_redirect_message(&request)?)
+        );
+        query.push_str(&format!("&RelayState={}", urlencoding::encode(relay_state)));
+        query.push_str(&format!(
+            "&SigAlg={}",
+            urlencoding::encode(saml::RSA_SHA256)
+        ));
+        let signature = saml::sign_query(&query, &key.private_key)?;
+        query.push_str(&format!("&Signature={}", urlencoding::encode(&signature)));
+
+        let separator = if sso_url.contains('?') { '&' } else { '?' };
+        Ok(format!("{}{}{}", sso_url, separator, query))
+    }
+
+    async fn refresh_provider_metadata(&self, provider: &mut IdentityProvider) -> Result<()> {
+        let refreshed = match provider.protocol {
+            IdentityProviderProtocol::Saml => {
+                maybe_refresh_saml_metadata(self.http_client.clone(), provider).await?
+            }
+            _ => maybe_refresh_oidc_discovery(self.http_client.clone(), provider).await?,
+        };
+        if refreshed {
+            provider.updated_at = Utc::now();
+            self.provider_repo.update(provider, None).await?;
+        }
+        Ok(())
+    }
+
     async fn exchange_and_fetch_profile(
         &self,
         provider: &mut IdentityProvider,
@@ -440,11 +687,11 @@ impl OAuthBrokerService {
             }
         };
         let id_token_claims = match provider.protocol {
-            crate::domain::identity_provider::IdentityProviderProtocol::Oidc => Some(
+            IdentityProviderProtocol::Oidc => Some(
                 self.validate_id_token(provider, &token_body, expected_nonce)
                     .await?,
             ),
-            crate::domain::identity_provider::IdentityProviderProtocol::Oauth2 => None,
+            IdentityProviderProtocol::Oauth2 | IdentityProviderProtocol::Saml => None,
         };
         let userinfo_claims = self
             .fetch_userinfo_claims(provider, &token_body.access_token, auth_session_id)
@@ -495,7 +742,7 @@ impl OAuthBrokerService {
                 .await?;
         if refreshed {
             provider.updated_at = Utc::now();
-            self.provider_repo.update(provider).await?;
+            self.provider_repo.update(provider, None).await?;
         }
         let jwk = select_jwk(&jwks, header.kid.as_deref())?;
         let decoding_key = DecodingKey::from_jwk(jwk)
@@ -527,8 +774,8 @@ impl OAuthBrokerService {
     ) -> Result<Option<Value>> {
         let Some(userinfo_endpoint) = provider.userinfo_endpoint.as_deref() else {
             return match provider.protocol {
-                crate::domain::identity_provider::IdentityProviderProtocol::Oidc => Ok(None),
-                crate::domain::identity_provider::IdentityProviderProtocol::Oauth2 => Err(
+                IdentityProviderProtocol::Oidc => Ok(None),
+                IdentityProviderProtocol::Oauth2 | IdentityProviderProtocol::Saml => Err(
                     Error::Validation("Identity provider userinfo endpoint is missing".to_string()),
                 ),
             };
@@ -804,22 +1051,21 @@ impl OAuthBrokerService {
                     if let Some(reason) = user.sign_in_block_reason(now) {
                         return Err(Error::Validation(reason.to_string()));
                     }
-                    self.federation_repo
-                        .create(&FederatedIdentity {
-                            id: Uuid::new_v4(),
-                            realm_id: provider.realm_id,
-                            provider_id: provider.id,
-                            user_id: user.id,
-                            subject: upstream.subject.clone(),
-                            external_username: upstream.username.clone(),
-                            external_email: upstream.email.clone(),
-                            raw_claims_json: Some(upstream.claims.to_string()),
-                            linked_via: "auto_email".to_string(),
-                            last_login_at: Some(now),
-                            created_at: now,
-                            updated_at: now,
-                        })
-                        .await?;
+                    let identity = FederatedIdentity {
+                        id: Uuid::new_v4(),
+                        realm_id: provider.realm_id,
+                        provider_id: provider.id,
+                        user_id: user.id,
+                        subject: upstream.subject.clone(),
+                        external_username: upstream.username.clone(),
+                        external_email: upstream.email.clone(),
+                        raw_claims_json: Some(upstream.claims.to_string()),
+                        linked_via: "auto_email".to_string(),
+                        last_login_at: Some(now),
+                        created_at: now,
+                        updated_at: now,
+                    };
+                    self.federation_repo.create(&identity).await?;
                     self.audit_service
                         .record(crate::domain::audit::NewAuditEvent {
                             realm_id: provider.realm_id,
@@ -834,6 +1080,7 @@ impl OAuthBrokerService {
                             }),
                         })
                         .await?;
+                    self.publish_linked(&identity, &provider.alias).await;
                     return Ok(self.build_result(
                         &provider,
                         "logged_in",
@@ -1014,22 +1261,21 @@ impl OAuthBrokerService {
         }
 
         let now = Utc::now();
-        self.federation_repo
-            .create(&FederatedIdentity {
-                id: Uuid::new_v4(),
-                realm_id,
-                provider_id: provider.id,
-                user_id: user.id,
-                subject: broker_result.subject.clone(),
-                external_username: broker_result.external_username.clone(),
-                external_email: broker_result.external_email.clone(),
-                raw_claims_json: None,
-                linked_via: "manual".to_string(),
-                last_login_at: Some(now),
-                created_at: now,
-                updated_at: now,
-            })
-            .await?;
+        let identity = FederatedIdentity {
+            id: Uuid::new_v4(),
+            realm_id,
+            provider_id: provider.id,
+            user_id: user.id,
+            subject: broker_result.subject.clone(),
+            external_username: broker_result.external_username.clone(),
+            external_email: broker_result.external_email.clone(),
+            raw_claims_json: None,
+            linked_via: "manual".to_string(),
+            last_login_at: Some(now),
+            created_at: now,
+            updated_at: now,
+        };
+        self.federation_repo.create(&identity).await?;
         self.audit_service
             .record(crate::domain::audit::NewAuditEvent {
                 realm_id,
@@ -1044,6 +1290,7 @@ impl OAuthBrokerService {
                 }),
             })
             .await?;
+        self.publish_linked(&identity, &provider.alias).await;
 
         Ok(self.build_result(
             &provider,
@@ -1163,10 +1410,24 @@ impl OAuthBrokerService {
                 }),
             })
             .await?;
+        self.publish_linked(&federation, &provider.alias).await;
 
         Ok(user)
     }
 
+    async fn publish_linked(&self, identity: &FederatedIdentity, provider_alias: &str) {
+        self.event_publisher
+            .publish(DomainEvent::IdpLinked(IdpLinked {
+                realm_id: identity.realm_id,
+                user_id: identity.user_id,
+                provider_id: identity.provider_id,
+                provider_alias: provider_alias.to_string(),
+                subject: identity.subject.clone(),
+                linked_via: identity.linked_via.clone(),
+            }))
+            .await;
+    }
+
     fn callback_url(&self, realm_path: &str, alias: &str) -> Result<String> {
         let mut base = Url::parse(self.public_url.trim())
             .map_err(|_| Error::System("server.public_url is invalid".to_string()))?;
@@ -1178,6 +1439,17 @@ impl OAuthBrokerService {
         Ok(base.to_string())
     }
 
+    fn acs_url(&self, realm_path: &str, alias: &str) -> Result<String> {
+        let mut base = Url::parse(self.public_url.trim())
+            .map_err(|_| Error::System("server.public_url is invalid".to_string()))?;
+        base.set_path(&format!(
+            "/api/realms/{}/auth/saml/{}/acs",
+            realm_path, alias
+        ));
+        base.set_query(None);
+        Ok(base.to_string())
+    }
+
     fn build_result(
         &self,
         provider: &IdentityProvider,
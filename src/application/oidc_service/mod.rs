@@ -1,42 +1,167 @@
+use crate::application::audit_service::AuditService;
+use crate::application::claims_service::ClaimsService;
+use crate::application::flow_executor::FlowExecutor;
+use crate::application::logout_service::LogoutService;
+use crate::application::rbac_service::RbacService;
 use crate::application::secret_service::SecretService;
+use crate::domain::audit::NewAuditEvent;
+use crate::domain::claims::{ClaimsGrant, ClaimsRequest};
+use crate::domain::events::SessionRevocationReason;
 use crate::domain::pagination::{PageRequest, PageResponse};
-use crate::ports::token_service::TokenService;
+use crate::ports::token_service::{AccessTokenClaims, TokenService};
 use crate::{
-    application::auth_service::AuthService,
+    application::auth_service::{AuthService, LoginResponse},
     domain::{
         auth_session::{AuthenticationSession, SessionStatus},
-        execution::ExecutionPlan,
-        oidc::{AuthCode, ClientDeleteSummary, ClientStats, OidcClient, OidcContext, OidcRequest},
+        execution::{ExecutionPlan, ExecutionResult},
+        oidc::{
+            generate_user_code, normalize_user_code, AuthCode, AuthorizationRequestParams,
+            ClientAuthentication, ClientDeleteSummary, ClientStats, DeviceAuthorization,
+            DeviceAuthorizationStatus, DeviceContext, EndSessionRequest, OidcClient, OidcContext,
+            OidcRequest, PushedAuthorizationRequest, TokenEndpointAuthMethod, TokenExchangeRule,
+            ACCESS_TOKEN_TYPE, PAR_REQUEST_URI_PREFIX,
+        },
+        realm::Realm,
         session::RefreshToken,
+        signing_key::SigningAlgorithm,
     },
     error::{Error, Result},
     ports::{
-        auth_session_repository::AuthSessionRepository, flow_store::FlowStore,
-        oidc_repository::OidcRepository, realm_repository::RealmRepository,
+        auth_session_repository::AuthSessionRepository,
+        device_authorization_repository::DeviceAuthorizationRepository, flow_store::FlowStore,
+        oidc_repository::OidcRepository,
+        pushed_authorization_request_repository::PushedAuthorizationRequestRepository,
+        realm_repository::RealmRepository, session_repository::SessionRepository,
         transaction_manager::Transaction, user_repository::UserRepository,
     },
 };
-use chrono::{Duration, Utc};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
 use rand::distr::{Alphanumeric, SampleString};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use sha2::{Digest, Sha256};
 use std::sync::Arc;
+use tracing::error;
 use uuid::Uuid;
 
 #[derive(Serialize)]
 pub struct TokenResponse {
     pub access_token: String,
-    pub id_token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_token: Option<String>,
     pub token_type: String, // e.g., "Bearer"
-    pub expires_in: i64,    // e.g., 900 (seconds until expiry)
+    pub expires_in: i64,    // seconds until the access token expires
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Set on token exchange responses (RFC 8693, Section 2.2.1).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issued_token_type: Option<String>,
 }
 
-#[derive(Deserialize)]
+impl TokenResponse {
+    fn from_login(login: LoginResponse, refresh_token: &RefreshToken, expires_in: i64) -> Self {
+        Self {
+            access_token: login.access_token,
+            id_token: login.id_token,
+            token_type: "Bearer".to_string(),
+            expires_in,
+            refresh_token: Some(refresh_token.id.to_string()),
+            scope: None,
+            issued_token_type: None,
+        }
+    }
+}
+
+/// RFC 7662 introspection response. Inactive tokens serialize as
+/// `{"active": false}` only, so nothing leaks about why they are inactive.
+#[derive(Debug, Default, Serialize)]
+pub struct TokenIntrospection {
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iat: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
 pub struct UpdateClientRequest {
     pub client_id: Option<String>,
     pub redirect_uris: Option<Vec<String>>,
     pub web_origins: Option<Vec<String>>,
     pub scopes: Option<Vec<String>>,
+    pub token_endpoint_auth_method: Option<TokenEndpointAuthMethod>,
+    /// JWK Set used to verify `private_key_jwt` client assertions.
+    pub jwks: Option<serde_json::Value>,
+    /// Overrides the realm's token signing algorithm for this client.
+    pub signing_algorithm: Option<SigningAlgorithm>,
+    /// An empty string clears the registered URI.
+    pub backchannel_logout_uri: Option<String>,
+    /// An empty string clears the registered URI.
+    pub frontchannel_logout_uri: Option<String>,
+    /// Replaces the token exchange rules; an empty list disables exchange.
+    pub token_exchange_policy: Option<Vec<TokenExchangeRule>>,
+    pub direct_grant_enabled: Option<bool>,
+    pub require_pushed_authorization_requests: Option<bool>,
+}
+
+/// Parameters of an RFC 8693 token exchange request. The authenticated client
+/// is the actor, so `actor_token` is not accepted.
+#[derive(Debug, Default)]
+pub struct TokenExchangeRequest {
+    pub subject_token: String,
+    pub subject_token_type: String,
+    pub audience: String,
+    pub scope: Option<String>,
+    pub requested_token_type: Option<String>,
+    pub actor_token: Option<String>,
+}
+
+/// Credentials of a Resource Owner Password grant (RFC 6749, Section 4.3).
+#[derive(Debug, Default)]
+pub struct PasswordGrantRequest {
+    pub username: String,
+    pub password: String,
+    pub scope: Option<String>,
+    /// One-time code for direct grant flows with a TOTP or SMS step.
+    pub otp: Option<String>,
+}
+
+/// Upper bound on challenges answered during one password grant, so a
+/// misconfigured flow cannot loop.
+const MAX_DIRECT_GRANT_STEPS: usize = 8;
+
+/// How long a device code can be redeemed (RFC 8628, Section 3.2 `expires_in`).
+const DEVICE_CODE_TTL_SECS: i64 = 600;
+/// Initial polling interval, and the step added on every `slow_down`.
+const DEVICE_POLL_INTERVAL_SECS: i64 = 5;
+
+/// How long a pushed `request_uri` can be redeemed (RFC 9126, Section 2.2
+/// `expires_in`).
+pub const PAR_REQUEST_URI_TTL_SECS: i64 = 60;
+
+/// What the end-session endpoint does once the sessions are gone.
+#[derive(Debug, Default)]
+pub struct EndSessionOutcome {
+    /// The validated `post_logout_redirect_uri`.
+    pub post_logout_redirect_uri: Option<String>,
+    /// Front-channel logout URLs for the browser to load before leaving.
+    pub frontchannel_logout_urls: Vec<String>,
 }
 
 pub struct OidcService {
@@ -49,6 +174,14 @@ pub struct OidcService {
     auth_session_repo: Arc<dyn AuthSessionRepository>,
     flow_store: Arc<dyn FlowStore>,
     realm_repo: Arc<dyn RealmRepository>,
+    rbac_service: Arc<RbacService>,
+    session_repo: Arc<dyn SessionRepository>,
+    audit_service: Arc<AuditService>,
+    device_repo: Arc<dyn DeviceAuthorizationRepository>,
+    par_repo: Arc<dyn PushedAuthorizationRequestRepository>,
+    claims_service: Arc<ClaimsService>,
+    flow_executor: Arc<FlowExecutor>,
+    logout_service: Arc<LogoutService>,
 }
 
 impl OidcService {
@@ -62,6 +195,14 @@ impl OidcService {
         auth_session_repo: Arc<dyn AuthSessionRepository>,
         flow_store: Arc<dyn FlowStore>,
         realm_repo: Arc<dyn RealmRepository>,
+        rbac_service: Arc<RbacService>,
+        session_repo: Arc<dyn SessionRepository>,
+        audit_service: Arc<AuditService>,
+        device_repo: Arc<dyn DeviceAuthorizationRepository>,
+        par_repo: Arc<dyn PushedAuthorizationRequestRepository>,
+        claims_service: Arc<ClaimsService>,
+        flow_executor: Arc<FlowExecutor>,
+        logout_service: Arc<LogoutService>,
     ) -> Self {
         Self {
             oidc_repo,
@@ -72,6 +213,14 @@ impl OidcService {
             auth_session_repo,
             flow_store,
             realm_repo,
+            rbac_service,
+            session_repo,
+            audit_service,
+            device_repo,
+            par_repo,
+            claims_service,
+            flow_executor,
+            logout_service,
         }
     }
 
@@ -82,33 +231,189 @@ impl OidcService {
         realm_id: Uuid,
         req: OidcRequest,
     ) -> Result<AuthenticationSession> {
-        // 1. Validate Client & Redirect URI immediately
+        // 1. Validate Client, Redirect URI & PKCE immediately
         // This ensures we don't start a flow for a bad client.
+        // 2. The realm tells us the configured Browser Flow
+        let realm = self.validate_authorization_request(realm_id, &req).await?;
+
+        // 3. Construct OIDC Context (Data to preserve across the login flow)
+        let oidc_context = OidcContext {
+            client_id: req.client_id,
+            redirect_uri: req.redirect_uri,
+            response_type: req.response_type,
+            scope: req.scope,
+            state: req.state,
+            nonce: req.nonce,
+            code_challenge: req.code_challenge,
+            code_challenge_method: req.code_challenge_method.map(normalize_pkce_method),
+            claims: req.claims,
+        };
+
+        // 4. Start the realm's browser flow with the OIDC data in its context
+        self.create_browser_flow_session(
+            &realm,
+            serde_json::json!({ "oidc": oidc_context }),
+            Utc::now() + Duration::minutes(30),
+        )
+        .await
+    }
+
+    /// Checks what `/authorize` checks before any flow starts: response type,
+    /// client and redirect URI, and the realm's PKCE rules.
+    async fn validate_authorization_request(
+        &self,
+        realm_id: Uuid,
+        req: &OidcRequest,
+    ) -> Result<Realm> {
         if req.response_type != "code" {
             return Err(Error::OidcInvalidRequest(
                 "Unsupported response_type".to_string(),
             ));
         }
+        if let Some(claims) = req.claims.as_deref() {
+            ClaimsRequest::parse(claims).map_err(|err| {
+                Error::OidcInvalidRequest(format!("Invalid claims parameter: {}", err))
+            })?;
+        }
 
         let client = self
             .validate_client(&realm_id, &req.client_id, &req.redirect_uri)
             .await?;
 
-        // 2. Fetch Realm to find the configured Browser Flow
         let realm = self
             .realm_repo
             .find_by_id(&realm_id)
             .await?
             .ok_or(Error::NotFound("Realm not found".to_string()))?;
-        enforce_pkce_requirements(&client, &req, realm.pkce_required_public_clients)?;
+        enforce_pkce_requirements(&client, req, realm.pkce_required_public_clients)?;
+        Ok(realm)
+    }
+
+    /// Turns the parameters sent to `/authorize` into the request to act on:
+    /// a pushed `request_uri` is redeemed once, a signed `request` object is
+    /// verified against the client's JWKS, otherwise the query is used as is.
+    /// Clients that require PAR only get the first.
+    pub async fn resolve_authorization_request(
+        &self,
+        realm_id: Uuid,
+        params: AuthorizationRequestParams,
+        request_object_audiences: &[String],
+    ) -> Result<OidcRequest> {
+        let client_id = required_param(params.client_id.as_deref(), "client_id")?;
+
+        if let Some(request_uri) = params.request_uri.as_deref() {
+            if params.request.is_some() {
+                return Err(Error::OidcInvalidRequest(
+                    "request and request_uri cannot be used together".to_string(),
+                ));
+            }
+            let pushed = self
+                .par_repo
+                .consume(&realm_id, &client_id, request_uri, Utc::now())
+                .await?
+                .ok_or_else(|| {
+                    Error::OidcInvalidRequestUri(
+                        "request_uri is invalid, expired or already used".to_string(),
+                    )
+                })?;
+            return Ok(pushed.request);
+        }
+
+        let client = self
+            .oidc_repo
+            .find_client_by_id(&realm_id, &client_id)
+            .await?
+            .ok_or_else(|| Error::OidcClientNotFound(client_id.clone()))?;
+        if client.require_pushed_authorization_requests {
+            return Err(Error::OidcInvalidRequest(
+                "Client requires pushed authorization requests".to_string(),
+            ));
+        }
+
+        match params.request.as_deref() {
+            Some(request_object) => {
+                verify_request_object(&client, request_object, request_object_audiences)
+            }
+            None => authorization_request_from_params(&client.client_id, params),
+        }
+    }
 
-        // 3. Identify the Flow ID
-        let flow_id_str = realm.browser_flow_id.ok_or(Error::Validation(
+    /// RFC 9126 pushed authorization request from an authenticated client.
+    /// The request is validated as `/authorize` would validate it, then stored
+    /// behind a `request_uri` that can be redeemed once.
+    pub async fn push_authorization_request(
+        &self,
+        client: &OidcClient,
+        params: AuthorizationRequestParams,
+        request_object_audiences: &[String],
+    ) -> Result<PushedAuthorizationRequest> {
+        if params.request_uri.is_some() {
+            return Err(Error::OidcInvalidRequest(
+                "request_uri is not allowed in a pushed authorization request".to_string(),
+            ));
+        }
+        if params
+            .client_id
+            .as_deref()
+            .is_some_and(|client_id| client_id != client.client_id)
+        {
+            return Err(Error::OidcInvalidRequest(
+                "client_id does not match the authenticated client".to_string(),
+            ));
+        }
+
+        let request = match params.request.as_deref() {
+            Some(request_object) => {
+                verify_request_object(client, request_object, request_object_audiences)?
+            }
+            None => authorization_request_from_params(&client.client_id, params)?,
+        };
+        self.validate_authorization_request(client.realm_id, &request)
+            .await?;
+
+        let now = Utc::now();
+        let pushed = PushedAuthorizationRequest {
+            request_uri: format!(
+                "{}{}",
+                PAR_REQUEST_URI_PREFIX,
+                Alphanumeric.sample_string(&mut rand::rng(), 32)
+            ),
+            realm_id: client.realm_id,
+            client_id: client.client_id.clone(),
+            request,
+            expires_at: now + Duration::seconds(PAR_REQUEST_URI_TTL_SECS),
+            created_at: now,
+        };
+        self.par_repo.create(&pushed).await?;
+        Ok(pushed)
+    }
+
+    /// Creates an `auth_sessions` row at the start node of the realm's
+    /// browser flow. `context` carries what the flow's success handler needs.
+    async fn create_browser_flow_session(
+        &self,
+        realm: &Realm,
+        context: serde_json::Value,
+        expires_at: DateTime<Utc>,
+    ) -> Result<AuthenticationSession> {
+        let flow_id = realm.browser_flow_id.as_deref().ok_or(Error::Validation(
             "Realm has no browser flow configured".to_string(),
         ))?;
-        let flow_id = Uuid::parse_str(&flow_id_str).unwrap_or_default();
+        self.create_flow_session(realm, flow_id, context, expires_at)
+            .await
+    }
 
-        // 4. Get the Active Version of that Flow (To find Start Node)
+    async fn create_flow_session(
+        &self,
+        realm: &Realm,
+        flow_id: &str,
+        context: serde_json::Value,
+        expires_at: DateTime<Utc>,
+    ) -> Result<AuthenticationSession> {
+        // 1. Identify the Flow ID
+        let flow_id = Uuid::parse_str(flow_id).unwrap_or_default();
+
+        // 2. Get the Active Version of that Flow (To find Start Node)
         let version = self
             .flow_store
             .get_active_version(&flow_id)
@@ -119,44 +424,28 @@ impl OidcService {
         let plan: ExecutionPlan = serde_json::from_str(&version.execution_artifact)
             .map_err(|e| Error::Unexpected(anyhow::anyhow!("Corrupt execution artifact: {}", e)))?;
 
-        // 5. Construct OIDC Context (Data to preserve across the login flow)
-        let oidc_context = OidcContext {
-            client_id: req.client_id,
-            redirect_uri: req.redirect_uri,
-            response_type: req.response_type,
-            scope: req.scope,
-            state: req.state,
-            nonce: req.nonce,
-            code_challenge: req.code_challenge,
-            code_challenge_method: req.code_challenge_method.map(normalize_pkce_method),
-        };
-
-        // 6. Create the Authentication Session
+        // 3. Create the Authentication Session
         let session = AuthenticationSession {
             id: Uuid::new_v4(),
-            realm_id,
+            realm_id: realm.id,
             flow_version_id: Uuid::parse_str(&version.id).unwrap_or_default(),
             current_node_id: plan.start_node_id, // Start at the correct node
-
-            // CRITICAL: Save OIDC data here in the unified JSON context
-            context: serde_json::json!({
-                "oidc": oidc_context
-            }),
-
+            context,
             status: SessionStatus::Active,
             user_id: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
-            expires_at: Utc::now() + Duration::minutes(30),
+            expires_at,
         };
 
-        // 7. Persist directly to 'auth_sessions' table
+        // 4. Persist directly to 'auth_sessions' table
         self.auth_session_repo.create(&session).await?;
 
         Ok(session)
     }
 
     /// Handles the creation of the authorization code AFTER login success.
+    /// `grant` is what the token exchanged for the code will release.
     #[allow(clippy::too_many_arguments)]
     pub async fn create_authorization_code(
         &self,
@@ -167,6 +456,7 @@ impl OidcService {
         nonce: Option<String>,
         code_challenge: Option<String>,
         code_challenge_method: String,
+        grant: ClaimsGrant,
     ) -> Result<AuthCode> {
         // Double check client validation just to be safe
         let _client = self
@@ -183,15 +473,18 @@ impl OidcService {
             code_challenge,
             code_challenge_method: normalize_pkce_method(code_challenge_method),
             expires_at: Utc::now() + Duration::seconds(300),
+            claims: grant.claims_json(),
+            scope: grant.scope,
         };
 
         self.oidc_repo.save_auth_code(&auth_code).await?;
         Ok(auth_code)
     }
 
-    /// Handles the `/token` endpoint logic.
+    /// Handles `grant_type=authorization_code` for an already authenticated client.
     pub async fn exchange_code_for_token(
         &self,
+        client: &OidcClient,
         code: &str,
         redirect_uri: &str,
         code_verifier: &str,
@@ -205,6 +498,12 @@ impl OidcService {
             .await?
             .ok_or(Error::OidcInvalidCode)?;
 
+        if auth_code.client_id != client.client_id {
+            return Err(Error::OidcInvalidGrant(
+                "Authorization code was issued to another client".to_string(),
+            ));
+        }
+
         if auth_code.redirect_uri != redirect_uri {
             return Err(Error::OidcInvalidRedirect(redirect_uri.to_string()));
         }
@@ -239,27 +538,847 @@ impl OidcService {
             .ok_or(Error::UserNotFound)?;
 
         // 5. Create Session
+        let grant = ClaimsGrant::from_stored(auth_code.scope, auth_code.claims.as_deref());
         let (login_response, refresh_token) = self
             .auth_service
-            .create_session(
+            .create_session_with_grant(
                 &user,
                 Some(auth_code.client_id.clone()),
+                grant.clone(),
                 ip_address,
                 user_agent,
             )
             .await?;
 
         // 6. Map Response
-        let token_response = TokenResponse {
-            access_token: login_response.access_token,
-            id_token: login_response.id_token.unwrap_or_default(),
+        let mut token_response = TokenResponse::from_login(
+            login_response,
+            &refresh_token,
+            self.token_service.access_token_ttl_secs(),
+        );
+        token_response.scope = grant.scope;
+
+        Ok((token_response, refresh_token))
+    }
+
+    /// Handles `grant_type=client_credentials`. Only confidential clients may
+    /// use it; the token carries the permissions of the client's own roles.
+    pub async fn client_credentials_grant(
+        &self,
+        client: &OidcClient,
+        scope: Option<&str>,
+    ) -> Result<TokenResponse> {
+        if client.token_endpoint_auth_method == TokenEndpointAuthMethod::None {
+            return Err(Error::OidcUnauthorizedClient(
+                "Public clients cannot use client_credentials".to_string(),
+            ));
+        }
+
+        let granted_scope = resolve_requested_scope(client, scope)?;
+        let (roles, permissions) = self
+            .rbac_service
+            .get_client_roles_and_permissions(client.realm_id, client.id)
+            .await?;
+
+        let access_token = self
+            .token_service
+            .create_client_access_token(client, &permissions, &roles, granted_scope.as_deref())
+            .await?;
+
+        Ok(TokenResponse {
+            access_token,
+            id_token: None,
             token_type: "Bearer".to_string(),
-            expires_in: 900,
-        };
+            expires_in: self.token_service.access_token_ttl_secs(),
+            refresh_token: None,
+            scope: granted_scope,
+            issued_token_type: None,
+        })
+    }
 
+    /// Handles `grant_type=password` by running the realm's direct grant flow
+    /// without a browser. The request is offered as input to every challenge;
+    /// a node that asks again, or a step that needs a browser, ends the grant
+    /// with `invalid_grant`.
+    pub async fn password_grant(
+        &self,
+        client: &OidcClient,
+        request: &PasswordGrantRequest,
+        ip_address: Option<String>,
+        user_agent: Option<String>,
+    ) -> Result<(TokenResponse, RefreshToken)> {
+        if !client.direct_grant_enabled {
+            return Err(Error::OidcUnauthorizedClient(
+                "Client is not allowed to use the password grant".to_string(),
+            ));
+        }
+        let granted_scope = resolve_requested_scope(client, request.scope.as_deref())?;
+
+        let realm = self
+            .realm_repo
+            .find_by_id(&client.realm_id)
+            .await?
+            .ok_or(Error::NotFound("Realm not found".to_string()))?;
+        let flow_id = realm.direct_grant_flow_id.as_deref().ok_or_else(|| {
+            Error::OidcUnauthorizedClient("Realm has no direct grant flow configured".to_string())
+        })?;
+        let session = self
+            .create_flow_session(
+                &realm,
+                flow_id,
+                json!({ "direct_grant": { "client_id": client.client_id } }),
+                Utc::now() + Duration::minutes(5),
+            )
+            .await?;
+
+        let outcome = self.run_direct_grant_flow(session.id, request).await;
+        let user_id = self
+            .auth_session_repo
+            .find_by_id(&session.id)
+            .await?
+            .and_then(|session| session.user_id);
+        // Nobody can resume a headless session, so it is dropped either way.
+        self.auth_session_repo.delete(&session.id).await?;
+        outcome?;
+
+        let user_id = user_id.ok_or(Error::UserNotFound)?;
+        let user = self
+            .user_repo
+            .find_by_id(&user_id)
+            .await?
+            .ok_or(Error::UserNotFound)?;
+        let (login_response, refresh_token) = self
+            .auth_service
+            .create_session_with_grant(
+                &user,
+                Some(client.client_id.clone()),
+                ClaimsGrant {
+                    scope: granted_scope.clone(),
+                    claims: None,
+                },
+                ip_address,
+                user_agent,
+            )
+            .await?;
+
+        let mut token_response = TokenResponse::from_login(
+            login_response,
+            &refresh_token,
+            self.token_service.access_token_ttl_secs(),
+        );
+        token_response.scope = granted_scope;
         Ok((token_response, refresh_token))
     }
 
+    /// Drives a direct grant session until the flow allows or denies it.
+    async fn run_direct_grant_flow(
+        &self,
+        session_id: Uuid,
+        request: &PasswordGrantRequest,
+    ) -> Result<()> {
+        let mut input = json!({
+            "username": request.username,
+            "password": request.password,
+        });
+        if let Some(otp) = &request.otp {
+            input["otp"] = json!(otp);
+        }
+
+        let mut answered_node: Option<String> = None;
+        let mut result = self.flow_executor.execute(session_id, None).await;
+        for _ in 0..MAX_DIRECT_GRANT_STEPS {
+            match result.map_err(|err| match err {
+                Error::Validation(message) => Error::OidcInvalidGrant(message),
+                other => other,
+            })? {
+                ExecutionResult::Success { .. } => return Ok(()),
+                ExecutionResult::Failure { reason } => {
+                    return Err(Error::OidcInvalidGrant(reason));
+                }
+                ExecutionResult::AwaitingAction { .. } => {
+                    return Err(Error::OidcInvalidGrant(
+                        "The direct grant flow requires a browser".to_string(),
+                    ));
+                }
+                ExecutionResult::Challenge { context, .. } => {
+                    let current_node = self
+                        .auth_session_repo
+                        .find_by_id(&session_id)
+                        .await?
+                        .map(|session| session.current_node_id);
+                    // The node rejected the input it was already given.
+                    if current_node.is_some() && current_node == answered_node {
+                        let message = context
+                            .get("error")
+                            .and_then(|value| value.as_str())
+                            .unwrap_or("Authentication failed");
+                        return Err(Error::OidcInvalidGrant(message.to_string()));
+                    }
+                    answered_node = current_node;
+                    result = self
+                        .flow_executor
+                        .execute(session_id, Some(input.clone()))
+                        .await;
+                }
+                ExecutionResult::Continue => {
+                    return Err(Error::System(
+                        "Direct grant flow did not reach a terminal state".to_string(),
+                    ));
+                }
+            }
+        }
+        Err(Error::OidcInvalidGrant(
+            "The direct grant flow requires more steps than a token request can answer".to_string(),
+        ))
+    }
+
+    /// Handles `grant_type=refresh_token`. Rotates the presented token within
+    /// its family; presenting an already-rotated token revokes the family.
+    pub async fn refresh_token_grant(
+        &self,
+        client: &OidcClient,
+        refresh_token: &str,
+    ) -> Result<(TokenResponse, RefreshToken)> {
+        let token_id = Uuid::parse_str(refresh_token)
+            .map_err(|_| Error::OidcInvalidGrant("Invalid refresh token".to_string()))?;
+
+        let (login_response, new_token) = self
+            .auth_service
+            .refresh_session_for_client(client.realm_id, &client.client_id, token_id)
+            .await
+            .map_err(|err| match err {
+                Error::InvalidRefreshToken => {
+                    Error::OidcInvalidGrant("Invalid or expired refresh token".to_string())
+                }
+                Error::SecurityViolation(message) => Error::OidcInvalidGrant(message),
+                Error::ReauthRequired => {
+                    Error::OidcInvalidGrant("Re-authentication is required".to_string())
+                }
+                other => other,
+            })?;
+
+        Ok((
+            TokenResponse::from_login(
+                login_response,
+                &new_token,
+                self.token_service.access_token_ttl_secs(),
+            ),
+            new_token,
+        ))
+    }
+
+    /// Handles `grant_type=urn:ietf:params:oauth:grant-type:token-exchange`
+    /// (RFC 8693). The client's `token_exchange_policy` must allow the
+    /// audience and every requested scope. Granted and denied exchanges are
+    /// both audited.
+    pub async fn token_exchange_grant(
+        &self,
+        client: &OidcClient,
+        request: &TokenExchangeRequest,
+    ) -> Result<TokenResponse> {
+        let subject = match self.token_exchange_subject(client, request).await {
+            Ok(subject) => subject,
+            Err(err) => {
+                self.record_token_exchange(client, None, request, Some(&err))
+                    .await;
+                return Err(err);
+            }
+        };
+
+        let result = self.issue_exchanged_token(client, &subject, request).await;
+        self.record_token_exchange(client, Some(&subject), request, result.as_ref().err())
+            .await;
+        result
+    }
+
+    /// Validates the exchange request and returns the claims of its live
+    /// subject token.
+    async fn token_exchange_subject(
+        &self,
+        client: &OidcClient,
+        request: &TokenExchangeRequest,
+    ) -> Result<AccessTokenClaims> {
+        if client.token_endpoint_auth_method == TokenEndpointAuthMethod::None {
+            return Err(Error::OidcUnauthorizedClient(
+                "Public clients cannot use token exchange".to_string(),
+            ));
+        }
+        if request.subject_token_type != ACCESS_TOKEN_TYPE {
+            return Err(Error::OidcInvalidRequest(
+                "Unsupported subject_token_type".to_string(),
+            ));
+        }
+        if request
+            .requested_token_type
+            .as_deref()
+            .is_some_and(|value| value != ACCESS_TOKEN_TYPE)
+        {
+            return Err(Error::OidcInvalidRequest(
+                "Unsupported requested_token_type".to_string(),
+            ));
+        }
+        if request.actor_token.is_some() {
+            return Err(Error::OidcInvalidRequest(
+                "actor_token is not supported; the authenticated client is the actor".to_string(),
+            ));
+        }
+
+        let invalid = || Error::OidcInvalidRequest("Invalid subject_token".to_string());
+        let claims = self
+            .token_service
+            .validate_access_token(client.realm_id, &request.subject_token)
+            .await
+            .map_err(|_| invalid())?;
+        // client_credentials tokens have no user to act for.
+        if claims.sid.is_nil() {
+            return Err(invalid());
+        }
+
+        let Some(session) = self.session_repo.find_by_id_any(&claims.sid).await? else {
+            return Err(invalid());
+        };
+        if session.realm_id != client.realm_id
+            || self
+                .session_repo
+                .find_active_in_family(&session.family_id)
+                .await?
+                .is_none()
+        {
+            return Err(invalid());
+        }
+
+        Ok(claims)
+    }
+
+    async fn issue_exchanged_token(
+        &self,
+        client: &OidcClient,
+        subject: &AccessTokenClaims,
+        request: &TokenExchangeRequest,
+    ) -> Result<TokenResponse> {
+        let rules = client
+            .token_exchange_rules()
+            .map_err(|e| Error::Unexpected(e.into()))?;
+        let rule = rules
+            .iter()
+            .find(|rule| rule.audience == request.audience)
+            .ok_or_else(|| {
+                Error::OidcInvalidTarget(format!(
+                    "Client may not exchange tokens for audience '{}'",
+                    request.audience
+                ))
+            })?;
+        if self
+            .oidc_repo
+            .find_client_by_id(&client.realm_id, &request.audience)
+            .await?
+            .is_none()
+        {
+            return Err(Error::OidcInvalidTarget(format!(
+                "Unknown audience '{}'",
+                request.audience
+            )));
+        }
+
+        // Scopes can only narrow: each must be allowed by the rule and, if
+        // the subject token is scoped, held by the subject token.
+        let requested: Vec<&str> = request
+            .scope
+            .as_deref()
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect();
+        let subject_scopes: Option<Vec<&str>> = subject
+            .scope
+            .as_deref()
+            .map(|scope| scope.split_whitespace().collect());
+        for scope in &requested {
+            if !rule.scopes.iter().any(|allowed| allowed == scope) {
+                return Err(Error::OidcInvalidScope(format!(
+                    "Scope '{}' is not allowed for audience '{}'",
+                    scope, request.audience
+                )));
+            }
+            if subject_scopes
+                .as_ref()
+                .is_some_and(|held| !held.contains(scope))
+            {
+                return Err(Error::OidcInvalidScope(format!(
+                    "Scope '{}' exceeds the subject token's scope",
+                    scope
+                )));
+            }
+        }
+        let granted_scope = (!requested.is_empty()).then(|| requested.join(" "));
+
+        let access_token = self
+            .token_service
+            .create_exchanged_access_token(
+                client.realm_id,
+                subject,
+                &client.client_id,
+                &request.audience,
+                granted_scope.as_deref(),
+            )
+            .await?;
+        let expires_in = (subject.exp as i64 - Utc::now().timestamp())
+            .clamp(0, self.token_service.access_token_ttl_secs());
+
+        Ok(TokenResponse {
+            access_token,
+            id_token: None,
+            token_type: "Bearer".to_string(),
+            expires_in,
+            refresh_token: None,
+            scope: granted_scope,
+            issued_token_type: Some(ACCESS_TOKEN_TYPE.to_string()),
+        })
+    }
+
+    async fn record_token_exchange(
+        &self,
+        client: &OidcClient,
+        subject: Option<&AccessTokenClaims>,
+        request: &TokenExchangeRequest,
+        error: Option<&Error>,
+    ) {
+        let event = NewAuditEvent {
+            realm_id: client.realm_id,
+            actor_user_id: subject.map(|claims| claims.sub),
+            action: if error.is_none() {
+                "token_exchange_granted"
+            } else {
+                "token_exchange_denied"
+            }
+            .to_string(),
+            target_type: "oidc_client".to_string(),
+            target_id: Some(client.id.to_string()),
+            metadata: json!({
+                "client_id": client.client_id,
+                "audience": request.audience,
+                "scope": request.scope,
+                "subject_session_id": subject.map(|claims| claims.sid),
+                "delegation_chain": subject.and_then(|claims| claims.act.clone()),
+                "error": error.map(ToString::to_string),
+            }),
+        };
+
+        if let Err(err) = self.audit_service.record(event).await {
+            error!("Failed to write token exchange audit event: {:?}", err);
+        }
+    }
+
+    /// RFC 8628 device authorization request. The device shows `user_code`
+    /// and polls the token endpoint with `device_code`.
+    pub async fn start_device_authorization(
+        &self,
+        client: &OidcClient,
+        scope: Option<&str>,
+    ) -> Result<DeviceAuthorization> {
+        let scope = resolve_requested_scope(client, scope)?;
+        let now = Utc::now();
+        let authorization = DeviceAuthorization {
+            device_code: Alphanumeric.sample_string(&mut rand::rng(), 48),
+            user_code: generate_user_code(),
+            realm_id: client.realm_id,
+            client_id: client.client_id.clone(),
+            scope,
+            status: DeviceAuthorizationStatus::Pending,
+            user_id: None,
+            interval_secs: DEVICE_POLL_INTERVAL_SECS,
+            last_polled_at: None,
+            expires_at: now + Duration::seconds(DEVICE_CODE_TTL_SECS),
+            created_at: now,
+        };
+        self.device_repo.create(&authorization).await?;
+        Ok(authorization)
+    }
+
+    /// Looks up a pending device authorization by the code the user typed.
+    pub async fn find_pending_device_authorization(
+        &self,
+        realm_id: Uuid,
+        user_code: &str,
+    ) -> Result<DeviceAuthorization> {
+        let invalid = || Error::OidcInvalidRequest("Invalid or expired user code".to_string());
+        let user_code = normalize_user_code(user_code).ok_or_else(invalid)?;
+        let authorization = self
+            .device_repo
+            .find_by_user_code(&realm_id, &user_code)
+            .await?
+            .ok_or_else(invalid)?;
+        if authorization.status != DeviceAuthorizationStatus::Pending
+            || authorization.expires_at <= Utc::now()
+        {
+            return Err(invalid());
+        }
+        Ok(authorization)
+    }
+
+    /// Starts the realm's browser flow to approve a device. On success the
+    /// flow approves the authorization instead of issuing an auth code.
+    pub async fn initiate_device_verification(
+        &self,
+        realm_id: Uuid,
+        user_code: &str,
+    ) -> Result<AuthenticationSession> {
+        let authorization = self
+            .find_pending_device_authorization(realm_id, user_code)
+            .await?;
+        let realm = self
+            .realm_repo
+            .find_by_id(&realm_id)
+            .await?
+            .ok_or(Error::NotFound("Realm not found".to_string()))?;
+
+        let device_context = DeviceContext {
+            device_code: authorization.device_code,
+            client_id: authorization.client_id,
+        };
+        let expires_at = authorization
+            .expires_at
+            .min(Utc::now() + Duration::minutes(30));
+        self.create_browser_flow_session(
+            &realm,
+            serde_json::json!({ "device": device_context }),
+            expires_at,
+        )
+        .await
+    }
+
+    /// Called when a device verification flow succeeds for `user_id`.
+    pub async fn approve_device_authorization(
+        &self,
+        realm_id: Uuid,
+        device_code: &str,
+        user_id: Uuid,
+    ) -> Result<()> {
+        self.resolve_device_authorization(
+            realm_id,
+            device_code,
+            DeviceAuthorizationStatus::Approved,
+            Some(user_id),
+        )
+        .await
+    }
+
+    /// The user declined the request shown for `user_code`.
+    pub async fn deny_device_authorization(&self, realm_id: Uuid, user_code: &str) -> Result<()> {
+        let authorization = self
+            .find_pending_device_authorization(realm_id, user_code)
+            .await?;
+        self.resolve_device_authorization(
+            realm_id,
+            &authorization.device_code,
+            DeviceAuthorizationStatus::Denied,
+            None,
+        )
+        .await
+    }
+
+    async fn resolve_device_authorization(
+        &self,
+        realm_id: Uuid,
+        device_code: &str,
+        status: DeviceAuthorizationStatus,
+        user_id: Option<Uuid>,
+    ) -> Result<()> {
+        let belongs_to_realm = self
+            .device_repo
+            .find_by_device_code(device_code)
+            .await?
+            .is_some_and(|authorization| authorization.realm_id == realm_id);
+        if !belongs_to_realm
+            || !self
+                .device_repo
+                .resolve(device_code, status, user_id, Utc::now())
+                .await?
+        {
+            return Err(Error::OidcInvalidRequest(
+                "Device authorization is no longer pending".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Handles `grant_type=urn:ietf:params:oauth:grant-type:device_code`
+    /// (RFC 8628, Section 3.4). Polling faster than the current interval
+    /// yields `slow_down` and raises the interval.
+    pub async fn device_code_grant(
+        &self,
+        client: &OidcClient,
+        device_code: &str,
+        ip_address: Option<String>,
+        user_agent: Option<String>,
+    ) -> Result<(TokenResponse, RefreshToken)> {
+        let authorization = self
+            .device_repo
+            .find_by_device_code(device_code)
+            .await?
+            .ok_or(Error::OidcInvalidGrant("Invalid device code".to_string()))?;
+
+        if authorization.realm_id != client.realm_id || authorization.client_id != client.client_id
+        {
+            return Err(Error::OidcInvalidGrant(
+                "Device code was issued to another client".to_string(),
+            ));
+        }
+
+        let now = Utc::now();
+        if authorization.expires_at <= now {
+            return Err(Error::OidcExpiredToken);
+        }
+
+        match authorization.status {
+            DeviceAuthorizationStatus::Denied => Err(Error::OidcAccessDenied(
+                "The user denied the authorization request".to_string(),
+            )),
+            DeviceAuthorizationStatus::Pending => {
+                let too_fast = authorization.last_polled_at.is_some_and(|last| {
+                    now - last < Duration::seconds(authorization.interval_secs)
+                });
+                let interval_secs = if too_fast {
+                    authorization.interval_secs + DEVICE_POLL_INTERVAL_SECS
+                } else {
+                    authorization.interval_secs
+                };
+                self.device_repo
+                    .record_poll(device_code, now, interval_secs)
+                    .await?;
+                Err(if too_fast {
+                    Error::OidcSlowDown
+                } else {
+                    Error::OidcAuthorizationPending
+                })
+            }
+            DeviceAuthorizationStatus::Approved => {
+                if !self.device_repo.consume(device_code).await? {
+                    return Err(Error::OidcInvalidGrant(
+                        "Device code has already been used".to_string(),
+                    ));
+                }
+
+                let user_id = authorization.user_id.ok_or(Error::UserNotFound)?;
+                let user = self
+                    .user_repo
+                    .find_by_id(&user_id)
+                    .await?
+                    .ok_or(Error::UserNotFound)?;
+                let (login_response, refresh_token) = self
+                    .auth_service
+                    .create_session_with_grant(
+                        &user,
+                        Some(client.client_id.clone()),
+                        ClaimsGrant {
+                            scope: authorization.scope.clone(),
+                            claims: None,
+                        },
+                        ip_address,
+                        user_agent,
+                    )
+                    .await?;
+
+                let mut token_response = TokenResponse::from_login(
+                    login_response,
+                    &refresh_token,
+                    self.token_service.access_token_ttl_secs(),
+                );
+                token_response.scope = authorization.scope;
+                Ok((token_response, refresh_token))
+            }
+        }
+    }
+
+    /// RFC 7662 introspection. Refresh tokens are UUIDs and access tokens are
+    /// JWTs, so the token format decides the lookup and `token_type_hint` is
+    /// not needed. Tokens from another realm are reported as inactive.
+    pub async fn introspect_token(
+        &self,
+        client: &OidcClient,
+        token: &str,
+    ) -> Result<TokenIntrospection> {
+        require_confidential_client(client)?;
+
+        if let Ok(token_id) = Uuid::parse_str(token) {
+            let Some(refresh) = self.session_repo.find_by_id(&token_id).await? else {
+                return Ok(TokenIntrospection::default());
+            };
+            if refresh.realm_id != client.realm_id {
+                return Ok(TokenIntrospection::default());
+            }
+            return Ok(TokenIntrospection {
+                active: true,
+                sub: Some(refresh.user_id.to_string()),
+                scope: None,
+                client_id: refresh.client_id,
+                token_type: Some("refresh_token".to_string()),
+                exp: Some(refresh.expires_at.timestamp()),
+                iat: Some(refresh.created_at.timestamp()),
+                aud: None,
+            });
+        }
+
+        let Ok(claims) = self
+            .token_service
+            .validate_access_token(client.realm_id, token)
+            .await
+        else {
+            return Ok(TokenIntrospection::default());
+        };
+
+        // client_credentials tokens carry no session: `sub` is the client UUID.
+        let (realm_id, client_id) = if claims.sid.is_nil() {
+            match self.oidc_repo.find_client_by_uuid(&claims.sub).await? {
+                Some(owner) => (owner.realm_id, Some(owner.client_id)),
+                None => return Ok(TokenIntrospection::default()),
+            }
+        } else {
+            // The sid is the refresh token the access token was minted with. It
+            // may since have rotated, so liveness is judged on its family.
+            let Some(session) = self.session_repo.find_by_id_any(&claims.sid).await? else {
+                return Ok(TokenIntrospection::default());
+            };
+            if self
+                .session_repo
+                .find_active_in_family(&session.family_id)
+                .await?
+                .is_none()
+            {
+                return Ok(TokenIntrospection::default());
+            }
+            (session.realm_id, session.client_id)
+        };
+
+        if realm_id != client.realm_id {
+            return Ok(TokenIntrospection::default());
+        }
+
+        Ok(TokenIntrospection {
+            active: true,
+            sub: Some(claims.sub.to_string()),
+            scope: claims.scope,
+            client_id: claims.azp.or(client_id),
+            token_type: Some("Bearer".to_string()),
+            exp: Some(claims.exp as i64),
+            iat: Some(claims.iat as i64),
+            aud: claims.aud,
+        })
+    }
+
+    /// RFC 7009 revocation. Revoking a refresh token, or an access token backed
+    /// by a session, revokes the whole refresh-token family and notifies the
+    /// relying parties of the ended session. Unknown, expired and
+    /// already-revoked tokens are not an error.
+    pub async fn revoke_token(&self, client: &OidcClient, token: &str) -> Result<()> {
+        require_confidential_client(client)?;
+
+        let session_id = match Uuid::parse_str(token) {
+            Ok(token_id) => token_id,
+            Err(_) => match self
+                .token_service
+                .validate_access_token(client.realm_id, token)
+                .await
+            {
+                // Client tokens are stateless and simply run out.
+                Ok(claims) if !claims.sid.is_nil() => claims.sid,
+                _ => return Ok(()),
+            },
+        };
+
+        let Some(session) = self.session_repo.find_by_id_any(&session_id).await? else {
+            return Ok(());
+        };
+        if session.realm_id != client.realm_id {
+            return Ok(());
+        }
+        if session.client_id.as_deref() != Some(client.client_id.as_str()) {
+            return Err(Error::OidcUnauthorizedClient(
+                "Token was issued to another client".to_string(),
+            ));
+        }
+
+        let ended = self
+            .session_repo
+            .find_active_in_family(&session.family_id)
+            .await?;
+        self.session_repo.revoke_family(&session.family_id).await?;
+        if let Some(ended) = ended {
+            self.logout_service
+                .sessions_ended(&[ended], SessionRevocationReason::Revoked)
+                .await;
+        }
+        Ok(())
+    }
+
+    /// Authenticates a client at the token endpoint. The presented method must
+    /// match the client's registered `token_endpoint_auth_method`.
+    pub async fn authenticate_client(
+        &self,
+        realm_id: &Uuid,
+        auth: &ClientAuthentication,
+    ) -> Result<OidcClient> {
+        let client_id = match auth {
+            ClientAuthentication::None { client_id }
+            | ClientAuthentication::SecretBasic { client_id, .. }
+            | ClientAuthentication::SecretPost { client_id, .. } => client_id.clone(),
+            ClientAuthentication::PrivateKeyJwt {
+                client_id,
+                assertion,
+                ..
+            } => match client_id {
+                Some(client_id) => client_id.clone(),
+                None => unverified_assertion_subject(assertion)?,
+            },
+        };
+
+        let client = self
+            .oidc_repo
+            .find_client_by_id(realm_id, &client_id)
+            .await?
+            .ok_or_else(|| Error::OidcInvalidClient(client_id.clone()))?;
+
+        if client.token_endpoint_auth_method != auth.method() {
+            return Err(Error::OidcInvalidClient(format!(
+                "Client must authenticate with {}",
+                client.token_endpoint_auth_method
+            )));
+        }
+
+        match auth {
+            ClientAuthentication::None { .. } => {}
+            ClientAuthentication::SecretBasic { client_secret, .. }
+            | ClientAuthentication::SecretPost { client_secret, .. } => {
+                let stored = client.client_secret.as_deref().ok_or_else(|| {
+                    Error::OidcInvalidClient("Client has no secret configured".to_string())
+                })?;
+                let expected = self.secret_service.decrypt(stored)?;
+                if !secrets_match(&expected, client_secret) {
+                    return Err(Error::OidcInvalidClient(
+                        "Client authentication failed".to_string(),
+                    ));
+                }
+            }
+            ClientAuthentication::PrivateKeyJwt {
+                assertion,
+                audiences,
+                ..
+            } => {
+                let (jti, expires_at) = verify_client_assertion(&client, assertion, audiences)?;
+                if !self
+                    .oidc_repo
+                    .record_client_assertion_jti(&client.id, &jti, expires_at)
+                    .await?
+                {
+                    return Err(Error::OidcInvalidClient(
+                        "Client assertion has already been used".to_string(),
+                    ));
+                }
+            }
+        }
+
+        Ok(client)
+    }
+
     /// Validates that a client exists and the redirect URI is allowed.
     pub async fn validate_client(
         &self,
@@ -286,6 +1405,94 @@ impl OidcService {
         Ok(client)
     }
 
+    /// RP-initiated logout. `id_token_hint` identifies the user and client
+    /// session to end; the browser's SSO session (`sso_token_id`) always ends.
+    /// A `post_logout_redirect_uri` is only honoured when it exactly matches
+    /// one of the client's registered redirect URIs.
+    pub async fn end_session(
+        &self,
+        realm_id: Uuid,
+        request: EndSessionRequest,
+        sso_token_id: Option<Uuid>,
+    ) -> Result<EndSessionOutcome> {
+        let hint = match request.id_token_hint.as_deref() {
+            Some(token) => Some(
+                self.token_service
+                    .validate_id_token_hint(realm_id, token)
+                    .await
+                    .map_err(|_| Error::OidcInvalidRequest("Invalid id_token_hint".to_string()))?,
+            ),
+            None => None,
+        };
+
+        if let (Some(hint), Some(client_id)) = (&hint, &request.client_id) {
+            if &hint.aud != client_id {
+                return Err(Error::OidcInvalidRequest(
+                    "id_token_hint was not issued to client_id".to_string(),
+                ));
+            }
+        }
+        let client_id = request
+            .client_id
+            .clone()
+            .or_else(|| hint.as_ref().map(|hint| hint.aud.clone()));
+
+        let user_id = match &hint {
+            Some(hint) => {
+                let user = match Uuid::parse_str(&hint.sub) {
+                    Ok(id) => self.user_repo.find_by_id(&id).await?,
+                    Err(_) => None,
+                };
+                match user {
+                    Some(user) if user.realm_id == realm_id => Some(user.id),
+                    _ => {
+                        return Err(Error::OidcInvalidRequest(
+                            "id_token_hint does not belong to this realm".to_string(),
+                        ))
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let post_logout_redirect_uri = match request.post_logout_redirect_uri {
+            Some(uri) => {
+                let client_id = client_id.as_deref().ok_or_else(|| {
+                    Error::OidcInvalidRequest(
+                        "post_logout_redirect_uri requires id_token_hint or client_id".to_string(),
+                    )
+                })?;
+                self.validate_client(&realm_id, client_id, &uri)
+                    .await
+                    .map_err(|err| match err {
+                        Error::OidcInvalidRedirect(_) => Error::OidcInvalidRequest(
+                            "post_logout_redirect_uri is not registered for this client"
+                                .to_string(),
+                        ),
+                        other => other,
+                    })?;
+                Some(uri)
+            }
+            None => None,
+        };
+
+        let frontchannel_logout_urls = self
+            .auth_service
+            .end_session(
+                realm_id,
+                sso_token_id,
+                user_id,
+                client_id.as_deref(),
+                hint.and_then(|hint| hint.sid),
+            )
+            .await?;
+
+        Ok(EndSessionOutcome {
+            post_logout_redirect_uri,
+            frontchannel_logout_urls,
+        })
+    }
+
     // --- CRUD and Helpers ---
 
     fn generate_client_secret(&self) -> String {
@@ -309,6 +1516,9 @@ impl OidcService {
     }
 
     pub async fn register_client(&self, client: &mut OidcClient) -> Result<Option<String>> {
+        validate_client_auth_settings(client)?;
+        validate_client_logout_uris(client)?;
+        validate_token_exchange_policy(client)?;
         let plaintext = match client.client_secret.as_deref() {
             Some(secret) if !secret.trim().is_empty() => secret.to_string(),
             _ => self.generate_client_secret(),
@@ -325,6 +1535,9 @@ impl OidcService {
         client: &mut OidcClient,
         tx: Option<&mut dyn Transaction>,
     ) -> Result<Option<String>> {
+        validate_client_auth_settings(client)?;
+        validate_client_logout_uris(client)?;
+        validate_token_exchange_policy(client)?;
         let plaintext = match client.client_secret.as_deref() {
             Some(secret) if !secret.trim().is_empty() => secret.to_string(),
             _ => self.generate_client_secret(),
@@ -344,6 +1557,17 @@ impl OidcService {
         self.oidc_repo.find_client_by_id(realm_id, client_id).await
     }
 
+    pub async fn find_client_by_client_id_with_tx(
+        &self,
+        realm_id: &Uuid,
+        client_id: &str,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<Option<OidcClient>> {
+        self.oidc_repo
+            .find_client_by_id_with_tx(realm_id, client_id, tx)
+            .await
+    }
+
     pub async fn find_client_by_client_id_with_secret(
         &self,
         realm_id: &Uuid,
@@ -426,6 +1650,40 @@ impl OidcService {
                 serde_json::to_string(&scopes).map_err(|e| Error::Unexpected(e.into()))?;
         }
 
+        if let Some(method) = payload.token_endpoint_auth_method {
+            client.token_endpoint_auth_method = method;
+        }
+
+        if let Some(jwks) = payload.jwks {
+            client.jwks = Some(jwks.to_string());
+        }
+
+        if let Some(algorithm) = payload.signing_algorithm {
+            client.signing_algorithm = Some(algorithm);
+        }
+
+        if let Some(uri) = payload.backchannel_logout_uri {
+            client.backchannel_logout_uri = Some(uri).filter(|uri| !uri.is_empty());
+        }
+
+        if let Some(uri) = payload.frontchannel_logout_uri {
+            client.frontchannel_logout_uri = Some(uri).filter(|uri| !uri.is_empty());
+        }
+
+        if let Some(rules) = payload.token_exchange_policy {
+            client.token_exchange_policy = token_exchange_policy_json(&rules)?;
+        }
+
+        if let Some(enabled) = payload.direct_grant_enabled {
+            client.direct_grant_enabled = enabled;
+        }
+        if let Some(required) = payload.require_pushed_authorization_requests {
+            client.require_pushed_authorization_requests = required;
+        }
+
+        validate_client_auth_settings(&client)?;
+        validate_client_logout_uris(&client)?;
+        validate_token_exchange_policy(&client)?;
         self.update_client_record(&client).await?;
         Ok(client)
     }
@@ -436,6 +1694,14 @@ impl OidcService {
         self.oidc_repo.delete_client(&id).await
     }
 
+    pub async fn delete_client_with_tx(
+        &self,
+        id: Uuid,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        self.oidc_repo.delete_client_with_tx(&id, tx).await
+    }
+
     pub async fn get_client_delete_summary(&self, id: Uuid) -> Result<ClientDeleteSummary> {
         self.oidc_repo.count_client_delete_summary(&id).await
     }
@@ -449,18 +1715,18 @@ impl OidcService {
         Ok((client, plaintext))
     }
 
-    pub fn get_jwks(&self) -> Result<serde_json::Value> {
-        self.token_service.get_jwks()
+    pub async fn get_jwks(&self, realm_id: &Uuid) -> Result<serde_json::Value> {
+        self.token_service.get_jwks(realm_id).await
     }
 
     pub async fn is_origin_allowed(&self, origin: &str) -> Result<bool> {
         self.oidc_repo.is_origin_allowed(origin).await
     }
 
-    pub async fn userinfo(&self, access_token: &str) -> Result<serde_json::Value> {
+    pub async fn userinfo(&self, realm_id: Uuid, access_token: &str) -> Result<serde_json::Value> {
         let claims = self
             .token_service
-            .validate_access_token(access_token)
+            .validate_access_token(realm_id, access_token)
             .await?;
         let user = self
             .user_repo
@@ -468,18 +1734,342 @@ impl OidcService {
             .await?
             .ok_or(Error::UserNotFound)?;
 
-        Ok(json!({
+        // The session holds what the user granted the client; its claims
+        // are added to the fixed members, never replacing them.
+        let session = self.session_repo.find_by_id(&claims.sid).await?;
+        let grant = session
+            .as_ref()
+            .map(|session| {
+                ClaimsGrant::from_stored(session.scope.clone(), session.claims.as_deref())
+            })
+            .unwrap_or_default();
+        let issued = self
+            .claims_service
+            .issue(
+                &user,
+                session
+                    .as_ref()
+                    .and_then(|session| session.client_id.as_deref()),
+                &grant,
+                &claims.roles,
+            )
+            .await?;
+
+        let mut userinfo = json!({
             "sub": user.id.to_string(),
             "preferred_username": user.username,
             "roles": claims.roles,
             "groups": claims.groups,
-        }))
+        });
+        if let Some(members) = userinfo.as_object_mut() {
+            for (name, value) in issued.userinfo {
+                members.entry(name).or_insert(value);
+            }
+        }
+        Ok(userinfo)
     }
 }
 
 #[cfg(test)]
 mod tests;
 
+/// Introspection and revocation are only open to clients that authenticated
+/// with a credential.
+fn require_confidential_client(client: &OidcClient) -> Result<()> {
+    if client.token_endpoint_auth_method == TokenEndpointAuthMethod::None {
+        return Err(Error::OidcInvalidClient(
+            "Public clients cannot use this endpoint".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Compares two secrets without leaking the matching prefix length.
+fn secrets_match(expected: &str, presented: &str) -> bool {
+    let expected = Sha256::digest(expected.as_bytes());
+    let presented = Sha256::digest(presented.as_bytes());
+    expected
+        .iter()
+        .zip(presented.iter())
+        .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+        == 0
+}
+
+/// `client_assertion_type` value for RFC 7523 JWT bearer client assertions.
+pub const CLIENT_ASSERTION_TYPE_JWT_BEARER: &str =
+    "urn:ietf:params:oauth:client-assertion-type:jwt-bearer";
+
+/// Reads `sub` from an assertion before its signature is checked, so the
+/// client (and therefore its JWKS) can be looked up. The assertion is fully
+/// verified afterwards in `verify_client_assertion`.
+fn unverified_assertion_subject(assertion: &str) -> Result<String> {
+    let invalid = || Error::OidcInvalidClient("Malformed client assertion".to_string());
+    let payload = assertion.split('.').nth(1).ok_or_else(invalid)?;
+    let bytes = URL_SAFE_NO_PAD.decode(payload).map_err(|_| invalid())?;
+    let claims: serde_json::Value = serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+    claims
+        .get("sub")
+        .and_then(serde_json::Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(invalid)
+}
+
+/// Verifies a `private_key_jwt` assertion (RFC 7523, Section 3) against the
+/// client's registered JWKS: `iss` and `sub` must be the client, `aud` must
+/// name this server and the signature must use an asymmetric algorithm.
+/// Returns the assertion's `jti` and expiry so replays can be rejected.
+fn verify_client_assertion(
+    client: &OidcClient,
+    assertion: &str,
+    audiences: &[String],
+) -> Result<(String, DateTime<Utc>)> {
+    let claims = verify_client_signed_jwt(
+        client,
+        assertion,
+        audiences,
+        &["exp", "aud", "iss", "sub"],
+        "client assertion",
+        Error::OidcInvalidClient,
+    )?;
+    if claims.get("sub").and_then(serde_json::Value::as_str) != Some(client.client_id.as_str()) {
+        return Err(Error::OidcInvalidClient(
+            "Client assertion subject does not match the client".to_string(),
+        ));
+    }
+
+    let jti = claims
+        .get("jti")
+        .and_then(serde_json::Value::as_str)
+        .filter(|jti| !jti.is_empty())
+        .ok_or_else(|| Error::OidcInvalidClient("Client assertion has no jti".to_string()))?;
+    let expires_at = claims
+        .get("exp")
+        .and_then(serde_json::Value::as_i64)
+        .and_then(|exp| DateTime::from_timestamp(exp, 0))
+        .ok_or_else(|| Error::OidcInvalidClient("Client assertion has no exp".to_string()))?;
+
+    Ok((jti.to_string(), expires_at))
+}
+
+/// Verifies an RFC 9101 request object against the client's registered keys
+/// and returns the authorization request it carries. Only the parameters in
+/// the object are used.
+fn verify_request_object(
+    client: &OidcClient,
+    request_object: &str,
+    audiences: &[String],
+) -> Result<OidcRequest> {
+    let claims = verify_client_signed_jwt(
+        client,
+        request_object,
+        audiences,
+        &["exp", "aud", "iss"],
+        "request object",
+        Error::OidcInvalidRequestObject,
+    )?;
+    let params: AuthorizationRequestParams = serde_json::from_value(claims)
+        .map_err(|err| Error::OidcInvalidRequestObject(err.to_string()))?;
+    if params.is_by_reference() {
+        return Err(Error::OidcInvalidRequestObject(
+            "Request object must not contain request or request_uri".to_string(),
+        ));
+    }
+    if params
+        .client_id
+        .as_deref()
+        .is_some_and(|client_id| client_id != client.client_id)
+    {
+        return Err(Error::OidcInvalidRequestObject(
+            "Request object client_id does not match the client".to_string(),
+        ));
+    }
+
+    authorization_request_from_params(&client.client_id, params)
+}
+
+/// Verifies a JWT the client signed with a key from its registered JWKS and
+/// returns its claims. `iss` must be the client, `aud` one of `audiences`,
+/// and HMAC algorithms are refused because they would need a shared secret.
+/// `kind` names the token in error messages and `error` picks the variant.
+fn verify_client_signed_jwt(
+    client: &OidcClient,
+    token: &str,
+    audiences: &[String],
+    required_claims: &[&str],
+    kind: &str,
+    error: fn(String) -> Error,
+) -> Result<serde_json::Value> {
+    let jwks = parse_client_jwks(
+        client
+            .jwks
+            .as_deref()
+            .ok_or_else(|| error("Client has no registered JWKS".to_string()))?,
+    )
+    .map_err(|_| error("Client JWKS is invalid".to_string()))?;
+
+    let header = decode_header(token).map_err(|_| error(format!("Malformed {}", kind)))?;
+    if matches!(
+        header.alg,
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+    ) {
+        return Err(error(format!(
+            "Algorithm {:?} is not allowed for the {}",
+            header.alg, kind
+        )));
+    }
+
+    let jwk = match header.kid.as_deref() {
+        Some(kid) => jwks.find(kid),
+        None if jwks.keys.len() == 1 => jwks.keys.first(),
+        None => None,
+    }
+    .ok_or_else(|| error(format!("No registered key matches the {}", kind)))?;
+    let decoding_key =
+        DecodingKey::from_jwk(jwk).map_err(|_| error("Client JWKS is invalid".to_string()))?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.set_required_spec_claims(required_claims);
+    validation.set_issuer(&[client.client_id.as_str()]);
+    validation.set_audience(audiences);
+
+    decode::<serde_json::Value>(token, &decoding_key, &validation)
+        .map(|token_data| token_data.claims)
+        .map_err(|err| error(format!("Invalid {}: {}", kind, err)))
+}
+
+/// Builds an authorization request from plain parameters.
+fn authorization_request_from_params(
+    client_id: &str,
+    params: AuthorizationRequestParams,
+) -> Result<OidcRequest> {
+    Ok(OidcRequest {
+        client_id: client_id.to_string(),
+        redirect_uri: required_param(params.redirect_uri.as_deref(), "redirect_uri")?,
+        response_type: required_param(params.response_type.as_deref(), "response_type")?,
+        scope: params.scope,
+        state: params.state,
+        nonce: params.nonce,
+        code_challenge: params.code_challenge,
+        code_challenge_method: params.code_challenge_method,
+        claims: params.claims,
+    })
+}
+
+fn required_param(value: Option<&str>, name: &str) -> Result<String> {
+    value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| Error::OidcInvalidRequest(format!("{} is required", name)))
+}
+
+fn parse_client_jwks(raw: &str) -> std::result::Result<JwkSet, serde_json::Error> {
+    serde_json::from_str(raw)
+}
+
+/// Checks that a client's auth method and JWKS are consistent before saving.
+fn validate_client_auth_settings(client: &OidcClient) -> Result<()> {
+    if let Some(raw) = client.jwks.as_deref() {
+        let jwks = parse_client_jwks(raw)
+            .map_err(|err| Error::Validation(format!("Invalid client JWKS: {}", err)))?;
+        if jwks.keys.is_empty() {
+            return Err(Error::Validation(
+                "Client JWKS must contain at least one key".to_string(),
+            ));
+        }
+    }
+
+    if client.token_endpoint_auth_method == TokenEndpointAuthMethod::PrivateKeyJwt
+        && client.jwks.is_none()
+    {
+        return Err(Error::Validation(
+            "private_key_jwt requires a registered JWKS".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Logout URIs are called by ReAuth (back-channel) or loaded by the browser
+/// (front-channel), so both must be absolute http(s) URLs without a fragment.
+fn validate_client_logout_uris(client: &OidcClient) -> Result<()> {
+    let uris = [
+        ("backchannel_logout_uri", &client.backchannel_logout_uri),
+        ("frontchannel_logout_uri", &client.frontchannel_logout_uri),
+    ];
+    for (field, uri) in uris {
+        let Some(uri) = uri.as_deref() else {
+            continue;
+        };
+        let valid = url::Url::parse(uri)
+            .map(|url| matches!(url.scheme(), "http" | "https") && url.fragment().is_none())
+            .unwrap_or(false);
+        if !valid {
+            return Err(Error::Validation(format!(
+                "{} must be an absolute http(s) URL without a fragment",
+                field
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Serializes token exchange rules for storage; no rules means no policy.
+pub fn token_exchange_policy_json(rules: &[TokenExchangeRule]) -> Result<Option<String>> {
+    if rules.is_empty() {
+        return Ok(None);
+    }
+    serde_json::to_string(rules)
+        .map(Some)
+        .map_err(|e| Error::Unexpected(e.into()))
+}
+
+/// Every token exchange rule needs an audience, and each audience may appear
+/// only once so the rule that applies is unambiguous.
+fn validate_token_exchange_policy(client: &OidcClient) -> Result<()> {
+    let rules = client
+        .token_exchange_rules()
+        .map_err(|err| Error::Validation(format!("Invalid token_exchange_policy: {}", err)))?;
+    let mut audiences = std::collections::HashSet::new();
+    for rule in &rules {
+        if rule.audience.trim().is_empty() {
+            return Err(Error::Validation(
+                "token_exchange_policy audiences must not be empty".to_string(),
+            ));
+        }
+        if !audiences.insert(rule.audience.as_str()) {
+            return Err(Error::Validation(format!(
+                "token_exchange_policy lists audience '{}' more than once",
+                rule.audience
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the granted scope string. Every requested scope must be registered
+/// on the client; omitting `scope` grants none.
+fn resolve_requested_scope(client: &OidcClient, scope: Option<&str>) -> Result<Option<String>> {
+    let Some(requested) = scope.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(None);
+    };
+
+    let allowed: Vec<String> = serde_json::from_str(&client.scopes)
+        .map_err(|_| Error::Unexpected(anyhow::anyhow!("Invalid scopes format in DB")))?;
+    let requested: Vec<&str> = requested.split_whitespace().collect();
+    if let Some(unknown) = requested
+        .iter()
+        .find(|value| !allowed.iter().any(|scope| scope == *value))
+    {
+        return Err(Error::OidcInvalidScope(format!(
+            "Scope '{}' is not allowed for this client",
+            unknown
+        )));
+    }
+
+    Ok(Some(requested.join(" ")))
+}
+
 fn normalize_pkce_method(method: String) -> String {
     method.to_uppercase()
 }
@@ -1,15 +1,27 @@
-use super::OidcService;
+use super::{OidcService, PasswordGrantRequest, TokenExchangeRequest};
+use crate::application::audit_service::AuditService;
 use crate::application::auth_service::AuthService;
+use crate::application::claims_service::ClaimsService;
+use crate::application::flow_executor::FlowExecutor;
+use crate::application::logout_service::LogoutService;
 use crate::application::rbac_service::RbacService;
+use crate::application::runtime_registry::RuntimeRegistry;
 use crate::application::secret_service::SecretService;
 use crate::config::AuthConfig;
 use crate::constants::DEFAULT_REALM_NAME;
+use crate::domain::audit::{AuditActionCount, AuditEvent};
 use crate::domain::auth_flow::AuthFlow;
 use crate::domain::auth_session::AuthenticationSession;
+use crate::domain::auth_session_action::AuthSessionAction;
+use crate::domain::claims::ProtocolMapper;
 use crate::domain::events::EventEnvelope;
 use crate::domain::execution::ExecutionPlan;
 use crate::domain::group::Group;
-use crate::domain::oidc::{AuthCode, OidcClient, OidcContext, OidcRequest};
+use crate::domain::oidc::{
+    AuthCode, AuthorizationRequestParams, ClientAuthentication, DeviceAuthorization,
+    DeviceAuthorizationStatus, OidcClient, OidcContext, OidcRequest, PushedAuthorizationRequest,
+    TokenEndpointAuthMethod, ACCESS_TOKEN_TYPE, PAR_REQUEST_URI_PREFIX,
+};
 use crate::domain::pagination::{PageRequest, PageResponse};
 use crate::domain::rbac::{
     CustomPermission, CustomPermissionRoleImpact, GroupMemberFilter, GroupMemberRow,
@@ -19,22 +31,32 @@ use crate::domain::rbac::{
 use crate::domain::role::{Permission, Role};
 use crate::domain::session::RefreshToken;
 use crate::domain::user::User;
+use crate::domain::user_email::UserEmail;
+use crate::domain::user_phone_number::UserPhoneNumber;
 use crate::error::{Error, Result};
+use crate::ports::audit_repository::AuditRepository;
+use crate::ports::auth_session_action_repository::AuthSessionActionRepository;
 use crate::ports::auth_session_repository::AuthSessionRepository;
+use crate::ports::device_authorization_repository::DeviceAuthorizationRepository;
+use crate::ports::flow_repository::FlowRepository;
 use crate::ports::flow_store::FlowStore;
 use crate::ports::oidc_repository::OidcRepository;
 use crate::ports::outbox_repository::OutboxRepository;
+use crate::ports::protocol_mapper_repository::ProtocolMapperRepository;
+use crate::ports::pushed_authorization_request_repository::PushedAuthorizationRequestRepository;
 use crate::ports::rbac_repository::RbacRepository;
 use crate::ports::realm_repository::RealmRepository;
 use crate::ports::session_repository::SessionRepository;
-use crate::ports::token_service::{AccessTokenClaims, TokenService};
+use crate::ports::token_service::{AccessTokenClaims, ActorClaim, IdTokenClaims, TokenService};
 use crate::ports::transaction_manager::{Transaction, TransactionManager};
+use crate::ports::user_email_repository::UserEmailRepository;
+use crate::ports::user_phone_number_repository::UserPhoneNumberRepository;
 use crate::ports::user_repository::UserRepository;
 use async_trait::async_trait;
 use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use base64::Engine;
 use chrono::{DateTime, Duration, Utc};
-use serde_json::json;
+use serde_json::{json, Map, Value};
 use sha2::{Digest, Sha256};
 use std::any::Any;
 use std::collections::{HashMap, HashSet};
@@ -257,6 +279,7 @@ struct TestOidcRepo {
     client: Mutex<Option<OidcClient>>,
     auth_codes: Mutex<HashMap<String, AuthCode>>,
     deleted_codes: Mutex<Vec<String>>,
+    assertion_jtis: Mutex<HashSet<(Uuid, String)>>,
 }
 
 impl TestOidcRepo {
@@ -356,6 +379,44 @@ impl OidcRepository for TestOidcRepo {
     async fn is_origin_allowed(&self, _origin: &str) -> Result<bool> {
         Ok(true)
     }
+
+    async fn record_client_assertion_jti(
+        &self,
+        client_id: &Uuid,
+        jti: &str,
+        _expires_at: DateTime<Utc>,
+    ) -> Result<bool> {
+        Ok(self
+            .assertion_jtis
+            .lock()
+            .unwrap()
+            .insert((*client_id, jti.to_string())))
+    }
+}
+
+struct TestFlowRepo;
+
+#[async_trait]
+impl FlowRepository for TestFlowRepo {
+    async fn find_flow_by_name(&self, _realm_id: &Uuid, _name: &str) -> Result<Option<AuthFlow>> {
+        Ok(None)
+    }
+
+    async fn find_flow_by_id(&self, _flow_id: &Uuid) -> Result<Option<AuthFlow>> {
+        Ok(None)
+    }
+
+    async fn create_flow<'a>(
+        &self,
+        _flow: &AuthFlow,
+        _tx: Option<&'a mut dyn Transaction>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn list_flows_by_realm(&self, _realm_id: &Uuid) -> Result<Vec<AuthFlow>> {
+        Ok(Vec::new())
+    }
 }
 
 #[derive(Default)]
@@ -532,19 +593,51 @@ impl SessionRepository for TestSessionRepo {
         Ok(self.stored.lock().unwrap().get(id).cloned())
     }
 
+    async fn find_active_in_family(&self, family_id: &Uuid) -> Result<Option<RefreshToken>> {
+        Ok(self
+            .stored
+            .lock()
+            .unwrap()
+            .values()
+            .find(|token| {
+                &token.family_id == family_id
+                    && token.revoked_at.is_none()
+                    && token.replaced_by.is_none()
+            })
+            .cloned())
+    }
+
+    async fn list_active_for_user(
+        &self,
+        realm_id: &Uuid,
+        user_id: &Uuid,
+    ) -> Result<Vec<RefreshToken>> {
+        Ok(self
+            .stored
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|token| {
+                &token.realm_id == realm_id
+                    && &token.user_id == user_id
+                    && token.revoked_at.is_none()
+                    && token.replaced_by.is_none()
+            })
+            .cloned()
+            .collect())
+    }
+
     async fn delete_by_id(&self, id: &Uuid) -> Result<()> {
-        if let Some(mut token) = self.stored.lock().unwrap().get(id).cloned() {
+        if let Some(token) = self.stored.lock().unwrap().get_mut(id) {
             token.revoked_at = Some(Utc::now());
-            self.stored.lock().unwrap().insert(*id, token);
         }
         Ok(())
     }
 
     async fn mark_replaced(&self, old_id: &Uuid, new_id: &Uuid) -> Result<()> {
-        if let Some(mut token) = self.stored.lock().unwrap().get(old_id).cloned() {
+        if let Some(token) = self.stored.lock().unwrap().get_mut(old_id) {
             token.replaced_by = Some(*new_id);
             token.revoked_at = Some(Utc::now());
-            self.stored.lock().unwrap().insert(*old_id, token);
         }
         Ok(())
     }
@@ -659,571 +752,1097 @@ impl SessionRepository for TestSessionRepo {
 }
 
 #[derive(Default)]
-struct TestTokenService {
-    access_tokens: Mutex<Vec<Uuid>>,
-    id_tokens: Mutex<Vec<String>>,
-}
+struct TestProtocolMapperRepo;
 
 #[allow(clippy::unused_async)]
 #[async_trait]
-impl TokenService for TestTokenService {
-    async fn create_access_token(
-        &self,
-        _user: &User,
-        session_id: Uuid,
-        _permissions: &HashSet<String>,
-        _roles: &[String],
-        _groups: &[String],
-    ) -> Result<String> {
-        self.access_tokens.lock().unwrap().push(session_id);
-        Ok("access-token".to_string())
+impl ProtocolMapperRepository for TestProtocolMapperRepo {
+    async fn list_for_client(&self, _client_id: &Uuid) -> Result<Vec<ProtocolMapper>> {
+        Ok(Vec::new())
     }
 
-    async fn create_id_token(
-        &self,
-        _user: &User,
-        client_id: &str,
-        _groups: &[String],
-    ) -> Result<String> {
-        self.id_tokens.lock().unwrap().push(client_id.to_string());
-        Ok("id-token".to_string())
+    async fn find_by_id(&self, _id: &Uuid) -> Result<Option<ProtocolMapper>> {
+        Ok(None)
     }
 
-    async fn validate_access_token(&self, _token: &str) -> Result<AccessTokenClaims> {
-        Err(Error::InvalidCredentials)
+    async fn create(&self, _mapper: &ProtocolMapper) -> Result<()> {
+        Ok(())
     }
 
-    fn get_key_id(&self) -> &str {
-        "kid"
+    async fn update(&self, _mapper: &ProtocolMapper) -> Result<()> {
+        Ok(())
     }
 
-    fn get_jwks(&self) -> Result<serde_json::Value> {
-        Ok(json!({}))
+    async fn delete(&self, _id: &Uuid) -> Result<()> {
+        Ok(())
     }
 }
 
-struct TestRbacRepo;
+#[derive(Default)]
+struct TestUserEmailRepo {
+    emails: Mutex<Vec<UserEmail>>,
+}
 
 #[allow(clippy::unused_async)]
 #[async_trait]
-impl RbacRepository for TestRbacRepo {
-    async fn count_role_stats(&self, _realm_id: &Uuid) -> Result<crate::domain::rbac::RoleStats> {
-        Ok(crate::domain::rbac::RoleStats {
-            total: 0,
-            composite: 0,
-            client: 0,
-        })
+impl UserEmailRepository for TestUserEmailRepo {
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<UserEmail>> {
+        Ok(self
+            .emails
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|email| email.user_id == *user_id)
+            .cloned()
+            .collect())
     }
-    async fn create_role(&self, _role: &Role, _tx: Option<&mut dyn Transaction>) -> Result<()> {
-        Ok(())
+
+    async fn find_by_email(
+        &self,
+        _realm_id: &Uuid,
+        _email_normalized: &str,
+    ) -> Result<Option<UserEmail>> {
+        Ok(None)
     }
 
-    async fn create_group(&self, _group: &Group, _tx: Option<&mut dyn Transaction>) -> Result<()> {
+    async fn find_primary(&self, user_id: &Uuid) -> Result<Option<UserEmail>> {
+        Ok(self
+            .emails
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|email| email.user_id == *user_id && email.is_primary)
+            .cloned())
+    }
+
+    async fn save(&self, email: &UserEmail, _tx: Option<&mut dyn Transaction>) -> Result<()> {
+        self.emails.lock().unwrap().push(email.clone());
         Ok(())
     }
 
-    async fn assign_role_to_group(
+    async fn set_primary(
         &self,
-        _role_id: &Uuid,
-        _group_id: &Uuid,
+        _user_id: &Uuid,
+        _email_id: &Uuid,
         _tx: Option<&mut dyn Transaction>,
     ) -> Result<()> {
         Ok(())
     }
 
-    async fn remove_role_from_group(
+    async fn set_verified(
         &self,
-        _role_id: &Uuid,
-        _group_id: &Uuid,
+        _email_id: &Uuid,
+        _is_verified: bool,
         _tx: Option<&mut dyn Transaction>,
     ) -> Result<()> {
         Ok(())
     }
 
-    async fn assign_user_to_group(
-        &self,
-        _user_id: &Uuid,
-        _group_id: &Uuid,
-        _tx: Option<&mut dyn Transaction>,
-    ) -> Result<()> {
+    async fn delete(&self, _email_id: &Uuid, _tx: Option<&mut dyn Transaction>) -> Result<()> {
         Ok(())
     }
+}
 
-    async fn remove_user_from_group(
+struct TestUserPhoneNumberRepo;
+
+#[allow(clippy::unused_async)]
+#[async_trait]
+impl UserPhoneNumberRepository for TestUserPhoneNumberRepo {
+    async fn find_by_user_id(&self, _user_id: &Uuid) -> Result<Vec<UserPhoneNumber>> {
+        Ok(Vec::new())
+    }
+
+    async fn find_by_phone_number(
         &self,
-        _user_id: &Uuid,
-        _group_id: &Uuid,
+        _realm_id: &Uuid,
+        _phone_number_normalized: &str,
+    ) -> Result<Option<UserPhoneNumber>> {
+        Ok(None)
+    }
+
+    async fn find_primary(&self, _user_id: &Uuid) -> Result<Option<UserPhoneNumber>> {
+        Ok(None)
+    }
+
+    async fn save(
+        &self,
+        _phone_number: &UserPhoneNumber,
         _tx: Option<&mut dyn Transaction>,
     ) -> Result<()> {
         Ok(())
     }
 
-    async fn assign_permission_to_role(
+    async fn set_primary(
         &self,
-        _permission: &Permission,
-        _role_id: &Uuid,
+        _user_id: &Uuid,
+        _phone_number_id: &Uuid,
         _tx: Option<&mut dyn Transaction>,
     ) -> Result<()> {
         Ok(())
     }
 
-    async fn assign_role_to_user(
+    async fn set_verified(
         &self,
-        _user_id: &Uuid,
-        _role_id: &Uuid,
+        _phone_number_id: &Uuid,
+        _is_verified: bool,
         _tx: Option<&mut dyn Transaction>,
     ) -> Result<()> {
         Ok(())
     }
 
-    async fn remove_role_from_user(
+    async fn delete(
         &self,
-        _user_id: &Uuid,
-        _role_id: &Uuid,
+        _phone_number_id: &Uuid,
         _tx: Option<&mut dyn Transaction>,
     ) -> Result<()> {
         Ok(())
     }
+}
 
-    async fn find_role_by_name(&self, _realm_id: &Uuid, _name: &str) -> Result<Option<Role>> {
-        Ok(None)
+#[derive(Default)]
+struct TestTokenService {
+    access_tokens: Mutex<Vec<Uuid>>,
+    id_tokens: Mutex<Vec<String>>,
+    client_tokens: Mutex<Vec<String>>,
+    /// Claims every presented access token validates to; `None` rejects all.
+    access_claims: Mutex<Option<AccessTokenClaims>>,
+    /// (actor, audience, scope) of each exchanged token.
+    exchanged: Mutex<Vec<(String, String, Option<String>)>>,
+}
+
+impl TestTokenService {
+    fn set_access_claims(&self, claims: AccessTokenClaims) {
+        *self.access_claims.lock().unwrap() = Some(claims);
     }
+}
 
-    async fn find_group_by_name(&self, _realm_id: &Uuid, _name: &str) -> Result<Option<Group>> {
-        Ok(None)
+#[allow(clippy::unused_async)]
+#[async_trait]
+impl TokenService for TestTokenService {
+    async fn create_access_token(
+        &self,
+        _user: &User,
+        session_id: Uuid,
+        _client_id: Option<&str>,
+        _permissions: &HashSet<String>,
+        _roles: &[String],
+        _groups: &[String],
+        _scope: Option<&str>,
+        _extra_claims: &Map<String, Value>,
+        _actor: Option<&ActorClaim>,
+    ) -> Result<String> {
+        self.access_tokens.lock().unwrap().push(session_id);
+        Ok("access-token".to_string())
     }
 
-    async fn find_group_by_id(&self, _group_id: &Uuid) -> Result<Option<Group>> {
-        Ok(None)
+    async fn create_id_token(
+        &self,
+        _user: &User,
+        client_id: &str,
+        _session_id: Uuid,
+        _groups: &[String],
+        _extra_claims: &Map<String, Value>,
+    ) -> Result<String> {
+        self.id_tokens.lock().unwrap().push(client_id.to_string());
+        Ok("id-token".to_string())
     }
 
-    async fn list_roles(&self, _realm_id: &Uuid, _req: &PageRequest) -> Result<PageResponse<Role>> {
-        Ok(empty_page())
+    async fn validate_id_token_hint(&self, _realm_id: Uuid, _token: &str) -> Result<IdTokenClaims> {
+        Err(Error::InvalidCredentials)
     }
 
-    async fn list_client_roles(
+    async fn create_logout_token(
         &self,
-        _realm_id: &Uuid,
-        _client_id: &Uuid,
-        _req: &PageRequest,
-    ) -> Result<PageResponse<Role>> {
-        Ok(empty_page())
+        _realm_id: Uuid,
+        _client_id: &str,
+        _subject: Uuid,
+        _session_id: Option<Uuid>,
+    ) -> Result<String> {
+        Ok("logout-token".to_string())
     }
 
-    async fn find_role_by_id(&self, _role_id: &Uuid) -> Result<Option<Role>> {
-        Ok(None)
+    async fn create_client_access_token(
+        &self,
+        client: &OidcClient,
+        _permissions: &HashSet<String>,
+        _roles: &[String],
+        _scope: Option<&str>,
+    ) -> Result<String> {
+        self.client_tokens
+            .lock()
+            .unwrap()
+            .push(client.client_id.clone());
+        Ok("client-access-token".to_string())
     }
 
-    async fn list_groups(
+    async fn create_exchanged_access_token(
         &self,
-        _realm_id: &Uuid,
-        _req: &PageRequest,
-    ) -> Result<PageResponse<Group>> {
-        Ok(empty_page())
+        _realm_id: Uuid,
+        _subject: &AccessTokenClaims,
+        actor: &str,
+        audience: &str,
+        scope: Option<&str>,
+    ) -> Result<String> {
+        self.exchanged.lock().unwrap().push((
+            actor.to_string(),
+            audience.to_string(),
+            scope.map(str::to_string),
+        ));
+        Ok("exchanged-token".to_string())
     }
 
-    async fn list_group_roots(
+    async fn validate_access_token(
         &self,
-        _realm_id: &Uuid,
-        _req: &PageRequest,
-    ) -> Result<PageResponse<GroupTreeRow>> {
-        Ok(empty_page())
+        _realm_id: Uuid,
+        _token: &str,
+    ) -> Result<AccessTokenClaims> {
+        self.access_claims
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or(Error::InvalidCredentials)
     }
 
-    async fn list_group_children(
-        &self,
-        _realm_id: &Uuid,
-        _parent_id: &Uuid,
-        _req: &PageRequest,
-    ) -> Result<PageResponse<GroupTreeRow>> {
-        Ok(empty_page())
+    fn unverified_session_id(&self, _token: &str) -> Option<Uuid> {
+        self.access_claims
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|claims| claims.sid)
     }
 
-    async fn list_role_members(
-        &self,
-        _realm_id: &Uuid,
-        _role_id: &Uuid,
-        _filter: RoleMemberFilter,
-        _req: &PageRequest,
-    ) -> Result<PageResponse<RoleMemberRow>> {
-        Ok(empty_page())
+    fn access_token_ttl_secs(&self) -> i64 {
+        900
     }
 
-    async fn list_group_members(
-        &self,
-        _realm_id: &Uuid,
-        _group_id: &Uuid,
-        _filter: GroupMemberFilter,
-        _req: &PageRequest,
-    ) -> Result<PageResponse<GroupMemberRow>> {
-        Ok(empty_page())
+    async fn get_jwks(&self, _realm_id: &Uuid) -> Result<serde_json::Value> {
+        Ok(json!({}))
     }
+}
 
-    async fn list_group_roles(
-        &self,
-        _realm_id: &Uuid,
-        _group_id: &Uuid,
-        _filter: GroupRoleFilter,
-        _req: &PageRequest,
-    ) -> Result<PageResponse<GroupRoleRow>> {
-        Ok(empty_page())
+/// The executor is only reached by password grants, which never suspend on
+/// an async action in these tests.
+struct TestActionRepo;
+
+#[async_trait]
+impl AuthSessionActionRepository for TestActionRepo {
+    async fn create(&self, _action: &AuthSessionAction) -> Result<()> {
+        Ok(())
     }
 
-    async fn list_user_roles(
-        &self,
-        _realm_id: &Uuid,
-        _user_id: &Uuid,
-        _filter: UserRoleFilter,
-        _req: &PageRequest,
-    ) -> Result<PageResponse<UserRoleRow>> {
-        Ok(empty_page())
+    async fn find_by_id(&self, _id: &Uuid) -> Result<Option<AuthSessionAction>> {
+        Ok(None)
     }
 
-    async fn list_role_composites(
-        &self,
-        _realm_id: &Uuid,
-        _role_id: &Uuid,
-        _client_id: &Option<Uuid>,
-        _filter: RoleCompositeFilter,
-        _req: &PageRequest,
-    ) -> Result<PageResponse<RoleCompositeRow>> {
-        Ok(empty_page())
+    async fn find_by_token_hash(&self, _token_hash: &str) -> Result<Option<AuthSessionAction>> {
+        Ok(None)
     }
 
-    async fn list_group_ids_by_parent(
-        &self,
-        _realm_id: &Uuid,
-        _parent_id: Option<&Uuid>,
-    ) -> Result<Vec<Uuid>> {
-        Ok(Vec::new())
+    async fn mark_consumed(&self, _id: &Uuid) -> Result<()> {
+        Ok(())
     }
 
-    async fn list_group_subtree_ids(&self, _realm_id: &Uuid, _root_id: &Uuid) -> Result<Vec<Uuid>> {
-        Ok(Vec::new())
+    async fn delete_expired_before(&self, _cutoff: DateTime<Utc>) -> Result<u64> {
+        Ok(0)
     }
+}
 
-    async fn set_group_orders(
-        &self,
-        _realm_id: &Uuid,
-        _parent_id: Option<&Uuid>,
-        _ordered_ids: &[Uuid],
-        _tx: Option<&mut dyn Transaction>,
-    ) -> Result<()> {
-        Ok(())
-    }
+#[derive(Default)]
+struct TestDeviceRepo {
+    authorizations: Mutex<HashMap<String, DeviceAuthorization>>,
+}
 
-    async fn is_group_descendant(
-        &self,
-        _realm_id: &Uuid,
-        _ancestor_id: &Uuid,
-        _candidate_id: &Uuid,
-    ) -> Result<bool> {
-        Ok(false)
+impl TestDeviceRepo {
+    fn get(&self, device_code: &str) -> Option<DeviceAuthorization> {
+        self.authorizations
+            .lock()
+            .unwrap()
+            .get(device_code)
+            .cloned()
     }
 
-    async fn get_next_group_sort_order(
-        &self,
-        _realm_id: &Uuid,
-        _parent_id: Option<&Uuid>,
-    ) -> Result<i64> {
-        Ok(1)
+    fn update(&self, device_code: &str, update: impl FnOnce(&mut DeviceAuthorization)) {
+        if let Some(authorization) = self.authorizations.lock().unwrap().get_mut(device_code) {
+            update(authorization);
+        }
     }
+}
 
-    async fn find_user_ids_in_group(&self, _group_id: &Uuid) -> Result<Vec<Uuid>> {
-        Ok(Vec::new())
+#[allow(clippy::unused_async)]
+#[async_trait]
+impl DeviceAuthorizationRepository for TestDeviceRepo {
+    async fn create(&self, authorization: &DeviceAuthorization) -> Result<()> {
+        self.authorizations
+            .lock()
+            .unwrap()
+            .insert(authorization.device_code.clone(), authorization.clone());
+        Ok(())
     }
 
-    async fn find_user_ids_in_groups(&self, _group_ids: &[Uuid]) -> Result<Vec<Uuid>> {
-        Ok(Vec::new())
+    async fn find_by_device_code(&self, device_code: &str) -> Result<Option<DeviceAuthorization>> {
+        Ok(self.get(device_code))
     }
 
-    async fn find_role_ids_for_group(&self, _group_id: &Uuid) -> Result<Vec<Uuid>> {
-        Ok(Vec::new())
+    async fn find_by_user_code(
+        &self,
+        realm_id: &Uuid,
+        user_code: &str,
+    ) -> Result<Option<DeviceAuthorization>> {
+        Ok(self
+            .authorizations
+            .lock()
+            .unwrap()
+            .values()
+            .find(|auth| auth.realm_id == *realm_id && auth.user_code == user_code)
+            .cloned())
     }
 
-    async fn find_effective_role_ids_for_group(&self, _group_id: &Uuid) -> Result<Vec<Uuid>> {
-        Ok(Vec::new())
+    async fn resolve(
+        &self,
+        device_code: &str,
+        status: DeviceAuthorizationStatus,
+        user_id: Option<Uuid>,
+        now: DateTime<Utc>,
+    ) -> Result<bool> {
+        let mut authorizations = self.authorizations.lock().unwrap();
+        match authorizations.get_mut(device_code) {
+            Some(auth)
+                if auth.status == DeviceAuthorizationStatus::Pending && auth.expires_at > now =>
+            {
+                auth.status = status;
+                auth.user_id = user_id;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
     }
 
-    async fn count_user_ids_in_groups(&self, _group_ids: &[Uuid]) -> Result<i64> {
-        Ok(0)
+    async fn record_poll(
+        &self,
+        device_code: &str,
+        polled_at: DateTime<Utc>,
+        interval_secs: i64,
+    ) -> Result<()> {
+        self.update(device_code, |auth| {
+            auth.last_polled_at = Some(polled_at);
+            auth.interval_secs = interval_secs;
+        });
+        Ok(())
     }
 
-    async fn count_role_ids_in_groups(&self, _group_ids: &[Uuid]) -> Result<i64> {
-        Ok(0)
+    async fn consume(&self, device_code: &str) -> Result<bool> {
+        let mut authorizations = self.authorizations.lock().unwrap();
+        if authorizations
+            .get(device_code)
+            .is_some_and(|auth| auth.status == DeviceAuthorizationStatus::Approved)
+        {
+            authorizations.remove(device_code);
+            return Ok(true);
+        }
+        Ok(false)
     }
 
-    async fn count_group_ids_for_role(&self, _role_id: &Uuid) -> Result<i64> {
+    async fn delete_expired_before(&self, _cutoff: DateTime<Utc>, _batch_size: i64) -> Result<u64> {
         Ok(0)
     }
+}
 
-    async fn count_parent_role_ids_for_role(&self, _role_id: &Uuid) -> Result<i64> {
-        Ok(0)
-    }
+#[derive(Default)]
+struct TestParRepo {
+    requests: Mutex<HashMap<String, PushedAuthorizationRequest>>,
+}
 
-    async fn find_direct_role_ids_for_user(&self, _user_id: &Uuid) -> Result<Vec<Uuid>> {
-        Ok(Vec::new())
+#[allow(clippy::unused_async)]
+#[async_trait]
+impl PushedAuthorizationRequestRepository for TestParRepo {
+    async fn create(&self, request: &PushedAuthorizationRequest) -> Result<()> {
+        self.requests
+            .lock()
+            .unwrap()
+            .insert(request.request_uri.clone(), request.clone());
+        Ok(())
     }
 
-    async fn find_effective_role_ids_for_user(&self, _user_id: &Uuid) -> Result<Vec<Uuid>> {
-        Ok(Vec::new())
+    async fn consume(
+        &self,
+        realm_id: &Uuid,
+        client_id: &str,
+        request_uri: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<PushedAuthorizationRequest>> {
+        let mut requests = self.requests.lock().unwrap();
+        if !requests
+            .get(request_uri)
+            .is_some_and(|request| request.realm_id == *realm_id && request.client_id == client_id)
+        {
+            return Ok(None);
+        }
+        Ok(requests
+            .remove(request_uri)
+            .filter(|request| request.expires_at > now))
     }
 
-    async fn find_role_ids_for_user(&self, _user_id: &Uuid) -> Result<Vec<Uuid>> {
-        Ok(Vec::new())
+    async fn delete_expired_before(&self, _cutoff: DateTime<Utc>, _batch_size: i64) -> Result<u64> {
+        Ok(0)
     }
+}
 
-    async fn find_permissions_for_roles(&self, _role_ids: &[Uuid]) -> Result<HashSet<Permission>> {
-        Ok(HashSet::new())
-    }
+#[derive(Default)]
+struct TestAuditRepo {
+    events: Mutex<Vec<AuditEvent>>,
+}
 
-    async fn find_user_ids_for_role(&self, _role_id: &Uuid) -> Result<Vec<Uuid>> {
-        Ok(Vec::new())
+impl TestAuditRepo {
+    fn actions(&self) -> Vec<String> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|event| event.action.clone())
+            .collect()
     }
+}
 
-    async fn find_direct_user_ids_for_role(&self, _role_id: &Uuid) -> Result<Vec<Uuid>> {
-        Ok(Vec::new())
+#[allow(clippy::unused_async)]
+#[async_trait]
+impl AuditRepository for TestAuditRepo {
+    async fn insert(&self, event: &AuditEvent) -> Result<()> {
+        self.events.lock().unwrap().push(event.clone());
+        Ok(())
     }
 
-    async fn list_role_composite_ids(&self, _role_id: &Uuid) -> Result<Vec<Uuid>> {
+    async fn list_recent(&self, _realm_id: &Uuid, _limit: usize) -> Result<Vec<AuditEvent>> {
         Ok(Vec::new())
     }
 
-    async fn list_effective_role_composite_ids(&self, _role_id: &Uuid) -> Result<Vec<Uuid>> {
+    async fn count_by_actions_since(
+        &self,
+        _realm_id: &Uuid,
+        _actions: &[&str],
+        _since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<AuditActionCount>> {
         Ok(Vec::new())
     }
 
-    async fn get_effective_permissions_for_user(&self, _user_id: &Uuid) -> Result<HashSet<String>> {
-        Ok(HashSet::new())
+    async fn count_by_target_and_actions_since(
+        &self,
+        _realm_id: &Uuid,
+        _target_type: &str,
+        _target_id: &str,
+        _actions: &[&str],
+        _since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<AuditActionCount>> {
+        Ok(Vec::new())
     }
 
-    async fn find_role_names_for_user(&self, _user_id: &Uuid) -> Result<Vec<String>> {
+    async fn list_recent_by_actions(
+        &self,
+        _realm_id: &Uuid,
+        _actions: &[&str],
+        _limit: usize,
+    ) -> Result<Vec<AuditEvent>> {
         Ok(Vec::new())
     }
 
-    async fn find_group_names_for_user(&self, _user_id: &Uuid) -> Result<Vec<String>> {
+    async fn list_recent_by_target_and_actions(
+        &self,
+        _realm_id: &Uuid,
+        _target_type: &str,
+        _target_id: &str,
+        _actions: &[&str],
+        _limit: usize,
+    ) -> Result<Vec<AuditEvent>> {
         Ok(Vec::new())
     }
+}
 
-    async fn delete_role(&self, _role_id: &Uuid, _tx: Option<&mut dyn Transaction>) -> Result<()> {
-        Ok(())
-    }
+struct TestRbacRepo;
 
-    async fn delete_groups(
-        &self,
-        _group_ids: &[Uuid],
-        _tx: Option<&mut dyn Transaction>,
-    ) -> Result<()> {
-        Ok(())
+#[allow(clippy::unused_async)]
+#[async_trait]
+impl RbacRepository for TestRbacRepo {
+    async fn count_role_stats(&self, _realm_id: &Uuid) -> Result<crate::domain::rbac::RoleStats> {
+        Ok(crate::domain::rbac::RoleStats {
+            total: 0,
+            composite: 0,
+            client: 0,
+        })
     }
-
-    async fn update_role(&self, _role: &Role, _tx: Option<&mut dyn Transaction>) -> Result<()> {
+    async fn create_role(&self, _role: &Role, _tx: Option<&mut dyn Transaction>) -> Result<()> {
         Ok(())
     }
 
-    async fn update_group(&self, _group: &Group, _tx: Option<&mut dyn Transaction>) -> Result<()> {
+    async fn create_group(&self, _group: &Group, _tx: Option<&mut dyn Transaction>) -> Result<()> {
         Ok(())
     }
 
-    async fn get_permissions_for_role(&self, _role_id: &Uuid) -> Result<Vec<String>> {
-        Ok(Vec::new())
-    }
-
-    async fn remove_permission(
+    async fn assign_role_to_group(
         &self,
         _role_id: &Uuid,
-        _permission: &str,
+        _group_id: &Uuid,
         _tx: Option<&mut dyn Transaction>,
     ) -> Result<()> {
         Ok(())
     }
 
-    async fn bulk_update_permissions(
+    async fn remove_role_from_group(
         &self,
         _role_id: &Uuid,
-        _permissions: Vec<String>,
-        _action: &str,
+        _group_id: &Uuid,
         _tx: Option<&mut dyn Transaction>,
     ) -> Result<()> {
         Ok(())
     }
 
-    async fn assign_composite_role(
+    async fn assign_user_to_group(
         &self,
-        _parent_role_id: &Uuid,
-        _child_role_id: &Uuid,
+        _user_id: &Uuid,
+        _group_id: &Uuid,
         _tx: Option<&mut dyn Transaction>,
     ) -> Result<()> {
         Ok(())
     }
 
-    async fn remove_composite_role(
+    async fn remove_user_from_group(
         &self,
-        _parent_role_id: &Uuid,
-        _child_role_id: &Uuid,
+        _user_id: &Uuid,
+        _group_id: &Uuid,
         _tx: Option<&mut dyn Transaction>,
     ) -> Result<()> {
         Ok(())
     }
 
-    async fn is_role_descendant(&self, _ancestor_id: &Uuid, _candidate_id: &Uuid) -> Result<bool> {
-        Ok(false)
-    }
-
-    async fn create_custom_permission(
+    async fn assign_permission_to_role(
         &self,
-        _permission: &CustomPermission,
+        _permission: &Permission,
+        _role_id: &Uuid,
         _tx: Option<&mut dyn Transaction>,
     ) -> Result<()> {
         Ok(())
     }
 
-    async fn update_custom_permission(
+    async fn assign_role_to_user(
         &self,
-        _permission: &CustomPermission,
+        _user_id: &Uuid,
+        _role_id: &Uuid,
         _tx: Option<&mut dyn Transaction>,
     ) -> Result<()> {
         Ok(())
     }
 
-    async fn delete_custom_permission(
+    async fn remove_role_from_user(
         &self,
-        _permission_id: &Uuid,
+        _user_id: &Uuid,
+        _role_id: &Uuid,
         _tx: Option<&mut dyn Transaction>,
     ) -> Result<()> {
         Ok(())
     }
 
-    async fn find_custom_permission_by_key(
-        &self,
-        _realm_id: &Uuid,
-        _client_id: Option<&Uuid>,
-        _permission: &str,
-    ) -> Result<Option<CustomPermission>> {
+    async fn find_role_by_name(&self, _realm_id: &Uuid, _name: &str) -> Result<Option<Role>> {
         Ok(None)
     }
 
-    async fn find_custom_permission_by_id(
-        &self,
-        _realm_id: &Uuid,
-        _permission_id: &Uuid,
-    ) -> Result<Option<CustomPermission>> {
+    async fn find_group_by_name(&self, _realm_id: &Uuid, _name: &str) -> Result<Option<Group>> {
         Ok(None)
     }
 
-    async fn list_custom_permissions(
+    async fn find_group_by_id(&self, _group_id: &Uuid) -> Result<Option<Group>> {
+        Ok(None)
+    }
+
+    async fn find_client_realm_id(&self, _client_id: &Uuid) -> Result<Option<Uuid>> {
+        Ok(None)
+    }
+
+    async fn is_system_realm(&self, _realm_id: &Uuid) -> Result<bool> {
+        Ok(false)
+    }
+
+    async fn list_roles(&self, _realm_id: &Uuid, _req: &PageRequest) -> Result<PageResponse<Role>> {
+        Ok(empty_page())
+    }
+
+    async fn list_client_roles(
         &self,
         _realm_id: &Uuid,
-        _client_id: Option<&Uuid>,
-    ) -> Result<Vec<CustomPermission>> {
-        Ok(Vec::new())
+        _client_id: &Uuid,
+        _req: &PageRequest,
+    ) -> Result<PageResponse<Role>> {
+        Ok(empty_page())
     }
 
-    async fn list_roles_for_permission_key(
+    async fn find_roles_for_client(
         &self,
         _realm_id: &Uuid,
-        _permission: &str,
-    ) -> Result<Vec<CustomPermissionRoleImpact>> {
+        _client_id: &Uuid,
+    ) -> Result<Vec<Role>> {
         Ok(Vec::new())
     }
 
-    async fn remove_role_permissions_by_key(
-        &self,
-        _permission: &str,
-        _tx: Option<&mut dyn Transaction>,
-    ) -> Result<()> {
-        Ok(())
+    async fn find_role_by_id(&self, _role_id: &Uuid) -> Result<Option<Role>> {
+        Ok(None)
     }
-}
 
-#[derive(Default)]
-struct TestOutboxRepo;
+    async fn list_groups(
+        &self,
+        _realm_id: &Uuid,
+        _req: &PageRequest,
+    ) -> Result<PageResponse<Group>> {
+        Ok(empty_page())
+    }
 
-#[async_trait]
-impl OutboxRepository for TestOutboxRepo {
-    async fn insert(
+    async fn list_group_roots(
         &self,
-        _envelope: &EventEnvelope,
+        _realm_id: &Uuid,
+        _req: &PageRequest,
+    ) -> Result<PageResponse<GroupTreeRow>> {
+        Ok(empty_page())
+    }
+
+    async fn list_group_children(
+        &self,
+        _realm_id: &Uuid,
+        _parent_id: &Uuid,
+        _req: &PageRequest,
+    ) -> Result<PageResponse<GroupTreeRow>> {
+        Ok(empty_page())
+    }
+
+    async fn list_role_members(
+        &self,
+        _realm_id: &Uuid,
+        _role_id: &Uuid,
+        _filter: RoleMemberFilter,
+        _req: &PageRequest,
+    ) -> Result<PageResponse<RoleMemberRow>> {
+        Ok(empty_page())
+    }
+
+    async fn list_group_members(
+        &self,
+        _realm_id: &Uuid,
+        _group_id: &Uuid,
+        _filter: GroupMemberFilter,
+        _req: &PageRequest,
+    ) -> Result<PageResponse<GroupMemberRow>> {
+        Ok(empty_page())
+    }
+
+    async fn list_group_roles(
+        &self,
+        _realm_id: &Uuid,
+        _group_id: &Uuid,
+        _filter: GroupRoleFilter,
+        _req: &PageRequest,
+    ) -> Result<PageResponse<GroupRoleRow>> {
+        Ok(empty_page())
+    }
+
+    async fn list_user_roles(
+        &self,
+        _realm_id: &Uuid,
+        _user_id: &Uuid,
+        _filter: UserRoleFilter,
+        _req: &PageRequest,
+    ) -> Result<PageResponse<UserRoleRow>> {
+        Ok(empty_page())
+    }
+
+    async fn list_role_composites(
+        &self,
+        _realm_id: &Uuid,
+        _role_id: &Uuid,
+        _client_id: &Option<Uuid>,
+        _filter: RoleCompositeFilter,
+        _req: &PageRequest,
+    ) -> Result<PageResponse<RoleCompositeRow>> {
+        Ok(empty_page())
+    }
+
+    async fn list_group_ids_by_parent(
+        &self,
+        _realm_id: &Uuid,
+        _parent_id: Option<&Uuid>,
+    ) -> Result<Vec<Uuid>> {
+        Ok(Vec::new())
+    }
+
+    async fn list_group_subtree_ids(&self, _realm_id: &Uuid, _root_id: &Uuid) -> Result<Vec<Uuid>> {
+        Ok(Vec::new())
+    }
+
+    async fn set_group_orders(
+        &self,
+        _realm_id: &Uuid,
+        _parent_id: Option<&Uuid>,
+        _ordered_ids: &[Uuid],
         _tx: Option<&mut dyn Transaction>,
     ) -> Result<()> {
         Ok(())
     }
-}
 
-struct TestTx;
+    async fn is_group_descendant(
+        &self,
+        _realm_id: &Uuid,
+        _ancestor_id: &Uuid,
+        _candidate_id: &Uuid,
+    ) -> Result<bool> {
+        Ok(false)
+    }
 
-impl Transaction for TestTx {
-    fn as_any(&mut self) -> &mut dyn Any {
-        self
+    async fn is_user_in_group_subtree(
+        &self,
+        _realm_id: &Uuid,
+        _root_id: &Uuid,
+        _user_id: &Uuid,
+    ) -> Result<bool> {
+        Ok(false)
     }
 
-    fn into_any(self: Box<Self>) -> Box<dyn Any> {
-        self
+    async fn get_next_group_sort_order(
+        &self,
+        _realm_id: &Uuid,
+        _parent_id: Option<&Uuid>,
+    ) -> Result<i64> {
+        Ok(1)
     }
-}
 
-#[derive(Default)]
-struct TestTxManager;
+    async fn find_user_ids_in_group(&self, _group_id: &Uuid) -> Result<Vec<Uuid>> {
+        Ok(Vec::new())
+    }
 
-#[async_trait]
-impl TransactionManager for TestTxManager {
-    async fn begin(&self) -> Result<Box<dyn Transaction>> {
-        Ok(Box::new(TestTx))
+    async fn find_user_ids_in_groups(&self, _group_ids: &[Uuid]) -> Result<Vec<Uuid>> {
+        Ok(Vec::new())
     }
 
-    async fn commit(&self, _tx: Box<dyn Transaction>) -> Result<()> {
+    async fn find_role_ids_for_group(&self, _group_id: &Uuid) -> Result<Vec<Uuid>> {
+        Ok(Vec::new())
+    }
+
+    async fn find_effective_role_ids_for_group(&self, _group_id: &Uuid) -> Result<Vec<Uuid>> {
+        Ok(Vec::new())
+    }
+
+    async fn count_user_ids_in_groups(&self, _group_ids: &[Uuid]) -> Result<i64> {
+        Ok(0)
+    }
+
+    async fn count_role_ids_in_groups(&self, _group_ids: &[Uuid]) -> Result<i64> {
+        Ok(0)
+    }
+
+    async fn count_group_ids_for_role(&self, _role_id: &Uuid) -> Result<i64> {
+        Ok(0)
+    }
+
+    async fn count_parent_role_ids_for_role(&self, _role_id: &Uuid) -> Result<i64> {
+        Ok(0)
+    }
+
+    async fn find_direct_role_ids_for_user(&self, _user_id: &Uuid) -> Result<Vec<Uuid>> {
+        Ok(Vec::new())
+    }
+
+    async fn find_effective_role_ids_for_user(&self, _user_id: &Uuid) -> Result<Vec<Uuid>> {
+        Ok(Vec::new())
+    }
+
+    async fn find_role_ids_for_user(&self, _user_id: &Uuid) -> Result<Vec<Uuid>> {
+        Ok(Vec::new())
+    }
+
+    async fn find_permissions_for_roles(&self, _role_ids: &[Uuid]) -> Result<HashSet<Permission>> {
+        Ok(HashSet::new())
+    }
+
+    async fn find_user_ids_for_role(&self, _role_id: &Uuid) -> Result<Vec<Uuid>> {
+        Ok(Vec::new())
+    }
+
+    async fn find_direct_user_ids_for_role(&self, _role_id: &Uuid) -> Result<Vec<Uuid>> {
+        Ok(Vec::new())
+    }
+
+    async fn list_role_composite_ids(&self, _role_id: &Uuid) -> Result<Vec<Uuid>> {
+        Ok(Vec::new())
+    }
+
+    async fn list_effective_role_composite_ids(&self, _role_id: &Uuid) -> Result<Vec<Uuid>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_effective_permissions_for_user(&self, _user_id: &Uuid) -> Result<HashSet<String>> {
+        Ok(HashSet::new())
+    }
+
+    async fn find_role_names_for_user(&self, _user_id: &Uuid) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    async fn find_group_names_for_user(&self, _user_id: &Uuid) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    async fn find_group_paths_for_user(&self, _user_id: &Uuid) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    async fn delete_role(&self, _role_id: &Uuid, _tx: Option<&mut dyn Transaction>) -> Result<()> {
         Ok(())
     }
 
-    async fn rollback(&self, _tx: Box<dyn Transaction>) -> Result<()> {
+    async fn delete_groups(
+        &self,
+        _group_ids: &[Uuid],
+        _tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
         Ok(())
     }
-}
 
-fn build_auth_service(
-    user_repo: Arc<TestUserRepo>,
-    realm_repo: Arc<TestRealmRepo>,
-    session_repo: Arc<TestSessionRepo>,
-    token_service: Arc<TestTokenService>,
-) -> Arc<AuthService> {
-    let rbac_repo = Arc::new(TestRbacRepo);
-    let cache = Arc::new(crate::adapters::cache::moka_cache::MokaCacheService::default());
-    let event_bus = Arc::new(crate::adapters::eventing::in_memory_bus::InMemoryEventBus::default());
-    let outbox_repo = Arc::new(TestOutboxRepo);
-    let tx_manager = Arc::new(TestTxManager);
-    let rbac_service = Arc::new(RbacService::new(
-        rbac_repo,
-        cache,
-        event_bus,
-        outbox_repo,
-        tx_manager,
-    ));
-    let settings = AuthConfig {
-        jwt_secret: "secret".to_string(),
-        jwt_key_id: "kid".to_string(),
-        issuer: "http://issuer".to_string(),
-        access_token_ttl_secs: 60,
-        refresh_token_ttl_secs: 120,
-        pkce_required_public_clients: true,
-        lockout_threshold: 5,
-        lockout_duration_secs: 900,
-        refresh_token_cleanup_interval_secs: 3600,
-        refresh_token_retention_secs: 0,
-        passkey_challenge_cleanup_interval_secs: 300,
-        passkey_challenge_cleanup_batch_size: 500,
-        oauth_broker_state_cleanup_interval_secs: 300,
-        oauth_broker_state_cleanup_batch_size: 500,
-        single_session_per_client: false,
-    };
+    async fn update_role(&self, _role: &Role, _tx: Option<&mut dyn Transaction>) -> Result<()> {
+        Ok(())
+    }
 
-    Arc::new(AuthService::new(
-        user_repo,
-        realm_repo,
-        session_repo,
-        token_service,
-        rbac_service,
-        settings,
+    async fn update_group(&self, _group: &Group, _tx: Option<&mut dyn Transaction>) -> Result<()> {
+        Ok(())
+    }
+
+    async fn get_permissions_for_role(&self, _role_id: &Uuid) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    async fn remove_permission(
+        &self,
+        _role_id: &Uuid,
+        _permission: &str,
+        _tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn bulk_update_permissions(
+        &self,
+        _role_id: &Uuid,
+        _permissions: Vec<String>,
+        _action: &str,
+        _tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn assign_composite_role(
+        &self,
+        _parent_role_id: &Uuid,
+        _child_role_id: &Uuid,
+        _tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn remove_composite_role(
+        &self,
+        _parent_role_id: &Uuid,
+        _child_role_id: &Uuid,
+        _tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn is_role_descendant(&self, _ancestor_id: &Uuid, _candidate_id: &Uuid) -> Result<bool> {
+        Ok(false)
+    }
+
+    async fn create_custom_permission(
+        &self,
+        _permission: &CustomPermission,
+        _tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn update_custom_permission(
+        &self,
+        _permission: &CustomPermission,
+        _tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete_custom_permission(
+        &self,
+        _permission_id: &Uuid,
+        _tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn find_custom_permission_by_key(
+        &self,
+        _realm_id: &Uuid,
+        _client_id: Option<&Uuid>,
+        _permission: &str,
+    ) -> Result<Option<CustomPermission>> {
+        Ok(None)
+    }
+
+    async fn find_custom_permission_by_id(
+        &self,
+        _realm_id: &Uuid,
+        _permission_id: &Uuid,
+    ) -> Result<Option<CustomPermission>> {
+        Ok(None)
+    }
+
+    async fn list_custom_permissions(
+        &self,
+        _realm_id: &Uuid,
+        _client_id: Option<&Uuid>,
+    ) -> Result<Vec<CustomPermission>> {
+        Ok(Vec::new())
+    }
+
+    async fn list_roles_for_permission_key(
+        &self,
+        _realm_id: &Uuid,
+        _permission: &str,
+    ) -> Result<Vec<CustomPermissionRoleImpact>> {
+        Ok(Vec::new())
+    }
+
+    async fn remove_role_permissions_by_key(
+        &self,
+        _permission: &str,
+        _tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct TestOutboxRepo;
+
+#[async_trait]
+impl OutboxRepository for TestOutboxRepo {
+    async fn insert(
+        &self,
+        _envelope: &EventEnvelope,
+        _tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct TestTx;
+
+impl Transaction for TestTx {
+    fn as_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+#[derive(Default)]
+struct TestTxManager;
+
+#[async_trait]
+impl TransactionManager for TestTxManager {
+    async fn begin(&self) -> Result<Box<dyn Transaction>> {
+        Ok(Box::new(TestTx))
+    }
+
+    async fn commit(&self, _tx: Box<dyn Transaction>) -> Result<()> {
+        Ok(())
+    }
+
+    async fn rollback(&self, _tx: Box<dyn Transaction>) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn build_rbac_service() -> Arc<RbacService> {
+    let rbac_repo = Arc::new(TestRbacRepo);
+    let cache = Arc::new(crate::adapters::cache::moka_cache::MokaCacheService::default());
+    let event_bus = Arc::new(crate::adapters::eventing::in_memory_bus::InMemoryEventBus::default());
+    let outbox_repo = Arc::new(TestOutboxRepo);
+    let tx_manager = Arc::new(TestTxManager);
+    Arc::new(RbacService::new(
+        rbac_repo,
+        cache,
+        event_bus,
+        outbox_repo,
+        tx_manager,
+    ))
+}
+
+fn build_claims_service(oidc_repo: Arc<TestOidcRepo>) -> Arc<ClaimsService> {
+    Arc::new(ClaimsService::new(
+        oidc_repo,
+        Arc::new(TestProtocolMapperRepo),
+        Arc::new(TestUserEmailRepo::default()),
+        Arc::new(TestUserPhoneNumberRepo),
+        Arc::new(TestRbacRepo),
+    ))
+}
+
+fn build_auth_service(
+    oidc_repo: Arc<TestOidcRepo>,
+    user_repo: Arc<TestUserRepo>,
+    realm_repo: Arc<TestRealmRepo>,
+    session_repo: Arc<TestSessionRepo>,
+    token_service: Arc<TestTokenService>,
+    claims_service: Arc<ClaimsService>,
+) -> Arc<AuthService> {
+    let rbac_service = build_rbac_service();
+    let settings = AuthConfig {
+        jwt_secret: "secret".to_string(),
+        issuer: "http://issuer".to_string(),
+        access_token_ttl_secs: 60,
+        refresh_token_ttl_secs: 120,
+        pkce_required_public_clients: true,
+        lockout_threshold: 5,
+        lockout_duration_secs: 900,
+        refresh_token_cleanup_interval_secs: 3600,
+        refresh_token_retention_secs: 0,
+        passkey_challenge_cleanup_interval_secs: 300,
+        passkey_challenge_cleanup_batch_size: 500,
+        oauth_broker_state_cleanup_interval_secs: 300,
+        oauth_broker_state_cleanup_batch_size: 500,
+        device_code_cleanup_interval_secs: 300,
+        device_code_cleanup_batch_size: 500,
+        par_request_cleanup_interval_secs: 300,
+        par_request_cleanup_batch_size: 500,
+        signing_key_rotation_interval_secs: 0,
+        signing_key_retention_secs: 0,
+        signing_key_rotation_check_interval_secs: 0,
+        user_federation_sync_check_interval_secs: 0,
+        single_session_per_client: false,
+        impersonation_session_ttl_secs: 900,
+    };
+
+    let event_bus = Arc::new(crate::adapters::eventing::in_memory_bus::InMemoryEventBus::default());
+    let logout_service = Arc::new(LogoutService::new(
+        session_repo.clone(),
+        oidc_repo,
+        Arc::new(TestOutboxRepo),
+        event_bus.clone(),
+        settings.issuer.clone(),
+    ));
+
+    Arc::new(AuthService::new(
+        user_repo,
+        realm_repo,
+        session_repo,
+        token_service,
+        rbac_service,
+        settings,
         crate::config::SecurityConfig::default(),
+        logout_service,
+        claims_service,
+        event_bus,
     ))
 }
 
@@ -1236,134 +1855,1034 @@ fn build_service(
     session_repo: Arc<TestSessionRepo>,
     token_service: Arc<TestTokenService>,
 ) -> OidcService {
+    build_service_with_audit(
+        oidc_repo,
+        auth_session_repo,
+        flow_store,
+        realm_repo,
+        user_repo,
+        session_repo,
+        token_service,
+        Arc::new(TestAuditRepo::default()),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_service_with_audit(
+    oidc_repo: Arc<TestOidcRepo>,
+    auth_session_repo: Arc<TestAuthSessionRepo>,
+    flow_store: Arc<TestFlowStore>,
+    realm_repo: Arc<TestRealmRepo>,
+    user_repo: Arc<TestUserRepo>,
+    session_repo: Arc<TestSessionRepo>,
+    token_service: Arc<TestTokenService>,
+    audit_repo: Arc<TestAuditRepo>,
+) -> OidcService {
+    build_service_with_repos(
+        oidc_repo,
+        auth_session_repo,
+        flow_store,
+        realm_repo,
+        user_repo,
+        session_repo,
+        token_service,
+        audit_repo,
+        Arc::new(TestDeviceRepo::default()),
+        Arc::new(TestParRepo::default()),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_service_with_repos(
+    oidc_repo: Arc<TestOidcRepo>,
+    auth_session_repo: Arc<TestAuthSessionRepo>,
+    flow_store: Arc<TestFlowStore>,
+    realm_repo: Arc<TestRealmRepo>,
+    user_repo: Arc<TestUserRepo>,
+    session_repo: Arc<TestSessionRepo>,
+    token_service: Arc<TestTokenService>,
+    audit_repo: Arc<TestAuditRepo>,
+    device_repo: Arc<TestDeviceRepo>,
+    par_repo: Arc<TestParRepo>,
+) -> OidcService {
+    let claims_service = build_claims_service(oidc_repo.clone());
     let auth_service = build_auth_service(
+        oidc_repo.clone(),
         user_repo.clone(),
         realm_repo.clone(),
         session_repo.clone(),
         token_service.clone(),
+        claims_service.clone(),
     );
+    let logout_service = Arc::new(LogoutService::new(
+        session_repo.clone(),
+        oidc_repo.clone(),
+        Arc::new(TestOutboxRepo),
+        Arc::new(crate::adapters::eventing::in_memory_bus::InMemoryEventBus::default()),
+        "http://localhost".to_string(),
+    ));
     let secret_service = Arc::new(SecretService::from_key("test-secret"));
+    let auth_session_repo_for_executor = auth_session_repo.clone();
+    let flow_store_for_executor = flow_store.clone();
+
+    OidcService::new(
+        oidc_repo,
+        user_repo,
+        auth_service,
+        token_service,
+        secret_service,
+        auth_session_repo,
+        flow_store,
+        realm_repo,
+        build_rbac_service(),
+        session_repo,
+        Arc::new(AuditService::new(audit_repo)),
+        device_repo,
+        par_repo,
+        claims_service,
+        Arc::new(FlowExecutor::new(
+            auth_session_repo_for_executor,
+            flow_store_for_executor,
+            Arc::new(TestFlowRepo),
+            Arc::new(RuntimeRegistry::new()),
+            Arc::new(TestActionRepo),
+            None,
+            None,
+            None,
+        )),
+        logout_service,
+    )
+}
+
+fn base_realm() -> crate::domain::realm::Realm {
+    crate::domain::realm::Realm {
+        id: Uuid::new_v4(),
+        name: DEFAULT_REALM_NAME.to_string(),
+        access_token_ttl_secs: 300,
+        refresh_token_ttl_secs: 900,
+        pkce_required_public_clients: true,
+        signing_algorithm: crate::domain::signing_key::SigningAlgorithm::default(),
+        lockout_threshold: 5,
+        lockout_duration_secs: 900,
+        is_system: false,
+        registration_enabled: true,
+        default_registration_role_ids: Vec::new(),
+        invitation_resend_limit: 3,
+        idp_broker_enabled: false,
+        idp_default_jit_policy: crate::domain::realm::RealmIdpDefaultJitPolicy::PerProvider,
+        idp_default_email_link_policy:
+            crate::domain::realm::RealmIdpDefaultEmailLinkPolicy::ManualOnly,
+        idp_minimum_remaining_factor: true,
+        browser_flow_id: None,
+        registration_flow_id: None,
+        direct_grant_flow_id: None,
+        reset_credentials_flow_id: None,
+        invitation_flow_id: None,
+    }
+}
+
+fn build_oidc_request(client_id: &str, redirect_uri: &str) -> OidcRequest {
+    OidcRequest {
+        client_id: client_id.to_string(),
+        redirect_uri: redirect_uri.to_string(),
+        response_type: "code".to_string(),
+        scope: Some("openid".to_string()),
+        state: Some("state".to_string()),
+        nonce: Some("nonce".to_string()),
+        code_challenge: Some("challenge".to_string()),
+        code_challenge_method: Some("S256".to_string()),
+        claims: None,
+    }
+}
+
+fn build_oidc_request_without_pkce(client_id: &str, redirect_uri: &str) -> OidcRequest {
+    OidcRequest {
+        client_id: client_id.to_string(),
+        redirect_uri: redirect_uri.to_string(),
+        response_type: "code".to_string(),
+        scope: Some("openid".to_string()),
+        state: Some("state".to_string()),
+        nonce: Some("nonce".to_string()),
+        code_challenge: None,
+        code_challenge_method: None,
+        claims: None,
+    }
+}
+
+fn build_client(realm_id: Uuid, client_id: &str, redirect_uris: Vec<&str>) -> OidcClient {
+    OidcClient {
+        id: Uuid::new_v4(),
+        realm_id,
+        client_id: client_id.to_string(),
+        client_secret: None,
+        redirect_uris: serde_json::to_string(&redirect_uris).unwrap(),
+        scopes: serde_json::to_string(&vec!["openid"]).unwrap(),
+        web_origins: serde_json::to_string(&vec!["http://localhost"]).unwrap(),
+        managed_by_config: false,
+        token_endpoint_auth_method: TokenEndpointAuthMethod::None,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: None,
+        frontchannel_logout_uri: None,
+        token_exchange_policy: None,
+        direct_grant_enabled: false,
+        require_pushed_authorization_requests: false,
+    }
+}
+
+#[tokio::test]
+async fn validate_client_rejects_missing_client() {
+    let oidc_repo = Arc::new(TestOidcRepo::default());
+    let auth_session_repo = Arc::new(TestAuthSessionRepo::default());
+    let flow_store = Arc::new(TestFlowStore::default());
+    let realm_repo = Arc::new(TestRealmRepo::default());
+    let user_repo = Arc::new(TestUserRepo::default());
+    let session_repo = Arc::new(TestSessionRepo::default());
+    let token_service = Arc::new(TestTokenService::default());
+
+    let service = build_service(
+        oidc_repo,
+        auth_session_repo,
+        flow_store,
+        realm_repo,
+        user_repo,
+        session_repo,
+        token_service,
+    );
+
+    let err = service
+        .validate_client(&Uuid::new_v4(), "client", "http://localhost")
+        .await
+        .unwrap_err();
+
+    match err {
+        Error::OidcClientNotFound(_) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn validate_client_rejects_invalid_redirect_uri() {
+    let realm_id = Uuid::new_v4();
+    let oidc_repo = Arc::new(TestOidcRepo::default());
+    oidc_repo.set_client(Some(build_client(
+        realm_id,
+        "client",
+        vec!["https://allowed"],
+    )));
+
+    let service = build_service(
+        oidc_repo,
+        Arc::new(TestAuthSessionRepo::default()),
+        Arc::new(TestFlowStore::default()),
+        Arc::new(TestRealmRepo::default()),
+        Arc::new(TestUserRepo::default()),
+        Arc::new(TestSessionRepo::default()),
+        Arc::new(TestTokenService::default()),
+    );
+
+    let err = service
+        .validate_client(&realm_id, "client", "https://bad")
+        .await
+        .unwrap_err();
+
+    match err {
+        Error::OidcInvalidRedirect(_) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn validate_client_rejects_invalid_redirect_json() {
+    let realm_id = Uuid::new_v4();
+    let oidc_repo = Arc::new(TestOidcRepo::default());
+    let mut client = build_client(realm_id, "client", vec!["http://localhost"]);
+    client.redirect_uris = "not-json".to_string();
+    oidc_repo.set_client(Some(client));
+
+    let service = build_service(
+        oidc_repo,
+        Arc::new(TestAuthSessionRepo::default()),
+        Arc::new(TestFlowStore::default()),
+        Arc::new(TestRealmRepo::default()),
+        Arc::new(TestUserRepo::default()),
+        Arc::new(TestSessionRepo::default()),
+        Arc::new(TestTokenService::default()),
+    );
+
+    let err = service
+        .validate_client(&realm_id, "client", "http://localhost")
+        .await
+        .unwrap_err();
+
+    match err {
+        Error::Unexpected(message) => {
+            assert!(format!("{:?}", message).contains("redirect_uris"));
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn initiate_browser_login_requires_realm() {
+    let realm_id = Uuid::new_v4();
+    let oidc_repo = Arc::new(TestOidcRepo::default());
+    oidc_repo.set_client(Some(build_client(
+        realm_id,
+        "client",
+        vec!["http://localhost"],
+    )));
+
+    let service = build_service(
+        oidc_repo,
+        Arc::new(TestAuthSessionRepo::default()),
+        Arc::new(TestFlowStore::default()),
+        Arc::new(TestRealmRepo::default()),
+        Arc::new(TestUserRepo::default()),
+        Arc::new(TestSessionRepo::default()),
+        Arc::new(TestTokenService::default()),
+    );
+
+    let err = service
+        .initiate_browser_login(realm_id, build_oidc_request("client", "http://localhost"))
+        .await
+        .unwrap_err();
+
+    match err {
+        Error::NotFound(message) => assert!(message.contains("Realm not found")),
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn initiate_browser_login_requires_flow_binding() {
+    let realm_id = Uuid::new_v4();
+    let oidc_repo = Arc::new(TestOidcRepo::default());
+    oidc_repo.set_client(Some(build_client(
+        realm_id,
+        "client",
+        vec!["http://localhost"],
+    )));
+
+    let realm_repo = Arc::new(TestRealmRepo::default());
+    let mut realm = base_realm();
+    realm.id = realm_id;
+    realm_repo.set_realm(Some(realm));
+
+    let service = build_service(
+        oidc_repo,
+        Arc::new(TestAuthSessionRepo::default()),
+        Arc::new(TestFlowStore::default()),
+        realm_repo,
+        Arc::new(TestUserRepo::default()),
+        Arc::new(TestSessionRepo::default()),
+        Arc::new(TestTokenService::default()),
+    );
+
+    let err = service
+        .initiate_browser_login(realm_id, build_oidc_request("client", "http://localhost"))
+        .await
+        .unwrap_err();
+
+    match err {
+        Error::Validation(message) => assert!(message.contains("browser flow configured")),
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn initiate_browser_login_requires_flow_version() {
+    let realm_id = Uuid::new_v4();
+    let flow_id = Uuid::new_v4();
+
+    let oidc_repo = Arc::new(TestOidcRepo::default());
+    oidc_repo.set_client(Some(build_client(
+        realm_id,
+        "client",
+        vec!["http://localhost"],
+    )));
+
+    let realm_repo = Arc::new(TestRealmRepo::default());
+    let mut realm = base_realm();
+    realm.id = realm_id;
+    realm.browser_flow_id = Some(flow_id.to_string());
+    realm_repo.set_realm(Some(realm));
+
+    let service = build_service(
+        oidc_repo,
+        Arc::new(TestAuthSessionRepo::default()),
+        Arc::new(TestFlowStore::default()),
+        realm_repo,
+        Arc::new(TestUserRepo::default()),
+        Arc::new(TestSessionRepo::default()),
+        Arc::new(TestTokenService::default()),
+    );
+
+    let err = service
+        .initiate_browser_login(realm_id, build_oidc_request("client", "http://localhost"))
+        .await
+        .unwrap_err();
+
+    match err {
+        Error::NotFound(message) => assert!(message.contains("Flow version not found")),
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn initiate_browser_login_requires_pkce_for_public_clients() {
+    let realm_id = Uuid::new_v4();
+    let flow_id = Uuid::new_v4();
+
+    let oidc_repo = Arc::new(TestOidcRepo::default());
+    oidc_repo.set_client(Some(build_client(
+        realm_id,
+        "client",
+        vec!["http://localhost"],
+    )));
+
+    let realm_repo = Arc::new(TestRealmRepo::default());
+    let mut realm = base_realm();
+    realm.id = realm_id;
+    realm.browser_flow_id = Some(flow_id.to_string());
+    realm_repo.set_realm(Some(realm));
+
+    let version_id = Uuid::new_v4();
+    let flow_store = TestFlowStore::default();
+    flow_store.set_active_version(flow_id, Some(version_id.to_string()));
+    let execution_plan = json!({
+        "start_node_id": "success",
+        "nodes": {
+            "success": {
+                "id": "success",
+                "step_type": "terminal",
+                "next": {},
+                "config": { "is_failure": false }
+            }
+        }
+    });
+    flow_store.set_version(&version_id.to_string(), &execution_plan.to_string());
+
+    let service = build_service(
+        oidc_repo,
+        Arc::new(TestAuthSessionRepo::default()),
+        Arc::new(flow_store),
+        realm_repo,
+        Arc::new(TestUserRepo::default()),
+        Arc::new(TestSessionRepo::default()),
+        Arc::new(TestTokenService::default()),
+    );
+
+    let err = service
+        .initiate_browser_login(
+            realm_id,
+            build_oidc_request_without_pkce("client", "http://localhost"),
+        )
+        .await
+        .unwrap_err();
+
+    match err {
+        Error::OidcInvalidRequest(message) => assert!(message.contains("PKCE")),
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn initiate_browser_login_allows_missing_pkce_when_disabled() {
+    let realm_id = Uuid::new_v4();
+    let flow_id = Uuid::new_v4();
+
+    let oidc_repo = Arc::new(TestOidcRepo::default());
+    oidc_repo.set_client(Some(build_client(
+        realm_id,
+        "client",
+        vec!["http://localhost"],
+    )));
+
+    let flow_store = TestFlowStore::default();
+    let version_id = Uuid::new_v4();
+    flow_store.set_active_version(flow_id, Some(version_id.to_string()));
+    let execution_plan = json!({
+        "start_node_id": "success",
+        "nodes": {
+            "success": {
+                "id": "success",
+                "step_type": "terminal",
+                "next": {},
+                "config": { "is_failure": false }
+            }
+        }
+    });
+    flow_store.set_version(&version_id.to_string(), &execution_plan.to_string());
+
+    let realm_repo = Arc::new(TestRealmRepo::default());
+    let mut realm = base_realm();
+    realm.id = realm_id;
+    realm.browser_flow_id = Some(flow_id.to_string());
+    realm.pkce_required_public_clients = false;
+    realm_repo.set_realm(Some(realm));
+
+    let service = build_service(
+        oidc_repo,
+        Arc::new(TestAuthSessionRepo::default()),
+        Arc::new(flow_store),
+        realm_repo,
+        Arc::new(TestUserRepo::default()),
+        Arc::new(TestSessionRepo::default()),
+        Arc::new(TestTokenService::default()),
+    );
+
+    let session = service
+        .initiate_browser_login(
+            realm_id,
+            build_oidc_request_without_pkce("client", "http://localhost"),
+        )
+        .await
+        .expect("login should succeed");
+
+    assert_eq!(session.realm_id, realm_id);
+}
+
+#[tokio::test]
+async fn initiate_browser_login_rejects_corrupt_execution_artifact() {
+    let realm_id = Uuid::new_v4();
+    let flow_id = Uuid::new_v4();
+    let version_id = Uuid::new_v4();
+
+    let oidc_repo = Arc::new(TestOidcRepo::default());
+    oidc_repo.set_client(Some(build_client(
+        realm_id,
+        "client",
+        vec!["http://localhost"],
+    )));
+
+    let flow_store = Arc::new(TestFlowStore::default());
+    flow_store.set_active_version(flow_id, Some(version_id.to_string()));
+    flow_store.set_version(&version_id.to_string(), "not-json");
+
+    let realm_repo = Arc::new(TestRealmRepo::default());
+    let mut realm = base_realm();
+    realm.id = realm_id;
+    realm.browser_flow_id = Some(flow_id.to_string());
+    realm_repo.set_realm(Some(realm));
+
+    let service = build_service(
+        oidc_repo,
+        Arc::new(TestAuthSessionRepo::default()),
+        flow_store,
+        realm_repo,
+        Arc::new(TestUserRepo::default()),
+        Arc::new(TestSessionRepo::default()),
+        Arc::new(TestTokenService::default()),
+    );
+
+    let err = service
+        .initiate_browser_login(realm_id, build_oidc_request("client", "http://localhost"))
+        .await
+        .unwrap_err();
+
+    match err {
+        Error::Unexpected(message) => assert!(format!("{:?}", message).contains("Corrupt")),
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn initiate_browser_login_creates_session() {
+    let realm_id = Uuid::new_v4();
+    let flow_id = Uuid::new_v4();
+    let version_id = Uuid::new_v4();
+
+    let oidc_repo = Arc::new(TestOidcRepo::default());
+    oidc_repo.set_client(Some(build_client(
+        realm_id,
+        "client",
+        vec!["http://localhost"],
+    )));
+
+    let flow_store = Arc::new(TestFlowStore::default());
+    flow_store.set_latest_version(flow_id, Some(version_id.to_string()));
+    flow_store.set_version(
+        &version_id.to_string(),
+        &serde_json::to_string(&ExecutionPlan {
+            start_node_id: "start".to_string(),
+            nodes: HashMap::new(),
+        })
+        .unwrap(),
+    );
+
+    let realm_repo = Arc::new(TestRealmRepo::default());
+    let mut realm = base_realm();
+    realm.id = realm_id;
+    realm.browser_flow_id = Some(flow_id.to_string());
+    realm_repo.set_realm(Some(realm));
+
+    let auth_session_repo = Arc::new(TestAuthSessionRepo::default());
+
+    let service = build_service(
+        oidc_repo,
+        auth_session_repo.clone(),
+        flow_store,
+        realm_repo,
+        Arc::new(TestUserRepo::default()),
+        Arc::new(TestSessionRepo::default()),
+        Arc::new(TestTokenService::default()),
+    );
+
+    let session = service
+        .initiate_browser_login(realm_id, build_oidc_request("client", "http://localhost"))
+        .await
+        .expect("expected session");
+
+    assert_eq!(session.flow_version_id, version_id);
+    let created = auth_session_repo.created_sessions();
+    assert_eq!(created.len(), 1);
+    let context = created[0].context.get("oidc").cloned();
+    assert!(context
+        .and_then(|value| serde_json::from_value::<OidcContext>(value).ok())
+        .is_some());
+}
+
+#[tokio::test]
+async fn exchange_code_for_token_rejects_missing_code() {
+    let service = build_service(
+        Arc::new(TestOidcRepo::default()),
+        Arc::new(TestAuthSessionRepo::default()),
+        Arc::new(TestFlowStore::default()),
+        Arc::new(TestRealmRepo::default()),
+        Arc::new(TestUserRepo::default()),
+        Arc::new(TestSessionRepo::default()),
+        Arc::new(TestTokenService::default()),
+    );
+
+    match service
+        .exchange_code_for_token(
+            &build_client(Uuid::new_v4(), "client", vec!["http://localhost"]),
+            "missing",
+            "http://localhost",
+            "verifier",
+            None,
+            None,
+        )
+        .await
+    {
+        Err(Error::OidcInvalidCode) => {}
+        Err(other) => panic!("unexpected error: {:?}", other),
+        Ok(_) => panic!("expected error"),
+    }
+}
+
+#[tokio::test]
+async fn exchange_code_for_token_rejects_invalid_pkce() {
+    let oidc_repo = Arc::new(TestOidcRepo::default());
+    oidc_repo.insert_auth_code(AuthCode {
+        code: "code".to_string(),
+        user_id: Uuid::new_v4(),
+        client_id: "client".to_string(),
+        redirect_uri: "http://localhost".to_string(),
+        nonce: None,
+        code_challenge: Some("expected".to_string()),
+        code_challenge_method: "S256".to_string(),
+        expires_at: Utc::now() + Duration::seconds(60),
+        scope: None,
+        claims: None,
+    });
+
+    let service = build_service(
+        oidc_repo,
+        Arc::new(TestAuthSessionRepo::default()),
+        Arc::new(TestFlowStore::default()),
+        Arc::new(TestRealmRepo::default()),
+        Arc::new(TestUserRepo::default()),
+        Arc::new(TestSessionRepo::default()),
+        Arc::new(TestTokenService::default()),
+    );
+
+    match service
+        .exchange_code_for_token(
+            &build_client(Uuid::new_v4(), "client", vec!["http://localhost"]),
+            "code",
+            "http://localhost",
+            "bad",
+            None,
+            None,
+        )
+        .await
+    {
+        Err(Error::OidcInvalidCode) => {}
+        Err(other) => panic!("unexpected error: {:?}", other),
+        Ok(_) => panic!("expected error"),
+    }
+}
+
+#[tokio::test]
+async fn exchange_code_for_token_rejects_redirect_mismatch() {
+    let verifier = "verifier";
+    let oidc_repo = Arc::new(TestOidcRepo::default());
+    oidc_repo.insert_auth_code(AuthCode {
+        code: "code".to_string(),
+        user_id: Uuid::new_v4(),
+        client_id: "client".to_string(),
+        redirect_uri: "http://localhost".to_string(),
+        nonce: None,
+        code_challenge: Some(pkce_challenge(verifier)),
+        code_challenge_method: "S256".to_string(),
+        expires_at: Utc::now() + Duration::seconds(60),
+        scope: None,
+        claims: None,
+    });
+
+    let service = build_service(
+        oidc_repo,
+        Arc::new(TestAuthSessionRepo::default()),
+        Arc::new(TestFlowStore::default()),
+        Arc::new(TestRealmRepo::default()),
+        Arc::new(TestUserRepo::default()),
+        Arc::new(TestSessionRepo::default()),
+        Arc::new(TestTokenService::default()),
+    );
+
+    match service
+        .exchange_code_for_token(
+            &build_client(Uuid::new_v4(), "client", vec!["http://localhost"]),
+            "code",
+            "http://evil.invalid",
+            verifier,
+            None,
+            None,
+        )
+        .await
+    {
+        Err(Error::OidcInvalidRedirect(uri)) => {
+            assert_eq!(uri, "http://evil.invalid");
+        }
+        Err(other) => panic!("unexpected error: {:?}", other),
+        Ok(_) => panic!("expected error"),
+    }
+}
+
+#[tokio::test]
+async fn exchange_code_for_token_rejects_code_issued_to_other_client() {
+    let verifier = "verifier";
+    let oidc_repo = Arc::new(TestOidcRepo::default());
+    oidc_repo.insert_auth_code(AuthCode {
+        code: "code".to_string(),
+        user_id: Uuid::new_v4(),
+        client_id: "client".to_string(),
+        redirect_uri: "http://localhost".to_string(),
+        nonce: None,
+        code_challenge: Some(pkce_challenge(verifier)),
+        code_challenge_method: "S256".to_string(),
+        expires_at: Utc::now() + Duration::seconds(60),
+        scope: None,
+        claims: None,
+    });
+
+    let service = build_service(
+        oidc_repo.clone(),
+        Arc::new(TestAuthSessionRepo::default()),
+        Arc::new(TestFlowStore::default()),
+        Arc::new(TestRealmRepo::default()),
+        Arc::new(TestUserRepo::default()),
+        Arc::new(TestSessionRepo::default()),
+        Arc::new(TestTokenService::default()),
+    );
+
+    match service
+        .exchange_code_for_token(
+            &build_client(Uuid::new_v4(), "intruder", vec!["http://localhost"]),
+            "code",
+            "http://localhost",
+            verifier,
+            None,
+            None,
+        )
+        .await
+    {
+        Err(Error::OidcInvalidGrant(_)) => {}
+        Err(other) => panic!("unexpected error: {:?}", other),
+        Ok(_) => panic!("expected error"),
+    }
+    assert!(oidc_repo.deleted_codes().is_empty());
+}
+
+#[tokio::test]
+async fn exchange_code_for_token_requires_user() {
+    let verifier = "verifier";
+    let oidc_repo = Arc::new(TestOidcRepo::default());
+    oidc_repo.insert_auth_code(AuthCode {
+        code: "code".to_string(),
+        user_id: Uuid::new_v4(),
+        client_id: "client".to_string(),
+        redirect_uri: "http://localhost".to_string(),
+        nonce: None,
+        code_challenge: Some(pkce_challenge(verifier)),
+        code_challenge_method: "S256".to_string(),
+        expires_at: Utc::now() + Duration::seconds(60),
+        scope: None,
+        claims: None,
+    });
+
+    let service = build_service(
+        oidc_repo,
+        Arc::new(TestAuthSessionRepo::default()),
+        Arc::new(TestFlowStore::default()),
+        Arc::new(TestRealmRepo::default()),
+        Arc::new(TestUserRepo::default()),
+        Arc::new(TestSessionRepo::default()),
+        Arc::new(TestTokenService::default()),
+    );
+
+    match service
+        .exchange_code_for_token(
+            &build_client(Uuid::new_v4(), "client", vec!["http://localhost"]),
+            "code",
+            "http://localhost",
+            verifier,
+            None,
+            None,
+        )
+        .await
+    {
+        Err(Error::UserNotFound) => {}
+        Err(other) => panic!("unexpected error: {:?}", other),
+        Ok(_) => panic!("expected error"),
+    }
+}
+
+#[tokio::test]
+async fn exchange_code_for_token_returns_tokens_and_deletes_code() {
+    let verifier = "verifier";
+    let user_id = Uuid::new_v4();
+
+    let oidc_repo = Arc::new(TestOidcRepo::default());
+    oidc_repo.insert_auth_code(AuthCode {
+        code: "code".to_string(),
+        user_id,
+        client_id: "client".to_string(),
+        redirect_uri: "http://localhost".to_string(),
+        nonce: None,
+        code_challenge: Some(pkce_challenge(verifier)),
+        code_challenge_method: "S256".to_string(),
+        expires_at: Utc::now() + Duration::seconds(60),
+        scope: None,
+        claims: None,
+    });
+
+    let user_repo = Arc::new(TestUserRepo::default());
+    user_repo.insert(User {
+        id: user_id,
+        realm_id: Uuid::new_v4(),
+        username: "user".to_string(),
+        first_name: None,
+        last_name: None,
+        hashed_password: "hash".to_string(),
+        public_metadata_json: crate::domain::user::EMPTY_METADATA_JSON.to_string(),
+        private_metadata_json: crate::domain::user::EMPTY_METADATA_JSON.to_string(),
+        unsafe_metadata_json: crate::domain::user::EMPTY_METADATA_JSON.to_string(),
+        force_password_reset: false,
+        password_login_disabled: false,
+        created_at: Some(Utc::now()),
+        updated_at: None,
+        last_sign_in_at: None,
+        locked_until: None,
+        banned_at: None,
+    });
+
+    let realm_repo = Arc::new(TestRealmRepo::default());
+    let realm = base_realm();
+    realm_repo.set_realm(Some(realm));
+
+    let session_repo = Arc::new(TestSessionRepo::default());
+    let token_service = Arc::new(TestTokenService::default());
 
-    OidcService::new(
-        oidc_repo,
+    let service = build_service(
+        oidc_repo.clone(),
+        Arc::new(TestAuthSessionRepo::default()),
+        Arc::new(TestFlowStore::default()),
+        realm_repo,
         user_repo,
-        auth_service,
+        session_repo.clone(),
         token_service,
-        secret_service,
-        auth_session_repo,
-        flow_store,
-        realm_repo,
-    )
-}
+    );
 
-fn base_realm() -> crate::domain::realm::Realm {
-    crate::domain::realm::Realm {
-        id: Uuid::new_v4(),
-        name: DEFAULT_REALM_NAME.to_string(),
-        access_token_ttl_secs: 300,
-        refresh_token_ttl_secs: 900,
-        pkce_required_public_clients: true,
-        lockout_threshold: 5,
-        lockout_duration_secs: 900,
-        is_system: false,
-        registration_enabled: true,
-        default_registration_role_ids: Vec::new(),
-        invitation_resend_limit: 3,
-        idp_broker_enabled: false,
-        idp_default_jit_policy: crate::domain::realm::RealmIdpDefaultJitPolicy::PerProvider,
-        idp_default_email_link_policy:
-            crate::domain::realm::RealmIdpDefaultEmailLinkPolicy::ManualOnly,
-        idp_minimum_remaining_factor: true,
-        browser_flow_id: None,
-        registration_flow_id: None,
-        direct_grant_flow_id: None,
-        reset_credentials_flow_id: None,
-        invitation_flow_id: None,
-    }
-}
+    let (token_response, refresh_token) = service
+        .exchange_code_for_token(
+            &build_client(Uuid::new_v4(), "client", vec!["http://localhost"]),
+            "code",
+            "http://localhost",
+            verifier,
+            None,
+            None,
+        )
+        .await
+        .expect("expected success");
 
-fn build_oidc_request(client_id: &str, redirect_uri: &str) -> OidcRequest {
-    OidcRequest {
-        client_id: client_id.to_string(),
-        redirect_uri: redirect_uri.to_string(),
-        response_type: "code".to_string(),
-        scope: Some("openid".to_string()),
-        state: Some("state".to_string()),
-        nonce: Some("nonce".to_string()),
-        code_challenge: Some("challenge".to_string()),
-        code_challenge_method: Some("S256".to_string()),
-    }
-}
+    assert_eq!(token_response.access_token, "access-token");
+    assert_eq!(token_response.id_token.as_deref(), Some("id-token"));
+    assert_eq!(token_response.token_type, "Bearer");
+    assert!(token_response.expires_in > 0);
 
-fn build_oidc_request_without_pkce(client_id: &str, redirect_uri: &str) -> OidcRequest {
-    OidcRequest {
-        client_id: client_id.to_string(),
-        redirect_uri: redirect_uri.to_string(),
-        response_type: "code".to_string(),
-        scope: Some("openid".to_string()),
-        state: Some("state".to_string()),
-        nonce: Some("nonce".to_string()),
-        code_challenge: None,
-        code_challenge_method: None,
-    }
+    assert_eq!(refresh_token.user_id, user_id);
+    assert_eq!(session_repo.saved_tokens().len(), 1);
+    assert_eq!(oidc_repo.deleted_codes(), vec!["code".to_string()]);
 }
 
-fn build_client(realm_id: Uuid, client_id: &str, redirect_uris: Vec<&str>) -> OidcClient {
-    OidcClient {
-        id: Uuid::new_v4(),
-        realm_id,
-        client_id: client_id.to_string(),
-        client_secret: None,
-        redirect_uris: serde_json::to_string(&redirect_uris).unwrap(),
-        scopes: serde_json::to_string(&vec!["openid"]).unwrap(),
-        web_origins: serde_json::to_string(&vec!["http://localhost"]).unwrap(),
-        managed_by_config: false,
-    }
+#[tokio::test]
+async fn exchange_code_for_token_keeps_granted_scope_on_session() {
+    let verifier = "verifier";
+    let realm = base_realm();
+    let user = build_user(realm.id);
+
+    let oidc_repo = Arc::new(TestOidcRepo::default());
+    oidc_repo.insert_auth_code(AuthCode {
+        code: "code".to_string(),
+        user_id: user.id,
+        client_id: "client".to_string(),
+        redirect_uri: "http://localhost".to_string(),
+        nonce: None,
+        code_challenge: Some(pkce_challenge(verifier)),
+        code_challenge_method: "S256".to_string(),
+        expires_at: Utc::now() + Duration::seconds(60),
+        scope: Some("openid profile".to_string()),
+        claims: Some(r#"{"userinfo":{"email":null}}"#.to_string()),
+    });
+    let user_repo = Arc::new(TestUserRepo::default());
+    user_repo.insert(user);
+    let realm_repo = Arc::new(TestRealmRepo::default());
+    realm_repo.set_realm(Some(realm.clone()));
+
+    let service = build_service(
+        oidc_repo,
+        Arc::new(TestAuthSessionRepo::default()),
+        Arc::new(TestFlowStore::default()),
+        realm_repo,
+        user_repo,
+        Arc::new(TestSessionRepo::default()),
+        Arc::new(TestTokenService::default()),
+    );
+
+    let (token_response, refresh_token) = service
+        .exchange_code_for_token(
+            &build_client(realm.id, "client", vec!["http://localhost"]),
+            "code",
+            "http://localhost",
+            verifier,
+            None,
+            None,
+        )
+        .await
+        .expect("expected success");
+
+    assert_eq!(token_response.scope.as_deref(), Some("openid profile"));
+    assert_eq!(refresh_token.scope.as_deref(), Some("openid profile"));
+    assert_eq!(
+        refresh_token.claims.as_deref(),
+        Some(r#"{"userinfo":{"email":null}}"#)
+    );
 }
 
 #[tokio::test]
-async fn validate_client_rejects_missing_client() {
+async fn userinfo_adds_claims_of_scopes_registered_on_client() {
+    let realm = base_realm();
+    let mut client = build_client(realm.id, "client", vec!["http://localhost"]);
+    client.scopes = serde_json::to_string(&vec!["openid", "profile"]).unwrap();
     let oidc_repo = Arc::new(TestOidcRepo::default());
-    let auth_session_repo = Arc::new(TestAuthSessionRepo::default());
-    let flow_store = Arc::new(TestFlowStore::default());
-    let realm_repo = Arc::new(TestRealmRepo::default());
+    oidc_repo.set_client(Some(client));
+
+    let mut user = build_user(realm.id);
+    user.first_name = Some("Ada".to_string());
+    user.last_name = Some("Lovelace".to_string());
+    user.public_metadata_json = json!({ "locale": "en-GB" }).to_string();
     let user_repo = Arc::new(TestUserRepo::default());
+    user_repo.insert(user.clone());
+
     let session_repo = Arc::new(TestSessionRepo::default());
+    let mut session = RefreshToken::new(
+        user.id,
+        realm.id,
+        Some("client".to_string()),
+        Duration::minutes(5),
+    );
+    // `email` is granted but not registered on the client.
+    session.scope = Some("openid profile email".to_string());
+    session_repo.save(&session).await.unwrap();
+
     let token_service = Arc::new(TestTokenService::default());
+    token_service.set_access_claims(AccessTokenClaims {
+        sub: user.id,
+        sid: session.id,
+        perms: HashSet::new(),
+        roles: vec!["viewer".to_string()],
+        groups: Vec::new(),
+        exp: (Utc::now() + Duration::minutes(5)).timestamp() as usize,
+        iat: Utc::now().timestamp() as usize,
+        azp: None,
+        scope: None,
+        aud: None,
+        act: None,
+        extra: Map::new(),
+    });
 
     let service = build_service(
         oidc_repo,
-        auth_session_repo,
-        flow_store,
-        realm_repo,
+        Arc::new(TestAuthSessionRepo::default()),
+        Arc::new(TestFlowStore::default()),
+        Arc::new(TestRealmRepo::default()),
         user_repo,
         session_repo,
         token_service,
     );
 
-    let err = service
-        .validate_client(&Uuid::new_v4(), "client", "http://localhost")
-        .await
-        .unwrap_err();
+    let userinfo = service.userinfo(realm.id, "token").await.expect("userinfo");
+    assert_eq!(userinfo["sub"], json!(user.id.to_string()));
+    assert_eq!(userinfo["roles"], json!(["viewer"]));
+    assert_eq!(userinfo["name"], json!("Ada Lovelace"));
+    assert_eq!(userinfo["given_name"], json!("Ada"));
+    assert_eq!(userinfo["locale"], json!("en-GB"));
+    assert!(userinfo.get("email").is_none());
+}
 
-    match err {
-        Error::OidcClientNotFound(_) => {}
-        other => panic!("unexpected error: {:?}", other),
+fn build_confidential_client(realm_id: Uuid, client_id: &str, secret: &str) -> OidcClient {
+    let mut client = build_client(realm_id, client_id, vec!["http://localhost"]);
+    client.client_secret = Some(secret.to_string());
+    client.scopes = serde_json::to_string(&vec!["openid", "orders:read"]).unwrap();
+    client.token_endpoint_auth_method = TokenEndpointAuthMethod::ClientSecretPost;
+    client
+}
+
+fn secret_post(client_id: &str, secret: &str) -> ClientAuthentication {
+    ClientAuthentication::SecretPost {
+        client_id: client_id.to_string(),
+        client_secret: secret.to_string(),
     }
 }
 
-#[tokio::test]
-async fn validate_client_rejects_invalid_redirect_uri() {
-    let realm_id = Uuid::new_v4();
-    let oidc_repo = Arc::new(TestOidcRepo::default());
-    oidc_repo.set_client(Some(build_client(
+fn build_user(realm_id: Uuid) -> User {
+    User {
+        id: Uuid::new_v4(),
         realm_id,
-        "client",
-        vec!["https://allowed"],
-    )));
+        username: "user".to_string(),
+        first_name: None,
+        last_name: None,
+        hashed_password: "hash".to_string(),
+        public_metadata_json: crate::domain::user::EMPTY_METADATA_JSON.to_string(),
+        private_metadata_json: crate::domain::user::EMPTY_METADATA_JSON.to_string(),
+        unsafe_metadata_json: crate::domain::user::EMPTY_METADATA_JSON.to_string(),
+        force_password_reset: false,
+        password_login_disabled: false,
+        created_at: Some(Utc::now()),
+        updated_at: None,
+        last_sign_in_at: None,
+        locked_until: None,
+        banned_at: None,
+    }
+}
 
+#[tokio::test]
+async fn client_credentials_grant_rejects_public_client() {
     let service = build_service(
-        oidc_repo,
+        Arc::new(TestOidcRepo::default()),
         Arc::new(TestAuthSessionRepo::default()),
         Arc::new(TestFlowStore::default()),
         Arc::new(TestRealmRepo::default()),
@@ -1371,25 +2890,49 @@ async fn validate_client_rejects_invalid_redirect_uri() {
         Arc::new(TestSessionRepo::default()),
         Arc::new(TestTokenService::default()),
     );
+    let client = build_client(Uuid::new_v4(), "public-app", vec!["http://localhost"]);
 
-    let err = service
-        .validate_client(&realm_id, "client", "https://bad")
-        .await
-        .unwrap_err();
+    match service.client_credentials_grant(&client, None).await {
+        Err(Error::OidcUnauthorizedClient(_)) => {}
+        Err(other) => panic!("unexpected error: {:?}", other),
+        Ok(_) => panic!("expected error"),
+    }
+}
 
-    match err {
-        Error::OidcInvalidRedirect(_) => {}
-        other => panic!("unexpected error: {:?}", other),
+#[tokio::test]
+async fn password_grant_requires_direct_grant_enabled_client() {
+    let auth_session_repo = Arc::new(TestAuthSessionRepo::default());
+    let service = build_service(
+        Arc::new(TestOidcRepo::default()),
+        auth_session_repo.clone(),
+        Arc::new(TestFlowStore::default()),
+        Arc::new(TestRealmRepo::default()),
+        Arc::new(TestUserRepo::default()),
+        Arc::new(TestSessionRepo::default()),
+        Arc::new(TestTokenService::default()),
+    );
+    let client = build_confidential_client(Uuid::new_v4(), "legacy-app", "s3cret");
+    let request = PasswordGrantRequest {
+        username: "alice".to_string(),
+        password: "password".to_string(),
+        ..PasswordGrantRequest::default()
+    };
+
+    match service.password_grant(&client, &request, None, None).await {
+        Err(Error::OidcUnauthorizedClient(_)) => {}
+        Err(other) => panic!("unexpected error: {:?}", other),
+        Ok(_) => panic!("expected error"),
     }
+    assert!(auth_session_repo.created_sessions().is_empty());
 }
 
 #[tokio::test]
-async fn validate_client_rejects_invalid_redirect_json() {
+async fn authenticate_client_rejects_wrong_secret() {
     let realm_id = Uuid::new_v4();
     let oidc_repo = Arc::new(TestOidcRepo::default());
-    let mut client = build_client(realm_id, "client", vec!["http://localhost"]);
-    client.redirect_uris = "not-json".to_string();
-    oidc_repo.set_client(Some(client));
+    oidc_repo.set_client(Some(build_confidential_client(
+        realm_id, "backend", "s3cret",
+    )));
 
     let service = build_service(
         oidc_repo,
@@ -1401,27 +2944,28 @@ async fn validate_client_rejects_invalid_redirect_json() {
         Arc::new(TestTokenService::default()),
     );
 
-    let err = service
-        .validate_client(&realm_id, "client", "http://localhost")
+    match service
+        .authenticate_client(&realm_id, &secret_post("backend", "wrong"))
         .await
-        .unwrap_err();
-
-    match err {
-        Error::Unexpected(message) => {
-            assert!(format!("{:?}", message).contains("redirect_uris"));
-        }
-        other => panic!("unexpected error: {:?}", other),
+    {
+        Err(Error::OidcInvalidClient(_)) => {}
+        Err(other) => panic!("unexpected error: {:?}", other),
+        Ok(_) => panic!("expected error"),
     }
+
+    let client = service
+        .authenticate_client(&realm_id, &secret_post("backend", "s3cret"))
+        .await
+        .expect("valid secret");
+    assert_eq!(client.client_id, "backend");
 }
 
 #[tokio::test]
-async fn initiate_browser_login_requires_realm() {
+async fn authenticate_client_rejects_mismatched_method() {
     let realm_id = Uuid::new_v4();
     let oidc_repo = Arc::new(TestOidcRepo::default());
-    oidc_repo.set_client(Some(build_client(
-        realm_id,
-        "client",
-        vec!["http://localhost"],
+    oidc_repo.set_client(Some(build_confidential_client(
+        realm_id, "backend", "s3cret",
     )));
 
     let service = build_service(
@@ -1434,499 +2978,1035 @@ async fn initiate_browser_login_requires_realm() {
         Arc::new(TestTokenService::default()),
     );
 
-    let err = service
-        .initiate_browser_login(realm_id, build_oidc_request("client", "http://localhost"))
-        .await
-        .unwrap_err();
-
-    match err {
-        Error::NotFound(message) => assert!(message.contains("Realm not found")),
-        other => panic!("unexpected error: {:?}", other),
+    let attempts = [
+        ClientAuthentication::None {
+            client_id: "backend".to_string(),
+        },
+        ClientAuthentication::SecretBasic {
+            client_id: "backend".to_string(),
+            client_secret: "s3cret".to_string(),
+        },
+    ];
+    for auth in attempts {
+        match service.authenticate_client(&realm_id, &auth).await {
+            Err(Error::OidcInvalidClient(message)) => {
+                assert!(message.contains("client_secret_post"));
+            }
+            Err(other) => panic!("unexpected error: {:?}", other),
+            Ok(_) => panic!("expected {:?} to be rejected", auth.method()),
+        }
     }
 }
 
 #[tokio::test]
-async fn initiate_browser_login_requires_flow_binding() {
+async fn authenticate_client_verifies_private_key_jwt_assertion() {
     let realm_id = Uuid::new_v4();
-    let oidc_repo = Arc::new(TestOidcRepo::default());
-    oidc_repo.set_client(Some(build_client(
-        realm_id,
-        "client",
-        vec!["http://localhost"],
-    )));
+    let audience = "https://auth.example.com/api/realms/test/oidc/token";
+    let signing_key = p256::ecdsa::SigningKey::from_bytes((&[7u8; 32]).into()).unwrap();
+    let other_key = p256::ecdsa::SigningKey::from_bytes((&[9u8; 32]).into()).unwrap();
 
-    let realm_repo = Arc::new(TestRealmRepo::default());
-    let mut realm = base_realm();
-    realm.id = realm_id;
-    realm_repo.set_realm(Some(realm));
+    let mut client = build_client(realm_id, "worker", vec!["http://localhost"]);
+    client.token_endpoint_auth_method = TokenEndpointAuthMethod::PrivateKeyJwt;
+    client.jwks = Some(json!({ "keys": [es256_jwk(&signing_key, "worker-key")] }).to_string());
+    let oidc_repo = Arc::new(TestOidcRepo::default());
+    oidc_repo.set_client(Some(client));
 
     let service = build_service(
         oidc_repo,
         Arc::new(TestAuthSessionRepo::default()),
         Arc::new(TestFlowStore::default()),
-        realm_repo,
+        Arc::new(TestRealmRepo::default()),
         Arc::new(TestUserRepo::default()),
         Arc::new(TestSessionRepo::default()),
         Arc::new(TestTokenService::default()),
-    );
-
-    let err = service
-        .initiate_browser_login(realm_id, build_oidc_request("client", "http://localhost"))
-        .await
-        .unwrap_err();
+    );
+    let assertion_auth = |assertion: String| ClientAuthentication::PrivateKeyJwt {
+        client_id: None,
+        assertion,
+        audiences: vec![audience.to_string()],
+    };
 
-    match err {
-        Error::Validation(message) => assert!(message.contains("browser flow configured")),
-        other => panic!("unexpected error: {:?}", other),
+    let client = service
+        .authenticate_client(
+            &realm_id,
+            &assertion_auth(es256_assertion(
+                &signing_key,
+                "worker-key",
+                "worker",
+                audience,
+            )),
+        )
+        .await
+        .expect("valid assertion");
+    assert_eq!(client.client_id, "worker");
+
+    let rejected = [
+        es256_assertion(&other_key, "worker-key", "worker", audience),
+        es256_assertion(
+            &signing_key,
+            "worker-key",
+            "worker",
+            "https://other.example.com",
+        ),
+    ];
+    for assertion in rejected {
+        match service
+            .authenticate_client(&realm_id, &assertion_auth(assertion))
+            .await
+        {
+            Err(Error::OidcInvalidClient(_)) => {}
+            Err(other) => panic!("unexpected error: {:?}", other),
+            Ok(_) => panic!("expected assertion to be rejected"),
+        }
     }
 }
 
 #[tokio::test]
-async fn initiate_browser_login_requires_flow_version() {
+async fn authenticate_client_rejects_replayed_client_assertion() {
     let realm_id = Uuid::new_v4();
-    let flow_id = Uuid::new_v4();
+    let audience = "https://auth.example.com/api/realms/test/oidc/token";
+    let signing_key = p256::ecdsa::SigningKey::from_bytes((&[7u8; 32]).into()).unwrap();
 
+    let mut client = build_client(realm_id, "worker", vec!["http://localhost"]);
+    client.token_endpoint_auth_method = TokenEndpointAuthMethod::PrivateKeyJwt;
+    client.jwks = Some(json!({ "keys": [es256_jwk(&signing_key, "worker-key")] }).to_string());
     let oidc_repo = Arc::new(TestOidcRepo::default());
-    oidc_repo.set_client(Some(build_client(
-        realm_id,
-        "client",
-        vec!["http://localhost"],
-    )));
-
-    let realm_repo = Arc::new(TestRealmRepo::default());
-    let mut realm = base_realm();
-    realm.id = realm_id;
-    realm.browser_flow_id = Some(flow_id.to_string());
-    realm_repo.set_realm(Some(realm));
+    oidc_repo.set_client(Some(client));
 
     let service = build_service(
         oidc_repo,
         Arc::new(TestAuthSessionRepo::default()),
         Arc::new(TestFlowStore::default()),
-        realm_repo,
+        Arc::new(TestRealmRepo::default()),
         Arc::new(TestUserRepo::default()),
         Arc::new(TestSessionRepo::default()),
         Arc::new(TestTokenService::default()),
     );
+    let auth = ClientAuthentication::PrivateKeyJwt {
+        client_id: None,
+        assertion: es256_assertion(&signing_key, "worker-key", "worker", audience),
+        audiences: vec![audience.to_string()],
+    };
 
-    let err = service
-        .initiate_browser_login(realm_id, build_oidc_request("client", "http://localhost"))
+    service
+        .authenticate_client(&realm_id, &auth)
         .await
-        .unwrap_err();
-
-    match err {
-        Error::NotFound(message) => assert!(message.contains("Flow version not found")),
-        other => panic!("unexpected error: {:?}", other),
+        .expect("first use");
+    match service.authenticate_client(&realm_id, &auth).await {
+        Err(Error::OidcInvalidClient(message)) => assert!(message.contains("already been used")),
+        Err(other) => panic!("unexpected error: {:?}", other),
+        Ok(_) => panic!("expected replay to be rejected"),
     }
 }
 
-#[tokio::test]
-async fn initiate_browser_login_requires_pkce_for_public_clients() {
-    let realm_id = Uuid::new_v4();
-    let flow_id = Uuid::new_v4();
-
-    let oidc_repo = Arc::new(TestOidcRepo::default());
-    oidc_repo.set_client(Some(build_client(
-        realm_id,
-        "client",
-        vec!["http://localhost"],
-    )));
-
-    let realm_repo = Arc::new(TestRealmRepo::default());
-    let mut realm = base_realm();
-    realm.id = realm_id;
-    realm.browser_flow_id = Some(flow_id.to_string());
-    realm_repo.set_realm(Some(realm));
+fn es256_jwk(key: &p256::ecdsa::SigningKey, kid: &str) -> serde_json::Value {
+    let point = key.verifying_key().to_encoded_point(false);
+    json!({
+        "kty": "EC",
+        "crv": "P-256",
+        "kid": kid,
+        "alg": "ES256",
+        "use": "sig",
+        "x": URL_SAFE_NO_PAD.encode(point.x().unwrap()),
+        "y": URL_SAFE_NO_PAD.encode(point.y().unwrap()),
+    })
+}
 
-    let version_id = Uuid::new_v4();
-    let flow_store = TestFlowStore::default();
-    flow_store.set_active_version(flow_id, Some(version_id.to_string()));
-    let execution_plan = json!({
-        "start_node_id": "success",
-        "nodes": {
-            "success": {
-                "id": "success",
-                "step_type": "terminal",
-                "next": {},
-                "config": { "is_failure": false }
-            }
-        }
+fn es256_assertion(
+    key: &p256::ecdsa::SigningKey,
+    kid: &str,
+    client_id: &str,
+    audience: &str,
+) -> String {
+    use p256::pkcs8::EncodePrivateKey;
+
+    let der = key.to_pkcs8_der().unwrap();
+    let mut header = jsonwebtoken::Header::new(jsonwebtoken::Algorithm::ES256);
+    header.kid = Some(kid.to_string());
+    let claims = json!({
+        "iss": client_id,
+        "sub": client_id,
+        "aud": audience,
+        "jti": Uuid::new_v4().to_string(),
+        "exp": (Utc::now() + Duration::minutes(1)).timestamp(),
     });
-    flow_store.set_version(&version_id.to_string(), &execution_plan.to_string());
+    jsonwebtoken::encode(
+        &header,
+        &claims,
+        &jsonwebtoken::EncodingKey::from_ec_der(der.as_bytes()),
+    )
+    .unwrap()
+}
 
+#[tokio::test]
+async fn client_credentials_grant_rejects_unregistered_scope() {
     let service = build_service(
-        oidc_repo,
+        Arc::new(TestOidcRepo::default()),
         Arc::new(TestAuthSessionRepo::default()),
-        Arc::new(flow_store),
-        realm_repo,
+        Arc::new(TestFlowStore::default()),
+        Arc::new(TestRealmRepo::default()),
         Arc::new(TestUserRepo::default()),
         Arc::new(TestSessionRepo::default()),
         Arc::new(TestTokenService::default()),
     );
+    let client = build_confidential_client(Uuid::new_v4(), "backend", "s3cret");
 
-    let err = service
-        .initiate_browser_login(
-            realm_id,
-            build_oidc_request_without_pkce("client", "http://localhost"),
-        )
+    match service
+        .client_credentials_grant(&client, Some("orders:write"))
         .await
-        .unwrap_err();
-
-    match err {
-        Error::OidcInvalidRequest(message) => assert!(message.contains("PKCE")),
-        other => panic!("unexpected error: {:?}", other),
+    {
+        Err(Error::OidcInvalidScope(_)) => {}
+        Err(other) => panic!("unexpected error: {:?}", other),
+        Ok(_) => panic!("expected error"),
     }
 }
 
 #[tokio::test]
-async fn initiate_browser_login_allows_missing_pkce_when_disabled() {
-    let realm_id = Uuid::new_v4();
-    let flow_id = Uuid::new_v4();
+async fn client_credentials_grant_issues_client_token_without_refresh() {
+    let session_repo = Arc::new(TestSessionRepo::default());
+    let token_service = Arc::new(TestTokenService::default());
 
-    let oidc_repo = Arc::new(TestOidcRepo::default());
-    oidc_repo.set_client(Some(build_client(
-        realm_id,
-        "client",
-        vec!["http://localhost"],
-    )));
+    let service = build_service(
+        Arc::new(TestOidcRepo::default()),
+        Arc::new(TestAuthSessionRepo::default()),
+        Arc::new(TestFlowStore::default()),
+        Arc::new(TestRealmRepo::default()),
+        Arc::new(TestUserRepo::default()),
+        session_repo.clone(),
+        token_service.clone(),
+    );
+    let client = build_confidential_client(Uuid::new_v4(), "backend", "s3cret");
 
-    let flow_store = TestFlowStore::default();
-    let version_id = Uuid::new_v4();
-    flow_store.set_active_version(flow_id, Some(version_id.to_string()));
-    let execution_plan = json!({
-        "start_node_id": "success",
-        "nodes": {
-            "success": {
-                "id": "success",
-                "step_type": "terminal",
-                "next": {},
-                "config": { "is_failure": false }
-            }
-        }
-    });
-    flow_store.set_version(&version_id.to_string(), &execution_plan.to_string());
+    let response = service
+        .client_credentials_grant(&client, Some("orders:read"))
+        .await
+        .expect("client credentials grant");
+
+    assert_eq!(response.access_token, "client-access-token");
+    assert_eq!(response.scope.as_deref(), Some("orders:read"));
+    assert!(response.id_token.is_none());
+    assert!(response.refresh_token.is_none());
+    assert!(session_repo.saved_tokens().is_empty());
+    assert_eq!(
+        token_service.client_tokens.lock().unwrap().clone(),
+        vec!["backend".to_string()]
+    );
+}
 
+#[tokio::test]
+async fn refresh_token_grant_rejects_token_issued_to_other_client() {
+    let realm = base_realm();
+    let oidc_repo = Arc::new(TestOidcRepo::default());
+    let client = build_client(realm.id, "mobile-app", vec!["http://localhost"]);
+    oidc_repo.set_client(Some(client.clone()));
     let realm_repo = Arc::new(TestRealmRepo::default());
-    let mut realm = base_realm();
-    realm.id = realm_id;
-    realm.browser_flow_id = Some(flow_id.to_string());
-    realm.pkce_required_public_clients = false;
-    realm_repo.set_realm(Some(realm));
+    realm_repo.set_realm(Some(realm.clone()));
+    let session_repo = Arc::new(TestSessionRepo::default());
+    let token = RefreshToken::new(
+        Uuid::new_v4(),
+        realm.id,
+        Some("other-app".to_string()),
+        Duration::minutes(5),
+    );
+    session_repo.save(&token).await.unwrap();
 
     let service = build_service(
         oidc_repo,
         Arc::new(TestAuthSessionRepo::default()),
-        Arc::new(flow_store),
+        Arc::new(TestFlowStore::default()),
         realm_repo,
         Arc::new(TestUserRepo::default()),
-        Arc::new(TestSessionRepo::default()),
+        session_repo.clone(),
         Arc::new(TestTokenService::default()),
     );
 
-    let session = service
-        .initiate_browser_login(
-            realm_id,
-            build_oidc_request_without_pkce("client", "http://localhost"),
-        )
+    match service
+        .refresh_token_grant(&client, &token.id.to_string())
         .await
-        .expect("login should succeed");
+    {
+        Err(Error::OidcInvalidGrant(_)) => {}
+        Err(other) => panic!("unexpected error: {:?}", other),
+        Ok(_) => panic!("expected error"),
+    }
 
-    assert_eq!(session.realm_id, realm_id);
+    // The other client's family must be left untouched.
+    let stored = session_repo.find_by_id(&token.id).await.unwrap();
+    assert!(stored.is_some());
 }
 
 #[tokio::test]
-async fn initiate_browser_login_rejects_corrupt_execution_artifact() {
-    let realm_id = Uuid::new_v4();
-    let flow_id = Uuid::new_v4();
-    let version_id = Uuid::new_v4();
-
+async fn refresh_token_grant_rotates_and_revokes_family_on_reuse() {
+    let realm = base_realm();
     let oidc_repo = Arc::new(TestOidcRepo::default());
-    oidc_repo.set_client(Some(build_client(
-        realm_id,
-        "client",
-        vec!["http://localhost"],
-    )));
-
-    let flow_store = Arc::new(TestFlowStore::default());
-    flow_store.set_active_version(flow_id, Some(version_id.to_string()));
-    flow_store.set_version(&version_id.to_string(), "not-json");
-
+    let client = build_client(realm.id, "mobile-app", vec!["http://localhost"]);
+    oidc_repo.set_client(Some(client.clone()));
     let realm_repo = Arc::new(TestRealmRepo::default());
-    let mut realm = base_realm();
-    realm.id = realm_id;
-    realm.browser_flow_id = Some(flow_id.to_string());
-    realm_repo.set_realm(Some(realm));
+    realm_repo.set_realm(Some(realm.clone()));
+    let user_repo = Arc::new(TestUserRepo::default());
+    let user = build_user(realm.id);
+    user_repo.insert(user.clone());
+    let session_repo = Arc::new(TestSessionRepo::default());
+    let token = RefreshToken::new(
+        user.id,
+        realm.id,
+        Some("mobile-app".to_string()),
+        Duration::minutes(5),
+    );
+    session_repo.save(&token).await.unwrap();
 
     let service = build_service(
         oidc_repo,
         Arc::new(TestAuthSessionRepo::default()),
-        flow_store,
+        Arc::new(TestFlowStore::default()),
         realm_repo,
-        Arc::new(TestUserRepo::default()),
-        Arc::new(TestSessionRepo::default()),
+        user_repo,
+        session_repo.clone(),
         Arc::new(TestTokenService::default()),
     );
 
-    let err = service
-        .initiate_browser_login(realm_id, build_oidc_request("client", "http://localhost"))
+    let (response, rotated) = service
+        .refresh_token_grant(&client, &token.id.to_string())
         .await
-        .unwrap_err();
+        .expect("refresh grant");
+    assert_eq!(rotated.family_id, token.family_id);
+    assert_eq!(response.refresh_token, Some(rotated.id.to_string()));
+    assert_eq!(response.id_token.as_deref(), Some("id-token"));
 
-    match err {
-        Error::Unexpected(message) => assert!(format!("{:?}", message).contains("Corrupt")),
-        other => panic!("unexpected error: {:?}", other),
+    match service
+        .refresh_token_grant(&client, &token.id.to_string())
+        .await
+    {
+        Err(Error::OidcInvalidGrant(_)) => {}
+        Err(other) => panic!("unexpected error: {:?}", other),
+        Ok(_) => panic!("expected reuse to be rejected"),
     }
+    assert!(session_repo
+        .find_by_id(&rotated.id)
+        .await
+        .unwrap()
+        .is_none());
 }
 
 #[tokio::test]
-async fn initiate_browser_login_creates_session() {
-    let realm_id = Uuid::new_v4();
-    let flow_id = Uuid::new_v4();
-    let version_id = Uuid::new_v4();
-
-    let oidc_repo = Arc::new(TestOidcRepo::default());
-    oidc_repo.set_client(Some(build_client(
-        realm_id,
-        "client",
-        vec!["http://localhost"],
-    )));
-
-    let flow_store = Arc::new(TestFlowStore::default());
-    flow_store.set_latest_version(flow_id, Some(version_id.to_string()));
-    flow_store.set_version(
-        &version_id.to_string(),
-        &serde_json::to_string(&ExecutionPlan {
-            start_node_id: "start".to_string(),
-            nodes: HashMap::new(),
-        })
-        .unwrap(),
-    );
-
-    let realm_repo = Arc::new(TestRealmRepo::default());
-    let mut realm = base_realm();
-    realm.id = realm_id;
-    realm.browser_flow_id = Some(flow_id.to_string());
-    realm_repo.set_realm(Some(realm));
-
-    let auth_session_repo = Arc::new(TestAuthSessionRepo::default());
-
+async fn introspect_token_rejects_public_client() {
+    let realm = base_realm();
+    let client = build_client(realm.id, "spa", vec!["http://localhost"]);
     let service = build_service(
-        oidc_repo,
-        auth_session_repo.clone(),
-        flow_store,
-        realm_repo,
+        Arc::new(TestOidcRepo::default()),
+        Arc::new(TestAuthSessionRepo::default()),
+        Arc::new(TestFlowStore::default()),
+        Arc::new(TestRealmRepo::default()),
         Arc::new(TestUserRepo::default()),
         Arc::new(TestSessionRepo::default()),
         Arc::new(TestTokenService::default()),
     );
 
-    let session = service
-        .initiate_browser_login(realm_id, build_oidc_request("client", "http://localhost"))
+    match service
+        .introspect_token(&client, &Uuid::new_v4().to_string())
         .await
-        .expect("expected session");
-
-    assert_eq!(session.flow_version_id, version_id);
-    let created = auth_session_repo.created_sessions();
-    assert_eq!(created.len(), 1);
-    let context = created[0].context.get("oidc").cloned();
-    assert!(context
-        .and_then(|value| serde_json::from_value::<OidcContext>(value).ok())
-        .is_some());
+    {
+        Err(Error::OidcInvalidClient(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match service.revoke_token(&client, "anything").await {
+        Err(Error::OidcInvalidClient(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
 }
 
-#[tokio::test]
-async fn exchange_code_for_token_rejects_missing_code() {
+#[tokio::test]
+async fn introspect_token_reports_refresh_token_until_revoked() {
+    let realm = base_realm();
+    let client = build_confidential_client(realm.id, "resource-server", "secret");
+    let session_repo = Arc::new(TestSessionRepo::default());
+    let user_id = Uuid::new_v4();
+    let token = RefreshToken::new(
+        user_id,
+        realm.id,
+        Some("mobile-app".to_string()),
+        Duration::minutes(5),
+    );
+    session_repo.save(&token).await.unwrap();
+    let foreign = RefreshToken::new(
+        user_id,
+        Uuid::new_v4(),
+        Some("mobile-app".to_string()),
+        Duration::minutes(5),
+    );
+    session_repo.save(&foreign).await.unwrap();
+
     let service = build_service(
         Arc::new(TestOidcRepo::default()),
         Arc::new(TestAuthSessionRepo::default()),
         Arc::new(TestFlowStore::default()),
         Arc::new(TestRealmRepo::default()),
         Arc::new(TestUserRepo::default()),
-        Arc::new(TestSessionRepo::default()),
+        session_repo.clone(),
         Arc::new(TestTokenService::default()),
     );
 
-    match service
-        .exchange_code_for_token("missing", "http://localhost", "verifier", None, None)
+    let introspection = service
+        .introspect_token(&client, &token.id.to_string())
         .await
-    {
-        Err(Error::OidcInvalidCode) => {}
-        Err(other) => panic!("unexpected error: {:?}", other),
-        Ok(_) => panic!("expected error"),
-    }
+        .expect("introspect");
+    assert!(introspection.active);
+    assert_eq!(introspection.sub, Some(user_id.to_string()));
+    assert_eq!(introspection.client_id.as_deref(), Some("mobile-app"));
+    assert_eq!(introspection.exp, Some(token.expires_at.timestamp()));
+
+    // Tokens from another realm look exactly like unknown ones.
+    let introspection = service
+        .introspect_token(&client, &foreign.id.to_string())
+        .await
+        .expect("introspect");
+    assert!(!introspection.active);
+
+    session_repo.revoke_family(&token.family_id).await.unwrap();
+    let introspection = service
+        .introspect_token(&client, &token.id.to_string())
+        .await
+        .expect("introspect");
+    assert!(!introspection.active);
+    assert_eq!(
+        serde_json::to_value(&introspection).unwrap(),
+        json!({ "active": false })
+    );
 }
 
 #[tokio::test]
-async fn exchange_code_for_token_rejects_invalid_pkce() {
-    let oidc_repo = Arc::new(TestOidcRepo::default());
-    oidc_repo.insert_auth_code(AuthCode {
-        code: "code".to_string(),
-        user_id: Uuid::new_v4(),
-        client_id: "client".to_string(),
-        redirect_uri: "http://localhost".to_string(),
-        nonce: None,
-        code_challenge: Some("expected".to_string()),
-        code_challenge_method: "S256".to_string(),
-        expires_at: Utc::now() + Duration::seconds(60),
-    });
+async fn revoke_token_revokes_family_of_own_tokens_only() {
+    let realm = base_realm();
+    let client = build_confidential_client(realm.id, "mobile-app", "secret");
+    let session_repo = Arc::new(TestSessionRepo::default());
+    let own = RefreshToken::new(
+        Uuid::new_v4(),
+        realm.id,
+        Some("mobile-app".to_string()),
+        Duration::minutes(5),
+    );
+    let sibling = RefreshToken {
+        id: Uuid::new_v4(),
+        ..own.clone()
+    };
+    let other = RefreshToken::new(
+        Uuid::new_v4(),
+        realm.id,
+        Some("other-app".to_string()),
+        Duration::minutes(5),
+    );
+    for token in [&own, &sibling, &other] {
+        session_repo.save(token).await.unwrap();
+    }
 
     let service = build_service(
-        oidc_repo,
+        Arc::new(TestOidcRepo::default()),
         Arc::new(TestAuthSessionRepo::default()),
         Arc::new(TestFlowStore::default()),
         Arc::new(TestRealmRepo::default()),
         Arc::new(TestUserRepo::default()),
-        Arc::new(TestSessionRepo::default()),
+        session_repo.clone(),
         Arc::new(TestTokenService::default()),
     );
 
-    match service
-        .exchange_code_for_token("code", "http://localhost", "bad", None, None)
+    match service.revoke_token(&client, &other.id.to_string()).await {
+        Err(Error::OidcUnauthorizedClient(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(session_repo.find_by_id(&other.id).await.unwrap().is_some());
+
+    service
+        .revoke_token(&client, &own.id.to_string())
         .await
-    {
-        Err(Error::OidcInvalidCode) => {}
-        Err(other) => panic!("unexpected error: {:?}", other),
-        Ok(_) => panic!("expected error"),
+        .expect("revoke");
+    assert!(session_repo.find_by_id(&own.id).await.unwrap().is_none());
+    assert!(session_repo
+        .find_by_id(&sibling.id)
+        .await
+        .unwrap()
+        .is_none());
+
+    // Unknown and malformed tokens are not an error (RFC 7009, Section 2.2).
+    service
+        .revoke_token(&client, &Uuid::new_v4().to_string())
+        .await
+        .expect("unknown token");
+    service
+        .revoke_token(&client, "not-a-token")
+        .await
+        .expect("malformed token");
+}
+
+fn exchange_client(realm_id: Uuid, policy: serde_json::Value) -> OidcClient {
+    let mut client = build_confidential_client(realm_id, "gateway", "secret");
+    client.token_exchange_policy = Some(policy.to_string());
+    client
+}
+
+fn exchange_request(audience: &str, scope: Option<&str>) -> TokenExchangeRequest {
+    TokenExchangeRequest {
+        subject_token: "subject-token".to_string(),
+        subject_token_type: ACCESS_TOKEN_TYPE.to_string(),
+        audience: audience.to_string(),
+        scope: scope.map(str::to_string),
+        ..TokenExchangeRequest::default()
+    }
+}
+
+fn subject_claims(session: &RefreshToken, scope: Option<&str>) -> AccessTokenClaims {
+    AccessTokenClaims {
+        sub: session.user_id,
+        sid: session.id,
+        perms: HashSet::new(),
+        roles: Vec::new(),
+        groups: Vec::new(),
+        exp: (Utc::now() + Duration::minutes(5)).timestamp() as usize,
+        iat: Utc::now().timestamp() as usize,
+        azp: None,
+        scope: scope.map(str::to_string),
+        aud: Some("gateway".to_string()),
+        act: Some(ActorClaim {
+            sub: "edge".to_string(),
+            act: None,
+        }),
+        extra: Map::new(),
     }
 }
 
 #[tokio::test]
-async fn exchange_code_for_token_rejects_redirect_mismatch() {
-    let verifier = "verifier";
+async fn token_exchange_grant_issues_token_for_allowed_audience() {
+    let realm = base_realm();
+    let session = RefreshToken::new(
+        Uuid::new_v4(),
+        realm.id,
+        Some("web-app".to_string()),
+        Duration::minutes(5),
+    );
+    let session_repo = Arc::new(TestSessionRepo::default());
+    session_repo.save(&session).await.unwrap();
     let oidc_repo = Arc::new(TestOidcRepo::default());
-    oidc_repo.insert_auth_code(AuthCode {
-        code: "code".to_string(),
-        user_id: Uuid::new_v4(),
-        client_id: "client".to_string(),
-        redirect_uri: "http://localhost".to_string(),
-        nonce: None,
-        code_challenge: Some(pkce_challenge(verifier)),
-        code_challenge_method: "S256".to_string(),
-        expires_at: Utc::now() + Duration::seconds(60),
-    });
+    oidc_repo.set_client(Some(build_client(realm.id, "orders-api", vec![])));
+    let token_service = Arc::new(TestTokenService::default());
+    token_service.set_access_claims(subject_claims(&session, None));
+    let audit_repo = Arc::new(TestAuditRepo::default());
 
-    let service = build_service(
+    let service = build_service_with_audit(
         oidc_repo,
         Arc::new(TestAuthSessionRepo::default()),
         Arc::new(TestFlowStore::default()),
         Arc::new(TestRealmRepo::default()),
         Arc::new(TestUserRepo::default()),
-        Arc::new(TestSessionRepo::default()),
-        Arc::new(TestTokenService::default()),
+        session_repo,
+        token_service.clone(),
+        audit_repo.clone(),
+    );
+    let client = exchange_client(
+        realm.id,
+        json!([{ "audience": "orders-api", "scopes": ["orders:read"] }]),
     );
 
-    match service
-        .exchange_code_for_token("code", "http://evil.invalid", verifier, None, None)
+    let response = service
+        .token_exchange_grant(
+            &client,
+            &exchange_request("orders-api", Some("orders:read")),
+        )
         .await
-    {
-        Err(Error::OidcInvalidRedirect(uri)) => {
-            assert_eq!(uri, "http://evil.invalid");
-        }
-        Err(other) => panic!("unexpected error: {:?}", other),
-        Ok(_) => panic!("expected error"),
-    }
+        .expect("token exchange");
+
+    assert_eq!(response.access_token, "exchanged-token");
+    assert_eq!(
+        response.issued_token_type.as_deref(),
+        Some(ACCESS_TOKEN_TYPE)
+    );
+    assert_eq!(response.scope.as_deref(), Some("orders:read"));
+    assert!(response.refresh_token.is_none());
+    assert_eq!(
+        token_service.exchanged.lock().unwrap().clone(),
+        vec![(
+            "gateway".to_string(),
+            "orders-api".to_string(),
+            Some("orders:read".to_string())
+        )]
+    );
+    assert_eq!(audit_repo.actions(), vec!["token_exchange_granted"]);
+    let event = audit_repo.events.lock().unwrap()[0].clone();
+    assert_eq!(event.actor_user_id, Some(session.user_id));
+    assert_eq!(event.metadata["delegation_chain"]["sub"], "edge");
 }
 
 #[tokio::test]
-async fn exchange_code_for_token_requires_user() {
-    let verifier = "verifier";
+async fn token_exchange_grant_enforces_policy_and_subject_scope() {
+    let realm = base_realm();
+    let session = RefreshToken::new(
+        Uuid::new_v4(),
+        realm.id,
+        Some("web-app".to_string()),
+        Duration::minutes(5),
+    );
+    let session_repo = Arc::new(TestSessionRepo::default());
+    session_repo.save(&session).await.unwrap();
     let oidc_repo = Arc::new(TestOidcRepo::default());
-    oidc_repo.insert_auth_code(AuthCode {
-        code: "code".to_string(),
-        user_id: Uuid::new_v4(),
-        client_id: "client".to_string(),
-        redirect_uri: "http://localhost".to_string(),
-        nonce: None,
-        code_challenge: Some(pkce_challenge(verifier)),
-        code_challenge_method: "S256".to_string(),
-        expires_at: Utc::now() + Duration::seconds(60),
-    });
+    oidc_repo.set_client(Some(build_client(realm.id, "orders-api", vec![])));
+    let token_service = Arc::new(TestTokenService::default());
+    token_service.set_access_claims(subject_claims(&session, Some("orders:read")));
+    let audit_repo = Arc::new(TestAuditRepo::default());
 
-    let service = build_service(
+    let service = build_service_with_audit(
         oidc_repo,
         Arc::new(TestAuthSessionRepo::default()),
         Arc::new(TestFlowStore::default()),
         Arc::new(TestRealmRepo::default()),
         Arc::new(TestUserRepo::default()),
-        Arc::new(TestSessionRepo::default()),
-        Arc::new(TestTokenService::default()),
+        session_repo.clone(),
+        token_service.clone(),
+        audit_repo.clone(),
+    );
+    let client = exchange_client(
+        realm.id,
+        json!([{ "audience": "orders-api", "scopes": ["orders:read", "orders:write"] }]),
     );
 
-    match service
-        .exchange_code_for_token("code", "http://localhost", verifier, None, None)
+    // Not in the policy.
+    let result = service
+        .token_exchange_grant(&client, &exchange_request("billing-api", None))
+        .await;
+    assert!(matches!(result, Err(Error::OidcInvalidTarget(_))));
+
+    // Allowed by the policy, but broader than the subject token.
+    let result = service
+        .token_exchange_grant(
+            &client,
+            &exchange_request("orders-api", Some("orders:write")),
+        )
+        .await;
+    assert!(matches!(result, Err(Error::OidcInvalidScope(_))));
+
+    // Public clients cannot exchange at all.
+    let mut public = client.clone();
+    public.token_endpoint_auth_method = TokenEndpointAuthMethod::None;
+    let result = service
+        .token_exchange_grant(&public, &exchange_request("orders-api", None))
+        .await;
+    assert!(matches!(result, Err(Error::OidcUnauthorizedClient(_))));
+
+    // A revoked subject session cannot be exchanged.
+    session_repo
+        .revoke_family(&session.family_id)
         .await
-    {
-        Err(Error::UserNotFound) => {}
-        Err(other) => panic!("unexpected error: {:?}", other),
-        Ok(_) => panic!("expected error"),
-    }
+        .unwrap();
+    let result = service
+        .token_exchange_grant(&client, &exchange_request("orders-api", None))
+        .await;
+    assert!(matches!(result, Err(Error::OidcInvalidRequest(_))));
+
+    assert!(token_service.exchanged.lock().unwrap().is_empty());
+    assert_eq!(audit_repo.actions(), vec!["token_exchange_denied"; 4]);
+}
+
+fn device_service(
+    realm: &crate::domain::realm::Realm,
+    user_repo: Arc<TestUserRepo>,
+    session_repo: Arc<TestSessionRepo>,
+    device_repo: Arc<TestDeviceRepo>,
+) -> OidcService {
+    let realm_repo = Arc::new(TestRealmRepo::default());
+    realm_repo.set_realm(Some(realm.clone()));
+    build_service_with_repos(
+        Arc::new(TestOidcRepo::default()),
+        Arc::new(TestAuthSessionRepo::default()),
+        Arc::new(TestFlowStore::default()),
+        realm_repo,
+        user_repo,
+        session_repo,
+        Arc::new(TestTokenService::default()),
+        Arc::new(TestAuditRepo::default()),
+        device_repo,
+        Arc::new(TestParRepo::default()),
+    )
 }
 
 #[tokio::test]
-async fn exchange_code_for_token_returns_tokens_and_deletes_code() {
-    let verifier = "verifier";
-    let user_id = Uuid::new_v4();
+async fn device_code_grant_reports_pending_and_slow_down() {
+    let realm = base_realm();
+    let device_repo = Arc::new(TestDeviceRepo::default());
+    let service = device_service(
+        &realm,
+        Arc::new(TestUserRepo::default()),
+        Arc::new(TestSessionRepo::default()),
+        device_repo.clone(),
+    );
+    let client = build_client(realm.id, "tv-app", vec![]);
 
-    let oidc_repo = Arc::new(TestOidcRepo::default());
-    oidc_repo.insert_auth_code(AuthCode {
-        code: "code".to_string(),
-        user_id,
-        client_id: "client".to_string(),
-        redirect_uri: "http://localhost".to_string(),
-        nonce: None,
-        code_challenge: Some(pkce_challenge(verifier)),
-        code_challenge_method: "S256".to_string(),
-        expires_at: Utc::now() + Duration::seconds(60),
-    });
+    let authorization = service
+        .start_device_authorization(&client, Some("openid"))
+        .await
+        .expect("device authorization");
+    assert_eq!(authorization.interval_secs, 5);
+    assert_eq!(authorization.scope.as_deref(), Some("openid"));
+
+    let result = service
+        .device_code_grant(&client, &authorization.device_code, None, None)
+        .await;
+    assert!(matches!(result, Err(Error::OidcAuthorizationPending)));
+
+    // Polling again right away is too fast, and the interval grows.
+    let result = service
+        .device_code_grant(&client, &authorization.device_code, None, None)
+        .await;
+    assert!(matches!(result, Err(Error::OidcSlowDown)));
+    assert_eq!(
+        device_repo
+            .get(&authorization.device_code)
+            .expect("stored")
+            .interval_secs,
+        10
+    );
 
-    let user_repo = Arc::new(TestUserRepo::default());
-    user_repo.insert(User {
-        id: user_id,
-        realm_id: Uuid::new_v4(),
-        username: "user".to_string(),
-        first_name: None,
-        last_name: None,
-        hashed_password: "hash".to_string(),
-        public_metadata_json: crate::domain::user::EMPTY_METADATA_JSON.to_string(),
-        private_metadata_json: crate::domain::user::EMPTY_METADATA_JSON.to_string(),
-        unsafe_metadata_json: crate::domain::user::EMPTY_METADATA_JSON.to_string(),
-        force_password_reset: false,
-        password_login_disabled: false,
-        created_at: Some(Utc::now()),
-        updated_at: None,
-        last_sign_in_at: None,
-        locked_until: None,
-        banned_at: None,
-    });
+    let other_client = build_client(realm.id, "other-app", vec![]);
+    let result = service
+        .device_code_grant(&other_client, &authorization.device_code, None, None)
+        .await;
+    assert!(matches!(result, Err(Error::OidcInvalidGrant(_))));
 
-    let realm_repo = Arc::new(TestRealmRepo::default());
-    let realm = base_realm();
-    realm_repo.set_realm(Some(realm));
+    let result = service
+        .start_device_authorization(&client, Some("admin"))
+        .await;
+    assert!(matches!(result, Err(Error::OidcInvalidScope(_))));
+}
 
+#[tokio::test]
+async fn device_code_grant_issues_tokens_once_after_approval() {
+    let realm = base_realm();
+    let user = build_user(realm.id);
+    let user_repo = Arc::new(TestUserRepo::default());
+    user_repo.insert(user.clone());
     let session_repo = Arc::new(TestSessionRepo::default());
-    let token_service = Arc::new(TestTokenService::default());
+    let device_repo = Arc::new(TestDeviceRepo::default());
+    let service = device_service(&realm, user_repo, session_repo.clone(), device_repo.clone());
+    let client = build_client(realm.id, "tv-app", vec![]);
 
-    let service = build_service(
-        oidc_repo.clone(),
+    let approved = service
+        .start_device_authorization(&client, Some("openid"))
+        .await
+        .expect("device authorization");
+    let typed = crate::domain::oidc::format_user_code(&approved.user_code).to_lowercase();
+    let pending = service
+        .find_pending_device_authorization(realm.id, &typed)
+        .await
+        .expect("pending by typed user code");
+    assert_eq!(pending.device_code, approved.device_code);
+
+    let result = service
+        .approve_device_authorization(Uuid::new_v4(), &approved.device_code, user.id)
+        .await;
+    assert!(matches!(result, Err(Error::OidcInvalidRequest(_))));
+    service
+        .approve_device_authorization(realm.id, &approved.device_code, user.id)
+        .await
+        .expect("approve");
+
+    let (response, refresh_token) = service
+        .device_code_grant(&client, &approved.device_code, None, None)
+        .await
+        .expect("device code grant");
+    assert_eq!(response.scope.as_deref(), Some("openid"));
+    assert_eq!(response.refresh_token, Some(refresh_token.id.to_string()));
+    assert_eq!(refresh_token.client_id.as_deref(), Some("tv-app"));
+    assert_eq!(session_repo.saved_tokens().len(), 1);
+
+    let result = service
+        .device_code_grant(&client, &approved.device_code, None, None)
+        .await;
+    assert!(matches!(result, Err(Error::OidcInvalidGrant(_))));
+
+    let denied = service
+        .start_device_authorization(&client, None)
+        .await
+        .expect("device authorization");
+    service
+        .deny_device_authorization(realm.id, &denied.user_code)
+        .await
+        .expect("deny");
+    let result = service
+        .device_code_grant(&client, &denied.device_code, None, None)
+        .await;
+    assert!(matches!(result, Err(Error::OidcAccessDenied(_))));
+
+    let expired = service
+        .start_device_authorization(&client, None)
+        .await
+        .expect("device authorization");
+    device_repo.update(&expired.device_code, |auth| {
+        auth.expires_at = Utc::now() - Duration::seconds(1);
+    });
+    let result = service
+        .device_code_grant(&client, &expired.device_code, None, None)
+        .await;
+    assert!(matches!(result, Err(Error::OidcExpiredToken)));
+    let result = service
+        .find_pending_device_authorization(realm.id, &expired.user_code)
+        .await;
+    assert!(matches!(result, Err(Error::OidcInvalidRequest(_))));
+}
+
+const PAR_AUDIENCE: &str = "http://localhost/api/realms/master/oidc/par";
+
+fn par_service(
+    realm: &crate::domain::realm::Realm,
+    client: &OidcClient,
+    par_repo: Arc<TestParRepo>,
+) -> OidcService {
+    let oidc_repo = Arc::new(TestOidcRepo::default());
+    oidc_repo.set_client(Some(client.clone()));
+    let realm_repo = Arc::new(TestRealmRepo::default());
+    realm_repo.set_realm(Some(realm.clone()));
+    build_service_with_repos(
+        oidc_repo,
         Arc::new(TestAuthSessionRepo::default()),
         Arc::new(TestFlowStore::default()),
         realm_repo,
-        user_repo,
-        session_repo.clone(),
-        token_service,
+        Arc::new(TestUserRepo::default()),
+        Arc::new(TestSessionRepo::default()),
+        Arc::new(TestTokenService::default()),
+        Arc::new(TestAuditRepo::default()),
+        Arc::new(TestDeviceRepo::default()),
+        par_repo,
+    )
+}
+
+fn authorization_params(client_id: &str, redirect_uri: &str) -> AuthorizationRequestParams {
+    AuthorizationRequestParams {
+        client_id: Some(client_id.to_string()),
+        redirect_uri: Some(redirect_uri.to_string()),
+        response_type: Some("code".to_string()),
+        scope: Some("openid".to_string()),
+        state: Some("state".to_string()),
+        code_challenge: Some(pkce_challenge("par-verifier")),
+        code_challenge_method: Some("S256".to_string()),
+        ..Default::default()
+    }
+}
+
+fn es256_request_object(
+    key: &p256::ecdsa::SigningKey,
+    kid: &str,
+    client_id: &str,
+    audience: &str,
+    extra: serde_json::Value,
+) -> String {
+    use p256::pkcs8::EncodePrivateKey;
+
+    let der = key.to_pkcs8_der().unwrap();
+    let mut header = jsonwebtoken::Header::new(jsonwebtoken::Algorithm::ES256);
+    header.kid = Some(kid.to_string());
+    let mut claims = json!({
+        "iss": client_id,
+        "aud": audience,
+        "client_id": client_id,
+        "redirect_uri": "http://localhost/callback",
+        "response_type": "code",
+        "scope": "openid",
+        "state": "signed-state",
+        "code_challenge": pkce_challenge("jar-verifier"),
+        "code_challenge_method": "S256",
+        "exp": (Utc::now() + Duration::minutes(1)).timestamp(),
+    });
+    for (name, value) in extra.as_object().unwrap() {
+        claims[name] = value.clone();
+    }
+    jsonwebtoken::encode(
+        &header,
+        &claims,
+        &jsonwebtoken::EncodingKey::from_ec_der(der.as_bytes()),
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn pushed_request_uri_is_redeemed_once_by_its_client() {
+    let realm = base_realm();
+    let client = build_client(realm.id, "web-app", vec!["http://localhost/callback"]);
+    let par_repo = Arc::new(TestParRepo::default());
+    let service = par_service(&realm, &client, par_repo.clone());
+
+    let pushed = service
+        .push_authorization_request(
+            &client,
+            authorization_params("web-app", "http://localhost/callback"),
+            &[PAR_AUDIENCE.to_string()],
+        )
+        .await
+        .expect("push");
+    assert!(pushed.request_uri.starts_with(PAR_REQUEST_URI_PREFIX));
+    assert_eq!(
+        (pushed.expires_at - pushed.created_at).num_seconds(),
+        super::PAR_REQUEST_URI_TTL_SECS
     );
 
-    let (token_response, refresh_token) = service
-        .exchange_code_for_token("code", "http://localhost", verifier, None, None)
+    let by_reference = |client_id: &str| AuthorizationRequestParams {
+        client_id: Some(client_id.to_string()),
+        request_uri: Some(pushed.request_uri.clone()),
+        ..Default::default()
+    };
+
+    let result = service
+        .resolve_authorization_request(realm.id, by_reference("other-app"), &[])
+        .await;
+    assert!(matches!(result, Err(Error::OidcInvalidRequestUri(_))));
+
+    let request = service
+        .resolve_authorization_request(realm.id, by_reference("web-app"), &[])
         .await
-        .expect("expected success");
+        .expect("resolve");
+    assert_eq!(request.redirect_uri, "http://localhost/callback");
+    assert_eq!(request.state.as_deref(), Some("state"));
+
+    let result = service
+        .resolve_authorization_request(realm.id, by_reference("web-app"), &[])
+        .await;
+    assert!(matches!(result, Err(Error::OidcInvalidRequestUri(_))));
+}
 
-    assert_eq!(token_response.access_token, "access-token");
-    assert_eq!(token_response.id_token, "id-token");
-    assert_eq!(token_response.token_type, "Bearer");
-    assert!(token_response.expires_in > 0);
+#[tokio::test]
+async fn push_authorization_request_validates_like_authorize() {
+    let realm = base_realm();
+    let client = build_client(realm.id, "web-app", vec!["http://localhost/callback"]);
+    let par_repo = Arc::new(TestParRepo::default());
+    let service = par_service(&realm, &client, par_repo.clone());
+    let audiences = [PAR_AUDIENCE.to_string()];
+
+    let result = service
+        .push_authorization_request(
+            &client,
+            authorization_params("web-app", "http://evil.example/callback"),
+            &audiences,
+        )
+        .await;
+    assert!(matches!(result, Err(Error::OidcInvalidRedirect(_))));
+
+    let mut without_pkce = authorization_params("web-app", "http://localhost/callback");
+    without_pkce.code_challenge = None;
+    without_pkce.code_challenge_method = None;
+    let result = service
+        .push_authorization_request(&client, without_pkce, &audiences)
+        .await;
+    assert!(matches!(result, Err(Error::OidcInvalidRequest(_))));
+
+    let mut nested = authorization_params("web-app", "http://localhost/callback");
+    nested.request_uri = Some(format!("{}abc", PAR_REQUEST_URI_PREFIX));
+    let result = service
+        .push_authorization_request(&client, nested, &audiences)
+        .await;
+    assert!(matches!(result, Err(Error::OidcInvalidRequest(_))));
+
+    assert!(par_repo.requests.lock().unwrap().is_empty());
+}
 
-    assert_eq!(refresh_token.user_id, user_id);
-    assert_eq!(session_repo.saved_tokens().len(), 1);
-    assert_eq!(oidc_repo.deleted_codes(), vec!["code".to_string()]);
+#[tokio::test]
+async fn client_requiring_par_cannot_use_query_parameters() {
+    let realm = base_realm();
+    let mut client = build_client(realm.id, "web-app", vec!["http://localhost/callback"]);
+    client.require_pushed_authorization_requests = true;
+    let service = par_service(&realm, &client, Arc::new(TestParRepo::default()));
+
+    let result = service
+        .resolve_authorization_request(
+            realm.id,
+            authorization_params("web-app", "http://localhost/callback"),
+            &[],
+        )
+        .await;
+    assert!(matches!(result, Err(Error::OidcInvalidRequest(_))));
+
+    let pushed = service
+        .push_authorization_request(
+            &client,
+            authorization_params("web-app", "http://localhost/callback"),
+            &[],
+        )
+        .await
+        .expect("push");
+    let request = service
+        .resolve_authorization_request(
+            realm.id,
+            AuthorizationRequestParams {
+                client_id: Some("web-app".to_string()),
+                request_uri: Some(pushed.request_uri),
+                ..Default::default()
+            },
+            &[],
+        )
+        .await
+        .expect("resolve pushed request");
+    assert_eq!(request.client_id, "web-app");
+}
+
+#[tokio::test]
+async fn signed_request_object_is_verified_against_client_jwks() {
+    let realm = base_realm();
+    let key = p256::ecdsa::SigningKey::from_bytes((&[7u8; 32]).into()).unwrap();
+    let mut client = build_client(realm.id, "web-app", vec!["http://localhost/callback"]);
+    client.jwks = Some(json!({ "keys": [es256_jwk(&key, "request-key")] }).to_string());
+    let service = par_service(&realm, &client, Arc::new(TestParRepo::default()));
+    let audiences = [PAR_AUDIENCE.to_string()];
+    let with_request = |request: String| AuthorizationRequestParams {
+        client_id: Some("web-app".to_string()),
+        // Query parameters next to a request object are ignored.
+        state: Some("query-state".to_string()),
+        request: Some(request),
+        ..Default::default()
+    };
+
+    let request = service
+        .resolve_authorization_request(
+            realm.id,
+            with_request(es256_request_object(
+                &key,
+                "request-key",
+                "web-app",
+                PAR_AUDIENCE,
+                json!({}),
+            )),
+            &audiences,
+        )
+        .await
+        .expect("verified request object");
+    assert_eq!(request.state.as_deref(), Some("signed-state"));
+    assert_eq!(request.redirect_uri, "http://localhost/callback");
+
+    let other_key = p256::ecdsa::SigningKey::from_bytes((&[9u8; 32]).into()).unwrap();
+    let rejected = [
+        es256_request_object(&key, "request-key", "web-app", "http://other", json!({})),
+        es256_request_object(
+            &other_key,
+            "request-key",
+            "web-app",
+            PAR_AUDIENCE,
+            json!({}),
+        ),
+        es256_request_object(&key, "unknown-key", "web-app", PAR_AUDIENCE, json!({})),
+        es256_request_object(
+            &key,
+            "request-key",
+            "web-app",
+            PAR_AUDIENCE,
+            json!({ "client_id": "other-app" }),
+        ),
+        es256_request_object(
+            &key,
+            "request-key",
+            "web-app",
+            PAR_AUDIENCE,
+            json!({ "request_uri": format!("{}abc", PAR_REQUEST_URI_PREFIX) }),
+        ),
+    ];
+    for request_object in rejected {
+        let result = service
+            .resolve_authorization_request(realm.id, with_request(request_object), &audiences)
+            .await;
+        assert!(
+            matches!(result, Err(Error::OidcInvalidRequestObject(_))),
+            "unexpected result: {:?}",
+            result
+        );
+    }
 }
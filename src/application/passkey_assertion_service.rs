@@ -1,11 +1,13 @@
 use crate::application::audit_service::AuditService;
 use crate::config::Settings;
 use crate::domain::audit::NewAuditEvent;
+use crate::domain::events::{DomainEvent, PasskeyEnrolled};
 use crate::domain::passkey_challenge::{PasskeyChallenge, PasskeyChallengeKind};
 use crate::domain::passkey_runtime::{PASSKEY_REAUTH_AT_KEY, PASSKEY_REAUTH_USER_ID_KEY};
 use crate::domain::realm_passkey_settings::RealmPasskeySettings;
 use crate::error::{Error, Result};
 use crate::ports::auth_session_repository::AuthSessionRepository;
+use crate::ports::event_bus::EventPublisher;
 use crate::ports::passkey_challenge_repository::PasskeyChallengeRepository;
 use crate::ports::passkey_credential_repository::PasskeyCredentialRepository;
 use crate::ports::realm_passkey_settings_repository::RealmPasskeySettingsRepository;
@@ -89,6 +91,7 @@ pub struct PasskeyAssertionService {
     credential_repo: Arc<dyn PasskeyCredentialRepository>,
     passkey_settings_repo: Arc<dyn RealmPasskeySettingsRepository>,
     audit_service: Arc<AuditService>,
+    event_publisher: Arc<dyn EventPublisher>,
     settings: Settings,
 }
 
@@ -102,6 +105,7 @@ impl PasskeyAssertionService {
         credential_repo: Arc<dyn PasskeyCredentialRepository>,
         passkey_settings_repo: Arc<dyn RealmPasskeySettingsRepository>,
         audit_service: Arc<AuditService>,
+        event_publisher: Arc<dyn EventPublisher>,
         settings: Settings,
     ) -> Self {
         Self {
@@ -112,6 +116,7 @@ impl PasskeyAssertionService {
             credential_repo,
             passkey_settings_repo,
             audit_service,
+            event_publisher,
             settings,
         }
     }
@@ -604,6 +609,14 @@ impl PasskeyAssertionService {
             }),
         )
         .await;
+        self.event_publisher
+            .publish(DomainEvent::PasskeyEnrolled(PasskeyEnrolled {
+                realm_id: request.realm_id,
+                user_id,
+                credential_id: credential.id,
+                friendly_name: credential.friendly_name.clone(),
+            }))
+            .await;
 
         Ok(VerifyEnrollmentResult {
             auth_session_id: consumed.auth_session_id,
@@ -0,0 +1,327 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
+use uuid::Uuid;
+
+use crate::domain::crypto::HashedPassword;
+use crate::domain::password_policy::{
+    PasswordHistoryEntry, PasswordPolicy, PasswordViolationCode, PASSWORD_HISTORY_LIMIT,
+    PASSWORD_LENGTH_LIMIT,
+};
+use crate::domain::user::User;
+use crate::error::{Error, Result};
+use crate::ports::password_history_repository::PasswordHistoryRepository;
+use crate::ports::password_policy_repository::PasswordPolicyRepository;
+use crate::ports::realm_repository::RealmRepository;
+use crate::ports::transaction_manager::Transaction;
+
+const MAX_AGE_DAYS_LIMIT: i64 = 3650;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdatePasswordPolicyPayload {
+    pub min_length: Option<i64>,
+    pub max_length: Option<i64>,
+    pub require_lowercase: Option<bool>,
+    pub require_uppercase: Option<bool>,
+    pub require_digit: Option<bool>,
+    pub require_symbol: Option<bool>,
+    pub disallow_username: Option<bool>,
+    pub history_count: Option<i64>,
+    pub max_age_days: Option<i64>,
+    pub check_breached: Option<bool>,
+}
+
+/// The offline breached password list from `security.breached_passwords_file`,
+/// held as uppercase SHA-1 digests.
+#[derive(Debug, Default)]
+pub struct BreachedPasswordList {
+    digests: HashSet<String>,
+}
+
+impl BreachedPasswordList {
+    pub fn from_file(path: &Path) -> std::io::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        Ok(Self::from_lines(contents.lines()))
+    }
+
+    /// Lines that look like a SHA-1 digest (optionally `HASH:count`) are kept
+    /// as digests; anything else is treated as a plaintext password.
+    pub fn from_lines<'a>(lines: impl IntoIterator<Item = &'a str>) -> Self {
+        let digests = lines
+            .into_iter()
+            .map(|line| line.trim_end_matches(['\r', '\n']))
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let candidate = line.trim().split(':').next().unwrap_or_default();
+                if candidate.len() == 40 && candidate.chars().all(|c| c.is_ascii_hexdigit()) {
+                    candidate.to_ascii_uppercase()
+                } else {
+                    sha1_hex(line)
+                }
+            })
+            .collect();
+        Self { digests }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.digests.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.digests.len()
+    }
+
+    pub fn contains(&self, password: &str) -> bool {
+        self.digests.contains(&sha1_hex(password))
+    }
+}
+
+fn sha1_hex(value: &str) -> String {
+    hex::encode_upper(Sha1::digest(value.as_bytes()))
+}
+
+pub struct PasswordPolicyService {
+    realm_repo: Arc<dyn RealmRepository>,
+    policy_repo: Arc<dyn PasswordPolicyRepository>,
+    history_repo: Arc<dyn PasswordHistoryRepository>,
+    breached_passwords: BreachedPasswordList,
+}
+
+impl PasswordPolicyService {
+    pub fn new(
+        realm_repo: Arc<dyn RealmRepository>,
+        policy_repo: Arc<dyn PasswordPolicyRepository>,
+        history_repo: Arc<dyn PasswordHistoryRepository>,
+        breached_passwords: BreachedPasswordList,
+    ) -> Self {
+        Self {
+            realm_repo,
+            policy_repo,
+            history_repo,
+            breached_passwords,
+        }
+    }
+
+    pub async fn get_policy(&self, realm_id: Uuid) -> Result<PasswordPolicy> {
+        self.ensure_realm_exists(&realm_id).await?;
+        self.policy_for_realm(realm_id).await
+    }
+
+    pub async fn update_policy(
+        &self,
+        realm_id: Uuid,
+        payload: UpdatePasswordPolicyPayload,
+    ) -> Result<PasswordPolicy> {
+        self.ensure_realm_exists(&realm_id).await?;
+        let mut policy = self.policy_for_realm(realm_id).await?;
+
+        if let Some(value) = payload.min_length {
+            policy.min_length = value;
+        }
+        if let Some(value) = payload.max_length {
+            policy.max_length = value;
+        }
+        if let Some(value) = payload.require_lowercase {
+            policy.require_lowercase = value;
+        }
+        if let Some(value) = payload.require_uppercase {
+            policy.require_uppercase = value;
+        }
+        if let Some(value) = payload.require_digit {
+            policy.require_digit = value;
+        }
+        if let Some(value) = payload.require_symbol {
+            policy.require_symbol = value;
+        }
+        if let Some(value) = payload.disallow_username {
+            policy.disallow_username = value;
+        }
+        if let Some(value) = payload.history_count {
+            policy.history_count = value;
+        }
+        if let Some(value) = payload.max_age_days {
+            policy.max_age_days = value;
+        }
+        if let Some(value) = payload.check_breached {
+            policy.check_breached = value;
+        }
+
+        self.validate_policy(&policy)?;
+        self.policy_repo.upsert(&policy, None).await?;
+        Ok(policy)
+    }
+
+    /// Validates and stores a complete policy, e.g. one read from a Harbor
+    /// bundle.
+    pub async fn save_policy_with_tx(
+        &self,
+        policy: PasswordPolicy,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<PasswordPolicy> {
+        self.validate_policy(&policy)?;
+        self.policy_repo.upsert(&policy, tx).await?;
+        Ok(policy)
+    }
+
+    /// The realm's stored policy, or the defaults when none was saved.
+    pub async fn policy_for_realm(&self, realm_id: Uuid) -> Result<PasswordPolicy> {
+        Ok(self
+            .policy_repo
+            .find_by_realm_id(&realm_id)
+            .await?
+            .unwrap_or_else(|| PasswordPolicy::defaults(realm_id)))
+    }
+
+    /// Checks `password` against every rule of `policy`. `user` is the account
+    /// whose password is changing; without it the reuse check is skipped.
+    pub async fn check_password(
+        &self,
+        policy: &PasswordPolicy,
+        password: &str,
+        username: &str,
+        user: Option<&User>,
+    ) -> Result<()> {
+        let mut violations = policy.check(password, Some(username));
+
+        if policy.check_breached && self.breached_passwords.contains(password) {
+            violations.push(policy.violation(PasswordViolationCode::Breached));
+        }
+
+        if let Some(user) = user {
+            if self.was_recently_used(policy, user, password).await? {
+                violations.push(policy.violation(PasswordViolationCode::RecentlyUsed));
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::PasswordPolicy(violations))
+        }
+    }
+
+    /// Records a newly set password hash and trims the user's history to what
+    /// the policy needs (always at least the current password, which dates it).
+    pub async fn record_password(
+        &self,
+        policy: &PasswordPolicy,
+        entry: &PasswordHistoryEntry,
+        tx: &mut dyn Transaction,
+    ) -> Result<()> {
+        self.history_repo.insert(entry, Some(&mut *tx)).await?;
+        self.history_repo
+            .prune(&entry.user_id, policy.history_count.max(1), Some(tx))
+            .await
+    }
+
+    /// When the user's current password was set. Users whose password predates
+    /// the history table fall back to their creation time.
+    pub async fn password_changed_at(&self, user: &User) -> Result<DateTime<Utc>> {
+        let latest = self.history_repo.list_recent(&user.id, 1).await?;
+        Ok(latest
+            .first()
+            .map(|entry| entry.created_at)
+            .or(user.created_at)
+            .unwrap_or_else(Utc::now))
+    }
+
+    async fn was_recently_used(
+        &self,
+        policy: &PasswordPolicy,
+        user: &User,
+        password: &str,
+    ) -> Result<bool> {
+        if policy.history_count <= 0 {
+            return Ok(false);
+        }
+
+        let mut hashes: Vec<String> = self
+            .history_repo
+            .list_recent(&user.id, policy.history_count)
+            .await?
+            .into_iter()
+            .map(|entry| entry.hashed_password)
+            .collect();
+        if !hashes.contains(&user.hashed_password) {
+            hashes.insert(0, user.hashed_password.clone());
+            hashes.truncate(policy.history_count as usize);
+        }
+
+        for hash in hashes.iter().filter(|hash| !hash.trim().is_empty()) {
+            let Ok(previous) = HashedPassword::from_hash(hash) else {
+                continue;
+            };
+            if previous.verify(password)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn validate_policy(&self, policy: &PasswordPolicy) -> Result<()> {
+        if policy.min_length < 1 || policy.min_length > PASSWORD_LENGTH_LIMIT {
+            return Err(Error::Validation(format!(
+                "min_length must be between 1 and {}",
+                PASSWORD_LENGTH_LIMIT
+            )));
+        }
+        if policy.max_length < policy.min_length || policy.max_length > PASSWORD_LENGTH_LIMIT {
+            return Err(Error::Validation(format!(
+                "max_length must be between min_length and {}",
+                PASSWORD_LENGTH_LIMIT
+            )));
+        }
+        if policy.history_count < 0 || policy.history_count > PASSWORD_HISTORY_LIMIT {
+            return Err(Error::Validation(format!(
+                "history_count must be between 0 and {}",
+                PASSWORD_HISTORY_LIMIT
+            )));
+        }
+        if policy.max_age_days < 0 || policy.max_age_days > MAX_AGE_DAYS_LIMIT {
+            return Err(Error::Validation(format!(
+                "max_age_days must be between 0 and {}",
+                MAX_AGE_DAYS_LIMIT
+            )));
+        }
+        if policy.check_breached && self.breached_passwords.is_empty() {
+            return Err(Error::Validation(
+                "check_breached requires security.breached_passwords_file to be configured"
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn ensure_realm_exists(&self, realm_id: &Uuid) -> Result<()> {
+        if self.realm_repo.find_by_id(realm_id).await?.is_none() {
+            return Err(Error::RealmNotFound(realm_id.to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BreachedPasswordList;
+
+    #[test]
+    fn breached_list_accepts_plaintext_and_sha1_lines() {
+        let list = BreachedPasswordList::from_lines([
+            "password123",
+            // SHA-1 of "letmein", lowercase and with an HIBP count suffix.
+            "b7a875fc1ea228b9061041b7cec4bd3c52ab3ce3:12345",
+            "",
+            "  ",
+        ]);
+
+        assert_eq!(list.len(), 2);
+        assert!(list.contains("password123"));
+        assert!(list.contains("letmein"));
+        assert!(!list.contains("Password123"));
+        assert!(!BreachedPasswordList::default().contains("password123"));
+    }
+}
@@ -4,6 +4,7 @@ use crate::domain::events::{
     UserRoleChanged,
 };
 use crate::domain::pagination::{PageRequest, PageResponse};
+use crate::domain::permissions::{AdminResource, PermissionGrant, PermissionScope};
 use crate::domain::rbac::*;
 use crate::domain::role::Permission;
 use crate::error::{Error, Result};
@@ -84,8 +85,33 @@ impl RbacService {
         role_id: Uuid,
         group_id: Uuid,
     ) -> Result<()> {
-        let _ = self.get_role(realm_id, role_id).await?;
+        self.assign_group_role(realm_id, role_id, group_id, None)
+            .await
+    }
+
+    /// Like `assign_role_to_group`, but on behalf of an admin who must hold
+    /// every system permission the role grants.
+    pub async fn assign_role_to_group_as(
+        &self,
+        actor_id: Uuid,
+        realm_id: Uuid,
+        role_id: Uuid,
+        group_id: Uuid,
+    ) -> Result<()> {
+        self.assign_group_role(realm_id, role_id, group_id, Some(&actor_id))
+            .await
+    }
+
+    async fn assign_group_role(
+        &self,
+        realm_id: Uuid,
+        role_id: Uuid,
+        group_id: Uuid,
+        actor_id: Option<&Uuid>,
+    ) -> Result<()> {
+        let role = self.get_role(realm_id, role_id).await?;
         let _ = self.get_group(realm_id, group_id).await?;
+        self.ensure_actor_holds_role(&role, actor_id).await?;
 
         let event = DomainEvent::RoleAssignedToGroup(RoleGroupChanged { role_id, group_id });
 
@@ -341,6 +367,30 @@ impl RbacService {
         realm_id: Uuid,
         user_id: Uuid,
         role_id: Uuid,
+    ) -> Result<()> {
+        self.assign_user_role(realm_id, user_id, role_id, None)
+            .await
+    }
+
+    /// Like `assign_role_to_user`, but on behalf of an admin who must hold
+    /// every system permission the role grants.
+    pub async fn assign_role_to_user_as(
+        &self,
+        actor_id: Uuid,
+        realm_id: Uuid,
+        user_id: Uuid,
+        role_id: Uuid,
+    ) -> Result<()> {
+        self.assign_user_role(realm_id, user_id, role_id, Some(&actor_id))
+            .await
+    }
+
+    async fn assign_user_role(
+        &self,
+        realm_id: Uuid,
+        user_id: Uuid,
+        role_id: Uuid,
+        actor_id: Option<&Uuid>,
     ) -> Result<()> {
         let role = self
             .rbac_repo
@@ -352,6 +402,8 @@ impl RbacService {
             return Err(Error::SecurityViolation("Cross-realm assignment".into()));
         }
 
+        self.ensure_actor_holds_role(&role, actor_id).await?;
+
         let event = DomainEvent::UserRoleAssigned(UserRoleChanged { user_id, role_id });
 
         let mut tx = self.tx_manager.begin().await?;
@@ -438,11 +490,35 @@ impl RbacService {
         realm_id: Uuid,
         role_id: Uuid,
         permission: Permission,
+    ) -> Result<()> {
+        self.assign_permission(realm_id, role_id, permission, None)
+            .await
+    }
+
+    /// Like `assign_permission_to_role`, but on behalf of an admin who may only
+    /// hand out system permissions they hold at least as broadly themselves.
+    pub async fn assign_permission_to_role_as(
+        &self,
+        actor_id: Uuid,
+        realm_id: Uuid,
+        role_id: Uuid,
+        permission: Permission,
+    ) -> Result<()> {
+        self.assign_permission(realm_id, role_id, permission, Some(&actor_id))
+            .await
+    }
+
+    async fn assign_permission(
+        &self,
+        realm_id: Uuid,
+        role_id: Uuid,
+        permission: Permission,
+        actor_id: Option<&Uuid>,
     ) -> Result<()> {
         // 1. Verify Role belongs to Realm
         let role = self.get_role(realm_id, role_id).await?;
 
-        self.ensure_permission_assignable(&role, &permission)
+        self.ensure_permission_assignable(&role, &permission, actor_id)
             .await?;
 
         // 2. Assign
@@ -522,6 +598,31 @@ impl RbacService {
         role_id: Uuid,
         permissions: Vec<String>,
         action: String,
+    ) -> Result<()> {
+        self.bulk_update(realm_id, role_id, permissions, action, None)
+            .await
+    }
+
+    /// Like `bulk_update_permissions`, bounded by the acting admin's own grants.
+    pub async fn bulk_update_permissions_as(
+        &self,
+        actor_id: Uuid,
+        realm_id: Uuid,
+        role_id: Uuid,
+        permissions: Vec<String>,
+        action: String,
+    ) -> Result<()> {
+        self.bulk_update(realm_id, role_id, permissions, action, Some(&actor_id))
+            .await
+    }
+
+    async fn bulk_update(
+        &self,
+        realm_id: Uuid,
+        role_id: Uuid,
+        permissions: Vec<String>,
+        action: String,
+        actor_id: Option<&Uuid>,
     ) -> Result<()> {
         // 1. Verify Role belongs to Realm
         let role = self.get_role(realm_id, role_id).await?;
@@ -535,7 +636,8 @@ impl RbacService {
 
         if action == "add" {
             for permission in &permissions {
-                self.ensure_permission_assignable(&role, permission).await?;
+                self.ensure_permission_assignable(&role, permission, actor_id)
+                    .await?;
             }
         }
 
@@ -826,6 +928,32 @@ impl RbacService {
         group_id: Uuid,
         role_ids: Vec<Uuid>,
         action: String,
+    ) -> Result<()> {
+        self.bulk_update_group_roles_inner(realm_id, group_id, role_ids, action, None)
+            .await
+    }
+
+    /// Like `bulk_update_group_roles`, but on behalf of an admin who must hold
+    /// every system permission granted by the roles being added.
+    pub async fn bulk_update_group_roles_as(
+        &self,
+        actor_id: Uuid,
+        realm_id: Uuid,
+        group_id: Uuid,
+        role_ids: Vec<Uuid>,
+        action: String,
+    ) -> Result<()> {
+        self.bulk_update_group_roles_inner(realm_id, group_id, role_ids, action, Some(&actor_id))
+            .await
+    }
+
+    async fn bulk_update_group_roles_inner(
+        &self,
+        realm_id: Uuid,
+        group_id: Uuid,
+        role_ids: Vec<Uuid>,
+        action: String,
+        actor_id: Option<&Uuid>,
     ) -> Result<()> {
         // 1. Verify the group belongs to the realm.
         let _ = self.get_group(realm_id, group_id).await?;
@@ -839,7 +967,10 @@ impl RbacService {
 
         // 3. Verify every role exists / belongs to the realm before mutating anything.
         for &role_id in &role_ids {
-            let _ = self.get_role(realm_id, role_id).await?;
+            let role = self.get_role(realm_id, role_id).await?;
+            if action == "add" {
+                self.ensure_actor_holds_role(&role, actor_id).await?;
+            }
         }
 
         let make_event = |role_id: Uuid| {
@@ -899,6 +1030,28 @@ impl RbacService {
         Ok((roles, groups))
     }
 
+    /// Resolves the roles defined on a client and the effective permissions
+    /// they grant (composites included). A client authenticating with
+    /// client_credentials acts with exactly this set.
+    pub async fn get_client_roles_and_permissions(
+        &self,
+        realm_id: Uuid,
+        client_id: Uuid,
+    ) -> Result<(Vec<String>, HashSet<String>)> {
+        let roles = self
+            .rbac_repo
+            .find_roles_for_client(&realm_id, &client_id)
+            .await?;
+        if roles.is_empty() {
+            return Ok((Vec::new(), HashSet::new()));
+        }
+
+        let role_ids: Vec<Uuid> = roles.iter().map(|role| role.id).collect();
+        let permissions = self.rbac_repo.find_permissions_for_roles(&role_ids).await?;
+        let names = roles.into_iter().map(|role| role.name).collect();
+        Ok((names, permissions))
+    }
+
     pub async fn get_direct_user_ids_for_role(
         &self,
         realm_id: Uuid,
@@ -972,26 +1125,128 @@ impl RbacService {
             .await
     }
 
+    /// Whether the user holds `permission` globally. Scoped grants do not count.
     #[instrument(skip_all, fields(telemetry = "span"))]
     pub async fn user_has_permission(&self, user_id: &Uuid, permission: &str) -> Result<bool> {
-        let perms = self.get_effective_permissions(user_id).await?;
-
-        if perms.contains(permission) {
-            return Ok(true);
-        }
+        Ok(self
+            .matching_grants(user_id, permission)
+            .await?
+            .iter()
+            .any(|grant| grant.scope == PermissionScope::Global))
+    }
 
-        if let Some((resource, _)) = permission.split_once(':') {
-            let wildcard = format!("{}:*", resource);
-            if perms.contains(&wildcard) {
+    /// Whether the user holds `permission` for `resource`: globally, for the
+    /// resource's realm, or through a grant scoped to the client or to a group
+    /// subtree containing the user or group.
+    #[instrument(skip_all, fields(telemetry = "span"))]
+    pub async fn user_has_permission_for(
+        &self,
+        user_id: &Uuid,
+        permission: &str,
+        resource: &AdminResource,
+    ) -> Result<bool> {
+        for grant in self.matching_grants(user_id, permission).await? {
+            let matches = match (grant.scope, *resource) {
+                (PermissionScope::Global, _) => true,
+                (PermissionScope::Realm(realm_id), resource) => realm_id == resource.realm_id(),
+                (PermissionScope::Client(granted), AdminResource::Client { client_id, .. }) => {
+                    granted == client_id
+                }
+                (
+                    PermissionScope::Group(root_id),
+                    AdminResource::User {
+                        realm_id,
+                        user_id: target_id,
+                    },
+                ) => {
+                    self.rbac_repo
+                        .is_user_in_group_subtree(&realm_id, &root_id, &target_id)
+                        .await?
+                }
+                (PermissionScope::Group(root_id), AdminResource::Group { realm_id, group_id }) => {
+                    root_id == group_id
+                        || self
+                            .rbac_repo
+                            .is_group_descendant(&realm_id, &root_id, &group_id)
+                            .await?
+                }
+                _ => false,
+            };
+            if matches {
                 return Ok(true);
             }
         }
+        Ok(false)
+    }
 
-        if perms.contains("*") {
-            return Ok(true);
-        }
+    /// Whether the user holds `grant` at least as broadly as it reaches: the
+    /// same permission (or a wildcard over it) scoped to cover the grant's
+    /// scope. Clients and groups that no longer exist resolve to `realm_id`.
+    #[instrument(skip_all, fields(telemetry = "span"))]
+    pub async fn user_covers_grant(
+        &self,
+        user_id: &Uuid,
+        grant: &PermissionGrant,
+        realm_id: Uuid,
+    ) -> Result<bool> {
+        let resource = match grant.scope {
+            PermissionScope::Global => {
+                return self.user_has_permission(user_id, &grant.permission).await;
+            }
+            PermissionScope::Realm(realm_id) => AdminResource::Realm(realm_id),
+            PermissionScope::Client(client_id) => AdminResource::Client {
+                realm_id: self
+                    .rbac_repo
+                    .find_client_realm_id(&client_id)
+                    .await?
+                    .unwrap_or(realm_id),
+                client_id,
+            },
+            PermissionScope::Group(group_id) => AdminResource::Group {
+                realm_id: self
+                    .rbac_repo
+                    .find_group_by_id(&group_id)
+                    .await?
+                    .map_or(realm_id, |group| group.realm_id),
+                group_id,
+            },
+        };
+        self.user_has_permission_for(user_id, &grant.permission, &resource)
+            .await
+    }
 
-        Ok(false)
+    /// Whether the user holds `permission` for at least part of the realm,
+    /// including grants scoped to a client or group. Callers that let such a
+    /// user through must check each resource with `user_has_permission_for`.
+    #[instrument(skip_all, fields(telemetry = "span"))]
+    pub async fn user_has_permission_in_realm(
+        &self,
+        user_id: &Uuid,
+        permission: &str,
+        realm_id: &Uuid,
+    ) -> Result<bool> {
+        Ok(self
+            .matching_grants(user_id, permission)
+            .await?
+            .iter()
+            .any(|grant| match grant.scope {
+                PermissionScope::Realm(granted) => granted == *realm_id,
+                _ => true,
+            }))
+    }
+
+    async fn matching_grants(
+        &self,
+        user_id: &Uuid,
+        permission: &str,
+    ) -> Result<Vec<PermissionGrant>> {
+        Ok(self
+            .get_effective_permissions(user_id)
+            .await?
+            .iter()
+            .filter_map(|value| PermissionGrant::parse(value))
+            .filter(|grant| grant.includes(permission))
+            .collect())
     }
 
     #[instrument(skip_all, fields(telemetry = "span"))]
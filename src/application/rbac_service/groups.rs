@@ -246,6 +246,16 @@ impl RbacService {
     ) -> Result<Group> {
         let mut group = self.get_group(realm_id, group_id).await?;
 
+        if group.name != payload.name
+            && self
+                .rbac_repo
+                .find_group_by_name(&realm_id, &payload.name)
+                .await?
+                .is_some_and(|existing| existing.id != group_id)
+        {
+            return Err(Error::GroupAlreadyExists);
+        }
+
         group.name = payload.name;
         group.description = payload.description;
 
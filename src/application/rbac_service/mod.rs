@@ -1,5 +1,9 @@
 use crate::{
-    domain::{events::DomainEvent, permissions, role::Role},
+    domain::{
+        events::DomainEvent,
+        permissions::{self, PermissionGrant, PermissionScope},
+        role::Role,
+    },
     error::{Error, Result},
     ports::{
         cache_service::CacheService,
@@ -96,6 +100,12 @@ impl RbacService {
             ));
         }
 
+        if trimmed.contains('@') {
+            return Err(Error::Validation(
+                "Permission ID cannot contain '@', which marks scoped grants".into(),
+            ));
+        }
+
         if trimmed == "*" {
             return Err(Error::Validation(
                 "Wildcard permissions are reserved for system roles".into(),
@@ -111,14 +121,32 @@ impl RbacService {
         Ok(())
     }
 
-    async fn ensure_permission_assignable(&self, role: &Role, permission: &str) -> Result<()> {
+    /// Validates `permission` for `role`. When `actor_id` is set, system
+    /// permissions must also be held by the actor at least as broadly as the
+    /// grant being handed out, so scoped admins cannot mint wider grants.
+    async fn ensure_permission_assignable(
+        &self,
+        role: &Role,
+        permission: &str,
+        actor_id: Option<&Uuid>,
+    ) -> Result<()> {
+        if permission.contains('@') {
+            self.ensure_scoped_permission_assignable(role, permission)
+                .await?;
+            return self
+                .ensure_actor_holds_grant(role, permission, actor_id)
+                .await;
+        }
+
         if permissions::is_system_permission(permission) {
             if role.client_id.is_some() {
                 return Err(Error::Validation(
                     "System permissions cannot be assigned to client roles".into(),
                 ));
             }
-            return Ok(());
+            return self
+                .ensure_actor_holds_grant(role, permission, actor_id)
+                .await;
         }
 
         let custom = self
@@ -134,4 +162,98 @@ impl RbacService {
 
         Ok(())
     }
+
+    async fn ensure_scoped_permission_assignable(
+        &self,
+        role: &Role,
+        permission: &str,
+    ) -> Result<()> {
+        let grant = PermissionGrant::parse(permission).ok_or_else(|| {
+            Error::Validation("Scoped permissions look like 'permission@kind:id'".into())
+        })?;
+        if let Some(reason) = grant.scope_violation() {
+            return Err(Error::Validation(reason.into()));
+        }
+        if role.client_id.is_some() {
+            return Err(Error::Validation(
+                "System permissions cannot be assigned to client roles".into(),
+            ));
+        }
+        match grant.scope {
+            PermissionScope::Global => {}
+            PermissionScope::Realm(realm_id) => {
+                if realm_id != role.realm_id
+                    && !self.rbac_repo.is_system_realm(&role.realm_id).await?
+                {
+                    return Err(Error::Validation(
+                        "Scoped realm must be the role's realm".into(),
+                    ));
+                }
+            }
+            PermissionScope::Client(client_id) => {
+                let client_realm_id = self.rbac_repo.find_client_realm_id(&client_id).await?;
+                let in_scope = match client_realm_id {
+                    Some(realm_id) if realm_id == role.realm_id => true,
+                    Some(_) => self.rbac_repo.is_system_realm(&role.realm_id).await?,
+                    None => false,
+                };
+                if !in_scope {
+                    return Err(Error::Validation(
+                        "Scoped client not found in this realm".into(),
+                    ));
+                }
+            }
+            PermissionScope::Group(group_id) => {
+                let group = self.rbac_repo.find_group_by_id(&group_id).await?;
+                if group.is_none_or(|group| group.realm_id != role.realm_id) {
+                    return Err(Error::Validation(
+                        "Scoped group not found in this realm".into(),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn ensure_actor_holds_grant(
+        &self,
+        role: &Role,
+        permission: &str,
+        actor_id: Option<&Uuid>,
+    ) -> Result<()> {
+        let Some(actor_id) = actor_id else {
+            return Ok(());
+        };
+        let Some(grant) = PermissionGrant::parse(permission) else {
+            return Err(Error::InsufficientPermissions);
+        };
+        let held = self
+            .user_covers_grant(actor_id, &grant, role.realm_id)
+            .await?;
+        if !held {
+            return Err(Error::InsufficientPermissions);
+        }
+        Ok(())
+    }
+
+    /// Rejects handing `role` to a user or group unless `actor_id` holds every
+    /// system permission the role grants, including through composite roles.
+    async fn ensure_actor_holds_role(&self, role: &Role, actor_id: Option<&Uuid>) -> Result<()> {
+        if actor_id.is_none() {
+            return Ok(());
+        }
+        let mut role_ids = self
+            .rbac_repo
+            .list_effective_role_composite_ids(&role.id)
+            .await?;
+        role_ids.push(role.id);
+        let granted = self.rbac_repo.find_permissions_for_roles(&role_ids).await?;
+        for permission in &granted {
+            if permission.contains('@') || permissions::is_system_permission(permission) {
+                self.ensure_actor_holds_grant(role, permission, actor_id)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
 }
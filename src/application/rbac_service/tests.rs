@@ -4,6 +4,7 @@ use crate::domain::events::{
 };
 use crate::domain::group::Group;
 use crate::domain::pagination::{PageRequest, PageResponse};
+use crate::domain::permissions::AdminResource;
 use crate::domain::rbac::*;
 use crate::domain::role::Permission;
 use crate::ports::outbox_repository::OutboxRepository;
@@ -146,9 +147,12 @@ struct BulkUpdateCall {
 struct TestRbacRepo {
     roles: Mutex<HashMap<Uuid, Role>>,
     groups: Mutex<HashMap<Uuid, Group>>,
+    clients: Mutex<HashMap<Uuid, Uuid>>,
+    system_realms: Mutex<HashSet<Uuid>>,
     group_children_by_parent: Mutex<HashMap<Option<Uuid>, Vec<Uuid>>>,
     group_subtree_by_root: Mutex<HashMap<Uuid, Vec<Uuid>>>,
     group_descendant: Mutex<bool>,
+    group_subtree_members: Mutex<HashSet<(Uuid, Uuid)>>,
     next_group_sort_order: Mutex<i64>,
     count_user_ids_in_groups_result: Mutex<i64>,
     count_role_ids_in_groups_result: Mutex<i64>,
@@ -197,9 +201,12 @@ impl Default for TestRbacRepo {
         Self {
             roles: Mutex::new(HashMap::new()),
             groups: Mutex::new(HashMap::new()),
+            clients: Mutex::new(HashMap::new()),
+            system_realms: Mutex::new(HashSet::new()),
             group_children_by_parent: Mutex::new(HashMap::new()),
             group_subtree_by_root: Mutex::new(HashMap::new()),
             group_descendant: Mutex::new(false),
+            group_subtree_members: Mutex::new(HashSet::new()),
             next_group_sort_order: Mutex::new(0),
             count_user_ids_in_groups_result: Mutex::new(0),
             count_role_ids_in_groups_result: Mutex::new(0),
@@ -250,6 +257,10 @@ impl TestRbacRepo {
         self.roles.lock().unwrap().insert(role.id, role);
     }
 
+    fn insert_client(&self, realm_id: Uuid, client_id: Uuid) {
+        self.clients.lock().unwrap().insert(client_id, realm_id);
+    }
+
     fn set_role_descendant(&self, value: bool) {
         *self.role_descendant.lock().unwrap() = value;
     }
@@ -258,6 +269,13 @@ impl TestRbacRepo {
         *self.group_descendant.lock().unwrap() = value;
     }
 
+    fn add_group_subtree_member(&self, root_id: Uuid, user_id: Uuid) {
+        self.group_subtree_members
+            .lock()
+            .unwrap()
+            .insert((root_id, user_id));
+    }
+
     fn set_next_group_sort_order(&self, value: i64) {
         *self.next_group_sort_order.lock().unwrap() = value;
     }
@@ -582,6 +600,16 @@ impl RbacRepository for TestRbacRepo {
         Ok(self.groups.lock().unwrap().get(group_id).cloned())
     }
 
+    async fn find_client_realm_id(&self, client_id: &Uuid) -> Result<Option<Uuid>> {
+        self.maybe_fail("find_client_realm_id")?;
+        Ok(self.clients.lock().unwrap().get(client_id).copied())
+    }
+
+    async fn is_system_realm(&self, realm_id: &Uuid) -> Result<bool> {
+        self.maybe_fail("is_system_realm")?;
+        Ok(self.system_realms.lock().unwrap().contains(realm_id))
+    }
+
     async fn list_roles(&self, realm_id: &Uuid, req: &PageRequest) -> Result<PageResponse<Role>> {
         self.maybe_fail("list_roles")?;
         let page = self.list_roles_result.lock().unwrap();
@@ -609,6 +637,20 @@ impl RbacRepository for TestRbacRepo {
         ))
     }
 
+    async fn find_roles_for_client(&self, realm_id: &Uuid, client_id: &Uuid) -> Result<Vec<Role>> {
+        self.maybe_fail("find_roles_for_client")?;
+        let mut roles: Vec<Role> = self
+            .roles
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|role| role.realm_id == *realm_id && role.client_id == Some(*client_id))
+            .cloned()
+            .collect();
+        roles.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(roles)
+    }
+
     async fn find_role_by_id(&self, role_id: &Uuid) -> Result<Option<Role>> {
         self.maybe_fail("find_role_by_id")?;
         Ok(self.roles.lock().unwrap().get(role_id).cloned())
@@ -797,6 +839,20 @@ impl RbacRepository for TestRbacRepo {
         Ok(*self.group_descendant.lock().unwrap())
     }
 
+    async fn is_user_in_group_subtree(
+        &self,
+        realm_id: &Uuid,
+        root_id: &Uuid,
+        user_id: &Uuid,
+    ) -> Result<bool> {
+        self.maybe_fail("is_user_in_group_subtree")?;
+        Ok(self
+            .group_subtree_members
+            .lock()
+            .unwrap()
+            .contains(&(*root_id, *user_id)))
+    }
+
     async fn get_next_group_sort_order(
         &self,
         realm_id: &Uuid,
@@ -973,6 +1029,11 @@ impl RbacRepository for TestRbacRepo {
             .unwrap_or_default())
     }
 
+    async fn find_group_paths_for_user(&self, _user_id: &Uuid) -> Result<Vec<String>> {
+        self.maybe_fail("find_group_paths_for_user")?;
+        Ok(Vec::new())
+    }
+
     async fn delete_role(&self, role_id: &Uuid, _tx: Option<&mut dyn Transaction>) -> Result<()> {
         self.maybe_fail("delete_role")?;
         self.roles.lock().unwrap().remove(role_id);
@@ -1639,6 +1700,313 @@ async fn user_has_permission_without_namespace_returns_false() {
     assert!(!has_permission);
 }
 
+#[tokio::test]
+async fn scoped_grants_only_cover_their_resources() {
+    let harness = harness();
+    let user_id = Uuid::new_v4();
+    let realm_id = Uuid::new_v4();
+    let other_realm_id = Uuid::new_v4();
+    let client_id = Uuid::new_v4();
+    let group_id = Uuid::new_v4();
+    let member_id = Uuid::new_v4();
+
+    let permissions_set: HashSet<String> = [
+        format!("{}@realm:{}", permissions::REALM_READ, realm_id),
+        format!("{}@client:{}", permissions::CLIENT_UPDATE, client_id),
+        format!("{}@group:{}", permissions::USER_WRITE, group_id),
+        format!("{}@group:{}", permissions::RBAC_WRITE, group_id),
+    ]
+    .into_iter()
+    .collect();
+    harness
+        .cache
+        .set_user_permissions(&user_id, &permissions_set)
+        .await;
+    harness.repo.add_group_subtree_member(group_id, member_id);
+
+    let service = &harness.service;
+    let check = |permission: &'static str, resource: AdminResource| async move {
+        service
+            .user_has_permission_for(&user_id, permission, &resource)
+            .await
+            .expect("permission check")
+    };
+
+    assert!(!service
+        .user_has_permission(&user_id, permissions::REALM_READ)
+        .await
+        .unwrap());
+    assert!(check(permissions::REALM_READ, AdminResource::Realm(realm_id)).await);
+    assert!(
+        !check(
+            permissions::REALM_READ,
+            AdminResource::Realm(other_realm_id)
+        )
+        .await
+    );
+
+    assert!(
+        check(
+            permissions::CLIENT_UPDATE,
+            AdminResource::Client {
+                realm_id,
+                client_id
+            }
+        )
+        .await
+    );
+    assert!(
+        !check(
+            permissions::CLIENT_UPDATE,
+            AdminResource::Client {
+                realm_id,
+                client_id: Uuid::new_v4()
+            }
+        )
+        .await
+    );
+    assert!(!check(permissions::CLIENT_UPDATE, AdminResource::Realm(realm_id)).await);
+
+    assert!(
+        check(
+            permissions::USER_WRITE,
+            AdminResource::User {
+                realm_id,
+                user_id: member_id
+            }
+        )
+        .await
+    );
+    assert!(
+        !check(
+            permissions::USER_WRITE,
+            AdminResource::User {
+                realm_id,
+                user_id: Uuid::new_v4()
+            }
+        )
+        .await
+    );
+    assert!(
+        check(
+            permissions::RBAC_WRITE,
+            AdminResource::Group { realm_id, group_id }
+        )
+        .await
+    );
+    harness.repo.set_group_descendant(true);
+    assert!(
+        check(
+            permissions::RBAC_WRITE,
+            AdminResource::Group {
+                realm_id,
+                group_id: Uuid::new_v4()
+            }
+        )
+        .await
+    );
+
+    assert!(service
+        .user_has_permission_in_realm(&user_id, permissions::USER_WRITE, &other_realm_id)
+        .await
+        .unwrap());
+    assert!(!service
+        .user_has_permission_in_realm(&user_id, permissions::REALM_READ, &other_realm_id)
+        .await
+        .unwrap());
+}
+
+#[tokio::test]
+async fn assign_scoped_permission_validates_scope() {
+    let harness = harness();
+    let realm_id = Uuid::new_v4();
+    let role_id = Uuid::new_v4();
+    let group_id = Uuid::new_v4();
+    harness
+        .repo
+        .insert_role(build_role(realm_id, role_id, "helpdesk"));
+
+    let assign = |permission: String| {
+        harness
+            .service
+            .assign_permission_to_role(realm_id, role_id, permission)
+    };
+
+    let missing_group = assign(format!("{}@group:{}", permissions::USER_WRITE, group_id)).await;
+    assert!(matches!(missing_group, Err(Error::Validation(_))));
+
+    harness
+        .repo
+        .groups
+        .lock()
+        .unwrap()
+        .insert(group_id, build_group(realm_id, group_id, None, "emea", 0));
+    assign(format!("{}@group:{}", permissions::USER_WRITE, group_id))
+        .await
+        .expect("group scoped grant");
+    assign(format!("{}@realm:{}", permissions::ALL, realm_id))
+        .await
+        .expect("realm scoped grant");
+    let client_id = Uuid::new_v4();
+    harness.repo.insert_client(realm_id, client_id);
+    assign(format!(
+        "{}@client:{}",
+        permissions::CLIENT_UPDATE,
+        client_id
+    ))
+    .await
+    .expect("client scoped grant");
+
+    for invalid in [
+        format!("{}@group:{}", permissions::REALM_WRITE, group_id),
+        format!("{}@client:{}", permissions::CLIENT_CREATE, Uuid::new_v4()),
+        format!("app:read@realm:{}", realm_id),
+        "user:write@group:not-a-uuid".to_string(),
+    ] {
+        let result = assign(invalid.clone()).await;
+        assert!(
+            matches!(result, Err(Error::Validation(_))),
+            "{invalid} should be rejected"
+        );
+    }
+    assert_eq!(
+        harness
+            .repo
+            .assign_permission_to_role_calls
+            .lock()
+            .unwrap()
+            .len(),
+        3
+    );
+}
+
+#[tokio::test]
+async fn assign_scoped_permission_rejects_foreign_realm_scope() {
+    let harness = harness();
+    let realm_id = Uuid::new_v4();
+    let role_id = Uuid::new_v4();
+    harness
+        .repo
+        .insert_role(build_role(realm_id, role_id, "helpdesk"));
+
+    let result = harness
+        .service
+        .assign_permission_to_role(
+            realm_id,
+            role_id,
+            format!("{}@realm:{}", permissions::USER_WRITE, Uuid::new_v4()),
+        )
+        .await;
+
+    assert!(matches!(result, Err(Error::Validation(_))));
+    assert!(harness
+        .repo
+        .assign_permission_to_role_calls
+        .lock()
+        .unwrap()
+        .is_empty());
+
+    harness.repo.system_realms.lock().unwrap().insert(realm_id);
+    harness
+        .service
+        .assign_permission_to_role(
+            realm_id,
+            role_id,
+            format!("{}@realm:{}", permissions::USER_WRITE, Uuid::new_v4()),
+        )
+        .await
+        .expect("system realm roles may administer other realms");
+}
+
+#[tokio::test]
+async fn assign_scoped_permission_rejects_client_from_another_realm() {
+    let harness = harness();
+    let realm_id = Uuid::new_v4();
+    let role_id = Uuid::new_v4();
+    let client_id = Uuid::new_v4();
+    harness
+        .repo
+        .insert_role(build_role(realm_id, role_id, "helpdesk"));
+    harness.repo.insert_client(Uuid::new_v4(), client_id);
+
+    let result = harness
+        .service
+        .assign_permission_to_role(
+            realm_id,
+            role_id,
+            format!("{}@client:{}", permissions::CLIENT_UPDATE, client_id),
+        )
+        .await;
+
+    assert!(matches!(result, Err(Error::Validation(_))));
+    assert!(harness
+        .repo
+        .assign_permission_to_role_calls
+        .lock()
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn assign_permission_as_actor_is_bounded_by_actor_grants() {
+    let harness = harness();
+    let realm_id = Uuid::new_v4();
+    let role_id = Uuid::new_v4();
+    let actor_id = Uuid::new_v4();
+    harness
+        .repo
+        .insert_role(build_role(realm_id, role_id, "helpdesk"));
+    let actor_permissions: HashSet<String> = [
+        format!("{}@realm:{}", permissions::RBAC_WRITE, realm_id),
+        format!("{}@realm:{}", permissions::USER_WRITE, realm_id),
+    ]
+    .into_iter()
+    .collect();
+    harness
+        .cache
+        .set_user_permissions(&actor_id, &actor_permissions)
+        .await;
+
+    let assign = |permission: String| {
+        harness
+            .service
+            .assign_permission_to_role_as(actor_id, realm_id, role_id, permission)
+    };
+
+    for broader in [
+        permissions::USER_WRITE.to_string(),
+        permissions::ALL.to_string(),
+        format!("{}@realm:{}", permissions::REALM_WRITE, realm_id),
+    ] {
+        let result = assign(broader.clone()).await;
+        assert!(
+            matches!(result, Err(Error::InsufficientPermissions)),
+            "{broader} should be refused"
+        );
+    }
+    let bulk = harness
+        .service
+        .bulk_update_permissions_as(
+            actor_id,
+            realm_id,
+            role_id,
+            vec![permissions::USER_WRITE.to_string()],
+            "add".to_string(),
+        )
+        .await;
+    assert!(matches!(bulk, Err(Error::InsufficientPermissions)));
+    assert!(harness
+        .repo
+        .assign_permission_to_role_calls
+        .lock()
+        .unwrap()
+        .is_empty());
+
+    assign(format!("{}@realm:{}", permissions::USER_WRITE, realm_id))
+        .await
+        .expect("grant within the actor's own scope");
+}
+
 #[tokio::test]
 async fn get_effective_permissions_caches_repo_result() {
     let harness = harness();
@@ -4507,6 +4875,46 @@ async fn update_group_updates_repo_state() {
     assert_eq!(stored.description.as_deref(), Some("Updated"));
 }
 
+#[tokio::test]
+async fn update_group_rejects_name_of_another_group() {
+    let harness = harness();
+    let realm_id = Uuid::new_v4();
+    let group_id = Uuid::new_v4();
+    let other_id = Uuid::new_v4();
+
+    for (id, name) in [(group_id, "group"), (other_id, "taken")] {
+        harness.repo.groups.lock().unwrap().insert(
+            id,
+            Group {
+                id,
+                realm_id,
+                parent_id: None,
+                name: name.to_string(),
+                description: None,
+                sort_order: 0,
+            },
+        );
+    }
+
+    let err = harness
+        .service
+        .update_group(
+            realm_id,
+            group_id,
+            CreateGroupPayload {
+                parent_id: None,
+                name: "taken".to_string(),
+                description: None,
+            },
+        )
+        .await
+        .expect_err("name conflict");
+
+    assert!(matches!(err, Error::GroupAlreadyExists));
+    let stored = harness.repo.groups.lock().unwrap().get(&group_id).cloned();
+    assert_eq!(stored.expect("stored group").name, "group");
+}
+
 #[tokio::test]
 async fn update_group_publishes_group_updated_event() {
     let harness = harness();
@@ -5964,3 +6372,38 @@ async fn bulk_update_group_roles_rejects_invalid_action() {
 
     assert!(matches!(result, Err(Error::Validation(_))));
 }
+
+#[tokio::test]
+async fn get_client_roles_and_permissions_uses_only_client_roles() {
+    let harness = harness();
+    let realm_id = Uuid::new_v4();
+    let client_id = Uuid::new_v4();
+
+    let mut client_role = build_role(realm_id, Uuid::new_v4(), "orders-reader");
+    client_role.client_id = Some(client_id);
+    let realm_role = build_role(realm_id, Uuid::new_v4(), "realm-admin");
+    harness.repo.insert_role(client_role);
+    harness.repo.insert_role(realm_role);
+
+    let (roles, _permissions) = harness
+        .service
+        .get_client_roles_and_permissions(realm_id, client_id)
+        .await
+        .expect("client roles");
+
+    assert_eq!(roles, vec!["orders-reader".to_string()]);
+}
+
+#[tokio::test]
+async fn get_client_roles_and_permissions_is_empty_without_roles() {
+    let harness = harness();
+
+    let (roles, permissions) = harness
+        .service
+        .get_client_roles_and_permissions(Uuid::new_v4(), Uuid::new_v4())
+        .await
+        .expect("client roles");
+
+    assert!(roles.is_empty());
+    assert!(permissions.is_empty());
+}
@@ -2,6 +2,7 @@ use crate::domain::realm_email_settings::RealmEmailSettings;
 use crate::error::{Error, Result};
 use crate::ports::realm_email_settings_repository::RealmEmailSettingsRepository;
 use crate::ports::realm_repository::RealmRepository;
+use crate::ports::transaction_manager::Transaction;
 use serde::Deserialize;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -61,11 +62,21 @@ impl RealmEmailSettingsService {
 
         apply_payload(&mut settings, payload);
         validate_settings(&settings)?;
-        self.email_repo.upsert(&settings).await?;
+        self.email_repo.upsert(&settings, None).await?;
 
         Ok(settings)
     }
 
+    pub async fn save_settings_with_tx(
+        &self,
+        settings: RealmEmailSettings,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<RealmEmailSettings> {
+        validate_settings(&settings)?;
+        self.email_repo.upsert(&settings, tx).await?;
+        Ok(settings)
+    }
+
     async fn ensure_realm_exists(&self, realm_id: &Uuid) -> Result<()> {
         if self.realm_repo.find_by_id(realm_id).await?.is_none() {
             return Err(Error::RealmNotFound(realm_id.to_string()));
@@ -6,6 +6,7 @@ use crate::error::{Error, Result};
 use crate::ports::oauth_start_attempt_repository::OAuthStartAttemptRepository;
 use crate::ports::realm_idp_settings_repository::RealmIdpSettingsRepository;
 use crate::ports::realm_repository::RealmRepository;
+use crate::ports::transaction_manager::Transaction;
 use chrono::{Duration, Utc};
 use serde::Deserialize;
 use serde_json::json;
@@ -73,7 +74,7 @@ impl RealmIdpSettingsService {
         }
 
         validate_settings(&settings)?;
-        self.settings_repo.upsert(&settings).await?;
+        self.settings_repo.upsert(&settings, None).await?;
         Ok(settings)
     }
 
@@ -143,6 +144,16 @@ impl RealmIdpSettingsService {
         Ok(())
     }
 
+    pub async fn save_settings_with_tx(
+        &self,
+        settings: RealmIdpSettings,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<RealmIdpSettings> {
+        validate_settings(&settings)?;
+        self.settings_repo.upsert(&settings, tx).await?;
+        Ok(settings)
+    }
+
     async fn ensure_realm_exists(&self, realm_id: &Uuid) -> Result<()> {
         if self.realm_repo.find_by_id(realm_id).await?.is_none() {
             return Err(Error::RealmNotFound(realm_id.to_string()));
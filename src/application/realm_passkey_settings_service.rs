@@ -2,6 +2,7 @@ use crate::domain::realm_passkey_settings::RealmPasskeySettings;
 use crate::error::{Error, Result};
 use crate::ports::realm_passkey_settings_repository::RealmPasskeySettingsRepository;
 use crate::ports::realm_repository::RealmRepository;
+use crate::ports::transaction_manager::Transaction;
 use serde::Deserialize;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -74,7 +75,17 @@ impl RealmPasskeySettingsService {
         }
 
         validate_settings(&settings)?;
-        self.passkey_repo.upsert(&settings).await?;
+        self.passkey_repo.upsert(&settings, None).await?;
+        Ok(settings)
+    }
+
+    pub async fn save_settings_with_tx(
+        &self,
+        settings: RealmPasskeySettings,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<RealmPasskeySettings> {
+        validate_settings(&settings)?;
+        self.passkey_repo.upsert(&settings, tx).await?;
         Ok(settings)
     }
 
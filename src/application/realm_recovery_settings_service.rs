@@ -2,6 +2,7 @@ use crate::domain::realm_recovery_settings::RealmRecoverySettings;
 use crate::error::{Error, Result};
 use crate::ports::realm_recovery_settings_repository::RealmRecoverySettingsRepository;
 use crate::ports::realm_repository::RealmRepository;
+use crate::ports::transaction_manager::Transaction;
 use serde::Deserialize;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -79,11 +80,21 @@ impl RealmRecoverySettingsService {
         }
 
         validate_settings(&settings)?;
-        self.recovery_repo.upsert(&settings).await?;
+        self.recovery_repo.upsert(&settings, None).await?;
 
         Ok(settings)
     }
 
+    pub async fn save_settings_with_tx(
+        &self,
+        settings: RealmRecoverySettings,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<RealmRecoverySettings> {
+        validate_settings(&settings)?;
+        self.recovery_repo.upsert(&settings, tx).await?;
+        Ok(settings)
+    }
+
     async fn ensure_realm_exists(&self, realm_id: &Uuid) -> Result<()> {
         if self.realm_repo.find_by_id(realm_id).await?.is_none() {
             return Err(Error::RealmNotFound(realm_id.to_string()));
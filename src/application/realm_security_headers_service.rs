@@ -2,6 +2,7 @@ use crate::domain::realm_security_headers::RealmSecurityHeaders;
 use crate::error::{Error, Result};
 use crate::ports::realm_repository::RealmRepository;
 use crate::ports::realm_security_headers_repository::RealmSecurityHeadersRepository;
+use crate::ports::transaction_manager::Transaction;
 use serde::Deserialize;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -72,11 +73,21 @@ impl RealmSecurityHeadersService {
         }
 
         validate_settings(&settings)?;
-        self.headers_repo.upsert(&settings).await?;
+        self.headers_repo.upsert(&settings, None).await?;
 
         Ok(settings)
     }
 
+    pub async fn save_settings_with_tx(
+        &self,
+        settings: RealmSecurityHeaders,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<RealmSecurityHeaders> {
+        validate_settings(&settings)?;
+        self.headers_repo.upsert(&settings, tx).await?;
+        Ok(settings)
+    }
+
     async fn ensure_realm_exists(&self, realm_id: &Uuid) -> Result<()> {
         if self.realm_repo.find_by_id(realm_id).await?.is_none() {
             return Err(Error::RealmNotFound(realm_id.to_string()));
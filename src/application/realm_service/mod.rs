@@ -3,6 +3,7 @@ use crate::application::theme_service::ThemeResolverService;
 use crate::config::Settings;
 use crate::constants::DEFAULT_REALM_NAME;
 use crate::domain::realm::{RealmIdpDefaultEmailLinkPolicy, RealmIdpDefaultJitPolicy};
+use crate::domain::signing_key::SigningAlgorithm;
 use crate::ports::transaction_manager::{Transaction, TransactionManager};
 use crate::{
     domain::realm::Realm,
@@ -27,6 +28,7 @@ pub struct UpdateRealmPayload {
     pub access_token_ttl_secs: Option<i64>,
     pub refresh_token_ttl_secs: Option<i64>,
     pub pkce_required_public_clients: Option<bool>,
+    pub signing_algorithm: Option<SigningAlgorithm>,
     pub lockout_threshold: Option<i64>,
     pub lockout_duration_secs: Option<i64>,
     pub registration_enabled: Option<bool>,
@@ -87,6 +89,7 @@ impl RealmService {
                 access_token_ttl_secs: settings.auth.access_token_ttl_secs,
                 refresh_token_ttl_secs: settings.auth.refresh_token_ttl_secs,
                 pkce_required_public_clients: settings.auth.pkce_required_public_clients,
+                signing_algorithm: SigningAlgorithm::default(),
                 lockout_threshold: settings.auth.lockout_threshold,
                 lockout_duration_secs: settings.auth.lockout_duration_secs,
                 is_system,
@@ -195,6 +198,9 @@ impl RealmService {
         if let Some(value) = payload.pkce_required_public_clients {
             realm.pkce_required_public_clients = value;
         }
+        if let Some(value) = payload.signing_algorithm {
+            realm.signing_algorithm = value;
+        }
         if let Some(value) = payload.lockout_threshold {
             validate_lockout_value("lockout_threshold", value, MAX_LOCKOUT_THRESHOLD)?;
             realm.lockout_threshold = value;
@@ -3,6 +3,7 @@ use crate::application::flow_service::FlowService;
 use crate::application::theme_service::ThemeResolverService;
 use crate::domain::auth_flow::AuthFlow;
 use crate::domain::realm::{Realm, RealmIdpDefaultEmailLinkPolicy, RealmIdpDefaultJitPolicy};
+use crate::domain::signing_key::SigningAlgorithm;
 use crate::domain::theme::{
     Theme, ThemeAsset, ThemeAssetMeta, ThemeBinding, ThemeLayout, ThemeNode, ThemeTokens,
     ThemeVersion,
@@ -352,6 +353,7 @@ fn base_realm() -> Realm {
         access_token_ttl_secs: 300,
         refresh_token_ttl_secs: 900,
         pkce_required_public_clients: true,
+        signing_algorithm: SigningAlgorithm::default(),
         lockout_threshold: 5,
         lockout_duration_secs: 900,
         is_system: false,
@@ -383,6 +385,7 @@ async fn update_realm_errors_when_missing() {
                 access_token_ttl_secs: None,
                 refresh_token_ttl_secs: None,
                 pkce_required_public_clients: None,
+                signing_algorithm: None,
                 lockout_threshold: None,
                 lockout_duration_secs: None,
                 registration_enabled: None,
@@ -425,6 +428,7 @@ async fn update_realm_updates_selected_fields() {
                 access_token_ttl_secs: Some(111),
                 refresh_token_ttl_secs: Some(222),
                 pkce_required_public_clients: Some(false),
+                signing_algorithm: None,
                 lockout_threshold: Some(7),
                 lockout_duration_secs: Some(1200),
                 registration_enabled: None,
@@ -474,6 +478,7 @@ async fn update_realm_rejects_invalid_lockout_values() {
                 access_token_ttl_secs: None,
                 refresh_token_ttl_secs: None,
                 pkce_required_public_clients: None,
+                signing_algorithm: None,
                 lockout_threshold: Some(-1),
                 lockout_duration_secs: None,
                 registration_enabled: None,
@@ -506,6 +511,7 @@ async fn update_realm_rejects_invalid_lockout_values() {
                 access_token_ttl_secs: None,
                 refresh_token_ttl_secs: None,
                 pkce_required_public_clients: None,
+                signing_algorithm: None,
                 lockout_threshold: None,
                 lockout_duration_secs: Some(86_401),
                 registration_enabled: None,
@@ -549,6 +555,7 @@ async fn update_realm_with_tx_forwards_transaction() {
                 access_token_ttl_secs: None,
                 refresh_token_ttl_secs: None,
                 pkce_required_public_clients: None,
+                signing_algorithm: None,
                 lockout_threshold: None,
                 lockout_duration_secs: None,
                 registration_enabled: None,
@@ -0,0 +1,946 @@
+use crate::application::audit_service::AuditService;
+use crate::application::logout_service::LogoutService;
+use crate::application::rbac_service::RbacService;
+use crate::application::signing_key_service::SigningKeyService;
+use crate::domain::audit::NewAuditEvent;
+use crate::domain::auth_session::{AuthenticationSession, SessionStatus};
+use crate::domain::events::SessionRevocationReason;
+use crate::domain::execution::ExecutionPlan;
+use crate::domain::realm::Realm;
+use crate::domain::saml::{
+    self, AssertionAttribute, AssertionParams, AuthnRequest, IdpMetadata, LogoutRequest,
+    NameIdFormat, SamlAttributeMapping, SamlAttributeSource, SamlBinding, SamlLoginContext,
+    SamlServiceProvider, SAML_CONTEXT_KEY,
+};
+use crate::domain::session::RefreshToken;
+use crate::domain::user::User;
+use crate::error::{Error, Result};
+use crate::ports::auth_session_repository::AuthSessionRepository;
+use crate::ports::flow_store::FlowStore;
+use crate::ports::realm_repository::RealmRepository;
+use crate::ports::saml_service_provider_repository::SamlServiceProviderRepository;
+use crate::ports::session_repository::SessionRepository;
+use crate::ports::user_email_repository::UserEmailRepository;
+use crate::ports::user_repository::UserRepository;
+use chrono::{Duration, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashSet;
+use std::sync::Arc;
+use url::Url;
+use uuid::Uuid;
+
+/// How long an issued assertion may be presented to the service provider.
+const ASSERTION_LIFETIME_SECS: i64 = 300;
+const MAX_ENTITY_ID_LEN: usize = 1024;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSamlServiceProviderRequest {
+    pub entity_id: String,
+    pub name: Option<String>,
+    pub acs_urls: Vec<String>,
+    pub slo_url: Option<String>,
+    pub slo_binding: Option<SamlBinding>,
+    pub name_id_format: Option<NameIdFormat>,
+    pub attribute_mappings: Option<Vec<SamlAttributeMapping>>,
+    pub sign_response: Option<bool>,
+    /// PEM or base64 DER certificates the provider signs its requests with.
+    pub signing_certificates: Option<Vec<String>>,
+    pub require_signed_requests: Option<bool>,
+    pub allow_idp_initiated: Option<bool>,
+    pub enabled: Option<bool>,
+}
+
+/// Omitted fields keep their value; an empty `slo_url` removes it.
+#[derive(Debug, Deserialize)]
+pub struct UpdateSamlServiceProviderRequest {
+    pub entity_id: Option<String>,
+    pub name: Option<String>,
+    pub acs_urls: Option<Vec<String>>,
+    pub slo_url: Option<String>,
+    pub slo_binding: Option<SamlBinding>,
+    pub name_id_format: Option<NameIdFormat>,
+    pub attribute_mappings: Option<Vec<SamlAttributeMapping>>,
+    pub sign_response: Option<bool>,
+    /// PEM or base64 DER certificates the provider signs its requests with.
+    pub signing_certificates: Option<Vec<String>>,
+    pub require_signed_requests: Option<bool>,
+    pub allow_idp_initiated: Option<bool>,
+    pub enabled: Option<bool>,
+}
+
+/// A message the browser must POST to a service provider.
+#[derive(Debug, Clone)]
+pub struct SamlPostForm {
+    pub action: String,
+    /// `SAMLResponse`, base64.
+    pub saml_response: String,
+    pub relay_state: Option<String>,
+}
+
+/// Where the browser goes after single logout.
+#[derive(Debug, Clone)]
+pub enum SamlLogoutOutcome {
+    Redirect(String),
+    Post(SamlPostForm),
+}
+
+/// The realm's SAML 2.0 identity provider. Logins run the realm's browser
+/// flow, like OIDC authorization requests; the response is prepared once
+/// the flow succeeds and handed to the browser exactly once.
+pub struct SamlService {
+    repo: Arc<dyn SamlServiceProviderRepository>,
+    realm_repo: Arc<dyn RealmRepository>,
+    user_repo: Arc<dyn UserRepository>,
+    user_email_repo: Arc<dyn UserEmailRepository>,
+    auth_session_repo: Arc<dyn AuthSessionRepository>,
+    flow_store: Arc<dyn FlowStore>,
+    session_repo: Arc<dyn SessionRepository>,
+    rbac_service: Arc<RbacService>,
+    signing_key_service: Arc<SigningKeyService>,
+    logout_service: Arc<LogoutService>,
+    audit_service: Arc<AuditService>,
+    public_url: String,
+}
+
+impl SamlService {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        repo: Arc<dyn SamlServiceProviderRepository>,
+        realm_repo: Arc<dyn RealmRepository>,
+        user_repo: Arc<dyn UserRepository>,
+        user_email_repo: Arc<dyn UserEmailRepository>,
+        auth_session_repo: Arc<dyn AuthSessionRepository>,
+        flow_store: Arc<dyn FlowStore>,
+        session_repo: Arc<dyn SessionRepository>,
+        rbac_service: Arc<RbacService>,
+        signing_key_service: Arc<SigningKeyService>,
+        logout_service: Arc<LogoutService>,
+        audit_service: Arc<AuditService>,
+        public_url: String,
+    ) -> Self {
+        Self {
+            repo,
+            realm_repo,
+            user_repo,
+            user_email_repo,
+            auth_session_repo,
+            flow_store,
+            session_repo,
+            rbac_service,
+            signing_key_service,
+            logout_service,
+            audit_service,
+            public_url,
+        }
+    }
+
+    // --- Service provider registrations ---
+
+    pub async fn list(&self, realm_id: Uuid) -> Result<Vec<SamlServiceProvider>> {
+        self.repo.list_by_realm(&realm_id).await
+    }
+
+    pub async fn get(&self, realm_id: Uuid, id: Uuid) -> Result<SamlServiceProvider> {
+        self.repo
+            .find_by_id(&realm_id, &id)
+            .await?
+            .ok_or_else(|| Error::NotFound("SAML service provider not found".to_string()))
+    }
+
+    pub async fn create(
+        &self,
+        realm_id: Uuid,
+        request: CreateSamlServiceProviderRequest,
+    ) -> Result<SamlServiceProvider> {
+        let entity_id = request.entity_id.trim().to_string();
+        validate_entity_id(&entity_id)?;
+        self.ensure_entity_id_free(realm_id, &entity_id, None)
+            .await?;
+
+        let now = Utc::now();
+        let provider = SamlServiceProvider {
+            id: Uuid::new_v4(),
+            realm_id,
+            name: request
+                .name
+                .filter(|name| !name.trim().is_empty())
+                .unwrap_or_else(|| entity_id.clone()),
+            entity_id,
+            acs_urls: request.acs_urls,
+            slo_url: request.slo_url.filter(|url| !url.is_empty()),
+            slo_binding: request.slo_binding.unwrap_or(SamlBinding::Redirect),
+            name_id_format: request.name_id_format.unwrap_or(NameIdFormat::Unspecified),
+            attribute_mappings: request.attribute_mappings.unwrap_or_default(),
+            sign_response: request.sign_response.unwrap_or(false),
+            signing_certificates: normalize_certificates(
+                request.signing_certificates.unwrap_or_default(),
+            )?,
+            require_signed_requests: request.require_signed_requests.unwrap_or(false),
+            allow_idp_initiated: request.allow_idp_initiated.unwrap_or(false),
+            enabled: request.enabled.unwrap_or(true),
+            created_at: now,
+            updated_at: now,
+        };
+        validate_provider(&provider)?;
+        self.repo.create(&provider).await?;
+        Ok(provider)
+    }
+
+    pub async fn update(
+        &self,
+        realm_id: Uuid,
+        id: Uuid,
+        request: UpdateSamlServiceProviderRequest,
+    ) -> Result<SamlServiceProvider> {
+        let mut provider = self.get(realm_id, id).await?;
+
+        if let Some(entity_id) = request.entity_id {
+            let entity_id = entity_id.trim().to_string();
+            validate_entity_id(&entity_id)?;
+            self.ensure_entity_id_free(realm_id, &entity_id, Some(id))
+                .await?;
+            provider.entity_id = entity_id;
+        }
+        if let Some(value) = request.name.filter(|name| !name.trim().is_empty()) {
+            provider.name = value;
+        }
+        if let Some(value) = request.acs_urls {
+            provider.acs_urls = value;
+        }
+        if let Some(value) = request.slo_url {
+            provider.slo_url = Some(value).filter(|url| !url.is_empty());
+        }
+        if let Some(value) = request.slo_binding {
+            provider.slo_binding = value;
+        }
+        if let Some(value) = request.name_id_format {
+            provider.name_id_format = value;
+        }
+        if let Some(value) = request.attribute_mappings {
+            provider.attribute_mappings = value;
+        }
+        if let Some(value) = request.sign_response {
+            provider.sign_response = value;
+        }
+        if let Some(value) = request.signing_certificates {
+            provider.signing_certificates = normalize_certificates(value)?;
+        }
+        if let Some(value) = request.require_signed_requests {
+            provider.require_signed_requests = value;
+        }
+        if let Some(value) = request.allow_idp_initiated {
+            provider.allow_idp_initiated = value;
+        }
+        if let Some(value) = request.enabled {
+            provider.enabled = value;
+        }
+        validate_provider(&provider)?;
+        provider.updated_at = Utc::now();
+        self.repo.update(&provider).await?;
+        Ok(provider)
+    }
+
+    pub async fn delete(&self, realm_id: Uuid, id: Uuid) -> Result<()> {
+        if !self.repo.delete(&realm_id, &id).await? {
+            return Err(Error::NotFound(
+                "SAML service provider not found".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn ensure_entity_id_free(
+        &self,
+        realm_id: Uuid,
+        entity_id: &str,
+        current: Option<Uuid>,
+    ) -> Result<()> {
+        match self.repo.find_by_entity_id(&realm_id, entity_id).await? {
+            Some(existing) if Some(existing.id) != current => Err(Error::Validation(
+                "A SAML service provider with this entity ID already exists in this realm"
+                    .to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    // --- Identity provider endpoints ---
+
+    /// The IdP's entity ID, which is also where its metadata is served.
+    pub fn entity_id(&self, realm: &Realm) -> String {
+        self.endpoint(realm, "metadata")
+    }
+
+    pub fn sso_url(&self, realm: &Realm) -> String {
+        self.endpoint(realm, "sso")
+    }
+
+    pub fn slo_url(&self, realm: &Realm) -> String {
+        self.endpoint(realm, "slo")
+    }
+
+    fn endpoint(&self, realm: &Realm, path: &str) -> String {
+        format!(
+            "{}/api/realms/{}/saml/{}",
+            self.public_url.trim_end_matches('/'),
+            realm.name,
+            path
+        )
+    }
+
+    /// The IdP's `EntityDescriptor`, listing a certificate for every realm
+    /// RSA key that can still verify so service providers can follow
+    /// rotation.
+    pub async fn metadata(&self, realm: &Realm) -> Result<String> {
+        let certificates = self.signing_key_service.saml_certificates(realm.id).await?;
+        let name_id_formats: Vec<&str> = NameIdFormat::ALL
+            .iter()
+            .map(|format| format.uri())
+            .collect();
+        Ok(saml::build_idp_metadata(&IdpMetadata {
+            entity_id: &self.entity_id(realm),
+            sso_url: &self.sso_url(realm),
+            slo_url: &self.slo_url(realm),
+            certificates: &certificates,
+            name_id_formats: &name_id_formats,
+        }))
+    }
+
+    // --- Single sign-on ---
+
+    /// Starts the browser flow for an AuthnRequest received over `binding`.
+    /// `raw_query` is the undecoded query of an HTTP-Redirect request, which
+    /// its signature covers.
+    pub async fn start_sp_initiated_login(
+        &self,
+        realm: &Realm,
+        binding: SamlBinding,
+        saml_request: &str,
+        relay_state: Option<String>,
+        raw_query: Option<&str>,
+    ) -> Result<AuthenticationSession> {
+        let xml = decode_message(binding, saml_request)?;
+        let request = AuthnRequest::parse(&xml)?;
+        if let Some(destination) = saml_destination(&xml)? {
+            if destination != self.sso_url(realm) {
+                return Err(Error::SamlInvalidRequest(
+                    "AuthnRequest Destination does not match this endpoint".to_string(),
+                ));
+            }
+        }
+        if request
+            .protocol_binding
+            .as_deref()
+            .is_some_and(|binding| binding != saml::BINDING_POST)
+        {
+            return Err(Error::SamlInvalidRequest(
+                "Responses are only sent with the HTTP-POST binding".to_string(),
+            ));
+        }
+
+        let provider = self.enabled_provider(realm.id, &request.issuer).await?;
+        verify_request_signature(
+            &provider,
+            binding,
+            &xml,
+            raw_query,
+            provider.require_signed_requests,
+        )?;
+        if let Some(format) = request.name_id_policy_format.as_deref() {
+            let requested = NameIdFormat::from_uri(format).ok_or_else(|| {
+                Error::SamlInvalidRequest("Unsupported NameIDPolicy format".to_string())
+            })?;
+            if requested != NameIdFormat::Unspecified && requested != provider.name_id_format {
+                return Err(Error::SamlInvalidRequest(
+                    "NameIDPolicy format is not configured for this service provider".to_string(),
+                ));
+            }
+        }
+        let acs_url =
+            provider.resolve_acs_url(request.assertion_consumer_service_url.as_deref())?;
+
+        self.create_browser_flow_session(
+            realm,
+            SamlLoginContext {
+                service_provider_id: provider.id,
+                acs_url,
+                request_id: Some(request.id),
+                relay_state,
+                response: None,
+            },
+        )
+        .await
+    }
+
+    /// Starts the browser flow for an unsolicited response to `entity_id`.
+    pub async fn start_idp_initiated_login(
+        &self,
+        realm: &Realm,
+        entity_id: &str,
+        relay_state: Option<String>,
+    ) -> Result<AuthenticationSession> {
+        let provider = self.enabled_provider(realm.id, entity_id).await?;
+        if !provider.allow_idp_initiated {
+            return Err(Error::SamlInvalidRequest(
+                "IdP-initiated login is disabled for this service provider".to_string(),
+            ));
+        }
+        let acs_url = provider.resolve_acs_url(None)?;
+        self.create_browser_flow_session(
+            realm,
+            SamlLoginContext {
+                service_provider_id: provider.id,
+                acs_url,
+                request_id: None,
+                relay_state,
+                response: None,
+            },
+        )
+        .await
+    }
+
+    /// Called when a SAML login's browser flow succeeds: builds and signs the
+    /// response and keeps it on the auth session until the browser collects
+    /// it. `session_index` is the family of the user's SSO session, which
+    /// the service provider echoes back on logout.
+    pub async fn complete_login(
+        &self,
+        session_id: Uuid,
+        user_id: Uuid,
+        session_index: Uuid,
+    ) -> Result<()> {
+        let mut session = self
+            .auth_session_repo
+            .find_by_id(&session_id)
+            .await?
+            .ok_or(Error::InvalidLoginSession)?;
+        let mut context = login_context(&session)?;
+        let provider = self
+            .repo
+            .find_by_id(&session.realm_id, &context.service_provider_id)
+            .await?
+            .filter(|provider| provider.enabled)
+            .ok_or_else(|| {
+                Error::SamlInvalidRequest("Service provider is no longer available".to_string())
+            })?;
+        let user = self
+            .user_repo
+            .find_by_id(&user_id)
+            .await?
+            .ok_or(Error::UserNotFound)?;
+        let realm = self
+            .realm_repo
+            .find_by_id(&session.realm_id)
+            .await?
+            .ok_or_else(|| Error::RealmNotFound(session.realm_id.to_string()))?;
+        let issuer = self.entity_id(&realm);
+
+        let name_id = self.name_id(&provider, &user).await?;
+        let attributes = self.attributes(&provider, &user).await?;
+        let key = self
+            .signing_key_service
+            .saml_signing_key(session.realm_id)
+            .await?;
+        let issued_at = Utc::now();
+        let session_index = session_index.to_string();
+
+        let assertion = saml::build_assertion(
+            &saml::new_message_id(),
+            &AssertionParams {
+                issuer: &issuer,
+                audience: &provider.entity_id,
+                recipient: &context.acs_url,
+                in_response_to: context.request_id.as_deref(),
+                name_id: &name_id,
+                name_id_format: provider.name_id_format.uri(),
+                session_index: &session_index,
+                attributes: &attributes,
+                issued_at,
+                lifetime: Duration::seconds(ASSERTION_LIFETIME_SECS),
+            },
+        );
+        let assertion = saml::sign_enveloped(
+            &assertion,
+            "</saml:Issuer>",
+            &key.private_key,
+            &key.certificate,
+        )?;
+        let mut response = saml::build_response(
+            &saml::new_message_id(),
+            &issuer,
+            &context.acs_url,
+            context.request_id.as_deref(),
+            issued_at,
+            saml::STATUS_SUCCESS,
+            Some(&assertion),
+        );
+        if provider.sign_response {
+            response = saml::sign_enveloped(
+                &response,
+                "</saml:Issuer>",
+                &key.private_key,
+                &key.certificate,
+            )?;
+        }
+
+        context.response = Some(saml::encode_post_message(&response));
+        session.update_context(
+            SAML_CONTEXT_KEY,
+            serde_json::to_value(&context).map_err(|e| Error::Unexpected(e.into()))?,
+        );
+        self.auth_session_repo.update(&session).await?;
+
+        self.audit_service
+            .record(NewAuditEvent {
+                realm_id: session.realm_id,
+                actor_user_id: Some(user_id),
+                action: "saml_assertion_issued".to_string(),
+                target_type: "saml_service_provider".to_string(),
+                target_id: Some(provider.id.to_string()),
+                metadata: json!({
+                    "entity_id": provider.entity_id,
+                    "acs_url": context.acs_url,
+                    "idp_initiated": context.request_id.is_none(),
+                }),
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Hands out the prepared response of a completed SAML login, once.
+    pub async fn take_response(&self, realm_id: Uuid, session_id: Uuid) -> Result<SamlPostForm> {
+        let session = self
+            .auth_session_repo
+            .find_by_id(&session_id)
+            .await?
+            .filter(|session| {
+                session.realm_id == realm_id && session.status == SessionStatus::Completed
+            })
+            .ok_or(Error::InvalidLoginSession)?;
+        let context = login_context(&session)?;
+        let saml_response = context.response.ok_or(Error::InvalidLoginSession)?;
+        self.auth_session_repo.delete(&session.id).await?;
+
+        Ok(SamlPostForm {
+            action: context.acs_url,
+            saml_response,
+            relay_state: context.relay_state,
+        })
+    }
+
+    // --- Single logout ---
+
+    /// Ends the SSO sessions a service provider's LogoutRequest names, or
+    /// the browser's own session when it names none, and answers on the
+    /// provider's SLO endpoint. `browser_session` is the refresh token in
+    /// the browser's SSO cookie. LogoutRequests end sessions, so they must
+    /// always be signed.
+    pub async fn logout(
+        &self,
+        realm: &Realm,
+        binding: SamlBinding,
+        saml_request: &str,
+        relay_state: Option<String>,
+        raw_query: Option<&str>,
+        browser_session: Option<Uuid>,
+    ) -> Result<SamlLogoutOutcome> {
+        let xml = decode_message(binding, saml_request)?;
+        let request = LogoutRequest::parse(&xml)?;
+        let provider = self.enabled_provider(realm.id, &request.issuer).await?;
+        verify_request_signature(&provider, binding, &xml, raw_query, true)?;
+        let slo_url = provider.slo_url.clone().ok_or_else(|| {
+            Error::SamlInvalidRequest("Service provider has no single logout endpoint".to_string())
+        })?;
+
+        let mut sessions = Vec::new();
+        for index in &request.session_indexes {
+            let Ok(family_id) = Uuid::parse_str(index) else {
+                continue;
+            };
+            let Some(token) = self.session_repo.find_active_in_family(&family_id).await? else {
+                continue;
+            };
+            if token.realm_id == realm.id
+                && self.name_id_matches(&provider, &request, &token).await?
+            {
+                sessions.push(token);
+            }
+        }
+        if let Some(token_id) = browser_session {
+            if let Some(token) = self.session_repo.find_by_id(&token_id).await? {
+                let belongs = token.realm_id == realm.id
+                    && token.revoked_at.is_none()
+                    && if request.session_indexes.is_empty() {
+                        self.name_id_matches(&provider, &request, &token).await?
+                    } else {
+                        sessions
+                            .iter()
+                            .any(|session| session.user_id == token.user_id)
+                    };
+                if belongs && !sessions.iter().any(|s| s.family_id == token.family_id) {
+                    sessions.push(token);
+                }
+            }
+        }
+
+        let families: HashSet<Uuid> = sessions.iter().map(|session| session.family_id).collect();
+        for family_id in &families {
+            self.session_repo.revoke_family(family_id).await?;
+        }
+        self.logout_service
+            .sessions_ended(&sessions, SessionRevocationReason::Logout)
+            .await;
+
+        self.audit_service
+            .record(NewAuditEvent {
+                realm_id: realm.id,
+                actor_user_id: sessions.first().map(|session| session.user_id),
+                action: "saml_logout".to_string(),
+                target_type: "saml_service_provider".to_string(),
+                target_id: Some(provider.id.to_string()),
+                metadata: json!({
+                    "entity_id": provider.entity_id,
+                    "sessions_ended": families.len(),
+                }),
+            })
+            .await?;
+
+        let key = self.signing_key_service.saml_signing_key(realm.id).await?;
+        let response = saml::build_logout_response(
+            &saml::new_message_id(),
+            &self.entity_id(realm),
+            &slo_url,
+            &request.id,
+            Utc::now(),
+            saml::STATUS_SUCCESS,
+        );
+
+        match provider.slo_binding {
+            SamlBinding::Redirect => {
+                let mut query = format!(
+                    "SAMLResponse={}",
+                    urlencoding::encode(&saml::encode_redirect_message(&response)?)
+                );
+                if let Some(relay_state) = &relay_state {
+                    query.push_str(&format!("&RelayState={}", urlencoding::encode(relay_state)));
+                }
+                query.push_str(&format!(
+                    "&SigAlg={}",
+                    urlencoding::encode(saml::RSA_SHA256)
+                ));
+                let signature = saml::sign_query(&query, &key.private_key)?;
+                query.push_str(&format!("&Signature={}", urlencoding::encode(&signature)));
+
+                let separator = if slo_url.contains('?') { '&' } else { '?' };
+                Ok(SamlLogoutOutcome::Redirect(format!(
+                    "{}{}{}",
+                    slo_url, separator, query
+                )))
+            }
+            SamlBinding::Post => {
+                let response = saml::sign_enveloped(
+                    &response,
+                    "</saml:Issuer>",
+                    &key.private_key,
+                    &key.certificate,
+                )?;
+                Ok(SamlLogoutOutcome::Post(SamlPostForm {
+                    action: slo_url,
+                    saml_response: saml::encode_post_message(&response),
+                    relay_state,
+                }))
+            }
+        }
+    }
+
+    // --- Helpers ---
+
+    async fn enabled_provider(
+        &self,
+        realm_id: Uuid,
+        entity_id: &str,
+    ) -> Result<SamlServiceProvider> {
+        self.repo
+            .find_by_entity_id(&realm_id, entity_id)
+            .await?
+            .filter(|provider| provider.enabled)
+            .ok_or_else(|| Error::SamlInvalidRequest("Unknown service provider".to_string()))
+    }
+
+    async fn create_browser_flow_session(
+        &self,
+        realm: &Realm,
+        context: SamlLoginContext,
+    ) -> Result<AuthenticationSession> {
+        let flow_id = realm.browser_flow_id.as_deref().ok_or(Error::Validation(
+            "Realm has no browser flow configured".to_string(),
+        ))?;
+        let flow_id = Uuid::parse_str(flow_id).unwrap_or_default();
+        let version = self
+            .flow_store
+            .get_active_version(&flow_id)
+            .await?
+            .or(self.flow_store.get_latest_version(&flow_id).await?)
+            .ok_or(Error::NotFound("Flow version not found".to_string()))?;
+        let plan: ExecutionPlan = serde_json::from_str(&version.execution_artifact)
+            .map_err(|e| Error::Unexpected(anyhow::anyhow!("Corrupt execution artifact: {}", e)))?;
+
+        let context = serde_json::to_value(&context).map_err(|e| Error::Unexpected(e.into()))?;
+        let now = Utc::now();
+        let session = AuthenticationSession {
+            id: Uuid::new_v4(),
+            realm_id: realm.id,
+            flow_version_id: Uuid::parse_str(&version.id).unwrap_or_default(),
+            current_node_id: plan.start_node_id,
+            context: json!({ SAML_CONTEXT_KEY: context }),
+            status: SessionStatus::Active,
+            user_id: None,
+            created_at: now,
+            updated_at: now,
+            expires_at: now + Duration::minutes(30),
+        };
+        self.auth_session_repo.create(&session).await?;
+        Ok(session)
+    }
+
+    async fn name_id(&self, provider: &SamlServiceProvider, user: &User) -> Result<String> {
+        Ok(match provider.name_id_format {
+            NameIdFormat::Unspecified => user.username.clone(),
+            NameIdFormat::EmailAddress => self.primary_email(user).await?.ok_or_else(|| {
+                Error::SamlInvalidRequest(
+                    "The service provider requires an email address and the user has none"
+                        .to_string(),
+                )
+            })?,
+            NameIdFormat::Persistent => user.id.to_string(),
+            NameIdFormat::Transient => saml::new_message_id(),
+        })
+    }
+
+    /// Whether a LogoutRequest's NameID names the owner of `token`. Transient
+    /// identifiers cannot be checked after the fact, so the session index
+    /// alone decides for them.
+    async fn name_id_matches(
+        &self,
+        provider: &SamlServiceProvider,
+        request: &LogoutRequest,
+        token: &RefreshToken,
+    ) -> Result<bool> {
+        if provider.name_id_format == NameIdFormat::Transient {
+            return Ok(!request.session_indexes.is_empty());
+        }
+        let Some(name_id) = request.name_id.as_deref() else {
+            return Ok(false);
+        };
+        let Some(user) = self.user_repo.find_by_id(&token.user_id).await? else {
+            return Ok(false);
+        };
+        Ok(self.name_id(provider, &user).await.ok().as_deref() == Some(name_id))
+    }
+
+    async fn attributes(
+        &self,
+        provider: &SamlServiceProvider,
+        user: &User,
+    ) -> Result<Vec<AssertionAttribute>> {
+        let needs = |source: SamlAttributeSource| {
+            provider
+                .attribute_mappings
+                .iter()
+                .any(|mapping| mapping.source == source)
+        };
+        let email = if needs(SamlAttributeSource::Email) {
+            self.primary_email(user).await?
+        } else {
+            None
+        };
+        let (roles, groups) =
+            if needs(SamlAttributeSource::Roles) || needs(SamlAttributeSource::Groups) {
+                self.rbac_service
+                    .get_user_roles_and_groups(&user.id)
+                    .await?
+            } else {
+                (Vec::new(), Vec::new())
+            };
+
+        Ok(provider
+            .attribute_mappings
+            .iter()
+            .filter_map(|mapping| {
+                let values = match mapping.source {
+                    SamlAttributeSource::UserId => vec![user.id.to_string()],
+                    SamlAttributeSource::Username => vec![user.username.clone()],
+                    SamlAttributeSource::Email => email.iter().cloned().collect(),
+                    SamlAttributeSource::GivenName => user.first_name.iter().cloned().collect(),
+                    SamlAttributeSource::FamilyName => user.last_name.iter().cloned().collect(),
+                    SamlAttributeSource::Roles => roles.clone(),
+                    SamlAttributeSource::Groups => groups.clone(),
+                };
+                (!values.is_empty()).then(|| AssertionAttribute {
+                    name: mapping.name.clone(),
+                    friendly_name: mapping.friendly_name.clone(),
+                    values,
+                })
+            })
+            .collect())
+    }
+
+    async fn primary_email(&self, user: &User) -> Result<Option<String>> {
+        Ok(self
+            .user_email_repo
+            .find_primary(&user.id)
+            .await?
+            .map(|email| email.email))
+    }
+}
+
+fn login_context(session: &AuthenticationSession) -> Result<SamlLoginContext> {
+    session
+        .context
+        .get(SAML_CONTEXT_KEY)
+        .cloned()
+        .and_then(|value| serde_json::from_value(value).ok())
+        .ok_or(Error::InvalidLoginSession)
+}
+
+fn decode_message(binding: SamlBinding, value: &str) -> Result<String> {
+    match binding {
+        SamlBinding::Redirect => saml::decode_redirect_message(value),
+        SamlBinding::Post => saml::decode_post_message(value),
+    }
+}
+
+/// The `Destination` attribute of a protocol message's root, if any.
+fn saml_destination(xml: &str) -> Result<Option<String>> {
+    let root = saml::xml::XmlElement::parse(xml)?;
+    Ok(root.attribute("Destination").map(str::to_string))
+}
+
+fn normalize_certificates(certificates: Vec<String>) -> Result<Vec<String>> {
+    certificates
+        .iter()
+        .map(|certificate| saml::broker::normalize_certificate(certificate))
+        .collect()
+}
+
+/// Checks the signature on a request from `provider`: over the raw query for
+/// the HTTP-Redirect binding, enveloped in the message for HTTP-POST. Without
+/// a registered certificate a signature cannot be checked, so the request
+/// counts as unsigned.
+fn verify_request_signature(
+    provider: &SamlServiceProvider,
+    binding: SamlBinding,
+    xml: &str,
+    raw_query: Option<&str>,
+    required: bool,
+) -> Result<()> {
+    let invalid = |message: &str| Error::SamlInvalidRequest(message.to_string());
+    if provider.signing_certificates.is_empty() {
+        return if required {
+            Err(invalid(
+                "Service provider has no certificate to verify signed requests",
+            ))
+        } else {
+            Ok(())
+        };
+    }
+    let keys = saml::broker::certificate_keys(&provider.signing_certificates)?;
+    match binding {
+        SamlBinding::Redirect => match raw_query.map(redirect_signature).transpose()?.flatten() {
+            Some((signed, signature)) => saml::verify_query(&signed, &signature, &keys),
+            None if required => Err(invalid("Request must be signed")),
+            None => Ok(()),
+        },
+        SamlBinding::Post => {
+            let root = saml::xml::XmlElement::parse(xml)?;
+            if root.child(saml::XMLDSIG_NS, "Signature").is_some() {
+                saml::verify_enveloped(&root, &keys)
+            } else if required {
+                Err(invalid("Request must be signed"))
+            } else {
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The signed octets and decoded `Signature` of an HTTP-Redirect query, or
+/// `None` when it is unsigned. The octets are rebuilt from the parameters as
+/// received, in the order the binding specifies.
+fn redirect_signature(raw_query: &str) -> Result<Option<(String, String)>> {
+    let invalid = |message: &str| Error::SamlInvalidRequest(message.to_string());
+    let param = |name: &str| {
+        raw_query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+    };
+    let Some(signature) = param("Signature") else {
+        return Ok(None);
+    };
+    let sig_alg = param("SigAlg").ok_or_else(|| invalid("Signed request has no SigAlg"))?;
+    let decoded_alg =
+        urlencoding::decode(sig_alg).map_err(|_| invalid("Invalid SigAlg encoding"))?;
+    if decoded_alg != saml::RSA_SHA256 {
+        return Err(invalid("Unsupported signature algorithm"));
+    }
+    let saml_request = param("SAMLRequest").ok_or_else(|| invalid("SAMLRequest is required"))?;
+
+    let mut signed = format!("SAMLRequest={}", saml_request);
+    if let Some(relay_state) = param("RelayState") {
+        signed.push_str(&format!("&RelayState={}", relay_state));
+    }
+    signed.push_str(&format!("&SigAlg={}", sig_alg));
+    let signature = urlencoding::decode(signature)
+        .map_err(|_| invalid("Invalid Signature encoding"))?
+        .into_owned();
+    Ok(Some((signed, signature)))
+}
+
+fn validate_entity_id(entity_id: &str) -> Result<()> {
+    if entity_id.is_empty() || entity_id.len() > MAX_ENTITY_ID_LEN {
+        return Err(Error::Validation(
+            "entity_id must be between 1 and 1024 characters".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_provider(provider: &SamlServiceProvider) -> Result<()> {
+    if provider.acs_urls.is_empty() {
+        return Err(Error::Validation(
+            "At least one assertion consumer service URL is required".to_string(),
+        ));
+    }
+    for url in provider.acs_urls.iter().chain(provider.slo_url.iter()) {
+        let valid = Url::parse(url)
+            .map(|parsed| matches!(parsed.scheme(), "http" | "https"))
+            .unwrap_or(false);
+        if !valid {
+            return Err(Error::Validation(format!(
+                "Invalid SAML endpoint URL: {}",
+                url
+            )));
+        }
+    }
+    if provider.require_signed_requests && provider.signing_certificates.is_empty() {
+        return Err(Error::Validation(
+            "Requiring signed requests needs a signing certificate".to_string(),
+        ));
+    }
+    let mut names = HashSet::new();
+    for mapping in &provider.attribute_mappings {
+        if mapping.name.trim().is_empty() {
+            return Err(Error::Validation(
+                "Attribute mapping names must not be empty".to_string(),
+            ));
+        }
+        if !names.insert(mapping.name.as_str()) {
+            return Err(Error::Validation(format!(
+                "Duplicate attribute mapping: {}",
+                mapping.name
+            )));
+        }
+    }
+    Ok(())
+}
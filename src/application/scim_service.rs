@@ -0,0 +1,662 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use rand::distr::{Alphanumeric, SampleString};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::application::oidc_service::OidcService;
+use crate::application::rbac_service::{CreateGroupPayload, RbacService};
+use crate::application::user_email_service::UserEmailService;
+use crate::application::user_service::UserService;
+use crate::domain::group::Group;
+use crate::domain::pagination::PageRequest;
+use crate::domain::scim::{
+    self, PatchRequest, ScimFilter, ScimGroupInput, ScimResourceType, ScimUserInput, MAX_RESULTS,
+};
+use crate::domain::user::{User, UserListFilters};
+use crate::error::{Error, Result};
+use crate::ports::scim_repository::ScimRepository;
+use crate::ports::token_service::TokenService;
+
+const SCAN_PAGE_SIZE: i64 = 100;
+
+/// The client a SCIM request authenticated as.
+#[derive(Debug, Clone)]
+pub struct ScimCaller {
+    pub client_id: String,
+    pub realm_id: Uuid,
+    scopes: HashSet<String>,
+}
+
+impl ScimCaller {
+    /// Client roles cannot hold system permissions, so SCIM access is granted
+    /// through the scopes the client requested.
+    pub fn require(&self, scope: &str) -> Result<()> {
+        if self.scopes.contains(scope) {
+            Ok(())
+        } else {
+            Err(Error::InsufficientPermissions)
+        }
+    }
+}
+
+/// Paging of a list request. `start_index` is 1-based.
+#[derive(Debug, Clone, Copy)]
+pub struct ScimPage {
+    pub start_index: i64,
+    pub count: i64,
+}
+
+impl ScimPage {
+    pub fn new(start_index: Option<i64>, count: Option<i64>) -> Self {
+        Self {
+            start_index: start_index.unwrap_or(1).max(1),
+            count: count.unwrap_or(MAX_RESULTS).clamp(0, MAX_RESULTS),
+        }
+    }
+
+    fn slice<T>(&self, items: Vec<T>) -> Vec<T> {
+        items
+            .into_iter()
+            .skip((self.start_index - 1) as usize)
+            .take(self.count as usize)
+            .collect()
+    }
+}
+
+/// Serves a realm's users and groups over SCIM 2.0. Every change goes
+/// through `UserService`, `UserEmailService` and `RbacService`, so domain
+/// events and webhooks fire as they do for the admin API.
+pub struct ScimService {
+    token_service: Arc<dyn TokenService>,
+    oidc_service: Arc<OidcService>,
+    user_service: Arc<UserService>,
+    user_email_service: Arc<UserEmailService>,
+    rbac_service: Arc<RbacService>,
+    scim_repo: Arc<dyn ScimRepository>,
+}
+
+impl ScimService {
+    pub fn new(
+        token_service: Arc<dyn TokenService>,
+        oidc_service: Arc<OidcService>,
+        user_service: Arc<UserService>,
+        user_email_service: Arc<UserEmailService>,
+        rbac_service: Arc<RbacService>,
+        scim_repo: Arc<dyn ScimRepository>,
+    ) -> Self {
+        Self {
+            token_service,
+            oidc_service,
+            user_service,
+            user_email_service,
+            rbac_service,
+            scim_repo,
+        }
+    }
+
+    /// Accepts client_credentials access tokens of a client in `realm_id`.
+    pub async fn authenticate(&self, realm_id: Uuid, token: &str) -> Result<ScimCaller> {
+        let invalid = || Error::OidcInvalidToken("The access token is invalid".to_string());
+        let claims = self
+            .token_service
+            .validate_access_token(realm_id, token)
+            .await
+            .map_err(|_| invalid())?;
+        if !claims.sid.is_nil() {
+            return Err(Error::OidcInvalidToken(
+                "SCIM requires a client_credentials access token".to_string(),
+            ));
+        }
+        let client = self
+            .oidc_service
+            .get_client(claims.sub)
+            .await
+            .map_err(|_| invalid())?;
+        if client.realm_id != realm_id {
+            return Err(invalid());
+        }
+        Ok(ScimCaller {
+            client_id: client.client_id,
+            realm_id,
+            scopes: claims
+                .scope
+                .as_deref()
+                .unwrap_or_default()
+                .split_whitespace()
+                .map(str::to_string)
+                .collect(),
+        })
+    }
+
+    // --- Users ---
+
+    pub async fn list_users(
+        &self,
+        realm_id: Uuid,
+        filter: Option<&ScimFilter>,
+        page: ScimPage,
+        base_url: &str,
+    ) -> Result<Value> {
+        let Some(filter) = filter else {
+            let users = self.all_users(realm_id).await?;
+            let total = users.len();
+            let mut resources = Vec::new();
+            for user in page.slice(users) {
+                resources.push(self.user_resource(&user, base_url).await?);
+            }
+            return Ok(scim::list_response(total, page.start_index, resources));
+        };
+
+        // The filters provisioning clients send to find a user before
+        // creating it are answered without a scan. Usernames are unique as
+        // stored, so the userName lookup is exact.
+        let candidates = if let Some(username) = filter.equality_on("userName") {
+            self.user_service
+                .find_by_username(&realm_id, username)
+                .await?
+                .into_iter()
+                .collect()
+        } else if let Some(external_id) = filter.equality_on("externalId") {
+            match self
+                .scim_repo
+                .find_by_external_id(ScimResourceType::User, &realm_id, external_id)
+                .await?
+            {
+                Some(user_id) => vec![
+                    self.user_service
+                        .get_user_in_realm(realm_id, user_id)
+                        .await?,
+                ],
+                None => Vec::new(),
+            }
+        } else {
+            self.all_users(realm_id).await?
+        };
+
+        let mut matched = Vec::new();
+        for user in candidates {
+            let resource = self.user_resource(&user, base_url).await?;
+            if filter.matches(&resource) {
+                matched.push(resource);
+            }
+        }
+        let total = matched.len();
+        Ok(scim::list_response(
+            total,
+            page.start_index,
+            page.slice(matched),
+        ))
+    }
+
+    pub async fn get_user(&self, realm_id: Uuid, user_id: Uuid, base_url: &str) -> Result<Value> {
+        let user = self
+            .user_service
+            .get_user_in_realm(realm_id, user_id)
+            .await?;
+        self.user_resource(&user, base_url).await
+    }
+
+    /// Creates a user. Without a password, the user gets a random one and
+    /// signs in some other way (an identity provider, a recovery link).
+    pub async fn create_user(
+        &self,
+        realm_id: Uuid,
+        resource: &Value,
+        base_url: &str,
+    ) -> Result<Value> {
+        let input = ScimUserInput::from_resource(resource)?;
+        self.ensure_user_input_free(realm_id, &input, None).await?;
+
+        let (password, generated) = match &input.password {
+            Some(password) => (password.clone(), false),
+            None => (Alphanumeric.sample_string(&mut rand::rng(), 32), true),
+        };
+        let user = self
+            .user_service
+            .create_user(
+                realm_id,
+                &input.user_name,
+                &password,
+                input.emails.first().map(String::as_str),
+                generated,
+            )
+            .await?;
+
+        let user_id = user.id;
+        let input = ScimUserInput {
+            password: None,
+            ..input
+        };
+        self.apply_user(realm_id, user, &input).await?;
+        self.get_user(realm_id, user_id, base_url).await
+    }
+
+    pub async fn replace_user(
+        &self,
+        realm_id: Uuid,
+        user_id: Uuid,
+        resource: &Value,
+        base_url: &str,
+    ) -> Result<Value> {
+        let user = self
+            .user_service
+            .get_user_in_realm(realm_id, user_id)
+            .await?;
+        let input = ScimUserInput::from_resource(resource)?;
+        self.ensure_user_input_free(realm_id, &input, Some(user_id))
+            .await?;
+        self.apply_user(realm_id, user, &input).await?;
+        self.get_user(realm_id, user_id, base_url).await
+    }
+
+    pub async fn patch_user(
+        &self,
+        realm_id: Uuid,
+        user_id: Uuid,
+        patch: &PatchRequest,
+        base_url: &str,
+    ) -> Result<Value> {
+        let mut resource = self.get_user(realm_id, user_id, base_url).await?;
+        patch.apply(&mut resource)?;
+        self.replace_user(realm_id, user_id, &resource, base_url)
+            .await
+    }
+
+    pub async fn delete_user(&self, realm_id: Uuid, user_id: Uuid) -> Result<()> {
+        self.user_service
+            .get_user_in_realm(realm_id, user_id)
+            .await?;
+        self.user_service
+            .delete_users(&realm_id, &[user_id])
+            .await?;
+        Ok(())
+    }
+
+    // --- Groups ---
+
+    pub async fn list_groups(
+        &self,
+        realm_id: Uuid,
+        filter: Option<&ScimFilter>,
+        page: ScimPage,
+        base_url: &str,
+    ) -> Result<Value> {
+        let groups = self.all_groups(realm_id).await?;
+        let Some(filter) = filter else {
+            let total = groups.len();
+            let mut resources = Vec::new();
+            for group in page.slice(groups) {
+                resources.push(self.group_resource(&group, base_url).await?);
+            }
+            return Ok(scim::list_response(total, page.start_index, resources));
+        };
+
+        let mut matched = Vec::new();
+        for group in groups {
+            let resource = self.group_resource(&group, base_url).await?;
+            if filter.matches(&resource) {
+                matched.push(resource);
+            }
+        }
+        let total = matched.len();
+        Ok(scim::list_response(
+            total,
+            page.start_index,
+            page.slice(matched),
+        ))
+    }
+
+    pub async fn get_group(&self, realm_id: Uuid, group_id: Uuid, base_url: &str) -> Result<Value> {
+        let group = self.group_in_realm(realm_id, group_id).await?;
+        self.group_resource(&group, base_url).await
+    }
+
+    pub async fn create_group(
+        &self,
+        realm_id: Uuid,
+        resource: &Value,
+        base_url: &str,
+    ) -> Result<Value> {
+        let input = ScimGroupInput::from_resource(resource)?;
+        self.ensure_members_in_realm(realm_id, &input.member_ids)
+            .await?;
+        self.ensure_external_id_free(
+            ScimResourceType::Group,
+            realm_id,
+            input.external_id.as_deref(),
+            None,
+        )
+        .await?;
+
+        let group = self
+            .rbac_service
+            .create_group(
+                realm_id,
+                CreateGroupPayload {
+                    name: input.display_name.clone(),
+                    description: None,
+                    parent_id: None,
+                },
+            )
+            .await?;
+        let group_id = group.id;
+        self.apply_group(realm_id, group, &input).await?;
+        self.get_group(realm_id, group_id, base_url).await
+    }
+
+    pub async fn replace_group(
+        &self,
+        realm_id: Uuid,
+        group_id: Uuid,
+        resource: &Value,
+        base_url: &str,
+    ) -> Result<Value> {
+        let group = self.group_in_realm(realm_id, group_id).await?;
+        let input = ScimGroupInput::from_resource(resource)?;
+        self.ensure_members_in_realm(realm_id, &input.member_ids)
+            .await?;
+        self.ensure_external_id_free(
+            ScimResourceType::Group,
+            realm_id,
+            input.external_id.as_deref(),
+            Some(group_id),
+        )
+        .await?;
+        self.apply_group(realm_id, group, &input).await?;
+        self.get_group(realm_id, group_id, base_url).await
+    }
+
+    pub async fn patch_group(
+        &self,
+        realm_id: Uuid,
+        group_id: Uuid,
+        patch: &PatchRequest,
+        base_url: &str,
+    ) -> Result<Value> {
+        let mut resource = self.get_group(realm_id, group_id, base_url).await?;
+        patch.apply(&mut resource)?;
+        self.replace_group(realm_id, group_id, &resource, base_url)
+            .await
+    }
+
+    /// Deletes a group. Groups with child groups are refused, as in the
+    /// admin API without `cascade`.
+    pub async fn delete_group(&self, realm_id: Uuid, group_id: Uuid) -> Result<()> {
+        self.group_in_realm(realm_id, group_id).await?;
+        self.rbac_service
+            .delete_group(realm_id, group_id, false)
+            .await
+    }
+
+    // --- Helpers ---
+
+    async fn all_users(&self, realm_id: Uuid) -> Result<Vec<User>> {
+        let mut users = Vec::new();
+        for page in 1.. {
+            let request = PageRequest {
+                page,
+                per_page: SCAN_PAGE_SIZE,
+                sort_by: Some("created_at".to_string()),
+                ..PageRequest::default()
+            };
+            let response = self
+                .user_service
+                .list_users(realm_id, request, UserListFilters::default())
+                .await?;
+            let done = response.data.len() < SCAN_PAGE_SIZE as usize;
+            users.extend(response.data);
+            if done {
+                break;
+            }
+        }
+        Ok(users)
+    }
+
+    async fn all_groups(&self, realm_id: Uuid) -> Result<Vec<Group>> {
+        let mut groups = Vec::new();
+        for page in 1.. {
+            let request = PageRequest {
+                page,
+                per_page: SCAN_PAGE_SIZE,
+                ..PageRequest::default()
+            };
+            let response = self.rbac_service.list_groups(realm_id, request).await?;
+            let done = response.data.len() < SCAN_PAGE_SIZE as usize;
+            groups.extend(response.data);
+            if done {
+                break;
+            }
+        }
+        Ok(groups)
+    }
+
+    async fn user_resource(&self, user: &User, base_url: &str) -> Result<Value> {
+        let emails = self.user_email_service.list_emails(user.id).await?;
+        let external_id = self
+            .scim_repo
+            .find_external_id(ScimResourceType::User, &user.id)
+            .await?;
+        Ok(scim::user_resource(
+            user,
+            &emails,
+            external_id.as_deref(),
+            base_url,
+        ))
+    }
+
+    async fn group_resource(&self, group: &Group, base_url: &str) -> Result<Value> {
+        let mut members = Vec::new();
+        for user_id in self
+            .rbac_service
+            .get_group_member_ids(group.realm_id, group.id)
+            .await?
+        {
+            let user = self.user_service.get_user(user_id).await?;
+            members.push((user.id, user.username));
+        }
+        let external_id = self
+            .scim_repo
+            .find_external_id(ScimResourceType::Group, &group.id)
+            .await?;
+        Ok(scim::group_resource(
+            group,
+            &members,
+            external_id.as_deref(),
+            base_url,
+        ))
+    }
+
+    /// Groups of other realms are reported missing rather than forbidden.
+    async fn group_in_realm(&self, realm_id: Uuid, group_id: Uuid) -> Result<Group> {
+        match self.rbac_service.get_group(realm_id, group_id).await {
+            Err(Error::SecurityViolation(_)) => Err(Error::NotFound("Group not found".into())),
+            other => other,
+        }
+    }
+
+    async fn apply_user(&self, realm_id: Uuid, user: User, input: &ScimUserInput) -> Result<()> {
+        let user = self
+            .user_service
+            .update_profile(
+                realm_id,
+                user.id,
+                Some(input.user_name.clone()),
+                Some(input.given_name.clone()),
+                Some(input.family_name.clone()),
+            )
+            .await?;
+        if let Some(password) = &input.password {
+            self.user_service
+                .update_password(realm_id, user.id, password, false)
+                .await?;
+        }
+        match (input.active, user.banned_at.is_some()) {
+            (true, true) => {
+                self.user_service.unban_user(realm_id, user.id).await?;
+            }
+            (false, false) => {
+                self.user_service.ban_user(realm_id, user.id).await?;
+            }
+            _ => {}
+        }
+        self.sync_emails(realm_id, user.id, &input.emails).await?;
+        self.scim_repo
+            .set_external_id(
+                ScimResourceType::User,
+                &realm_id,
+                &user.id,
+                input.external_id.as_deref(),
+            )
+            .await
+    }
+
+    /// Makes the user's addresses exactly `emails`, the first one primary.
+    async fn sync_emails(&self, realm_id: Uuid, user_id: Uuid, emails: &[String]) -> Result<()> {
+        let wanted = |address: &str| {
+            emails
+                .iter()
+                .any(|email| email.eq_ignore_ascii_case(address))
+        };
+        let current = self.user_email_service.list_emails(user_id).await?;
+        for email in emails {
+            if !current
+                .iter()
+                .any(|existing| existing.email.eq_ignore_ascii_case(email))
+            {
+                self.user_email_service
+                    .add_email(user_id, realm_id, email, current.is_empty(), false)
+                    .await?;
+            }
+        }
+
+        let current = self.user_email_service.list_emails(user_id).await?;
+        if let Some(primary) = emails.first().and_then(|primary| {
+            current
+                .iter()
+                .find(|existing| existing.email.eq_ignore_ascii_case(primary))
+        }) {
+            if !primary.is_primary {
+                self.user_email_service
+                    .set_primary(user_id, primary.id)
+                    .await?;
+            }
+        }
+
+        // The primary address can only go once it is the last one left.
+        let current = self.user_email_service.list_emails(user_id).await?;
+        let mut stale: Vec<_> = current
+            .iter()
+            .filter(|existing| !wanted(&existing.email))
+            .collect();
+        stale.sort_by_key(|existing| existing.is_primary);
+        for email in stale {
+            self.user_email_service
+                .remove_email(user_id, email.id)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn apply_group(
+        &self,
+        realm_id: Uuid,
+        group: Group,
+        input: &ScimGroupInput,
+    ) -> Result<()> {
+        if group.name != input.display_name {
+            self.rbac_service
+                .update_group(
+                    realm_id,
+                    group.id,
+                    CreateGroupPayload {
+                        name: input.display_name.clone(),
+                        description: group.description.clone(),
+                        parent_id: group.parent_id,
+                    },
+                )
+                .await?;
+        }
+
+        let current = self
+            .rbac_service
+            .get_group_member_ids(realm_id, group.id)
+            .await?;
+        for user_id in input.member_ids.iter().filter(|id| !current.contains(id)) {
+            self.rbac_service
+                .assign_user_to_group(realm_id, *user_id, group.id)
+                .await?;
+        }
+        for user_id in current.iter().filter(|id| !input.member_ids.contains(id)) {
+            self.rbac_service
+                .remove_user_from_group(realm_id, *user_id, group.id)
+                .await?;
+        }
+
+        self.scim_repo
+            .set_external_id(
+                ScimResourceType::Group,
+                &realm_id,
+                &group.id,
+                input.external_id.as_deref(),
+            )
+            .await
+    }
+
+    async fn ensure_members_in_realm(&self, realm_id: Uuid, member_ids: &[Uuid]) -> Result<()> {
+        for user_id in member_ids {
+            self.user_service
+                .get_user_in_realm(realm_id, *user_id)
+                .await
+                .map_err(|_| Error::Validation(format!("Unknown member '{}'", user_id)))?;
+        }
+        Ok(())
+    }
+
+    /// Checks what could still fail half-way through applying `input`.
+    async fn ensure_user_input_free(
+        &self,
+        realm_id: Uuid,
+        input: &ScimUserInput,
+        owner: Option<Uuid>,
+    ) -> Result<()> {
+        for email in &input.emails {
+            if let Some(existing) = self.user_service.find_by_email(&realm_id, email).await? {
+                if Some(existing.id) != owner {
+                    return Err(Error::EmailAlreadyExists);
+                }
+            }
+        }
+        self.ensure_external_id_free(
+            ScimResourceType::User,
+            realm_id,
+            input.external_id.as_deref(),
+            owner,
+        )
+        .await
+    }
+
+    async fn ensure_external_id_free(
+        &self,
+        resource_type: ScimResourceType,
+        realm_id: Uuid,
+        external_id: Option<&str>,
+        owner: Option<Uuid>,
+    ) -> Result<()> {
+        let Some(external_id) = external_id else {
+            return Ok(());
+        };
+        match self
+            .scim_repo
+            .find_by_external_id(resource_type, &realm_id, external_id)
+            .await?
+        {
+            Some(existing) if Some(existing) != owner => Err(Error::Conflict(format!(
+                "externalId '{}' is already in use",
+                external_id
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
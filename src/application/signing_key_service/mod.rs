@@ -0,0 +1,599 @@
+use crate::application::secret_service::SecretService;
+use crate::domain::saml;
+use crate::domain::signing_key::{SigningAlgorithm, SigningKey, SigningKeyState};
+use crate::error::{Error, Result};
+use crate::ports::oidc_repository::OidcRepository;
+use crate::ports::realm_repository::RealmRepository;
+use crate::ports::signing_key_repository::SigningKeyRepository;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{Duration, Utc};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use p256::pkcs8::{
+    DecodePublicKey as _, EncodePrivateKey as _, EncodePublicKey as _, LineEnding as EcLineEnding,
+};
+use rand::RngExt;
+use rsa::pkcs8::{
+    DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey, LineEnding,
+};
+use rsa::traits::PublicKeyParts;
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+use tokio::sync::Mutex;
+use tracing::info;
+use uuid::Uuid;
+
+// Smaller keys keep the unit tests fast; they are never used outside tests.
+#[cfg(not(test))]
+const RSA_KEY_BITS: usize = 2048;
+#[cfg(test)]
+const RSA_KEY_BITS: usize = 1024;
+
+/// SAML peers pin the certificate from metadata, but many still reject an
+/// expired one, so it outlives any sensible rotation interval.
+const SAML_CERTIFICATE_VALIDITY_DAYS: i64 = 3650;
+
+/// How long a cached key is trusted before its state is re-read. Rotation and
+/// retirement only clear the caches of the instance that made them, so this
+/// bounds how long other instances keep using a key that has since changed.
+const KEY_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// The decrypted, parsed form of a realm's active key, ready to sign with.
+pub struct ActiveSigningKey {
+    pub kid: String,
+    pub algorithm: Algorithm,
+    pub encoding_key: EncodingKey,
+}
+
+/// A key that may verify tokens, with the realm and algorithm it is pinned to.
+pub struct VerifyingKey {
+    pub realm_id: Uuid,
+    pub algorithm: Algorithm,
+    pub decoding_key: DecodingKey,
+}
+
+/// A realm's active RSA key in the form SAML signs with.
+pub struct SamlSigningKey {
+    pub kid: String,
+    pub private_key: RsaPrivateKey,
+    /// DER, self-signed.
+    pub certificate: Vec<u8>,
+}
+
+/// When scheduled rotation replaces active keys and retires passive ones.
+#[derive(Debug, Clone, Copy)]
+pub struct KeyRotationPolicy {
+    /// Age after which an active key is rotated out. Zero disables rotation.
+    pub rotation_interval: Duration,
+    /// How long a rotated-out key keeps verifying before it is retired.
+    pub passive_retention: Duration,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct KeyRotationSummary {
+    pub rotated: usize,
+    pub retired: u64,
+}
+
+/// Per-realm signing key store. A realm gets an active key for an algorithm
+/// the first time it signs with it (or, for the realm's own algorithm, the
+/// first time it publishes its JWKS).
+pub struct SigningKeyService {
+    repo: Arc<dyn SigningKeyRepository>,
+    realm_repo: Arc<dyn RealmRepository>,
+    oidc_repo: Arc<dyn OidcRepository>,
+    secret_service: Arc<SecretService>,
+    active_keys: KeyCache<(Uuid, SigningAlgorithm), ActiveSigningKey>,
+    verifying_keys: KeyCache<String, VerifyingKey>,
+    saml_keys: KeyCache<Uuid, SamlSigningKey>,
+    cache_ttl: std::time::Duration,
+    // Serializes key creation so concurrent first requests for a realm do not
+    // race to create two active keys.
+    write_lock: Mutex<()>,
+}
+
+impl SigningKeyService {
+    pub fn new(
+        repo: Arc<dyn SigningKeyRepository>,
+        realm_repo: Arc<dyn RealmRepository>,
+        oidc_repo: Arc<dyn OidcRepository>,
+        secret_service: Arc<SecretService>,
+    ) -> Self {
+        Self {
+            repo,
+            realm_repo,
+            oidc_repo,
+            secret_service,
+            active_keys: RwLock::new(HashMap::new()),
+            verifying_keys: RwLock::new(HashMap::new()),
+            saml_keys: RwLock::new(HashMap::new()),
+            cache_ttl: KEY_CACHE_TTL,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// The algorithm tokens for `client_id` are signed with: the client's
+    /// override if it has one, otherwise the realm's algorithm.
+    pub async fn signing_algorithm(
+        &self,
+        realm_id: Uuid,
+        client_id: Option<&str>,
+    ) -> Result<SigningAlgorithm> {
+        if let Some(client_id) = client_id {
+            let client = self
+                .oidc_repo
+                .find_client_by_id(&realm_id, client_id)
+                .await?;
+            if let Some(algorithm) = client.and_then(|client| client.signing_algorithm) {
+                return Ok(algorithm);
+            }
+        }
+
+        let realm = self
+            .realm_repo
+            .find_by_id(&realm_id)
+            .await?
+            .ok_or_else(|| Error::RealmNotFound(realm_id.to_string()))?;
+        Ok(realm.signing_algorithm)
+    }
+
+    /// Returns the realm's active key for `algorithm`, creating one if the
+    /// realm has none.
+    pub async fn signing_key(
+        &self,
+        realm_id: Uuid,
+        algorithm: SigningAlgorithm,
+    ) -> Result<Arc<ActiveSigningKey>> {
+        let cache_key = (realm_id, algorithm);
+        if let Some(key) = self.cached(&self.active_keys, &cache_key) {
+            return Ok(key);
+        }
+
+        let key = self.active_key(realm_id, algorithm).await?;
+        let private_key_pem = self.secret_service.decrypt(&key.private_key_pem)?;
+        let active = Arc::new(ActiveSigningKey {
+            kid: key.kid.clone(),
+            algorithm: jwt_algorithm(key.algorithm),
+            encoding_key: encoding_key(key.algorithm, &private_key_pem)?,
+        });
+        self.active_keys
+            .write()
+            .unwrap()
+            .insert(cache_key, Cached::new(active.clone()));
+        Ok(active)
+    }
+
+    /// The realm's active RS256 key with a certificate wrapping it, for
+    /// signing SAML messages. The realm's JWT algorithm does not matter here.
+    pub async fn saml_signing_key(&self, realm_id: Uuid) -> Result<Arc<SamlSigningKey>> {
+        if let Some(key) = self.cached(&self.saml_keys, &realm_id) {
+            return Ok(key);
+        }
+
+        let key = self.active_key(realm_id, SigningAlgorithm::Rs256).await?;
+        let saml_key = Arc::new(self.saml_key(&key)?);
+        self.saml_keys
+            .write()
+            .unwrap()
+            .insert(realm_id, Cached::new(saml_key.clone()));
+        Ok(saml_key)
+    }
+
+    /// Certificates for every RS256 key that can still verify, the active key
+    /// first, for the realm's SAML metadata.
+    pub async fn saml_certificates(&self, realm_id: Uuid) -> Result<Vec<Vec<u8>>> {
+        let active = self.saml_signing_key(realm_id).await?;
+        let mut certificates = vec![active.certificate.clone()];
+        for key in self.repo.list_by_realm(&realm_id).await? {
+            if key.algorithm == SigningAlgorithm::Rs256 && key.can_verify() && key.kid != active.kid
+            {
+                certificates.push(self.saml_key(&key)?.certificate);
+            }
+        }
+        Ok(certificates)
+    }
+
+    /// Looks up a non-retired key of `realm_id` by `kid`, for validating a
+    /// token issued in that realm. Keys of other realms never match.
+    pub async fn verifying_key(
+        &self,
+        realm_id: Uuid,
+        kid: &str,
+    ) -> Result<Option<Arc<VerifyingKey>>> {
+        if let Some(key) = self.cached(&self.verifying_keys, &kid.to_string()) {
+            return Ok((key.realm_id == realm_id).then_some(key));
+        }
+
+        let Some(key) = self.repo.find_by_kid(kid).await? else {
+            self.verifying_keys.write().unwrap().remove(kid);
+            return Ok(None);
+        };
+        if !key.can_verify() {
+            self.verifying_keys.write().unwrap().remove(kid);
+            return Ok(None);
+        }
+
+        let verifying = Arc::new(VerifyingKey {
+            realm_id: key.realm_id,
+            algorithm: jwt_algorithm(key.algorithm),
+            decoding_key: decoding_key(key.algorithm, &key.public_key_pem)?,
+        });
+        self.verifying_keys
+            .write()
+            .unwrap()
+            .insert(kid.to_string(), Cached::new(verifying.clone()));
+        Ok((verifying.realm_id == realm_id).then_some(verifying))
+    }
+
+    /// The realm's JWK Set: every key that can still verify, active keys
+    /// first.
+    pub async fn jwks(&self, realm_id: Uuid) -> Result<Value> {
+        let algorithm = self.signing_algorithm(realm_id, None).await?;
+        self.signing_key(realm_id, algorithm).await?;
+
+        let mut keys = self.repo.list_by_realm(&realm_id).await?;
+        keys.retain(SigningKey::can_verify);
+        keys.sort_by_key(|key| key.state != SigningKeyState::Active);
+
+        let keys = keys.iter().map(public_jwk).collect::<Result<Vec<_>>>()?;
+        Ok(json!({ "keys": keys }))
+    }
+
+    pub async fn list_keys(&self, realm_id: Uuid) -> Result<Vec<SigningKey>> {
+        self.repo.list_by_realm(&realm_id).await
+    }
+
+    /// Generates a passive key. It is published in the JWKS straight away and
+    /// becomes the active key at the next rotation of its algorithm.
+    pub async fn generate_key(
+        &self,
+        realm_id: Uuid,
+        algorithm: SigningAlgorithm,
+    ) -> Result<SigningKey> {
+        let key = self.new_key(realm_id, algorithm, SigningKeyState::Passive)?;
+        self.repo.create(&key).await?;
+        Ok(key)
+    }
+
+    /// Promotes the newest pre-published key of `algorithm` (or a freshly
+    /// generated one) to active. The previous active key stays published as
+    /// passive.
+    pub async fn rotate(&self, realm_id: Uuid, algorithm: SigningAlgorithm) -> Result<SigningKey> {
+        let _guard = self.write_lock.lock().await;
+
+        let pending = self
+            .repo
+            .list_by_realm(&realm_id)
+            .await?
+            .into_iter()
+            .find(|key| {
+                key.algorithm == algorithm
+                    && key.state == SigningKeyState::Passive
+                    && key.activated_at.is_none()
+            });
+        let mut key = match pending {
+            Some(key) => key,
+            None => {
+                let key = self.new_key(realm_id, algorithm, SigningKeyState::Passive)?;
+                self.repo.create(&key).await?;
+                key
+            }
+        };
+
+        let now = Utc::now();
+        self.repo.activate(&realm_id, &key.id, now).await?;
+        self.active_keys
+            .write()
+            .unwrap()
+            .remove(&(realm_id, algorithm));
+        if algorithm == SigningAlgorithm::Rs256 {
+            self.saml_keys.write().unwrap().remove(&realm_id);
+        }
+        info!(
+            "Rotated {} signing key for realm {} to {}",
+            algorithm, realm_id, key.kid
+        );
+
+        key.state = SigningKeyState::Active;
+        key.activated_at = Some(now);
+        Ok(key)
+    }
+
+    /// Retires a passive key so tokens signed with it stop validating.
+    pub async fn retire_key(&self, realm_id: Uuid, id: Uuid) -> Result<SigningKey> {
+        let mut key = self
+            .repo
+            .find_by_id(&realm_id, &id)
+            .await?
+            .ok_or_else(|| Error::NotFound("Signing key not found".to_string()))?;
+        match key.state {
+            SigningKeyState::Active => {
+                return Err(Error::Validation(
+                    "The active signing key cannot be retired; rotate first".to_string(),
+                ))
+            }
+            SigningKeyState::Retired => return Ok(key),
+            SigningKeyState::Passive => {}
+        }
+
+        let now = Utc::now();
+        self.repo.retire(&realm_id, &id, now).await?;
+        self.verifying_keys.write().unwrap().remove(&key.kid);
+
+        key.state = SigningKeyState::Retired;
+        key.retired_at = Some(now);
+        Ok(key)
+    }
+
+    /// Scheduled rotation: rotates active keys older than the interval and
+    /// retires passive keys past their retention window.
+    pub async fn rotate_due_keys(&self, policy: &KeyRotationPolicy) -> Result<KeyRotationSummary> {
+        let now = Utc::now();
+        let mut summary = KeyRotationSummary::default();
+
+        if policy.rotation_interval > Duration::zero() {
+            for key in self.repo.list_active().await? {
+                let since = key.activated_at.unwrap_or(key.created_at);
+                if since + policy.rotation_interval <= now {
+                    self.rotate(key.realm_id, key.algorithm).await?;
+                    summary.rotated += 1;
+                }
+            }
+        }
+
+        summary.retired = self
+            .repo
+            .retire_passive_before(now - policy.passive_retention)
+            .await?;
+        if summary.retired > 0 {
+            self.verifying_keys.write().unwrap().clear();
+        }
+
+        Ok(summary)
+    }
+
+    /// A cached entry younger than the cache TTL.
+    fn cached<K: Eq + Hash, T>(&self, cache: &KeyCache<K, T>, key: &K) -> Option<Arc<T>> {
+        cache
+            .read()
+            .unwrap()
+            .get(key)
+            .filter(|entry| entry.loaded_at.elapsed() < self.cache_ttl)
+            .map(|entry| entry.value.clone())
+    }
+
+    /// The realm's active key record for `algorithm`, created if missing.
+    async fn active_key(&self, realm_id: Uuid, algorithm: SigningAlgorithm) -> Result<SigningKey> {
+        if let Some(key) = self.repo.find_active(&realm_id, algorithm).await? {
+            return Ok(key);
+        }
+
+        let _guard = self.write_lock.lock().await;
+        if let Some(key) = self.repo.find_active(&realm_id, algorithm).await? {
+            return Ok(key);
+        }
+        let key = self.new_key(realm_id, algorithm, SigningKeyState::Active)?;
+        self.repo.create(&key).await?;
+        info!(
+            "Generated initial {} signing key {} for realm {}",
+            algorithm, key.kid, realm_id
+        );
+        Ok(key)
+    }
+
+    /// Decrypts an RS256 key and wraps it in a self-signed certificate. The
+    /// certificate only depends on the key, so it is stable across restarts.
+    fn saml_key(&self, key: &SigningKey) -> Result<SamlSigningKey> {
+        let private_key_pem = self.secret_service.decrypt(&key.private_key_pem)?;
+        let private_key = RsaPrivateKey::from_pkcs8_pem(&private_key_pem)
+            .map_err(|e| key_error("Invalid signing key", e))?;
+        let certificate = saml::self_signed_certificate(
+            &private_key,
+            &format!("reauth-{}", key.kid),
+            key.id.as_bytes(),
+            key.created_at,
+            key.created_at + Duration::days(SAML_CERTIFICATE_VALIDITY_DAYS),
+        )?;
+        Ok(SamlSigningKey {
+            kid: key.kid.clone(),
+            private_key,
+            certificate,
+        })
+    }
+
+    fn new_key(
+        &self,
+        realm_id: Uuid,
+        algorithm: SigningAlgorithm,
+        state: SigningKeyState,
+    ) -> Result<SigningKey> {
+        let (private_key_pem, public_key_pem) = generate_key_pair(algorithm)?;
+
+        let now = Utc::now();
+        let id = Uuid::new_v4();
+        Ok(SigningKey {
+            id,
+            realm_id,
+            kid: id.simple().to_string(),
+            algorithm,
+            state,
+            private_key_pem: self.secret_service.encrypt(&private_key_pem)?,
+            public_key_pem,
+            created_at: now,
+            activated_at: (state == SigningKeyState::Active).then_some(now),
+            deactivated_at: None,
+            retired_at: None,
+        })
+    }
+}
+
+type KeyCache<K, T> = RwLock<HashMap<K, Cached<T>>>;
+
+struct Cached<T> {
+    value: Arc<T>,
+    loaded_at: Instant,
+}
+
+impl<T> Cached<T> {
+    fn new(value: Arc<T>) -> Self {
+        Self {
+            value,
+            loaded_at: Instant::now(),
+        }
+    }
+}
+
+fn jwt_algorithm(algorithm: SigningAlgorithm) -> Algorithm {
+    match algorithm {
+        SigningAlgorithm::Rs256 => Algorithm::RS256,
+        SigningAlgorithm::Es256 => Algorithm::ES256,
+        SigningAlgorithm::Es384 => Algorithm::ES384,
+        SigningAlgorithm::EdDsa => Algorithm::EdDSA,
+    }
+}
+
+fn key_error(context: &str, err: impl std::fmt::Display) -> Error {
+    Error::Unexpected(anyhow::anyhow!("{}: {}", context, err))
+}
+
+fn encoding_key(algorithm: SigningAlgorithm, private_key_pem: &str) -> Result<EncodingKey> {
+    let pem = private_key_pem.as_bytes();
+    match algorithm {
+        SigningAlgorithm::Rs256 => EncodingKey::from_rsa_pem(pem),
+        SigningAlgorithm::Es256 | SigningAlgorithm::Es384 => EncodingKey::from_ec_pem(pem),
+        SigningAlgorithm::EdDsa => EncodingKey::from_ed_pem(pem),
+    }
+    .map_err(|e| key_error("Invalid signing key", e))
+}
+
+fn decoding_key(algorithm: SigningAlgorithm, public_key_pem: &str) -> Result<DecodingKey> {
+    let pem = public_key_pem.as_bytes();
+    match algorithm {
+        SigningAlgorithm::Rs256 => DecodingKey::from_rsa_pem(pem),
+        SigningAlgorithm::Es256 | SigningAlgorithm::Es384 => DecodingKey::from_ec_pem(pem),
+        SigningAlgorithm::EdDsa => DecodingKey::from_ed_pem(pem),
+    }
+    .map_err(|e| key_error("Invalid public key", e))
+}
+
+/// Generates a key pair as (PKCS#8 private PEM, SPKI public PEM).
+fn generate_key_pair(algorithm: SigningAlgorithm) -> Result<(String, String)> {
+    let (private_pem, public_pem) = match algorithm {
+        SigningAlgorithm::Rs256 => {
+            let private_key = RsaPrivateKey::new(&mut rand::rng(), RSA_KEY_BITS)
+                .map_err(|e| key_error("Failed to generate signing key", e))?;
+            let private_pem = private_key
+                .to_pkcs8_pem(LineEnding::LF)
+                .map_err(|e| key_error("Failed to encode signing key", e))?
+                .to_string();
+            let public_pem = RsaPublicKey::from(&private_key)
+                .to_public_key_pem(LineEnding::LF)
+                .map_err(|e| key_error("Failed to encode public key", e))?;
+            return Ok((private_pem, public_pem));
+        }
+        SigningAlgorithm::Es256 => {
+            // A random scalar is out of range with negligible probability;
+            // draw again if it is.
+            let secret = loop {
+                if let Ok(secret) = p256::SecretKey::from_slice(&random_bytes::<32>()) {
+                    break secret;
+                }
+            };
+            (
+                secret.to_pkcs8_pem(EcLineEnding::LF),
+                secret.public_key().to_public_key_pem(EcLineEnding::LF),
+            )
+        }
+        SigningAlgorithm::Es384 => {
+            let secret = loop {
+                if let Ok(secret) = p384::SecretKey::from_slice(&random_bytes::<48>()) {
+                    break secret;
+                }
+            };
+            (
+                secret.to_pkcs8_pem(EcLineEnding::LF),
+                secret.public_key().to_public_key_pem(EcLineEnding::LF),
+            )
+        }
+        SigningAlgorithm::EdDsa => {
+            let secret = ed25519_dalek::SigningKey::from_bytes(&random_bytes::<32>());
+            (
+                secret.to_pkcs8_pem(EcLineEnding::LF),
+                secret.verifying_key().to_public_key_pem(EcLineEnding::LF),
+            )
+        }
+    };
+
+    Ok((
+        private_pem
+            .map_err(|e| key_error("Failed to encode signing key", e))?
+            .to_string(),
+        public_pem.map_err(|e| key_error("Failed to encode public key", e))?,
+    ))
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0_u8; N];
+    rand::rng().fill(&mut bytes);
+    bytes
+}
+
+/// Builds the public JWK for a key (RFC 7517; RFC 8037 for Ed25519).
+fn public_jwk(key: &SigningKey) -> Result<Value> {
+    let pem = key.public_key_pem.as_str();
+    let mut jwk = match key.algorithm {
+        SigningAlgorithm::Rs256 => {
+            let public_key = RsaPublicKey::from_public_key_pem(pem)
+                .map_err(|e| key_error("Failed to parse public key", e))?;
+            json!({
+                "kty": "RSA",
+                "n": URL_SAFE_NO_PAD.encode(public_key.n().to_be_bytes()),
+                "e": URL_SAFE_NO_PAD.encode(public_key.e().to_be_bytes()),
+            })
+        }
+        SigningAlgorithm::Es256 => {
+            let point = p256::PublicKey::from_public_key_pem(pem)
+                .map_err(|e| key_error("Failed to parse public key", e))?
+                .to_encoded_point(false);
+            json!({
+                "kty": "EC",
+                "crv": "P-256",
+                "x": point.x().map(|x| URL_SAFE_NO_PAD.encode(x)),
+                "y": point.y().map(|y| URL_SAFE_NO_PAD.encode(y)),
+            })
+        }
+        SigningAlgorithm::Es384 => {
+            let point = p384::PublicKey::from_public_key_pem(pem)
+                .map_err(|e| key_error("Failed to parse public key", e))?
+                .to_encoded_point(false);
+            json!({
+                "kty": "EC",
+                "crv": "P-384",
+                "x": point.x().map(|x| URL_SAFE_NO_PAD.encode(x)),
+                "y": point.y().map(|y| URL_SAFE_NO_PAD.encode(y)),
+            })
+        }
+        SigningAlgorithm::EdDsa => {
+            let public_key = ed25519_dalek::VerifyingKey::from_public_key_pem(pem)
+                .map_err(|e| key_error("Failed to parse public key", e))?;
+            json!({
+                "kty": "OKP",
+                "crv": "Ed25519",
+                "x": URL_SAFE_NO_PAD.encode(public_key.as_bytes()),
+            })
+        }
+    };
+
+    jwk["use"] = json!("sig");
+    jwk["kid"] = json!(key.kid);
+    jwk["alg"] = json!(key.algorithm.as_str());
+    Ok(jwk)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,746 @@
+use super::{KeyRotationPolicy, KeyRotationSummary, SigningKeyService};
+use crate::application::secret_service::SecretService;
+use crate::domain::auth_flow::AuthFlow;
+use crate::domain::oidc::{
+    AuthCode, ClientDeleteSummary, ClientStats, OidcClient, TokenEndpointAuthMethod,
+};
+use crate::domain::pagination::{PageRequest, PageResponse};
+use crate::domain::realm::{Realm, RealmIdpDefaultEmailLinkPolicy, RealmIdpDefaultJitPolicy};
+use crate::domain::signing_key::{SigningAlgorithm, SigningKey, SigningKeyState};
+use crate::error::{Error, Result};
+use crate::ports::oidc_repository::OidcRepository;
+use crate::ports::realm_repository::RealmRepository;
+use crate::ports::signing_key_repository::SigningKeyRepository;
+use crate::ports::transaction_manager::Transaction;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{decode, encode, Header, Validation};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+#[derive(Default)]
+struct TestSigningKeyRepo {
+    keys: Mutex<Vec<SigningKey>>,
+}
+
+impl TestSigningKeyRepo {
+    fn states(&self, realm_id: Uuid) -> Vec<SigningKeyState> {
+        let mut keys: Vec<_> = self
+            .keys
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|key| key.realm_id == realm_id)
+            .cloned()
+            .collect();
+        keys.sort_by_key(|key| key.created_at);
+        keys.into_iter().map(|key| key.state).collect()
+    }
+
+    fn backdate_active(&self, realm_id: Uuid, by: Duration) {
+        for key in self.keys.lock().unwrap().iter_mut() {
+            if key.realm_id == realm_id && key.state == SigningKeyState::Active {
+                key.activated_at = key.activated_at.map(|at| at - by);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl SigningKeyRepository for TestSigningKeyRepo {
+    async fn create(&self, key: &SigningKey) -> Result<()> {
+        self.keys.lock().unwrap().push(key.clone());
+        Ok(())
+    }
+
+    async fn find_by_id(&self, realm_id: &Uuid, id: &Uuid) -> Result<Option<SigningKey>> {
+        Ok(self
+            .keys
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|key| key.realm_id == *realm_id && key.id == *id)
+            .cloned())
+    }
+
+    async fn find_by_kid(&self, kid: &str) -> Result<Option<SigningKey>> {
+        Ok(self
+            .keys
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|key| key.kid == kid)
+            .cloned())
+    }
+
+    async fn find_active(
+        &self,
+        realm_id: &Uuid,
+        algorithm: SigningAlgorithm,
+    ) -> Result<Option<SigningKey>> {
+        Ok(self
+            .keys
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|key| {
+                key.realm_id == *realm_id
+                    && key.algorithm == algorithm
+                    && key.state == SigningKeyState::Active
+            })
+            .cloned())
+    }
+
+    async fn list_by_realm(&self, realm_id: &Uuid) -> Result<Vec<SigningKey>> {
+        let mut keys: Vec<_> = self
+            .keys
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|key| key.realm_id == *realm_id)
+            .cloned()
+            .collect();
+        keys.sort_by_key(|key| std::cmp::Reverse(key.created_at));
+        Ok(keys)
+    }
+
+    async fn list_active(&self) -> Result<Vec<SigningKey>> {
+        Ok(self
+            .keys
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|key| key.state == SigningKeyState::Active)
+            .cloned()
+            .collect())
+    }
+
+    async fn activate(&self, realm_id: &Uuid, id: &Uuid, at: DateTime<Utc>) -> Result<()> {
+        let mut keys = self.keys.lock().unwrap();
+        let algorithm = keys
+            .iter()
+            .find(|key| key.realm_id == *realm_id && key.id == *id)
+            .map(|key| key.algorithm)
+            .ok_or_else(|| Error::NotFound("Signing key not found".to_string()))?;
+        for key in keys
+            .iter_mut()
+            .filter(|key| key.realm_id == *realm_id && key.algorithm == algorithm)
+        {
+            if key.state == SigningKeyState::Active {
+                key.state = SigningKeyState::Passive;
+                key.deactivated_at = Some(at);
+            }
+        }
+        let key = keys
+            .iter_mut()
+            .find(|key| key.realm_id == *realm_id && key.id == *id)
+            .ok_or_else(|| Error::NotFound("Signing key not found".to_string()))?;
+        key.state = SigningKeyState::Active;
+        key.activated_at = Some(at);
+        key.deactivated_at = None;
+        Ok(())
+    }
+
+    async fn retire(&self, realm_id: &Uuid, id: &Uuid, at: DateTime<Utc>) -> Result<()> {
+        let mut keys = self.keys.lock().unwrap();
+        let key = keys
+            .iter_mut()
+            .find(|key| {
+                key.realm_id == *realm_id && key.id == *id && key.state == SigningKeyState::Passive
+            })
+            .ok_or_else(|| Error::NotFound("Signing key not found".to_string()))?;
+        key.state = SigningKeyState::Retired;
+        key.retired_at = Some(at);
+        Ok(())
+    }
+
+    async fn retire_passive_before(&self, cutoff: DateTime<Utc>) -> Result<u64> {
+        let mut retired = 0;
+        for key in self.keys.lock().unwrap().iter_mut() {
+            if key.state == SigningKeyState::Passive
+                && key.deactivated_at.is_some_and(|at| at < cutoff)
+            {
+                key.state = SigningKeyState::Retired;
+                key.retired_at = Some(Utc::now());
+                retired += 1;
+            }
+        }
+        Ok(retired)
+    }
+}
+
+/// Every realm uses `algorithm`, so tests can pick any realm id.
+#[derive(Default)]
+struct TestRealmRepo {
+    algorithm: Mutex<SigningAlgorithm>,
+}
+
+#[async_trait]
+impl RealmRepository for TestRealmRepo {
+    async fn create<'a>(&self, _realm: &Realm, _tx: Option<&'a mut dyn Transaction>) -> Result<()> {
+        Ok(())
+    }
+
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<Realm>> {
+        Ok(Some(Realm {
+            id: *id,
+            name: "test".to_string(),
+            access_token_ttl_secs: 900,
+            refresh_token_ttl_secs: 3600,
+            pkce_required_public_clients: true,
+            signing_algorithm: *self.algorithm.lock().unwrap(),
+            lockout_threshold: 5,
+            lockout_duration_secs: 900,
+            is_system: false,
+            registration_enabled: false,
+            default_registration_role_ids: Vec::new(),
+            invitation_resend_limit: 3,
+            idp_broker_enabled: false,
+            idp_default_jit_policy: RealmIdpDefaultJitPolicy::PerProvider,
+            idp_default_email_link_policy: RealmIdpDefaultEmailLinkPolicy::ManualOnly,
+            idp_minimum_remaining_factor: true,
+            browser_flow_id: None,
+            registration_flow_id: None,
+            direct_grant_flow_id: None,
+            reset_credentials_flow_id: None,
+            invitation_flow_id: None,
+        }))
+    }
+
+    async fn find_by_name(&self, _name: &str) -> Result<Option<Realm>> {
+        Ok(None)
+    }
+
+    async fn list_all(&self) -> Result<Vec<Realm>> {
+        Ok(Vec::new())
+    }
+
+    async fn update<'a>(&self, _realm: &Realm, _tx: Option<&'a mut dyn Transaction>) -> Result<()> {
+        Ok(())
+    }
+
+    async fn list_flows_by_realm(&self, _realm_id: &Uuid) -> Result<Vec<AuthFlow>> {
+        Ok(Vec::new())
+    }
+
+    async fn update_flow_binding<'a>(
+        &self,
+        _realm_id: &Uuid,
+        _slot: &str,
+        _flow_id: &Uuid,
+        _tx: Option<&'a mut dyn Transaction>,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct TestOidcRepo {
+    clients: Mutex<Vec<OidcClient>>,
+}
+
+impl TestOidcRepo {
+    fn add_client(&self, realm_id: Uuid, client_id: &str, algorithm: Option<SigningAlgorithm>) {
+        self.clients.lock().unwrap().push(OidcClient {
+            id: Uuid::new_v4(),
+            realm_id,
+            client_id: client_id.to_string(),
+            client_secret: None,
+            redirect_uris: "[]".to_string(),
+            scopes: "[]".to_string(),
+            web_origins: "[]".to_string(),
+            managed_by_config: false,
+            token_endpoint_auth_method: TokenEndpointAuthMethod::None,
+            jwks: None,
+            signing_algorithm: algorithm,
+            backchannel_logout_uri: None,
+            frontchannel_logout_uri: None,
+            token_exchange_policy: None,
+            direct_grant_enabled: false,
+            require_pushed_authorization_requests: false,
+        });
+    }
+}
+
+#[async_trait]
+impl OidcRepository for TestOidcRepo {
+    async fn find_client_by_id(
+        &self,
+        realm_id: &Uuid,
+        client_id: &str,
+    ) -> Result<Option<OidcClient>> {
+        Ok(self
+            .clients
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|client| client.realm_id == *realm_id && client.client_id == client_id)
+            .cloned())
+    }
+
+    async fn create_client(&self, _client: &OidcClient) -> Result<()> {
+        Ok(())
+    }
+
+    async fn find_clients_by_realm(
+        &self,
+        _realm_id: &Uuid,
+        _req: &PageRequest,
+    ) -> Result<PageResponse<OidcClient>> {
+        Ok(PageResponse::new(Vec::new(), 0, 1, 10))
+    }
+
+    async fn count_client_stats(&self, _realm_id: &Uuid) -> Result<ClientStats> {
+        Ok(ClientStats {
+            total: 0,
+            confidential: 0,
+            public: 0,
+        })
+    }
+
+    async fn count_client_delete_summary(&self, id: &Uuid) -> Result<ClientDeleteSummary> {
+        Ok(ClientDeleteSummary {
+            client_id: *id,
+            name: String::new(),
+            role_count: 0,
+            permission_count: 0,
+        })
+    }
+
+    async fn delete_client(&self, _id: &Uuid) -> Result<()> {
+        Ok(())
+    }
+
+    async fn find_client_by_uuid(&self, _id: &Uuid) -> Result<Option<OidcClient>> {
+        Ok(None)
+    }
+
+    async fn update_client(&self, _client: &OidcClient) -> Result<()> {
+        Ok(())
+    }
+
+    async fn save_auth_code(&self, _code: &AuthCode) -> Result<()> {
+        Ok(())
+    }
+
+    async fn find_auth_code_by_code(&self, _code: &str) -> Result<Option<AuthCode>> {
+        Ok(None)
+    }
+
+    async fn delete_auth_code(&self, _code: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn is_origin_allowed(&self, _origin: &str) -> Result<bool> {
+        Ok(false)
+    }
+
+    async fn record_client_assertion_jti(
+        &self,
+        _client_id: &Uuid,
+        _jti: &str,
+        _expires_at: DateTime<Utc>,
+    ) -> Result<bool> {
+        Ok(true)
+    }
+}
+
+struct TestContext {
+    service: SigningKeyService,
+    repo: Arc<TestSigningKeyRepo>,
+    realm_repo: Arc<TestRealmRepo>,
+    oidc_repo: Arc<TestOidcRepo>,
+}
+
+fn build_context() -> TestContext {
+    let repo = Arc::new(TestSigningKeyRepo::default());
+    let realm_repo = Arc::new(TestRealmRepo::default());
+    let oidc_repo = Arc::new(TestOidcRepo::default());
+    let secret_service = Arc::new(SecretService::from_key("test-secret"));
+    let service = SigningKeyService::new(
+        repo.clone(),
+        realm_repo.clone(),
+        oidc_repo.clone(),
+        secret_service,
+    );
+    TestContext {
+        service,
+        repo,
+        realm_repo,
+        oidc_repo,
+    }
+}
+
+fn build_service() -> (SigningKeyService, Arc<TestSigningKeyRepo>) {
+    let ctx = build_context();
+    (ctx.service, ctx.repo)
+}
+
+const RS256: SigningAlgorithm = SigningAlgorithm::Rs256;
+
+fn jwks_kids(jwks: &serde_json::Value) -> Vec<String> {
+    jwks["keys"]
+        .as_array()
+        .expect("keys array")
+        .iter()
+        .map(|key| key["kid"].as_str().expect("kid").to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn signing_key_is_created_once_and_stored_encrypted() {
+    let (service, repo) = build_service();
+    let realm_id = Uuid::new_v4();
+
+    let first = service
+        .signing_key(realm_id, RS256)
+        .await
+        .expect("signing key");
+    let second = service
+        .signing_key(realm_id, RS256)
+        .await
+        .expect("signing key");
+
+    assert_eq!(first.kid, second.kid);
+    assert_eq!(repo.states(realm_id), vec![SigningKeyState::Active]);
+    let stored = repo.keys.lock().unwrap()[0].clone();
+    assert!(!stored.private_key_pem.contains("PRIVATE KEY"));
+}
+
+#[tokio::test]
+async fn rotation_keeps_the_previous_key_published_and_verifying() {
+    let (service, repo) = build_service();
+    let realm_id = Uuid::new_v4();
+    let old = service
+        .signing_key(realm_id, RS256)
+        .await
+        .expect("signing key");
+
+    let rotated = service.rotate(realm_id, RS256).await.expect("rotate");
+    let new = service
+        .signing_key(realm_id, RS256)
+        .await
+        .expect("signing key");
+
+    assert_eq!(new.kid, rotated.kid);
+    assert_ne!(new.kid, old.kid);
+    assert_eq!(
+        repo.states(realm_id),
+        vec![SigningKeyState::Passive, SigningKeyState::Active]
+    );
+    let jwks = service.jwks(realm_id).await.expect("jwks");
+    assert_eq!(jwks_kids(&jwks), vec![new.kid.clone(), old.kid.clone()]);
+    assert!(service
+        .verifying_key(realm_id, &old.kid)
+        .await
+        .expect("lookup")
+        .is_some());
+}
+
+#[tokio::test]
+async fn rotate_promotes_a_pre_published_key() {
+    let (service, _repo) = build_service();
+    let realm_id = Uuid::new_v4();
+    service
+        .signing_key(realm_id, RS256)
+        .await
+        .expect("signing key");
+
+    let staged = service
+        .generate_key(realm_id, RS256)
+        .await
+        .expect("generate");
+    assert_eq!(staged.state, SigningKeyState::Passive);
+    let jwks = service.jwks(realm_id).await.expect("jwks");
+    assert!(jwks_kids(&jwks).contains(&staged.kid));
+
+    let rotated = service.rotate(realm_id, RS256).await.expect("rotate");
+    assert_eq!(rotated.id, staged.id);
+}
+
+#[tokio::test]
+async fn retire_key_rejects_the_active_key_and_stops_verification() {
+    let (service, _repo) = build_service();
+    let realm_id = Uuid::new_v4();
+    let old = service
+        .signing_key(realm_id, RS256)
+        .await
+        .expect("signing key");
+    let keys = service.list_keys(realm_id).await.expect("list");
+    let old_id = keys[0].id;
+
+    let err = service.retire_key(realm_id, old_id).await.unwrap_err();
+    assert!(matches!(err, Error::Validation(_)));
+
+    service.rotate(realm_id, RS256).await.expect("rotate");
+    service
+        .verifying_key(realm_id, &old.kid)
+        .await
+        .expect("lookup")
+        .expect("old key verifies");
+    let retired = service.retire_key(realm_id, old_id).await.expect("retire");
+
+    assert_eq!(retired.state, SigningKeyState::Retired);
+    assert!(service
+        .verifying_key(realm_id, &old.kid)
+        .await
+        .expect("lookup")
+        .is_none());
+    let jwks = service.jwks(realm_id).await.expect("jwks");
+    assert!(!jwks_kids(&jwks).contains(&old.kid));
+}
+
+#[tokio::test]
+async fn verifying_key_only_matches_keys_of_the_expected_realm() {
+    let (service, _repo) = build_service();
+    let realm_id = Uuid::new_v4();
+    let key = service
+        .signing_key(realm_id, RS256)
+        .await
+        .expect("signing key");
+
+    // Twice, so the second lookup is answered from the cache.
+    for _ in 0..2 {
+        assert!(service
+            .verifying_key(Uuid::new_v4(), &key.kid)
+            .await
+            .expect("lookup")
+            .is_none());
+        assert!(service
+            .verifying_key(realm_id, &key.kid)
+            .await
+            .expect("lookup")
+            .is_some());
+    }
+}
+
+#[tokio::test]
+async fn cached_verifying_keys_recheck_retirement_by_another_instance() {
+    let ctx = build_context();
+    let mut other = SigningKeyService::new(
+        ctx.repo.clone(),
+        ctx.realm_repo.clone(),
+        ctx.oidc_repo.clone(),
+        Arc::new(SecretService::from_key("test-secret")),
+    );
+    other.cache_ttl = std::time::Duration::ZERO;
+    let realm_id = Uuid::new_v4();
+    let old = ctx
+        .service
+        .signing_key(realm_id, RS256)
+        .await
+        .expect("signing key");
+    ctx.service.rotate(realm_id, RS256).await.expect("rotate");
+    other
+        .verifying_key(realm_id, &old.kid)
+        .await
+        .expect("lookup")
+        .expect("old key verifies");
+
+    let old_id = ctx
+        .service
+        .list_keys(realm_id)
+        .await
+        .expect("list")
+        .into_iter()
+        .find(|key| key.kid == old.kid)
+        .expect("old key")
+        .id;
+    ctx.service
+        .retire_key(realm_id, old_id)
+        .await
+        .expect("retire");
+
+    assert!(other
+        .verifying_key(realm_id, &old.kid)
+        .await
+        .expect("lookup")
+        .is_none());
+}
+
+#[tokio::test]
+async fn rotate_due_keys_rotates_old_keys_and_retires_expired_passive_keys() {
+    let (service, repo) = build_service();
+    let fresh_realm = Uuid::new_v4();
+    let stale_realm = Uuid::new_v4();
+    service
+        .signing_key(fresh_realm, RS256)
+        .await
+        .expect("signing key");
+    service
+        .signing_key(stale_realm, RS256)
+        .await
+        .expect("signing key");
+    repo.backdate_active(stale_realm, Duration::days(100));
+
+    let policy = KeyRotationPolicy {
+        rotation_interval: Duration::days(90),
+        passive_retention: Duration::days(7),
+    };
+    let summary = service.rotate_due_keys(&policy).await.expect("rotate");
+    assert_eq!(
+        summary,
+        KeyRotationSummary {
+            rotated: 1,
+            retired: 0
+        }
+    );
+    assert_eq!(repo.states(fresh_realm), vec![SigningKeyState::Active]);
+    assert_eq!(
+        repo.states(stale_realm),
+        vec![SigningKeyState::Passive, SigningKeyState::Active]
+    );
+
+    let immediate = KeyRotationPolicy {
+        rotation_interval: Duration::zero(),
+        passive_retention: Duration::seconds(-1),
+    };
+    let summary = service.rotate_due_keys(&immediate).await.expect("rotate");
+    assert_eq!(summary.rotated, 0);
+    assert_eq!(summary.retired, 1);
+    assert_eq!(
+        repo.states(stale_realm),
+        vec![SigningKeyState::Retired, SigningKeyState::Active]
+    );
+}
+
+#[tokio::test]
+async fn signing_algorithm_prefers_the_client_override() {
+    let ctx = build_context();
+    let realm_id = Uuid::new_v4();
+    *ctx.realm_repo.algorithm.lock().unwrap() = SigningAlgorithm::Es256;
+    ctx.oidc_repo
+        .add_client(realm_id, "edge", Some(SigningAlgorithm::EdDsa));
+    ctx.oidc_repo.add_client(realm_id, "web", None);
+
+    for (client_id, expected) in [
+        (None, SigningAlgorithm::Es256),
+        (Some("web"), SigningAlgorithm::Es256),
+        (Some("edge"), SigningAlgorithm::EdDsa),
+        (Some("unknown"), SigningAlgorithm::Es256),
+    ] {
+        let algorithm = ctx
+            .service
+            .signing_algorithm(realm_id, client_id)
+            .await
+            .expect("algorithm");
+        assert_eq!(algorithm, expected, "{client_id:?}");
+    }
+}
+
+#[tokio::test]
+async fn each_algorithm_signs_verifies_and_publishes_a_matching_jwk() {
+    let ctx = build_context();
+    let realm_id = Uuid::new_v4();
+    let cases = [
+        (SigningAlgorithm::Es256, "EC", Some("P-256")),
+        (SigningAlgorithm::Es384, "EC", Some("P-384")),
+        (SigningAlgorithm::EdDsa, "OKP", Some("Ed25519")),
+        (SigningAlgorithm::Rs256, "RSA", None),
+    ];
+    let claims = serde_json::json!({ "sub": "user", "exp": Utc::now().timestamp() + 60 });
+
+    for (algorithm, _, _) in cases {
+        let key = ctx
+            .service
+            .signing_key(realm_id, algorithm)
+            .await
+            .expect("signing key");
+        let mut header = Header::new(key.algorithm);
+        header.kid = Some(key.kid.clone());
+        let token = encode(&header, &claims, &key.encoding_key).expect("sign");
+
+        let verifying = ctx
+            .service
+            .verifying_key(realm_id, &key.kid)
+            .await
+            .expect("lookup")
+            .expect("verifying key");
+        decode::<serde_json::Value>(
+            &token,
+            &verifying.decoding_key,
+            &Validation::new(verifying.algorithm),
+        )
+        .expect("verify");
+    }
+
+    // Every algorithm keeps its own active key.
+    assert_eq!(
+        ctx.repo.states(realm_id),
+        vec![SigningKeyState::Active; cases.len()]
+    );
+
+    let jwks = ctx.service.jwks(realm_id).await.expect("jwks");
+    for (algorithm, kty, crv) in cases {
+        let jwk = jwks["keys"]
+            .as_array()
+            .expect("keys array")
+            .iter()
+            .find(|jwk| jwk["alg"] == algorithm.as_str())
+            .expect("published key");
+        assert_eq!(jwk["kty"], kty, "{algorithm}");
+        assert_eq!(jwk["crv"].as_str(), crv, "{algorithm}");
+    }
+}
+
+#[tokio::test]
+async fn rotation_only_replaces_keys_of_the_same_algorithm() {
+    let (service, repo) = build_service();
+    let realm_id = Uuid::new_v4();
+    let rsa = service.signing_key(realm_id, RS256).await.expect("rsa");
+    let ec = service
+        .signing_key(realm_id, SigningAlgorithm::Es256)
+        .await
+        .expect("ec");
+
+    let rotated = service
+        .rotate(realm_id, SigningAlgorithm::Es256)
+        .await
+        .expect("rotate");
+
+    assert_eq!(rotated.algorithm, SigningAlgorithm::Es256);
+    assert_eq!(
+        repo.states(realm_id),
+        vec![
+            SigningKeyState::Active,
+            SigningKeyState::Passive,
+            SigningKeyState::Active
+        ]
+    );
+    let current_rsa = service.signing_key(realm_id, RS256).await.expect("rsa");
+    assert_eq!(current_rsa.kid, rsa.kid);
+    let current_ec = service
+        .signing_key(realm_id, SigningAlgorithm::Es256)
+        .await
+        .expect("ec");
+    assert_ne!(current_ec.kid, ec.kid);
+}
+
+#[tokio::test]
+async fn saml_keys_follow_rotation_and_keep_stable_certificates() {
+    let (service, _repo) = build_service();
+    let realm_id = Uuid::new_v4();
+
+    let first = service.saml_signing_key(realm_id).await.expect("saml key");
+    let public_key =
+        crate::domain::saml::certificate_public_key(&first.certificate).expect("certificate key");
+    assert_eq!(public_key, rsa::RsaPublicKey::from(&first.private_key));
+    assert_eq!(
+        service
+            .saml_certificates(realm_id)
+            .await
+            .expect("certificates"),
+        vec![first.certificate.clone()]
+    );
+
+    service.rotate(realm_id, RS256).await.expect("rotate");
+    let second = service.saml_signing_key(realm_id).await.expect("saml key");
+    assert_ne!(second.kid, first.kid);
+    assert_eq!(
+        service
+            .saml_certificates(realm_id)
+            .await
+            .expect("certificates"),
+        vec![second.certificate.clone(), first.certificate.clone()]
+    );
+}
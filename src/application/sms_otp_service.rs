@@ -0,0 +1,168 @@
+use crate::domain::events::{DomainEvent, MfaChanged, MfaMethod};
+use crate::domain::sms_otp::{
+    generate_sms_code, hash_sms_code, render_sms_message, SmsOtpChallenge, SmsOtpPolicy,
+    SmsResendDecision, DEFAULT_SMS_MESSAGE_TEMPLATE,
+};
+use crate::domain::user_phone_number::{mask_phone_number, UserPhoneNumber};
+use crate::error::{Error, Result};
+use crate::ports::event_bus::EventPublisher;
+use crate::ports::realm_repository::RealmRepository;
+use crate::ports::sms_sender::{SmsMessage, SmsSender};
+use crate::ports::user_phone_number_repository::UserPhoneNumberRepository;
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Debug)]
+pub enum SmsResendOutcome {
+    Sent(SmsOtpChallenge),
+    CoolingDown { retry_after_secs: i64 },
+    LimitReached,
+}
+
+pub struct SmsOtpService {
+    sms_sender: Arc<dyn SmsSender>,
+    phone_number_repo: Arc<dyn UserPhoneNumberRepository>,
+    realm_repo: Arc<dyn RealmRepository>,
+    event_publisher: Arc<dyn EventPublisher>,
+}
+
+impl SmsOtpService {
+    pub fn new(
+        sms_sender: Arc<dyn SmsSender>,
+        phone_number_repo: Arc<dyn UserPhoneNumberRepository>,
+        realm_repo: Arc<dyn RealmRepository>,
+        event_publisher: Arc<dyn EventPublisher>,
+    ) -> Self {
+        Self {
+            sms_sender,
+            phone_number_repo,
+            realm_repo,
+            event_publisher,
+        }
+    }
+
+    /// The user's primary number, which is where codes are sent.
+    pub async fn find_target_phone(
+        &self,
+        realm_id: Uuid,
+        user_id: Uuid,
+        require_verified: bool,
+    ) -> Result<Option<UserPhoneNumber>> {
+        Ok(self
+            .phone_number_repo
+            .find_primary(&user_id)
+            .await?
+            .filter(|phone| phone.realm_id == realm_id)
+            .filter(|phone| phone.is_verified || !require_verified))
+    }
+
+    pub async fn issue(
+        &self,
+        realm_id: Uuid,
+        phone: &UserPhoneNumber,
+        policy: SmsOtpPolicy,
+    ) -> Result<SmsOtpChallenge> {
+        let now = Utc::now();
+        let code = generate_sms_code();
+        self.deliver(realm_id, &phone.phone_number, &code, &policy)
+            .await?;
+        Ok(SmsOtpChallenge {
+            user_id: phone.user_id,
+            phone_number_id: phone.id,
+            masked_phone: mask_phone_number(&phone.phone_number),
+            code_hash: hash_sms_code(&code),
+            expires_at: now + Duration::minutes(policy.ttl_minutes.max(1)),
+            sent_at: now,
+            resend_count: 0,
+            policy,
+        })
+    }
+
+    /// Sends a fresh code to the same number when the throttle allows it. The
+    /// previous code stops working once the new one is issued.
+    pub async fn resend(
+        &self,
+        realm_id: Uuid,
+        challenge: &SmsOtpChallenge,
+    ) -> Result<SmsResendOutcome> {
+        let now = Utc::now();
+        match challenge.resend_decision(now) {
+            SmsResendDecision::CoolingDown { retry_after_secs } => {
+                return Ok(SmsResendOutcome::CoolingDown { retry_after_secs });
+            }
+            SmsResendDecision::LimitReached => return Ok(SmsResendOutcome::LimitReached),
+            SmsResendDecision::Allowed => {}
+        }
+
+        let phone = self
+            .phone_number_repo
+            .find_by_user_id(&challenge.user_id)
+            .await?
+            .into_iter()
+            .find(|phone| phone.id == challenge.phone_number_id && phone.realm_id == realm_id)
+            .ok_or_else(|| Error::NotFound("Phone number not found".to_string()))?;
+
+        let code = generate_sms_code();
+        self.deliver(realm_id, &phone.phone_number, &code, &challenge.policy)
+            .await?;
+        let mut next = challenge.clone();
+        next.code_hash = hash_sms_code(&code);
+        next.sent_at = now;
+        next.expires_at = now + Duration::minutes(challenge.policy.ttl_minutes.max(1));
+        next.resend_count += 1;
+        Ok(SmsResendOutcome::Sent(next))
+    }
+
+    /// A correct code proves the user controls the number. The first proof
+    /// enrolls the number as an SMS second factor.
+    pub async fn mark_verified(&self, realm_id: Uuid, challenge: &SmsOtpChallenge) -> Result<()> {
+        let was_verified = self
+            .phone_number_repo
+            .find_by_user_id(&challenge.user_id)
+            .await?
+            .into_iter()
+            .find(|phone| phone.id == challenge.phone_number_id && phone.realm_id == realm_id)
+            .is_some_and(|phone| phone.is_verified);
+        self.phone_number_repo
+            .set_verified(&challenge.phone_number_id, true, None)
+            .await?;
+        if !was_verified {
+            self.event_publisher
+                .publish(DomainEvent::MfaEnrolled(MfaChanged {
+                    realm_id,
+                    user_id: challenge.user_id,
+                    method: MfaMethod::Sms,
+                }))
+                .await;
+        }
+        Ok(())
+    }
+
+    async fn deliver(
+        &self,
+        realm_id: Uuid,
+        to: &str,
+        code: &str,
+        policy: &SmsOtpPolicy,
+    ) -> Result<()> {
+        let realm = self
+            .realm_repo
+            .find_by_id(&realm_id)
+            .await?
+            .ok_or_else(|| Error::RealmNotFound(realm_id.to_string()))?;
+        let template = policy
+            .message_template
+            .as_deref()
+            .unwrap_or(DEFAULT_SMS_MESSAGE_TEMPLATE);
+        let body = render_sms_message(template, code, &realm.name, policy.ttl_minutes.max(1));
+        self.sms_sender
+            .send(&SmsMessage {
+                realm_id,
+                realm_name: realm.name,
+                to: to.to_string(),
+                body,
+            })
+            .await
+    }
+}
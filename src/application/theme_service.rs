@@ -532,6 +532,15 @@ impl ThemeResolverService {
     /// Permanently deletes a theme. The system/default theme, the realm's active
     /// theme, and any theme assigned to clients are protected.
     pub async fn delete_theme(&self, realm_id: Uuid, theme_id: Uuid) -> Result<()> {
+        self.delete_theme_with_tx(realm_id, theme_id, None).await
+    }
+
+    pub async fn delete_theme_with_tx(
+        &self,
+        realm_id: Uuid,
+        theme_id: Uuid,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
         let theme = self
             .repo
             .find_theme(&realm_id, &theme_id)
@@ -565,7 +574,7 @@ impl ThemeResolverService {
             ));
         }
 
-        self.repo.delete_theme(&realm_id, &theme_id, None).await
+        self.repo.delete_theme(&realm_id, &theme_id, tx).await
     }
 
     pub async fn publish_theme(&self, realm_id: Uuid, theme_id: Uuid) -> Result<ThemeVersion> {
@@ -0,0 +1,410 @@
+use crate::application::secret_service::SecretService;
+use crate::domain::events::{DomainEvent, MfaChanged, MfaMethod};
+use crate::domain::totp_credential::{
+    decode_totp_secret, format_totp_secret, generate_totp_secret, match_totp_step,
+    totp_otpauth_uri, TotpCredential, TOTP_DIGITS, TOTP_PERIOD_SECS,
+};
+use crate::error::{Error, Result};
+use crate::ports::event_bus::EventPublisher;
+use crate::ports::totp_credential_repository::TotpCredentialRepository;
+use chrono::Utc;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Clock skew tolerated when the node does not configure a drift window.
+pub const DEFAULT_TOTP_DRIFT_STEPS: i64 = 1;
+/// Upper bound for configured drift windows (±2 minutes at 30s periods).
+pub const MAX_TOTP_DRIFT_STEPS: i64 = 4;
+
+/// A secret offered to the user while they set up their authenticator app.
+/// Only `secret_encrypted` may be stored; the plain secret is for display.
+pub struct TotpEnrollment {
+    pub secret: String,
+    pub secret_encrypted: String,
+}
+
+impl TotpEnrollment {
+    pub fn secret_display(&self) -> String {
+        format_totp_secret(&self.secret)
+    }
+
+    pub fn otpauth_uri(&self, issuer: &str, account: &str) -> String {
+        totp_otpauth_uri(issuer, account, &self.secret, TOTP_DIGITS, TOTP_PERIOD_SECS)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TotpVerification {
+    Valid,
+    Invalid,
+    NotEnrolled,
+}
+
+pub struct TotpService {
+    repo: Arc<dyn TotpCredentialRepository>,
+    secret_service: Arc<SecretService>,
+    event_publisher: Arc<dyn EventPublisher>,
+}
+
+impl TotpService {
+    pub fn new(
+        repo: Arc<dyn TotpCredentialRepository>,
+        secret_service: Arc<SecretService>,
+        event_publisher: Arc<dyn EventPublisher>,
+    ) -> Self {
+        Self {
+            repo,
+            secret_service,
+            event_publisher,
+        }
+    }
+
+    pub fn start_enrollment(&self) -> Result<TotpEnrollment> {
+        let secret = generate_totp_secret();
+        let secret_encrypted = self.secret_service.encrypt(&secret)?;
+        Ok(TotpEnrollment {
+            secret,
+            secret_encrypted,
+        })
+    }
+
+    /// Reopens a pending enrollment so the page shows the same secret when it
+    /// is rendered again.
+    pub fn resume_enrollment(&self, secret_encrypted: &str) -> Result<TotpEnrollment> {
+        let secret = self.secret_service.decrypt(secret_encrypted)?;
+        Ok(TotpEnrollment {
+            secret,
+            secret_encrypted: secret_encrypted.to_string(),
+        })
+    }
+
+    /// Stores the credential once the user proves their app generates codes
+    /// for the pending secret. Returns false when `code` does not match.
+    pub async fn complete_enrollment(
+        &self,
+        realm_id: Uuid,
+        user_id: Uuid,
+        secret_encrypted: &str,
+        code: &str,
+        drift_steps: i64,
+    ) -> Result<bool> {
+        if self.repo.find_by_user(&realm_id, &user_id).await?.is_some() {
+            return Err(Error::Conflict(
+                "An authenticator app is already enrolled for this user".to_string(),
+            ));
+        }
+
+        let secret = self.secret_service.decrypt(secret_encrypted)?;
+        let secret_bytes = decode_totp_secret(&secret)
+            .ok_or_else(|| Error::System("Invalid pending TOTP secret".to_string()))?;
+        let now = Utc::now();
+        let Some(step) = match_totp_step(
+            &secret_bytes,
+            code,
+            now,
+            TOTP_PERIOD_SECS,
+            TOTP_DIGITS,
+            clamp_drift(drift_steps),
+        ) else {
+            return Ok(false);
+        };
+
+        let mut credential = TotpCredential::new(realm_id, user_id, secret_encrypted.to_string());
+        // The enrollment code counts as used so it cannot be replayed at the
+        // verify step that usually follows.
+        credential.last_used_step = Some(step);
+        credential.last_used_at = Some(now);
+        self.repo.create(&credential).await?;
+        self.event_publisher
+            .publish(DomainEvent::MfaEnrolled(MfaChanged {
+                realm_id,
+                user_id,
+                method: MfaMethod::Totp,
+            }))
+            .await;
+        Ok(true)
+    }
+
+    pub async fn has_credential(&self, realm_id: Uuid, user_id: Uuid) -> Result<bool> {
+        Ok(self.repo.find_by_user(&realm_id, &user_id).await?.is_some())
+    }
+
+    pub async fn find_credential(
+        &self,
+        realm_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<TotpCredential>> {
+        self.repo.find_by_user(&realm_id, &user_id).await
+    }
+
+    /// Checks `code` within the drift window and burns its time step, so each
+    /// code is accepted at most once.
+    pub async fn verify_code(
+        &self,
+        realm_id: Uuid,
+        user_id: Uuid,
+        code: &str,
+        drift_steps: i64,
+    ) -> Result<TotpVerification> {
+        let Some(credential) = self.repo.find_by_user(&realm_id, &user_id).await? else {
+            return Ok(TotpVerification::NotEnrolled);
+        };
+
+        let secret = self.secret_service.decrypt(&credential.secret_encrypted)?;
+        let secret_bytes = decode_totp_secret(&secret)
+            .ok_or_else(|| Error::System("Invalid stored TOTP secret".to_string()))?;
+        let now = Utc::now();
+        let Some(step) = match_totp_step(
+            &secret_bytes,
+            code,
+            now,
+            credential.period_secs,
+            credential.digits as u32,
+            clamp_drift(drift_steps),
+        ) else {
+            return Ok(TotpVerification::Invalid);
+        };
+
+        if credential
+            .last_used_step
+            .is_some_and(|last_used| step <= last_used)
+        {
+            return Ok(TotpVerification::Invalid);
+        }
+        if !self.repo.record_use(&credential.id, step, now).await? {
+            return Ok(TotpVerification::Invalid);
+        }
+        Ok(TotpVerification::Valid)
+    }
+
+    pub async fn remove_credential(&self, realm_id: Uuid, user_id: Uuid) -> Result<bool> {
+        let deleted = self.repo.delete_by_user(&realm_id, &user_id).await?;
+        if deleted {
+            self.event_publisher
+                .publish(DomainEvent::MfaRemoved(MfaChanged {
+                    realm_id,
+                    user_id,
+                    method: MfaMethod::Totp,
+                }))
+                .await;
+        }
+        Ok(deleted)
+    }
+}
+
+fn clamp_drift(drift_steps: i64) -> i64 {
+    drift_steps.clamp(0, MAX_TOTP_DRIFT_STEPS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::totp_credential::{hotp_code, totp_step};
+    use async_trait::async_trait;
+    use chrono::{DateTime, Utc};
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct InMemoryTotpRepo {
+        credentials: Mutex<Vec<TotpCredential>>,
+    }
+
+    #[async_trait]
+    impl TotpCredentialRepository for InMemoryTotpRepo {
+        async fn create(&self, credential: &TotpCredential) -> Result<()> {
+            self.credentials.lock().unwrap().push(credential.clone());
+            Ok(())
+        }
+
+        async fn find_by_user(
+            &self,
+            realm_id: &Uuid,
+            user_id: &Uuid,
+        ) -> Result<Option<TotpCredential>> {
+            Ok(self
+                .credentials
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|c| c.realm_id == *realm_id && c.user_id == *user_id)
+                .cloned())
+        }
+
+        async fn record_use(
+            &self,
+            credential_id: &Uuid,
+            step: i64,
+            used_at: DateTime<Utc>,
+        ) -> Result<bool> {
+            let mut credentials = self.credentials.lock().unwrap();
+            let Some(credential) = credentials.iter_mut().find(|c| c.id == *credential_id) else {
+                return Ok(false);
+            };
+            if credential.last_used_step.is_some_and(|last| last >= step) {
+                return Ok(false);
+            }
+            credential.last_used_step = Some(step);
+            credential.last_used_at = Some(used_at);
+            Ok(true)
+        }
+
+        async fn delete_by_user(&self, realm_id: &Uuid, user_id: &Uuid) -> Result<bool> {
+            let mut credentials = self.credentials.lock().unwrap();
+            let before = credentials.len();
+            credentials.retain(|c| !(c.realm_id == *realm_id && c.user_id == *user_id));
+            Ok(credentials.len() != before)
+        }
+    }
+
+    #[derive(Default)]
+    struct TestEventPublisher {
+        published: Mutex<Vec<DomainEvent>>,
+    }
+
+    #[async_trait]
+    impl EventPublisher for TestEventPublisher {
+        async fn publish(&self, event: DomainEvent) {
+            self.published.lock().unwrap().push(event);
+        }
+    }
+
+    impl TestEventPublisher {
+        fn mfa_events(&self) -> Vec<(&'static str, MfaMethod)> {
+            self.published
+                .lock()
+                .unwrap()
+                .iter()
+                .filter_map(|event| match event {
+                    DomainEvent::MfaEnrolled(e) | DomainEvent::MfaRemoved(e) => {
+                        Some((event.event_type(), e.method))
+                    }
+                    _ => None,
+                })
+                .collect()
+        }
+    }
+
+    fn service() -> (TotpService, Arc<TestEventPublisher>) {
+        let events = Arc::new(TestEventPublisher::default());
+        let service = TotpService::new(
+            Arc::new(InMemoryTotpRepo::default()),
+            Arc::new(SecretService::from_key("test-secret")),
+            events.clone(),
+        );
+        (service, events)
+    }
+
+    fn code_at_offset(secret: &str, offset: i64) -> String {
+        let bytes = decode_totp_secret(secret).expect("secret");
+        let step = totp_step(Utc::now(), TOTP_PERIOD_SECS) + offset;
+        hotp_code(&bytes, step as u64, TOTP_DIGITS)
+    }
+
+    #[tokio::test]
+    async fn enrollment_stores_encrypted_secret_and_burns_code() {
+        let (service, events) = service();
+        let realm_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        let enrollment = service.start_enrollment().expect("enrollment");
+        assert!(enrollment.secret_encrypted.starts_with("enc:v1:"));
+
+        assert!(!service
+            .complete_enrollment(realm_id, user_id, &enrollment.secret_encrypted, "12ab56", 1)
+            .await
+            .expect("wrong code"));
+        assert!(events.mfa_events().is_empty());
+
+        let code = code_at_offset(&enrollment.secret, 0);
+        assert!(service
+            .complete_enrollment(realm_id, user_id, &enrollment.secret_encrypted, &code, 1)
+            .await
+            .expect("enroll"));
+        let stored = service
+            .find_credential(realm_id, user_id)
+            .await
+            .expect("find")
+            .expect("stored");
+        assert_ne!(stored.secret_encrypted, enrollment.secret);
+        assert_eq!(events.mfa_events(), vec![("mfa.enrolled", MfaMethod::Totp)]);
+
+        // The code used to enroll cannot be replayed to pass verification.
+        assert_eq!(
+            service
+                .verify_code(realm_id, user_id, &code, 1)
+                .await
+                .expect("verify"),
+            TotpVerification::Invalid
+        );
+
+        let err = service
+            .complete_enrollment(realm_id, user_id, &enrollment.secret_encrypted, &code, 1)
+            .await
+            .expect_err("second enrollment rejected");
+        assert!(matches!(err, Error::Conflict(_)));
+    }
+
+    #[tokio::test]
+    async fn verify_accepts_drift_and_rejects_replay() {
+        let (service, events) = service();
+        let realm_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        let enrollment = service.start_enrollment().expect("enrollment");
+        let previous = code_at_offset(&enrollment.secret, -1);
+        let credential =
+            TotpCredential::new(realm_id, user_id, enrollment.secret_encrypted.clone());
+        service.repo.create(&credential).await.expect("create");
+
+        assert_eq!(
+            service
+                .verify_code(realm_id, Uuid::new_v4(), &previous, 1)
+                .await
+                .expect("verify"),
+            TotpVerification::NotEnrolled
+        );
+        assert_eq!(
+            service
+                .verify_code(realm_id, user_id, &previous, 0)
+                .await
+                .expect("verify"),
+            TotpVerification::Invalid
+        );
+        assert_eq!(
+            service
+                .verify_code(realm_id, user_id, &previous, 1)
+                .await
+                .expect("verify"),
+            TotpVerification::Valid
+        );
+        assert_eq!(
+            service
+                .verify_code(realm_id, user_id, &previous, 1)
+                .await
+                .expect("verify"),
+            TotpVerification::Invalid
+        );
+
+        let current = code_at_offset(&enrollment.secret, 0);
+        assert_eq!(
+            service
+                .verify_code(realm_id, user_id, &current, 1)
+                .await
+                .expect("verify"),
+            TotpVerification::Valid
+        );
+
+        assert!(service
+            .remove_credential(realm_id, user_id)
+            .await
+            .expect("remove"));
+        assert!(!service
+            .has_credential(realm_id, user_id)
+            .await
+            .expect("has"));
+        assert_eq!(events.mfa_events(), vec![("mfa.removed", MfaMethod::Totp)]);
+        assert!(!service
+            .remove_credential(realm_id, user_id)
+            .await
+            .expect("remove again"));
+        assert_eq!(events.mfa_events().len(), 1);
+    }
+}
@@ -6,16 +6,19 @@ use serde_json::json;
 use uuid::Uuid;
 
 use crate::application::audit_service::AuditService;
+use crate::application::logout_service::LogoutService;
 use crate::application::user_service::UserService;
 use crate::domain::audit::NewAuditEvent;
+use crate::domain::events::{DomainEvent, MfaChanged, MfaMethod};
 use crate::domain::realm_passkey_settings::RealmPasskeySettings;
 use crate::error::{Error, Result};
+use crate::ports::event_bus::EventPublisher;
 use crate::ports::federated_identity_repository::FederatedIdentityRepository;
 use crate::ports::identity_provider_repository::IdentityProviderRepository;
 use crate::ports::passkey_credential_repository::PasskeyCredentialRepository;
 use crate::ports::realm_passkey_settings_repository::RealmPasskeySettingsRepository;
 use crate::ports::realm_repository::RealmRepository;
-use crate::ports::session_repository::SessionRepository;
+use crate::ports::totp_credential_repository::TotpCredentialRepository;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct UserPasswordCredentialSummary {
@@ -36,6 +39,13 @@ pub struct UserPasskeyCredentialSummary {
     pub last_used_at: Option<DateTime<Utc>>,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct UserTotpCredentialSummary {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct UserFederatedIdentitySummary {
     pub id: Uuid,
@@ -52,6 +62,7 @@ pub struct UserCredentialsSummary {
     pub user_id: Uuid,
     pub password: UserPasswordCredentialSummary,
     pub passkeys: Vec<UserPasskeyCredentialSummary>,
+    pub totp: Option<UserTotpCredentialSummary>,
     pub federated_identities: Vec<UserFederatedIdentitySummary>,
 }
 
@@ -59,6 +70,8 @@ pub struct UserCredentialsService {
     user_service: Arc<UserService>,
     repos: UserCredentialsRepositories,
     audit_service: Arc<AuditService>,
+    logout_service: Arc<LogoutService>,
+    event_publisher: Arc<dyn EventPublisher>,
 }
 
 pub struct UserCredentialsRepositories {
@@ -67,7 +80,7 @@ pub struct UserCredentialsRepositories {
     pub realm_repo: Arc<dyn RealmRepository>,
     pub federated_identity_repo: Arc<dyn FederatedIdentityRepository>,
     pub identity_provider_repo: Arc<dyn IdentityProviderRepository>,
-    pub session_repo: Arc<dyn SessionRepository>,
+    pub totp_credential_repo: Arc<dyn TotpCredentialRepository>,
 }
 
 impl UserCredentialsService {
@@ -75,11 +88,15 @@ impl UserCredentialsService {
         user_service: Arc<UserService>,
         repos: UserCredentialsRepositories,
         audit_service: Arc<AuditService>,
+        logout_service: Arc<LogoutService>,
+        event_publisher: Arc<dyn EventPublisher>,
     ) -> Self {
         Self {
             user_service,
             repos,
             audit_service,
+            logout_service,
+            event_publisher,
         }
     }
 
@@ -97,6 +114,16 @@ impl UserCredentialsService {
             .passkey_credential_repo
             .list_by_user(&realm_id, &user.id)
             .await?;
+        let totp = self
+            .repos
+            .totp_credential_repo
+            .find_by_user(&realm_id, &user.id)
+            .await?
+            .map(|credential| UserTotpCredentialSummary {
+                id: credential.id,
+                created_at: credential.created_at,
+                last_used_at: credential.last_used_at,
+            });
         let federated_identities = self
             .repos
             .federated_identity_repo
@@ -152,6 +179,7 @@ impl UserCredentialsService {
                 password_login_disabled: user.password_login_disabled,
             },
             passkeys,
+            totp,
             federated_identities,
         })
     }
@@ -162,14 +190,14 @@ impl UserCredentialsService {
         user_id: Uuid,
         new_password: &str,
         sign_out_all_sessions: bool,
+        skip_password_checks: bool,
     ) -> Result<()> {
         self.user_service
-            .update_password(realm_id, user_id, new_password)
+            .update_password(realm_id, user_id, new_password, skip_password_checks)
             .await?;
         if sign_out_all_sessions {
-            self.repos
-                .session_repo
-                .revoke_all_for_user(&realm_id, &user_id)
+            self.logout_service
+                .revoke_all_for_user(realm_id, user_id)
                 .await?;
         }
         Ok(())
@@ -196,6 +224,46 @@ impl UserCredentialsService {
         Ok(())
     }
 
+    pub async fn remove_totp(
+        &self,
+        realm_id: Uuid,
+        actor_user_id: Option<Uuid>,
+        user_id: Uuid,
+    ) -> Result<()> {
+        self.user_service
+            .get_user_in_realm(realm_id, user_id)
+            .await?;
+        let deleted = self
+            .repos
+            .totp_credential_repo
+            .delete_by_user(&realm_id, &user_id)
+            .await?;
+        if !deleted {
+            return Err(Error::NotFound(
+                "Authenticator app credential not found".to_string(),
+            ));
+        }
+
+        self.audit_service
+            .record(NewAuditEvent {
+                realm_id,
+                actor_user_id,
+                action: "totp_credential_removed".to_string(),
+                target_type: "user".to_string(),
+                target_id: Some(user_id.to_string()),
+                metadata: json!({}),
+            })
+            .await?;
+        self.event_publisher
+            .publish(DomainEvent::MfaRemoved(MfaChanged {
+                realm_id,
+                user_id,
+                method: MfaMethod::Totp,
+            }))
+            .await;
+        Ok(())
+    }
+
     pub async fn rename_passkey(
         &self,
         realm_id: Uuid,
@@ -0,0 +1,195 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::application::secret_service::SecretService;
+use crate::domain::ldap::{Modification, SearchEntry, SearchRequest};
+use crate::domain::user::User;
+use crate::domain::user_federation::{
+    FederationEditMode, FederationMapperKind, UserAttribute, UserFederationLink,
+    UserFederationMapper, UserFederationProvider,
+};
+use crate::error::{Error, Result};
+use crate::ports::federated_user_store::FederatedUserStore;
+use crate::ports::ldap_client::{LdapConnection, LdapConnector};
+use crate::ports::user_federation_repository::UserFederationRepository;
+
+/// Connects to a provider's directory and binds as its service account, or
+/// anonymously when no bind DN is configured.
+pub(super) async fn connect_service_account(
+    connector: &dyn LdapConnector,
+    secret_service: &SecretService,
+    provider: &UserFederationProvider,
+) -> Result<Box<dyn LdapConnection>> {
+    let mut connection = connector.connect(&provider.connection_url).await?;
+    let bind_dn = provider.bind_dn.clone().unwrap_or_default();
+    let password = provider
+        .bind_credential
+        .as_deref()
+        .map(|value| secret_service.decrypt(value))
+        .transpose()?
+        .unwrap_or_default();
+    let result = connection.simple_bind(&bind_dn, &password).await?;
+    if !result.is_success() {
+        connection.unbind().await.ok();
+        return Err(Error::System(format!(
+            "LDAP service account bind failed: {}",
+            result
+        )));
+    }
+    Ok(connection)
+}
+
+/// Looks up the entry for a login name. More than one match is a
+/// misconfigured filter, not a user to pick from.
+pub(super) async fn find_user_entry(
+    connection: &mut dyn LdapConnection,
+    provider: &UserFederationProvider,
+    mappers: &[UserFederationMapper],
+    username: &str,
+) -> Result<Option<SearchEntry>> {
+    let request = SearchRequest::new(
+        &provider.users_dn,
+        provider.search_scope,
+        provider.username_filter(username)?,
+        provider.search_attributes(mappers),
+    );
+    let mut entries = connection.search(request).await?;
+    if entries.len() > 1 {
+        return Err(Error::System(format!(
+            "LDAP search for '{}' returned {} entries",
+            username,
+            entries.len()
+        )));
+    }
+    Ok(entries.pop())
+}
+
+/// Writes local changes of federated users to their directory according to
+/// the provider's edit mode. The username always belongs to the directory.
+pub struct DirectoryUserStore {
+    repo: Arc<dyn UserFederationRepository>,
+    connector: Arc<dyn LdapConnector>,
+    secret_service: Arc<SecretService>,
+}
+
+impl DirectoryUserStore {
+    pub fn new(
+        repo: Arc<dyn UserFederationRepository>,
+        connector: Arc<dyn LdapConnector>,
+        secret_service: Arc<SecretService>,
+    ) -> Self {
+        Self {
+            repo,
+            connector,
+            secret_service,
+        }
+    }
+
+    async fn linked_provider(
+        &self,
+        user: &User,
+    ) -> Result<Option<(UserFederationProvider, UserFederationLink)>> {
+        let Some(link) = self.repo.find_link_by_user(&user.id).await? else {
+            return Ok(None);
+        };
+        let Some(provider) = self
+            .repo
+            .find_provider(&user.realm_id, &link.provider_id)
+            .await?
+        else {
+            return Ok(None);
+        };
+        if !provider.enabled {
+            return Err(Error::Validation(format!(
+                "This account belongs to the disabled user directory '{}'",
+                provider.name
+            )));
+        }
+        Ok(Some((provider, link)))
+    }
+
+    async fn write(
+        &self,
+        provider: &UserFederationProvider,
+        dn: &str,
+        changes: Vec<Modification>,
+    ) -> Result<()> {
+        let mut connection =
+            connect_service_account(self.connector.as_ref(), &self.secret_service, provider)
+                .await?;
+        let result = connection.modify(dn, changes).await;
+        connection.unbind().await.ok();
+        let result = result?;
+        if !result.is_success() {
+            return Err(Error::Validation(format!(
+                "The user directory refused the change: {}",
+                result
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl FederatedUserStore for DirectoryUserStore {
+    async fn update_password(&self, user: &User, new_password: &str) -> Result<()> {
+        let Some((provider, link)) = self.linked_provider(user).await? else {
+            return Ok(());
+        };
+        if provider.edit_mode == FederationEditMode::ReadOnly {
+            return Err(Error::Validation(format!(
+                "The password of this account is managed by the user directory '{}'",
+                provider.name
+            )));
+        }
+        self.write(
+            &provider,
+            &link.dn,
+            vec![provider.password_modification(new_password)],
+        )
+        .await
+    }
+
+    async fn update_profile(&self, current: &User, updated: &User) -> Result<()> {
+        let Some((provider, link)) = self.linked_provider(current).await? else {
+            return Ok(());
+        };
+        if current.username != updated.username {
+            return Err(Error::Validation(format!(
+                "The username of this account is managed by the user directory '{}'",
+                provider.name
+            )));
+        }
+
+        let mut changes = Vec::new();
+        for mapper in self.repo.list_mappers(&provider.id).await? {
+            let FederationMapperKind::Attribute {
+                ldap_attribute,
+                user_attribute,
+            } = mapper.kind
+            else {
+                continue;
+            };
+            let (before, after) = match user_attribute {
+                UserAttribute::FirstName => (&current.first_name, &updated.first_name),
+                UserAttribute::LastName => (&current.last_name, &updated.last_name),
+                UserAttribute::Email => continue,
+            };
+            if before != after {
+                // Replacing with no values removes the attribute.
+                let values = after.iter().map(|value| value.as_bytes().to_vec());
+                changes.push(Modification::replace(&ldap_attribute, values.collect()));
+            }
+        }
+        if changes.is_empty() {
+            return Ok(());
+        }
+        if provider.edit_mode == FederationEditMode::ReadOnly {
+            return Err(Error::Validation(format!(
+                "The profile of this account is managed by the user directory '{}'",
+                provider.name
+            )));
+        }
+        self.write(&provider, &link.dn, changes).await
+    }
+}
@@ -0,0 +1,933 @@
+mod directory;
+
+pub use directory::DirectoryUserStore;
+
+use crate::application::rbac_service::RbacService;
+use crate::application::secret_service::SecretService;
+use crate::application::user_email_service::UserEmailService;
+use crate::application::user_service::UserService;
+use crate::domain::ldap::{SearchRequest, SearchScope};
+use crate::domain::user::User;
+use crate::domain::user_federation::{
+    FederatedProfile, FederationEditMode, FederationMapperKind, FederationVendor, UserAttribute,
+    UserFederationLink, UserFederationMapper, UserFederationProvider,
+};
+use crate::error::{Error, Result};
+use crate::ports::ldap_client::LdapConnector;
+use crate::ports::user_federation_repository::UserFederationRepository;
+use chrono::{DateTime, Utc};
+use directory::{connect_service_account, find_user_entry};
+use rand::distr::{Alphanumeric, SampleString};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{info, warn};
+use url::Url;
+use uuid::Uuid;
+
+const MAX_NAME_LEN: usize = 255;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateUserFederationProviderRequest {
+    pub name: String,
+    pub vendor: FederationVendor,
+    pub connection_url: String,
+    pub bind_dn: Option<String>,
+    pub bind_credential: Option<String>,
+    pub users_dn: String,
+    pub user_object_classes: Option<Vec<String>>,
+    pub custom_user_filter: Option<String>,
+    pub search_scope: Option<SearchScope>,
+    pub username_attribute: Option<String>,
+    pub uuid_attribute: Option<String>,
+    pub membership_attribute: Option<String>,
+    pub edit_mode: Option<FederationEditMode>,
+    pub sync_interval_secs: Option<i64>,
+    pub priority: Option<i64>,
+    pub enabled: Option<bool>,
+}
+
+/// Omitted fields keep their value; an empty `bind_dn`, `bind_credential`
+/// or `custom_user_filter` removes it.
+#[derive(Debug, Deserialize)]
+pub struct UpdateUserFederationProviderRequest {
+    pub name: Option<String>,
+    pub connection_url: Option<String>,
+    pub bind_dn: Option<String>,
+    pub bind_credential: Option<String>,
+    pub users_dn: Option<String>,
+    pub user_object_classes: Option<Vec<String>>,
+    pub custom_user_filter: Option<String>,
+    pub search_scope: Option<SearchScope>,
+    pub username_attribute: Option<String>,
+    pub uuid_attribute: Option<String>,
+    pub membership_attribute: Option<String>,
+    pub edit_mode: Option<FederationEditMode>,
+    pub sync_interval_secs: Option<i64>,
+    pub priority: Option<i64>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserFederationProviderResponse {
+    #[serde(flatten)]
+    pub provider: UserFederationProvider,
+    pub bind_credential_set: bool,
+    pub linked_user_count: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateUserFederationMapperRequest {
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: FederationMapperKind,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UserFederationConnectionTest {
+    pub ok: bool,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UserFederationSyncResult {
+    pub provider_id: Uuid,
+    pub added: u64,
+    pub updated: u64,
+    pub failed: u64,
+    pub synced_at: DateTime<Utc>,
+}
+
+/// The outcome of checking a password against a user's directory.
+#[derive(Debug)]
+pub enum FederatedLogin {
+    /// The user is local; check the password locally.
+    NotFederated,
+    Authenticated(Box<User>),
+    InvalidCredentials,
+    /// The directory could not be asked; the password is neither accepted
+    /// nor counted as a failed attempt.
+    Unavailable,
+}
+
+enum DirectoryLogin {
+    UnknownUser,
+    InvalidCredentials,
+    Authenticated(FederatedProfile),
+}
+
+/// User storage federation: LDAP and Active Directory providers per realm.
+/// Federated users are imported into `users` on first login or by sync and
+/// always authenticate by binding to their directory.
+pub struct UserFederationService {
+    repo: Arc<dyn UserFederationRepository>,
+    connector: Arc<dyn LdapConnector>,
+    secret_service: Arc<SecretService>,
+    user_service: Arc<UserService>,
+    user_email_service: Arc<UserEmailService>,
+    rbac_service: Arc<RbacService>,
+}
+
+impl UserFederationService {
+    pub fn new(
+        repo: Arc<dyn UserFederationRepository>,
+        connector: Arc<dyn LdapConnector>,
+        secret_service: Arc<SecretService>,
+        user_service: Arc<UserService>,
+        user_email_service: Arc<UserEmailService>,
+        rbac_service: Arc<RbacService>,
+    ) -> Self {
+        Self {
+            repo,
+            connector,
+            secret_service,
+            user_service,
+            user_email_service,
+            rbac_service,
+        }
+    }
+
+    // --- Providers ---
+
+    pub async fn list(&self, realm_id: Uuid) -> Result<Vec<UserFederationProviderResponse>> {
+        let mut responses = Vec::new();
+        for provider in self.repo.list_providers(&realm_id).await? {
+            responses.push(self.to_response(provider).await?);
+        }
+        Ok(responses)
+    }
+
+    pub async fn get(&self, realm_id: Uuid, id: Uuid) -> Result<UserFederationProviderResponse> {
+        let provider = self.find(realm_id, id).await?;
+        self.to_response(provider).await
+    }
+
+    async fn find(&self, realm_id: Uuid, id: Uuid) -> Result<UserFederationProvider> {
+        self.repo
+            .find_provider(&realm_id, &id)
+            .await?
+            .ok_or_else(|| Error::NotFound("User federation provider not found".to_string()))
+    }
+
+    /// Creates a provider with the vendor's default attributes and mappers
+    /// for email, first and last name.
+    pub async fn create(
+        &self,
+        realm_id: Uuid,
+        request: CreateUserFederationProviderRequest,
+    ) -> Result<UserFederationProviderResponse> {
+        let name = request.name.trim().to_string();
+        self.ensure_name_free(realm_id, &name, None).await?;
+
+        let defaults = request.vendor.defaults();
+        let now = Utc::now();
+        let provider = UserFederationProvider {
+            id: Uuid::new_v4(),
+            realm_id,
+            name,
+            enabled: request.enabled.unwrap_or(true),
+            vendor: request.vendor,
+            connection_url: request.connection_url.trim().to_string(),
+            bind_dn: request.bind_dn.filter(|value| !value.trim().is_empty()),
+            bind_credential: self.encrypt_credential(request.bind_credential)?,
+            users_dn: request.users_dn.trim().to_string(),
+            user_object_classes: request.user_object_classes.unwrap_or_else(|| {
+                defaults
+                    .user_object_classes
+                    .iter()
+                    .map(|class| class.to_string())
+                    .collect()
+            }),
+            custom_user_filter: request
+                .custom_user_filter
+                .filter(|value| !value.trim().is_empty()),
+            search_scope: request.search_scope.unwrap_or(SearchScope::Subtree),
+            username_attribute: request
+                .username_attribute
+                .unwrap_or_else(|| defaults.username_attribute.to_string()),
+            uuid_attribute: request
+                .uuid_attribute
+                .unwrap_or_else(|| defaults.uuid_attribute.to_string()),
+            membership_attribute: request
+                .membership_attribute
+                .unwrap_or_else(|| defaults.membership_attribute.to_string()),
+            edit_mode: request.edit_mode.unwrap_or(FederationEditMode::ReadOnly),
+            sync_interval_secs: request.sync_interval_secs.unwrap_or(0),
+            priority: request.priority.unwrap_or(0),
+            last_sync_at: None,
+            last_sync_error: None,
+            created_at: now,
+            updated_at: now,
+        };
+        validate_provider(&provider)?;
+        self.repo.create_provider(&provider).await?;
+
+        for (ldap_attribute, user_attribute) in [
+            (defaults.email_attribute, UserAttribute::Email),
+            (defaults.first_name_attribute, UserAttribute::FirstName),
+            (defaults.last_name_attribute, UserAttribute::LastName),
+        ] {
+            self.repo
+                .create_mapper(&UserFederationMapper {
+                    id: Uuid::new_v4(),
+                    provider_id: provider.id,
+                    name: user_attribute.to_string(),
+                    kind: FederationMapperKind::Attribute {
+                        ldap_attribute: ldap_attribute.to_string(),
+                        user_attribute,
+                    },
+                    created_at: now,
+                })
+                .await?;
+        }
+        self.to_response(provider).await
+    }
+
+    pub async fn update(
+        &self,
+        realm_id: Uuid,
+        id: Uuid,
+        request: UpdateUserFederationProviderRequest,
+    ) -> Result<UserFederationProviderResponse> {
+        let mut provider = self.find(realm_id, id).await?;
+
+        if let Some(name) = request.name {
+            let name = name.trim().to_string();
+            self.ensure_name_free(realm_id, &name, Some(id)).await?;
+            provider.name = name;
+        }
+        if let Some(value) = request.connection_url {
+            provider.connection_url = value.trim().to_string();
+        }
+        if let Some(value) = request.bind_dn {
+            provider.bind_dn = Some(value).filter(|value| !value.trim().is_empty());
+        }
+        if let Some(value) = request.bind_credential {
+            provider.bind_credential = self.encrypt_credential(Some(value))?;
+        }
+        if let Some(value) = request.users_dn {
+            provider.users_dn = value.trim().to_string();
+        }
+        if let Some(value) = request.user_object_classes {
+            provider.user_object_classes = value;
+        }
+        if let Some(value) = request.custom_user_filter {
+            provider.custom_user_filter = Some(value).filter(|value| !value.trim().is_empty());
+        }
+        if let Some(value) = request.search_scope {
+            provider.search_scope = value;
+        }
+        if let Some(value) = request.username_attribute {
+            provider.username_attribute = value;
+        }
+        if let Some(value) = request.uuid_attribute {
+            provider.uuid_attribute = value;
+        }
+        if let Some(value) = request.membership_attribute {
+            provider.membership_attribute = value;
+        }
+        if let Some(value) = request.edit_mode {
+            provider.edit_mode = value;
+        }
+        if let Some(value) = request.sync_interval_secs {
+            provider.sync_interval_secs = value;
+        }
+        if let Some(value) = request.priority {
+            provider.priority = value;
+        }
+        if let Some(value) = request.enabled {
+            provider.enabled = value;
+        }
+        validate_provider(&provider)?;
+        provider.updated_at = Utc::now();
+        self.repo.update_provider(&provider).await?;
+        self.to_response(provider).await
+    }
+
+    /// Deletes a provider. Its imported users stay as local users.
+    pub async fn delete(&self, realm_id: Uuid, id: Uuid) -> Result<()> {
+        if !self.repo.delete_provider(&realm_id, &id).await? {
+            return Err(Error::NotFound(
+                "User federation provider not found".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Connects to the directory and binds as the service account.
+    pub async fn test_connection(
+        &self,
+        realm_id: Uuid,
+        id: Uuid,
+    ) -> Result<UserFederationConnectionTest> {
+        let provider = self.find(realm_id, id).await?;
+        let result =
+            connect_service_account(self.connector.as_ref(), &self.secret_service, &provider).await;
+        Ok(match result {
+            Ok(mut connection) => {
+                connection.unbind().await.ok();
+                UserFederationConnectionTest {
+                    ok: true,
+                    detail: match provider.bind_dn.as_deref() {
+                        Some(bind_dn) => format!("Connected and bound as '{}'", bind_dn),
+                        None => "Connected and bound anonymously".to_string(),
+                    },
+                }
+            }
+            Err(err) => UserFederationConnectionTest {
+                ok: false,
+                detail: err.to_string(),
+            },
+        })
+    }
+
+    async fn ensure_name_free(
+        &self,
+        realm_id: Uuid,
+        name: &str,
+        current: Option<Uuid>,
+    ) -> Result<()> {
+        if name.is_empty() || name.len() > MAX_NAME_LEN {
+            return Err(Error::Validation(format!(
+                "User federation provider name must be 1-{} characters",
+                MAX_NAME_LEN
+            )));
+        }
+        match self.repo.find_provider_by_name(&realm_id, name).await? {
+            Some(existing) if Some(existing.id) != current => Err(Error::Validation(
+                "A user federation provider with this name already exists in this realm"
+                    .to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn encrypt_credential(&self, value: Option<String>) -> Result<Option<String>> {
+        value
+            .filter(|value| !value.is_empty())
+            .map(|value| self.secret_service.encrypt(&value))
+            .transpose()
+    }
+
+    async fn to_response(
+        &self,
+        provider: UserFederationProvider,
+    ) -> Result<UserFederationProviderResponse> {
+        let linked_user_count = self.repo.count_links(&provider.id).await?;
+        Ok(UserFederationProviderResponse {
+            bind_credential_set: provider.bind_credential.is_some(),
+            linked_user_count,
+            provider,
+        })
+    }
+
+    // --- Mappers ---
+
+    pub async fn list_mappers(
+        &self,
+        realm_id: Uuid,
+        provider_id: Uuid,
+    ) -> Result<Vec<UserFederationMapper>> {
+        let provider = self.find(realm_id, provider_id).await?;
+        self.repo.list_mappers(&provider.id).await
+    }
+
+    pub async fn create_mapper(
+        &self,
+        realm_id: Uuid,
+        provider_id: Uuid,
+        request: CreateUserFederationMapperRequest,
+    ) -> Result<UserFederationMapper> {
+        let provider = self.find(realm_id, provider_id).await?;
+        let name = request.name.trim().to_string();
+        if name.is_empty() || name.len() > MAX_NAME_LEN {
+            return Err(Error::Validation(format!(
+                "Mapper name must be 1-{} characters",
+                MAX_NAME_LEN
+            )));
+        }
+        let existing = self.repo.list_mappers(&provider.id).await?;
+        if existing.iter().any(|mapper| mapper.name == name) {
+            return Err(Error::Validation(
+                "A mapper with this name already exists for this provider".to_string(),
+            ));
+        }
+        match &request.kind {
+            FederationMapperKind::Attribute { ldap_attribute, .. } => {
+                if ldap_attribute.trim().is_empty() {
+                    return Err(Error::Validation(
+                        "ldap_attribute cannot be empty".to_string(),
+                    ));
+                }
+            }
+            FederationMapperKind::GroupRole { group_dn, role_id } => {
+                if group_dn.trim().is_empty() {
+                    return Err(Error::Validation("group_dn cannot be empty".to_string()));
+                }
+                self.rbac_service.get_role(realm_id, *role_id).await?;
+            }
+        }
+
+        let mapper = UserFederationMapper {
+            id: Uuid::new_v4(),
+            provider_id: provider.id,
+            name,
+            kind: request.kind,
+            created_at: Utc::now(),
+        };
+        self.repo.create_mapper(&mapper).await?;
+        Ok(mapper)
+    }
+
+    pub async fn delete_mapper(&self, realm_id: Uuid, provider_id: Uuid, id: Uuid) -> Result<()> {
+        let provider = self.find(realm_id, provider_id).await?;
+        if !self.repo.delete_mapper(&provider.id, &id).await? {
+            return Err(Error::NotFound("Mapper not found".to_string()));
+        }
+        Ok(())
+    }
+
+    // --- Authentication ---
+
+    /// Checks the password of a known user. Federated users bind to their
+    /// directory as themselves; on success their profile is refreshed.
+    pub async fn authenticate(&self, user: &User, password: &str) -> Result<FederatedLogin> {
+        let Some(link) = self.repo.find_link_by_user(&user.id).await? else {
+            return Ok(FederatedLogin::NotFederated);
+        };
+        let Some(provider) = self
+            .repo
+            .find_provider(&user.realm_id, &link.provider_id)
+            .await?
+        else {
+            return Ok(FederatedLogin::NotFederated);
+        };
+        if !provider.enabled {
+            return Ok(FederatedLogin::Unavailable);
+        }
+        // An empty password is an unauthenticated bind, which succeeds.
+        if password.is_empty() {
+            return Ok(FederatedLogin::InvalidCredentials);
+        }
+
+        let mappers = self.repo.list_mappers(&provider.id).await?;
+        match self
+            .bind_user(&provider, &mappers, &user.username, password)
+            .await
+        {
+            Err(err) => {
+                warn!(
+                    "User federation provider '{}' is unavailable: {}",
+                    provider.name, err
+                );
+                Ok(FederatedLogin::Unavailable)
+            }
+            Ok(DirectoryLogin::UnknownUser) => {
+                warn!(
+                    "Federated user '{}' is no longer in directory '{}'",
+                    user.username, provider.name
+                );
+                Ok(FederatedLogin::InvalidCredentials)
+            }
+            Ok(DirectoryLogin::InvalidCredentials) => Ok(FederatedLogin::InvalidCredentials),
+            Ok(DirectoryLogin::Authenticated(profile))
+                if profile.external_id != link.external_id =>
+            {
+                warn!(
+                    "Directory entry for '{}' in '{}' belongs to someone else",
+                    user.username, provider.name
+                );
+                Ok(FederatedLogin::InvalidCredentials)
+            }
+            Ok(DirectoryLogin::Authenticated(profile)) => {
+                let user = self
+                    .refresh_on_login(&provider, &mappers, user.clone(), &profile)
+                    .await;
+                Ok(FederatedLogin::Authenticated(Box::new(user)))
+            }
+        }
+    }
+
+    /// Looks for an unknown login name in the realm's providers, by
+    /// priority, and imports the user once the directory accepts the
+    /// password.
+    pub async fn import_on_login(
+        &self,
+        realm_id: Uuid,
+        username: &str,
+        password: &str,
+    ) -> Result<FederatedLogin> {
+        if password.is_empty() {
+            return Ok(FederatedLogin::NotFederated);
+        }
+        let mut unavailable = false;
+        for provider in self.repo.list_providers(&realm_id).await? {
+            if !provider.enabled {
+                continue;
+            }
+            let mappers = self.repo.list_mappers(&provider.id).await?;
+            let profile = match self
+                .bind_user(&provider, &mappers, username, password)
+                .await
+            {
+                Err(err) => {
+                    warn!(
+                        "User federation provider '{}' is unavailable: {}",
+                        provider.name, err
+                    );
+                    unavailable = true;
+                    continue;
+                }
+                Ok(DirectoryLogin::UnknownUser) => continue,
+                Ok(DirectoryLogin::InvalidCredentials) => {
+                    return Ok(FederatedLogin::InvalidCredentials)
+                }
+                Ok(DirectoryLogin::Authenticated(profile)) => profile,
+            };
+
+            let user = match self
+                .repo
+                .find_link_by_external_id(&provider.id, &profile.external_id)
+                .await?
+            {
+                // Renamed in the directory since the last sync.
+                Some(link) => {
+                    let user = self
+                        .user_service
+                        .get_user_in_realm(realm_id, link.user_id)
+                        .await?;
+                    self.refresh_on_login(&provider, &mappers, user, &profile)
+                        .await
+                }
+                None => {
+                    let user = self.import(&provider, &mappers, &profile).await?;
+                    info!(
+                        "Imported user '{}' from user federation provider '{}'",
+                        user.username, provider.name
+                    );
+                    user
+                }
+            };
+            return Ok(FederatedLogin::Authenticated(Box::new(user)));
+        }
+        Ok(if unavailable {
+            FederatedLogin::Unavailable
+        } else {
+            FederatedLogin::NotFederated
+        })
+    }
+
+    /// Finds `username` with the service account, then binds as that entry.
+    async fn bind_user(
+        &self,
+        provider: &UserFederationProvider,
+        mappers: &[UserFederationMapper],
+        username: &str,
+        password: &str,
+    ) -> Result<DirectoryLogin> {
+        let mut connection =
+            connect_service_account(self.connector.as_ref(), &self.secret_service, provider)
+                .await?;
+        let result = async {
+            let Some(entry) =
+                find_user_entry(connection.as_mut(), provider, mappers, username).await?
+            else {
+                return Ok(DirectoryLogin::UnknownUser);
+            };
+            let Some(profile) = provider.map_entry(&entry, mappers) else {
+                return Ok(DirectoryLogin::UnknownUser);
+            };
+            let bind = connection.simple_bind(&entry.dn, password).await?;
+            if !bind.is_success() {
+                return Ok(DirectoryLogin::InvalidCredentials);
+            }
+            Ok(DirectoryLogin::Authenticated(profile))
+        }
+        .await;
+        connection.unbind().await.ok();
+        result
+    }
+
+    /// A failed refresh does not fail the login; the next sync retries it.
+    async fn refresh_on_login(
+        &self,
+        provider: &UserFederationProvider,
+        mappers: &[UserFederationMapper],
+        user: User,
+        profile: &FederatedProfile,
+    ) -> User {
+        let user_id = user.id;
+        match self.refresh(provider, mappers, user.clone(), profile).await {
+            Ok(user) => user,
+            Err(err) => {
+                warn!(
+                    "Could not refresh federated user {} from '{}': {}",
+                    user_id, provider.name, err
+                );
+                user
+            }
+        }
+    }
+
+    // --- Import and sync ---
+
+    /// Imports every user entry of a provider, updating users imported
+    /// before. Entries that clash with unlinked local users are counted as
+    /// failed.
+    pub async fn sync(&self, realm_id: Uuid, id: Uuid) -> Result<UserFederationSyncResult> {
+        let mut provider = self.find(realm_id, id).await?;
+        if !provider.enabled {
+            return Err(Error::Validation(
+                "User federation provider is disabled".to_string(),
+            ));
+        }
+
+        let result = self.run_sync(&provider).await;
+        provider.last_sync_at = Some(Utc::now());
+        provider.last_sync_error = match &result {
+            Ok(summary) if summary.failed > 0 => {
+                Some(format!("{} entries failed to sync", summary.failed))
+            }
+            Ok(_) => None,
+            Err(err) => Some(err.to_string()),
+        };
+        self.repo.update_provider(&provider).await?;
+        result
+    }
+
+    /// Syncs the providers whose interval has passed. Returns how many were
+    /// synced.
+    pub async fn sync_due_providers(&self) -> Result<usize> {
+        let now = Utc::now();
+        let mut synced = 0;
+        for provider in self.repo.list_enabled_providers().await? {
+            if !provider.is_sync_due(now) {
+                continue;
+            }
+            match self.sync(provider.realm_id, provider.id).await {
+                Ok(summary) => info!(
+                    "Synced user federation provider '{}': {} added, {} updated, {} failed",
+                    provider.name, summary.added, summary.updated, summary.failed
+                ),
+                Err(err) => warn!(
+                    "Sync of user federation provider '{}' failed: {}",
+                    provider.name, err
+                ),
+            }
+            synced += 1;
+        }
+        Ok(synced)
+    }
+
+    async fn run_sync(
+        &self,
+        provider: &UserFederationProvider,
+    ) -> Result<UserFederationSyncResult> {
+        let mappers = self.repo.list_mappers(&provider.id).await?;
+        let request = SearchRequest::new(
+            &provider.users_dn,
+            provider.search_scope,
+            provider.user_filter()?,
+            provider.search_attributes(&mappers),
+        );
+        let mut connection =
+            connect_service_account(self.connector.as_ref(), &self.secret_service, provider)
+                .await?;
+        let entries = connection.search(request).await;
+        connection.unbind().await.ok();
+
+        let mut summary = UserFederationSyncResult {
+            provider_id: provider.id,
+            added: 0,
+            updated: 0,
+            failed: 0,
+            synced_at: Utc::now(),
+        };
+        for entry in entries? {
+            let Some(profile) = provider.map_entry(&entry, &mappers) else {
+                warn!(
+                    "Skipping '{}' from '{}': no username or identifier",
+                    entry.dn, provider.name
+                );
+                summary.failed += 1;
+                continue;
+            };
+            let linked = self
+                .repo
+                .find_link_by_external_id(&provider.id, &profile.external_id)
+                .await?;
+            let result = match linked {
+                Some(link) => {
+                    let user = self
+                        .user_service
+                        .get_user_in_realm(provider.realm_id, link.user_id)
+                        .await;
+                    match user {
+                        Ok(user) => self.refresh(provider, &mappers, user, &profile).await,
+                        Err(err) => Err(err),
+                    }
+                    .map(|_| false)
+                }
+                None => self
+                    .import(provider, &mappers, &profile)
+                    .await
+                    .map(|_| true),
+            };
+            match result {
+                Ok(true) => summary.added += 1,
+                Ok(false) => summary.updated += 1,
+                Err(err) => {
+                    warn!(
+                        "Could not sync '{}' from '{}': {}",
+                        profile.dn, provider.name, err
+                    );
+                    summary.failed += 1;
+                }
+            }
+        }
+        Ok(summary)
+    }
+
+    /// Creates and links a local user for a directory entry. The random
+    /// local password is never used: federated users bind to the directory.
+    async fn import(
+        &self,
+        provider: &UserFederationProvider,
+        mappers: &[UserFederationMapper],
+        profile: &FederatedProfile,
+    ) -> Result<User> {
+        if self
+            .user_service
+            .find_by_username(&provider.realm_id, &profile.username)
+            .await?
+            .is_some()
+        {
+            return Err(Error::Conflict(format!(
+                "A local user named '{}' already exists",
+                profile.username
+            )));
+        }
+        let password = Alphanumeric.sample_string(&mut rand::rng(), 32);
+        let user = self
+            .user_service
+            .create_user(provider.realm_id, &profile.username, &password, None, true)
+            .await?;
+        self.refresh(provider, mappers, user, profile).await
+    }
+
+    /// Copies the mapped directory attributes and group roles onto the
+    /// user and records the link.
+    async fn refresh(
+        &self,
+        provider: &UserFederationProvider,
+        mappers: &[UserFederationMapper],
+        user: User,
+        profile: &FederatedProfile,
+    ) -> Result<User> {
+        let first_name = if maps(mappers, UserAttribute::FirstName) {
+            profile.first_name.clone()
+        } else {
+            user.first_name.clone()
+        };
+        let last_name = if maps(mappers, UserAttribute::LastName) {
+            profile.last_name.clone()
+        } else {
+            user.last_name.clone()
+        };
+        let user = self
+            .user_service
+            .sync_federated_profile(
+                provider.realm_id,
+                user.id,
+                profile.username.clone(),
+                first_name,
+                last_name,
+            )
+            .await?;
+
+        if maps(mappers, UserAttribute::Email) {
+            if let Some(email) = profile.email.as_deref() {
+                self.sync_email(provider.realm_id, user.id, email).await?;
+            }
+        }
+        self.sync_roles(provider.realm_id, user.id, mappers, profile)
+            .await?;
+
+        let now = Utc::now();
+        self.repo
+            .save_link(&UserFederationLink {
+                user_id: user.id,
+                provider_id: provider.id,
+                external_id: profile.external_id.clone(),
+                dn: profile.dn.clone(),
+                last_synced_at: Some(now),
+                created_at: now,
+            })
+            .await?;
+        Ok(user)
+    }
+
+    /// Makes the directory address the primary one. Other addresses stay.
+    async fn sync_email(&self, realm_id: Uuid, user_id: Uuid, email: &str) -> Result<()> {
+        let current = self.user_email_service.list_emails(user_id).await?;
+        let existing = current
+            .iter()
+            .find(|existing| existing.email.eq_ignore_ascii_case(email));
+        match existing {
+            Some(existing) if existing.is_primary => Ok(()),
+            Some(existing) => {
+                self.user_email_service
+                    .set_primary(user_id, existing.id)
+                    .await
+            }
+            None => {
+                let added = self
+                    .user_email_service
+                    .add_email(user_id, realm_id, email, current.is_empty(), false)
+                    .await?;
+                if !added.is_primary {
+                    self.user_email_service
+                        .set_primary(user_id, added.id)
+                        .await?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Grants each mapped role to group members and takes it from everyone
+    /// else. A role mapped from several groups is kept for members of any.
+    async fn sync_roles(
+        &self,
+        realm_id: Uuid,
+        user_id: Uuid,
+        mappers: &[UserFederationMapper],
+        profile: &FederatedProfile,
+    ) -> Result<()> {
+        let mut wanted: HashMap<Uuid, bool> = HashMap::new();
+        for mapper in mappers {
+            if let FederationMapperKind::GroupRole { group_dn, role_id } = &mapper.kind {
+                *wanted.entry(*role_id).or_default() |= profile.is_member_of(group_dn);
+            }
+        }
+        if wanted.is_empty() {
+            return Ok(());
+        }
+
+        let current = self
+            .rbac_service
+            .get_direct_role_ids_for_user(realm_id, user_id)
+            .await?;
+        for (role_id, member) in wanted {
+            let has_role = current.contains(&role_id);
+            if member && !has_role {
+                self.rbac_service
+                    .assign_role_to_user(realm_id, user_id, role_id)
+                    .await?;
+            } else if !member && has_role {
+                self.rbac_service
+                    .remove_role_from_user(realm_id, user_id, role_id)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn maps(mappers: &[UserFederationMapper], attribute: UserAttribute) -> bool {
+    mappers.iter().any(|mapper| {
+        matches!(
+            &mapper.kind,
+            FederationMapperKind::Attribute { user_attribute, .. } if *user_attribute == attribute
+        )
+    })
+}
+
+fn validate_provider(provider: &UserFederationProvider) -> Result<()> {
+    let url = Url::parse(&provider.connection_url)
+        .map_err(|_| Error::Validation("connection_url must be a valid URL".to_string()))?;
+    if !matches!(url.scheme(), "ldap" | "ldaps") || url.host_str().is_none() {
+        return Err(Error::Validation(
+            "connection_url must be an ldap:// or ldaps:// URL".to_string(),
+        ));
+    }
+    if provider.users_dn.is_empty() {
+        return Err(Error::Validation("users_dn cannot be empty".to_string()));
+    }
+    for (field, value) in [
+        ("username_attribute", &provider.username_attribute),
+        ("uuid_attribute", &provider.uuid_attribute),
+        ("membership_attribute", &provider.membership_attribute),
+    ] {
+        if value.trim().is_empty() {
+            return Err(Error::Validation(format!("{} cannot be empty", field)));
+        }
+    }
+    if provider.sync_interval_secs < 0 {
+        return Err(Error::Validation(
+            "sync_interval_secs cannot be negative".to_string(),
+        ));
+    }
+    provider.user_filter()?;
+    Ok(())
+}
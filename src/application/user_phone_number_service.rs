@@ -2,24 +2,29 @@ use std::sync::Arc;
 
 use uuid::Uuid;
 
+use crate::domain::events::{DomainEvent, MfaChanged, MfaMethod};
 use crate::domain::user_phone_number::{normalize_phone_number, UserPhoneNumber};
 use crate::error::{Error, Result};
+use crate::ports::event_bus::EventPublisher;
 use crate::ports::transaction_manager::TransactionManager;
 use crate::ports::user_phone_number_repository::UserPhoneNumberRepository;
 
 pub struct UserPhoneNumberService {
     phone_number_repo: Arc<dyn UserPhoneNumberRepository>,
     tx_manager: Arc<dyn TransactionManager>,
+    event_publisher: Arc<dyn EventPublisher>,
 }
 
 impl UserPhoneNumberService {
     pub fn new(
         phone_number_repo: Arc<dyn UserPhoneNumberRepository>,
         tx_manager: Arc<dyn TransactionManager>,
+        event_publisher: Arc<dyn EventPublisher>,
     ) -> Self {
         Self {
             phone_number_repo,
             tx_manager,
+            event_publisher,
         }
     }
 
@@ -68,6 +73,9 @@ impl UserPhoneNumberService {
         self.phone_number_repo.save(&record, Some(&mut *tx)).await?;
         self.tx_manager.commit(tx).await?;
 
+        if record.is_verified {
+            self.publish_sms_factor_change(&record, true).await;
+        }
         Ok(record)
     }
 
@@ -84,7 +92,13 @@ impl UserPhoneNumberService {
             ));
         }
 
-        self.phone_number_repo.delete(&phone_number_id, None).await
+        self.phone_number_repo
+            .delete(&phone_number_id, None)
+            .await?;
+        if target.is_verified {
+            self.publish_sms_factor_change(target, false).await;
+        }
+        Ok(())
     }
 
     pub async fn set_primary(&self, user_id: Uuid, phone_number_id: Uuid) -> Result<()> {
@@ -111,15 +125,33 @@ impl UserPhoneNumberService {
         is_verified: bool,
     ) -> Result<()> {
         let all = self.phone_number_repo.find_by_user_id(&user_id).await?;
-        if !all
+        let target = all
             .iter()
-            .any(|phone_number| phone_number.id == phone_number_id)
-        {
-            return Err(Error::NotFound("Phone number not found".to_string()));
-        }
+            .find(|phone_number| phone_number.id == phone_number_id)
+            .ok_or_else(|| Error::NotFound("Phone number not found".to_string()))?;
 
         self.phone_number_repo
             .set_verified(&phone_number_id, is_verified, None)
-            .await
+            .await?;
+        if target.is_verified != is_verified {
+            self.publish_sms_factor_change(target, is_verified).await;
+        }
+        Ok(())
+    }
+
+    /// A verified number is an SMS second factor, so gaining or losing one is
+    /// published as `mfa.enrolled` or `mfa.removed`.
+    async fn publish_sms_factor_change(&self, phone_number: &UserPhoneNumber, enrolled: bool) {
+        let change = MfaChanged {
+            realm_id: phone_number.realm_id,
+            user_id: phone_number.user_id,
+            method: MfaMethod::Sms,
+        };
+        let event = if enrolled {
+            DomainEvent::MfaEnrolled(change)
+        } else {
+            DomainEvent::MfaRemoved(change)
+        };
+        self.event_publisher.publish(event).await;
     }
 }
@@ -1,11 +1,14 @@
 use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::application::password_policy_service::PasswordPolicyService;
 use crate::domain::crypto::HashedPassword;
 use crate::domain::events::{DomainEvent, UserChanged, UserCreated, UserDeleted};
 use crate::domain::pagination::{PageRequest, PageResponse};
+use crate::domain::password_policy::PasswordHistoryEntry;
 use crate::domain::user_email::UserEmail;
 use crate::ports::event_bus::EventPublisher;
+use crate::ports::federated_user_store::FederatedUserStore;
 use crate::ports::outbox_repository::OutboxRepository;
 use crate::ports::transaction_manager::{Transaction, TransactionManager};
 use crate::ports::user_email_repository::UserEmailRepository;
@@ -63,6 +66,8 @@ pub struct UserService {
     event_bus: Arc<dyn EventPublisher>,
     outbox_repo: Arc<dyn OutboxRepository>,
     tx_manager: Arc<dyn TransactionManager>,
+    password_policy_service: Arc<PasswordPolicyService>,
+    federated_user_store: Arc<dyn FederatedUserStore>,
 }
 
 impl UserService {
@@ -72,6 +77,8 @@ impl UserService {
         event_bus: Arc<dyn EventPublisher>,
         outbox_repo: Arc<dyn OutboxRepository>,
         tx_manager: Arc<dyn TransactionManager>,
+        password_policy_service: Arc<PasswordPolicyService>,
+        federated_user_store: Arc<dyn FederatedUserStore>,
     ) -> Self {
         Self {
             user_repo,
@@ -79,6 +86,8 @@ impl UserService {
             event_bus,
             outbox_repo,
             tx_manager,
+            password_policy_service,
+            federated_user_store,
         }
     }
 
@@ -115,14 +124,15 @@ impl UserService {
     }
 
     /// Create a new user. If `email` is supplied it is stored as the primary email
-    /// in `user_emails` within the same transaction.
+    /// in `user_emails` within the same transaction. The password must satisfy the
+    /// realm's password policy unless `ignore_password_policies` is set.
     pub async fn create_user(
         &self,
         realm_id: Uuid,
         username: &str,
         password: &str,
         email: Option<&str>,
-        _ignore_password_policies: bool,
+        ignore_password_policies: bool,
     ) -> Result<User> {
         if self
             .user_repo
@@ -145,6 +155,16 @@ impl UserService {
             }
         }
 
+        let policy = self
+            .password_policy_service
+            .policy_for_realm(realm_id)
+            .await?;
+        if !ignore_password_policies {
+            self.password_policy_service
+                .check_password(&policy, password, username, None)
+                .await?;
+        }
+
         let hashed_password = HashedPassword::new(password)?;
 
         let user = User {
@@ -174,6 +194,11 @@ impl UserService {
         let mut tx = self.tx_manager.begin().await?;
         let result: Result<()> = async {
             self.user_repo.save(&user, Some(&mut *tx)).await?;
+            let history_entry =
+                PasswordHistoryEntry::new(realm_id, user.id, user.hashed_password.clone());
+            self.password_policy_service
+                .record_password(&policy, &history_entry, &mut *tx)
+                .await?;
 
             if let Some(email_val) = normalized_email.as_deref() {
                 let user_email =
@@ -281,6 +306,7 @@ impl UserService {
     }
 
     /// Update mutable profile fields. Emails and phone numbers are managed via sub-resource services.
+    /// Changes to federated users are written through to their directory first.
     pub async fn update_profile(
         &self,
         realm_id: Uuid,
@@ -289,7 +315,49 @@ impl UserService {
         new_first_name: Option<Option<String>>,
         new_last_name: Option<Option<String>>,
     ) -> Result<User> {
-        let mut user = self.get_user_in_realm(realm_id, user_id).await?;
+        self.apply_profile(
+            realm_id,
+            user_id,
+            new_username,
+            new_first_name,
+            new_last_name,
+            true,
+        )
+        .await
+    }
+
+    /// Stores profile fields read from a user federation provider, without
+    /// writing them back to the directory.
+    pub async fn sync_federated_profile(
+        &self,
+        realm_id: Uuid,
+        user_id: Uuid,
+        username: String,
+        first_name: Option<String>,
+        last_name: Option<String>,
+    ) -> Result<User> {
+        self.apply_profile(
+            realm_id,
+            user_id,
+            Some(username),
+            Some(first_name),
+            Some(last_name),
+            false,
+        )
+        .await
+    }
+
+    async fn apply_profile(
+        &self,
+        realm_id: Uuid,
+        user_id: Uuid,
+        new_username: Option<String>,
+        new_first_name: Option<Option<String>>,
+        new_last_name: Option<Option<String>>,
+        write_through: bool,
+    ) -> Result<User> {
+        let current = self.get_user_in_realm(realm_id, user_id).await?;
+        let mut user = current.clone();
         let mut changed = false;
 
         if let Some(username) = new_username {
@@ -324,6 +392,11 @@ impl UserService {
         }
 
         if changed {
+            if write_through {
+                self.federated_user_store
+                    .update_profile(&current, &user)
+                    .await?;
+            }
             user.updated_at = Some(Utc::now());
             let event = DomainEvent::UserUpdated(UserChanged {
                 user_id: user.id,
@@ -402,13 +475,30 @@ impl UserService {
         })
     }
 
+    /// Sets a new password, enforcing the realm's password policy (including
+    /// reuse history) unless `ignore_password_policies` is set. Federated
+    /// users' passwords are changed in their directory first.
     pub async fn update_password(
         &self,
         realm_id: Uuid,
         user_id: Uuid,
         new_password: &str,
+        ignore_password_policies: bool,
     ) -> Result<User> {
         let mut user = self.get_user_in_realm(realm_id, user_id).await?;
+        let policy = self
+            .password_policy_service
+            .policy_for_realm(realm_id)
+            .await?;
+        if !ignore_password_policies {
+            self.password_policy_service
+                .check_password(&policy, new_password, &user.username, Some(&user))
+                .await?;
+        }
+        self.federated_user_store
+            .update_password(&user, new_password)
+            .await?;
+
         let hashed_password = HashedPassword::new(new_password)?;
         user.hashed_password = hashed_password.as_str().to_string();
         user.force_password_reset = false;
@@ -417,8 +507,62 @@ impl UserService {
             user_id: user.id,
             username: user.username.clone(),
         });
-        self.update_user_with_event(&user, event).await?;
-        Ok(user)
+        let history_entry =
+            PasswordHistoryEntry::new(realm_id, user.id, user.hashed_password.clone());
+
+        let mut tx = self.tx_manager.begin().await?;
+        let result: Result<()> = async {
+            self.user_repo.update(&user, Some(&mut *tx)).await?;
+            self.password_policy_service
+                .record_password(&policy, &history_entry, &mut *tx)
+                .await?;
+            self.write_outbox(&event, realm_id, &mut *tx).await?;
+            Ok(())
+        }
+        .await;
+
+        match result {
+            Ok(()) => {
+                self.tx_manager.commit(tx).await?;
+                self.event_bus.publish(event).await;
+                Ok(user)
+            }
+            Err(err) => {
+                self.tx_manager.rollback(tx).await?;
+                Err(err)
+            }
+        }
+    }
+
+    /// Sets `force_password_reset` when the realm's maximum password age has
+    /// passed. Returns whether the user now has to reset their password.
+    pub async fn expire_stale_password(&self, user: &mut User) -> Result<bool> {
+        if user.force_password_reset {
+            return Ok(true);
+        }
+        let policy = self
+            .password_policy_service
+            .policy_for_realm(user.realm_id)
+            .await?;
+        if policy.max_age_days <= 0 {
+            return Ok(false);
+        }
+        let changed_at = self
+            .password_policy_service
+            .password_changed_at(user)
+            .await?;
+        if !policy.is_expired(changed_at, Utc::now()) {
+            return Ok(false);
+        }
+
+        user.force_password_reset = true;
+        user.updated_at = Some(Utc::now());
+        let event = DomainEvent::UserUpdated(UserChanged {
+            user_id: user.id,
+            username: user.username.clone(),
+        });
+        self.update_user_with_event(user, event).await?;
+        Ok(true)
     }
 
     pub async fn update_credential_policy(
@@ -494,6 +638,21 @@ impl UserService {
         Ok(user)
     }
 
+    pub async fn unban_user(&self, realm_id: Uuid, user_id: Uuid) -> Result<User> {
+        let mut user = self.get_user_in_realm(realm_id, user_id).await?;
+        if user.banned_at.is_none() {
+            return Ok(user);
+        }
+        user.banned_at = None;
+        user.updated_at = Some(Utc::now());
+        let event = DomainEvent::UserUpdated(UserChanged {
+            user_id: user.id,
+            username: user.username.clone(),
+        });
+        self.update_user_with_event(&user, event).await?;
+        Ok(user)
+    }
+
     pub async fn get_primary_email(&self, user_id: &Uuid) -> Result<Option<String>> {
         Ok(self
             .user_email_repo
@@ -567,7 +567,7 @@ fn serialize_error_chain(chain: &[String]) -> Option<String> {
     serde_json::to_string(chain).ok()
 }
 
-fn normalize_http_method(method: Option<&str>) -> Result<String> {
+pub(crate) fn normalize_http_method(method: Option<&str>) -> Result<String> {
     let normalized = method.unwrap_or("POST").trim().to_uppercase();
     match normalized.as_str() {
         "POST" | "PUT" => Ok(normalized),
@@ -577,7 +577,7 @@ fn normalize_http_method(method: Option<&str>) -> Result<String> {
     }
 }
 
-fn validate_subscription_event_types(event_types: &[String]) -> Result<()> {
+pub(crate) fn validate_subscription_event_types(event_types: &[String]) -> Result<()> {
     for event_type in event_types {
         if !is_supported_webhook_event_type(event_type) {
             return Err(Error::Validation(format!(
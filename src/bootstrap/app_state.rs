@@ -1,5 +1,7 @@
 use std::sync::Arc;
 
+use crate::application::claims_service::ClaimsService;
+use crate::application::client_registration_service::ClientRegistrationService;
 use crate::application::delivery_replay_service::DeliveryReplayService;
 use crate::application::email_delivery_service::EmailDeliveryService;
 use crate::application::flow_executor::FlowExecutor;
@@ -8,22 +10,28 @@ use crate::application::flow_service::FlowService;
 use crate::application::harbor::HarborService;
 use crate::application::idp_service::IdentityProviderService;
 use crate::application::invitation_service::InvitationService;
+use crate::application::logout_service::LogoutService;
 use crate::application::metrics_service::MetricsService;
 use crate::application::node_registry::NodeRegistryService;
 use crate::application::oauth_broker_service::OAuthBrokerService;
 use crate::application::oidc_service::OidcService;
 use crate::application::passkey_analytics_service::PasskeyAnalyticsService;
 use crate::application::passkey_assertion_service::PasskeyAssertionService;
+use crate::application::password_policy_service::PasswordPolicyService;
 use crate::application::realm_email_settings_service::RealmEmailSettingsService;
 use crate::application::realm_idp_settings_service::RealmIdpSettingsService;
 use crate::application::realm_passkey_settings_service::RealmPasskeySettingsService;
 use crate::application::realm_recovery_settings_service::RealmRecoverySettingsService;
 use crate::application::realm_security_headers_service::RealmSecurityHeadersService;
+use crate::application::saml_service::SamlService;
+use crate::application::scim_service::ScimService;
+use crate::application::signing_key_service::SigningKeyService;
 use crate::application::theme_service::ThemeResolverService;
+use crate::application::user_federation_service::UserFederationService;
 use crate::application::webhook_service::WebhookService;
 use crate::application::{
-    audit_service::AuditService, auth_service::AuthService, rbac_service::RbacService,
-    realm_service::RealmService, telemetry_service::TelemetryService,
+    audit_service::AuditService, auth_service::AuthService, consent_service::ConsentService,
+    rbac_service::RbacService, realm_service::RealmService, telemetry_service::TelemetryService,
     user_credentials_service::UserCredentialsService, user_email_service::UserEmailService,
     user_phone_number_service::UserPhoneNumberService, user_service::UserService,
 };
@@ -67,8 +75,10 @@ pub struct AppState {
     pub user_email_service: Arc<UserEmailService>,
     pub user_phone_number_service: Arc<UserPhoneNumberService>,
     pub user_credentials_service: Arc<UserCredentialsService>,
+    pub consent_service: Arc<ConsentService>,
     pub rbac_service: Arc<RbacService>,
     pub auth_service: Arc<AuthService>,
+    pub logout_service: Arc<LogoutService>,
     pub audit_service: Arc<AuditService>,
     pub telemetry_service: Arc<TelemetryService>,
     pub delivery_replay_service: Arc<DeliveryReplayService>,
@@ -79,15 +89,22 @@ pub struct AppState {
     pub realm_passkey_settings_service: Arc<RealmPasskeySettingsService>,
     pub realm_recovery_settings_service: Arc<RealmRecoverySettingsService>,
     pub realm_security_headers_service: Arc<RealmSecurityHeadersService>,
+    pub password_policy_service: Arc<PasswordPolicyService>,
     pub passkey_assertion_service: Arc<PasskeyAssertionService>,
     pub passkey_analytics_service: Arc<PasskeyAnalyticsService>,
     pub email_delivery_service: Arc<EmailDeliveryService>,
     pub invitation_service: Arc<InvitationService>,
     pub identity_provider_service: Arc<IdentityProviderService>,
+    pub user_federation_service: Arc<UserFederationService>,
     pub webhook_service: Arc<WebhookService>,
     pub theme_service: Arc<ThemeResolverService>,
     pub harbor_service: Arc<HarborService>,
     pub oidc_service: Arc<OidcService>,
+    pub client_registration_service: Arc<ClientRegistrationService>,
+    pub scim_service: Arc<ScimService>,
+    pub saml_service: Arc<SamlService>,
+    pub claims_service: Arc<ClaimsService>,
+    pub signing_key_service: Arc<SigningKeyService>,
     pub oauth_broker_service: Arc<OAuthBrokerService>,
     pub flow_service: Arc<FlowService>,
     pub flow_manager: Arc<FlowManager>,
@@ -1,31 +1,44 @@
 use crate::adapters::cache::moka_cache::MokaCacheService;
 use crate::adapters::crypto::jwt_service::JwtService;
-use crate::adapters::crypto::key_manager::KeyManager;
 use crate::adapters::eventing::in_memory_bus::InMemoryEventBus;
+use crate::application::secret_service::SecretService;
+use crate::application::signing_key_service::SigningKeyService;
+use crate::bootstrap::repositories::Repositories;
 use crate::config::Settings;
-use crate::error;
 use std::sync::Arc;
 
-/// Initializes core infrastructure services (Event Bus, Cache, Crypto/JWT).
-/// Returns a Result because loading keys from disk can fail.
-pub fn initialize_core_infra(
-    settings: &Settings,
-) -> error::Result<(
-    Arc<InMemoryEventBus>,
-    Arc<MokaCacheService>,
-    Arc<JwtService>, // Using concrete type here is fine for bootstrap
-)> {
-    // 1. Load or create RSA keys
-    // Now settings.database.data_dir exists!
-    let key_pair = KeyManager::get_or_create_keys(&settings.database.data_dir)?;
+pub struct CoreInfra {
+    pub event_bus: Arc<InMemoryEventBus>,
+    pub cache: Arc<MokaCacheService>,
+    pub jwt_service: Arc<JwtService>, // Using concrete type here is fine for bootstrap
+    pub secret_service: Arc<SecretService>,
+    pub signing_key_service: Arc<SigningKeyService>,
+}
 
-    // 2. Construct services
+/// Initializes core infrastructure services (Event Bus, Cache, Crypto/JWT).
+pub fn initialize_core_infra(settings: &Settings, repos: &Repositories) -> CoreInfra {
     let event_bus = Arc::new(InMemoryEventBus::new());
     let cache = Arc::new(MokaCacheService::new());
 
-    // 3. Initialize JWT Service with the loaded keys
-    // We use `?` to propagate any key decoding errors
-    let jwt = Arc::new(JwtService::new(settings.auth.clone(), key_pair)?);
+    // Signing keys live in the database per realm; private keys are
+    // encrypted with the same secret as other stored credentials.
+    let secret_service = Arc::new(SecretService::from_settings(settings));
+    let signing_key_service = Arc::new(SigningKeyService::new(
+        repos.signing_key_repo.clone(),
+        repos.realm_repo.clone(),
+        repos.oidc_repo.clone(),
+        secret_service.clone(),
+    ));
+    let jwt_service = Arc::new(JwtService::new(
+        settings.auth.clone(),
+        signing_key_service.clone(),
+    ));
 
-    Ok((event_bus, cache, jwt))
+    CoreInfra {
+        event_bus,
+        cache,
+        jwt_service,
+        secret_service,
+        signing_key_service,
+    }
 }
@@ -1,25 +1,36 @@
+use crate::adapters::eventing::multi_publisher::MultiEventPublisher;
+use crate::adapters::eventing::outbox_publisher::OutboxEventPublisher;
 use crate::adapters::eventing::outbox_worker::OutboxWorker;
+use crate::adapters::ldap::tcp_ldap_connector::TcpLdapConnector;
 use crate::adapters::logging::banner::print_banner;
 use crate::adapters::observability::sqlite_telemetry_repository::SqliteTelemetryRepository;
 use crate::adapters::observability::telemetry_store::init_telemetry_db;
 use crate::adapters::observability::telemetry_writer::TelemetryWriter;
 use crate::adapters::persistence::connection::Database;
 use crate::adapters::persistence::transaction::SqliteTransactionManager;
+use crate::adapters::sms::http_sms_sender::HttpSmsSender;
+use crate::adapters::sms::log_sms_sender::LogSmsSender;
 use crate::adapters::web::outbound_http_client::ReqwestDeliveryClient;
 use crate::application::delivery_replay_service::DeliveryReplayService;
 use crate::application::metrics_service::MetricsService;
+use crate::application::signing_key_service::{KeyRotationPolicy, SigningKeyService};
 use crate::application::telemetry_service::TelemetryService;
+use crate::application::user_federation_service::UserFederationService;
 use crate::bootstrap::app_state::SetupState;
 use crate::bootstrap::database::{initialize_database, run_migrations_and_seed};
 use crate::bootstrap::events::subscribe_event_listeners;
-use crate::bootstrap::infrastructure::initialize_core_infra;
+use crate::bootstrap::infrastructure::{initialize_core_infra, CoreInfra};
 use crate::bootstrap::logging::init_logging;
 use crate::bootstrap::repositories::initialize_repositories;
 use crate::bootstrap::services::initialize_services;
 use crate::config::Settings;
 use crate::constants::DEFAULT_REALM_NAME;
+use crate::ports::device_authorization_repository::DeviceAuthorizationRepository;
+use crate::ports::http_client::HttpDeliveryClient;
 use crate::ports::oauth_broker_state_repository::OAuthBrokerStateRepository;
 use crate::ports::passkey_challenge_repository::PasskeyChallengeRepository;
+use crate::ports::pushed_authorization_request_repository::PushedAuthorizationRequestRepository;
+use crate::ports::sms_sender::SmsSender;
 use crate::ports::transaction_manager::TransactionManager;
 use crate::AppState;
 use chrono::{Duration, Utc};
@@ -41,6 +52,10 @@ struct InitializeOptions {
     enable_harbor_cleanup: bool,
     enable_passkey_challenge_cleanup: bool,
     enable_oauth_broker_state_cleanup: bool,
+    enable_device_code_cleanup: bool,
+    enable_par_request_cleanup: bool,
+    enable_signing_key_rotation: bool,
+    enable_user_federation_sync: bool,
 }
 
 pub async fn initialize() -> anyhow::Result<AppState> {
@@ -57,28 +72,42 @@ pub async fn initialize() -> anyhow::Result<AppState> {
             enable_harbor_cleanup: true,
             enable_passkey_challenge_cleanup: true,
             enable_oauth_broker_state_cleanup: true,
+            enable_device_code_cleanup: true,
+            enable_par_request_cleanup: true,
+            enable_signing_key_rotation: true,
+            enable_user_federation_sync: true,
         },
     )
     .await
 }
 
+/// No banner, config watcher or background workers.
+const HEADLESS_OPTIONS: InitializeOptions = InitializeOptions {
+    print_banner: false,
+    log_summary: false,
+    watch_config: false,
+    enable_telemetry_cleanup: false,
+    enable_outbox_worker: false,
+    enable_refresh_cleanup: false,
+    enable_harbor_cleanup: false,
+    enable_passkey_challenge_cleanup: false,
+    enable_oauth_broker_state_cleanup: false,
+    enable_device_code_cleanup: false,
+    enable_par_request_cleanup: false,
+    enable_signing_key_rotation: false,
+    enable_user_federation_sync: false,
+};
+
 pub async fn initialize_for_tests() -> anyhow::Result<AppState> {
     let settings = Settings::new()?;
-    initialize_with_settings(
-        settings,
-        InitializeOptions {
-            print_banner: false,
-            log_summary: false,
-            watch_config: false,
-            enable_telemetry_cleanup: false,
-            enable_outbox_worker: false,
-            enable_refresh_cleanup: false,
-            enable_harbor_cleanup: false,
-            enable_passkey_challenge_cleanup: false,
-            enable_oauth_broker_state_cleanup: false,
-        },
-    )
-    .await
+    initialize_with_settings(settings, HEADLESS_OPTIONS).await
+}
+
+/// Builds the application for one-shot CLI commands that use services
+/// without serving requests.
+pub async fn initialize_for_cli() -> anyhow::Result<AppState> {
+    let settings = Settings::new()?;
+    initialize_with_settings(settings, HEADLESS_OPTIONS).await
 }
 
 async fn initialize_with_settings(
@@ -102,7 +131,13 @@ async fn initialize_with_settings(
     let db_pool = initialize_database(&settings).await?;
     let repos = initialize_repositories(&db_pool);
 
-    let (event_bus, cache_service, jwt_service) = initialize_core_infra(&settings)?;
+    let CoreInfra {
+        event_bus,
+        cache: cache_service,
+        jwt_service,
+        secret_service,
+        signing_key_service,
+    } = initialize_core_infra(&settings, &repos);
 
     let tx_manager: Arc<dyn TransactionManager> =
         Arc::new(SqliteTransactionManager::new(db_pool.clone()));
@@ -115,11 +150,19 @@ async fn initialize_with_settings(
         repos: &repos,
         cache: &cache_service,
         event_publisher: event_bus.clone(),
+        security_event_publisher: Arc::new(MultiEventPublisher::new(vec![
+            Arc::new(OutboxEventPublisher::new(db_pool.clone())),
+            event_bus.clone(),
+        ])),
         outbox_repo: repos.outbox_repo.clone(),
         token_service: &jwt_service,
+        secret_service,
+        signing_key_service,
         telemetry_repo: telemetry_repo.clone(),
         tx_manager: &tx_manager,
         http_client: http_client.clone(),
+        sms_sender: build_sms_sender(&settings, http_client.clone()),
+        ldap_connector: Arc::new(TcpLdapConnector::new(std::time::Duration::from_secs(5))),
     });
 
     let delivery_replay_service = Arc::new(DeliveryReplayService::new(
@@ -159,7 +202,7 @@ async fn initialize_with_settings(
         spawn_telemetry_cleanup(settings_shared.clone(), telemetry_service.clone());
     }
     if options.enable_outbox_worker {
-        OutboxWorker::new(db_pool.clone(), telemetry_db).spawn();
+        OutboxWorker::new(db_pool.clone(), telemetry_db, jwt_service.clone()).spawn();
     }
     if options.enable_refresh_cleanup {
         spawn_refresh_token_cleanup(settings_shared.clone(), db_pool.clone());
@@ -179,6 +222,30 @@ async fn initialize_with_settings(
             repos.oauth_broker_state_repo.clone(),
         );
     }
+    if options.enable_device_code_cleanup {
+        spawn_device_code_cleanup(
+            settings_shared.clone(),
+            repos.device_authorization_repo.clone(),
+        );
+    }
+    if options.enable_par_request_cleanup {
+        spawn_par_request_cleanup(
+            settings_shared.clone(),
+            repos.pushed_authorization_request_repo.clone(),
+        );
+    }
+    if options.enable_signing_key_rotation {
+        spawn_signing_key_rotation(
+            settings_shared.clone(),
+            services.signing_key_service.clone(),
+        );
+    }
+    if options.enable_user_federation_sync {
+        spawn_user_federation_sync(
+            settings_shared.clone(),
+            services.user_federation_service.clone(),
+        );
+    }
 
     Ok(AppState {
         settings: settings_shared,
@@ -186,8 +253,10 @@ async fn initialize_with_settings(
         user_email_service: services.user_email_service,
         user_phone_number_service: services.user_phone_number_service,
         user_credentials_service: services.user_credentials_service,
+        consent_service: services.consent_service,
         rbac_service: services.rbac_service,
         auth_service: services.auth_service,
+        logout_service: services.logout_service,
         audit_service: services.audit_service,
         telemetry_service,
         delivery_replay_service,
@@ -198,11 +267,13 @@ async fn initialize_with_settings(
         realm_passkey_settings_service: services.realm_passkey_settings_service,
         realm_recovery_settings_service: services.realm_recovery_settings_service,
         realm_security_headers_service: services.realm_security_headers_service,
+        password_policy_service: services.password_policy_service,
         passkey_assertion_service: services.passkey_assertion_service,
         passkey_analytics_service: services.passkey_analytics_service,
         email_delivery_service: services.email_delivery_service,
         invitation_service: services.invitation_service,
         identity_provider_service: services.identity_provider_service,
+        user_federation_service: services.user_federation_service,
         webhook_service: services.webhook_service,
         theme_service: services.theme_service,
         harbor_service: services.harbor_service,
@@ -212,6 +283,11 @@ async fn initialize_with_settings(
         flow_store: repos.flow_store,
         // flow_engine has been removed
         oidc_service: services.oidc_service,
+        client_registration_service: services.client_registration_service,
+        scim_service: services.scim_service,
+        saml_service: services.saml_service,
+        claims_service: services.claims_service,
+        signing_key_service: services.signing_key_service,
         oauth_broker_service: services.oauth_broker_service,
         flow_service: services.flow_service,
         flow_manager: services.flow_manager,
@@ -233,6 +309,22 @@ fn log_config_summary(settings: &Settings) {
     );
 }
 
+fn build_sms_sender(
+    settings: &Settings,
+    http_client: Arc<dyn HttpDeliveryClient>,
+) -> Arc<dyn SmsSender> {
+    match settings.sms.provider.as_str() {
+        "http" => Arc::new(HttpSmsSender::new(http_client, &settings.sms)),
+        _ => {
+            info!(
+                "SMS delivery uses the development log sender ({}).",
+                settings.sms.log_file
+            );
+            Arc::new(LogSmsSender::new(&settings.sms.log_file))
+        }
+    }
+}
+
 fn warn_public_url_mismatch(settings: &Settings) {
     if let Some((public_origin, bind_origins)) = settings.public_url_mismatch() {
         warn!(
@@ -607,6 +699,170 @@ fn spawn_oauth_broker_state_cleanup(
     });
 }
 
+fn spawn_device_code_cleanup(
+    settings: Arc<RwLock<Settings>>,
+    device_authorization_repo: Arc<dyn DeviceAuthorizationRepository>,
+) {
+    tokio::spawn(async move {
+        loop {
+            let interval_secs = { settings.read().await.auth.device_code_cleanup_interval_secs };
+            if interval_secs == 0 {
+                info!("Device code cleanup disabled (device_code_cleanup_interval_secs=0).");
+                return;
+            }
+
+            tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+
+            let batch_size = { settings.read().await.auth.device_code_cleanup_batch_size };
+            let cutoff = Utc::now();
+            let mut total_removed = 0u64;
+
+            loop {
+                match device_authorization_repo
+                    .delete_expired_before(cutoff, batch_size)
+                    .await
+                {
+                    Ok(removed) => {
+                        total_removed += removed;
+                        if removed < batch_size.max(1) as u64 {
+                            break;
+                        }
+                    }
+                    Err(err) => {
+                        warn!("Failed to cleanup device codes: {}", err);
+                        break;
+                    }
+                }
+            }
+
+            if total_removed > 0 {
+                info!(
+                    "Device code cleanup removed {} rows (cutoff {}).",
+                    total_removed, cutoff
+                );
+            }
+        }
+    });
+}
+
+fn spawn_par_request_cleanup(
+    settings: Arc<RwLock<Settings>>,
+    par_request_repo: Arc<dyn PushedAuthorizationRequestRepository>,
+) {
+    tokio::spawn(async move {
+        loop {
+            let interval_secs = { settings.read().await.auth.par_request_cleanup_interval_secs };
+            if interval_secs == 0 {
+                info!("PAR request cleanup disabled (par_request_cleanup_interval_secs=0).");
+                return;
+            }
+
+            tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+
+            let batch_size = { settings.read().await.auth.par_request_cleanup_batch_size };
+            let cutoff = Utc::now();
+            let mut total_removed = 0u64;
+
+            loop {
+                match par_request_repo
+                    .delete_expired_before(cutoff, batch_size)
+                    .await
+                {
+                    Ok(removed) => {
+                        total_removed += removed;
+                        if removed < batch_size.max(1) as u64 {
+                            break;
+                        }
+                    }
+                    Err(err) => {
+                        warn!("Failed to cleanup pushed authorization requests: {}", err);
+                        break;
+                    }
+                }
+            }
+
+            if total_removed > 0 {
+                info!(
+                    "PAR request cleanup removed {} rows (cutoff {}).",
+                    total_removed, cutoff
+                );
+            }
+        }
+    });
+}
+
+fn spawn_signing_key_rotation(
+    settings: Arc<RwLock<Settings>>,
+    signing_key_service: Arc<SigningKeyService>,
+) {
+    tokio::spawn(async move {
+        loop {
+            let (check_interval_secs, policy) = {
+                let settings = settings.read().await;
+                (
+                    settings.auth.signing_key_rotation_check_interval_secs,
+                    KeyRotationPolicy {
+                        rotation_interval: Duration::seconds(
+                            settings.auth.signing_key_rotation_interval_secs as i64,
+                        ),
+                        passive_retention: Duration::seconds(
+                            settings.auth.signing_key_retention_secs as i64,
+                        ),
+                    },
+                )
+            };
+            if check_interval_secs == 0 {
+                info!(
+                    "Signing key rotation disabled (signing_key_rotation_check_interval_secs=0)."
+                );
+                return;
+            }
+
+            tokio::time::sleep(std::time::Duration::from_secs(check_interval_secs)).await;
+
+            match signing_key_service.rotate_due_keys(&policy).await {
+                Ok(summary) => {
+                    if summary.rotated > 0 || summary.retired > 0 {
+                        info!(
+                            "Signing key rotation rotated {} realm(s) and retired {} key(s).",
+                            summary.rotated, summary.retired
+                        );
+                    }
+                }
+                Err(err) => {
+                    warn!("Failed to rotate signing keys: {}", err);
+                }
+            }
+        }
+    });
+}
+
+fn spawn_user_federation_sync(
+    settings: Arc<RwLock<Settings>>,
+    user_federation_service: Arc<UserFederationService>,
+) {
+    tokio::spawn(async move {
+        loop {
+            let check_interval_secs = {
+                let settings = settings.read().await;
+                settings.auth.user_federation_sync_check_interval_secs
+            };
+            if check_interval_secs == 0 {
+                info!(
+                    "User federation sync disabled (user_federation_sync_check_interval_secs=0)."
+                );
+                return;
+            }
+
+            tokio::time::sleep(std::time::Duration::from_secs(check_interval_secs)).await;
+
+            if let Err(err) = user_federation_service.sync_due_providers().await {
+                warn!("Failed to sync user federation providers: {}", err);
+            }
+        }
+    });
+}
+
 async fn cleanup_harbor_artifacts(
     storage_dir: &str,
     retention_hours: u64,
@@ -695,12 +951,17 @@ fn warn_non_reloadable_changes(old: &Settings, new: &Settings) {
     if old.auth.jwt_secret != new.auth.jwt_secret {
         changes.push("auth.jwt_secret");
     }
-    if old.auth.jwt_key_id != new.auth.jwt_key_id {
-        changes.push("auth.jwt_key_id");
-    }
     if old.auth.issuer != new.auth.issuer {
         changes.push("auth.issuer");
     }
+    if old.sms.provider != new.sms.provider
+        || old.sms.log_file != new.sms.log_file
+        || old.sms.gateway_url != new.sms.gateway_url
+        || old.sms.gateway_authorization != new.sms.gateway_authorization
+        || old.sms.gateway_body_template != new.sms.gateway_body_template
+    {
+        changes.push("sms");
+    }
 
     if !changes.is_empty() {
         warn!(
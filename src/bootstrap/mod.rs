@@ -10,5 +10,5 @@ pub mod seed;
 mod services;
 
 pub use app_state::AppState;
-pub use initialize::{initialize, initialize_for_tests};
+pub use initialize::{initialize, initialize_for_cli, initialize_for_tests};
 pub use runtime::run;
@@ -2,6 +2,9 @@ use crate::adapters::persistence::connection::Database;
 use crate::adapters::persistence::sqlite_audit_repository::SqliteAuditRepository;
 use crate::adapters::persistence::sqlite_auth_session_action_repository::SqliteAuthSessionActionRepository;
 use crate::adapters::persistence::sqlite_auth_session_repository::SqliteAuthSessionRepository;
+use crate::adapters::persistence::sqlite_client_registration_repository::SqliteClientRegistrationRepository;
+use crate::adapters::persistence::sqlite_consent_repository::SqliteConsentRepository;
+use crate::adapters::persistence::sqlite_device_authorization_repository::SqliteDeviceAuthorizationRepository;
 use crate::adapters::persistence::sqlite_federated_identity_repository::SqliteFederatedIdentityRepository;
 use crate::adapters::persistence::sqlite_flow_store::SqliteFlowStore;
 use crate::adapters::persistence::sqlite_harbor_job_conflict_repository::SqliteHarborJobConflictRepository;
@@ -15,19 +18,30 @@ use crate::adapters::persistence::sqlite_oidc_repository::SqliteOidcRepository;
 use crate::adapters::persistence::sqlite_outbox_repository::SqliteOutboxRepository;
 use crate::adapters::persistence::sqlite_passkey_challenge_repository::SqlitePasskeyChallengeRepository;
 use crate::adapters::persistence::sqlite_passkey_credential_repository::SqlitePasskeyCredentialRepository;
+use crate::adapters::persistence::sqlite_password_history_repository::SqlitePasswordHistoryRepository;
+use crate::adapters::persistence::sqlite_password_policy_repository::SqlitePasswordPolicyRepository;
+use crate::adapters::persistence::sqlite_protocol_mapper_repository::SqliteProtocolMapperRepository;
+use crate::adapters::persistence::sqlite_pushed_authorization_request_repository::SqlitePushedAuthorizationRequestRepository;
 use crate::adapters::persistence::sqlite_realm_email_settings_repository::SqliteRealmEmailSettingsRepository;
 use crate::adapters::persistence::sqlite_realm_idp_settings_repository::SqliteRealmIdpSettingsRepository;
 use crate::adapters::persistence::sqlite_realm_passkey_settings_repository::SqliteRealmPasskeySettingsRepository;
 use crate::adapters::persistence::sqlite_realm_recovery_settings_repository::SqliteRealmRecoverySettingsRepository;
 use crate::adapters::persistence::sqlite_realm_security_headers_repository::SqliteRealmSecurityHeadersRepository;
 use crate::adapters::persistence::sqlite_recovery_attempt_repository::SqliteRecoveryAttemptRepository;
+use crate::adapters::persistence::sqlite_saml_service_provider_repository::SqliteSamlServiceProviderRepository;
+use crate::adapters::persistence::sqlite_scim_repository::SqliteScimRepository;
 use crate::adapters::persistence::sqlite_theme_repository::SqliteThemeRepository;
+use crate::adapters::persistence::sqlite_totp_credential_repository::SqliteTotpCredentialRepository;
 use crate::adapters::persistence::sqlite_user_email_repository::SqliteUserEmailRepository;
+use crate::adapters::persistence::sqlite_user_federation_repository::SqliteUserFederationRepository;
 use crate::adapters::persistence::sqlite_user_phone_number_repository::SqliteUserPhoneNumberRepository;
 use crate::adapters::persistence::sqlite_webhook_repository::SqliteWebhookRepository;
 use crate::ports::audit_repository::AuditRepository;
 use crate::ports::auth_session_action_repository::AuthSessionActionRepository;
 use crate::ports::auth_session_repository::AuthSessionRepository;
+use crate::ports::client_registration_repository::ClientRegistrationRepository;
+use crate::ports::consent_repository::ConsentRepository;
+use crate::ports::device_authorization_repository::DeviceAuthorizationRepository;
 use crate::ports::federated_identity_repository::FederatedIdentityRepository;
 use crate::ports::flow_store::FlowStore;
 use crate::ports::harbor_job_conflict_repository::HarborJobConflictRepository;
@@ -40,14 +54,22 @@ use crate::ports::oidc_repository::OidcRepository;
 use crate::ports::outbox_repository::OutboxRepository;
 use crate::ports::passkey_challenge_repository::PasskeyChallengeRepository;
 use crate::ports::passkey_credential_repository::PasskeyCredentialRepository;
+use crate::ports::password_history_repository::PasswordHistoryRepository;
+use crate::ports::password_policy_repository::PasswordPolicyRepository;
+use crate::ports::protocol_mapper_repository::ProtocolMapperRepository;
+use crate::ports::pushed_authorization_request_repository::PushedAuthorizationRequestRepository;
 use crate::ports::realm_email_settings_repository::RealmEmailSettingsRepository;
 use crate::ports::realm_idp_settings_repository::RealmIdpSettingsRepository;
 use crate::ports::realm_passkey_settings_repository::RealmPasskeySettingsRepository;
 use crate::ports::realm_recovery_settings_repository::RealmRecoverySettingsRepository;
 use crate::ports::realm_security_headers_repository::RealmSecurityHeadersRepository;
 use crate::ports::recovery_attempt_repository::RecoveryAttemptRepository;
+use crate::ports::saml_service_provider_repository::SamlServiceProviderRepository;
+use crate::ports::scim_repository::ScimRepository;
 use crate::ports::theme_repository::ThemeRepository;
+use crate::ports::totp_credential_repository::TotpCredentialRepository;
 use crate::ports::user_email_repository::UserEmailRepository;
+use crate::ports::user_federation_repository::UserFederationRepository;
 use crate::ports::user_phone_number_repository::UserPhoneNumberRepository;
 use crate::ports::webhook_repository::WebhookRepository;
 use crate::{
@@ -55,6 +77,7 @@ use crate::{
         sqlite_flow_repository::SqliteFlowRepository, sqlite_rbac_repository::SqliteRbacRepository,
         sqlite_realm_repository::SqliteRealmRepository,
         sqlite_session_repository::SqliteSessionRepository,
+        sqlite_signing_key_repository::SqliteSigningKeyRepository,
         sqlite_user_repository::SqliteUserRepository,
     },
     ports::{
@@ -64,6 +87,7 @@ use crate::{
         rbac_repository::RbacRepository,
         realm_repository::RealmRepository,
         session_repository::SessionRepository,
+        signing_key_repository::SigningKeyRepository,
         user_repository::UserRepository,
     },
 };
@@ -73,6 +97,7 @@ use std::sync::Arc;
 pub struct Repositories {
     pub user_repo: Arc<dyn UserRepository>,
     pub user_email_repo: Arc<dyn UserEmailRepository>,
+    pub user_federation_repo: Arc<dyn UserFederationRepository>,
     pub user_phone_number_repo: Arc<dyn UserPhoneNumberRepository>,
     pub rbac_repo: Arc<dyn RbacRepository>,
     pub realm_repo: Arc<dyn RealmRepository>,
@@ -83,9 +108,14 @@ pub struct Repositories {
     pub realm_security_headers_repo: Arc<dyn RealmSecurityHeadersRepository>,
     pub passkey_credential_repo: Arc<dyn PasskeyCredentialRepository>,
     pub passkey_challenge_repo: Arc<dyn PasskeyChallengeRepository>,
+    pub totp_credential_repo: Arc<dyn TotpCredentialRepository>,
+    pub consent_repo: Arc<dyn ConsentRepository>,
+    pub password_policy_repo: Arc<dyn PasswordPolicyRepository>,
+    pub password_history_repo: Arc<dyn PasswordHistoryRepository>,
     pub recovery_attempt_repo: Arc<dyn RecoveryAttemptRepository>,
     pub login_attempt_repo: Arc<dyn LoginAttemptRepository>,
     pub session_repo: Arc<dyn SessionRepository>,
+    pub signing_key_repo: Arc<dyn SigningKeyRepository>,
     pub flow_repo: Arc<dyn FlowRepository>,
     pub oidc_repo: Arc<dyn OidcRepository>,
     pub flow_store: Arc<dyn FlowStore>,
@@ -93,6 +123,12 @@ pub struct Repositories {
     pub federated_identity_repo: Arc<dyn FederatedIdentityRepository>,
     pub oauth_broker_state_repo: Arc<dyn OAuthBrokerStateRepository>,
     pub oauth_start_attempt_repo: Arc<dyn OAuthStartAttemptRepository>,
+    pub device_authorization_repo: Arc<dyn DeviceAuthorizationRepository>,
+    pub pushed_authorization_request_repo: Arc<dyn PushedAuthorizationRequestRepository>,
+    pub protocol_mapper_repo: Arc<dyn ProtocolMapperRepository>,
+    pub client_registration_repo: Arc<dyn ClientRegistrationRepository>,
+    pub scim_repo: Arc<dyn ScimRepository>,
+    pub saml_service_provider_repo: Arc<dyn SamlServiceProviderRepository>,
     pub harbor_job_repo: Arc<dyn HarborJobRepository>,
     pub harbor_job_conflict_repo: Arc<dyn HarborJobConflictRepository>,
     pub invitation_repo: Arc<dyn InvitationRepository>,
@@ -109,6 +145,7 @@ pub fn initialize_repositories(db_pool: &Database) -> Repositories {
     // to enforce the hexagonal architecture.
     let user_repo = Arc::new(SqliteUserRepository::new(db_pool.clone()));
     let user_email_repo = Arc::new(SqliteUserEmailRepository::new(db_pool.clone()));
+    let user_federation_repo = Arc::new(SqliteUserFederationRepository::new(db_pool.clone()));
     let user_phone_number_repo = Arc::new(SqliteUserPhoneNumberRepository::new(db_pool.clone()));
     let rbac_repo = Arc::new(SqliteRbacRepository::new(db_pool.clone()));
     let realm_repo = Arc::new(SqliteRealmRepository::new(db_pool.clone()));
@@ -123,9 +160,14 @@ pub fn initialize_repositories(db_pool: &Database) -> Repositories {
         Arc::new(SqliteRealmSecurityHeadersRepository::new(db_pool.clone()));
     let passkey_credential_repo = Arc::new(SqlitePasskeyCredentialRepository::new(db_pool.clone()));
     let passkey_challenge_repo = Arc::new(SqlitePasskeyChallengeRepository::new(db_pool.clone()));
+    let totp_credential_repo = Arc::new(SqliteTotpCredentialRepository::new(db_pool.clone()));
+    let consent_repo = Arc::new(SqliteConsentRepository::new(db_pool.clone()));
+    let password_policy_repo = Arc::new(SqlitePasswordPolicyRepository::new(db_pool.clone()));
+    let password_history_repo = Arc::new(SqlitePasswordHistoryRepository::new(db_pool.clone()));
     let recovery_attempt_repo = Arc::new(SqliteRecoveryAttemptRepository::new(db_pool.clone()));
     let login_attempt_repo = Arc::new(SqliteLoginAttemptRepository::new(db_pool.clone()));
     let session_repo = Arc::new(SqliteSessionRepository::new(db_pool.clone()));
+    let signing_key_repo = Arc::new(SqliteSigningKeyRepository::new(db_pool.clone()));
     let flow_repo = Arc::new(SqliteFlowRepository::new(db_pool.clone()));
     let oidc_repo = Arc::new(SqliteOidcRepository::new(db_pool.clone()));
     let flow_store = Arc::new(SqliteFlowStore::new(db_pool.clone()));
@@ -134,6 +176,17 @@ pub fn initialize_repositories(db_pool: &Database) -> Repositories {
     let oauth_broker_state_repo = Arc::new(SqliteOAuthBrokerStateRepository::new(db_pool.clone()));
     let oauth_start_attempt_repo =
         Arc::new(SqliteOAuthStartAttemptRepository::new(db_pool.clone()));
+    let device_authorization_repo =
+        Arc::new(SqliteDeviceAuthorizationRepository::new(db_pool.clone()));
+    let pushed_authorization_request_repo = Arc::new(
+        SqlitePushedAuthorizationRequestRepository::new(db_pool.clone()),
+    );
+    let protocol_mapper_repo = Arc::new(SqliteProtocolMapperRepository::new(db_pool.clone()));
+    let client_registration_repo =
+        Arc::new(SqliteClientRegistrationRepository::new(db_pool.clone()));
+    let scim_repo = Arc::new(SqliteScimRepository::new(db_pool.clone()));
+    let saml_service_provider_repo =
+        Arc::new(SqliteSamlServiceProviderRepository::new(db_pool.clone()));
     let harbor_job_repo = Arc::new(SqliteHarborJobRepository::new(db_pool.clone()));
     let harbor_job_conflict_repo =
         Arc::new(SqliteHarborJobConflictRepository::new(db_pool.clone()));
@@ -149,6 +202,7 @@ pub fn initialize_repositories(db_pool: &Database) -> Repositories {
     Repositories {
         user_repo,
         user_email_repo,
+        user_federation_repo,
         user_phone_number_repo,
         rbac_repo,
         realm_repo,
@@ -159,9 +213,14 @@ pub fn initialize_repositories(db_pool: &Database) -> Repositories {
         realm_security_headers_repo,
         passkey_credential_repo,
         passkey_challenge_repo,
+        totp_credential_repo,
+        consent_repo,
+        password_policy_repo,
+        password_history_repo,
         recovery_attempt_repo,
         login_attempt_repo,
         session_repo,
+        signing_key_repo,
         flow_repo,
         oidc_repo,
         flow_store,
@@ -169,6 +228,12 @@ pub fn initialize_repositories(db_pool: &Database) -> Repositories {
         federated_identity_repo,
         oauth_broker_state_repo,
         oauth_start_attempt_repo,
+        device_authorization_repo,
+        pushed_authorization_request_repo,
+        protocol_mapper_repo,
+        client_registration_repo,
+        scim_repo,
+        saml_service_provider_repo,
         harbor_job_repo,
         harbor_job_conflict_repo,
         invitation_repo,
@@ -486,6 +486,7 @@ fn build_realm_flow_payload(flow_ids: &HashMap<String, Uuid>) -> UpdateRealmPayl
         access_token_ttl_secs: None,
         refresh_token_ttl_secs: None,
         pkce_required_public_clients: None,
+        signing_algorithm: None,
         lockout_threshold: None,
         lockout_duration_secs: None,
         registration_enabled: None,
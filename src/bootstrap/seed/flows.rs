@@ -57,6 +57,7 @@ pub async fn ensure_default_flows(
         access_token_ttl_secs: None,
         refresh_token_ttl_secs: None,
         pkce_required_public_clients: None,
+        signing_algorithm: None,
         lockout_threshold: None,
         lockout_duration_secs: None,
         registration_enabled: None,
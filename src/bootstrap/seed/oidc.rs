@@ -1,5 +1,5 @@
 use crate::bootstrap::seed::context::SeedContext;
-use crate::domain::oidc::OidcClient;
+use crate::domain::oidc::{OidcClient, TokenEndpointAuthMethod};
 use rand::distr::{Alphanumeric, SampleString};
 use tracing::info;
 use uuid::Uuid;
@@ -60,6 +60,14 @@ pub async fn seed_default_oidc_client(ctx: &SeedContext<'_>, realm_id: Uuid) ->
                 scopes: desired_scopes,
                 web_origins: desired_web_origins,
                 managed_by_config: true,
+                token_endpoint_auth_method: TokenEndpointAuthMethod::None,
+                jwks: None,
+                signing_algorithm: None,
+                backchannel_logout_uri: None,
+                frontchannel_logout_uri: None,
+                token_exchange_policy: None,
+                direct_grant_enabled: false,
+                require_pushed_authorization_requests: false,
             };
 
             let _ = ctx.oidc_service.register_client(&mut client).await?;
@@ -1,37 +1,52 @@
 use crate::adapters::auth::{register_builtins, BuiltinAuthContext};
 use crate::application::audit_service::AuditService;
+use crate::application::claims_service::ClaimsService;
+use crate::application::client_registration_service::ClientRegistrationService;
+use crate::application::consent_service::ConsentService;
 use crate::application::email_delivery_service::EmailDeliveryService;
 use crate::application::flow_executor::FlowExecutor;
 use crate::application::flow_manager::FlowManager;
 use crate::application::flow_service::FlowService;
 use crate::application::harbor::client_provider::ClientHarborProvider;
 use crate::application::harbor::flow_provider::FlowHarborProvider;
+use crate::application::harbor::group_provider::GroupHarborProvider;
+use crate::application::harbor::identity_provider_provider::IdentityProviderHarborProvider;
 use crate::application::harbor::provider::HarborRegistry;
 use crate::application::harbor::realm_provider::RealmHarborProvider;
+use crate::application::harbor::realm_settings_provider::RealmSettingsHarborProvider;
 use crate::application::harbor::role_provider::RoleHarborProvider;
 use crate::application::harbor::runner::TokioHarborJobRunner;
 use crate::application::harbor::service::HarborService;
 use crate::application::harbor::theme_provider::ThemeHarborProvider;
 use crate::application::harbor::user_provider::UserHarborProvider;
+use crate::application::harbor::webhook_provider::WebhookHarborProvider;
 use crate::application::idp_service::IdentityProviderService;
 use crate::application::invitation_service::InvitationService;
+use crate::application::logout_service::LogoutService;
 use crate::application::node_registry::NodeRegistryService;
 use crate::application::oauth_broker_service::OAuthBrokerService;
 use crate::application::oidc_service::OidcService;
 use crate::application::passkey_analytics_service::PasskeyAnalyticsService;
 use crate::application::passkey_assertion_service::PasskeyAssertionService;
+use crate::application::password_policy_service::{BreachedPasswordList, PasswordPolicyService};
 use crate::application::realm_email_settings_service::RealmEmailSettingsService;
 use crate::application::realm_idp_settings_service::RealmIdpSettingsService;
 use crate::application::realm_passkey_settings_service::RealmPasskeySettingsService;
 use crate::application::realm_recovery_settings_service::RealmRecoverySettingsService;
 use crate::application::realm_security_headers_service::RealmSecurityHeadersService;
 use crate::application::runtime_registry::RuntimeRegistry;
+use crate::application::saml_service::SamlService;
+use crate::application::scim_service::ScimService;
 use crate::application::secret_service::SecretService;
+use crate::application::signing_key_service::SigningKeyService;
+use crate::application::sms_otp_service::SmsOtpService;
 use crate::application::theme_service::ThemeResolverService;
+use crate::application::totp_service::TotpService;
 use crate::application::user_credentials_service::{
     UserCredentialsRepositories, UserCredentialsService,
 };
 use crate::application::user_email_service::UserEmailService;
+use crate::application::user_federation_service::{DirectoryUserStore, UserFederationService};
 use crate::application::user_phone_number_service::UserPhoneNumberService;
 use crate::application::webhook_service::WebhookService;
 use crate::ports::transaction_manager::TransactionManager;
@@ -54,6 +69,7 @@ pub struct Services {
     pub user_email_service: Arc<UserEmailService>,
     pub user_phone_number_service: Arc<UserPhoneNumberService>,
     pub user_credentials_service: Arc<UserCredentialsService>,
+    pub consent_service: Arc<ConsentService>,
     pub rbac_service: Arc<RbacService>,
     pub realm_service: Arc<RealmService>,
     pub realm_email_settings_service: Arc<RealmEmailSettingsService>,
@@ -61,17 +77,25 @@ pub struct Services {
     pub realm_passkey_settings_service: Arc<RealmPasskeySettingsService>,
     pub realm_recovery_settings_service: Arc<RealmRecoverySettingsService>,
     pub realm_security_headers_service: Arc<RealmSecurityHeadersService>,
+    pub password_policy_service: Arc<PasswordPolicyService>,
     pub passkey_assertion_service: Arc<PasskeyAssertionService>,
     pub passkey_analytics_service: Arc<PasskeyAnalyticsService>,
     pub email_delivery_service: Arc<EmailDeliveryService>,
     pub invitation_service: Arc<InvitationService>,
     pub identity_provider_service: Arc<IdentityProviderService>,
+    pub user_federation_service: Arc<UserFederationService>,
     pub auth_service: Arc<AuthService>,
+    pub claims_service: Arc<ClaimsService>,
+    pub logout_service: Arc<LogoutService>,
     pub audit_service: Arc<AuditService>,
     pub webhook_service: Arc<WebhookService>,
     pub theme_service: Arc<ThemeResolverService>,
     pub harbor_service: Arc<HarborService>,
     pub oidc_service: Arc<OidcService>,
+    pub client_registration_service: Arc<ClientRegistrationService>,
+    pub scim_service: Arc<ScimService>,
+    pub saml_service: Arc<SamlService>,
+    pub signing_key_service: Arc<SigningKeyService>,
     pub oauth_broker_service: Arc<OAuthBrokerService>,
     pub flow_service: Arc<FlowService>,
     pub flow_manager: Arc<FlowManager>,
@@ -82,17 +106,26 @@ pub struct Services {
 use crate::ports::telemetry_repository::TelemetryRepository;
 
 use crate::ports::http_client::HttpDeliveryClient;
+use crate::ports::ldap_client::LdapConnector;
+use crate::ports::sms_sender::SmsSender;
 
 pub struct ServiceInitContext<'a> {
     pub settings: &'a Settings,
     pub repos: &'a Repositories,
     pub cache: &'a Arc<MokaCacheService>,
     pub event_publisher: Arc<dyn EventPublisher>,
+    /// Publishes authentication and security events straight to the outbox;
+    /// they record facts that already happened, so no transaction applies.
+    pub security_event_publisher: Arc<dyn EventPublisher>,
     pub outbox_repo: Arc<dyn OutboxRepository>,
     pub token_service: &'a Arc<JwtService>,
+    pub secret_service: Arc<SecretService>,
+    pub signing_key_service: Arc<SigningKeyService>,
     pub telemetry_repo: Arc<dyn TelemetryRepository>,
     pub tx_manager: &'a Arc<dyn TransactionManager>,
     pub http_client: Arc<dyn HttpDeliveryClient>,
+    pub ldap_connector: Arc<dyn LdapConnector>,
+    pub sms_sender: Arc<dyn SmsSender>,
 }
 
 pub fn initialize_services(ctx: ServiceInitContext<'_>) -> Services {
@@ -101,19 +134,36 @@ pub fn initialize_services(ctx: ServiceInitContext<'_>) -> Services {
         repos,
         cache,
         event_publisher,
+        security_event_publisher,
         outbox_repo,
         token_service,
+        secret_service,
+        signing_key_service,
         telemetry_repo,
         tx_manager,
         http_client,
+        sms_sender,
+        ldap_connector,
     } = ctx;
     // 1. Foundation Services
+    let password_policy_service = Arc::new(PasswordPolicyService::new(
+        repos.realm_repo.clone(),
+        repos.password_policy_repo.clone(),
+        repos.password_history_repo.clone(),
+        load_breached_passwords(&settings.security.breached_passwords_file),
+    ));
     let user_service = Arc::new(UserService::new(
         repos.user_repo.clone(),
         repos.user_email_repo.clone(),
         event_publisher.clone(),
         outbox_repo.clone(),
         tx_manager.clone(),
+        password_policy_service.clone(),
+        Arc::new(DirectoryUserStore::new(
+            repos.user_federation_repo.clone(),
+            ldap_connector.clone(),
+            secret_service.clone(),
+        )),
     ));
     let user_email_service = Arc::new(UserEmailService::new(
         repos.user_email_repo.clone(),
@@ -122,8 +172,16 @@ pub fn initialize_services(ctx: ServiceInitContext<'_>) -> Services {
     let user_phone_number_service = Arc::new(UserPhoneNumberService::new(
         repos.user_phone_number_repo.clone(),
         tx_manager.clone(),
+        security_event_publisher.clone(),
     ));
     let audit_service = Arc::new(AuditService::new(repos.audit_repo.clone()));
+    let logout_service = Arc::new(LogoutService::new(
+        repos.session_repo.clone(),
+        repos.oidc_repo.clone(),
+        outbox_repo.clone(),
+        security_event_publisher.clone(),
+        settings.auth.issuer.clone(),
+    ));
     let user_credentials_service = Arc::new(UserCredentialsService::new(
         user_service.clone(),
         UserCredentialsRepositories {
@@ -132,9 +190,16 @@ pub fn initialize_services(ctx: ServiceInitContext<'_>) -> Services {
             realm_repo: repos.realm_repo.clone(),
             federated_identity_repo: repos.federated_identity_repo.clone(),
             identity_provider_repo: repos.identity_provider_repo.clone(),
-            session_repo: repos.session_repo.clone(),
+            totp_credential_repo: repos.totp_credential_repo.clone(),
         },
         audit_service.clone(),
+        logout_service.clone(),
+        security_event_publisher.clone(),
+    ));
+    let consent_service = Arc::new(ConsentService::new(
+        repos.consent_repo.clone(),
+        logout_service.clone(),
+        audit_service.clone(),
     ));
     let webhook_service = Arc::new(WebhookService::new(
         repos.webhook_repo.clone(),
@@ -153,6 +218,14 @@ pub fn initialize_services(ctx: ServiceInitContext<'_>) -> Services {
         outbox_repo.clone(),
         tx_manager.clone(),
     ));
+    let user_federation_service = Arc::new(UserFederationService::new(
+        repos.user_federation_repo.clone(),
+        ldap_connector,
+        secret_service.clone(),
+        user_service.clone(),
+        user_email_service.clone(),
+        rbac_service.clone(),
+    ));
     let flow_service = Arc::new(FlowService::new(repos.flow_repo.clone()));
 
     let realm_service = Arc::new(RealmService::new(
@@ -196,6 +269,14 @@ pub fn initialize_services(ctx: ServiceInitContext<'_>) -> Services {
         settings.clone(),
     ));
 
+    let claims_service = Arc::new(ClaimsService::new(
+        repos.oidc_repo.clone(),
+        repos.protocol_mapper_repo.clone(),
+        repos.user_email_repo.clone(),
+        repos.user_phone_number_repo.clone(),
+        repos.rbac_repo.clone(),
+    ));
+
     let auth_service = Arc::new(AuthService::new(
         repos.user_repo.clone(),
         repos.realm_repo.clone(),
@@ -204,10 +285,11 @@ pub fn initialize_services(ctx: ServiceInitContext<'_>) -> Services {
         rbac_service.clone(),
         settings.auth.clone(),
         settings.security.clone(),
+        logout_service.clone(),
+        claims_service.clone(),
+        security_event_publisher.clone(),
     ));
 
-    let secret_service = Arc::new(SecretService::from_settings(settings));
-
     let identity_provider_service = Arc::new(IdentityProviderService::new(
         repos.identity_provider_repo.clone(),
         repos.federated_identity_repo.clone(),
@@ -228,7 +310,9 @@ pub fn initialize_services(ctx: ServiceInitContext<'_>) -> Services {
         repos.user_repo.clone(),
         repos.user_email_repo.clone(),
         audit_service.clone(),
+        security_event_publisher.clone(),
         secret_service.clone(),
+        signing_key_service.clone(),
         http_client.clone(),
         settings.server.public_url.clone(),
     ));
@@ -241,6 +325,7 @@ pub fn initialize_services(ctx: ServiceInitContext<'_>) -> Services {
         repos.passkey_credential_repo.clone(),
         repos.realm_passkey_settings_repo.clone(),
         audit_service.clone(),
+        security_event_publisher.clone(),
         settings.clone(),
     ));
 
@@ -250,6 +335,17 @@ pub fn initialize_services(ctx: ServiceInitContext<'_>) -> Services {
         repos.passkey_credential_repo.clone(),
         repos.passkey_challenge_repo.clone(),
     ));
+    let totp_service = Arc::new(TotpService::new(
+        repos.totp_credential_repo.clone(),
+        secret_service.clone(),
+        security_event_publisher.clone(),
+    ));
+    let sms_otp_service = Arc::new(SmsOtpService::new(
+        sms_sender,
+        repos.user_phone_number_repo.clone(),
+        repos.realm_repo.clone(),
+        security_event_publisher.clone(),
+    ));
     // 2. Runtime Registry (The Brain)
     let mut registry_impl = RuntimeRegistry::new();
 
@@ -266,14 +362,21 @@ pub fn initialize_services(ctx: ServiceInitContext<'_>) -> Services {
             lockout_threshold: settings.auth.lockout_threshold,
             lockout_duration_secs: settings.auth.lockout_duration_secs,
             session_repo: repos.session_repo.clone(),
+            logout_service: logout_service.clone(),
             flow_store: repos.flow_store.clone(),
             action_repo: repos.auth_session_action_repo.clone(),
             recovery_attempt_repo: repos.recovery_attempt_repo.clone(),
             audit_service: audit_service.clone(),
+            event_publisher: security_event_publisher.clone(),
             recovery_settings_repo: repos.realm_recovery_settings_repo.clone(),
             passkey_settings_repo: repos.realm_passkey_settings_repo.clone(),
             identity_provider_service: identity_provider_service.clone(),
             oauth_broker_service: oauth_broker_service.clone(),
+            user_federation_service: user_federation_service.clone(),
+            totp_service,
+            sms_otp_service,
+            password_policy_service: password_policy_service.clone(),
+            consent_service: consent_service.clone(),
         },
     );
 
@@ -287,10 +390,12 @@ pub fn initialize_services(ctx: ServiceInitContext<'_>) -> Services {
     let flow_executor = Arc::new(FlowExecutor::new(
         repos.auth_session_repo.clone(),
         repos.flow_store.clone(),
+        repos.flow_repo.clone(),
         runtime_registry.clone(),
         repos.auth_session_action_repo.clone(),
         Some(email_delivery_service.clone()),
         Some(audit_service.clone()),
+        Some(security_event_publisher),
     ));
 
     let publish_validator = Arc::new(
@@ -331,6 +436,45 @@ pub fn initialize_services(ctx: ServiceInitContext<'_>) -> Services {
         repos.auth_session_repo.clone(),
         repos.flow_store.clone(),
         repos.realm_repo.clone(),
+        rbac_service.clone(),
+        repos.session_repo.clone(),
+        audit_service.clone(),
+        repos.device_authorization_repo.clone(),
+        repos.pushed_authorization_request_repo.clone(),
+        claims_service.clone(),
+        flow_executor.clone(),
+        logout_service.clone(),
+    ));
+
+    let client_registration_service = Arc::new(ClientRegistrationService::new(
+        repos.realm_repo.clone(),
+        repos.client_registration_repo.clone(),
+        oidc_service.clone(),
+        audit_service.clone(),
+    ));
+
+    let scim_service = Arc::new(ScimService::new(
+        token_service.clone(),
+        oidc_service.clone(),
+        user_service.clone(),
+        user_email_service.clone(),
+        rbac_service.clone(),
+        repos.scim_repo.clone(),
+    ));
+
+    let saml_service = Arc::new(SamlService::new(
+        repos.saml_service_provider_repo.clone(),
+        repos.realm_repo.clone(),
+        repos.user_repo.clone(),
+        repos.user_email_repo.clone(),
+        repos.auth_session_repo.clone(),
+        repos.flow_store.clone(),
+        repos.session_repo.clone(),
+        rbac_service.clone(),
+        signing_key_service.clone(),
+        logout_service.clone(),
+        audit_service.clone(),
+        settings.server.public_url.clone(),
     ));
 
     let mut harbor_registry = HarborRegistry::new();
@@ -350,6 +494,27 @@ pub fn initialize_services(ctx: ServiceInitContext<'_>) -> Services {
         repos.rbac_repo.clone(),
         oidc_service.clone(),
     )));
+    harbor_registry.register(Arc::new(GroupHarborProvider::new(
+        repos.rbac_repo.clone(),
+        repos.user_repo.clone(),
+        oidc_service.clone(),
+    )));
+    harbor_registry.register(Arc::new(IdentityProviderHarborProvider::new(
+        repos.identity_provider_repo.clone(),
+        repos.federated_identity_repo.clone(),
+        secret_service.clone(),
+    )));
+    harbor_registry.register(Arc::new(WebhookHarborProvider::new(
+        repos.webhook_repo.clone(),
+    )));
+    harbor_registry.register(Arc::new(RealmSettingsHarborProvider::new(
+        realm_email_settings_service.clone(),
+        realm_passkey_settings_service.clone(),
+        realm_recovery_settings_service.clone(),
+        realm_security_headers_service.clone(),
+        realm_idp_settings_service.clone(),
+        password_policy_service.clone(),
+    )));
     let harbor_job_runner = Arc::new(TokioHarborJobRunner);
     let harbor_service = Arc::new(HarborService::new(
         harbor_registry,
@@ -359,6 +524,8 @@ pub fn initialize_services(ctx: ServiceInitContext<'_>) -> Services {
         flow_manager.clone(),
         rbac_service.clone(),
         user_service.clone(),
+        identity_provider_service.clone(),
+        webhook_service.clone(),
         tx_manager.clone(),
         repos.harbor_job_repo.clone(),
         repos.harbor_job_conflict_repo.clone(),
@@ -370,6 +537,7 @@ pub fn initialize_services(ctx: ServiceInitContext<'_>) -> Services {
         user_email_service,
         user_phone_number_service,
         user_credentials_service,
+        consent_service,
         rbac_service,
         realm_service,
         realm_email_settings_service,
@@ -377,17 +545,25 @@ pub fn initialize_services(ctx: ServiceInitContext<'_>) -> Services {
         realm_passkey_settings_service,
         realm_recovery_settings_service,
         realm_security_headers_service,
+        password_policy_service,
         passkey_assertion_service,
         passkey_analytics_service,
         email_delivery_service,
         invitation_service,
         identity_provider_service,
+        user_federation_service,
         auth_service,
+        claims_service,
+        logout_service,
         audit_service,
         webhook_service,
         theme_service,
         harbor_service,
         oidc_service,
+        client_registration_service,
+        scim_service,
+        saml_service,
+        signing_key_service,
         oauth_broker_service,
         flow_service,
         flow_manager,
@@ -395,3 +571,26 @@ pub fn initialize_services(ctx: ServiceInitContext<'_>) -> Services {
         flow_executor,
     }
 }
+
+/// A missing or unreadable list is logged and treated as empty, so realms
+/// cannot turn `check_breached` on until it is fixed.
+fn load_breached_passwords(path: &str) -> BreachedPasswordList {
+    let path = path.trim();
+    if path.is_empty() {
+        return BreachedPasswordList::default();
+    }
+    match BreachedPasswordList::from_file(std::path::Path::new(path)) {
+        Ok(list) => {
+            tracing::info!(
+                "Loaded {} breached password entries from {}",
+                list.len(),
+                path
+            );
+            list
+        }
+        Err(err) => {
+            tracing::error!("Failed to read breached password list {}: {}", path, err);
+            BreachedPasswordList::default()
+        }
+    }
+}
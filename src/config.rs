@@ -79,7 +79,6 @@ pub struct HarborConfig {
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct AuthConfig {
     pub jwt_secret: String,
-    pub jwt_key_id: String,
     #[serde(default)]
     pub issuer: String,
     pub access_token_ttl_secs: i64,
@@ -102,11 +101,40 @@ pub struct AuthConfig {
     pub oauth_broker_state_cleanup_interval_secs: u64,
     #[serde(default = "default_oauth_broker_state_cleanup_batch_size")]
     pub oauth_broker_state_cleanup_batch_size: i64,
+    /// Expired, unredeemed device authorizations (RFC 8628) are deleted on
+    /// this interval. 0 disables cleanup.
+    #[serde(default = "default_device_code_cleanup_interval_secs")]
+    pub device_code_cleanup_interval_secs: u64,
+    #[serde(default = "default_device_code_cleanup_batch_size")]
+    pub device_code_cleanup_batch_size: i64,
+    /// Expired, unredeemed pushed authorization requests (RFC 9126) are
+    /// deleted on this interval. 0 disables cleanup.
+    #[serde(default = "default_par_request_cleanup_interval_secs")]
+    pub par_request_cleanup_interval_secs: u64,
+    #[serde(default = "default_par_request_cleanup_batch_size")]
+    pub par_request_cleanup_batch_size: i64,
+    /// Age after which a realm's active signing key is rotated out. 0 disables
+    /// scheduled rotation (keys can still be rotated through the admin API).
+    #[serde(default = "default_signing_key_rotation_interval_secs")]
+    pub signing_key_rotation_interval_secs: u64,
+    /// How long a rotated-out key keeps verifying tokens before it is retired.
+    #[serde(default = "default_signing_key_retention_secs")]
+    pub signing_key_retention_secs: u64,
+    #[serde(default = "default_signing_key_rotation_check_interval_secs")]
+    pub signing_key_rotation_check_interval_secs: u64,
+    /// How often user federation providers are checked for a due scheduled
+    /// sync. Each provider has its own interval. 0 disables scheduled sync.
+    #[serde(default = "default_user_federation_sync_check_interval_secs")]
+    pub user_federation_sync_check_interval_secs: u64,
     /// When true, logging in revokes the user's existing sessions for the same
     /// client, enforcing a single active session per (user, client). When false
     /// (default), concurrent sessions are allowed (e.g. multiple browsers).
     #[serde(default)]
     pub single_session_per_client: bool,
+    /// Lifetime of a session an admin starts as another user. It is not
+    /// extended by refreshing.
+    #[serde(default = "default_impersonation_session_ttl_secs")]
+    pub impersonation_session_ttl_secs: i64,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -140,6 +168,49 @@ pub struct SecurityConfig {
     /// silent refresh.
     #[serde(default)]
     pub immediate_step_up_invalidation: bool,
+    /// Offline breached password list used by realms whose password policy
+    /// has `check_breached` set. One entry per line: a plaintext password or
+    /// an uppercase SHA-1 hex digest (HIBP `HASH:count` lines are accepted).
+    /// Read once at startup; empty disables the check.
+    #[serde(default)]
+    pub breached_passwords_file: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SmsConfig {
+    /// `log` appends messages to `log_file` (development only); `http` posts
+    /// them to a generic SMS gateway.
+    #[serde(default = "default_sms_provider")]
+    pub provider: String,
+    #[serde(default)]
+    pub log_file: String,
+    #[serde(default)]
+    pub gateway_url: String,
+    #[serde(default = "default_sms_gateway_method")]
+    pub gateway_method: String,
+    /// Sent verbatim as the `Authorization` header when set.
+    #[serde(default)]
+    pub gateway_authorization: String,
+    /// Request body with `{to}`, `{message}` and `{realm}` placeholders.
+    /// Values are JSON-escaped before substitution.
+    #[serde(default = "default_sms_gateway_body_template")]
+    pub gateway_body_template: String,
+    #[serde(default = "default_sms_gateway_content_type")]
+    pub gateway_content_type: String,
+}
+
+impl Default for SmsConfig {
+    fn default() -> Self {
+        Self {
+            provider: default_sms_provider(),
+            log_file: String::new(),
+            gateway_url: String::new(),
+            gateway_method: default_sms_gateway_method(),
+            gateway_authorization: String::new(),
+            gateway_body_template: default_sms_gateway_body_template(),
+            gateway_content_type: default_sms_gateway_content_type(),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -161,6 +232,8 @@ pub struct Settings {
     pub harbor: HarborConfig,
     #[serde(default)]
     pub security: SecurityConfig,
+    #[serde(default)]
+    pub sms: SmsConfig,
 }
 
 impl Settings {
@@ -228,6 +301,7 @@ impl Settings {
         self.apply_observability_defaults();
         self.apply_harbor_defaults();
         self.apply_theme_defaults();
+        self.apply_sms_defaults();
     }
 
     fn apply_database_defaults(&mut self) {
@@ -273,6 +347,23 @@ impl Settings {
         self.harbor.storage_dir = path.to_string_lossy().to_string();
     }
 
+    fn apply_sms_defaults(&mut self) {
+        self.sms.provider = self.sms.provider.trim().to_ascii_lowercase();
+        if !self.sms.log_file.trim().is_empty() {
+            return;
+        }
+
+        let data_dir = self.database.data_dir.trim();
+        let base_dir = if data_dir.is_empty() {
+            "./data"
+        } else {
+            data_dir
+        };
+
+        let path = Path::new(base_dir).join("sms_outbox.log");
+        self.sms.log_file = path.to_string_lossy().to_string();
+    }
+
     fn apply_theme_defaults(&mut self) {
         self.theme.default_theme_name = self.theme.default_theme_name.trim().to_string();
         self.theme.default_binding_name = self.theme.default_binding_name.trim().to_string();
@@ -321,6 +412,21 @@ impl Settings {
             self.auth.oauth_broker_state_cleanup_interval_secs,
             self.auth.oauth_broker_state_cleanup_batch_size,
         )?;
+        validate_device_code_cleanup_settings(
+            self.auth.device_code_cleanup_interval_secs,
+            self.auth.device_code_cleanup_batch_size,
+        )?;
+        validate_par_request_cleanup_settings(
+            self.auth.par_request_cleanup_interval_secs,
+            self.auth.par_request_cleanup_batch_size,
+        )?;
+        validate_signing_key_rotation_settings(
+            self.auth.signing_key_rotation_interval_secs,
+            self.auth.signing_key_retention_secs,
+            self.auth.access_token_ttl_secs,
+        )?;
+        validate_impersonation_settings(self.auth.impersonation_session_ttl_secs)?;
+        validate_sms_settings(&self.sms)?;
 
         Ok(())
     }
@@ -329,6 +435,9 @@ impl Settings {
         let mut redacted = self.clone();
         redacted.auth.jwt_secret = "<redacted>".to_string();
         redacted.default_admin.password = "<redacted>".to_string();
+        if !redacted.sms.gateway_authorization.is_empty() {
+            redacted.sms.gateway_authorization = "<redacted>".to_string();
+        }
         redacted
     }
 
@@ -416,6 +525,22 @@ fn default_harbor_artifact_retention_hours() -> u64 {
     168
 }
 
+fn default_sms_provider() -> String {
+    "log".to_string()
+}
+
+fn default_sms_gateway_method() -> String {
+    "POST".to_string()
+}
+
+fn default_sms_gateway_body_template() -> String {
+    r#"{"to":"{to}","message":"{message}"}"#.to_string()
+}
+
+fn default_sms_gateway_content_type() -> String {
+    "application/json".to_string()
+}
+
 fn default_pkce_required_public_clients() -> bool {
     true
 }
@@ -452,6 +577,42 @@ fn default_oauth_broker_state_cleanup_batch_size() -> i64 {
     500
 }
 
+fn default_device_code_cleanup_interval_secs() -> u64 {
+    300
+}
+
+fn default_device_code_cleanup_batch_size() -> i64 {
+    500
+}
+
+fn default_par_request_cleanup_interval_secs() -> u64 {
+    300
+}
+
+fn default_par_request_cleanup_batch_size() -> i64 {
+    500
+}
+
+fn default_signing_key_rotation_interval_secs() -> u64 {
+    90 * 86_400
+}
+
+fn default_signing_key_retention_secs() -> u64 {
+    7 * 86_400
+}
+
+fn default_signing_key_rotation_check_interval_secs() -> u64 {
+    3600
+}
+
+fn default_user_federation_sync_check_interval_secs() -> u64 {
+    60
+}
+
+fn default_impersonation_session_ttl_secs() -> i64 {
+    900
+}
+
 fn default_data_dir() -> String {
     env::current_exe()
         .ok()
@@ -627,3 +788,86 @@ fn validate_oauth_broker_state_cleanup_settings(
     }
     Ok(())
 }
+
+fn validate_device_code_cleanup_settings(
+    cleanup_interval_secs: u64,
+    batch_size: i64,
+) -> Result<(), config::ConfigError> {
+    if cleanup_interval_secs > 86_400 {
+        return Err(config::ConfigError::Message(
+            "auth.device_code_cleanup_interval_secs must be <= 86400".to_string(),
+        ));
+    }
+    if batch_size < 1 {
+        return Err(config::ConfigError::Message(
+            "auth.device_code_cleanup_batch_size must be >= 1".to_string(),
+        ));
+    }
+    if batch_size > 10_000 {
+        return Err(config::ConfigError::Message(
+            "auth.device_code_cleanup_batch_size must be <= 10000".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_par_request_cleanup_settings(
+    cleanup_interval_secs: u64,
+    batch_size: i64,
+) -> Result<(), config::ConfigError> {
+    if cleanup_interval_secs > 86_400 {
+        return Err(config::ConfigError::Message(
+            "auth.par_request_cleanup_interval_secs must be <= 86400".to_string(),
+        ));
+    }
+    if batch_size < 1 {
+        return Err(config::ConfigError::Message(
+            "auth.par_request_cleanup_batch_size must be >= 1".to_string(),
+        ));
+    }
+    if batch_size > 10_000 {
+        return Err(config::ConfigError::Message(
+            "auth.par_request_cleanup_batch_size must be <= 10000".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_signing_key_rotation_settings(
+    rotation_interval_secs: u64,
+    retention_secs: u64,
+    access_token_ttl_secs: i64,
+) -> Result<(), config::ConfigError> {
+    if rotation_interval_secs > 0 && rotation_interval_secs < 3600 {
+        return Err(config::ConfigError::Message(
+            "auth.signing_key_rotation_interval_secs must be 0 or >= 3600".to_string(),
+        ));
+    }
+    // Retiring a key before its tokens expire would invalidate them early.
+    if (retention_secs as i64) < access_token_ttl_secs {
+        return Err(config::ConfigError::Message(
+            "auth.signing_key_retention_secs must be >= auth.access_token_ttl_secs".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_impersonation_settings(session_ttl_secs: i64) -> Result<(), config::ConfigError> {
+    if !(60..=86_400).contains(&session_ttl_secs) {
+        return Err(config::ConfigError::Message(
+            "auth.impersonation_session_ttl_secs must be between 60 and 86400".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_sms_settings(sms: &SmsConfig) -> Result<(), config::ConfigError> {
+    match sms.provider.as_str() {
+        "log" => Ok(()),
+        "http" => validate_url("sms.gateway_url", &sms.gateway_url),
+        other => Err(config::ConfigError::Message(format!(
+            "sms.provider must be log or http (got {})",
+            other
+        ))),
+    }
+}
@@ -7,6 +7,8 @@ pub struct AuditEvent {
     pub id: Uuid,
     pub realm_id: Uuid,
     pub actor_user_id: Option<Uuid>,
+    /// The admin who was impersonating `actor_user_id`, if any.
+    pub impersonator_user_id: Option<Uuid>,
     pub action: String,
     pub target_type: String,
     pub target_id: Option<String>,
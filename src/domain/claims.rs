@@ -0,0 +1,253 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Claims released by each standard OIDC scope (OIDC Core, Section 5.4).
+pub const STANDARD_SCOPE_CLAIMS: &[(&str, &[&str])] = &[
+    (
+        "profile",
+        &[
+            "name",
+            "family_name",
+            "given_name",
+            "middle_name",
+            "nickname",
+            "preferred_username",
+            "profile",
+            "picture",
+            "website",
+            "gender",
+            "birthdate",
+            "zoneinfo",
+            "locale",
+            "updated_at",
+        ],
+    ),
+    ("email", &["email", "email_verified"]),
+    ("phone", &["phone_number", "phone_number_verified"]),
+    ("address", &["address"]),
+];
+
+/// Claims set by the token service itself, which mappers may not replace.
+pub const RESERVED_CLAIMS: &[&str] = &[
+    "iss",
+    "sub",
+    "aud",
+    "exp",
+    "iat",
+    "nbf",
+    "jti",
+    "sid",
+    "azp",
+    "scope",
+    "perms",
+    "roles",
+    "groups",
+    "act",
+    "nonce",
+    "auth_time",
+    "events",
+];
+
+/// Every claim the standard scopes can release, for discovery.
+pub fn supported_claims() -> Vec<&'static str> {
+    let mut claims = vec!["sub"];
+    for (_, scope_claims) in STANDARD_SCOPE_CLAIMS {
+        claims.extend_from_slice(scope_claims);
+    }
+    claims
+}
+
+/// Claims released by `scope`; unknown scopes release none.
+pub fn scope_claims(scope: &str) -> &'static [&'static str] {
+    STANDARD_SCOPE_CLAIMS
+        .iter()
+        .find(|(name, _)| *name == scope)
+        .map(|(_, claims)| *claims)
+        .unwrap_or(&[])
+}
+
+/// One member of the OIDC `claims` request parameter (OIDC Core, Section
+/// 5.5.1). `null` requests the claim with default behavior.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClaimRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub essential: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub values: Option<Vec<Value>>,
+}
+
+/// The OIDC `claims` request parameter: individual claims asked for in the
+/// ID token and at the userinfo endpoint.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClaimsRequest {
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub userinfo: BTreeMap<String, Option<ClaimRequest>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub id_token: BTreeMap<String, Option<ClaimRequest>>,
+}
+
+impl ClaimsRequest {
+    /// Parses the JSON value of the `claims` parameter, which must be an
+    /// object.
+    pub fn parse(raw: &str) -> Result<Self, serde_json::Error> {
+        let value: Value = serde_json::from_str(raw)?;
+        if !value.is_object() {
+            return Err(serde::de::Error::custom("claims must be a JSON object"));
+        }
+        serde_json::from_value(value)
+    }
+}
+
+/// What a client was granted when the user authorized it. It is kept with the
+/// authorization code and the session, so refreshed tokens and userinfo
+/// release the same claims.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClaimsGrant {
+    pub scope: Option<String>,
+    pub claims: Option<ClaimsRequest>,
+}
+
+impl ClaimsGrant {
+    /// Rebuilds a grant from its stored columns. A stored `claims` value that
+    /// no longer parses is dropped rather than failing the session.
+    pub fn from_stored(scope: Option<String>, claims: Option<&str>) -> Self {
+        Self {
+            scope,
+            claims: claims.and_then(|raw| ClaimsRequest::parse(raw).ok()),
+        }
+    }
+
+    /// The `claims` request as stored alongside the scope.
+    pub fn claims_json(&self) -> Option<String> {
+        self.claims
+            .as_ref()
+            .and_then(|claims| serde_json::to_string(claims).ok())
+    }
+
+    pub fn scopes(&self) -> impl Iterator<Item = &str> {
+        self.scope.as_deref().unwrap_or_default().split_whitespace()
+    }
+}
+
+/// Where a protocol mapper's value comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProtocolMapperKind {
+    /// A dot-separated path into the user's public metadata.
+    UserMetadata { path: String },
+    /// The user's role names, optionally only those starting with `prefix`.
+    RoleList {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        prefix: Option<String>,
+    },
+    /// The full paths (`/parent/child`) of the user's groups.
+    GroupPath,
+}
+
+impl ProtocolMapperKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::UserMetadata { .. } => "user_metadata",
+            Self::RoleList { .. } => "role_list",
+            Self::GroupPath => "group_path",
+        }
+    }
+}
+
+/// Copies one value into a named claim of the selected tokens of a client.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtocolMapper {
+    pub id: Uuid,
+    pub realm_id: Uuid,
+    /// The owning client's row id (not its `client_id`).
+    pub client_id: Uuid,
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: ProtocolMapperKind,
+    pub claim_name: String,
+    pub add_to_access_token: bool,
+    pub add_to_id_token: bool,
+    pub add_to_userinfo: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Extra claims for each token issued to a client, on top of what the token
+/// service always sets.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IssuedClaims {
+    pub access_token: Map<String, Value>,
+    pub id_token: Map<String, Value>,
+    pub userinfo: Map<String, Value>,
+}
+
+/// Looks up a dot-separated path (`address.country`) in a JSON object.
+pub fn json_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .filter(|segment| !segment.is_empty())
+        .try_fold(value, |current, segment| current.get(segment))
+        .filter(|found| !found.is_null())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn claims_request_parses_null_and_detailed_members() {
+        let request = ClaimsRequest::parse(
+            r#"{"userinfo":{"email":null,"given_name":{"essential":true}},"id_token":{"acr":{"values":["1"]}}}"#,
+        )
+        .expect("parse");
+        assert_eq!(request.userinfo.get("email"), Some(&None));
+        assert_eq!(
+            request.userinfo.get("given_name"),
+            Some(&Some(ClaimRequest {
+                essential: Some(true),
+                ..Default::default()
+            }))
+        );
+        assert!(request.id_token.contains_key("acr"));
+        assert!(ClaimsRequest::parse("[]").is_err());
+    }
+
+    #[test]
+    fn scope_claims_cover_standard_scopes_only() {
+        assert!(scope_claims("email").contains(&"email_verified"));
+        assert!(scope_claims("profile").contains(&"preferred_username"));
+        assert!(scope_claims("orders:read").is_empty());
+        assert!(supported_claims().contains(&"address"));
+    }
+
+    #[test]
+    fn json_path_walks_nested_objects() {
+        let metadata = json!({ "org": { "department": "billing", "empty": null } });
+        assert_eq!(
+            json_path(&metadata, "org.department"),
+            Some(&json!("billing"))
+        );
+        assert_eq!(json_path(&metadata, "org.empty"), None);
+        assert_eq!(json_path(&metadata, "org.missing"), None);
+    }
+
+    #[test]
+    fn mapper_kind_serializes_with_type_tag() {
+        let kind: ProtocolMapperKind =
+            serde_json::from_value(json!({ "type": "role_list", "prefix": "app:" })).unwrap();
+        assert_eq!(
+            kind,
+            ProtocolMapperKind::RoleList {
+                prefix: Some("app:".to_string())
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(ProtocolMapperKind::GroupPath).unwrap(),
+            json!({ "type": "group_path" })
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use url::Url;
+use uuid::Uuid;
+
+use crate::domain::oidc::{DEVICE_CODE_GRANT_TYPE, TOKEN_EXCHANGE_GRANT_TYPE};
+
+/// Grant types a dynamically registered client may ask for.
+pub const REGISTRABLE_GRANT_TYPES: &[&str] = &[
+    "authorization_code",
+    "refresh_token",
+    "client_credentials",
+    "password",
+    DEVICE_CODE_GRANT_TYPE,
+    TOKEN_EXCHANGE_GRANT_TYPE,
+];
+
+/// What dynamic client registration (RFC 7591) may create in a realm.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientRegistrationPolicy {
+    pub realm_id: Uuid,
+    /// Hosts redirect URIs may point at. `*.example.com` matches any
+    /// subdomain of `example.com`. An empty list allows any host.
+    pub allowed_redirect_hosts: Vec<String>,
+    /// Requires `https` redirect URIs, except for loopback hosts.
+    pub require_https: bool,
+    /// Grant types registered clients may use.
+    pub allowed_grant_types: Vec<String>,
+}
+
+impl ClientRegistrationPolicy {
+    pub fn defaults(realm_id: Uuid) -> Self {
+        Self {
+            realm_id,
+            allowed_redirect_hosts: Vec::new(),
+            require_https: true,
+            allowed_grant_types: vec![
+                "authorization_code".to_string(),
+                "refresh_token".to_string(),
+            ],
+        }
+    }
+
+    /// Why `uri` may not be registered, if it may not.
+    pub fn check_redirect_uri(&self, uri: &str) -> Option<String> {
+        let Ok(url) = Url::parse(uri) else {
+            return Some(format!("'{}' is not an absolute URL", uri));
+        };
+        if url.fragment().is_some() {
+            return Some(format!("'{}' must not contain a fragment", uri));
+        }
+        let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+        match url.scheme() {
+            "https" => {}
+            "http" if !self.require_https || is_loopback(&host) => {}
+            _ => return Some(format!("'{}' must use https", uri)),
+        }
+        if !self.allowed_redirect_hosts.is_empty()
+            && !self
+                .allowed_redirect_hosts
+                .iter()
+                .any(|pattern| host_matches(pattern, &host))
+        {
+            return Some(format!("Host of '{}' is not allowed", uri));
+        }
+        None
+    }
+
+    pub fn allows_grant_type(&self, grant_type: &str) -> bool {
+        self.allowed_grant_types
+            .iter()
+            .any(|allowed| allowed == grant_type)
+    }
+}
+
+fn is_loopback(host: &str) -> bool {
+    matches!(host, "localhost" | "127.0.0.1" | "[::1]")
+}
+
+fn host_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.trim().to_ascii_lowercase();
+    match pattern.strip_prefix("*.") {
+        Some(parent) => host
+            .strip_suffix(parent)
+            .is_some_and(|prefix| prefix.len() > 1 && prefix.ends_with('.')),
+        None => pattern == host,
+    }
+}
+
+/// A realm-issued bearer token that allows registering clients. Only its
+/// SHA-256 hash is stored.
+#[derive(Debug, Clone, Serialize)]
+pub struct InitialAccessToken {
+    pub id: Uuid,
+    pub realm_id: Uuid,
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    pub description: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    /// Registrations left; the token stops working at 0.
+    pub remaining_uses: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Marks a client as created through dynamic registration and holds what RFC
+/// 7592 management needs: the registration access token hash and the
+/// registered metadata that `OidcClient` does not model.
+#[derive(Debug, Clone)]
+pub struct ClientRegistration {
+    /// The client's row id.
+    pub client_id: Uuid,
+    pub realm_id: Uuid,
+    pub registration_token_hash: String,
+    pub client_name: Option<String>,
+    pub grant_types: Vec<String>,
+    /// The initial access token the client was registered with.
+    pub initial_access_token_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redirect_uris_follow_scheme_and_host_rules() {
+        let mut policy = ClientRegistrationPolicy::defaults(Uuid::new_v4());
+        assert!(policy
+            .check_redirect_uri("https://app.example.com/cb")
+            .is_none());
+        assert!(policy
+            .check_redirect_uri("http://localhost:3000/cb")
+            .is_none());
+        assert!(policy
+            .check_redirect_uri("http://app.example.com/cb")
+            .is_some());
+        assert!(policy
+            .check_redirect_uri("https://app.example.com/cb#x")
+            .is_some());
+        assert!(policy.check_redirect_uri("/relative").is_some());
+
+        policy.allowed_redirect_hosts = vec!["*.preview.example.com".to_string()];
+        assert!(policy
+            .check_redirect_uri("https://pr-12.preview.example.com/cb")
+            .is_none());
+        assert!(policy
+            .check_redirect_uri("https://preview.example.com/cb")
+            .is_some());
+        assert!(policy
+            .check_redirect_uri("https://evilpreview.example.com/cb")
+            .is_some());
+    }
+}
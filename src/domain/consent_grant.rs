@@ -0,0 +1,76 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Scopes a user approved for a client. There is one grant per user and
+/// client; approving more scopes later widens it.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsentGrant {
+    pub id: Uuid,
+    pub realm_id: Uuid,
+    pub user_id: Uuid,
+    pub client_id: String,
+    pub scopes: Vec<String>,
+    pub granted_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ConsentGrant {
+    pub fn new(realm_id: Uuid, user_id: Uuid, client_id: String, scopes: &[String]) -> Self {
+        let now = Utc::now();
+        let mut grant = Self {
+            id: Uuid::new_v4(),
+            realm_id,
+            user_id,
+            client_id,
+            scopes: Vec::new(),
+            granted_at: now,
+            updated_at: now,
+        };
+        grant.extend(scopes);
+        grant
+    }
+
+    /// True when every requested scope was already approved.
+    pub fn covers(&self, requested: &[String]) -> bool {
+        requested.iter().all(|scope| self.scopes.contains(scope))
+    }
+
+    /// Adds newly approved scopes, keeping the list sorted and unique.
+    pub fn extend(&mut self, scopes: &[String]) {
+        for scope in scopes {
+            if !self.scopes.contains(scope) {
+                self.scopes.push(scope.clone());
+            }
+        }
+        self.scopes.sort();
+        self.updated_at = Utc::now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scopes(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn grant_covers_subsets_and_widens_on_extend() {
+        let mut grant = ConsentGrant::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            "app".to_string(),
+            &scopes(&["profile", "openid", "openid"]),
+        );
+        assert_eq!(grant.scopes, scopes(&["openid", "profile"]));
+        assert!(grant.covers(&scopes(&["openid"])));
+        assert!(grant.covers(&[]));
+        assert!(!grant.covers(&scopes(&["openid", "email"])));
+
+        grant.extend(&scopes(&["email"]));
+        assert_eq!(grant.scopes, scopes(&["email", "openid", "profile"]));
+        assert!(grant.covers(&scopes(&["openid", "email"])));
+    }
+}
@@ -29,6 +29,16 @@ pub enum DomainEvent {
     GroupRemoved(UserGroupChanged),
     RoleDeleted(RoleDeleted),
     GroupDeleted(GroupDeleted),
+    LoginSucceeded(LoginSucceeded),
+    LoginFailed(LoginFailed),
+    UserLockedOut(UserLockedOut),
+    PasskeyEnrolled(PasskeyEnrolled),
+    MfaEnrolled(MfaChanged),
+    MfaRemoved(MfaChanged),
+    SessionRevoked(SessionRevoked),
+    PasswordReset(PasswordReset),
+    IdpLinked(IdpLinked),
+    RefreshTokenReuseDetected(RefreshTokenReuseDetected),
 }
 
 pub const EVENT_VERSION_V1: &str = "v1";
@@ -132,6 +142,132 @@ pub struct GroupDeleted {
     pub affected_user_ids: Vec<Uuid>,
 }
 
+// --- Authentication and security events ---
+//
+// These are published after the fact rather than written in the same
+// transaction as a state change, so they carry their realm for the outbox
+// publisher to route them to the realm's webhook subscriptions.
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LoginSucceeded {
+    #[serde(skip)]
+    pub realm_id: Uuid,
+    pub user_id: Uuid,
+    pub username: Option<String>,
+    pub auth_session_id: Uuid,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LoginFailed {
+    #[serde(skip)]
+    pub realm_id: Uuid,
+    /// Known once an earlier step identified the user, e.g. a failed second factor.
+    pub user_id: Option<Uuid>,
+    pub username: Option<String>,
+    pub auth_session_id: Uuid,
+    /// The authenticator that rejected the attempt; `None` when the flow itself denied access.
+    pub authenticator: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct UserLockedOut {
+    #[serde(skip)]
+    pub realm_id: Uuid,
+    /// `None` when the failed attempts named an unknown username.
+    pub user_id: Option<Uuid>,
+    pub username: String,
+    pub locked_until: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PasskeyEnrolled {
+    #[serde(skip)]
+    pub realm_id: Uuid,
+    pub user_id: Uuid,
+    pub credential_id: Uuid,
+    pub friendly_name: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MfaMethod {
+    /// An authenticator app.
+    Totp,
+    /// One-time codes sent to a verified phone number.
+    Sms,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MfaChanged {
+    #[serde(skip)]
+    pub realm_id: Uuid,
+    pub user_id: Uuid,
+    pub method: MfaMethod,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionRevocationReason {
+    /// The user signed out, including RP-initiated and SAML single logout.
+    Logout,
+    /// The session was revoked from the sessions console.
+    Revoked,
+    /// All of the user's sessions ended after a lock, ban or password change.
+    AccountChange,
+    /// The user withdrew consent from the session's client.
+    ConsentWithdrawn,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SessionRevoked {
+    #[serde(skip)]
+    pub realm_id: Uuid,
+    pub user_id: Uuid,
+    pub session_id: Uuid,
+    pub client_id: Option<String>,
+    pub reason: SessionRevocationReason,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PasswordResetSource {
+    /// Completed from a recovery link or code.
+    Recovery,
+    /// Required at sign-in by an administrator or an expired password.
+    ForcedReset,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PasswordReset {
+    #[serde(skip)]
+    pub realm_id: Uuid,
+    pub user_id: Uuid,
+    pub source: PasswordResetSource,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct IdpLinked {
+    #[serde(skip)]
+    pub realm_id: Uuid,
+    pub user_id: Uuid,
+    pub provider_id: Uuid,
+    pub provider_alias: String,
+    pub subject: String,
+    /// `auto_email`, `manual` or `jit`.
+    pub linked_via: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RefreshTokenReuseDetected {
+    #[serde(skip)]
+    pub realm_id: Uuid,
+    pub user_id: Uuid,
+    /// The session family that was revoked in response.
+    pub family_id: Uuid,
+    pub client_id: Option<String>,
+}
+
 impl DomainEvent {
     pub fn event_type(&self) -> &'static str {
         match self {
@@ -155,6 +291,35 @@ impl DomainEvent {
             DomainEvent::GroupRemoved(_) => "group.removed",
             DomainEvent::RoleDeleted(_) => "role.deleted",
             DomainEvent::GroupDeleted(_) => "group.deleted",
+            DomainEvent::LoginSucceeded(_) => "login.succeeded",
+            DomainEvent::LoginFailed(_) => "login.failed",
+            DomainEvent::UserLockedOut(_) => "user.locked_out",
+            DomainEvent::PasskeyEnrolled(_) => "passkey.enrolled",
+            DomainEvent::MfaEnrolled(_) => "mfa.enrolled",
+            DomainEvent::MfaRemoved(_) => "mfa.removed",
+            DomainEvent::SessionRevoked(_) => "session.revoked",
+            DomainEvent::PasswordReset(_) => "password.reset",
+            DomainEvent::IdpLinked(_) => "idp.linked",
+            DomainEvent::RefreshTokenReuseDetected(_) => "token.refresh_reuse_detected",
+        }
+    }
+
+    /// The realm of events that are not written to the outbox by the service
+    /// that raised them. CRUD events return `None`; their services write the
+    /// envelope themselves, inside the change's transaction.
+    pub fn realm_id(&self) -> Option<Uuid> {
+        match self {
+            DomainEvent::LoginSucceeded(e) => Some(e.realm_id),
+            DomainEvent::LoginFailed(e) => Some(e.realm_id),
+            DomainEvent::UserLockedOut(e) => Some(e.realm_id),
+            DomainEvent::PasskeyEnrolled(e) => Some(e.realm_id),
+            DomainEvent::MfaEnrolled(e) => Some(e.realm_id),
+            DomainEvent::MfaRemoved(e) => Some(e.realm_id),
+            DomainEvent::SessionRevoked(e) => Some(e.realm_id),
+            DomainEvent::PasswordReset(e) => Some(e.realm_id),
+            DomainEvent::IdpLinked(e) => Some(e.realm_id),
+            DomainEvent::RefreshTokenReuseDetected(e) => Some(e.realm_id),
+            _ => None,
         }
     }
 
@@ -180,6 +345,16 @@ impl DomainEvent {
             DomainEvent::GroupRemoved(e) => serde_json::to_value(e),
             DomainEvent::RoleDeleted(e) => serde_json::to_value(e),
             DomainEvent::GroupDeleted(e) => serde_json::to_value(e),
+            DomainEvent::LoginSucceeded(e) => serde_json::to_value(e),
+            DomainEvent::LoginFailed(e) => serde_json::to_value(e),
+            DomainEvent::UserLockedOut(e) => serde_json::to_value(e),
+            DomainEvent::PasskeyEnrolled(e) => serde_json::to_value(e),
+            DomainEvent::MfaEnrolled(e) => serde_json::to_value(e),
+            DomainEvent::MfaRemoved(e) => serde_json::to_value(e),
+            DomainEvent::SessionRevoked(e) => serde_json::to_value(e),
+            DomainEvent::PasswordReset(e) => serde_json::to_value(e),
+            DomainEvent::IdpLinked(e) => serde_json::to_value(e),
+            DomainEvent::RefreshTokenReuseDetected(e) => serde_json::to_value(e),
         }
         .unwrap_or_else(|_| Value::Object(Default::default()))
     }
@@ -337,6 +512,63 @@ pub const WEBHOOK_EVENT_CATALOG: &[WebhookEventGroup] = &[
             },
         ],
     },
+    WebhookEventGroup {
+        id: "security",
+        label: "Security",
+        description: "Sign-ins, lockouts, credentials and sessions",
+        events: &[
+            WebhookEventDefinition {
+                event_type: "login.succeeded",
+                label: "Login succeeded",
+                description: "A user completed an authentication flow.",
+            },
+            WebhookEventDefinition {
+                event_type: "login.failed",
+                label: "Login failed",
+                description: "An authenticator rejected a credential or the flow denied access.",
+            },
+            WebhookEventDefinition {
+                event_type: "user.locked_out",
+                label: "User locked out",
+                description: "Repeated failed sign-ins locked a username.",
+            },
+            WebhookEventDefinition {
+                event_type: "passkey.enrolled",
+                label: "Passkey enrolled",
+                description: "A user registered a new passkey.",
+            },
+            WebhookEventDefinition {
+                event_type: "mfa.enrolled",
+                label: "MFA enrolled",
+                description: "A user set up an authenticator app or verified a phone for SMS codes.",
+            },
+            WebhookEventDefinition {
+                event_type: "mfa.removed",
+                label: "MFA removed",
+                description: "A user's authenticator app or verified SMS phone number was removed.",
+            },
+            WebhookEventDefinition {
+                event_type: "session.revoked",
+                label: "Session revoked",
+                description: "A session ended by logout, revocation or an account change.",
+            },
+            WebhookEventDefinition {
+                event_type: "password.reset",
+                label: "Password reset",
+                description: "A user reset their password through recovery or a forced reset.",
+            },
+            WebhookEventDefinition {
+                event_type: "idp.linked",
+                label: "Identity provider linked",
+                description: "An external identity was linked to a user.",
+            },
+            WebhookEventDefinition {
+                event_type: "token.refresh_reuse_detected",
+                label: "Refresh token reuse detected",
+                description: "A rotated refresh token was presented again and its session family was revoked.",
+            },
+        ],
+    },
 ];
 
 pub fn is_supported_webhook_event_type(event_type: &str) -> bool {
@@ -628,6 +860,48 @@ mod tests {
         }
     }
 
+    #[test]
+    fn security_events_carry_their_realm_outside_the_payload() {
+        let realm_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        let session_id = Uuid::new_v4();
+
+        let event = DomainEvent::SessionRevoked(SessionRevoked {
+            realm_id,
+            user_id,
+            session_id,
+            client_id: Some("app".to_string()),
+            reason: SessionRevocationReason::AccountChange,
+        });
+        assert_eq!(event.realm_id(), Some(realm_id));
+        let envelope = event.to_envelope(Uuid::new_v4(), Utc::now(), event.realm_id(), None);
+        assert_eq!(envelope.event_type, "session.revoked");
+        assert_eq!(envelope.realm_id, Some(realm_id));
+        assert_eq!(
+            envelope.data,
+            json!({
+                "user_id": user_id,
+                "session_id": session_id,
+                "client_id": "app",
+                "reason": "account_change",
+            })
+        );
+
+        let event = DomainEvent::PasswordReset(PasswordReset {
+            realm_id,
+            user_id,
+            source: PasswordResetSource::ForcedReset,
+        });
+        assert_eq!(event.payload_value()["source"], json!("forced_reset"));
+        assert!(event.payload_value().get("realm_id").is_none());
+
+        let crud = DomainEvent::UserCreated(UserCreated {
+            user_id,
+            username: "alice".to_string(),
+        });
+        assert_eq!(crud.realm_id(), None);
+    }
+
     #[test]
     fn webhook_event_catalog_contains_supported_event_types() {
         for group in WEBHOOK_EVENT_CATALOG {
@@ -643,10 +917,14 @@ mod tests {
         }
 
         assert!(is_supported_webhook_event_type("role.created"));
+        assert!(is_supported_webhook_event_type(
+            "token.refresh_reuse_detected"
+        ));
         assert!(!is_supported_webhook_event_type("billing.invoice.created"));
     }
 
     fn catalog_event(event_type: &str) -> DomainEvent {
+        let realm_id = Uuid::new_v4();
         let user_id = Uuid::new_v4();
         let role_id = Uuid::new_v4();
         let group_id = Uuid::new_v4();
@@ -708,6 +986,70 @@ mod tests {
                 group_ids: vec![group_id],
                 affected_user_ids: vec![user_id],
             }),
+            "login.succeeded" => DomainEvent::LoginSucceeded(LoginSucceeded {
+                realm_id,
+                user_id,
+                username: Some("alice".to_string()),
+                auth_session_id: group_id,
+            }),
+            "login.failed" => DomainEvent::LoginFailed(LoginFailed {
+                realm_id,
+                user_id: None,
+                username: Some("alice".to_string()),
+                auth_session_id: group_id,
+                authenticator: Some("core.auth.password".to_string()),
+                reason: "Invalid credentials".to_string(),
+            }),
+            "user.locked_out" => DomainEvent::UserLockedOut(UserLockedOut {
+                realm_id,
+                user_id: Some(user_id),
+                username: "alice".to_string(),
+                locked_until: chrono::Utc::now(),
+            }),
+            "passkey.enrolled" => DomainEvent::PasskeyEnrolled(PasskeyEnrolled {
+                realm_id,
+                user_id,
+                credential_id: role_id,
+                friendly_name: None,
+            }),
+            "mfa.enrolled" => DomainEvent::MfaEnrolled(MfaChanged {
+                realm_id,
+                user_id,
+                method: MfaMethod::Totp,
+            }),
+            "mfa.removed" => DomainEvent::MfaRemoved(MfaChanged {
+                realm_id,
+                user_id,
+                method: MfaMethod::Sms,
+            }),
+            "session.revoked" => DomainEvent::SessionRevoked(SessionRevoked {
+                realm_id,
+                user_id,
+                session_id: role_id,
+                client_id: None,
+                reason: SessionRevocationReason::Logout,
+            }),
+            "password.reset" => DomainEvent::PasswordReset(PasswordReset {
+                realm_id,
+                user_id,
+                source: PasswordResetSource::Recovery,
+            }),
+            "idp.linked" => DomainEvent::IdpLinked(IdpLinked {
+                realm_id,
+                user_id,
+                provider_id: role_id,
+                provider_alias: "google".to_string(),
+                subject: "sub-1".to_string(),
+                linked_via: "manual".to_string(),
+            }),
+            "token.refresh_reuse_detected" => {
+                DomainEvent::RefreshTokenReuseDetected(RefreshTokenReuseDetected {
+                    realm_id,
+                    user_id,
+                    family_id: role_id,
+                    client_id: Some("app".to_string()),
+                })
+            }
             _ => panic!("unsupported event type: {event_type}"),
         }
     }
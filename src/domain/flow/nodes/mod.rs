@@ -14,10 +14,14 @@ pub mod password_node;
 pub mod recovery_issue_node;
 pub mod registration_node;
 pub mod reset_password_node;
+pub mod sms_otp_issue_node;
 pub mod start_node;
 pub mod subflow_node;
 pub mod terminal_node;
+pub mod totp_enroll_node;
+pub mod totp_verify_node;
 pub mod verify_email_otp_node;
+pub mod verify_sms_otp_node;
 
 #[cfg(test)]
 mod tests;
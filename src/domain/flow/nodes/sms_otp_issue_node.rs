@@ -0,0 +1,80 @@
+use crate::domain::flow::provider::NodeProvider;
+use serde_json::{json, Value};
+
+pub struct SmsOtpIssueNodeProvider;
+
+impl NodeProvider for SmsOtpIssueNodeProvider {
+    fn id(&self) -> &'static str {
+        "core.logic.issue_sms_otp"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Issue SMS OTP"
+    }
+
+    fn description(&self) -> &'static str {
+        "Send a one-time verification code to the user's primary phone number."
+    }
+
+    fn icon(&self) -> &'static str {
+        "MessageSquare"
+    }
+
+    fn category(&self) -> &'static str {
+        "Logic"
+    }
+
+    fn inputs(&self) -> Vec<&'static str> {
+        vec!["default"]
+    }
+
+    fn outputs(&self) -> Vec<&'static str> {
+        vec!["issued", "no_phone"]
+    }
+
+    fn config_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "logic_type": {
+                    "type": "string",
+                    "const": "core.logic.issue_sms_otp",
+                    "default": "core.logic.issue_sms_otp"
+                },
+                "require_verified_phone": {
+                    "type": "boolean",
+                    "title": "Only send to verified numbers",
+                    "default": false
+                },
+                "token_ttl_minutes": {
+                    "type": "integer",
+                    "title": "Code TTL (minutes)",
+                    "minimum": 1,
+                    "default": 5
+                },
+                "resend_cooldown_secs": {
+                    "type": "integer",
+                    "title": "Resend cooldown (seconds)",
+                    "minimum": 0,
+                    "default": 30
+                },
+                "max_resends": {
+                    "type": "integer",
+                    "title": "Maximum resends",
+                    "minimum": 0,
+                    "default": 3
+                },
+                "message_template": {
+                    "type": "string",
+                    "title": "Message Template",
+                    "description": "Supports {code}, {realm} and {minutes}."
+                }
+            },
+            "additionalProperties": true
+        })
+    }
+
+    fn side_effects(&self) -> bool {
+        true
+    }
+}
@@ -14,10 +14,14 @@ use super::password_node::PasswordNodeProvider;
 use super::recovery_issue_node::RecoveryIssueNodeProvider;
 use super::registration_node::RegistrationNodeProvider;
 use super::reset_password_node::ResetPasswordNodeProvider;
+use super::sms_otp_issue_node::SmsOtpIssueNodeProvider;
 use super::start_node::StartNode;
 use super::subflow_node::SubflowNodeProvider;
 use super::terminal_node::{AllowNode, DenyNode};
+use super::totp_enroll_node::TotpEnrollNodeProvider;
+use super::totp_verify_node::TotpVerifyNodeProvider;
 use super::verify_email_otp_node::VerifyEmailOtpNodeProvider;
+use super::verify_sms_otp_node::VerifySmsOtpNodeProvider;
 use crate::domain::flow::provider::NodeProvider;
 
 #[test]
@@ -96,6 +100,34 @@ fn passkey_enroll_node_metadata_is_consistent() {
     assert!(node.supports_ui());
 }
 
+#[test]
+fn totp_enroll_node_metadata_is_consistent() {
+    let node = TotpEnrollNodeProvider;
+
+    assert_eq!(node.id(), "core.auth.totp_enroll");
+    assert_eq!(node.category(), "Authenticator");
+    assert_eq!(node.outputs(), vec!["success", "skip", "failure"]);
+    assert_eq!(node.default_template_key(), Some("totp_enroll"));
+    assert!(node.supports_ui());
+    assert!(node.config_schema()["properties"]
+        .get("drift_steps")
+        .is_some());
+}
+
+#[test]
+fn totp_verify_node_metadata_is_consistent() {
+    let node = TotpVerifyNodeProvider;
+
+    assert_eq!(node.id(), "core.auth.totp_verify");
+    assert_eq!(node.category(), "Authenticator");
+    assert_eq!(node.outputs(), vec!["success", "not_enrolled", "failure"]);
+    assert_eq!(node.default_template_key(), Some("mfa"));
+    assert!(node.supports_ui());
+    assert!(node.config_schema()["properties"]
+        .get("max_attempts")
+        .is_some());
+}
+
 #[test]
 fn registration_node_metadata_is_consistent() {
     let node = RegistrationNodeProvider;
@@ -299,6 +331,32 @@ fn verify_email_otp_node_metadata_is_consistent() {
     assert!(node.supports_ui());
 }
 
+#[test]
+fn sms_otp_issue_node_metadata_is_consistent() {
+    let node = SmsOtpIssueNodeProvider;
+
+    assert_eq!(node.id(), "core.logic.issue_sms_otp");
+    assert_eq!(node.category(), "Logic");
+    assert_eq!(node.inputs(), vec!["default"]);
+    assert_eq!(node.outputs(), vec!["issued", "no_phone"]);
+    assert!(!node.supports_ui());
+    assert!(node.side_effects());
+    assert!(node.config_schema()["properties"]
+        .get("resend_cooldown_secs")
+        .is_some());
+}
+
+#[test]
+fn verify_sms_otp_node_metadata_is_consistent() {
+    let node = VerifySmsOtpNodeProvider;
+
+    assert_eq!(node.id(), "core.auth.verify_sms_otp");
+    assert_eq!(node.category(), "Authenticator");
+    assert_eq!(node.outputs(), vec!["success", "failure"]);
+    assert_eq!(node.default_template_key(), Some("sms_otp"));
+    assert!(node.supports_ui());
+}
+
 #[test]
 fn condition_node_metadata_is_consistent() {
     let node = ConditionNodeProvider;
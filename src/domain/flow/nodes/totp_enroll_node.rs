@@ -0,0 +1,73 @@
+use crate::domain::flow::provider::NodeProvider;
+use crate::domain::ui::{PageCategory, UiSurface};
+use serde_json::{json, Value};
+
+pub struct TotpEnrollNodeProvider;
+
+impl NodeProvider for TotpEnrollNodeProvider {
+    fn id(&self) -> &'static str {
+        "core.auth.totp_enroll"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Authenticator App Enroll"
+    }
+
+    fn description(&self) -> &'static str {
+        "Set up a TOTP authenticator app for the authenticated user."
+    }
+
+    fn icon(&self) -> &'static str {
+        "QrCode"
+    }
+
+    fn category(&self) -> &'static str {
+        "Authenticator"
+    }
+
+    fn outputs(&self) -> Vec<&'static str> {
+        vec!["success", "skip", "failure"]
+    }
+
+    fn config_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "allow_skip": {
+                    "type": "boolean",
+                    "title": "Allow Skip",
+                    "default": true
+                },
+                "issuer": {
+                    "type": "string",
+                    "title": "Issuer shown in the app",
+                    "description": "Defaults to the realm name."
+                },
+                "drift_steps": {
+                    "type": "integer",
+                    "title": "Allowed clock drift (30s steps)",
+                    "default": 1,
+                    "minimum": 0,
+                    "maximum": 4
+                }
+            },
+            "additionalProperties": true
+        })
+    }
+
+    fn supports_ui(&self) -> bool {
+        true
+    }
+
+    fn default_template_key(&self) -> Option<&'static str> {
+        Some("totp_enroll")
+    }
+
+    fn ui_surface(&self) -> Option<UiSurface> {
+        Some(UiSurface::Form)
+    }
+
+    fn allowed_page_categories(&self) -> Vec<PageCategory> {
+        vec![PageCategory::Mfa]
+    }
+}
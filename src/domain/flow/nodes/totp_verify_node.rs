@@ -0,0 +1,69 @@
+use crate::domain::flow::provider::NodeProvider;
+use crate::domain::ui::{PageCategory, UiSurface};
+use serde_json::{json, Value};
+
+pub struct TotpVerifyNodeProvider;
+
+impl NodeProvider for TotpVerifyNodeProvider {
+    fn id(&self) -> &'static str {
+        "core.auth.totp_verify"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Authenticator App Verify"
+    }
+
+    fn description(&self) -> &'static str {
+        "Require a TOTP code from the user's authenticator app."
+    }
+
+    fn icon(&self) -> &'static str {
+        "Smartphone"
+    }
+
+    fn category(&self) -> &'static str {
+        "Authenticator"
+    }
+
+    fn outputs(&self) -> Vec<&'static str> {
+        vec!["success", "not_enrolled", "failure"]
+    }
+
+    fn config_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "drift_steps": {
+                    "type": "integer",
+                    "title": "Allowed clock drift (30s steps)",
+                    "default": 1,
+                    "minimum": 0,
+                    "maximum": 4
+                },
+                "max_attempts": {
+                    "type": "integer",
+                    "title": "Attempts before failure",
+                    "default": 5,
+                    "minimum": 1
+                }
+            },
+            "additionalProperties": true
+        })
+    }
+
+    fn supports_ui(&self) -> bool {
+        true
+    }
+
+    fn default_template_key(&self) -> Option<&'static str> {
+        Some("mfa")
+    }
+
+    fn ui_surface(&self) -> Option<UiSurface> {
+        Some(UiSurface::Form)
+    }
+
+    fn allowed_page_categories(&self) -> Vec<PageCategory> {
+        vec![PageCategory::Mfa]
+    }
+}
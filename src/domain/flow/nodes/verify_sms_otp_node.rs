@@ -0,0 +1,62 @@
+use crate::domain::flow::provider::NodeProvider;
+use crate::domain::ui::{PageCategory, UiSurface};
+use serde_json::{json, Value};
+
+pub struct VerifySmsOtpNodeProvider;
+
+impl NodeProvider for VerifySmsOtpNodeProvider {
+    fn id(&self) -> &'static str {
+        "core.auth.verify_sms_otp"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Verify SMS OTP"
+    }
+
+    fn description(&self) -> &'static str {
+        "Check the SMS verification code and mark the phone number as verified."
+    }
+
+    fn icon(&self) -> &'static str {
+        "MessageSquare"
+    }
+
+    fn category(&self) -> &'static str {
+        "Authenticator"
+    }
+
+    fn outputs(&self) -> Vec<&'static str> {
+        vec!["success", "failure"]
+    }
+
+    fn config_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "max_attempts": {
+                    "type": "integer",
+                    "title": "Attempts before failure",
+                    "default": 5,
+                    "minimum": 1
+                }
+            },
+            "additionalProperties": true
+        })
+    }
+
+    fn supports_ui(&self) -> bool {
+        true
+    }
+
+    fn default_template_key(&self) -> Option<&'static str> {
+        Some("sms_otp")
+    }
+
+    fn ui_surface(&self) -> Option<UiSurface> {
+        Some(UiSurface::Form)
+    }
+
+    fn allowed_page_categories(&self) -> Vec<PageCategory> {
+        vec![PageCategory::Mfa]
+    }
+}
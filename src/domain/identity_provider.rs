@@ -1,3 +1,4 @@
+use crate::domain::saml::NameIdFormat;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -7,6 +8,7 @@ use uuid::Uuid;
 pub enum IdentityProviderProtocol {
     Oidc,
     Oauth2,
+    Saml,
 }
 
 impl std::fmt::Display for IdentityProviderProtocol {
@@ -14,6 +16,7 @@ impl std::fmt::Display for IdentityProviderProtocol {
         match self {
             Self::Oidc => write!(f, "oidc"),
             Self::Oauth2 => write!(f, "oauth2"),
+            Self::Saml => write!(f, "saml"),
         }
     }
 }
@@ -25,6 +28,7 @@ impl TryFrom<String> for IdentityProviderProtocol {
         match value.as_str() {
             "oidc" => Ok(Self::Oidc),
             "oauth2" => Ok(Self::Oauth2),
+            "saml" => Ok(Self::Saml),
             other => Err(format!("Unsupported identity provider protocol: {}", other)),
         }
     }
@@ -39,9 +43,12 @@ pub struct IdentityProvider {
     pub protocol: IdentityProviderProtocol,
     pub preset_key: Option<String>,
     pub enabled: bool,
+    /// For SAML, the entity ID ReAuth uses as the service provider.
     pub client_id: String,
     pub client_secret: Option<String>,
+    /// For SAML, the identity provider's entity ID.
     pub issuer: Option<String>,
+    /// For SAML, the HTTP-Redirect SingleSignOnService location.
     pub authorization_endpoint: Option<String>,
     pub token_endpoint: Option<String>,
     pub userinfo_endpoint: Option<String>,
@@ -61,6 +68,12 @@ pub struct IdentityProvider {
     pub metadata_cache_json: Option<String>,
     pub jwks_cached_at: Option<DateTime<Utc>>,
     pub jwks_cache_json: Option<String>,
+    /// Where SAML metadata is imported from; refreshed like OIDC discovery.
+    pub saml_metadata_url: Option<String>,
+    /// Base64 DER certificates that may sign SAML responses.
+    pub saml_signing_certificates_json: String,
+    /// The `NameIDPolicy` requested in SAML AuthnRequests.
+    pub saml_name_id_format: Option<NameIdFormat>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -89,6 +102,8 @@ pub struct OAuthBrokerState {
     pub auth_session_id: Uuid,
     pub pkce_verifier_hash: String,
     pub redirect_uri: String,
+    /// The OIDC nonce, or the ID of the AuthnRequest a SAML response must
+    /// answer.
     pub nonce: Option<String>,
     pub expires_at: DateTime<Utc>,
     pub consumed_at: Option<DateTime<Utc>>,
@@ -0,0 +1,167 @@
+//! The subset of BER (X.690) that LDAP uses: definite lengths, single-byte
+//! tags, and INTEGER/ENUMERATED/BOOLEAN/OCTET STRING primitives.
+
+use crate::error::{Error, Result};
+
+pub const TAG_BOOLEAN: u8 = 0x01;
+pub const TAG_INTEGER: u8 = 0x02;
+pub const TAG_OCTET_STRING: u8 = 0x04;
+pub const TAG_ENUMERATED: u8 = 0x0a;
+pub const TAG_SEQUENCE: u8 = 0x30;
+pub const TAG_SET: u8 = 0x31;
+
+/// Messages larger than this are rejected rather than buffered.
+pub const MAX_MESSAGE_LEN: usize = 16 * 1024 * 1024;
+
+pub(super) fn malformed(detail: impl std::fmt::Display) -> Error {
+    Error::System(format!("Malformed LDAP message: {}", detail))
+}
+
+pub fn write_tlv(out: &mut Vec<u8>, tag: u8, content: &[u8]) {
+    out.push(tag);
+    write_length(out, content.len());
+    out.extend_from_slice(content);
+}
+
+/// Writes a constructed element whose content is produced by `build`.
+pub fn write_constructed(out: &mut Vec<u8>, tag: u8, build: impl FnOnce(&mut Vec<u8>)) {
+    let mut content = Vec::new();
+    build(&mut content);
+    write_tlv(out, tag, &content);
+}
+
+fn write_length(out: &mut Vec<u8>, len: usize) {
+    if len < 0x80 {
+        out.push(len as u8);
+        return;
+    }
+    let bytes = len.to_be_bytes();
+    let skip = bytes.iter().take_while(|byte| **byte == 0).count();
+    out.push(0x80 | (bytes.len() - skip) as u8);
+    out.extend_from_slice(&bytes[skip..]);
+}
+
+pub fn write_integer(out: &mut Vec<u8>, tag: u8, value: i64) {
+    let bytes = value.to_be_bytes();
+    // Drop leading bytes that only repeat the sign of the next one.
+    let mut start = 0;
+    while start < bytes.len() - 1 {
+        let (byte, next) = (bytes[start], bytes[start + 1]);
+        if (byte == 0x00 && next & 0x80 == 0) || (byte == 0xff && next & 0x80 != 0) {
+            start += 1;
+        } else {
+            break;
+        }
+    }
+    write_tlv(out, tag, &bytes[start..]);
+}
+
+pub fn write_boolean(out: &mut Vec<u8>, value: bool) {
+    write_tlv(out, TAG_BOOLEAN, &[if value { 0xff } else { 0x00 }]);
+}
+
+pub fn write_octet_string(out: &mut Vec<u8>, tag: u8, value: &[u8]) {
+    write_tlv(out, tag, value);
+}
+
+pub fn decode_integer(content: &[u8]) -> Result<i64> {
+    if content.is_empty() || content.len() > 8 {
+        return Err(malformed("integer has an invalid length"));
+    }
+    let mut value: i64 = if content[0] & 0x80 != 0 { -1 } else { 0 };
+    for byte in content {
+        value = (value << 8) | i64::from(*byte);
+    }
+    Ok(value)
+}
+
+/// Returns the length of the first complete element in `buf`, or `None`
+/// while more bytes are needed. Used to frame messages read from a stream.
+pub fn element_length(buf: &[u8]) -> Result<Option<usize>> {
+    if buf.len() < 2 {
+        return Ok(None);
+    }
+    let first = buf[1];
+    let (header, content) = if first & 0x80 == 0 {
+        (2, usize::from(first))
+    } else {
+        let count = usize::from(first & 0x7f);
+        if count == 0 || count > std::mem::size_of::<usize>() {
+            return Err(malformed("unsupported length encoding"));
+        }
+        if buf.len() < 2 + count {
+            return Ok(None);
+        }
+        let len = buf[2..2 + count]
+            .iter()
+            .fold(0usize, |acc, byte| (acc << 8) | usize::from(*byte));
+        (2 + count, len)
+    };
+    if content > MAX_MESSAGE_LEN {
+        return Err(malformed("message exceeds the size limit"));
+    }
+    let total = header + content;
+    Ok((buf.len() >= total).then_some(total))
+}
+
+/// Sequential reader over the elements of a constructed value.
+pub struct BerReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> BerReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn peek_tag(&self) -> Option<u8> {
+        self.data.first().copied()
+    }
+
+    /// Reads the next element, returning its tag and content.
+    pub fn read(&mut self) -> Result<(u8, &'a [u8])> {
+        let total = element_length(self.data)?.ok_or_else(|| malformed("element is truncated"))?;
+        let tag = self.data[0];
+        let header = if self.data[1] & 0x80 == 0 {
+            2
+        } else {
+            2 + usize::from(self.data[1] & 0x7f)
+        };
+        let content = &self.data[header..total];
+        self.data = &self.data[total..];
+        Ok((tag, content))
+    }
+
+    /// Reads the next element and checks its tag.
+    pub fn expect(&mut self, tag: u8) -> Result<&'a [u8]> {
+        let (found, content) = self.read()?;
+        if found != tag {
+            return Err(malformed(format!(
+                "expected tag {:#04x}, found {:#04x}",
+                tag, found
+            )));
+        }
+        Ok(content)
+    }
+
+    pub fn read_integer(&mut self, tag: u8) -> Result<i64> {
+        decode_integer(self.expect(tag)?)
+    }
+
+    pub fn read_boolean(&mut self) -> Result<bool> {
+        let content = self.expect(TAG_BOOLEAN)?;
+        match content {
+            [value] => Ok(*value != 0),
+            _ => Err(malformed("boolean has an invalid length")),
+        }
+    }
+
+    pub fn read_string(&mut self, tag: u8) -> Result<String> {
+        String::from_utf8(self.expect(tag)?.to_vec())
+            .map_err(|_| malformed("string is not valid UTF-8"))
+    }
+}
@@ -0,0 +1,439 @@
+use super::ber::{
+    malformed, write_constructed, write_octet_string, write_tlv, BerReader, TAG_OCTET_STRING,
+    TAG_SEQUENCE,
+};
+use super::message::SearchEntry;
+use crate::error::{Error, Result};
+
+const TAG_AND: u8 = 0xa0;
+const TAG_OR: u8 = 0xa1;
+const TAG_NOT: u8 = 0xa2;
+const TAG_EQUALITY: u8 = 0xa3;
+const TAG_SUBSTRINGS: u8 = 0xa4;
+const TAG_GREATER_OR_EQUAL: u8 = 0xa5;
+const TAG_LESS_OR_EQUAL: u8 = 0xa6;
+const TAG_PRESENT: u8 = 0x87;
+const TAG_APPROX: u8 = 0xa8;
+const TAG_SUB_INITIAL: u8 = 0x80;
+const TAG_SUB_ANY: u8 = 0x81;
+const TAG_SUB_FINAL: u8 = 0x82;
+
+/// A search filter (RFC 4511, Section 4.5.1) with its string form from
+/// RFC 4515. Extensible matches are not supported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+    Equality(String, Vec<u8>),
+    Substrings {
+        attribute: String,
+        initial: Option<Vec<u8>>,
+        any: Vec<Vec<u8>>,
+        final_: Option<Vec<u8>>,
+    },
+    GreaterOrEqual(String, Vec<u8>),
+    LessOrEqual(String, Vec<u8>),
+    Present(String),
+    Approx(String, Vec<u8>),
+}
+
+impl Filter {
+    pub fn equality(attribute: &str, value: &str) -> Self {
+        Self::Equality(attribute.to_string(), value.as_bytes().to_vec())
+    }
+
+    pub fn parse(input: &str) -> Result<Self> {
+        let trimmed = input.trim();
+        // A bare item such as `uid=jdoe` is accepted for convenience.
+        let wrapped;
+        let source = if trimmed.starts_with('(') {
+            trimmed
+        } else {
+            wrapped = format!("({})", trimmed);
+            &wrapped
+        };
+        let mut parser = Parser {
+            input: source.as_bytes(),
+            pos: 0,
+        };
+        let filter = parser.filter()?;
+        if parser.pos != parser.input.len() {
+            return Err(invalid("unexpected characters after the filter"));
+        }
+        Ok(filter)
+    }
+
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Self::And(filters) | Self::Or(filters) => {
+                let tag = if matches!(self, Self::And(_)) {
+                    TAG_AND
+                } else {
+                    TAG_OR
+                };
+                write_constructed(out, tag, |out| {
+                    for filter in filters {
+                        filter.encode(out);
+                    }
+                });
+            }
+            Self::Not(filter) => write_constructed(out, TAG_NOT, |out| filter.encode(out)),
+            Self::Equality(attribute, value) => {
+                encode_assertion(out, TAG_EQUALITY, attribute, value)
+            }
+            Self::GreaterOrEqual(attribute, value) => {
+                encode_assertion(out, TAG_GREATER_OR_EQUAL, attribute, value)
+            }
+            Self::LessOrEqual(attribute, value) => {
+                encode_assertion(out, TAG_LESS_OR_EQUAL, attribute, value)
+            }
+            Self::Approx(attribute, value) => encode_assertion(out, TAG_APPROX, attribute, value),
+            Self::Present(attribute) => write_tlv(out, TAG_PRESENT, attribute.as_bytes()),
+            Self::Substrings {
+                attribute,
+                initial,
+                any,
+                final_,
+            } => write_constructed(out, TAG_SUBSTRINGS, |out| {
+                write_octet_string(out, TAG_OCTET_STRING, attribute.as_bytes());
+                write_constructed(out, TAG_SEQUENCE, |out| {
+                    if let Some(value) = initial {
+                        write_tlv(out, TAG_SUB_INITIAL, value);
+                    }
+                    for value in any {
+                        write_tlv(out, TAG_SUB_ANY, value);
+                    }
+                    if let Some(value) = final_ {
+                        write_tlv(out, TAG_SUB_FINAL, value);
+                    }
+                });
+            }),
+        }
+    }
+
+    pub fn decode(tag: u8, content: &[u8]) -> Result<Self> {
+        let mut reader = BerReader::new(content);
+        match tag {
+            TAG_AND | TAG_OR => {
+                let mut filters = Vec::new();
+                while !reader.is_empty() {
+                    let (tag, content) = reader.read()?;
+                    filters.push(Self::decode(tag, content)?);
+                }
+                Ok(if tag == TAG_AND {
+                    Self::And(filters)
+                } else {
+                    Self::Or(filters)
+                })
+            }
+            TAG_NOT => {
+                let (tag, content) = reader.read()?;
+                Ok(Self::Not(Box::new(Self::decode(tag, content)?)))
+            }
+            TAG_EQUALITY | TAG_GREATER_OR_EQUAL | TAG_LESS_OR_EQUAL | TAG_APPROX => {
+                let attribute = reader.read_string(TAG_OCTET_STRING)?;
+                let value = reader.expect(TAG_OCTET_STRING)?.to_vec();
+                Ok(match tag {
+                    TAG_EQUALITY => Self::Equality(attribute, value),
+                    TAG_GREATER_OR_EQUAL => Self::GreaterOrEqual(attribute, value),
+                    TAG_LESS_OR_EQUAL => Self::LessOrEqual(attribute, value),
+                    _ => Self::Approx(attribute, value),
+                })
+            }
+            TAG_PRESENT => Ok(Self::Present(
+                String::from_utf8(content.to_vec())
+                    .map_err(|_| malformed("attribute name is not valid UTF-8"))?,
+            )),
+            TAG_SUBSTRINGS => {
+                let attribute = reader.read_string(TAG_OCTET_STRING)?;
+                let mut parts = BerReader::new(reader.expect(TAG_SEQUENCE)?);
+                let (mut initial, mut any, mut final_) = (None, Vec::new(), None);
+                while !parts.is_empty() {
+                    let (tag, value) = parts.read()?;
+                    match tag {
+                        TAG_SUB_INITIAL => initial = Some(value.to_vec()),
+                        TAG_SUB_ANY => any.push(value.to_vec()),
+                        TAG_SUB_FINAL => final_ = Some(value.to_vec()),
+                        other => {
+                            return Err(malformed(format!("unknown substring tag {:#04x}", other)))
+                        }
+                    }
+                }
+                Ok(Self::Substrings {
+                    attribute,
+                    initial,
+                    any,
+                    final_,
+                })
+            }
+            other => Err(malformed(format!("unsupported filter tag {:#04x}", other))),
+        }
+    }
+
+    /// Evaluates the filter against an entry, comparing values
+    /// case-insensitively. Meant for tests and stubs; real directories apply
+    /// each attribute's own matching rules.
+    pub fn matches(&self, entry: &SearchEntry) -> bool {
+        let strings = |attribute: &str| -> Vec<String> {
+            entry
+                .strings(attribute)
+                .into_iter()
+                .map(|value| value.to_lowercase())
+                .collect()
+        };
+        let lower = |value: &[u8]| String::from_utf8_lossy(value).to_lowercase();
+        match self {
+            Self::And(filters) => filters.iter().all(|filter| filter.matches(entry)),
+            Self::Or(filters) => filters.iter().any(|filter| filter.matches(entry)),
+            Self::Not(filter) => !filter.matches(entry),
+            Self::Equality(attribute, value) | Self::Approx(attribute, value) => {
+                strings(attribute).contains(&lower(value))
+            }
+            Self::GreaterOrEqual(attribute, value) => strings(attribute)
+                .iter()
+                .any(|candidate| *candidate >= lower(value)),
+            Self::LessOrEqual(attribute, value) => strings(attribute)
+                .iter()
+                .any(|candidate| *candidate <= lower(value)),
+            Self::Present(attribute) => !entry.values(attribute).is_empty(),
+            Self::Substrings {
+                attribute,
+                initial,
+                any,
+                final_,
+            } => strings(attribute).iter().any(|candidate| {
+                let mut rest = candidate.as_str();
+                if let Some(initial) = initial {
+                    match rest.strip_prefix(lower(initial).as_str()) {
+                        Some(tail) => rest = tail,
+                        None => return false,
+                    }
+                }
+                for part in any {
+                    let part = lower(part);
+                    match rest.find(&part) {
+                        Some(index) => rest = &rest[index + part.len()..],
+                        None => return false,
+                    }
+                }
+                final_
+                    .as_ref()
+                    .is_none_or(|final_| rest.ends_with(&lower(final_)))
+            }),
+        }
+    }
+}
+
+impl std::fmt::Display for Filter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let escape = |value: &[u8]| escape_filter_bytes(value);
+        match self {
+            Self::And(filters) | Self::Or(filters) => {
+                write!(
+                    f,
+                    "({}",
+                    if matches!(self, Self::And(_)) {
+                        '&'
+                    } else {
+                        '|'
+                    }
+                )?;
+                for filter in filters {
+                    write!(f, "{}", filter)?;
+                }
+                write!(f, ")")
+            }
+            Self::Not(filter) => write!(f, "(!{})", filter),
+            Self::Equality(attribute, value) => write!(f, "({}={})", attribute, escape(value)),
+            Self::GreaterOrEqual(attribute, value) => {
+                write!(f, "({}>={})", attribute, escape(value))
+            }
+            Self::LessOrEqual(attribute, value) => write!(f, "({}<={})", attribute, escape(value)),
+            Self::Approx(attribute, value) => write!(f, "({}~={})", attribute, escape(value)),
+            Self::Present(attribute) => write!(f, "({}=*)", attribute),
+            Self::Substrings {
+                attribute,
+                initial,
+                any,
+                final_,
+            } => {
+                write!(f, "({}=", attribute)?;
+                if let Some(value) = initial {
+                    write!(f, "{}", escape(value))?;
+                }
+                write!(f, "*")?;
+                for value in any {
+                    write!(f, "{}*", escape(value))?;
+                }
+                if let Some(value) = final_ {
+                    write!(f, "{}", escape(value))?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+/// Escapes a value for use inside a filter string (RFC 4515, Section 3).
+pub fn escape_filter_value(value: &str) -> String {
+    escape_filter_bytes(value.as_bytes())
+}
+
+fn escape_filter_bytes(value: &[u8]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for chunk in value.utf8_chunks() {
+        for ch in chunk.valid().chars() {
+            match ch {
+                '*' | '(' | ')' | '\\' | '\0' => escaped.push_str(&format!("\\{:02x}", ch as u32)),
+                other => escaped.push(other),
+            }
+        }
+        for byte in chunk.invalid() {
+            escaped.push_str(&format!("\\{:02x}", byte));
+        }
+    }
+    escaped
+}
+
+fn encode_assertion(out: &mut Vec<u8>, tag: u8, attribute: &str, value: &[u8]) {
+    write_constructed(out, tag, |out| {
+        write_octet_string(out, TAG_OCTET_STRING, attribute.as_bytes());
+        write_octet_string(out, TAG_OCTET_STRING, value);
+    });
+}
+
+fn invalid(detail: &str) -> Error {
+    Error::Validation(format!("Invalid LDAP filter: {}", detail))
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<()> {
+        if self.peek() != Some(byte) {
+            return Err(invalid(&format!("expected '{}'", byte as char)));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn filter(&mut self) -> Result<Filter> {
+        self.expect(b'(')?;
+        let filter = match self.peek() {
+            Some(b'&') => {
+                self.pos += 1;
+                Filter::And(self.filter_list()?)
+            }
+            Some(b'|') => {
+                self.pos += 1;
+                Filter::Or(self.filter_list()?)
+            }
+            Some(b'!') => {
+                self.pos += 1;
+                Filter::Not(Box::new(self.filter()?))
+            }
+            _ => self.item()?,
+        };
+        self.expect(b')')?;
+        Ok(filter)
+    }
+
+    fn filter_list(&mut self) -> Result<Vec<Filter>> {
+        let mut filters = Vec::new();
+        while self.peek() == Some(b'(') {
+            filters.push(self.filter()?);
+        }
+        if filters.is_empty() {
+            return Err(invalid("'&' and '|' need at least one filter"));
+        }
+        Ok(filters)
+    }
+
+    fn item(&mut self) -> Result<Filter> {
+        let start = self.pos;
+        while let Some(byte) = self.peek() {
+            if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'.' || byte == b';' {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        let attribute = String::from_utf8(self.input[start..self.pos].to_vec())
+            .map_err(|_| invalid("attribute name is not valid UTF-8"))?;
+        if attribute.is_empty() {
+            return Err(invalid("missing attribute name"));
+        }
+
+        let operator = match (self.peek(), self.input.get(self.pos + 1).copied()) {
+            (Some(b'~'), Some(b'=')) => "~=",
+            (Some(b'>'), Some(b'=')) => ">=",
+            (Some(b'<'), Some(b'=')) => "<=",
+            (Some(b'='), _) => "=",
+            _ => return Err(invalid("expected a comparison operator")),
+        };
+        self.pos += operator.len();
+
+        // Split the raw value on unescaped '*' so substring parts can be told
+        // apart from escaped asterisks.
+        let mut parts = vec![Vec::new()];
+        loop {
+            match self.peek() {
+                None => return Err(invalid("unterminated filter")),
+                Some(b')') => break,
+                Some(b'(') => return Err(invalid("unescaped '(' in value")),
+                Some(b'*') => {
+                    self.pos += 1;
+                    parts.push(Vec::new());
+                }
+                Some(b'\\') => {
+                    let hex = self
+                        .input
+                        .get(self.pos + 1..self.pos + 3)
+                        .and_then(|hex| std::str::from_utf8(hex).ok())
+                        .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                        .ok_or_else(|| invalid("invalid escape sequence"))?;
+                    parts.last_mut().expect("parts").push(hex);
+                    self.pos += 3;
+                }
+                Some(byte) => {
+                    parts.last_mut().expect("parts").push(byte);
+                    self.pos += 1;
+                }
+            }
+        }
+
+        if parts.len() == 1 {
+            let value = parts.pop().expect("parts");
+            return Ok(match operator {
+                "~=" => Filter::Approx(attribute, value),
+                ">=" => Filter::GreaterOrEqual(attribute, value),
+                "<=" => Filter::LessOrEqual(attribute, value),
+                _ => Filter::Equality(attribute, value),
+            });
+        }
+        if operator != "=" {
+            return Err(invalid("wildcards are only allowed with '='"));
+        }
+        if parts.len() == 2 && parts.iter().all(Vec::is_empty) {
+            return Ok(Filter::Present(attribute));
+        }
+        let final_ = parts.pop().filter(|part| !part.is_empty());
+        let mut rest = parts.into_iter();
+        let initial = rest.next().filter(|part| !part.is_empty());
+        let any = rest.filter(|part| !part.is_empty()).collect();
+        Ok(Filter::Substrings {
+            attribute,
+            initial,
+            any,
+            final_,
+        })
+    }
+}
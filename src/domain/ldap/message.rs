@@ -0,0 +1,594 @@
+//! LDAPv3 protocol messages (RFC 4511) for the operations user federation
+//! needs: simple bind, search, modify and unbind. Both directions encode and
+//! decode so that a directory stub can reuse the codec.
+
+use serde::{Deserialize, Serialize};
+
+use super::ber::{
+    self, malformed, write_boolean, write_constructed, write_integer, write_octet_string,
+    BerReader, TAG_ENUMERATED, TAG_INTEGER, TAG_OCTET_STRING, TAG_SEQUENCE, TAG_SET,
+};
+use super::filter::Filter;
+use crate::error::Result;
+
+const TAG_BIND_REQUEST: u8 = 0x60;
+const TAG_BIND_RESPONSE: u8 = 0x61;
+const TAG_UNBIND_REQUEST: u8 = 0x42;
+const TAG_SEARCH_REQUEST: u8 = 0x63;
+const TAG_SEARCH_RESULT_ENTRY: u8 = 0x64;
+const TAG_SEARCH_RESULT_DONE: u8 = 0x65;
+const TAG_MODIFY_REQUEST: u8 = 0x66;
+const TAG_MODIFY_RESPONSE: u8 = 0x67;
+const TAG_SEARCH_RESULT_REFERENCE: u8 = 0x73;
+const TAG_EXTENDED_RESPONSE: u8 = 0x78;
+const TAG_SIMPLE_AUTH: u8 = 0x80;
+const TAG_REFERRAL: u8 = 0xa3;
+const TAG_CONTROLS: u8 = 0xa0;
+
+pub const RESULT_SUCCESS: i64 = 0;
+pub const RESULT_SIZE_LIMIT_EXCEEDED: i64 = 4;
+pub const RESULT_NO_SUCH_OBJECT: i64 = 32;
+pub const RESULT_INVALID_CREDENTIALS: i64 = 49;
+pub const RESULT_INSUFFICIENT_ACCESS_RIGHTS: i64 = 50;
+pub const RESULT_UNWILLING_TO_PERFORM: i64 = 53;
+
+/// Simple paged results control (RFC 2696).
+pub const PAGED_RESULTS_OID: &str = "1.2.840.113556.1.4.319";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LdapMessage {
+    pub message_id: i64,
+    pub op: ProtocolOp,
+    pub controls: Vec<Control>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProtocolOp {
+    BindRequest(BindRequest),
+    BindResponse(LdapResult),
+    UnbindRequest,
+    SearchRequest(SearchRequest),
+    SearchResultEntry(SearchEntry),
+    SearchResultReference(Vec<String>),
+    SearchResultDone(LdapResult),
+    ModifyRequest(ModifyRequest),
+    ModifyResponse(LdapResult),
+    /// Only decoded; servers send it unsolicited before disconnecting.
+    ExtendedResponse(LdapResult),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BindRequest {
+    pub name: String,
+    pub password: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LdapResult {
+    pub code: i64,
+    pub matched_dn: String,
+    pub message: String,
+}
+
+impl LdapResult {
+    pub fn success() -> Self {
+        Self::with_code(RESULT_SUCCESS, "")
+    }
+
+    pub fn with_code(code: i64, message: &str) -> Self {
+        Self {
+            code,
+            matched_dn: String::new(),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.code == RESULT_SUCCESS
+    }
+}
+
+impl std::fmt::Display for LdapResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.message.is_empty() {
+            write!(f, "LDAP result code {}", self.code)
+        } else {
+            write!(f, "LDAP result code {}: {}", self.code, self.message)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchScope {
+    Base,
+    OneLevel,
+    Subtree,
+}
+
+impl SearchScope {
+    fn code(self) -> i64 {
+        match self {
+            Self::Base => 0,
+            Self::OneLevel => 1,
+            Self::Subtree => 2,
+        }
+    }
+
+    fn from_code(code: i64) -> Result<Self> {
+        match code {
+            0 => Ok(Self::Base),
+            1 => Ok(Self::OneLevel),
+            2 => Ok(Self::Subtree),
+            other => Err(malformed(format!("unknown search scope {}", other))),
+        }
+    }
+}
+
+impl std::fmt::Display for SearchScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Base => write!(f, "base"),
+            Self::OneLevel => write!(f, "one_level"),
+            Self::Subtree => write!(f, "subtree"),
+        }
+    }
+}
+
+impl TryFrom<String> for SearchScope {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        match value.as_str() {
+            "base" => Ok(Self::Base),
+            "one_level" => Ok(Self::OneLevel),
+            "subtree" => Ok(Self::Subtree),
+            other => Err(format!("Unknown LDAP search scope '{}'", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchRequest {
+    pub base_dn: String,
+    pub scope: SearchScope,
+    pub size_limit: i64,
+    pub time_limit: i64,
+    pub types_only: bool,
+    pub filter: Filter,
+    pub attributes: Vec<String>,
+}
+
+impl SearchRequest {
+    pub fn new(base_dn: &str, scope: SearchScope, filter: Filter, attributes: Vec<String>) -> Self {
+        Self {
+            base_dn: base_dn.to_string(),
+            scope,
+            size_limit: 0,
+            time_limit: 0,
+            types_only: false,
+            filter,
+            attributes,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryAttribute {
+    pub name: String,
+    pub values: Vec<Vec<u8>>,
+}
+
+/// A directory entry. Attribute names compare case-insensitively, as LDAP
+/// attribute descriptions do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchEntry {
+    pub dn: String,
+    pub attributes: Vec<EntryAttribute>,
+}
+
+impl SearchEntry {
+    pub fn new(dn: &str) -> Self {
+        Self {
+            dn: dn.to_string(),
+            attributes: Vec::new(),
+        }
+    }
+
+    pub fn with_values(mut self, name: &str, values: &[&str]) -> Self {
+        self.set(
+            name,
+            values
+                .iter()
+                .map(|value| value.as_bytes().to_vec())
+                .collect(),
+        );
+        self
+    }
+
+    pub fn set(&mut self, name: &str, values: Vec<Vec<u8>>) {
+        match self
+            .attributes
+            .iter_mut()
+            .find(|attribute| attribute.name.eq_ignore_ascii_case(name))
+        {
+            Some(attribute) => attribute.values = values,
+            None => self.attributes.push(EntryAttribute {
+                name: name.to_string(),
+                values,
+            }),
+        }
+    }
+
+    pub fn values(&self, name: &str) -> &[Vec<u8>] {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name.eq_ignore_ascii_case(name))
+            .map(|attribute| attribute.values.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// The attribute's values that are valid UTF-8.
+    pub fn strings(&self, name: &str) -> Vec<String> {
+        self.values(name)
+            .iter()
+            .filter_map(|value| String::from_utf8(value.clone()).ok())
+            .collect()
+    }
+
+    pub fn first_string(&self, name: &str) -> Option<String> {
+        self.strings(name)
+            .into_iter()
+            .map(|value| value.trim().to_string())
+            .find(|value| !value.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModifyOperation {
+    Add,
+    Delete,
+    Replace,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Modification {
+    pub operation: ModifyOperation,
+    pub attribute: String,
+    pub values: Vec<Vec<u8>>,
+}
+
+impl Modification {
+    pub fn replace(attribute: &str, values: Vec<Vec<u8>>) -> Self {
+        Self {
+            operation: ModifyOperation::Replace,
+            attribute: attribute.to_string(),
+            values,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModifyRequest {
+    pub dn: String,
+    pub changes: Vec<Modification>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Control {
+    pub oid: String,
+    pub critical: bool,
+    pub value: Option<Vec<u8>>,
+}
+
+/// Value of the simple paged results control. An empty cookie starts a
+/// search on requests and marks the last page on responses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PagedResults {
+    pub size: i64,
+    pub cookie: Vec<u8>,
+}
+
+impl PagedResults {
+    pub fn to_control(&self) -> Control {
+        let mut value = Vec::new();
+        write_constructed(&mut value, TAG_SEQUENCE, |out| {
+            write_integer(out, TAG_INTEGER, self.size);
+            write_octet_string(out, TAG_OCTET_STRING, &self.cookie);
+        });
+        Control {
+            oid: PAGED_RESULTS_OID.to_string(),
+            critical: false,
+            value: Some(value),
+        }
+    }
+
+    pub fn from_controls(controls: &[Control]) -> Result<Option<Self>> {
+        let Some(value) = controls
+            .iter()
+            .find(|control| control.oid == PAGED_RESULTS_OID)
+            .and_then(|control| control.value.as_deref())
+        else {
+            return Ok(None);
+        };
+        let mut outer = BerReader::new(value);
+        let mut reader = BerReader::new(outer.expect(TAG_SEQUENCE)?);
+        Ok(Some(Self {
+            size: reader.read_integer(TAG_INTEGER)?,
+            cookie: reader.expect(TAG_OCTET_STRING)?.to_vec(),
+        }))
+    }
+}
+
+impl LdapMessage {
+    pub fn new(message_id: i64, op: ProtocolOp) -> Self {
+        Self {
+            message_id,
+            op,
+            controls: Vec::new(),
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_constructed(&mut out, TAG_SEQUENCE, |out| {
+            write_integer(out, TAG_INTEGER, self.message_id);
+            encode_op(out, &self.op);
+            if !self.controls.is_empty() {
+                write_constructed(out, TAG_CONTROLS, |out| {
+                    for control in &self.controls {
+                        write_constructed(out, TAG_SEQUENCE, |out| {
+                            write_octet_string(out, TAG_OCTET_STRING, control.oid.as_bytes());
+                            if control.critical {
+                                write_boolean(out, true);
+                            }
+                            if let Some(value) = &control.value {
+                                write_octet_string(out, TAG_OCTET_STRING, value);
+                            }
+                        });
+                    }
+                });
+            }
+        });
+        out
+    }
+
+    /// Decodes one complete message, as framed by [`ber::element_length`].
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut outer = BerReader::new(bytes);
+        let mut reader = BerReader::new(outer.expect(TAG_SEQUENCE)?);
+        let message_id = reader.read_integer(TAG_INTEGER)?;
+        let (tag, content) = reader.read()?;
+        let op = decode_op(tag, content)?;
+        let mut controls = Vec::new();
+        if reader.peek_tag() == Some(TAG_CONTROLS) {
+            let mut list = BerReader::new(reader.expect(TAG_CONTROLS)?);
+            while !list.is_empty() {
+                let mut control = BerReader::new(list.expect(TAG_SEQUENCE)?);
+                let oid = control.read_string(TAG_OCTET_STRING)?;
+                let critical = if control.peek_tag() == Some(ber::TAG_BOOLEAN) {
+                    control.read_boolean()?
+                } else {
+                    false
+                };
+                let value = if control.is_empty() {
+                    None
+                } else {
+                    Some(control.expect(TAG_OCTET_STRING)?.to_vec())
+                };
+                controls.push(Control {
+                    oid,
+                    critical,
+                    value,
+                });
+            }
+        }
+        Ok(Self {
+            message_id,
+            op,
+            controls,
+        })
+    }
+}
+
+fn encode_op(out: &mut Vec<u8>, op: &ProtocolOp) {
+    match op {
+        ProtocolOp::BindRequest(request) => {
+            write_constructed(out, TAG_BIND_REQUEST, |out| {
+                write_integer(out, TAG_INTEGER, 3);
+                write_octet_string(out, TAG_OCTET_STRING, request.name.as_bytes());
+                write_octet_string(out, TAG_SIMPLE_AUTH, request.password.as_bytes());
+            });
+        }
+        ProtocolOp::BindResponse(result) => encode_result(out, TAG_BIND_RESPONSE, result),
+        ProtocolOp::UnbindRequest => ber::write_tlv(out, TAG_UNBIND_REQUEST, &[]),
+        ProtocolOp::SearchRequest(request) => {
+            write_constructed(out, TAG_SEARCH_REQUEST, |out| {
+                write_octet_string(out, TAG_OCTET_STRING, request.base_dn.as_bytes());
+                write_integer(out, TAG_ENUMERATED, request.scope.code());
+                // derefAliases: neverDerefAliases
+                write_integer(out, TAG_ENUMERATED, 0);
+                write_integer(out, TAG_INTEGER, request.size_limit);
+                write_integer(out, TAG_INTEGER, request.time_limit);
+                write_boolean(out, request.types_only);
+                request.filter.encode(out);
+                write_constructed(out, TAG_SEQUENCE, |out| {
+                    for attribute in &request.attributes {
+                        write_octet_string(out, TAG_OCTET_STRING, attribute.as_bytes());
+                    }
+                });
+            });
+        }
+        ProtocolOp::SearchResultEntry(entry) => {
+            write_constructed(out, TAG_SEARCH_RESULT_ENTRY, |out| {
+                write_octet_string(out, TAG_OCTET_STRING, entry.dn.as_bytes());
+                write_constructed(out, TAG_SEQUENCE, |out| {
+                    for attribute in &entry.attributes {
+                        encode_attribute(out, &attribute.name, &attribute.values);
+                    }
+                });
+            });
+        }
+        ProtocolOp::SearchResultReference(urls) => {
+            write_constructed(out, TAG_SEARCH_RESULT_REFERENCE, |out| {
+                for url in urls {
+                    write_octet_string(out, TAG_OCTET_STRING, url.as_bytes());
+                }
+            });
+        }
+        ProtocolOp::SearchResultDone(result) => encode_result(out, TAG_SEARCH_RESULT_DONE, result),
+        ProtocolOp::ModifyRequest(request) => {
+            write_constructed(out, TAG_MODIFY_REQUEST, |out| {
+                write_octet_string(out, TAG_OCTET_STRING, request.dn.as_bytes());
+                write_constructed(out, TAG_SEQUENCE, |out| {
+                    for change in &request.changes {
+                        write_constructed(out, TAG_SEQUENCE, |out| {
+                            let operation = match change.operation {
+                                ModifyOperation::Add => 0,
+                                ModifyOperation::Delete => 1,
+                                ModifyOperation::Replace => 2,
+                            };
+                            write_integer(out, TAG_ENUMERATED, operation);
+                            encode_attribute(out, &change.attribute, &change.values);
+                        });
+                    }
+                });
+            });
+        }
+        ProtocolOp::ModifyResponse(result) => encode_result(out, TAG_MODIFY_RESPONSE, result),
+        ProtocolOp::ExtendedResponse(result) => encode_result(out, TAG_EXTENDED_RESPONSE, result),
+    }
+}
+
+fn encode_result(out: &mut Vec<u8>, tag: u8, result: &LdapResult) {
+    write_constructed(out, tag, |out| {
+        write_integer(out, TAG_ENUMERATED, result.code);
+        write_octet_string(out, TAG_OCTET_STRING, result.matched_dn.as_bytes());
+        write_octet_string(out, TAG_OCTET_STRING, result.message.as_bytes());
+    });
+}
+
+fn encode_attribute(out: &mut Vec<u8>, name: &str, values: &[Vec<u8>]) {
+    write_constructed(out, TAG_SEQUENCE, |out| {
+        write_octet_string(out, TAG_OCTET_STRING, name.as_bytes());
+        write_constructed(out, TAG_SET, |out| {
+            for value in values {
+                write_octet_string(out, TAG_OCTET_STRING, value);
+            }
+        });
+    });
+}
+
+fn decode_op(tag: u8, content: &[u8]) -> Result<ProtocolOp> {
+    let mut reader = BerReader::new(content);
+    match tag {
+        TAG_BIND_REQUEST => {
+            let version = reader.read_integer(TAG_INTEGER)?;
+            if version != 3 {
+                return Err(malformed(format!("unsupported LDAP version {}", version)));
+            }
+            let name = reader.read_string(TAG_OCTET_STRING)?;
+            let password = reader.read_string(TAG_SIMPLE_AUTH)?;
+            Ok(ProtocolOp::BindRequest(BindRequest { name, password }))
+        }
+        TAG_BIND_RESPONSE => Ok(ProtocolOp::BindResponse(decode_result(&mut reader)?)),
+        TAG_UNBIND_REQUEST => Ok(ProtocolOp::UnbindRequest),
+        TAG_SEARCH_REQUEST => {
+            let base_dn = reader.read_string(TAG_OCTET_STRING)?;
+            let scope = SearchScope::from_code(reader.read_integer(TAG_ENUMERATED)?)?;
+            reader.read_integer(TAG_ENUMERATED)?;
+            let size_limit = reader.read_integer(TAG_INTEGER)?;
+            let time_limit = reader.read_integer(TAG_INTEGER)?;
+            let types_only = reader.read_boolean()?;
+            let (filter_tag, filter_content) = reader.read()?;
+            let filter = Filter::decode(filter_tag, filter_content)?;
+            let mut list = BerReader::new(reader.expect(TAG_SEQUENCE)?);
+            let mut attributes = Vec::new();
+            while !list.is_empty() {
+                attributes.push(list.read_string(TAG_OCTET_STRING)?);
+            }
+            Ok(ProtocolOp::SearchRequest(SearchRequest {
+                base_dn,
+                scope,
+                size_limit,
+                time_limit,
+                types_only,
+                filter,
+                attributes,
+            }))
+        }
+        TAG_SEARCH_RESULT_ENTRY => {
+            let dn = reader.read_string(TAG_OCTET_STRING)?;
+            let mut list = BerReader::new(reader.expect(TAG_SEQUENCE)?);
+            let mut attributes = Vec::new();
+            while !list.is_empty() {
+                let (name, values) = decode_attribute(list.expect(TAG_SEQUENCE)?)?;
+                attributes.push(EntryAttribute { name, values });
+            }
+            Ok(ProtocolOp::SearchResultEntry(SearchEntry {
+                dn,
+                attributes,
+            }))
+        }
+        TAG_SEARCH_RESULT_REFERENCE => {
+            let mut urls = Vec::new();
+            while !reader.is_empty() {
+                urls.push(reader.read_string(TAG_OCTET_STRING)?);
+            }
+            Ok(ProtocolOp::SearchResultReference(urls))
+        }
+        TAG_SEARCH_RESULT_DONE => Ok(ProtocolOp::SearchResultDone(decode_result(&mut reader)?)),
+        TAG_MODIFY_REQUEST => {
+            let dn = reader.read_string(TAG_OCTET_STRING)?;
+            let mut list = BerReader::new(reader.expect(TAG_SEQUENCE)?);
+            let mut changes = Vec::new();
+            while !list.is_empty() {
+                let mut change = BerReader::new(list.expect(TAG_SEQUENCE)?);
+                let operation = match change.read_integer(TAG_ENUMERATED)? {
+                    0 => ModifyOperation::Add,
+                    1 => ModifyOperation::Delete,
+                    2 => ModifyOperation::Replace,
+                    other => return Err(malformed(format!("unknown modify operation {}", other))),
+                };
+                let (attribute, values) = decode_attribute(change.expect(TAG_SEQUENCE)?)?;
+                changes.push(Modification {
+                    operation,
+                    attribute,
+                    values,
+                });
+            }
+            Ok(ProtocolOp::ModifyRequest(ModifyRequest { dn, changes }))
+        }
+        TAG_MODIFY_RESPONSE => Ok(ProtocolOp::ModifyResponse(decode_result(&mut reader)?)),
+        TAG_EXTENDED_RESPONSE => Ok(ProtocolOp::ExtendedResponse(decode_result(&mut reader)?)),
+        other => Err(malformed(format!(
+            "unsupported operation tag {:#04x}",
+            other
+        ))),
+    }
+}
+
+fn decode_result(reader: &mut BerReader<'_>) -> Result<LdapResult> {
+    let code = reader.read_integer(TAG_ENUMERATED)?;
+    let matched_dn = reader.read_string(TAG_OCTET_STRING)?;
+    let message = reader.read_string(TAG_OCTET_STRING)?;
+    if reader.peek_tag() == Some(TAG_REFERRAL) {
+        reader.read()?;
+    }
+    Ok(LdapResult {
+        code,
+        matched_dn,
+        message,
+    })
+}
+
+fn decode_attribute(content: &[u8]) -> Result<(String, Vec<Vec<u8>>)> {
+    let mut reader = BerReader::new(content);
+    let name = reader.read_string(TAG_OCTET_STRING)?;
+    let mut set = BerReader::new(reader.expect(TAG_SET)?);
+    let mut values = Vec::new();
+    while !set.is_empty() {
+        values.push(set.expect(TAG_OCTET_STRING)?.to_vec());
+    }
+    Ok((name, values))
+}
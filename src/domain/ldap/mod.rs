@@ -0,0 +1,61 @@
+//! LDAPv3 wire protocol: a BER codec, the protocol messages used by user
+//! federation and RFC 4515 search filters.
+
+pub mod ber;
+mod filter;
+mod message;
+
+#[cfg(test)]
+mod tests;
+
+pub use filter::{escape_filter_value, Filter};
+pub use message::{
+    BindRequest, Control, EntryAttribute, LdapMessage, LdapResult, Modification, ModifyOperation,
+    ModifyRequest, PagedResults, ProtocolOp, SearchEntry, SearchRequest, SearchScope,
+    PAGED_RESULTS_OID, RESULT_INSUFFICIENT_ACCESS_RIGHTS, RESULT_INVALID_CREDENTIALS,
+    RESULT_NO_SUCH_OBJECT, RESULT_SIZE_LIMIT_EXCEEDED, RESULT_SUCCESS, RESULT_UNWILLING_TO_PERFORM,
+};
+
+/// Canonical form for comparing distinguished names: lowercase, without
+/// the optional spaces around `,` and `=`. Escaped separators are kept.
+pub fn normalize_dn(dn: &str) -> String {
+    let mut normalized = String::with_capacity(dn.len());
+    let mut escaped = false;
+    let mut after_separator = false;
+    for ch in dn.trim().chars() {
+        if escaped {
+            normalized.extend(ch.to_lowercase());
+            escaped = false;
+            continue;
+        }
+        match ch {
+            ' ' if after_separator => continue,
+            '\\' => {
+                normalized.push(ch);
+                escaped = true;
+            }
+            ',' | '=' | '+' => {
+                while normalized.ends_with(' ') {
+                    normalized.pop();
+                }
+                normalized.push(ch);
+                after_separator = true;
+                continue;
+            }
+            other => normalized.extend(other.to_lowercase()),
+        }
+        after_separator = false;
+    }
+    normalized
+}
+
+/// Formats an Active Directory `objectGUID` (16 bytes, the first three
+/// fields little-endian) in its usual string form.
+pub fn format_object_guid(bytes: &[u8]) -> Option<String> {
+    let bytes: [u8; 16] = bytes.try_into().ok()?;
+    let reordered = [
+        bytes[3], bytes[2], bytes[1], bytes[0], bytes[5], bytes[4], bytes[7], bytes[6], bytes[8],
+        bytes[9], bytes[10], bytes[11], bytes[12], bytes[13], bytes[14], bytes[15],
+    ];
+    Some(uuid::Uuid::from_bytes(reordered).to_string())
+}
@@ -0,0 +1,212 @@
+use super::ber::{element_length, write_integer, BerReader, TAG_INTEGER};
+use super::*;
+
+fn round_trip(message: &LdapMessage) -> LdapMessage {
+    let bytes = message.encode();
+    assert_eq!(element_length(&bytes).unwrap(), Some(bytes.len()));
+    LdapMessage::decode(&bytes).expect("decode")
+}
+
+#[test]
+fn integers_use_minimal_twos_complement() {
+    for (value, expected) in [
+        (0_i64, vec![0x00]),
+        (127, vec![0x7f]),
+        (128, vec![0x00, 0x80]),
+        (256, vec![0x01, 0x00]),
+        (-1, vec![0xff]),
+        (-129, vec![0xff, 0x7f]),
+    ] {
+        let mut out = Vec::new();
+        write_integer(&mut out, TAG_INTEGER, value);
+        assert_eq!(&out[2..], expected.as_slice(), "{}", value);
+        assert_eq!(
+            BerReader::new(&out).read_integer(TAG_INTEGER).unwrap(),
+            value
+        );
+    }
+}
+
+#[test]
+fn frames_long_and_partial_messages() {
+    let entry = SearchEntry::new("uid=jdoe,ou=people,dc=example,dc=com")
+        .with_values("description", &[&"x".repeat(300)]);
+    let bytes = LdapMessage::new(7, ProtocolOp::SearchResultEntry(entry)).encode();
+    assert_eq!(bytes[1], 0x82);
+    assert_eq!(element_length(&bytes[..1]).unwrap(), None);
+    assert_eq!(element_length(&bytes[..10]).unwrap(), None);
+    assert_eq!(element_length(&bytes).unwrap(), Some(bytes.len()));
+}
+
+#[test]
+fn bind_and_modify_messages_round_trip() {
+    let bind = LdapMessage::new(
+        1,
+        ProtocolOp::BindRequest(BindRequest {
+            name: "cn=admin,dc=example,dc=com".to_string(),
+            password: "secret".to_string(),
+        }),
+    );
+    assert_eq!(round_trip(&bind), bind);
+
+    let response = LdapMessage::new(
+        1,
+        ProtocolOp::BindResponse(LdapResult::with_code(
+            RESULT_INVALID_CREDENTIALS,
+            "bad password",
+        )),
+    );
+    assert_eq!(round_trip(&response), response);
+
+    let modify = LdapMessage::new(
+        2,
+        ProtocolOp::ModifyRequest(ModifyRequest {
+            dn: "uid=jdoe,ou=people,dc=example,dc=com".to_string(),
+            changes: vec![Modification::replace("userPassword", vec![b"n3w".to_vec()])],
+        }),
+    );
+    assert_eq!(round_trip(&modify), modify);
+
+    let unbind = LdapMessage::new(3, ProtocolOp::UnbindRequest);
+    assert_eq!(round_trip(&unbind), unbind);
+}
+
+#[test]
+fn search_messages_round_trip_with_paging_controls() {
+    let mut search = LdapMessage::new(
+        4,
+        ProtocolOp::SearchRequest(SearchRequest::new(
+            "ou=people,dc=example,dc=com",
+            SearchScope::Subtree,
+            Filter::parse("(&(objectClass=person)(uid=j*))").unwrap(),
+            vec!["uid".to_string(), "mail".to_string()],
+        )),
+    );
+    search.controls.push(
+        PagedResults {
+            size: 500,
+            cookie: Vec::new(),
+        }
+        .to_control(),
+    );
+    let decoded = round_trip(&search);
+    assert_eq!(decoded, search);
+    assert_eq!(
+        PagedResults::from_controls(&decoded.controls).unwrap(),
+        Some(PagedResults {
+            size: 500,
+            cookie: Vec::new()
+        })
+    );
+
+    let entry = LdapMessage::new(
+        4,
+        ProtocolOp::SearchResultEntry(
+            SearchEntry::new("uid=jdoe,ou=people,dc=example,dc=com")
+                .with_values("uid", &["jdoe"])
+                .with_values("mail", &["jdoe@example.com", "j.doe@example.com"]),
+        ),
+    );
+    assert_eq!(round_trip(&entry), entry);
+
+    let done = LdapMessage::new(4, ProtocolOp::SearchResultDone(LdapResult::success()));
+    assert_eq!(round_trip(&done), done);
+}
+
+#[test]
+fn parses_and_prints_filters() {
+    let filter = Filter::parse(
+        "(&(objectClass=inetOrgPerson)(|(uid=jdoe)(mail=*@example.com))(!(cn=a\\2ab*c)))",
+    )
+    .unwrap();
+    assert_eq!(
+        filter,
+        Filter::And(vec![
+            Filter::equality("objectClass", "inetOrgPerson"),
+            Filter::Or(vec![
+                Filter::equality("uid", "jdoe"),
+                Filter::Substrings {
+                    attribute: "mail".to_string(),
+                    initial: None,
+                    any: Vec::new(),
+                    final_: Some(b"@example.com".to_vec()),
+                },
+            ]),
+            Filter::Not(Box::new(Filter::Substrings {
+                attribute: "cn".to_string(),
+                initial: Some(b"a*b".to_vec()),
+                any: Vec::new(),
+                final_: Some(b"c".to_vec()),
+            })),
+        ])
+    );
+    assert_eq!(
+        filter.to_string(),
+        "(&(objectClass=inetOrgPerson)(|(uid=jdoe)(mail=*@example.com))(!(cn=a\\2ab*c)))"
+    );
+    assert_eq!(
+        Filter::parse("uid=*").unwrap(),
+        Filter::Present("uid".to_string())
+    );
+    assert_eq!(
+        Filter::parse("(age>=30)").unwrap(),
+        Filter::GreaterOrEqual("age".to_string(), b"30".to_vec())
+    );
+
+    for invalid in [
+        "(uid=jdoe",
+        "(&)",
+        "(=x)",
+        "(uid>=a*)",
+        "(uid=\\zz)",
+        "(uid=a)b",
+    ] {
+        assert!(Filter::parse(invalid).is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn escaped_values_cannot_change_the_filter() {
+    let username = "jdoe)(uid=*";
+    let filter = Filter::parse(&format!("(uid={})", escape_filter_value(username))).unwrap();
+    assert_eq!(filter, Filter::equality("uid", username));
+    assert_eq!(escape_filter_value("a*b(c)\\"), "a\\2ab\\28c\\29\\5c");
+}
+
+#[test]
+fn filters_match_entries() {
+    let entry = SearchEntry::new("uid=jdoe,ou=people,dc=example,dc=com")
+        .with_values("objectClass", &["top", "inetOrgPerson"])
+        .with_values("uid", &["JDoe"])
+        .with_values("mail", &["jane.doe@example.com"]);
+    let matches = |filter: &str| Filter::parse(filter).unwrap().matches(&entry);
+
+    assert!(matches("(uid=jdoe)"));
+    assert!(matches(
+        "(&(objectClass=inetOrgPerson)(mail=jane*@example.com))"
+    ));
+    assert!(matches("(mail=*doe*)"));
+    assert!(matches("(|(uid=other)(mail=*))"));
+    assert!(!matches("(!(uid=jdoe))"));
+    assert!(!matches("(telephoneNumber=*)"));
+    assert!(!matches("(mail=*@example.org)"));
+}
+
+#[test]
+fn normalizes_distinguished_names() {
+    assert_eq!(
+        normalize_dn(" CN=Domain Admins , OU=Groups,DC=Example,DC=com "),
+        "cn=domain admins,ou=groups,dc=example,dc=com"
+    );
+    assert_eq!(
+        normalize_dn("cn=Smith\\, John,dc=x"),
+        "cn=smith\\, john,dc=x"
+    );
+}
+
+#[test]
+fn rejects_malformed_messages() {
+    assert!(LdapMessage::decode(&[0x30, 0x03, 0x02, 0x01]).is_err());
+    assert!(LdapMessage::decode(&[0x30, 0x05, 0x02, 0x01, 0x01, 0x7f, 0x00]).is_err());
+    assert!(element_length(&[0x30, 0x89, 0, 0, 0, 0, 0, 0, 0, 0, 0]).is_err());
+}
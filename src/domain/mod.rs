@@ -2,7 +2,10 @@ pub mod audit;
 pub mod auth_flow;
 pub mod auth_session;
 pub mod auth_session_action;
+pub mod claims;
+pub mod client_registration;
 pub mod compiler;
+pub mod consent_grant;
 pub mod crypto;
 pub mod events;
 pub mod execution;
@@ -12,6 +15,7 @@ pub mod harbor_job;
 pub mod harbor_job_conflict;
 pub mod identity_provider;
 pub mod invitation;
+pub mod ldap;
 pub mod log;
 pub mod login_attempt;
 pub mod oauth_start_attempt;
@@ -20,6 +24,7 @@ pub mod pagination;
 pub mod passkey_challenge;
 pub mod passkey_credential;
 pub mod passkey_runtime;
+pub mod password_policy;
 pub mod permissions;
 pub mod rbac;
 pub mod realm;
@@ -30,12 +35,18 @@ pub mod realm_recovery_settings;
 pub mod realm_security_headers;
 pub mod recovery_attempt;
 pub mod role;
+pub mod saml;
+pub mod scim;
 pub mod session;
+pub mod signing_key;
+pub mod sms_otp;
 pub mod telemetry;
 pub mod theme;
 pub mod theme_pages;
+pub mod totp_credential;
 pub mod ui;
 pub mod user;
 pub mod user_email;
+pub mod user_federation;
 pub mod user_phone_number;
 pub mod webhook;
@@ -1,9 +1,57 @@
+use crate::domain::signing_key::SigningAlgorithm;
 use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use base64::Engine;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
+/// How a client authenticates at the token endpoint (RFC 7591, Section 2).
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenEndpointAuthMethod {
+    /// Public client: identified by `client_id` alone.
+    #[default]
+    None,
+    ClientSecretBasic,
+    ClientSecretPost,
+    PrivateKeyJwt,
+}
+
+impl TokenEndpointAuthMethod {
+    pub const ALL: [Self; 4] = [
+        Self::None,
+        Self::ClientSecretBasic,
+        Self::ClientSecretPost,
+        Self::PrivateKeyJwt,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::ClientSecretBasic => "client_secret_basic",
+            Self::ClientSecretPost => "client_secret_post",
+            Self::PrivateKeyJwt => "private_key_jwt",
+        }
+    }
+}
+
+impl std::fmt::Display for TokenEndpointAuthMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<String> for TokenEndpointAuthMethod {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::ALL
+            .into_iter()
+            .find(|method| method.as_str() == value)
+            .ok_or_else(|| format!("Unsupported token_endpoint_auth_method: {}", value))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, sqlx::FromRow)]
 pub struct OidcClient {
     #[sqlx(try_from = "String")]
@@ -16,6 +64,140 @@ pub struct OidcClient {
     pub scopes: String,        // Stored as JSON array string
     pub web_origins: String,   // JSON array string (e.g. ["http://localhost:6565"])
     pub managed_by_config: bool,
+    #[sqlx(try_from = "String")]
+    pub token_endpoint_auth_method: TokenEndpointAuthMethod,
+    pub jwks: Option<String>, // JWK Set JSON used to verify `private_key_jwt` assertions
+    /// Overrides the realm's token signing algorithm for this client.
+    pub signing_algorithm: Option<SigningAlgorithm>,
+    /// Receives signed logout tokens when one of the client's sessions ends.
+    pub backchannel_logout_uri: Option<String>,
+    /// Loaded in an iframe by the end-session page.
+    pub frontchannel_logout_uri: Option<String>,
+    /// JSON array of `TokenExchangeRule`s: the audiences this client may
+    /// exchange tokens toward. `None` disables token exchange.
+    pub token_exchange_policy: Option<String>,
+    /// Allows `grant_type=password`, which runs the realm's direct grant flow.
+    pub direct_grant_enabled: bool,
+    /// Rejects authorization requests that were not pushed to the PAR
+    /// endpoint first (RFC 9126, Section 6).
+    pub require_pushed_authorization_requests: bool,
+}
+
+impl OidcClient {
+    /// Parses `token_exchange_policy`; a client without one has no rules.
+    pub fn token_exchange_rules(&self) -> Result<Vec<TokenExchangeRule>, serde_json::Error> {
+        match self.token_exchange_policy.as_deref() {
+            Some(raw) => serde_json::from_str(raw),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+/// `grant_type` of an RFC 8693 token exchange request.
+pub const TOKEN_EXCHANGE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:token-exchange";
+/// RFC 8693 token type identifier for access tokens, the only type exchanged.
+pub const ACCESS_TOKEN_TYPE: &str = "urn:ietf:params:oauth:token-type:access_token";
+
+/// Allows a client to exchange tokens toward `audience` (another client's
+/// `client_id`), requesting at most `scopes`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TokenExchangeRule {
+    pub audience: String,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+}
+
+/// `grant_type` a device polls the token endpoint with (RFC 8628, Section 3.4).
+pub const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// User codes avoid vowels (no accidental words) and look-alike characters.
+const USER_CODE_ALPHABET: &[u8] = b"BCDFGHJKLMNPQRSTVWXZ";
+const USER_CODE_LENGTH: usize = 8;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceAuthorizationStatus {
+    Pending,
+    Approved,
+    Denied,
+}
+
+impl DeviceAuthorizationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Denied => "denied",
+        }
+    }
+}
+
+impl TryFrom<String> for DeviceAuthorizationStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        match value.as_str() {
+            "pending" => Ok(Self::Pending),
+            "approved" => Ok(Self::Approved),
+            "denied" => Ok(Self::Denied),
+            other => Err(format!("Unknown device authorization status: {}", other)),
+        }
+    }
+}
+
+/// An RFC 8628 device authorization. The device polls with `device_code`
+/// while the user approves `user_code` in a browser.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceAuthorization {
+    pub device_code: String,
+    /// Stored normalized; see [`format_user_code`] for display.
+    pub user_code: String,
+    pub realm_id: Uuid,
+    pub client_id: String,
+    pub scope: Option<String>,
+    pub status: DeviceAuthorizationStatus,
+    pub user_id: Option<Uuid>,
+    /// Minimum seconds between polls; raised on every `slow_down`.
+    pub interval_secs: i64,
+    pub last_polled_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Login-session context of a device verification, stored under `"device"`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceContext {
+    pub device_code: String,
+    pub client_id: String,
+}
+
+pub fn generate_user_code() -> String {
+    use rand::RngExt;
+    let mut rng = rand::rng();
+    (0..USER_CODE_LENGTH)
+        .map(|_| USER_CODE_ALPHABET[rng.random_range(0..USER_CODE_ALPHABET.len())] as char)
+        .collect()
+}
+
+/// Normalizes a user code as typed (any case, dashes and spaces allowed).
+/// Returns `None` if it cannot be a code we issued.
+pub fn normalize_user_code(input: &str) -> Option<String> {
+    let code: String = input
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    let valid =
+        code.len() == USER_CODE_LENGTH && code.bytes().all(|b| USER_CODE_ALPHABET.contains(&b));
+    valid.then_some(code)
+}
+
+/// Formats a normalized user code as `XXXX-XXXX`.
+pub fn format_user_code(code: &str) -> String {
+    if code.len() != USER_CODE_LENGTH {
+        return code.to_string();
+    }
+    format!("{}-{}", &code[..4], &code[4..])
 }
 
 /// Aggregate counts for the Clients analytics cards.
@@ -47,6 +229,12 @@ pub struct AuthCode {
     pub code_challenge: Option<String>,
     pub code_challenge_method: String,
     pub expires_at: chrono::DateTime<chrono::Utc>,
+    /// Scope and `claims` request granted with the code, carried into the
+    /// session it is exchanged for.
+    #[sqlx(default)]
+    pub scope: Option<String>,
+    #[sqlx(default)]
+    pub claims: Option<String>,
 }
 
 /// Verifies the PKCE code challenge.
@@ -74,9 +262,13 @@ pub struct OidcContext {
     pub nonce: Option<String>,
     pub code_challenge: Option<String>,
     pub code_challenge_method: Option<String>,
+    #[serde(default)]
+    pub claims: Option<String>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+/// A resolved authorization request: the parameters `/authorize` acts on,
+/// whether they arrived in the query, a request object or a pushed request.
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct OidcRequest {
     pub client_id: String,
     pub redirect_uri: String,
@@ -86,6 +278,131 @@ pub struct OidcRequest {
     pub nonce: Option<String>,
     pub code_challenge: Option<String>,
     pub code_challenge_method: Option<String>,
+    /// The OIDC `claims` parameter as JSON.
+    #[serde(default)]
+    pub claims: Option<String>,
+}
+
+/// Authorization request parameters as sent to `/authorize` or the PAR
+/// endpoint. `request` is a signed request object (RFC 9101) and
+/// `request_uri` a reference returned by the PAR endpoint (RFC 9126).
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct AuthorizationRequestParams {
+    pub client_id: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub response_type: Option<String>,
+    pub scope: Option<String>,
+    pub state: Option<String>,
+    pub nonce: Option<String>,
+    pub code_challenge: Option<String>,
+    pub code_challenge_method: Option<String>,
+    /// JSON text in a query or form, a JSON object in a request object.
+    #[serde(default, deserialize_with = "deserialize_claims_param")]
+    pub claims: Option<String>,
+    pub request: Option<String>,
+    pub request_uri: Option<String>,
+}
+
+fn deserialize_claims_param<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(
+        match Option::<serde_json::Value>::deserialize(deserializer)? {
+            None | Some(serde_json::Value::Null) => None,
+            Some(serde_json::Value::String(raw)) => Some(raw),
+            Some(value) => Some(value.to_string()),
+        },
+    )
+}
+
+impl AuthorizationRequestParams {
+    /// Whether the parameters come from a request object or pushed request
+    /// rather than the query itself.
+    pub fn is_by_reference(&self) -> bool {
+        self.request.is_some() || self.request_uri.is_some()
+    }
+}
+
+/// Prefix of the `request_uri` values issued by the PAR endpoint.
+pub const PAR_REQUEST_URI_PREFIX: &str = "urn:ietf:params:oauth:request_uri:";
+
+/// An RFC 9126 pushed authorization request, redeemable once at `/authorize`
+/// until `expires_at`.
+#[derive(Debug, Clone)]
+pub struct PushedAuthorizationRequest {
+    pub request_uri: String,
+    pub realm_id: Uuid,
+    pub client_id: String,
+    pub request: OidcRequest,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Client credentials as presented at the token endpoint.
+#[derive(Debug, Clone)]
+pub enum ClientAuthentication {
+    None {
+        client_id: String,
+    },
+    SecretBasic {
+        client_id: String,
+        client_secret: String,
+    },
+    SecretPost {
+        client_id: String,
+        client_secret: String,
+    },
+    /// RFC 7523 client assertion. `client_id` may be omitted, in which case
+    /// the assertion's `sub` identifies the client.
+    PrivateKeyJwt {
+        client_id: Option<String>,
+        assertion: String,
+        /// Values accepted in the assertion's `aud` claim.
+        audiences: Vec<String>,
+    },
+}
+
+impl ClientAuthentication {
+    pub fn method(&self) -> TokenEndpointAuthMethod {
+        match self {
+            Self::None { .. } => TokenEndpointAuthMethod::None,
+            Self::SecretBasic { .. } => TokenEndpointAuthMethod::ClientSecretBasic,
+            Self::SecretPost { .. } => TokenEndpointAuthMethod::ClientSecretPost,
+            Self::PrivateKeyJwt { .. } => TokenEndpointAuthMethod::PrivateKeyJwt,
+        }
+    }
+}
+
+/// Outbox event type for a pending Back-Channel Logout request.
+pub const BACKCHANNEL_LOGOUT_EVENT_TYPE: &str = "oidc.backchannel_logout";
+
+/// Outbox payload for one Back-Channel Logout request. The logout token is
+/// signed by the worker at delivery time, so a retry never sends an expired one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackchannelLogoutDelivery {
+    pub client_id: String,
+    pub backchannel_logout_uri: String,
+    pub sub: Uuid,
+    pub sid: Uuid,
+}
+
+/// RP-initiated logout parameters, accepted as query or form fields.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct EndSessionRequest {
+    pub id_token_hint: Option<String>,
+    pub client_id: Option<String>,
+    pub post_logout_redirect_uri: Option<String>,
+    pub state: Option<String>,
+}
+
+/// Appends the `iss` and `sid` parameters a front-channel logout URI expects.
+pub fn frontchannel_logout_url(uri: &str, issuer: &str, sid: Uuid) -> Option<String> {
+    let mut url = url::Url::parse(uri).ok()?;
+    url.query_pairs_mut()
+        .append_pair("iss", issuer)
+        .append_pair("sid", &sid.to_string());
+    Some(url.to_string())
 }
 
 #[cfg(test)]
@@ -112,6 +429,53 @@ mod tests {
         assert!(!verify_pkce_challenge(challenge, verifier));
     }
 
+    #[test]
+    fn token_endpoint_auth_method_round_trips_through_string() {
+        for method in TokenEndpointAuthMethod::ALL {
+            let parsed = TokenEndpointAuthMethod::try_from(method.as_str().to_string())
+                .expect("known method");
+            assert_eq!(parsed, method);
+        }
+        assert!(TokenEndpointAuthMethod::try_from("client_secret_jwt".to_string()).is_err());
+    }
+
+    #[test]
+    fn frontchannel_logout_url_keeps_existing_query() {
+        let sid = Uuid::new_v4();
+        let url = frontchannel_logout_url(
+            "https://app.example.com/logout?tenant=a",
+            "https://id.example.com",
+            sid,
+        )
+        .expect("valid uri");
+
+        assert_eq!(
+            url,
+            format!(
+                "https://app.example.com/logout?tenant=a&iss=https%3A%2F%2Fid.example.com&sid={}",
+                sid
+            )
+        );
+        assert!(frontchannel_logout_url("not a url", "https://id.example.com", sid).is_none());
+    }
+
+    #[test]
+    fn user_codes_normalize_from_typed_input() {
+        let code = generate_user_code();
+        assert_eq!(normalize_user_code(&code).as_deref(), Some(code.as_str()));
+
+        let display = format_user_code("BCDFGHJK");
+        assert_eq!(display, "BCDF-GHJK");
+        assert_eq!(
+            normalize_user_code(" bcdf-ghjk ").as_deref(),
+            Some("BCDFGHJK")
+        );
+        // Vowels and digits are never issued.
+        assert!(normalize_user_code("ABCD-EFGH").is_none());
+        assert!(normalize_user_code("BCDF-GHJ1").is_none());
+        assert!(normalize_user_code("BCDF").is_none());
+    }
+
     #[tokio::test]
     async fn oidc_models_from_row_parse_fields() {
         let pool = SqlitePool::connect("sqlite::memory:")
@@ -122,7 +486,7 @@ mod tests {
         let client_id = Uuid::new_v4();
         let realm_id = Uuid::new_v4();
         let client: OidcClient = sqlx::query_as(
-        "SELECT ? as id, ? as realm_id, ? as client_id, ? as client_secret, ? as redirect_uris, ? as scopes, ? as web_origins, ? as managed_by_config",
+        "SELECT ? as id, ? as realm_id, ? as client_id, ? as client_secret, ? as redirect_uris, ? as scopes, ? as web_origins, ? as managed_by_config, ? as token_endpoint_auth_method, ? as jwks, ? as signing_algorithm, ? as backchannel_logout_uri, ? as frontchannel_logout_uri, ? as token_exchange_policy, ? as direct_grant_enabled, ? as require_pushed_authorization_requests",
     )
     .bind(client_id.to_string())
     .bind(realm_id.to_string())
@@ -132,6 +496,14 @@ mod tests {
     .bind("[\"openid\"]")
     .bind("[\"http://localhost:3000\"]")
     .bind(true)
+    .bind("private_key_jwt")
+    .bind("{\"keys\":[]}")
+    .bind("ES384")
+    .bind("https://app.example.com/backchannel-logout")
+    .bind(Option::<String>::None)
+    .bind("[{\"audience\":\"orders-api\",\"scopes\":[\"orders:read\"]}]")
+    .bind(true)
+    .bind(false)
     .fetch_one(&pool)
     .await
     .expect("client row");
@@ -140,6 +512,26 @@ mod tests {
         assert_eq!(client.realm_id, realm_id);
         assert_eq!(client.client_id, "client");
         assert!(client.managed_by_config);
+        assert_eq!(
+            client.token_endpoint_auth_method,
+            TokenEndpointAuthMethod::PrivateKeyJwt
+        );
+        assert_eq!(client.jwks.as_deref(), Some("{\"keys\":[]}"));
+        assert_eq!(client.signing_algorithm, Some(SigningAlgorithm::Es384));
+        assert_eq!(
+            client.backchannel_logout_uri.as_deref(),
+            Some("https://app.example.com/backchannel-logout")
+        );
+        assert!(client.frontchannel_logout_uri.is_none());
+        assert_eq!(
+            client.token_exchange_rules().expect("policy"),
+            vec![TokenExchangeRule {
+                audience: "orders-api".to_string(),
+                scopes: vec!["orders:read".to_string()],
+            }]
+        );
+        assert!(client.direct_grant_enabled);
+        assert!(!client.require_pushed_authorization_requests);
 
         let user_id = Uuid::new_v4();
         let auth_code: AuthCode = sqlx::query_as(
@@ -0,0 +1,319 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Upper bound for `max_length`; also the cap applied when policies are skipped.
+pub const PASSWORD_LENGTH_LIMIT: i64 = 1024;
+/// Upper bound for `history_count`.
+pub const PASSWORD_HISTORY_LIMIT: i64 = 24;
+
+/// Usernames shorter than this are not checked for similarity; every password
+/// would "contain" a one or two letter name.
+const MIN_SIMILAR_USERNAME_CHARS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PasswordPolicy {
+    pub realm_id: Uuid,
+    pub min_length: i64,
+    pub max_length: i64,
+    pub require_lowercase: bool,
+    pub require_uppercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    /// Rejects passwords containing the username (or its reverse, or the local
+    /// part of an email-style username).
+    pub disallow_username: bool,
+    /// Number of previous passwords, the current one included, that cannot be
+    /// reused. 0 disables the check.
+    pub history_count: i64,
+    /// Days after which a successful password login sets
+    /// `force_password_reset`. 0 disables expiry.
+    pub max_age_days: i64,
+    /// Rejects passwords found in the configured breached password list.
+    pub check_breached: bool,
+}
+
+impl PasswordPolicy {
+    pub fn defaults(realm_id: Uuid) -> Self {
+        Self {
+            realm_id,
+            min_length: 8,
+            max_length: 100,
+            require_lowercase: false,
+            require_uppercase: false,
+            require_digit: false,
+            require_symbol: false,
+            disallow_username: false,
+            history_count: 0,
+            max_age_days: 0,
+            check_breached: false,
+        }
+    }
+
+    /// Runs the checks that need nothing but the password itself. History and
+    /// breach checks need storage and live in `PasswordPolicyService`.
+    pub fn check(&self, password: &str, username: Option<&str>) -> Vec<PasswordPolicyViolation> {
+        let mut violations = Vec::new();
+        let length = password.chars().count() as i64;
+
+        if length < self.min_length {
+            violations.push(self.violation(PasswordViolationCode::TooShort));
+        }
+        if length > self.max_length {
+            violations.push(self.violation(PasswordViolationCode::TooLong));
+        }
+        if self.require_lowercase && !password.chars().any(char::is_lowercase) {
+            violations.push(self.violation(PasswordViolationCode::MissingLowercase));
+        }
+        if self.require_uppercase && !password.chars().any(char::is_uppercase) {
+            violations.push(self.violation(PasswordViolationCode::MissingUppercase));
+        }
+        if self.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            violations.push(self.violation(PasswordViolationCode::MissingDigit));
+        }
+        if self.require_symbol && !password.chars().any(is_symbol) {
+            violations.push(self.violation(PasswordViolationCode::MissingSymbol));
+        }
+        if self.disallow_username {
+            if let Some(username) = username {
+                if resembles_username(password, username) {
+                    violations.push(self.violation(PasswordViolationCode::ContainsUsername));
+                }
+            }
+        }
+
+        violations
+    }
+
+    pub fn violation(&self, code: PasswordViolationCode) -> PasswordPolicyViolation {
+        let message = match code {
+            PasswordViolationCode::TooShort => {
+                format!("Password must be at least {} characters", self.min_length)
+            }
+            PasswordViolationCode::TooLong => {
+                format!(
+                    "Password must be no more than {} characters",
+                    self.max_length
+                )
+            }
+            PasswordViolationCode::MissingLowercase => {
+                "Password must contain a lowercase letter".to_string()
+            }
+            PasswordViolationCode::MissingUppercase => {
+                "Password must contain an uppercase letter".to_string()
+            }
+            PasswordViolationCode::MissingDigit => "Password must contain a digit".to_string(),
+            PasswordViolationCode::MissingSymbol => "Password must contain a symbol".to_string(),
+            PasswordViolationCode::ContainsUsername => {
+                "Password must not contain the username".to_string()
+            }
+            PasswordViolationCode::RecentlyUsed => format!(
+                "Password must differ from the last {} passwords",
+                self.history_count
+            ),
+            PasswordViolationCode::Breached => {
+                "Password appears in a list of breached passwords".to_string()
+            }
+        };
+        PasswordPolicyViolation { code, message }
+    }
+
+    /// Whether a password last set at `changed_at` has outlived `max_age_days`.
+    pub fn is_expired(&self, changed_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        self.max_age_days > 0 && now - changed_at >= Duration::days(self.max_age_days)
+    }
+
+    /// One-line summary of the composition rules for login pages.
+    pub fn hint(&self) -> String {
+        let mut classes = Vec::new();
+        if self.require_lowercase {
+            classes.push("a lowercase letter");
+        }
+        if self.require_uppercase {
+            classes.push("an uppercase letter");
+        }
+        if self.require_digit {
+            classes.push("a digit");
+        }
+        if self.require_symbol {
+            classes.push("a symbol");
+        }
+
+        let mut hint = format!("Use at least {} characters", self.min_length);
+        match classes.as_slice() {
+            [] => {}
+            [only] => hint.push_str(&format!(", including {}", only)),
+            [rest @ .., last] => {
+                hint.push_str(&format!(", including {} and {}", rest.join(", "), last))
+            }
+        }
+        hint.push('.');
+        hint
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PasswordViolationCode {
+    TooShort,
+    TooLong,
+    MissingLowercase,
+    MissingUppercase,
+    MissingDigit,
+    MissingSymbol,
+    ContainsUsername,
+    RecentlyUsed,
+    Breached,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PasswordPolicyViolation {
+    pub code: PasswordViolationCode,
+    pub message: String,
+}
+
+/// A previously set password, kept for reuse checks and to date the current one.
+#[derive(Debug, Clone)]
+pub struct PasswordHistoryEntry {
+    pub id: Uuid,
+    pub realm_id: Uuid,
+    pub user_id: Uuid,
+    pub hashed_password: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PasswordHistoryEntry {
+    pub fn new(realm_id: Uuid, user_id: Uuid, hashed_password: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            realm_id,
+            user_id,
+            hashed_password,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// Joins violation messages into the single `error` string shown by pages
+/// that do not render `password_violations` individually.
+pub fn summarize_violations(violations: &[PasswordPolicyViolation]) -> String {
+    violations
+        .iter()
+        .map(|violation| violation.message.as_str())
+        .collect::<Vec<_>>()
+        .join(". ")
+}
+
+fn is_symbol(c: char) -> bool {
+    !c.is_alphanumeric() && !c.is_whitespace()
+}
+
+fn resembles_username(password: &str, username: &str) -> bool {
+    let password = password.to_lowercase();
+    let username = username.trim().to_lowercase();
+    let local_part = username.split('@').next().unwrap_or_default();
+
+    let candidates = [username.as_str(), local_part];
+    candidates
+        .iter()
+        .filter(|candidate| candidate.chars().count() >= MIN_SIMILAR_USERNAME_CHARS)
+        .any(|candidate| {
+            let reversed: String = candidate.chars().rev().collect();
+            password.contains(candidate) || password.contains(&reversed)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(violations: &[PasswordPolicyViolation]) -> Vec<PasswordViolationCode> {
+        violations.iter().map(|violation| violation.code).collect()
+    }
+
+    #[test]
+    fn defaults_only_enforce_length() {
+        let policy = PasswordPolicy::defaults(Uuid::new_v4());
+
+        assert!(policy.check("password", Some("password")).is_empty());
+        assert_eq!(
+            codes(&policy.check("short", None)),
+            vec![PasswordViolationCode::TooShort]
+        );
+        assert_eq!(
+            codes(&policy.check(&"a".repeat(101), None)),
+            vec![PasswordViolationCode::TooLong]
+        );
+    }
+
+    #[test]
+    fn character_classes_are_reported_individually() {
+        let policy = PasswordPolicy {
+            require_lowercase: true,
+            require_uppercase: true,
+            require_digit: true,
+            require_symbol: true,
+            ..PasswordPolicy::defaults(Uuid::new_v4())
+        };
+
+        assert_eq!(
+            codes(&policy.check("alllowercase", None)),
+            vec![
+                PasswordViolationCode::MissingUppercase,
+                PasswordViolationCode::MissingDigit,
+                PasswordViolationCode::MissingSymbol,
+            ]
+        );
+        assert!(policy.check("Str0ng!pass", None).is_empty());
+        // Whitespace does not count as a symbol.
+        assert_eq!(
+            codes(&policy.check("Str0ng pass", None)),
+            vec![PasswordViolationCode::MissingSymbol]
+        );
+    }
+
+    #[test]
+    fn username_similarity_checks_reverse_and_email_local_part() {
+        let policy = PasswordPolicy {
+            disallow_username: true,
+            ..PasswordPolicy::defaults(Uuid::new_v4())
+        };
+        let contains = |password: &str, username: &str| {
+            codes(&policy.check(password, Some(username)))
+                .contains(&PasswordViolationCode::ContainsUsername)
+        };
+
+        assert!(contains("my-Alice-2024", "alice"));
+        assert!(contains("ecila-rules", "alice"));
+        assert!(contains("alice.smith99", "alice.smith@example.com"));
+        assert!(!contains("correct-horse", "alice"));
+        // Very short usernames would match almost anything.
+        assert!(!contains("bobsled-racing", "bo"));
+    }
+
+    #[test]
+    fn expiry_is_disabled_at_zero_days() {
+        let now = Utc::now();
+        let mut policy = PasswordPolicy::defaults(Uuid::new_v4());
+        assert!(!policy.is_expired(now - Duration::days(3650), now));
+
+        policy.max_age_days = 90;
+        assert!(!policy.is_expired(now - Duration::days(89), now));
+        assert!(policy.is_expired(now - Duration::days(90), now));
+    }
+
+    #[test]
+    fn hint_lists_required_classes() {
+        let mut policy = PasswordPolicy::defaults(Uuid::new_v4());
+        assert_eq!(policy.hint(), "Use at least 8 characters.");
+
+        policy.min_length = 12;
+        policy.require_uppercase = true;
+        policy.require_digit = true;
+        policy.require_symbol = true;
+        assert_eq!(
+            policy.hint(),
+            "Use at least 12 characters, including an uppercase letter, a digit and a symbol."
+        );
+    }
+}
@@ -1,4 +1,6 @@
 use serde::Serialize;
+use std::fmt;
+use uuid::Uuid;
 
 // The Raw Strings (For use in backend guards: require_permission(REALM_READ))
 pub const REALM_READ: &str = "realm:read";
@@ -177,6 +179,141 @@ pub fn is_system_permission(permission: &str) -> bool {
         .any(|p| p.id == permission)
 }
 
+/// Where a granted permission applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PermissionScope {
+    /// Every realm. This is what a plain permission string grants.
+    Global,
+    Realm(Uuid),
+    Client(Uuid),
+    /// Users in the group and its descendants, and those groups themselves.
+    Group(Uuid),
+}
+
+/// A permission plus the resource it is limited to.
+///
+/// Scoped grants are stored next to plain ones in a role's permissions as
+/// `permission@kind:id`, for example `user:write@group:<uuid>` or
+/// `*@realm:<uuid>`. Plain strings stay global.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PermissionGrant {
+    pub permission: String,
+    pub scope: PermissionScope,
+}
+
+impl PermissionGrant {
+    pub fn scoped(permission: &str, scope: PermissionScope) -> Self {
+        Self {
+            permission: permission.to_string(),
+            scope,
+        }
+    }
+
+    /// Parses a stored permission. Returns `None` for a malformed scope.
+    pub fn parse(value: &str) -> Option<Self> {
+        let Some((permission, scope)) = value.split_once('@') else {
+            return Some(Self::scoped(value, PermissionScope::Global));
+        };
+        let (kind, id) = scope.split_once(':')?;
+        let id = Uuid::parse_str(id).ok()?;
+        let scope = match kind {
+            "realm" => PermissionScope::Realm(id),
+            "client" => PermissionScope::Client(id),
+            "group" => PermissionScope::Group(id),
+            _ => return None,
+        };
+        if permission.is_empty() {
+            return None;
+        }
+        Some(Self::scoped(permission, scope))
+    }
+
+    /// True when this grant's permission (ignoring scope) includes `permission`,
+    /// directly, through a `resource:*` wildcard or through `*`.
+    pub fn includes(&self, permission: &str) -> bool {
+        if self.permission == ALL || self.permission == permission {
+            return true;
+        }
+        match (
+            self.permission.strip_suffix(":*"),
+            permission.split_once(':'),
+        ) {
+            (Some(granted), Some((resource, _))) => granted == resource,
+            _ => false,
+        }
+    }
+
+    /// Why this grant may not be given to a realm role, if it may not. Only
+    /// system permissions can be scoped, and narrow scopes only carry the
+    /// permissions that make sense for their resource.
+    pub fn scope_violation(&self) -> Option<&'static str> {
+        if self.scope == PermissionScope::Global {
+            return None;
+        }
+        if !is_system_permission(&self.permission) {
+            return Some("Only system permissions can be scoped");
+        }
+        match self.scope {
+            PermissionScope::Client(_)
+                if !matches!(
+                    self.permission.as_str(),
+                    CLIENT_READ | CLIENT_UPDATE | CLIENT_DELETE
+                ) =>
+            {
+                Some("Client scopes only carry client read, update and delete permissions")
+            }
+            PermissionScope::Group(_)
+                if !(self.permission.starts_with("user:")
+                    || matches!(self.permission.as_str(), RBAC_READ | RBAC_WRITE)) =>
+            {
+                Some("Group scopes only carry user and RBAC permissions")
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PermissionGrant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.scope {
+            PermissionScope::Global => write!(f, "{}", self.permission),
+            PermissionScope::Realm(id) => write!(f, "{}@realm:{}", self.permission, id),
+            PermissionScope::Client(id) => write!(f, "{}@client:{}", self.permission, id),
+            PermissionScope::Group(id) => write!(f, "{}@group:{}", self.permission, id),
+        }
+    }
+}
+
+/// The admin resource a request acts on, used to match scoped grants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdminResource {
+    /// Realm-wide operations such as listing users or creating clients.
+    Realm(Uuid),
+    Client {
+        realm_id: Uuid,
+        client_id: Uuid,
+    },
+    User {
+        realm_id: Uuid,
+        user_id: Uuid,
+    },
+    Group {
+        realm_id: Uuid,
+        group_id: Uuid,
+    },
+}
+
+impl AdminResource {
+    pub fn realm_id(&self) -> Uuid {
+        match *self {
+            AdminResource::Realm(realm_id)
+            | AdminResource::Client { realm_id, .. }
+            | AdminResource::User { realm_id, .. }
+            | AdminResource::Group { realm_id, .. } => realm_id,
+        }
+    }
+}
+
 // Helper to shorten the vector construction
 fn p(id: &str, name: &str, desc: &str) -> PermissionDef {
     PermissionDef {
@@ -200,4 +337,30 @@ mod tests {
     fn non_system_permissions_are_rejected() {
         assert!(!is_system_permission("custom:perm"));
     }
+
+    #[test]
+    fn scoped_grants_round_trip_and_validate() {
+        let group_id = Uuid::new_v4();
+        let encoded = format!("user:write@group:{}", group_id);
+        let grant = PermissionGrant::parse(&encoded).expect("grant");
+        assert_eq!(grant.scope, PermissionScope::Group(group_id));
+        assert_eq!(grant.to_string(), encoded);
+        assert!(grant.includes(USER_WRITE));
+        assert!(!grant.includes(USER_READ));
+        assert!(grant.scope_violation().is_none());
+
+        assert_eq!(
+            PermissionGrant::parse(REALM_READ).map(|grant| grant.scope),
+            Some(PermissionScope::Global)
+        );
+        assert!(PermissionGrant::parse("user:write@group:nope").is_none());
+        assert!(PermissionGrant::parse(&format!("user:write@team:{}", group_id)).is_none());
+
+        let client_create =
+            PermissionGrant::scoped(CLIENT_CREATE, PermissionScope::Client(Uuid::new_v4()));
+        assert!(client_create.scope_violation().is_some());
+        let realm_all = PermissionGrant::scoped(ALL, PermissionScope::Realm(Uuid::new_v4()));
+        assert!(realm_all.includes(CLIENT_CREATE));
+        assert!(realm_all.scope_violation().is_none());
+    }
 }
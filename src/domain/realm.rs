@@ -1,3 +1,4 @@
+use crate::domain::signing_key::SigningAlgorithm;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -73,6 +74,8 @@ pub struct Realm {
     pub access_token_ttl_secs: i64,
     pub refresh_token_ttl_secs: i64,
     pub pkce_required_public_clients: bool,
+    /// Algorithm for access and ID tokens; clients may override it.
+    pub signing_algorithm: SigningAlgorithm,
     pub lockout_threshold: i64,
     pub lockout_duration_secs: i64,
     pub is_system: bool,
@@ -117,6 +120,7 @@ mod tests {
             access_token_ttl_secs: 3600,
             refresh_token_ttl_secs: 7200,
             pkce_required_public_clients: true,
+            signing_algorithm: SigningAlgorithm::Es256,
             lockout_threshold: 5,
             lockout_duration_secs: 900,
             is_system: false,
@@ -147,6 +151,7 @@ mod tests {
             decoded.pkce_required_public_clients,
             realm.pkce_required_public_clients
         );
+        assert_eq!(decoded.signing_algorithm, realm.signing_algorithm);
         assert_eq!(decoded.lockout_threshold, realm.lockout_threshold);
         assert_eq!(decoded.lockout_duration_secs, realm.lockout_duration_secs);
         assert_eq!(decoded.browser_flow_id, realm.browser_flow_id);
@@ -160,6 +165,7 @@ mod tests {
             access_token_ttl_secs: 3600,
             refresh_token_ttl_secs: 7200,
             pkce_required_public_clients: true,
+            signing_algorithm: SigningAlgorithm::Es256,
             lockout_threshold: 5,
             lockout_duration_secs: 900,
             is_system: false,
@@ -0,0 +1,512 @@
+//! The service provider side of SAML, for brokering logins to an upstream
+//! identity provider: metadata import, AuthnRequests, and validation of the
+//! responses that come back to the assertion consumer service.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use rsa::RsaPublicKey;
+use serde_json::{json, Map, Value};
+
+use super::certificate::{certificate_public_key, decode_certificate};
+use super::message::{
+    saml_instant, AssertionAttribute, ASSERTION_NS, BINDING_POST, BINDING_REDIRECT, METADATA_NS,
+    PROTOCOL_NS, STATUS_SUCCESS,
+};
+use super::signature::{verify_enveloped, XMLDSIG_NS};
+use super::xml::{XmlElement, XmlWriter};
+use super::NameIdFormat;
+use crate::domain::identity_provider::OAuthUpstreamIdentity;
+use crate::error::{Error, Result};
+
+const CONFIRMATION_BEARER: &str = "urn:oasis:names:tc:SAML:2.0:cm:bearer";
+
+/// Tolerated clock difference when checking assertion validity windows.
+const CLOCK_SKEW_SECS: i64 = 180;
+
+/// Attribute names tried, after the configured one, for each identity field.
+const DEFAULT_EMAIL_ATTRIBUTES: &[&str] = &["email", "mail", "urn:oid:0.9.2342.19200300.100.1.3"];
+const DEFAULT_USERNAME_ATTRIBUTES: &[&str] =
+    &["username", "uid", "urn:oid:0.9.2342.19200300.100.1.1"];
+
+/// What ReAuth needs from an upstream identity provider's metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpstreamIdpMetadata {
+    pub entity_id: String,
+    /// The HTTP-Redirect SingleSignOnService location.
+    pub sso_url: String,
+    /// Base64 DER signing certificates.
+    pub signing_certificates: Vec<String>,
+}
+
+/// Reads an `EntityDescriptor` (or an `EntitiesDescriptor` holding exactly
+/// one identity provider).
+pub fn parse_idp_metadata(xml: &str) -> Result<UpstreamIdpMetadata> {
+    let invalid = |message: &str| Error::Validation(message.to_string());
+    let root = XmlElement::parse(xml).map_err(|_| invalid("SAML metadata is not valid XML"))?;
+
+    let entity = if root.is(METADATA_NS, "EntityDescriptor") {
+        &root
+    } else if root.is(METADATA_NS, "EntitiesDescriptor") {
+        let mut identity_providers = root
+            .children_named(METADATA_NS, "EntityDescriptor")
+            .filter(|entity| entity.child(METADATA_NS, "IDPSSODescriptor").is_some());
+        let entity = identity_providers
+            .next()
+            .ok_or_else(|| invalid("SAML metadata does not describe an identity provider"))?;
+        if identity_providers.next().is_some() {
+            return Err(invalid(
+                "SAML metadata describes more than one identity provider",
+            ));
+        }
+        entity
+    } else {
+        return Err(invalid("SAML metadata must be an EntityDescriptor"));
+    };
+
+    let entity_id = entity
+        .attribute("entityID")
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| invalid("SAML metadata has no entityID"))?;
+    let descriptor = entity
+        .child(METADATA_NS, "IDPSSODescriptor")
+        .ok_or_else(|| invalid("SAML metadata does not describe an identity provider"))?;
+    let sso_url = descriptor
+        .children_named(METADATA_NS, "SingleSignOnService")
+        .find(|service| service.attribute("Binding") == Some(BINDING_REDIRECT))
+        .and_then(|service| service.attribute("Location"))
+        .ok_or_else(|| invalid("Identity provider does not offer the HTTP-Redirect SSO binding"))?;
+
+    let mut signing_certificates = Vec::new();
+    for key in descriptor.children_named(METADATA_NS, "KeyDescriptor") {
+        if !matches!(key.attribute("use"), None | Some("signing")) {
+            continue;
+        }
+        let certificates = key
+            .child(XMLDSIG_NS, "KeyInfo")
+            .and_then(|info| info.child(XMLDSIG_NS, "X509Data"))
+            .map(|data| data.children_named(XMLDSIG_NS, "X509Certificate"));
+        for certificate in certificates.into_iter().flatten() {
+            let certificate = normalize_certificate(&certificate.text())?;
+            if !signing_certificates.contains(&certificate) {
+                signing_certificates.push(certificate);
+            }
+        }
+    }
+    if signing_certificates.is_empty() {
+        return Err(invalid("SAML metadata has no signing certificate"));
+    }
+
+    Ok(UpstreamIdpMetadata {
+        entity_id: entity_id.to_string(),
+        sso_url: sso_url.to_string(),
+        signing_certificates,
+    })
+}
+
+/// Checks a base64 (or PEM) certificate carries an RSA key and returns its
+/// compact base64 DER.
+pub fn normalize_certificate(value: &str) -> Result<String> {
+    let invalid = || Error::Validation("SAML signing certificate is invalid".to_string());
+    let der = decode_certificate(value).map_err(|_| invalid())?;
+    certificate_public_key(&der).map_err(|_| invalid())?;
+    Ok(STANDARD.encode(der))
+}
+
+/// The RSA keys of stored base64 DER certificates.
+pub fn certificate_keys(certificates: &[String]) -> Result<Vec<RsaPublicKey>> {
+    certificates
+        .iter()
+        .map(|certificate| certificate_public_key(&decode_certificate(certificate)?))
+        .collect()
+}
+
+/// What goes into an AuthnRequest sent to an upstream identity provider.
+#[derive(Debug, Clone)]
+pub struct AuthnRequestParams<'a> {
+    /// ReAuth's service provider entity ID.
+    pub issuer: &'a str,
+    pub destination: &'a str,
+    pub acs_url: &'a str,
+    pub name_id_format: Option<NameIdFormat>,
+    pub issued_at: DateTime<Utc>,
+}
+
+/// A `<samlp:AuthnRequest>` asking for an HTTP-POST response at the ACS URL.
+pub fn build_authn_request(id: &str, params: &AuthnRequestParams<'_>) -> String {
+    let issued_at = saml_instant(params.issued_at);
+    let mut xml = XmlWriter::new();
+    xml.open(
+        "samlp:AuthnRequest",
+        &[
+            ("xmlns:samlp", PROTOCOL_NS),
+            ("xmlns:saml", ASSERTION_NS),
+            ("AssertionConsumerServiceURL", params.acs_url),
+            ("Destination", params.destination),
+            ("ID", id),
+            ("IssueInstant", &issued_at),
+            ("ProtocolBinding", BINDING_POST),
+            ("Version", "2.0"),
+        ],
+    )
+    .leaf("saml:Issuer", &[], params.issuer);
+    if let Some(format) = params.name_id_format {
+        xml.leaf(
+            "samlp:NameIDPolicy",
+            &[("AllowCreate", "true"), ("Format", format.uri())],
+            "",
+        );
+    }
+    xml.finish()
+}
+
+/// Service provider metadata for registering ReAuth with an upstream
+/// identity provider.
+pub fn build_sp_metadata(entity_id: &str, acs_url: &str, certificates: &[Vec<u8>]) -> String {
+    let mut xml = XmlWriter::new();
+    xml.open(
+        "md:EntityDescriptor",
+        &[("xmlns:md", METADATA_NS), ("entityID", entity_id)],
+    )
+    .open(
+        "md:SPSSODescriptor",
+        &[
+            ("AuthnRequestsSigned", "true"),
+            ("WantAssertionsSigned", "true"),
+            ("protocolSupportEnumeration", PROTOCOL_NS),
+        ],
+    );
+    for certificate in certificates {
+        xml.open("md:KeyDescriptor", &[("use", "signing")])
+            .open("ds:KeyInfo", &[("xmlns:ds", XMLDSIG_NS)])
+            .open("ds:X509Data", &[])
+            .leaf("ds:X509Certificate", &[], &STANDARD.encode(certificate))
+            .close()
+            .close()
+            .close();
+    }
+    xml.leaf(
+        "md:AssertionConsumerService",
+        &[
+            ("Binding", BINDING_POST),
+            ("Location", acs_url),
+            ("index", "0"),
+            ("isDefault", "true"),
+        ],
+        "",
+    );
+    xml.finish()
+}
+
+/// What a response at the ACS must match.
+#[derive(Debug, Clone)]
+pub struct ResponseExpectations<'a> {
+    pub idp_entity_id: &'a str,
+    /// ReAuth's service provider entity ID, the required audience.
+    pub sp_entity_id: &'a str,
+    pub acs_url: &'a str,
+    /// The AuthnRequest ID; unsolicited responses are not accepted.
+    pub request_id: &'a str,
+    pub keys: &'a [RsaPublicKey],
+    pub now: DateTime<Utc>,
+}
+
+/// The parts of a validated assertion that identify the user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatedAssertion {
+    pub id: String,
+    pub name_id: String,
+    pub name_id_format: Option<String>,
+    pub session_index: Option<String>,
+    pub attributes: Vec<AssertionAttribute>,
+}
+
+/// Validates a `<samlp:Response>`: status, destination, the request it
+/// answers, the signature (on the response, the assertion or both), issuer,
+/// bearer subject confirmation, validity window and audience.
+pub fn validate_response(
+    xml: &str,
+    expected: &ResponseExpectations<'_>,
+) -> Result<ValidatedAssertion> {
+    let invalid = |message: &str| Error::SamlInvalidRequest(message.to_string());
+    let root = XmlElement::parse(xml)?;
+    if !root.is(PROTOCOL_NS, "Response") {
+        return Err(invalid("Expected a Response message"));
+    }
+    if root.attribute("Version") != Some("2.0") {
+        return Err(invalid("Unsupported SAML version"));
+    }
+    if let Some(destination) = root.attribute("Destination") {
+        if destination != expected.acs_url {
+            return Err(invalid("Response Destination does not match the ACS URL"));
+        }
+    }
+    if root.attribute("InResponseTo") != Some(expected.request_id) {
+        return Err(invalid("Response does not answer the pending AuthnRequest"));
+    }
+    if let Some(issuer) = root.child(ASSERTION_NS, "Issuer") {
+        if issuer.text() != expected.idp_entity_id {
+            return Err(invalid(
+                "Response Issuer does not match the identity provider",
+            ));
+        }
+    }
+
+    let status = root
+        .child(PROTOCOL_NS, "Status")
+        .and_then(|status| status.child(PROTOCOL_NS, "StatusCode"))
+        .and_then(|code| code.attribute("Value"))
+        .ok_or_else(|| invalid("Response has no status"))?;
+    if status != STATUS_SUCCESS {
+        let message = root
+            .child(PROTOCOL_NS, "Status")
+            .and_then(|status| status.child(PROTOCOL_NS, "StatusMessage"))
+            .map(XmlElement::text)
+            .filter(|message| !message.is_empty());
+        return Err(Error::SamlInvalidRequest(match message {
+            Some(message) => format!("Identity provider returned {}: {}", status, message),
+            None => format!("Identity provider returned {}", status),
+        }));
+    }
+
+    if root.child(ASSERTION_NS, "EncryptedAssertion").is_some() {
+        return Err(invalid("Encrypted assertions are not supported"));
+    }
+    let mut assertions = root.children_named(ASSERTION_NS, "Assertion");
+    let assertion = assertions
+        .next()
+        .ok_or_else(|| invalid("Response has no assertion"))?;
+    if assertions.next().is_some() {
+        return Err(invalid("Response carries more than one assertion"));
+    }
+
+    let response_signed = root.child(XMLDSIG_NS, "Signature").is_some();
+    let assertion_signed = assertion.child(XMLDSIG_NS, "Signature").is_some();
+    if !response_signed && !assertion_signed {
+        return Err(invalid("Response is not signed"));
+    }
+    if response_signed {
+        verify_enveloped(&root, expected.keys)?;
+    }
+    if assertion_signed {
+        verify_enveloped(assertion, expected.keys)?;
+    }
+
+    validate_assertion(assertion, expected)
+}
+
+fn validate_assertion(
+    assertion: &XmlElement,
+    expected: &ResponseExpectations<'_>,
+) -> Result<ValidatedAssertion> {
+    let invalid = |message: &str| Error::SamlInvalidRequest(message.to_string());
+    let skew = Duration::seconds(CLOCK_SKEW_SECS);
+
+    let id = assertion
+        .attribute("ID")
+        .filter(|id| !id.is_empty())
+        .ok_or_else(|| invalid("Assertion has no ID"))?;
+    if assertion.attribute("Version") != Some("2.0") {
+        return Err(invalid("Unsupported SAML version"));
+    }
+    let issuer = assertion
+        .child(ASSERTION_NS, "Issuer")
+        .map(XmlElement::text)
+        .ok_or_else(|| invalid("Assertion has no Issuer"))?;
+    if issuer != expected.idp_entity_id {
+        return Err(invalid(
+            "Assertion Issuer does not match the identity provider",
+        ));
+    }
+
+    let subject = assertion
+        .child(ASSERTION_NS, "Subject")
+        .ok_or_else(|| invalid("Assertion has no Subject"))?;
+    if subject.child(ASSERTION_NS, "EncryptedID").is_some() {
+        return Err(invalid("Encrypted NameIDs are not supported"));
+    }
+    let name_id = subject
+        .child(ASSERTION_NS, "NameID")
+        .ok_or_else(|| invalid("Assertion has no NameID"))?;
+    let name_id_value = name_id.text();
+    if name_id_value.is_empty() {
+        return Err(invalid("Assertion has an empty NameID"));
+    }
+
+    let mut confirmed = false;
+    for confirmation in subject.children_named(ASSERTION_NS, "SubjectConfirmation") {
+        if confirmation.attribute("Method") != Some(CONFIRMATION_BEARER) {
+            continue;
+        }
+        let Some(data) = confirmation.child(ASSERTION_NS, "SubjectConfirmationData") else {
+            continue;
+        };
+        let recipient_ok = data.attribute("Recipient") == Some(expected.acs_url);
+        let answers_request = data
+            .attribute("InResponseTo")
+            .is_none_or(|value| value == expected.request_id);
+        let not_expired = match data.attribute("NotOnOrAfter") {
+            Some(value) => expected.now - skew < parse_instant(value)?,
+            None => false,
+        };
+        let started = match data.attribute("NotBefore") {
+            Some(value) => expected.now + skew >= parse_instant(value)?,
+            None => true,
+        };
+        if recipient_ok && answers_request && not_expired && started {
+            confirmed = true;
+            break;
+        }
+    }
+    if !confirmed {
+        return Err(invalid(
+            "Assertion has no valid bearer subject confirmation for this ACS",
+        ));
+    }
+
+    let conditions = assertion
+        .child(ASSERTION_NS, "Conditions")
+        .ok_or_else(|| invalid("Assertion has no Conditions"))?;
+    if let Some(not_before) = conditions.attribute("NotBefore") {
+        if expected.now + skew < parse_instant(not_before)? {
+            return Err(invalid("Assertion is not yet valid"));
+        }
+    }
+    if let Some(not_on_or_after) = conditions.attribute("NotOnOrAfter") {
+        if expected.now - skew >= parse_instant(not_on_or_after)? {
+            return Err(invalid("Assertion has expired"));
+        }
+    }
+    let mut restrictions = conditions
+        .children_named(ASSERTION_NS, "AudienceRestriction")
+        .peekable();
+    if restrictions.peek().is_none() {
+        return Err(invalid("Assertion has no audience restriction"));
+    }
+    for restriction in restrictions {
+        if !restriction
+            .children_named(ASSERTION_NS, "Audience")
+            .any(|audience| audience.text() == expected.sp_entity_id)
+        {
+            return Err(invalid(
+                "Assertion is not addressed to this service provider",
+            ));
+        }
+    }
+
+    let session_index = assertion
+        .child(ASSERTION_NS, "AuthnStatement")
+        .and_then(|statement| statement.attribute("SessionIndex"))
+        .map(str::to_string);
+    let attributes = assertion
+        .children_named(ASSERTION_NS, "AttributeStatement")
+        .flat_map(|statement| statement.children_named(ASSERTION_NS, "Attribute"))
+        .filter_map(|attribute| {
+            Some(AssertionAttribute {
+                name: attribute.attribute("Name")?.to_string(),
+                friendly_name: attribute.attribute("FriendlyName").map(str::to_string),
+                values: attribute
+                    .children_named(ASSERTION_NS, "AttributeValue")
+                    .map(XmlElement::text)
+                    .collect(),
+            })
+        })
+        .collect();
+
+    Ok(ValidatedAssertion {
+        id: id.to_string(),
+        name_id: name_id_value,
+        name_id_format: name_id.attribute("Format").map(str::to_string),
+        session_index,
+        attributes,
+    })
+}
+
+impl ValidatedAssertion {
+    /// Maps the assertion onto the upstream identity the broker links.
+    ///
+    /// `mapping` is the provider's claim mapping: `subject`, `email` and
+    /// `username` name the attribute (by `Name` or `FriendlyName`) to read,
+    /// falling back to common attribute names and, for the subject, the
+    /// NameID. `email_verified` is either `true` to trust asserted emails or
+    /// the name of a boolean attribute.
+    pub fn upstream_identity(&self, mapping: &Value) -> Result<OAuthUpstreamIdentity> {
+        let mapped = |key: &str| mapping.get(key).and_then(Value::as_str);
+
+        let subject = match mapped("subject") {
+            Some(name) => self.first_value(&[name]).ok_or_else(|| {
+                Error::SamlInvalidRequest(format!(
+                    "Assertion is missing the subject attribute '{}'",
+                    name
+                ))
+            })?,
+            None => self.name_id.clone(),
+        };
+        let email = self
+            .first_value(&candidates(mapped("email"), DEFAULT_EMAIL_ATTRIBUTES))
+            .or_else(|| {
+                (self.name_id_format.as_deref() == Some(NameIdFormat::EmailAddress.uri()))
+                    .then(|| self.name_id.clone())
+            });
+        let username =
+            self.first_value(&candidates(mapped("username"), DEFAULT_USERNAME_ATTRIBUTES));
+        let email_verified = match mapping.get("email_verified") {
+            Some(Value::Bool(trusted)) => *trusted,
+            Some(Value::String(name)) => self
+                .first_value(&[name.as_str()])
+                .is_some_and(|value| matches!(value.as_str(), "true" | "1")),
+            _ => false,
+        } && email.is_some();
+
+        let mut attributes = Map::new();
+        for attribute in &self.attributes {
+            let values = json!(attribute.values);
+            if let Some(friendly_name) = &attribute.friendly_name {
+                attributes
+                    .entry(friendly_name.clone())
+                    .or_insert_with(|| values.clone());
+            }
+            attributes.insert(attribute.name.clone(), values);
+        }
+
+        Ok(OAuthUpstreamIdentity {
+            subject,
+            email,
+            email_verified,
+            username,
+            claims: json!({
+                "name_id": self.name_id,
+                "name_id_format": self.name_id_format,
+                "session_index": self.session_index,
+                "assertion_id": self.id,
+                "attributes": attributes,
+            }),
+        })
+    }
+
+    fn first_value(&self, names: &[&str]) -> Option<String> {
+        names.iter().find_map(|name| {
+            self.attributes
+                .iter()
+                .find(|attribute| {
+                    attribute.name == *name || attribute.friendly_name.as_deref() == Some(*name)
+                })
+                .and_then(|attribute| attribute.values.first())
+                .filter(|value| !value.is_empty())
+                .cloned()
+        })
+    }
+}
+
+fn candidates<'a>(configured: Option<&'a str>, defaults: &[&'a str]) -> Vec<&'a str> {
+    configured
+        .into_iter()
+        .chain(defaults.iter().copied())
+        .collect()
+}
+
+fn parse_instant(value: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|_| Error::SamlInvalidRequest(format!("Invalid SAML instant '{}'", value)))
+}
@@ -0,0 +1,202 @@
+//! Minimal X.509 support: a self-signed certificate wrapping a realm's RSA
+//! key (SAML metadata publishes keys as certificates), and reading the RSA
+//! public key out of a peer's certificate. Nothing here validates chains or
+//! expiry; SAML trusts the certificate pinned in metadata.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, Datelike, Utc};
+use rsa::pkcs1v15::SigningKey;
+use rsa::pkcs8::{DecodePublicKey, EncodePublicKey};
+use rsa::sha2::Sha256;
+use rsa::signature::{SignatureEncoding, Signer};
+use rsa::{RsaPrivateKey, RsaPublicKey};
+
+use crate::error::{Error, Result};
+
+/// sha256WithRSAEncryption (1.2.840.113549.1.1.11).
+const SHA256_WITH_RSA_OID: [u8; 9] = [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0b];
+/// id-at-commonName (2.5.4.3).
+const COMMON_NAME_OID: [u8; 3] = [0x55, 0x04, 0x03];
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_BIT_STRING: u8 = 0x03;
+const TAG_NULL: u8 = 0x05;
+const TAG_OID: u8 = 0x06;
+const TAG_UTF8_STRING: u8 = 0x0c;
+const TAG_UTC_TIME: u8 = 0x17;
+const TAG_GENERALIZED_TIME: u8 = 0x18;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_SET: u8 = 0x31;
+const TAG_VERSION: u8 = 0xa0;
+
+/// A DER-encoded, self-signed v3 certificate for `key`, with `common_name`
+/// as subject and issuer. The same inputs always give the same certificate.
+pub fn self_signed_certificate(
+    key: &RsaPrivateKey,
+    common_name: &str,
+    serial: &[u8],
+    not_before: DateTime<Utc>,
+    not_after: DateTime<Utc>,
+) -> Result<Vec<u8>> {
+    let public_key = RsaPublicKey::from(key)
+        .to_public_key_der()
+        .map_err(|e| certificate_error("Failed to encode public key", e))?;
+    let algorithm = der(
+        TAG_SEQUENCE,
+        &[der(TAG_OID, &SHA256_WITH_RSA_OID), der(TAG_NULL, &[])].concat(),
+    );
+    let name = der(
+        TAG_SEQUENCE,
+        &der(
+            TAG_SET,
+            &der(
+                TAG_SEQUENCE,
+                &[
+                    der(TAG_OID, &COMMON_NAME_OID),
+                    der(TAG_UTF8_STRING, common_name.as_bytes()),
+                ]
+                .concat(),
+            ),
+        ),
+    );
+    let validity = der(
+        TAG_SEQUENCE,
+        &[der_time(not_before), der_time(not_after)].concat(),
+    );
+
+    let tbs = der(
+        TAG_SEQUENCE,
+        &[
+            der(TAG_VERSION, &der(TAG_INTEGER, &[2])),
+            der(TAG_INTEGER, &positive_integer(serial)),
+            algorithm.clone(),
+            name.clone(),
+            validity,
+            name,
+            public_key.as_bytes().to_vec(),
+        ]
+        .concat(),
+    );
+
+    let signature = SigningKey::<Sha256>::new(key.clone())
+        .try_sign(&tbs)
+        .map_err(|e| certificate_error("Failed to sign certificate", e))?
+        .to_vec();
+    let mut bit_string = vec![0];
+    bit_string.extend(signature);
+
+    Ok(der(
+        TAG_SEQUENCE,
+        &[tbs, algorithm, der(TAG_BIT_STRING, &bit_string)].concat(),
+    ))
+}
+
+/// The RSA public key in a DER certificate.
+pub fn certificate_public_key(certificate: &[u8]) -> Result<RsaPublicKey> {
+    let invalid = || Error::SamlInvalidRequest("Invalid X.509 certificate".to_string());
+    let (_, certificate, _) = read_tlv(certificate).ok_or_else(invalid)?;
+    let (_, mut tbs, _) = read_tlv(certificate).ok_or_else(invalid)?;
+
+    // version (optional), serial, signature algorithm, issuer, validity,
+    // subject, then the subject public key info.
+    let (tag, _, rest) = read_tlv(tbs).ok_or_else(invalid)?;
+    if tag == TAG_VERSION {
+        tbs = rest;
+    }
+    for _ in 0..5 {
+        let (_, _, rest) = read_tlv(tbs).ok_or_else(invalid)?;
+        tbs = rest;
+    }
+    let (_, _, rest) = read_tlv(tbs).ok_or_else(invalid)?;
+    let spki = &tbs[..tbs.len() - rest.len()];
+    RsaPublicKey::from_public_key_der(spki).map_err(|_| {
+        Error::SamlInvalidRequest("Only RSA signing certificates are supported".to_string())
+    })
+}
+
+/// Decodes a base64 certificate, with or without PEM armor and line breaks.
+pub fn decode_certificate(value: &str) -> Result<Vec<u8>> {
+    let body: String = value
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("-----"))
+        .flat_map(|line| line.chars())
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    let der = STANDARD
+        .decode(body)
+        .map_err(|_| Error::SamlInvalidRequest("Invalid X.509 certificate".to_string()))?;
+    certificate_public_key(&der)?;
+    Ok(der)
+}
+
+fn certificate_error(context: &str, err: impl std::fmt::Display) -> Error {
+    Error::Unexpected(anyhow::anyhow!("{}: {}", context, err))
+}
+
+fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let skip = bytes.iter().take_while(|byte| **byte == 0).count();
+        out.push(0x80 | (bytes.len() - skip) as u8);
+        out.extend_from_slice(&bytes[skip..]);
+    }
+    out.extend_from_slice(content);
+    out
+}
+
+/// A positive DER INTEGER body: leading zeros dropped, one added back when
+/// the high bit is set.
+fn positive_integer(bytes: &[u8]) -> Vec<u8> {
+    let trimmed: Vec<u8> = bytes
+        .iter()
+        .copied()
+        .skip_while(|byte| *byte == 0)
+        .collect();
+    match trimmed.first() {
+        None => vec![0],
+        Some(first) if first & 0x80 != 0 => [&[0], trimmed.as_slice()].concat(),
+        Some(_) => trimmed,
+    }
+}
+
+/// UTCTime through 2049, GeneralizedTime after (RFC 5280 4.1.2.5).
+fn der_time(time: DateTime<Utc>) -> Vec<u8> {
+    if time.year() < 2050 {
+        der(
+            TAG_UTC_TIME,
+            time.format("%y%m%d%H%M%SZ").to_string().as_bytes(),
+        )
+    } else {
+        der(
+            TAG_GENERALIZED_TIME,
+            time.format("%Y%m%d%H%M%SZ").to_string().as_bytes(),
+        )
+    }
+}
+
+/// Splits one DER element off `input`: (tag, content, remainder).
+fn read_tlv(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let tag = *input.first()?;
+    let first = *input.get(1)? as usize;
+    let (len, header) = if first < 0x80 {
+        (first, 2)
+    } else {
+        let count = first & 0x7f;
+        if count == 0 || count > 4 {
+            return None;
+        }
+        let len = input
+            .get(2..2 + count)?
+            .iter()
+            .fold(0_usize, |len, byte| (len << 8) | *byte as usize);
+        (len, 2 + count)
+    };
+    let end = header.checked_add(len)?;
+    let content = input.get(header..end)?;
+    Some((tag, content, &input[end..]))
+}
@@ -0,0 +1,409 @@
+//! SAML 2.0 protocol messages (SAML Core) and the HTTP-Redirect and
+//! HTTP-POST encodings that carry them (SAML Bindings).
+
+use std::io::{Read, Write};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use uuid::Uuid;
+
+use super::signature::XMLDSIG_NS;
+use super::xml::{XmlElement, XmlWriter};
+use crate::error::{Error, Result};
+
+pub const PROTOCOL_NS: &str = "urn:oasis:names:tc:SAML:2.0:protocol";
+pub const ASSERTION_NS: &str = "urn:oasis:names:tc:SAML:2.0:assertion";
+pub const METADATA_NS: &str = "urn:oasis:names:tc:SAML:2.0:metadata";
+
+pub const BINDING_REDIRECT: &str = "urn:oasis:names:tc:SAML:2.0:bindings:HTTP-Redirect";
+pub const BINDING_POST: &str = "urn:oasis:names:tc:SAML:2.0:bindings:HTTP-POST";
+
+pub const STATUS_SUCCESS: &str = "urn:oasis:names:tc:SAML:2.0:status:Success";
+pub const STATUS_REQUESTER: &str = "urn:oasis:names:tc:SAML:2.0:status:Requester";
+pub const STATUS_RESPONDER: &str = "urn:oasis:names:tc:SAML:2.0:status:Responder";
+
+const CONFIRMATION_BEARER: &str = "urn:oasis:names:tc:SAML:2.0:cm:bearer";
+const ATTRNAME_FORMAT_BASIC: &str = "urn:oasis:names:tc:SAML:2.0:attrname-format:basic";
+const AUTHN_CONTEXT_UNSPECIFIED: &str = "urn:oasis:names:tc:SAML:2.0:ac:classes:unspecified";
+
+/// Decoded messages larger than this are rejected.
+const MAX_MESSAGE_BYTES: u64 = 256 * 1024;
+
+/// A fresh message or assertion `ID` (an xs:ID, so it cannot start with a
+/// digit).
+pub fn new_message_id() -> String {
+    format!("_{}", Uuid::new_v4().simple())
+}
+
+/// An xs:dateTime in UTC with second precision, as SAML expects.
+pub fn saml_instant(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// `<samlp:AuthnRequest>` from a service provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthnRequest {
+    pub id: String,
+    pub issuer: String,
+    pub assertion_consumer_service_url: Option<String>,
+    pub protocol_binding: Option<String>,
+    pub name_id_policy_format: Option<String>,
+    pub force_authn: bool,
+    pub is_passive: bool,
+}
+
+impl AuthnRequest {
+    pub fn parse(xml: &str) -> Result<Self> {
+        let root = XmlElement::parse(xml)?;
+        let id = protocol_header(&root, "AuthnRequest")?;
+        Ok(Self {
+            id,
+            issuer: issuer(&root)?,
+            assertion_consumer_service_url: root
+                .attribute("AssertionConsumerServiceURL")
+                .map(str::to_string),
+            protocol_binding: root.attribute("ProtocolBinding").map(str::to_string),
+            name_id_policy_format: root
+                .child(PROTOCOL_NS, "NameIDPolicy")
+                .and_then(|policy| policy.attribute("Format"))
+                .map(str::to_string),
+            force_authn: xs_bool(root.attribute("ForceAuthn")),
+            is_passive: xs_bool(root.attribute("IsPassive")),
+        })
+    }
+}
+
+/// `<samlp:LogoutRequest>` from a service provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogoutRequest {
+    pub id: String,
+    pub issuer: String,
+    pub name_id: Option<String>,
+    pub session_indexes: Vec<String>,
+}
+
+impl LogoutRequest {
+    pub fn parse(xml: &str) -> Result<Self> {
+        let root = XmlElement::parse(xml)?;
+        let id = protocol_header(&root, "LogoutRequest")?;
+        Ok(Self {
+            id,
+            issuer: issuer(&root)?,
+            name_id: root
+                .child(ASSERTION_NS, "NameID")
+                .map(XmlElement::text)
+                .filter(|value| !value.is_empty()),
+            session_indexes: root
+                .children_named(PROTOCOL_NS, "SessionIndex")
+                .map(XmlElement::text)
+                .filter(|value| !value.is_empty())
+                .collect(),
+        })
+    }
+}
+
+/// Checks the root is the expected SAML 2.0 protocol message and returns its
+/// `ID`.
+fn protocol_header(root: &XmlElement, local_name: &str) -> Result<String> {
+    if !root.is(PROTOCOL_NS, local_name) {
+        return Err(Error::SamlInvalidRequest(format!(
+            "Expected a {} message",
+            local_name
+        )));
+    }
+    if root.attribute("Version") != Some("2.0") {
+        return Err(Error::SamlInvalidRequest(
+            "Unsupported SAML version".to_string(),
+        ));
+    }
+    root.attribute("ID")
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| Error::SamlInvalidRequest("Message has no ID".to_string()))
+}
+
+fn issuer(root: &XmlElement) -> Result<String> {
+    root.child(ASSERTION_NS, "Issuer")
+        .map(XmlElement::text)
+        .filter(|issuer| !issuer.is_empty())
+        .ok_or_else(|| Error::SamlInvalidRequest("Message has no Issuer".to_string()))
+}
+
+fn xs_bool(value: Option<&str>) -> bool {
+    matches!(value, Some("true") | Some("1"))
+}
+
+/// One `<saml:Attribute>` with its values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssertionAttribute {
+    pub name: String,
+    pub friendly_name: Option<String>,
+    pub values: Vec<String>,
+}
+
+/// What goes into an assertion issued to a service provider.
+#[derive(Debug, Clone)]
+pub struct AssertionParams<'a> {
+    pub issuer: &'a str,
+    pub audience: &'a str,
+    pub recipient: &'a str,
+    pub in_response_to: Option<&'a str>,
+    pub name_id: &'a str,
+    pub name_id_format: &'a str,
+    pub session_index: &'a str,
+    pub attributes: &'a [AssertionAttribute],
+    pub issued_at: DateTime<Utc>,
+    pub lifetime: Duration,
+}
+
+/// An unsigned `<saml:Assertion>` with a bearer subject confirmation.
+pub fn build_assertion(id: &str, params: &AssertionParams<'_>) -> String {
+    let issued_at = saml_instant(params.issued_at);
+    let not_on_or_after = saml_instant(params.issued_at + params.lifetime);
+
+    let mut confirmation_data = vec![("NotOnOrAfter", not_on_or_after.as_str())];
+    if let Some(in_response_to) = params.in_response_to {
+        confirmation_data.insert(0, ("InResponseTo", in_response_to));
+    }
+    confirmation_data.push(("Recipient", params.recipient));
+
+    let mut xml = XmlWriter::new();
+    xml.open(
+        "saml:Assertion",
+        &[
+            ("xmlns:saml", ASSERTION_NS),
+            ("ID", id),
+            ("IssueInstant", &issued_at),
+            ("Version", "2.0"),
+        ],
+    )
+    .leaf("saml:Issuer", &[], params.issuer)
+    .open("saml:Subject", &[])
+    .leaf(
+        "saml:NameID",
+        &[("Format", params.name_id_format)],
+        params.name_id,
+    )
+    .open(
+        "saml:SubjectConfirmation",
+        &[("Method", CONFIRMATION_BEARER)],
+    )
+    .leaf("saml:SubjectConfirmationData", &confirmation_data, "")
+    .close()
+    .close()
+    .open(
+        "saml:Conditions",
+        &[
+            ("NotBefore", &issued_at),
+            ("NotOnOrAfter", &not_on_or_after),
+        ],
+    )
+    .open("saml:AudienceRestriction", &[])
+    .leaf("saml:Audience", &[], params.audience)
+    .close()
+    .close()
+    .open(
+        "saml:AuthnStatement",
+        &[
+            ("AuthnInstant", &issued_at),
+            ("SessionIndex", params.session_index),
+        ],
+    )
+    .open("saml:AuthnContext", &[])
+    .leaf("saml:AuthnContextClassRef", &[], AUTHN_CONTEXT_UNSPECIFIED)
+    .close()
+    .close();
+
+    if !params.attributes.is_empty() {
+        xml.open("saml:AttributeStatement", &[]);
+        for attribute in params.attributes {
+            let mut attributes = vec![("Name", attribute.name.as_str())];
+            attributes.push(("NameFormat", ATTRNAME_FORMAT_BASIC));
+            if let Some(friendly_name) = &attribute.friendly_name {
+                attributes.insert(0, ("FriendlyName", friendly_name.as_str()));
+            }
+            xml.open("saml:Attribute", &attributes);
+            for value in &attribute.values {
+                xml.leaf("saml:AttributeValue", &[], value);
+            }
+            xml.close();
+        }
+        xml.close();
+    }
+
+    xml.finish()
+}
+
+/// A `<samlp:Response>`, with the (already signed) assertion when the status
+/// is success.
+pub fn build_response(
+    id: &str,
+    issuer: &str,
+    destination: &str,
+    in_response_to: Option<&str>,
+    issued_at: DateTime<Utc>,
+    status: &str,
+    assertion: Option<&str>,
+) -> String {
+    let issued_at = saml_instant(issued_at);
+    let mut attributes = vec![
+        ("xmlns:samlp", PROTOCOL_NS),
+        ("Destination", destination),
+        ("ID", id),
+    ];
+    if let Some(in_response_to) = in_response_to {
+        attributes.push(("InResponseTo", in_response_to));
+    }
+    attributes.push(("IssueInstant", &issued_at));
+    attributes.push(("Version", "2.0"));
+
+    let mut xml = XmlWriter::new();
+    xml.open("samlp:Response", &attributes);
+    status_header(&mut xml, issuer, status);
+    if let Some(assertion) = assertion {
+        xml.raw(assertion);
+    }
+    xml.finish()
+}
+
+/// A `<samlp:LogoutResponse>` answering a service provider's logout request.
+pub fn build_logout_response(
+    id: &str,
+    issuer: &str,
+    destination: &str,
+    in_response_to: &str,
+    issued_at: DateTime<Utc>,
+    status: &str,
+) -> String {
+    let issued_at = saml_instant(issued_at);
+    let mut xml = XmlWriter::new();
+    xml.open(
+        "samlp:LogoutResponse",
+        &[
+            ("xmlns:samlp", PROTOCOL_NS),
+            ("Destination", destination),
+            ("ID", id),
+            ("InResponseTo", in_response_to),
+            ("IssueInstant", &issued_at),
+            ("Version", "2.0"),
+        ],
+    );
+    status_header(&mut xml, issuer, status);
+    xml.finish()
+}
+
+/// The `Issuer` (declaring its own namespace, so the signed assertion that
+/// follows canonicalizes the same inside and outside the response) and the
+/// `Status`.
+fn status_header(xml: &mut XmlWriter, issuer: &str, status: &str) {
+    xml.leaf("saml:Issuer", &[("xmlns:saml", ASSERTION_NS)], issuer)
+        .open("samlp:Status", &[])
+        .leaf("samlp:StatusCode", &[("Value", status)], "")
+        .close();
+}
+
+/// The endpoints and keys an identity provider publishes in its metadata.
+#[derive(Debug, Clone)]
+pub struct IdpMetadata<'a> {
+    pub entity_id: &'a str,
+    pub sso_url: &'a str,
+    pub slo_url: &'a str,
+    /// DER certificates, the one currently signing first.
+    pub certificates: &'a [Vec<u8>],
+    pub name_id_formats: &'a [&'a str],
+}
+
+pub fn build_idp_metadata(metadata: &IdpMetadata<'_>) -> String {
+    let mut xml = XmlWriter::new();
+    xml.open(
+        "md:EntityDescriptor",
+        &[("xmlns:md", METADATA_NS), ("entityID", metadata.entity_id)],
+    )
+    .open(
+        "md:IDPSSODescriptor",
+        &[
+            ("WantAuthnRequestsSigned", "false"),
+            ("protocolSupportEnumeration", PROTOCOL_NS),
+        ],
+    );
+    for certificate in metadata.certificates {
+        xml.open("md:KeyDescriptor", &[("use", "signing")])
+            .open("ds:KeyInfo", &[("xmlns:ds", XMLDSIG_NS)])
+            .open("ds:X509Data", &[])
+            .leaf("ds:X509Certificate", &[], &STANDARD.encode(certificate))
+            .close()
+            .close()
+            .close();
+    }
+    for binding in [BINDING_REDIRECT, BINDING_POST] {
+        xml.leaf(
+            "md:SingleLogoutService",
+            &[("Binding", binding), ("Location", metadata.slo_url)],
+            "",
+        );
+    }
+    for format in metadata.name_id_formats {
+        xml.leaf("md:NameIDFormat", &[], format);
+    }
+    for binding in [BINDING_REDIRECT, BINDING_POST] {
+        xml.leaf(
+            "md:SingleSignOnService",
+            &[("Binding", binding), ("Location", metadata.sso_url)],
+            "",
+        );
+    }
+    xml.finish()
+}
+
+/// Decodes a `SAMLRequest`/`SAMLResponse` from the HTTP-Redirect binding
+/// (base64 of raw DEFLATE).
+pub fn decode_redirect_message(value: &str) -> Result<String> {
+    let compressed = decode_base64(value)?;
+    let mut xml = String::new();
+    DeflateDecoder::new(compressed.as_slice())
+        .take(MAX_MESSAGE_BYTES + 1)
+        .read_to_string(&mut xml)
+        .map_err(|_| Error::SamlInvalidRequest("Invalid DEFLATE encoding".to_string()))?;
+    if xml.len() as u64 > MAX_MESSAGE_BYTES {
+        return Err(Error::SamlInvalidRequest(
+            "Message is too large".to_string(),
+        ));
+    }
+    Ok(xml)
+}
+
+pub fn encode_redirect_message(xml: &str) -> Result<String> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(xml.as_bytes())
+        .map_err(|e| Error::Unexpected(e.into()))?;
+    let compressed = encoder.finish().map_err(|e| Error::Unexpected(e.into()))?;
+    Ok(STANDARD.encode(compressed))
+}
+
+/// Decodes a `SAMLRequest`/`SAMLResponse` from the HTTP-POST binding.
+pub fn decode_post_message(value: &str) -> Result<String> {
+    let xml = decode_base64(value)?;
+    if xml.len() as u64 > MAX_MESSAGE_BYTES {
+        return Err(Error::SamlInvalidRequest(
+            "Message is too large".to_string(),
+        ));
+    }
+    String::from_utf8(xml)
+        .map_err(|_| Error::SamlInvalidRequest("Message is not valid UTF-8".to_string()))
+}
+
+pub fn encode_post_message(xml: &str) -> String {
+    STANDARD.encode(xml)
+}
+
+fn decode_base64(value: &str) -> Result<Vec<u8>> {
+    let compact: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+    STANDARD
+        .decode(compact)
+        .map_err(|_| Error::SamlInvalidRequest("Invalid base64 encoding".to_string()))
+}
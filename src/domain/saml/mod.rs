@@ -0,0 +1,215 @@
+//! SAML 2.0 identity provider: service provider registrations, protocol
+//! messages and the bindings that carry them. [`broker`] holds the service
+//! provider side used to broker logins to upstream SAML identity providers.
+
+pub mod broker;
+mod certificate;
+mod message;
+mod signature;
+pub mod xml;
+
+#[cfg(test)]
+mod tests;
+
+pub use certificate::{certificate_public_key, decode_certificate, self_signed_certificate};
+pub use message::{
+    build_assertion, build_idp_metadata, build_logout_response, build_response,
+    decode_post_message, decode_redirect_message, encode_post_message, encode_redirect_message,
+    new_message_id, saml_instant, AssertionAttribute, AssertionParams, AuthnRequest, IdpMetadata,
+    LogoutRequest, ASSERTION_NS, BINDING_POST, BINDING_REDIRECT, METADATA_NS, PROTOCOL_NS,
+    STATUS_REQUESTER, STATUS_RESPONDER, STATUS_SUCCESS,
+};
+pub use signature::{
+    sign_enveloped, sign_query, verify_enveloped, verify_query, RSA_SHA256, XMLDSIG_NS,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+
+/// Key under which a SAML login keeps its state in the auth session context.
+pub const SAML_CONTEXT_KEY: &str = "saml";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SamlBinding {
+    Redirect,
+    Post,
+}
+
+impl SamlBinding {
+    pub fn uri(self) -> &'static str {
+        match self {
+            Self::Redirect => BINDING_REDIRECT,
+            Self::Post => BINDING_POST,
+        }
+    }
+}
+
+impl std::fmt::Display for SamlBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Redirect => write!(f, "redirect"),
+            Self::Post => write!(f, "post"),
+        }
+    }
+}
+
+impl TryFrom<String> for SamlBinding {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        match value.as_str() {
+            "redirect" => Ok(Self::Redirect),
+            "post" => Ok(Self::Post),
+            other => Err(format!("Unsupported SAML binding: {}", other)),
+        }
+    }
+}
+
+/// How the subject of an assertion is identified to the service provider.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NameIdFormat {
+    /// The username.
+    Unspecified,
+    /// The primary email address.
+    EmailAddress,
+    /// The user id, stable across logins.
+    Persistent,
+    /// A fresh opaque value per login.
+    Transient,
+}
+
+impl NameIdFormat {
+    pub const ALL: [NameIdFormat; 4] = [
+        Self::Unspecified,
+        Self::EmailAddress,
+        Self::Persistent,
+        Self::Transient,
+    ];
+
+    pub fn uri(self) -> &'static str {
+        match self {
+            Self::Unspecified => "urn:oasis:names:tc:SAML:1.1:nameid-format:unspecified",
+            Self::EmailAddress => "urn:oasis:names:tc:SAML:1.1:nameid-format:emailAddress",
+            Self::Persistent => "urn:oasis:names:tc:SAML:2.0:nameid-format:persistent",
+            Self::Transient => "urn:oasis:names:tc:SAML:2.0:nameid-format:transient",
+        }
+    }
+
+    pub fn from_uri(uri: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|format| format.uri() == uri)
+    }
+}
+
+impl std::fmt::Display for NameIdFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unspecified => write!(f, "unspecified"),
+            Self::EmailAddress => write!(f, "email_address"),
+            Self::Persistent => write!(f, "persistent"),
+            Self::Transient => write!(f, "transient"),
+        }
+    }
+}
+
+impl TryFrom<String> for NameIdFormat {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        match value.as_str() {
+            "unspecified" => Ok(Self::Unspecified),
+            "email_address" => Ok(Self::EmailAddress),
+            "persistent" => Ok(Self::Persistent),
+            "transient" => Ok(Self::Transient),
+            other => Err(format!("Unsupported NameID format: {}", other)),
+        }
+    }
+}
+
+/// The user property an assertion attribute carries.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SamlAttributeSource {
+    UserId,
+    Username,
+    Email,
+    GivenName,
+    FamilyName,
+    /// One value per effective role name.
+    Roles,
+    /// One value per group name.
+    Groups,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SamlAttributeMapping {
+    pub name: String,
+    #[serde(default)]
+    pub friendly_name: Option<String>,
+    pub source: SamlAttributeSource,
+}
+
+/// A service provider registered with a realm's SAML identity provider.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SamlServiceProvider {
+    pub id: Uuid,
+    pub realm_id: Uuid,
+    pub entity_id: String,
+    pub name: String,
+    /// Assertion consumer service URLs; responses only go to one of these,
+    /// the first when the request names none.
+    pub acs_urls: Vec<String>,
+    pub slo_url: Option<String>,
+    pub slo_binding: SamlBinding,
+    pub name_id_format: NameIdFormat,
+    pub attribute_mappings: Vec<SamlAttributeMapping>,
+    /// Also signs the response envelope; the assertion is always signed.
+    pub sign_response: bool,
+    /// Base64 DER certificates the provider signs its requests with.
+    #[serde(default)]
+    pub signing_certificates: Vec<String>,
+    /// Refuses unsigned AuthnRequests. LogoutRequests must always be signed.
+    #[serde(default)]
+    pub require_signed_requests: bool,
+    pub allow_idp_initiated: bool,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SamlServiceProvider {
+    /// The ACS URL a response goes to: the requested one if registered,
+    /// otherwise the default.
+    pub fn resolve_acs_url(&self, requested: Option<&str>) -> Result<String> {
+        match requested {
+            Some(url) if self.acs_urls.iter().any(|acs| acs == url) => Ok(url.to_string()),
+            Some(_) => Err(Error::SamlInvalidRequest(
+                "AssertionConsumerServiceURL is not registered for this service provider"
+                    .to_string(),
+            )),
+            None => self.acs_urls.first().cloned().ok_or_else(|| {
+                Error::SamlInvalidRequest(
+                    "Service provider has no assertion consumer service".to_string(),
+                )
+            }),
+        }
+    }
+}
+
+/// State a SAML login carries through the browser flow, under
+/// [`SAML_CONTEXT_KEY`] in the auth session context.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SamlLoginContext {
+    pub service_provider_id: Uuid,
+    pub acs_url: String,
+    /// The AuthnRequest `ID`; `None` for IdP-initiated logins.
+    pub request_id: Option<String>,
+    pub relay_state: Option<String>,
+    /// The signed, base64 `SAMLResponse`, set once the flow succeeds.
+    #[serde(default)]
+    pub response: Option<String>,
+}
@@ -0,0 +1,224 @@
+//! XML Signature for SAML: enveloped RSA-SHA256 signatures over exclusive
+//! canonical XML, and the detached query-string signatures of the
+//! HTTP-Redirect binding.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use rsa::pkcs1v15::{Signature, SigningKey, VerifyingKey};
+use rsa::sha2::{Digest, Sha256};
+use rsa::signature::{SignatureEncoding, Signer, Verifier};
+use rsa::{RsaPrivateKey, RsaPublicKey};
+
+use super::xml::{XmlElement, XmlWriter};
+use crate::error::{Error, Result};
+
+pub const XMLDSIG_NS: &str = "http://www.w3.org/2000/09/xmldsig#";
+pub const EXC_C14N: &str = "http://www.w3.org/2001/10/xml-exc-c14n#";
+pub const ENVELOPED_SIGNATURE: &str = "http://www.w3.org/2000/09/xmldsig#enveloped-signature";
+pub const RSA_SHA256: &str = "http://www.w3.org/2001/04/xmldsig-more#rsa-sha256";
+pub const SHA256: &str = "http://www.w3.org/2001/04/xmlenc#sha256";
+
+/// Signs the root element of `xml` and returns the document with the
+/// `ds:Signature` inserted right after the first `closing_issuer_tag`, where
+/// the SAML schema expects it. The root must carry an `ID`.
+pub fn sign_enveloped(
+    xml: &str,
+    closing_issuer_tag: &str,
+    key: &RsaPrivateKey,
+    certificate: &[u8],
+) -> Result<String> {
+    let root = XmlElement::parse(xml)?;
+    let id = root
+        .attribute("ID")
+        .ok_or_else(|| Error::System("Signed SAML element has no ID".to_string()))?;
+    let digest = STANDARD.encode(Sha256::digest(root.canonicalize(None, &[]).as_bytes()));
+    let reference = format!("#{}", id);
+
+    let mut signed_info = XmlWriter::new();
+    signed_info
+        .open("ds:SignedInfo", &[("xmlns:ds", XMLDSIG_NS)])
+        .leaf("ds:CanonicalizationMethod", &[("Algorithm", EXC_C14N)], "")
+        .leaf("ds:SignatureMethod", &[("Algorithm", RSA_SHA256)], "")
+        .open("ds:Reference", &[("URI", &reference)])
+        .open("ds:Transforms", &[])
+        .leaf("ds:Transform", &[("Algorithm", ENVELOPED_SIGNATURE)], "")
+        .leaf("ds:Transform", &[("Algorithm", EXC_C14N)], "")
+        .close()
+        .leaf("ds:DigestMethod", &[("Algorithm", SHA256)], "")
+        .leaf("ds:DigestValue", &[], &digest);
+    let signed_info = XmlElement::parse(&signed_info.finish())?;
+    let canonical_signed_info = signed_info.canonicalize(None, &[]);
+    let signature_value = STANDARD.encode(sign_bytes(key, canonical_signed_info.as_bytes())?);
+
+    // Inside ds:Signature the namespace is declared by the parent, so the
+    // embedded SignedInfo drops its own declaration.
+    let embedded_signed_info = canonical_signed_info.replacen(
+        &format!(r#"<ds:SignedInfo xmlns:ds="{}">"#, XMLDSIG_NS),
+        "<ds:SignedInfo>",
+        1,
+    );
+    let mut signature = XmlWriter::new();
+    signature
+        .open("ds:Signature", &[("xmlns:ds", XMLDSIG_NS)])
+        .raw(&embedded_signed_info)
+        .leaf("ds:SignatureValue", &[], &signature_value)
+        .open("ds:KeyInfo", &[])
+        .open("ds:X509Data", &[])
+        .leaf("ds:X509Certificate", &[], &STANDARD.encode(certificate));
+    let signature = signature.finish();
+
+    let position = xml
+        .find(closing_issuer_tag)
+        .map(|index| index + closing_issuer_tag.len())
+        .ok_or_else(|| Error::System("Signed SAML element has no Issuer".to_string()))?;
+    Ok(format!(
+        "{}{}{}",
+        &xml[..position],
+        signature,
+        &xml[position..]
+    ))
+}
+
+/// Checks the enveloped signature that is a direct child of `element` and
+/// references it by `ID`. Succeeds if any of `keys` verifies it.
+pub fn verify_enveloped(element: &XmlElement, keys: &[RsaPublicKey]) -> Result<()> {
+    let invalid = |message: &str| Error::SamlInvalidRequest(message.to_string());
+
+    let mut signatures = element.children_named(XMLDSIG_NS, "Signature");
+    let signature = signatures
+        .next()
+        .ok_or_else(|| invalid("Message is not signed"))?;
+    if signatures.next().is_some() {
+        return Err(invalid("Message carries more than one signature"));
+    }
+    let signed_info = signature
+        .child(XMLDSIG_NS, "SignedInfo")
+        .ok_or_else(|| invalid("Signature has no SignedInfo"))?;
+
+    let c14n = signed_info
+        .child(XMLDSIG_NS, "CanonicalizationMethod")
+        .ok_or_else(|| invalid("Signature has no CanonicalizationMethod"))?;
+    if c14n.attribute("Algorithm") != Some(EXC_C14N) {
+        return Err(invalid("Unsupported canonicalization method"));
+    }
+    let method = signed_info
+        .child(XMLDSIG_NS, "SignatureMethod")
+        .and_then(|method| method.attribute("Algorithm"));
+    if method != Some(RSA_SHA256) {
+        return Err(invalid("Unsupported signature method"));
+    }
+
+    let mut references = signed_info.children_named(XMLDSIG_NS, "Reference");
+    let reference = references
+        .next()
+        .ok_or_else(|| invalid("Signature has no Reference"))?;
+    if references.next().is_some() {
+        return Err(invalid("Signature has more than one Reference"));
+    }
+    let id = element
+        .attribute("ID")
+        .ok_or_else(|| invalid("Signed element has no ID"))?;
+    if reference.attribute("URI") != Some(format!("#{}", id).as_str()) {
+        return Err(invalid("Signature does not reference the signed element"));
+    }
+
+    let mut inclusive_prefixes = Vec::new();
+    let mut enveloped = false;
+    let mut canonicalized = false;
+    if let Some(transforms) = reference.child(XMLDSIG_NS, "Transforms") {
+        for transform in transforms.children_named(XMLDSIG_NS, "Transform") {
+            match transform.attribute("Algorithm") {
+                Some(ENVELOPED_SIGNATURE) => enveloped = true,
+                Some(EXC_C14N) => {
+                    canonicalized = true;
+                    inclusive_prefixes = prefix_list(transform);
+                }
+                _ => return Err(invalid("Unsupported signature transform")),
+            }
+        }
+    }
+    if !enveloped || !canonicalized {
+        return Err(invalid(
+            "Signature must use the enveloped and exclusive canonicalization transforms",
+        ));
+    }
+    let digest_method = reference
+        .child(XMLDSIG_NS, "DigestMethod")
+        .and_then(|method| method.attribute("Algorithm"));
+    if digest_method != Some(SHA256) {
+        return Err(invalid("Unsupported digest method"));
+    }
+    let expected_digest = reference
+        .child(XMLDSIG_NS, "DigestValue")
+        .map(|value| decode_base64(&value.text()))
+        .transpose()?
+        .ok_or_else(|| invalid("Signature has no DigestValue"))?;
+
+    let canonical = element.canonicalize(Some(signature), &inclusive_prefixes);
+    if Sha256::digest(canonical.as_bytes()).as_slice() != expected_digest.as_slice() {
+        return Err(invalid("Message digest does not match its signature"));
+    }
+
+    let signature_value = signature
+        .child(XMLDSIG_NS, "SignatureValue")
+        .map(|value| decode_base64(&value.text()))
+        .transpose()?
+        .ok_or_else(|| invalid("Signature has no SignatureValue"))?;
+    let canonical_signed_info = signed_info.canonicalize(None, &prefix_list(c14n));
+    if verify_bytes(keys, canonical_signed_info.as_bytes(), &signature_value) {
+        Ok(())
+    } else {
+        Err(invalid("Signature verification failed"))
+    }
+}
+
+/// Signs an HTTP-Redirect binding query (`SAMLRequest=...&RelayState=...&SigAlg=...`,
+/// already URL-encoded) and returns the base64 `Signature` parameter value.
+pub fn sign_query(query: &str, key: &RsaPrivateKey) -> Result<String> {
+    Ok(STANDARD.encode(sign_bytes(key, query.as_bytes())?))
+}
+
+/// Checks an HTTP-Redirect binding signature over the raw query parameters.
+pub fn verify_query(query: &str, signature: &str, keys: &[RsaPublicKey]) -> Result<()> {
+    let signature = decode_base64(signature)?;
+    if verify_bytes(keys, query.as_bytes(), &signature) {
+        Ok(())
+    } else {
+        Err(Error::SamlInvalidRequest(
+            "Signature verification failed".to_string(),
+        ))
+    }
+}
+
+fn sign_bytes(key: &RsaPrivateKey, data: &[u8]) -> Result<Vec<u8>> {
+    SigningKey::<Sha256>::new(key.clone())
+        .try_sign(data)
+        .map(|signature| signature.to_vec())
+        .map_err(|e| Error::Unexpected(anyhow::anyhow!("Failed to sign SAML message: {}", e)))
+}
+
+fn verify_bytes(keys: &[RsaPublicKey], data: &[u8], signature: &[u8]) -> bool {
+    let Ok(signature) = Signature::try_from(signature) else {
+        return false;
+    };
+    keys.iter().any(|key| {
+        VerifyingKey::<Sha256>::new(key.clone())
+            .verify(data, &signature)
+            .is_ok()
+    })
+}
+
+fn prefix_list(transform: &XmlElement) -> Vec<String> {
+    transform
+        .child(EXC_C14N, "InclusiveNamespaces")
+        .and_then(|inclusive| inclusive.attribute("PrefixList"))
+        .map(|list| list.split_whitespace().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+fn decode_base64(value: &str) -> Result<Vec<u8>> {
+    let compact: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+    STANDARD
+        .decode(compact)
+        .map_err(|_| Error::SamlInvalidRequest("Invalid base64 in signature".to_string()))
+}
@@ -0,0 +1,406 @@
+use super::*;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{Duration, TimeZone};
+use rsa::{RsaPrivateKey, RsaPublicKey};
+
+use super::xml::XmlElement;
+
+fn key() -> RsaPrivateKey {
+    RsaPrivateKey::new(&mut rand::rng(), 1024).expect("rsa key")
+}
+
+fn issued_at() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 3, 4, 5, 6, 7).unwrap()
+}
+
+#[test]
+fn parses_authn_requests() {
+    let xml = r#"<samlp:AuthnRequest xmlns:samlp="urn:oasis:names:tc:SAML:2.0:protocol"
+        xmlns:saml="urn:oasis:names:tc:SAML:2.0:assertion"
+        ID="_req1" Version="2.0" IssueInstant="2026-03-04T05:06:07Z"
+        AssertionConsumerServiceURL="https://sp.example/acs"
+        ProtocolBinding="urn:oasis:names:tc:SAML:2.0:bindings:HTTP-POST" ForceAuthn="true">
+        <saml:Issuer> https://sp.example </saml:Issuer>
+        <samlp:NameIDPolicy Format="urn:oasis:names:tc:SAML:1.1:nameid-format:emailAddress"/>
+    </samlp:AuthnRequest>"#;
+
+    let request = AuthnRequest::parse(xml).expect("authn request");
+    assert_eq!(request.id, "_req1");
+    assert_eq!(request.issuer, "https://sp.example");
+    assert_eq!(
+        request.assertion_consumer_service_url.as_deref(),
+        Some("https://sp.example/acs")
+    );
+    assert_eq!(request.protocol_binding.as_deref(), Some(BINDING_POST));
+    assert_eq!(
+        request
+            .name_id_policy_format
+            .as_deref()
+            .and_then(NameIdFormat::from_uri),
+        Some(NameIdFormat::EmailAddress)
+    );
+    assert!(request.force_authn);
+    assert!(!request.is_passive);
+
+    let wrong_version = xml.replace(r#"Version="2.0""#, r#"Version="1.1""#);
+    assert!(AuthnRequest::parse(&wrong_version).is_err());
+    assert!(LogoutRequest::parse(xml).is_err());
+}
+
+#[test]
+fn rejects_document_type_declarations() {
+    let xml = r#"<!DOCTYPE x [<!ENTITY a "b">]><samlp:AuthnRequest xmlns:samlp="urn:oasis:names:tc:SAML:2.0:protocol" ID="_r" Version="2.0"/>"#;
+    assert!(XmlElement::parse(xml).is_err());
+}
+
+#[test]
+fn canonicalization_keeps_only_visibly_used_namespaces() {
+    let xml = "<a:root xmlns:a=\"urn:a\" xmlns:b=\"urn:b\" z=\"1\" a:y=\"2\">\r\n  <b:child>x &amp; y</b:child><a:empty/></a:root>";
+    let root = XmlElement::parse(xml).expect("xml");
+    assert_eq!(
+        root.canonicalize(None, &[]),
+        "<a:root xmlns:a=\"urn:a\" z=\"1\" a:y=\"2\">\n  <b:child xmlns:b=\"urn:b\">x &amp; y</b:child><a:empty></a:empty></a:root>"
+    );
+    let child = root.child("urn:b", "child").expect("child");
+    assert_eq!(
+        child.canonicalize(None, &[]),
+        "<b:child xmlns:b=\"urn:b\">x &amp; y</b:child>"
+    );
+}
+
+#[test]
+fn signed_responses_verify_and_detect_tampering() {
+    let key = key();
+    let public_key = RsaPublicKey::from(&key);
+    let certificate = self_signed_certificate(
+        &key,
+        "reauth",
+        &[1, 2, 3],
+        issued_at(),
+        issued_at() + Duration::days(3650),
+    )
+    .expect("certificate");
+    assert_eq!(
+        certificate_public_key(&certificate).expect("public key"),
+        public_key
+    );
+
+    let attributes = [AssertionAttribute {
+        name: "email".to_string(),
+        friendly_name: None,
+        values: vec!["alice@example.com".to_string()],
+    }];
+    let assertion = build_assertion(
+        "_assertion",
+        &AssertionParams {
+            issuer: "https://idp.example/metadata",
+            audience: "https://sp.example",
+            recipient: "https://sp.example/acs",
+            in_response_to: Some("_req1"),
+            name_id: "alice",
+            name_id_format: NameIdFormat::Unspecified.uri(),
+            session_index: "family-1",
+            attributes: &attributes,
+            issued_at: issued_at(),
+            lifetime: Duration::minutes(5),
+        },
+    );
+    let assertion =
+        sign_enveloped(&assertion, "</saml:Issuer>", &key, &certificate).expect("signed");
+    let response = build_response(
+        "_response",
+        "https://idp.example/metadata",
+        "https://sp.example/acs",
+        Some("_req1"),
+        issued_at(),
+        STATUS_SUCCESS,
+        Some(&assertion),
+    );
+    let response = sign_enveloped(&response, "</saml:Issuer>", &key, &certificate).expect("signed");
+
+    let root = XmlElement::parse(&response).expect("response");
+    verify_enveloped(&root, std::slice::from_ref(&public_key)).expect("response signature");
+    let parsed_assertion = root.child(ASSERTION_NS, "Assertion").expect("assertion");
+    verify_enveloped(parsed_assertion, std::slice::from_ref(&public_key))
+        .expect("assertion signature");
+
+    let other_key = RsaPublicKey::from(&self::key());
+    assert!(verify_enveloped(parsed_assertion, &[other_key]).is_err());
+
+    let tampered = response.replace(">alice<", ">mallory<");
+    let tampered = XmlElement::parse(&tampered).expect("tampered");
+    let tampered_assertion = tampered
+        .child(ASSERTION_NS, "Assertion")
+        .expect("assertion");
+    assert!(verify_enveloped(tampered_assertion, &[public_key]).is_err());
+}
+
+#[test]
+fn redirect_binding_round_trips_and_signs_queries() {
+    let xml = "<samlp:LogoutRequest xmlns:samlp=\"urn:oasis:names:tc:SAML:2.0:protocol\" ID=\"_l\" Version=\"2.0\"><saml:Issuer xmlns:saml=\"urn:oasis:names:tc:SAML:2.0:assertion\">sp</saml:Issuer><saml:NameID xmlns:saml=\"urn:oasis:names:tc:SAML:2.0:assertion\">alice</saml:NameID><samlp:SessionIndex>f1</samlp:SessionIndex></samlp:LogoutRequest>";
+    let encoded = encode_redirect_message(xml).expect("encode");
+    let decoded = decode_redirect_message(&encoded).expect("decode");
+    assert_eq!(decoded, xml);
+
+    let request = LogoutRequest::parse(&decoded).expect("logout request");
+    assert_eq!(request.issuer, "sp");
+    assert_eq!(request.name_id.as_deref(), Some("alice"));
+    assert_eq!(request.session_indexes, vec!["f1".to_string()]);
+
+    let key = key();
+    let query = format!(
+        "SAMLRequest={}&SigAlg={}",
+        urlencoding::encode(&encoded),
+        urlencoding::encode(RSA_SHA256)
+    );
+    let signature = sign_query(&query, &key).expect("sign");
+    let public_key = RsaPublicKey::from(&key);
+    verify_query(&query, &signature, std::slice::from_ref(&public_key)).expect("verify");
+    assert!(verify_query(&format!("{}&x=1", query), &signature, &[public_key]).is_err());
+
+    assert!(decode_redirect_message("not base64!").is_err());
+    assert_eq!(
+        decode_post_message(&encode_post_message(xml)).expect("post"),
+        xml
+    );
+}
+
+#[test]
+fn acs_urls_must_be_registered() {
+    let now = issued_at();
+    let provider = SamlServiceProvider {
+        id: Uuid::new_v4(),
+        realm_id: Uuid::new_v4(),
+        entity_id: "https://sp.example".to_string(),
+        name: "SP".to_string(),
+        acs_urls: vec![
+            "https://sp.example/acs".to_string(),
+            "https://sp.example/acs2".to_string(),
+        ],
+        slo_url: None,
+        slo_binding: SamlBinding::Redirect,
+        name_id_format: NameIdFormat::Persistent,
+        attribute_mappings: Vec::new(),
+        sign_response: false,
+        signing_certificates: Vec::new(),
+        require_signed_requests: false,
+        allow_idp_initiated: false,
+        enabled: true,
+        created_at: now,
+        updated_at: now,
+    };
+    assert_eq!(
+        provider.resolve_acs_url(None).unwrap(),
+        "https://sp.example/acs"
+    );
+    assert_eq!(
+        provider
+            .resolve_acs_url(Some("https://sp.example/acs2"))
+            .unwrap(),
+        "https://sp.example/acs2"
+    );
+    assert!(provider
+        .resolve_acs_url(Some("https://evil.example/acs"))
+        .is_err());
+}
+
+fn upstream_response(key: &RsaPrivateKey, certificate: &[u8], audience: &str) -> String {
+    let attributes = [
+        AssertionAttribute {
+            name: "urn:oid:0.9.2342.19200300.100.1.3".to_string(),
+            friendly_name: Some("mail".to_string()),
+            values: vec!["alice@example.com".to_string()],
+        },
+        AssertionAttribute {
+            name: "employeeNumber".to_string(),
+            friendly_name: None,
+            values: vec!["e-42".to_string()],
+        },
+    ];
+    let assertion = build_assertion(
+        "_assertion",
+        &AssertionParams {
+            issuer: "https://idp.example",
+            audience,
+            recipient: "https://reauth.example/acs",
+            in_response_to: Some("_req1"),
+            name_id: "alice",
+            name_id_format: NameIdFormat::Persistent.uri(),
+            session_index: "s-1",
+            attributes: &attributes,
+            issued_at: issued_at(),
+            lifetime: Duration::minutes(5),
+        },
+    );
+    let assertion = sign_enveloped(&assertion, "</saml:Issuer>", key, certificate).expect("signed");
+    build_response(
+        "_response",
+        "https://idp.example",
+        "https://reauth.example/acs",
+        Some("_req1"),
+        issued_at(),
+        STATUS_SUCCESS,
+        Some(&assertion),
+    )
+}
+
+#[test]
+fn imports_upstream_identity_provider_metadata() {
+    let key = key();
+    let certificate = self_signed_certificate(
+        &key,
+        "idp",
+        &[4],
+        issued_at(),
+        issued_at() + Duration::days(30),
+    )
+    .expect("certificate");
+    let encoded = STANDARD.encode(&certificate);
+    let xml = format!(
+        r#"<md:EntityDescriptor xmlns:md="urn:oasis:names:tc:SAML:2.0:metadata" xmlns:ds="http://www.w3.org/2000/09/xmldsig#" entityID="https://idp.example">
+          <md:IDPSSODescriptor protocolSupportEnumeration="urn:oasis:names:tc:SAML:2.0:protocol">
+            <md:KeyDescriptor use="encryption"><ds:KeyInfo><ds:X509Data><ds:X509Certificate>ignored</ds:X509Certificate></ds:X509Data></ds:KeyInfo></md:KeyDescriptor>
+            <md:KeyDescriptor><ds:KeyInfo><ds:X509Data><ds:X509Certificate>
+              {}
+            </ds:X509Certificate></ds:X509Data></ds:KeyInfo></md:KeyDescriptor>
+            <md:SingleSignOnService Binding="urn:oasis:names:tc:SAML:2.0:bindings:HTTP-POST" Location="https://idp.example/post"/>
+            <md:SingleSignOnService Binding="urn:oasis:names:tc:SAML:2.0:bindings:HTTP-Redirect" Location="https://idp.example/sso"/>
+          </md:IDPSSODescriptor>
+        </md:EntityDescriptor>"#,
+        encoded
+    );
+
+    let metadata = broker::parse_idp_metadata(&xml).expect("metadata");
+    assert_eq!(metadata.entity_id, "https://idp.example");
+    assert_eq!(metadata.sso_url, "https://idp.example/sso");
+    assert_eq!(metadata.signing_certificates, vec![encoded]);
+
+    let post_only = xml.replace("HTTP-Redirect", "HTTP-Artifact");
+    assert!(broker::parse_idp_metadata(&post_only).is_err());
+    assert!(broker::parse_idp_metadata("<md:EntityDescriptor/>").is_err());
+}
+
+#[test]
+fn authn_requests_round_trip() {
+    let xml = broker::build_authn_request(
+        "_req1",
+        &broker::AuthnRequestParams {
+            issuer: "https://reauth.example",
+            destination: "https://idp.example/sso",
+            acs_url: "https://reauth.example/acs",
+            name_id_format: Some(NameIdFormat::EmailAddress),
+            issued_at: issued_at(),
+        },
+    );
+    let encoded = encode_redirect_message(&xml).expect("encode");
+    let request = AuthnRequest::parse(&decode_redirect_message(&encoded).expect("decode"))
+        .expect("authn request");
+    assert_eq!(request.id, "_req1");
+    assert_eq!(request.issuer, "https://reauth.example");
+    assert_eq!(
+        request.assertion_consumer_service_url.as_deref(),
+        Some("https://reauth.example/acs")
+    );
+    assert_eq!(request.protocol_binding.as_deref(), Some(BINDING_POST));
+    assert_eq!(
+        request.name_id_policy_format.as_deref(),
+        Some(NameIdFormat::EmailAddress.uri())
+    );
+}
+
+#[test]
+fn upstream_responses_are_validated_and_mapped() {
+    let key = key();
+    let certificate = self_signed_certificate(
+        &key,
+        "idp",
+        &[5],
+        issued_at(),
+        issued_at() + Duration::days(30),
+    )
+    .expect("certificate");
+    let keys = [RsaPublicKey::from(&key)];
+    let expected = broker::ResponseExpectations {
+        idp_entity_id: "https://idp.example",
+        sp_entity_id: "https://reauth.example",
+        acs_url: "https://reauth.example/acs",
+        request_id: "_req1",
+        keys: &keys,
+        now: issued_at() + Duration::minutes(1),
+    };
+    let response = upstream_response(&key, &certificate, "https://reauth.example");
+
+    let assertion = broker::validate_response(&response, &expected).expect("valid response");
+    assert_eq!(assertion.name_id, "alice");
+    assert_eq!(assertion.session_index.as_deref(), Some("s-1"));
+
+    let identity = assertion
+        .upstream_identity(&serde_json::json!({ "email_verified": true }))
+        .expect("identity");
+    assert_eq!(identity.subject, "alice");
+    assert_eq!(identity.email.as_deref(), Some("alice@example.com"));
+    assert!(identity.email_verified);
+    assert_eq!(
+        identity.claims["attributes"]["mail"][0],
+        "alice@example.com"
+    );
+    let identity = assertion
+        .upstream_identity(&serde_json::json!({ "subject": "employeeNumber" }))
+        .expect("identity");
+    assert_eq!(identity.subject, "e-42");
+    assert!(!identity.email_verified);
+    assert!(assertion
+        .upstream_identity(&serde_json::json!({ "subject": "missing" }))
+        .is_err());
+
+    let wrong_audience = upstream_response(&key, &certificate, "https://other.example");
+    assert!(broker::validate_response(&wrong_audience, &expected).is_err());
+
+    let expired = broker::ResponseExpectations {
+        now: issued_at() + Duration::minutes(30),
+        ..expected.clone()
+    };
+    assert!(broker::validate_response(&response, &expired).is_err());
+
+    let unsolicited = broker::ResponseExpectations {
+        request_id: "_req2",
+        ..expected.clone()
+    };
+    assert!(broker::validate_response(&response, &unsolicited).is_err());
+
+    let other_keys = [RsaPublicKey::from(&self::key())];
+    let untrusted = broker::ResponseExpectations {
+        keys: &other_keys,
+        ..expected.clone()
+    };
+    assert!(broker::validate_response(&response, &untrusted).is_err());
+
+    let tampered = response.replace(">alice<", ">mallory<");
+    assert!(broker::validate_response(&tampered, &expected).is_err());
+
+    let unsigned = build_response(
+        "_response",
+        "https://idp.example",
+        "https://reauth.example/acs",
+        Some("_req1"),
+        issued_at(),
+        STATUS_SUCCESS,
+        Some(&build_assertion(
+            "_assertion",
+            &AssertionParams {
+                issuer: "https://idp.example",
+                audience: "https://reauth.example",
+                recipient: "https://reauth.example/acs",
+                in_response_to: Some("_req1"),
+                name_id: "alice",
+                name_id_format: NameIdFormat::Persistent.uri(),
+                session_index: "s-1",
+                attributes: &[],
+                issued_at: issued_at(),
+                lifetime: Duration::minutes(5),
+            },
+        )),
+    );
+    assert!(broker::validate_response(&unsigned, &expected).is_err());
+}
@@ -0,0 +1,570 @@
+//! The XML subset SAML messages need: a parser that resolves namespaces, an
+//! escaping writer, and Exclusive XML Canonicalization 1.0 (without comments).
+//! Document type declarations are rejected, so entity expansion is never a
+//! concern.
+
+use std::collections::BTreeMap;
+
+use crate::error::{Error, Result};
+
+pub const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// Deeper documents are rejected; real SAML messages stay well below this.
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XmlNode {
+    Element(XmlElement),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XmlElement {
+    /// The qualified name as written, e.g. `saml:Issuer`.
+    pub name: String,
+    /// The element's namespace URI; empty when it has none.
+    pub namespace: String,
+    /// Attributes in document order, namespace declarations included.
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<XmlNode>,
+    /// Every namespace in scope at this element, by prefix (`""` is the
+    /// default namespace).
+    scope: BTreeMap<String, String>,
+}
+
+impl XmlElement {
+    /// Parses a document and returns its root element.
+    pub fn parse(input: &str) -> Result<Self> {
+        let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+        let normalized = input.replace("\r\n", "\n").replace('\r', "\n");
+        let mut parser = Parser {
+            input: &normalized,
+            pos: 0,
+        };
+        parser.skip_misc()?;
+        if !parser.starts_with("<") {
+            return Err(xml_error("missing root element"));
+        }
+        let root = parser.element(&BTreeMap::new(), 0)?;
+        parser.skip_misc()?;
+        if parser.pos != parser.input.len() {
+            return Err(xml_error("content after the root element"));
+        }
+        Ok(root)
+    }
+
+    pub fn local_name(&self) -> &str {
+        split_name(&self.name).1
+    }
+
+    /// True when the element has the given namespace and local name.
+    pub fn is(&self, namespace: &str, local_name: &str) -> bool {
+        self.namespace == namespace && self.local_name() == local_name
+    }
+
+    /// An unprefixed attribute's value.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn elements(&self) -> impl Iterator<Item = &XmlElement> {
+        self.children.iter().filter_map(|node| match node {
+            XmlNode::Element(element) => Some(element),
+            XmlNode::Text(_) => None,
+        })
+    }
+
+    /// The first child element with the given namespace and local name.
+    pub fn child(&self, namespace: &str, local_name: &str) -> Option<&XmlElement> {
+        self.elements()
+            .find(|element| element.is(namespace, local_name))
+    }
+
+    pub fn children_named<'a>(
+        &'a self,
+        namespace: &'a str,
+        local_name: &'a str,
+    ) -> impl Iterator<Item = &'a XmlElement> {
+        self.elements()
+            .filter(move |element| element.is(namespace, local_name))
+    }
+
+    /// The element's text content, trimmed.
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        collect_text(self, &mut text);
+        text.trim().to_string()
+    }
+
+    /// Exclusive canonical form of this element, leaving out `exclude` (the
+    /// enveloped signature) and treating `inclusive_prefixes` as in the
+    /// `InclusiveNamespaces PrefixList` (`#default` names the default
+    /// namespace).
+    pub fn canonicalize(
+        &self,
+        exclude: Option<&XmlElement>,
+        inclusive_prefixes: &[String],
+    ) -> String {
+        let inclusive = inclusive_prefixes
+            .iter()
+            .map(|prefix| {
+                if prefix == "#default" {
+                    String::new()
+                } else {
+                    prefix.clone()
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut output = String::new();
+        canonicalize_element(self, exclude, &inclusive, &BTreeMap::new(), &mut output);
+        output
+    }
+
+    fn namespace_of(&self, prefix: &str) -> Option<&str> {
+        if prefix == "xml" {
+            return Some(XML_NS);
+        }
+        self.scope.get(prefix).map(String::as_str)
+    }
+}
+
+fn collect_text(element: &XmlElement, text: &mut String) {
+    for node in &element.children {
+        match node {
+            XmlNode::Text(value) => text.push_str(value),
+            XmlNode::Element(child) => collect_text(child, text),
+        }
+    }
+}
+
+fn split_name(name: &str) -> (&str, &str) {
+    match name.split_once(':') {
+        Some((prefix, local)) => (prefix, local),
+        None => ("", name),
+    }
+}
+
+fn xml_error(message: &str) -> Error {
+    Error::SamlInvalidRequest(format!("Malformed XML: {}", message))
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn rest(&self) -> &str {
+        &self.input[self.pos..]
+    }
+
+    fn starts_with(&self, prefix: &str) -> bool {
+        self.rest().starts_with(prefix)
+    }
+
+    fn skip_whitespace(&mut self) {
+        let trimmed = self.rest().trim_start_matches([' ', '\t', '\n']);
+        self.pos = self.input.len() - trimmed.len();
+    }
+
+    fn skip_past(&mut self, terminator: &str) -> Result<()> {
+        let index = self
+            .rest()
+            .find(terminator)
+            .ok_or_else(|| xml_error("unterminated markup"))?;
+        self.pos += index + terminator.len();
+        Ok(())
+    }
+
+    /// Skips whitespace, the XML declaration, processing instructions and
+    /// comments outside the root element.
+    fn skip_misc(&mut self) -> Result<()> {
+        loop {
+            self.skip_whitespace();
+            if self.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if self.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.starts_with("<!") {
+                return Err(xml_error("document type declarations are not allowed"));
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<String> {
+        let end = self
+            .rest()
+            .find(|c: char| c.is_whitespace() || matches!(c, '/' | '>' | '=' | '<'))
+            .unwrap_or(self.rest().len());
+        if end == 0 {
+            return Err(xml_error("expected a name"));
+        }
+        let name = self.rest()[..end].to_string();
+        self.pos += end;
+        Ok(name)
+    }
+
+    fn element(
+        &mut self,
+        parent_scope: &BTreeMap<String, String>,
+        depth: usize,
+    ) -> Result<XmlElement> {
+        if depth >= MAX_DEPTH {
+            return Err(xml_error("document is nested too deeply"));
+        }
+        self.pos += 1; // '<'
+        let name = self.name()?;
+        let mut attributes: Vec<(String, String)> = Vec::new();
+        let self_closing = loop {
+            self.skip_whitespace();
+            if self.starts_with("/>") {
+                self.pos += 2;
+                break true;
+            }
+            if self.starts_with(">") {
+                self.pos += 1;
+                break false;
+            }
+            let key = self.name()?;
+            self.skip_whitespace();
+            if !self.starts_with("=") {
+                return Err(xml_error("expected '=' after an attribute name"));
+            }
+            self.pos += 1;
+            self.skip_whitespace();
+            let quote = self
+                .rest()
+                .chars()
+                .next()
+                .filter(|c| *c == '"' || *c == '\'')
+                .ok_or_else(|| xml_error("attribute values must be quoted"))?;
+            self.pos += 1;
+            let end = self
+                .rest()
+                .find(quote)
+                .ok_or_else(|| xml_error("unterminated attribute value"))?;
+            let raw = &self.rest()[..end];
+            if raw.contains('<') {
+                return Err(xml_error("'<' in an attribute value"));
+            }
+            let value = decode_entities(&raw.replace(['\t', '\n'], " "))?;
+            self.pos += end + 1;
+            if attributes.iter().any(|(existing, _)| *existing == key) {
+                return Err(xml_error("duplicate attribute"));
+            }
+            attributes.push((key, value));
+        };
+
+        let mut scope = parent_scope.clone();
+        for (key, value) in &attributes {
+            if key == "xmlns" {
+                scope.insert(String::new(), value.clone());
+            } else if let Some(prefix) = key.strip_prefix("xmlns:") {
+                scope.insert(prefix.to_string(), value.clone());
+            }
+        }
+        let (prefix, _) = split_name(&name);
+        let namespace = match scope.get(prefix) {
+            Some(namespace) => namespace.clone(),
+            None if prefix.is_empty() => String::new(),
+            None => return Err(xml_error("undeclared namespace prefix")),
+        };
+        for (key, _) in &attributes {
+            let (attribute_prefix, _) = split_name(key);
+            if !attribute_prefix.is_empty()
+                && attribute_prefix != "xmlns"
+                && attribute_prefix != "xml"
+                && !scope.contains_key(attribute_prefix)
+            {
+                return Err(xml_error("undeclared namespace prefix"));
+            }
+        }
+
+        let mut element = XmlElement {
+            name,
+            namespace,
+            attributes,
+            children: Vec::new(),
+            scope,
+        };
+        if self_closing {
+            return Ok(element);
+        }
+
+        let mut text = String::new();
+        loop {
+            if self.pos >= self.input.len() {
+                return Err(xml_error("unclosed element"));
+            }
+            if self.starts_with("</") {
+                flush_text(&mut text, &mut element.children)?;
+                self.pos += 2;
+                let closing = self.name()?;
+                if closing != element.name {
+                    return Err(xml_error("mismatched closing tag"));
+                }
+                self.skip_whitespace();
+                if !self.starts_with(">") {
+                    return Err(xml_error("malformed closing tag"));
+                }
+                self.pos += 1;
+                return Ok(element);
+            }
+            if self.starts_with("<![CDATA[") {
+                self.pos += "<![CDATA[".len();
+                let end = self
+                    .rest()
+                    .find("]]>")
+                    .ok_or_else(|| xml_error("unterminated CDATA section"))?;
+                // Kept raw; entity decoding happens on flush, so escape it.
+                text.push_str(&escape_text(&self.rest()[..end]));
+                self.pos += end + 3;
+            } else if self.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if self.starts_with("<!") {
+                return Err(xml_error("document type declarations are not allowed"));
+            } else if self.starts_with("<") {
+                flush_text(&mut text, &mut element.children)?;
+                let child = self.element(&element.scope, depth + 1)?;
+                element.children.push(XmlNode::Element(child));
+            } else {
+                let end = self.rest().find('<').unwrap_or(self.rest().len());
+                text.push_str(&self.rest()[..end]);
+                self.pos += end;
+            }
+        }
+    }
+}
+
+fn flush_text(text: &mut String, children: &mut Vec<XmlNode>) -> Result<()> {
+    if !text.is_empty() {
+        children.push(XmlNode::Text(decode_entities(text)?));
+        text.clear();
+    }
+    Ok(())
+}
+
+fn decode_entities(raw: &str) -> Result<String> {
+    let mut output = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(index) = rest.find('&') {
+        output.push_str(&rest[..index]);
+        rest = &rest[index + 1..];
+        let end = rest
+            .find(';')
+            .ok_or_else(|| xml_error("unterminated entity reference"))?;
+        let entity = &rest[..end];
+        let decoded = match entity {
+            "lt" => '<',
+            "gt" => '>',
+            "amp" => '&',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => {
+                let code = if let Some(hex) = entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                {
+                    u32::from_str_radix(hex, 16).ok()
+                } else if let Some(decimal) = entity.strip_prefix('#') {
+                    decimal.parse().ok()
+                } else {
+                    None
+                };
+                code.and_then(char::from_u32)
+                    .ok_or_else(|| xml_error("unknown entity reference"))?
+            }
+        };
+        output.push(decoded);
+        rest = &rest[end + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Escapes character data for element content.
+pub fn escape_text(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\r', "&#xD;")
+}
+
+/// Escapes an attribute value for a double-quoted attribute.
+pub fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('"', "&quot;")
+        .replace('\t', "&#x9;")
+        .replace('\n', "&#xA;")
+        .replace('\r', "&#xD;")
+}
+
+/// Builds an XML string. Callers pass attributes in the order they should
+/// appear and must close every element they open.
+#[derive(Default)]
+pub struct XmlWriter {
+    output: String,
+    open: Vec<String>,
+}
+
+impl XmlWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn open(&mut self, name: &str, attributes: &[(&str, &str)]) -> &mut Self {
+        self.start_tag(name, attributes);
+        self.open.push(name.to_string());
+        self
+    }
+
+    pub fn close(&mut self) -> &mut Self {
+        if let Some(name) = self.open.pop() {
+            self.output.push_str("</");
+            self.output.push_str(&name);
+            self.output.push('>');
+        }
+        self
+    }
+
+    /// An element holding only text (or nothing).
+    pub fn leaf(&mut self, name: &str, attributes: &[(&str, &str)], text: &str) -> &mut Self {
+        self.start_tag(name, attributes);
+        self.output.push_str(&escape_text(text));
+        self.output.push_str("</");
+        self.output.push_str(name);
+        self.output.push('>');
+        self
+    }
+
+    /// Appends already serialized XML.
+    pub fn raw(&mut self, xml: &str) -> &mut Self {
+        self.output.push_str(xml);
+        self
+    }
+
+    pub fn finish(mut self) -> String {
+        while !self.open.is_empty() {
+            self.close();
+        }
+        self.output
+    }
+
+    fn start_tag(&mut self, name: &str, attributes: &[(&str, &str)]) {
+        self.output.push('<');
+        self.output.push_str(name);
+        for (key, value) in attributes {
+            self.output.push(' ');
+            self.output.push_str(key);
+            self.output.push_str("=\"");
+            self.output.push_str(&escape_attribute(value));
+            self.output.push('"');
+        }
+        self.output.push('>');
+    }
+}
+
+fn canonicalize_element(
+    element: &XmlElement,
+    exclude: Option<&XmlElement>,
+    inclusive: &[String],
+    rendered: &BTreeMap<String, String>,
+    output: &mut String,
+) {
+    // Namespaces this element visibly uses (plus the inclusive ones in
+    // scope) are declared unless an output ancestor already declared them
+    // with the same value.
+    let mut utilized: Vec<&str> = vec![split_name(&element.name).0];
+    for (key, _) in &element.attributes {
+        let (prefix, _) = split_name(key);
+        if !prefix.is_empty() && prefix != "xmlns" && key != "xmlns" {
+            utilized.push(prefix);
+        }
+    }
+    for prefix in inclusive {
+        if element.scope.contains_key(prefix) {
+            utilized.push(prefix);
+        }
+    }
+    utilized.sort_unstable();
+    utilized.dedup();
+
+    let mut rendered = rendered.clone();
+    let mut declarations = Vec::new();
+    for prefix in utilized {
+        if prefix == "xml" {
+            continue;
+        }
+        let namespace = element.scope.get(prefix).map(String::as_str).unwrap_or("");
+        let already = rendered.get(prefix).map(String::as_str);
+        if already == Some(namespace) || (already.is_none() && namespace.is_empty()) {
+            continue;
+        }
+        rendered.insert(prefix.to_string(), namespace.to_string());
+        declarations.push((prefix, namespace));
+    }
+
+    let mut attributes: Vec<(&str, &str, &str, &str)> = element
+        .attributes
+        .iter()
+        .filter(|(key, _)| key != "xmlns" && !key.starts_with("xmlns:"))
+        .map(|(key, value)| {
+            let (prefix, local) = split_name(key);
+            let namespace = if prefix.is_empty() {
+                ""
+            } else {
+                element.namespace_of(prefix).unwrap_or("")
+            };
+            (namespace, local, key.as_str(), value.as_str())
+        })
+        .collect();
+    attributes.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+
+    output.push('<');
+    output.push_str(&element.name);
+    for (prefix, namespace) in declarations {
+        if prefix.is_empty() {
+            output.push_str(" xmlns=\"");
+        } else {
+            output.push_str(" xmlns:");
+            output.push_str(prefix);
+            output.push_str("=\"");
+        }
+        output.push_str(&escape_attribute(namespace));
+        output.push('"');
+    }
+    for (_, _, key, value) in attributes {
+        output.push(' ');
+        output.push_str(key);
+        output.push_str("=\"");
+        output.push_str(&escape_attribute(value));
+        output.push('"');
+    }
+    output.push('>');
+
+    for node in &element.children {
+        match node {
+            XmlNode::Text(text) => output.push_str(&escape_text(text)),
+            XmlNode::Element(child) => {
+                if exclude.is_some_and(|excluded| std::ptr::eq(excluded, child)) {
+                    continue;
+                }
+                canonicalize_element(child, exclude, inclusive, &rendered, output);
+            }
+        }
+    }
+
+    output.push_str("</");
+    output.push_str(&element.name);
+    output.push('>');
+}
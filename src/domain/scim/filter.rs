@@ -0,0 +1,357 @@
+use serde_json::Value;
+
+use super::{attr_name, get_ci};
+use crate::error::{Error, Result};
+
+/// A parsed SCIM filter expression (RFC 7644, Section 3.4.2.2).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScimFilter {
+    Compare {
+        path: AttrPath,
+        op: CompareOp,
+        value: Value,
+    },
+    Present(AttrPath),
+    /// `emails[type eq "work"]`: some element of a multi-valued attribute
+    /// matches the inner filter.
+    ValuePath {
+        attr: String,
+        filter: Box<ScimFilter>,
+    },
+    And(Box<ScimFilter>, Box<ScimFilter>),
+    Or(Box<ScimFilter>, Box<ScimFilter>),
+    Not(Box<ScimFilter>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Co,
+    Sw,
+    Ew,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+/// An attribute reference such as `userName` or `name.givenName`. A schema
+/// URN prefix is accepted and dropped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttrPath {
+    pub attr: String,
+    pub sub_attr: Option<String>,
+}
+
+impl AttrPath {
+    pub fn parse(raw: &str) -> Result<Self> {
+        let name = attr_name(raw);
+        let mut parts = name.splitn(2, '.');
+        let attr = parts.next().unwrap_or_default();
+        if attr.is_empty() {
+            return Err(invalid(format!("'{}' is not an attribute path", raw)));
+        }
+        Ok(Self {
+            attr: attr.to_string(),
+            sub_attr: parts.next().map(str::to_string),
+        })
+    }
+
+    pub fn is(&self, attr: &str, sub_attr: Option<&str>) -> bool {
+        self.attr.eq_ignore_ascii_case(attr)
+            && match (self.sub_attr.as_deref(), sub_attr) {
+                (None, None) => true,
+                (Some(left), Some(right)) => left.eq_ignore_ascii_case(right),
+                _ => false,
+            }
+    }
+
+    /// The values the path selects in `resource`. Multi-valued attributes
+    /// without a sub-attribute select each element's `value`.
+    fn select<'a>(&self, resource: &'a Value) -> Vec<&'a Value> {
+        let Some(value) = get_ci(resource, &self.attr) else {
+            return Vec::new();
+        };
+        let pick = |item: &'a Value| match self.sub_attr.as_deref() {
+            Some(sub_attr) => get_ci(item, sub_attr),
+            None if item.is_object() => get_ci(item, "value"),
+            None => Some(item),
+        };
+        match value {
+            Value::Array(items) => items.iter().filter_map(pick).collect(),
+            Value::Object(_) if self.sub_attr.is_some() => pick(value).into_iter().collect(),
+            other => vec![other],
+        }
+    }
+}
+
+impl ScimFilter {
+    pub fn parse(input: &str) -> Result<Self> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let filter = parser.or()?;
+        if parser.pos != parser.tokens.len() {
+            return Err(invalid("Unexpected input after the filter".to_string()));
+        }
+        Ok(filter)
+    }
+
+    pub fn matches(&self, resource: &Value) -> bool {
+        match self {
+            ScimFilter::Compare { path, op, value } => {
+                let selected = path.select(resource);
+                match op {
+                    CompareOp::Ne => !selected
+                        .iter()
+                        .any(|item| compare(item, CompareOp::Eq, value)),
+                    _ => selected.iter().any(|item| compare(item, *op, value)),
+                }
+            }
+            ScimFilter::Present(path) => path.select(resource).iter().any(|item| match item {
+                Value::Null => false,
+                Value::String(text) => !text.is_empty(),
+                Value::Array(items) => !items.is_empty(),
+                _ => true,
+            }),
+            ScimFilter::ValuePath { attr, filter } => match get_ci(resource, attr) {
+                Some(Value::Array(items)) => items.iter().any(|item| filter.matches(item)),
+                Some(item @ Value::Object(_)) => filter.matches(item),
+                _ => false,
+            },
+            ScimFilter::And(left, right) => left.matches(resource) && right.matches(resource),
+            ScimFilter::Or(left, right) => left.matches(resource) || right.matches(resource),
+            ScimFilter::Not(inner) => !inner.matches(resource),
+        }
+    }
+
+    /// The string `path eq "value"` compares against, if this filter is
+    /// exactly that comparison.
+    pub fn equality_on(&self, attr: &str) -> Option<&str> {
+        match self {
+            ScimFilter::Compare {
+                path,
+                op: CompareOp::Eq,
+                value: Value::String(value),
+            } if path.is(attr, None) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+fn compare(actual: &Value, op: CompareOp, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Value::String(actual), Value::String(expected)) => {
+            let actual = actual.to_lowercase();
+            let expected = expected.to_lowercase();
+            match op {
+                CompareOp::Eq => actual == expected,
+                CompareOp::Ne => actual != expected,
+                CompareOp::Co => actual.contains(&expected),
+                CompareOp::Sw => actual.starts_with(&expected),
+                CompareOp::Ew => actual.ends_with(&expected),
+                CompareOp::Gt => actual > expected,
+                CompareOp::Ge => actual >= expected,
+                CompareOp::Lt => actual < expected,
+                CompareOp::Le => actual <= expected,
+            }
+        }
+        (Value::Number(actual), Value::Number(expected)) => {
+            let (Some(actual), Some(expected)) = (actual.as_f64(), expected.as_f64()) else {
+                return false;
+            };
+            match op {
+                CompareOp::Eq => actual == expected,
+                CompareOp::Ne => actual != expected,
+                CompareOp::Gt => actual > expected,
+                CompareOp::Ge => actual >= expected,
+                CompareOp::Lt => actual < expected,
+                CompareOp::Le => actual <= expected,
+                _ => false,
+            }
+        }
+        (Value::Bool(actual), Value::Bool(expected)) => match op {
+            CompareOp::Eq => actual == expected,
+            CompareOp::Ne => actual != expected,
+            _ => false,
+        },
+        // Entra ID sends booleans as strings ("True").
+        (Value::Bool(actual), Value::String(expected)) => {
+            match expected.to_ascii_lowercase().parse::<bool>() {
+                Ok(expected) => compare(&Value::Bool(*actual), op, &Value::Bool(expected)),
+                Err(_) => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    OpenBracket,
+    CloseBracket,
+    Word(String),
+    Str(String),
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' | '[' | ']' => {
+                chars.next();
+                tokens.push(match c {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    '[' => Token::OpenBracket,
+                    _ => Token::CloseBracket,
+                });
+            }
+            '"' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped) => text.push(escaped),
+                            None => return Err(invalid("Unterminated string".to_string())),
+                        },
+                        Some(other) => text.push(other),
+                        None => return Err(invalid("Unterminated string".to_string())),
+                    }
+                }
+                tokens.push(Token::Str(text));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '(' | ')' | '[' | ']' | '"') {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.tokens.get(self.pos), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            _ => Err(invalid(format!("Expected {:?}", expected))),
+        }
+    }
+
+    fn or(&mut self) -> Result<ScimFilter> {
+        let mut left = self.and()?;
+        while self.peek_keyword("or") {
+            self.pos += 1;
+            let right = self.and()?;
+            left = ScimFilter::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<ScimFilter> {
+        let mut left = self.unary()?;
+        while self.peek_keyword("and") {
+            self.pos += 1;
+            let right = self.unary()?;
+            left = ScimFilter::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<ScimFilter> {
+        if self.peek_keyword("not") {
+            self.pos += 1;
+            self.expect(Token::Open)?;
+            let inner = self.or()?;
+            self.expect(Token::Close)?;
+            return Ok(ScimFilter::Not(Box::new(inner)));
+        }
+        if self.tokens.get(self.pos) == Some(&Token::Open) {
+            self.pos += 1;
+            let inner = self.or()?;
+            self.expect(Token::Close)?;
+            return Ok(inner);
+        }
+        self.attribute_expression()
+    }
+
+    fn attribute_expression(&mut self) -> Result<ScimFilter> {
+        let Some(Token::Word(path)) = self.next() else {
+            return Err(invalid("Expected an attribute path".to_string()));
+        };
+        if self.tokens.get(self.pos) == Some(&Token::OpenBracket) {
+            self.pos += 1;
+            let inner = self.or()?;
+            self.expect(Token::CloseBracket)?;
+            return Ok(ScimFilter::ValuePath {
+                attr: AttrPath::parse(&path)?.attr,
+                filter: Box::new(inner),
+            });
+        }
+
+        let path = AttrPath::parse(&path)?;
+        let Some(Token::Word(operator)) = self.next() else {
+            return Err(invalid("Expected an operator".to_string()));
+        };
+        let op = match operator.to_ascii_lowercase().as_str() {
+            "pr" => return Ok(ScimFilter::Present(path)),
+            "eq" => CompareOp::Eq,
+            "ne" => CompareOp::Ne,
+            "co" => CompareOp::Co,
+            "sw" => CompareOp::Sw,
+            "ew" => CompareOp::Ew,
+            "gt" => CompareOp::Gt,
+            "ge" => CompareOp::Ge,
+            "lt" => CompareOp::Lt,
+            "le" => CompareOp::Le,
+            other => return Err(invalid(format!("Unknown operator '{}'", other))),
+        };
+        let value = match self.next() {
+            Some(Token::Str(text)) => Value::String(text),
+            Some(Token::Word(word)) => match word.to_ascii_lowercase().as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                "null" => Value::Null,
+                _ => serde_json::from_str::<serde_json::Number>(&word)
+                    .map(Value::Number)
+                    .map_err(|_| invalid(format!("'{}' is not a valid value", word)))?,
+            },
+            _ => return Err(invalid("Expected a comparison value".to_string())),
+        };
+        Ok(ScimFilter::Compare { path, op, value })
+    }
+}
+
+fn invalid(message: String) -> Error {
+    Error::Validation(format!("Invalid filter: {}", message))
+}
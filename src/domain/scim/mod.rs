@@ -0,0 +1,444 @@
+//! SCIM 2.0 (RFC 7643 / RFC 7644) resources, filters and PATCH operations.
+
+mod filter;
+mod patch;
+
+pub use filter::{AttrPath, CompareOp, ScimFilter};
+pub use patch::{PatchOperation, PatchRequest};
+
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use crate::domain::group::Group;
+use crate::domain::user::User;
+use crate::domain::user_email::UserEmail;
+use crate::error::{Error, Result};
+
+pub const USER_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:User";
+pub const GROUP_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:Group";
+pub const LIST_RESPONSE_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:ListResponse";
+pub const PATCH_OP_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:PatchOp";
+pub const ERROR_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:Error";
+pub const SERVICE_PROVIDER_CONFIG_SCHEMA: &str =
+    "urn:ietf:params:scim:schemas:core:2.0:ServiceProviderConfig";
+pub const RESOURCE_TYPE_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:ResourceType";
+pub const SCHEMA_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:Schema";
+
+/// Scopes a client_credentials token needs to use the SCIM endpoints. Write
+/// scopes also allow deletion.
+pub const SCOPE_USERS_READ: &str = "scim:users:read";
+pub const SCOPE_USERS_WRITE: &str = "scim:users:write";
+pub const SCOPE_GROUPS_READ: &str = "scim:groups:read";
+pub const SCOPE_GROUPS_WRITE: &str = "scim:groups:write";
+
+/// The largest page a list request returns.
+pub const MAX_RESULTS: i64 = 200;
+
+/// The two resource types a realm exposes over SCIM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScimResourceType {
+    User,
+    Group,
+}
+
+impl ScimResourceType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScimResourceType::User => "User",
+            ScimResourceType::Group => "Group",
+        }
+    }
+}
+
+/// A user as the SCIM client writes it: the body of POST and PUT, or the
+/// result of applying a PATCH.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScimUserInput {
+    pub user_name: String,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    pub active: bool,
+    pub password: Option<String>,
+    /// Distinct addresses, the primary one first.
+    pub emails: Vec<String>,
+    pub external_id: Option<String>,
+}
+
+impl ScimUserInput {
+    pub fn from_resource(resource: &Value) -> Result<Self> {
+        let user_name = string_attr(resource, "userName")
+            .ok_or_else(|| Error::Validation("userName is required".to_string()))?;
+        let name = get_ci(resource, "name");
+        let active = match get_ci(resource, "active") {
+            None | Some(Value::Null) => true,
+            Some(value) => bool_value(value)
+                .ok_or_else(|| Error::Validation("active must be a boolean".to_string()))?,
+        };
+
+        let mut emails: Vec<(String, bool)> = Vec::new();
+        if let Some(Value::Array(items)) = get_ci(resource, "emails") {
+            for item in items {
+                let Some(email) = string_attr(item, "value") else {
+                    continue;
+                };
+                let primary = get_ci(item, "primary")
+                    .and_then(bool_value)
+                    .unwrap_or(false);
+                if !emails
+                    .iter()
+                    .any(|(existing, _)| existing.eq_ignore_ascii_case(&email))
+                {
+                    emails.push((email, primary));
+                }
+            }
+        }
+        if let Some(primary) = emails.iter().position(|(_, primary)| *primary) {
+            let entry = emails.remove(primary);
+            emails.insert(0, entry);
+        }
+
+        Ok(Self {
+            user_name,
+            given_name: name.and_then(|name| string_attr(name, "givenName")),
+            family_name: name.and_then(|name| string_attr(name, "familyName")),
+            active,
+            password: string_attr(resource, "password"),
+            emails: emails.into_iter().map(|(email, _)| email).collect(),
+            external_id: string_attr(resource, "externalId"),
+        })
+    }
+}
+
+/// A group as the SCIM client writes it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScimGroupInput {
+    pub display_name: String,
+    pub member_ids: Vec<Uuid>,
+    pub external_id: Option<String>,
+}
+
+impl ScimGroupInput {
+    pub fn from_resource(resource: &Value) -> Result<Self> {
+        let display_name = string_attr(resource, "displayName")
+            .ok_or_else(|| Error::Validation("displayName is required".to_string()))?;
+        let mut member_ids = Vec::new();
+        if let Some(Value::Array(items)) = get_ci(resource, "members") {
+            for item in items {
+                let Some(value) = string_attr(item, "value") else {
+                    continue;
+                };
+                let member_id = Uuid::parse_str(&value)
+                    .map_err(|_| Error::Validation(format!("'{}' is not a member id", value)))?;
+                if !member_ids.contains(&member_id) {
+                    member_ids.push(member_id);
+                }
+            }
+        }
+        Ok(Self {
+            display_name,
+            member_ids,
+            external_id: string_attr(resource, "externalId"),
+        })
+    }
+}
+
+pub fn user_resource(
+    user: &User,
+    emails: &[UserEmail],
+    external_id: Option<&str>,
+    base_url: &str,
+) -> Value {
+    let mut name = Map::new();
+    if let Some(given_name) = &user.first_name {
+        name.insert("givenName".to_string(), json!(given_name));
+    }
+    if let Some(family_name) = &user.last_name {
+        name.insert("familyName".to_string(), json!(family_name));
+    }
+    let mut emails: Vec<&UserEmail> = emails.iter().collect();
+    emails.sort_by_key(|email| !email.is_primary);
+
+    let mut resource = json!({
+        "schemas": [USER_SCHEMA],
+        "id": user.id,
+        "userName": user.username,
+        "name": name,
+        "active": user.banned_at.is_none(),
+        "emails": emails
+            .iter()
+            .map(|email| json!({ "value": email.email, "primary": email.is_primary }))
+            .collect::<Vec<_>>(),
+        "meta": meta(
+            ScimResourceType::User,
+            user.created_at,
+            user.updated_at,
+            format!("{}/Users/{}", base_url, user.id),
+        ),
+    });
+    if let Some(external_id) = external_id {
+        resource["externalId"] = json!(external_id);
+    }
+    resource
+}
+
+/// `members` pairs each member's id with its username.
+pub fn group_resource(
+    group: &Group,
+    members: &[(Uuid, String)],
+    external_id: Option<&str>,
+    base_url: &str,
+) -> Value {
+    let mut resource = json!({
+        "schemas": [GROUP_SCHEMA],
+        "id": group.id,
+        "displayName": group.name,
+        "members": members
+            .iter()
+            .map(|(id, username)| json!({
+                "value": id,
+                "display": username,
+                "type": "User",
+                "$ref": format!("{}/Users/{}", base_url, id),
+            }))
+            .collect::<Vec<_>>(),
+        "meta": meta(
+            ScimResourceType::Group,
+            None,
+            None,
+            format!("{}/Groups/{}", base_url, group.id),
+        ),
+    });
+    if let Some(external_id) = external_id {
+        resource["externalId"] = json!(external_id);
+    }
+    resource
+}
+
+fn meta(
+    resource_type: ScimResourceType,
+    created: Option<chrono::DateTime<chrono::Utc>>,
+    last_modified: Option<chrono::DateTime<chrono::Utc>>,
+    location: String,
+) -> Value {
+    let mut meta = json!({
+        "resourceType": resource_type.as_str(),
+        "location": location,
+    });
+    if let Some(created) = created {
+        meta["created"] = json!(created.to_rfc3339());
+    }
+    if let Some(last_modified) = last_modified {
+        meta["lastModified"] = json!(last_modified.to_rfc3339());
+    }
+    meta
+}
+
+/// A page of a list request. `start_index` is 1-based, as in SCIM.
+pub fn list_response(total: usize, start_index: i64, resources: Vec<Value>) -> Value {
+    json!({
+        "schemas": [LIST_RESPONSE_SCHEMA],
+        "totalResults": total,
+        "startIndex": start_index,
+        "itemsPerPage": resources.len(),
+        "Resources": resources,
+    })
+}
+
+pub fn error_body(status: u16, scim_type: Option<&str>, detail: &str) -> Value {
+    let mut body = json!({
+        "schemas": [ERROR_SCHEMA],
+        "status": status.to_string(),
+        "detail": detail,
+    });
+    if let Some(scim_type) = scim_type {
+        body["scimType"] = json!(scim_type);
+    }
+    body
+}
+
+pub fn service_provider_config(base_url: &str) -> Value {
+    json!({
+        "schemas": [SERVICE_PROVIDER_CONFIG_SCHEMA],
+        "patch": { "supported": true },
+        "bulk": { "supported": false, "maxOperations": 0, "maxPayloadSize": 0 },
+        "filter": { "supported": true, "maxResults": MAX_RESULTS },
+        "changePassword": { "supported": true },
+        "sort": { "supported": false },
+        "etag": { "supported": false },
+        "authenticationSchemes": [{
+            "type": "oauthbearertoken",
+            "name": "OAuth Bearer Token",
+            "description": "A client_credentials access token carrying the scim:* scopes.",
+            "primary": true,
+        }],
+        "meta": {
+            "resourceType": "ServiceProviderConfig",
+            "location": format!("{}/ServiceProviderConfig", base_url),
+        },
+    })
+}
+
+pub fn resource_types(base_url: &str) -> Vec<Value> {
+    [
+        (ScimResourceType::User, "/Users", USER_SCHEMA),
+        (ScimResourceType::Group, "/Groups", GROUP_SCHEMA),
+    ]
+    .into_iter()
+    .map(|(resource_type, endpoint, schema)| {
+        json!({
+            "schemas": [RESOURCE_TYPE_SCHEMA],
+            "id": resource_type.as_str(),
+            "name": resource_type.as_str(),
+            "endpoint": endpoint,
+            "schema": schema,
+            "meta": {
+                "resourceType": "ResourceType",
+                "location": format!("{}/ResourceTypes/{}", base_url, resource_type.as_str()),
+            },
+        })
+    })
+    .collect()
+}
+
+pub fn schemas(base_url: &str) -> Vec<Value> {
+    let mut name = attribute("name", "complex", false, false);
+    name["subAttributes"] = json!([
+        attribute("givenName", "string", false, false),
+        attribute("familyName", "string", false, false),
+    ]);
+    let mut password = attribute("password", "string", false, false);
+    password["mutability"] = json!("writeOnly");
+    password["returned"] = json!("never");
+    let mut emails = attribute("emails", "complex", true, false);
+    emails["subAttributes"] = json!([
+        attribute("value", "string", false, true),
+        attribute("primary", "boolean", false, false),
+    ]);
+    let mut user_name = attribute("userName", "string", false, true);
+    user_name["uniqueness"] = json!("server");
+
+    let mut display_name = attribute("displayName", "string", false, true);
+    display_name["uniqueness"] = json!("server");
+    let mut member_value = attribute("value", "string", false, false);
+    member_value["mutability"] = json!("immutable");
+    let mut member_display = attribute("display", "string", false, false);
+    member_display["mutability"] = json!("readOnly");
+    let mut members = attribute("members", "complex", true, false);
+    members["subAttributes"] = json!([member_value, member_display]);
+
+    [
+        (
+            USER_SCHEMA,
+            "User",
+            vec![
+                user_name,
+                name,
+                attribute("active", "boolean", false, false),
+                password,
+                emails,
+            ],
+        ),
+        (GROUP_SCHEMA, "Group", vec![display_name, members]),
+    ]
+    .into_iter()
+    .map(|(id, name, attributes)| {
+        json!({
+            "schemas": [SCHEMA_SCHEMA],
+            "id": id,
+            "name": name,
+            "attributes": attributes,
+            "meta": {
+                "resourceType": "Schema",
+                "location": format!("{}/Schemas/{}", base_url, id),
+            },
+        })
+    })
+    .collect()
+}
+
+fn attribute(name: &str, kind: &str, multi_valued: bool, required: bool) -> Value {
+    json!({
+        "name": name,
+        "type": kind,
+        "multiValued": multi_valued,
+        "required": required,
+        "caseExact": false,
+        "mutability": "readWrite",
+        "returned": "default",
+        "uniqueness": "none",
+    })
+}
+
+/// An attribute name without its schema URN prefix.
+pub(crate) fn attr_name(raw: &str) -> &str {
+    let raw = raw.trim();
+    if raw.len() > 4 && raw[..4].eq_ignore_ascii_case("urn:") {
+        raw.rsplit(':').next().unwrap_or(raw)
+    } else {
+        raw
+    }
+}
+
+/// SCIM attribute names are case-insensitive.
+pub(crate) fn get_ci<'a>(value: &'a Value, name: &str) -> Option<&'a Value> {
+    value
+        .as_object()?
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value)
+}
+
+pub(crate) fn get_ci_mut<'a>(value: &'a mut Value, name: &str) -> Option<&'a mut Value> {
+    value
+        .as_object_mut()?
+        .iter_mut()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value)
+}
+
+/// Sets `name`, keeping the existing spelling of the key. `None` or `null`
+/// removes it.
+pub(crate) fn set_ci(target: &mut Value, name: &str, value: Option<Value>) {
+    let Some(value) = value.filter(|value| !value.is_null()) else {
+        remove_ci(target, name);
+        return;
+    };
+    if target.is_null() {
+        *target = Value::Object(Map::new());
+    }
+    let Some(object) = target.as_object_mut() else {
+        return;
+    };
+    let key = object
+        .keys()
+        .find(|key| key.eq_ignore_ascii_case(name))
+        .cloned()
+        .unwrap_or_else(|| name.to_string());
+    object.insert(key, value);
+}
+
+pub(crate) fn remove_ci(target: &mut Value, name: &str) {
+    if let Some(object) = target.as_object_mut() {
+        object.retain(|key, _| !key.eq_ignore_ascii_case(name));
+    }
+}
+
+fn string_attr(value: &Value, name: &str) -> Option<String> {
+    get_ci(value, name)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string)
+}
+
+/// Entra ID sends booleans as strings ("True").
+fn bool_value(value: &Value) -> Option<bool> {
+    match value {
+        Value::Bool(value) => Some(*value),
+        Value::String(text) => text.to_ascii_lowercase().parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,263 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use super::filter::{AttrPath, CompareOp, ScimFilter};
+use super::{attr_name, get_ci, get_ci_mut, remove_ci, set_ci, PATCH_OP_SCHEMA};
+use crate::error::{Error, Result};
+
+/// A SCIM PATCH request (RFC 7644, Section 3.5.2).
+#[derive(Debug, Clone, Deserialize)]
+pub struct PatchRequest {
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    #[serde(rename = "Operations")]
+    pub operations: Vec<PatchOperation>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PatchOperation {
+    pub op: String,
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub value: Option<Value>,
+}
+
+impl PatchRequest {
+    /// Applies every operation to `resource`, the resource as it is served.
+    /// The result is then stored like a full replacement.
+    pub fn apply(&self, resource: &mut Value) -> Result<()> {
+        if !self.schemas.iter().any(|schema| schema == PATCH_OP_SCHEMA) {
+            return Err(Error::Validation(format!(
+                "PATCH requests must use the {} schema",
+                PATCH_OP_SCHEMA
+            )));
+        }
+        for operation in &self.operations {
+            operation.apply(resource)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Add,
+    Replace,
+    Remove,
+}
+
+impl PatchOperation {
+    fn apply(&self, resource: &mut Value) -> Result<()> {
+        let op = match self.op.to_ascii_lowercase().as_str() {
+            "add" => Op::Add,
+            "replace" => Op::Replace,
+            "remove" => Op::Remove,
+            other => {
+                return Err(Error::Validation(format!(
+                    "Unknown PATCH operation '{}'",
+                    other
+                )))
+            }
+        };
+
+        let Some(path) = self.path.as_deref().filter(|path| !path.trim().is_empty()) else {
+            if op == Op::Remove {
+                return Err(Error::Validation("remove requires a path".to_string()));
+            }
+            let Some(Value::Object(values)) = &self.value else {
+                return Err(Error::Validation(
+                    "An operation without a path needs an object value".to_string(),
+                ));
+            };
+            for (name, value) in values {
+                apply_at(resource, op, &PatchPath::parse(name)?, Some(value))?;
+            }
+            return Ok(());
+        };
+
+        if op != Op::Remove && self.value.is_none() {
+            return Err(Error::Validation(format!("'{}' needs a value", path)));
+        }
+        apply_at(resource, op, &PatchPath::parse(path)?, self.value.as_ref())
+    }
+}
+
+/// `attr`, `attr.sub`, `attr[filter]` or `attr[filter].sub`.
+#[derive(Debug)]
+struct PatchPath {
+    attr: String,
+    filter: Option<ScimFilter>,
+    sub_attr: Option<String>,
+}
+
+impl PatchPath {
+    fn parse(raw: &str) -> Result<Self> {
+        let raw = raw.trim();
+        let Some(open) = raw.find('[') else {
+            let path = AttrPath::parse(raw)?;
+            return Ok(Self {
+                attr: path.attr,
+                filter: None,
+                sub_attr: path.sub_attr,
+            });
+        };
+        let close = raw
+            .rfind(']')
+            .filter(|close| *close > open)
+            .ok_or_else(|| Error::Validation(format!("'{}' is not a valid path", raw)))?;
+        let sub_attr = raw[close + 1..]
+            .strip_prefix('.')
+            .filter(|sub_attr| !sub_attr.is_empty())
+            .map(str::to_string);
+        Ok(Self {
+            attr: attr_name(&raw[..open]).to_string(),
+            filter: Some(ScimFilter::parse(&raw[open + 1..close])?),
+            sub_attr,
+        })
+    }
+}
+
+fn apply_at(resource: &mut Value, op: Op, path: &PatchPath, value: Option<&Value>) -> Result<()> {
+    match &path.filter {
+        None => apply_plain(resource, op, path, value),
+        Some(filter) => apply_filtered(resource, op, path, filter, value),
+    }
+}
+
+fn apply_plain(
+    resource: &mut Value,
+    op: Op,
+    path: &PatchPath,
+    value: Option<&Value>,
+) -> Result<()> {
+    if let Some(sub_attr) = &path.sub_attr {
+        match (op, get_ci_mut(resource, &path.attr)) {
+            (Op::Remove, Some(Value::Array(items))) => {
+                for item in items {
+                    remove_ci(item, sub_attr);
+                }
+            }
+            (Op::Remove, Some(parent)) => remove_ci(parent, sub_attr),
+            (Op::Remove, None) => {}
+            (_, Some(parent @ Value::Object(_))) => set_ci(parent, sub_attr, value.cloned()),
+            (_, _) => {
+                let mut parent = Value::Object(Map::new());
+                set_ci(&mut parent, sub_attr, value.cloned());
+                set_ci(resource, &path.attr, Some(parent));
+            }
+        }
+        return Ok(());
+    }
+
+    let Some(value) = value else {
+        remove_ci(resource, &path.attr);
+        return Ok(());
+    };
+    match (op, get_ci_mut(resource, &path.attr)) {
+        // Entra ID removes members by listing them as the value.
+        (Op::Remove, Some(Value::Array(items))) => {
+            let removed = as_items(value);
+            let removed: Vec<&Value> = removed.iter().filter_map(item_value).collect();
+            items.retain(|item| !removed.contains(&item_value(item).unwrap_or(item)));
+        }
+        (Op::Remove, _) => remove_ci(resource, &path.attr),
+        (Op::Add, Some(Value::Array(items))) => {
+            for item in as_items(value) {
+                if !items.contains(&item) {
+                    items.push(item);
+                }
+            }
+        }
+        (_, Some(existing @ Value::Object(_))) if value.is_object() => {
+            for (name, sub_value) in value.as_object().into_iter().flatten() {
+                set_ci(existing, name, Some(sub_value.clone()));
+            }
+        }
+        _ => set_ci(resource, &path.attr, Some(value.clone())),
+    }
+    Ok(())
+}
+
+fn apply_filtered(
+    resource: &mut Value,
+    op: Op,
+    path: &PatchPath,
+    filter: &ScimFilter,
+    value: Option<&Value>,
+) -> Result<()> {
+    if get_ci(resource, &path.attr).is_none() {
+        set_ci(resource, &path.attr, Some(Value::Array(Vec::new())));
+    }
+    let Some(Value::Array(items)) = get_ci_mut(resource, &path.attr) else {
+        return Err(Error::Validation(format!(
+            "'{}' is not a multi-valued attribute",
+            path.attr
+        )));
+    };
+
+    if op == Op::Remove {
+        match &path.sub_attr {
+            None => items.retain(|item| !filter.matches(item)),
+            Some(sub_attr) => {
+                for item in items.iter_mut().filter(|item| filter.matches(item)) {
+                    remove_ci(item, sub_attr);
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    let mut matched = false;
+    for item in items.iter_mut().filter(|item| filter.matches(item)) {
+        matched = true;
+        write_item(item, path.sub_attr.as_deref(), value);
+    }
+    if !matched {
+        // `emails[type eq "work"].value` on a user without a work email
+        // creates one.
+        let ScimFilter::Compare {
+            path: key,
+            op: CompareOp::Eq,
+            value: key_value,
+        } = filter
+        else {
+            return Err(Error::Validation(format!(
+                "No value of '{}' matches the filter",
+                path.attr
+            )));
+        };
+        let mut item = Value::Object(Map::new());
+        set_ci(&mut item, &key.attr, Some(key_value.clone()));
+        write_item(&mut item, path.sub_attr.as_deref(), value);
+        items.push(item);
+    }
+    Ok(())
+}
+
+fn write_item(item: &mut Value, sub_attr: Option<&str>, value: Option<&Value>) {
+    match (sub_attr, value) {
+        (Some(sub_attr), _) => set_ci(item, sub_attr, value.cloned()),
+        (None, Some(Value::Object(values))) => {
+            for (name, sub_value) in values {
+                set_ci(item, name, Some(sub_value.clone()));
+            }
+        }
+        (None, Some(value)) => *item = value.clone(),
+        (None, None) => {}
+    }
+}
+
+fn as_items(value: &Value) -> Vec<Value> {
+    match value {
+        Value::Array(items) => items.clone(),
+        other => vec![other.clone()],
+    }
+}
+
+fn item_value(item: &Value) -> Option<&Value> {
+    match item {
+        Value::Object(_) => get_ci(item, "value"),
+        other => Some(other),
+    }
+}
@@ -0,0 +1,173 @@
+use super::*;
+use serde_json::json;
+
+fn alice() -> Value {
+    json!({
+        "schemas": [USER_SCHEMA],
+        "id": "5b0f9d1e-6c3a-4c1f-9a55-0d4c1c2b7e10",
+        "userName": "Alice",
+        "name": { "givenName": "Alice", "familyName": "Liddell" },
+        "active": true,
+        "emails": [
+            { "value": "alice@work.example", "type": "work", "primary": true },
+            { "value": "alice@home.example", "type": "home", "primary": false }
+        ],
+        "meta": { "created": "2026-01-02T03:04:05+00:00" }
+    })
+}
+
+fn patch(operations: Value) -> PatchRequest {
+    serde_json::from_value(json!({
+        "schemas": [PATCH_OP_SCHEMA],
+        "Operations": operations,
+    }))
+    .expect("patch request")
+}
+
+#[test]
+fn filters_compare_case_insensitively_and_combine() {
+    let user = alice();
+    let matches = |filter: &str| ScimFilter::parse(filter).expect(filter).matches(&user);
+
+    assert!(matches(r#"userName eq "alice""#));
+    assert!(matches(
+        r#"urn:ietf:params:scim:schemas:core:2.0:User:userName eq "ALICE""#
+    ));
+    assert!(matches(r#"name.familyName sw "lid""#));
+    assert!(matches(r#"emails co "home.example""#));
+    assert!(matches(
+        r#"emails[type eq "work" and value ew "work.example"]"#
+    ));
+    assert!(!matches(
+        r#"emails[type eq "work" and value ew "home.example"]"#
+    ));
+    assert!(matches(r#"active eq true and not (userName eq "bob")"#));
+    assert!(matches(r#"userName eq "bob" or (active eq "True")"#));
+    assert!(matches(r#"meta.created gt "2026-01-01T00:00:00Z""#));
+    assert!(matches("name.givenName pr"));
+    assert!(!matches("externalId pr"));
+    assert!(matches(r#"externalId ne "x""#));
+}
+
+#[test]
+fn malformed_filters_are_rejected() {
+    for filter in [
+        "",
+        "userName",
+        r#"userName eq"#,
+        r#"userName like "a""#,
+        r#"userName eq "a"#,
+        r#"(userName eq "a""#,
+        r#"userName eq "a" extra"#,
+    ] {
+        assert!(ScimFilter::parse(filter).is_err(), "{}", filter);
+    }
+}
+
+#[test]
+fn equality_on_only_matches_a_single_comparison() {
+    let filter = ScimFilter::parse(r#"userName eq "alice""#).expect("filter");
+    assert_eq!(filter.equality_on("username"), Some("alice"));
+    assert_eq!(filter.equality_on("externalId"), None);
+
+    let filter = ScimFilter::parse(r#"userName eq "alice" and active eq true"#).expect("filter");
+    assert_eq!(filter.equality_on("userName"), None);
+}
+
+#[test]
+fn patch_replaces_attributes_with_and_without_paths() {
+    let mut user = alice();
+    patch(json!([
+        { "op": "Replace", "path": "active", "value": "False" },
+        { "op": "replace", "path": "name.givenName", "value": "Al" },
+        { "op": "replace", "value": { "userName": "al", "externalId": "ext-1" } },
+    ]))
+    .apply(&mut user)
+    .expect("apply");
+
+    let input = ScimUserInput::from_resource(&user).expect("input");
+    assert_eq!(input.user_name, "al");
+    assert_eq!(input.given_name.as_deref(), Some("Al"));
+    assert_eq!(input.family_name.as_deref(), Some("Liddell"));
+    assert!(!input.active);
+    assert_eq!(input.external_id.as_deref(), Some("ext-1"));
+}
+
+#[test]
+fn patch_edits_multi_valued_attributes_through_filters() {
+    let mut user = alice();
+    patch(json!([
+        { "op": "remove", "path": "emails[type eq \"home\"]" },
+        { "op": "replace", "path": "emails[type eq \"work\"].value", "value": "a@work.example" },
+        { "op": "add", "path": "emails[type eq \"other\"].value", "value": "a@other.example" },
+    ]))
+    .apply(&mut user)
+    .expect("apply");
+
+    let input = ScimUserInput::from_resource(&user).expect("input");
+    assert_eq!(input.emails, vec!["a@work.example", "a@other.example"]);
+}
+
+#[test]
+fn patch_adds_and_removes_group_members() {
+    let first = Uuid::new_v4();
+    let second = Uuid::new_v4();
+    let third = Uuid::new_v4();
+    let mut group = json!({
+        "schemas": [GROUP_SCHEMA],
+        "displayName": "Engineering",
+        "members": [{ "value": first }, { "value": second }],
+    });
+
+    patch(json!([
+        { "op": "add", "path": "members", "value": [{ "value": third }] },
+        { "op": "remove", "path": format!("members[value eq \"{}\"]", first) },
+        { "op": "Remove", "path": "members", "value": [{ "value": second }] },
+    ]))
+    .apply(&mut group)
+    .expect("apply");
+
+    let input = ScimGroupInput::from_resource(&group).expect("input");
+    assert_eq!(input.display_name, "Engineering");
+    assert_eq!(input.member_ids, vec![third]);
+}
+
+#[test]
+fn patch_requires_the_patch_op_schema_and_known_operations() {
+    let mut user = alice();
+    let request: PatchRequest = serde_json::from_value(json!({
+        "schemas": [],
+        "Operations": [{ "op": "replace", "path": "active", "value": false }],
+    }))
+    .expect("patch request");
+    assert!(request.apply(&mut user).is_err());
+
+    assert!(
+        patch(json!([{ "op": "move", "path": "active", "value": false }]))
+            .apply(&mut user)
+            .is_err()
+    );
+    assert!(patch(json!([{ "op": "remove" }])).apply(&mut user).is_err());
+}
+
+#[test]
+fn user_input_puts_the_primary_email_first_and_requires_a_username() {
+    let input = ScimUserInput::from_resource(&json!({
+        "userName": "bob",
+        "emails": [
+            { "value": "bob@home.example" },
+            { "value": "bob@work.example", "primary": true },
+            { "value": "BOB@home.example" },
+        ],
+    }))
+    .expect("input");
+    assert_eq!(input.emails, vec!["bob@work.example", "bob@home.example"]);
+    assert!(input.active);
+
+    assert!(ScimUserInput::from_resource(&json!({ "name": {} })).is_err());
+    assert!(ScimGroupInput::from_resource(&json!({
+        "displayName": "x",
+        "members": [{ "value": "not-a-uuid" }],
+    }))
+    .is_err());
+}
@@ -28,6 +28,13 @@ pub struct RefreshToken {
     /// When set, the live token of this family must re-authenticate. Silent
     /// refresh is rejected until a fresh interactive auth mints a new family.
     pub step_up_at: Option<DateTime<Utc>>,
+    /// Scope granted when the session was created; refreshed tokens keep it.
+    pub scope: Option<String>,
+    /// The OIDC `claims` request (JSON) granted with the scope.
+    pub claims: Option<String>,
+    /// The admin who started this session as the user (`user:impersonate`).
+    /// Impersonation sessions never outlive their original expiry.
+    pub impersonator_id: Option<Uuid>,
 }
 
 /// Optional filters for listing sessions in the admin console.
@@ -68,6 +75,9 @@ impl RefreshToken {
             revoked_at: None,
             replaced_by: None,
             step_up_at: None,
+            scope: None,
+            claims: None,
+            impersonator_id: None,
         }
     }
 
@@ -95,6 +105,11 @@ impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for RefreshToken {
             Some(value) => Some(parse_uuid(value, "replaced_by")?),
             None => None,
         };
+        let impersonator_str: Option<String> = row.try_get("impersonator_id")?;
+        let impersonator_id = match impersonator_str {
+            Some(value) => Some(parse_uuid(value, "impersonator_id")?),
+            None => None,
+        };
 
         Ok(RefreshToken {
             id: parse_uuid(id_str, "id")?,
@@ -110,6 +125,9 @@ impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for RefreshToken {
             revoked_at: row.try_get("revoked_at")?,
             replaced_by,
             step_up_at: row.try_get("step_up_at")?,
+            scope: row.try_get("scope")?,
+            claims: row.try_get("claims")?,
+            impersonator_id,
         })
     }
 }
@@ -133,7 +151,7 @@ mod tests {
         let now = Utc::now();
 
         let token: RefreshToken = sqlx::query_as(
-        "SELECT ? as id, ? as family_id, ? as user_id, ? as realm_id, ? as client_id, ? as expires_at, ? as ip_address, ? as user_agent, ? as created_at, ? as last_used_at, ? as revoked_at, ? as replaced_by, ? as step_up_at",
+        "SELECT ? as id, ? as family_id, ? as user_id, ? as realm_id, ? as client_id, ? as expires_at, ? as ip_address, ? as user_agent, ? as created_at, ? as last_used_at, ? as revoked_at, ? as replaced_by, ? as step_up_at, ? as scope, ? as claims, ? as impersonator_id",
     )
     .bind(id.to_string())
     .bind(id.to_string())
@@ -148,6 +166,9 @@ mod tests {
     .bind::<Option<chrono::DateTime<Utc>>>(None)
     .bind::<Option<String>>(None)
     .bind::<Option<chrono::DateTime<Utc>>>(None)
+    .bind("openid email")
+    .bind::<Option<String>>(None)
+    .bind::<Option<String>>(None)
     .fetch_one(&pool)
     .await
     .expect("fetch token");
@@ -163,6 +184,8 @@ mod tests {
         assert_eq!(token.user_agent, Some("agent".to_string()));
         assert!(token.revoked_at.is_none());
         assert!(token.replaced_by.is_none());
+        assert_eq!(token.scope.as_deref(), Some("openid email"));
+        assert!(token.claims.is_none());
     }
 
     #[test]
@@ -182,6 +205,9 @@ mod tests {
             revoked_at: None,
             replaced_by: None,
             step_up_at: None,
+            scope: None,
+            claims: None,
+            impersonator_id: None,
         };
 
         let json = serde_json::to_string(&token).expect("serialize");
@@ -0,0 +1,172 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Lifecycle of a realm signing key.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SigningKeyState {
+    /// Signs new tokens. At most one per realm and algorithm.
+    Active,
+    /// Verify-only: still published in the JWKS so outstanding tokens
+    /// (or tokens from a pre-published key) keep validating.
+    Passive,
+    /// Neither signs nor verifies; kept for the audit trail.
+    Retired,
+}
+
+impl SigningKeyState {
+    pub const ALL: [Self; 3] = [Self::Active, Self::Passive, Self::Retired];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Passive => "passive",
+            Self::Retired => "retired",
+        }
+    }
+}
+
+impl std::fmt::Display for SigningKeyState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<String> for SigningKeyState {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::ALL
+            .into_iter()
+            .find(|state| state.as_str() == value)
+            .ok_or_else(|| format!("Unsupported signing key state: {}", value))
+    }
+}
+
+/// JOSE algorithm used to sign tokens. Chosen per realm, with an optional
+/// per-client override.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum SigningAlgorithm {
+    #[default]
+    #[serde(rename = "RS256")]
+    Rs256,
+    #[serde(rename = "ES256")]
+    Es256,
+    #[serde(rename = "ES384")]
+    Es384,
+    #[serde(rename = "EdDSA")]
+    EdDsa,
+}
+
+impl SigningAlgorithm {
+    pub const ALL: [Self; 4] = [Self::Rs256, Self::Es256, Self::Es384, Self::EdDsa];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Rs256 => "RS256",
+            Self::Es256 => "ES256",
+            Self::Es384 => "ES384",
+            Self::EdDsa => "EdDSA",
+        }
+    }
+}
+
+impl std::fmt::Display for SigningAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<String> for SigningAlgorithm {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.as_str() == value)
+            .ok_or_else(|| format!("Unsupported signing algorithm: {}", value))
+    }
+}
+
+// Stored as TEXT; implemented directly (rather than `try_from`) so that
+// nullable columns can decode into `Option<SigningAlgorithm>`.
+impl sqlx::Type<sqlx::Sqlite> for SigningAlgorithm {
+    fn type_info() -> sqlx::sqlite::SqliteTypeInfo {
+        <String as sqlx::Type<sqlx::Sqlite>>::type_info()
+    }
+}
+
+impl<'q> sqlx::Encode<'q, sqlx::Sqlite> for SigningAlgorithm {
+    fn encode_by_ref(
+        &self,
+        args: &mut Vec<sqlx::sqlite::SqliteArgumentValue<'q>>,
+    ) -> std::result::Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+        <String as sqlx::Encode<sqlx::Sqlite>>::encode(self.as_str().to_string(), args)
+    }
+}
+
+impl<'r> sqlx::Decode<'r, sqlx::Sqlite> for SigningAlgorithm {
+    fn decode(value: sqlx::sqlite::SqliteValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+        let s: String = <String as sqlx::Decode<sqlx::Sqlite>>::decode(value)?;
+        Ok(Self::try_from(s)?)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct SigningKey {
+    #[sqlx(try_from = "String")]
+    pub id: Uuid,
+    #[sqlx(try_from = "String")]
+    pub realm_id: Uuid,
+    /// JOSE `kid` header value; unique across realms.
+    pub kid: String,
+    pub algorithm: SigningAlgorithm,
+    #[sqlx(try_from = "String")]
+    pub state: SigningKeyState,
+    /// PKCS#8 PEM, encrypted at rest.
+    #[serde(skip_serializing)]
+    pub private_key_pem: String,
+    pub public_key_pem: String,
+    pub created_at: DateTime<Utc>,
+    /// When the key last became active. Drives scheduled rotation.
+    pub activated_at: Option<DateTime<Utc>>,
+    /// When the key stopped being active. Drives passive-key retirement.
+    pub deactivated_at: Option<DateTime<Utc>>,
+    pub retired_at: Option<DateTime<Utc>>,
+}
+
+impl SigningKey {
+    /// Whether tokens signed with this key should still validate.
+    pub fn can_verify(&self) -> bool {
+        self.state != SigningKeyState::Retired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signing_key_state_round_trips_through_string() {
+        for state in SigningKeyState::ALL {
+            assert_eq!(SigningKeyState::try_from(state.to_string()), Ok(state));
+        }
+        assert!(SigningKeyState::try_from("revoked".to_string()).is_err());
+    }
+
+    #[test]
+    fn signing_algorithm_uses_jose_names() {
+        for algorithm in SigningAlgorithm::ALL {
+            assert_eq!(
+                SigningAlgorithm::try_from(algorithm.to_string()),
+                Ok(algorithm)
+            );
+            assert_eq!(
+                serde_json::to_value(algorithm).unwrap(),
+                serde_json::json!(algorithm.as_str())
+            );
+        }
+        assert!(SigningAlgorithm::try_from("HS256".to_string()).is_err());
+    }
+}
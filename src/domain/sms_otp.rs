@@ -0,0 +1,183 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use rand::RngExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+pub const SMS_OTP_LENGTH: usize = 6;
+pub const DEFAULT_SMS_OTP_TTL_MINUTES: i64 = 5;
+pub const DEFAULT_SMS_RESEND_COOLDOWN_SECS: i64 = 30;
+pub const DEFAULT_SMS_MAX_RESENDS: u32 = 3;
+pub const DEFAULT_SMS_MESSAGE_TEMPLATE: &str =
+    "{code} is your {realm} verification code. It expires in {minutes} minutes.";
+
+/// Delivery settings captured from the issue node so the verify node can
+/// resend with the same policy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmsOtpPolicy {
+    pub ttl_minutes: i64,
+    pub resend_cooldown_secs: i64,
+    pub max_resends: u32,
+    pub message_template: Option<String>,
+}
+
+impl Default for SmsOtpPolicy {
+    fn default() -> Self {
+        Self {
+            ttl_minutes: DEFAULT_SMS_OTP_TTL_MINUTES,
+            resend_cooldown_secs: DEFAULT_SMS_RESEND_COOLDOWN_SECS,
+            max_resends: DEFAULT_SMS_MAX_RESENDS,
+            message_template: None,
+        }
+    }
+}
+
+/// Pending SMS code kept in the authentication session context. Only the
+/// hash of the code is stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmsOtpChallenge {
+    pub user_id: Uuid,
+    pub phone_number_id: Uuid,
+    pub masked_phone: String,
+    pub code_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub sent_at: DateTime<Utc>,
+    pub resend_count: u32,
+    pub policy: SmsOtpPolicy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmsOtpCheck {
+    Valid,
+    Invalid,
+    Expired,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmsResendDecision {
+    Allowed,
+    CoolingDown { retry_after_secs: i64 },
+    LimitReached,
+}
+
+impl SmsOtpChallenge {
+    pub fn check(&self, code: &str, now: DateTime<Utc>) -> SmsOtpCheck {
+        if now >= self.expires_at {
+            return SmsOtpCheck::Expired;
+        }
+        let code = code.trim();
+        if code.len() != SMS_OTP_LENGTH || !code.chars().all(|c| c.is_ascii_digit()) {
+            return SmsOtpCheck::Invalid;
+        }
+        if hash_sms_code(code) == self.code_hash {
+            SmsOtpCheck::Valid
+        } else {
+            SmsOtpCheck::Invalid
+        }
+    }
+
+    pub fn resend_decision(&self, now: DateTime<Utc>) -> SmsResendDecision {
+        if self.resend_count >= self.policy.max_resends {
+            return SmsResendDecision::LimitReached;
+        }
+        let ready_at = self.sent_at + Duration::seconds(self.policy.resend_cooldown_secs.max(0));
+        if now < ready_at {
+            let remaining = (ready_at - now).num_milliseconds();
+            return SmsResendDecision::CoolingDown {
+                retry_after_secs: (remaining + 999) / 1000,
+            };
+        }
+        SmsResendDecision::Allowed
+    }
+}
+
+pub fn generate_sms_code() -> String {
+    let mut rng = rand::rng();
+    (0..SMS_OTP_LENGTH)
+        .map(|_| rng.random_range(0..10).to_string())
+        .collect()
+}
+
+pub fn hash_sms_code(code: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(code.as_bytes());
+    URL_SAFE_NO_PAD.encode(hasher.finalize())
+}
+
+pub fn render_sms_message(template: &str, code: &str, realm: &str, ttl_minutes: i64) -> String {
+    template
+        .replace("{code}", code)
+        .replace("{realm}", realm)
+        .replace("{minutes}", &ttl_minutes.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn challenge(code: &str, now: DateTime<Utc>) -> SmsOtpChallenge {
+        SmsOtpChallenge {
+            user_id: Uuid::new_v4(),
+            phone_number_id: Uuid::new_v4(),
+            masked_phone: "*****42".to_string(),
+            code_hash: hash_sms_code(code),
+            expires_at: now + Duration::minutes(5),
+            sent_at: now,
+            resend_count: 0,
+            policy: SmsOtpPolicy {
+                max_resends: 1,
+                ..SmsOtpPolicy::default()
+            },
+        }
+    }
+
+    #[test]
+    fn check_matches_code_until_expiry() {
+        let now = Utc::now();
+        let challenge = challenge("123456", now);
+
+        assert_eq!(challenge.check(" 123456 ", now), SmsOtpCheck::Valid);
+        assert_eq!(challenge.check("654321", now), SmsOtpCheck::Invalid);
+        assert_eq!(challenge.check("12345", now), SmsOtpCheck::Invalid);
+        assert_eq!(
+            challenge.check("123456", now + Duration::minutes(5)),
+            SmsOtpCheck::Expired
+        );
+    }
+
+    #[test]
+    fn resend_honours_cooldown_and_limit() {
+        let now = Utc::now();
+        let mut challenge = challenge("123456", now);
+
+        assert_eq!(
+            challenge.resend_decision(now + Duration::seconds(10)),
+            SmsResendDecision::CoolingDown {
+                retry_after_secs: 20
+            }
+        );
+        assert_eq!(
+            challenge.resend_decision(now + Duration::seconds(30)),
+            SmsResendDecision::Allowed
+        );
+
+        challenge.resend_count = 1;
+        assert_eq!(
+            challenge.resend_decision(now + Duration::minutes(1)),
+            SmsResendDecision::LimitReached
+        );
+    }
+
+    #[test]
+    fn generated_codes_are_numeric_and_message_is_rendered() {
+        let code = generate_sms_code();
+        assert_eq!(code.len(), SMS_OTP_LENGTH);
+        assert!(code.chars().all(|c| c.is_ascii_digit()));
+        assert_eq!(
+            render_sms_message(DEFAULT_SMS_MESSAGE_TEMPLATE, "042917", "acme", 5),
+            "042917 is your acme verification code. It expires in 5 minutes."
+        );
+    }
+}
@@ -109,6 +109,18 @@ const SYSTEM_PAGES: &[ThemePageDefinition] = &[
         description: "OTP and challenge prompts.",
         category: PageCategory::Mfa,
     },
+    ThemePageDefinition {
+        key: "totp_enroll",
+        label: "Authenticator Setup",
+        description: "Authenticator app enrollment with setup key.",
+        category: PageCategory::Mfa,
+    },
+    ThemePageDefinition {
+        key: "sms_otp",
+        label: "SMS Code",
+        description: "SMS verification code entry with resend.",
+        category: PageCategory::Mfa,
+    },
     ThemePageDefinition {
         key: "consent",
         label: "Consent",
@@ -200,6 +212,8 @@ pub fn default_page_blueprint(key: &str) -> Option<Value> {
         "invitation_unavailable" => Some(default_invitation_unavailable_blueprint()),
         "verify_email" => Some(default_verify_blueprint()),
         "mfa" => Some(default_mfa_blueprint()),
+        "totp_enroll" => Some(default_totp_enroll_blueprint()),
+        "sms_otp" => Some(default_sms_otp_blueprint()),
         "consent" => Some(default_consent_blueprint()),
         "magic_link_sent" => Some(default_magic_link_blueprint()),
         "error" => Some(default_error_blueprint()),
@@ -310,6 +324,7 @@ fn default_register_blueprint() -> Value {
             { "type": "Text", "size": { "width": "fill", "height": "hug" }, "props": { "text": "Create your account" } },
             { "type": "Component", "component": "Input", "size": { "width": "fill", "height": "hug" }, "props": { "label": "Email", "name": "email", "input_type": "email" } },
             { "type": "Component", "component": "Input", "size": { "width": "fill", "height": "hug" }, "props": { "label": "Password", "name": "password", "input_type": "password" } },
+            { "type": "Text", "size": { "width": "fill", "height": "hug" }, "props": { "text_path": "password_policy_hint", "visible_if": "password_policy_hint" } },
             { "type": "Component", "component": "Button", "size": { "width": "fill", "height": "hug" }, "props": { "label": "Sign up", "variant": "primary" } }
         ]
     })
@@ -359,6 +374,7 @@ fn default_reset_password_blueprint() -> Value {
             { "type": "Text", "size": { "width": "fill", "height": "hug" }, "props": { "text": "Set a new password" } },
             { "type": "Component", "component": "Input", "size": { "width": "fill", "height": "hug" }, "props": { "label": "New password", "name": "password", "input_type": "password" } },
             { "type": "Component", "component": "Input", "size": { "width": "fill", "height": "hug" }, "props": { "label": "Confirm password", "name": "password_confirm", "input_type": "password" } },
+            { "type": "Text", "size": { "width": "fill", "height": "hug" }, "props": { "text_path": "password_policy_hint", "visible_if": "password_policy_hint" } },
             { "type": "Component", "component": "Button", "size": { "width": "fill", "height": "hug" }, "props": { "label": "Update password", "variant": "primary" } }
         ]
     })
@@ -409,6 +425,37 @@ fn default_mfa_blueprint() -> Value {
     })
 }
 
+fn default_totp_enroll_blueprint() -> Value {
+    json!({
+        "layout": "default",
+        "nodes": [
+            { "type": "Text", "size": { "width": "fill", "height": "hug" }, "props": { "text": "Set up your authenticator app" } },
+            { "type": "Text", "size": { "width": "fill", "height": "hug" }, "props": { "text": "Add this account to your authenticator app, or enter the setup key manually." } },
+            { "type": "Component", "component": "Link", "size": { "width": "fill", "height": "hug" }, "props": { "label": "Open in authenticator app", "href_path": "otpauth_uri", "target": "_self", "align": "left" } },
+            { "type": "Text", "size": { "width": "fill", "height": "hug" }, "props": { "text_path": "secret" } },
+            { "type": "Text", "size": { "width": "fill", "height": "hug" }, "props": { "text_path": "error", "visible_if": "error" } },
+            { "type": "Component", "component": "Input", "size": { "width": "fill", "height": "hug" }, "props": { "label": "Code", "name": "otp" } },
+            { "type": "Component", "component": "Button", "size": { "width": "fill", "height": "hug" }, "props": { "label": "Verify", "variant": "primary" } },
+            { "type": "Component", "component": "Button", "size": { "width": "fill", "height": "hug" }, "props": { "label": "Skip for now", "variant": "outline", "intent": "skip", "visible_if": "can_skip" } }
+        ]
+    })
+}
+
+fn default_sms_otp_blueprint() -> Value {
+    json!({
+        "layout": "default",
+        "nodes": [
+            { "type": "Text", "size": { "width": "fill", "height": "hug" }, "props": { "text": "Enter the code we sent by SMS" } },
+            { "type": "Text", "size": { "width": "fill", "height": "hug" }, "props": { "text_path": "phone", "visible_if": "phone" } },
+            { "type": "Text", "size": { "width": "fill", "height": "hug" }, "props": { "text_path": "error", "visible_if": "error" } },
+            { "type": "Component", "component": "Input", "size": { "width": "fill", "height": "hug" }, "props": { "label": "Code", "name": "otp" } },
+            { "type": "Component", "component": "Button", "size": { "width": "fill", "height": "hug" }, "props": { "label": "Verify", "variant": "primary" } },
+            { "type": "Component", "component": "Button", "size": { "width": "fill", "height": "hug" }, "props": { "label": "Resend code", "variant": "secondary", "intent": "resend", "visible_if": "can_resend" } },
+            { "type": "Text", "size": { "width": "fill", "height": "hug" }, "props": { "text_path": "resend_message", "visible_if": "resend_message" } }
+        ]
+    })
+}
+
 fn default_consent_blueprint() -> Value {
     json!({
         "layout": "default",
@@ -0,0 +1,463 @@
+//! User storage federation: realms that keep (some of) their users in an
+//! external LDAP directory or Active Directory. Users are imported into
+//! `users` and linked to their directory entry; passwords are always
+//! checked against the directory by binding as the user.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::ldap::{
+    format_object_guid, normalize_dn, Filter, Modification, SearchEntry, SearchScope,
+};
+use crate::error::Result;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FederationVendor {
+    Ldap,
+    ActiveDirectory,
+}
+
+/// Attribute names a vendor's schema uses out of the box.
+pub struct VendorDefaults {
+    pub username_attribute: &'static str,
+    pub uuid_attribute: &'static str,
+    pub user_object_classes: &'static [&'static str],
+    pub membership_attribute: &'static str,
+    pub email_attribute: &'static str,
+    pub first_name_attribute: &'static str,
+    pub last_name_attribute: &'static str,
+}
+
+impl FederationVendor {
+    pub fn defaults(self) -> VendorDefaults {
+        match self {
+            Self::Ldap => VendorDefaults {
+                username_attribute: "uid",
+                uuid_attribute: "entryUUID",
+                user_object_classes: &["inetOrgPerson", "organizationalPerson"],
+                membership_attribute: "memberOf",
+                email_attribute: "mail",
+                first_name_attribute: "givenName",
+                last_name_attribute: "sn",
+            },
+            Self::ActiveDirectory => VendorDefaults {
+                username_attribute: "sAMAccountName",
+                uuid_attribute: "objectGUID",
+                user_object_classes: &["person", "organizationalPerson", "user"],
+                membership_attribute: "memberOf",
+                email_attribute: "mail",
+                first_name_attribute: "givenName",
+                last_name_attribute: "sn",
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for FederationVendor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ldap => write!(f, "ldap"),
+            Self::ActiveDirectory => write!(f, "active_directory"),
+        }
+    }
+}
+
+impl TryFrom<String> for FederationVendor {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        match value.as_str() {
+            "ldap" => Ok(Self::Ldap),
+            "active_directory" => Ok(Self::ActiveDirectory),
+            other => Err(format!("Unknown user federation vendor '{}'", other)),
+        }
+    }
+}
+
+/// Whether local changes to federated users are written back to the
+/// directory (`Writable`) or refused (`ReadOnly`).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FederationEditMode {
+    ReadOnly,
+    Writable,
+}
+
+impl std::fmt::Display for FederationEditMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ReadOnly => write!(f, "read_only"),
+            Self::Writable => write!(f, "writable"),
+        }
+    }
+}
+
+impl TryFrom<String> for FederationEditMode {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        match value.as_str() {
+            "read_only" => Ok(Self::ReadOnly),
+            "writable" => Ok(Self::Writable),
+            other => Err(format!("Unknown user federation edit mode '{}'", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UserFederationProvider {
+    pub id: Uuid,
+    pub realm_id: Uuid,
+    pub name: String,
+    pub enabled: bool,
+    pub vendor: FederationVendor,
+    /// `ldap://` or `ldaps://` URL of the directory server.
+    pub connection_url: String,
+    pub bind_dn: Option<String>,
+    /// Encrypted with the secret service; never returned by the API.
+    #[serde(skip_serializing)]
+    pub bind_credential: Option<String>,
+    pub users_dn: String,
+    pub user_object_classes: Vec<String>,
+    pub custom_user_filter: Option<String>,
+    pub search_scope: SearchScope,
+    pub username_attribute: String,
+    pub uuid_attribute: String,
+    pub membership_attribute: String,
+    pub edit_mode: FederationEditMode,
+    /// Seconds between scheduled syncs; 0 only syncs on demand.
+    pub sync_interval_secs: i64,
+    /// Lower values are asked first when an unknown user logs in.
+    pub priority: i64,
+    pub last_sync_at: Option<DateTime<Utc>>,
+    pub last_sync_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl UserFederationProvider {
+    /// The filter selecting user entries, from the object classes and the
+    /// optional custom filter.
+    pub fn user_filter(&self) -> Result<Filter> {
+        let mut filters: Vec<Filter> = self
+            .user_object_classes
+            .iter()
+            .map(|class| Filter::equality("objectClass", class))
+            .collect();
+        if let Some(custom) = self.custom_user_filter.as_deref() {
+            filters.push(Filter::parse(custom)?);
+        }
+        Ok(match filters.len() {
+            0 => Filter::Present("objectClass".to_string()),
+            1 => filters.pop().expect("filter"),
+            _ => Filter::And(filters),
+        })
+    }
+
+    /// The filter finding the user entry for a login name.
+    pub fn username_filter(&self, username: &str) -> Result<Filter> {
+        Ok(Filter::And(vec![
+            self.user_filter()?,
+            Filter::equality(&self.username_attribute, username),
+        ]))
+    }
+
+    /// Attributes to request when reading user entries.
+    pub fn search_attributes(&self, mappers: &[UserFederationMapper]) -> Vec<String> {
+        let mut attributes = vec![
+            self.username_attribute.clone(),
+            self.uuid_attribute.clone(),
+            self.membership_attribute.clone(),
+        ];
+        for mapper in mappers {
+            if let FederationMapperKind::Attribute { ldap_attribute, .. } = &mapper.kind {
+                attributes.push(ldap_attribute.clone());
+            }
+        }
+        let mut seen = std::collections::HashSet::new();
+        attributes.retain(|attribute| seen.insert(attribute.to_ascii_lowercase()));
+        attributes
+    }
+
+    /// The stable identifier of an entry. Binary values such as Active
+    /// Directory's `objectGUID` are formatted as GUIDs.
+    pub fn external_id(&self, entry: &SearchEntry) -> Option<String> {
+        let value = entry.values(&self.uuid_attribute).first()?;
+        if self.uuid_attribute.eq_ignore_ascii_case("objectGUID")
+            || (value.len() == 16 && std::str::from_utf8(value).is_err())
+        {
+            return format_object_guid(value);
+        }
+        Some(String::from_utf8(value.clone()).ok()?.trim().to_string()).filter(|id| !id.is_empty())
+    }
+
+    /// Reads an entry through the provider's attributes and mappers.
+    /// Entries without a username or identifier are skipped.
+    pub fn map_entry(
+        &self,
+        entry: &SearchEntry,
+        mappers: &[UserFederationMapper],
+    ) -> Option<FederatedProfile> {
+        let mut profile = FederatedProfile {
+            external_id: self.external_id(entry)?,
+            dn: entry.dn.clone(),
+            username: entry.first_string(&self.username_attribute)?,
+            email: None,
+            first_name: None,
+            last_name: None,
+            group_dns: entry
+                .strings(&self.membership_attribute)
+                .iter()
+                .map(|dn| normalize_dn(dn))
+                .collect(),
+        };
+        for mapper in mappers {
+            if let FederationMapperKind::Attribute {
+                ldap_attribute,
+                user_attribute,
+            } = &mapper.kind
+            {
+                let value = entry.first_string(ldap_attribute);
+                match user_attribute {
+                    UserAttribute::Email => profile.email = value,
+                    UserAttribute::FirstName => profile.first_name = value,
+                    UserAttribute::LastName => profile.last_name = value,
+                }
+            }
+        }
+        Some(profile)
+    }
+
+    /// The change that sets a user's password in this directory. Active
+    /// Directory only accepts `unicodePwd` as a quoted UTF-16LE string, and
+    /// only over an encrypted connection.
+    pub fn password_modification(&self, password: &str) -> Modification {
+        match self.vendor {
+            FederationVendor::ActiveDirectory => {
+                let quoted = format!("\"{}\"", password);
+                let encoded = quoted
+                    .encode_utf16()
+                    .flat_map(|unit| unit.to_le_bytes())
+                    .collect();
+                Modification::replace("unicodePwd", vec![encoded])
+            }
+            FederationVendor::Ldap => {
+                Modification::replace("userPassword", vec![password.as_bytes().to_vec()])
+            }
+        }
+    }
+
+    pub fn is_sync_due(&self, now: DateTime<Utc>) -> bool {
+        if !self.enabled || self.sync_interval_secs <= 0 {
+            return false;
+        }
+        self.last_sync_at
+            .is_none_or(|last| last + Duration::seconds(self.sync_interval_secs) <= now)
+    }
+}
+
+/// Local user fields an attribute mapper can fill.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UserAttribute {
+    Email,
+    FirstName,
+    LastName,
+}
+
+impl std::fmt::Display for UserAttribute {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Email => write!(f, "email"),
+            Self::FirstName => write!(f, "first_name"),
+            Self::LastName => write!(f, "last_name"),
+        }
+    }
+}
+
+impl TryFrom<String> for UserAttribute {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        match value.as_str() {
+            "email" => Ok(Self::Email),
+            "first_name" => Ok(Self::FirstName),
+            "last_name" => Ok(Self::LastName),
+            other => Err(format!("Unknown user attribute '{}'", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FederationMapperKind {
+    /// Copies a directory attribute into a user field.
+    Attribute {
+        ldap_attribute: String,
+        user_attribute: UserAttribute,
+    },
+    /// Grants a realm role to members of a directory group. The directory
+    /// owns the grant: sync removes it from users who left the group.
+    GroupRole { group_dn: String, role_id: Uuid },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UserFederationMapper {
+    pub id: Uuid,
+    pub provider_id: Uuid,
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: FederationMapperKind,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Ties an imported user to the directory entry it came from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UserFederationLink {
+    pub user_id: Uuid,
+    pub provider_id: Uuid,
+    pub external_id: String,
+    pub dn: String,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A directory entry read through a provider's mappers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FederatedProfile {
+    pub external_id: String,
+    pub dn: String,
+    pub username: String,
+    pub email: Option<String>,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    /// Normalized DNs of the groups the entry is a member of.
+    pub group_dns: Vec<String>,
+}
+
+impl FederatedProfile {
+    pub fn is_member_of(&self, group_dn: &str) -> bool {
+        let group_dn = normalize_dn(group_dn);
+        self.group_dns.contains(&group_dn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider(vendor: FederationVendor) -> UserFederationProvider {
+        let defaults = vendor.defaults();
+        let now = Utc::now();
+        UserFederationProvider {
+            id: Uuid::new_v4(),
+            realm_id: Uuid::new_v4(),
+            name: "corp".to_string(),
+            enabled: true,
+            vendor,
+            connection_url: "ldap://127.0.0.1:389".to_string(),
+            bind_dn: None,
+            bind_credential: None,
+            users_dn: "ou=people,dc=example,dc=com".to_string(),
+            user_object_classes: defaults
+                .user_object_classes
+                .iter()
+                .map(|class| class.to_string())
+                .collect(),
+            custom_user_filter: Some("(!(disabled=TRUE))".to_string()),
+            search_scope: SearchScope::Subtree,
+            username_attribute: defaults.username_attribute.to_string(),
+            uuid_attribute: defaults.uuid_attribute.to_string(),
+            membership_attribute: defaults.membership_attribute.to_string(),
+            edit_mode: FederationEditMode::ReadOnly,
+            sync_interval_secs: 0,
+            priority: 0,
+            last_sync_at: None,
+            last_sync_error: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn mapper(ldap_attribute: &str, user_attribute: UserAttribute) -> UserFederationMapper {
+        UserFederationMapper {
+            id: Uuid::new_v4(),
+            provider_id: Uuid::new_v4(),
+            name: ldap_attribute.to_string(),
+            kind: FederationMapperKind::Attribute {
+                ldap_attribute: ldap_attribute.to_string(),
+                user_attribute,
+            },
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn maps_entries_through_attribute_mappers() {
+        let provider = provider(FederationVendor::Ldap);
+        let entry = SearchEntry::new("uid=jdoe,ou=people,dc=example,dc=com")
+            .with_values("objectClass", &["inetOrgPerson", "organizationalPerson"])
+            .with_values("uid", &["jdoe"])
+            .with_values("entryUUID", &["5f0c7a4e-1d1a-4d53-9a57-2d6f2b0c1e11"])
+            .with_values("mail", &["jdoe@example.com"])
+            .with_values("givenName", &["Jane"])
+            .with_values("memberOf", &["CN=Admins, OU=Groups,DC=example,DC=com"]);
+        let mappers = [
+            mapper("mail", UserAttribute::Email),
+            mapper("givenName", UserAttribute::FirstName),
+        ];
+
+        assert!(provider.user_filter().unwrap().matches(&entry));
+        let profile = provider.map_entry(&entry, &mappers).expect("profile");
+        assert_eq!(profile.username, "jdoe");
+        assert_eq!(profile.external_id, "5f0c7a4e-1d1a-4d53-9a57-2d6f2b0c1e11");
+        assert_eq!(profile.email.as_deref(), Some("jdoe@example.com"));
+        assert_eq!(profile.first_name.as_deref(), Some("Jane"));
+        assert_eq!(profile.last_name, None);
+        assert!(profile.is_member_of("cn=admins,ou=groups,dc=example,dc=com"));
+        assert_eq!(
+            provider.search_attributes(&mappers),
+            vec!["uid", "entryUUID", "memberOf", "mail", "givenName"]
+        );
+    }
+
+    #[test]
+    fn active_directory_uses_object_guid_and_unicode_passwords() {
+        let provider = provider(FederationVendor::ActiveDirectory);
+        let guid = [
+            0x78, 0x56, 0x34, 0x12, 0x34, 0x12, 0x78, 0x56, 0x90, 0xab, 0xcd, 0xef, 0x01, 0x23,
+            0x45, 0x67,
+        ];
+        let mut entry = SearchEntry::new("CN=Jane Doe,OU=Staff,DC=corp,DC=example")
+            .with_values("sAMAccountName", &["jdoe"]);
+        entry.set("objectGUID", vec![guid.to_vec()]);
+        assert_eq!(
+            provider.external_id(&entry).as_deref(),
+            Some("12345678-1234-5678-90ab-cdef01234567")
+        );
+
+        let change = provider.password_modification("Pa55");
+        assert_eq!(change.attribute, "unicodePwd");
+        assert_eq!(
+            change.values,
+            vec![vec![b'"', 0, b'P', 0, b'a', 0, b'5', 0, b'5', 0, b'"', 0]]
+        );
+    }
+
+    #[test]
+    fn scheduled_sync_respects_the_interval() {
+        let mut provider = provider(FederationVendor::Ldap);
+        let now = Utc::now();
+        assert!(!provider.is_sync_due(now));
+        provider.sync_interval_secs = 600;
+        assert!(provider.is_sync_due(now));
+        provider.last_sync_at = Some(now - Duration::seconds(60));
+        assert!(!provider.is_sync_due(now));
+        provider.last_sync_at = Some(now - Duration::seconds(600));
+        assert!(provider.is_sync_due(now));
+        provider.enabled = false;
+        assert!(!provider.is_sync_due(now));
+    }
+}
@@ -48,3 +48,16 @@ pub fn normalize_phone_number(phone_number: &str) -> String {
         .filter(|character| character.is_ascii_digit() || *character == '+')
         .collect()
 }
+
+/// Hides all but the last two digits, e.g. for "we sent a code to ..." hints.
+pub fn mask_phone_number(phone_number: &str) -> String {
+    let digits: Vec<char> = normalize_phone_number(phone_number)
+        .chars()
+        .filter(|character| character.is_ascii_digit())
+        .collect();
+    let visible = digits.len().min(2);
+    let hidden = digits.len() - visible;
+    let mut masked = "*".repeat(hidden);
+    masked.extend(&digits[hidden..]);
+    masked
+}
@@ -1,6 +1,7 @@
 //! Defines the custom `Error` and `Result` types for the crate application.
 
 use crate::domain::flow::models::FlowPublishValidation;
+use crate::domain::password_policy::PasswordPolicyViolation;
 use http::header::InvalidHeaderValue;
 
 /// A specialized `Result` type for crate operations.
@@ -96,6 +97,48 @@ pub enum Error {
     #[error("OIDC Request Error: {0}")]
     OidcInvalidRequest(String),
 
+    #[error("OIDC client authentication failed: {0}")]
+    OidcInvalidClient(String),
+
+    #[error("OIDC client is not authorized for this grant: {0}")]
+    OidcUnauthorizedClient(String),
+
+    #[error("Invalid grant: {0}")]
+    OidcInvalidGrant(String),
+
+    #[error("Invalid scope: {0}")]
+    OidcInvalidScope(String),
+
+    #[error("Invalid target: {0}")]
+    OidcInvalidTarget(String),
+
+    #[error("Device authorization is pending")]
+    OidcAuthorizationPending,
+
+    #[error("Device is polling too frequently")]
+    OidcSlowDown,
+
+    #[error("Device code has expired")]
+    OidcExpiredToken,
+
+    #[error("Access denied: {0}")]
+    OidcAccessDenied(String),
+
+    #[error("Invalid request_uri: {0}")]
+    OidcInvalidRequestUri(String),
+
+    #[error("Invalid request object: {0}")]
+    OidcInvalidRequestObject(String),
+
+    #[error("Invalid client metadata: {0}")]
+    OidcInvalidClientMetadata(String),
+
+    #[error("SAML Request Error: {0}")]
+    SamlInvalidRequest(String),
+
+    #[error("Invalid token: {0}")]
+    OidcInvalidToken(String),
+
     #[error("Validation failed: {0}")]
     Validation(String),
 
@@ -114,6 +157,9 @@ pub enum Error {
     #[error("Validation failed: {0}")]
     FlowPublishValidation(FlowPublishValidation),
 
+    #[error("Password does not meet the password policy")]
+    PasswordPolicy(Vec<PasswordPolicyViolation>),
+
     #[error("Not found: {0}")]
     NotFound(String),
 
@@ -122,4 +168,7 @@ pub enum Error {
 
     #[error("Security violation: {0}")]
     SecurityViolation(String),
+
+    #[error("Insufficient Permissions")]
+    InsufficientPermissions,
 }
@@ -1,13 +1,14 @@
 use rand::distr::{Alphanumeric, SampleString};
 use reauth::adapters::persistence::sqlite_realm_repository::SqliteRealmRepository;
 use reauth::adapters::persistence::sqlite_user_repository::SqliteUserRepository;
+use reauth::application::harbor::{read_bundle_dir, HarborPlanAction, HarborReconcilePlan};
 use reauth::bootstrap::database::initialize_database;
 use reauth::bootstrap::seed::history::SeedHistory;
 use reauth::constants::DEFAULT_REALM_NAME;
 use reauth::domain::crypto::HashedPassword;
 use reauth::ports::realm_repository::RealmRepository;
 use reauth::ports::user_repository::UserRepository;
-use reauth::{adapters::run_migrations, config::Settings, initialize, run};
+use reauth::{adapters::run_migrations, config::Settings, initialize, initialize_for_cli, run};
 use std::env::{args, set_var};
 use std::fs;
 use std::path::PathBuf;
@@ -29,6 +30,12 @@ Flags:
 
 Admin:
   reauth admin reset-password --user <username> [--realm <realm>] [--password <password>]
+
+Harbor:
+  reauth harbor reconcile --dir <path> [--realm <realm>] [--prune] [--apply] [--json]
+      Diff the bundles under <path> against the realm and print the plan.
+      --apply writes the plan in one transaction; --prune also deletes
+      resources of the declared kinds that the bundles no longer list.
 "#;
 
 #[tokio::main]
@@ -49,6 +56,10 @@ async fn main() -> anyhow::Result<()> {
         return handle_admin_command(admin_command).await;
     }
 
+    if let Some(harbor_command) = parse_harbor_command(&args)? {
+        return handle_harbor_command(harbor_command).await;
+    }
+
     if args.iter().any(|a| a == "--print-config") {
         let settings = Settings::new()?;
         let redacted = settings.redacted();
@@ -120,6 +131,16 @@ enum AdminCommand {
     },
 }
 
+enum HarborCommand {
+    Reconcile {
+        realm: String,
+        dir: PathBuf,
+        prune: bool,
+        apply: bool,
+        json: bool,
+    },
+}
+
 fn parse_config_path(args: &[String]) -> anyhow::Result<Option<String>> {
     for (idx, arg) in args.iter().enumerate() {
         if let Some(value) = arg.strip_prefix("--config=") {
@@ -158,6 +179,28 @@ fn parse_admin_command(args: &[String]) -> anyhow::Result<Option<AdminCommand>>
     }))
 }
 
+fn parse_harbor_command(args: &[String]) -> anyhow::Result<Option<HarborCommand>> {
+    let Some(harbor_idx) = args.iter().position(|arg| arg == "harbor") else {
+        return Ok(None);
+    };
+    let command = args.get(harbor_idx + 1).map(String::as_str);
+    if command != Some("reconcile") {
+        return Ok(None);
+    }
+
+    let dir = parse_arg_value(args, "--dir")
+        .ok_or_else(|| anyhow::anyhow!("--dir is required for harbor reconcile"))?;
+    let realm = parse_arg_value(args, "--realm").unwrap_or_else(|| DEFAULT_REALM_NAME.to_string());
+
+    Ok(Some(HarborCommand::Reconcile {
+        realm,
+        dir: PathBuf::from(dir),
+        prune: args.iter().any(|a| a == "--prune"),
+        apply: args.iter().any(|a| a == "--apply"),
+        json: args.iter().any(|a| a == "--json"),
+    }))
+}
+
 fn parse_arg_value(args: &[String], key: &str) -> Option<String> {
     for (idx, arg) in args.iter().enumerate() {
         if let Some(value) = arg.strip_prefix(&format!("{}=", key)) {
@@ -194,6 +237,104 @@ async fn handle_admin_command(command: AdminCommand) -> anyhow::Result<()> {
     }
 }
 
+async fn handle_harbor_command(command: HarborCommand) -> anyhow::Result<()> {
+    match command {
+        HarborCommand::Reconcile {
+            realm,
+            dir,
+            prune,
+            apply,
+            json,
+        } => {
+            let bundle = read_bundle_dir(&dir)?;
+            let app_state = initialize_for_cli().await?;
+            let realm = app_state
+                .realm_service
+                .find_by_name(&realm)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Realm '{}' not found", realm))?;
+
+            if !apply {
+                let plan = app_state
+                    .harbor_service
+                    .plan_reconcile(realm.id, bundle, prune)
+                    .await?;
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&plan)?);
+                } else {
+                    print_reconcile_plan(&plan);
+                }
+                return Ok(());
+            }
+
+            let result = app_state
+                .harbor_service
+                .apply_reconcile(realm.id, bundle, prune)
+                .await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&result)?);
+                return Ok(());
+            }
+            print_reconcile_plan(&result.plan);
+            if let Some(import) = &result.import {
+                for resource in &import.resources {
+                    for error in &resource.errors {
+                        eprintln!("Warning: {}: {}", resource.key, error);
+                    }
+                }
+                for warning in &import.warnings {
+                    eprintln!("Warning: {}", warning);
+                }
+            }
+            if result.applied {
+                println!("Applied to realm '{}'.", realm.name);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn print_reconcile_plan(plan: &HarborReconcilePlan) {
+    for entry in &plan.entries {
+        let marker = match entry.action {
+            HarborPlanAction::Create => '+',
+            HarborPlanAction::Update => '~',
+            HarborPlanAction::Delete => '-',
+            HarborPlanAction::Unchanged => continue,
+        };
+        let name = if entry.name.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", entry.name)
+        };
+        if entry.changed_fields.is_empty() {
+            println!("  {} {}{}", marker, entry.key, name);
+        } else {
+            println!(
+                "  {} {}{} ({})",
+                marker,
+                entry.key,
+                name,
+                entry.changed_fields.join(", ")
+            );
+        }
+    }
+    for warning in &plan.warnings {
+        eprintln!("Warning: {}", warning);
+    }
+    if !plan.has_changes() {
+        println!(
+            "No changes. {} resource(s) up to date.",
+            plan.summary.unchanged
+        );
+        return;
+    }
+    println!(
+        "Plan: {} to create, {} to update, {} to delete, {} unchanged.",
+        plan.summary.create, plan.summary.update, plan.summary.delete, plan.summary.unchanged
+    );
+}
+
 async fn admin_reset_password(
     realm_name: &str,
     username: &str,
@@ -0,0 +1,30 @@
+use crate::domain::client_registration::{
+    ClientRegistration, ClientRegistrationPolicy, InitialAccessToken,
+};
+use crate::error::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[async_trait]
+pub trait ClientRegistrationRepository: Send + Sync {
+    async fn find_policy(&self, realm_id: &Uuid) -> Result<Option<ClientRegistrationPolicy>>;
+    async fn upsert_policy(&self, policy: &ClientRegistrationPolicy) -> Result<()>;
+
+    async fn create_initial_access_token(&self, token: &InitialAccessToken) -> Result<()>;
+    async fn list_initial_access_tokens(&self, realm_id: &Uuid) -> Result<Vec<InitialAccessToken>>;
+    async fn find_initial_access_token(
+        &self,
+        realm_id: &Uuid,
+        token_hash: &str,
+    ) -> Result<Option<InitialAccessToken>>;
+    /// Takes one use off the token. Returns false when it is used up or
+    /// expired at `now`, so concurrent registrations cannot overspend it.
+    async fn consume_initial_access_token(&self, id: &Uuid, now: DateTime<Utc>) -> Result<bool>;
+    async fn delete_initial_access_token(&self, realm_id: &Uuid, id: &Uuid) -> Result<bool>;
+
+    async fn create_registration(&self, registration: &ClientRegistration) -> Result<()>;
+    /// The registration of a client, by the client's row id.
+    async fn find_registration(&self, client_id: &Uuid) -> Result<Option<ClientRegistration>>;
+    async fn update_registration(&self, registration: &ClientRegistration) -> Result<()>;
+}
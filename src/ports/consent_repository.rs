@@ -0,0 +1,20 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::consent_grant::ConsentGrant;
+use crate::error::Result;
+
+#[async_trait]
+pub trait ConsentRepository: Send + Sync {
+    async fn find(
+        &self,
+        realm_id: &Uuid,
+        user_id: &Uuid,
+        client_id: &str,
+    ) -> Result<Option<ConsentGrant>>;
+    async fn list_for_user(&self, realm_id: &Uuid, user_id: &Uuid) -> Result<Vec<ConsentGrant>>;
+    /// Inserts the grant, or replaces the scopes of the user's existing grant
+    /// for the same client.
+    async fn save(&self, grant: &ConsentGrant) -> Result<()>;
+    async fn delete(&self, realm_id: &Uuid, user_id: &Uuid, client_id: &str) -> Result<bool>;
+}
@@ -0,0 +1,33 @@
+use crate::domain::oidc::{DeviceAuthorization, DeviceAuthorizationStatus};
+use crate::error::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[async_trait]
+pub trait DeviceAuthorizationRepository: Send + Sync {
+    async fn create(&self, authorization: &DeviceAuthorization) -> Result<()>;
+    async fn find_by_device_code(&self, device_code: &str) -> Result<Option<DeviceAuthorization>>;
+    async fn find_by_user_code(
+        &self,
+        realm_id: &Uuid,
+        user_code: &str,
+    ) -> Result<Option<DeviceAuthorization>>;
+    /// Approves or denies a pending, unexpired authorization.
+    async fn resolve(
+        &self,
+        device_code: &str,
+        status: DeviceAuthorizationStatus,
+        user_id: Option<Uuid>,
+        now: DateTime<Utc>,
+    ) -> Result<bool>;
+    async fn record_poll(
+        &self,
+        device_code: &str,
+        polled_at: DateTime<Utc>,
+        interval_secs: i64,
+    ) -> Result<()>;
+    /// Deletes an approved authorization; only one poll can redeem it.
+    async fn consume(&self, device_code: &str) -> Result<bool>;
+    async fn delete_expired_before(&self, cutoff: DateTime<Utc>, batch_size: i64) -> Result<u64>;
+}
@@ -0,0 +1,15 @@
+use async_trait::async_trait;
+
+use crate::domain::user::User;
+use crate::error::Result;
+
+/// Writes local changes of users imported from a user federation provider
+/// back to their directory. Users without a federation link are left alone.
+#[async_trait]
+pub trait FederatedUserStore: Send + Sync {
+    /// Called before a new password is stored locally. Fails when the
+    /// directory is read-only or refuses the password.
+    async fn update_password(&self, user: &User, new_password: &str) -> Result<()>;
+    /// Called before profile changes (`current` to `updated`) are stored.
+    async fn update_profile(&self, current: &User, updated: &User) -> Result<()>;
+}
@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+
+use crate::domain::ldap::{LdapResult, Modification, SearchEntry, SearchRequest};
+use crate::error::Result;
+
+/// Opens connections to LDAP directories. Errors mean the directory could
+/// not be reached or spoke an unexpected protocol; directory refusals are
+/// reported as non-success [`LdapResult`]s.
+#[async_trait]
+pub trait LdapConnector: Send + Sync {
+    /// Connects to an `ldap://` or `ldaps://` URL.
+    async fn connect(&self, url: &str) -> Result<Box<dyn LdapConnection>>;
+}
+
+#[async_trait]
+pub trait LdapConnection: Send {
+    async fn simple_bind(&mut self, dn: &str, password: &str) -> Result<LdapResult>;
+    /// Runs a search to completion, following paged results when the
+    /// server supports them.
+    async fn search(&mut self, request: SearchRequest) -> Result<Vec<SearchEntry>>;
+    async fn modify(&mut self, dn: &str, changes: Vec<Modification>) -> Result<LdapResult>;
+    async fn unbind(&mut self) -> Result<()>;
+}
@@ -2,8 +2,12 @@ pub mod audit_repository;
 pub mod auth_session_action_repository;
 pub mod auth_session_repository;
 pub mod cache_service;
+pub mod client_registration_repository;
+pub mod consent_repository;
+pub mod device_authorization_repository;
 pub mod event_bus;
 pub mod federated_identity_repository;
+pub mod federated_user_store;
 pub mod flow_repository;
 pub mod flow_store;
 pub mod harbor_job_conflict_repository;
@@ -11,6 +15,7 @@ pub mod harbor_job_repository;
 pub mod http_client;
 pub mod identity_provider_repository;
 pub mod invitation_repository;
+pub mod ldap_client;
 pub mod login_attempt_repository;
 pub mod oauth_broker_state_repository;
 pub mod oauth_start_attempt_repository;
@@ -18,6 +23,10 @@ pub mod oidc_repository;
 pub mod outbox_repository;
 pub mod passkey_challenge_repository;
 pub mod passkey_credential_repository;
+pub mod password_history_repository;
+pub mod password_policy_repository;
+pub mod protocol_mapper_repository;
+pub mod pushed_authorization_request_repository;
 pub mod rbac_repository;
 pub mod realm_email_settings_repository;
 pub mod realm_idp_settings_repository;
@@ -26,12 +35,18 @@ pub mod realm_recovery_settings_repository;
 pub mod realm_repository;
 pub mod realm_security_headers_repository;
 pub mod recovery_attempt_repository;
+pub mod saml_service_provider_repository;
+pub mod scim_repository;
 pub mod session_repository;
+pub mod signing_key_repository;
+pub mod sms_sender;
 pub mod telemetry_repository;
 pub mod theme_repository;
 pub mod token_service;
+pub mod totp_credential_repository;
 pub mod transaction_manager;
 pub mod user_email_repository;
+pub mod user_federation_repository;
 pub mod user_phone_number_repository;
 pub mod user_repository;
 pub mod webhook_repository;
@@ -5,6 +5,7 @@ use crate::{
     error::Result,
 };
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
 #[async_trait]
@@ -16,6 +17,14 @@ pub trait OidcRepository: Send + Sync {
         realm_id: &Uuid,
         client_id: &str,
     ) -> Result<Option<OidcClient>>;
+    async fn find_client_by_id_with_tx(
+        &self,
+        realm_id: &Uuid,
+        client_id: &str,
+        _tx: Option<&mut dyn Transaction>,
+    ) -> Result<Option<OidcClient>> {
+        self.find_client_by_id(realm_id, client_id).await
+    }
     async fn create_client(&self, client: &OidcClient) -> Result<()>;
     async fn create_client_with_tx(
         &self,
@@ -35,6 +44,13 @@ pub trait OidcRepository: Send + Sync {
     async fn count_client_delete_summary(&self, id: &Uuid) -> Result<ClientDeleteSummary>;
 
     async fn delete_client(&self, id: &Uuid) -> Result<()>;
+    async fn delete_client_with_tx(
+        &self,
+        id: &Uuid,
+        _tx: Option<&mut dyn Transaction>,
+    ) -> Result<()> {
+        self.delete_client(id).await
+    }
 
     async fn find_client_by_uuid(&self, id: &Uuid) -> Result<Option<OidcClient>>;
     async fn update_client(&self, client: &OidcClient) -> Result<()>;
@@ -51,4 +67,14 @@ pub trait OidcRepository: Send + Sync {
     async fn find_auth_code_by_code(&self, code: &str) -> Result<Option<AuthCode>>;
     async fn delete_auth_code(&self, code: &str) -> Result<()>;
     async fn is_origin_allowed(&self, origin: &str) -> Result<bool>;
+
+    /// Records the `jti` of a client assertion until `expires_at`. Returns
+    /// false when the client already used that `jti`, which is how replayed
+    /// assertions are rejected.
+    async fn record_client_assertion_jti(
+        &self,
+        client_id: &Uuid,
+        jti: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool>;
 }
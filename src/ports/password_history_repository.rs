@@ -0,0 +1,24 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::password_policy::PasswordHistoryEntry;
+use crate::error::Result;
+use crate::ports::transaction_manager::Transaction;
+
+#[async_trait]
+pub trait PasswordHistoryRepository: Send + Sync {
+    /// Returns up to `limit` entries for the user, newest first.
+    async fn list_recent(&self, user_id: &Uuid, limit: i64) -> Result<Vec<PasswordHistoryEntry>>;
+    async fn insert(
+        &self,
+        entry: &PasswordHistoryEntry,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()>;
+    /// Deletes all but the newest `keep` entries for the user.
+    async fn prune(
+        &self,
+        user_id: &Uuid,
+        keep: i64,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()>;
+}
@@ -0,0 +1,13 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::password_policy::PasswordPolicy;
+use crate::error::Result;
+use crate::ports::transaction_manager::Transaction;
+
+#[async_trait]
+pub trait PasswordPolicyRepository: Send + Sync {
+    async fn find_by_realm_id(&self, realm_id: &Uuid) -> Result<Option<PasswordPolicy>>;
+    async fn upsert(&self, policy: &PasswordPolicy, tx: Option<&mut dyn Transaction>)
+        -> Result<()>;
+}
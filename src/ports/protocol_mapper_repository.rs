@@ -0,0 +1,14 @@
+use crate::domain::claims::ProtocolMapper;
+use crate::error::Result;
+use async_trait::async_trait;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait ProtocolMapperRepository: Send + Sync {
+    /// Mappers of a client (by row id), oldest first.
+    async fn list_for_client(&self, client_id: &Uuid) -> Result<Vec<ProtocolMapper>>;
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<ProtocolMapper>>;
+    async fn create(&self, mapper: &ProtocolMapper) -> Result<()>;
+    async fn update(&self, mapper: &ProtocolMapper) -> Result<()>;
+    async fn delete(&self, id: &Uuid) -> Result<()>;
+}
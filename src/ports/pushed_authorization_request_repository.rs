@@ -0,0 +1,21 @@
+use crate::domain::oidc::PushedAuthorizationRequest;
+use crate::error::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[async_trait]
+pub trait PushedAuthorizationRequestRepository: Send + Sync {
+    async fn create(&self, request: &PushedAuthorizationRequest) -> Result<()>;
+    /// Deletes and returns the request `client_id` pushed behind
+    /// `request_uri`, so it can be redeemed only once. Expired requests are
+    /// not returned.
+    async fn consume(
+        &self,
+        realm_id: &Uuid,
+        client_id: &str,
+        request_uri: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<PushedAuthorizationRequest>>;
+    async fn delete_expired_before(&self, cutoff: DateTime<Utc>, batch_size: i64) -> Result<u64>;
+}
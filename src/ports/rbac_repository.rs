@@ -67,8 +67,20 @@ pub trait RbacRepository: Send + Sync {
 
     // --- Read ---
     async fn find_role_by_name(&self, realm_id: &Uuid, name: &str) -> Result<Option<Role>>;
+    async fn find_role_by_name_with_tx(
+        &self,
+        realm_id: &Uuid,
+        name: &str,
+        _tx: Option<&mut dyn Transaction>,
+    ) -> Result<Option<Role>> {
+        self.find_role_by_name(realm_id, name).await
+    }
     async fn find_group_by_name(&self, realm_id: &Uuid, name: &str) -> Result<Option<Group>>;
     async fn find_group_by_id(&self, group_id: &Uuid) -> Result<Option<Group>>;
+    /// The realm an OIDC client is registered in (scoped grant validation).
+    async fn find_client_realm_id(&self, client_id: &Uuid) -> Result<Option<Uuid>>;
+    /// Whether the realm is the system (master) realm whose roles administer others.
+    async fn is_system_realm(&self, realm_id: &Uuid) -> Result<bool>;
 
     async fn count_role_stats(&self, realm_id: &Uuid) -> Result<RoleStats>;
     async fn list_roles(&self, realm_id: &Uuid, req: &PageRequest) -> Result<PageResponse<Role>>;
@@ -78,6 +90,16 @@ pub trait RbacRepository: Send + Sync {
         client_id: &Uuid,
         req: &PageRequest,
     ) -> Result<PageResponse<Role>>;
+    /// All roles defined on a client, unpaginated (client_credentials grants).
+    async fn find_roles_for_client(&self, realm_id: &Uuid, client_id: &Uuid) -> Result<Vec<Role>>;
+    async fn find_roles_for_client_with_tx(
+        &self,
+        realm_id: &Uuid,
+        client_id: &Uuid,
+        _tx: Option<&mut dyn Transaction>,
+    ) -> Result<Vec<Role>> {
+        self.find_roles_for_client(realm_id, client_id).await
+    }
     // Find a specific role by ID (for validation)
     async fn find_role_by_id(&self, role_id: &Uuid) -> Result<Option<Role>>;
     // List groups in a realm (for listing)
@@ -148,6 +170,13 @@ pub trait RbacRepository: Send + Sync {
         ancestor_id: &Uuid,
         candidate_id: &Uuid,
     ) -> Result<bool>;
+    /// True when the user is a member of `root_id` or one of its descendants.
+    async fn is_user_in_group_subtree(
+        &self,
+        realm_id: &Uuid,
+        root_id: &Uuid,
+        user_id: &Uuid,
+    ) -> Result<bool>;
     async fn get_next_group_sort_order(
         &self,
         realm_id: &Uuid,
@@ -173,6 +202,8 @@ pub trait RbacRepository: Send + Sync {
     async fn get_effective_permissions_for_user(&self, user_id: &Uuid) -> Result<HashSet<String>>;
     async fn find_role_names_for_user(&self, user_id: &Uuid) -> Result<Vec<String>>;
     async fn find_group_names_for_user(&self, user_id: &Uuid) -> Result<Vec<String>>;
+    /// Full paths (`/parent/child`) of the user's direct groups.
+    async fn find_group_paths_for_user(&self, user_id: &Uuid) -> Result<Vec<String>>;
     async fn delete_role(&self, role_id: &Uuid, tx: Option<&mut dyn Transaction>) -> Result<()>;
     async fn delete_groups(
         &self,
@@ -3,9 +3,14 @@ use uuid::Uuid;
 
 use crate::domain::realm_email_settings::RealmEmailSettings;
 use crate::error::Result;
+use crate::ports::transaction_manager::Transaction;
 
 #[async_trait]
 pub trait RealmEmailSettingsRepository: Send + Sync {
     async fn find_by_realm_id(&self, realm_id: &Uuid) -> Result<Option<RealmEmailSettings>>;
-    async fn upsert(&self, settings: &RealmEmailSettings) -> Result<()>;
+    async fn upsert(
+        &self,
+        settings: &RealmEmailSettings,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()>;
 }
@@ -3,9 +3,14 @@ use uuid::Uuid;
 
 use crate::domain::realm_idp_settings::RealmIdpSettings;
 use crate::error::Result;
+use crate::ports::transaction_manager::Transaction;
 
 #[async_trait]
 pub trait RealmIdpSettingsRepository: Send + Sync {
     async fn find_by_realm_id(&self, realm_id: &Uuid) -> Result<Option<RealmIdpSettings>>;
-    async fn upsert(&self, settings: &RealmIdpSettings) -> Result<()>;
+    async fn upsert(
+        &self,
+        settings: &RealmIdpSettings,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()>;
 }
@@ -3,9 +3,14 @@ use uuid::Uuid;
 
 use crate::domain::realm_passkey_settings::RealmPasskeySettings;
 use crate::error::Result;
+use crate::ports::transaction_manager::Transaction;
 
 #[async_trait]
 pub trait RealmPasskeySettingsRepository: Send + Sync {
     async fn find_by_realm_id(&self, realm_id: &Uuid) -> Result<Option<RealmPasskeySettings>>;
-    async fn upsert(&self, settings: &RealmPasskeySettings) -> Result<()>;
+    async fn upsert(
+        &self,
+        settings: &RealmPasskeySettings,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()>;
 }
@@ -3,9 +3,14 @@ use uuid::Uuid;
 
 use crate::domain::realm_recovery_settings::RealmRecoverySettings;
 use crate::error::Result;
+use crate::ports::transaction_manager::Transaction;
 
 #[async_trait]
 pub trait RealmRecoverySettingsRepository: Send + Sync {
     async fn find_by_realm_id(&self, realm_id: &Uuid) -> Result<Option<RealmRecoverySettings>>;
-    async fn upsert(&self, settings: &RealmRecoverySettings) -> Result<()>;
+    async fn upsert(
+        &self,
+        settings: &RealmRecoverySettings,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()>;
 }
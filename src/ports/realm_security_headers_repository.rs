@@ -1,10 +1,15 @@
 use crate::domain::realm_security_headers::RealmSecurityHeaders;
 use crate::error::Result;
+use crate::ports::transaction_manager::Transaction;
 use async_trait::async_trait;
 use uuid::Uuid;
 
 #[async_trait]
 pub trait RealmSecurityHeadersRepository: Send + Sync {
     async fn find_by_realm_id(&self, realm_id: &Uuid) -> Result<Option<RealmSecurityHeaders>>;
-    async fn upsert(&self, settings: &RealmSecurityHeaders) -> Result<()>;
+    async fn upsert(
+        &self,
+        settings: &RealmSecurityHeaders,
+        tx: Option<&mut dyn Transaction>,
+    ) -> Result<()>;
 }
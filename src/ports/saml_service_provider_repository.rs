@@ -0,0 +1,19 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::saml::SamlServiceProvider;
+use crate::error::Result;
+
+#[async_trait]
+pub trait SamlServiceProviderRepository: Send + Sync {
+    async fn create(&self, provider: &SamlServiceProvider) -> Result<()>;
+    async fn update(&self, provider: &SamlServiceProvider) -> Result<()>;
+    async fn find_by_id(&self, realm_id: &Uuid, id: &Uuid) -> Result<Option<SamlServiceProvider>>;
+    async fn find_by_entity_id(
+        &self,
+        realm_id: &Uuid,
+        entity_id: &str,
+    ) -> Result<Option<SamlServiceProvider>>;
+    async fn list_by_realm(&self, realm_id: &Uuid) -> Result<Vec<SamlServiceProvider>>;
+    async fn delete(&self, realm_id: &Uuid, id: &Uuid) -> Result<bool>;
+}
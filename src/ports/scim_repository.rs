@@ -0,0 +1,28 @@
+use crate::domain::scim::ScimResourceType;
+use crate::error::Result;
+use async_trait::async_trait;
+use uuid::Uuid;
+
+/// The externalIds SCIM clients assign to users and groups.
+#[async_trait]
+pub trait ScimRepository: Send + Sync {
+    async fn find_external_id(
+        &self,
+        resource_type: ScimResourceType,
+        resource_id: &Uuid,
+    ) -> Result<Option<String>>;
+    async fn find_by_external_id(
+        &self,
+        resource_type: ScimResourceType,
+        realm_id: &Uuid,
+        external_id: &str,
+    ) -> Result<Option<Uuid>>;
+    /// Sets or, with `None`, clears the resource's externalId.
+    async fn set_external_id(
+        &self,
+        resource_type: ScimResourceType,
+        realm_id: &Uuid,
+        resource_id: &Uuid,
+        external_id: Option<&str>,
+    ) -> Result<()>;
+}
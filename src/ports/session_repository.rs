@@ -9,6 +9,14 @@ pub trait SessionRepository: Send + Sync {
     async fn save(&self, token: &RefreshToken) -> Result<()>;
     async fn find_by_id(&self, id: &Uuid) -> Result<Option<RefreshToken>>;
     async fn find_by_id_any(&self, id: &Uuid) -> Result<Option<RefreshToken>>;
+    /// Returns the live (unexpired, unrevoked, unrotated) token of a family.
+    async fn find_active_in_family(&self, family_id: &Uuid) -> Result<Option<RefreshToken>>;
+    /// Returns a user's live (unexpired, unrevoked, unrotated) tokens in a realm.
+    async fn list_active_for_user(
+        &self,
+        realm_id: &Uuid,
+        user_id: &Uuid,
+    ) -> Result<Vec<RefreshToken>>;
     async fn delete_by_id(&self, id: &Uuid) -> Result<()>;
     async fn mark_replaced(&self, old_id: &Uuid, new_id: &Uuid) -> Result<()>;
     async fn revoke_family(&self, family_id: &Uuid) -> Result<()>;
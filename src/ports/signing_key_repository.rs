@@ -0,0 +1,28 @@
+use crate::domain::signing_key::{SigningAlgorithm, SigningKey};
+use crate::error::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[async_trait]
+pub trait SigningKeyRepository: Send + Sync {
+    async fn create(&self, key: &SigningKey) -> Result<()>;
+    async fn find_by_id(&self, realm_id: &Uuid, id: &Uuid) -> Result<Option<SigningKey>>;
+    async fn find_by_kid(&self, kid: &str) -> Result<Option<SigningKey>>;
+    async fn find_active(
+        &self,
+        realm_id: &Uuid,
+        algorithm: SigningAlgorithm,
+    ) -> Result<Option<SigningKey>>;
+    /// All keys of a realm, newest first.
+    async fn list_by_realm(&self, realm_id: &Uuid) -> Result<Vec<SigningKey>>;
+    /// The active keys of every realm.
+    async fn list_active(&self) -> Result<Vec<SigningKey>>;
+    /// Makes `id` the realm's active key for its algorithm and demotes the
+    /// previous active key of that algorithm to passive, in one transaction.
+    async fn activate(&self, realm_id: &Uuid, id: &Uuid, at: DateTime<Utc>) -> Result<()>;
+    async fn retire(&self, realm_id: &Uuid, id: &Uuid, at: DateTime<Utc>) -> Result<()>;
+    /// Retires passive keys that stopped signing before `cutoff`. Returns the
+    /// number of keys retired.
+    async fn retire_passive_before(&self, cutoff: DateTime<Utc>) -> Result<u64>;
+}
@@ -0,0 +1,18 @@
+use crate::error::Result;
+use async_trait::async_trait;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct SmsMessage {
+    pub realm_id: Uuid,
+    pub realm_name: String,
+    /// Destination in the form the user entered it; gateways do their own
+    /// E.164 handling.
+    pub to: String,
+    pub body: String,
+}
+
+#[async_trait]
+pub trait SmsSender: Send + Sync {
+    async fn send(&self, message: &SmsMessage) -> Result<()>;
+}
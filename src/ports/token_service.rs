@@ -1,5 +1,9 @@
-use crate::{domain::user::User, error::Result};
+use crate::{
+    domain::{oidc::OidcClient, user::User},
+    error::Result,
+};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::collections::HashSet;
 use uuid::Uuid;
 
@@ -14,11 +18,40 @@ pub struct IdTokenClaims {
     // Profile Claims
     pub preferred_username: String,
     pub groups: Vec<String>,
-    // You can add email, picture, etc. here later
+    /// Session ID (the refresh token family), echoed in logout tokens and
+    /// front-channel logout requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<Uuid>,
+    /// Scope claims and protocol mapper claims released to the client.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-/// The claims (payload) for our Access Token (JWT)
+/// Claims of an OIDC Back-Channel Logout token.
 #[derive(Debug, Serialize, Deserialize)]
+pub struct LogoutTokenClaims {
+    pub iss: String,
+    pub aud: String,
+    pub iat: i64,
+    pub exp: i64,
+    pub jti: String,
+    pub sub: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<Uuid>,
+    pub events: serde_json::Value,
+}
+
+/// RFC 8693 `act` claim: the party acting on the subject's behalf. A nested
+/// `act` names the party that acted before it, forming the delegation chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActorClaim {
+    pub sub: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<Box<ActorClaim>>,
+}
+
+/// The claims (payload) for our Access Token (JWT)
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccessTokenClaims {
     pub sub: Uuid,              // Subject (the User ID)
     pub sid: Uuid,              // Session ID (the Refresh Token ID)
@@ -28,31 +61,103 @@ pub struct AccessTokenClaims {
     pub exp: usize, // Expiration
     #[serde(default)]
     pub iat: usize, // Issued At (seconds since epoch)
+    /// Authorized party. Set on client_credentials tokens, where `sub` is the
+    /// client's UUID and `sid` is nil because no user session backs the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub azp: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Intended recipient. Only set on tokens issued by token exchange.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+    /// Delegation chain of an exchanged token, or the admin impersonating
+    /// the subject.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<ActorClaim>,
+    /// Claims added by the client's protocol mappers.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 #[async_trait::async_trait]
 pub trait TokenService: Send + Sync {
-    /// Creates a new, signed Access Token (JWT)
+    /// Creates a new, signed Access Token (JWT). `client_id` selects the
+    /// client's signing algorithm override, if any. `extra_claims` never
+    /// replace the claims set here. `actor` names the admin impersonating
+    /// the user, if any.
+    #[allow(clippy::too_many_arguments)]
     async fn create_access_token(
         &self,
         user: &User,
         session_id: Uuid,
+        client_id: Option<&str>,
         permissions: &HashSet<String>,
         roles: &[String],
         groups: &[String],
+        scope: Option<&str>,
+        extra_claims: &Map<String, Value>,
+        actor: Option<&ActorClaim>,
     ) -> Result<String>;
 
     async fn create_id_token(
         &self,
         user: &User,
         client_id: &str, // ID Token needs to know who it's for
+        session_id: Uuid,
         groups: &[String],
+        extra_claims: &Map<String, Value>,
+    ) -> Result<String>;
+
+    /// Verifies an ID token issued in `realm_id`, as presented in
+    /// `id_token_hint`. Expired tokens are accepted: the hint only identifies
+    /// the session.
+    async fn validate_id_token_hint(&self, realm_id: Uuid, token: &str) -> Result<IdTokenClaims>;
+
+    /// Creates a Back-Channel Logout token for `client_id`, signed with the
+    /// client's algorithm.
+    async fn create_logout_token(
+        &self,
+        realm_id: Uuid,
+        client_id: &str,
+        subject: Uuid,
+        session_id: Option<Uuid>,
     ) -> Result<String>;
 
-    /// Validates an Access Token and returns its claims
-    async fn validate_access_token(&self, token: &str) -> Result<AccessTokenClaims>;
+    /// Creates an access token for a client acting on its own behalf
+    /// (client_credentials grant).
+    async fn create_client_access_token(
+        &self,
+        client: &OidcClient,
+        permissions: &HashSet<String>,
+        roles: &[String],
+        scope: Option<&str>,
+    ) -> Result<String>;
+
+    /// Creates the access token issued by a token exchange (RFC 8693). It
+    /// keeps the subject's identity and session, is aimed at `audience`, and
+    /// names `actor` (the requesting client) in front of the subject token's
+    /// existing `act` chain. It never outlives the subject token.
+    async fn create_exchanged_access_token(
+        &self,
+        realm_id: Uuid,
+        subject: &AccessTokenClaims,
+        actor: &str,
+        audience: &str,
+        scope: Option<&str>,
+    ) -> Result<String>;
+
+    /// Validates an Access Token issued in `realm_id` and returns its claims.
+    /// Tokens signed with another realm's key are rejected.
+    async fn validate_access_token(&self, realm_id: Uuid, token: &str)
+        -> Result<AccessTokenClaims>;
+
+    /// Reads the `sid` claim without verifying the token, only to find the
+    /// session, and so the realm, whose keys must then verify it.
+    fn unverified_session_id(&self, token: &str) -> Option<Uuid>;
+
+    /// Lifetime of the access tokens this service signs, in seconds.
+    fn access_token_ttl_secs(&self) -> i64;
 
-    /// Gets the unique ID used to sign tokens (for JWKS endpoint)
-    fn get_key_id(&self) -> &str;
-    fn get_jwks(&self) -> Result<serde_json::Value>;
+    /// Returns the realm's JWK Set: every key that can still verify tokens.
+    async fn get_jwks(&self, realm_id: &Uuid) -> Result<serde_json::Value>;
 }
@@ -0,0 +1,22 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::domain::totp_credential::TotpCredential;
+use crate::error::Result;
+
+#[async_trait]
+pub trait TotpCredentialRepository: Send + Sync {
+    async fn create(&self, credential: &TotpCredential) -> Result<()>;
+    async fn find_by_user(&self, realm_id: &Uuid, user_id: &Uuid)
+        -> Result<Option<TotpCredential>>;
+    /// Records `step` as used. Returns false when that step (or a later one)
+    /// was already accepted, which is how replayed codes are rejected.
+    async fn record_use(
+        &self,
+        credential_id: &Uuid,
+        step: i64,
+        used_at: DateTime<Utc>,
+    ) -> Result<bool>;
+    async fn delete_by_user(&self, realm_id: &Uuid, user_id: &Uuid) -> Result<bool>;
+}
@@ -0,0 +1,42 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::user_federation::{
+    UserFederationLink, UserFederationMapper, UserFederationProvider,
+};
+use crate::error::Result;
+
+#[async_trait]
+pub trait UserFederationRepository: Send + Sync {
+    async fn create_provider(&self, provider: &UserFederationProvider) -> Result<()>;
+    async fn update_provider(&self, provider: &UserFederationProvider) -> Result<()>;
+    async fn find_provider(
+        &self,
+        realm_id: &Uuid,
+        id: &Uuid,
+    ) -> Result<Option<UserFederationProvider>>;
+    async fn find_provider_by_name(
+        &self,
+        realm_id: &Uuid,
+        name: &str,
+    ) -> Result<Option<UserFederationProvider>>;
+    /// Ordered by priority, then name.
+    async fn list_providers(&self, realm_id: &Uuid) -> Result<Vec<UserFederationProvider>>;
+    /// Enabled providers across all realms.
+    async fn list_enabled_providers(&self) -> Result<Vec<UserFederationProvider>>;
+    async fn delete_provider(&self, realm_id: &Uuid, id: &Uuid) -> Result<bool>;
+
+    async fn create_mapper(&self, mapper: &UserFederationMapper) -> Result<()>;
+    async fn list_mappers(&self, provider_id: &Uuid) -> Result<Vec<UserFederationMapper>>;
+    async fn delete_mapper(&self, provider_id: &Uuid, id: &Uuid) -> Result<bool>;
+
+    /// Inserts or replaces the link of `link.user_id`.
+    async fn save_link(&self, link: &UserFederationLink) -> Result<()>;
+    async fn find_link_by_user(&self, user_id: &Uuid) -> Result<Option<UserFederationLink>>;
+    async fn find_link_by_external_id(
+        &self,
+        provider_id: &Uuid,
+        external_id: &str,
+    ) -> Result<Option<UserFederationLink>>;
+    async fn count_links(&self, provider_id: &Uuid) -> Result<u64>;
+}
@@ -13,6 +13,14 @@ use uuid::Uuid;
 #[async_trait]
 pub trait UserRepository: Send + Sync {
     async fn find_by_username(&self, realm_id: &Uuid, username: &str) -> Result<Option<User>>;
+    async fn find_by_username_with_tx(
+        &self,
+        realm_id: &Uuid,
+        username: &str,
+        _tx: Option<&mut dyn Transaction>,
+    ) -> Result<Option<User>> {
+        self.find_by_username(realm_id, username).await
+    }
     async fn find_by_email(&self, realm_id: &Uuid, email: &str) -> Result<Option<User>>;
     async fn find_by_id(&self, id: &Uuid) -> Result<Option<User>>;
     async fn save(&self, user: &User, tx: Option<&mut dyn Transaction>) -> Result<()>;
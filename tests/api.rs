@@ -32,3 +32,63 @@ mod user_admin_actions_http;
 
 #[path = "api/session_management_http.rs"]
 mod session_management_http;
+
+#[path = "api/oidc_token_grants_http.rs"]
+mod oidc_token_grants_http;
+
+#[path = "api/oidc_token_introspection_http.rs"]
+mod oidc_token_introspection_http;
+
+#[path = "api/oidc_logout_http.rs"]
+mod oidc_logout_http;
+
+#[path = "api/oidc_token_exchange_http.rs"]
+mod oidc_token_exchange_http;
+
+#[path = "api/oidc_device_flow_http.rs"]
+mod oidc_device_flow_http;
+
+#[path = "api/totp_http.rs"]
+mod totp_http;
+
+#[path = "api/sms_otp_http.rs"]
+mod sms_otp_http;
+
+#[path = "api/oidc_password_grant_http.rs"]
+mod oidc_password_grant_http;
+
+#[path = "api/password_policy_http.rs"]
+mod password_policy_http;
+
+#[path = "api/oidc_par_http.rs"]
+mod oidc_par_http;
+
+#[path = "api/oidc_claims_http.rs"]
+mod oidc_claims_http;
+
+#[path = "api/oidc_client_registration_http.rs"]
+mod oidc_client_registration_http;
+
+#[path = "api/scoped_admin_http.rs"]
+mod scoped_admin_http;
+
+#[path = "api/impersonation_http.rs"]
+mod impersonation_http;
+
+#[path = "api/scim_http.rs"]
+mod scim_http;
+
+#[path = "api/consent_http.rs"]
+mod consent_http;
+
+#[path = "api/saml_http.rs"]
+mod saml_http;
+
+#[path = "api/saml_broker_http.rs"]
+mod saml_broker_http;
+
+#[path = "api/user_federation_http.rs"]
+mod user_federation_http;
+
+#[path = "api/migration_upgrade_http.rs"]
+mod migration_upgrade_http;
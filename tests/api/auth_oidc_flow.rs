@@ -33,7 +33,7 @@ use reauth::constants::{DEFAULT_REALM_NAME, LOGIN_SESSION_COOKIE, REFRESH_TOKEN_
 use reauth::domain::audit::NewAuditEvent;
 use reauth::domain::auth_session::{AuthenticationSession, SessionStatus};
 use reauth::domain::identity_provider::{IdentityProviderProtocol, OAuthBrokerResult};
-use reauth::domain::oidc::OidcClient;
+use reauth::domain::oidc::{OidcClient, TokenEndpointAuthMethod};
 use reauth::domain::permissions;
 use reauth::domain::realm::Realm;
 use reauth::domain::user::User;
@@ -508,6 +508,32 @@ async fn assert_error_response(response: axum::response::Response, status: Statu
     assert!(!message.is_empty());
 }
 
+/// Reads `(event_type, realm_id, payload)` for outbox rows of the given
+/// event types, oldest first.
+async fn outbox_events(
+    ctx: &TestContext,
+    event_types: &[&str],
+) -> Vec<(String, Option<String>, serde_json::Value)> {
+    let database_url = ctx.app_state.settings.read().await.database.url.clone();
+    let pool = sqlx::SqlitePool::connect(&database_url)
+        .await
+        .expect("connect");
+    let rows: Vec<(String, Option<String>, String)> = sqlx::query_as(
+        "SELECT event_type, realm_id, payload_json FROM event_outbox ORDER BY occurred_at",
+    )
+    .fetch_all(&pool)
+    .await
+    .expect("outbox rows");
+    pool.close().await;
+    rows.into_iter()
+        .filter(|(event_type, _, _)| event_types.contains(&event_type.as_str()))
+        .map(|(event_type, realm_id, payload)| {
+            let payload: serde_json::Value = serde_json::from_str(&payload).expect("payload");
+            (event_type, realm_id, payload)
+        })
+        .collect()
+}
+
 async fn setup_master_realm(ctx: &TestContext) -> Realm {
     let realm = ctx
         .app_state
@@ -823,6 +849,10 @@ async fn create_oauth_identity_provider(
                 button_color: None,
                 sort_order: Some(spec.sort_order),
                 enabled: Some(spec.enabled),
+                saml_metadata_url: None,
+                saml_metadata_xml: None,
+                saml_signing_certificates: None,
+                saml_name_id_format: None,
             },
         )
         .await
@@ -863,6 +893,10 @@ async fn create_github_identity_provider(
                 button_color: None,
                 sort_order: Some(spec.sort_order),
                 enabled: Some(spec.enabled),
+                saml_metadata_url: None,
+                saml_metadata_xml: None,
+                saml_signing_certificates: None,
+                saml_name_id_format: None,
             },
         )
         .await
@@ -907,6 +941,10 @@ async fn create_oidc_identity_provider(
                 button_color: None,
                 sort_order: Some(spec.sort_order),
                 enabled: Some(spec.enabled),
+                saml_metadata_url: None,
+                saml_metadata_xml: None,
+                saml_signing_certificates: None,
+                saml_name_id_format: None,
             },
         )
         .await
@@ -923,6 +961,7 @@ async fn enable_identity_brokering(ctx: &TestContext, realm: &Realm) {
                 access_token_ttl_secs: None,
                 refresh_token_ttl_secs: None,
                 pkce_required_public_clients: None,
+                signing_algorithm: None,
                 lockout_threshold: None,
                 lockout_duration_secs: None,
                 registration_enabled: None,
@@ -980,6 +1019,10 @@ async fn link_federated_identity(
                 button_color: None,
                 sort_order: Some(0),
                 enabled: Some(true),
+                saml_metadata_url: None,
+                saml_metadata_xml: None,
+                saml_signing_certificates: None,
+                saml_name_id_format: None,
             },
         )
         .await
@@ -1343,6 +1386,14 @@ async fn register_oidc_client(
         scopes: serde_json::to_string(&vec!["openid"]).expect("scopes json"),
         web_origins: serde_json::to_string(&Vec::<String>::new()).expect("web_origins json"),
         managed_by_config: false,
+        token_endpoint_auth_method: TokenEndpointAuthMethod::None,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: None,
+        frontchannel_logout_uri: None,
+        token_exchange_policy: None,
+        direct_grant_enabled: false,
+        require_pushed_authorization_requests: false,
     };
 
     let _ = ctx
@@ -1470,6 +1521,7 @@ async fn oidc_token_exchange_returns_tokens_and_refresh_cookie() {
             None,
             Some(code_challenge),
             "S256".to_string(),
+            Default::default(),
         )
         .await
         .expect("create auth code");
@@ -1762,6 +1814,7 @@ async fn auth_login_challenge_hides_identity_providers_when_brokering_disabled()
                 access_token_ttl_secs: None,
                 refresh_token_ttl_secs: None,
                 pkce_required_public_clients: None,
+                signing_algorithm: None,
                 lockout_threshold: None,
                 lockout_duration_secs: None,
                 registration_enabled: None,
@@ -3187,6 +3240,13 @@ async fn oauth_callback_success_auto_links_and_redirects_home() {
         credentials.federated_identities[0].provider_alias,
         "github".to_string()
     );
+
+    let linked = outbox_events(&ctx, &["idp.linked"]).await;
+    assert_eq!(linked.len(), 1);
+    assert_eq!(linked[0].1, Some(realm.id.to_string()));
+    assert_eq!(linked[0].2["data"]["user_id"], serde_json::json!(user.id));
+    assert_eq!(linked[0].2["data"]["provider_alias"], "github");
+    assert_eq!(linked[0].2["data"]["linked_via"], "auto_email");
 }
 
 #[tokio::test]
@@ -4308,6 +4368,10 @@ async fn oauth_callback_disabled_provider_mid_flow_surfaces_oauth_failure() {
                 button_color: None,
                 sort_order: None,
                 enabled: Some(false),
+                saml_metadata_url: None,
+                saml_metadata_xml: None,
+                saml_signing_certificates: None,
+                saml_name_id_format: None,
             },
         )
         .await
@@ -4779,6 +4843,7 @@ async fn oidc_token_exchange_rejects_invalid_pkce_verifier() {
             None,
             Some(code_challenge),
             "S256".to_string(),
+            Default::default(),
         )
         .await
         .expect("create auth code");
@@ -4907,6 +4972,79 @@ async fn auth_login_execute_rejects_invalid_password_with_challenge() {
     );
 }
 
+#[tokio::test]
+#[serial(test_db)]
+async fn auth_login_publishes_failed_and_succeeded_events_to_the_outbox() {
+    let ctx = TestContext::new().await;
+    let realm = setup_master_realm(&ctx).await;
+    ensure_password_browser_flow(&ctx, &realm).await;
+
+    let user = ctx
+        .app_state
+        .user_service
+        .create_user(realm.id, "frank", "password-123", None, false)
+        .await
+        .expect("create user");
+
+    let mut start_request = Request::builder()
+        .method("GET")
+        .uri(format!("/api/realms/{}/auth/login", DEFAULT_REALM_NAME))
+        .body(Body::empty())
+        .unwrap();
+    start_request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 3000))));
+    let start_response = ctx.request(start_request).await;
+    assert_eq!(start_response.status(), StatusCode::OK);
+    let session_id = cookie_value(start_response.headers(), LOGIN_SESSION_COOKIE)
+        .and_then(|val| Uuid::parse_str(&val).ok())
+        .expect("login session cookie");
+
+    for password in ["wrong-password", "password-123"] {
+        let mut exec_request = Request::builder()
+            .method("POST")
+            .uri(format!(
+                "/api/realms/{}/auth/login/execute",
+                DEFAULT_REALM_NAME
+            ))
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(
+                header::COOKIE,
+                format!("{}={}", LOGIN_SESSION_COOKIE, session_id),
+            )
+            .body(Body::from(
+                serde_json::json!({ "username": "frank", "password": password }).to_string(),
+            ))
+            .unwrap();
+        exec_request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 3000))));
+        assert_eq!(ctx.request(exec_request).await.status(), StatusCode::OK);
+    }
+
+    let events = outbox_events(&ctx, &["login.failed", "login.succeeded"]).await;
+    assert_eq!(events.len(), 2);
+    let (failed_type, failed_realm, failed) = &events[0];
+    assert_eq!(failed_type, "login.failed");
+    assert_eq!(failed_realm.as_deref(), Some(realm.id.to_string().as_str()));
+    assert_eq!(failed["data"]["username"], "frank");
+    assert_eq!(failed["data"]["authenticator"], "core.auth.password");
+    assert_eq!(failed["data"]["reason"], "Invalid credentials");
+    assert_eq!(
+        failed["data"]["auth_session_id"],
+        serde_json::json!(session_id)
+    );
+
+    let (succeeded_type, succeeded_realm, succeeded) = &events[1];
+    assert_eq!(succeeded_type, "login.succeeded");
+    assert_eq!(
+        succeeded_realm.as_deref(),
+        Some(realm.id.to_string().as_str())
+    );
+    assert_eq!(succeeded["data"]["user_id"], serde_json::json!(user.id));
+    assert_eq!(succeeded["realm_id"], serde_json::json!(realm.id));
+}
+
 #[tokio::test]
 #[serial(test_db)]
 async fn auth_login_execute_requires_session_cookie() {
@@ -5150,7 +5288,7 @@ async fn oidc_token_rejects_invalid_grant_type() {
     let _realm = setup_master_realm(&ctx).await;
 
     let body = url::form_urlencoded::Serializer::new(String::new())
-        .append_pair("grant_type", "password")
+        .append_pair("grant_type", "implicit")
         .append_pair("code", "code123")
         .append_pair("redirect_uri", "http://localhost/callback")
         .append_pair("client_id", "client-app")
@@ -5281,6 +5419,7 @@ async fn oidc_token_rejects_invalid_redirect_uri() {
             None,
             Some(code_challenge),
             "S256".to_string(),
+            Default::default(),
         )
         .await
         .expect("create auth code");
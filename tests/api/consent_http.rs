@@ -0,0 +1,404 @@
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{header, HeaderMap, Request, StatusCode};
+use http_body_util::BodyExt;
+use serial_test::serial;
+use std::net::{Ipv4Addr, SocketAddr};
+use uuid::Uuid;
+
+use reauth::application::flow_manager::UpdateDraftRequest;
+use reauth::application::rbac_service::CreateRolePayload;
+use reauth::application::realm_service::CreateRealmPayload;
+use reauth::bootstrap::app_state::SetupState;
+use reauth::constants::{DEFAULT_REALM_NAME, LOGIN_SESSION_COOKIE};
+use reauth::domain::oidc::{OidcClient, TokenEndpointAuthMethod};
+use reauth::domain::permissions;
+use reauth::domain::realm::Realm;
+use reauth::domain::user::User;
+
+use crate::support::TestContext;
+
+const REDIRECT_URI: &str = "http://localhost/callback";
+
+async fn json_body(response: axum::response::Response) -> serde_json::Value {
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("read body")
+        .to_bytes();
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+fn cookie_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .filter_map(|value| value.split(';').next())
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, value)| key.trim() == name && !value.trim().is_empty())
+        .map(|(_, value)| value.trim().to_string())
+}
+
+async fn setup_realm(ctx: &TestContext) -> Realm {
+    let realm = ctx
+        .app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: DEFAULT_REALM_NAME.to_string(),
+        })
+        .await
+        .expect("create realm");
+    *ctx.app_state.setup_state.write().await = SetupState::sealed();
+    realm
+}
+
+/// password -> consent -> allow.
+async fn publish_consent_browser_flow(ctx: &TestContext, realm: &Realm) {
+    let flow_id = realm
+        .browser_flow_id
+        .as_ref()
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .expect("browser flow id");
+    let graph = serde_json::json!({
+        "nodes": [
+            { "id": "start", "type": "core.start", "data": { "config": {} } },
+            { "id": "auth-password", "type": "core.auth.password", "data": { "config": { "auth_type": "core.auth.password" } } },
+            { "id": "consent", "type": "core.oidc.consent", "data": { "config": { "auth_type": "core.oidc.consent", "template_key": "consent" } } },
+            { "id": "allow", "type": "core.terminal.allow", "data": { "config": {} } },
+            { "id": "deny", "type": "core.terminal.deny", "data": { "config": { "is_failure": true } } }
+        ],
+        "edges": [
+            { "id": "e-start-password", "source": "start", "target": "auth-password", "sourceHandle": "next" },
+            { "id": "e-password-consent", "source": "auth-password", "target": "consent", "sourceHandle": "success" },
+            { "id": "e-consent-allow", "source": "consent", "target": "allow", "sourceHandle": "allow" },
+            { "id": "e-consent-deny", "source": "consent", "target": "deny", "sourceHandle": "deny" }
+        ]
+    });
+
+    ctx.app_state
+        .flow_manager
+        .update_draft(
+            flow_id,
+            UpdateDraftRequest {
+                name: None,
+                description: None,
+                graph_json: Some(graph),
+            },
+        )
+        .await
+        .expect("update draft");
+    ctx.app_state
+        .flow_manager
+        .publish_flow(realm.id, flow_id)
+        .await
+        .expect("publish flow");
+}
+
+async fn register_web_client(ctx: &TestContext, realm_id: Uuid) {
+    let mut client = OidcClient {
+        id: Uuid::new_v4(),
+        realm_id,
+        client_id: "web-app".to_string(),
+        client_secret: None,
+        redirect_uris: serde_json::to_string(&vec![REDIRECT_URI]).expect("redirect_uris json"),
+        scopes: serde_json::to_string(&vec!["openid", "profile", "email"]).expect("scopes json"),
+        web_origins: "[]".to_string(),
+        managed_by_config: false,
+        token_endpoint_auth_method: TokenEndpointAuthMethod::ClientSecretPost,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: None,
+        frontchannel_logout_uri: None,
+        token_exchange_policy: None,
+        direct_grant_enabled: false,
+        require_pushed_authorization_requests: false,
+    };
+    ctx.app_state
+        .oidc_service
+        .register_client(&mut client)
+        .await
+        .expect("register client");
+}
+
+async fn execute(
+    ctx: &TestContext,
+    session_id: &str,
+    payload: serde_json::Value,
+) -> serde_json::Value {
+    let mut request = Request::builder()
+        .method("POST")
+        .uri(format!(
+            "/api/realms/{}/auth/login/execute",
+            DEFAULT_REALM_NAME
+        ))
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(
+            header::COOKIE,
+            format!("{}={}", LOGIN_SESSION_COOKIE, session_id),
+        )
+        .body(Body::from(payload.to_string()))
+        .unwrap();
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 3000))));
+    let response = ctx.request(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    json_body(response).await
+}
+
+/// Starts an authorization request and signs in; returns the login session
+/// and the response that followed the password step.
+async fn authorize_and_sign_in(ctx: &TestContext, scope: &str) -> (String, serde_json::Value) {
+    let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+    for (key, value) in [
+        ("client_id", "web-app"),
+        ("redirect_uri", REDIRECT_URI),
+        ("response_type", "code"),
+        ("scope", scope),
+        ("state", "consent-state"),
+    ] {
+        serializer.append_pair(key, value);
+    }
+    let request = Request::builder()
+        .uri(format!(
+            "/api/realms/{}/oidc/authorize?{}",
+            DEFAULT_REALM_NAME,
+            serializer.finish()
+        ))
+        .body(Body::empty())
+        .unwrap();
+    let response = ctx.request(request).await;
+    assert!(response.status().is_redirection());
+    let session_id =
+        cookie_value(response.headers(), LOGIN_SESSION_COOKIE).expect("login session cookie");
+
+    let mut request = Request::builder()
+        .uri(format!("/api/realms/{}/auth/login", DEFAULT_REALM_NAME))
+        .header(
+            header::COOKIE,
+            format!("{}={}", LOGIN_SESSION_COOKIE, session_id),
+        )
+        .body(Body::empty())
+        .unwrap();
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 3000))));
+    let response = ctx.request(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let session_id = cookie_value(response.headers(), LOGIN_SESSION_COOKIE).unwrap_or(session_id);
+
+    let body = execute(
+        ctx,
+        &session_id,
+        serde_json::json!({ "username": "dana", "password": "password-123" }),
+    )
+    .await;
+    (session_id, body)
+}
+
+async fn grant_user_admin(ctx: &TestContext, realm_id: Uuid) -> String {
+    let admin = ctx
+        .app_state
+        .user_service
+        .create_user(realm_id, "consent-admin", "password", None, false)
+        .await
+        .expect("create admin");
+    let role = ctx
+        .app_state
+        .rbac_service
+        .create_role(
+            realm_id,
+            CreateRolePayload {
+                name: "consent-admin".to_string(),
+                description: None,
+                client_id: None,
+            },
+        )
+        .await
+        .expect("create role");
+    for permission in [permissions::USER_READ, permissions::USER_WRITE] {
+        ctx.app_state
+            .rbac_service
+            .assign_permission_to_role(realm_id, role.id, permission.to_string())
+            .await
+            .expect("assign permission");
+    }
+    ctx.app_state
+        .rbac_service
+        .assign_role_to_user(realm_id, admin.id, role.id)
+        .await
+        .expect("assign role");
+    token_for(ctx, &admin, None).await
+}
+
+async fn token_for(ctx: &TestContext, user: &User, client_id: Option<&str>) -> String {
+    let (login, _) = ctx
+        .app_state
+        .auth_service
+        .create_session(user, client_id.map(str::to_string), None, None)
+        .await
+        .expect("create session");
+    login.access_token
+}
+
+fn request(method: &str, uri: String, token: &str) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::empty())
+        .expect("request")
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn consent_is_remembered_until_new_scopes_are_requested() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    publish_consent_browser_flow(&ctx, &realm).await;
+    register_web_client(&ctx, realm.id).await;
+    let user = ctx
+        .app_state
+        .user_service
+        .create_user(realm.id, "dana", "password-123", None, false)
+        .await
+        .expect("create user");
+
+    let (session_id, body) = authorize_and_sign_in(&ctx, "openid profile").await;
+    assert_eq!(body["status"], "challenge", "{}", body);
+    assert_eq!(body["context"]["client_id"], "web-app", "{}", body);
+
+    let body = execute(
+        &ctx,
+        &session_id,
+        serde_json::json!({ "decision": "allow" }),
+    )
+    .await;
+    assert_eq!(body["status"], "redirect", "{}", body);
+    let grants = ctx
+        .app_state
+        .consent_service
+        .list_grants(realm.id, user.id)
+        .await
+        .expect("list grants");
+    assert_eq!(grants.len(), 1);
+    assert_eq!(grants[0].scopes, vec!["openid", "profile"]);
+
+    // Covered by the stored grant: straight through to the client.
+    let (_, body) = authorize_and_sign_in(&ctx, "openid").await;
+    assert_eq!(body["status"], "redirect", "{}", body);
+
+    // A new scope asks again, and approving it widens the grant.
+    let (session_id, body) = authorize_and_sign_in(&ctx, "openid email").await;
+    assert_eq!(body["status"], "challenge", "{}", body);
+    execute(
+        &ctx,
+        &session_id,
+        serde_json::json!({ "decision": "allow" }),
+    )
+    .await;
+    let grants = ctx
+        .app_state
+        .consent_service
+        .list_grants(realm.id, user.id)
+        .await
+        .expect("list grants");
+    assert_eq!(grants[0].scopes, vec!["email", "openid", "profile"]);
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn consent_grants_are_listed_and_revoked_with_client_sessions() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    register_web_client(&ctx, realm.id).await;
+    let user = ctx
+        .app_state
+        .user_service
+        .create_user(realm.id, "dana", "password-123", None, false)
+        .await
+        .expect("create user");
+    let scopes = vec!["openid".to_string()];
+    ctx.app_state
+        .consent_service
+        .record_grant(realm.id, user.id, "web-app", &scopes)
+        .await
+        .expect("record grant");
+
+    let (_, client_session) = ctx
+        .app_state
+        .auth_service
+        .create_session(&user, Some("web-app".to_string()), None, None)
+        .await
+        .expect("client session");
+    let self_token = token_for(&ctx, &user, None).await;
+    let admin_token = grant_user_admin(&ctx, realm.id).await;
+
+    let response = ctx
+        .request(request(
+            "GET",
+            format!("/api/realms/{}/users/me/consents", DEFAULT_REALM_NAME),
+            &self_token,
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = json_body(response).await;
+    assert_eq!(body[0]["client_id"], "web-app");
+    assert_eq!(body[0]["scopes"][0], "openid");
+
+    let response = ctx
+        .request(request(
+            "GET",
+            format!(
+                "/api/realms/{}/users/{}/consents",
+                DEFAULT_REALM_NAME, user.id
+            ),
+            &admin_token,
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(json_body(response).await.as_array().map(Vec::len), Some(1));
+
+    let response = ctx
+        .request(request(
+            "DELETE",
+            format!(
+                "/api/realms/{}/users/{}/consents/web-app",
+                DEFAULT_REALM_NAME, user.id
+            ),
+            &admin_token,
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let revoked = ctx
+        .app_state
+        .session_repo
+        .find_by_id_any(&client_session.id)
+        .await
+        .expect("session lookup")
+        .expect("session");
+    assert!(revoked.revoked_at.is_some());
+    assert!(ctx
+        .app_state
+        .consent_service
+        .list_grants(realm.id, user.id)
+        .await
+        .expect("list grants")
+        .is_empty());
+
+    // Already gone, for the user as well.
+    let response = ctx
+        .request(request(
+            "DELETE",
+            format!(
+                "/api/realms/{}/users/me/consents/web-app",
+                DEFAULT_REALM_NAME
+            ),
+            &self_token,
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,306 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use http_body_util::BodyExt;
+use serde_json::json;
+use serial_test::serial;
+use uuid::Uuid;
+
+use reauth::application::rbac_service::CreateRolePayload;
+use reauth::application::realm_service::CreateRealmPayload;
+use reauth::bootstrap::app_state::SetupState;
+use reauth::constants::DEFAULT_REALM_NAME;
+use reauth::domain::permissions;
+use reauth::domain::realm::Realm;
+use reauth::domain::user::User;
+
+use crate::support::TestContext;
+
+async fn json_body(response: axum::response::Response) -> serde_json::Value {
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("read body")
+        .to_bytes();
+    if bytes.is_empty() {
+        return serde_json::Value::Null;
+    }
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+async fn setup_realm(ctx: &TestContext) -> Realm {
+    let realm = ctx
+        .app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: DEFAULT_REALM_NAME.to_string(),
+        })
+        .await
+        .expect("create realm");
+    *ctx.app_state.setup_state.write().await = SetupState::sealed();
+    realm
+}
+
+async fn user_with(ctx: &TestContext, realm_id: Uuid, granted: &[&str]) -> User {
+    let username = format!("user-{}", Uuid::new_v4().simple());
+    let user = ctx
+        .app_state
+        .user_service
+        .create_user(realm_id, &username, "password", None, false)
+        .await
+        .expect("create user");
+    if granted.is_empty() {
+        return user;
+    }
+    let role = ctx
+        .app_state
+        .rbac_service
+        .create_role(
+            realm_id,
+            CreateRolePayload {
+                name: username,
+                description: None,
+                client_id: None,
+            },
+        )
+        .await
+        .expect("create role");
+    for permission in granted {
+        ctx.app_state
+            .rbac_service
+            .assign_permission_to_role(realm_id, role.id, permission.to_string())
+            .await
+            .expect("assign permission");
+    }
+    ctx.app_state
+        .rbac_service
+        .assign_role_to_user(realm_id, user.id, role.id)
+        .await
+        .expect("assign role");
+    user
+}
+
+async fn token_for(ctx: &TestContext, user: &User) -> String {
+    let (login, _) = ctx
+        .app_state
+        .auth_service
+        .create_session(user, None, None, None)
+        .await
+        .expect("create session");
+    login.access_token
+}
+
+fn json_request(
+    method: &str,
+    uri: String,
+    token: &str,
+    payload: Option<serde_json::Value>,
+) -> Request<Body> {
+    let body = payload
+        .map(|payload| Body::from(payload.to_string()))
+        .unwrap_or_else(Body::empty);
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .body(body)
+        .expect("json request")
+}
+
+fn impersonate_uri(user_id: Uuid) -> String {
+    format!(
+        "/api/realms/{}/users/{}/impersonate",
+        DEFAULT_REALM_NAME, user_id
+    )
+}
+
+fn jwt_payload(token: &str) -> serde_json::Value {
+    let payload = token.split('.').nth(1).expect("jwt payload");
+    serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).expect("base64")).expect("json")
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn impersonation_mints_an_audited_time_boxed_session() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let support = user_with(
+        &ctx,
+        realm.id,
+        &[permissions::USER_IMPERSONATE, permissions::SESSION_READ],
+    )
+    .await;
+    let customer = user_with(&ctx, realm.id, &[]).await;
+    let support_token = token_for(&ctx, &support).await;
+
+    let response = ctx
+        .request(json_request(
+            "POST",
+            impersonate_uri(customer.id),
+            &support_token,
+            None,
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = json_body(response).await;
+    let token = body["access_token"].as_str().expect("access_token");
+    let session_id = body["session_id"].as_str().expect("session_id");
+    assert!(body["expires_at"].is_string());
+
+    let claims = jwt_payload(token);
+    assert_eq!(claims["sub"], customer.id.to_string());
+    assert_eq!(claims["act"]["sub"], support.id.to_string());
+
+    let response = ctx
+        .request(json_request(
+            "PUT",
+            format!(
+                "/api/realms/{}/users/me/metadata/unsafe",
+                DEFAULT_REALM_NAME
+            ),
+            token,
+            Some(json!({ "metadata": { "theme": "dark" } })),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // An impersonation session cannot start another one.
+    let response = ctx
+        .request(json_request(
+            "POST",
+            impersonate_uri(support.id),
+            token,
+            None,
+        ))
+        .await;
+    assert_ne!(response.status(), StatusCode::CREATED);
+
+    let events = ctx
+        .app_state
+        .audit_service
+        .list_recent(realm.id, 20)
+        .await
+        .expect("audit events");
+    let started = events
+        .iter()
+        .find(|event| event.action == "user.impersonate")
+        .expect("impersonation audit");
+    assert_eq!(started.actor_user_id, Some(support.id));
+    assert_eq!(started.target_id, Some(customer.id.to_string()));
+    // Every change made with the session is recorded under both identities,
+    // including the refused attempt.
+    let requests: Vec<_> = events
+        .iter()
+        .filter(|event| event.action == "impersonation.request")
+        .collect();
+    assert_eq!(requests.len(), 2);
+    for request in requests {
+        assert_eq!(request.actor_user_id, Some(customer.id));
+        assert_eq!(request.impersonator_user_id, Some(support.id));
+        assert_eq!(request.target_id.as_deref(), Some(session_id));
+    }
+
+    let response = ctx
+        .request(json_request(
+            "GET",
+            format!("/api/realms/{}/sessions", DEFAULT_REALM_NAME),
+            &support_token,
+            None,
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let sessions = json_body(response).await;
+    let listed = sessions["data"]
+        .as_array()
+        .expect("sessions")
+        .iter()
+        .find(|session| session["id"] == session_id)
+        .expect("impersonation session listed")
+        .clone();
+    assert_eq!(listed["impersonator_id"], support.id.to_string());
+    assert_eq!(listed["impersonator_username"], support.username);
+    assert_eq!(listed["username"], customer.username);
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn impersonation_requires_permission_and_cannot_escalate() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let support = user_with(&ctx, realm.id, &[permissions::USER_IMPERSONATE]).await;
+    let reader = user_with(&ctx, realm.id, &[permissions::USER_READ]).await;
+    let admin = user_with(&ctx, realm.id, &[permissions::REALM_WRITE]).await;
+    let customer = user_with(&ctx, realm.id, &[]).await;
+
+    let reader_token = token_for(&ctx, &reader).await;
+    let response = ctx
+        .request(json_request(
+            "POST",
+            impersonate_uri(customer.id),
+            &reader_token,
+            None,
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let support_token = token_for(&ctx, &support).await;
+    let response = ctx
+        .request(json_request(
+            "POST",
+            impersonate_uri(admin.id),
+            &support_token,
+            None,
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = ctx
+        .request(json_request(
+            "POST",
+            impersonate_uri(support.id),
+            &support_token,
+            None,
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn master_admin_impersonates_across_realms_with_covering_grants() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let branch = ctx
+        .app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: "branch".to_string(),
+        })
+        .await
+        .expect("create branch realm");
+    let branch_read = format!("{}@realm:{}", permissions::USER_READ, branch.id);
+    let support = user_with(
+        &ctx,
+        realm.id,
+        &[permissions::USER_IMPERSONATE, permissions::USER_READ],
+    )
+    .await;
+    let customer = user_with(&ctx, branch.id, &[&branch_read]).await;
+
+    let support_token = token_for(&ctx, &support).await;
+    let response = ctx
+        .request(json_request(
+            "POST",
+            format!("/api/realms/branch/users/{}/impersonate", customer.id),
+            &support_token,
+            None,
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = json_body(response).await;
+    let claims = jwt_payload(body["access_token"].as_str().expect("access token"));
+    assert_eq!(claims["sub"], customer.id.to_string());
+}
@@ -1,38 +1,330 @@
 use axum::body::Body;
-use axum::http::{Request, StatusCode};
+use axum::http::{header, Request, StatusCode};
 use http_body_util::BodyExt;
 use serial_test::serial;
+use uuid::Uuid;
+
+use reauth::application::rbac_service::CreateRolePayload;
+use reauth::application::realm_service::CreateRealmPayload;
+use reauth::constants::DEFAULT_REALM_NAME;
+use reauth::domain::oidc::{OidcClient, TokenEndpointAuthMethod};
+use reauth::domain::permissions;
+use reauth::domain::realm::Realm;
+use reauth::domain::signing_key::SigningAlgorithm;
 
 use crate::support::TestContext;
 
+async fn json_body(response: axum::response::Response) -> serde_json::Value {
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("body read failed")
+        .to_bytes();
+    serde_json::from_slice(&bytes).expect("invalid JSON body")
+}
+
+async fn setup_realm(ctx: &TestContext) -> Realm {
+    ctx.app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: DEFAULT_REALM_NAME.to_string(),
+        })
+        .await
+        .expect("create realm")
+}
+
+async fn setup_realm_admin_token(ctx: &TestContext, realm_id: Uuid) -> String {
+    let user = ctx
+        .app_state
+        .user_service
+        .create_user(realm_id, "key-admin", "password", None, false)
+        .await
+        .expect("create admin");
+    let role = ctx
+        .app_state
+        .rbac_service
+        .create_role(
+            realm_id,
+            CreateRolePayload {
+                name: "key-admin".to_string(),
+                description: None,
+                client_id: None,
+            },
+        )
+        .await
+        .expect("create role");
+    for permission in [permissions::REALM_READ, permissions::REALM_WRITE] {
+        ctx.app_state
+            .rbac_service
+            .assign_permission_to_role(realm_id, role.id, permission.to_string())
+            .await
+            .expect("assign permission");
+    }
+    ctx.app_state
+        .rbac_service
+        .assign_role_to_user(realm_id, user.id, role.id)
+        .await
+        .expect("assign role");
+
+    let (login, _) = ctx
+        .app_state
+        .auth_service
+        .create_session(&user, None, None, None)
+        .await
+        .expect("create session");
+    login.access_token
+}
+
+fn request(method: &str, uri: String, token: Option<&str>) -> Request<Body> {
+    let mut builder = Request::builder().method(method).uri(uri);
+    if let Some(token) = token {
+        builder = builder.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    builder.body(Body::empty()).expect("request")
+}
+
+fn json_request(method: &str, uri: String, token: &str, body: serde_json::Value) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .expect("request")
+}
+
+fn token_alg(token: &str) -> String {
+    let header = jsonwebtoken::decode_header(token).expect("token header");
+    format!("{:?}", header.alg)
+}
+
+async fn jwks_keys(ctx: &TestContext) -> Vec<serde_json::Value> {
+    let response = ctx
+        .request(request(
+            "GET",
+            format!(
+                "/api/realms/{}/oidc/.well-known/jwks.json",
+                DEFAULT_REALM_NAME
+            ),
+            None,
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    json_body(response).await["keys"]
+        .as_array()
+        .expect("jwks should include keys array")
+        .clone()
+}
+
+async fn jwks_kids(ctx: &TestContext) -> Vec<String> {
+    jwks_keys(ctx)
+        .await
+        .iter()
+        .map(|key| key["kid"].as_str().expect("kid").to_string())
+        .collect()
+}
+
 #[tokio::test]
 #[serial(test_db)]
 async fn jwks_endpoint_returns_keys() {
     let ctx = TestContext::new().await;
+    let _realm = setup_realm(&ctx).await;
+
+    let keys = jwks_kids(&ctx).await;
+
+    assert!(!keys.is_empty(), "jwks should contain at least one key");
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn jwks_endpoint_rejects_unknown_realm() {
+    let ctx = TestContext::new().await;
 
     let response = ctx
-        .request(
-            Request::builder()
-                .uri("/api/realms/master/oidc/.well-known/jwks.json")
-                .body(Body::empty())
-                .unwrap(),
-        )
+        .request(request(
+            "GET",
+            "/api/realms/missing/oidc/.well-known/jwks.json".to_string(),
+            None,
+        ))
         .await;
 
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn rotated_out_keys_keep_verifying_until_retired() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let old_token = setup_realm_admin_token(&ctx, realm.id).await;
+    let keys_uri = format!("/api/realms/{}/keys", realm.id);
+
+    let response = ctx
+        .request(request("GET", keys_uri.clone(), Some(&old_token)))
+        .await;
     assert_eq!(response.status(), StatusCode::OK);
+    let keys = json_body(response).await;
+    let old_key_id = keys[0]["id"].as_str().expect("key id").to_string();
+    let old_kid = keys[0]["kid"].as_str().expect("kid").to_string();
+    assert_eq!(keys[0]["state"], "active");
+    assert!(keys[0].get("private_key_pem").is_none());
 
-    let body = response
-        .into_body()
-        .collect()
+    let response = ctx
+        .request(request(
+            "POST",
+            format!("{}/rotate", keys_uri),
+            Some(&old_token),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let new_kid = json_body(response).await["kid"]
+        .as_str()
+        .expect("kid")
+        .to_string();
+
+    // Both keys are published, the new active key first.
+    assert_eq!(jwks_kids(&ctx).await, vec![new_kid, old_kid.clone()]);
+
+    // Tokens signed before the rotation still validate.
+    let response = ctx
+        .request(request("GET", keys_uri.clone(), Some(&old_token)))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = ctx
+        .request(request(
+            "POST",
+            format!("{}/{}/retire", keys_uri, old_key_id),
+            Some(&old_token),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(json_body(response).await["state"], "retired");
+
+    assert!(!jwks_kids(&ctx).await.contains(&old_kid));
+    let response = ctx
+        .request(request("GET", keys_uri, Some(&old_token)))
+        .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn realm_and_client_choose_the_token_signing_algorithm() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let admin_token = setup_realm_admin_token(&ctx, realm.id).await;
+    assert_eq!(token_alg(&admin_token), "RS256");
+
+    let response = ctx
+        .request(json_request(
+            "PUT",
+            format!("/api/realms/{}", realm.id),
+            &admin_token,
+            serde_json::json!({ "signing_algorithm": "ES256" }),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(json_body(response).await["signing_algorithm"], "ES256");
+
+    let mut client = OidcClient {
+        id: Uuid::new_v4(),
+        realm_id: realm.id,
+        client_id: "edge-verifier".to_string(),
+        client_secret: None,
+        redirect_uris: "[]".to_string(),
+        scopes: "[\"openid\"]".to_string(),
+        web_origins: "[]".to_string(),
+        managed_by_config: false,
+        token_endpoint_auth_method: TokenEndpointAuthMethod::None,
+        jwks: None,
+        signing_algorithm: Some(SigningAlgorithm::EdDsa),
+        backchannel_logout_uri: None,
+        frontchannel_logout_uri: None,
+        token_exchange_policy: None,
+        direct_grant_enabled: false,
+        require_pushed_authorization_requests: false,
+    };
+    ctx.app_state
+        .oidc_service
+        .register_client(&mut client)
         .await
-        .expect("body read failed")
-        .to_bytes();
+        .expect("register client");
+
+    let user = ctx
+        .app_state
+        .user_service
+        .create_user(realm.id, "erin", "password", None, false)
+        .await
+        .expect("create user");
+    let (realm_login, _) = ctx
+        .app_state
+        .auth_service
+        .create_session(&user, None, None, None)
+        .await
+        .expect("create session");
+    let (client_login, _) = ctx
+        .app_state
+        .auth_service
+        .create_session(&user, Some("edge-verifier".to_string()), None, None)
+        .await
+        .expect("create session");
+    assert_eq!(token_alg(&realm_login.access_token), "ES256");
+    assert_eq!(token_alg(&client_login.access_token), "EdDSA");
 
-    let json: serde_json::Value = serde_json::from_slice(&body).expect("invalid jwks JSON");
-    let keys = json
-        .get("keys")
-        .and_then(|value| value.as_array())
-        .expect("jwks should include keys array");
+    let keys = jwks_keys(&ctx).await;
+    let published = |alg: &str| keys.iter().find(|key| key["alg"] == alg).cloned();
+    let ec = published("ES256").expect("EC key published");
+    assert_eq!(ec["kty"], "EC");
+    assert_eq!(ec["crv"], "P-256");
+    let okp = published("EdDSA").expect("OKP key published");
+    assert_eq!(okp["kty"], "OKP");
+    assert_eq!(okp["crv"], "Ed25519");
+    assert!(published("RS256").is_some());
 
-    assert!(!keys.is_empty(), "jwks should contain at least one key");
+    // Tokens signed with the previous algorithm keep validating.
+    let keys_uri = format!("/api/realms/{}/keys", realm.id);
+    let response = ctx
+        .request(request("GET", keys_uri.clone(), Some(&admin_token)))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Rotation defaults to the realm's algorithm; a body picks another one.
+    let response = ctx
+        .request(request(
+            "POST",
+            format!("{}/rotate", keys_uri),
+            Some(&admin_token),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(json_body(response).await["algorithm"], "ES256");
+    let response = ctx
+        .request(json_request(
+            "POST",
+            keys_uri,
+            &admin_token,
+            serde_json::json!({ "algorithm": "ES384" }),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let staged = json_body(response).await;
+    assert_eq!(staged["algorithm"], "ES384");
+    assert_eq!(staged["state"], "passive");
+
+    let response = ctx
+        .request(request(
+            "GET",
+            format!(
+                "/api/realms/{}/oidc/.well-known/openid-configuration",
+                DEFAULT_REALM_NAME
+            ),
+            None,
+        ))
+        .await;
+    assert_eq!(
+        json_body(response).await["id_token_signing_alg_values_supported"],
+        serde_json::json!(["RS256", "ES256", "ES384", "EdDSA"])
+    );
 }
@@ -0,0 +1,99 @@
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{header, Request, StatusCode};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serial_test::serial;
+use sha2::{Digest, Sha256};
+use std::net::{Ipv4Addr, SocketAddr};
+use uuid::Uuid;
+
+use reauth::constants::DEFAULT_REALM_NAME;
+use reauth::domain::oidc::TokenEndpointAuthMethod;
+
+use crate::support::TestContext;
+
+/// The migration that added per-client token endpoint authentication.
+const TOKEN_ENDPOINT_AUTH_MIGRATION: i64 = 20260614120000;
+const ADMIN_CLIENT_ID: &str = "reauth-admin";
+
+#[tokio::test]
+#[serial(test_db)]
+async fn seeded_admin_client_exchanges_codes_after_upgrade() {
+    let realm_id = Uuid::new_v4();
+    // Releases before the migration stored a secret for every client,
+    // including the public admin UI client.
+    let fixture = format!(
+        r#"
+        INSERT INTO realms (id, name) VALUES ('{realm_id}', '{DEFAULT_REALM_NAME}');
+        INSERT INTO oidc_clients
+            (id, realm_id, client_id, client_secret, redirect_uris, scopes, managed_by_config)
+        VALUES
+            ('{}', '{realm_id}', '{ADMIN_CLIENT_ID}', 'legacy-secret',
+             '["http://localhost/callback"]', '["openid"]', 1);
+        "#,
+        Uuid::new_v4()
+    );
+    let ctx = TestContext::new_upgraded_from(TOKEN_ENDPOINT_AUTH_MIGRATION, &fixture).await;
+
+    let client = ctx
+        .app_state
+        .oidc_service
+        .find_client_by_client_id(&realm_id, ADMIN_CLIENT_ID)
+        .await
+        .expect("find client")
+        .expect("upgraded client");
+    assert!(client.client_secret.is_some());
+    assert_eq!(
+        client.token_endpoint_auth_method,
+        TokenEndpointAuthMethod::None
+    );
+    let redirect_uris: Vec<String> =
+        serde_json::from_str(&client.redirect_uris).expect("redirect uris");
+    let redirect_uri = redirect_uris.first().expect("redirect uri").clone();
+
+    let user = ctx
+        .app_state
+        .user_service
+        .create_user(realm_id, "upgraded-admin", "password-123", None, false)
+        .await
+        .expect("create user");
+    let code_verifier = "upgrade-verifier";
+    let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+    let auth_code = ctx
+        .app_state
+        .oidc_service
+        .create_authorization_code(
+            realm_id,
+            user.id,
+            ADMIN_CLIENT_ID.to_string(),
+            redirect_uri.clone(),
+            None,
+            Some(code_challenge),
+            "S256".to_string(),
+            Default::default(),
+        )
+        .await
+        .expect("create auth code");
+
+    // The admin UI sends only its client_id, never a secret.
+    let body = url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("grant_type", "authorization_code")
+        .append_pair("code", &auth_code.code)
+        .append_pair("redirect_uri", &redirect_uri)
+        .append_pair("client_id", ADMIN_CLIENT_ID)
+        .append_pair("code_verifier", code_verifier)
+        .finish();
+    let mut request = Request::builder()
+        .method("POST")
+        .uri(format!("/api/realms/{}/oidc/token", DEFAULT_REALM_NAME))
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(Body::from(body))
+        .expect("request");
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 3000))));
+
+    let response = ctx.request(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
@@ -0,0 +1,401 @@
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{header, Request, StatusCode};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use http_body_util::BodyExt;
+use serde_json::json;
+use serial_test::serial;
+use std::net::{Ipv4Addr, SocketAddr};
+use uuid::Uuid;
+
+use reauth::application::flow_manager::templates::FlowTemplates;
+use reauth::application::flow_manager::UpdateDraftRequest;
+use reauth::application::rbac_service::CreateRolePayload;
+use reauth::application::realm_service::CreateRealmPayload;
+use reauth::application::user_service::UserMetadataVisibility;
+use reauth::bootstrap::app_state::SetupState;
+use reauth::constants::DEFAULT_REALM_NAME;
+use reauth::domain::oidc::{OidcClient, TokenEndpointAuthMethod};
+use reauth::domain::permissions;
+use reauth::domain::realm::Realm;
+
+use crate::support::TestContext;
+
+async fn json_body(response: axum::response::Response) -> serde_json::Value {
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("read body")
+        .to_bytes();
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+fn jwt_payload(token: &str) -> serde_json::Value {
+    let payload = token.split('.').nth(1).expect("jwt payload");
+    let bytes = URL_SAFE_NO_PAD.decode(payload).expect("decode payload");
+    serde_json::from_slice(&bytes).expect("payload json")
+}
+
+async fn setup_realm(ctx: &TestContext) -> Realm {
+    let realm = ctx
+        .app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: DEFAULT_REALM_NAME.to_string(),
+        })
+        .await
+        .expect("create realm");
+    *ctx.app_state.setup_state.write().await = SetupState::sealed();
+    realm
+}
+
+async fn setup_client_admin_token(ctx: &TestContext, realm_id: Uuid) -> String {
+    let user = ctx
+        .app_state
+        .user_service
+        .create_user(realm_id, "client-admin", "password", None, false)
+        .await
+        .expect("create admin");
+    let role = ctx
+        .app_state
+        .rbac_service
+        .create_role(
+            realm_id,
+            CreateRolePayload {
+                name: "client-admin".to_string(),
+                description: None,
+                client_id: None,
+            },
+        )
+        .await
+        .expect("create role");
+    for permission in [permissions::CLIENT_READ, permissions::CLIENT_UPDATE] {
+        ctx.app_state
+            .rbac_service
+            .assign_permission_to_role(realm_id, role.id, permission.to_string())
+            .await
+            .expect("assign permission");
+    }
+    ctx.app_state
+        .rbac_service
+        .assign_role_to_user(realm_id, user.id, role.id)
+        .await
+        .expect("assign role");
+
+    let (login, _) = ctx
+        .app_state
+        .auth_service
+        .create_session(&user, None, None, None)
+        .await
+        .expect("create session");
+    login.access_token
+}
+
+/// Registers a confidential client allowed to use the password grant and
+/// returns it with its plaintext secret.
+async fn register_client(
+    ctx: &TestContext,
+    realm_id: Uuid,
+    scopes: &[&str],
+) -> (OidcClient, String) {
+    let mut client = OidcClient {
+        id: Uuid::new_v4(),
+        realm_id,
+        client_id: "claims-app".to_string(),
+        client_secret: None,
+        redirect_uris: serde_json::to_string(&vec!["http://localhost/callback"])
+            .expect("redirect_uris json"),
+        scopes: serde_json::to_string(scopes).expect("scopes json"),
+        web_origins: "[]".to_string(),
+        managed_by_config: false,
+        token_endpoint_auth_method: TokenEndpointAuthMethod::ClientSecretPost,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: None,
+        frontchannel_logout_uri: None,
+        token_exchange_policy: None,
+        direct_grant_enabled: true,
+        require_pushed_authorization_requests: false,
+    };
+    let secret = ctx
+        .app_state
+        .oidc_service
+        .register_client(&mut client)
+        .await
+        .expect("register client")
+        .expect("client secret");
+    (client, secret)
+}
+
+async fn publish_direct_grant_flow(ctx: &TestContext, realm: &Realm) {
+    let flow_id = realm
+        .direct_grant_flow_id
+        .as_ref()
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .expect("direct grant flow id");
+    ctx.app_state
+        .flow_manager
+        .update_draft(
+            flow_id,
+            UpdateDraftRequest {
+                name: None,
+                description: None,
+                graph_json: Some(FlowTemplates::direct_grant_flow()),
+            },
+        )
+        .await
+        .expect("update draft");
+    ctx.app_state
+        .flow_manager
+        .publish_flow(realm.id, flow_id)
+        .await
+        .expect("publish flow");
+}
+
+fn admin_request(
+    method: &str,
+    uri: String,
+    token: &str,
+    payload: Option<serde_json::Value>,
+) -> Request<Body> {
+    let builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .header(header::CONTENT_TYPE, "application/json");
+    let body = payload
+        .map(|payload| Body::from(payload.to_string()))
+        .unwrap_or_else(Body::empty);
+    builder.body(body).expect("admin request")
+}
+
+async fn post_token(ctx: &TestContext, form: &[(&str, &str)]) -> axum::response::Response {
+    let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+    for (key, value) in form {
+        serializer.append_pair(key, value);
+    }
+    let mut request = Request::builder()
+        .method("POST")
+        .uri(format!("/api/realms/{}/oidc/token", DEFAULT_REALM_NAME))
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(Body::from(serializer.finish()))
+        .unwrap();
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 3000))));
+    ctx.request(request).await
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn protocol_mappers_are_managed_per_client() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let token = setup_client_admin_token(&ctx, realm.id).await;
+    let (client, _) = register_client(&ctx, realm.id, &["openid"]).await;
+    let base = format!(
+        "/api/realms/{}/clients/{}/mappers",
+        DEFAULT_REALM_NAME, client.id
+    );
+
+    let mapper = json!({
+        "name": "department",
+        "type": "user_metadata",
+        "path": "org.department",
+        "claim_name": "department",
+        "add_to_access_token": true,
+    });
+    let response = ctx
+        .request(admin_request(
+            "POST",
+            base.clone(),
+            &token,
+            Some(mapper.clone()),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let created = json_body(response).await;
+    assert_eq!(created["type"], "user_metadata");
+    assert_eq!(created["path"], "org.department");
+    let mapper_id = created["id"].as_str().expect("mapper id").to_string();
+
+    let response = ctx
+        .request(admin_request("POST", base.clone(), &token, Some(mapper)))
+        .await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let response = ctx
+        .request(admin_request(
+            "POST",
+            base.clone(),
+            &token,
+            Some(json!({
+                "name": "subject",
+                "type": "group_path",
+                "claim_name": "sub",
+                "add_to_id_token": true,
+            })),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = ctx
+        .request(admin_request(
+            "PUT",
+            format!("{}/{}", base, mapper_id),
+            &token,
+            Some(json!({
+                "name": "app_roles",
+                "type": "role_list",
+                "prefix": "app:",
+                "claim_name": "app_roles",
+                "add_to_id_token": true,
+            })),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = ctx
+        .request(admin_request("GET", base.clone(), &token, None))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let listed = json_body(response).await;
+    assert_eq!(listed.as_array().map(Vec::len), Some(1));
+    assert_eq!(listed[0]["type"], "role_list");
+    assert_eq!(listed[0]["prefix"], "app:");
+    assert_eq!(listed[0]["add_to_access_token"], false);
+
+    let response = ctx
+        .request(admin_request(
+            "DELETE",
+            format!("{}/{}", base, mapper_id),
+            &token,
+            None,
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = ctx.request(admin_request("GET", base, &token, None)).await;
+    assert_eq!(json_body(response).await, json!([]));
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn tokens_carry_scope_claims_and_mapped_claims() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    publish_direct_grant_flow(&ctx, &realm).await;
+    let user = ctx
+        .app_state
+        .user_service
+        .create_user(
+            realm.id,
+            "claims-user",
+            "password-123",
+            Some("claims-user@example.com"),
+            false,
+        )
+        .await
+        .expect("create user");
+    ctx.app_state
+        .user_service
+        .update_metadata(
+            realm.id,
+            user.id,
+            UserMetadataVisibility::Public,
+            json!({ "org": { "department": "research" } }),
+        )
+        .await
+        .expect("update metadata");
+    let token = setup_client_admin_token(&ctx, realm.id).await;
+    let (client, secret) = register_client(&ctx, realm.id, &["openid", "email"]).await;
+
+    let response = ctx
+        .request(admin_request(
+            "POST",
+            format!(
+                "/api/realms/{}/clients/{}/mappers",
+                DEFAULT_REALM_NAME, client.id
+            ),
+            &token,
+            Some(json!({
+                "name": "department",
+                "type": "user_metadata",
+                "path": "org.department",
+                "claim_name": "department",
+                "add_to_access_token": true,
+                "add_to_userinfo": true,
+            })),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let response = post_token(
+        &ctx,
+        &[
+            ("grant_type", "password"),
+            ("client_id", "claims-app"),
+            ("client_secret", &secret),
+            ("username", "claims-user"),
+            ("password", "password-123"),
+            ("scope", "openid email"),
+        ],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = json_body(response).await;
+    assert_eq!(body["scope"], "openid email");
+
+    let access_token = body["access_token"].as_str().expect("access token");
+    let access_claims = jwt_payload(access_token);
+    assert_eq!(access_claims["department"], "research");
+    assert_eq!(access_claims["scope"], "openid email");
+
+    let id_claims = jwt_payload(body["id_token"].as_str().expect("id token"));
+    assert_eq!(id_claims["email"], "claims-user@example.com");
+    assert_eq!(id_claims["email_verified"], false);
+    assert!(id_claims.get("department").is_none());
+    // `profile` was not granted, so its claims stay out.
+    assert!(id_claims.get("updated_at").is_none());
+
+    let request = Request::builder()
+        .method("GET")
+        .uri(format!("/api/realms/{}/oidc/userinfo", DEFAULT_REALM_NAME))
+        .header(header::AUTHORIZATION, format!("Bearer {}", access_token))
+        .body(Body::empty())
+        .unwrap();
+    let response = ctx.request(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let userinfo = json_body(response).await;
+    assert_eq!(userinfo["email"], "claims-user@example.com");
+    assert_eq!(userinfo["department"], "research");
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn discovery_advertises_supported_claims() {
+    let ctx = TestContext::new().await;
+    setup_realm(&ctx).await;
+
+    let request = Request::builder()
+        .method("GET")
+        .uri(format!(
+            "/api/realms/{}/oidc/.well-known/openid-configuration",
+            DEFAULT_REALM_NAME
+        ))
+        .body(Body::empty())
+        .unwrap();
+    let response = ctx.request(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = json_body(response).await;
+
+    assert_eq!(body["claims_parameter_supported"], true);
+    let claims = body["claims_supported"].as_array().expect("claims");
+    for claim in ["sub", "email", "phone_number", "given_name"] {
+        assert!(claims.iter().any(|value| value == claim), "{}", claim);
+    }
+    let scopes = body["scopes_supported"].as_array().expect("scopes");
+    assert!(scopes.iter().any(|value| value == "phone"));
+}
@@ -0,0 +1,415 @@
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{header, Request, StatusCode};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use http_body_util::BodyExt;
+use serde_json::json;
+use serial_test::serial;
+use std::net::{Ipv4Addr, SocketAddr};
+use uuid::Uuid;
+
+use reauth::application::rbac_service::CreateRolePayload;
+use reauth::application::realm_service::CreateRealmPayload;
+use reauth::bootstrap::app_state::SetupState;
+use reauth::constants::DEFAULT_REALM_NAME;
+use reauth::domain::permissions;
+use reauth::domain::realm::Realm;
+
+use crate::support::TestContext;
+
+async fn json_body(response: axum::response::Response) -> serde_json::Value {
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("read body")
+        .to_bytes();
+    if bytes.is_empty() {
+        return serde_json::Value::Null;
+    }
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+async fn setup_realm(ctx: &TestContext) -> Realm {
+    let realm = ctx
+        .app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: DEFAULT_REALM_NAME.to_string(),
+        })
+        .await
+        .expect("create realm");
+    *ctx.app_state.setup_state.write().await = SetupState::sealed();
+    realm
+}
+
+async fn admin_token(ctx: &TestContext, realm_id: Uuid, granted: &[&str]) -> String {
+    let username = format!("admin-{}", Uuid::new_v4().simple());
+    let user = ctx
+        .app_state
+        .user_service
+        .create_user(realm_id, &username, "password", None, false)
+        .await
+        .expect("create admin");
+    let role = ctx
+        .app_state
+        .rbac_service
+        .create_role(
+            realm_id,
+            CreateRolePayload {
+                name: username.clone(),
+                description: None,
+                client_id: None,
+            },
+        )
+        .await
+        .expect("create role");
+    for permission in granted {
+        ctx.app_state
+            .rbac_service
+            .assign_permission_to_role(realm_id, role.id, permission.to_string())
+            .await
+            .expect("assign permission");
+    }
+    ctx.app_state
+        .rbac_service
+        .assign_role_to_user(realm_id, user.id, role.id)
+        .await
+        .expect("assign role");
+
+    let (login, _) = ctx
+        .app_state
+        .auth_service
+        .create_session(&user, None, None, None)
+        .await
+        .expect("create session");
+    login.access_token
+}
+
+fn json_request(
+    method: &str,
+    uri: String,
+    token: Option<&str>,
+    payload: Option<serde_json::Value>,
+) -> Request<Body> {
+    let mut builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json");
+    if let Some(token) = token {
+        builder = builder.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let body = payload
+        .map(|payload| Body::from(payload.to_string()))
+        .unwrap_or_else(Body::empty);
+    builder.body(body).expect("json request")
+}
+
+fn register_uri() -> String {
+    format!("/api/realms/{}/oidc/register", DEFAULT_REALM_NAME)
+}
+
+async fn issue_initial_access_token(ctx: &TestContext, realm_id: Uuid, admin: &str) -> String {
+    let response = ctx
+        .request(json_request(
+            "POST",
+            format!("/api/realms/{}/initial-access-tokens", realm_id),
+            Some(admin),
+            Some(json!({ "description": "preview envs", "count": 1 })),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = json_body(response).await;
+    assert_eq!(body["remaining_uses"], 1);
+    assert!(body.get("token_hash").is_none());
+    body["access_token"]
+        .as_str()
+        .expect("initial access token")
+        .to_string()
+}
+
+async fn client_credentials(
+    ctx: &TestContext,
+    client_id: &str,
+    secret: &str,
+) -> axum::response::Response {
+    let credentials = STANDARD.encode(format!("{}:{}", client_id, secret));
+    let mut request = Request::builder()
+        .method("POST")
+        .uri(format!("/api/realms/{}/oidc/token", DEFAULT_REALM_NAME))
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .header(header::AUTHORIZATION, format!("Basic {}", credentials))
+        .body(Body::from("grant_type=client_credentials"))
+        .unwrap();
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 3000))));
+    ctx.request(request).await
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn dynamic_registration_follows_realm_policy() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let admin = admin_token(
+        &ctx,
+        realm.id,
+        &[permissions::REALM_WRITE, permissions::CLIENT_CREATE],
+    )
+    .await;
+
+    let response = ctx
+        .request(json_request(
+            "PUT",
+            format!("/api/realms/{}/client-registration-policy", realm.id),
+            Some(&admin),
+            Some(json!({
+                "allowed_redirect_hosts": ["*.preview.example.com"],
+                "allowed_grant_types": ["authorization_code", "refresh_token", "client_credentials"],
+            })),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let token = issue_initial_access_token(&ctx, realm.id, &admin).await;
+
+    let metadata = json!({
+        "client_name": "Preview 12",
+        "redirect_uris": ["https://pr-12.preview.example.com/callback"],
+        "grant_types": ["authorization_code", "refresh_token", "client_credentials"],
+        "scope": "openid profile",
+    });
+
+    let response = ctx
+        .request(json_request(
+            "POST",
+            register_uri(),
+            None,
+            Some(metadata.clone()),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(json_body(response).await["error"], "invalid_token");
+
+    let response = ctx
+        .request(json_request(
+            "POST",
+            register_uri(),
+            Some(&token),
+            Some(json!({ "redirect_uris": ["https://evil.example.org/callback"] })),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(json_body(response).await["error"], "invalid_redirect_uri");
+
+    let response = ctx
+        .request(json_request(
+            "POST",
+            register_uri(),
+            Some(&token),
+            Some(json!({
+                "redirect_uris": ["https://pr-12.preview.example.com/callback"],
+                "grant_types": ["authorization_code", "password"],
+            })),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        json_body(response).await["error"],
+        "invalid_client_metadata"
+    );
+
+    // Rejected requests do not spend the token.
+    let response = ctx
+        .request(json_request(
+            "POST",
+            register_uri(),
+            Some(&token),
+            Some(metadata.clone()),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let registered = json_body(response).await;
+    let client_id = registered["client_id"].as_str().expect("client_id");
+    let secret = registered["client_secret"].as_str().expect("client_secret");
+    let registration_token = registered["registration_access_token"]
+        .as_str()
+        .expect("registration access token");
+    assert_eq!(registered["client_name"], "Preview 12");
+    assert_eq!(
+        registered["token_endpoint_auth_method"],
+        "client_secret_basic"
+    );
+    assert_eq!(registered["response_types"], json!(["code"]));
+    assert_eq!(registered["scope"], "openid profile");
+    assert_eq!(registered["client_secret_expires_at"], 0);
+    assert!(registered["registration_client_uri"]
+        .as_str()
+        .expect("registration_client_uri")
+        .ends_with(&format!("/oidc/register/{}", client_id)));
+
+    let response = ctx
+        .request(json_request(
+            "POST",
+            register_uri(),
+            Some(&token),
+            Some(metadata),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = client_credentials(&ctx, client_id, secret).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let manage_uri = format!("{}/{}", register_uri(), client_id);
+    let response = ctx
+        .request(json_request("GET", manage_uri.clone(), Some(&token), None))
+        .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = ctx
+        .request(json_request(
+            "GET",
+            manage_uri.clone(),
+            Some(registration_token),
+            None,
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let read = json_body(response).await;
+    assert_eq!(read["client_secret"], secret);
+    assert!(read.get("registration_access_token").is_none());
+
+    let response = ctx
+        .request(json_request(
+            "PUT",
+            manage_uri.clone(),
+            Some(registration_token),
+            Some(json!({
+                "client_id": "someone-else",
+                "redirect_uris": ["https://pr-12.preview.example.com/callback"],
+            })),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = ctx
+        .request(json_request(
+            "PUT",
+            manage_uri.clone(),
+            Some(registration_token),
+            Some(json!({
+                "client_id": client_id,
+                "client_name": "Preview 12b",
+                "redirect_uris": ["https://pr-12b.preview.example.com/callback"],
+            })),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let updated = json_body(response).await;
+    assert_eq!(
+        updated["redirect_uris"],
+        json!(["https://pr-12b.preview.example.com/callback"])
+    );
+    assert_eq!(updated["grant_types"], json!(["authorization_code"]));
+    assert_eq!(updated["scope"], "openid");
+
+    // The token endpoint holds the client to the grant types it registered.
+    let response = client_credentials(&ctx, client_id, secret).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(json_body(response).await["error"], "unauthorized_client");
+
+    let response = ctx
+        .request(json_request(
+            "DELETE",
+            manage_uri.clone(),
+            Some(registration_token),
+            None,
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = ctx
+        .request(json_request(
+            "GET",
+            manage_uri,
+            Some(registration_token),
+            None,
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn initial_access_tokens_require_client_create() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let reader = admin_token(&ctx, realm.id, &[permissions::REALM_READ]).await;
+    let admin = admin_token(&ctx, realm.id, &[permissions::CLIENT_CREATE]).await;
+    let tokens_uri = format!("/api/realms/{}/initial-access-tokens", realm.id);
+
+    let response = ctx
+        .request(json_request(
+            "POST",
+            tokens_uri.clone(),
+            Some(&reader),
+            Some(json!({})),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = ctx
+        .request(json_request(
+            "POST",
+            tokens_uri.clone(),
+            Some(&admin),
+            Some(json!({ "count": 0 })),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let token = issue_initial_access_token(&ctx, realm.id, &admin).await;
+    let response = ctx
+        .request(json_request("GET", tokens_uri.clone(), Some(&admin), None))
+        .await;
+    let listed = json_body(response).await;
+    let token_id = listed[0]["id"].as_str().expect("token id").to_string();
+    assert!(listed[0].get("access_token").is_none());
+
+    let response = ctx
+        .request(json_request(
+            "DELETE",
+            format!("{}/{}", tokens_uri, token_id),
+            Some(&admin),
+            None,
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = ctx
+        .request(json_request(
+            "POST",
+            register_uri(),
+            Some(&token),
+            Some(json!({ "redirect_uris": ["https://app.example.com/callback"] })),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = ctx
+        .request(json_request(
+            "GET",
+            format!(
+                "/api/realms/{}/oidc/.well-known/openid-configuration",
+                DEFAULT_REALM_NAME
+            ),
+            None,
+            None,
+        ))
+        .await;
+    assert!(json_body(response).await["registration_endpoint"]
+        .as_str()
+        .expect("registration_endpoint")
+        .ends_with("/oidc/register"));
+}
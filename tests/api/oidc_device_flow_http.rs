@@ -0,0 +1,357 @@
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{header, HeaderMap, Request, StatusCode};
+use http_body_util::BodyExt;
+use serial_test::serial;
+use std::net::{Ipv4Addr, SocketAddr};
+use uuid::Uuid;
+
+use reauth::application::flow_manager::UpdateDraftRequest;
+use reauth::application::realm_service::CreateRealmPayload;
+use reauth::bootstrap::app_state::SetupState;
+use reauth::constants::{DEFAULT_REALM_NAME, LOGIN_SESSION_COOKIE};
+use reauth::domain::oidc::{OidcClient, TokenEndpointAuthMethod, DEVICE_CODE_GRANT_TYPE};
+use reauth::domain::realm::Realm;
+
+use crate::support::TestContext;
+
+async fn json_body(response: axum::response::Response) -> serde_json::Value {
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("read body")
+        .to_bytes();
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+async fn text_body(response: axum::response::Response) -> String {
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("read body")
+        .to_bytes();
+    String::from_utf8(bytes.to_vec()).expect("utf-8 body")
+}
+
+fn cookie_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .filter_map(|value| value.split(';').next())
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, value)| key.trim() == name && !value.trim().is_empty())
+        .map(|(_, value)| value.trim().to_string())
+}
+
+async fn setup_realm(ctx: &TestContext) -> Realm {
+    let realm = ctx
+        .app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: DEFAULT_REALM_NAME.to_string(),
+        })
+        .await
+        .expect("create realm");
+    *ctx.app_state.setup_state.write().await = SetupState::sealed();
+    realm
+}
+
+async fn publish_password_browser_flow(ctx: &TestContext, realm: &Realm) {
+    let flow_id = realm
+        .browser_flow_id
+        .as_ref()
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .expect("browser flow id");
+    let graph = serde_json::json!({
+        "nodes": [
+            { "id": "start", "type": "core.start", "data": { "config": {} } },
+            { "id": "auth-password", "type": "core.auth.password", "data": { "config": { "auth_type": "core.auth.password" } } },
+            { "id": "allow", "type": "core.terminal.allow", "data": { "config": {} } }
+        ],
+        "edges": [
+            { "id": "e-start-password", "source": "start", "target": "auth-password", "sourceHandle": "next" },
+            { "id": "e-password-allow", "source": "auth-password", "target": "allow", "sourceHandle": "success" }
+        ]
+    });
+
+    ctx.app_state
+        .flow_manager
+        .update_draft(
+            flow_id,
+            UpdateDraftRequest {
+                name: None,
+                description: None,
+                graph_json: Some(graph),
+            },
+        )
+        .await
+        .expect("update draft");
+    ctx.app_state
+        .flow_manager
+        .publish_flow(realm.id, flow_id)
+        .await
+        .expect("publish flow");
+}
+
+async fn register_device_client(ctx: &TestContext, realm_id: Uuid) {
+    let mut client = OidcClient {
+        id: Uuid::new_v4(),
+        realm_id,
+        client_id: "tv-app".to_string(),
+        client_secret: None,
+        redirect_uris: "[]".to_string(),
+        scopes: serde_json::to_string(&vec!["openid"]).expect("scopes json"),
+        web_origins: "[]".to_string(),
+        managed_by_config: false,
+        token_endpoint_auth_method: TokenEndpointAuthMethod::None,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: None,
+        frontchannel_logout_uri: None,
+        token_exchange_policy: None,
+        direct_grant_enabled: false,
+        require_pushed_authorization_requests: false,
+    };
+    ctx.app_state
+        .oidc_service
+        .register_client(&mut client)
+        .await
+        .expect("register client");
+}
+
+fn form_request(uri: &str, pairs: &[(&str, &str)], cookie: Option<String>) -> Request<Body> {
+    let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+    for (key, value) in pairs {
+        serializer.append_pair(key, value);
+    }
+    let mut builder = Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded");
+    if let Some(cookie) = cookie {
+        builder = builder.header(header::COOKIE, cookie);
+    }
+    let mut request = builder.body(Body::from(serializer.finish())).unwrap();
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 3000))));
+    request
+}
+
+async fn start_device_authorization(ctx: &TestContext) -> serde_json::Value {
+    let response = ctx
+        .request(form_request(
+            &format!(
+                "/api/realms/{}/oidc/device_authorization",
+                DEFAULT_REALM_NAME
+            ),
+            &[("client_id", "tv-app"), ("scope", "openid")],
+            None,
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    json_body(response).await
+}
+
+async fn poll(ctx: &TestContext, device_code: &str) -> axum::response::Response {
+    ctx.request(form_request(
+        &format!("/api/realms/{}/oidc/token", DEFAULT_REALM_NAME),
+        &[
+            ("grant_type", DEVICE_CODE_GRANT_TYPE),
+            ("client_id", "tv-app"),
+            ("device_code", device_code),
+        ],
+        None,
+    ))
+    .await
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn device_flow_approves_through_browser_login() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    publish_password_browser_flow(&ctx, &realm).await;
+    register_device_client(&ctx, realm.id).await;
+    ctx.app_state
+        .user_service
+        .create_user(realm.id, "grace", "password-123", None, false)
+        .await
+        .expect("create user");
+
+    let device = start_device_authorization(&ctx).await;
+    let device_code = device["device_code"].as_str().expect("device_code");
+    let user_code = device["user_code"].as_str().expect("user_code");
+    assert_eq!(user_code.len(), 9);
+    assert_eq!(device["interval"], 5);
+    assert_eq!(device["expires_in"], 600);
+    assert!(device["verification_uri"]
+        .as_str()
+        .expect("verification_uri")
+        .ends_with("/api/realms/master/oidc/device"));
+    assert!(device["verification_uri_complete"]
+        .as_str()
+        .expect("verification_uri_complete")
+        .ends_with(&format!("?user_code={}", user_code)));
+
+    let response = poll(&ctx, device_code).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(json_body(response).await["error"], "authorization_pending");
+    let response = poll(&ctx, device_code).await;
+    assert_eq!(json_body(response).await["error"], "slow_down");
+
+    // The prefilled link only shows a confirmation page.
+    let response = ctx
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "/api/realms/{}/oidc/device?user_code={}",
+                    DEFAULT_REALM_NAME, user_code
+                ))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(text_body(response)
+        .await
+        .contains(&format!("value=\"{}\"", user_code)));
+
+    let response = ctx
+        .request(form_request(
+            &format!("/api/realms/{}/oidc/device", DEFAULT_REALM_NAME),
+            &[("user_code", &user_code.to_lowercase())],
+            None,
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(
+        response
+            .headers()
+            .get(header::LOCATION)
+            .and_then(|value| value.to_str().ok()),
+        Some("/#/login?realm=master")
+    );
+    let session_id =
+        cookie_value(response.headers(), LOGIN_SESSION_COOKIE).expect("login session cookie");
+    let login_cookie = format!("{}={}", LOGIN_SESSION_COOKIE, session_id);
+
+    let mut start = Request::builder()
+        .uri(format!("/api/realms/{}/auth/login", DEFAULT_REALM_NAME))
+        .header(header::COOKIE, login_cookie.clone())
+        .body(Body::empty())
+        .unwrap();
+    start
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 3000))));
+    let response = ctx.request(start).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(json_body(response).await["challengeName"], "login-password");
+
+    let mut execute = Request::builder()
+        .method("POST")
+        .uri(format!(
+            "/api/realms/{}/auth/login/execute",
+            DEFAULT_REALM_NAME
+        ))
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::COOKIE, login_cookie)
+        .body(Body::from(
+            serde_json::json!({ "username": "grace", "password": "password-123" }).to_string(),
+        ))
+        .unwrap();
+    execute
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 3000))));
+    let response = ctx.request(execute).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = json_body(response).await;
+    assert_eq!(json["status"], "redirect");
+    assert_eq!(json["url"], "/api/realms/master/oidc/device/complete");
+
+    // The approved code is redeemed once, regardless of the poll interval.
+    let response = poll(&ctx, device_code).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let tokens = json_body(response).await;
+    assert!(tokens["access_token"].as_str().is_some());
+    assert!(tokens["refresh_token"].as_str().is_some());
+    assert_eq!(tokens["scope"], "openid");
+
+    let response = poll(&ctx, device_code).await;
+    assert_eq!(json_body(response).await["error"], "invalid_grant");
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn device_verification_rejects_unknown_codes_and_reports_denial() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    publish_password_browser_flow(&ctx, &realm).await;
+    register_device_client(&ctx, realm.id).await;
+
+    let response = ctx
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "/api/realms/{}/oidc/.well-known/openid-configuration",
+                    DEFAULT_REALM_NAME
+                ))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+    let discovery = json_body(response).await;
+    assert!(discovery["device_authorization_endpoint"]
+        .as_str()
+        .expect("device_authorization_endpoint")
+        .ends_with("/api/realms/master/oidc/device_authorization"));
+    assert!(discovery["grant_types_supported"]
+        .as_array()
+        .expect("grant types")
+        .contains(&serde_json::json!(DEVICE_CODE_GRANT_TYPE)));
+
+    let response = ctx
+        .request(form_request(
+            &format!("/api/realms/{}/oidc/device", DEFAULT_REALM_NAME),
+            &[("user_code", "<b>BCDF-GHJK</b>")],
+            None,
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let page = text_body(response).await;
+    assert!(page.contains("Invalid or expired user code"));
+    assert!(page.contains("&lt;b&gt;BCDF-GHJK&lt;/b&gt;"));
+
+    let device = start_device_authorization(&ctx).await;
+    let user_code = device["user_code"].as_str().expect("user_code");
+    let response = ctx
+        .request(form_request(
+            &format!("/api/realms/{}/oidc/device", DEFAULT_REALM_NAME),
+            &[("user_code", user_code), ("action", "deny")],
+            None,
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(cookie_value(response.headers(), LOGIN_SESSION_COOKIE).is_none());
+
+    let response = poll(&ctx, device["device_code"].as_str().expect("device_code")).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(json_body(response).await["error"], "access_denied");
+
+    let response = poll(&ctx, "unknown").await;
+    assert_eq!(json_body(response).await["error"], "invalid_grant");
+    let response = ctx
+        .request(form_request(
+            &format!("/api/realms/{}/oidc/token", DEFAULT_REALM_NAME),
+            &[
+                ("grant_type", DEVICE_CODE_GRANT_TYPE),
+                ("client_id", "tv-app"),
+            ],
+            None,
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
@@ -0,0 +1,285 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use http_body_util::BodyExt;
+use serial_test::serial;
+use uuid::Uuid;
+
+use reauth::application::realm_service::CreateRealmPayload;
+use reauth::constants::{DEFAULT_REALM_NAME, REFRESH_TOKEN_COOKIE};
+use reauth::domain::oidc::{OidcClient, TokenEndpointAuthMethod};
+use reauth::domain::realm::Realm;
+use reauth::domain::user::User;
+use reauth::error::Error;
+
+use crate::support::TestContext;
+
+async fn body_bytes(response: axum::response::Response) -> Vec<u8> {
+    response
+        .into_body()
+        .collect()
+        .await
+        .expect("read body")
+        .to_bytes()
+        .to_vec()
+}
+
+async fn setup_realm(ctx: &TestContext) -> Realm {
+    ctx.app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: DEFAULT_REALM_NAME.to_string(),
+        })
+        .await
+        .expect("create realm")
+}
+
+async fn setup_user(ctx: &TestContext, realm_id: Uuid) -> User {
+    ctx.app_state
+        .user_service
+        .create_user(realm_id, "erin", "password-123", None, false)
+        .await
+        .expect("create user")
+}
+
+async fn register_client(
+    ctx: &TestContext,
+    realm_id: Uuid,
+    client_id: &str,
+    frontchannel_logout_uri: Option<&str>,
+) -> reauth::error::Result<()> {
+    let mut client = OidcClient {
+        id: Uuid::new_v4(),
+        realm_id,
+        client_id: client_id.to_string(),
+        client_secret: None,
+        redirect_uris: serde_json::to_string(&vec!["http://localhost/callback"])
+            .expect("redirect_uris json"),
+        scopes: serde_json::to_string(&vec!["openid"]).expect("scopes json"),
+        web_origins: "[]".to_string(),
+        managed_by_config: false,
+        token_endpoint_auth_method: TokenEndpointAuthMethod::None,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: Some("http://localhost/backchannel".to_string()),
+        frontchannel_logout_uri: frontchannel_logout_uri.map(str::to_string),
+        token_exchange_policy: None,
+        direct_grant_enabled: false,
+        require_pushed_authorization_requests: false,
+    };
+
+    ctx.app_state
+        .oidc_service
+        .register_client(&mut client)
+        .await
+        .map(|_| ())
+}
+
+async fn get_logout(
+    ctx: &TestContext,
+    params: &[(&str, &str)],
+    sso_token: Option<Uuid>,
+) -> axum::response::Response {
+    let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+    for (key, value) in params {
+        serializer.append_pair(key, value);
+    }
+
+    let mut builder = Request::builder().method("GET").uri(format!(
+        "/api/realms/{}/oidc/logout?{}",
+        DEFAULT_REALM_NAME,
+        serializer.finish()
+    ));
+    if let Some(token) = sso_token {
+        builder = builder.header(
+            header::COOKIE,
+            format!("{}={}", REFRESH_TOKEN_COOKIE, token),
+        );
+    }
+
+    ctx.request(builder.body(Body::empty()).unwrap()).await
+}
+
+fn id_token_claims(id_token: &str) -> serde_json::Value {
+    let payload = id_token.split('.').nth(1).expect("jwt payload");
+    serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).expect("base64 payload"))
+        .expect("claims json")
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn end_session_revokes_sessions_and_redirects_with_state() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let user = setup_user(&ctx, realm.id).await;
+    register_client(&ctx, realm.id, "web-app", None)
+        .await
+        .expect("register client");
+
+    let (_, sso) = ctx
+        .app_state
+        .auth_service
+        .create_session(&user, None, None, None)
+        .await
+        .expect("sso session");
+    let (login, client_session) = ctx
+        .app_state
+        .auth_service
+        .create_session(&user, Some("web-app".to_string()), None, None)
+        .await
+        .expect("client session");
+    let id_token = login.id_token.expect("id token");
+    assert_eq!(
+        id_token_claims(&id_token)["sid"],
+        client_session.family_id.to_string()
+    );
+
+    let response = get_logout(
+        &ctx,
+        &[
+            ("id_token_hint", &id_token),
+            ("post_logout_redirect_uri", "http://localhost/callback"),
+            ("state", "abc"),
+        ],
+        Some(sso.id),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(
+        response.headers().get(header::LOCATION).unwrap(),
+        "http://localhost/callback?state=abc"
+    );
+    assert!(response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .any(|value| value.to_str().unwrap().starts_with(REFRESH_TOKEN_COOKIE)));
+
+    let result = ctx
+        .app_state
+        .auth_service
+        .validate_token_and_get_user(&login.access_token)
+        .await;
+    assert!(matches!(result, Err(Error::SessionRevoked)));
+    let result = ctx.app_state.auth_service.refresh_session(sso.id).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn end_session_renders_frontchannel_logout_iframes() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let user = setup_user(&ctx, realm.id).await;
+    register_client(&ctx, realm.id, "web-app", Some("http://localhost/fc"))
+        .await
+        .expect("register client");
+    let (login, client_session) = ctx
+        .app_state
+        .auth_service
+        .create_session(&user, Some("web-app".to_string()), None, None)
+        .await
+        .expect("client session");
+
+    let response = get_logout(
+        &ctx,
+        &[
+            ("id_token_hint", &login.id_token.expect("id token")),
+            ("post_logout_redirect_uri", "http://localhost/callback"),
+        ],
+        None,
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let html = String::from_utf8(body_bytes(response).await).expect("utf8 body");
+    assert!(html.contains("<iframe src=\"http://localhost/fc?iss="));
+    assert!(html.contains(&format!("&amp;sid={}\"", client_session.family_id)));
+    assert!(html.contains("url=http://localhost/callback"));
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn end_session_rejects_invalid_hints_and_redirects() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let user = setup_user(&ctx, realm.id).await;
+    register_client(&ctx, realm.id, "web-app", None)
+        .await
+        .expect("register client");
+    let (login, _) = ctx
+        .app_state
+        .auth_service
+        .create_session(&user, Some("web-app".to_string()), None, None)
+        .await
+        .expect("client session");
+    let id_token = login.id_token.expect("id token");
+
+    let cases: [&[(&str, &str)]; 4] = [
+        &[("id_token_hint", "not-a-token")],
+        &[("id_token_hint", &id_token), ("client_id", "other-app")],
+        &[
+            ("id_token_hint", &id_token),
+            ("post_logout_redirect_uri", "http://evil.example/"),
+        ],
+        &[("post_logout_redirect_uri", "http://localhost/callback")],
+    ];
+    for params in cases {
+        let response = get_logout(&ctx, params, None).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{:?}", params);
+        let json: serde_json::Value =
+            serde_json::from_slice(&body_bytes(response).await).expect("json body");
+        assert_eq!(json["error"], "invalid_request");
+    }
+
+    // None of the rejected requests ended the session.
+    ctx.app_state
+        .auth_service
+        .validate_token_and_get_user(&login.access_token)
+        .await
+        .expect("session still active");
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn logout_uris_must_be_absolute_http_urls() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+
+    let result = register_client(&ctx, realm.id, "web-app", Some("/relative")).await;
+    assert!(matches!(result, Err(Error::Validation(_))));
+    let result = register_client(&ctx, realm.id, "web-app", Some("https://app/#frag")).await;
+    assert!(matches!(result, Err(Error::Validation(_))));
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn discovery_advertises_logout_support() {
+    let ctx = TestContext::new().await;
+    setup_realm(&ctx).await;
+
+    let response = ctx
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "/api/realms/{}/oidc/.well-known/openid-configuration",
+                    DEFAULT_REALM_NAME
+                ))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json: serde_json::Value =
+        serde_json::from_slice(&body_bytes(response).await).expect("json body");
+
+    assert!(json["end_session_endpoint"]
+        .as_str()
+        .unwrap()
+        .ends_with(&format!("/api/realms/{}/oidc/logout", DEFAULT_REALM_NAME)));
+    assert_eq!(json["frontchannel_logout_supported"], true);
+    assert_eq!(json["backchannel_logout_supported"], true);
+    assert_eq!(json["backchannel_logout_session_supported"], true);
+}
@@ -0,0 +1,303 @@
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{header, HeaderMap, Request, StatusCode};
+use http_body_util::BodyExt;
+use serial_test::serial;
+use std::net::{Ipv4Addr, SocketAddr};
+use uuid::Uuid;
+
+use reauth::application::flow_manager::UpdateDraftRequest;
+use reauth::application::realm_service::CreateRealmPayload;
+use reauth::bootstrap::app_state::SetupState;
+use reauth::constants::{DEFAULT_REALM_NAME, LOGIN_SESSION_COOKIE};
+use reauth::domain::oidc::{OidcClient, TokenEndpointAuthMethod, PAR_REQUEST_URI_PREFIX};
+use reauth::domain::realm::Realm;
+
+use crate::support::TestContext;
+
+const REDIRECT_URI: &str = "http://localhost/callback";
+
+async fn json_body(response: axum::response::Response) -> serde_json::Value {
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("read body")
+        .to_bytes();
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+fn cookie_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .filter_map(|value| value.split(';').next())
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, value)| key.trim() == name && !value.trim().is_empty())
+        .map(|(_, value)| value.trim().to_string())
+}
+
+fn location(response: &axum::response::Response) -> String {
+    response
+        .headers()
+        .get(header::LOCATION)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string()
+}
+
+async fn setup_realm(ctx: &TestContext) -> Realm {
+    let realm = ctx
+        .app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: DEFAULT_REALM_NAME.to_string(),
+        })
+        .await
+        .expect("create realm");
+    *ctx.app_state.setup_state.write().await = SetupState::sealed();
+    realm
+}
+
+async fn publish_password_browser_flow(ctx: &TestContext, realm: &Realm) {
+    let flow_id = realm
+        .browser_flow_id
+        .as_ref()
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .expect("browser flow id");
+    let graph = serde_json::json!({
+        "nodes": [
+            { "id": "start", "type": "core.start", "data": { "config": {} } },
+            { "id": "auth-password", "type": "core.auth.password", "data": { "config": { "auth_type": "core.auth.password" } } },
+            { "id": "allow", "type": "core.terminal.allow", "data": { "config": {} } }
+        ],
+        "edges": [
+            { "id": "e-start-password", "source": "start", "target": "auth-password", "sourceHandle": "next" },
+            { "id": "e-password-allow", "source": "auth-password", "target": "allow", "sourceHandle": "success" }
+        ]
+    });
+
+    ctx.app_state
+        .flow_manager
+        .update_draft(
+            flow_id,
+            UpdateDraftRequest {
+                name: None,
+                description: None,
+                graph_json: Some(graph),
+            },
+        )
+        .await
+        .expect("update draft");
+    ctx.app_state
+        .flow_manager
+        .publish_flow(realm.id, flow_id)
+        .await
+        .expect("publish flow");
+}
+
+async fn register_web_client(ctx: &TestContext, realm_id: Uuid, require_par: bool) -> String {
+    let mut client = OidcClient {
+        id: Uuid::new_v4(),
+        realm_id,
+        client_id: "web-app".to_string(),
+        client_secret: None,
+        redirect_uris: serde_json::to_string(&vec![REDIRECT_URI]).expect("redirect_uris json"),
+        scopes: serde_json::to_string(&vec!["openid"]).expect("scopes json"),
+        web_origins: "[]".to_string(),
+        managed_by_config: false,
+        token_endpoint_auth_method: TokenEndpointAuthMethod::ClientSecretPost,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: None,
+        frontchannel_logout_uri: None,
+        token_exchange_policy: None,
+        direct_grant_enabled: false,
+        require_pushed_authorization_requests: require_par,
+    };
+    ctx.app_state
+        .oidc_service
+        .register_client(&mut client)
+        .await
+        .expect("register client")
+        .expect("client secret")
+}
+
+fn par_request(pairs: &[(&str, &str)]) -> Request<Body> {
+    let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+    for (key, value) in pairs {
+        serializer.append_pair(key, value);
+    }
+    let mut request = Request::builder()
+        .method("POST")
+        .uri(format!("/api/realms/{}/oidc/par", DEFAULT_REALM_NAME))
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(Body::from(serializer.finish()))
+        .unwrap();
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 3000))));
+    request
+}
+
+fn authorize_request(pairs: &[(&str, &str)]) -> Request<Body> {
+    let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+    for (key, value) in pairs {
+        serializer.append_pair(key, value);
+    }
+    Request::builder()
+        .uri(format!(
+            "/api/realms/{}/oidc/authorize?{}",
+            DEFAULT_REALM_NAME,
+            serializer.finish()
+        ))
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn pushed_request_uri_starts_the_browser_flow_once() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    publish_password_browser_flow(&ctx, &realm).await;
+    let secret = register_web_client(&ctx, realm.id, false).await;
+
+    let response = ctx
+        .request(par_request(&[
+            ("client_id", "web-app"),
+            ("client_secret", &secret),
+            ("redirect_uri", REDIRECT_URI),
+            ("response_type", "code"),
+            ("scope", "openid"),
+            ("state", "pushed-state"),
+        ]))
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = json_body(response).await;
+    assert_eq!(body["expires_in"], 60);
+    let request_uri = body["request_uri"].as_str().expect("request_uri");
+    assert!(request_uri.starts_with(PAR_REQUEST_URI_PREFIX));
+
+    let authorize = [("client_id", "web-app"), ("request_uri", request_uri)];
+    let response = ctx.request(authorize_request(&authorize)).await;
+    assert!(response.status().is_redirection());
+    assert!(location(&response).starts_with("/#/login?realm=master"));
+    let session_id = cookie_value(response.headers(), LOGIN_SESSION_COOKIE)
+        .and_then(|value| Uuid::parse_str(&value).ok())
+        .expect("login session cookie");
+    let session = ctx
+        .app_state
+        .auth_session_repo
+        .find_by_id(&session_id)
+        .await
+        .expect("session lookup")
+        .expect("session");
+    assert_eq!(session.context["oidc"]["state"], "pushed-state");
+    assert_eq!(session.context["oidc"]["redirect_uri"], REDIRECT_URI);
+
+    // The request_uri is single use, and errors are not redirected because
+    // the redirect URI behind it is unknown now.
+    let response = ctx.request(authorize_request(&authorize)).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(json_body(response).await["error"], "invalid_request_uri");
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn par_endpoint_authenticates_and_validates_the_request() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let secret = register_web_client(&ctx, realm.id, false).await;
+
+    let response = ctx
+        .request(par_request(&[
+            ("client_id", "web-app"),
+            ("client_secret", "wrong-secret"),
+            ("redirect_uri", REDIRECT_URI),
+            ("response_type", "code"),
+        ]))
+        .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(json_body(response).await["error"], "invalid_client");
+
+    let response = ctx
+        .request(par_request(&[
+            ("client_id", "web-app"),
+            ("client_secret", &secret),
+            ("redirect_uri", "http://evil.example/callback"),
+            ("response_type", "code"),
+        ]))
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(json_body(response).await["error"], "invalid_request");
+
+    let response = ctx
+        .request(par_request(&[
+            ("client_id", "web-app"),
+            ("client_secret", &secret),
+            ("redirect_uri", REDIRECT_URI),
+            ("response_type", "code"),
+            ("request_uri", &format!("{}abc", PAR_REQUEST_URI_PREFIX)),
+        ]))
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(json_body(response).await["error"], "invalid_request");
+
+    let response = ctx
+        .request(par_request(&[
+            ("client_id", "web-app"),
+            ("client_secret", &secret),
+            ("redirect_uri", REDIRECT_URI),
+            ("response_type", "code"),
+            ("request", "not-a-jwt"),
+        ]))
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(json_body(response).await["error"], "invalid_request_object");
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn client_requiring_par_is_redirected_with_an_error() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    publish_password_browser_flow(&ctx, &realm).await;
+    register_web_client(&ctx, realm.id, true).await;
+
+    let response = ctx
+        .request(authorize_request(&[
+            ("client_id", "web-app"),
+            ("redirect_uri", REDIRECT_URI),
+            ("response_type", "code"),
+            ("state", "plain-state"),
+        ]))
+        .await;
+    assert!(response.status().is_redirection());
+    let redirect = url::Url::parse(&location(&response)).expect("redirect url");
+    let query: std::collections::HashMap<_, _> = redirect.query_pairs().into_owned().collect();
+    assert_eq!(
+        query.get("error").map(String::as_str),
+        Some("invalid_request")
+    );
+    assert_eq!(query.get("state").map(String::as_str), Some("plain-state"));
+    assert!(cookie_value(response.headers(), LOGIN_SESSION_COOKIE).is_none());
+
+    let response = ctx
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "/api/realms/{}/oidc/.well-known/openid-configuration",
+                    DEFAULT_REALM_NAME
+                ))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+    let discovery = json_body(response).await;
+    assert!(discovery["pushed_authorization_request_endpoint"]
+        .as_str()
+        .is_some_and(|endpoint| endpoint.ends_with("/api/realms/master/oidc/par")));
+    assert_eq!(discovery["request_parameter_supported"], true);
+}
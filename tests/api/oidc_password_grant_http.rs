@@ -0,0 +1,261 @@
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{header, Request, StatusCode};
+use http_body_util::BodyExt;
+use serial_test::serial;
+use std::net::{Ipv4Addr, SocketAddr};
+use uuid::Uuid;
+
+use reauth::application::flow_manager::templates::FlowTemplates;
+use reauth::application::flow_manager::UpdateDraftRequest;
+use reauth::application::realm_service::CreateRealmPayload;
+use reauth::constants::DEFAULT_REALM_NAME;
+use reauth::domain::oidc::{OidcClient, TokenEndpointAuthMethod};
+use reauth::domain::realm::Realm;
+
+use crate::support::TestContext;
+
+async fn json_body(response: axum::response::Response) -> serde_json::Value {
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("read body")
+        .to_bytes();
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+async fn setup_realm(ctx: &TestContext) -> Realm {
+    ctx.app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: DEFAULT_REALM_NAME.to_string(),
+        })
+        .await
+        .expect("create realm")
+}
+
+async fn publish_direct_grant_flow(ctx: &TestContext, realm: &Realm, graph: serde_json::Value) {
+    let flow_id = realm
+        .direct_grant_flow_id
+        .as_ref()
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .expect("direct grant flow id");
+    ctx.app_state
+        .flow_manager
+        .update_draft(
+            flow_id,
+            UpdateDraftRequest {
+                name: None,
+                description: None,
+                graph_json: Some(graph),
+            },
+        )
+        .await
+        .expect("update draft");
+    ctx.app_state
+        .flow_manager
+        .publish_flow(realm.id, flow_id)
+        .await
+        .expect("publish flow");
+}
+
+/// Registers a confidential client and returns its plaintext secret.
+async fn register_client(
+    ctx: &TestContext,
+    realm_id: Uuid,
+    client_id: &str,
+    direct_grant_enabled: bool,
+) -> String {
+    let mut client = OidcClient {
+        id: Uuid::new_v4(),
+        realm_id,
+        client_id: client_id.to_string(),
+        client_secret: None,
+        redirect_uris: serde_json::to_string(&vec!["http://localhost/callback"])
+            .expect("redirect_uris json"),
+        scopes: serde_json::to_string(&vec!["openid", "profile"]).expect("scopes json"),
+        web_origins: "[]".to_string(),
+        managed_by_config: false,
+        token_endpoint_auth_method: TokenEndpointAuthMethod::ClientSecretPost,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: None,
+        frontchannel_logout_uri: None,
+        token_exchange_policy: None,
+        direct_grant_enabled,
+        require_pushed_authorization_requests: false,
+    };
+
+    ctx.app_state
+        .oidc_service
+        .register_client(&mut client)
+        .await
+        .expect("register client")
+        .expect("client secret")
+}
+
+async fn post_token(ctx: &TestContext, form: &[(&str, &str)]) -> axum::response::Response {
+    let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+    for (key, value) in form {
+        serializer.append_pair(key, value);
+    }
+    let mut request = Request::builder()
+        .method("POST")
+        .uri(format!("/api/realms/{}/oidc/token", DEFAULT_REALM_NAME))
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(Body::from(serializer.finish()))
+        .unwrap();
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 3000))));
+    ctx.request(request).await
+}
+
+async fn password_grant(
+    ctx: &TestContext,
+    client_id: &str,
+    secret: &str,
+    username: &str,
+    password: &str,
+) -> (StatusCode, serde_json::Value) {
+    let response = post_token(
+        ctx,
+        &[
+            ("grant_type", "password"),
+            ("client_id", client_id),
+            ("client_secret", secret),
+            ("username", username),
+            ("password", password),
+            ("scope", "openid profile"),
+        ],
+    )
+    .await;
+    (response.status(), json_body(response).await)
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn password_grant_runs_direct_grant_flow_for_enabled_clients() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    publish_direct_grant_flow(&ctx, &realm, FlowTemplates::direct_grant_flow()).await;
+    ctx.app_state
+        .user_service
+        .create_user(realm.id, "legacy-user", "password-123", None, false)
+        .await
+        .expect("create user");
+    let secret = register_client(&ctx, realm.id, "legacy-app", true).await;
+    let other_secret = register_client(&ctx, realm.id, "browser-app", false).await;
+
+    let (status, body) = password_grant(
+        &ctx,
+        "browser-app",
+        &other_secret,
+        "legacy-user",
+        "password-123",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    assert_eq!(body["error"], "unauthorized_client");
+
+    let (status, body) =
+        password_grant(&ctx, "legacy-app", &secret, "legacy-user", "wrong-password").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    assert_eq!(body["error"], "invalid_grant");
+    assert_eq!(body["error_description"], "Invalid credentials");
+
+    let (status, body) =
+        password_grant(&ctx, "legacy-app", &secret, "legacy-user", "password-123").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["token_type"], "Bearer");
+    assert_eq!(body["scope"], "openid profile");
+    assert!(body["access_token"].as_str().is_some());
+    let refresh_token = body["refresh_token"].as_str().expect("refresh token");
+
+    let response = post_token(
+        &ctx,
+        &[
+            ("grant_type", "refresh_token"),
+            ("client_id", "legacy-app"),
+            ("client_secret", &secret),
+            ("refresh_token", refresh_token),
+        ],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = post_token(
+        &ctx,
+        &[
+            ("grant_type", "password"),
+            ("client_id", "legacy-app"),
+            ("client_secret", &secret),
+            ("username", "legacy-user"),
+        ],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn password_grant_maps_unanswered_challenges_and_denials_to_invalid_grant() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    ctx.app_state
+        .user_service
+        .create_user(realm.id, "mfa-user", "password-123", None, false)
+        .await
+        .expect("create user");
+    let secret = register_client(&ctx, realm.id, "legacy-app", true).await;
+
+    // A second factor the token request cannot answer.
+    publish_direct_grant_flow(
+        &ctx,
+        &realm,
+        serde_json::json!({
+            "nodes": [
+                { "id": "start", "type": "core.start", "data": { "config": {} } },
+                { "id": "auth-password", "type": "core.auth.password", "data": { "config": { "auth_type": "core.auth.password" } } },
+                { "id": "totp-enroll", "type": "core.auth.totp_enroll", "data": { "config": { "auth_type": "core.auth.totp_enroll", "allow_skip": false } } },
+                { "id": "allow", "type": "core.terminal.allow", "data": { "config": {} } },
+                { "id": "deny", "type": "core.terminal.deny", "data": { "config": { "is_failure": true } } }
+            ],
+            "edges": [
+                { "id": "e-start-password", "source": "start", "target": "auth-password", "sourceHandle": "next" },
+                { "id": "e-password-enroll", "source": "auth-password", "target": "totp-enroll", "sourceHandle": "success" },
+                { "id": "e-enroll-allow", "source": "totp-enroll", "target": "allow", "sourceHandle": "success" },
+                { "id": "e-enroll-skip", "source": "totp-enroll", "target": "allow", "sourceHandle": "skip" },
+                { "id": "e-enroll-deny", "source": "totp-enroll", "target": "deny", "sourceHandle": "failure" }
+            ]
+        }),
+    )
+    .await;
+    let (status, body) =
+        password_grant(&ctx, "legacy-app", &secret, "mfa-user", "password-123").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    assert_eq!(body["error"], "invalid_grant");
+
+    // A deny terminal right after the password check.
+    publish_direct_grant_flow(
+        &ctx,
+        &realm,
+        serde_json::json!({
+            "nodes": [
+                { "id": "start", "type": "core.start", "data": { "config": {} } },
+                { "id": "auth-password", "type": "core.auth.password", "data": { "config": { "auth_type": "core.auth.password" } } },
+                { "id": "deny", "type": "core.terminal.deny", "data": { "config": { "is_failure": true } } }
+            ],
+            "edges": [
+                { "id": "e-start-password", "source": "start", "target": "auth-password", "sourceHandle": "next" },
+                { "id": "e-password-deny", "source": "auth-password", "target": "deny", "sourceHandle": "success" }
+            ]
+        }),
+    )
+    .await;
+    let (status, body) =
+        password_grant(&ctx, "legacy-app", &secret, "mfa-user", "password-123").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    assert_eq!(body["error"], "invalid_grant");
+}
@@ -0,0 +1,338 @@
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{header, Request, StatusCode};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use http_body_util::BodyExt;
+use serial_test::serial;
+use std::net::{Ipv4Addr, SocketAddr};
+use uuid::Uuid;
+
+use reauth::application::realm_service::CreateRealmPayload;
+use reauth::constants::DEFAULT_REALM_NAME;
+use reauth::domain::oidc::{
+    OidcClient, TokenEndpointAuthMethod, ACCESS_TOKEN_TYPE, TOKEN_EXCHANGE_GRANT_TYPE,
+};
+use reauth::domain::realm::Realm;
+use reauth::domain::user::User;
+use reauth::error::Error;
+
+use crate::support::TestContext;
+
+async fn json_body(response: axum::response::Response) -> serde_json::Value {
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("read body")
+        .to_bytes();
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+fn jwt_payload(token: &str) -> serde_json::Value {
+    let payload = token.split('.').nth(1).expect("jwt payload segment");
+    let bytes = URL_SAFE_NO_PAD.decode(payload).expect("jwt payload base64");
+    serde_json::from_slice(&bytes).expect("jwt payload json")
+}
+
+async fn setup_realm(ctx: &TestContext) -> Realm {
+    ctx.app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: DEFAULT_REALM_NAME.to_string(),
+        })
+        .await
+        .expect("create realm")
+}
+
+async fn setup_user(ctx: &TestContext, realm_id: Uuid) -> User {
+    ctx.app_state
+        .user_service
+        .create_user(realm_id, "frank", "password-123", None, false)
+        .await
+        .expect("create user")
+}
+
+/// Registers a client with a token exchange policy and returns its secret,
+/// if it has one.
+async fn register_client(
+    ctx: &TestContext,
+    realm_id: Uuid,
+    client_id: &str,
+    auth_method: TokenEndpointAuthMethod,
+    policy: Option<serde_json::Value>,
+) -> reauth::error::Result<Option<String>> {
+    let mut client = OidcClient {
+        id: Uuid::new_v4(),
+        realm_id,
+        client_id: client_id.to_string(),
+        client_secret: None,
+        redirect_uris: serde_json::to_string(&vec!["http://localhost/callback"])
+            .expect("redirect_uris json"),
+        scopes: serde_json::to_string(&vec!["openid"]).expect("scopes json"),
+        web_origins: "[]".to_string(),
+        managed_by_config: false,
+        token_endpoint_auth_method: auth_method,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: None,
+        frontchannel_logout_uri: None,
+        token_exchange_policy: policy.map(|policy| policy.to_string()),
+        direct_grant_enabled: false,
+        require_pushed_authorization_requests: false,
+    };
+
+    ctx.app_state
+        .oidc_service
+        .register_client(&mut client)
+        .await
+}
+
+async fn exchange(
+    ctx: &TestContext,
+    client: (&str, &str),
+    subject_token: &str,
+    audience: &str,
+    scope: Option<&str>,
+) -> axum::response::Response {
+    let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+    serializer
+        .append_pair("grant_type", TOKEN_EXCHANGE_GRANT_TYPE)
+        .append_pair("client_id", client.0)
+        .append_pair("client_secret", client.1)
+        .append_pair("subject_token", subject_token)
+        .append_pair("subject_token_type", ACCESS_TOKEN_TYPE)
+        .append_pair("audience", audience);
+    if let Some(scope) = scope {
+        serializer.append_pair("scope", scope);
+    }
+
+    let mut request = Request::builder()
+        .method("POST")
+        .uri(format!("/api/realms/{}/oidc/token", DEFAULT_REALM_NAME))
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(Body::from(serializer.finish()))
+        .unwrap();
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 3000))));
+
+    ctx.request(request).await
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn token_exchange_builds_delegation_chain() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let user = setup_user(&ctx, realm.id).await;
+    let gateway_secret = register_client(
+        &ctx,
+        realm.id,
+        "gateway",
+        TokenEndpointAuthMethod::ClientSecretPost,
+        Some(serde_json::json!([{ "audience": "orders-api", "scopes": ["orders:read"] }])),
+    )
+    .await
+    .expect("register gateway")
+    .expect("gateway secret");
+    let orders_secret = register_client(
+        &ctx,
+        realm.id,
+        "orders-api",
+        TokenEndpointAuthMethod::ClientSecretPost,
+        Some(serde_json::json!([{ "audience": "billing-api" }])),
+    )
+    .await
+    .expect("register orders-api")
+    .expect("orders-api secret");
+    register_client(
+        &ctx,
+        realm.id,
+        "billing-api",
+        TokenEndpointAuthMethod::None,
+        None,
+    )
+    .await
+    .expect("register billing-api");
+
+    let (login, session) = ctx
+        .app_state
+        .auth_service
+        .create_session(&user, None, None, None)
+        .await
+        .expect("session");
+
+    let response = exchange(
+        &ctx,
+        ("gateway", &gateway_secret),
+        &login.access_token,
+        "orders-api",
+        Some("orders:read"),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = json_body(response).await;
+    assert_eq!(json["issued_token_type"], ACCESS_TOKEN_TYPE);
+    assert_eq!(json["token_type"], "Bearer");
+    assert_eq!(json["scope"], "orders:read");
+    assert!(json.get("refresh_token").is_none());
+    let orders_token = json["access_token"].as_str().expect("access_token");
+    let claims = jwt_payload(orders_token);
+    assert_eq!(claims["sub"], user.id.to_string());
+    assert_eq!(claims["aud"], "orders-api");
+    assert_eq!(claims["azp"], "gateway");
+    assert_eq!(claims["act"], serde_json::json!({ "sub": "gateway" }));
+    assert_eq!(claims["perms"], serde_json::json!([]));
+
+    // orders-api passes the user's authority on to billing-api.
+    let response = exchange(
+        &ctx,
+        ("orders-api", &orders_secret),
+        orders_token,
+        "billing-api",
+        None,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = json_body(response).await;
+    let claims = jwt_payload(json["access_token"].as_str().expect("access_token"));
+    assert_eq!(claims["aud"], "billing-api");
+    assert_eq!(
+        claims["act"],
+        serde_json::json!({ "sub": "orders-api", "act": { "sub": "gateway" } })
+    );
+
+    // Exchanged tokens are for the audience, not for this server's API.
+    let result = ctx
+        .app_state
+        .auth_service
+        .validate_token_and_get_user(orders_token)
+        .await;
+    assert!(matches!(result, Err(Error::InvalidCredentials)));
+
+    // They live and die with the subject's session.
+    ctx.app_state
+        .auth_service
+        .logout(session.id)
+        .await
+        .expect("logout");
+    let response = exchange(
+        &ctx,
+        ("orders-api", &orders_secret),
+        orders_token,
+        "billing-api",
+        None,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(json_body(response).await["error"], "invalid_request");
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn token_exchange_rejects_requests_outside_policy() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let user = setup_user(&ctx, realm.id).await;
+    let gateway_secret = register_client(
+        &ctx,
+        realm.id,
+        "gateway",
+        TokenEndpointAuthMethod::ClientSecretPost,
+        Some(serde_json::json!([{ "audience": "orders-api", "scopes": ["orders:read"] }])),
+    )
+    .await
+    .expect("register gateway")
+    .expect("gateway secret");
+    register_client(
+        &ctx,
+        realm.id,
+        "orders-api",
+        TokenEndpointAuthMethod::None,
+        None,
+    )
+    .await
+    .expect("register orders-api");
+    let (login, _) = ctx
+        .app_state
+        .auth_service
+        .create_session(&user, None, None, None)
+        .await
+        .expect("session");
+
+    let response = exchange(
+        &ctx,
+        ("gateway", &gateway_secret),
+        &login.access_token,
+        "billing-api",
+        None,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(json_body(response).await["error"], "invalid_target");
+
+    let response = exchange(
+        &ctx,
+        ("gateway", &gateway_secret),
+        &login.access_token,
+        "orders-api",
+        Some("orders:write"),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(json_body(response).await["error"], "invalid_scope");
+
+    let response = exchange(
+        &ctx,
+        ("gateway", &gateway_secret),
+        "not-a-token",
+        "orders-api",
+        None,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(json_body(response).await["error"], "invalid_request");
+
+    let response = exchange(
+        &ctx,
+        ("gateway", &gateway_secret),
+        &login.access_token,
+        "",
+        None,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let events = ctx
+        .app_state
+        .audit_service
+        .list_recent(realm.id, 10)
+        .await
+        .expect("audit events");
+    let denied = events
+        .iter()
+        .filter(|event| event.action == "token_exchange_denied")
+        .count();
+    assert_eq!(denied, 3);
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn token_exchange_policy_rejects_duplicate_audiences() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+
+    let result = register_client(
+        &ctx,
+        realm.id,
+        "gateway",
+        TokenEndpointAuthMethod::ClientSecretPost,
+        Some(serde_json::json!([
+            { "audience": "orders-api" },
+            { "audience": "orders-api", "scopes": ["orders:read"] }
+        ])),
+    )
+    .await;
+    assert!(matches!(result, Err(Error::Validation(_))));
+}
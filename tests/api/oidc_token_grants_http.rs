@@ -0,0 +1,395 @@
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{header, Request, StatusCode};
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use http_body_util::BodyExt;
+use serial_test::serial;
+use std::net::{Ipv4Addr, SocketAddr};
+use uuid::Uuid;
+
+use reauth::application::rbac_service::{CreateCustomPermissionPayload, CreateRolePayload};
+use reauth::application::realm_service::CreateRealmPayload;
+use reauth::constants::DEFAULT_REALM_NAME;
+use reauth::domain::oidc::{OidcClient, TokenEndpointAuthMethod};
+use reauth::domain::realm::Realm;
+
+use crate::support::TestContext;
+
+async fn json_body(response: axum::response::Response) -> serde_json::Value {
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("read body")
+        .to_bytes();
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+fn jwt_payload(token: &str) -> serde_json::Value {
+    let payload = token.split('.').nth(1).expect("jwt payload segment");
+    let bytes = URL_SAFE_NO_PAD.decode(payload).expect("jwt payload base64");
+    serde_json::from_slice(&bytes).expect("jwt payload json")
+}
+
+async fn setup_realm(ctx: &TestContext) -> Realm {
+    ctx.app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: DEFAULT_REALM_NAME.to_string(),
+        })
+        .await
+        .expect("create realm")
+}
+
+/// Registers a client and returns it together with its plaintext secret.
+async fn register_client(
+    ctx: &TestContext,
+    realm_id: Uuid,
+    client_id: &str,
+    auth_method: TokenEndpointAuthMethod,
+) -> (OidcClient, String) {
+    let mut client = OidcClient {
+        id: Uuid::new_v4(),
+        realm_id,
+        client_id: client_id.to_string(),
+        client_secret: None,
+        redirect_uris: serde_json::to_string(&vec!["http://localhost/callback"])
+            .expect("redirect_uris json"),
+        scopes: serde_json::to_string(&vec!["openid", "orders:read"]).expect("scopes json"),
+        web_origins: "[]".to_string(),
+        managed_by_config: false,
+        token_endpoint_auth_method: auth_method,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: None,
+        frontchannel_logout_uri: None,
+        token_exchange_policy: None,
+        direct_grant_enabled: false,
+        require_pushed_authorization_requests: false,
+    };
+
+    let secret = ctx
+        .app_state
+        .oidc_service
+        .register_client(&mut client)
+        .await
+        .expect("register client")
+        .expect("client secret");
+
+    (client, secret)
+}
+
+async fn post_token(ctx: &TestContext, form: &[(&str, &str)]) -> axum::response::Response {
+    post_token_with_basic(ctx, form, None).await
+}
+
+async fn post_token_with_basic(
+    ctx: &TestContext,
+    form: &[(&str, &str)],
+    basic: Option<(&str, &str)>,
+) -> axum::response::Response {
+    let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+    for (key, value) in form {
+        serializer.append_pair(key, value);
+    }
+
+    let mut builder = Request::builder()
+        .method("POST")
+        .uri(format!("/api/realms/{}/oidc/token", DEFAULT_REALM_NAME))
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded");
+    if let Some((client_id, secret)) = basic {
+        let credentials = STANDARD.encode(format!("{}:{}", client_id, secret));
+        builder = builder.header(header::AUTHORIZATION, format!("Basic {}", credentials));
+    }
+    let mut request = builder.body(Body::from(serializer.finish())).unwrap();
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 3000))));
+
+    ctx.request(request).await
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn client_credentials_grant_issues_token_with_client_role_permissions() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let (client, secret) = register_client(
+        &ctx,
+        realm.id,
+        "billing-worker",
+        TokenEndpointAuthMethod::ClientSecretPost,
+    )
+    .await;
+
+    ctx.app_state
+        .rbac_service
+        .create_custom_permission(
+            realm.id,
+            CreateCustomPermissionPayload {
+                permission: "orders:read".to_string(),
+                name: "Read orders".to_string(),
+                description: None,
+                client_id: Some(client.id),
+            },
+        )
+        .await
+        .expect("create permission");
+    let role = ctx
+        .app_state
+        .rbac_service
+        .create_role(
+            realm.id,
+            CreateRolePayload {
+                name: "orders-reader".to_string(),
+                description: None,
+                client_id: Some(client.id),
+            },
+        )
+        .await
+        .expect("create client role");
+    ctx.app_state
+        .rbac_service
+        .assign_permission_to_role(realm.id, role.id, "orders:read".to_string())
+        .await
+        .expect("assign permission");
+
+    let response = post_token(
+        &ctx,
+        &[
+            ("grant_type", "client_credentials"),
+            ("client_id", "billing-worker"),
+            ("client_secret", &secret),
+            ("scope", "orders:read"),
+        ],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let json = json_body(response).await;
+    assert_eq!(json["token_type"], "Bearer");
+    assert_eq!(json["scope"], "orders:read");
+    assert!(json.get("refresh_token").is_none());
+    assert!(json.get("id_token").is_none());
+
+    let claims = jwt_payload(json["access_token"].as_str().expect("access token"));
+    assert_eq!(claims["sub"], client.id.to_string());
+    assert_eq!(claims["azp"], "billing-worker");
+    assert_eq!(claims["roles"], serde_json::json!(["orders-reader"]));
+    assert_eq!(claims["perms"], serde_json::json!(["orders:read"]));
+    let lifetime = claims["exp"].as_i64().expect("exp") - claims["iat"].as_i64().expect("iat");
+    assert_eq!(json["expires_in"].as_i64(), Some(lifetime));
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn client_credentials_grant_rejects_invalid_secret() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let _ = register_client(
+        &ctx,
+        realm.id,
+        "billing-worker",
+        TokenEndpointAuthMethod::ClientSecretPost,
+    )
+    .await;
+
+    let response = post_token(
+        &ctx,
+        &[
+            ("grant_type", "client_credentials"),
+            ("client_id", "billing-worker"),
+            ("client_secret", "not-the-secret"),
+        ],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let json = json_body(response).await;
+    assert_eq!(json["error"], "invalid_client");
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn client_secret_basic_authenticates_from_authorization_header() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let (_client, secret) = register_client(
+        &ctx,
+        realm.id,
+        "reporting",
+        TokenEndpointAuthMethod::ClientSecretBasic,
+    )
+    .await;
+
+    let response = post_token_with_basic(
+        &ctx,
+        &[("grant_type", "client_credentials")],
+        Some(("reporting", &secret)),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(json_body(response).await["access_token"].is_string());
+
+    let response = post_token_with_basic(
+        &ctx,
+        &[("grant_type", "client_credentials")],
+        Some(("reporting", "not-the-secret")),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert!(response.headers().contains_key(header::WWW_AUTHENTICATE));
+    assert_eq!(json_body(response).await["error"], "invalid_client");
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn token_endpoint_rejects_mismatched_auth_method() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let (_client, secret) = register_client(
+        &ctx,
+        realm.id,
+        "reporting",
+        TokenEndpointAuthMethod::ClientSecretBasic,
+    )
+    .await;
+
+    // Correct secret, wrong transport: client_secret_post is not allowed.
+    let response = post_token(
+        &ctx,
+        &[
+            ("grant_type", "client_credentials"),
+            ("client_id", "reporting"),
+            ("client_secret", &secret),
+        ],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(json_body(response).await["error"], "invalid_client");
+
+    // Presenting two methods at once is a malformed request.
+    let response = post_token_with_basic(
+        &ctx,
+        &[
+            ("grant_type", "client_credentials"),
+            ("client_secret", &secret),
+        ],
+        Some(("reporting", &secret)),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(json_body(response).await["error"], "invalid_request");
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn refresh_token_grant_rotates_and_detects_reuse() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let (_client, secret) = register_client(
+        &ctx,
+        realm.id,
+        "mobile-app",
+        TokenEndpointAuthMethod::ClientSecretPost,
+    )
+    .await;
+
+    let user = ctx
+        .app_state
+        .user_service
+        .create_user(realm.id, "carol", "password-123", None, false)
+        .await
+        .expect("create user");
+    let (_, refresh_token) = ctx
+        .app_state
+        .auth_service
+        .create_session(&user, Some("mobile-app".to_string()), None, None)
+        .await
+        .expect("create session");
+    let original = refresh_token.id.to_string();
+
+    let response = post_token(
+        &ctx,
+        &[
+            ("grant_type", "refresh_token"),
+            ("client_id", "mobile-app"),
+            ("client_secret", &secret),
+            ("refresh_token", &original),
+        ],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = json_body(response).await;
+    let rotated = json["refresh_token"]
+        .as_str()
+        .expect("rotated refresh token")
+        .to_string();
+    assert_ne!(rotated, original);
+    assert!(json["id_token"].as_str().is_some());
+
+    // Replaying the original token trips reuse detection and kills the family.
+    let response = post_token(
+        &ctx,
+        &[
+            ("grant_type", "refresh_token"),
+            ("client_id", "mobile-app"),
+            ("client_secret", &secret),
+            ("refresh_token", &original),
+        ],
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(json_body(response).await["error"], "invalid_grant");
+
+    let rotated_id = Uuid::parse_str(&rotated).expect("rotated id");
+    let live = ctx
+        .app_state
+        .session_repo
+        .find_by_id(&rotated_id)
+        .await
+        .expect("session lookup");
+    assert!(live.is_none());
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn discovery_advertises_supported_grant_types_and_auth_methods() {
+    let ctx = TestContext::new().await;
+    let _realm = setup_realm(&ctx).await;
+
+    let response = ctx
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "/api/realms/{}/oidc/.well-known/openid-configuration",
+                    DEFAULT_REALM_NAME
+                ))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let json = json_body(response).await;
+    let grants = json["grant_types_supported"]
+        .as_array()
+        .expect("grant_types_supported");
+    for grant in [
+        "authorization_code",
+        "client_credentials",
+        "refresh_token",
+        "password",
+    ] {
+        assert!(grants.iter().any(|value| value == grant), "missing {grant}");
+    }
+    assert_eq!(
+        json["token_endpoint_auth_methods_supported"],
+        serde_json::json!([
+            "none",
+            "client_secret_basic",
+            "client_secret_post",
+            "private_key_jwt"
+        ])
+    );
+}
@@ -0,0 +1,346 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use http_body_util::BodyExt;
+use serial_test::serial;
+use uuid::Uuid;
+
+use reauth::application::realm_service::CreateRealmPayload;
+use reauth::constants::DEFAULT_REALM_NAME;
+use reauth::domain::oidc::{OidcClient, TokenEndpointAuthMethod, BACKCHANNEL_LOGOUT_EVENT_TYPE};
+use reauth::domain::realm::Realm;
+
+use crate::support::TestContext;
+
+async fn json_body(response: axum::response::Response) -> serde_json::Value {
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("read body")
+        .to_bytes();
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+async fn setup_realm(ctx: &TestContext) -> Realm {
+    ctx.app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: DEFAULT_REALM_NAME.to_string(),
+        })
+        .await
+        .expect("create realm")
+}
+
+/// Registers a client and returns its plaintext secret.
+async fn register_client(
+    ctx: &TestContext,
+    realm_id: Uuid,
+    client_id: &str,
+    auth_method: TokenEndpointAuthMethod,
+    backchannel_logout_uri: Option<&str>,
+) -> String {
+    let mut client = OidcClient {
+        id: Uuid::new_v4(),
+        realm_id,
+        client_id: client_id.to_string(),
+        client_secret: None,
+        redirect_uris: serde_json::to_string(&vec!["http://localhost/callback"])
+            .expect("redirect_uris json"),
+        scopes: serde_json::to_string(&vec!["openid"]).expect("scopes json"),
+        web_origins: "[]".to_string(),
+        managed_by_config: false,
+        token_endpoint_auth_method: auth_method,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: backchannel_logout_uri.map(str::to_string),
+        frontchannel_logout_uri: None,
+        token_exchange_policy: None,
+        direct_grant_enabled: false,
+        require_pushed_authorization_requests: false,
+    };
+
+    ctx.app_state
+        .oidc_service
+        .register_client(&mut client)
+        .await
+        .expect("register client")
+        .expect("client secret")
+}
+
+/// Payloads of the outbox rows of the given event type, oldest first.
+async fn outbox_payloads(ctx: &TestContext, event_type: &str) -> Vec<serde_json::Value> {
+    let database_url = ctx.app_state.settings.read().await.database.url.clone();
+    let pool = sqlx::SqlitePool::connect(&database_url)
+        .await
+        .expect("connect");
+    let rows: Vec<(String,)> = sqlx::query_as(
+        "SELECT payload_json FROM event_outbox WHERE event_type = ? ORDER BY occurred_at",
+    )
+    .bind(event_type)
+    .fetch_all(&pool)
+    .await
+    .expect("outbox rows");
+    pool.close().await;
+    rows.into_iter()
+        .map(|(payload,)| serde_json::from_str(&payload).expect("payload"))
+        .collect()
+}
+
+async fn post_form(
+    ctx: &TestContext,
+    endpoint: &str,
+    form: &[(&str, &str)],
+    basic: Option<(&str, &str)>,
+) -> axum::response::Response {
+    let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+    for (key, value) in form {
+        serializer.append_pair(key, value);
+    }
+
+    let mut builder = Request::builder()
+        .method("POST")
+        .uri(format!(
+            "/api/realms/{}/oidc/{}",
+            DEFAULT_REALM_NAME, endpoint
+        ))
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded");
+    if let Some((client_id, secret)) = basic {
+        let credentials = STANDARD.encode(format!("{}:{}", client_id, secret));
+        builder = builder.header(header::AUTHORIZATION, format!("Basic {}", credentials));
+    }
+
+    ctx.request(builder.body(Body::from(serializer.finish())).unwrap())
+        .await
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn introspect_reports_tokens_until_the_owning_client_revokes_them() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let api_secret = register_client(
+        &ctx,
+        realm.id,
+        "orders-api",
+        TokenEndpointAuthMethod::ClientSecretBasic,
+        None,
+    )
+    .await;
+    let app_secret = register_client(
+        &ctx,
+        realm.id,
+        "mobile-app",
+        TokenEndpointAuthMethod::ClientSecretPost,
+        None,
+    )
+    .await;
+
+    let user = ctx
+        .app_state
+        .user_service
+        .create_user(realm.id, "dave", "password-123", None, false)
+        .await
+        .expect("create user");
+    let (login, refresh_token) = ctx
+        .app_state
+        .auth_service
+        .create_session(&user, Some("mobile-app".to_string()), None, None)
+        .await
+        .expect("create session");
+    let refresh_token = refresh_token.id.to_string();
+
+    let response = post_form(
+        &ctx,
+        "introspect",
+        &[("token", &login.access_token)],
+        Some(("orders-api", &api_secret)),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = json_body(response).await;
+    assert_eq!(json["active"], true);
+    assert_eq!(json["sub"], user.id.to_string());
+    assert_eq!(json["client_id"], "mobile-app");
+    assert!(json["exp"].is_i64());
+
+    let response = post_form(
+        &ctx,
+        "introspect",
+        &[
+            ("token", &refresh_token),
+            ("token_type_hint", "refresh_token"),
+        ],
+        Some(("orders-api", &api_secret)),
+    )
+    .await;
+    let json = json_body(response).await;
+    assert_eq!(json["active"], true);
+    assert_eq!(json["token_type"], "refresh_token");
+
+    // Only the client the token was issued to may revoke it.
+    let response = post_form(
+        &ctx,
+        "revoke",
+        &[("token", &refresh_token)],
+        Some(("orders-api", &api_secret)),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(json_body(response).await["error"], "unauthorized_client");
+
+    let response = post_form(
+        &ctx,
+        "revoke",
+        &[
+            ("token", &refresh_token),
+            ("client_id", "mobile-app"),
+            ("client_secret", &app_secret),
+        ],
+        None,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    for token in [&login.access_token, &refresh_token] {
+        let response = post_form(
+            &ctx,
+            "introspect",
+            &[("token", token)],
+            Some(("orders-api", &api_secret)),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            json_body(response).await,
+            serde_json::json!({ "active": false })
+        );
+    }
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn revoking_a_refresh_token_ends_the_session_for_relying_parties() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let secret = register_client(
+        &ctx,
+        realm.id,
+        "web-app",
+        TokenEndpointAuthMethod::ClientSecretBasic,
+        Some("http://localhost/backchannel"),
+    )
+    .await;
+
+    let user = ctx
+        .app_state
+        .user_service
+        .create_user(realm.id, "frank", "password-123", None, false)
+        .await
+        .expect("create user");
+    let (_, session) = ctx
+        .app_state
+        .auth_service
+        .create_session(&user, Some("web-app".to_string()), None, None)
+        .await
+        .expect("create session");
+    assert!(outbox_payloads(&ctx, BACKCHANNEL_LOGOUT_EVENT_TYPE)
+        .await
+        .is_empty());
+
+    let response = post_form(
+        &ctx,
+        "revoke",
+        &[
+            ("token", &session.id.to_string()),
+            ("token_type_hint", "refresh_token"),
+        ],
+        Some(("web-app", &secret)),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let queued = outbox_payloads(&ctx, BACKCHANNEL_LOGOUT_EVENT_TYPE).await;
+    assert_eq!(queued.len(), 1);
+    assert_eq!(queued[0]["data"]["client_id"], "web-app");
+    assert_eq!(
+        queued[0]["data"]["backchannel_logout_uri"],
+        "http://localhost/backchannel"
+    );
+    assert_eq!(queued[0]["data"]["sub"], serde_json::json!(user.id));
+    assert_eq!(
+        queued[0]["data"]["sid"],
+        serde_json::json!(session.family_id)
+    );
+
+    let revoked = outbox_payloads(&ctx, "session.revoked").await;
+    assert_eq!(revoked.len(), 1);
+    assert_eq!(
+        revoked[0]["data"]["session_id"],
+        serde_json::json!(session.id)
+    );
+    assert_eq!(revoked[0]["data"]["reason"], "revoked");
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn introspect_and_revoke_require_confidential_clients() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let _ = register_client(&ctx, realm.id, "spa", TokenEndpointAuthMethod::None, None).await;
+
+    for endpoint in ["introspect", "revoke"] {
+        let response = post_form(
+            &ctx,
+            endpoint,
+            &[("token", "anything"), ("client_id", "spa")],
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{endpoint}");
+        assert_eq!(json_body(response).await["error"], "invalid_client");
+
+        let response = post_form(&ctx, endpoint, &[("token", "anything")], None).await;
+        assert_eq!(
+            response.status(),
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "{endpoint}"
+        );
+    }
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn discovery_advertises_introspection_and_revocation_endpoints() {
+    let ctx = TestContext::new().await;
+    let _realm = setup_realm(&ctx).await;
+
+    let response = ctx
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "/api/realms/{}/oidc/.well-known/openid-configuration",
+                    DEFAULT_REALM_NAME
+                ))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+    let json = json_body(response).await;
+
+    assert!(json["introspection_endpoint"]
+        .as_str()
+        .is_some_and(|url| url.ends_with("/oidc/introspect")));
+    assert!(json["revocation_endpoint"]
+        .as_str()
+        .is_some_and(|url| url.ends_with("/oidc/revoke")));
+    assert_eq!(
+        json["introspection_endpoint_auth_methods_supported"],
+        serde_json::json!([
+            "client_secret_basic",
+            "client_secret_post",
+            "private_key_jwt"
+        ])
+    );
+}
@@ -0,0 +1,399 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use http_body_util::BodyExt;
+use serial_test::serial;
+use uuid::Uuid;
+
+use reauth::application::password_policy_service::UpdatePasswordPolicyPayload;
+use reauth::application::rbac_service::CreateRolePayload;
+use reauth::application::realm_service::CreateRealmPayload;
+use reauth::bootstrap::app_state::SetupState;
+use reauth::constants::DEFAULT_REALM_NAME;
+use reauth::domain::permissions;
+use reauth::error::Error;
+
+use crate::support::TestContext;
+
+async fn json_body(response: axum::response::Response) -> serde_json::Value {
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("read body")
+        .to_bytes();
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+async fn setup_realm(ctx: &TestContext) -> reauth::domain::realm::Realm {
+    ctx.app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: DEFAULT_REALM_NAME.to_string(),
+        })
+        .await
+        .expect("create realm")
+}
+
+async fn setup_admin_token(ctx: &TestContext, realm_id: Uuid) -> String {
+    let user = ctx
+        .app_state
+        .user_service
+        .create_user(
+            realm_id,
+            "policy-admin",
+            "password",
+            Some("policy-admin@example.com"),
+            false,
+        )
+        .await
+        .expect("create admin");
+
+    let role = ctx
+        .app_state
+        .rbac_service
+        .create_role(
+            realm_id,
+            CreateRolePayload {
+                name: "policy-admin".to_string(),
+                description: Some("Password policy admin".to_string()),
+                client_id: None,
+            },
+        )
+        .await
+        .expect("create role");
+
+    for permission in [
+        permissions::REALM_READ,
+        permissions::REALM_WRITE,
+        permissions::USER_WRITE,
+    ] {
+        ctx.app_state
+            .rbac_service
+            .assign_permission_to_role(realm_id, role.id, permission.to_string())
+            .await
+            .expect("assign permission");
+    }
+
+    ctx.app_state
+        .rbac_service
+        .assign_role_to_user(realm_id, user.id, role.id)
+        .await
+        .expect("assign role");
+
+    let (login, _) = ctx
+        .app_state
+        .auth_service
+        .create_session(&user, None, None, None)
+        .await
+        .expect("create session");
+    login.access_token
+}
+
+fn violation_codes(body: &serde_json::Value) -> Vec<String> {
+    body.get("violations")
+        .and_then(|value| value.as_array())
+        .map(|violations| {
+            violations
+                .iter()
+                .filter_map(|violation| violation.get("code").and_then(|code| code.as_str()))
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn realm_password_policy_can_be_read_and_updated() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let token = setup_admin_token(&ctx, realm.id).await;
+
+    let get_req = Request::builder()
+        .method("GET")
+        .uri(format!("/api/realms/{}/password-policy", realm.id))
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::empty())
+        .expect("get request");
+    let get_res = ctx.request(get_req).await;
+    assert_eq!(get_res.status(), StatusCode::OK);
+    let defaults = json_body(get_res).await;
+    assert_eq!(defaults["min_length"], 8);
+    assert_eq!(defaults["max_length"], 100);
+    assert_eq!(defaults["history_count"], 0);
+
+    let update_req = Request::builder()
+        .method("PUT")
+        .uri(format!("/api/realms/{}/password-policy", realm.id))
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            serde_json::json!({
+                "min_length": 12,
+                "require_digit": true,
+                "history_count": 3,
+            })
+            .to_string(),
+        ))
+        .expect("update request");
+    let update_res = ctx.request(update_req).await;
+    assert_eq!(update_res.status(), StatusCode::OK);
+    let updated = json_body(update_res).await;
+    assert_eq!(updated["min_length"], 12);
+    assert_eq!(updated["require_digit"], true);
+    assert_eq!(updated["history_count"], 3);
+    assert_eq!(updated["max_length"], 100);
+
+    let invalid_req = Request::builder()
+        .method("PUT")
+        .uri(format!("/api/realms/{}/password-policy", realm.id))
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            serde_json::json!({ "max_length": 4 }).to_string(),
+        ))
+        .expect("invalid request");
+    let invalid_res = ctx.request(invalid_req).await;
+    assert_eq!(invalid_res.status(), StatusCode::BAD_REQUEST);
+
+    // Enabling the breach check needs a configured list.
+    let breached_req = Request::builder()
+        .method("PUT")
+        .uri(format!("/api/realms/{}/password-policy", realm.id))
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            serde_json::json!({ "check_breached": true }).to_string(),
+        ))
+        .expect("breached request");
+    let breached_res = ctx.request(breached_req).await;
+    assert_eq!(breached_res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn create_user_reports_policy_violations() {
+    let ctx = TestContext::new().await;
+    *ctx.app_state.setup_state.write().await = SetupState::sealed();
+    let realm = setup_realm(&ctx).await;
+    let token = setup_admin_token(&ctx, realm.id).await;
+
+    ctx.app_state
+        .password_policy_service
+        .update_policy(
+            realm.id,
+            UpdatePasswordPolicyPayload {
+                min_length: Some(10),
+                require_uppercase: Some(true),
+                require_digit: Some(true),
+                disallow_username: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("update policy");
+
+    let create = |username: &str, password: &str, ignore: bool| {
+        Request::builder()
+            .method("POST")
+            .uri(format!("/api/realms/{}/users", DEFAULT_REALM_NAME))
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "username": username,
+                    "password": password,
+                    "ignore_password_policies": ignore,
+                })
+                .to_string(),
+            ))
+            .expect("create request")
+    };
+
+    let rejected = ctx
+        .request(create("marigold", "marigold-garden", false))
+        .await;
+    assert_eq!(rejected.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body = json_body(rejected).await;
+    assert_eq!(body["code"], "password.policy_violation");
+    assert_eq!(
+        violation_codes(&body),
+        vec!["missing_uppercase", "missing_digit", "contains_username"]
+    );
+    assert!(body["fields"]["password"]
+        .as_str()
+        .is_some_and(|message| message.contains("uppercase")));
+
+    let accepted = ctx
+        .request(create("marigold", "Sunflower-Field-42", false))
+        .await;
+    assert_eq!(accepted.status(), StatusCode::CREATED);
+
+    // Admins can still bypass the policy explicitly.
+    let bypassed = ctx.request(create("rosemary", "short", true)).await;
+    assert_eq!(bypassed.status(), StatusCode::CREATED);
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn admin_password_update_rejects_recent_passwords() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let token = setup_admin_token(&ctx, realm.id).await;
+
+    ctx.app_state
+        .password_policy_service
+        .update_policy(
+            realm.id,
+            UpdatePasswordPolicyPayload {
+                history_count: Some(2),
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("update policy");
+
+    let user = ctx
+        .app_state
+        .user_service
+        .create_user(realm.id, "history-user", "first-password", None, false)
+        .await
+        .expect("create user");
+    ctx.app_state
+        .user_service
+        .update_password(realm.id, user.id, "second-password", false)
+        .await
+        .expect("second password");
+
+    let update = |password: &str| {
+        Request::builder()
+            .method("PUT")
+            .uri(format!(
+                "/api/realms/{}/users/{}/credentials/password",
+                DEFAULT_REALM_NAME, user.id
+            ))
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "password": password,
+                    "skip_password_checks": false,
+                    "sign_out_all_sessions": false,
+                })
+                .to_string(),
+            ))
+            .expect("update request")
+    };
+
+    for reused in ["first-password", "second-password"] {
+        let res = ctx.request(update(reused)).await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            violation_codes(&json_body(res).await),
+            vec!["recently_used"]
+        );
+    }
+
+    let res = ctx.request(update("third-password")).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // Only the last two passwords are remembered.
+    let res = ctx.request(update("first-password")).await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn stale_password_forces_reset_when_max_age_is_set() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+
+    let mut user = ctx
+        .app_state
+        .user_service
+        .create_user(realm.id, "aging-user", "aging-password", None, false)
+        .await
+        .expect("create user");
+
+    // Without a max age nothing expires.
+    assert!(!ctx
+        .app_state
+        .user_service
+        .expire_stale_password(&mut user)
+        .await
+        .expect("check expiry"));
+
+    ctx.app_state
+        .password_policy_service
+        .update_policy(
+            realm.id,
+            UpdatePasswordPolicyPayload {
+                max_age_days: Some(30),
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("update policy");
+    assert!(!ctx
+        .app_state
+        .user_service
+        .expire_stale_password(&mut user)
+        .await
+        .expect("check fresh password"));
+
+    let database_url = ctx.app_state.settings.read().await.database.url.clone();
+    let pool = sqlx::SqlitePool::connect(&database_url)
+        .await
+        .expect("connect");
+    sqlx::query(
+        "UPDATE password_history SET created_at = datetime('now', '-31 days') WHERE user_id = ?",
+    )
+    .bind(user.id.to_string())
+    .execute(&pool)
+    .await
+    .expect("backdate history");
+    pool.close().await;
+
+    assert!(ctx
+        .app_state
+        .user_service
+        .expire_stale_password(&mut user)
+        .await
+        .expect("check stale password"));
+    let stored = ctx
+        .app_state
+        .user_service
+        .get_user_in_realm(realm.id, user.id)
+        .await
+        .expect("stored user");
+    assert!(stored.force_password_reset);
+
+    // The forced reset still runs through the policy, and a new password
+    // clears the flag and restarts the clock.
+    let err = ctx
+        .app_state
+        .user_service
+        .update_password(realm.id, user.id, "short", false)
+        .await
+        .expect_err("policy still applies");
+    assert!(matches!(err, Error::PasswordPolicy(_)));
+
+    ctx.app_state
+        .user_service
+        .update_password(realm.id, user.id, "renewed-password", false)
+        .await
+        .expect("renew password");
+    let mut renewed = ctx
+        .app_state
+        .user_service
+        .get_user_in_realm(realm.id, user.id)
+        .await
+        .expect("renewed user");
+    assert!(!renewed.force_password_reset);
+    assert!(!ctx
+        .app_state
+        .user_service
+        .expire_stale_password(&mut renewed)
+        .await
+        .expect("check renewed password"));
+}
@@ -0,0 +1,492 @@
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{header, HeaderMap, Request, StatusCode};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{Duration, Utc};
+use http_body_util::BodyExt;
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use serial_test::serial;
+use std::net::{Ipv4Addr, SocketAddr};
+use url::Url;
+use uuid::Uuid;
+
+use reauth::application::flow_manager::UpdateDraftRequest;
+use reauth::application::idp_service::CreateIdentityProviderRequest;
+use reauth::application::realm_service::{CreateRealmPayload, UpdateRealmPayload};
+use reauth::bootstrap::app_state::SetupState;
+use reauth::constants::{DEFAULT_REALM_NAME, LOGIN_SESSION_COOKIE, REFRESH_TOKEN_COOKIE};
+use reauth::domain::identity_provider::IdentityProviderProtocol;
+use reauth::domain::realm::Realm;
+use reauth::domain::saml::{
+    self, AssertionAttribute, AssertionParams, AuthnRequest, NameIdFormat, RSA_SHA256,
+};
+
+use crate::support::TestContext;
+
+const IDP_ENTITY_ID: &str = "https://idp.example/saml";
+const IDP_SSO_URL: &str = "https://idp.example/saml/sso";
+const SP_ENTITY_ID: &str = "https://reauth.example/sp";
+
+struct UpstreamIdp {
+    key: RsaPrivateKey,
+    certificate: Vec<u8>,
+}
+
+impl UpstreamIdp {
+    fn new() -> Self {
+        let key = RsaPrivateKey::new(&mut rand::rng(), 2048).expect("rsa key");
+        let now = Utc::now();
+        let certificate = saml::self_signed_certificate(
+            &key,
+            "idp.example",
+            &[7],
+            now - Duration::days(1),
+            now + Duration::days(30),
+        )
+        .expect("certificate");
+        Self { key, certificate }
+    }
+
+    fn metadata(&self) -> String {
+        format!(
+            r#"<md:EntityDescriptor xmlns:md="urn:oasis:names:tc:SAML:2.0:metadata" xmlns:ds="http://www.w3.org/2000/09/xmldsig#" entityID="{}">
+  <md:IDPSSODescriptor protocolSupportEnumeration="urn:oasis:names:tc:SAML:2.0:protocol">
+    <md:KeyDescriptor use="signing"><ds:KeyInfo><ds:X509Data><ds:X509Certificate>{}</ds:X509Certificate></ds:X509Data></ds:KeyInfo></md:KeyDescriptor>
+    <md:SingleSignOnService Binding="urn:oasis:names:tc:SAML:2.0:bindings:HTTP-Redirect" Location="{}"/>
+  </md:IDPSSODescriptor>
+</md:EntityDescriptor>"#,
+            IDP_ENTITY_ID,
+            STANDARD.encode(&self.certificate),
+            IDP_SSO_URL
+        )
+    }
+
+    /// A base64 `SAMLResponse` answering `request` with a signed assertion.
+    fn response(&self, request: &AuthnRequest, audience: &str) -> String {
+        let acs_url = request
+            .assertion_consumer_service_url
+            .as_deref()
+            .expect("acs url");
+        let attributes = [
+            AssertionAttribute {
+                name: "mail".to_string(),
+                friendly_name: None,
+                values: vec!["river@example.com".to_string()],
+            },
+            AssertionAttribute {
+                name: "uid".to_string(),
+                friendly_name: None,
+                values: vec!["river".to_string()],
+            },
+        ];
+        let assertion = saml::build_assertion(
+            &saml::new_message_id(),
+            &AssertionParams {
+                issuer: IDP_ENTITY_ID,
+                audience,
+                recipient: acs_url,
+                in_response_to: Some(&request.id),
+                name_id: "upstream-river",
+                name_id_format: NameIdFormat::Persistent.uri(),
+                session_index: "idp-session-1",
+                attributes: &attributes,
+                issued_at: Utc::now(),
+                lifetime: Duration::minutes(5),
+            },
+        );
+        let assertion =
+            saml::sign_enveloped(&assertion, "</saml:Issuer>", &self.key, &self.certificate)
+                .expect("sign assertion");
+        let response = saml::build_response(
+            &saml::new_message_id(),
+            IDP_ENTITY_ID,
+            acs_url,
+            Some(&request.id),
+            Utc::now(),
+            saml::STATUS_SUCCESS,
+            Some(&assertion),
+        );
+        saml::encode_post_message(&response)
+    }
+}
+
+fn cookie_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .filter_map(|value| value.split(';').next())
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, value)| key.trim() == name && !value.trim().is_empty())
+        .map(|(_, value)| value.trim().to_string())
+}
+
+fn location(response: &axum::response::Response) -> String {
+    response
+        .headers()
+        .get(header::LOCATION)
+        .and_then(|value| value.to_str().ok())
+        .expect("location")
+        .to_string()
+}
+
+async fn json_body(response: axum::response::Response) -> serde_json::Value {
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("read body")
+        .to_bytes();
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+fn with_login_cookie(uri: &str, session_id: &str) -> Request<Body> {
+    Request::builder()
+        .uri(uri)
+        .header(
+            header::COOKIE,
+            format!("{}={}", LOGIN_SESSION_COOKIE, session_id),
+        )
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn acs_post(saml_response: &str, relay_state: &str) -> Request<Body> {
+    let body = url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("SAMLResponse", saml_response)
+        .append_pair("RelayState", relay_state)
+        .finish();
+    Request::builder()
+        .method("POST")
+        .uri(format!(
+            "/api/realms/{}/auth/saml/corp/acs",
+            DEFAULT_REALM_NAME
+        ))
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+async fn setup_realm(ctx: &TestContext) -> Realm {
+    let realm = ctx
+        .app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: DEFAULT_REALM_NAME.to_string(),
+        })
+        .await
+        .expect("create realm");
+    *ctx.app_state.setup_state.write().await = SetupState::sealed();
+
+    ctx.app_state
+        .realm_service
+        .update_realm(
+            realm.id,
+            UpdateRealmPayload {
+                name: None,
+                access_token_ttl_secs: None,
+                refresh_token_ttl_secs: None,
+                pkce_required_public_clients: None,
+                signing_algorithm: None,
+                lockout_threshold: None,
+                lockout_duration_secs: None,
+                registration_enabled: None,
+                default_registration_role_ids: None,
+                invitation_resend_limit: None,
+                idp_broker_enabled: Some(true),
+                idp_default_jit_policy: None,
+                idp_default_email_link_policy: None,
+                idp_minimum_remaining_factor: None,
+                browser_flow_id: None,
+                registration_flow_id: None,
+                direct_grant_flow_id: None,
+                reset_credentials_flow_id: None,
+                invitation_flow_id: None,
+            },
+        )
+        .await
+        .expect("enable identity brokering");
+    realm
+}
+
+async fn publish_idp_browser_flow(ctx: &TestContext, realm: &Realm) {
+    let flow_id = realm
+        .browser_flow_id
+        .as_ref()
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .expect("browser flow id");
+    let graph = serde_json::json!({
+        "nodes": [
+            { "id": "start", "type": "core.start", "data": { "config": {} } },
+            { "id": "auth-idp", "type": "core.auth.oauth_idp", "data": { "config": { "auth_type": "core.auth.oauth_idp" } } },
+            { "id": "allow", "type": "core.terminal.allow", "data": { "config": {} } },
+            { "id": "deny", "type": "core.terminal.deny", "data": { "config": {} } }
+        ],
+        "edges": [
+            { "id": "e-start-idp", "source": "start", "target": "auth-idp", "sourceHandle": "next" },
+            { "id": "e-idp-allow", "source": "auth-idp", "target": "allow", "sourceHandle": "logged_in" },
+            { "id": "e-idp-jit", "source": "auth-idp", "target": "allow", "sourceHandle": "jit_provisioned" },
+            { "id": "e-idp-deny", "source": "auth-idp", "target": "deny", "sourceHandle": "failed" }
+        ]
+    });
+
+    ctx.app_state
+        .flow_manager
+        .update_draft(
+            flow_id,
+            UpdateDraftRequest {
+                name: None,
+                description: None,
+                graph_json: Some(graph),
+            },
+        )
+        .await
+        .expect("update draft");
+    ctx.app_state
+        .flow_manager
+        .publish_flow(realm.id, flow_id)
+        .await
+        .expect("publish flow");
+}
+
+async fn create_saml_provider(ctx: &TestContext, realm: &Realm, upstream: &UpstreamIdp) {
+    ctx.app_state
+        .identity_provider_service
+        .create(
+            realm.id,
+            CreateIdentityProviderRequest {
+                preset: None,
+                alias: "corp".to_string(),
+                display_name: "Corp SSO".to_string(),
+                protocol: IdentityProviderProtocol::Saml,
+                client_id: SP_ENTITY_ID.to_string(),
+                client_secret: None,
+                issuer: None,
+                authorization_endpoint: None,
+                token_endpoint: None,
+                userinfo_endpoint: None,
+                jwks_uri: None,
+                scopes: None,
+                claim_mapping: Some(serde_json::json!({ "email_verified": true })),
+                pkce_required: None,
+                allow_login: Some(true),
+                allow_link: Some(true),
+                allow_jit_provisioning: Some(true),
+                allow_email_auto_link: Some(false),
+                require_verified_email: Some(true),
+                icon_ref: None,
+                button_color: None,
+                sort_order: None,
+                enabled: Some(true),
+                saml_metadata_url: None,
+                saml_metadata_xml: Some(upstream.metadata()),
+                saml_signing_certificates: None,
+                saml_name_id_format: Some(NameIdFormat::Persistent),
+            },
+        )
+        .await
+        .expect("create saml identity provider");
+}
+
+/// Starts a brokered login and returns the login session cookie, the
+/// RelayState and the AuthnRequest sent to the identity provider.
+async fn start_login(ctx: &TestContext, realm: &Realm) -> (String, String, AuthnRequest) {
+    let mut login_request = Request::builder()
+        .uri(format!("/api/realms/{}/auth/login", DEFAULT_REALM_NAME))
+        .body(Body::empty())
+        .unwrap();
+    login_request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 3000))));
+    let login_response = ctx.request(login_request).await;
+    let session_id =
+        cookie_value(login_response.headers(), LOGIN_SESSION_COOKIE).expect("login cookie");
+
+    let mut start_request = with_login_cookie(
+        &format!("/api/realms/{}/auth/oauth/corp/start", DEFAULT_REALM_NAME),
+        &session_id,
+    );
+    start_request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 3000))));
+    let start_response = ctx.request(start_request).await;
+    assert_eq!(start_response.status(), StatusCode::OK);
+    let redirect_url = json_body(start_response).await["redirect_url"]
+        .as_str()
+        .expect("redirect url")
+        .to_string();
+    assert!(redirect_url.starts_with(IDP_SSO_URL));
+
+    // The AuthnRequest is signed with the realm's SAML key.
+    let query = redirect_url
+        .split_once('?')
+        .map(|(_, query)| query)
+        .expect("query");
+    let (signed_part, signature) = query.rsplit_once("&Signature=").expect("signature");
+    let certificates = ctx
+        .app_state
+        .signing_key_service
+        .saml_certificates(realm.id)
+        .await
+        .expect("realm certificates");
+    let keys: Vec<RsaPublicKey> = certificates
+        .iter()
+        .map(|certificate| saml::certificate_public_key(certificate).expect("public key"))
+        .collect();
+    saml::verify_query(
+        signed_part,
+        &urlencoding::decode(signature).expect("signature"),
+        &keys,
+    )
+    .expect("authn request signature");
+
+    let url = Url::parse(&redirect_url).expect("redirect url");
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.to_string())
+            .expect("query parameter")
+    };
+    assert_eq!(param("SigAlg"), RSA_SHA256);
+    let request = AuthnRequest::parse(
+        &saml::decode_redirect_message(&param("SAMLRequest")).expect("decode request"),
+    )
+    .expect("authn request");
+    assert_eq!(request.issuer, SP_ENTITY_ID);
+
+    (session_id, param("RelayState"), request)
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn saml_provider_metadata_describes_the_service_provider() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let upstream = UpstreamIdp::new();
+    create_saml_provider(&ctx, &realm, &upstream).await;
+
+    let response = ctx
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "/api/realms/{}/auth/saml/corp/metadata",
+                    DEFAULT_REALM_NAME
+                ))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok()),
+        Some("application/samlmetadata+xml")
+    );
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("read body")
+        .to_bytes();
+    let metadata = String::from_utf8(bytes.to_vec()).expect("utf-8 body");
+    assert!(metadata.contains(&format!(r#"entityID="{}""#, SP_ENTITY_ID)));
+    assert!(metadata.contains("/api/realms/master/auth/saml/corp/acs"));
+
+    let provider = ctx
+        .app_state
+        .identity_provider_service
+        .get_domain_by_alias(realm.id, "corp")
+        .await
+        .expect("provider");
+    assert_eq!(provider.issuer.as_deref(), Some(IDP_ENTITY_ID));
+    assert_eq!(
+        provider.authorization_endpoint.as_deref(),
+        Some(IDP_SSO_URL)
+    );
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn saml_acs_provisions_user_and_rejects_replay() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let upstream = UpstreamIdp::new();
+    create_saml_provider(&ctx, &realm, &upstream).await;
+    publish_idp_browser_flow(&ctx, &realm).await;
+
+    let (session_id, relay_state, request) = start_login(&ctx, &realm).await;
+    let saml_response = upstream.response(&request, SP_ENTITY_ID);
+
+    // The identity provider posts cross-site, without the login cookie.
+    let acs_response = ctx.request(acs_post(&saml_response, &relay_state)).await;
+    assert_eq!(acs_response.status(), StatusCode::SEE_OTHER);
+    let continue_url = location(&acs_response);
+    assert_eq!(
+        continue_url,
+        format!("/api/realms/{}/auth/saml/corp/continue", DEFAULT_REALM_NAME)
+    );
+
+    let replay = ctx.request(acs_post(&saml_response, &relay_state)).await;
+    assert_eq!(replay.status(), StatusCode::FOUND);
+    assert!(location(&replay).contains("oauth_error="));
+
+    // Another browser cannot pick up the parked result.
+    let stranger = ctx
+        .request(with_login_cookie(
+            &continue_url,
+            &Uuid::new_v4().to_string(),
+        ))
+        .await;
+    assert!(location(&stranger).contains("oauth_error="));
+
+    let continue_response = ctx
+        .request(with_login_cookie(&continue_url, &session_id))
+        .await;
+    assert_eq!(continue_response.status(), StatusCode::FOUND);
+    assert_eq!(location(&continue_response), "/");
+    assert!(cookie_value(continue_response.headers(), REFRESH_TOKEN_COOKIE).is_some());
+
+    let user = ctx
+        .app_state
+        .user_service
+        .find_by_email(&realm.id, "river@example.com")
+        .await
+        .expect("find user")
+        .expect("jit provisioned user");
+    assert_eq!(user.username, "river");
+    let credentials = ctx
+        .app_state
+        .user_credentials_service
+        .list_credentials(realm.id, user.id)
+        .await
+        .expect("list credentials");
+    assert_eq!(credentials.federated_identities.len(), 1);
+    assert_eq!(credentials.federated_identities[0].provider_alias, "corp");
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn saml_acs_rejects_assertions_for_another_audience() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let upstream = UpstreamIdp::new();
+    create_saml_provider(&ctx, &realm, &upstream).await;
+    publish_idp_browser_flow(&ctx, &realm).await;
+
+    let (_, relay_state, request) = start_login(&ctx, &realm).await;
+    let saml_response = upstream.response(&request, "https://other.example/sp");
+
+    let acs_response = ctx.request(acs_post(&saml_response, &relay_state)).await;
+    assert_eq!(acs_response.status(), StatusCode::FOUND);
+    assert!(location(&acs_response).contains("oauth_error="));
+    assert!(ctx
+        .app_state
+        .user_service
+        .find_by_email(&realm.id, "river@example.com")
+        .await
+        .expect("find user")
+        .is_none());
+}
@@ -0,0 +1,674 @@
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{header, HeaderMap, Request, StatusCode};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{Duration, Utc};
+use http_body_util::BodyExt;
+use rsa::RsaPrivateKey;
+use serial_test::serial;
+use std::net::{Ipv4Addr, SocketAddr};
+use url::Url;
+use uuid::Uuid;
+
+use reauth::application::flow_manager::UpdateDraftRequest;
+use reauth::application::realm_service::CreateRealmPayload;
+use reauth::application::saml_service::CreateSamlServiceProviderRequest;
+use reauth::bootstrap::app_state::SetupState;
+use reauth::constants::{DEFAULT_REALM_NAME, LOGIN_SESSION_COOKIE, REFRESH_TOKEN_COOKIE};
+use reauth::domain::realm::Realm;
+use reauth::domain::saml::xml::XmlElement;
+use reauth::domain::saml::{
+    self, NameIdFormat, SamlAttributeMapping, SamlAttributeSource, SamlBinding,
+    SamlServiceProvider, ASSERTION_NS, METADATA_NS,
+};
+use reauth::domain::user::User;
+
+use crate::support::TestContext;
+
+const SP_ENTITY_ID: &str = "https://wiki.example/saml";
+const SP_ACS_URL: &str = "https://wiki.example/saml/acs";
+const SP_SLO_URL: &str = "https://wiki.example/saml/slo";
+
+async fn text_body(response: axum::response::Response) -> String {
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("read body")
+        .to_bytes();
+    String::from_utf8(bytes.to_vec()).expect("utf-8 body")
+}
+
+fn cookie_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .filter_map(|value| value.split(';').next())
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, value)| key.trim() == name && !value.trim().is_empty())
+        .map(|(_, value)| value.trim().to_string())
+}
+
+/// The value of a hidden input in an auto-submitting HTTP-POST form.
+fn form_input(html: &str, name: &str) -> Option<String> {
+    let marker = format!(r#"name="{}" value=""#, name);
+    let start = html.find(&marker)? + marker.len();
+    let end = html[start..].find('"')?;
+    Some(html[start..start + end].to_string())
+}
+
+fn get(uri: &str) -> Request<Body> {
+    Request::builder().uri(uri).body(Body::empty()).unwrap()
+}
+
+async fn setup_realm(ctx: &TestContext) -> Realm {
+    let realm = ctx
+        .app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: DEFAULT_REALM_NAME.to_string(),
+        })
+        .await
+        .expect("create realm");
+    *ctx.app_state.setup_state.write().await = SetupState::sealed();
+    realm
+}
+
+async fn setup_user(ctx: &TestContext, realm_id: Uuid) -> User {
+    ctx.app_state
+        .user_service
+        .create_user(realm_id, "harper", "password-123", None, false)
+        .await
+        .expect("create user")
+}
+
+async fn publish_password_browser_flow(ctx: &TestContext, realm: &Realm) {
+    let flow_id = realm
+        .browser_flow_id
+        .as_ref()
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .expect("browser flow id");
+    let graph = serde_json::json!({
+        "nodes": [
+            { "id": "start", "type": "core.start", "data": { "config": {} } },
+            { "id": "auth-password", "type": "core.auth.password", "data": { "config": { "auth_type": "core.auth.password" } } },
+            { "id": "allow", "type": "core.terminal.allow", "data": { "config": {} } }
+        ],
+        "edges": [
+            { "id": "e-start-password", "source": "start", "target": "auth-password", "sourceHandle": "next" },
+            { "id": "e-password-allow", "source": "auth-password", "target": "allow", "sourceHandle": "success" }
+        ]
+    });
+
+    ctx.app_state
+        .flow_manager
+        .update_draft(
+            flow_id,
+            UpdateDraftRequest {
+                name: None,
+                description: None,
+                graph_json: Some(graph),
+            },
+        )
+        .await
+        .expect("update draft");
+    ctx.app_state
+        .flow_manager
+        .publish_flow(realm.id, flow_id)
+        .await
+        .expect("publish flow");
+}
+
+/// The service provider's request signing key and certificate.
+struct SpSigner {
+    key: RsaPrivateKey,
+    certificate: Vec<u8>,
+}
+
+impl SpSigner {
+    fn new() -> Self {
+        let key = RsaPrivateKey::new(&mut rand::rng(), 2048).expect("rsa key");
+        let now = Utc::now();
+        let certificate = saml::self_signed_certificate(
+            &key,
+            "wiki.example",
+            &[9],
+            now - Duration::days(1),
+            now + Duration::days(30),
+        )
+        .expect("certificate");
+        Self { key, certificate }
+    }
+
+    /// Appends `SigAlg` and `Signature` to an HTTP-Redirect query.
+    fn sign_query(&self, query: &str) -> String {
+        let query = format!("{}&SigAlg={}", query, urlencoding::encode(saml::RSA_SHA256));
+        let signature = saml::sign_query(&query, &self.key).expect("sign query");
+        format!("{}&Signature={}", query, urlencoding::encode(&signature))
+    }
+
+    fn sign_enveloped(&self, xml: &str) -> String {
+        saml::sign_enveloped(xml, "</saml:Issuer>", &self.key, &self.certificate)
+            .expect("sign message")
+    }
+}
+
+/// Registers the wiki. With a `signer` its certificate is registered and
+/// AuthnRequests must be signed.
+async fn register_service_provider(
+    ctx: &TestContext,
+    realm_id: Uuid,
+    allow_idp_initiated: bool,
+    signer: Option<&SpSigner>,
+) -> SamlServiceProvider {
+    ctx.app_state
+        .saml_service
+        .create(
+            realm_id,
+            CreateSamlServiceProviderRequest {
+                entity_id: SP_ENTITY_ID.to_string(),
+                name: Some("Wiki".to_string()),
+                acs_urls: vec![SP_ACS_URL.to_string()],
+                slo_url: Some(SP_SLO_URL.to_string()),
+                slo_binding: Some(SamlBinding::Redirect),
+                name_id_format: Some(NameIdFormat::Persistent),
+                attribute_mappings: Some(vec![SamlAttributeMapping {
+                    name: "uid".to_string(),
+                    friendly_name: None,
+                    source: SamlAttributeSource::Username,
+                }]),
+                sign_response: Some(true),
+                signing_certificates: signer
+                    .map(|signer| vec![STANDARD.encode(&signer.certificate)]),
+                require_signed_requests: Some(signer.is_some()),
+                allow_idp_initiated: Some(allow_idp_initiated),
+                enabled: None,
+            },
+        )
+        .await
+        .expect("register service provider")
+}
+
+fn authn_request(id: &str) -> String {
+    format!(
+        r#"<samlp:AuthnRequest xmlns:samlp="urn:oasis:names:tc:SAML:2.0:protocol" xmlns:saml="urn:oasis:names:tc:SAML:2.0:assertion" ID="{}" Version="2.0" IssueInstant="2026-01-01T00:00:00Z" AssertionConsumerServiceURL="{}" ProtocolBinding="{}"><saml:Issuer>{}</saml:Issuer></samlp:AuthnRequest>"#,
+        id,
+        SP_ACS_URL,
+        saml::BINDING_POST,
+        SP_ENTITY_ID
+    )
+}
+
+fn redirect_query(param: &str, xml: &str, relay_state: Option<&str>) -> String {
+    let encoded = saml::encode_redirect_message(xml).expect("deflate");
+    let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+    serializer.append_pair(param, &encoded);
+    if let Some(relay_state) = relay_state {
+        serializer.append_pair("RelayState", relay_state);
+    }
+    serializer.finish()
+}
+
+/// Signs in through the login UI endpoints with the flow the SSO endpoint
+/// started, and follows the flow's redirect to the HTTP-POST form.
+async fn sign_in(ctx: &TestContext, session_id: &str) -> String {
+    let login_cookie = format!("{}={}", LOGIN_SESSION_COOKIE, session_id);
+    let mut start = Request::builder()
+        .uri(format!("/api/realms/{}/auth/login", DEFAULT_REALM_NAME))
+        .header(header::COOKIE, login_cookie.clone())
+        .body(Body::empty())
+        .unwrap();
+    start
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 3000))));
+    let response = ctx.request(start).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let mut execute = Request::builder()
+        .method("POST")
+        .uri(format!(
+            "/api/realms/{}/auth/login/execute",
+            DEFAULT_REALM_NAME
+        ))
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::COOKIE, login_cookie)
+        .body(Body::from(
+            serde_json::json!({ "username": "harper", "password": "password-123" }).to_string(),
+        ))
+        .unwrap();
+    execute
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 3000))));
+    let response = ctx.request(execute).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_str(&text_body(response).await).expect("json");
+    assert_eq!(json["status"], "redirect");
+    json["url"].as_str().expect("redirect url").to_string()
+}
+
+async fn idp_certificate(ctx: &TestContext) -> rsa::RsaPublicKey {
+    let response = ctx
+        .request(
+            Request::builder()
+                .uri(format!("/api/realms/{}/saml/metadata", DEFAULT_REALM_NAME))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/samlmetadata+xml"
+    );
+    let metadata = XmlElement::parse(&text_body(response).await).expect("metadata xml");
+    assert!(metadata.is(METADATA_NS, "EntityDescriptor"));
+    let certificate = metadata
+        .child(METADATA_NS, "IDPSSODescriptor")
+        .and_then(|descriptor| descriptor.child(METADATA_NS, "KeyDescriptor"))
+        .and_then(|key| key.elements().next())
+        .and_then(|key_info| key_info.elements().next())
+        .and_then(|data| data.elements().next())
+        .map(|certificate| certificate.text())
+        .expect("signing certificate");
+    saml::certificate_public_key(&saml::decode_certificate(&certificate).expect("certificate"))
+        .expect("certificate key")
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn sp_initiated_login_posts_a_signed_assertion_once() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    publish_password_browser_flow(&ctx, &realm).await;
+    let user = setup_user(&ctx, realm.id).await;
+    register_service_provider(&ctx, realm.id, false, None).await;
+    let public_key = idp_certificate(&ctx).await;
+
+    let response = ctx
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "/api/realms/{}/saml/sso?{}",
+                    DEFAULT_REALM_NAME,
+                    redirect_query("SAMLRequest", &authn_request("_req-1"), Some("page-7"))
+                ))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(
+        response.headers().get(header::LOCATION).unwrap(),
+        "/#/login?realm=master"
+    );
+    let session_id =
+        cookie_value(response.headers(), LOGIN_SESSION_COOKIE).expect("login session cookie");
+
+    let url = sign_in(&ctx, &session_id).await;
+    assert!(url.starts_with("/api/realms/master/saml/sso/complete?session="));
+
+    let response = ctx.request(get(&url)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let html = text_body(response).await;
+    assert!(html.contains(&format!(r#"action="{}""#, SP_ACS_URL)));
+    assert_eq!(form_input(&html, "RelayState").as_deref(), Some("page-7"));
+
+    let xml = saml::decode_post_message(&form_input(&html, "SAMLResponse").expect("response"))
+        .expect("response xml");
+    let response_xml = XmlElement::parse(&xml).expect("response");
+    assert_eq!(response_xml.attribute("InResponseTo"), Some("_req-1"));
+    assert_eq!(response_xml.attribute("Destination"), Some(SP_ACS_URL));
+    saml::verify_enveloped(&response_xml, std::slice::from_ref(&public_key))
+        .expect("response signature");
+    let assertion = response_xml
+        .child(ASSERTION_NS, "Assertion")
+        .expect("assertion");
+    saml::verify_enveloped(assertion, std::slice::from_ref(&public_key))
+        .expect("assertion signature");
+
+    let name_id = assertion
+        .child(ASSERTION_NS, "Subject")
+        .and_then(|subject| subject.child(ASSERTION_NS, "NameID"))
+        .expect("name id");
+    assert_eq!(name_id.text(), user.id.to_string());
+    assert_eq!(
+        name_id.attribute("Format"),
+        Some(NameIdFormat::Persistent.uri())
+    );
+    assert!(xml.contains(&format!("<saml:Audience>{}</saml:Audience>", SP_ENTITY_ID)));
+    assert!(xml.contains(r#"Name="uid""#));
+    assert!(xml.contains("<saml:AttributeValue>harper</saml:AttributeValue>"));
+
+    // The prepared response is handed out exactly once.
+    let response = ctx.request(get(&url)).await;
+    assert_ne!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn sso_rejects_unknown_providers_and_unregistered_acs_urls() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    publish_password_browser_flow(&ctx, &realm).await;
+    register_service_provider(&ctx, realm.id, false, None).await;
+
+    let unknown = authn_request("_req-2").replace(SP_ENTITY_ID, "https://other.example");
+    let foreign_acs = authn_request("_req-3").replace(SP_ACS_URL, "https://evil.example/acs");
+    for xml in [unknown, foreign_acs] {
+        let response = ctx
+            .request(
+                Request::builder()
+                    .uri(format!(
+                        "/api/realms/{}/saml/sso?{}",
+                        DEFAULT_REALM_NAME,
+                        redirect_query("SAMLRequest", &xml, None)
+                    ))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert!(cookie_value(response.headers(), LOGIN_SESSION_COOKIE).is_none());
+    }
+
+    // IdP-initiated login is opt-in per service provider.
+    let response = ctx
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "/api/realms/{}/saml/sso/init?sp={}",
+                    DEFAULT_REALM_NAME,
+                    urlencoding::encode(SP_ENTITY_ID)
+                ))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn idp_initiated_login_sends_an_unsolicited_response() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    publish_password_browser_flow(&ctx, &realm).await;
+    setup_user(&ctx, realm.id).await;
+    register_service_provider(&ctx, realm.id, true, None).await;
+
+    let response = ctx
+        .request(
+            Request::builder()
+                .uri(format!(
+                    "/api/realms/{}/saml/sso/init?sp={}",
+                    DEFAULT_REALM_NAME,
+                    urlencoding::encode(SP_ENTITY_ID)
+                ))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    let session_id =
+        cookie_value(response.headers(), LOGIN_SESSION_COOKIE).expect("login session cookie");
+
+    let url = sign_in(&ctx, &session_id).await;
+    let response = ctx.request(get(&url)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let html = text_body(response).await;
+    let xml = saml::decode_post_message(&form_input(&html, "SAMLResponse").expect("response"))
+        .expect("response xml");
+    let response_xml = XmlElement::parse(&xml).expect("response");
+    assert_eq!(response_xml.attribute("InResponseTo"), None);
+    assert!(form_input(&html, "RelayState").is_none());
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn sp_initiated_logout_ends_the_named_session() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let user = setup_user(&ctx, realm.id).await;
+    let signer = SpSigner::new();
+    register_service_provider(&ctx, realm.id, false, Some(&signer)).await;
+    let public_key = idp_certificate(&ctx).await;
+
+    let (_, sso) = ctx
+        .app_state
+        .auth_service
+        .create_session(&user, None, None, None)
+        .await
+        .expect("sso session");
+
+    let logout_request = format!(
+        r#"<samlp:LogoutRequest xmlns:samlp="urn:oasis:names:tc:SAML:2.0:protocol" xmlns:saml="urn:oasis:names:tc:SAML:2.0:assertion" ID="_logout-1" Version="2.0" IssueInstant="2026-01-01T00:00:00Z"><saml:Issuer>{}</saml:Issuer><saml:NameID Format="{}">{}</saml:NameID><samlp:SessionIndex>{}</samlp:SessionIndex></samlp:LogoutRequest>"#,
+        SP_ENTITY_ID,
+        NameIdFormat::Persistent.uri(),
+        user.id,
+        sso.family_id
+    );
+    let slo = |query: String| {
+        Request::builder()
+            .uri(format!(
+                "/api/realms/{}/saml/slo?{}",
+                DEFAULT_REALM_NAME, query
+            ))
+            .header(
+                header::COOKIE,
+                format!("{}={}", REFRESH_TOKEN_COOKIE, sso.id),
+            )
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    // Unsigned LogoutRequests, and ones altered after signing, end nothing.
+    let unsigned = redirect_query("SAMLRequest", &logout_request, Some("bye"));
+    let tampered = signer
+        .sign_query(&unsigned)
+        .replace("RelayState=bye", "RelayState=elsewhere");
+    for query in [unsigned.clone(), tampered] {
+        let response = ctx.request(slo(query)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+    let (_, refreshed) = ctx
+        .app_state
+        .auth_service
+        .refresh_session(sso.id)
+        .await
+        .expect("session survives unsigned logout");
+
+    let response = ctx.request(slo(signer.sign_query(&unsigned))).await;
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert!(response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .any(|value| value.to_str().unwrap().starts_with(REFRESH_TOKEN_COOKIE)));
+
+    let location = Url::parse(
+        response
+            .headers()
+            .get(header::LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .expect("location"),
+    )
+    .expect("location url");
+    assert_eq!(
+        format!(
+            "{}{}",
+            location.origin().ascii_serialization(),
+            location.path()
+        ),
+        SP_SLO_URL
+    );
+    let raw_query = location.query().expect("query");
+    let (signed_part, signature) = raw_query.split_once("&Signature=").expect("signature");
+    saml::verify_query(
+        signed_part,
+        &urlencoding::decode(signature).expect("signature"),
+        &[public_key],
+    )
+    .expect("redirect signature");
+
+    let params: std::collections::HashMap<String, String> =
+        location.query_pairs().into_owned().collect();
+    assert_eq!(params.get("RelayState").map(String::as_str), Some("bye"));
+    let logout_response =
+        saml::decode_redirect_message(&params["SAMLResponse"]).expect("logout response");
+    let logout_response = XmlElement::parse(&logout_response).expect("logout response xml");
+    assert_eq!(logout_response.attribute("InResponseTo"), Some("_logout-1"));
+
+    let result = ctx
+        .app_state
+        .auth_service
+        .refresh_session(refreshed.id)
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn authn_request_signatures_are_verified_when_required() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    publish_password_browser_flow(&ctx, &realm).await;
+    let signer = SpSigner::new();
+    register_service_provider(&ctx, realm.id, false, Some(&signer)).await;
+
+    let sso_redirect = |query: String| {
+        get(&format!(
+            "/api/realms/{}/saml/sso?{}",
+            DEFAULT_REALM_NAME, query
+        ))
+    };
+    let sso_post = |saml_request: String| {
+        let body = url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("SAMLRequest", &saml_request)
+            .finish();
+        Request::builder()
+            .method("POST")
+            .uri(format!("/api/realms/{}/saml/sso", DEFAULT_REALM_NAME))
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from(body))
+            .unwrap()
+    };
+    let unsigned = redirect_query("SAMLRequest", &authn_request("_req-4"), Some("p"));
+    let forged = SpSigner::new().sign_query(&unsigned);
+    let rejected = [
+        sso_redirect(unsigned.clone()),
+        sso_redirect(forged),
+        sso_post(saml::encode_post_message(&authn_request("_req-5"))),
+        sso_post(saml::encode_post_message(
+            &signer
+                .sign_enveloped(&authn_request("_req-6"))
+                .replace("2026-01-01T00:00:00Z", "2026-01-02T00:00:00Z"),
+        )),
+    ];
+    for request in rejected {
+        let response = ctx.request(request).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert!(cookie_value(response.headers(), LOGIN_SESSION_COOKIE).is_none());
+    }
+
+    let accepted = [
+        sso_redirect(signer.sign_query(&unsigned)),
+        sso_post(saml::encode_post_message(
+            &signer.sign_enveloped(&authn_request("_req-7")),
+        )),
+    ];
+    for request in accepted {
+        let response = ctx.request(request).await;
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert!(cookie_value(response.headers(), LOGIN_SESSION_COOKIE).is_some());
+    }
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn logout_requests_need_a_registered_certificate() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    register_service_provider(&ctx, realm.id, false, None).await;
+    let signer = SpSigner::new();
+
+    let logout_request = format!(
+        r#"<samlp:LogoutRequest xmlns:samlp="urn:oasis:names:tc:SAML:2.0:protocol" xmlns:saml="urn:oasis:names:tc:SAML:2.0:assertion" ID="_logout-2" Version="2.0" IssueInstant="2026-01-01T00:00:00Z"><saml:Issuer>{}</saml:Issuer></samlp:LogoutRequest>"#,
+        SP_ENTITY_ID
+    );
+    let query = redirect_query("SAMLRequest", &logout_request, None);
+    for query in [query.clone(), signer.sign_query(&query)] {
+        let response = ctx
+            .request(get(&format!(
+                "/api/realms/{}/saml/slo?{}",
+                DEFAULT_REALM_NAME, query
+            )))
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn service_provider_registrations_are_validated() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    register_service_provider(&ctx, realm.id, false, None).await;
+
+    let request = |entity_id: &str, acs_urls: Vec<&str>| CreateSamlServiceProviderRequest {
+        entity_id: entity_id.to_string(),
+        name: None,
+        acs_urls: acs_urls.into_iter().map(str::to_string).collect(),
+        slo_url: None,
+        slo_binding: None,
+        name_id_format: None,
+        attribute_mappings: None,
+        sign_response: None,
+        signing_certificates: None,
+        require_signed_requests: None,
+        allow_idp_initiated: None,
+        enabled: None,
+    };
+    let unverifiable = CreateSamlServiceProviderRequest {
+        require_signed_requests: Some(true),
+        ..request("https://crm.example", vec!["https://crm.example/acs"])
+    };
+    let bad_certificate = CreateSamlServiceProviderRequest {
+        signing_certificates: Some(vec!["not a certificate".to_string()]),
+        ..request("https://crm.example", vec!["https://crm.example/acs"])
+    };
+    let cases = [
+        request(SP_ENTITY_ID, vec!["https://wiki.example/acs"]),
+        request("https://crm.example", vec![]),
+        request("https://crm.example", vec!["javascript:alert(1)"]),
+        request("", vec!["https://crm.example/acs"]),
+        unverifiable,
+        bad_certificate,
+    ];
+    for case in cases {
+        let result = ctx.app_state.saml_service.create(realm.id, case).await;
+        assert!(result.is_err());
+    }
+
+    let created = ctx
+        .app_state
+        .saml_service
+        .create(
+            realm.id,
+            request("https://crm.example", vec!["https://crm.example/acs"]),
+        )
+        .await
+        .expect("create");
+    assert_eq!(created.name, "https://crm.example");
+    assert_eq!(created.name_id_format, NameIdFormat::Unspecified);
+    assert_eq!(
+        ctx.app_state
+            .saml_service
+            .list(realm.id)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+}
@@ -0,0 +1,391 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use serial_test::serial;
+use uuid::Uuid;
+
+use reauth::application::realm_service::CreateRealmPayload;
+use reauth::bootstrap::app_state::SetupState;
+use reauth::constants::DEFAULT_REALM_NAME;
+use reauth::domain::oidc::{OidcClient, TokenEndpointAuthMethod};
+use reauth::domain::realm::Realm;
+use reauth::domain::scim;
+
+use crate::support::TestContext;
+
+const SCIM_CONTENT_TYPE: &str = "application/scim+json";
+
+async fn json_body(response: axum::response::Response) -> Value {
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("read body")
+        .to_bytes();
+    if bytes.is_empty() {
+        return Value::Null;
+    }
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+async fn setup_realm(ctx: &TestContext) -> Realm {
+    let realm = ctx
+        .app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: DEFAULT_REALM_NAME.to_string(),
+        })
+        .await
+        .expect("create realm");
+    *ctx.app_state.setup_state.write().await = SetupState::sealed();
+    realm
+}
+
+/// Registers a confidential client allowed `scopes` and returns a
+/// client_credentials access token requesting them.
+async fn scim_token(ctx: &TestContext, realm_id: Uuid, scopes: &[&str]) -> String {
+    let mut client = OidcClient {
+        id: Uuid::new_v4(),
+        realm_id,
+        client_id: format!("scim-{}", Uuid::new_v4().simple()),
+        client_secret: None,
+        redirect_uris: "[]".to_string(),
+        scopes: serde_json::to_string(scopes).unwrap(),
+        web_origins: "[]".to_string(),
+        managed_by_config: false,
+        token_endpoint_auth_method: TokenEndpointAuthMethod::ClientSecretBasic,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: None,
+        frontchannel_logout_uri: None,
+        token_exchange_policy: None,
+        direct_grant_enabled: false,
+        require_pushed_authorization_requests: false,
+    };
+    ctx.app_state
+        .oidc_service
+        .register_client(&mut client)
+        .await
+        .expect("register client");
+
+    ctx.app_state
+        .oidc_service
+        .client_credentials_grant(&client, Some(&scopes.join(" ")))
+        .await
+        .expect("client credentials")
+        .access_token
+}
+
+fn scim_uri(path: &str) -> String {
+    format!("/api/realms/{}/scim/v2{}", DEFAULT_REALM_NAME, path)
+}
+
+async fn send(
+    ctx: &TestContext,
+    method: &str,
+    path: &str,
+    token: &str,
+    body: Option<Value>,
+) -> axum::response::Response {
+    let mut builder = Request::builder()
+        .method(method)
+        .uri(scim_uri(path))
+        .header(header::AUTHORIZATION, format!("Bearer {}", token));
+    let body = match body {
+        Some(body) => {
+            builder = builder.header(header::CONTENT_TYPE, SCIM_CONTENT_TYPE);
+            Body::from(body.to_string())
+        }
+        None => Body::empty(),
+    };
+    ctx.request(builder.body(body).expect("request")).await
+}
+
+fn user_body(user_name: &str, email: &str) -> Value {
+    json!({
+        "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+        "userName": user_name,
+        "externalId": format!("ext-{}", user_name),
+        "name": { "givenName": "Ada", "familyName": "Lovelace" },
+        "emails": [{ "value": email, "type": "work", "primary": true }],
+        "active": true
+    })
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn scim_user_lifecycle() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let token = scim_token(
+        &ctx,
+        realm.id,
+        &[scim::SCOPE_USERS_READ, scim::SCOPE_USERS_WRITE],
+    )
+    .await;
+
+    let response = send(
+        &ctx,
+        "POST",
+        "/Users",
+        &token,
+        Some(user_body("ada", "ada@example.com")),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    assert_eq!(
+        response.headers().get(header::CONTENT_TYPE).unwrap(),
+        SCIM_CONTENT_TYPE
+    );
+    let location = response
+        .headers()
+        .get(header::LOCATION)
+        .expect("location")
+        .to_str()
+        .unwrap()
+        .to_string();
+    let created = json_body(response).await;
+    let id = created["id"].as_str().expect("id").to_string();
+    assert!(location.ends_with(&format!("/Users/{}", id)));
+    assert_eq!(created["userName"], "ada");
+    assert_eq!(created["externalId"], "ext-ada");
+    assert_eq!(created["emails"][0]["value"], "ada@example.com");
+
+    let duplicate = send(
+        &ctx,
+        "POST",
+        "/Users",
+        &token,
+        Some(user_body("ada", "other@example.com")),
+    )
+    .await;
+    assert_eq!(duplicate.status(), StatusCode::CONFLICT);
+    let error = json_body(duplicate).await;
+    assert_eq!(error["status"], "409");
+    assert_eq!(error["scimType"], "uniqueness");
+
+    let listed = send(
+        &ctx,
+        "GET",
+        "/Users?filter=userName%20eq%20%22ada%22",
+        &token,
+        None,
+    )
+    .await;
+    assert_eq!(listed.status(), StatusCode::OK);
+    let listed = json_body(listed).await;
+    assert_eq!(listed["totalResults"], 1);
+    assert_eq!(listed["Resources"][0]["id"], id.as_str());
+
+    let by_external = json_body(
+        send(
+            &ctx,
+            "GET",
+            "/Users?filter=externalId%20eq%20%22ext-ada%22",
+            &token,
+            None,
+        )
+        .await,
+    )
+    .await;
+    assert_eq!(by_external["totalResults"], 1);
+
+    let invalid = send(&ctx, "GET", "/Users?filter=userName%20zz", &token, None).await;
+    assert_eq!(invalid.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(json_body(invalid).await["scimType"], "invalidFilter");
+
+    let patched = send(
+        &ctx,
+        "PATCH",
+        &format!("/Users/{}", id),
+        &token,
+        Some(json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [
+                { "op": "replace", "path": "active", "value": false },
+                { "op": "replace", "path": "name.givenName", "value": "Augusta" }
+            ]
+        })),
+    )
+    .await;
+    assert_eq!(patched.status(), StatusCode::OK);
+    let patched = json_body(patched).await;
+    assert_eq!(patched["active"], false);
+    assert_eq!(patched["name"]["givenName"], "Augusta");
+
+    let user_id = Uuid::parse_str(&id).unwrap();
+    let stored = ctx
+        .app_state
+        .user_service
+        .get_user_in_realm(realm.id, user_id)
+        .await
+        .expect("stored user");
+    assert!(stored.banned_at.is_some());
+
+    let deleted = send(&ctx, "DELETE", &format!("/Users/{}", id), &token, None).await;
+    assert_eq!(deleted.status(), StatusCode::NO_CONTENT);
+    let missing = send(&ctx, "GET", &format!("/Users/{}", id), &token, None).await;
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    assert_eq!(json_body(missing).await["status"], "404");
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn scim_groups_manage_members() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let token = scim_token(
+        &ctx,
+        realm.id,
+        &[
+            scim::SCOPE_USERS_READ,
+            scim::SCOPE_USERS_WRITE,
+            scim::SCOPE_GROUPS_READ,
+            scim::SCOPE_GROUPS_WRITE,
+        ],
+    )
+    .await;
+
+    let mut user_ids = Vec::new();
+    for name in ["grace", "alan"] {
+        let created = send(
+            &ctx,
+            "POST",
+            "/Users",
+            &token,
+            Some(user_body(name, &format!("{}@example.com", name))),
+        )
+        .await;
+        assert_eq!(created.status(), StatusCode::CREATED);
+        user_ids.push(json_body(created).await["id"].as_str().unwrap().to_string());
+    }
+
+    let created = send(
+        &ctx,
+        "POST",
+        "/Groups",
+        &token,
+        Some(json!({
+            "schemas": ["urn:ietf:params:scim:schemas:core:2.0:Group"],
+            "displayName": "engineering",
+            "members": [{ "value": user_ids[0] }]
+        })),
+    )
+    .await;
+    assert_eq!(created.status(), StatusCode::CREATED);
+    let group = json_body(created).await;
+    let group_id = group["id"].as_str().unwrap().to_string();
+    assert_eq!(group["members"].as_array().unwrap().len(), 1);
+    assert_eq!(group["members"][0]["value"], user_ids[0].as_str());
+
+    let patched = send(
+        &ctx,
+        "PATCH",
+        &format!("/Groups/{}", group_id),
+        &token,
+        Some(json!({
+            "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+            "Operations": [
+                { "op": "add", "path": "members", "value": [{ "value": user_ids[1] }] },
+                { "op": "remove", "path": format!("members[value eq \"{}\"]", user_ids[0]) }
+            ]
+        })),
+    )
+    .await;
+    assert_eq!(patched.status(), StatusCode::OK);
+    let patched = json_body(patched).await;
+    let members = patched["members"].as_array().unwrap();
+    assert_eq!(members.len(), 1);
+    assert_eq!(members[0]["value"], user_ids[1].as_str());
+
+    let group_uuid = Uuid::parse_str(&group_id).unwrap();
+    let stored = ctx
+        .app_state
+        .rbac_service
+        .get_group_member_ids(realm.id, group_uuid)
+        .await
+        .expect("member ids");
+    assert_eq!(stored, vec![Uuid::parse_str(&user_ids[1]).unwrap()]);
+
+    let listed = json_body(
+        send(
+            &ctx,
+            "GET",
+            "/Groups?filter=displayName%20eq%20%22engineering%22",
+            &token,
+            None,
+        )
+        .await,
+    )
+    .await;
+    assert_eq!(listed["totalResults"], 1);
+
+    let deleted = send(
+        &ctx,
+        "DELETE",
+        &format!("/Groups/{}", group_id),
+        &token,
+        None,
+    )
+    .await;
+    assert_eq!(deleted.status(), StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn scim_requires_client_token_with_scopes() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+
+    let unauthenticated = ctx
+        .request(
+            Request::builder()
+                .method("GET")
+                .uri(scim_uri("/Users"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+    assert_eq!(unauthenticated.status(), StatusCode::UNAUTHORIZED);
+    assert!(unauthenticated
+        .headers()
+        .contains_key(header::WWW_AUTHENTICATE));
+
+    let user = ctx
+        .app_state
+        .user_service
+        .create_user(realm.id, "admin", "password", None, false)
+        .await
+        .expect("create user");
+    let (login, _) = ctx
+        .app_state
+        .auth_service
+        .create_session(&user, None, None, None)
+        .await
+        .expect("create session");
+    let user_token = send(&ctx, "GET", "/Users", &login.access_token, None).await;
+    assert_eq!(user_token.status(), StatusCode::UNAUTHORIZED);
+
+    let reader = scim_token(&ctx, realm.id, &[scim::SCOPE_USERS_READ]).await;
+    let config = send(&ctx, "GET", "/ServiceProviderConfig", &reader, None).await;
+    assert_eq!(config.status(), StatusCode::OK);
+    assert_eq!(json_body(config).await["patch"]["supported"], true);
+
+    let listed = send(&ctx, "GET", "/Users", &reader, None).await;
+    assert_eq!(listed.status(), StatusCode::OK);
+
+    let forbidden = send(
+        &ctx,
+        "POST",
+        "/Users",
+        &reader,
+        Some(user_body("eve", "eve@example.com")),
+    )
+    .await;
+    assert_eq!(forbidden.status(), StatusCode::FORBIDDEN);
+    assert_eq!(json_body(forbidden).await["status"], "403");
+
+    let groups = send(&ctx, "GET", "/Groups", &reader, None).await;
+    assert_eq!(groups.status(), StatusCode::FORBIDDEN);
+}
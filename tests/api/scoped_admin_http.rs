@@ -0,0 +1,482 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use serial_test::serial;
+use uuid::Uuid;
+
+use reauth::application::rbac_service::{CreateGroupPayload, CreateRolePayload};
+use reauth::application::realm_service::CreateRealmPayload;
+use reauth::bootstrap::app_state::SetupState;
+use reauth::constants::DEFAULT_REALM_NAME;
+use reauth::domain::group::Group;
+use reauth::domain::oidc::{OidcClient, TokenEndpointAuthMethod};
+use reauth::domain::permissions;
+use reauth::domain::realm::Realm;
+use reauth::domain::role::Role;
+use reauth::domain::user::User;
+
+use crate::support::TestContext;
+
+async fn setup_realm(ctx: &TestContext) -> Realm {
+    let realm = ctx
+        .app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: DEFAULT_REALM_NAME.to_string(),
+        })
+        .await
+        .expect("create realm");
+    *ctx.app_state.setup_state.write().await = SetupState::sealed();
+    realm
+}
+
+async fn create_user(ctx: &TestContext, realm_id: Uuid) -> User {
+    let username = format!("user-{}", Uuid::new_v4().simple());
+    ctx.app_state
+        .user_service
+        .create_user(realm_id, &username, "password", None, false)
+        .await
+        .expect("create user")
+}
+
+async fn create_group(
+    ctx: &TestContext,
+    realm_id: Uuid,
+    name: &str,
+    parent_id: Option<Uuid>,
+) -> Group {
+    ctx.app_state
+        .rbac_service
+        .create_group(
+            realm_id,
+            CreateGroupPayload {
+                name: name.to_string(),
+                description: None,
+                parent_id,
+            },
+        )
+        .await
+        .expect("create group")
+}
+
+async fn create_role(ctx: &TestContext, realm_id: Uuid, name: &str) -> Role {
+    ctx.app_state
+        .rbac_service
+        .create_role(
+            realm_id,
+            CreateRolePayload {
+                name: name.to_string(),
+                description: None,
+                client_id: None,
+            },
+        )
+        .await
+        .expect("create role")
+}
+
+async fn create_client(ctx: &TestContext, realm_id: Uuid, client_id: &str) -> OidcClient {
+    let mut client = OidcClient {
+        id: Uuid::new_v4(),
+        realm_id,
+        client_id: client_id.to_string(),
+        client_secret: None,
+        redirect_uris: "[]".to_string(),
+        scopes: "[]".to_string(),
+        web_origins: "[]".to_string(),
+        managed_by_config: false,
+        token_endpoint_auth_method: TokenEndpointAuthMethod::ClientSecretBasic,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: None,
+        frontchannel_logout_uri: None,
+        token_exchange_policy: None,
+        direct_grant_enabled: false,
+        require_pushed_authorization_requests: false,
+    };
+    ctx.app_state
+        .oidc_service
+        .register_client(&mut client)
+        .await
+        .expect("register client");
+    client
+}
+
+async fn admin_token(ctx: &TestContext, realm_id: Uuid, granted: &[String]) -> String {
+    let user = create_user(ctx, realm_id).await;
+    let role = ctx
+        .app_state
+        .rbac_service
+        .create_role(
+            realm_id,
+            CreateRolePayload {
+                name: user.username.clone(),
+                description: None,
+                client_id: None,
+            },
+        )
+        .await
+        .expect("create role");
+    for permission in granted {
+        ctx.app_state
+            .rbac_service
+            .assign_permission_to_role(realm_id, role.id, permission.clone())
+            .await
+            .expect("assign permission");
+    }
+    ctx.app_state
+        .rbac_service
+        .assign_role_to_user(realm_id, user.id, role.id)
+        .await
+        .expect("assign role");
+
+    let (login, _) = ctx
+        .app_state
+        .auth_service
+        .create_session(&user, None, None, None)
+        .await
+        .expect("create session");
+    login.access_token
+}
+
+async fn get_status(ctx: &TestContext, uri: String, token: &str) -> StatusCode {
+    let request = Request::builder()
+        .method("GET")
+        .uri(uri)
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .body(Body::empty())
+        .expect("request");
+    ctx.request(request).await.status()
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn group_scoped_admin_manages_only_its_subtree() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+
+    let emea = create_group(&ctx, realm.id, "emea", None).await;
+    let berlin = create_group(&ctx, realm.id, "berlin", Some(emea.id)).await;
+    let apac = create_group(&ctx, realm.id, "apac", None).await;
+    let alice = create_user(&ctx, realm.id).await;
+    let bob = create_user(&ctx, realm.id).await;
+    for (user_id, group_id) in [(alice.id, berlin.id), (bob.id, apac.id)] {
+        ctx.app_state
+            .rbac_service
+            .assign_user_to_group(realm.id, user_id, group_id)
+            .await
+            .expect("assign group");
+    }
+
+    let helpdesk = admin_token(
+        &ctx,
+        realm.id,
+        &[
+            format!("{}@group:{}", permissions::USER_WRITE, emea.id),
+            format!("{}@group:{}", permissions::RBAC_WRITE, emea.id),
+        ],
+    )
+    .await;
+    let users_uri = format!("/api/realms/{}/users", DEFAULT_REALM_NAME);
+    let groups_uri = format!("/api/realms/{}/rbac/groups", DEFAULT_REALM_NAME);
+
+    assert_eq!(
+        get_status(&ctx, format!("{}/{}", users_uri, alice.id), &helpdesk).await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        get_status(&ctx, format!("{}/{}", users_uri, bob.id), &helpdesk).await,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        get_status(&ctx, users_uri.clone(), &helpdesk).await,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        get_status(&ctx, format!("{}/{}", groups_uri, berlin.id), &helpdesk).await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        get_status(&ctx, format!("{}/{}", groups_uri, apac.id), &helpdesk).await,
+        StatusCode::FORBIDDEN
+    );
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn client_and_realm_scoped_admins_stay_in_scope() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let branch = ctx
+        .app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: "branch".to_string(),
+        })
+        .await
+        .expect("create branch realm");
+
+    let portal = create_client(&ctx, realm.id, "portal").await;
+    let billing = create_client(&ctx, realm.id, "billing").await;
+    let clients_uri = format!("/api/realms/{}/clients", DEFAULT_REALM_NAME);
+
+    let portal_admin = admin_token(
+        &ctx,
+        realm.id,
+        &[format!("{}@client:{}", permissions::CLIENT_READ, portal.id)],
+    )
+    .await;
+    assert_eq!(
+        get_status(
+            &ctx,
+            format!("{}/{}", clients_uri, portal.id),
+            &portal_admin
+        )
+        .await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        get_status(
+            &ctx,
+            format!("{}/{}", clients_uri, billing.id),
+            &portal_admin
+        )
+        .await,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        get_status(&ctx, clients_uri.clone(), &portal_admin).await,
+        StatusCode::FORBIDDEN
+    );
+
+    let branch_admin = admin_token(
+        &ctx,
+        realm.id,
+        &[format!("{}@realm:{}", permissions::USER_READ, branch.id)],
+    )
+    .await;
+    assert_eq!(
+        get_status(&ctx, "/api/realms/branch/users".to_string(), &branch_admin).await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        get_status(
+            &ctx,
+            format!("/api/realms/{}/users", DEFAULT_REALM_NAME),
+            &branch_admin
+        )
+        .await,
+        StatusCode::FORBIDDEN
+    );
+}
+
+async fn post_json(
+    ctx: &TestContext,
+    uri: String,
+    token: &str,
+    body: serde_json::Value,
+) -> StatusCode {
+    let request = Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header(header::AUTHORIZATION, format!("Bearer {}", token))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .expect("request");
+    ctx.request(request).await.status()
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn scoped_admin_cannot_grant_broader_permissions() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let role = ctx
+        .app_state
+        .rbac_service
+        .create_role(
+            realm.id,
+            CreateRolePayload {
+                name: "helpdesk".to_string(),
+                description: None,
+                client_id: None,
+            },
+        )
+        .await
+        .expect("create role");
+
+    let scoped = admin_token(
+        &ctx,
+        realm.id,
+        &[
+            format!("{}@realm:{}", permissions::RBAC_WRITE, realm.id),
+            format!("{}@realm:{}", permissions::USER_WRITE, realm.id),
+        ],
+    )
+    .await;
+    let permissions_uri = format!(
+        "/api/realms/{}/rbac/roles/{}/permissions",
+        DEFAULT_REALM_NAME, role.id
+    );
+
+    assert_eq!(
+        post_json(
+            &ctx,
+            permissions_uri.clone(),
+            &scoped,
+            serde_json::json!({ "permission": permissions::USER_WRITE }),
+        )
+        .await,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        post_json(
+            &ctx,
+            format!("{}/bulk", permissions_uri),
+            &scoped,
+            serde_json::json!({ "permissions": [permissions::ALL], "action": "add" }),
+        )
+        .await,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        post_json(
+            &ctx,
+            permissions_uri,
+            &scoped,
+            serde_json::json!({
+                "permission": format!("{}@realm:{}", permissions::USER_WRITE, realm.id)
+            }),
+        )
+        .await,
+        StatusCode::OK
+    );
+
+    let granted = ctx
+        .app_state
+        .rbac_service
+        .get_permissions_for_role(realm.id, role.id)
+        .await
+        .expect("role permissions");
+    assert_eq!(
+        granted,
+        vec![format!("{}@realm:{}", permissions::USER_WRITE, realm.id)]
+    );
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn scoped_admin_cannot_assign_broader_roles() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+
+    let superuser = create_role(&ctx, realm.id, "superuser").await;
+    ctx.app_state
+        .rbac_service
+        .assign_permission_to_role(realm.id, superuser.id, permissions::ALL.to_string())
+        .await
+        .expect("assign wildcard");
+    let wrapper = create_role(&ctx, realm.id, "wrapper").await;
+    ctx.app_state
+        .rbac_service
+        .assign_composite_role(realm.id, wrapper.id, superuser.id)
+        .await
+        .expect("assign composite");
+    let helpdesk = create_role(&ctx, realm.id, "helpdesk").await;
+    ctx.app_state
+        .rbac_service
+        .assign_permission_to_role(
+            realm.id,
+            helpdesk.id,
+            format!("{}@realm:{}", permissions::USER_WRITE, realm.id),
+        )
+        .await
+        .expect("assign scoped permission");
+
+    let scoped = admin_token(
+        &ctx,
+        realm.id,
+        &[
+            format!("{}@realm:{}", permissions::RBAC_WRITE, realm.id),
+            format!("{}@realm:{}", permissions::USER_WRITE, realm.id),
+        ],
+    )
+    .await;
+    let target = create_user(&ctx, realm.id).await;
+    let group = create_group(&ctx, realm.id, "support", None).await;
+    let user_roles_uri = format!(
+        "/api/realms/{}/users/{}/roles",
+        DEFAULT_REALM_NAME, target.id
+    );
+    let group_roles_uri = format!(
+        "/api/realms/{}/rbac/groups/{}/roles",
+        DEFAULT_REALM_NAME, group.id
+    );
+
+    for role_id in [superuser.id, wrapper.id] {
+        assert_eq!(
+            post_json(
+                &ctx,
+                user_roles_uri.clone(),
+                &scoped,
+                serde_json::json!({ "role_id": role_id }),
+            )
+            .await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            post_json(
+                &ctx,
+                group_roles_uri.clone(),
+                &scoped,
+                serde_json::json!({ "role_id": role_id }),
+            )
+            .await,
+            StatusCode::FORBIDDEN
+        );
+    }
+    assert_eq!(
+        post_json(
+            &ctx,
+            format!("{}/bulk", group_roles_uri),
+            &scoped,
+            serde_json::json!({ "role_ids": [helpdesk.id, superuser.id], "action": "add" }),
+        )
+        .await,
+        StatusCode::FORBIDDEN
+    );
+
+    assert_eq!(
+        post_json(
+            &ctx,
+            user_roles_uri,
+            &scoped,
+            serde_json::json!({ "role_id": helpdesk.id }),
+        )
+        .await,
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        post_json(
+            &ctx,
+            group_roles_uri,
+            &scoped,
+            serde_json::json!({ "role_id": helpdesk.id }),
+        )
+        .await,
+        StatusCode::NO_CONTENT
+    );
+
+    let user_roles = ctx
+        .app_state
+        .rbac_service
+        .get_direct_role_ids_for_user(realm.id, target.id)
+        .await
+        .expect("user roles");
+    assert_eq!(user_roles, vec![helpdesk.id]);
+    let group_roles = ctx
+        .app_state
+        .rbac_service
+        .get_group_role_ids(realm.id, group.id)
+        .await
+        .expect("group roles");
+    assert_eq!(group_roles, vec![helpdesk.id]);
+}
@@ -0,0 +1,368 @@
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{header, HeaderMap, Request, StatusCode};
+use http_body_util::BodyExt;
+use serial_test::serial;
+use std::net::{Ipv4Addr, SocketAddr};
+use uuid::Uuid;
+
+use reauth::application::flow_manager::UpdateDraftRequest;
+use reauth::application::realm_service::CreateRealmPayload;
+use reauth::bootstrap::app_state::SetupState;
+use reauth::constants::{DEFAULT_REALM_NAME, LOGIN_SESSION_COOKIE};
+use reauth::domain::realm::Realm;
+
+use crate::support::TestContext;
+
+async fn json_body(response: axum::response::Response) -> serde_json::Value {
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("read body")
+        .to_bytes();
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+fn cookie_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .filter_map(|value| value.split(';').next())
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, value)| key.trim() == name && !value.trim().is_empty())
+        .map(|(_, value)| value.trim().to_string())
+}
+
+async fn setup_realm(ctx: &TestContext) -> Realm {
+    let realm = ctx
+        .app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: DEFAULT_REALM_NAME.to_string(),
+        })
+        .await
+        .expect("create realm");
+    *ctx.app_state.setup_state.write().await = SetupState::sealed();
+    realm
+}
+
+/// password -> issue_sms_otp -> verify_sms_otp, denying users without a phone.
+async fn publish_sms_browser_flow(
+    ctx: &TestContext,
+    realm: &Realm,
+    mut issue_config: serde_json::Value,
+) {
+    issue_config["logic_type"] = serde_json::json!("core.logic.issue_sms_otp");
+    let flow_id = realm
+        .browser_flow_id
+        .as_ref()
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .expect("browser flow id");
+    let graph = serde_json::json!({
+        "nodes": [
+            { "id": "start", "type": "core.start", "data": { "config": {} } },
+            { "id": "auth-password", "type": "core.auth.password", "data": { "config": { "auth_type": "core.auth.password" } } },
+            { "id": "issue-sms", "type": "core.logic.issue_sms_otp", "data": { "config": issue_config } },
+            { "id": "verify-sms", "type": "core.auth.verify_sms_otp", "data": { "config": { "auth_type": "core.auth.verify_sms_otp", "max_attempts": 3 } } },
+            { "id": "allow", "type": "core.terminal.allow", "data": { "config": {} } },
+            { "id": "deny", "type": "core.terminal.deny", "data": { "config": { "is_failure": true } } }
+        ],
+        "edges": [
+            { "id": "e-start-password", "source": "start", "target": "auth-password", "sourceHandle": "next" },
+            { "id": "e-password-issue", "source": "auth-password", "target": "issue-sms", "sourceHandle": "success" },
+            { "id": "e-issue-verify", "source": "issue-sms", "target": "verify-sms", "sourceHandle": "issued" },
+            { "id": "e-issue-deny", "source": "issue-sms", "target": "deny", "sourceHandle": "no_phone" },
+            { "id": "e-verify-allow", "source": "verify-sms", "target": "allow", "sourceHandle": "success" },
+            { "id": "e-verify-deny", "source": "verify-sms", "target": "deny", "sourceHandle": "failure" }
+        ]
+    });
+
+    ctx.app_state
+        .flow_manager
+        .update_draft(
+            flow_id,
+            UpdateDraftRequest {
+                name: None,
+                description: None,
+                graph_json: Some(graph),
+            },
+        )
+        .await
+        .expect("update draft");
+    ctx.app_state
+        .flow_manager
+        .publish_flow(realm.id, flow_id)
+        .await
+        .expect("publish flow");
+}
+
+fn with_connect_info(mut request: Request<Body>) -> Request<Body> {
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 3000))));
+    request
+}
+
+async fn start_login(ctx: &TestContext) -> String {
+    let response = ctx
+        .request(with_connect_info(
+            Request::builder()
+                .uri(format!("/api/realms/{}/auth/login", DEFAULT_REALM_NAME))
+                .body(Body::empty())
+                .unwrap(),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let session_id =
+        cookie_value(response.headers(), LOGIN_SESSION_COOKIE).expect("login session cookie");
+    format!("{}={}", LOGIN_SESSION_COOKIE, session_id)
+}
+
+async fn execute(
+    ctx: &TestContext,
+    cookie: &str,
+    payload: serde_json::Value,
+) -> (StatusCode, serde_json::Value) {
+    let response = ctx
+        .request(with_connect_info(
+            Request::builder()
+                .method("POST")
+                .uri(format!(
+                    "/api/realms/{}/auth/login/execute",
+                    DEFAULT_REALM_NAME
+                ))
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(header::COOKIE, cookie)
+                .body(Body::from(payload.to_string()))
+                .unwrap(),
+        ))
+        .await;
+    (response.status(), json_body(response).await)
+}
+
+async fn execute_ok(
+    ctx: &TestContext,
+    cookie: &str,
+    payload: serde_json::Value,
+) -> serde_json::Value {
+    let (status, body) = execute(ctx, cookie, payload).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    body
+}
+
+/// Messages written by the `log` SMS sender, oldest first.
+async fn outbox(ctx: &TestContext) -> Vec<serde_json::Value> {
+    let path = ctx.app_state.settings.read().await.sms.log_file.clone();
+    std::fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("outbox entry"))
+        .collect()
+}
+
+async fn last_code(ctx: &TestContext) -> String {
+    let messages = outbox(ctx).await;
+    let message = messages.last().expect("sms sent")["message"]
+        .as_str()
+        .expect("message")
+        .to_string();
+    message.split_whitespace().next().expect("code").to_string()
+}
+
+/// `(event_type, payload)` of the `mfa.*` outbox rows, oldest first.
+async fn mfa_events(ctx: &TestContext) -> Vec<(String, serde_json::Value)> {
+    let database_url = ctx.app_state.settings.read().await.database.url.clone();
+    let pool = sqlx::SqlitePool::connect(&database_url)
+        .await
+        .expect("connect");
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT event_type, payload_json FROM event_outbox WHERE event_type LIKE 'mfa.%' ORDER BY occurred_at",
+    )
+    .fetch_all(&pool)
+    .await
+    .expect("outbox rows");
+    pool.close().await;
+    rows.into_iter()
+        .map(|(event_type, payload)| (event_type, serde_json::from_str(&payload).expect("payload")))
+        .collect()
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn sms_otp_login_throttles_resend_and_verifies_phone() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    publish_sms_browser_flow(&ctx, &realm, serde_json::json!({})).await;
+    let user = ctx
+        .app_state
+        .user_service
+        .create_user(realm.id, "ivan", "password-123", None, false)
+        .await
+        .expect("create user");
+    let phone = ctx
+        .app_state
+        .user_phone_number_service
+        .add_phone_number(user.id, realm.id, "+1 555 0142", true, false)
+        .await
+        .expect("add phone");
+
+    let cookie = start_login(&ctx).await;
+    let challenge = execute_ok(
+        &ctx,
+        &cookie,
+        serde_json::json!({ "username": "ivan", "password": "password-123" }),
+    )
+    .await;
+    assert_eq!(challenge["challengeName"], "core.auth.verify_sms_otp");
+    let context = &challenge["context"];
+    assert_eq!(context["template_key"], "sms_otp");
+    assert_eq!(context["can_resend"], true);
+    let masked = context["phone"].as_str().expect("masked phone");
+    assert!(masked.ends_with("42") && !masked.contains("555"));
+
+    let messages = outbox(&ctx).await;
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0]["to"], "+1 555 0142");
+    assert_eq!(messages[0]["realm"], DEFAULT_REALM_NAME);
+    let code = last_code(&ctx).await;
+
+    // The default cooldown blocks an immediate resend.
+    let throttled = execute_ok(&ctx, &cookie, serde_json::json!({ "action": "resend" })).await;
+    assert_eq!(throttled["challengeName"], "core.auth.verify_sms_otp");
+    assert!(throttled["context"]["error"]
+        .as_str()
+        .expect("cooldown error")
+        .starts_with("Please wait"));
+    assert_eq!(outbox(&ctx).await.len(), 1);
+
+    let wrong = if code == "000000" { "111111" } else { "000000" };
+    let rejected = execute_ok(&ctx, &cookie, serde_json::json!({ "otp": wrong })).await;
+    assert_eq!(rejected["context"]["error"], "Invalid verification code.");
+
+    assert!(mfa_events(&ctx).await.is_empty());
+    let done = execute_ok(&ctx, &cookie, serde_json::json!({ "otp": code })).await;
+    assert_eq!(done["status"], "redirect");
+
+    let stored = ctx
+        .app_state
+        .user_phone_number_service
+        .get_primary_phone_number(user.id)
+        .await
+        .expect("primary phone")
+        .expect("phone");
+    assert_eq!(stored.id, phone.id);
+    assert!(stored.is_verified);
+
+    let events = mfa_events(&ctx).await;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].0, "mfa.enrolled");
+    assert_eq!(events[0].1["data"]["method"], "sms");
+    assert_eq!(events[0].1["data"]["user_id"], serde_json::json!(user.id));
+
+    // Signing in again with the now verified number enrolls nothing new.
+    let cookie = start_login(&ctx).await;
+    execute_ok(
+        &ctx,
+        &cookie,
+        serde_json::json!({ "username": "ivan", "password": "password-123" }),
+    )
+    .await;
+    let code = last_code(&ctx).await;
+    let done = execute_ok(&ctx, &cookie, serde_json::json!({ "otp": code })).await;
+    assert_eq!(done["status"], "redirect");
+    assert_eq!(mfa_events(&ctx).await.len(), 1);
+
+    ctx.app_state
+        .user_phone_number_service
+        .remove_phone_number(user.id, phone.id)
+        .await
+        .expect("remove phone");
+    let events = mfa_events(&ctx).await;
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[1].0, "mfa.removed");
+    assert_eq!(events[1].1["data"]["method"], "sms");
+    assert_eq!(events[1].1["realm_id"], serde_json::json!(realm.id));
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn sms_otp_resend_replaces_code_and_denies_without_phone() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    publish_sms_browser_flow(
+        &ctx,
+        &realm,
+        serde_json::json!({
+            "resend_cooldown_secs": 0,
+            "max_resends": 1,
+            "message_template": "{code} signs you in to {realm}"
+        }),
+    )
+    .await;
+    let user = ctx
+        .app_state
+        .user_service
+        .create_user(realm.id, "judy", "password-123", None, false)
+        .await
+        .expect("create user");
+    ctx.app_state
+        .user_service
+        .create_user(realm.id, "mallory", "password-123", None, false)
+        .await
+        .expect("create user without phone");
+    ctx.app_state
+        .user_phone_number_service
+        .add_phone_number(user.id, realm.id, "+44 20 7946 0018", true, true)
+        .await
+        .expect("add phone");
+
+    let cookie = start_login(&ctx).await;
+    execute_ok(
+        &ctx,
+        &cookie,
+        serde_json::json!({ "username": "judy", "password": "password-123" }),
+    )
+    .await;
+    let first_code = last_code(&ctx).await;
+
+    let resent = execute_ok(&ctx, &cookie, serde_json::json!({ "action": "resend" })).await;
+    assert_eq!(
+        resent["context"]["resend_message"],
+        "A new code has been sent."
+    );
+    // The single allowed resend is used up.
+    assert_eq!(resent["context"]["can_resend"], false);
+    let messages = outbox(&ctx).await;
+    assert_eq!(messages.len(), 2);
+    assert!(messages[1]["message"]
+        .as_str()
+        .expect("message")
+        .ends_with("signs you in to master"));
+    let second_code = last_code(&ctx).await;
+
+    let limited = execute_ok(&ctx, &cookie, serde_json::json!({ "action": "resend" })).await;
+    assert_eq!(
+        limited["context"]["error"],
+        "No more codes can be sent for this sign-in."
+    );
+
+    if first_code != second_code {
+        let stale = execute_ok(&ctx, &cookie, serde_json::json!({ "otp": first_code })).await;
+        assert_eq!(stale["context"]["error"], "Invalid verification code.");
+    }
+    let done = execute_ok(&ctx, &cookie, serde_json::json!({ "otp": second_code })).await;
+    assert_eq!(done["status"], "redirect");
+
+    let cookie = start_login(&ctx).await;
+    let (status, denied) = execute(
+        &ctx,
+        &cookie,
+        serde_json::json!({ "username": "mallory", "password": "password-123" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", denied);
+    assert_eq!(denied["status"], "failure");
+    assert_eq!(outbox(&ctx).await.len(), 2);
+}
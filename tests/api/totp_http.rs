@@ -0,0 +1,378 @@
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{header, HeaderMap, Request, StatusCode};
+use chrono::Utc;
+use http_body_util::BodyExt;
+use serial_test::serial;
+use std::net::{Ipv4Addr, SocketAddr};
+use uuid::Uuid;
+
+use reauth::application::flow_manager::UpdateDraftRequest;
+use reauth::application::rbac_service::CreateRolePayload;
+use reauth::application::realm_service::CreateRealmPayload;
+use reauth::bootstrap::app_state::SetupState;
+use reauth::constants::{DEFAULT_REALM_NAME, LOGIN_SESSION_COOKIE};
+use reauth::domain::permissions;
+use reauth::domain::realm::Realm;
+use reauth::domain::totp_credential::{decode_totp_secret, hotp_code, totp_step};
+
+use crate::support::TestContext;
+
+async fn json_body(response: axum::response::Response) -> serde_json::Value {
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("read body")
+        .to_bytes();
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+fn cookie_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .filter_map(|value| value.split(';').next())
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, value)| key.trim() == name && !value.trim().is_empty())
+        .map(|(_, value)| value.trim().to_string())
+}
+
+async fn setup_realm(ctx: &TestContext) -> Realm {
+    let realm = ctx
+        .app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: DEFAULT_REALM_NAME.to_string(),
+        })
+        .await
+        .expect("create realm");
+    *ctx.app_state.setup_state.write().await = SetupState::sealed();
+    realm
+}
+
+/// password -> totp_verify, falling back to totp_enroll for users without an app.
+async fn publish_totp_browser_flow(ctx: &TestContext, realm: &Realm) {
+    let flow_id = realm
+        .browser_flow_id
+        .as_ref()
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .expect("browser flow id");
+    let graph = serde_json::json!({
+        "nodes": [
+            { "id": "start", "type": "core.start", "data": { "config": {} } },
+            { "id": "auth-password", "type": "core.auth.password", "data": { "config": { "auth_type": "core.auth.password" } } },
+            { "id": "totp-verify", "type": "core.auth.totp_verify", "data": { "config": { "auth_type": "core.auth.totp_verify", "max_attempts": 3 } } },
+            { "id": "totp-enroll", "type": "core.auth.totp_enroll", "data": { "config": { "auth_type": "core.auth.totp_enroll", "allow_skip": false } } },
+            { "id": "allow", "type": "core.terminal.allow", "data": { "config": {} } },
+            { "id": "deny", "type": "core.terminal.deny", "data": { "config": { "is_failure": true } } }
+        ],
+        "edges": [
+            { "id": "e-start-password", "source": "start", "target": "auth-password", "sourceHandle": "next" },
+            { "id": "e-password-verify", "source": "auth-password", "target": "totp-verify", "sourceHandle": "success" },
+            { "id": "e-verify-allow", "source": "totp-verify", "target": "allow", "sourceHandle": "success" },
+            { "id": "e-verify-enroll", "source": "totp-verify", "target": "totp-enroll", "sourceHandle": "not_enrolled" },
+            { "id": "e-verify-deny", "source": "totp-verify", "target": "deny", "sourceHandle": "failure" },
+            { "id": "e-enroll-allow", "source": "totp-enroll", "target": "allow", "sourceHandle": "success" },
+            { "id": "e-enroll-skip", "source": "totp-enroll", "target": "allow", "sourceHandle": "skip" },
+            { "id": "e-enroll-deny", "source": "totp-enroll", "target": "deny", "sourceHandle": "failure" }
+        ]
+    });
+
+    ctx.app_state
+        .flow_manager
+        .update_draft(
+            flow_id,
+            UpdateDraftRequest {
+                name: None,
+                description: None,
+                graph_json: Some(graph),
+            },
+        )
+        .await
+        .expect("update draft");
+    ctx.app_state
+        .flow_manager
+        .publish_flow(realm.id, flow_id)
+        .await
+        .expect("publish flow");
+}
+
+async fn admin_token(ctx: &TestContext, realm_id: Uuid) -> String {
+    let user = ctx
+        .app_state
+        .user_service
+        .create_user(realm_id, "user-writer", "password", None, false)
+        .await
+        .expect("create writer");
+    let role = ctx
+        .app_state
+        .rbac_service
+        .create_role(
+            realm_id,
+            CreateRolePayload {
+                name: "user-writer".to_string(),
+                description: None,
+                client_id: None,
+            },
+        )
+        .await
+        .expect("create role");
+    ctx.app_state
+        .rbac_service
+        .assign_permission_to_role(realm_id, role.id, permissions::USER_WRITE.to_string())
+        .await
+        .expect("assign user write");
+    ctx.app_state
+        .rbac_service
+        .assign_role_to_user(realm_id, user.id, role.id)
+        .await
+        .expect("assign role");
+    let (login, _) = ctx
+        .app_state
+        .auth_service
+        .create_session(&user, None, None, None)
+        .await
+        .expect("create session");
+    login.access_token
+}
+
+fn with_connect_info(mut request: Request<Body>) -> Request<Body> {
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 3000))));
+    request
+}
+
+/// Starts a browser login and returns the login session cookie.
+async fn start_login(ctx: &TestContext) -> String {
+    let response = ctx
+        .request(with_connect_info(
+            Request::builder()
+                .uri(format!("/api/realms/{}/auth/login", DEFAULT_REALM_NAME))
+                .body(Body::empty())
+                .unwrap(),
+        ))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let session_id =
+        cookie_value(response.headers(), LOGIN_SESSION_COOKIE).expect("login session cookie");
+    format!("{}={}", LOGIN_SESSION_COOKIE, session_id)
+}
+
+async fn execute(
+    ctx: &TestContext,
+    cookie: &str,
+    payload: serde_json::Value,
+) -> (StatusCode, serde_json::Value) {
+    let response = ctx
+        .request(with_connect_info(
+            Request::builder()
+                .method("POST")
+                .uri(format!(
+                    "/api/realms/{}/auth/login/execute",
+                    DEFAULT_REALM_NAME
+                ))
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(header::COOKIE, cookie)
+                .body(Body::from(payload.to_string()))
+                .unwrap(),
+        ))
+        .await;
+    (response.status(), json_body(response).await)
+}
+
+async fn execute_ok(
+    ctx: &TestContext,
+    cookie: &str,
+    payload: serde_json::Value,
+) -> serde_json::Value {
+    let (status, body) = execute(ctx, cookie, payload).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    body
+}
+
+fn code_for(secret: &str, offset: i64) -> String {
+    let bytes = decode_totp_secret(secret).expect("secret");
+    let step = totp_step(Utc::now(), 30) + offset;
+    hotp_code(&bytes, step as u64, 6)
+}
+
+/// `(event_type, payload)` of the `mfa.*` outbox rows, oldest first.
+async fn mfa_events(ctx: &TestContext) -> Vec<(String, serde_json::Value)> {
+    let database_url = ctx.app_state.settings.read().await.database.url.clone();
+    let pool = sqlx::SqlitePool::connect(&database_url)
+        .await
+        .expect("connect");
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT event_type, payload_json FROM event_outbox WHERE event_type LIKE 'mfa.%' ORDER BY occurred_at",
+    )
+    .fetch_all(&pool)
+    .await
+    .expect("outbox rows");
+    pool.close().await;
+    rows.into_iter()
+        .map(|(event_type, payload)| (event_type, serde_json::from_str(&payload).expect("payload")))
+        .collect()
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn totp_enroll_then_verify_rejects_replay_and_admin_can_remove() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    publish_totp_browser_flow(&ctx, &realm).await;
+    let user = ctx
+        .app_state
+        .user_service
+        .create_user(realm.id, "grace", "password-123", None, false)
+        .await
+        .expect("create user");
+    let credentials = || {
+        ctx.app_state
+            .user_credentials_service
+            .list_credentials(realm.id, user.id)
+    };
+
+    // First login: no authenticator yet, so the flow routes to enrollment.
+    let cookie = start_login(&ctx).await;
+    let challenge = execute_ok(
+        &ctx,
+        &cookie,
+        serde_json::json!({ "username": "grace", "password": "password-123" }),
+    )
+    .await;
+    assert_eq!(challenge["challengeName"], "core.auth.totp_enroll");
+    let context = &challenge["context"];
+    assert_eq!(context["template_key"], "totp_enroll");
+    let otpauth_uri = context["otpauth_uri"].as_str().expect("otpauth uri");
+    assert!(otpauth_uri.starts_with("otpauth://totp/master:grace?secret="));
+    let secret = context["secret"].as_str().expect("secret").to_string();
+
+    let rejected = execute_ok(&ctx, &cookie, serde_json::json!({ "otp": "000000" })).await;
+    assert_eq!(rejected["challengeName"], "core.auth.totp_enroll");
+    assert!(rejected["context"]["error"].as_str().is_some());
+    // The page keeps offering the same secret until enrollment succeeds.
+    assert_eq!(rejected["context"]["secret"], secret.as_str());
+    // Skipping is disabled on this node.
+    let skipped = execute_ok(&ctx, &cookie, serde_json::json!({ "action": "skip" })).await;
+    assert_eq!(skipped["challengeName"], "core.auth.totp_enroll");
+
+    let enroll_code = code_for(&secret, 0);
+    let done = execute_ok(&ctx, &cookie, serde_json::json!({ "otp": enroll_code })).await;
+    assert_eq!(done["status"], "redirect");
+
+    let summary = credentials().await.expect("credentials");
+    let totp = summary.totp.expect("totp enrolled");
+    assert!(totp.last_used_at.is_some());
+    let events = mfa_events(&ctx).await;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].0, "mfa.enrolled");
+    assert_eq!(events[0].1["data"]["method"], "totp");
+    assert_eq!(events[0].1["data"]["user_id"], serde_json::json!(user.id));
+
+    // Second login verifies instead; the enrollment code cannot be replayed.
+    let cookie = start_login(&ctx).await;
+    let challenge = execute_ok(
+        &ctx,
+        &cookie,
+        serde_json::json!({ "username": "grace", "password": "password-123" }),
+    )
+    .await;
+    assert_eq!(challenge["challengeName"], "core.auth.totp_verify");
+    assert_eq!(challenge["context"]["template_key"], "mfa");
+
+    let replayed = execute_ok(&ctx, &cookie, serde_json::json!({ "otp": enroll_code })).await;
+    assert_eq!(replayed["challengeName"], "core.auth.totp_verify");
+    assert_eq!(replayed["context"]["error"], "Invalid authentication code.");
+
+    // The next code is inside the default one-step drift window.
+    let done = execute_ok(
+        &ctx,
+        &cookie,
+        serde_json::json!({ "otp": code_for(&secret, 1) }),
+    )
+    .await;
+    assert_eq!(done["status"], "redirect");
+
+    let token = admin_token(&ctx, realm.id).await;
+    let remove = || {
+        Request::builder()
+            .method("DELETE")
+            .uri(format!(
+                "/api/realms/{}/users/{}/credentials/totp",
+                DEFAULT_REALM_NAME, user.id
+            ))
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap()
+    };
+    let response = ctx.request(remove()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(credentials().await.expect("credentials").totp.is_none());
+    let response = ctx.request(remove()).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let events = mfa_events(&ctx).await;
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[1].0, "mfa.removed");
+    assert_eq!(events[1].1["data"]["method"], "totp");
+    assert_eq!(events[1].1["realm_id"], serde_json::json!(realm.id));
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn totp_verify_fails_after_max_attempts() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    publish_totp_browser_flow(&ctx, &realm).await;
+    let user = ctx
+        .app_state
+        .user_service
+        .create_user(realm.id, "heidi", "password-123", None, false)
+        .await
+        .expect("create user");
+
+    let cookie = start_login(&ctx).await;
+    let challenge = execute_ok(
+        &ctx,
+        &cookie,
+        serde_json::json!({ "username": "heidi", "password": "password-123" }),
+    )
+    .await;
+    let secret = challenge["context"]["secret"]
+        .as_str()
+        .expect("secret")
+        .to_string();
+    let done = execute_ok(
+        &ctx,
+        &cookie,
+        serde_json::json!({ "otp": code_for(&secret, 0) }),
+    )
+    .await;
+    assert_eq!(done["status"], "redirect");
+    assert!(ctx
+        .app_state
+        .user_credentials_service
+        .list_credentials(realm.id, user.id)
+        .await
+        .expect("credentials")
+        .totp
+        .is_some());
+
+    let cookie = start_login(&ctx).await;
+    execute_ok(
+        &ctx,
+        &cookie,
+        serde_json::json!({ "username": "heidi", "password": "password-123" }),
+    )
+    .await;
+    for _ in 0..2 {
+        let rejected = execute_ok(&ctx, &cookie, serde_json::json!({ "otp": "abcdef" })).await;
+        assert_eq!(rejected["challengeName"], "core.auth.totp_verify");
+    }
+    let (status, denied) = execute(&ctx, &cookie, serde_json::json!({ "otp": "abcdef" })).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", denied);
+    assert_eq!(denied["status"], "failure");
+}
@@ -121,6 +121,10 @@ async fn link_federated_identity(
                 button_color: None,
                 sort_order: Some(0),
                 enabled: Some(true),
+                saml_metadata_url: None,
+                saml_metadata_xml: None,
+                saml_signing_certificates: None,
+                saml_name_id_format: None,
             },
         )
         .await
@@ -516,6 +520,7 @@ async fn user_credentials_can_unlink_last_federated_factor_when_guard_disabled()
                 access_token_ttl_secs: None,
                 refresh_token_ttl_secs: None,
                 pkce_required_public_clients: None,
+                signing_algorithm: None,
                 lockout_threshold: None,
                 lockout_duration_secs: None,
                 registration_enabled: None,
@@ -0,0 +1,737 @@
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{header, Request, StatusCode};
+use http_body_util::BodyExt;
+use serial_test::serial;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use uuid::Uuid;
+
+use reauth::application::flow_manager::templates::FlowTemplates;
+use reauth::application::flow_manager::UpdateDraftRequest;
+use reauth::application::rbac_service::CreateRolePayload;
+use reauth::application::realm_service::CreateRealmPayload;
+use reauth::constants::DEFAULT_REALM_NAME;
+use reauth::domain::ldap::ber::element_length;
+use reauth::domain::ldap::{
+    normalize_dn, LdapMessage, LdapResult, ModifyOperation, ProtocolOp, SearchEntry, SearchScope,
+    RESULT_INSUFFICIENT_ACCESS_RIGHTS, RESULT_INVALID_CREDENTIALS, RESULT_NO_SUCH_OBJECT,
+};
+use reauth::domain::oidc::{OidcClient, TokenEndpointAuthMethod};
+use reauth::domain::permissions;
+use reauth::domain::realm::Realm;
+use reauth::error::Error;
+
+use crate::support::TestContext;
+
+const ADMIN_DN: &str = "cn=admin,dc=example,dc=com";
+const ADMIN_PASSWORD: &str = "admin-secret";
+const PEOPLE_DN: &str = "ou=people,dc=example,dc=com";
+const STAFF_GROUP_DN: &str = "cn=staff,ou=groups,dc=example,dc=com";
+
+/// An in-process LDAP server holding entries in memory. It answers simple
+/// binds, searches (by scope and filter) and modifies, which is all user
+/// federation uses. Searches and modifies need the admin bind.
+#[derive(Clone, Default)]
+struct LdapStub {
+    entries: Arc<Mutex<Vec<(SearchEntry, String)>>>,
+    modifications: Arc<Mutex<Vec<(String, String)>>>,
+}
+
+impl LdapStub {
+    async fn start() -> (Self, String) {
+        let stub = Self::default();
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind stub");
+        let url = format!("ldap://{}", listener.local_addr().expect("stub addr"));
+        let server = stub.clone();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(server.clone().serve(socket));
+            }
+        });
+        (stub, url)
+    }
+
+    fn add_person(&self, uid: &str, password: &str, attributes: &[(&str, &[&str])]) {
+        let mut entry = SearchEntry::new(&format!("uid={},{}", uid, PEOPLE_DN))
+            .with_values("objectClass", &["inetOrgPerson", "organizationalPerson"])
+            .with_values("uid", &[uid])
+            .with_values("entryUUID", &[&Uuid::new_v4().to_string()]);
+        for (name, values) in attributes {
+            entry = entry.with_values(name, values);
+        }
+        self.entries
+            .lock()
+            .unwrap()
+            .push((entry, password.to_string()));
+    }
+
+    fn set_attribute(&self, uid: &str, name: &str, values: &[&str]) {
+        let dn = normalize_dn(&format!("uid={},{}", uid, PEOPLE_DN));
+        let mut entries = self.entries.lock().unwrap();
+        let (entry, _) = entries
+            .iter_mut()
+            .find(|(entry, _)| normalize_dn(&entry.dn) == dn)
+            .expect("entry");
+        entry.set(
+            name,
+            values
+                .iter()
+                .map(|value| value.as_bytes().to_vec())
+                .collect(),
+        );
+    }
+
+    fn set_password(&self, uid: &str, password: &str) {
+        let dn = normalize_dn(&format!("uid={},{}", uid, PEOPLE_DN));
+        let mut entries = self.entries.lock().unwrap();
+        let (_, current) = entries
+            .iter_mut()
+            .find(|(entry, _)| normalize_dn(&entry.dn) == dn)
+            .expect("entry");
+        *current = password.to_string();
+    }
+
+    fn first_value(&self, uid: &str, name: &str) -> Option<String> {
+        let dn = normalize_dn(&format!("uid={},{}", uid, PEOPLE_DN));
+        let entries = self.entries.lock().unwrap();
+        entries
+            .iter()
+            .find(|(entry, _)| normalize_dn(&entry.dn) == dn)
+            .and_then(|(entry, _)| entry.first_string(name))
+    }
+
+    fn modified_attributes(&self) -> Vec<(String, String)> {
+        self.modifications.lock().unwrap().clone()
+    }
+
+    async fn serve(self, mut socket: tokio::net::TcpStream) {
+        let mut buffer = Vec::new();
+        let mut bound_dn = String::new();
+        loop {
+            let Some(len) = element_length(&buffer).expect("framing") else {
+                let mut chunk = [0u8; 4096];
+                match socket.read(&mut chunk).await {
+                    Ok(0) | Err(_) => return,
+                    Ok(read) => buffer.extend_from_slice(&chunk[..read]),
+                }
+                continue;
+            };
+            let request = LdapMessage::decode(&buffer[..len]).expect("ldap message");
+            buffer.drain(..len);
+
+            let responses = match request.op {
+                ProtocolOp::BindRequest(bind) => {
+                    let result = self.bind(&bind.name, &bind.password);
+                    if result.is_success() {
+                        bound_dn = normalize_dn(&bind.name);
+                    }
+                    vec![ProtocolOp::BindResponse(result)]
+                }
+                ProtocolOp::SearchRequest(search) => {
+                    if bound_dn != normalize_dn(ADMIN_DN) {
+                        vec![ProtocolOp::SearchResultDone(LdapResult::with_code(
+                            RESULT_INSUFFICIENT_ACCESS_RIGHTS,
+                            "admin bind required",
+                        ))]
+                    } else {
+                        let base = normalize_dn(&search.base_dn);
+                        let entries = self.entries.lock().unwrap();
+                        let mut responses: Vec<ProtocolOp> = entries
+                            .iter()
+                            .map(|(entry, _)| entry)
+                            .filter(|entry| in_scope(&normalize_dn(&entry.dn), &base, search.scope))
+                            .filter(|entry| search.filter.matches(entry))
+                            .cloned()
+                            .map(ProtocolOp::SearchResultEntry)
+                            .collect();
+                        responses.push(ProtocolOp::SearchResultDone(LdapResult::success()));
+                        responses
+                    }
+                }
+                ProtocolOp::ModifyRequest(modify) => {
+                    vec![ProtocolOp::ModifyResponse(self.modify(
+                        &bound_dn,
+                        &modify.dn,
+                        modify.changes,
+                    ))]
+                }
+                ProtocolOp::UnbindRequest => return,
+                other => panic!("unexpected LDAP request {:?}", other),
+            };
+            for op in responses {
+                let message = LdapMessage::new(request.message_id, op);
+                if socket.write_all(&message.encode()).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn bind(&self, dn: &str, password: &str) -> LdapResult {
+        if dn.is_empty() && password.is_empty() {
+            return LdapResult::success();
+        }
+        if normalize_dn(dn) == normalize_dn(ADMIN_DN) && password == ADMIN_PASSWORD {
+            return LdapResult::success();
+        }
+        let dn = normalize_dn(dn);
+        let entries = self.entries.lock().unwrap();
+        match entries
+            .iter()
+            .find(|(entry, _)| normalize_dn(&entry.dn) == dn)
+        {
+            Some((_, stored)) if !password.is_empty() && stored == password => {
+                LdapResult::success()
+            }
+            _ => LdapResult::with_code(RESULT_INVALID_CREDENTIALS, "invalid credentials"),
+        }
+    }
+
+    fn modify(
+        &self,
+        bound_dn: &str,
+        dn: &str,
+        changes: Vec<reauth::domain::ldap::Modification>,
+    ) -> LdapResult {
+        if bound_dn != normalize_dn(ADMIN_DN) {
+            return LdapResult::with_code(RESULT_INSUFFICIENT_ACCESS_RIGHTS, "admin bind required");
+        }
+        let target = normalize_dn(dn);
+        let mut entries = self.entries.lock().unwrap();
+        let Some((entry, password)) = entries
+            .iter_mut()
+            .find(|(entry, _)| normalize_dn(&entry.dn) == target)
+        else {
+            return LdapResult::with_code(RESULT_NO_SUCH_OBJECT, "no such entry");
+        };
+        for change in changes {
+            assert_eq!(change.operation, ModifyOperation::Replace);
+            let value = change
+                .values
+                .first()
+                .map(|value| String::from_utf8(value.clone()).expect("utf-8 value"))
+                .unwrap_or_default();
+            if change.attribute.eq_ignore_ascii_case("userPassword") {
+                *password = value.clone();
+            } else {
+                entry.set(&change.attribute, change.values.clone());
+            }
+            self.modifications
+                .lock()
+                .unwrap()
+                .push((change.attribute, value));
+        }
+        LdapResult::success()
+    }
+}
+
+fn in_scope(dn: &str, base: &str, scope: SearchScope) -> bool {
+    match scope {
+        SearchScope::Base => dn == base,
+        SearchScope::OneLevel => dn.split_once(',').is_some_and(|(_, parent)| parent == base),
+        SearchScope::Subtree => dn == base || dn.ends_with(&format!(",{}", base)),
+    }
+}
+
+async fn json_body(response: axum::response::Response) -> serde_json::Value {
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("read body")
+        .to_bytes();
+    serde_json::from_slice(&bytes).expect("json body")
+}
+
+async fn setup_realm(ctx: &TestContext) -> Realm {
+    let realm = ctx
+        .app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: DEFAULT_REALM_NAME.to_string(),
+        })
+        .await
+        .expect("create realm");
+    let flow_id = realm
+        .direct_grant_flow_id
+        .as_ref()
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .expect("direct grant flow id");
+    ctx.app_state
+        .flow_manager
+        .update_draft(
+            flow_id,
+            UpdateDraftRequest {
+                name: None,
+                description: None,
+                graph_json: Some(FlowTemplates::direct_grant_flow()),
+            },
+        )
+        .await
+        .expect("update draft");
+    ctx.app_state
+        .flow_manager
+        .publish_flow(realm.id, flow_id)
+        .await
+        .expect("publish flow");
+    realm
+}
+
+async fn setup_admin_token(ctx: &TestContext, realm_id: Uuid) -> String {
+    let user = ctx
+        .app_state
+        .user_service
+        .create_user(realm_id, "federation-admin", "password", None, false)
+        .await
+        .expect("create admin");
+    let role = ctx
+        .app_state
+        .rbac_service
+        .create_role(
+            realm_id,
+            CreateRolePayload {
+                name: "federation-admin".to_string(),
+                description: None,
+                client_id: None,
+            },
+        )
+        .await
+        .expect("create role");
+    for permission in [permissions::REALM_READ, permissions::REALM_WRITE] {
+        ctx.app_state
+            .rbac_service
+            .assign_permission_to_role(realm_id, role.id, permission.to_string())
+            .await
+            .expect("assign permission");
+    }
+    ctx.app_state
+        .rbac_service
+        .assign_role_to_user(realm_id, user.id, role.id)
+        .await
+        .expect("assign role");
+    let (login, _) = ctx
+        .app_state
+        .auth_service
+        .create_session(&user, None, None, None)
+        .await
+        .expect("create session");
+    login.access_token
+}
+
+async fn create_staff_role(ctx: &TestContext, realm_id: Uuid) -> Uuid {
+    ctx.app_state
+        .rbac_service
+        .create_role(
+            realm_id,
+            CreateRolePayload {
+                name: "staff".to_string(),
+                description: None,
+                client_id: None,
+            },
+        )
+        .await
+        .expect("create staff role")
+        .id
+}
+
+/// Registers a confidential client allowed to use the password grant and
+/// returns its secret.
+async fn register_client(ctx: &TestContext, realm_id: Uuid) -> String {
+    let mut client = OidcClient {
+        id: Uuid::new_v4(),
+        realm_id,
+        client_id: "directory-app".to_string(),
+        client_secret: None,
+        redirect_uris: serde_json::to_string(&vec!["http://localhost/callback"])
+            .expect("redirect_uris json"),
+        scopes: serde_json::to_string(&vec!["openid", "profile"]).expect("scopes json"),
+        web_origins: "[]".to_string(),
+        managed_by_config: false,
+        token_endpoint_auth_method: TokenEndpointAuthMethod::ClientSecretPost,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: None,
+        frontchannel_logout_uri: None,
+        token_exchange_policy: None,
+        direct_grant_enabled: true,
+        require_pushed_authorization_requests: false,
+    };
+    ctx.app_state
+        .oidc_service
+        .register_client(&mut client)
+        .await
+        .expect("register client")
+        .expect("client secret")
+}
+
+async fn password_grant(
+    ctx: &TestContext,
+    secret: &str,
+    username: &str,
+    password: &str,
+) -> (StatusCode, serde_json::Value) {
+    let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+    for (key, value) in [
+        ("grant_type", "password"),
+        ("client_id", "directory-app"),
+        ("client_secret", secret),
+        ("username", username),
+        ("password", password),
+        ("scope", "openid profile"),
+    ] {
+        serializer.append_pair(key, value);
+    }
+    let mut request = Request::builder()
+        .method("POST")
+        .uri(format!("/api/realms/{}/oidc/token", DEFAULT_REALM_NAME))
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(Body::from(serializer.finish()))
+        .unwrap();
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 3000))));
+    let response = ctx.request(request).await;
+    (response.status(), json_body(response).await)
+}
+
+async fn admin_request(
+    ctx: &TestContext,
+    token: &str,
+    method: &str,
+    path: &str,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let builder = Request::builder()
+        .method(method)
+        .uri(format!(
+            "/api/realms/{}/user-federation{}",
+            DEFAULT_REALM_NAME, path
+        ))
+        .header(header::AUTHORIZATION, format!("Bearer {}", token));
+    let request = match body {
+        Some(body) => builder
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string())),
+        None => builder.body(Body::empty()),
+    }
+    .expect("request");
+    let response = ctx.request(request).await;
+    let status = response.status();
+    let bytes = response
+        .into_body()
+        .collect()
+        .await
+        .expect("read body")
+        .to_bytes();
+    let body = if bytes.is_empty() {
+        serde_json::Value::Null
+    } else {
+        serde_json::from_slice(&bytes).expect("json body")
+    };
+    (status, body)
+}
+
+async fn create_provider(ctx: &TestContext, token: &str, url: &str) -> Uuid {
+    let (status, body) = admin_request(
+        ctx,
+        token,
+        "POST",
+        "",
+        Some(serde_json::json!({
+            "name": "corp",
+            "vendor": "ldap",
+            "connection_url": url,
+            "bind_dn": ADMIN_DN,
+            "bind_credential": ADMIN_PASSWORD,
+            "users_dn": PEOPLE_DN,
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+    assert_eq!(body["bind_credential_set"], true);
+    assert!(body.get("bind_credential").is_none());
+    assert_eq!(body["edit_mode"], "read_only");
+    assert_eq!(body["username_attribute"], "uid");
+    Uuid::parse_str(body["id"].as_str().expect("provider id")).expect("uuid")
+}
+
+async fn add_staff_mapper(ctx: &TestContext, token: &str, provider_id: Uuid, role_id: Uuid) {
+    let (status, body) = admin_request(
+        ctx,
+        token,
+        "POST",
+        &format!("/{}/mappers", provider_id),
+        Some(serde_json::json!({
+            "name": "staff-role",
+            "type": "group_role",
+            "group_dn": "CN=Staff, OU=Groups, DC=example, DC=com",
+            "role_id": role_id,
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+}
+
+async fn direct_role_ids(ctx: &TestContext, realm_id: Uuid, user_id: Uuid) -> Vec<Uuid> {
+    ctx.app_state
+        .rbac_service
+        .get_direct_role_ids_for_user(realm_id, user_id)
+        .await
+        .expect("roles")
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn directory_users_are_imported_on_login_and_authenticate_by_bind() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let token = setup_admin_token(&ctx, realm.id).await;
+    let staff_role = create_staff_role(&ctx, realm.id).await;
+    let secret = register_client(&ctx, realm.id).await;
+
+    let (ldap, url) = LdapStub::start().await;
+    ldap.add_person(
+        "jdoe",
+        "directory-pass",
+        &[
+            ("mail", &["jdoe@example.com"]),
+            ("givenName", &["Jane"]),
+            ("sn", &["Doe"]),
+            ("memberOf", &[STAFF_GROUP_DN]),
+        ],
+    );
+    let provider_id = create_provider(&ctx, &token, &url).await;
+    add_staff_mapper(&ctx, &token, provider_id, staff_role).await;
+
+    let (status, body) = admin_request(
+        &ctx,
+        &token,
+        "POST",
+        &format!("/{}/test-connection", provider_id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["ok"], true, "{}", body);
+
+    let (status, body) = password_grant(&ctx, &secret, "jdoe", "wrong-pass").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    assert_eq!(body["error"], "invalid_grant");
+    assert!(ctx
+        .app_state
+        .user_service
+        .find_by_username(&realm.id, "jdoe")
+        .await
+        .expect("lookup")
+        .is_none());
+
+    let (status, body) = password_grant(&ctx, &secret, "jdoe", "directory-pass").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let user = ctx
+        .app_state
+        .user_service
+        .find_by_username(&realm.id, "jdoe")
+        .await
+        .expect("lookup")
+        .expect("imported user");
+    assert_eq!(user.first_name.as_deref(), Some("Jane"));
+    assert_eq!(user.last_name.as_deref(), Some("Doe"));
+    let emails = ctx
+        .app_state
+        .user_email_service
+        .list_emails(user.id)
+        .await
+        .expect("emails");
+    assert_eq!(emails.len(), 1);
+    assert_eq!(emails[0].email, "jdoe@example.com");
+    assert!(emails[0].is_primary);
+    assert_eq!(
+        direct_role_ids(&ctx, realm.id, user.id).await,
+        vec![staff_role]
+    );
+
+    // The directory decides: a password changed there works at once and
+    // the old one stops working.
+    ldap.set_password("jdoe", "rotated-pass");
+    ldap.set_attribute("jdoe", "memberOf", &[]);
+    let (status, _) = password_grant(&ctx, &secret, "jdoe", "directory-pass").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, body) = password_grant(&ctx, &secret, "jdoe", "rotated-pass").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(direct_role_ids(&ctx, realm.id, user.id).await.is_empty());
+
+    // Read-only providers own passwords and mapped profile fields.
+    let err = ctx
+        .app_state
+        .user_service
+        .update_password(realm.id, user.id, "local-Pass-123", true)
+        .await
+        .expect_err("read-only password");
+    assert!(matches!(err, Error::Validation(_)), "{:?}", err);
+    let err = ctx
+        .app_state
+        .user_service
+        .update_profile(
+            realm.id,
+            user.id,
+            None,
+            Some(Some("Janet".to_string())),
+            None,
+        )
+        .await
+        .expect_err("read-only profile");
+    assert!(matches!(err, Error::Validation(_)), "{:?}", err);
+    assert!(ldap.modified_attributes().is_empty());
+
+    // An unreachable directory neither accepts nor rejects the password.
+    let (status, body) = admin_request(
+        &ctx,
+        &token,
+        "PUT",
+        &format!("/{}", provider_id),
+        Some(serde_json::json!({ "connection_url": "ldap://127.0.0.1:1" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (status, body) = password_grant(&ctx, &secret, "jdoe", "rotated-pass").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    assert_eq!(
+        body["error_description"],
+        "The user directory is unavailable. Try again later."
+    );
+}
+
+#[tokio::test]
+#[serial(test_db)]
+async fn sync_imports_directory_users_and_writable_providers_accept_changes() {
+    let ctx = TestContext::new().await;
+    let realm = setup_realm(&ctx).await;
+    let token = setup_admin_token(&ctx, realm.id).await;
+    let staff_role = create_staff_role(&ctx, realm.id).await;
+
+    let (ldap, url) = LdapStub::start().await;
+    ldap.add_person(
+        "alice",
+        "alice-pass",
+        &[
+            ("mail", &["alice@example.com"]),
+            ("givenName", &["Alice"]),
+            ("memberOf", &[STAFF_GROUP_DN]),
+        ],
+    );
+    ldap.add_person("bob", "bob-pass", &[("givenName", &["Bob"])]);
+    ldap.add_person("federation-admin", "clash", &[]);
+    let provider_id = create_provider(&ctx, &token, &url).await;
+    add_staff_mapper(&ctx, &token, provider_id, staff_role).await;
+
+    let (status, body) = admin_request(
+        &ctx,
+        &token,
+        "POST",
+        &format!("/{}/sync", provider_id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["added"], 2, "{}", body);
+    assert_eq!(body["updated"], 0);
+    // The local admin has the same username but was never linked.
+    assert_eq!(body["failed"], 1);
+
+    let alice = ctx
+        .app_state
+        .user_service
+        .find_by_username(&realm.id, "alice")
+        .await
+        .expect("lookup")
+        .expect("alice");
+    assert_eq!(alice.first_name.as_deref(), Some("Alice"));
+    assert_eq!(
+        direct_role_ids(&ctx, realm.id, alice.id).await,
+        vec![staff_role]
+    );
+
+    ldap.set_attribute("alice", "givenName", &["Alicia"]);
+    ldap.set_attribute("alice", "memberOf", &[]);
+    let (status, body) = admin_request(
+        &ctx,
+        &token,
+        "POST",
+        &format!("/{}/sync", provider_id),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["added"], 0, "{}", body);
+    assert_eq!(body["updated"], 2);
+    let alice = ctx
+        .app_state
+        .user_service
+        .get_user_in_realm(realm.id, alice.id)
+        .await
+        .expect("alice");
+    assert_eq!(alice.first_name.as_deref(), Some("Alicia"));
+    assert!(direct_role_ids(&ctx, realm.id, alice.id).await.is_empty());
+
+    let (status, body) =
+        admin_request(&ctx, &token, "GET", &format!("/{}", provider_id), None).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["linked_user_count"], 2);
+    assert!(body["last_sync_at"].is_string());
+    assert_eq!(body["last_sync_error"], "1 entries failed to sync");
+
+    // Writable providers take password and profile changes.
+    let (status, body) = admin_request(
+        &ctx,
+        &token,
+        "PUT",
+        &format!("/{}", provider_id),
+        Some(serde_json::json!({ "edit_mode": "writable" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    ctx.app_state
+        .user_service
+        .update_password(realm.id, alice.id, "New-alice-pass-1", true)
+        .await
+        .expect("writable password");
+    ctx.app_state
+        .user_service
+        .update_profile(
+            realm.id,
+            alice.id,
+            None,
+            None,
+            Some(Some("Smith".to_string())),
+        )
+        .await
+        .expect("writable profile");
+    assert_eq!(
+        ldap.modified_attributes(),
+        vec![
+            ("userPassword".to_string(), "New-alice-pass-1".to_string()),
+            ("sn".to_string(), "Smith".to_string()),
+        ]
+    );
+    assert_eq!(ldap.first_value("alice", "sn").as_deref(), Some("Smith"));
+    let err = ctx
+        .app_state
+        .user_service
+        .update_username(realm.id, alice.id, "alice2".to_string())
+        .await
+        .expect_err("username stays with the directory");
+    assert!(matches!(err, Error::Validation(_)), "{:?}", err);
+
+    let (status, _) =
+        admin_request(&ctx, &token, "DELETE", &format!("/{}", provider_id), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    // Unlinked users become local users.
+    ctx.app_state
+        .user_service
+        .update_username(realm.id, alice.id, "alice2".to_string())
+        .await
+        .expect("local rename");
+}
@@ -7,7 +7,7 @@ use reauth::application::harbor::{
 };
 use reauth::application::rbac_service::CreateRolePayload;
 use reauth::application::realm_service::{CreateRealmPayload, UpdateRealmPayload};
-use reauth::domain::oidc::OidcClient;
+use reauth::domain::oidc::{OidcClient, TokenEndpointAuthMethod};
 use reauth::error::Error;
 use serde_json::{json, Value};
 use support::TestContext;
@@ -84,6 +84,14 @@ async fn harbor_import_conflict_skip_leaves_existing_client() {
         scopes: "[]".to_string(),
         web_origins: "[]".to_string(),
         managed_by_config: false,
+        token_endpoint_auth_method: TokenEndpointAuthMethod::None,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: None,
+        frontchannel_logout_uri: None,
+        token_exchange_policy: None,
+        direct_grant_enabled: false,
+        require_pushed_authorization_requests: false,
     };
     let _ = ctx
         .app_state
@@ -163,6 +171,14 @@ async fn harbor_full_realm_remaps_client_ids_in_flows_and_bindings() {
         scopes: "[]".to_string(),
         web_origins: "[]".to_string(),
         managed_by_config: false,
+        token_endpoint_auth_method: TokenEndpointAuthMethod::None,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: None,
+        frontchannel_logout_uri: None,
+        token_exchange_policy: None,
+        direct_grant_enabled: false,
+        require_pushed_authorization_requests: false,
     };
     let _ = ctx
         .app_state
@@ -321,6 +337,14 @@ async fn harbor_import_allows_same_client_id_in_different_realms() {
         scopes: "[\"openid\"]".to_string(),
         web_origins: "[]".to_string(),
         managed_by_config: false,
+        token_endpoint_auth_method: TokenEndpointAuthMethod::None,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: None,
+        frontchannel_logout_uri: None,
+        token_exchange_policy: None,
+        direct_grant_enabled: false,
+        require_pushed_authorization_requests: false,
     };
     let _ = ctx
         .app_state
@@ -502,6 +526,14 @@ async fn harbor_full_realm_imports_roles_and_remaps_client_roles() {
         scopes: "[]".to_string(),
         web_origins: "[]".to_string(),
         managed_by_config: false,
+        token_endpoint_auth_method: TokenEndpointAuthMethod::None,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: None,
+        frontchannel_logout_uri: None,
+        token_exchange_policy: None,
+        direct_grant_enabled: false,
+        require_pushed_authorization_requests: false,
     };
     let _ = ctx
         .app_state
@@ -665,6 +697,14 @@ async fn harbor_bootstrap_import_creates_new_realm_from_full_bundle() {
         scopes: "[\"openid\"]".to_string(),
         web_origins: "[]".to_string(),
         managed_by_config: false,
+        token_endpoint_auth_method: TokenEndpointAuthMethod::None,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: None,
+        frontchannel_logout_uri: None,
+        token_exchange_policy: None,
+        direct_grant_enabled: false,
+        require_pushed_authorization_requests: false,
     };
     let _ = ctx
         .app_state
@@ -812,6 +852,7 @@ async fn harbor_bootstrap_import_restores_realm_settings_and_flow_bindings() {
                 access_token_ttl_secs: Some(1800),
                 refresh_token_ttl_secs: Some(14400),
                 pkce_required_public_clients: Some(false),
+                signing_algorithm: None,
                 lockout_threshold: Some(7),
                 lockout_duration_secs: Some(1200),
                 registration_enabled: None,
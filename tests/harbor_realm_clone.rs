@@ -0,0 +1,388 @@
+mod support;
+
+use std::collections::HashMap;
+
+use reauth::application::harbor::{ConflictPolicy, ExportPolicy, HarborScope};
+use reauth::application::idp_service::CreateIdentityProviderRequest;
+use reauth::application::password_policy_service::UpdatePasswordPolicyPayload;
+use reauth::application::rbac_service::{CreateGroupPayload, CreateRolePayload};
+use reauth::application::realm_email_settings_service::UpdateRealmEmailSettingsPayload;
+use reauth::application::realm_service::CreateRealmPayload;
+use reauth::application::webhook_service::CreateWebhookPayload;
+use reauth::domain::identity_provider::IdentityProviderProtocol;
+use reauth::domain::pagination::PageRequest;
+use reauth::domain::realm::Realm;
+use support::TestContext;
+use uuid::Uuid;
+
+const REDACTED: &str = "${REDACTED}";
+
+async fn create_realm(ctx: &TestContext, name: &str) -> Realm {
+    ctx.app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: name.to_string(),
+        })
+        .await
+        .expect("create realm")
+}
+
+async fn create_identity_provider(ctx: &TestContext, realm_id: Uuid, alias: &str) {
+    ctx.app_state
+        .identity_provider_service
+        .create(
+            realm_id,
+            CreateIdentityProviderRequest {
+                preset: None,
+                alias: alias.to_string(),
+                display_name: "Corporate SSO".to_string(),
+                protocol: IdentityProviderProtocol::Oauth2,
+                client_id: "reauth".to_string(),
+                client_secret: Some("idp-secret".to_string()),
+                issuer: None,
+                authorization_endpoint: Some("https://sso.example.com/authorize".to_string()),
+                token_endpoint: Some("https://sso.example.com/token".to_string()),
+                userinfo_endpoint: Some("https://sso.example.com/userinfo".to_string()),
+                jwks_uri: None,
+                scopes: Some(vec!["openid".to_string(), "email".to_string()]),
+                claim_mapping: None,
+                pkce_required: Some(true),
+                allow_login: Some(true),
+                allow_link: Some(true),
+                allow_jit_provisioning: Some(false),
+                allow_email_auto_link: Some(false),
+                require_verified_email: Some(true),
+                icon_ref: None,
+                button_color: None,
+                sort_order: Some(0),
+                enabled: Some(true),
+                saml_metadata_url: None,
+                saml_metadata_xml: None,
+                saml_signing_certificates: None,
+                saml_name_id_format: None,
+            },
+        )
+        .await
+        .expect("create identity provider");
+}
+
+async fn find_group_id(ctx: &TestContext, realm_id: Uuid, name: &str) -> Option<Uuid> {
+    ctx.app_state
+        .rbac_service
+        .list_groups(
+            realm_id,
+            PageRequest {
+                per_page: 200,
+                ..PageRequest::default()
+            },
+        )
+        .await
+        .expect("list groups")
+        .data
+        .into_iter()
+        .find(|group| group.name == name)
+        .map(|group| group.id)
+}
+
+#[tokio::test]
+async fn harbor_full_realm_clone_includes_new_resources() {
+    let ctx = TestContext::new_with_seed(false).await;
+    let source = create_realm(&ctx, "clone-source").await;
+    let target = create_realm(&ctx, "clone-target").await;
+
+    create_identity_provider(&ctx, source.id, "corp").await;
+
+    ctx.app_state
+        .webhook_service
+        .create_endpoint(
+            source.id,
+            CreateWebhookPayload {
+                name: "audit-sink".to_string(),
+                url: "https://hooks.example.com/reauth".to_string(),
+                description: Some("Audit forwarding".to_string()),
+                signing_secret: Some("whsec-source".to_string()),
+                custom_headers: HashMap::from([("X-Api-Key".to_string(), "key".to_string())]),
+                http_method: Some("PUT".to_string()),
+                subscriptions: vec!["user.created".to_string()],
+            },
+        )
+        .await
+        .expect("create webhook");
+
+    let role = ctx
+        .app_state
+        .rbac_service
+        .create_role(
+            source.id,
+            CreateRolePayload {
+                name: "deployer".to_string(),
+                description: None,
+                client_id: None,
+            },
+        )
+        .await
+        .expect("create role");
+    let engineering = ctx
+        .app_state
+        .rbac_service
+        .create_group(
+            source.id,
+            CreateGroupPayload {
+                name: "engineering".to_string(),
+                description: Some("All engineers".to_string()),
+                parent_id: None,
+            },
+        )
+        .await
+        .expect("create group");
+    let platform = ctx
+        .app_state
+        .rbac_service
+        .create_group(
+            source.id,
+            CreateGroupPayload {
+                name: "platform".to_string(),
+                description: None,
+                parent_id: Some(engineering.id),
+            },
+        )
+        .await
+        .expect("create child group");
+    ctx.app_state
+        .rbac_service
+        .assign_role_to_group(source.id, role.id, platform.id)
+        .await
+        .expect("assign group role");
+    let ada = ctx
+        .app_state
+        .user_service
+        .create_user(source.id, "ada", "Password123!", None, false)
+        .await
+        .expect("create user");
+    ctx.app_state
+        .rbac_service
+        .assign_user_to_group(source.id, ada.id, engineering.id)
+        .await
+        .expect("assign member");
+
+    ctx.app_state
+        .realm_email_settings_service
+        .update_settings(
+            source.id,
+            UpdateRealmEmailSettingsPayload {
+                enabled: Some(true),
+                from_address: Some("noreply@example.com".to_string()),
+                from_name: Some("ReAuth".to_string()),
+                reply_to_address: None,
+                smtp_host: Some("smtp.example.com".to_string()),
+                smtp_port: Some(587),
+                smtp_username: Some("mailer".to_string()),
+                smtp_password: Some("smtp-secret".to_string()),
+                smtp_security: Some("starttls".to_string()),
+            },
+        )
+        .await
+        .expect("update email settings");
+    ctx.app_state
+        .password_policy_service
+        .update_policy(
+            source.id,
+            UpdatePasswordPolicyPayload {
+                min_length: Some(14),
+                ..UpdatePasswordPolicyPayload::default()
+            },
+        )
+        .await
+        .expect("update password policy");
+
+    let bundle = ctx
+        .app_state
+        .harbor_service
+        .export_bundle(
+            source.id,
+            &source.name,
+            HarborScope::FullRealm,
+            ExportPolicy::Redact,
+            Some(vec![
+                "role".to_string(),
+                "groups".to_string(),
+                "idps".to_string(),
+                "webhooks".to_string(),
+                "realm_settings".to_string(),
+            ]),
+        )
+        .await
+        .expect("export bundle");
+
+    assert_eq!(bundle.manifest.schema_version, 2);
+    let idp = bundle
+        .resources
+        .iter()
+        .find(|resource| resource.key == "identity_provider")
+        .expect("identity provider resource");
+    assert_eq!(idp.data["client_secret"], REDACTED);
+    let webhook = bundle
+        .resources
+        .iter()
+        .find(|resource| resource.key == "webhook")
+        .expect("webhook resource");
+    assert_eq!(webhook.data["signing_secret"], REDACTED);
+    assert_eq!(webhook.data["custom_headers"]["X-Api-Key"], REDACTED);
+    let settings = bundle
+        .resources
+        .iter()
+        .find(|resource| resource.key == "realm_settings")
+        .expect("realm settings resource");
+    assert_eq!(settings.data["email"]["smtp_password"], REDACTED);
+    let groups = bundle
+        .resources
+        .iter()
+        .filter(|resource| resource.key == "group")
+        .collect::<Vec<_>>();
+    assert_eq!(groups.len(), 1, "one resource per root group");
+    assert_eq!(groups[0].data["children"][0]["name"], "platform");
+    assert_eq!(groups[0].data["members"][0], "ada");
+
+    ctx.app_state
+        .user_service
+        .create_user(target.id, "ada", "Password123!", None, false)
+        .await
+        .expect("create target user");
+
+    let result = ctx
+        .app_state
+        .harbor_service
+        .import_bundle(
+            target.id,
+            HarborScope::FullRealm,
+            bundle,
+            false,
+            ConflictPolicy::Overwrite,
+        )
+        .await
+        .expect("import bundle");
+    assert!(!result.dry_run);
+
+    let imported_idp = ctx
+        .app_state
+        .identity_provider_service
+        .get_domain_by_alias(target.id, "corp")
+        .await
+        .expect("imported identity provider");
+    assert_eq!(imported_idp.display_name, "Corporate SSO");
+    assert!(
+        !imported_idp.enabled,
+        "a provider without its secret is imported disabled"
+    );
+
+    let endpoints = ctx
+        .app_state
+        .webhook_service
+        .list_endpoints(target.id)
+        .await
+        .expect("list webhooks");
+    let endpoint = endpoints
+        .iter()
+        .find(|details| details.endpoint.name == "audit-sink")
+        .expect("imported webhook");
+    assert_eq!(endpoint.endpoint.http_method, "PUT");
+    assert_ne!(endpoint.endpoint.signing_secret, REDACTED);
+    assert!(endpoint.endpoint.custom_headers.is_empty());
+    assert!(endpoint
+        .subscriptions
+        .iter()
+        .any(|subscription| subscription.event_type == "user.created" && subscription.enabled));
+
+    let engineering_id = find_group_id(&ctx, target.id, "engineering")
+        .await
+        .expect("imported root group");
+    let platform_id = find_group_id(&ctx, target.id, "platform")
+        .await
+        .expect("imported child group");
+    let platform_group = ctx
+        .app_state
+        .rbac_service
+        .get_group(target.id, platform_id)
+        .await
+        .expect("child group");
+    assert_eq!(platform_group.parent_id, Some(engineering_id));
+    let role_ids = ctx
+        .app_state
+        .rbac_service
+        .get_group_role_ids(target.id, platform_id)
+        .await
+        .expect("group roles");
+    assert_eq!(role_ids.len(), 1);
+    let members = ctx
+        .app_state
+        .rbac_service
+        .get_group_member_ids(target.id, engineering_id)
+        .await
+        .expect("group members");
+    assert_eq!(members.len(), 1);
+
+    let email = ctx
+        .app_state
+        .realm_email_settings_service
+        .get_settings(target.id)
+        .await
+        .expect("email settings");
+    assert_eq!(email.smtp_host.as_deref(), Some("smtp.example.com"));
+    assert_eq!(email.smtp_password, None);
+    assert!(!email.enabled);
+    let policy = ctx
+        .app_state
+        .password_policy_service
+        .get_policy(target.id)
+        .await
+        .expect("password policy");
+    assert_eq!(policy.min_length, 14);
+}
+
+#[tokio::test]
+async fn harbor_identity_provider_export_with_secrets_stays_enabled() {
+    let ctx = TestContext::new_with_seed(false).await;
+    let source = create_realm(&ctx, "idp-source").await;
+    let target = create_realm(&ctx, "idp-target").await;
+    create_identity_provider(&ctx, source.id, "corp").await;
+
+    let bundle = ctx
+        .app_state
+        .harbor_service
+        .export_bundle(
+            source.id,
+            &source.name,
+            HarborScope::IdentityProvider {
+                alias: "corp".to_string(),
+            },
+            ExportPolicy::IncludeSecrets,
+            None,
+        )
+        .await
+        .expect("export identity provider");
+    assert_eq!(bundle.resources[0].data["client_secret"], "idp-secret");
+
+    for dry_run in [true, false] {
+        ctx.app_state
+            .harbor_service
+            .import_bundle(
+                target.id,
+                HarborScope::IdentityProvider {
+                    alias: "corp".to_string(),
+                },
+                bundle.clone(),
+                dry_run,
+                ConflictPolicy::Skip,
+            )
+            .await
+            .expect("import identity provider");
+    }
+
+    let imported = ctx
+        .app_state
+        .identity_provider_service
+        .get_domain_by_alias(target.id, "corp")
+        .await
+        .expect("imported identity provider");
+    assert!(imported.enabled);
+}
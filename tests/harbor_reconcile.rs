@@ -0,0 +1,370 @@
+mod support;
+
+use std::collections::HashMap;
+
+use reauth::application::harbor::{
+    read_bundle_dir, HarborBundle, HarborExportType, HarborManifest, HarborPlanAction,
+    HarborReconcilePlan, HarborResourceBundle,
+};
+use reauth::application::rbac_service::CreateRolePayload;
+use reauth::application::realm_service::CreateRealmPayload;
+use reauth::application::webhook_service::CreateWebhookPayload;
+use reauth::domain::pagination::PageRequest;
+use reauth::domain::realm::Realm;
+use serde_json::{json, Value};
+use support::TestContext;
+use uuid::Uuid;
+
+async fn create_realm(ctx: &TestContext, name: &str) -> Realm {
+    ctx.app_state
+        .realm_service
+        .create_realm(CreateRealmPayload {
+            name: name.to_string(),
+        })
+        .await
+        .expect("create realm")
+}
+
+fn resource(key: &str, data: Value) -> HarborResourceBundle {
+    HarborResourceBundle {
+        key: key.to_string(),
+        data,
+        assets: Vec::new(),
+        meta: None,
+    }
+}
+
+fn bundle(selection: &[&str], resources: Vec<HarborResourceBundle>) -> HarborBundle {
+    HarborBundle {
+        manifest: HarborManifest {
+            version: "1.0".to_string(),
+            schema_version: 2,
+            exported_at: "2026-10-01T10:00:00Z".to_string(),
+            source_realm: "git".to_string(),
+            export_type: HarborExportType::FullRealm,
+            selection: Some(selection.iter().map(|key| key.to_string()).collect()),
+        },
+        resources,
+    }
+}
+
+fn ops_bundle(role_description: &str) -> HarborBundle {
+    bundle(
+        &["client", "role", "group"],
+        vec![
+            resource(
+                "client",
+                json!({
+                    "client_id": "ops-console",
+                    "client_secret": null,
+                    "redirect_uris": ["https://ops.example.com/callback"],
+                    "scopes": ["openid"],
+                    "web_origins": []
+                }),
+            ),
+            resource(
+                "role",
+                json!({
+                    "name": "operator",
+                    "description": role_description,
+                    "client_id": "ops-console",
+                    "permissions": []
+                }),
+            ),
+            resource(
+                "group",
+                json!({
+                    "name": "operations",
+                    "description": "On-call engineers",
+                    "roles": [{ "name": "operator", "client_id": "ops-console" }],
+                    "members": [],
+                    "children": []
+                }),
+            ),
+        ],
+    )
+}
+
+fn entry_action(plan: &HarborReconcilePlan, key: &str, name: &str) -> Option<HarborPlanAction> {
+    plan.entries
+        .iter()
+        .find(|entry| entry.key == key && entry.name == name)
+        .map(|entry| entry.action)
+}
+
+async fn find_group_id(ctx: &TestContext, realm_id: Uuid, name: &str) -> Option<Uuid> {
+    ctx.app_state
+        .rbac_service
+        .list_groups(
+            realm_id,
+            PageRequest {
+                per_page: 200,
+                ..PageRequest::default()
+            },
+        )
+        .await
+        .expect("list groups")
+        .data
+        .into_iter()
+        .find(|group| group.name == name)
+        .map(|group| group.id)
+}
+
+#[tokio::test]
+async fn harbor_reconcile_applies_in_one_transaction_and_converges() {
+    let ctx = TestContext::new_with_seed(false).await;
+    let realm = create_realm(&ctx, "reconcile-apply").await;
+    let harbor = &ctx.app_state.harbor_service;
+
+    let plan = harbor
+        .plan_reconcile(realm.id, ops_bundle("Runs deploys"), false)
+        .await
+        .expect("plan");
+    assert_eq!(plan.summary.create, 3);
+    assert_eq!(
+        entry_action(&plan, "role", "ops-console/operator"),
+        Some(HarborPlanAction::Create)
+    );
+    assert!(
+        find_group_id(&ctx, realm.id, "operations").await.is_none(),
+        "planning writes nothing"
+    );
+
+    let result = harbor
+        .apply_reconcile(realm.id, ops_bundle("Runs deploys"), false)
+        .await
+        .expect("apply");
+    assert!(result.applied);
+
+    let group_id = find_group_id(&ctx, realm.id, "operations")
+        .await
+        .expect("group created");
+    let role_ids = ctx
+        .app_state
+        .rbac_service
+        .get_group_role_ids(realm.id, group_id)
+        .await
+        .expect("group roles");
+    assert_eq!(
+        role_ids.len(),
+        1,
+        "group picks up the client role created alongside it"
+    );
+
+    let replan = harbor
+        .plan_reconcile(realm.id, ops_bundle("Runs deploys"), false)
+        .await
+        .expect("replan");
+    assert!(
+        !replan.has_changes(),
+        "unexpected drift: {:?}",
+        replan.entries
+    );
+    assert_eq!(replan.summary.unchanged, 3);
+
+    let drifted = harbor
+        .plan_reconcile(realm.id, ops_bundle("Runs deploys and rollbacks"), false)
+        .await
+        .expect("drift plan");
+    let role_entry = drifted
+        .entries
+        .iter()
+        .find(|entry| entry.key == "role")
+        .expect("role entry");
+    assert_eq!(role_entry.action, HarborPlanAction::Update);
+    assert_eq!(role_entry.changed_fields, vec!["description".to_string()]);
+    assert_eq!(drifted.summary.unchanged, 2);
+
+    let noop = harbor
+        .apply_reconcile(realm.id, ops_bundle("Runs deploys"), false)
+        .await
+        .expect("noop apply");
+    assert!(!noop.applied);
+}
+
+#[tokio::test]
+async fn harbor_reconcile_rolls_back_when_a_step_fails() {
+    let ctx = TestContext::new_with_seed(false).await;
+    let realm = create_realm(&ctx, "reconcile-rollback").await;
+
+    // The client imports first; the second role's unknown client then
+    // fails the run after the client and the first role were written.
+    let mut broken = ops_bundle("Runs deploys");
+    broken.resources.push(resource(
+        "role",
+        json!({ "name": "auditor", "client_id": "missing-client", "permissions": [] }),
+    ));
+
+    let err = ctx
+        .app_state
+        .harbor_service
+        .apply_reconcile(realm.id, broken, false)
+        .await
+        .expect_err("apply should fail");
+    assert!(err.to_string().contains("missing-client"), "{}", err);
+
+    assert!(find_group_id(&ctx, realm.id, "operations").await.is_none());
+    assert!(ctx
+        .app_state
+        .oidc_service
+        .find_client_by_client_id(&realm.id, "ops-console")
+        .await
+        .expect("lookup")
+        .is_none());
+}
+
+#[tokio::test]
+async fn harbor_reconcile_prunes_only_when_asked() {
+    let ctx = TestContext::new_with_seed(false).await;
+    let realm = create_realm(&ctx, "reconcile-prune").await;
+
+    ctx.app_state
+        .rbac_service
+        .create_role(
+            realm.id,
+            CreateRolePayload {
+                name: "legacy".to_string(),
+                description: None,
+                client_id: None,
+            },
+        )
+        .await
+        .expect("create role");
+    ctx.app_state
+        .webhook_service
+        .create_endpoint(
+            realm.id,
+            CreateWebhookPayload {
+                name: "old-sink".to_string(),
+                url: "https://hooks.example.com/old".to_string(),
+                description: None,
+                signing_secret: None,
+                custom_headers: HashMap::new(),
+                http_method: None,
+                subscriptions: vec!["user.created".to_string()],
+            },
+        )
+        .await
+        .expect("create webhook");
+    ctx.app_state
+        .user_service
+        .create_user(realm.id, "ada", "Password123!", None, false)
+        .await
+        .expect("create user");
+
+    let desired = || {
+        bundle(
+            &["role", "webhook", "user"],
+            vec![resource(
+                "role",
+                json!({ "name": "deployer", "description": "Ships releases", "permissions": [] }),
+            )],
+        )
+    };
+
+    let plan = ctx
+        .app_state
+        .harbor_service
+        .plan_reconcile(realm.id, desired(), false)
+        .await
+        .expect("plan without prune");
+    assert_eq!(plan.summary.delete, 0);
+    assert!(!plan.warnings.is_empty());
+
+    let plan = ctx
+        .app_state
+        .harbor_service
+        .plan_reconcile(realm.id, desired(), true)
+        .await
+        .expect("plan with prune");
+    assert_eq!(
+        entry_action(&plan, "role", "legacy"),
+        Some(HarborPlanAction::Delete)
+    );
+    assert_eq!(
+        entry_action(&plan, "webhook", "old-sink"),
+        Some(HarborPlanAction::Delete)
+    );
+    assert!(
+        plan.entries.iter().all(|entry| entry.key != "user"),
+        "users are never pruned"
+    );
+
+    let result = ctx
+        .app_state
+        .harbor_service
+        .apply_reconcile(realm.id, desired(), true)
+        .await
+        .expect("apply with prune");
+    assert!(result.applied);
+
+    let rbac = &ctx.app_state.rbac_service;
+    assert!(rbac
+        .find_role_by_name(realm.id, "legacy")
+        .await
+        .expect("lookup")
+        .is_none());
+    assert!(rbac
+        .find_role_by_name(realm.id, "deployer")
+        .await
+        .expect("lookup")
+        .is_some());
+    let endpoints = ctx
+        .app_state
+        .webhook_service
+        .list_endpoints(realm.id)
+        .await
+        .expect("list webhooks");
+    assert!(endpoints.is_empty());
+    assert!(ctx
+        .app_state
+        .user_service
+        .find_by_username(&realm.id, "ada")
+        .await
+        .expect("lookup")
+        .is_some());
+}
+
+#[test]
+fn harbor_read_bundle_dir_merges_files() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let roles = bundle(
+        &["role"],
+        vec![resource(
+            "role",
+            json!({ "name": "deployer", "permissions": [] }),
+        )],
+    );
+    let webhooks = bundle(
+        &["webhook"],
+        vec![resource(
+            "webhook",
+            json!({ "name": "audit-sink", "url": "https://hooks.example.com/audit" }),
+        )],
+    );
+    std::fs::create_dir(dir.path().join("nested")).expect("nested dir");
+    std::fs::create_dir(dir.path().join(".git")).expect("hidden dir");
+    std::fs::write(
+        dir.path().join("roles.json"),
+        serde_json::to_vec(&roles).expect("serialize"),
+    )
+    .expect("write roles");
+    std::fs::write(
+        dir.path().join("nested").join("webhooks.json"),
+        serde_json::to_vec(&webhooks).expect("serialize"),
+    )
+    .expect("write webhooks");
+    std::fs::write(dir.path().join(".git").join("config.json"), "not json").expect("write hidden");
+    std::fs::write(dir.path().join("README.md"), "# realm").expect("write readme");
+
+    let merged = read_bundle_dir(dir.path()).expect("read dir");
+    assert_eq!(merged.manifest.export_type, HarborExportType::FullRealm);
+    assert_eq!(merged.resources.len(), 2);
+    assert_eq!(
+        merged.manifest.selection,
+        Some(vec!["role".to_string(), "webhook".to_string()])
+    );
+
+    let empty = tempfile::tempdir().expect("tempdir");
+    assert!(read_bundle_dir(empty.path()).is_err());
+}
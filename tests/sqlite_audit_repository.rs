@@ -35,6 +35,7 @@ async fn insert_and_list_recent_audit_events() -> Result<()> {
         id: Uuid::new_v4(),
         realm_id,
         actor_user_id: None,
+        impersonator_user_id: Some(Uuid::new_v4()),
         action: "user.login".to_string(),
         target_type: "user".to_string(),
         target_id: Some("user-123".to_string()),
@@ -50,6 +51,7 @@ async fn insert_and_list_recent_audit_events() -> Result<()> {
     assert_eq!(events[0].action, "user.login");
     assert_eq!(events[0].target_type, "user");
     assert_eq!(events[0].metadata["ip"], "127.0.0.1");
+    assert_eq!(events[0].impersonator_user_id, event1.impersonator_user_id);
 
     Ok(())
 }
@@ -0,0 +1,186 @@
+mod support;
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use reauth::adapters::persistence::connection::Database;
+use reauth::adapters::persistence::sqlite_client_registration_repository::SqliteClientRegistrationRepository;
+use reauth::adapters::persistence::sqlite_oidc_repository::SqliteOidcRepository;
+use reauth::domain::client_registration::{
+    ClientRegistration, ClientRegistrationPolicy, InitialAccessToken,
+};
+use reauth::domain::oidc::{OidcClient, TokenEndpointAuthMethod};
+use reauth::ports::client_registration_repository::ClientRegistrationRepository;
+use reauth::ports::oidc_repository::OidcRepository;
+use support::TestDb;
+use uuid::Uuid;
+
+async fn insert_realm(pool: &Database) -> Result<Uuid> {
+    let realm_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO realms (id, name, access_token_ttl_secs, refresh_token_ttl_secs) VALUES (?, ?, ?, ?)",
+    )
+    .bind(realm_id.to_string())
+    .bind("realm-dcr")
+    .bind(900)
+    .bind(604800)
+    .execute(&**pool)
+    .await?;
+    Ok(realm_id)
+}
+
+fn initial_access_token(realm_id: Uuid, hash: &str, remaining_uses: i64) -> InitialAccessToken {
+    let now = Utc::now();
+    InitialAccessToken {
+        id: Uuid::new_v4(),
+        realm_id,
+        token_hash: hash.to_string(),
+        description: Some("preview envs".to_string()),
+        expires_at: now + Duration::hours(1),
+        remaining_uses,
+        created_at: now,
+    }
+}
+
+#[tokio::test]
+async fn policy_round_trips_and_upserts() -> Result<()> {
+    let db = TestDb::new().await;
+    let repo = SqliteClientRegistrationRepository::new(db.pool.clone());
+    let realm_id = insert_realm(&db.pool).await?;
+
+    assert!(repo.find_policy(&realm_id).await?.is_none());
+
+    let mut policy = ClientRegistrationPolicy::defaults(realm_id);
+    policy.allowed_redirect_hosts = vec!["*.preview.example.com".to_string()];
+    repo.upsert_policy(&policy).await?;
+    policy.require_https = false;
+    policy
+        .allowed_grant_types
+        .push("client_credentials".to_string());
+    repo.upsert_policy(&policy).await?;
+
+    assert_eq!(repo.find_policy(&realm_id).await?, Some(policy));
+    Ok(())
+}
+
+#[tokio::test]
+async fn initial_access_tokens_are_consumed_until_used_up() -> Result<()> {
+    let db = TestDb::new().await;
+    let repo = SqliteClientRegistrationRepository::new(db.pool.clone());
+    let realm_id = insert_realm(&db.pool).await?;
+
+    let token = initial_access_token(realm_id, "hash-a", 2);
+    repo.create_initial_access_token(&token).await?;
+    let mut expired = initial_access_token(realm_id, "hash-b", 5);
+    expired.expires_at = Utc::now() - Duration::minutes(1);
+    repo.create_initial_access_token(&expired).await?;
+
+    let found = repo
+        .find_initial_access_token(&realm_id, "hash-a")
+        .await?
+        .expect("token");
+    assert_eq!(found.id, token.id);
+    assert_eq!(found.description.as_deref(), Some("preview envs"));
+    assert!(repo
+        .find_initial_access_token(&Uuid::new_v4(), "hash-a")
+        .await?
+        .is_none());
+
+    assert!(
+        repo.consume_initial_access_token(&token.id, Utc::now())
+            .await?
+    );
+    assert!(
+        repo.consume_initial_access_token(&token.id, Utc::now())
+            .await?
+    );
+    assert!(
+        !repo
+            .consume_initial_access_token(&token.id, Utc::now())
+            .await?
+    );
+    assert!(
+        !repo
+            .consume_initial_access_token(&expired.id, Utc::now())
+            .await?
+    );
+
+    assert_eq!(repo.list_initial_access_tokens(&realm_id).await?.len(), 2);
+    assert!(
+        repo.delete_initial_access_token(&realm_id, &expired.id)
+            .await?
+    );
+    assert!(
+        !repo
+            .delete_initial_access_token(&realm_id, &expired.id)
+            .await?
+    );
+    let remaining = repo.list_initial_access_tokens(&realm_id).await?;
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].remaining_uses, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn registrations_follow_their_client() -> Result<()> {
+    let db = TestDb::new().await;
+    let repo = SqliteClientRegistrationRepository::new(db.pool.clone());
+    let oidc_repo = SqliteOidcRepository::new(db.pool.clone());
+    let realm_id = insert_realm(&db.pool).await?;
+
+    let client = OidcClient {
+        id: Uuid::new_v4(),
+        realm_id,
+        client_id: "registered-app".to_string(),
+        client_secret: None,
+        redirect_uris: "[]".to_string(),
+        scopes: "[\"openid\"]".to_string(),
+        web_origins: "[]".to_string(),
+        managed_by_config: false,
+        token_endpoint_auth_method: TokenEndpointAuthMethod::ClientSecretBasic,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: None,
+        frontchannel_logout_uri: None,
+        token_exchange_policy: None,
+        direct_grant_enabled: false,
+        require_pushed_authorization_requests: false,
+    };
+    oidc_repo.create_client(&client).await?;
+    let token = initial_access_token(realm_id, "hash-c", 1);
+    repo.create_initial_access_token(&token).await?;
+
+    let mut registration = ClientRegistration {
+        client_id: client.id,
+        realm_id,
+        registration_token_hash: "registration-hash".to_string(),
+        client_name: Some("Preview 12".to_string()),
+        grant_types: vec!["authorization_code".to_string()],
+        initial_access_token_id: Some(token.id),
+        created_at: Utc::now(),
+    };
+    repo.create_registration(&registration).await?;
+
+    registration.client_name = None;
+    registration.grant_types.push("refresh_token".to_string());
+    repo.update_registration(&registration).await?;
+    let found = repo
+        .find_registration(&client.id)
+        .await?
+        .expect("registration");
+    assert_eq!(found.client_name, None);
+    assert_eq!(found.grant_types, registration.grant_types);
+    assert_eq!(found.registration_token_hash, "registration-hash");
+
+    // Revoking the initial access token keeps the clients it registered.
+    repo.delete_initial_access_token(&realm_id, &token.id)
+        .await?;
+    let found = repo
+        .find_registration(&client.id)
+        .await?
+        .expect("registration");
+    assert_eq!(found.initial_access_token_id, None);
+
+    oidc_repo.delete_client(&client.id).await?;
+    assert!(repo.find_registration(&client.id).await?.is_none());
+    Ok(())
+}
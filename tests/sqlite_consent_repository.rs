@@ -0,0 +1,136 @@
+mod support;
+
+use anyhow::Result;
+use reauth::adapters::persistence::connection::Database;
+use reauth::adapters::persistence::sqlite_consent_repository::SqliteConsentRepository;
+use reauth::domain::consent_grant::ConsentGrant;
+use reauth::ports::consent_repository::ConsentRepository;
+use support::TestDb;
+use uuid::Uuid;
+
+async fn insert_realm(pool: &Database, realm_id: Uuid, name: &str) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO realms (id, name, access_token_ttl_secs, refresh_token_ttl_secs) VALUES (?, ?, ?, ?)",
+    )
+    .bind(realm_id.to_string())
+    .bind(name)
+    .bind(900_i64)
+    .bind(604800_i64)
+    .execute(&**pool)
+    .await?;
+    Ok(())
+}
+
+async fn insert_user(pool: &Database, user_id: Uuid, realm_id: Uuid, username: &str) -> Result<()> {
+    sqlx::query("INSERT INTO users (id, realm_id, username, hashed_password) VALUES (?, ?, ?, ?)")
+        .bind(user_id.to_string())
+        .bind(realm_id.to_string())
+        .bind(username)
+        .bind("hash")
+        .execute(&**pool)
+        .await?;
+    Ok(())
+}
+
+async fn insert_client(pool: &Database, realm_id: Uuid, client_id: &str) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO oidc_clients (id, realm_id, client_id, redirect_uris, web_origins, scopes) VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(realm_id.to_string())
+    .bind(client_id)
+    .bind("[]")
+    .bind("[]")
+    .bind("[\"openid\"]")
+    .execute(&**pool)
+    .await?;
+    Ok(())
+}
+
+fn scopes(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+#[tokio::test]
+async fn consent_grants_upsert_per_user_and_client() -> Result<()> {
+    let db = TestDb::new().await;
+    let repo = SqliteConsentRepository::new(db.pool.clone());
+
+    let realm_id = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+    insert_realm(&db.pool, realm_id, "realm-consent").await?;
+    insert_user(&db.pool, user_id, realm_id, "alice").await?;
+    insert_client(&db.pool, realm_id, "web-app").await?;
+    insert_client(&db.pool, realm_id, "cli").await?;
+
+    let grant = ConsentGrant::new(
+        realm_id,
+        user_id,
+        "web-app".to_string(),
+        &scopes(&["openid"]),
+    );
+    repo.save(&grant).await?;
+    repo.save(&ConsentGrant::new(
+        realm_id,
+        user_id,
+        "cli".to_string(),
+        &scopes(&["openid"]),
+    ))
+    .await?;
+
+    // Saving a fresh grant for the same client widens the existing row.
+    let wider = ConsentGrant::new(
+        realm_id,
+        user_id,
+        "web-app".to_string(),
+        &scopes(&["openid", "profile"]),
+    );
+    repo.save(&wider).await?;
+
+    let found = repo
+        .find(&realm_id, &user_id, "web-app")
+        .await?
+        .expect("grant");
+    assert_eq!(found.id, grant.id);
+    assert_eq!(found.scopes, scopes(&["openid", "profile"]));
+
+    let listed = repo.list_for_user(&realm_id, &user_id).await?;
+    let clients: Vec<&str> = listed.iter().map(|g| g.client_id.as_str()).collect();
+    assert_eq!(clients, vec!["cli", "web-app"]);
+
+    assert!(repo.delete(&realm_id, &user_id, "cli").await?);
+    assert!(!repo.delete(&realm_id, &user_id, "cli").await?);
+    assert!(repo.find(&realm_id, &user_id, "cli").await?.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn consent_grants_are_removed_with_their_client() -> Result<()> {
+    let db = TestDb::new().await;
+    let repo = SqliteConsentRepository::new(db.pool.clone());
+
+    let realm_id = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+    insert_realm(&db.pool, realm_id, "realm-consent-cascade").await?;
+    insert_user(&db.pool, user_id, realm_id, "bob").await?;
+    insert_client(&db.pool, realm_id, "web-app").await?;
+
+    repo.save(&ConsentGrant::new(
+        realm_id,
+        user_id,
+        "web-app".to_string(),
+        &scopes(&["openid"]),
+    ))
+    .await?;
+
+    sqlx::query("DELETE FROM oidc_clients WHERE realm_id = ? AND client_id = ?")
+        .bind(realm_id.to_string())
+        .bind("web-app")
+        .execute(&*db.pool)
+        .await?;
+
+    // A client registered again under the same id starts without consent.
+    insert_client(&db.pool, realm_id, "web-app").await?;
+    assert!(repo.find(&realm_id, &user_id, "web-app").await?.is_none());
+    Ok(())
+}
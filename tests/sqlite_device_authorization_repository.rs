@@ -0,0 +1,157 @@
+mod support;
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use reauth::adapters::persistence::connection::Database;
+use reauth::adapters::persistence::sqlite_device_authorization_repository::SqliteDeviceAuthorizationRepository;
+use reauth::domain::oidc::{DeviceAuthorization, DeviceAuthorizationStatus};
+use reauth::ports::device_authorization_repository::DeviceAuthorizationRepository;
+use support::TestDb;
+use uuid::Uuid;
+
+async fn insert_realm(pool: &Database, realm_id: Uuid, name: &str) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO realms (id, name, access_token_ttl_secs, refresh_token_ttl_secs) VALUES (?, ?, ?, ?)",
+    )
+    .bind(realm_id.to_string())
+    .bind(name)
+    .bind(900_i64)
+    .bind(604800_i64)
+    .execute(&**pool)
+    .await?;
+    Ok(())
+}
+
+async fn insert_user(pool: &Database, user_id: Uuid, realm_id: Uuid, username: &str) -> Result<()> {
+    sqlx::query("INSERT INTO users (id, realm_id, username, hashed_password) VALUES (?, ?, ?, ?)")
+        .bind(user_id.to_string())
+        .bind(realm_id.to_string())
+        .bind(username)
+        .bind("hash")
+        .execute(&**pool)
+        .await?;
+    Ok(())
+}
+
+fn authorization(realm_id: Uuid, user_code: &str, expires_in: Duration) -> DeviceAuthorization {
+    let now = Utc::now();
+    DeviceAuthorization {
+        device_code: Uuid::new_v4().to_string(),
+        user_code: user_code.to_string(),
+        realm_id,
+        client_id: "tv-app".to_string(),
+        scope: Some("openid".to_string()),
+        status: DeviceAuthorizationStatus::Pending,
+        user_id: None,
+        interval_secs: 5,
+        last_polled_at: None,
+        expires_at: now + expires_in,
+        created_at: now,
+    }
+}
+
+#[tokio::test]
+async fn device_authorization_approve_poll_and_consume() -> Result<()> {
+    let db = TestDb::new().await;
+    let repo = SqliteDeviceAuthorizationRepository::new(db.pool.clone());
+
+    let realm_id = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+    insert_realm(&db.pool, realm_id, "realm-device").await?;
+    insert_user(&db.pool, user_id, realm_id, "alice").await?;
+
+    let pending = authorization(realm_id, "BCDFGHJK", Duration::minutes(10));
+    repo.create(&pending).await?;
+
+    let found = repo
+        .find_by_user_code(&realm_id, "BCDFGHJK")
+        .await?
+        .expect("found by user code");
+    assert_eq!(found.device_code, pending.device_code);
+    assert!(repo
+        .find_by_user_code(&Uuid::new_v4(), "BCDFGHJK")
+        .await?
+        .is_none());
+
+    // Not yet approved, so nothing to redeem.
+    assert!(!repo.consume(&pending.device_code).await?);
+
+    let polled_at = Utc::now();
+    repo.record_poll(&pending.device_code, polled_at, 10)
+        .await?;
+    let polled = repo
+        .find_by_device_code(&pending.device_code)
+        .await?
+        .expect("found by device code");
+    assert_eq!(polled.interval_secs, 10);
+    assert!(polled.last_polled_at.is_some());
+
+    assert!(
+        repo.resolve(
+            &pending.device_code,
+            DeviceAuthorizationStatus::Approved,
+            Some(user_id),
+            Utc::now(),
+        )
+        .await?
+    );
+    // A resolved authorization cannot be resolved again.
+    assert!(
+        !repo
+            .resolve(
+                &pending.device_code,
+                DeviceAuthorizationStatus::Denied,
+                None,
+                Utc::now(),
+            )
+            .await?
+    );
+    let approved = repo
+        .find_by_device_code(&pending.device_code)
+        .await?
+        .expect("approved");
+    assert_eq!(approved.status, DeviceAuthorizationStatus::Approved);
+    assert_eq!(approved.user_id, Some(user_id));
+
+    assert!(repo.consume(&pending.device_code).await?);
+    assert!(!repo.consume(&pending.device_code).await?);
+    assert!(repo
+        .find_by_device_code(&pending.device_code)
+        .await?
+        .is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn device_authorization_expiry_blocks_resolve_and_is_cleaned_up() -> Result<()> {
+    let db = TestDb::new().await;
+    let repo = SqliteDeviceAuthorizationRepository::new(db.pool.clone());
+
+    let realm_id = Uuid::new_v4();
+    insert_realm(&db.pool, realm_id, "realm-device-expiry").await?;
+
+    let expired = authorization(realm_id, "BCDFGHJK", Duration::minutes(-1));
+    let live = authorization(realm_id, "LMNPQRST", Duration::minutes(10));
+    repo.create(&expired).await?;
+    repo.create(&live).await?;
+
+    assert!(
+        !repo
+            .resolve(
+                &expired.device_code,
+                DeviceAuthorizationStatus::Denied,
+                None,
+                Utc::now(),
+            )
+            .await?
+    );
+
+    let deleted = repo.delete_expired_before(Utc::now(), 100).await?;
+    assert_eq!(deleted, 1);
+    assert!(repo
+        .find_by_device_code(&expired.device_code)
+        .await?
+        .is_none());
+    assert!(repo.find_by_device_code(&live.device_code).await?.is_some());
+    Ok(())
+}
@@ -0,0 +1,97 @@
+mod support;
+
+use anyhow::Result;
+use chrono::Utc;
+use reauth::adapters::persistence::connection::Database;
+use reauth::adapters::persistence::sqlite_identity_provider_repository::SqliteIdentityProviderRepository;
+use reauth::domain::identity_provider::{IdentityProvider, IdentityProviderProtocol};
+use reauth::domain::saml::NameIdFormat;
+use reauth::ports::identity_provider_repository::IdentityProviderRepository;
+use support::TestDb;
+use uuid::Uuid;
+
+async fn insert_realm(pool: &Database, realm_id: Uuid, name: &str) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO realms (id, name, access_token_ttl_secs, refresh_token_ttl_secs) VALUES (?, ?, ?, ?)",
+    )
+    .bind(realm_id.to_string())
+    .bind(name)
+    .bind(900_i64)
+    .bind(604800_i64)
+    .execute(&**pool)
+    .await?;
+    Ok(())
+}
+
+fn saml_provider(realm_id: Uuid) -> IdentityProvider {
+    let now = Utc::now();
+    IdentityProvider {
+        id: Uuid::new_v4(),
+        realm_id,
+        alias: "corp".to_string(),
+        display_name: "Corp SSO".to_string(),
+        protocol: IdentityProviderProtocol::Saml,
+        preset_key: None,
+        enabled: true,
+        client_id: "https://reauth.example/sp".to_string(),
+        client_secret: None,
+        issuer: Some("https://idp.example".to_string()),
+        authorization_endpoint: Some("https://idp.example/sso".to_string()),
+        token_endpoint: None,
+        userinfo_endpoint: None,
+        jwks_uri: None,
+        scopes_json: "[]".to_string(),
+        claim_mapping_json: "{}".to_string(),
+        pkce_required: true,
+        allow_login: true,
+        allow_link: true,
+        allow_jit_provisioning: false,
+        allow_email_auto_link: false,
+        require_verified_email: true,
+        icon_ref: None,
+        button_color: None,
+        sort_order: 0,
+        metadata_cached_at: None,
+        metadata_cache_json: None,
+        jwks_cached_at: None,
+        jwks_cache_json: None,
+        saml_metadata_url: Some("https://idp.example/metadata".to_string()),
+        saml_signing_certificates_json: r#"["MIIB"]"#.to_string(),
+        saml_name_id_format: Some(NameIdFormat::EmailAddress),
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+#[tokio::test]
+async fn saml_identity_providers_round_trip() -> Result<()> {
+    let db = TestDb::new().await;
+    let repo = SqliteIdentityProviderRepository::new(db.pool.clone());
+    let realm_id = Uuid::new_v4();
+    insert_realm(&db.pool, realm_id, "realm-saml-idp").await?;
+
+    let provider = saml_provider(realm_id);
+    repo.create(&provider, None).await?;
+    let found = repo
+        .find_by_alias(&realm_id, "corp")
+        .await?
+        .expect("provider");
+    assert_eq!(found.protocol, IdentityProviderProtocol::Saml);
+    assert_eq!(
+        found.saml_metadata_url.as_deref(),
+        Some("https://idp.example/metadata")
+    );
+    assert_eq!(found.saml_signing_certificates_json, r#"["MIIB"]"#);
+    assert_eq!(found.saml_name_id_format, Some(NameIdFormat::EmailAddress));
+
+    let mut updated = found;
+    updated.saml_metadata_url = None;
+    updated.saml_signing_certificates_json = "[]".to_string();
+    updated.saml_name_id_format = None;
+    repo.update(&updated, None).await?;
+    let found = repo.find_by_id(&provider.id).await?.expect("provider");
+    assert!(found.saml_metadata_url.is_none());
+    assert_eq!(found.saml_signing_certificates_json, "[]");
+    assert!(found.saml_name_id_format.is_none());
+    Ok(())
+}
@@ -4,9 +4,10 @@ use anyhow::Result;
 use chrono::{Duration, Utc};
 use reauth::adapters::persistence::connection::Database;
 use reauth::adapters::persistence::sqlite_oidc_repository::SqliteOidcRepository;
-use reauth::domain::oidc::{AuthCode, OidcClient};
+use reauth::domain::oidc::{AuthCode, OidcClient, TokenEndpointAuthMethod};
 use reauth::domain::pagination::{PageRequest, SortDirection};
 use reauth::domain::realm::{Realm, RealmIdpDefaultEmailLinkPolicy, RealmIdpDefaultJitPolicy};
+use reauth::domain::signing_key::SigningAlgorithm;
 use reauth::error::Error;
 use reauth::ports::oidc_repository::OidcRepository;
 use support::TestDb;
@@ -19,6 +20,7 @@ fn realm(id: Uuid, name: &str) -> Realm {
         access_token_ttl_secs: 900,
         refresh_token_ttl_secs: 604800,
         pkce_required_public_clients: true,
+        signing_algorithm: SigningAlgorithm::default(),
         lockout_threshold: 5,
         lockout_duration_secs: 900,
         is_system: false,
@@ -47,6 +49,14 @@ fn client(id: Uuid, realm_id: Uuid, client_id: &str, origins: &str) -> OidcClien
         scopes: "[\"openid\"]".to_string(),
         web_origins: origins.to_string(),
         managed_by_config: false,
+        token_endpoint_auth_method: TokenEndpointAuthMethod::None,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: None,
+        frontchannel_logout_uri: None,
+        token_exchange_policy: None,
+        direct_grant_enabled: false,
+        require_pushed_authorization_requests: false,
     }
 }
 
@@ -295,11 +305,15 @@ async fn auth_code_lifecycle_and_expiration() -> Result<()> {
         code_challenge: Some("challenge".to_string()),
         code_challenge_method: "S256".to_string(),
         expires_at: Utc::now() + Duration::minutes(10),
+        scope: Some("openid email".to_string()),
+        claims: Some(r#"{"userinfo":{"email":null}}"#.to_string()),
     };
 
     repo.save_auth_code(&code).await?;
     let fetched = repo.find_auth_code_by_code("code-123").await?.unwrap();
     assert_eq!(fetched.client_id, "client-a");
+    assert_eq!(fetched.scope.as_deref(), Some("openid email"));
+    assert_eq!(fetched.claims, code.claims);
 
     repo.delete_auth_code("code-123").await?;
     let missing = repo.find_auth_code_by_code("code-123").await?;
@@ -314,6 +328,8 @@ async fn auth_code_lifecycle_and_expiration() -> Result<()> {
         code_challenge: None,
         code_challenge_method: "plain".to_string(),
         expires_at: Utc::now() - Duration::minutes(5),
+        scope: None,
+        claims: None,
     };
     repo.save_auth_code(&expired).await?;
     let expired_fetch = repo.find_auth_code_by_code("code-expired").await?;
@@ -324,6 +340,47 @@ async fn auth_code_lifecycle_and_expiration() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn client_assertion_jti_is_accepted_once_until_it_expires() -> Result<()> {
+    let db = TestDb::new().await;
+    let repo = SqliteOidcRepository::new(db.pool.clone());
+
+    let realm_id = Uuid::new_v4();
+    insert_realm(&db.pool, &realm(realm_id, "realm-assertions")).await?;
+    let worker = client(Uuid::new_v4(), realm_id, "worker", "[]");
+    let other = client(Uuid::new_v4(), realm_id, "other", "[]");
+    repo.create_client(&worker).await?;
+    repo.create_client(&other).await?;
+
+    let expires_at = Utc::now() + Duration::minutes(1);
+    assert!(
+        repo.record_client_assertion_jti(&worker.id, "jti-1", expires_at)
+            .await?
+    );
+    assert!(
+        !repo
+            .record_client_assertion_jti(&worker.id, "jti-1", expires_at)
+            .await?
+    );
+    // Each client has its own jti space.
+    assert!(
+        repo.record_client_assertion_jti(&other.id, "jti-1", expires_at)
+            .await?
+    );
+
+    // An expired entry is purged, so it no longer blocks the jti.
+    let expired = Utc::now() - Duration::minutes(1);
+    assert!(
+        repo.record_client_assertion_jti(&worker.id, "jti-2", expired)
+            .await?
+    );
+    assert!(
+        repo.record_client_assertion_jti(&worker.id, "jti-2", expires_at)
+            .await?
+    );
+    Ok(())
+}
+
 #[tokio::test]
 async fn is_origin_allowed_matches_clients() -> Result<()> {
     let db = TestDb::new().await;
@@ -0,0 +1,133 @@
+mod support;
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use reauth::adapters::persistence::connection::Database;
+use reauth::adapters::persistence::sqlite_oidc_repository::SqliteOidcRepository;
+use reauth::adapters::persistence::sqlite_protocol_mapper_repository::SqliteProtocolMapperRepository;
+use reauth::domain::claims::{ProtocolMapper, ProtocolMapperKind};
+use reauth::domain::oidc::{OidcClient, TokenEndpointAuthMethod};
+use reauth::ports::oidc_repository::OidcRepository;
+use reauth::ports::protocol_mapper_repository::ProtocolMapperRepository;
+use support::TestDb;
+use uuid::Uuid;
+
+async fn seed_client(pool: &Database) -> Result<OidcClient> {
+    let realm_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO realms (id, name, access_token_ttl_secs, refresh_token_ttl_secs) VALUES (?, ?, ?, ?)",
+    )
+    .bind(realm_id.to_string())
+    .bind("realm-mappers")
+    .bind(900)
+    .bind(604800)
+    .execute(&**pool)
+    .await?;
+
+    let client = OidcClient {
+        id: Uuid::new_v4(),
+        realm_id,
+        client_id: "mapped-app".to_string(),
+        client_secret: None,
+        redirect_uris: "[]".to_string(),
+        scopes: "[\"openid\"]".to_string(),
+        web_origins: "[]".to_string(),
+        managed_by_config: false,
+        token_endpoint_auth_method: TokenEndpointAuthMethod::None,
+        jwks: None,
+        signing_algorithm: None,
+        backchannel_logout_uri: None,
+        frontchannel_logout_uri: None,
+        token_exchange_policy: None,
+        direct_grant_enabled: false,
+        require_pushed_authorization_requests: false,
+    };
+    SqliteOidcRepository::new(pool.clone())
+        .create_client(&client)
+        .await?;
+    Ok(client)
+}
+
+fn mapper(client: &OidcClient, name: &str, kind: ProtocolMapperKind) -> ProtocolMapper {
+    ProtocolMapper {
+        id: Uuid::new_v4(),
+        realm_id: client.realm_id,
+        client_id: client.id,
+        name: name.to_string(),
+        kind,
+        claim_name: name.to_string(),
+        add_to_access_token: true,
+        add_to_id_token: false,
+        add_to_userinfo: true,
+        created_at: Utc::now(),
+    }
+}
+
+#[tokio::test]
+async fn create_list_update_and_delete_mappers() -> Result<()> {
+    let db = TestDb::new().await;
+    let repo = SqliteProtocolMapperRepository::new(db.pool.clone());
+    let client = seed_client(&db.pool).await?;
+
+    let mut department = mapper(
+        &client,
+        "department",
+        ProtocolMapperKind::UserMetadata {
+            path: "org.department".to_string(),
+        },
+    );
+    department.created_at = Utc::now() - Duration::minutes(1);
+    let roles = mapper(
+        &client,
+        "app_roles",
+        ProtocolMapperKind::RoleList {
+            prefix: Some("app:".to_string()),
+        },
+    );
+    repo.create(&department).await?;
+    repo.create(&roles).await?;
+
+    let listed = repo.list_for_client(&client.id).await?;
+    assert_eq!(
+        listed.iter().map(|m| m.name.as_str()).collect::<Vec<_>>(),
+        vec!["department", "app_roles"]
+    );
+    assert_eq!(listed[0].kind, department.kind);
+    assert_eq!(listed[1].kind, roles.kind);
+    assert!(listed[1].add_to_access_token && !listed[1].add_to_id_token);
+
+    let mut updated = roles.clone();
+    updated.name = "groups".to_string();
+    updated.claim_name = "groups_full".to_string();
+    updated.kind = ProtocolMapperKind::GroupPath;
+    updated.add_to_id_token = true;
+    repo.update(&updated).await?;
+
+    let found = repo.find_by_id(&roles.id).await?.expect("mapper");
+    assert_eq!(found.name, "groups");
+    assert_eq!(found.claim_name, "groups_full");
+    assert_eq!(found.kind, ProtocolMapperKind::GroupPath);
+    assert!(found.add_to_id_token);
+
+    repo.delete(&roles.id).await?;
+    assert!(repo.find_by_id(&roles.id).await?.is_none());
+    assert_eq!(repo.list_for_client(&client.id).await?.len(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn mappers_are_removed_with_their_client() -> Result<()> {
+    let db = TestDb::new().await;
+    let repo = SqliteProtocolMapperRepository::new(db.pool.clone());
+    let client = seed_client(&db.pool).await?;
+
+    let group_paths = mapper(&client, "groups", ProtocolMapperKind::GroupPath);
+    repo.create(&group_paths).await?;
+
+    SqliteOidcRepository::new(db.pool.clone())
+        .delete_client(&client.id)
+        .await?;
+
+    assert!(repo.find_by_id(&group_paths.id).await?.is_none());
+    Ok(())
+}
@@ -0,0 +1,112 @@
+mod support;
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use reauth::adapters::persistence::connection::Database;
+use reauth::adapters::persistence::sqlite_pushed_authorization_request_repository::SqlitePushedAuthorizationRequestRepository;
+use reauth::domain::oidc::{OidcRequest, PushedAuthorizationRequest, PAR_REQUEST_URI_PREFIX};
+use reauth::ports::pushed_authorization_request_repository::PushedAuthorizationRequestRepository;
+use support::TestDb;
+use uuid::Uuid;
+
+async fn insert_realm(pool: &Database, realm_id: Uuid, name: &str) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO realms (id, name, access_token_ttl_secs, refresh_token_ttl_secs) VALUES (?, ?, ?, ?)",
+    )
+    .bind(realm_id.to_string())
+    .bind(name)
+    .bind(900_i64)
+    .bind(604800_i64)
+    .execute(&**pool)
+    .await?;
+    Ok(())
+}
+
+fn pushed_request(realm_id: Uuid, expires_in: Duration) -> PushedAuthorizationRequest {
+    let now = Utc::now();
+    PushedAuthorizationRequest {
+        request_uri: format!("{}{}", PAR_REQUEST_URI_PREFIX, Uuid::new_v4().simple()),
+        realm_id,
+        client_id: "web-app".to_string(),
+        request: OidcRequest {
+            client_id: "web-app".to_string(),
+            redirect_uri: "http://localhost/callback".to_string(),
+            response_type: "code".to_string(),
+            scope: Some("openid".to_string()),
+            state: Some("state".to_string()),
+            nonce: None,
+            code_challenge: Some("challenge".to_string()),
+            code_challenge_method: Some("S256".to_string()),
+            claims: None,
+        },
+        expires_at: now + expires_in,
+        created_at: now,
+    }
+}
+
+#[tokio::test]
+async fn pushed_authorization_request_is_consumed_once() -> Result<()> {
+    let db = TestDb::new().await;
+    let repo = SqlitePushedAuthorizationRequestRepository::new(db.pool.clone());
+
+    let realm_id = Uuid::new_v4();
+    insert_realm(&db.pool, realm_id, "realm-par").await?;
+
+    let pushed = pushed_request(realm_id, Duration::seconds(60));
+    repo.create(&pushed).await?;
+
+    assert!(repo
+        .consume(&Uuid::new_v4(), "web-app", &pushed.request_uri, Utc::now())
+        .await?
+        .is_none());
+    assert!(repo
+        .consume(&realm_id, "other-app", &pushed.request_uri, Utc::now())
+        .await?
+        .is_none());
+
+    let consumed = repo
+        .consume(&realm_id, "web-app", &pushed.request_uri, Utc::now())
+        .await?
+        .expect("consumed");
+    assert_eq!(consumed.request.redirect_uri, "http://localhost/callback");
+    assert_eq!(consumed.request.state.as_deref(), Some("state"));
+    assert_eq!(
+        consumed.request.code_challenge_method.as_deref(),
+        Some("S256")
+    );
+
+    assert!(repo
+        .consume(&realm_id, "web-app", &pushed.request_uri, Utc::now())
+        .await?
+        .is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn expired_pushed_authorization_requests_are_not_returned_and_cleaned_up() -> Result<()> {
+    let db = TestDb::new().await;
+    let repo = SqlitePushedAuthorizationRequestRepository::new(db.pool.clone());
+
+    let realm_id = Uuid::new_v4();
+    insert_realm(&db.pool, realm_id, "realm-par-expiry").await?;
+
+    let expired = pushed_request(realm_id, Duration::seconds(-1));
+    let stale = pushed_request(realm_id, Duration::seconds(-1));
+    let live = pushed_request(realm_id, Duration::seconds(60));
+    repo.create(&expired).await?;
+    repo.create(&stale).await?;
+    repo.create(&live).await?;
+
+    assert!(repo
+        .consume(&realm_id, "web-app", &expired.request_uri, Utc::now())
+        .await?
+        .is_none());
+
+    let deleted = repo.delete_expired_before(Utc::now(), 100).await?;
+    assert_eq!(deleted, 1);
+    assert!(repo
+        .consume(&realm_id, "web-app", &live.request_uri, Utc::now())
+        .await?
+        .is_some());
+    Ok(())
+}
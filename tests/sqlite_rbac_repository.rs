@@ -305,6 +305,57 @@ async fn group_hierarchy_and_ordering() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn group_subtree_membership() -> Result<()> {
+    let db = TestDb::new().await;
+    let repo = SqliteRbacRepository::new(db.pool.clone());
+    let realm_id = Uuid::new_v4();
+    insert_realm(&db.pool, realm_id, "realm-subtree").await?;
+
+    let root = group(Uuid::new_v4(), realm_id, None, "emea", 0);
+    let child = group(Uuid::new_v4(), realm_id, Some(root.id), "emea-berlin", 0);
+    let other = group(Uuid::new_v4(), realm_id, None, "apac", 1);
+    for group in [&root, &child, &other] {
+        repo.create_group(group, None).await?;
+    }
+
+    let direct = Uuid::new_v4();
+    let nested = Uuid::new_v4();
+    let outsider = Uuid::new_v4();
+    insert_user(&db.pool, direct, realm_id, "direct").await?;
+    insert_user(&db.pool, nested, realm_id, "nested").await?;
+    insert_user(&db.pool, outsider, realm_id, "outsider").await?;
+    repo.assign_user_to_group(&direct, &root.id, None).await?;
+    repo.assign_user_to_group(&nested, &child.id, None).await?;
+    repo.assign_user_to_group(&outsider, &other.id, None)
+        .await?;
+
+    assert!(
+        repo.is_user_in_group_subtree(&realm_id, &root.id, &direct)
+            .await?
+    );
+    assert!(
+        repo.is_user_in_group_subtree(&realm_id, &root.id, &nested)
+            .await?
+    );
+    assert!(
+        !repo
+            .is_user_in_group_subtree(&realm_id, &root.id, &outsider)
+            .await?
+    );
+    assert!(
+        !repo
+            .is_user_in_group_subtree(&realm_id, &child.id, &direct)
+            .await?
+    );
+    assert!(
+        !repo
+            .is_user_in_group_subtree(&Uuid::new_v4(), &root.id, &direct)
+            .await?
+    );
+    Ok(())
+}
+
 #[tokio::test]
 async fn custom_permissions_and_role_permissions() -> Result<()> {
     let db = TestDb::new().await;
@@ -6,6 +6,7 @@ use reauth::adapters::persistence::sqlite_realm_repository::SqliteRealmRepositor
 use reauth::adapters::persistence::transaction::SqliteTransactionManager;
 use reauth::domain::auth_flow::AuthFlow;
 use reauth::domain::realm::{Realm, RealmIdpDefaultEmailLinkPolicy, RealmIdpDefaultJitPolicy};
+use reauth::domain::signing_key::SigningAlgorithm;
 use reauth::error::Error;
 use reauth::ports::realm_repository::RealmRepository;
 use reauth::ports::transaction_manager::TransactionManager;
@@ -19,6 +20,7 @@ fn realm(id: Uuid, name: &str) -> Realm {
         access_token_ttl_secs: 900,
         refresh_token_ttl_secs: 604800,
         pkce_required_public_clients: true,
+        signing_algorithm: SigningAlgorithm::default(),
         lockout_threshold: 5,
         lockout_duration_secs: 900,
         is_system: false,
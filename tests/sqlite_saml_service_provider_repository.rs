@@ -0,0 +1,120 @@
+mod support;
+
+use anyhow::Result;
+use chrono::Utc;
+use reauth::adapters::persistence::connection::Database;
+use reauth::adapters::persistence::sqlite_saml_service_provider_repository::SqliteSamlServiceProviderRepository;
+use reauth::domain::saml::{
+    NameIdFormat, SamlAttributeMapping, SamlAttributeSource, SamlBinding, SamlServiceProvider,
+};
+use reauth::ports::saml_service_provider_repository::SamlServiceProviderRepository;
+use support::TestDb;
+use uuid::Uuid;
+
+async fn insert_realm(pool: &Database, realm_id: Uuid, name: &str) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO realms (id, name, access_token_ttl_secs, refresh_token_ttl_secs) VALUES (?, ?, ?, ?)",
+    )
+    .bind(realm_id.to_string())
+    .bind(name)
+    .bind(900_i64)
+    .bind(604800_i64)
+    .execute(&**pool)
+    .await?;
+    Ok(())
+}
+
+fn provider(realm_id: Uuid, entity_id: &str) -> SamlServiceProvider {
+    let now = Utc::now();
+    SamlServiceProvider {
+        id: Uuid::new_v4(),
+        realm_id,
+        entity_id: entity_id.to_string(),
+        name: entity_id.to_string(),
+        acs_urls: vec![format!("{}/acs", entity_id)],
+        slo_url: None,
+        slo_binding: SamlBinding::Redirect,
+        name_id_format: NameIdFormat::Unspecified,
+        attribute_mappings: Vec::new(),
+        sign_response: false,
+        signing_certificates: Vec::new(),
+        require_signed_requests: false,
+        allow_idp_initiated: false,
+        enabled: true,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+#[tokio::test]
+async fn saml_service_providers_round_trip_per_realm() -> Result<()> {
+    let db = TestDb::new().await;
+    let repo = SqliteSamlServiceProviderRepository::new(db.pool.clone());
+
+    let realm_id = Uuid::new_v4();
+    let other_realm_id = Uuid::new_v4();
+    insert_realm(&db.pool, realm_id, "realm-saml").await?;
+    insert_realm(&db.pool, other_realm_id, "realm-saml-other").await?;
+
+    let mut wiki = provider(realm_id, "https://wiki.example");
+    wiki.slo_url = Some("https://wiki.example/slo".to_string());
+    wiki.slo_binding = SamlBinding::Post;
+    wiki.name_id_format = NameIdFormat::EmailAddress;
+    wiki.attribute_mappings = vec![SamlAttributeMapping {
+        name: "urn:oid:0.9.2342.19200300.100.1.3".to_string(),
+        friendly_name: Some("mail".to_string()),
+        source: SamlAttributeSource::Email,
+    }];
+    repo.create(&wiki).await?;
+    repo.create(&provider(realm_id, "https://crm.example"))
+        .await?;
+    // The same entity ID may be registered in another realm.
+    repo.create(&provider(other_realm_id, "https://wiki.example"))
+        .await?;
+    assert!(repo
+        .create(&provider(realm_id, "https://wiki.example"))
+        .await
+        .is_err());
+
+    let found = repo
+        .find_by_entity_id(&realm_id, "https://wiki.example")
+        .await?
+        .expect("provider");
+    assert_eq!(found.id, wiki.id);
+    assert_eq!(found.slo_url.as_deref(), Some("https://wiki.example/slo"));
+    assert_eq!(found.slo_binding, SamlBinding::Post);
+    assert_eq!(found.name_id_format, NameIdFormat::EmailAddress);
+    assert_eq!(found.attribute_mappings, wiki.attribute_mappings);
+    assert!(repo.find_by_id(&other_realm_id, &wiki.id).await?.is_none());
+
+    let mut updated = found;
+    updated
+        .acs_urls
+        .push("https://wiki.example/acs2".to_string());
+    updated.allow_idp_initiated = true;
+    updated.enabled = false;
+    updated.signing_certificates = vec!["MIIB".to_string()];
+    updated.require_signed_requests = true;
+    repo.update(&updated).await?;
+    let found = repo
+        .find_by_id(&realm_id, &wiki.id)
+        .await?
+        .expect("provider");
+    assert_eq!(found.acs_urls.len(), 2);
+    assert!(found.allow_idp_initiated);
+    assert!(!found.enabled);
+    assert_eq!(found.signing_certificates, vec!["MIIB".to_string()]);
+    assert!(found.require_signed_requests);
+
+    let listed = repo.list_by_realm(&realm_id).await?;
+    let entity_ids: Vec<&str> = listed.iter().map(|p| p.entity_id.as_str()).collect();
+    assert_eq!(
+        entity_ids,
+        vec!["https://crm.example", "https://wiki.example"]
+    );
+
+    assert!(!repo.delete(&other_realm_id, &wiki.id).await?);
+    assert!(repo.delete(&realm_id, &wiki.id).await?);
+    assert!(repo.find_by_id(&realm_id, &wiki.id).await?.is_none());
+    Ok(())
+}
@@ -40,6 +40,9 @@ fn token(
         revoked_at: None,
         replaced_by: None,
         step_up_at: None,
+        scope: None,
+        claims: None,
+        impersonator_id: None,
     }
 }
 
@@ -78,11 +81,13 @@ async fn save_find_and_delete_refresh_token() -> Result<()> {
     insert_user(&db.pool, user_id, realm_id, "alice").await?;
 
     let created_at = Utc::now();
-    let refresh = token(Uuid::new_v4(), user_id, realm_id, created_at);
+    let mut refresh = token(Uuid::new_v4(), user_id, realm_id, created_at);
+    refresh.impersonator_id = Some(Uuid::new_v4());
     repo.save(&refresh).await?;
 
     let fetched = repo.find_by_id(&refresh.id).await?.unwrap();
     assert_eq!(fetched.user_id, user_id);
+    assert_eq!(fetched.impersonator_id, refresh.impersonator_id);
 
     repo.delete_by_id(&refresh.id).await?;
     let missing = repo.find_by_id(&refresh.id).await?;
@@ -114,6 +119,9 @@ async fn find_by_id_skips_expired_tokens() -> Result<()> {
         revoked_at: None,
         replaced_by: None,
         step_up_at: None,
+        scope: None,
+        claims: None,
+        impersonator_id: None,
     };
     repo.save(&expired).await?;
 
@@ -122,6 +130,65 @@ async fn find_by_id_skips_expired_tokens() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn find_active_in_family_follows_rotation_and_revocation() -> Result<()> {
+    let db = TestDb::new().await;
+    let repo = SqliteSessionRepository::new(db.pool.clone());
+
+    let realm_id = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+    insert_realm(&db.pool, realm_id, "realm-family").await?;
+    insert_user(&db.pool, user_id, realm_id, "carol").await?;
+
+    let original = token(Uuid::new_v4(), user_id, realm_id, Utc::now());
+    let rotated = RefreshToken {
+        id: Uuid::new_v4(),
+        ..original.clone()
+    };
+    repo.save(&original).await?;
+    repo.save(&rotated).await?;
+    repo.mark_replaced(&original.id, &rotated.id).await?;
+
+    let live = repo.find_active_in_family(&original.family_id).await?;
+    assert_eq!(live.map(|token| token.id), Some(rotated.id));
+
+    repo.revoke_family(&original.family_id).await?;
+    let live = repo.find_active_in_family(&original.family_id).await?;
+    assert!(live.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn list_active_for_user_skips_replaced_and_revoked_tokens() -> Result<()> {
+    let db = TestDb::new().await;
+    let repo = SqliteSessionRepository::new(db.pool.clone());
+
+    let realm_id = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+    let other_user = Uuid::new_v4();
+    insert_realm(&db.pool, realm_id, "realm-active").await?;
+    insert_user(&db.pool, user_id, realm_id, "dave").await?;
+    insert_user(&db.pool, other_user, realm_id, "eve").await?;
+
+    let original = token(Uuid::new_v4(), user_id, realm_id, Utc::now());
+    let rotated = RefreshToken {
+        id: Uuid::new_v4(),
+        ..original.clone()
+    };
+    let revoked = token(Uuid::new_v4(), user_id, realm_id, Utc::now());
+    let foreign = token(Uuid::new_v4(), other_user, realm_id, Utc::now());
+    for refresh in [&original, &rotated, &revoked, &foreign] {
+        repo.save(refresh).await?;
+    }
+    repo.mark_replaced(&original.id, &rotated.id).await?;
+    repo.revoke_family(&revoked.family_id).await?;
+
+    let active = repo.list_active_for_user(&realm_id, &user_id).await?;
+    let ids: Vec<Uuid> = active.into_iter().map(|token| token.id).collect();
+    assert_eq!(ids, vec![rotated.id]);
+    Ok(())
+}
+
 #[tokio::test]
 async fn list_refresh_tokens_with_filters_and_pagination() -> Result<()> {
     let db = TestDb::new().await;
@@ -0,0 +1,166 @@
+mod support;
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use reauth::adapters::persistence::connection::Database;
+use reauth::adapters::persistence::sqlite_signing_key_repository::SqliteSigningKeyRepository;
+use reauth::domain::signing_key::{SigningAlgorithm, SigningKey, SigningKeyState};
+use reauth::ports::signing_key_repository::SigningKeyRepository;
+use support::TestDb;
+use uuid::Uuid;
+
+async fn insert_realm(pool: &Database, realm_id: Uuid, name: &str) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO realms (id, name, access_token_ttl_secs, refresh_token_ttl_secs) VALUES (?, ?, ?, ?)",
+    )
+    .bind(realm_id.to_string())
+    .bind(name)
+    .bind(900_i64)
+    .bind(604800_i64)
+    .execute(&**pool)
+    .await?;
+    Ok(())
+}
+
+fn key(realm_id: Uuid, state: SigningKeyState, created_at: chrono::DateTime<Utc>) -> SigningKey {
+    let id = Uuid::new_v4();
+    SigningKey {
+        id,
+        realm_id,
+        kid: id.simple().to_string(),
+        algorithm: SigningAlgorithm::Rs256,
+        state,
+        private_key_pem: "encrypted".to_string(),
+        public_key_pem: "public".to_string(),
+        created_at,
+        activated_at: (state == SigningKeyState::Active).then_some(created_at),
+        deactivated_at: None,
+        retired_at: None,
+    }
+}
+
+#[tokio::test]
+async fn activate_swaps_the_active_key_in_one_step() -> Result<()> {
+    let db = TestDb::new().await;
+    let repo = SqliteSigningKeyRepository::new(db.pool.clone());
+    let realm_id = Uuid::new_v4();
+    insert_realm(&db.pool, realm_id, "realm-keys").await?;
+
+    let now = Utc::now();
+    let first = key(realm_id, SigningKeyState::Active, now - Duration::days(1));
+    let second = key(realm_id, SigningKeyState::Passive, now);
+    repo.create(&first).await?;
+    repo.create(&second).await?;
+
+    let listed = repo.list_by_realm(&realm_id).await?;
+    assert_eq!(
+        listed.iter().map(|key| key.id).collect::<Vec<_>>(),
+        vec![second.id, first.id]
+    );
+
+    repo.activate(&realm_id, &second.id, now).await?;
+
+    let active = repo
+        .find_active(&realm_id, SigningAlgorithm::Rs256)
+        .await?
+        .expect("active key");
+    assert_eq!(active.id, second.id);
+    assert_eq!(
+        active.activated_at.map(|at| at.timestamp()),
+        Some(now.timestamp())
+    );
+    let demoted = repo.find_by_kid(&first.kid).await?.expect("old key");
+    assert_eq!(demoted.state, SigningKeyState::Passive);
+    assert!(demoted.deactivated_at.is_some());
+    assert_eq!(repo.list_active().await?.len(), 1);
+
+    // Activating an unknown key leaves the current active key in place.
+    assert!(repo
+        .activate(&realm_id, &Uuid::new_v4(), now)
+        .await
+        .is_err());
+    let active = repo
+        .find_active(&realm_id, SigningAlgorithm::Rs256)
+        .await?
+        .expect("active key");
+    assert_eq!(active.id, second.id);
+    Ok(())
+}
+
+#[tokio::test]
+async fn only_one_active_key_per_realm_and_algorithm() -> Result<()> {
+    let db = TestDb::new().await;
+    let repo = SqliteSigningKeyRepository::new(db.pool.clone());
+    let realm_id = Uuid::new_v4();
+    insert_realm(&db.pool, realm_id, "realm-unique").await?;
+
+    let now = Utc::now();
+    repo.create(&key(realm_id, SigningKeyState::Active, now))
+        .await?;
+    let duplicate = repo
+        .create(&key(realm_id, SigningKeyState::Active, now))
+        .await;
+    assert!(duplicate.is_err());
+
+    let mut ec = key(realm_id, SigningKeyState::Active, now);
+    ec.algorithm = SigningAlgorithm::Es256;
+    repo.create(&ec).await?;
+    let mut ec_next = key(realm_id, SigningKeyState::Passive, now);
+    ec_next.algorithm = SigningAlgorithm::Es256;
+    repo.create(&ec_next).await?;
+
+    // Activating the new EC key leaves the RSA key active.
+    repo.activate(&realm_id, &ec_next.id, now).await?;
+    assert_eq!(repo.list_active().await?.len(), 2);
+    let active = repo
+        .find_active(&realm_id, SigningAlgorithm::Es256)
+        .await?
+        .expect("active EC key");
+    assert_eq!(active.id, ec_next.id);
+    assert!(repo
+        .find_active(&realm_id, SigningAlgorithm::Rs256)
+        .await?
+        .is_some());
+    Ok(())
+}
+
+#[tokio::test]
+async fn retire_only_touches_passive_keys() -> Result<()> {
+    let db = TestDb::new().await;
+    let repo = SqliteSigningKeyRepository::new(db.pool.clone());
+    let realm_id = Uuid::new_v4();
+    insert_realm(&db.pool, realm_id, "realm-retire").await?;
+
+    let now = Utc::now();
+    let active = key(realm_id, SigningKeyState::Active, now);
+    let mut stale = key(realm_id, SigningKeyState::Passive, now - Duration::days(30));
+    stale.deactivated_at = Some(now - Duration::days(10));
+    let mut recent = key(realm_id, SigningKeyState::Passive, now - Duration::days(20));
+    recent.deactivated_at = Some(now - Duration::days(1));
+    let staged = key(realm_id, SigningKeyState::Passive, now);
+    for key in [&active, &stale, &recent, &staged] {
+        repo.create(key).await?;
+    }
+
+    assert!(repo.retire(&realm_id, &active.id, now).await.is_err());
+
+    let retired = repo.retire_passive_before(now - Duration::days(7)).await?;
+    assert_eq!(retired, 1);
+    let stale = repo.find_by_id(&realm_id, &stale.id).await?.expect("stale");
+    assert_eq!(stale.state, SigningKeyState::Retired);
+    assert!(stale.retired_at.is_some());
+    // Never-activated keys have no deactivated_at and are left alone.
+    let staged = repo
+        .find_by_id(&realm_id, &staged.id)
+        .await?
+        .expect("staged");
+    assert_eq!(staged.state, SigningKeyState::Passive);
+
+    repo.retire(&realm_id, &recent.id, now).await?;
+    let recent = repo
+        .find_by_id(&realm_id, &recent.id)
+        .await?
+        .expect("recent");
+    assert_eq!(recent.state, SigningKeyState::Retired);
+    Ok(())
+}
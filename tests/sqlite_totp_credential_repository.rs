@@ -0,0 +1,82 @@
+mod support;
+
+use anyhow::Result;
+use chrono::Utc;
+use reauth::adapters::persistence::connection::Database;
+use reauth::adapters::persistence::sqlite_totp_credential_repository::SqliteTotpCredentialRepository;
+use reauth::domain::totp_credential::TotpCredential;
+use reauth::ports::totp_credential_repository::TotpCredentialRepository;
+use support::TestDb;
+use uuid::Uuid;
+
+async fn insert_realm(pool: &Database, realm_id: Uuid, name: &str) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO realms (id, name, access_token_ttl_secs, refresh_token_ttl_secs) VALUES (?, ?, ?, ?)",
+    )
+    .bind(realm_id.to_string())
+    .bind(name)
+    .bind(900_i64)
+    .bind(604800_i64)
+    .execute(&**pool)
+    .await?;
+    Ok(())
+}
+
+async fn insert_user(pool: &Database, user_id: Uuid, realm_id: Uuid, username: &str) -> Result<()> {
+    sqlx::query("INSERT INTO users (id, realm_id, username, hashed_password) VALUES (?, ?, ?, ?)")
+        .bind(user_id.to_string())
+        .bind(realm_id.to_string())
+        .bind(username)
+        .bind("hash")
+        .execute(&**pool)
+        .await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn totp_credential_record_use_only_moves_forward() -> Result<()> {
+    let db = TestDb::new().await;
+    let repo = SqliteTotpCredentialRepository::new(db.pool.clone());
+
+    let realm_id = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+    insert_realm(&db.pool, realm_id, "realm-totp").await?;
+    insert_user(&db.pool, user_id, realm_id, "alice").await?;
+
+    let credential = TotpCredential::new(realm_id, user_id, "enc:v1:secret".to_string());
+    repo.create(&credential).await?;
+    // One authenticator app per user.
+    assert!(repo
+        .create(&TotpCredential::new(
+            realm_id,
+            user_id,
+            "enc:v1:other".to_string()
+        ))
+        .await
+        .is_err());
+
+    let found = repo
+        .find_by_user(&realm_id, &user_id)
+        .await?
+        .expect("credential");
+    assert_eq!(found.id, credential.id);
+    assert_eq!(found.secret_encrypted, "enc:v1:secret");
+    assert_eq!(found.last_used_step, None);
+
+    assert!(repo.record_use(&credential.id, 100, Utc::now()).await?);
+    assert!(!repo.record_use(&credential.id, 100, Utc::now()).await?);
+    assert!(!repo.record_use(&credential.id, 99, Utc::now()).await?);
+    assert!(repo.record_use(&credential.id, 101, Utc::now()).await?);
+
+    let used = repo
+        .find_by_user(&realm_id, &user_id)
+        .await?
+        .expect("credential");
+    assert_eq!(used.last_used_step, Some(101));
+    assert!(used.last_used_at.is_some());
+
+    assert!(repo.delete_by_user(&realm_id, &user_id).await?);
+    assert!(!repo.delete_by_user(&realm_id, &user_id).await?);
+    assert!(repo.find_by_user(&realm_id, &user_id).await?.is_none());
+    Ok(())
+}
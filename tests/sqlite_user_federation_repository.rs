@@ -0,0 +1,221 @@
+mod support;
+
+use anyhow::Result;
+use chrono::Utc;
+use reauth::adapters::persistence::connection::Database;
+use reauth::adapters::persistence::sqlite_user_federation_repository::SqliteUserFederationRepository;
+use reauth::domain::ldap::SearchScope;
+use reauth::domain::user_federation::{
+    FederationEditMode, FederationMapperKind, FederationVendor, UserAttribute, UserFederationLink,
+    UserFederationMapper, UserFederationProvider,
+};
+use reauth::ports::user_federation_repository::UserFederationRepository;
+use support::TestDb;
+use uuid::Uuid;
+
+async fn insert_realm(pool: &Database, realm_id: Uuid, name: &str) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO realms (id, name, access_token_ttl_secs, refresh_token_ttl_secs) VALUES (?, ?, ?, ?)",
+    )
+    .bind(realm_id.to_string())
+    .bind(name)
+    .bind(900_i64)
+    .bind(604800_i64)
+    .execute(&**pool)
+    .await?;
+    Ok(())
+}
+
+async fn insert_user(pool: &Database, user_id: Uuid, realm_id: Uuid, username: &str) -> Result<()> {
+    sqlx::query("INSERT INTO users (id, realm_id, username, hashed_password) VALUES (?, ?, ?, ?)")
+        .bind(user_id.to_string())
+        .bind(realm_id.to_string())
+        .bind(username)
+        .bind("hashed")
+        .execute(&**pool)
+        .await?;
+    Ok(())
+}
+
+async fn insert_role(pool: &Database, role_id: Uuid, realm_id: Uuid, name: &str) -> Result<()> {
+    sqlx::query("INSERT INTO roles (id, realm_id, name) VALUES (?, ?, ?)")
+        .bind(role_id.to_string())
+        .bind(realm_id.to_string())
+        .bind(name)
+        .execute(&**pool)
+        .await?;
+    Ok(())
+}
+
+fn provider(realm_id: Uuid, name: &str, priority: i64) -> UserFederationProvider {
+    let now = Utc::now();
+    UserFederationProvider {
+        id: Uuid::new_v4(),
+        realm_id,
+        name: name.to_string(),
+        enabled: true,
+        vendor: FederationVendor::Ldap,
+        connection_url: "ldap://127.0.0.1:389".to_string(),
+        bind_dn: Some("cn=admin,dc=example,dc=com".to_string()),
+        bind_credential: Some("enc:v1:secret".to_string()),
+        users_dn: "ou=people,dc=example,dc=com".to_string(),
+        user_object_classes: vec!["inetOrgPerson".to_string()],
+        custom_user_filter: None,
+        search_scope: SearchScope::Subtree,
+        username_attribute: "uid".to_string(),
+        uuid_attribute: "entryUUID".to_string(),
+        membership_attribute: "memberOf".to_string(),
+        edit_mode: FederationEditMode::ReadOnly,
+        sync_interval_secs: 0,
+        priority,
+        last_sync_at: None,
+        last_sync_error: None,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+#[tokio::test]
+async fn user_federation_providers_round_trip_per_realm() -> Result<()> {
+    let db = TestDb::new().await;
+    let repo = SqliteUserFederationRepository::new(db.pool.clone());
+
+    let realm_id = Uuid::new_v4();
+    let other_realm_id = Uuid::new_v4();
+    insert_realm(&db.pool, realm_id, "realm-federation").await?;
+    insert_realm(&db.pool, other_realm_id, "realm-federation-other").await?;
+
+    let mut corp = provider(realm_id, "corp", 10);
+    corp.vendor = FederationVendor::ActiveDirectory;
+    corp.user_object_classes = vec!["person".to_string(), "user".to_string()];
+    corp.custom_user_filter = Some("(!(disabled=TRUE))".to_string());
+    repo.create_provider(&corp).await?;
+    repo.create_provider(&provider(realm_id, "partners", 0))
+        .await?;
+    let mut disabled = provider(other_realm_id, "corp", 0);
+    disabled.enabled = false;
+    repo.create_provider(&disabled).await?;
+    assert!(repo
+        .create_provider(&provider(realm_id, "corp", 0))
+        .await
+        .is_err());
+
+    let found = repo
+        .find_provider_by_name(&realm_id, "corp")
+        .await?
+        .expect("provider");
+    assert_eq!(found, corp);
+    assert!(repo
+        .find_provider(&other_realm_id, &corp.id)
+        .await?
+        .is_none());
+
+    let mut updated = found;
+    updated.edit_mode = FederationEditMode::Writable;
+    updated.search_scope = SearchScope::OneLevel;
+    updated.bind_credential = None;
+    updated.last_sync_at = Some(Utc::now());
+    updated.last_sync_error = Some("1 entries failed to sync".to_string());
+    repo.update_provider(&updated).await?;
+    let found = repo
+        .find_provider(&realm_id, &corp.id)
+        .await?
+        .expect("provider");
+    assert_eq!(found.edit_mode, FederationEditMode::Writable);
+    assert_eq!(found.search_scope, SearchScope::OneLevel);
+    assert_eq!(found.bind_credential, None);
+    assert_eq!(found.last_sync_error, updated.last_sync_error);
+
+    let names: Vec<String> = repo
+        .list_providers(&realm_id)
+        .await?
+        .into_iter()
+        .map(|provider| provider.name)
+        .collect();
+    assert_eq!(names, vec!["partners", "corp"]);
+    assert_eq!(repo.list_enabled_providers().await?.len(), 2);
+
+    assert!(!repo.delete_provider(&other_realm_id, &corp.id).await?);
+    assert!(repo.delete_provider(&realm_id, &corp.id).await?);
+    assert!(repo.find_provider(&realm_id, &corp.id).await?.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn user_federation_mappers_and_links_follow_their_provider() -> Result<()> {
+    let db = TestDb::new().await;
+    let repo = SqliteUserFederationRepository::new(db.pool.clone());
+
+    let realm_id = Uuid::new_v4();
+    insert_realm(&db.pool, realm_id, "realm-federation-links").await?;
+    let role_id = Uuid::new_v4();
+    insert_role(&db.pool, role_id, realm_id, "staff").await?;
+    let user_id = Uuid::new_v4();
+    insert_user(&db.pool, user_id, realm_id, "jdoe").await?;
+
+    let corp = provider(realm_id, "corp", 0);
+    repo.create_provider(&corp).await?;
+
+    let email = UserFederationMapper {
+        id: Uuid::new_v4(),
+        provider_id: corp.id,
+        name: "email".to_string(),
+        kind: FederationMapperKind::Attribute {
+            ldap_attribute: "mail".to_string(),
+            user_attribute: UserAttribute::Email,
+        },
+        created_at: Utc::now(),
+    };
+    let staff = UserFederationMapper {
+        id: Uuid::new_v4(),
+        provider_id: corp.id,
+        name: "staff".to_string(),
+        kind: FederationMapperKind::GroupRole {
+            group_dn: "cn=staff,ou=groups,dc=example,dc=com".to_string(),
+            role_id,
+        },
+        created_at: Utc::now(),
+    };
+    repo.create_mapper(&email).await?;
+    repo.create_mapper(&staff).await?;
+    assert!(repo.create_mapper(&email).await.is_err());
+    assert_eq!(
+        repo.list_mappers(&corp.id).await?,
+        vec![email.clone(), staff]
+    );
+
+    let mut link = UserFederationLink {
+        user_id,
+        provider_id: corp.id,
+        external_id: "5f0c7a4e-1d1a-4d53-9a57-2d6f2b0c1e11".to_string(),
+        dn: "uid=jdoe,ou=people,dc=example,dc=com".to_string(),
+        last_synced_at: None,
+        created_at: Utc::now(),
+    };
+    repo.save_link(&link).await?;
+    link.dn = "uid=jdoe,ou=staff,dc=example,dc=com".to_string();
+    link.last_synced_at = Some(Utc::now());
+    repo.save_link(&link).await?;
+    assert_eq!(repo.find_link_by_user(&user_id).await?, Some(link.clone()));
+    assert_eq!(
+        repo.find_link_by_external_id(&corp.id, &link.external_id)
+            .await?
+            .map(|found| found.user_id),
+        Some(user_id)
+    );
+    assert_eq!(repo.count_links(&corp.id).await?, 1);
+
+    assert!(repo.delete_mapper(&corp.id, &email.id).await?);
+    assert!(!repo.delete_mapper(&corp.id, &email.id).await?);
+
+    // Deleting the provider unlinks its users but keeps them.
+    repo.delete_provider(&realm_id, &corp.id).await?;
+    assert!(repo.find_link_by_user(&user_id).await?.is_none());
+    assert!(repo.list_mappers(&corp.id).await?.is_empty());
+    let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE id = ?")
+        .bind(user_id.to_string())
+        .fetch_one(&*db.pool)
+        .await?;
+    assert_eq!(remaining, 1);
+    Ok(())
+}
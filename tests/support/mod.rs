@@ -8,6 +8,7 @@ use reauth::adapters::{init_db, run_migrations};
 use reauth::config::DatabaseConfig;
 use reauth::initialize_for_tests;
 use reauth::AppState;
+use sqlx::migrate::Migrate;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tempfile::TempDir;
@@ -35,9 +36,45 @@ impl TestContext {
         Self::new_internal(seed).await
     }
 
+    /// Starts the app over a database an older release left behind: only the
+    /// migrations before `version` have run, then `fixture_sql` adds its rows.
+    /// Startup applies the remaining migrations and seeds, as an upgrade does.
+    pub async fn new_upgraded_from(version: i64, fixture_sql: &str) -> Self {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let db_path = temp_dir.path().join("reauth-test.db");
+        std::fs::File::create(&db_path).expect("db file");
+        let config = DatabaseConfig {
+            url: format!("sqlite:{}", db_path.to_string_lossy()),
+            max_connections: 1,
+            data_dir: temp_dir.path().to_string_lossy().to_string(),
+        };
+
+        let pool = init_db(&config).await.expect("failed to init db");
+        {
+            let mut conn = pool.acquire().await.expect("connection");
+            conn.ensure_migrations_table()
+                .await
+                .expect("migrations table");
+            let migrator = sqlx::migrate!("./migrations");
+            for migration in migrator.iter().filter(|m| m.version < version) {
+                conn.apply(migration).await.expect("apply old migration");
+            }
+            sqlx::raw_sql(fixture_sql)
+                .execute(&mut *conn)
+                .await
+                .expect("insert fixture");
+        }
+        pool.close().await;
+
+        Self::new_in(temp_dir, true).await
+    }
+
     async fn new_internal(seed: bool) -> Self {
+        Self::new_in(tempfile::tempdir().expect("temp dir"), seed).await
+    }
+
+    async fn new_in(temp_dir: TempDir, seed: bool) -> Self {
         let env_lock = TEST_ENV_LOCK.clone().lock_owned().await;
-        let temp_dir = tempfile::tempdir().expect("temp dir");
         let db_path = temp_dir.path().join("reauth-test.db");
         let db_url = format!("sqlite:{}", db_path.to_string_lossy());
 
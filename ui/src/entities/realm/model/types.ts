@@ -4,6 +4,7 @@ export interface Realm {
   access_token_ttl_secs: number
   refresh_token_ttl_secs: number
   pkce_required_public_clients: boolean
+  signing_algorithm: 'RS256' | 'ES256' | 'ES384' | 'EdDSA'
   lockout_threshold: number
   lockout_duration_secs: number
   is_system: boolean
@@ -268,18 +268,22 @@ export function FluidLoginScreen({
 
   const contextualValues = useMemo(() => {
     const base = typeof context === 'object' && context ? context : {}
+    // Only the awaiting page resends client-side; other pages (e.g. sms_otp)
+    // get their resend state from the server context.
+    const isAwaiting = templateKey === 'awaiting_action'
     return {
       ...base,
-      can_resend: canResend,
+      can_resend: isAwaiting ? canResend : base.can_resend,
       awaiting_status: autoStatus,
       awaiting_status_message: awaitingStatusMessage,
-      resend_message: resendMessage,
+      resend_message: isAwaiting ? resendMessage : base.resend_message,
       expires_at: expiresAt,
       expires_in_minutes: expiresInMinutes,
       is_expired: isExpired,
     }
   }, [
     context,
+    templateKey,
     canResend,
     autoStatus,
     awaitingStatusMessage,
@@ -486,6 +490,30 @@ export function FluidLoginScreen({
       void onSubmit({ otp })
       return
     }
+    if (templateKey === 'totp_enroll') {
+      if (normalized.decision === 'skip') {
+        void onSubmit({ action: 'skip' })
+        return
+      }
+      if (!normalized.otp) {
+        setLocalError('Enter the code shown in your authenticator app.')
+        return
+      }
+      void onSubmit({ otp: normalized.otp })
+      return
+    }
+    if (templateKey === 'sms_otp') {
+      if (normalized.decision === 'resend') {
+        void onSubmit({ action: 'resend' })
+        return
+      }
+      if (!normalized.otp) {
+        setLocalError('Enter the code we sent to your phone.')
+        return
+      }
+      void onSubmit({ otp: normalized.otp })
+      return
+    }
     if (templateKey === 'verify_email') {
       void onSubmit(normalized)
       return
@@ -1176,7 +1204,9 @@ export function FluidLoginScreen({
 
         if (component.toLowerCase() === 'link') {
           const label = String(props.label || 'Link')
-          const href = String(props.href || '#')
+          const hrefPath = String(props.href_path || '').trim()
+          const resolvedHref = hrefPath ? resolveContextValue(hrefPath) : undefined
+          const href = String(resolvedHref ?? props.href ?? '#')
           const target = String(props.target || '_self')
           const isExternal = target === '_blank'
           return wrap(
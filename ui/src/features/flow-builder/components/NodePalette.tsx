@@ -9,8 +9,11 @@ import {
   Lock,
   ListChecks,
   Mail,
+  MessageSquare,
   Play,
+  QrCode,
   ShieldAlert,
+  Smartphone,
   Split,
   UserPlus,
   XCircle,
@@ -35,6 +38,9 @@ const IconMap: Record<string, ElementType> = {
   Zap: Zap,
   GlobeLock: GlobeLock,
   ListChecks: ListChecks,
+  QrCode: QrCode,
+  Smartphone: Smartphone,
+  MessageSquare: MessageSquare,
 }
 
 export function NodePalette() {
@@ -16,6 +16,12 @@ export interface UserPasskeyCredential {
   last_used_at?: string | null
 }
 
+export interface UserTotpCredential {
+  id: string
+  created_at: string
+  last_used_at?: string | null
+}
+
 export interface UserFederatedIdentity {
   id: string
   provider_alias: string
@@ -34,6 +40,7 @@ export interface UserCredentials {
     password_login_disabled: boolean
   }
   passkeys: UserPasskeyCredential[]
+  totp?: UserTotpCredential | null
   federated_identities: UserFederatedIdentity[]
 }
 
@@ -89,6 +96,24 @@ export function useRevokeUserPasskey(userId: string) {
   })
 }
 
+export function useRemoveUserTotp(userId: string) {
+  const realm = useActiveRealm()
+  const queryClient = useQueryClient()
+
+  return useMutation({
+    mutationFn: async () => {
+      return apiClient.delete(`/api/realms/${realm}/users/${userId}/credentials/totp`)
+    },
+    onSuccess: () => {
+      toast.success('Authenticator app removed.')
+      void queryClient.invalidateQueries({ queryKey: queryKeys.userCredentials(userId) })
+    },
+    onError: (error) => {
+      toast.error(error.message || 'Failed to remove authenticator app.')
+    },
+  })
+}
+
 export function useRenameUserPasskey(userId: string) {
   const realm = useActiveRealm()
   const queryClient = useQueryClient()
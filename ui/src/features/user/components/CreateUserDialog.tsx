@@ -32,22 +32,14 @@ const emailSchema = z
     message: 'Invalid email address',
   })
 
-const createFormSchema = z
-  .object({
-    username: z.string().min(3),
-    email: emailSchema,
-    password: z.string(),
-    ignore_password_policies: z.boolean(),
-  })
-  .superRefine((data, ctx) => {
-    if (!data.ignore_password_policies && data.password.length < 8) {
-      ctx.addIssue({
-        code: z.ZodIssueCode.custom,
-        message: 'Password must be at least 8 characters',
-        path: ['password'],
-      })
-    }
-  })
+// Length and composition rules come from the realm password policy and are
+// enforced by the server.
+const createFormSchema = z.object({
+  username: z.string().min(3),
+  email: emailSchema,
+  password: z.string().min(1, { message: 'Password is required' }),
+  ignore_password_policies: z.boolean(),
+})
 
 const inviteFormSchema = z.object({
   email: z
@@ -2,6 +2,7 @@ import { useUserCredentials } from '@/features/user/api/useUserCredentials'
 import { FederatedIdentitiesSection } from '@/features/user/components/credentials/FederatedIdentitiesSection'
 import { PasskeysSection } from '@/features/user/components/credentials/PasskeysSection'
 import { PasswordSection } from '@/features/user/components/credentials/PasswordSection'
+import { TotpSection } from '@/features/user/components/credentials/TotpSection'
 import { Skeleton } from '@/shared/ui/skeleton'
 
 interface UserCredentialsTabProps {
@@ -25,6 +26,7 @@ export function UserCredentialsTab({ userId }: UserCredentialsTabProps) {
     <div className="flex h-full w-full flex-col gap-6">
       <PasswordSection userId={userId} password={data?.password} />
       <PasskeysSection userId={userId} passkeys={data?.passkeys ?? []} />
+      <TotpSection userId={userId} totp={data?.totp ?? null} />
       <FederatedIdentitiesSection userId={userId} identities={data?.federated_identities ?? []} />
     </div>
   )
@@ -35,22 +35,6 @@ const changePasswordSchema = z
       })
     }
 
-    if (!values.skip_password_checks && values.password.length < 8) {
-      ctx.addIssue({
-        code: z.ZodIssueCode.custom,
-        message: 'Password must be at least 8 characters',
-        path: ['password'],
-      })
-    }
-
-    if (values.password.length > 100) {
-      ctx.addIssue({
-        code: z.ZodIssueCode.custom,
-        message: 'Password must be no more than 100 characters',
-        path: ['password'],
-      })
-    }
-
     if (values.password !== values.confirm_password) {
       ctx.addIssue({
         code: z.ZodIssueCode.custom,
@@ -0,0 +1,63 @@
+import { Smartphone } from 'lucide-react'
+
+import { Button } from '@/components/button'
+import { Card, CardContent, CardHeader, CardTitle } from '@/components/card'
+import type { UserTotpCredential } from '@/features/user/api/useUserCredentials'
+import { useRemoveUserTotp } from '@/features/user/api/useUserCredentials'
+
+interface TotpSectionProps {
+  userId: string
+  totp: UserTotpCredential | null
+}
+
+export function TotpSection({ userId, totp }: TotpSectionProps) {
+  const removeTotpMutation = useRemoveUserTotp(userId)
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle>Authenticator App</CardTitle>
+      </CardHeader>
+      <CardContent>
+        {!totp ? (
+          <div className="flex flex-col items-center justify-center gap-2 rounded-lg border border-dashed py-10 text-center">
+            <Smartphone className="text-muted-foreground h-8 w-8" />
+            <p className="text-muted-foreground text-sm">No authenticator app enrolled.</p>
+          </div>
+        ) : (
+          <div className="bg-primary-foreground flex items-center justify-between gap-3 rounded-2xl border p-3">
+            <div className="flex min-w-0 items-center gap-3">
+              <Smartphone className="text-muted-foreground h-4 w-4 shrink-0" />
+              <div className="min-w-0 space-y-1">
+                <div className="text-sm font-medium">TOTP authenticator</div>
+                <div className="text-muted-foreground text-xs">
+                  created: {new Date(totp.created_at).toLocaleString()}
+                  {totp.last_used_at
+                    ? ` | last used: ${new Date(totp.last_used_at).toLocaleString()}`
+                    : ''}
+                </div>
+              </div>
+            </div>
+            <Button
+              variant="outline"
+              size="sm"
+              disabled={removeTotpMutation.isPending}
+              onClick={() => {
+                if (
+                  !window.confirm(
+                    'Remove this authenticator app? The user will need to enroll again.',
+                  )
+                ) {
+                  return
+                }
+                removeTotpMutation.mutate()
+              }}
+            >
+              Remove
+            </Button>
+          </div>
+        )}
+      </CardContent>
+    </Card>
+  )
+}
@@ -47,8 +47,28 @@ const RESOURCE_OPTIONS: Array<{
   { id: 'flows', label: 'Auth Flows' },
   { id: 'users', label: 'Users' },
   { id: 'roles', label: 'Roles' },
+  { id: 'groups', label: 'Groups' },
+  { id: 'identity_providers', label: 'Identity Providers' },
+  { id: 'webhooks', label: 'Webhooks' },
+  { id: 'realm_settings', label: 'Email, Passkey & Security Settings' },
 ]
 
+// Maps each checkbox to the export selection key the API expects.
+const RESOURCE_SELECTION_KEYS: Record<string, string> = {
+  realm: 'realm',
+  themes: 'theme',
+  clients: 'client',
+  flows: 'flow',
+  users: 'user',
+  roles: 'role',
+  groups: 'group',
+  identity_providers: 'identity_provider',
+  webhooks: 'webhook',
+  realm_settings: 'realm_settings',
+}
+
+const RESOURCE_IDS = Object.keys(RESOURCE_SELECTION_KEYS)
+
 const EMPTY_JOBS: HarborJob[] = []
 
 function getJobBadge(job: HarborJob) {
@@ -64,7 +84,8 @@ function getJobBadge(job: HarborJob) {
 }
 
 function formatJobType(job: HarborJob) {
-  const prefix = job.job_type === 'export' ? 'Export' : 'Import'
+  const prefix =
+    job.job_type === 'export' ? 'Export' : job.job_type === 'reconcile' ? 'Reconcile' : 'Import'
   return `${prefix} · ${job.scope.replaceAll('_', ' ')}`
 }
 
@@ -144,6 +165,10 @@ export function HarborDashboardPage() {
     flows: true,
     users: false,
     roles: true,
+    groups: true,
+    identity_providers: true,
+    webhooks: true,
+    realm_settings: true,
   })
 
   const exportMutation = useHarborExportArchive()
@@ -162,23 +187,9 @@ export function HarborDashboardPage() {
         }
       : null
 
-  const exportSelection = resourceSelection.all_settings
-    ? ['realm', 'theme', 'client', 'flow', 'user', 'role']
-    : (['realm', 'themes', 'clients', 'flows', 'users', 'roles'] as const)
-        .filter((key) => resourceSelection[key])
-        .map((key) =>
-          key === 'realm'
-            ? 'realm'
-            : key === 'themes'
-              ? 'theme'
-            : key === 'clients'
-              ? 'client'
-            : key === 'users'
-              ? 'user'
-                : key === 'roles'
-                  ? 'role'
-                  : 'flow',
-        )
+  const exportSelection = RESOURCE_IDS.filter(
+    (key) => resourceSelection.all_settings || resourceSelection[key],
+  ).map((key) => RESOURCE_SELECTION_KEYS[key])
 
   const liveJobs = jobsQuery.data ?? EMPTY_JOBS
   const activeJobs = liveJobs.filter((job) => isHarborJobActive(job))
@@ -199,19 +210,12 @@ export function HarborDashboardPage() {
     setResourceSelection((prev) => {
       const next = { ...prev, [id]: checked === true }
       if (id === 'all_settings') {
-        next.realm = checked === true
-        next.themes = checked === true
-        next.clients = checked === true
-        next.flows = checked === true
-        next.users = checked === true
-        next.roles = checked === true
-      }
-      if (['realm', 'themes', 'clients', 'flows', 'users', 'roles'].includes(id) && !checked) {
-        next.all_settings = false
+        RESOURCE_IDS.forEach((key) => {
+          next[key] = checked === true
+        })
       }
-      if (['realm', 'themes', 'clients', 'flows', 'users', 'roles'].includes(id) && checked) {
-        next.all_settings =
-          next.realm && next.themes && next.clients && next.flows && next.users && next.roles
+      if (RESOURCE_IDS.includes(id)) {
+        next.all_settings = RESOURCE_IDS.every((key) => next[key])
       }
       return next
     })